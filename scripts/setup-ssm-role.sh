@@ -35,6 +35,27 @@ aws iam attach-role-policy \
     --role-name "$ROLE_NAME" \
     --policy-arn "$POLICY_ARN" || echo "Policy may already be attached"
 
+# Allow instances to report bootstrap status via their own tags (runctl:bootstrap*)
+echo "Attaching bootstrap tagging policy..."
+cat > /tmp/runctl-bootstrap-tags-policy.json << 'TAGS'
+{
+  "Version": "2012-10-17",
+  "Statement": [{
+    "Effect": "Allow",
+    "Action": "ec2:CreateTags",
+    "Resource": "arn:aws:ec2:*:*:instance/*",
+    "Condition": {
+      "StringEquals": {"aws:ARN": "${ec2:SourceInstanceARN}"},
+      "ForAllValues:StringLike": {"aws:TagKeys": ["runctl:bootstrap*"]}
+    }
+  }]
+}
+TAGS
+aws iam put-role-policy \
+    --role-name "$ROLE_NAME" \
+    --policy-name runctl-bootstrap-tags \
+    --policy-document file:///tmp/runctl-bootstrap-tags-policy.json
+
 # Create instance profile
 if aws iam get-instance-profile --instance-profile-name "$PROFILE_NAME" &>/dev/null; then
     echo "Instance profile $PROFILE_NAME already exists"
@@ -0,0 +1,543 @@
+//! Instance-side status agent
+//!
+//! `runctl agent` runs on the training instance next to the job. Every
+//! `--interval` seconds it writes one status document with:
+//!
+//! - a heartbeat timestamp
+//! - CPU, memory, and GPU utilization
+//! - training progress (epoch/step/loss) parsed from the log tail
+//! - the training process state, from the files `runctl aws train` writes
+//! - the newest checkpoint in the checkpoint directory
+//! - the dataset cache summary, when `runctl cache` is in use (see `cache`)
+//!
+//! The document is written to a local file and, with `--s3`, to
+//! `<prefix>/<instance-id>.json` in S3. `runctl aws status`, `runctl top`
+//! and `runctl watch` read it from `s3://<[aws] s3_bucket>/runctl/agent/`
+//! with a single GetObject instead of several SSM commands, and fall back
+//! to SSM when it is missing or its heartbeat is older than three intervals.
+//!
+//! `runctl aws train --agent` starts the agent when `runctl` is installed on
+//! the instance (on `PATH` or at `~/.runctl/bin/runctl`). The agent exits
+//! after reporting that training finished.
+
+use crate::aws_utils::execute_ssm_command;
+use crate::config::Config;
+use crate::error::{Result, TrainctlError};
+use crate::watch::{parse_metrics, MetricsSnapshot};
+use aws_sdk_s3::Client as S3Client;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Key prefix of agent status documents in `[aws] s3_bucket`
+pub const AGENT_STATUS_PREFIX: &str = "runctl/agent";
+
+/// Default seconds between reports
+pub const DEFAULT_INTERVAL_SECS: u64 = 15;
+
+/// A status is stale once its heartbeat is this many intervals old
+const STALE_INTERVALS: u64 = 3;
+
+/// Log lines parsed for training progress
+const LOG_TAIL_LINES: usize = 200;
+
+/// What to watch and where to report
+#[derive(Debug, Clone)]
+pub struct AgentOptions {
+    pub instance_id: String,
+    /// Directory holding training.log, training.pid and the exit code files
+    pub project_dir: PathBuf,
+    /// Training log (default: `<project_dir>/training.log`)
+    pub log: Option<PathBuf>,
+    /// Checkpoint directory (default: `<project_dir>/checkpoints`)
+    pub checkpoint_dir: Option<PathBuf>,
+    pub interval_secs: u64,
+    /// S3 prefix (`s3://bucket/prefix`) the status document is uploaded under
+    pub s3: Option<String>,
+    /// Local copy of the status document (default: `<project_dir>/agent-status.json`)
+    pub status_file: Option<PathBuf>,
+    /// Report once and exit
+    pub once: bool,
+    /// Directory holding the dataset cache index (default: `~/.runctl/cache`)
+    pub cache_dir: Option<PathBuf>,
+}
+
+/// Newest file in the checkpoint directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointSummary {
+    pub name: String,
+    pub size_bytes: u64,
+    pub modified: DateTime<Utc>,
+}
+
+/// One report from the agent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentStatus {
+    pub instance_id: String,
+    pub heartbeat: DateTime<Utc>,
+    pub interval_secs: u64,
+    /// `pending`, `running`, `completed`, `stopped`, or `failed (exit N)`
+    pub training: String,
+    pub cpu_percent: Option<f64>,
+    pub memory_percent: Option<f64>,
+    /// Average over all GPUs
+    pub gpu_utilization: Option<f64>,
+    pub gpu_memory_percent: Option<f64>,
+    pub metrics: MetricsSnapshot,
+    pub latest_checkpoint: Option<CheckpointSummary>,
+    pub checkpoint_count: usize,
+    /// Dataset cache tiers and hit rate
+    #[serde(default)]
+    pub cache: Option<crate::cache::CacheSummary>,
+}
+
+impl AgentStatus {
+    /// Seconds since the heartbeat
+    pub fn age_secs(&self, now: DateTime<Utc>) -> u64 {
+        (now - self.heartbeat).num_seconds().max(0) as u64
+    }
+
+    /// Whether the agent reported recently enough to be trusted
+    pub fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        self.age_secs(now) <= self.interval_secs.max(1) * STALE_INTERVALS
+    }
+
+    /// Whether training has stopped, so the agent has nothing left to report
+    pub fn is_finished(&self) -> bool {
+        !matches!(self.training.as_str(), "pending" | "running")
+    }
+
+    /// One-line progress, e.g. `epoch 3/10, step 1200/5000, loss 0.4210`
+    pub fn progress_summary(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some((current, total)) = self.metrics.epoch {
+            parts.push(format!("epoch {}/{}", current, total));
+        }
+        if let Some((current, total)) = self.metrics.step {
+            parts.push(format!("step {}/{}", current, total));
+        }
+        if let Some(loss) = self.metrics.loss {
+            parts.push(format!("loss {:.4}", loss));
+        }
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
+
+/// Training state from the files `runctl aws train` leaves in the project dir
+fn training_state(
+    exit_code: Option<i32>,
+    complete_marker: bool,
+    process_alive: bool,
+    log_exists: bool,
+) -> String {
+    match exit_code {
+        Some(0) => "completed".to_string(),
+        Some(code) => format!("failed (exit {})", code),
+        None if process_alive => "running".to_string(),
+        None if complete_marker => "completed".to_string(),
+        None if log_exists => "stopped".to_string(),
+        None => "pending".to_string(),
+    }
+}
+
+fn read_training_state(project_dir: &Path, log: &Path) -> String {
+    let exit_code = std::fs::read_to_string(project_dir.join("training_exit_code.txt"))
+        .ok()
+        .and_then(|code| code.trim().parse().ok());
+    let complete_marker = std::fs::metadata(project_dir.join("training_complete.txt"))
+        .map(|m| m.len() > 0)
+        .unwrap_or(false);
+    let process_alive = std::fs::read_to_string(project_dir.join("training.pid"))
+        .ok()
+        .and_then(|pid| pid.trim().parse::<u32>().ok())
+        .is_some_and(|pid| Path::new(&format!("/proc/{}", pid)).exists());
+    training_state(exit_code, complete_marker, process_alive, log.exists())
+}
+
+fn read_log_tail(path: &Path) -> Vec<String> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines.len().saturating_sub(LOG_TAIL_LINES);
+    lines[start..].iter().map(|l| l.to_string()).collect()
+}
+
+/// Newest checkpoint file and the number of files in `dir`
+fn scan_checkpoints(dir: &Path) -> (Option<CheckpointSummary>, usize) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return (None, 0);
+    };
+    let files: Vec<CheckpointSummary> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+            Some(CheckpointSummary {
+                name: entry.file_name().to_string_lossy().into_owned(),
+                size_bytes: metadata.len(),
+                modified: metadata.modified().ok()?.into(),
+            })
+        })
+        .collect();
+    let count = files.len();
+    (files.into_iter().max_by_key(|c| c.modified), count)
+}
+
+/// Collect one status report
+pub async fn collect_status(options: &AgentOptions) -> AgentStatus {
+    let log = options
+        .log
+        .clone()
+        .unwrap_or_else(|| options.project_dir.join("training.log"));
+    let checkpoint_dir = options
+        .checkpoint_dir
+        .clone()
+        .unwrap_or_else(|| options.project_dir.join("checkpoints"));
+    let (latest_checkpoint, checkpoint_count) = scan_checkpoints(&checkpoint_dir);
+    let cache_dir = options
+        .cache_dir
+        .clone()
+        .unwrap_or_else(|| crate::config::CacheConfig::default().ebs_dir);
+
+    let mut status = AgentStatus {
+        instance_id: options.instance_id.clone(),
+        heartbeat: Utc::now(),
+        interval_secs: options.interval_secs,
+        training: read_training_state(&options.project_dir, &log),
+        cpu_percent: None,
+        memory_percent: None,
+        gpu_utilization: None,
+        gpu_memory_percent: None,
+        metrics: parse_metrics(&read_log_tail(&log)),
+        latest_checkpoint,
+        checkpoint_count,
+        cache: crate::cache::read_summary(&cache_dir),
+    };
+
+    match crate::diagnostics::local_resource_usage(&options.instance_id).await {
+        Ok(usage) => {
+            status.cpu_percent = Some(usage.cpu_percent);
+            status.memory_percent = Some(usage.memory_percent);
+            let gpus = usage.gpu_info.map(|g| g.gpus).unwrap_or_default();
+            if !gpus.is_empty() {
+                let n = gpus.len() as f64;
+                status.gpu_utilization =
+                    Some(gpus.iter().map(|g| g.utilization_percent).sum::<f64>() / n);
+                status.gpu_memory_percent =
+                    Some(gpus.iter().map(|g| g.memory_percent).sum::<f64>() / n);
+            }
+        }
+        Err(e) => debug!("Failed to read resource usage: {}", e),
+    }
+    status
+}
+
+/// S3 key of an instance's status document under `prefix`
+pub fn status_key(prefix: &str, instance_id: &str) -> String {
+    let prefix = prefix.trim_matches('/');
+    if prefix.is_empty() {
+        format!("{}.json", instance_id)
+    } else {
+        format!("{}/{}.json", prefix, instance_id)
+    }
+}
+
+/// Split `s3://bucket[/prefix]` into bucket and prefix
+fn parse_s3_prefix(uri: &str) -> Result<(String, String)> {
+    let rest = uri
+        .strip_prefix("s3://")
+        .ok_or_else(|| TrainctlError::Validation {
+            field: "s3".to_string(),
+            reason: format!("'{}' must start with s3://", uri),
+        })?;
+    let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+    if bucket.is_empty() {
+        return Err(TrainctlError::Validation {
+            field: "s3".to_string(),
+            reason: format!("'{}' has no bucket", uri),
+        });
+    }
+    Ok((bucket.to_string(), prefix.to_string()))
+}
+
+async fn publish(
+    status: &AgentStatus,
+    status_file: &Path,
+    s3: Option<(&S3Client, &str, &str)>,
+) -> Result<()> {
+    let body = serde_json::to_vec_pretty(status)?;
+
+    // Write then rename so readers never see a partial document
+    let tmp = status_file.with_extension("json.tmp");
+    std::fs::write(&tmp, &body)?;
+    std::fs::rename(&tmp, status_file)?;
+
+    if let Some((client, bucket, prefix)) = s3 {
+        let key = status_key(prefix, &status.instance_id);
+        client
+            .put_object()
+            .bucket(bucket)
+            .key(&key)
+            .content_type("application/json")
+            .body(body.into())
+            .send()
+            .await
+            .map_err(|e| {
+                TrainctlError::S3(format!("Failed to upload s3://{}/{}: {}", bucket, key, e))
+            })?;
+    }
+    Ok(())
+}
+
+/// Run the agent until training finishes (or once, with `--once`)
+pub async fn run_agent(options: AgentOptions, output_format: &str) -> Result<()> {
+    if options.interval_secs == 0 {
+        return Err(TrainctlError::Validation {
+            field: "interval".to_string(),
+            reason: "must be at least 1 second".to_string(),
+        });
+    }
+    let status_file = options
+        .status_file
+        .clone()
+        .unwrap_or_else(|| options.project_dir.join("agent-status.json"));
+    if let Some(parent) = status_file.parent().filter(|p| !p.as_os_str().is_empty()) {
+        crate::utils::ensure_dir(parent)?;
+    }
+
+    let s3 = match &options.s3 {
+        Some(uri) => {
+            let (bucket, prefix) = parse_s3_prefix(uri)?;
+            let aws_config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+            Some((S3Client::new(&aws_config), bucket, prefix))
+        }
+        None => None,
+    };
+
+    if output_format != "json" {
+        println!(
+            "runctl agent reporting on {} every {}s to {}{}",
+            options.instance_id,
+            options.interval_secs,
+            status_file.display(),
+            options
+                .s3
+                .as_deref()
+                .map(|uri| format!(" and {}", uri))
+                .unwrap_or_default()
+        );
+    }
+
+    let mut ticker = tokio::time::interval(Duration::from_secs(options.interval_secs));
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+
+        let status = collect_status(&options).await;
+        let target = s3
+            .as_ref()
+            .map(|(client, bucket, prefix)| (client, bucket.as_str(), prefix.as_str()));
+        // A failed upload is retried on the next tick; the CLI sees a stale heartbeat meanwhile
+        if let Err(e) = publish(&status, &status_file, target).await {
+            warn!("Failed to publish agent status: {}", e);
+        }
+        if output_format == "json" {
+            println!("{}", serde_json::to_string(&status)?);
+        }
+
+        if options.once || status.is_finished() {
+            return Ok(());
+        }
+    }
+}
+
+/// Fetch an instance's status document from `bucket`, if there is one
+pub async fn fetch_status(
+    client: &S3Client,
+    bucket: &str,
+    instance_id: &str,
+) -> Result<Option<AgentStatus>> {
+    let key = status_key(AGENT_STATUS_PREFIX, instance_id);
+    let response = match client.get_object().bucket(bucket).key(&key).send().await {
+        Ok(response) => response,
+        Err(e) if e.as_service_error().is_some_and(|e| e.is_no_such_key()) => return Ok(None),
+        Err(e) => {
+            return Err(TrainctlError::S3(format!(
+                "Failed to read s3://{}/{}: {}",
+                bucket, key, e
+            )))
+        }
+    };
+    let data = response
+        .body
+        .collect()
+        .await
+        .map_err(|e| TrainctlError::S3(format!("Failed to read response: {}", e)))?;
+    Ok(Some(serde_json::from_slice(&data.into_bytes())?))
+}
+
+/// The instance's agent status if `[aws] s3_bucket` is set and the heartbeat is fresh
+///
+/// Errors are logged and treated as no status, so callers fall back to SSM.
+pub async fn fetch_fresh_status(
+    config: &Config,
+    client: &S3Client,
+    instance_id: &str,
+) -> Option<AgentStatus> {
+    let bucket = config.aws.as_ref()?.s3_bucket.as_deref()?;
+    match fetch_status(client, bucket, instance_id).await {
+        Ok(status) => status.filter(|s| s.is_fresh(Utc::now())),
+        Err(e) => {
+            debug!("No agent status for {}: {}", instance_id, e);
+            None
+        }
+    }
+}
+
+/// Shell command that starts the agent in the background on the instance
+///
+/// Prints `AGENT:started`, or `AGENT:missing` when `runctl` isn't installed.
+pub fn launch_command(project_dir: &str, instance_id: &str, s3_bucket: Option<&str>) -> String {
+    use crate::utils::shell_quote;
+
+    let mut args = format!(
+        "agent --instance-id {} --project-dir {}",
+        shell_quote(instance_id),
+        shell_quote(project_dir)
+    );
+    if let Some(bucket) = s3_bucket {
+        args.push_str(&format!(
+            " --s3 {}",
+            shell_quote(&format!("s3://{}/{}", bucket, AGENT_STATUS_PREFIX))
+        ));
+    }
+    format!(
+        "RUNCTL_BIN=$(command -v runctl || echo \"$HOME/.runctl/bin/runctl\"); \
+         if [ -x \"$RUNCTL_BIN\" ]; then \
+             nohup \"$RUNCTL_BIN\" {args} > {log} 2>&1 & echo AGENT:started; \
+         else echo AGENT:missing; fi",
+        args = args,
+        log = shell_quote(&format!("{}/agent.log", project_dir))
+    )
+}
+
+/// Start the agent over SSM
+///
+/// Returns false when `runctl` is not installed on the instance.
+pub async fn start_via_ssm(
+    ssm_client: &aws_sdk_ssm::Client,
+    instance_id: &str,
+    project_dir: &str,
+    s3_bucket: Option<&str>,
+) -> Result<bool> {
+    let command = launch_command(project_dir, instance_id, s3_bucket);
+    let output = execute_ssm_command(ssm_client, instance_id, &command).await?;
+    Ok(output.contains("AGENT:started"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(training: &str, heartbeat: DateTime<Utc>) -> AgentStatus {
+        AgentStatus {
+            instance_id: "i-0abc".to_string(),
+            heartbeat,
+            interval_secs: 10,
+            training: training.to_string(),
+            cpu_percent: Some(50.0),
+            memory_percent: Some(20.0),
+            gpu_utilization: None,
+            gpu_memory_percent: None,
+            metrics: MetricsSnapshot::default(),
+            latest_checkpoint: None,
+            checkpoint_count: 0,
+            cache: None,
+        }
+    }
+
+    #[test]
+    fn test_training_state() {
+        assert_eq!(training_state(None, false, true, true), "running");
+        assert_eq!(training_state(Some(0), false, false, true), "completed");
+        assert_eq!(
+            training_state(Some(2), false, false, true),
+            "failed (exit 2)"
+        );
+        assert_eq!(training_state(None, true, false, true), "completed");
+        assert_eq!(training_state(None, false, false, true), "stopped");
+        assert_eq!(training_state(None, false, false, false), "pending");
+    }
+
+    #[test]
+    fn test_freshness() {
+        let now = Utc::now();
+        assert!(status("running", now - chrono::Duration::seconds(25)).is_fresh(now));
+        assert!(!status("running", now - chrono::Duration::seconds(31)).is_fresh(now));
+        assert!(!status("running", now).is_finished());
+        assert!(status("failed (exit 1)", now).is_finished());
+    }
+
+    #[test]
+    fn test_progress_summary() {
+        let mut s = status("running", Utc::now());
+        assert_eq!(s.progress_summary(), None);
+        s.metrics = parse_metrics(&["Epoch 3/10 step 120/500 loss=0.42".to_string()]);
+        assert_eq!(
+            s.progress_summary().unwrap(),
+            "epoch 3/10, step 120/500, loss 0.4200"
+        );
+    }
+
+    #[test]
+    fn test_status_key_and_prefix() {
+        assert_eq!(
+            status_key("runctl/agent/", "i-0abc"),
+            "runctl/agent/i-0abc.json"
+        );
+        assert_eq!(status_key("", "i-0abc"), "i-0abc.json");
+        assert_eq!(
+            parse_s3_prefix("s3://bucket/runctl/agent").unwrap(),
+            ("bucket".to_string(), "runctl/agent".to_string())
+        );
+        assert_eq!(
+            parse_s3_prefix("s3://bucket").unwrap(),
+            ("bucket".to_string(), String::new())
+        );
+        assert!(parse_s3_prefix("bucket/key").is_err());
+    }
+
+    #[test]
+    fn test_scan_checkpoints() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(scan_checkpoints(&dir.path().join("missing")), (None, 0));
+        std::fs::write(dir.path().join("epoch_1.pt"), b"one").unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        std::fs::write(dir.path().join("epoch_2.pt"), b"two!").unwrap();
+        std::fs::create_dir(dir.path().join("subdir")).unwrap();
+        let (latest, count) = scan_checkpoints(dir.path());
+        let latest = latest.unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(latest.name, "epoch_2.pt");
+        assert_eq!(latest.size_bytes, 4);
+    }
+
+    #[test]
+    fn test_launch_command() {
+        let command = launch_command("/home/ubuntu/proj", "i-0abc", Some("bucket"));
+        assert!(command.contains("--s3 's3://bucket/runctl/agent'"));
+        assert!(command.contains("AGENT:missing"));
+        assert!(!launch_command("/home/ubuntu/proj", "i-0abc", None).contains("--s3"));
+    }
+
+    #[test]
+    fn test_status_roundtrip() {
+        let s = status("running", Utc::now());
+        let json = serde_json::to_string(&s).unwrap();
+        assert_eq!(serde_json::from_str::<AgentStatus>(&json).unwrap(), s);
+    }
+}
@@ -0,0 +1,186 @@
+//! Command aliases and external subcommands
+//!
+//! Teams can codify their usual invocations in an `[aliases]` config section:
+//!
+//! ```toml
+//! [aliases]
+//! gpu = "aws create g5.xlarge --spot --preset gpu-dev"
+//! nightly = ["run", "--provider", "aws", "train.py", "--", "--epochs", "100"]
+//! ```
+//!
+//! `runctl gpu --wait` then runs `runctl aws create g5.xlarge --spot --preset
+//! gpu-dev --wait`. Aliases are expanded before clap parses the arguments, may
+//! refer to other aliases, and never shadow a built-in command.
+//!
+//! A command that is neither built in nor an alias is looked up on `PATH` as
+//! `runctl-<name>` (like cargo's `cargo-<name>`) and run with the remaining
+//! arguments. The `RUNCTL` environment variable tells it which runctl binary
+//! invoked it, and `RUNCTL_CONFIG` carries `--config` when one was given.
+
+use crate::error::{ConfigError, Result, TrainctlError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Aliases may refer to other aliases up to this depth
+const MAX_EXPANSION_DEPTH: usize = 16;
+
+/// Global flags that take a value, so the word after them isn't a command
+const GLOBAL_VALUE_FLAGS: &[&str] = &["-c", "--config", "--output", "--log-file"];
+
+/// Prefix of external subcommand executables on `PATH`
+pub const EXTERNAL_PREFIX: &str = "runctl-";
+
+/// An alias: one shell-quoted command line or a list of arguments
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AliasValue {
+    Line(String),
+    Args(Vec<String>),
+}
+
+impl AliasValue {
+    /// The arguments this alias expands to
+    pub fn to_args(&self, name: &str) -> Result<Vec<String>> {
+        let args = match self {
+            AliasValue::Line(line) => shell_words::split(line).map_err(|e| {
+                TrainctlError::Config(ConfigError::InvalidValue {
+                    field: format!("aliases.{}", name),
+                    reason: format!("cannot split {:?}: {}", line, e),
+                })
+            })?,
+            AliasValue::Args(args) => args.clone(),
+        };
+        if args.is_empty() {
+            return Err(TrainctlError::Config(ConfigError::InvalidValue {
+                field: format!("aliases.{}", name),
+                reason: "alias is empty".to_string(),
+            }));
+        }
+        Ok(args)
+    }
+}
+
+impl std::fmt::Display for AliasValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AliasValue::Line(line) => write!(f, "{}", line),
+            AliasValue::Args(args) => write!(f, "{}", shell_words::join(args)),
+        }
+    }
+}
+
+/// Index of the command word in `args` (`args[0]` is the binary), skipping global flags
+pub fn command_index(args: &[String]) -> Option<usize> {
+    let mut i = 1;
+    while i < args.len() {
+        let arg = args[i].as_str();
+        if arg == "--" {
+            return None;
+        }
+        if !arg.starts_with('-') {
+            return Some(i);
+        }
+        if GLOBAL_VALUE_FLAGS.contains(&arg) {
+            i += 1;
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Value of `-c`/`--config` in the raw arguments, needed to load aliases before parsing
+///
+/// `--config` is a global flag, so it may also follow the command word.
+pub fn config_flag(args: &[String]) -> Option<PathBuf> {
+    let mut iter = args.iter().skip(1).take_while(|arg| *arg != "--");
+    while let Some(arg) = iter.next() {
+        if arg == "-c" || arg == "--config" {
+            return iter.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+/// Replace an aliased command word with its expansion
+///
+/// Built-in commands always win over aliases of the same name. Returns the
+/// arguments unchanged when the command word is not an alias.
+pub fn expand(
+    mut args: Vec<String>,
+    aliases: &BTreeMap<String, AliasValue>,
+    builtins: &[String],
+) -> Result<Vec<String>> {
+    let Some(index) = command_index(&args) else {
+        return Ok(args);
+    };
+
+    let mut seen: Vec<String> = Vec::new();
+    loop {
+        let name = args[index].clone();
+        if builtins.contains(&name) {
+            return Ok(args);
+        }
+        let Some(alias) = aliases.get(&name) else {
+            return Ok(args);
+        };
+        if seen.contains(&name) || seen.len() >= MAX_EXPANSION_DEPTH {
+            seen.push(name);
+            return Err(TrainctlError::Config(ConfigError::InvalidValue {
+                field: format!("aliases.{}", seen[0]),
+                reason: format!("alias expands recursively: {}", seen.join(" -> ")),
+            }));
+        }
+        let expansion = alias.to_args(&name)?;
+        args.splice(index..=index, expansion);
+        seen.push(name);
+    }
+}
+
+/// Aliases that are never used because a built-in command has the same name
+pub fn shadowed<'a>(
+    aliases: &'a BTreeMap<String, AliasValue>,
+    builtins: &[String],
+) -> Vec<&'a str> {
+    aliases
+        .keys()
+        .filter(|name| builtins.contains(name))
+        .map(String::as_str)
+        .collect()
+}
+
+/// Find the `runctl-<name>` executable for an external subcommand
+pub fn find_external(name: &str) -> Option<PathBuf> {
+    if name.is_empty() || name.contains(std::path::is_separator) {
+        return None;
+    }
+    which::which(format!("{}{}", EXTERNAL_PREFIX, name)).ok()
+}
+
+/// Run an external subcommand and return its exit code
+pub fn run_external(program: &Path, args: &[String], config: Option<&Path>) -> Result<i32> {
+    let mut command = Command::new(program);
+    command.args(args);
+    if let Ok(exe) = std::env::current_exe() {
+        command.env("RUNCTL", exe);
+    }
+    if let Some(config) = config {
+        command.env("RUNCTL_CONFIG", config);
+    }
+    let status = command.status().map_err(|e| {
+        TrainctlError::Io(std::io::Error::new(
+            e.kind(),
+            format!("failed to run {}: {}", program.display(), e),
+        ))
+    })?;
+    // Killed by a signal: report the conventional 128 + signal code
+    #[cfg(unix)]
+    if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+        return Ok(128 + signal);
+    }
+    Ok(status.code().unwrap_or(1))
+}
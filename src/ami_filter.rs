@@ -0,0 +1,248 @@
+//! `--ami-filter` constraints on the Deep Learning AMI
+//!
+//! `"pytorch=2.3,cuda=12.1,os=ubuntu22"` (or a preset's `ami_filter`) parses
+//! into an [`AmiFilter`]. It lives outside the `aws` module so configs with
+//! `ami_filter` still load in builds without the `aws` feature; the lookup
+//! itself is in `aws::ami`.
+
+// Matching is only used by the lookup in `aws::ami`
+#![cfg_attr(not(feature = "aws"), allow(dead_code))]
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// `os=` values and the label the DLAMI names use for them
+const OS_LABELS: &[(&str, &str)] = &[
+    ("ubuntu20", "Ubuntu 20.04"),
+    ("ubuntu22", "Ubuntu 22.04"),
+    ("ubuntu24", "Ubuntu 24.04"),
+    ("al2", "Amazon Linux 2"),
+    ("al2023", "Amazon Linux 2023"),
+];
+
+/// Framework, CUDA and OS constraints on the Deep Learning AMI
+///
+/// Versions match by prefix on component boundaries: `pytorch=2.3` accepts
+/// 2.3.0 and 2.3.1 but not 2.30.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct AmiFilter {
+    pub pytorch: Option<String>,
+    pub tensorflow: Option<String>,
+    pub cuda: Option<String>,
+    /// One of the `OS_LABELS` keys
+    pub os: Option<String>,
+}
+
+impl FromStr for AmiFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut filter = AmiFilter::default();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .map(|(k, v)| (k.trim().to_lowercase(), v.trim().to_string()))
+                .ok_or_else(|| format!("expected key=value, got '{}'", part))?;
+            if value.is_empty() {
+                return Err(format!("'{}' has no value", key));
+            }
+            let slot = match key.as_str() {
+                "pytorch" | "torch" => &mut filter.pytorch,
+                "tensorflow" | "tf" => &mut filter.tensorflow,
+                "cuda" => &mut filter.cuda,
+                "os" => {
+                    let os = value.to_lowercase();
+                    if !OS_LABELS.iter().any(|(k, _)| *k == os) {
+                        let known: Vec<&str> = OS_LABELS.iter().map(|(k, _)| *k).collect();
+                        return Err(format!(
+                            "unknown os '{}' (expected one of {})",
+                            value,
+                            known.join(", ")
+                        ));
+                    }
+                    filter.os = Some(os);
+                    continue;
+                }
+                _ => {
+                    return Err(format!(
+                        "unknown key '{}' (expected pytorch, tensorflow, cuda or os)",
+                        key
+                    ))
+                }
+            };
+            if !value.chars().all(|c| c.is_ascii_digit() || c == '.') {
+                return Err(format!("'{}' is not a version number", value));
+            }
+            *slot = Some(value);
+        }
+        if filter.pytorch.is_some() && filter.tensorflow.is_some() {
+            return Err("pytorch and tensorflow can't both be set".to_string());
+        }
+        if filter == AmiFilter::default() {
+            return Err("no constraints given".to_string());
+        }
+        Ok(filter)
+    }
+}
+
+impl TryFrom<String> for AmiFilter {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<AmiFilter> for String {
+    fn from(filter: AmiFilter) -> Self {
+        filter.to_string()
+    }
+}
+
+impl fmt::Display for AmiFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = [
+            ("pytorch", &self.pytorch),
+            ("tensorflow", &self.tensorflow),
+            ("cuda", &self.cuda),
+            ("os", &self.os),
+        ]
+        .iter()
+        .filter_map(|(key, value)| value.as_ref().map(|v| format!("{}={}", key, v)))
+        .collect();
+        write!(f, "{}", parts.join(","))
+    }
+}
+
+impl AmiFilter {
+    fn os_label(&self) -> Option<&'static str> {
+        let os = self.os.as_deref()?;
+        OS_LABELS
+            .iter()
+            .find(|(key, _)| *key == os)
+            .map(|(_, label)| *label)
+    }
+
+    fn framework(&self) -> Option<(&'static str, &str)> {
+        match (&self.pytorch, &self.tensorflow) {
+            (Some(v), _) => Some(("PyTorch", v)),
+            (None, Some(v)) => Some(("TensorFlow", v)),
+            (None, None) => None,
+        }
+    }
+
+    /// DescribeImages name pattern; versions are re-checked by `check`
+    pub(crate) fn name_pattern(&self) -> String {
+        let mut pattern = match self.framework() {
+            Some((name, version)) => format!("Deep Learning*AMI GPU {} {}*", name, version),
+            None => "Deep Learning*AMI*".to_string(),
+        };
+        if let Some(label) = self.os_label() {
+            pattern.push_str(&format!("({})*", label));
+        }
+        pattern
+    }
+
+    /// Why an image with this name and description doesn't satisfy the filter
+    pub(crate) fn check(&self, name: &str, description: &str) -> std::result::Result<(), String> {
+        let text = format!("{} {}", name, description);
+        if let Some((framework, wanted)) = self.framework() {
+            let found = versions_after(framework, &text);
+            if !found.iter().any(|v| version_matches(v, wanted)) {
+                return Err(format!("{} {} not found", framework, wanted));
+            }
+        }
+        if let Some(wanted) = &self.cuda {
+            let found = versions_after("CUDA", description);
+            if found.is_empty() {
+                return Err("description does not state a CUDA version".to_string());
+            }
+            if !found.iter().any(|v| version_matches(v, wanted)) {
+                return Err(format!("CUDA {} (wanted {})", found.join(", "), wanted));
+            }
+        }
+        if let Some(label) = self.os_label() {
+            if !name.contains(&format!("({})", label)) {
+                return Err(format!("not {}", label));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Version numbers following `keyword` in `text` (e.g. "PyTorch 2.3.0",
+/// "PyTorch-2.3", "CUDA version 12.1", "CUDA versions 11.8, 12.1")
+fn versions_after(keyword: &str, text: &str) -> Vec<String> {
+    let re = regex::Regex::new(&format!(
+        r"(?i){}[\s-]*(?:versions?)?[\s:-]*((?:\d+(?:\.\d+)*(?:,\s*)?)+)",
+        regex::escape(keyword)
+    ))
+    .expect("valid regex");
+    re.captures_iter(text)
+        .flat_map(|caps| {
+            caps[1]
+                .split(',')
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// `found` is `wanted` or a more specific version of it (2.3.1 for 2.3)
+fn version_matches(found: &str, wanted: &str) -> bool {
+    found == wanted
+        || found
+            .strip_prefix(wanted)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ami_filter() {
+        let filter: AmiFilter = "pytorch=2.3, cuda=12.1,os=Ubuntu22".parse().unwrap();
+        assert_eq!(filter.pytorch.as_deref(), Some("2.3"));
+        assert_eq!(filter.cuda.as_deref(), Some("12.1"));
+        assert_eq!(filter.os.as_deref(), Some("ubuntu22"));
+        assert_eq!(filter.to_string(), "pytorch=2.3,cuda=12.1,os=ubuntu22");
+        assert_eq!(
+            filter.name_pattern(),
+            "Deep Learning*AMI GPU PyTorch 2.3*(Ubuntu 22.04)*"
+        );
+
+        assert!("".parse::<AmiFilter>().is_err());
+        assert!("pytorch".parse::<AmiFilter>().is_err());
+        assert!("jax=0.4".parse::<AmiFilter>().is_err());
+        assert!("os=windows".parse::<AmiFilter>().is_err());
+        assert!("cuda=latest".parse::<AmiFilter>().is_err());
+        assert!("pytorch=2.3,tensorflow=2.16".parse::<AmiFilter>().is_err());
+    }
+
+    #[test]
+    fn test_ami_filter_check() {
+        let filter: AmiFilter = "pytorch=2.3,cuda=12.1,os=ubuntu22".parse().unwrap();
+        let name = "Deep Learning OSS Nvidia Driver AMI GPU PyTorch 2.3.0 (Ubuntu 22.04) 20240708";
+        let description = "Supported EC2 instances: G4dn, G5, G6, P4d. PyTorch-2.3, NVIDIA-Driver 550.90.07, CUDA version 12.1";
+        assert!(filter.check(name, description).is_ok());
+
+        // Newer torch build that the name pattern `2.3*` would also match
+        let name_230 = name.replace("2.3.0", "2.30.0");
+        assert!(filter.check(&name_230, "CUDA version 12.1").is_err());
+        assert!(filter.check(name, "CUDA version 12.4").is_err());
+        assert!(filter.check(name, "no version info").is_err());
+        let al2 = "Deep Learning AMI GPU PyTorch 2.3.0 (Amazon Linux 2) 20240625";
+        assert!(filter.check(al2, description).is_err());
+
+        let multi: AmiFilter = "cuda=11.8".parse().unwrap();
+        assert!(multi
+            .check("Deep Learning AMI", "CUDA versions 11.7, 11.8, 12.1")
+            .is_ok());
+        assert!(version_matches("2.3.1", "2.3"));
+        assert!(!version_matches("2.30", "2.3"));
+    }
+}
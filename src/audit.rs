@@ -0,0 +1,98 @@
+//! Local audit log
+//!
+//! Append-only record of actions that hand out access to data (such as
+//! pre-signed URLs), stored as JSON lines in `.runctl/audit.jsonl` so a team
+//! can answer "who shared what, and until when".
+
+use crate::config::Config;
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// One audited action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub user: String,
+    /// Dotted action name, e.g. `s3.share`
+    pub action: String,
+    /// Resource the action applied to
+    pub target: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub details: BTreeMap<String, String>,
+}
+
+impl AuditEntry {
+    pub fn new(config: &Config, action: &str, target: &str) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            user: crate::utils::get_user_id(config),
+            action: action.to_string(),
+            target: target.to_string(),
+            details: BTreeMap::new(),
+        }
+    }
+
+    pub fn detail(mut self, key: &str, value: impl ToString) -> Self {
+        self.details.insert(key.to_string(), value.to_string());
+        self
+    }
+}
+
+pub fn log_path() -> PathBuf {
+    PathBuf::from(".runctl").join("audit.jsonl")
+}
+
+/// Append an entry to the default audit log
+pub fn record(entry: &AuditEntry) -> Result<()> {
+    append_to(&log_path(), entry)
+}
+
+pub fn append_to(path: &Path, entry: &AuditEntry) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        crate::utils::ensure_dir(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+/// Load all entries, skipping lines that don't parse
+pub fn load_from(path: &Path) -> Result<Vec<AuditEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let config = Config::default();
+
+        let entry = AuditEntry::new(&config, "s3.share", "s3://bucket/model.pt")
+            .detail("method", "GET")
+            .detail("expires_secs", 3600);
+        append_to(&path, &entry).unwrap();
+
+        let loaded = load_from(&path).unwrap();
+        assert_eq!(loaded, vec![entry]);
+        assert_eq!(loaded[0].details["expires_secs"], "3600");
+    }
+}
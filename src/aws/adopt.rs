@@ -0,0 +1,458 @@
+//! Adopting existing instances
+//!
+//! `runctl aws adopt <instance-id>` brings a manually created instance under
+//! runctl management without recreating it: it infers the project and user,
+//! writes the same `runctl:*` tags `create` would, registers the instance
+//! with the `ResourceTracker`, and checks that SSM or SSH access works so
+//! `train`, `monitor`, and `processes` can reach it.
+//!
+//! Adoption never sets `CreatedBy=runctl`; that tag stays reserved for
+//! instances runctl launched itself. `runctl:created` records the instance's
+//! launch time and `runctl:adopted` the time it was adopted.
+
+use crate::aws::helpers::{
+    ec2_instance_to_resource_status, find_instance_in_response, get_project_name, get_user_id,
+};
+use crate::config::Config;
+use crate::error::{Result, TrainctlError};
+use aws_sdk_ec2::types::{Instance, Tag};
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_ssm::Client as SsmClient;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::time::Duration;
+use tracing::warn;
+
+/// Existing tags that name a project, in priority order
+const PROJECT_TAGS: &[&str] = &["runctl:project", "Project", "project"];
+
+/// Existing tags that name an owner, in priority order
+const USER_TAGS: &[&str] = &["runctl:user", "Owner", "owner", "User", "user"];
+
+/// How long to wait for the SSH port to accept a connection
+const SSH_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone)]
+pub(crate) struct AdoptOptions {
+    pub instance_id: String,
+    pub project_name: Option<String>,
+    pub user: Option<String>,
+    pub dry_run: bool,
+}
+
+/// A value and where it came from ("flag", "tag Project", "config", ...)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct Inferred {
+    pub value: String,
+    pub source: String,
+}
+
+/// Result of probing remote access
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct AccessCheck {
+    pub method: String,
+    pub ok: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize)]
+struct AdoptResult {
+    success: bool,
+    instance_id: String,
+    dry_run: bool,
+    already_managed: bool,
+    project: Inferred,
+    user: Inferred,
+    tags: Vec<(String, String)>,
+    registered: bool,
+    access: Vec<AccessCheck>,
+}
+
+fn tag_value<'a>(tags: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    tags.iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+        .filter(|v| !v.trim().is_empty())
+}
+
+/// Pick a value from an explicit flag, the first matching tag, or a fallback
+fn infer(
+    explicit: Option<&str>,
+    tags: &[(String, String)],
+    tag_keys: &[&str],
+    fallback: (&str, &str),
+) -> Inferred {
+    if let Some(value) = explicit {
+        return Inferred {
+            value: value.to_string(),
+            source: "flag".to_string(),
+        };
+    }
+    for key in tag_keys {
+        if let Some(value) = tag_value(tags, key) {
+            return Inferred {
+                value: value.to_string(),
+                source: format!("tag {}", key),
+            };
+        }
+    }
+    Inferred {
+        value: fallback.0.to_string(),
+        source: fallback.1.to_string(),
+    }
+}
+
+/// Tags to write so the instance looks like one runctl created
+fn adoption_tags(
+    instance_id: &str,
+    existing: &[(String, String)],
+    project: &str,
+    user: &str,
+    launch_time: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Vec<(String, String)> {
+    let mut tags = vec![
+        ("runctl:project".to_string(), project.to_string()),
+        ("runctl:user".to_string(), user.to_string()),
+        (
+            "runctl:adopted".to_string(),
+            now.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        ),
+    ];
+    if tag_value(existing, "runctl:created").is_none() {
+        let created = launch_time.unwrap_or(now);
+        tags.push((
+            "runctl:created".to_string(),
+            created.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        ));
+    }
+    // Keep a user-chosen name; otherwise use the same scheme as `create`
+    if tag_value(existing, "Name").is_none() {
+        let short_id = instance_id.get(..8).unwrap_or(instance_id);
+        tags.push((
+            "Name".to_string(),
+            format!("runctl-{}-{}-{}", user, project, short_id),
+        ));
+    }
+    tags
+}
+
+fn instance_tags(instance: &Instance) -> Vec<(String, String)> {
+    instance
+        .tags()
+        .iter()
+        .filter_map(|tag| {
+            tag.key()
+                .zip(tag.value())
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+        })
+        .collect()
+}
+
+pub(crate) async fn describe(client: &Ec2Client, instance_id: &str) -> Result<Instance> {
+    let response = client
+        .describe_instances()
+        .instance_ids(instance_id)
+        .send()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to describe instance: {}", e)))?;
+    find_instance_in_response(&response, instance_id)
+        .cloned()
+        .ok_or_else(|| TrainctlError::ResourceNotFound {
+            resource_type: "instance".to_string(),
+            resource_id: instance_id.to_string(),
+        })
+}
+
+/// Check SSM (if an instance profile is attached) and the SSH port
+async fn verify_access(
+    instance: &Instance,
+    instance_id: &str,
+    aws_config: &aws_config::SdkConfig,
+) -> Vec<AccessCheck> {
+    let mut checks = Vec::new();
+
+    if instance.iam_instance_profile().is_some() {
+        let ssm_client = SsmClient::new(aws_config);
+        let check = match crate::aws_utils::execute_ssm_command(
+            &ssm_client,
+            instance_id,
+            "echo RUNCTL_ADOPT_OK",
+        )
+        .await
+        {
+            Ok(output) if output.contains("RUNCTL_ADOPT_OK") => AccessCheck {
+                method: "ssm".to_string(),
+                ok: true,
+                detail: "command executed".to_string(),
+            },
+            Ok(output) => AccessCheck {
+                method: "ssm".to_string(),
+                ok: false,
+                detail: format!("unexpected output: {}", output.trim()),
+            },
+            Err(e) => AccessCheck {
+                method: "ssm".to_string(),
+                ok: false,
+                detail: e.to_string().lines().next().unwrap_or_default().to_string(),
+            },
+        };
+        checks.push(check);
+    } else {
+        checks.push(AccessCheck {
+            method: "ssm".to_string(),
+            ok: false,
+            detail: "no IAM instance profile attached".to_string(),
+        });
+    }
+
+    match (instance.key_name(), crate::aws::public_address(instance)) {
+        (Some(key), Some(ip)) => {
+            let reachable =
+                tokio::time::timeout(SSH_PROBE_TIMEOUT, tokio::net::TcpStream::connect((ip, 22)))
+                    .await;
+            let (ok, detail) = match reachable {
+                Ok(Ok(_)) => (true, format!("port 22 open on {} (key pair {})", ip, key)),
+                Ok(Err(e)) => (
+                    false,
+                    format!("{}:22 refused: {}", crate::utils::bracket_ipv6(ip), e),
+                ),
+                Err(_) => (
+                    false,
+                    format!(
+                        "{}:22 timed out (check the security group)",
+                        crate::utils::bracket_ipv6(ip)
+                    ),
+                ),
+            };
+            checks.push(AccessCheck {
+                method: "ssh".to_string(),
+                ok,
+                detail,
+            });
+        }
+        (None, _) => checks.push(AccessCheck {
+            method: "ssh".to_string(),
+            ok: false,
+            detail: "no key pair".to_string(),
+        }),
+        (Some(_), None) => checks.push(AccessCheck {
+            method: "ssh".to_string(),
+            ok: false,
+            detail: "no public IP".to_string(),
+        }),
+    }
+
+    checks
+}
+
+/// Adopt an existing instance into runctl management
+pub(crate) async fn adopt_instance(
+    options: AdoptOptions,
+    config: &Config,
+    aws_config: &aws_config::SdkConfig,
+    output_format: &str,
+) -> Result<()> {
+    let client = Ec2Client::new(aws_config);
+    let instance_id = options.instance_id.as_str();
+    let instance = describe(&client, instance_id).await?;
+
+    let state = instance
+        .state()
+        .and_then(|s| s.name())
+        .map(|s| s.as_str().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    if matches!(state.as_str(), "terminated" | "shutting-down") {
+        return Err(TrainctlError::Resource {
+            resource_type: "instance".to_string(),
+            operation: "adopt".to_string(),
+            resource_id: Some(instance_id.to_string()),
+            message: format!("Instance is {} and cannot be adopted", state),
+            source: None,
+        });
+    }
+
+    let existing = instance_tags(&instance);
+    let already_managed = tag_value(&existing, "runctl:project").is_some();
+
+    let default_project = get_project_name(None, config);
+    let default_user = get_user_id(config);
+    let project = infer(
+        options.project_name.as_deref(),
+        &existing,
+        PROJECT_TAGS,
+        (&default_project, "current directory"),
+    );
+    let user = infer(
+        options.user.as_deref(),
+        &existing,
+        USER_TAGS,
+        (&default_user, "config/environment"),
+    );
+    crate::validation::validate_project_name(&project.value).map_err(|_| {
+        TrainctlError::Validation {
+            field: "project_name".to_string(),
+            reason: format!(
+                "Inferred project '{}' (from {}) is not a valid project name; pass --project-name",
+                project.value, project.source
+            ),
+        }
+    })?;
+
+    let launch_time = instance
+        .launch_time()
+        .and_then(|t| DateTime::from_timestamp(t.secs(), 0));
+    let tags = adoption_tags(
+        instance_id,
+        &existing,
+        &project.value,
+        &user.value,
+        launch_time,
+        Utc::now(),
+    );
+
+    let mut registered = false;
+    let mut access = Vec::new();
+    if !options.dry_run {
+        let mut request = client.create_tags().resources(instance_id);
+        for (key, value) in &tags {
+            request = request.tags(Tag::builder().key(key).value(value).build());
+        }
+        request
+            .send()
+            .await
+            .map_err(|e| TrainctlError::Aws(format!("Failed to tag instance: {}", e)))?;
+
+        // Re-read so the tracker sees the new tags
+        let tagged = describe(&client, instance_id).await?;
+        if let Some(tracker) = &config.resource_tracker {
+            let status = ec2_instance_to_resource_status(&tagged, instance_id)?;
+            match tracker.register(status).await {
+                Ok(()) | Err(TrainctlError::ResourceExists { .. }) => registered = true,
+                Err(e) => warn!("Failed to register instance in tracker: {}", e),
+            }
+        }
+
+        if state == "running" {
+            access = verify_access(&tagged, instance_id, aws_config).await;
+        }
+    }
+
+    if output_format == "json" {
+        let result = AdoptResult {
+            success: true,
+            instance_id: instance_id.to_string(),
+            dry_run: options.dry_run,
+            already_managed,
+            project,
+            user,
+            tags,
+            registered,
+            access,
+        };
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    let verb = if options.dry_run {
+        "Would adopt"
+    } else {
+        "Adopted"
+    };
+    println!("{} instance {} ({})", verb, instance_id, state);
+    if already_managed {
+        println!("  Note: instance already had runctl tags; they were refreshed");
+    }
+    println!("  Project: {} (from {})", project.value, project.source);
+    println!("  User:    {} (from {})", user.value, user.source);
+    println!("  Tags:");
+    for (key, value) in &tags {
+        println!("    {}={}", key, value);
+    }
+    if options.dry_run {
+        return Ok(());
+    }
+    if registered {
+        println!("  Registered with resource tracker");
+    }
+    if state != "running" {
+        println!(
+            "  Access not verified (instance is {}). Start it with: runctl aws start {} --wait",
+            state, instance_id
+        );
+        return Ok(());
+    }
+    println!("  Access:");
+    for check in &access {
+        let mark = if check.ok { "ok  " } else { "FAIL" };
+        println!("    [{}] {}: {}", mark, check.method, check.detail);
+    }
+    if !access.iter().any(|c| c.ok) {
+        println!();
+        println!("WARNING: Neither SSM nor SSH is reachable; training commands will fail.");
+        println!("  SSM: attach an instance profile with AmazonSSMManagedInstanceCore");
+        println!("       (see ./scripts/setup-ssm-role.sh)");
+        println!("  SSH: allow port 22 from your IP in the instance's security group");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_infer_priority() {
+        let existing = tags(&[("project", "lower"), ("Project", "upper"), ("Owner", "sam")]);
+        let project = infer(None, &existing, PROJECT_TAGS, ("cwd", "current directory"));
+        assert_eq!(project.value, "upper");
+        assert_eq!(project.source, "tag Project");
+
+        let project = infer(Some("explicit"), &existing, PROJECT_TAGS, ("cwd", "cwd"));
+        assert_eq!(project.source, "flag");
+
+        let user = infer(None, &existing, USER_TAGS, ("me", "config/environment"));
+        assert_eq!(user.value, "sam");
+
+        let user = infer(None, &tags(&[("Owner", " ")]), USER_TAGS, ("me", "env"));
+        assert_eq!(user.value, "me");
+        assert_eq!(user.source, "env");
+    }
+
+    #[test]
+    fn test_adoption_tags_preserve_existing() {
+        let now = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let launched = DateTime::from_timestamp(1_690_000_000, 0).unwrap();
+
+        let fresh = adoption_tags(
+            "i-0123456789abcdef0",
+            &[],
+            "vision",
+            "sam",
+            Some(launched),
+            now,
+        );
+        assert_eq!(
+            tag_value(&fresh, "runctl:created"),
+            Some("2023-07-22 04:26:40 UTC")
+        );
+        assert_eq!(
+            tag_value(&fresh, "Name"),
+            Some("runctl-sam-vision-i-012345")
+        );
+        assert!(tag_value(&fresh, "CreatedBy").is_none());
+
+        let existing = tags(&[("Name", "my-box"), ("runctl:created", "earlier")]);
+        let retag = adoption_tags("i-0123456789abcdef0", &existing, "vision", "sam", None, now);
+        assert!(tag_value(&retag, "Name").is_none());
+        assert!(tag_value(&retag, "runctl:created").is_none());
+        assert_eq!(tag_value(&retag, "runctl:project"), Some("vision"));
+    }
+}
@@ -0,0 +1,356 @@
+//! Deep Learning AMI lookup with a local cache
+//!
+//! Finding the newest Deep Learning AMI takes up to one DescribeImages call
+//! per name pattern. Results (including patterns with no match) are cached
+//! per region and pattern in `.runctl/ami_cache.json` for
+//! `[aws] ami_cache_ttl_secs` (default 24h), so repeated GPU creates skip the
+//! lookup. `aws create --refresh-ami` bypasses the cache. To pin an AMI
+//! instead, pass `--ami-id` or set `ami_id` on a preset.
+//!
+//! `--ami-filter "pytorch=2.3,cuda=12.1,os=ubuntu22"` (or a preset's
+//! `ami_filter`) narrows the search to a framework, CUDA and OS version. The
+//! constraints become a name pattern, and each candidate's name and
+//! description are checked before it is used; if nothing satisfies them the
+//! create fails rather than falling back to the newest image.
+//!
+//! `root_device_name` reads the root device an AMI boots from, so the root
+//! volume mapping resizes that device instead of adding an empty volume.
+
+use crate::ami_filter::AmiFilter;
+use crate::error::{Result, TrainctlError};
+use aws_sdk_ec2::types::Filter;
+use aws_sdk_ec2::Client as Ec2Client;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Deep Learning AMI name patterns, most preferred first
+const DLAMI_PATTERNS: &[&str] = &[
+    "Deep Learning AMI GPU PyTorch * (Amazon Linux 2)*",
+    "Deep Learning AMI GPU PyTorch *",
+    "Deep Learning AMI (Amazon Linux 2)*",
+    "Deep Learning Base AMI (Amazon Linux 2)*",
+];
+
+fn cache_path() -> PathBuf {
+    PathBuf::from(".runctl").join("ami_cache.json")
+}
+
+/// Result of one region/pattern lookup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CachedAmi {
+    /// None when no available image matched the pattern
+    ami_id: Option<String>,
+    resolved_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AmiCache {
+    /// Keyed by `<region>/<pattern>`, or `<region>/filter:<filter>` for `--ami-filter`
+    entries: BTreeMap<String, CachedAmi>,
+}
+
+impl AmiCache {
+    /// Load the cache, starting empty if it is missing or unreadable
+    fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            crate::utils::ensure_dir(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Save, logging rather than failing the lookup
+    fn save_or_warn(&self, path: &Path) {
+        if let Err(e) = self.save(path) {
+            warn!("Failed to save AMI cache: {}", e);
+        }
+    }
+
+    fn key(region: &str, pattern: &str) -> String {
+        format!("{}/{}", region, pattern)
+    }
+
+    /// Entry for a region and pattern if it is younger than `ttl_secs`
+    fn fresh(
+        &self,
+        region: &str,
+        pattern: &str,
+        ttl_secs: u64,
+        now: DateTime<Utc>,
+    ) -> Option<&CachedAmi> {
+        self.entries
+            .get(&Self::key(region, pattern))
+            .filter(|entry| (now - entry.resolved_at).num_seconds() < ttl_secs as i64)
+    }
+
+    fn insert(&mut self, region: &str, pattern: &str, ami_id: Option<String>, now: DateTime<Utc>) {
+        self.entries.insert(
+            Self::key(region, pattern),
+            CachedAmi {
+                ami_id,
+                resolved_at: now,
+            },
+        );
+    }
+}
+
+/// Newest available image matching `pattern`, if any
+async fn newest_image(client: &Ec2Client, pattern: &str) -> Result<Option<String>> {
+    let response = client
+        .describe_images()
+        .owners("amazon")
+        .filters(Filter::builder().name("name").values(pattern).build())
+        .filters(Filter::builder().name("state").values("available").build())
+        .send()
+        .await
+        .map_err(|e| {
+            TrainctlError::Aws(format!("Failed to search for Deep Learning AMI: {}", e))
+        })?;
+
+    response
+        .images()
+        .iter()
+        .max_by(|a, b| {
+            a.creation_date()
+                .unwrap_or("")
+                .cmp(b.creation_date().unwrap_or(""))
+        })
+        .map(|image| {
+            image
+                .image_id()
+                .map(str::to_string)
+                .ok_or_else(|| TrainctlError::Aws("AMI has no image ID".to_string()))
+        })
+        .transpose()
+}
+
+/// Newest available image satisfying `filter`
+async fn filtered_image(client: &Ec2Client, filter: &AmiFilter) -> Result<String> {
+    let pattern = filter.name_pattern();
+    let response = client
+        .describe_images()
+        .owners("amazon")
+        .filters(Filter::builder().name("name").values(&pattern).build())
+        .filters(Filter::builder().name("state").values("available").build())
+        .send()
+        .await
+        .map_err(|e| {
+            TrainctlError::Aws(format!("Failed to search for Deep Learning AMI: {}", e))
+        })?;
+
+    let mut images: Vec<_> = response.images().iter().collect();
+    images.sort_by(|a, b| {
+        b.creation_date()
+            .unwrap_or("")
+            .cmp(a.creation_date().unwrap_or(""))
+    });
+    let mut rejected = None;
+    for image in &images {
+        let name = image.name().unwrap_or("");
+        match filter.check(name, image.description().unwrap_or("")) {
+            Ok(()) => {
+                if let Some(id) = image.image_id() {
+                    debug!("AMI {} ({}) satisfies {}", id, name, filter);
+                    return Ok(id.to_string());
+                }
+            }
+            Err(reason) => {
+                debug!("Skipping AMI {}: {}", name, reason);
+                rejected.get_or_insert_with(|| format!("newest, {}: {}", name, reason));
+            }
+        }
+    }
+
+    Err(TrainctlError::CloudProvider {
+        provider: "aws".to_string(),
+        message: match rejected {
+            Some(detail) => format!(
+                "No Deep Learning AMI satisfies '{}': {} image(s) matched '{}' but none passed validation ({})",
+                filter,
+                images.len(),
+                pattern,
+                detail
+            ),
+            None => format!(
+                "No Deep Learning AMI satisfies '{}': nothing matched '{}'",
+                filter, pattern
+            ),
+        },
+        source: None,
+    })
+}
+
+/// Find the latest Deep Learning AMI for GPU instances
+///
+/// With a `filter`, only images satisfying it are considered. `ttl_secs` of 0
+/// or `refresh` skips cached results; fresh lookups are written back either
+/// way.
+pub(crate) async fn find_deep_learning_ami(
+    client: &Ec2Client,
+    region: &str,
+    filter: Option<&AmiFilter>,
+    ttl_secs: u64,
+    refresh: bool,
+) -> Result<String> {
+    // Key by the region the client actually queries
+    let region = client
+        .config()
+        .region()
+        .map(|r| r.to_string())
+        .unwrap_or_else(|| region.to_string());
+    let region = region.as_str();
+    let path = cache_path();
+    let mut cache = AmiCache::load(&path);
+    let use_cache = ttl_secs > 0 && !refresh;
+    let now = Utc::now();
+
+    if let Some(filter) = filter {
+        // Only successful filtered lookups are cached
+        let key = format!("filter:{}", filter);
+        let cached = use_cache
+            .then(|| cache.fresh(region, &key, ttl_secs, now))
+            .flatten()
+            .and_then(|entry| entry.ami_id.clone());
+        if let Some(ami_id) = cached {
+            debug!("Using cached AMI lookup for {} in {}", filter, region);
+            return Ok(ami_id);
+        }
+        let ami_id = filtered_image(client, filter).await?;
+        cache.insert(region, &key, Some(ami_id.clone()), now);
+        cache.save_or_warn(&path);
+        return Ok(ami_id);
+    }
+
+    let mut updated = false;
+
+    let mut found = None;
+    for pattern in DLAMI_PATTERNS {
+        let cached = use_cache
+            .then(|| cache.fresh(region, pattern, ttl_secs, now))
+            .flatten()
+            .map(|entry| entry.ami_id.clone());
+        let ami_id = match cached {
+            Some(ami_id) => {
+                debug!("Using cached AMI lookup for {} in {}", pattern, region);
+                ami_id
+            }
+            None => {
+                let ami_id = newest_image(client, pattern).await?;
+                cache.insert(region, pattern, ami_id.clone(), now);
+                updated = true;
+                ami_id
+            }
+        };
+        if ami_id.is_some() {
+            found = ami_id;
+            break;
+        }
+    }
+
+    if updated {
+        cache.save_or_warn(&path);
+    }
+
+    found.ok_or_else(|| TrainctlError::CloudProvider {
+        provider: "aws".to_string(),
+        message: "No Deep Learning AMI found with any pattern".to_string(),
+        source: None,
+    })
+}
+
+/// Root device name assumed when an AMI does not report one (Ubuntu's)
+pub(crate) const DEFAULT_ROOT_DEVICE: &str = "/dev/sda1";
+
+/// Root device name of `ami_id`, e.g. `/dev/sda1` (Ubuntu) or `/dev/xvda`
+/// (Amazon Linux)
+///
+/// A block device mapping under any other name attaches a second volume and
+/// leaves the root at the AMI's size.
+pub(crate) async fn root_device_name(client: &Ec2Client, ami_id: &str) -> Result<String> {
+    let response = client
+        .describe_images()
+        .image_ids(ami_id)
+        .send()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to describe AMI {}: {}", ami_id, e)))?;
+    let image = response
+        .images()
+        .first()
+        .ok_or_else(|| TrainctlError::ResourceNotFound {
+            resource_type: "AMI".to_string(),
+            resource_id: ami_id.to_string(),
+        })?;
+    Ok(match image.root_device_name() {
+        Some(device) => device.to_string(),
+        None => {
+            warn!(
+                "AMI {} reports no root device name; assuming {}",
+                ami_id, DEFAULT_ROOT_DEVICE
+            );
+            DEFAULT_ROOT_DEVICE.to_string()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_freshness() {
+        let now = Utc::now();
+        let mut cache = AmiCache::default();
+        cache.insert(
+            "us-east-1",
+            DLAMI_PATTERNS[0],
+            Some("ami-0123".to_string()),
+            now - chrono::Duration::hours(2),
+        );
+        cache.insert("us-east-1", DLAMI_PATTERNS[1], None, now);
+
+        let hit = cache.fresh("us-east-1", DLAMI_PATTERNS[0], 3 * 3600, now);
+        assert_eq!(hit.and_then(|e| e.ami_id.as_deref()), Some("ami-0123"));
+        assert!(cache
+            .fresh("us-east-1", DLAMI_PATTERNS[0], 3600, now)
+            .is_none());
+        assert!(cache
+            .fresh("us-west-2", DLAMI_PATTERNS[0], 3 * 3600, now)
+            .is_none());
+        // Misses are cached too, so the next pattern is tried without a lookup
+        let miss = cache.fresh("us-east-1", DLAMI_PATTERNS[1], 3600, now);
+        assert_eq!(miss.map(|e| e.ami_id.is_none()), Some(true));
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".runctl").join("ami_cache.json");
+        assert!(AmiCache::load(&path).entries.is_empty());
+
+        let now = Utc::now();
+        let mut cache = AmiCache::default();
+        cache.insert(
+            "eu-west-1",
+            DLAMI_PATTERNS[0],
+            Some("ami-9".to_string()),
+            now,
+        );
+        cache.save(&path).unwrap();
+
+        let loaded = AmiCache::load(&path);
+        assert_eq!(loaded.entries, cache.entries);
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(AmiCache::load(&path).entries.is_empty());
+    }
+}
@@ -15,6 +15,11 @@ use aws_sdk_s3::Client as S3Client;
 use std::path::PathBuf;
 use tracing::{info, warn};
 
+/// S3 key prefix searched for the checkpoint of an interrupted instance
+pub(crate) fn interruption_checkpoint_prefix(instance_id: &str) -> String {
+    format!("checkpoints/spot-interruptions/{}/", instance_id)
+}
+
 /// Resume training on a new instance after spot interruption
 ///
 /// This function:
@@ -61,7 +66,7 @@ pub async fn prepare_auto_resume(
         // Try to find checkpoint from S3 bucket if configured
         if let Some(bucket) = &aws_cfg.s3_bucket {
             let s3_client = aws_sdk_s3::Client::new(aws_config);
-            let prefix = format!("checkpoints/spot-interruptions/{}/", original_instance_id);
+            let prefix = interruption_checkpoint_prefix(original_instance_id);
 
             // List objects in S3 to find latest checkpoint
             match find_latest_checkpoint_in_s3(&s3_client, bucket, &prefix).await {
@@ -96,6 +101,7 @@ pub async fn prepare_auto_resume(
         use_spot: true, // Always use spot for auto-resume
         spot_max_price: aws_cfg.spot_max_price.clone(),
         no_fallback: false,
+        availability_zone: None,
         key_name: None,
         security_group: None,
         ami_id: None,
@@ -103,6 +109,13 @@ pub async fn prepare_auto_resume(
         data_volume_size: None,
         project_name: "runctl-auto-resume".to_string(),
         iam_instance_profile: aws_cfg.iam_instance_profile.clone(),
+        preset: None,
+        client_token: None,
+        // Each resume needs its own replacement instance
+        allow_duplicate: true,
+        refresh_ami: false,
+        ami_filter: None,
+        ssh_user: None,
     };
 
     // Create instance (this will print instance ID)
@@ -129,12 +142,22 @@ pub async fn prepare_auto_resume(
         output_s3: None,
         sync_code: true,
         include_patterns: vec![],
+        exclude_patterns: vec![],
+        max_file_size: None,
+        yes: true,
         project_name: "runctl-auto-resume".to_string(),
+        ssh_user: None,
         script_args,
+        resume: None,
+        env: vec![],
         wait: true,
         timeout_minutes: 120,
         docker: false,
         docker_image: None,
+        job: config.job.clone(),
+        agent: false,
+        launcher: None,
+        on_complete: None,
     };
 
     // Return the instance ID and training options instead of starting training
@@ -224,7 +247,7 @@ async fn find_latest_checkpoint_in_s3(
     }
 
     // Sort by modification time (newest first)
-    checkpoints.sort_by(|a, b| b.1.cmp(&a.1));
+    checkpoints.sort_by_key(|b| std::cmp::Reverse(b.1));
 
     Ok(checkpoints.first().map(|(path, _)| path.clone()))
 }
@@ -269,7 +292,7 @@ async fn find_newest_spot_instance(
     }
 
     // Sort by launch time (newest first)
-    instances.sort_by(|a, b| b.1.cmp(&a.1));
+    instances.sort_by_key(|b| std::cmp::Reverse(b.1));
 
     instances
         .first()
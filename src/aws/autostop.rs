@@ -0,0 +1,390 @@
+//! Idle auto-shutdown watchdog
+//!
+//! `runctl aws autostop <instance-id> --idle-minutes 30` installs a small
+//! script and a systemd timer on the instance over SSM. Once a minute the
+//! script checks GPU utilization (`nvidia-smi`) and training processes
+//! (`training.pid` files under `/home` and `python ... train`, `torchrun`,
+//! `deepspeed` processes). When none of them has been busy for the idle
+//! threshold it runs `shutdown -h now`; whether that stops or terminates the
+//! instance is the EC2 shutdown behavior, which is set to match `--action`.
+//!
+//! Idle time is tracked in `/run`, so it resets on every boot and a restarted
+//! instance always gets the full threshold before it is stopped again.
+//!
+//! Setting `autostop_idle_minutes` under `[aws]` installs the same watchdog
+//! from user data on every instance `aws create` launches.
+
+use crate::aws::adopt::describe;
+use crate::config::{AutostopAction, AwsConfig, Config};
+use crate::error::{Result, TrainctlError};
+use aws_sdk_ec2::types::{AttributeValue, ShutdownBehavior, Tag};
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_ssm::Client as SsmClient;
+use base64::Engine;
+use serde::Serialize;
+
+/// Where the watchdog script is installed on the instance
+const SCRIPT_PATH: &str = "/usr/local/bin/runctl-autostop";
+
+/// systemd unit name shared by the service and its timer
+const UNIT_NAME: &str = "runctl-autostop";
+
+/// Tag recording the installed threshold and action, e.g. `30m/stop`
+pub(crate) const AUTOSTOP_TAG: &str = "runctl:autostop";
+
+/// GPU utilization (percent) at or above which the instance counts as busy
+pub(crate) const DEFAULT_GPU_BUSY_PERCENT: u32 = 10;
+
+/// Printed by the install command so success can be told apart from SSM noise
+const INSTALLED_MARKER: &str = "RUNCTL_AUTOSTOP_INSTALLED";
+const REMOVED_MARKER: &str = "RUNCTL_AUTOSTOP_REMOVED";
+
+/// EC2 shutdown behavior that carries out `action`
+fn shutdown_behavior(action: AutostopAction) -> ShutdownBehavior {
+    match action {
+        AutostopAction::Stop => ShutdownBehavior::Stop,
+        AutostopAction::Terminate => ShutdownBehavior::Terminate,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct AutostopOptions {
+    pub instance_id: String,
+    pub idle_minutes: u64,
+    pub action: AutostopAction,
+    pub gpu_busy_percent: u32,
+    pub disable: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct AutostopResult {
+    success: bool,
+    instance_id: String,
+    enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    idle_minutes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    action: Option<AutostopAction>,
+}
+
+/// Render the watchdog script run by the systemd timer
+pub(crate) fn render_watchdog_script(idle_minutes: u64, gpu_busy_percent: u32) -> String {
+    format!(
+        r#"#!/bin/bash
+# runctl idle watchdog: shuts the instance down after {idle} idle minutes
+IDLE_MINUTES={idle}
+GPU_BUSY_PERCENT={gpu}
+STATE_DIR=/run/runctl-autostop
+STATE="$STATE_DIR/idle_since"
+mkdir -p "$STATE_DIR"
+
+busy=""
+if command -v nvidia-smi >/dev/null 2>&1; then
+    util=$(nvidia-smi --query-gpu=utilization.gpu --format=csv,noheader,nounits 2>/dev/null | sort -n | tail -1)
+    if [ -n "$util" ] && [ "$util" -ge "$GPU_BUSY_PERCENT" ]; then
+        busy="gpu $util%"
+    fi
+fi
+if [ -z "$busy" ]; then
+    for pidfile in /home/*/*/training.pid; do
+        [ -f "$pidfile" ] || continue
+        pid=$(cat "$pidfile" 2>/dev/null)
+        if [ -n "$pid" ] && kill -0 "$pid" 2>/dev/null; then
+            busy="$pidfile"
+            break
+        fi
+    done
+fi
+if [ -z "$busy" ] && pgrep -f "python.*train|python.*main.py|torchrun|deepspeed" >/dev/null 2>&1; then
+    busy="training process"
+fi
+
+now=$(date +%s)
+if [ -n "$busy" ]; then
+    rm -f "$STATE"
+    exit 0
+fi
+if [ ! -f "$STATE" ]; then
+    echo "$now" > "$STATE"
+    exit 0
+fi
+idle=$(( (now - $(cat "$STATE")) / 60 ))
+if [ "$idle" -ge "$IDLE_MINUTES" ]; then
+    logger -t {unit} "idle for $idle minutes, shutting down"
+    rm -f "$STATE"
+    shutdown -h now
+fi
+"#,
+        idle = idle_minutes,
+        gpu = gpu_busy_percent,
+        unit = UNIT_NAME,
+    )
+}
+
+/// Shell command that installs the script and enables its timer (run as root)
+pub(crate) fn install_command(idle_minutes: u64, gpu_busy_percent: u32) -> String {
+    let script = base64::engine::general_purpose::STANDARD
+        .encode(render_watchdog_script(idle_minutes, gpu_busy_percent));
+    format!(
+        r#"set -e
+echo '{script}' | base64 -d > {path}
+chmod 755 {path}
+cat > /etc/systemd/system/{unit}.service <<'EOF'
+[Unit]
+Description=runctl idle auto-shutdown check
+
+[Service]
+Type=oneshot
+ExecStart={path}
+EOF
+cat > /etc/systemd/system/{unit}.timer <<'EOF'
+[Unit]
+Description=Run the runctl idle auto-shutdown check every minute
+
+[Timer]
+OnBootSec=1min
+OnUnitActiveSec=1min
+
+[Install]
+WantedBy=timers.target
+EOF
+rm -f /run/{unit}/idle_since
+systemctl daemon-reload
+systemctl enable --now {unit}.timer
+echo {marker}
+"#,
+        script = script,
+        path = SCRIPT_PATH,
+        unit = UNIT_NAME,
+        marker = INSTALLED_MARKER,
+    )
+}
+
+/// Shell command that disables the timer and removes the watchdog
+pub(crate) fn remove_command() -> String {
+    format!(
+        r#"systemctl disable --now {unit}.timer 2>/dev/null || true
+rm -f /etc/systemd/system/{unit}.service /etc/systemd/system/{unit}.timer {path}
+rm -rf /run/{unit}
+systemctl daemon-reload
+echo {marker}
+"#,
+        unit = UNIT_NAME,
+        path = SCRIPT_PATH,
+        marker = REMOVED_MARKER,
+    )
+}
+
+/// Section appended to the bootstrap user data when a config default is set
+pub(crate) fn user_data_snippet(idle_minutes: u64, gpu_busy_percent: u32) -> String {
+    format!(
+        "\n# Idle auto-shutdown (aws.autostop_idle_minutes = {})\n(\n{}) || echo \"WARNING: idle watchdog install failed\"\n",
+        idle_minutes,
+        install_command(idle_minutes, gpu_busy_percent)
+    )
+}
+
+/// Idle threshold from `[aws]`, if auto-shutdown should be installed at launch
+pub(crate) fn configured_idle_minutes(aws_cfg: &AwsConfig) -> Option<u64> {
+    aws_cfg.autostop_idle_minutes.filter(|&m| m > 0)
+}
+
+fn tag_value(idle_minutes: u64, action: AutostopAction) -> String {
+    format!("{}m/{}", idle_minutes, action)
+}
+
+/// Make `shutdown -h` on the instance stop or terminate it
+pub(crate) async fn set_shutdown_behavior(
+    client: &Ec2Client,
+    instance_id: &str,
+    action: AutostopAction,
+) -> Result<()> {
+    client
+        .modify_instance_attribute()
+        .instance_id(instance_id)
+        .instance_initiated_shutdown_behavior(
+            AttributeValue::builder()
+                .value(shutdown_behavior(action).as_str())
+                .build(),
+        )
+        .send()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to set shutdown behavior: {}", e)))?;
+    Ok(())
+}
+
+/// Set the instance's shutdown behavior and record the watchdog in a tag
+pub(crate) async fn mark_instance(
+    client: &Ec2Client,
+    instance_id: &str,
+    idle_minutes: u64,
+    action: AutostopAction,
+) -> Result<()> {
+    set_shutdown_behavior(client, instance_id, action).await?;
+    client
+        .create_tags()
+        .resources(instance_id)
+        .tags(
+            Tag::builder()
+                .key(AUTOSTOP_TAG)
+                .value(tag_value(idle_minutes, action))
+                .build(),
+        )
+        .send()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to tag instance: {}", e)))?;
+    Ok(())
+}
+
+/// Install or remove the idle watchdog on a running instance
+pub(crate) async fn run_autostop(
+    options: AutostopOptions,
+    _config: &Config,
+    aws_config: &aws_config::SdkConfig,
+    output_format: &str,
+) -> Result<()> {
+    if !options.disable && options.idle_minutes == 0 {
+        return Err(TrainctlError::Validation {
+            field: "idle_minutes".to_string(),
+            reason: "must be at least 1 (use --disable to remove the watchdog)".to_string(),
+        });
+    }
+
+    let client = Ec2Client::new(aws_config);
+    let instance_id = options.instance_id.as_str();
+    let instance = describe(&client, instance_id).await?;
+
+    let state = instance
+        .state()
+        .and_then(|s| s.name())
+        .map(|s| s.as_str())
+        .unwrap_or("unknown");
+    if state != "running" {
+        return Err(TrainctlError::Validation {
+            field: "instance_id".to_string(),
+            reason: format!(
+                "instance {} is {}; autostop is installed over SSM and needs it running",
+                instance_id, state
+            ),
+        });
+    }
+    if instance.iam_instance_profile().is_none() {
+        return Err(TrainctlError::Validation {
+            field: "instance_id".to_string(),
+            reason: format!(
+                "instance {} has no IAM instance profile, so SSM is unavailable.\n\
+                 Set `autostop_idle_minutes` under [aws] to install the watchdog at launch instead.",
+                instance_id
+            ),
+        });
+    }
+
+    let ssm_client = SsmClient::new(aws_config);
+    let (command, marker) = if options.disable {
+        (remove_command(), REMOVED_MARKER)
+    } else {
+        (
+            install_command(options.idle_minutes, options.gpu_busy_percent),
+            INSTALLED_MARKER,
+        )
+    };
+    let output = crate::aws_utils::execute_ssm_command(&ssm_client, instance_id, &command).await?;
+    if !output.contains(marker) {
+        return Err(TrainctlError::Ssm(format!(
+            "autostop {} did not complete on {}: {}",
+            if options.disable {
+                "removal"
+            } else {
+                "install"
+            },
+            instance_id,
+            output.trim()
+        )));
+    }
+
+    if options.disable {
+        client
+            .delete_tags()
+            .resources(instance_id)
+            .tags(Tag::builder().key(AUTOSTOP_TAG).build())
+            .send()
+            .await
+            .map_err(|e| TrainctlError::Aws(format!("Failed to remove tag: {}", e)))?;
+    } else {
+        mark_instance(&client, instance_id, options.idle_minutes, options.action).await?;
+    }
+
+    if output_format == "json" {
+        let result = AutostopResult {
+            success: true,
+            instance_id: instance_id.to_string(),
+            enabled: !options.disable,
+            idle_minutes: (!options.disable).then_some(options.idle_minutes),
+            action: (!options.disable).then_some(options.action),
+        };
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else if options.disable {
+        println!("Removed idle watchdog from {}", instance_id);
+    } else {
+        println!(
+            "Installed idle watchdog on {}: {} after {} idle minutes (GPU < {}% and no training process)",
+            instance_id, options.action, options.idle_minutes, options.gpu_busy_percent
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watchdog_script_uses_thresholds() {
+        let script = render_watchdog_script(45, 15);
+        assert!(script.starts_with("#!/bin/bash"));
+        assert!(script.contains("IDLE_MINUTES=45"));
+        assert!(script.contains("GPU_BUSY_PERCENT=15"));
+        assert!(script.contains("training.pid"));
+        assert!(script.contains("shutdown -h now"));
+    }
+
+    #[test]
+    fn test_install_command_round_trips_script() {
+        let cmd = install_command(30, DEFAULT_GPU_BUSY_PERCENT);
+        let encoded = cmd
+            .lines()
+            .find_map(|l| l.strip_prefix("echo '"))
+            .and_then(|l| l.split('\'').next())
+            .unwrap();
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(decoded).unwrap(),
+            render_watchdog_script(30, DEFAULT_GPU_BUSY_PERCENT)
+        );
+        assert!(cmd.contains("systemctl enable --now runctl-autostop.timer"));
+        assert!(cmd.trim_end().ends_with(INSTALLED_MARKER));
+    }
+
+    #[test]
+    fn test_remove_command_disables_timer() {
+        let cmd = remove_command();
+        assert!(cmd.contains("systemctl disable --now runctl-autostop.timer"));
+        assert!(cmd.contains(SCRIPT_PATH));
+    }
+
+    #[test]
+    fn test_action_shutdown_behavior() {
+        assert_eq!(
+            shutdown_behavior(AutostopAction::Stop),
+            ShutdownBehavior::Stop
+        );
+        assert_eq!(
+            shutdown_behavior(AutostopAction::Terminate),
+            ShutdownBehavior::Terminate
+        );
+        assert_eq!(AutostopAction::default(), AutostopAction::Stop);
+        assert_eq!(tag_value(30, AutostopAction::Terminate), "30m/terminate");
+    }
+}
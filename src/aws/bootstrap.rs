@@ -0,0 +1,257 @@
+//! Bootstrap progress tracking
+//!
+//! The generated user-data script writes `RUNCTL_PHASE:<name>` markers to
+//! `/var/log/user-data.log` as it moves through instance setup. These helpers
+//! read the markers back over SSM so `runctl aws wait --verbose` can report
+//! which phase an instance is in instead of a binary ready/not-ready.
+//!
+//! Phases are ordered; the latest marker found in the log wins. Optional
+//! phases (e.g. data volume mount) may be skipped without affecting ordering.
+//!
+//! The script also tags the instance with `runctl:bootstrap` (`running`,
+//! `complete`, or `failed`) and, on failure, `runctl:bootstrap-error` with a
+//! short summary. Tags are readable without SSM, so `wait` and `status` can
+//! surface bootstrap failures on SSH-only instances too.
+
+use crate::error::Result;
+use aws_sdk_ssm::Client as SsmClient;
+
+/// Prefix written before each phase name in the user-data log
+pub(crate) const PHASE_MARKER: &str = "RUNCTL_PHASE:";
+
+/// Instance tag holding the bootstrap outcome
+pub const BOOTSTRAP_TAG: &str = "runctl:bootstrap";
+
+/// Instance tag holding the failure summary when bootstrap fails
+pub const BOOTSTRAP_ERROR_TAG: &str = "runctl:bootstrap-error";
+
+/// Bootstrap outcome as reported by the instance's own tags
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BootstrapTagStatus {
+    /// User-data script is still running
+    Running,
+    /// User-data script finished successfully
+    Complete,
+    /// User-data script failed, with the error summary it reported
+    Failed(String),
+}
+
+impl BootstrapTagStatus {
+    /// Short status name (matches the tag value)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BootstrapTagStatus::Running => "running",
+            BootstrapTagStatus::Complete => "complete",
+            BootstrapTagStatus::Failed(_) => "failed",
+        }
+    }
+
+    /// Error summary, if bootstrap failed
+    pub fn error(&self) -> Option<&str> {
+        match self {
+            BootstrapTagStatus::Failed(summary) => Some(summary),
+            _ => None,
+        }
+    }
+}
+
+/// Read the bootstrap outcome from instance tags
+///
+/// Returns `None` for instances created before tag signaling existed (or whose
+/// instance profile lacks `ec2:CreateTags`).
+pub fn bootstrap_status_from_tags(tags: &[(String, String)]) -> Option<BootstrapTagStatus> {
+    let value = tags
+        .iter()
+        .find(|(k, _)| k == BOOTSTRAP_TAG)
+        .map(|(_, v)| v.as_str())?;
+    match value {
+        "running" => Some(BootstrapTagStatus::Running),
+        "complete" => Some(BootstrapTagStatus::Complete),
+        "failed" => {
+            let summary = tags
+                .iter()
+                .find(|(k, _)| k == BOOTSTRAP_ERROR_TAG)
+                .map(|(_, v)| v.clone())
+                .filter(|v| !v.is_empty())
+                .unwrap_or_else(|| "no error summary reported".to_string());
+            Some(BootstrapTagStatus::Failed(summary))
+        }
+        _ => None,
+    }
+}
+
+/// Bootstrap phases reported by the user-data script, in execution order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BootstrapPhase {
+    /// No markers found yet (cloud-init has not started our script)
+    Pending,
+    /// User-data script started under cloud-init
+    CloudInit,
+    /// System packages installed
+    Packages,
+    /// uv installed
+    Uv,
+    /// Data volume formatted and mounted
+    DataVolume,
+    /// SSM agent confirmed running
+    AgentUp,
+    /// Setup finished
+    Complete,
+}
+
+impl BootstrapPhase {
+    /// Marker name written by the user-data script for this phase
+    pub fn marker(&self) -> &'static str {
+        match self {
+            BootstrapPhase::Pending => "pending",
+            BootstrapPhase::CloudInit => "cloud-init",
+            BootstrapPhase::Packages => "packages",
+            BootstrapPhase::Uv => "uv",
+            BootstrapPhase::DataVolume => "data-volume",
+            BootstrapPhase::AgentUp => "agent",
+            BootstrapPhase::Complete => "complete",
+        }
+    }
+
+    /// Parse a marker name back into a phase
+    pub fn from_marker(marker: &str) -> Option<Self> {
+        match marker.trim() {
+            "pending" => Some(BootstrapPhase::Pending),
+            "cloud-init" => Some(BootstrapPhase::CloudInit),
+            "packages" => Some(BootstrapPhase::Packages),
+            "uv" => Some(BootstrapPhase::Uv),
+            "data-volume" => Some(BootstrapPhase::DataVolume),
+            "agent" => Some(BootstrapPhase::AgentUp),
+            "complete" => Some(BootstrapPhase::Complete),
+            _ => None,
+        }
+    }
+
+    /// Human-readable description for progress output
+    pub fn description(&self) -> &'static str {
+        match self {
+            BootstrapPhase::Pending => "waiting for cloud-init",
+            BootstrapPhase::CloudInit => "cloud-init running user-data",
+            BootstrapPhase::Packages => "system packages installed",
+            BootstrapPhase::Uv => "uv installed",
+            BootstrapPhase::DataVolume => "data volume mounted",
+            BootstrapPhase::AgentUp => "SSM agent up",
+            BootstrapPhase::Complete => "bootstrap complete",
+        }
+    }
+}
+
+impl std::fmt::Display for BootstrapPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.marker())
+    }
+}
+
+/// Determine the latest bootstrap phase from user-data log contents
+///
+/// Unknown marker names are ignored so older runctl versions reading logs
+/// from newer instances degrade gracefully.
+pub fn parse_bootstrap_log(log: &str) -> BootstrapPhase {
+    log.lines()
+        .filter_map(|line| {
+            line.find(PHASE_MARKER)
+                .map(|idx| &line[idx + PHASE_MARKER.len()..])
+        })
+        .filter_map(BootstrapPhase::from_marker)
+        .max()
+        .unwrap_or(BootstrapPhase::Pending)
+}
+
+/// Read the current bootstrap phase from an instance via SSM
+pub(crate) async fn fetch_bootstrap_phase(
+    ssm_client: &SsmClient,
+    instance_id: &str,
+) -> Result<BootstrapPhase> {
+    let cmd = format!(
+        "grep -a '{}' /var/log/user-data.log 2>/dev/null || true",
+        PHASE_MARKER
+    );
+    let output = crate::aws_utils::execute_ssm_command(ssm_client, instance_id, &cmd).await?;
+    Ok(parse_bootstrap_log(&output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_bootstrap_status_from_tags() {
+        assert_eq!(bootstrap_status_from_tags(&tags(&[("Name", "x")])), None);
+        assert_eq!(
+            bootstrap_status_from_tags(&tags(&[(BOOTSTRAP_TAG, "running")])),
+            Some(BootstrapTagStatus::Running)
+        );
+        assert_eq!(
+            bootstrap_status_from_tags(&tags(&[(BOOTSTRAP_TAG, "complete")])),
+            Some(BootstrapTagStatus::Complete)
+        );
+    }
+
+    #[test]
+    fn test_bootstrap_failed_includes_summary() {
+        let status = bootstrap_status_from_tags(&tags(&[
+            (BOOTSTRAP_TAG, "failed"),
+            (BOOTSTRAP_ERROR_TAG, "line 42 exit 100: apt-get update -y"),
+        ]))
+        .unwrap();
+        assert_eq!(status.as_str(), "failed");
+        assert_eq!(status.error(), Some("line 42 exit 100: apt-get update -y"));
+
+        let no_summary = bootstrap_status_from_tags(&tags(&[(BOOTSTRAP_TAG, "failed")])).unwrap();
+        assert_eq!(no_summary.error(), Some("no error summary reported"));
+    }
+
+    #[test]
+    fn test_parse_empty_log_is_pending() {
+        assert_eq!(parse_bootstrap_log(""), BootstrapPhase::Pending);
+        assert_eq!(
+            parse_bootstrap_log("Starting instance setup...\n"),
+            BootstrapPhase::Pending
+        );
+    }
+
+    #[test]
+    fn test_parse_latest_phase_wins() {
+        let log = "RUNCTL_PHASE:cloud-init\nDetected OS: ubuntu\nRUNCTL_PHASE:packages\nRUNCTL_PHASE:uv\n";
+        assert_eq!(parse_bootstrap_log(log), BootstrapPhase::Uv);
+    }
+
+    #[test]
+    fn test_parse_skipped_optional_phase() {
+        let log = "RUNCTL_PHASE:cloud-init\nRUNCTL_PHASE:uv\nRUNCTL_PHASE:agent\n";
+        assert_eq!(parse_bootstrap_log(log), BootstrapPhase::AgentUp);
+    }
+
+    #[test]
+    fn test_parse_ignores_unknown_markers() {
+        let log = "RUNCTL_PHASE:packages\nRUNCTL_PHASE:something-new\n";
+        assert_eq!(parse_bootstrap_log(log), BootstrapPhase::Packages);
+    }
+
+    #[test]
+    fn test_marker_roundtrip() {
+        for phase in [
+            BootstrapPhase::Pending,
+            BootstrapPhase::CloudInit,
+            BootstrapPhase::Packages,
+            BootstrapPhase::Uv,
+            BootstrapPhase::DataVolume,
+            BootstrapPhase::AgentUp,
+            BootstrapPhase::Complete,
+        ] {
+            assert_eq!(BootstrapPhase::from_marker(phase.marker()), Some(phase));
+        }
+    }
+}
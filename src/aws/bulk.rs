@@ -0,0 +1,629 @@
+//! Bulk instance operations by selector
+//!
+//! `runctl aws bulk stop|start|terminate` act on every instance matching a
+//! selector (`--project`, `--user`, `--older-than`, `--type`) instead of a
+//! single ID, so cleaning up after a sweep is one command. Matches are shown
+//! in a preview table and confirmed before anything is changed.
+//!
+//! At least one selector is required; `--all` must be passed explicitly to
+//! target every runctl-visible instance in the region.
+
+use crate::aws::helpers::update_resource_status_in_tracker;
+use crate::config::Config;
+use crate::error::{Result, TrainctlError};
+use aws_sdk_ec2::types::{Filter, Instance};
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_ssm::Client as SsmClient;
+use chrono::{DateTime, Duration, Utc};
+use clap::{Args, Subcommand};
+use comfy_table::{Cell, Table};
+use serde::Serialize;
+
+#[derive(Subcommand, Clone)]
+pub enum BulkCommands {
+    /// Stop all running instances matching the selector
+    ///
+    /// Examples:
+    ///   runctl aws bulk stop --project llm --older-than 24h
+    ///   runctl aws bulk stop --type 'g4dn.*' --dry-run
+    Stop {
+        #[command(flatten)]
+        selector: InstanceSelector,
+
+        /// Skip graceful training shutdown
+        #[arg(long)]
+        force: bool,
+
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Show matching instances without stopping them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Start all stopped instances matching the selector
+    ///
+    /// Examples:
+    ///   runctl aws bulk start --project llm
+    Start {
+        #[command(flatten)]
+        selector: InstanceSelector,
+
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Show matching instances without starting them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Terminate all running or stopped instances matching the selector
+    ///
+    /// Instances tagged runctl:protected=true are skipped unless --force is set.
+    ///
+    /// Examples:
+    ///   runctl aws bulk terminate --project sweep-42 --older-than 2d
+    Terminate {
+        #[command(flatten)]
+        selector: InstanceSelector,
+
+        /// Include protected instances and skip graceful training shutdown
+        #[arg(long)]
+        force: bool,
+
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Show matching instances without terminating them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// Instance selector shared by all bulk commands
+///
+/// All given criteria must match (AND).
+#[derive(Args, Clone, Debug, Default)]
+pub struct InstanceSelector {
+    /// Match instances with this runctl:project tag
+    #[arg(long)]
+    pub project: Option<String>,
+
+    /// Match instances with this runctl:user tag
+    #[arg(long)]
+    pub user: Option<String>,
+
+    /// Match instances launched more than this long ago (e.g., 30m, 24h, 2d, 1w)
+    #[arg(long, value_name = "AGE", value_parser = crate::units::parse_duration)]
+    pub older_than: Option<std::time::Duration>,
+
+    /// Match instance types by glob pattern (e.g., 'g4dn.*', 'p3.?xlarge')
+    #[arg(long = "type", value_name = "PATTERN")]
+    pub instance_type: Option<String>,
+
+    /// Select every instance (required when no other selector is given)
+    #[arg(long)]
+    pub all: bool,
+
+    /// Also match instances runctl didn't launch (no runctl:created tag)
+    #[arg(long)]
+    pub include_unmanaged: bool,
+}
+
+impl InstanceSelector {
+    fn is_empty(&self) -> bool {
+        self.project.is_none()
+            && self.user.is_none()
+            && self.older_than.is_none()
+            && self.instance_type.is_none()
+    }
+
+    /// Check whether an instance matches every criterion in the selector
+    pub fn matches(&self, instance: &SelectedInstance) -> bool {
+        if let Some(project) = &self.project {
+            if instance.project.as_deref() != Some(project.as_str()) {
+                return false;
+            }
+        }
+        if let Some(user) = &self.user {
+            if instance.user.as_deref() != Some(user.as_str()) {
+                return false;
+            }
+        }
+        if let Some(pattern) = &self.instance_type {
+            if !glob_matches(pattern, &instance.instance_type) {
+                return false;
+            }
+        }
+        if let Some(min_age) = self.older_than {
+            let min_age = Duration::from_std(min_age).unwrap_or(Duration::MAX);
+            match instance.launch_time {
+                Some(launched) if Utc::now() - launched >= min_age => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+/// Bulk action to apply to the selected instances
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkAction {
+    Stop,
+    Start,
+    Terminate,
+}
+
+impl BulkAction {
+    /// EC2 states an instance must be in to be eligible for this action
+    fn eligible_states(&self) -> &'static [&'static str] {
+        match self {
+            BulkAction::Stop => &["running"],
+            BulkAction::Start => &["stopped"],
+            BulkAction::Terminate => &["running", "stopped"],
+        }
+    }
+
+    fn verb(&self) -> &'static str {
+        match self {
+            BulkAction::Stop => "stop",
+            BulkAction::Start => "start",
+            BulkAction::Terminate => "terminate",
+        }
+    }
+}
+
+/// Instance summary used for matching and the preview table
+#[derive(Debug, Clone, Serialize)]
+pub struct SelectedInstance {
+    pub instance_id: String,
+    pub name: Option<String>,
+    pub instance_type: String,
+    pub state: String,
+    pub project: Option<String>,
+    pub user: Option<String>,
+    pub launch_time: Option<DateTime<Utc>>,
+    pub protected: bool,
+    #[serde(skip)]
+    has_iam_profile: bool,
+}
+
+impl SelectedInstance {
+    fn from_ec2(instance: &Instance) -> Option<Self> {
+        let tag = |key: &str| {
+            instance
+                .tags()
+                .iter()
+                .find(|t| t.key() == Some(key))
+                .and_then(|t| t.value())
+                .map(|v| v.to_string())
+        };
+        Some(Self {
+            instance_id: instance.instance_id()?.to_string(),
+            name: tag("Name"),
+            instance_type: instance
+                .instance_type()
+                .map(|t| t.as_str().to_string())
+                .unwrap_or_else(|| "unknown".to_string()),
+            state: instance
+                .state()
+                .and_then(|s| s.name())
+                .map(|s| s.as_str().to_string())
+                .unwrap_or_else(|| "unknown".to_string()),
+            project: tag("runctl:project"),
+            user: tag("runctl:user"),
+            launch_time: instance
+                .launch_time()
+                .and_then(|t| DateTime::from_timestamp(t.secs(), 0)),
+            protected: tag("runctl:protected").as_deref() == Some("true"),
+            has_iam_profile: instance.iam_instance_profile().is_some(),
+        })
+    }
+
+    fn age(&self) -> String {
+        self.launch_time
+            .map(|lt| format_age(Utc::now() - lt))
+            .unwrap_or_else(|| "-".to_string())
+    }
+}
+
+#[derive(Serialize)]
+struct BulkFailure {
+    instance_id: String,
+    error: String,
+}
+
+#[derive(Serialize)]
+struct BulkResult {
+    success: bool,
+    action: &'static str,
+    dry_run: bool,
+    selected: Vec<SelectedInstance>,
+    skipped_protected: Vec<String>,
+    succeeded: Vec<String>,
+    failed: Vec<BulkFailure>,
+}
+
+pub async fn handle_command(
+    cmd: BulkCommands,
+    config: &Config,
+    aws_config: &aws_config::SdkConfig,
+    output_format: &str,
+) -> Result<()> {
+    let (action, selector, force, yes, dry_run) = match cmd {
+        BulkCommands::Stop {
+            selector,
+            force,
+            yes,
+            dry_run,
+        } => (BulkAction::Stop, selector, force, yes, dry_run),
+        BulkCommands::Start {
+            selector,
+            yes,
+            dry_run,
+        } => (BulkAction::Start, selector, false, yes, dry_run),
+        BulkCommands::Terminate {
+            selector,
+            force,
+            yes,
+            dry_run,
+        } => (BulkAction::Terminate, selector, force, yes, dry_run),
+    };
+    run_bulk(
+        action,
+        &selector,
+        force,
+        yes,
+        dry_run,
+        config,
+        aws_config,
+        output_format,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_bulk(
+    action: BulkAction,
+    selector: &InstanceSelector,
+    force: bool,
+    yes: bool,
+    dry_run: bool,
+    config: &Config,
+    aws_config: &aws_config::SdkConfig,
+    output_format: &str,
+) -> Result<()> {
+    if selector.is_empty() && !selector.all {
+        return Err(TrainctlError::Validation {
+            field: "selector".to_string(),
+            reason: "No selector given. Use --project, --user, --older-than, --type, or --all to target every instance".to_string(),
+        });
+    }
+    if let Some(project) = &selector.project {
+        crate::validation::validate_project_name(project)?;
+    }
+
+    if output_format == "json" && !yes && !dry_run && !crate::prompt::is_non_interactive() {
+        return Err(TrainctlError::Validation {
+            field: "yes".to_string(),
+            reason: "Bulk operations with --output json require --yes or --dry-run".to_string(),
+        });
+    }
+
+    let client = Ec2Client::new(aws_config);
+    let mut request = client.describe_instances();
+    // Only instances runctl launched (or adopted) unless asked otherwise
+    if !selector.include_unmanaged {
+        request = request.filters(
+            Filter::builder()
+                .name("tag-key")
+                .values("runctl:created")
+                .build(),
+        );
+    }
+    let response = request
+        .filters(
+            Filter::builder()
+                .name("instance-state-name")
+                .set_values(Some(
+                    action
+                        .eligible_states()
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect(),
+                ))
+                .build(),
+        )
+        .send()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to describe instances: {}", e)))?;
+
+    let mut selected: Vec<SelectedInstance> = response
+        .reservations()
+        .iter()
+        .flat_map(|r| r.instances())
+        .filter_map(SelectedInstance::from_ec2)
+        .filter(|inst| selector.matches(inst))
+        .collect();
+    selected.sort_by(|a, b| a.launch_time.cmp(&b.launch_time));
+
+    // Protected instances are never terminated in bulk without --force
+    let mut skipped_protected = Vec::new();
+    if action == BulkAction::Terminate && !force {
+        selected.retain(|inst| {
+            if inst.protected {
+                skipped_protected.push(inst.instance_id.clone());
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    if output_format != "json" {
+        if selected.is_empty() {
+            println!("No instances match the selector");
+        } else {
+            print_preview(&selected);
+            println!(
+                "{} instance(s) selected to {}",
+                selected.len(),
+                action.verb()
+            );
+        }
+        if !skipped_protected.is_empty() {
+            println!(
+                "Skipped {} protected instance(s): {} (use --force to include)",
+                skipped_protected.len(),
+                skipped_protected.join(", ")
+            );
+        }
+    }
+
+    if selected.is_empty() || dry_run {
+        if output_format == "json" {
+            let result = BulkResult {
+                success: true,
+                action: action.verb(),
+                dry_run,
+                selected,
+                skipped_protected,
+                succeeded: vec![],
+                failed: vec![],
+            };
+            println!("{}", serde_json::to_string_pretty(&result)?);
+        } else if dry_run && !selected.is_empty() {
+            println!("DRY RUN: no instances were changed");
+        }
+        return Ok(());
+    }
+
+    if !yes
+        && !crate::prompt::confirm(&format!(
+            "{} {} instance(s)?",
+            capitalize(action.verb()),
+            selected.len()
+        ))?
+    {
+        println!("Cancelled");
+        return Ok(());
+    }
+
+    let ssm_client = SsmClient::new(aws_config);
+    let mut succeeded = Vec::new();
+    let mut failed = Vec::new();
+
+    for inst in &selected {
+        let id = inst.instance_id.as_str();
+
+        // Best-effort graceful training shutdown (needs SSM)
+        if action != BulkAction::Start && !force && inst.has_iam_profile {
+            crate::aws_utils::stop_training_gracefully(&ssm_client, id, config, action.verb())
+                .await;
+        }
+
+        let outcome = match action {
+            BulkAction::Stop => client
+                .stop_instances()
+                .instance_ids(id)
+                .send()
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            BulkAction::Start => client
+                .start_instances()
+                .instance_ids(id)
+                .send()
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            BulkAction::Terminate => client
+                .terminate_instances()
+                .instance_ids(id)
+                .send()
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+        };
+
+        match outcome {
+            Ok(()) => {
+                update_resource_status_in_tracker(id, &client, config).await;
+                if output_format != "json" {
+                    println!("  {} requested: {}", capitalize(action.verb()), id);
+                }
+                succeeded.push(id.to_string());
+            }
+            Err(e) => {
+                if output_format != "json" {
+                    eprintln!("  ERROR: Failed to {} {}: {}", action.verb(), id, e);
+                }
+                failed.push(BulkFailure {
+                    instance_id: id.to_string(),
+                    error: e,
+                });
+            }
+        }
+    }
+
+    let failed_count = failed.len();
+    if output_format == "json" {
+        let result = BulkResult {
+            success: failed.is_empty(),
+            action: action.verb(),
+            dry_run: false,
+            selected,
+            skipped_protected,
+            succeeded,
+            failed,
+        };
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        println!();
+        println!(
+            "{}: {} succeeded, {} failed",
+            capitalize(action.verb()),
+            succeeded.len(),
+            failed_count
+        );
+    }
+
+    if failed_count > 0 {
+        return Err(TrainctlError::CloudProvider {
+            provider: "aws".to_string(),
+            message: format!("Failed to {} {} instance(s)", action.verb(), failed_count),
+            source: None,
+        });
+    }
+    Ok(())
+}
+
+fn print_preview(instances: &[SelectedInstance]) {
+    let mut table = Table::new();
+    table.set_header(vec![
+        "Instance ID",
+        "Name",
+        "Type",
+        "State",
+        "Project",
+        "User",
+        "Age",
+    ]);
+    for inst in instances {
+        table.add_row(vec![
+            Cell::new(&inst.instance_id),
+            Cell::new(inst.name.as_deref().unwrap_or("-")),
+            Cell::new(&inst.instance_type),
+            Cell::new(&inst.state),
+            Cell::new(inst.project.as_deref().unwrap_or("-")),
+            Cell::new(inst.user.as_deref().unwrap_or("-")),
+            Cell::new(inst.age()),
+        ]);
+    }
+    println!("{}", table);
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn format_age(age: Duration) -> String {
+    let minutes = age.num_minutes().max(0);
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else if minutes < 60 * 24 {
+        format!("{}h{}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}d{}h", minutes / (60 * 24), (minutes / 60) % 24)
+    }
+}
+
+/// Match `text` against a shell-style glob supporting `*` and `?`
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ti));
+            pi += 1;
+        } else if let Some((star_pi, star_ti)) = backtrack {
+            pi = star_pi + 1;
+            ti = star_ti + 1;
+            backtrack = Some((star_pi, star_ti + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(instance_type: &str, project: Option<&str>, age_hours: i64) -> SelectedInstance {
+        SelectedInstance {
+            instance_id: "i-0123456789abcdef0".to_string(),
+            name: None,
+            instance_type: instance_type.to_string(),
+            state: "running".to_string(),
+            project: project.map(|p| p.to_string()),
+            user: Some("alice".to_string()),
+            launch_time: Some(Utc::now() - Duration::hours(age_hours)),
+            protected: false,
+            has_iam_profile: false,
+        }
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("g4dn.*", "g4dn.xlarge"));
+        assert!(glob_matches("*", "t3.micro"));
+        assert!(glob_matches("p3.?xlarge", "p3.8xlarge"));
+        assert!(glob_matches("*.xlarge", "g5.xlarge"));
+        assert!(!glob_matches("g4dn.*", "g5.xlarge"));
+        assert!(!glob_matches("p3.?xlarge", "p3.16xlarge"));
+        assert!(glob_matches("t3.micro", "t3.micro"));
+    }
+
+    #[test]
+    fn test_selector_matches_all_criteria() {
+        let selector = InstanceSelector {
+            project: Some("llm".to_string()),
+            instance_type: Some("g4dn.*".to_string()),
+            older_than: Some(std::time::Duration::from_secs(24 * 3600)),
+            ..Default::default()
+        };
+
+        assert!(selector.matches(&instance("g4dn.xlarge", Some("llm"), 48)));
+        assert!(!selector.matches(&instance("g4dn.xlarge", Some("llm"), 2)));
+        assert!(!selector.matches(&instance("g5.xlarge", Some("llm"), 48)));
+        assert!(!selector.matches(&instance("g4dn.xlarge", Some("other"), 48)));
+        assert!(!selector.matches(&instance("g4dn.xlarge", None, 48)));
+    }
+
+    #[test]
+    fn test_empty_selector_requires_all() {
+        assert!(InstanceSelector::default().is_empty());
+        let selector = InstanceSelector {
+            user: Some("alice".to_string()),
+            ..Default::default()
+        };
+        assert!(!selector.is_empty());
+        assert!(selector.matches(&instance("t3.micro", None, 1)));
+    }
+}
@@ -0,0 +1,864 @@
+//! Multi-node distributed training (`aws train-multi`)
+//!
+//! Launches N instances into one cluster placement group with a single
+//! RunInstances call, so either every node gets capacity or none does, then:
+//!
+//! 1. ranks the nodes by launch index and tags them (`runctl:cluster`,
+//!    `runctl:node-rank`)
+//! 2. gives node 0 an SSH key that every node accepts, the nodes' host keys
+//!    (read over SSM) and a DeepSpeed/MPI hostfile at `~/.runctl/hostfile`,
+//!    so tools that fan out from node 0 work without prompts
+//! 3. syncs the code to every node and starts the launcher on each with the
+//!    rendezvous settings: `MASTER_ADDR` (node 0's private IP),
+//!    `MASTER_PORT`, `NNODES` and `NODE_RANK`, also exported to the script
+//! 4. with `--wait`, polls every node, prints each one's latest log line and
+//!    fails as soon as a node exits non-zero
+//!
+//! `--efa` attaches an Elastic Fabric Adapter instead of a plain network
+//! interface and sets `FI_PROVIDER=efa` for NCCL. EFA needs a supported
+//! instance type (p4d, p5, ...) and a security group that allows all traffic
+//! from itself.
+//!
+//! Every step after the launch runs over SSM, so an instance profile is
+//! required.
+
+use crate::aws::ami::{find_deep_learning_ami, root_device_name};
+use crate::aws::helpers::get_user_id;
+use crate::aws::ssh_user::{resolve_ssh_user, user_for_ami, SSH_USER_TAG};
+use crate::aws::training::train_on_instance;
+use crate::aws::types::{TrainInstanceOptions, TrainMultiOptions};
+use crate::aws::user_data::render_user_data;
+use crate::aws_utils::execute_ssm_command;
+use crate::config::Config;
+use crate::error::{ConfigError, JobOutcome, Result, TrainctlError};
+use crate::host_keys::parse_public_keys;
+use crate::utils::shell_quote;
+use aws_sdk_ec2::types::{
+    BlockDeviceMapping, EbsBlockDevice, IamInstanceProfileSpecification,
+    InstanceNetworkInterfaceSpecification, InstanceType as Ec2InstanceType, Placement,
+    PlacementStrategy, ResourceType, Tag, TagSpecification, VolumeType,
+};
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_ssm::Client as SsmClient;
+use base64::Engine;
+use serde::Serialize;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Cluster (placement group) name, on every node
+pub const CLUSTER_TAG: &str = "runctl:cluster";
+
+/// Node rank within the cluster (0 is the rendezvous master)
+pub const NODE_RANK_TAG: &str = "runctl:node-rank";
+
+/// Default rendezvous port
+pub const DEFAULT_MASTER_PORT: u16 = 29500;
+
+/// Most nodes one `train-multi` launches
+const MAX_NODES: u32 = 64;
+
+/// Seconds between node polls with `--wait`
+const POLL_INTERVAL_SECS: u64 = 30;
+
+/// Key node 0 uses to reach the other nodes, under the login user's `~/.ssh`
+const CLUSTER_KEY: &str = "runctl_cluster";
+
+/// Program that starts one process per GPU on each node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Launcher {
+    Torchrun,
+    Deepspeed,
+}
+
+impl FromStr for Launcher {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "torchrun" => Ok(Self::Torchrun),
+            "deepspeed" => Ok(Self::Deepspeed),
+            other => Err(format!(
+                "unknown launcher '{}' (expected torchrun or deepspeed)",
+                other
+            )),
+        }
+    }
+}
+
+/// Where the nodes meet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rendezvous {
+    pub nodes: u32,
+    pub master_addr: String,
+    pub master_port: u16,
+}
+
+impl Launcher {
+    /// Command that replaces `python3` on node `rank`
+    pub fn command(
+        &self,
+        rendezvous: &Rendezvous,
+        rank: u32,
+        gpus_per_node: Option<u32>,
+    ) -> String {
+        match self {
+            // `python3 -m` avoids depending on the torchrun entry point being on PATH
+            Self::Torchrun => format!(
+                "python3 -m torch.distributed.run --nnodes={} --node_rank={} --nproc_per_node={} \
+                 --master_addr={} --master_port={}",
+                rendezvous.nodes,
+                rank,
+                gpus_per_node
+                    .map(|g| g.to_string())
+                    .unwrap_or_else(|| "gpu".to_string()),
+                rendezvous.master_addr,
+                rendezvous.master_port
+            ),
+            // --no_ssh: each node starts its own processes, like torchrun
+            Self::Deepspeed => {
+                let mut command = format!(
+                    "deepspeed --no_ssh --num_nodes={} --node_rank={} --master_addr={} --master_port={}",
+                    rendezvous.nodes, rank, rendezvous.master_addr, rendezvous.master_port
+                );
+                if let Some(gpus) = gpus_per_node {
+                    command.push_str(&format!(" --num_gpus={}", gpus));
+                }
+                command
+            }
+        }
+    }
+}
+
+/// Environment exported to the training script on node `rank`
+pub fn distributed_env(rendezvous: &Rendezvous, rank: u32, efa: bool) -> Vec<(String, String)> {
+    let mut env = vec![
+        ("MASTER_ADDR".to_string(), rendezvous.master_addr.clone()),
+        (
+            "MASTER_PORT".to_string(),
+            rendezvous.master_port.to_string(),
+        ),
+        ("NNODES".to_string(), rendezvous.nodes.to_string()),
+        ("NODE_RANK".to_string(), rank.to_string()),
+    ];
+    if efa {
+        env.push(("FI_PROVIDER".to_string(), "efa".to_string()));
+        env.push(("FI_EFA_USE_DEVICE_RDMA".to_string(), "1".to_string()));
+    }
+    env
+}
+
+/// One node of a launched cluster
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClusterNode {
+    pub rank: u32,
+    pub instance_id: String,
+    pub private_ip: String,
+    /// GPUs found by `nvidia-smi` (0 on CPU instances)
+    pub gpus: u32,
+    /// Host keys as `type base64` pairs, for node 0's known_hosts
+    #[serde(skip)]
+    pub host_keys: Vec<(String, String)>,
+}
+
+/// Placement group name: `runctl-<project>-<8 hex chars>`
+fn cluster_name(project: &str) -> String {
+    let project: String = project
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .take(40)
+        .collect();
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    format!("runctl-{}-{}", project.trim_matches('-'), &id[..8])
+}
+
+/// `ip slots=N` lines for DeepSpeed and MPI
+fn hostfile(nodes: &[ClusterNode], gpus_per_node: Option<u32>) -> String {
+    nodes
+        .iter()
+        .map(|node| {
+            let slots = gpus_per_node.unwrap_or(node.gpus).max(1);
+            format!("{} slots={}\n", node.private_ip, slots)
+        })
+        .collect()
+}
+
+/// known_hosts lines for every node, keyed by private IP
+fn known_hosts(nodes: &[ClusterNode]) -> String {
+    nodes
+        .iter()
+        .flat_map(|node| {
+            node.host_keys
+                .iter()
+                .map(move |(key_type, key)| format!("{} {} {}\n", node.private_ip, key_type, key))
+        })
+        .collect()
+}
+
+/// ssh_config block that sends node 0's connections to the peers through the cluster key
+fn ssh_config(nodes: &[ClusterNode]) -> String {
+    let hosts: Vec<&str> = nodes.iter().map(|n| n.private_ip.as_str()).collect();
+    format!(
+        "Host {}\n    IdentityFile ~/.ssh/{key}\n    UserKnownHostsFile ~/.ssh/{key}_known_hosts\n    StrictHostKeyChecking yes\n",
+        hosts.join(" "),
+        key = CLUSTER_KEY
+    )
+}
+
+/// Run `script` as `user` in a login-like shell (SSM commands run as root)
+fn as_user(user: &str, script: &str) -> String {
+    format!(
+        "sudo -u {} -H bash -c {}",
+        shell_quote(user),
+        shell_quote(script)
+    )
+}
+
+/// Exit code and latest log line of one node, from the poll command's output
+fn parse_node_poll(output: &str) -> (Option<i32>, Option<String>) {
+    let mut exit_code = None;
+    let mut last_line = None;
+    for line in output.lines() {
+        if let Some(code) = line.strip_prefix("EXIT:") {
+            exit_code = code.trim().parse().ok();
+        } else if !line.trim().is_empty() {
+            last_line = Some(line.to_string());
+        }
+    }
+    (exit_code, last_line)
+}
+
+async fn create_placement_group(client: &Ec2Client, name: &str) -> Result<()> {
+    client
+        .create_placement_group()
+        .group_name(name)
+        .strategy(PlacementStrategy::Cluster)
+        .tag_specifications(
+            TagSpecification::builder()
+                .resource_type(ResourceType::PlacementGroup)
+                .tags(Tag::builder().key(CLUSTER_TAG).value(name).build())
+                .tags(Tag::builder().key("CreatedBy").value("runctl").build())
+                .build(),
+        )
+        .send()
+        .await
+        .map_err(|e| {
+            TrainctlError::Aws(format!("Failed to create placement group {}: {}", name, e))
+        })?;
+    Ok(())
+}
+
+async fn delete_placement_group(client: &Ec2Client, name: &str) {
+    if let Err(e) = client
+        .delete_placement_group()
+        .group_name(name)
+        .send()
+        .await
+    {
+        warn!("Failed to delete placement group {}: {}", name, e);
+    }
+}
+
+/// Launch every node in one request; returns instance IDs in rank order
+async fn launch_nodes(
+    client: &Ec2Client,
+    options: &TrainMultiOptions,
+    config: &Config,
+    cluster: &str,
+    ami_id: &str,
+    iam_instance_profile: &str,
+) -> Result<Vec<String>> {
+    let user_data = render_user_data(&options.project_name, false, &config.bootstrap)?;
+    let root_size = options.root_volume_size.unwrap_or(100);
+
+    let mut tags = TagSpecification::builder()
+        .resource_type(ResourceType::Instance)
+        .tags(Tag::builder().key(CLUSTER_TAG).value(cluster).build())
+        .tags(
+            Tag::builder()
+                .key("runctl:project")
+                .value(&options.project_name)
+                .build(),
+        )
+        .tags(
+            Tag::builder()
+                .key("runctl:user")
+                .value(get_user_id(config))
+                .build(),
+        )
+        .tags(
+            Tag::builder()
+                .key("runctl:created")
+                .value(
+                    chrono::Utc::now()
+                        .format("%Y-%m-%d %H:%M:%S UTC")
+                        .to_string(),
+                )
+                .build(),
+        )
+        .tags(Tag::builder().key("CreatedBy").value("runctl").build());
+    match user_for_ami(client, ami_id).await {
+        Ok(Some(user)) => tags = tags.tags(Tag::builder().key(SSH_USER_TAG).value(user).build()),
+        Ok(None) => {}
+        Err(e) => warn!("{}", e),
+    }
+
+    let mut request = client
+        .run_instances()
+        .image_id(ami_id)
+        .instance_type(Ec2InstanceType::from(options.instance_type.as_str()))
+        .min_count(options.nodes as i32)
+        .max_count(options.nodes as i32)
+        .user_data(base64::engine::general_purpose::STANDARD.encode(&user_data))
+        .placement(Placement::builder().group_name(cluster).build())
+        .iam_instance_profile(
+            IamInstanceProfileSpecification::builder()
+                .name(iam_instance_profile)
+                .build(),
+        )
+        .block_device_mappings(
+            BlockDeviceMapping::builder()
+                .device_name(root_device_name(client, ami_id).await?)
+                .ebs(
+                    EbsBlockDevice::builder()
+                        .volume_size(root_size)
+                        .delete_on_termination(true)
+                        .volume_type(VolumeType::Gp3)
+                        .build(),
+                )
+                .build(),
+        )
+        .tag_specifications(tags.build());
+    if let Some(key) = &options.key_name {
+        request = request.key_name(key);
+    }
+    if options.efa {
+        // Security groups move onto the interface when one is specified
+        let mut interface = InstanceNetworkInterfaceSpecification::builder()
+            .device_index(0)
+            .interface_type("efa")
+            .delete_on_termination(true);
+        if let Some(sg) = &options.security_group {
+            interface = interface.groups(sg);
+        }
+        request = request.network_interfaces(interface.build());
+    } else if let Some(sg) = &options.security_group {
+        request = request.security_group_ids(sg);
+    }
+
+    let response = request.send().await.map_err(|e| {
+        let message = format!("{}", e);
+        let hint = if message.contains("InsufficientInstanceCapacity") {
+            "\n\nA cluster placement group needs all nodes in one availability zone. \
+             Retry later, with fewer --nodes, or in another region."
+        } else {
+            ""
+        };
+        TrainctlError::Aws(format!(
+            "Failed to launch {} x {}: {}{}",
+            options.nodes, options.instance_type, message, hint
+        ))
+    })?;
+
+    let mut launched: Vec<(i32, String)> = response
+        .instances()
+        .iter()
+        .filter_map(|i| {
+            Some((
+                i.ami_launch_index().unwrap_or(0),
+                i.instance_id()?.to_string(),
+            ))
+        })
+        .collect();
+    launched.sort();
+    Ok(launched.into_iter().map(|(_, id)| id).collect())
+}
+
+/// Private IPs, host keys and GPU counts of the launched nodes
+async fn inspect_nodes(
+    client: &Ec2Client,
+    ssm: &SsmClient,
+    instance_ids: &[String],
+) -> Result<Vec<ClusterNode>> {
+    let response = client
+        .describe_instances()
+        .set_instance_ids(Some(instance_ids.to_vec()))
+        .send()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to describe cluster nodes: {}", e)))?;
+
+    let mut nodes = Vec::new();
+    for (rank, instance_id) in instance_ids.iter().enumerate() {
+        let private_ip = crate::aws::find_instance_in_response(&response, instance_id)
+            .and_then(|i| i.private_ip_address())
+            .ok_or_else(|| TrainctlError::Aws(format!("Node {} has no private IP", instance_id)))?
+            .to_string();
+        let output = execute_ssm_command(
+            ssm,
+            instance_id,
+            "cat /etc/ssh/ssh_host_*_key.pub 2>/dev/null; \
+             echo GPUS:$(nvidia-smi -L 2>/dev/null | wc -l)",
+        )
+        .await?;
+        let gpus = output
+            .lines()
+            .find_map(|l| l.strip_prefix("GPUS:"))
+            .and_then(|n| n.trim().parse().ok())
+            .unwrap_or(0);
+        nodes.push(ClusterNode {
+            rank: rank as u32,
+            instance_id: instance_id.clone(),
+            private_ip,
+            gpus,
+            host_keys: parse_public_keys(&output),
+        });
+    }
+    Ok(nodes)
+}
+
+/// Passwordless SSH from node 0 to every node, plus the hostfile
+async fn setup_node_ssh(
+    ssm: &SsmClient,
+    nodes: &[ClusterNode],
+    user: &str,
+    gpus_per_node: Option<u32>,
+) -> Result<()> {
+    let master = &nodes[0];
+    let keygen = format!(
+        "mkdir -p ~/.ssh && chmod 700 ~/.ssh && \
+         {{ [ -f ~/.ssh/{key} ] || ssh-keygen -q -t ed25519 -N '' -f ~/.ssh/{key} -C runctl-cluster; }} && \
+         cat ~/.ssh/{key}.pub",
+        key = CLUSTER_KEY
+    );
+    let output = execute_ssm_command(ssm, &master.instance_id, &as_user(user, &keygen)).await?;
+    let (key_type, key) = parse_public_keys(&output)
+        .into_iter()
+        .next()
+        .ok_or_else(|| {
+            TrainctlError::Ssm(format!(
+                "Failed to create the cluster SSH key on {}: {}",
+                master.instance_id,
+                output.trim()
+            ))
+        })?;
+    let public_key = format!("{} {} runctl-cluster", key_type, key);
+
+    let authorize = format!(
+        "mkdir -p ~/.ssh && chmod 700 ~/.ssh && touch ~/.ssh/authorized_keys && \
+         chmod 600 ~/.ssh/authorized_keys && \
+         {{ grep -qxF {k} ~/.ssh/authorized_keys || echo {k} >> ~/.ssh/authorized_keys; }}",
+        k = shell_quote(&public_key)
+    );
+    for node in nodes {
+        execute_ssm_command(ssm, &node.instance_id, &as_user(user, &authorize)).await?;
+    }
+
+    let files = format!(
+        "printf '%s' {known_hosts} > ~/.ssh/{key}_known_hosts && \
+         {{ grep -q {key}_known_hosts ~/.ssh/config 2>/dev/null || printf '%s' {config} >> ~/.ssh/config; }} && \
+         chmod 600 ~/.ssh/config && mkdir -p ~/.runctl && printf '%s' {hostfile} > ~/.runctl/hostfile",
+        known_hosts = shell_quote(&known_hosts(nodes)),
+        config = shell_quote(&ssh_config(nodes)),
+        hostfile = shell_quote(&hostfile(nodes, gpus_per_node)),
+        key = CLUSTER_KEY
+    );
+    execute_ssm_command(ssm, &master.instance_id, &as_user(user, &files)).await?;
+    Ok(())
+}
+
+/// Poll every node until all exit 0, one fails, or the timeout passes
+async fn wait_for_cluster(
+    ssm: &SsmClient,
+    cluster: &str,
+    nodes: &[ClusterNode],
+    project_dir: &str,
+    timeout_minutes: u64,
+    output_format: &str,
+) -> Result<()> {
+    let started = Instant::now();
+    let timeout = Duration::from_secs(timeout_minutes * 60);
+    let command = format!(
+        "cd {dir} 2>/dev/null || exit 0; \
+         [ -f training_exit_code.txt ] && echo EXIT:$(cat training_exit_code.txt); \
+         tail -n 1 training.log 2>/dev/null",
+        dir = shell_quote(project_dir)
+    );
+    let mut finished = vec![false; nodes.len()];
+    let mut last_lines: Vec<Option<String>> = vec![None; nodes.len()];
+
+    loop {
+        for (i, node) in nodes.iter().enumerate() {
+            if finished[i] {
+                continue;
+            }
+            let output = match execute_ssm_command(ssm, &node.instance_id, &command).await {
+                Ok(output) => output,
+                Err(e) => {
+                    warn!(
+                        "Failed to poll node {} ({}): {}",
+                        node.rank, node.instance_id, e
+                    );
+                    continue;
+                }
+            };
+            let (exit_code, last_line) = parse_node_poll(&output);
+            if output_format != "json" && last_line.is_some() && last_line != last_lines[i] {
+                println!(
+                    "[node {}] {}",
+                    node.rank,
+                    last_line.as_deref().unwrap_or_default()
+                );
+            }
+            last_lines[i] = last_line;
+            match exit_code {
+                Some(0) => finished[i] = true,
+                Some(code) => {
+                    return Err(TrainctlError::Job {
+                        job_id: cluster.to_string(),
+                        outcome: JobOutcome::Failed,
+                        message: format!(
+                            "Node {} ({}) exited with code {}; the other nodes may still be running. \
+                             Check: runctl aws monitor {}",
+                            node.rank, node.instance_id, code, node.instance_id
+                        ),
+                    });
+                }
+                None => {}
+            }
+        }
+
+        if finished.iter().all(|f| *f) {
+            return Ok(());
+        }
+        if timeout_minutes > 0 && started.elapsed() >= timeout {
+            return Err(TrainctlError::Job {
+                job_id: cluster.to_string(),
+                outcome: JobOutcome::TimedOut,
+                message: format!(
+                    "{} of {} nodes finished within {} minutes; training continues on the rest",
+                    finished.iter().filter(|f| **f).count(),
+                    nodes.len(),
+                    timeout_minutes
+                ),
+            });
+        }
+        tokio::time::sleep(Duration::from_secs(POLL_INTERVAL_SECS)).await;
+    }
+}
+
+#[derive(Serialize)]
+struct ClusterSummary<'a> {
+    success: bool,
+    cluster: &'a str,
+    master_addr: &'a str,
+    master_port: u16,
+    nodes: &'a [ClusterNode],
+}
+
+/// Launch a cluster and start a distributed run on it
+pub async fn train_multi(
+    options: TrainMultiOptions,
+    config: &Config,
+    aws_config: &aws_config::SdkConfig,
+    output_format: &str,
+) -> Result<()> {
+    if options.nodes < 2 || options.nodes > MAX_NODES {
+        return Err(TrainctlError::Validation {
+            field: "nodes".to_string(),
+            reason: format!(
+                "must be between 2 and {} (use `aws train` for a single node)",
+                MAX_NODES
+            ),
+        });
+    }
+    let aws_cfg = config
+        .aws
+        .as_ref()
+        .ok_or_else(|| TrainctlError::Config(ConfigError::MissingField("aws".to_string())))?;
+    let iam_instance_profile = options
+        .iam_instance_profile
+        .clone()
+        .or_else(|| aws_cfg.iam_instance_profile.clone())
+        .ok_or_else(|| TrainctlError::Validation {
+            field: "iam_instance_profile".to_string(),
+            reason: "train-multi sets up and syncs the nodes over SSM; pass \
+                     --iam-instance-profile or set [aws] iam_instance_profile"
+                .to_string(),
+        })?;
+    if options.efa && options.security_group.is_none() {
+        return Err(TrainctlError::Validation {
+            field: "security_group".to_string(),
+            reason: "--efa needs --security-group with a rule allowing all traffic from the group itself"
+                .to_string(),
+        });
+    }
+
+    let client = Ec2Client::new(aws_config);
+    let ssm = SsmClient::new(aws_config);
+    let text = output_format != "json";
+
+    let ami_id = match &options.ami_id {
+        Some(ami) => ami.clone(),
+        None => {
+            find_deep_learning_ami(
+                &client,
+                &aws_cfg.region,
+                None,
+                aws_cfg.ami_cache_ttl_secs,
+                false,
+            )
+            .await?
+        }
+    };
+
+    let cluster = cluster_name(&options.project_name);
+    if text {
+        println!(
+            "Launching {} x {} in placement group {}...",
+            options.nodes, options.instance_type, cluster
+        );
+    }
+    create_placement_group(&client, &cluster).await?;
+    let instance_ids = match launch_nodes(
+        &client,
+        &options,
+        config,
+        &cluster,
+        &ami_id,
+        &iam_instance_profile,
+    )
+    .await
+    {
+        Ok(ids) => ids,
+        Err(e) => {
+            delete_placement_group(&client, &cluster).await;
+            return Err(e);
+        }
+    };
+
+    for (rank, instance_id) in instance_ids.iter().enumerate() {
+        let name = format!("{}-node{}", cluster, rank);
+        if let Err(e) = client
+            .create_tags()
+            .resources(instance_id)
+            .tags(Tag::builder().key("Name").value(name).build())
+            .tags(
+                Tag::builder()
+                    .key(NODE_RANK_TAG)
+                    .value(rank.to_string())
+                    .build(),
+            )
+            .send()
+            .await
+        {
+            warn!("Failed to tag {} with its rank: {}", instance_id, e);
+        }
+    }
+
+    if text {
+        println!("Waiting for {} nodes to be ready...", instance_ids.len());
+    }
+    for instance_id in &instance_ids {
+        crate::aws_utils::wait_for_instance_running(&client, instance_id, Some(aws_config)).await?;
+    }
+
+    let nodes = inspect_nodes(&client, &ssm, &instance_ids).await?;
+    let master_instance = client
+        .describe_instances()
+        .instance_ids(&nodes[0].instance_id)
+        .send()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to describe instance: {}", e)))?;
+    let master = crate::aws::find_instance_in_response(&master_instance, &nodes[0].instance_id)
+        .ok_or_else(|| TrainctlError::ResourceNotFound {
+            resource_type: "instance".to_string(),
+            resource_id: nodes[0].instance_id.clone(),
+        })?;
+    let user = resolve_ssh_user(&client, master, None).await;
+
+    if text {
+        println!("Setting up SSH between nodes...");
+    }
+    setup_node_ssh(&ssm, &nodes, &user, options.gpus_per_node).await?;
+
+    let rendezvous = Rendezvous {
+        nodes: options.nodes,
+        master_addr: nodes[0].private_ip.clone(),
+        master_port: options.master_port,
+    };
+    // Node 0 first: it hosts the rendezvous the others connect to
+    for node in &nodes {
+        if text {
+            println!("\n[node {}] {}", node.rank, node.instance_id);
+        }
+        let train_options = TrainInstanceOptions {
+            instance_id: node.instance_id.clone(),
+            script: options.script.clone(),
+            data_s3: None,
+            output_s3: None,
+            sync_code: true,
+            include_patterns: options.include_patterns.clone(),
+            exclude_patterns: options.exclude_patterns.clone(),
+            max_file_size: None,
+            yes: options.yes,
+            project_name: options.project_name.clone(),
+            ssh_user: Some(user.clone()),
+            script_args: options.script_args.clone(),
+            resume: None,
+            env: distributed_env(&rendezvous, node.rank, options.efa),
+            wait: false,
+            timeout_minutes: options.timeout_minutes,
+            docker: false,
+            docker_image: None,
+            job: config.job.clone(),
+            agent: false,
+            launcher: Some(
+                options
+                    .launcher
+                    .command(&rendezvous, node.rank, options.gpus_per_node),
+            ),
+            on_complete: None,
+        };
+        train_on_instance(train_options, config, aws_config, output_format).await?;
+    }
+
+    if text {
+        println!(
+            "\nDistributed run started on {} nodes (master {}:{})",
+            nodes.len(),
+            rendezvous.master_addr,
+            rendezvous.master_port
+        );
+        for node in &nodes {
+            println!(
+                "   node {}: {} ({}, {} GPUs)",
+                node.rank, node.instance_id, node.private_ip, node.gpus
+            );
+        }
+        println!(
+            "   Monitor node 0: runctl aws monitor {} --follow",
+            nodes[0].instance_id
+        );
+        println!("   Hostfile on node 0: ~/.runctl/hostfile");
+    } else {
+        let summary = ClusterSummary {
+            success: true,
+            cluster: &cluster,
+            master_addr: &rendezvous.master_addr,
+            master_port: rendezvous.master_port,
+            nodes: &nodes,
+        };
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    }
+
+    if options.wait {
+        let project_dir = format!("/home/{}/{}", user, options.project_name);
+        wait_for_cluster(
+            &ssm,
+            &cluster,
+            &nodes,
+            &project_dir,
+            options.timeout_minutes,
+            output_format,
+        )
+        .await?;
+        if text {
+            println!("All {} nodes finished", nodes.len());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(rank: u32, ip: &str, gpus: u32) -> ClusterNode {
+        ClusterNode {
+            rank,
+            instance_id: format!("i-000{}", rank),
+            private_ip: ip.to_string(),
+            gpus,
+            host_keys: vec![("ssh-ed25519".to_string(), format!("AAAAkey{}", rank))],
+        }
+    }
+
+    fn rendezvous() -> Rendezvous {
+        Rendezvous {
+            nodes: 2,
+            master_addr: "10.0.0.5".to_string(),
+            master_port: DEFAULT_MASTER_PORT,
+        }
+    }
+
+    #[test]
+    fn test_launcher_commands() {
+        assert_eq!(
+            Launcher::Torchrun.command(&rendezvous(), 1, None),
+            "python3 -m torch.distributed.run --nnodes=2 --node_rank=1 --nproc_per_node=gpu \
+             --master_addr=10.0.0.5 --master_port=29500"
+        );
+        let deepspeed = Launcher::Deepspeed.command(&rendezvous(), 0, Some(8));
+        assert!(deepspeed.starts_with("deepspeed --no_ssh --num_nodes=2 --node_rank=0"));
+        assert!(deepspeed.ends_with("--num_gpus=8"));
+        assert_eq!("DeepSpeed".parse::<Launcher>(), Ok(Launcher::Deepspeed));
+        assert!("horovod".parse::<Launcher>().is_err());
+    }
+
+    #[test]
+    fn test_distributed_env() {
+        let env = distributed_env(&rendezvous(), 1, true);
+        assert!(env.contains(&("MASTER_ADDR".to_string(), "10.0.0.5".to_string())));
+        assert!(env.contains(&("NODE_RANK".to_string(), "1".to_string())));
+        assert!(env.contains(&("FI_PROVIDER".to_string(), "efa".to_string())));
+        assert_eq!(distributed_env(&rendezvous(), 0, false).len(), 4);
+    }
+
+    #[test]
+    fn test_ssh_files() {
+        let nodes = vec![node(0, "10.0.0.5", 8), node(1, "10.0.0.6", 0)];
+        assert_eq!(
+            hostfile(&nodes, None),
+            "10.0.0.5 slots=8\n10.0.0.6 slots=1\n"
+        );
+        assert_eq!(
+            hostfile(&nodes, Some(4)),
+            "10.0.0.5 slots=4\n10.0.0.6 slots=4\n"
+        );
+        assert_eq!(
+            known_hosts(&nodes),
+            "10.0.0.5 ssh-ed25519 AAAAkey0\n10.0.0.6 ssh-ed25519 AAAAkey1\n"
+        );
+        let config = ssh_config(&nodes);
+        assert!(config.starts_with("Host 10.0.0.5 10.0.0.6\n"));
+        assert!(config.contains("StrictHostKeyChecking yes"));
+    }
+
+    #[test]
+    fn test_cluster_name() {
+        let name = cluster_name("My Project!");
+        assert!(name.starts_with("runctl-My-Project-"));
+        assert_eq!(name.len(), "runctl-My-Project-".len() + 8);
+    }
+
+    #[test]
+    fn test_parse_node_poll() {
+        assert_eq!(
+            parse_node_poll("EXIT:0\nepoch 10/10 loss=0.1\n"),
+            (Some(0), Some("epoch 10/10 loss=0.1".to_string()))
+        );
+        assert_eq!(
+            parse_node_poll("step 5/100\n"),
+            (None, Some("step 5/100".to_string()))
+        );
+        assert_eq!(parse_node_poll(""), (None, None));
+    }
+
+    #[test]
+    fn test_as_user_quotes_script() {
+        assert_eq!(
+            as_user("ubuntu", "echo 'hi'"),
+            "sudo -u 'ubuntu' -H bash -c 'echo '\\''hi'\\'''"
+        );
+    }
+}
@@ -0,0 +1,359 @@
+//! Configurable completion detection and health checks
+//!
+//! `aws train --wait` normally relies on built-in heuristics (see
+//! `training::check_training_completion`). When the job spec (`[job]` config
+//! or `aws train --complete-*` flags) defines completion methods, this module
+//! evaluates exactly those instead:
+//!
+//! - **Marker file**: a non-empty file at a path relative to the project dir
+//! - **Log regex**: a match in the recent `training.log` tail
+//! - **Exit code**: the wrapper's `training_exit_code.txt` (non-zero = failed)
+//!
+//! Health checks are shell commands run on the instance at their own interval;
+//! the wait loop gives up after a check fails `max_failures` times in a row.
+//!
+//! All state is fetched in one SSM round trip and parsed locally, so regexes
+//! use Rust `regex` syntax rather than whatever `grep` the AMI ships.
+
+use crate::config::{CompletionConfig, HealthCheckConfig};
+use crate::error::{ConfigError, Result, TrainctlError};
+use crate::utils::shell_quote;
+use aws_sdk_ssm::Client as SsmClient;
+use regex::Regex;
+use std::time::{Duration, Instant};
+
+/// Lines of `training.log` searched for the completion regex
+const LOG_TAIL_LINES: usize = 500;
+
+/// Separates the probe's key/value block from the log tail
+const LOG_SEPARATOR: &str = "---RUNCTL-LOG---";
+
+/// Marker printed after a health check command with its exit status
+const RC_MARKER: &str = "RUNCTL_RC:";
+
+/// Result of evaluating the configured completion methods
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum CompletionOutcome {
+    Running,
+    Completed(String),
+    Failed(String),
+}
+
+/// Validated completion spec, ready to evaluate
+#[derive(Debug, Clone)]
+pub(crate) struct CompletionSpec {
+    marker_file: Option<String>,
+    log_regex: Option<Regex>,
+    exit_code: bool,
+}
+
+impl CompletionSpec {
+    /// Compile and validate the configured methods
+    ///
+    /// Returns `None` when no method is configured (use built-in heuristics).
+    pub(crate) fn from_config(config: &CompletionConfig) -> Result<Option<Self>> {
+        if !config.is_configured() {
+            return Ok(None);
+        }
+        if let Some(marker) = &config.marker_file {
+            if marker.is_empty() || marker.starts_with('/') || marker.split('/').any(|p| p == "..")
+            {
+                return Err(TrainctlError::Config(ConfigError::InvalidValue {
+                    field: "job.completion.marker_file".to_string(),
+                    reason: format!(
+                        "Must be a path relative to the project directory, got: '{}'",
+                        marker
+                    ),
+                }));
+            }
+        }
+        let log_regex = config
+            .log_regex
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| {
+                TrainctlError::Config(ConfigError::InvalidValue {
+                    field: "job.completion.log_regex".to_string(),
+                    reason: format!("Invalid regex: {}", e),
+                })
+            })?;
+        Ok(Some(Self {
+            marker_file: config.marker_file.clone(),
+            log_regex,
+            exit_code: config.exit_code,
+        }))
+    }
+
+    /// Shell command that reports everything `evaluate` needs
+    fn probe_command(&self, project_dir: &str) -> String {
+        let mut cmd = format!("cd {} 2>/dev/null || exit 0; ", shell_quote(project_dir));
+        if let Some(marker) = &self.marker_file {
+            cmd.push_str(&format!(
+                "if [ -s {} ]; then echo MARKER:yes; else echo MARKER:no; fi; ",
+                shell_quote(marker)
+            ));
+        }
+        if self.exit_code {
+            cmd.push_str(
+                "if [ -f training_exit_code.txt ]; then echo EXIT:$(cat training_exit_code.txt); fi; ",
+            );
+        }
+        if self.log_regex.is_some() {
+            cmd.push_str(&format!(
+                "echo {}; tail -n {} training.log 2>/dev/null",
+                LOG_SEPARATOR, LOG_TAIL_LINES
+            ));
+        }
+        cmd
+    }
+
+    /// Decide completion from the probe output
+    fn evaluate(&self, output: &str) -> CompletionOutcome {
+        let (head, log) = output.split_once(LOG_SEPARATOR).unwrap_or((output, ""));
+        let mut marker = false;
+        let mut exit_code: Option<i32> = None;
+        for line in head.lines().map(str::trim) {
+            if line == "MARKER:yes" {
+                marker = true;
+            } else if let Some(code) = line.strip_prefix("EXIT:") {
+                exit_code = code.trim().parse().ok();
+            }
+        }
+
+        // A recorded failure wins over any success signal
+        if self.exit_code {
+            match exit_code {
+                Some(0) => return CompletionOutcome::Completed("exit code 0".to_string()),
+                Some(code) => return CompletionOutcome::Failed(format!("exit code {}", code)),
+                None => {}
+            }
+        }
+        if marker {
+            if let Some(path) = &self.marker_file {
+                return CompletionOutcome::Completed(format!("marker file {}", path));
+            }
+        }
+        if let Some(re) = &self.log_regex {
+            if let Some(line) = log.lines().find(|l| re.is_match(l)) {
+                return CompletionOutcome::Completed(format!("log matched: {}", line.trim()));
+            }
+        }
+        CompletionOutcome::Running
+    }
+
+    /// Fetch state from the instance and evaluate it
+    pub(crate) async fn check(
+        &self,
+        ssm_client: &SsmClient,
+        instance_id: &str,
+        project_dir: &str,
+    ) -> Result<CompletionOutcome> {
+        let output = crate::aws_utils::execute_ssm_command(
+            ssm_client,
+            instance_id,
+            &self.probe_command(project_dir),
+        )
+        .await?;
+        Ok(self.evaluate(&output))
+    }
+}
+
+/// Outcome of one health check run
+#[derive(Debug, Clone)]
+pub(crate) struct HealthCheckResult {
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+    pub consecutive_failures: u32,
+    pub max_failures: u32,
+}
+
+impl HealthCheckResult {
+    /// Whether this check has failed often enough to stop waiting
+    pub fn exhausted(&self) -> bool {
+        !self.passed && self.consecutive_failures >= self.max_failures.max(1)
+    }
+}
+
+/// Decide whether a health check passed from its output
+///
+/// The output ends with `RUNCTL_RC:<status>`. With `expect`, the output must
+/// contain it; otherwise the command must exit 0.
+fn evaluate_health_output(output: &str, expect: Option<&str>) -> std::result::Result<(), String> {
+    let (body, rc) = match output.rfind(RC_MARKER) {
+        Some(idx) => (
+            &output[..idx],
+            output[idx + RC_MARKER.len()..].trim().parse::<i32>().ok(),
+        ),
+        None => (output, None),
+    };
+    let body = body.trim();
+    let summary = body.lines().last().unwrap_or("").trim();
+    match expect {
+        Some(expected) if body.contains(expected) => Ok(()),
+        Some(expected) => Err(format!("expected '{}', got: {}", expected, summary)),
+        None if rc == Some(0) => Ok(()),
+        None => Err(format!(
+            "exit status {}: {}",
+            rc.map(|c| c.to_string())
+                .unwrap_or_else(|| "unknown".to_string()),
+            summary
+        )),
+    }
+}
+
+struct ScheduledCheck {
+    config: HealthCheckConfig,
+    last_run: Option<Instant>,
+    consecutive_failures: u32,
+}
+
+/// Runs configured health checks when they're due
+pub(crate) struct HealthChecks {
+    checks: Vec<ScheduledCheck>,
+}
+
+impl HealthChecks {
+    pub(crate) fn new(configs: &[HealthCheckConfig]) -> Self {
+        Self {
+            checks: configs
+                .iter()
+                .map(|config| ScheduledCheck {
+                    config: config.clone(),
+                    last_run: None,
+                    consecutive_failures: 0,
+                })
+                .collect(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.checks.is_empty()
+    }
+
+    /// Run every check whose interval has elapsed
+    pub(crate) async fn run_due(
+        &mut self,
+        ssm_client: &SsmClient,
+        instance_id: &str,
+        project_dir: &str,
+    ) -> Vec<HealthCheckResult> {
+        let mut results = Vec::new();
+        for check in &mut self.checks {
+            let interval = Duration::from_secs(check.config.interval_secs.max(1));
+            if check.last_run.is_some_and(|t| t.elapsed() < interval) {
+                continue;
+            }
+            check.last_run = Some(Instant::now());
+
+            let cmd = format!(
+                "cd {} && ( {} ) 2>&1; echo \"{}$?\"",
+                shell_quote(project_dir),
+                check.config.command,
+                RC_MARKER
+            );
+            let outcome =
+                match crate::aws_utils::execute_ssm_command(ssm_client, instance_id, &cmd).await {
+                    Ok(output) => evaluate_health_output(&output, check.config.expect.as_deref()),
+                    Err(e) => Err(format!("could not run check: {}", e)),
+                };
+            let (passed, detail) = match outcome {
+                Ok(()) => {
+                    check.consecutive_failures = 0;
+                    (true, "ok".to_string())
+                }
+                Err(detail) => {
+                    check.consecutive_failures += 1;
+                    (false, detail)
+                }
+            };
+            results.push(HealthCheckResult {
+                name: check.config.name.clone(),
+                passed,
+                detail,
+                consecutive_failures: check.consecutive_failures,
+                max_failures: check.config.max_failures,
+            });
+        }
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(marker: Option<&str>, regex: Option<&str>, exit_code: bool) -> CompletionSpec {
+        CompletionSpec::from_config(&CompletionConfig {
+            marker_file: marker.map(str::to_string),
+            log_regex: regex.map(str::to_string),
+            exit_code,
+        })
+        .unwrap()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_unconfigured_uses_heuristics() {
+        assert!(CompletionSpec::from_config(&CompletionConfig::default())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_rejects_bad_spec() {
+        let bad_regex = CompletionConfig {
+            log_regex: Some("([".to_string()),
+            ..Default::default()
+        };
+        assert!(CompletionSpec::from_config(&bad_regex).is_err());
+        let absolute = CompletionConfig {
+            marker_file: Some("/tmp/done".to_string()),
+            ..Default::default()
+        };
+        assert!(CompletionSpec::from_config(&absolute).is_err());
+    }
+
+    #[test]
+    fn test_marker_and_regex() {
+        let s = spec(Some("outputs/DONE"), Some(r"finished \d+ epochs"), false);
+        assert_eq!(s.evaluate("MARKER:no\n"), CompletionOutcome::Running);
+        assert!(matches!(
+            s.evaluate("MARKER:yes\n"),
+            CompletionOutcome::Completed(_)
+        ));
+        let out = format!("MARKER:no\n{}\nepoch 3\nfinished 3 epochs\n", LOG_SEPARATOR);
+        assert_eq!(
+            s.evaluate(&out),
+            CompletionOutcome::Completed("log matched: finished 3 epochs".to_string())
+        );
+    }
+
+    #[test]
+    fn test_exit_code_failure_wins() {
+        let s = spec(Some("DONE"), None, true);
+        assert_eq!(
+            s.evaluate("MARKER:yes\nEXIT:2\n"),
+            CompletionOutcome::Failed("exit code 2".to_string())
+        );
+        assert_eq!(s.evaluate("MARKER:no\n"), CompletionOutcome::Running);
+    }
+
+    #[test]
+    fn test_probe_command_quotes_paths() {
+        let s = spec(Some("it's done"), None, false);
+        let cmd = s.probe_command("/home/ubuntu/proj");
+        assert!(cmd.contains(r"[ -s 'it'\''s done' ]"));
+        assert!(!cmd.contains(LOG_SEPARATOR));
+    }
+
+    #[test]
+    fn test_health_output() {
+        assert!(evaluate_health_output("GPU 0: A10G\nRUNCTL_RC:0\n", Some("GPU 0")).is_ok());
+        assert!(evaluate_health_output("No devices\nRUNCTL_RC:0\n", Some("GPU 0")).is_err());
+        assert!(evaluate_health_output("ok\nRUNCTL_RC:0\n", None).is_ok());
+        let err = evaluate_health_output("curl: refused\nRUNCTL_RC:7\n", None).unwrap_err();
+        assert_eq!(err, "exit status 7: curl: refused");
+    }
+}
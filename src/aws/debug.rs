@@ -0,0 +1,239 @@
+//! Instance side of `runctl jobs collect-debug`
+//!
+//! Describes the instance and reads its console output, which works whether
+//! it is running or stopped. On a running instance it also runs one command
+//! per file over SSM or SSH (the same transport as `monitor`): the training
+//! log tail, dmesg, nvidia-smi, the user-data log, pip freeze, disk usage and
+//! processes. Each command's output is capped, since SSM truncates at 24000
+//! characters. A file that can't be collected is reported as skipped rather
+//! than failing the bundle.
+
+use crate::aws::adopt::describe;
+use crate::aws::helpers::get_project_name;
+use crate::aws::monitor::{log_path_from_tags, Transport};
+use crate::aws::ssh_user::resolve_ssh_user;
+use crate::config::Config;
+use crate::error::Result;
+use crate::jobs::{DebugFile, Skipped};
+use crate::utils::shell_quote;
+use aws_sdk_ec2::types::Instance;
+use aws_sdk_ec2::Client as Ec2Client;
+use base64::Engine;
+
+/// Most bytes kept from each remote command
+const MAX_OUTPUT_BYTES: usize = 20_000;
+
+/// Files read from a running instance: (name in the bundle, command)
+pub(crate) fn remote_commands(
+    log_path: &str,
+    project_dir: &str,
+    lines: usize,
+) -> Vec<(&'static str, String)> {
+    let log = shell_quote(log_path);
+    let dir = shell_quote(project_dir);
+    let cap = format!(" | tail -c {}", MAX_OUTPUT_BYTES);
+    vec![
+        (
+            "training.log",
+            format!("tail -n {} {} 2>&1{}", lines, log, cap),
+        ),
+        (
+            "exit_code.txt",
+            format!(
+                "cat {}/training_exit_code.txt 2>/dev/null || echo 'no exit code recorded'",
+                dir
+            ),
+        ),
+        (
+            "dmesg.txt",
+            format!(
+                "(sudo -n dmesg -T 2>/dev/null || dmesg -T) 2>&1 | tail -n {}{}",
+                lines, cap
+            ),
+        ),
+        (
+            "nvidia-smi.txt",
+            format!(
+                "nvidia-smi 2>&1 || echo 'nvidia-smi not available'; nvidia-smi -q -d ECC,TEMPERATURE,POWER 2>/dev/null{}",
+                cap
+            ),
+        ),
+        (
+            "user-data.log",
+            format!(
+                "(sudo -n tail -n {n} /var/log/user-data.log 2>/dev/null || tail -n {n} /var/log/user-data.log) 2>&1{cap}",
+                n = lines,
+                cap = cap
+            ),
+        ),
+        (
+            "pip-freeze.txt",
+            format!(
+                "cd {} 2>/dev/null; export PATH=\"$HOME/.local/bin:$PATH\"; \
+                 (python3 -m pip freeze || pip freeze) 2>&1{}",
+                dir, cap
+            ),
+        ),
+        (
+            "disk-usage.txt",
+            format!(
+                "df -h 2>&1; echo; du -sh {}/* 2>&1 | sort -rh | head -n 40",
+                dir
+            ),
+        ),
+        (
+            "processes.txt",
+            "ps aux --sort=-%mem 2>&1 | head -n 40".to_string(),
+        ),
+    ]
+}
+
+/// What EC2 reports about the instance, as JSON
+fn instance_summary(instance: &Instance) -> serde_json::Value {
+    let tags: serde_json::Map<String, serde_json::Value> = instance
+        .tags()
+        .iter()
+        .filter_map(|t| Some((t.key()?.to_string(), t.value()?.into())))
+        .collect();
+    serde_json::json!({
+        "instance_id": instance.instance_id(),
+        "instance_type": instance.instance_type().map(|t| t.as_str()),
+        "state": instance.state().and_then(|s| s.name()).map(|s| s.as_str()),
+        "state_transition_reason": instance.state_transition_reason(),
+        "state_reason": instance.state_reason().and_then(|r| r.message()),
+        "lifecycle": instance.instance_lifecycle().map(|l| l.as_str()),
+        "launch_time": instance
+            .launch_time()
+            .and_then(|t| chrono::DateTime::<chrono::Utc>::from_timestamp(t.secs(), 0))
+            .map(|t| t.to_rfc3339()),
+        "availability_zone": instance.placement().and_then(|p| p.availability_zone()),
+        "image_id": instance.image_id(),
+        "public_ip": instance.public_ip_address(),
+        "private_ip": instance.private_ip_address(),
+        "iam_instance_profile": instance.iam_instance_profile().and_then(|p| p.arn()),
+        "tags": tags,
+    })
+}
+
+/// Console output, preferring the latest on instances that support it
+async fn console_output(client: &Ec2Client, instance_id: &str) -> Result<String> {
+    let response = match client
+        .get_console_output()
+        .instance_id(instance_id)
+        .latest(true)
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        // `latest` is only supported on Nitro instances
+        Err(_) => client
+            .get_console_output()
+            .instance_id(instance_id)
+            .send()
+            .await
+            .map_err(|e| {
+                crate::error::TrainctlError::Aws(format!("Failed to get console output: {}", e))
+            })?,
+    };
+    let encoded = response.output().unwrap_or_default();
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .unwrap_or_default();
+    Ok(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+/// Collect everything available about `instance_id`
+pub(crate) async fn collect_instance(
+    instance_id: &str,
+    lines: usize,
+    config: &Config,
+    aws_config: &aws_config::SdkConfig,
+) -> Result<(Vec<DebugFile>, Vec<Skipped>)> {
+    let client = Ec2Client::new(aws_config);
+    let instance = describe(&client, instance_id).await?;
+    let mut files = vec![DebugFile::new(
+        "instance.json",
+        serde_json::to_string_pretty(&instance_summary(&instance))?,
+    )];
+    let mut skipped = Vec::new();
+
+    match console_output(&client, instance_id).await {
+        Ok(output) if !output.is_empty() => files.push(DebugFile::new("console.log", output)),
+        Ok(_) => skipped.push(Skipped::new("console.log", "no console output yet")),
+        Err(e) => skipped.push(Skipped::new("console.log", e)),
+    }
+
+    let state = instance
+        .state()
+        .and_then(|s| s.name())
+        .map(|s| s.as_str().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let commands_skipped = |skipped: &mut Vec<Skipped>, reason: String| {
+        for (name, _) in remote_commands("", "", lines) {
+            skipped.push(Skipped::new(name, &reason));
+        }
+    };
+    if state != "running" {
+        commands_skipped(&mut skipped, format!("instance is {}", state));
+        return Ok((files, skipped));
+    }
+
+    let user = resolve_ssh_user(&client, &instance, None).await;
+    let tags: Vec<(String, String)> = instance
+        .tags()
+        .iter()
+        .filter_map(|t| Some((t.key()?.to_string(), t.value()?.to_string())))
+        .collect();
+    let log_path = log_path_from_tags(&tags, &user, &get_project_name(None, config));
+    let project_dir = log_path
+        .rsplit_once('/')
+        .map(|(dir, _)| dir.to_string())
+        .unwrap_or_default();
+    let transport = match Transport::connect(&client, &instance, &user, config, aws_config).await {
+        Ok(transport) => transport,
+        Err(e) => {
+            commands_skipped(&mut skipped, format!("no connection: {}", e));
+            return Ok((files, skipped));
+        }
+    };
+    for (name, command) in remote_commands(&log_path, &project_dir, lines) {
+        match transport.run(instance_id, &command).await {
+            Ok(output) => files.push(DebugFile::new(name, output)),
+            Err(e) => skipped.push(Skipped::new(name, e)),
+        }
+    }
+    Ok((files, skipped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_commands_quote_and_cap() {
+        let commands = remote_commands(
+            "/home/ubuntu/my proj/training.log",
+            "/home/ubuntu/my proj",
+            200,
+        );
+        let (name, log) = &commands[0];
+        assert_eq!(*name, "training.log");
+        assert_eq!(
+            log,
+            "tail -n 200 '/home/ubuntu/my proj/training.log' 2>&1 | tail -c 20000"
+        );
+        assert!(commands
+            .iter()
+            .any(|(name, cmd)| *name == "pip-freeze.txt"
+                && cmd.starts_with("cd '/home/ubuntu/my proj'")));
+        let names: Vec<&str> = commands.iter().map(|(name, _)| *name).collect();
+        for expected in [
+            "dmesg.txt",
+            "nvidia-smi.txt",
+            "user-data.log",
+            "disk-usage.txt",
+        ] {
+            assert!(names.contains(&expected), "missing {}", expected);
+        }
+    }
+}
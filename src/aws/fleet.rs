@@ -0,0 +1,932 @@
+//! Auto Scaling group fleets of training workers
+//!
+//! `runctl aws fleet create <name> --preset <p> --size N` builds a launch
+//! template from the preset (AMI, instance type, key, security group, instance
+//! profile, root volume, bootstrap user data) and an Auto Scaling group of N
+//! workers from it. `scale` changes the desired size, `refresh` rolls the
+//! workers onto a new template version, `status` shows the groups and their
+//! workers, and `delete` removes both.
+//!
+//! A fleet keeps its workers between jobs: `runctl queue run --fleet <name>`
+//! runs each queued job on an idle worker instead of launching an instance
+//! per job. A worker running a job is protected from scale-in, which is also
+//! how the scheduler tells busy workers from idle ones, so `scale` down and
+//! `refresh` never take a worker out from under a job.
+
+use crate::aws::ami::{find_deep_learning_ami, root_device_name};
+use crate::aws::helpers::get_user_id;
+use crate::aws::idempotency::PRESET_TAG;
+use crate::aws::user_data::render_user_data;
+use crate::config::{Config, PresetConfig};
+use crate::error::{ConfigError, Result, TrainctlError};
+use aws_sdk_autoscaling::types::{
+    Filter as AsgFilter, LaunchTemplateSpecification, LifecycleState, RefreshPreferences,
+    ScaleInProtectedInstances, Tag as AsgTag,
+};
+use aws_sdk_autoscaling::Client as AsgClient;
+use aws_sdk_ec2::types::{
+    InstanceType as Ec2InstanceType, LaunchTemplateBlockDeviceMappingRequest,
+    LaunchTemplateEbsBlockDeviceRequest, LaunchTemplateIamInstanceProfileSpecificationRequest,
+    LaunchTemplateInstanceMarketOptionsRequest, LaunchTemplateTagSpecificationRequest, MarketType,
+    RequestLaunchTemplateData, ResourceType, Tag, VolumeType,
+};
+use aws_sdk_ec2::Client as Ec2Client;
+use base64::Engine;
+use clap::Subcommand;
+use comfy_table::Table;
+use serde::Serialize;
+
+/// Tag on the group and its instances naming the fleet
+pub(crate) const FLEET_TAG: &str = "runctl:fleet";
+
+#[derive(Subcommand, Clone)]
+pub enum FleetCommands {
+    /// Create a launch template and Auto Scaling group of workers
+    ///
+    /// Examples:
+    ///   runctl aws fleet create workers --preset g5-dev --size 4
+    ///   runctl aws fleet create cpu --instance-type c6i.4xlarge --size 2 --max 8
+    ///   runctl aws fleet create workers --preset g5-dev --subnets subnet-1,subnet-2
+    Create {
+        /// Fleet name
+        #[arg(value_name = "NAME", value_parser = fleet_name_arg)]
+        name: String,
+
+        /// Named preset from `[presets.<name>]` for the workers
+        #[arg(long, value_name = "NAME")]
+        preset: Option<String>,
+
+        /// Instance type (default: the preset's, else [aws] default_instance_type)
+        #[arg(long, value_name = "TYPE", value_parser = crate::validation::instance_type_arg)]
+        instance_type: Option<String>,
+
+        /// Workers to run
+        #[arg(long, default_value_t = 1, value_name = "N")]
+        size: i32,
+
+        /// Smallest size the group may be scaled to
+        #[arg(long, default_value_t = 0, value_name = "N")]
+        min: i32,
+
+        /// Largest size the group may be scaled to (default: --size)
+        #[arg(long, value_name = "N")]
+        max: Option<i32>,
+
+        /// Subnets to launch into (default: every zone of the default VPC)
+        #[arg(long, value_delimiter = ',', value_name = "SUBNETS")]
+        subnets: Vec<String>,
+
+        /// Use spot instances
+        #[arg(long)]
+        spot: bool,
+    },
+    /// Set the number of workers
+    ///
+    /// Raises --max or lowers --min when the new size is outside them.
+    /// Workers running a job are not removed when scaling down.
+    ///
+    /// Examples:
+    ///   runctl aws fleet scale workers 8
+    ///   runctl aws fleet scale workers 0
+    Scale {
+        #[arg(value_name = "NAME")]
+        name: String,
+
+        #[arg(value_name = "SIZE")]
+        size: i32,
+    },
+    /// Replace the workers with new ones, a few at a time
+    ///
+    /// With --preset or --instance-type, a new launch template version is
+    /// created first. Workers running a job are replaced once it finishes.
+    ///
+    /// Examples:
+    ///   runctl aws fleet refresh workers
+    ///   runctl aws fleet refresh workers --preset g6-dev
+    Refresh {
+        #[arg(value_name = "NAME")]
+        name: String,
+
+        /// Preset for the new launch template version
+        #[arg(long, value_name = "NAME")]
+        preset: Option<String>,
+
+        /// Instance type for the new launch template version
+        #[arg(long, value_name = "TYPE", value_parser = crate::validation::instance_type_arg)]
+        instance_type: Option<String>,
+
+        /// Percentage of workers kept in service during the refresh
+        #[arg(long, default_value_t = 90, value_name = "PERCENT")]
+        min_healthy: i32,
+    },
+    /// Show fleets, or one fleet's workers
+    Status {
+        #[arg(value_name = "NAME")]
+        name: Option<String>,
+    },
+    /// Delete the group, its workers and its launch template
+    Delete {
+        #[arg(value_name = "NAME")]
+        name: String,
+
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+/// Fleet names become the group and launch template names
+fn fleet_name_arg(name: &str) -> std::result::Result<String, String> {
+    let valid = !name.is_empty()
+        && name.len() <= 100
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(name.to_string())
+    } else {
+        Err("use letters, digits, '-' and '_' (up to 100 characters)".to_string())
+    }
+}
+
+/// Auto Scaling group and launch template name for a fleet
+fn group_name(name: &str) -> String {
+    format!("runctl-fleet-{}", name)
+}
+
+/// Check `min <= size <= max`
+fn validate_capacity(size: i32, min: i32, max: i32) -> Result<()> {
+    if min < 0 || size < min || size > max {
+        return Err(TrainctlError::Validation {
+            field: "size".to_string(),
+            reason: format!(
+                "need 0 <= --min ({}) <= --size ({}) <= --max ({})",
+                min, size, max
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// A worker as the group reports it
+#[derive(Debug, Clone, Serialize)]
+struct Worker {
+    instance_id: String,
+    instance_type: String,
+    lifecycle: String,
+    healthy: bool,
+    /// Protected from scale-in, i.e. running a queue job
+    busy: bool,
+}
+
+impl Worker {
+    fn from_asg(instance: &aws_sdk_autoscaling::types::Instance) -> Option<Self> {
+        Some(Self {
+            instance_id: instance.instance_id()?.to_string(),
+            instance_type: instance.instance_type().unwrap_or_default().to_string(),
+            lifecycle: instance
+                .lifecycle_state()
+                .map(|s| s.as_str().to_string())
+                .unwrap_or_default(),
+            healthy: instance.health_status() == Some("Healthy"),
+            busy: instance.protected_from_scale_in().unwrap_or(false),
+        })
+    }
+
+    /// In service, healthy and not running a job
+    fn is_idle(&self) -> bool {
+        self.lifecycle == LifecycleState::InService.as_str() && self.healthy && !self.busy
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct FleetSummary {
+    name: String,
+    desired: i32,
+    min: i32,
+    max: i32,
+    workers: Vec<Worker>,
+    refresh: Option<String>,
+}
+
+impl FleetSummary {
+    fn from_group(name: String, group: &aws_sdk_autoscaling::types::AutoScalingGroup) -> Self {
+        Self {
+            name,
+            desired: group.desired_capacity().unwrap_or(0),
+            min: group.min_size().unwrap_or(0),
+            max: group.max_size().unwrap_or(0),
+            workers: group
+                .instances()
+                .iter()
+                .filter_map(Worker::from_asg)
+                .collect(),
+            refresh: None,
+        }
+    }
+}
+
+/// Idle workers a fleet keeps above its minimum size
+#[derive(Debug, Clone)]
+pub(crate) struct IdleSurplus {
+    pub fleet: String,
+    pub min: i32,
+    /// Instance types of the idle workers scaling down to `min` would remove
+    pub instance_types: Vec<String>,
+}
+
+/// Fleets with idle workers above their minimum size, for `runctl scan`
+///
+/// Scaling down removes idle workers first, so a fleet with more idle workers
+/// than `desired - min` can shrink by that much without touching a job.
+pub(crate) async fn idle_surplus(asg: &AsgClient) -> Result<Vec<IdleSurplus>> {
+    let mut surplus = Vec::new();
+    for group in list_groups(asg).await? {
+        let Some(fleet) = group
+            .tags()
+            .iter()
+            .find(|t| t.key() == Some(FLEET_TAG))
+            .and_then(|t| t.value())
+        else {
+            continue;
+        };
+        let summary = FleetSummary::from_group(fleet.to_string(), &group);
+        let removable = (summary.desired - summary.min).max(0) as usize;
+        let instance_types: Vec<String> = summary
+            .workers
+            .iter()
+            .filter(|w| w.is_idle())
+            .take(removable)
+            .map(|w| w.instance_type.clone())
+            .collect();
+        if !instance_types.is_empty() {
+            surplus.push(IdleSurplus {
+                fleet: summary.name,
+                min: summary.min,
+                instance_types,
+            });
+        }
+    }
+    Ok(surplus)
+}
+
+/// Workers of one fleet, handed out to queue jobs
+pub(crate) struct FleetPool {
+    client: AsgClient,
+    name: String,
+}
+
+impl FleetPool {
+    pub async fn new(name: &str) -> Result<Self> {
+        let sdk_config = crate::aws_utils::shared_sdk_config().await;
+        let pool = Self {
+            client: AsgClient::new(&sdk_config),
+            name: name.to_string(),
+        };
+        // Fail before any job is touched when the fleet doesn't exist
+        pool.workers().await?;
+        Ok(pool)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn workers(&self) -> Result<Vec<Worker>> {
+        let group = describe_group(&self.client, &self.name)
+            .await?
+            .ok_or_else(|| fleet_not_found(&self.name))?;
+        Ok(group
+            .instances()
+            .iter()
+            .filter_map(Worker::from_asg)
+            .collect())
+    }
+
+    /// Workers free to take a job
+    pub async fn idle(&self) -> Result<Vec<String>> {
+        Ok(self
+            .workers()
+            .await?
+            .into_iter()
+            .filter(Worker::is_idle)
+            .map(|w| w.instance_id)
+            .collect())
+    }
+
+    /// Mark a worker busy so scale-in and refreshes leave it alone
+    pub async fn claim(&self, instance_id: &str) -> Result<()> {
+        self.protect(instance_id, true).await
+    }
+
+    /// Hand a worker back once its job is done
+    pub async fn release(&self, instance_id: &str) -> Result<()> {
+        self.protect(instance_id, false).await
+    }
+
+    async fn protect(&self, instance_id: &str, protected: bool) -> Result<()> {
+        self.client
+            .set_instance_protection()
+            .auto_scaling_group_name(group_name(&self.name))
+            .instance_ids(instance_id)
+            .protected_from_scale_in(protected)
+            .send()
+            .await
+            .map_err(|e| {
+                TrainctlError::Aws(format!(
+                    "Failed to set scale-in protection on {}: {}",
+                    instance_id, e
+                ))
+            })?;
+        Ok(())
+    }
+}
+
+fn fleet_not_found(name: &str) -> TrainctlError {
+    TrainctlError::ResourceNotFound {
+        resource_type: "fleet".to_string(),
+        resource_id: name.to_string(),
+    }
+}
+
+pub async fn handle_command(
+    cmd: FleetCommands,
+    config: &Config,
+    aws_config: &aws_config::SdkConfig,
+    output_format: &str,
+) -> Result<()> {
+    let asg = AsgClient::new(aws_config);
+    let ec2 = Ec2Client::new(aws_config);
+    let text = output_format != "json";
+    match cmd {
+        FleetCommands::Create {
+            name,
+            preset,
+            instance_type,
+            size,
+            min,
+            max,
+            subnets,
+            spot,
+        } => {
+            let max = max.unwrap_or(size);
+            validate_capacity(size, min, max)?;
+            if describe_group(&asg, &name).await?.is_some() {
+                return Err(TrainctlError::Validation {
+                    field: "name".to_string(),
+                    reason: format!(
+                        "fleet '{}' already exists; use `runctl aws fleet scale` or `refresh`",
+                        name
+                    ),
+                });
+            }
+            let (preset_config, instance_type) =
+                worker_shape(config, preset.as_deref(), instance_type)?;
+            let data = launch_template_data(
+                &ec2,
+                config,
+                &name,
+                preset.as_deref(),
+                &preset_config,
+                &instance_type,
+                spot || preset_config.spot.unwrap_or(false),
+            )
+            .await?;
+            let template = group_name(&name);
+            ec2.create_launch_template()
+                .launch_template_name(&template)
+                .launch_template_data(data)
+                .send()
+                .await
+                .map_err(|e| {
+                    TrainctlError::Aws(format!("Failed to create launch template: {}", e))
+                })?;
+
+            let mut request = asg
+                .create_auto_scaling_group()
+                .auto_scaling_group_name(&template)
+                .launch_template(
+                    LaunchTemplateSpecification::builder()
+                        .launch_template_name(&template)
+                        .version("$Latest")
+                        .build(),
+                )
+                .min_size(min)
+                .max_size(max)
+                .desired_capacity(size)
+                .tags(
+                    AsgTag::builder()
+                        .key(FLEET_TAG)
+                        .value(&name)
+                        .propagate_at_launch(false)
+                        .build(),
+                );
+            request = if subnets.is_empty() {
+                request.set_availability_zones(Some(availability_zones(&ec2).await?))
+            } else {
+                request.vpc_zone_identifier(subnets.join(","))
+            };
+            if let Err(e) = request.send().await {
+                // Don't leave an orphaned template behind
+                let _ = ec2
+                    .delete_launch_template()
+                    .launch_template_name(&template)
+                    .send()
+                    .await;
+                return Err(TrainctlError::Aws(format!(
+                    "Failed to create Auto Scaling group: {}",
+                    e
+                )));
+            }
+
+            if text {
+                println!(
+                    "Created fleet {}: {} x {} (min {}, max {})",
+                    name, size, instance_type, min, max
+                );
+                println!(
+                    "   Run queued jobs on it: runctl queue run --fleet {}",
+                    name
+                );
+            } else {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "fleet": name,
+                        "instance_type": instance_type,
+                        "desired": size,
+                        "min": min,
+                        "max": max,
+                    }))?
+                );
+            }
+            Ok(())
+        }
+        FleetCommands::Scale { name, size } => {
+            let group = describe_group(&asg, &name)
+                .await?
+                .ok_or_else(|| fleet_not_found(&name))?;
+            let min = group.min_size().unwrap_or(0).min(size);
+            let max = group.max_size().unwrap_or(0).max(size);
+            validate_capacity(size, min, max)?;
+            asg.update_auto_scaling_group()
+                .auto_scaling_group_name(group_name(&name))
+                .min_size(min)
+                .max_size(max)
+                .desired_capacity(size)
+                .send()
+                .await
+                .map_err(|e| TrainctlError::Aws(format!("Failed to scale fleet: {}", e)))?;
+            let busy = group
+                .instances()
+                .iter()
+                .filter_map(Worker::from_asg)
+                .filter(|w| w.busy)
+                .count();
+            if text {
+                println!(
+                    "Fleet {}: {} -> {} workers",
+                    name,
+                    group.desired_capacity().unwrap_or(0),
+                    size
+                );
+                if (size as usize) < busy {
+                    println!(
+                        "   {} workers are running jobs and stay until they finish",
+                        busy
+                    );
+                }
+            } else {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "fleet": name,
+                        "desired": size,
+                        "min": min,
+                        "max": max,
+                    }))?
+                );
+            }
+            Ok(())
+        }
+        FleetCommands::Refresh {
+            name,
+            preset,
+            instance_type,
+            min_healthy,
+        } => {
+            if describe_group(&asg, &name).await?.is_none() {
+                return Err(fleet_not_found(&name));
+            }
+            if preset.is_some() || instance_type.is_some() {
+                let (preset_config, instance_type) =
+                    worker_shape(config, preset.as_deref(), instance_type)?;
+                let data = launch_template_data(
+                    &ec2,
+                    config,
+                    &name,
+                    preset.as_deref(),
+                    &preset_config,
+                    &instance_type,
+                    preset_config.spot.unwrap_or(false),
+                )
+                .await?;
+                ec2.create_launch_template_version()
+                    .launch_template_name(group_name(&name))
+                    .launch_template_data(data)
+                    .send()
+                    .await
+                    .map_err(|e| {
+                        TrainctlError::Aws(format!(
+                            "Failed to create launch template version: {}",
+                            e
+                        ))
+                    })?;
+                if text {
+                    println!("New launch template version: {}", instance_type);
+                }
+            }
+            let response = asg
+                .start_instance_refresh()
+                .auto_scaling_group_name(group_name(&name))
+                .preferences(
+                    RefreshPreferences::builder()
+                        .min_healthy_percentage(min_healthy)
+                        .scale_in_protected_instances(ScaleInProtectedInstances::Wait)
+                        .build(),
+                )
+                .send()
+                .await
+                .map_err(|e| {
+                    TrainctlError::Aws(format!("Failed to start instance refresh: {}", e))
+                })?;
+            let refresh_id = response.instance_refresh_id().unwrap_or_default();
+            if text {
+                println!("Refreshing fleet {} ({})", name, refresh_id);
+                println!("   Follow it with: runctl aws fleet status {}", name);
+            } else {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "fleet": name,
+                        "instance_refresh_id": refresh_id,
+                    }))?
+                );
+            }
+            Ok(())
+        }
+        FleetCommands::Status { name } => {
+            let mut fleets = Vec::new();
+            for group in list_groups(&asg).await? {
+                let Some(fleet) = group
+                    .tags()
+                    .iter()
+                    .find(|t| t.key() == Some(FLEET_TAG))
+                    .and_then(|t| t.value())
+                else {
+                    continue;
+                };
+                if name.as_deref().is_some_and(|n| n != fleet) {
+                    continue;
+                }
+                let mut summary = FleetSummary::from_group(fleet.to_string(), &group);
+                summary.refresh = latest_refresh(&asg, &summary.name).await;
+                fleets.push(summary);
+            }
+            if let Some(name) = &name {
+                if fleets.is_empty() {
+                    return Err(fleet_not_found(name));
+                }
+            }
+
+            if !text {
+                println!("{}", serde_json::to_string_pretty(&fleets)?);
+            } else if fleets.is_empty() {
+                println!(
+                    "No fleets. Create one with: runctl aws fleet create <name> --preset <preset>"
+                );
+            } else if name.is_some() {
+                let fleet = &fleets[0];
+                println!(
+                    "Fleet {}: {} desired (min {}, max {})",
+                    fleet.name, fleet.desired, fleet.min, fleet.max
+                );
+                if let Some(refresh) = &fleet.refresh {
+                    println!("Instance refresh: {}", refresh);
+                }
+                let mut table = Table::new();
+                table.set_header(vec!["Instance", "Type", "State", "Health", "Job"]);
+                for worker in &fleet.workers {
+                    table.add_row(vec![
+                        worker.instance_id.clone(),
+                        worker.instance_type.clone(),
+                        worker.lifecycle.clone(),
+                        if worker.healthy {
+                            "healthy"
+                        } else {
+                            "unhealthy"
+                        }
+                        .to_string(),
+                        if worker.busy { "busy" } else { "idle" }.to_string(),
+                    ]);
+                }
+                println!("{}", table);
+            } else {
+                let mut table = Table::new();
+                table.set_header(vec![
+                    "Fleet",
+                    "Desired",
+                    "Min/Max",
+                    "In service",
+                    "Busy",
+                    "Refresh",
+                ]);
+                for fleet in &fleets {
+                    let in_service = fleet
+                        .workers
+                        .iter()
+                        .filter(|w| w.lifecycle == LifecycleState::InService.as_str())
+                        .count();
+                    let busy = fleet.workers.iter().filter(|w| w.busy).count();
+                    table.add_row(vec![
+                        fleet.name.clone(),
+                        fleet.desired.to_string(),
+                        format!("{}/{}", fleet.min, fleet.max),
+                        in_service.to_string(),
+                        busy.to_string(),
+                        fleet.refresh.clone().unwrap_or_else(|| "-".to_string()),
+                    ]);
+                }
+                println!("{}", table);
+            }
+            Ok(())
+        }
+        FleetCommands::Delete { name, yes } => {
+            let group = describe_group(&asg, &name)
+                .await?
+                .ok_or_else(|| fleet_not_found(&name))?;
+            let workers = group.instances().len();
+            if !yes
+                && !crate::prompt::confirm(&format!(
+                    "Delete fleet {} and terminate its {} worker(s)?",
+                    name, workers
+                ))?
+            {
+                println!("Cancelled");
+                return Ok(());
+            }
+            asg.delete_auto_scaling_group()
+                .auto_scaling_group_name(group_name(&name))
+                .force_delete(true)
+                .send()
+                .await
+                .map_err(|e| TrainctlError::Aws(format!("Failed to delete fleet: {}", e)))?;
+            // The group deletes asynchronously; its template can go right away
+            ec2.delete_launch_template()
+                .launch_template_name(group_name(&name))
+                .send()
+                .await
+                .map_err(|e| {
+                    TrainctlError::Aws(format!("Failed to delete launch template: {}", e))
+                })?;
+            if text {
+                println!(
+                    "Deleting fleet {} ({} worker(s) terminating)",
+                    name, workers
+                );
+            } else {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "fleet": name,
+                        "deleted": true,
+                        "workers_terminated": workers,
+                    }))?
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+/// The preset (empty when none is given) and the resulting instance type
+fn worker_shape(
+    config: &Config,
+    preset: Option<&str>,
+    instance_type: Option<String>,
+) -> Result<(PresetConfig, String)> {
+    let preset_config = match preset {
+        Some(name) => config.preset(name)?.clone(),
+        None => PresetConfig::default(),
+    };
+    let instance_type = instance_type
+        .or_else(|| preset_config.instance_type.clone())
+        .or_else(|| config.aws.as_ref().map(|a| a.default_instance_type.clone()))
+        .ok_or_else(|| TrainctlError::Config(ConfigError::MissingField("aws".to_string())))?;
+    Ok((preset_config, instance_type))
+}
+
+/// Launch template contents for a fleet's workers
+async fn launch_template_data(
+    ec2: &Ec2Client,
+    config: &Config,
+    name: &str,
+    preset: Option<&str>,
+    preset_config: &PresetConfig,
+    instance_type: &str,
+    spot: bool,
+) -> Result<RequestLaunchTemplateData> {
+    let aws_cfg = config
+        .aws
+        .as_ref()
+        .ok_or_else(|| TrainctlError::Config(ConfigError::MissingField("aws".to_string())))?;
+
+    let is_gpu = instance_type.starts_with('g') || instance_type.starts_with('p');
+    let ami_id = match (&preset_config.ami_id, &preset_config.ami_filter) {
+        (Some(ami_id), None) => ami_id.clone(),
+        (_, Some(filter)) => {
+            find_deep_learning_ami(
+                ec2,
+                &aws_cfg.region,
+                Some(filter),
+                aws_cfg.ami_cache_ttl_secs,
+                false,
+            )
+            .await?
+        }
+        (None, None) if is_gpu => find_deep_learning_ami(
+            ec2,
+            &aws_cfg.region,
+            None,
+            aws_cfg.ami_cache_ttl_secs,
+            false,
+        )
+        .await
+        .unwrap_or_else(|_| aws_cfg.default_ami.clone()),
+        (None, None) => aws_cfg.default_ami.clone(),
+    };
+    let root_volume_size = preset_config
+        .root_volume_size
+        .unwrap_or(if is_gpu { 50 } else { 30 });
+
+    // No idle autostop: the group would replace a stopped worker
+    let bootstrap = config.bootstrap.merged_with(&preset_config.bootstrap);
+    let user_data = render_user_data(name, false, &bootstrap)?;
+
+    let user_id = get_user_id(config);
+    let mut tags = vec![
+        Tag::builder().key("Name").value(group_name(name)).build(),
+        Tag::builder().key(FLEET_TAG).value(name).build(),
+        Tag::builder().key("runctl:project").value(name).build(),
+        Tag::builder().key("runctl:user").value(user_id).build(),
+        Tag::builder().key("CreatedBy").value("runctl").build(),
+    ];
+    if let Some(preset) = preset {
+        tags.push(Tag::builder().key(PRESET_TAG).value(preset).build());
+    }
+
+    Ok(RequestLaunchTemplateData::builder()
+        .image_id(&ami_id)
+        .instance_type(Ec2InstanceType::from(instance_type))
+        .set_key_name(preset_config.key_name.clone())
+        .set_security_group_ids(preset_config.security_group.clone().map(|sg| vec![sg]))
+        .set_iam_instance_profile(preset_config.iam_instance_profile.as_ref().map(|profile| {
+            LaunchTemplateIamInstanceProfileSpecificationRequest::builder()
+                .name(profile)
+                .build()
+        }))
+        .block_device_mappings(
+            LaunchTemplateBlockDeviceMappingRequest::builder()
+                .device_name(root_device_name(ec2, &ami_id).await?)
+                .ebs(
+                    LaunchTemplateEbsBlockDeviceRequest::builder()
+                        .volume_size(root_volume_size)
+                        .volume_type(VolumeType::Gp3)
+                        .delete_on_termination(true)
+                        .build(),
+                )
+                .build(),
+        )
+        .user_data(base64::engine::general_purpose::STANDARD.encode(user_data))
+        .tag_specifications(
+            LaunchTemplateTagSpecificationRequest::builder()
+                .resource_type(ResourceType::Instance)
+                .set_tags(Some(tags))
+                .build(),
+        )
+        .set_instance_market_options(spot.then(|| {
+            LaunchTemplateInstanceMarketOptionsRequest::builder()
+                .market_type(MarketType::Spot)
+                .build()
+        }))
+        .build())
+}
+
+async fn availability_zones(ec2: &Ec2Client) -> Result<Vec<String>> {
+    let response = ec2
+        .describe_availability_zones()
+        .send()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to list availability zones: {}", e)))?;
+    Ok(response
+        .availability_zones()
+        .iter()
+        .filter_map(|zone| zone.zone_name().map(str::to_string))
+        .collect())
+}
+
+async fn describe_group(
+    asg: &AsgClient,
+    name: &str,
+) -> Result<Option<aws_sdk_autoscaling::types::AutoScalingGroup>> {
+    let response = asg
+        .describe_auto_scaling_groups()
+        .auto_scaling_group_names(group_name(name))
+        .send()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to describe fleet: {}", e)))?;
+    Ok(response.auto_scaling_groups().first().cloned())
+}
+
+/// Every Auto Scaling group runctl created
+async fn list_groups(asg: &AsgClient) -> Result<Vec<aws_sdk_autoscaling::types::AutoScalingGroup>> {
+    let mut groups = Vec::new();
+    let mut next_token = None;
+    loop {
+        let response = asg
+            .describe_auto_scaling_groups()
+            .filters(
+                AsgFilter::builder()
+                    .name("tag-key")
+                    .values(FLEET_TAG)
+                    .build(),
+            )
+            .set_next_token(next_token)
+            .send()
+            .await
+            .map_err(|e| TrainctlError::Aws(format!("Failed to list fleets: {}", e)))?;
+        groups.extend(response.auto_scaling_groups().iter().cloned());
+        next_token = response.next_token().map(str::to_string);
+        if next_token.is_none() {
+            return Ok(groups);
+        }
+    }
+}
+
+/// Status of the most recent instance refresh, e.g. "InProgress (40%)"
+async fn latest_refresh(asg: &AsgClient, name: &str) -> Option<String> {
+    let response = asg
+        .describe_instance_refreshes()
+        .auto_scaling_group_name(group_name(name))
+        .max_records(1)
+        .send()
+        .await
+        .ok()?;
+    let refresh = response.instance_refreshes().first()?;
+    let status = refresh.status()?.as_str().to_string();
+    Some(match refresh.percentage_complete() {
+        Some(percent) if status == "InProgress" => format!("{} ({}%)", status, percent),
+        _ => status,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn worker(lifecycle: &str, healthy: bool, busy: bool) -> Worker {
+        Worker {
+            instance_id: "i-0abc".to_string(),
+            instance_type: "g5.xlarge".to_string(),
+            lifecycle: lifecycle.to_string(),
+            healthy,
+            busy,
+        }
+    }
+
+    #[test]
+    fn test_only_healthy_unprotected_in_service_workers_are_idle() {
+        assert!(worker("InService", true, false).is_idle());
+        assert!(!worker("InService", true, true).is_idle());
+        assert!(!worker("InService", false, false).is_idle());
+        assert!(!worker("Pending", true, false).is_idle());
+        assert!(!worker("Terminating", true, false).is_idle());
+    }
+
+    #[test]
+    fn test_validate_capacity() {
+        assert!(validate_capacity(2, 0, 4).is_ok());
+        assert!(validate_capacity(0, 0, 0).is_ok());
+        assert!(validate_capacity(5, 0, 4).is_err());
+        assert!(validate_capacity(1, 2, 4).is_err());
+        assert!(validate_capacity(0, -1, 4).is_err());
+    }
+
+    #[test]
+    fn test_fleet_names() {
+        assert!(fleet_name_arg("gpu-workers_2").is_ok());
+        assert!(fleet_name_arg("").is_err());
+        assert!(fleet_name_arg("a b").is_err());
+        assert_eq!(group_name("workers"), "runctl-fleet-workers");
+    }
+}
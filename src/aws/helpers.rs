@@ -20,6 +20,7 @@
 //! - `get_user_id()`: Returns user ID with fallback chain (config → env → "unknown")
 //! - `get_project_name()`: Derives project name from config or current directory
 
+use crate::aws::ssh_key::KEY_NAME_TAG;
 use crate::config::Config;
 use crate::error::{Result, TrainctlError};
 use crate::provider::{normalize_state, ResourceStatus};
@@ -28,26 +29,7 @@ use chrono::{DateTime, Utc};
 
 use super::types;
 
-/// Get user identifier for tagging
-pub(crate) fn get_user_id(config: &Config) -> String {
-    // Try config first
-    if let Some(aws_cfg) = &config.aws {
-        if let Some(user_id) = &aws_cfg.user_id {
-            return user_id.clone();
-        }
-    }
-
-    // Auto-detect from username
-    if let Ok(username) = std::env::var("USER") {
-        return username;
-    }
-    if let Ok(username) = std::env::var("USERNAME") {
-        return username;
-    }
-
-    // Fallback
-    "unknown".to_string()
-}
+pub(crate) use crate::utils::get_user_id;
 
 /// Convert EC2 instance to ResourceStatus for ResourceTracker
 ///
@@ -72,7 +54,7 @@ pub(crate) fn ec2_instance_to_resource_status(
             .unwrap_or("unknown"),
     );
 
-    let tags: Vec<(String, String)> = instance
+    let mut tags: Vec<(String, String)> = instance
         .tags()
         .iter()
         .filter_map(|tag| {
@@ -81,6 +63,10 @@ pub(crate) fn ec2_instance_to_resource_status(
                 .map(|(k, v)| (k.to_string(), v.to_string()))
         })
         .collect();
+    // Recorded so SSH commands can find the private key from the tracker alone
+    if let Some(key_name) = instance.key_name() {
+        tags.push((KEY_NAME_TAG.to_string(), key_name.to_string()));
+    }
 
     let instance_type = instance.instance_type().map(|t| t.as_str().to_string());
     let launch_time = instance
@@ -88,9 +74,9 @@ pub(crate) fn ec2_instance_to_resource_status(
         .map(|lt| DateTime::<Utc>::from_timestamp(lt.secs(), 0).unwrap_or_else(Utc::now));
 
     let public_ip = instance.public_ip_address().map(|s| s.to_string());
+    let public_ipv6 = instance_ipv6(instance).map(str::to_string);
 
-    let cost_per_hour =
-        crate::utils::get_instance_cost(instance_type.as_deref().unwrap_or("unknown"));
+    let cost_per_hour = crate::aws_pricing::instance_hourly_price(instance);
 
     Ok(ResourceStatus {
         id: instance_id.to_string(),
@@ -103,10 +89,35 @@ pub(crate) fn ec2_instance_to_resource_status(
         launch_time,
         cost_per_hour,
         public_ip,
+        public_ipv6,
         tags,
     })
 }
 
+/// The instance's IPv6 address, from its primary network interface first
+pub(crate) fn instance_ipv6(instance: &Ec2Instance) -> Option<&str> {
+    let mut interfaces: Vec<_> = instance.network_interfaces().iter().collect();
+    interfaces.sort_by_key(|ni| {
+        ni.attachment()
+            .and_then(|a| a.device_index())
+            .unwrap_or(i32::MAX)
+    });
+    interfaces
+        .into_iter()
+        .flat_map(|ni| ni.ipv6_addresses())
+        .find_map(|address| address.ipv6_address())
+}
+
+/// Address to reach the instance at from outside its VPC
+///
+/// The public IPv4 address, or the IPv6 address on instances without one
+/// (IPv6-only subnets). EC2 IPv6 addresses are globally routable.
+pub(crate) fn public_address(instance: &Ec2Instance) -> Option<&str> {
+    instance
+        .public_ip_address()
+        .or_else(|| instance_ipv6(instance))
+}
+
 /// Find an instance in an EC2 DescribeInstances response
 ///
 /// This is a common pattern used throughout the AWS module to find a specific
@@ -122,6 +133,74 @@ pub(crate) fn find_instance_in_response<'a>(
         .find(|i| i.instance_id().map(|id| id == instance_id).unwrap_or(false))
 }
 
+/// When the instance entered its current state, from `StateTransitionReason`
+///
+/// EC2 reports stops as e.g. "User initiated (2024-05-01 10:11:12 GMT)".
+pub(crate) fn state_transition_time(instance: &Ec2Instance) -> Option<DateTime<Utc>> {
+    let reason = instance.state_transition_reason()?;
+    let start = reason.find('(')? + 1;
+    let end = start + reason[start..].find(" GMT)")?;
+    chrono::NaiveDateTime::parse_from_str(&reason[start..end], "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|t| t.and_utc())
+}
+
+/// Reconcile one described instance with the ResourceTracker
+///
+/// Applies stops and restarts made outside runctl to the tracked cost.
+pub(crate) async fn reconcile_instance(
+    tracker: &crate::resource_tracking::ResourceTracker,
+    instance: &Ec2Instance,
+) -> Option<crate::resource_tracking::Reconciliation> {
+    reconcile_account_instance(tracker, instance, None).await
+}
+
+/// Reconcile an instance found through an `[accounts]` entry
+///
+/// Like `reconcile_instance`, and records the account in the tracker's
+/// `runctl:account` tag so account budgets and cost reports can group by it.
+pub(crate) async fn reconcile_account_instance(
+    tracker: &crate::resource_tracking::ResourceTracker,
+    instance: &Ec2Instance,
+    account: Option<&str>,
+) -> Option<crate::resource_tracking::Reconciliation> {
+    let instance_id = instance.instance_id()?;
+    let mut live = match ec2_instance_to_resource_status(instance, instance_id) {
+        Ok(live) => live,
+        Err(e) => {
+            tracing::info!(
+                "Failed to convert instance {} to ResourceStatus: {}",
+                instance_id,
+                e
+            );
+            return None;
+        }
+    };
+    if let Some(account) = account {
+        live.tags.push((
+            crate::resource_tracking::budget::ACCOUNT_TAG.to_string(),
+            account.to_string(),
+        ));
+    }
+    match tracker
+        .reconcile(live, state_transition_time(instance))
+        .await
+    {
+        Ok(outcome) => {
+            tracing::debug!(
+                "Reconciled {} with ResourceTracker: {:?}",
+                instance_id,
+                outcome
+            );
+            Some(outcome)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to update {} in ResourceTracker: {}", instance_id, e);
+            None
+        }
+    }
+}
+
 /// Update resource status in ResourceTracker
 pub(crate) async fn update_resource_status_in_tracker(
     instance_id: &str,
@@ -136,71 +215,13 @@ pub(crate) async fn update_resource_status_in_tracker(
             .await
         {
             if let Some(instance) = find_instance_in_response(&response, instance_id) {
-                if let Ok(resource_status) = ec2_instance_to_resource_status(instance, instance_id)
-                {
-                    // Try to update existing resource, or register if new
-                    let instance_id_string = instance_id.to_string();
-                    if tracker.exists(&instance_id_string).await {
-                        // Use update_state to update the resource state
-                        if let Err(e) = tracker
-                            .update_state(&instance_id_string, resource_status.state)
-                            .await
-                        {
-                            tracing::warn!("Failed to update resource state: {}", e);
-                        }
-                    } else if let Err(e) = tracker.register(resource_status).await {
-                        tracing::warn!("Failed to update resource in tracker: {}", e);
-                    } else {
-                        tracing::info!(
-                            "Updated instance {} status in ResourceTracker",
-                            instance_id
-                        );
-                    }
-                }
+                reconcile_instance(tracker, instance).await;
             }
         }
     }
 }
 
-/// Get project name, deriving from current directory if not provided
-pub fn get_project_name(provided: Option<String>, config: &Config) -> String {
-    // Use provided value if given
-    if let Some(name) = provided {
-        return name;
-    }
-
-    // Try config
-    if let Some(aws_cfg) = &config.aws {
-        if let Some(project) = &aws_cfg.default_project_name {
-            return project.clone();
-        }
-    }
-
-    // Derive from current directory
-    if let Ok(current_dir) = std::env::current_dir() {
-        if let Some(dir_name) = current_dir.file_name() {
-            if let Some(name_str) = dir_name.to_str() {
-                // Sanitize directory name for use as project name
-                let sanitized = name_str
-                    .chars()
-                    .map(|c| {
-                        if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
-                            c
-                        } else {
-                            '-'
-                        }
-                    })
-                    .collect::<String>();
-                if !sanitized.is_empty() {
-                    return sanitized;
-                }
-            }
-        }
-    }
-
-    // Final fallback
-    "runctl-project".to_string()
-}
+pub use crate::utils::get_project_name;
 
 /// Validate project name if provided, with helpful error messages
 #[allow(dead_code)] // Reserved for future validation
@@ -247,9 +268,65 @@ pub(crate) async fn get_instance_info_json(
         instance_id: instance_id.to_string(),
         instance_type: instance_type.to_string(),
         public_ip: instance.public_ip_address().map(|s| s.to_string()),
+        public_ipv6: instance_ipv6(instance).map(str::to_string),
         private_ip: instance.private_ip_address().map(|s| s.to_string()),
         state,
         cost_per_hour,
         message: format!("Instance {} created successfully", instance_id),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_ec2::types::{
+        InstanceIpv6Address, InstanceNetworkInterface, InstanceNetworkInterfaceAttachment,
+    };
+
+    fn interface(device_index: i32, ipv6: &str) -> InstanceNetworkInterface {
+        InstanceNetworkInterface::builder()
+            .attachment(
+                InstanceNetworkInterfaceAttachment::builder()
+                    .device_index(device_index)
+                    .build(),
+            )
+            .ipv6_addresses(InstanceIpv6Address::builder().ipv6_address(ipv6).build())
+            .build()
+    }
+
+    #[test]
+    fn test_public_address_prefers_ipv4() {
+        let ipv6_only = Ec2Instance::builder()
+            .network_interfaces(interface(1, "2600:1f18::2"))
+            .network_interfaces(interface(0, "2600:1f18::1"))
+            .build();
+        assert_eq!(instance_ipv6(&ipv6_only), Some("2600:1f18::1"));
+        assert_eq!(public_address(&ipv6_only), Some("2600:1f18::1"));
+
+        let dual_stack = Ec2Instance::builder()
+            .public_ip_address("3.3.3.3")
+            .network_interfaces(interface(0, "2600:1f18::1"))
+            .build();
+        assert_eq!(public_address(&dual_stack), Some("3.3.3.3"));
+        assert_eq!(public_address(&Ec2Instance::builder().build()), None);
+    }
+
+    #[test]
+    fn test_state_transition_time() {
+        let stopped = Ec2Instance::builder()
+            .state_transition_reason("User initiated (2024-05-01 10:11:12 GMT)")
+            .build();
+        assert_eq!(
+            state_transition_time(&stopped),
+            Some(
+                chrono::NaiveDate::from_ymd_opt(2024, 5, 1)
+                    .unwrap()
+                    .and_hms_opt(10, 11, 12)
+                    .unwrap()
+                    .and_utc()
+            )
+        );
+        let running = Ec2Instance::builder().state_transition_reason("").build();
+        assert_eq!(state_transition_time(&running), None);
+    }
+}
@@ -0,0 +1,388 @@
+//! Idempotent instance creation
+//!
+//! Re-running `aws create` after a flaky network error should not provision a
+//! second instance. Two layers guard against that:
+//!
+//! - **Client tokens**: every launch request carries an EC2 `ClientToken`
+//!   derived from the launch parameters and a time bucket, so an identical
+//!   retry within the same bucket returns the original instance instead of
+//!   launching another. `--client-token` pins the token explicitly.
+//! - **Duplicate detection**: before launching, look for a pending/running
+//!   instance with the same project and launch fingerprint created recently
+//!   and offer to reuse it. This catches retries that straddle a bucket
+//!   boundary or that changed nothing but the user-data.
+//!
+//! The fingerprint is stored in the `runctl:launch-fingerprint` tag. Hashing
+//! uses FNV-1a so the value is stable across builds and platforms.
+
+use crate::aws::types::CreateInstanceOptions;
+use crate::error::{Result, TrainctlError};
+use aws_sdk_ec2::types::Filter;
+use aws_sdk_ec2::Client as Ec2Client;
+use chrono::{DateTime, Utc};
+
+/// Tag holding the launch fingerprint of instances created by runctl
+pub(crate) const FINGERPRINT_TAG: &str = "runctl:launch-fingerprint";
+
+/// Tag holding the preset an instance was created from
+pub(crate) const PRESET_TAG: &str = "runctl:preset";
+
+/// Width of the time bucket mixed into derived client tokens
+const CLIENT_TOKEN_WINDOW_SECS: i64 = 600;
+
+/// How far back duplicate detection looks for matching instances
+const DUPLICATE_WINDOW_MINS: i64 = 30;
+
+/// EC2 limits client tokens to 64 ASCII characters
+const MAX_CLIENT_TOKEN_LEN: usize = 64;
+
+fn fnv1a(parts: &[&str]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        // Separator byte keeps ("ab", "c") distinct from ("a", "bc")
+        for byte in part.bytes().chain(std::iter::once(0xff)) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// Stable fingerprint of the parameters that define "the same instance"
+pub(crate) fn launch_fingerprint(options: &CreateInstanceOptions) -> String {
+    let root = options.root_volume_size.map(|s| s.to_string());
+    let data = options.data_volume_size.map(|s| s.to_string());
+    let purchase = if options.use_spot {
+        "spot"
+    } else {
+        "on-demand"
+    };
+    let mut parts = vec![
+        options.project_name.as_str(),
+        options.preset.as_deref().unwrap_or(""),
+        options.instance_type.as_str(),
+        purchase,
+        options.spot_max_price.as_deref().unwrap_or(""),
+        options.key_name.as_deref().unwrap_or(""),
+        options.security_group.as_deref().unwrap_or(""),
+        options.ami_id.as_deref().unwrap_or(""),
+        root.as_deref().unwrap_or(""),
+        data.as_deref().unwrap_or(""),
+        options.iam_instance_profile.as_deref().unwrap_or(""),
+    ];
+    // Appended only when set, so fingerprints from before zones were
+    // selectable still match
+    if let Some(zone) = &options.availability_zone {
+        parts.push(zone);
+    }
+    format!("{:016x}", fnv1a(&parts))
+}
+
+/// Derive the client token for one launch request
+///
+/// Includes the resolved AMI and user data so a token is never reused with
+/// different parameters (EC2 rejects that with `IdempotentParameterMismatch`),
+/// and the purchase option so a spot request and its on-demand fallback don't
+/// share a token.
+pub(crate) fn derive_client_token(
+    fingerprint: &str,
+    purchase: &str,
+    ami_id: &str,
+    user_data: &str,
+    now: DateTime<Utc>,
+) -> String {
+    let bucket = now.timestamp().div_euclid(CLIENT_TOKEN_WINDOW_SECS);
+    let hash = fnv1a(&[fingerprint, purchase, ami_id, user_data]);
+    format!("runctl-{:016x}-{}", hash, bucket)
+}
+
+/// Check a user-supplied client token against EC2's limits
+pub(crate) fn validate_client_token(token: &str) -> Result<()> {
+    if token.is_empty() || token.len() > MAX_CLIENT_TOKEN_LEN || !token.is_ascii() {
+        return Err(TrainctlError::Validation {
+            field: "client_token".to_string(),
+            reason: format!(
+                "Client token must be 1-{} ASCII characters, got {} characters",
+                MAX_CLIENT_TOKEN_LEN,
+                token.chars().count()
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Token for a launch request, honouring an explicit `--client-token`
+///
+/// An explicit token is used as-is for both the spot request and the
+/// on-demand fallback; EC2 scopes tokens per API action. `allow_duplicate`
+/// without an explicit token disables tokens entirely.
+pub(crate) fn client_token_for(
+    options: &CreateInstanceOptions,
+    purchase: &str,
+    ami_id: &str,
+    user_data: &str,
+) -> Option<String> {
+    if let Some(token) = &options.client_token {
+        return Some(token.clone());
+    }
+    if options.allow_duplicate {
+        return None;
+    }
+    Some(derive_client_token(
+        &launch_fingerprint(options),
+        purchase,
+        ami_id,
+        user_data,
+        Utc::now(),
+    ))
+}
+
+/// An existing instance that matches the requested launch
+#[derive(Debug, Clone)]
+pub(crate) struct DuplicateInstance {
+    pub instance_id: String,
+    pub state: String,
+    pub launch_time: Option<DateTime<Utc>>,
+}
+
+impl DuplicateInstance {
+    /// Human-readable age, e.g. "3 minutes ago"
+    pub fn age(&self, now: DateTime<Utc>) -> String {
+        match self.launch_time {
+            Some(lt) => {
+                let mins = (now - lt).num_minutes().max(0);
+                match mins {
+                    0 => "less than a minute ago".to_string(),
+                    1 => "1 minute ago".to_string(),
+                    n => format!("{} minutes ago", n),
+                }
+            }
+            None => "recently".to_string(),
+        }
+    }
+}
+
+/// Find the newest pending/running instance with the same fingerprint
+pub(crate) async fn find_recent_duplicate(
+    client: &Ec2Client,
+    options: &CreateInstanceOptions,
+) -> Result<Option<DuplicateInstance>> {
+    let response = client
+        .describe_instances()
+        .filters(
+            Filter::builder()
+                .name("instance-state-name")
+                .values("pending")
+                .values("running")
+                .build(),
+        )
+        .filters(
+            Filter::builder()
+                .name("tag:runctl:project")
+                .values(&options.project_name)
+                .build(),
+        )
+        .filters(
+            Filter::builder()
+                .name(format!("tag:{}", FINGERPRINT_TAG))
+                .values(launch_fingerprint(options))
+                .build(),
+        )
+        .send()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to describe instances: {}", e)))?;
+
+    let candidates = response
+        .reservations()
+        .iter()
+        .flat_map(|r| r.instances())
+        .filter_map(|inst| {
+            Some(DuplicateInstance {
+                instance_id: inst.instance_id()?.to_string(),
+                state: inst
+                    .state()
+                    .and_then(|s| s.name())
+                    .map(|s| s.as_str().to_string())
+                    .unwrap_or_else(|| "unknown".to_string()),
+                launch_time: inst
+                    .launch_time()
+                    .and_then(|t| DateTime::from_timestamp(t.secs(), 0)),
+            })
+        })
+        .collect();
+    Ok(newest_within_window(candidates, Utc::now()))
+}
+
+/// Pick the newest candidate launched within the duplicate window
+fn newest_within_window(
+    candidates: Vec<DuplicateInstance>,
+    now: DateTime<Utc>,
+) -> Option<DuplicateInstance> {
+    let cutoff = now - chrono::Duration::minutes(DUPLICATE_WINDOW_MINS);
+    candidates
+        .into_iter()
+        .filter(|c| c.launch_time.is_some_and(|lt| lt >= cutoff))
+        .max_by_key(|c| c.launch_time)
+}
+
+/// Ask whether to reuse `duplicate` instead of launching a new instance
+///
+/// Only prompts on an interactive terminal with text output; otherwise the
+/// duplicate is reported and a new instance is created, since the answer
+/// defaults to no.
+pub(crate) fn confirm_reuse(
+    duplicate: &DuplicateInstance,
+    options: &CreateInstanceOptions,
+    output_format: &str,
+) -> Result<bool> {
+    let what = match &options.preset {
+        Some(preset) => format!("identical preset '{}'", preset),
+        None => format!("identical settings ({})", options.instance_type),
+    };
+    let message = format!(
+        "An instance for project {} with {} was created {}: {} ({})",
+        options.project_name,
+        what,
+        duplicate.age(Utc::now()),
+        duplicate.instance_id,
+        duplicate.state
+    );
+
+    if output_format != "text" || !crate::prompt::can_prompt() {
+        tracing::warn!(
+            "{}; creating a new one (use --allow-duplicate to skip this check)",
+            message
+        );
+        return Ok(false);
+    }
+
+    crate::prompt::confirm(&format!("{}; reuse it?", message))
+}
+
+/// Whether an instance returned for our client token was launched earlier
+///
+/// EC2 answers an idempotent retry with the original instance, which runctl
+/// has already tagged. Untagged means this call did the launch.
+pub(crate) async fn was_previously_launched(client: &Ec2Client, instance_id: &str) -> bool {
+    let Ok(response) = client
+        .describe_instances()
+        .instance_ids(instance_id)
+        .send()
+        .await
+    else {
+        return false;
+    };
+    response
+        .reservations()
+        .iter()
+        .flat_map(|r| r.instances())
+        .flat_map(|inst| inst.tags())
+        .any(|tag| tag.key() == Some("runctl:created"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> CreateInstanceOptions {
+        CreateInstanceOptions {
+            instance_type: "g5.xlarge".to_string(),
+            use_spot: true,
+            spot_max_price: None,
+            no_fallback: false,
+            availability_zone: None,
+            key_name: None,
+            security_group: None,
+            ami_id: None,
+            root_volume_size: None,
+            data_volume_size: Some(100),
+            project_name: "vision".to_string(),
+            iam_instance_profile: Some("runctl-ssm-profile".to_string()),
+            wait: false,
+            preset: Some("g5-dev".to_string()),
+            client_token: None,
+            allow_duplicate: false,
+            refresh_ami: false,
+            ami_filter: None,
+            ssh_user: None,
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_tracks_launch_parameters() {
+        let base = launch_fingerprint(&options());
+        assert_eq!(base, launch_fingerprint(&options()));
+        assert_eq!(base.len(), 16);
+
+        let mut other = options();
+        other.instance_type = "g5.2xlarge".to_string();
+        assert_ne!(base, launch_fingerprint(&other));
+
+        let mut other = options();
+        other.project_name = "audio".to_string();
+        assert_ne!(base, launch_fingerprint(&other));
+
+        let mut other = options();
+        other.availability_zone = Some("us-east-1b".to_string());
+        assert_ne!(base, launch_fingerprint(&other));
+
+        // Waiting doesn't change what gets launched
+        let mut other = options();
+        other.wait = true;
+        assert_eq!(base, launch_fingerprint(&other));
+    }
+
+    #[test]
+    fn test_client_token_buckets() {
+        let t0 = DateTime::from_timestamp(1_700_000_400, 0).unwrap();
+        let token = derive_client_token("abc", "spot", "ami-1", "#!/bin/bash", t0);
+        assert!(token.len() <= MAX_CLIENT_TOKEN_LEN);
+        assert!(validate_client_token(&token).is_ok());
+
+        let same_bucket = t0 + chrono::Duration::seconds(CLIENT_TOKEN_WINDOW_SECS - 1);
+        assert_eq!(
+            token,
+            derive_client_token("abc", "spot", "ami-1", "#!/bin/bash", same_bucket)
+        );
+        let next_bucket = t0 + chrono::Duration::seconds(CLIENT_TOKEN_WINDOW_SECS);
+        assert_ne!(
+            token,
+            derive_client_token("abc", "spot", "ami-1", "#!/bin/bash", next_bucket)
+        );
+        assert_ne!(
+            token,
+            derive_client_token("abc", "on-demand", "ami-1", "#!/bin/bash", t0)
+        );
+    }
+
+    #[test]
+    fn test_client_token_overrides() {
+        let mut opts = options();
+        opts.allow_duplicate = true;
+        assert!(client_token_for(&opts, "spot", "ami-1", "").is_none());
+        opts.client_token = Some("retry-42".to_string());
+        assert_eq!(
+            client_token_for(&opts, "spot", "ami-1", "").as_deref(),
+            Some("retry-42")
+        );
+
+        assert!(validate_client_token("").is_err());
+        assert!(validate_client_token(&"x".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_newest_within_window() {
+        let now = Utc::now();
+        let dup = |id: &str, mins: i64| DuplicateInstance {
+            instance_id: id.to_string(),
+            state: "running".to_string(),
+            launch_time: Some(now - chrono::Duration::minutes(mins)),
+        };
+        let found =
+            newest_within_window(vec![dup("i-old", 90), dup("i-a", 12), dup("i-b", 3)], now)
+                .unwrap();
+        assert_eq!(found.instance_id, "i-b");
+        assert_eq!(found.age(now), "3 minutes ago");
+        assert!(newest_within_window(vec![dup("i-old", 90)], now).is_none());
+    }
+}
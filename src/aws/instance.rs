@@ -3,17 +3,30 @@
 //! Handles creation, starting, stopping, and termination of EC2 instances.
 //! Includes spot instance support, AMI detection, and user data generation.
 
+use crate::aws::ami::{find_deep_learning_ami, root_device_name};
+use crate::aws::autostop;
+use crate::aws::bootstrap::{
+    bootstrap_status_from_tags, fetch_bootstrap_phase, BootstrapPhase, BootstrapTagStatus,
+};
 use crate::aws::helpers::{
     ec2_instance_to_resource_status, get_instance_info_json, get_user_id,
     update_resource_status_in_tracker,
 };
+use crate::aws::idempotency::{
+    client_token_for, confirm_reuse, find_recent_duplicate, launch_fingerprint,
+    validate_client_token, was_previously_launched, FINGERPRINT_TAG, PRESET_TAG,
+};
+use crate::aws::ssh_user::{user_for_ami, SSH_USER_TAG};
 use crate::aws::types::{
     CreateInstanceOptions, CreateSpotInstanceOptions, StartInstanceResult, StopInstanceResult,
     TerminateInstanceResult,
 };
+use crate::aws::user_data::render_user_data;
 use crate::aws_utils::count_running_instances;
+use crate::checkpoint::coordinate::StopOutcome;
 use crate::config::Config;
 use crate::error::{Result, TrainctlError};
+use crate::events::{self, Event, EventKind};
 use crate::safe_cleanup::{safe_cleanup, CleanupSafety};
 use aws_sdk_ec2::types::InstanceType as Ec2InstanceType;
 use aws_sdk_ec2::Client as Ec2Client;
@@ -21,7 +34,7 @@ use aws_sdk_s3::Client as S3Client;
 use aws_sdk_ssm::Client as SsmClient;
 use base64::Engine;
 use chrono::Utc;
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::ProgressBar;
 use std::time::Duration;
 use tracing::{info, warn};
 
@@ -57,6 +70,8 @@ pub async fn create_instance_and_get_id(
         });
     }
 
+    check_launch_budget(&options, config, true).await?;
+
     // Get AMI (simplified - reuse logic from create_instance)
     let final_ami = if let Some(ami) = &options.ami_id {
         ami.clone()
@@ -64,8 +79,15 @@ pub async fn create_instance_and_get_id(
         let is_gpu = options.instance_type.starts_with("g")
             || options.instance_type.starts_with("p")
             || options.instance_type.contains("gpu");
-        if is_gpu {
-            find_deep_learning_ami(&client, &aws_cfg.region).await?
+        if is_gpu || options.ami_filter.is_some() {
+            find_deep_learning_ami(
+                &client,
+                &aws_cfg.region,
+                options.ami_filter.as_ref(),
+                aws_cfg.ami_cache_ttl_secs,
+                options.refresh_ami,
+            )
+            .await?
         } else {
             // Use Amazon Linux 2023
             "ami-0c55b159cbfafe1f0".to_string()
@@ -77,16 +99,19 @@ pub async fn create_instance_and_get_id(
         // Create spot instance
         let spot_options = CreateSpotInstanceOptions {
             instance_type: options.instance_type.clone(),
-            ami_id: final_ami,
+            ami_id: final_ami.clone(),
             user_data: String::new(), // Simplified
-            max_price: options.spot_max_price,
+            max_price: options.spot_max_price.clone(),
+            availability_zone: options.availability_zone.clone(),
             key_name: options.key_name.clone(),
             security_group: options.security_group.clone(),
             root_volume_size: options.root_volume_size.unwrap_or(30),
             iam_instance_profile: options.iam_instance_profile.clone(),
+            client_token: client_token_for(&options, "spot", &final_ami, ""),
         };
         create_spot_instance(&client, spot_options, "text").await?
     } else {
+        let client_token = client_token_for(&options, "on-demand", &final_ami, "");
         create_ondemand_instance(
             &client,
             &options.instance_type,
@@ -94,14 +119,18 @@ pub async fn create_instance_and_get_id(
             "",
             options.key_name.as_deref(),
             options.security_group.as_deref(),
+            options.availability_zone.as_deref(),
             options.root_volume_size.unwrap_or(30),
             options.iam_instance_profile.as_deref(),
+            client_token.as_deref(),
         )
         .await?
     };
 
-    // Tag instance
-    if let Err(e) = tag_instance(&client, &instance_id, &options.project_name, config).await {
+    // Tag instance (an idempotent retry returns the original, already tagged)
+    if was_previously_launched(&client, &instance_id).await {
+        info!("Reusing instance {} from an earlier attempt", instance_id);
+    } else if let Err(e) = tag_instance(&client, &instance_id, &final_ami, &options, config).await {
         warn!("Failed to tag instance {}: {}", instance_id, e);
         // Continue - instance is created, tagging is non-critical
     }
@@ -120,6 +149,47 @@ pub async fn create_instance_and_get_id(
     Ok(instance_id)
 }
 
+/// Refuse or warn about a launch that would exceed the project's `[budget]`
+async fn check_launch_budget(
+    options: &CreateInstanceOptions,
+    config: &Config,
+    quiet: bool,
+) -> Result<()> {
+    let Some(tracker) = &config.resource_tracker else {
+        return Ok(());
+    };
+    let Some(limits) = config.budget.limits_for(&options.project_name) else {
+        return Ok(());
+    };
+    let exceeded = tracker
+        .check_launch_budget(
+            &config.budget,
+            &options.project_name,
+            crate::aws_pricing::hourly_price(
+                &options.instance_type,
+                config.aws.as_ref().map(|aws| aws.region.as_str()),
+                None,
+            ),
+        )
+        .await?;
+    if !limits.actions.contains(&crate::config::BudgetAction::Warn) {
+        return Ok(());
+    }
+    for breach in exceeded {
+        warn!(
+            "Project '{}' is over budget: {}",
+            options.project_name, breach
+        );
+        if !quiet {
+            println!(
+                "WARNING: Project '{}' is over budget: {}",
+                options.project_name, breach
+            );
+        }
+    }
+    Ok(())
+}
+
 /// Create an EC2 instance with the specified options
 ///
 /// Creates a new EC2 instance for ML training with automatic configuration:
@@ -178,10 +248,48 @@ pub async fn create_instance(
         println!("  Use 'runctl resources list' to review running instances.");
     }
 
+    check_launch_budget(&options, config, output_format == "json").await?;
+
     info!(
         "Creating EC2 instance: type={}, spot={}",
         options.instance_type, options.use_spot
     );
+    crate::progress::emit(
+        "create.request",
+        format!(
+            "Requesting {} {} instance",
+            if options.use_spot {
+                "spot"
+            } else {
+                "on-demand"
+            },
+            options.instance_type
+        ),
+    );
+
+    if let Some(token) = &options.client_token {
+        validate_client_token(token)?;
+    }
+
+    // Offer to reuse a matching instance from a recent (possibly failed-looking) run
+    if !options.allow_duplicate {
+        match find_recent_duplicate(&client, &options).await {
+            Ok(Some(duplicate)) => {
+                if confirm_reuse(&duplicate, &options, output_format)? {
+                    return report_reused_instance(
+                        &client,
+                        &duplicate.instance_id,
+                        &options,
+                        aws_config,
+                        output_format,
+                    )
+                    .await;
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Duplicate instance check failed: {}", e),
+        }
+    }
 
     // Validate IAM instance profile if provided
     if let Some(ref profile_name) = options.iam_instance_profile {
@@ -206,7 +314,7 @@ pub async fn create_instance(
         && options.key_name.is_none()
         && output_format != "json"
     {
-        println!("⚠️  WARNING: No IAM instance profile or SSH key provided.");
+        println!("WARNING: No IAM instance profile or SSH key provided.");
         println!("   Training commands will fail without SSM or SSH access.");
         println!("   Recommended: Setup SSM (one-time): ./scripts/setup-ssm-role.sh");
         println!("   Then use: --iam-instance-profile runctl-ssm-profile");
@@ -223,7 +331,7 @@ pub async fn create_instance(
             .is_some();
 
         if !has_s3_bucket && output_format != "json" {
-            println!("⚠️  WARNING: IAM instance profile provided but S3 bucket not configured.");
+            println!("WARNING: IAM instance profile provided but S3 bucket not configured.");
             println!("   SSM-based code sync requires an S3 bucket for temporary storage.");
             println!("   To resolve:");
             println!("     1. Add S3 bucket to .runctl.toml:");
@@ -254,7 +362,7 @@ pub async fn create_instance(
                         let error_msg = format!("{}", e);
                         if output_format != "json" {
                             println!(
-                                "⚠️  WARNING: S3 bucket '{}' validation failed: {}",
+                                "WARNING: S3 bucket '{}' validation failed: {}",
                                 bucket_name, error_msg
                             );
                             println!("   SSM code sync may fail if bucket is not accessible.");
@@ -325,9 +433,29 @@ pub async fn create_instance(
             || instance_type_lower.starts_with("p")
             || instance_type_lower.contains("gpu");
 
-        if is_gpu {
+        if let Some(filter) = &options.ami_filter {
+            // An explicit filter never falls back to an unconstrained AMI
+            let ami = find_deep_learning_ami(
+                &client,
+                &aws_cfg.region,
+                Some(filter),
+                aws_cfg.ami_cache_ttl_secs,
+                options.refresh_ami,
+            )
+            .await?;
+            println!("   Using Deep Learning AMI: {} ({})", ami, filter);
+            ami
+        } else if is_gpu {
             // Try to find Deep Learning AMI
-            match find_deep_learning_ami(&client, &aws_cfg.region).await {
+            match find_deep_learning_ami(
+                &client,
+                &aws_cfg.region,
+                None,
+                aws_cfg.ami_cache_ttl_secs,
+                options.refresh_ami,
+            )
+            .await
+            {
                 Ok(ami) => {
                     println!("   Using Deep Learning AMI: {}", ami);
                     ami
@@ -353,7 +481,18 @@ pub async fn create_instance(
     });
 
     // Generate user data script
-    let user_data = generate_user_data(&options.project_name, options.data_volume_size.is_some());
+    let mut user_data = render_user_data(
+        &options.project_name,
+        options.data_volume_size.is_some(),
+        &config.bootstrap,
+    )?;
+    let autostop_minutes = autostop::configured_idle_minutes(aws_cfg);
+    if let Some(minutes) = autostop_minutes {
+        user_data.push_str(&autostop::user_data_snippet(
+            minutes,
+            autostop::DEFAULT_GPU_BUSY_PERCENT,
+        ));
+    }
 
     // Try spot instance first if requested
     if options.use_spot {
@@ -362,32 +501,60 @@ pub async fn create_instance(
             ami_id: final_ami.clone(),
             user_data: user_data.clone(),
             max_price: options.spot_max_price.clone(),
+            availability_zone: options.availability_zone.clone(),
             key_name: options.key_name.clone(),
             security_group: options.security_group.clone(),
             root_volume_size: root_size,
             iam_instance_profile: options.iam_instance_profile.clone(),
+            client_token: client_token_for(&options, "spot", &final_ami, &user_data),
         };
         match create_spot_instance(&client, spot_options, output_format).await {
             Ok(instance_id) => {
+                let replayed = was_previously_launched(&client, &instance_id).await;
                 if output_format == "json" {
                     let instance_info =
                         get_instance_info_json(&client, &instance_id, &options.instance_type)
                             .await?;
                     println!("{}", serde_json::to_string_pretty(&instance_info)?);
+                } else if replayed {
+                    println!(
+                        "Spot instance {} was already launched by an earlier attempt (same client token); reusing it",
+                        instance_id
+                    );
                 } else {
                     println!("Created spot instance: {}", instance_id);
                 }
-                if let Err(e) =
-                    tag_instance(&client, &instance_id, &options.project_name, config).await
+                if replayed {
+                    // Tags and data volume were set up by the earlier attempt
+                } else if let Err(e) =
+                    tag_instance(&client, &instance_id, &final_ami, &options, config).await
                 {
                     warn!("Failed to tag instance {}: {}", instance_id, e);
                     if output_format != "json" {
                         println!("  Instance created but tagging failed. You can tag manually if needed.");
                     }
                 }
+                if let Some(minutes) = autostop_minutes.filter(|_| !replayed) {
+                    if let Err(e) = autostop::mark_instance(
+                        &client,
+                        &instance_id,
+                        minutes,
+                        aws_cfg.autostop_action,
+                    )
+                    .await
+                    {
+                        warn!("Failed to configure autostop on {}: {}", instance_id, e);
+                    }
+                }
+                if !replayed {
+                    events::record(
+                        Event::new(&instance_id, EventKind::Created)
+                            .detail(format!("spot {}", options.instance_type)),
+                    );
+                }
 
                 // Auto-attach data volume if requested
-                if let Some(data_size) = options.data_volume_size {
+                if let Some(data_size) = options.data_volume_size.filter(|_| !replayed) {
                     if output_format != "json" {
                         println!("   Creating and attaching {}GB data volume...", data_size);
                     }
@@ -468,12 +635,16 @@ pub async fn create_instance(
             }
             Err(e) if !options.no_fallback => {
                 // Calculate cost difference for user awareness
-                let spot_cost = crate::utils::get_instance_cost(&options.instance_type) * 0.1; // Assume 90% discount
-                let ondemand_cost = crate::utils::get_instance_cost(&options.instance_type);
+                let ondemand_cost = crate::aws_pricing::hourly_price(
+                    &options.instance_type,
+                    config.aws.as_ref().map(|aws| aws.region.as_str()),
+                    None,
+                );
+                let spot_cost = ondemand_cost * 0.1; // Assume 90% discount
                 let cost_multiplier = (ondemand_cost / spot_cost).round() as u32;
 
                 println!();
-                println!("⚠️  WARNING: Spot instance failed: {}", e);
+                println!("WARNING: Spot instance failed: {}", e);
                 println!();
                 println!("   Cost impact:");
                 println!("   - Spot (requested):   ~${:.4}/hour", spot_cost);
@@ -496,8 +667,8 @@ pub async fn create_instance(
                           - Try on-demand instance: remove --spot flag\n\
                           - Check spot price limits: current max price may be too low\n\
                           - Try a different instance type or region\n\
-                          - Check AWS spot instance availability in your region",
-                        e
+                          - Compare zones and regions: runctl aws spot-advisor {}",
+                        e, options.instance_type
                     ),
                     source: None,
                 });
@@ -513,28 +684,51 @@ pub async fn create_instance(
         &user_data,
         options.key_name.as_deref(),
         options.security_group.as_deref(),
+        options.availability_zone.as_deref(),
         root_size,
         options.iam_instance_profile.as_deref(),
+        client_token_for(&options, "on-demand", &final_ami, &user_data).as_deref(),
     )
     .await?;
+    let replayed = was_previously_launched(&client, &instance_id).await;
 
     if output_format == "json" {
         let instance_info =
             get_instance_info_json(&client, &instance_id, &options.instance_type).await?;
         println!("{}", serde_json::to_string_pretty(&instance_info)?);
+    } else if replayed {
+        println!(
+            "Instance {} was already launched by an earlier attempt (same client token); reusing it",
+            instance_id
+        );
     } else {
         println!("Created on-demand instance: {}", instance_id);
     }
 
-    if let Err(e) = tag_instance(&client, &instance_id, &options.project_name, config).await {
+    if replayed {
+        // Tags and data volume were set up by the earlier attempt
+    } else if let Err(e) = tag_instance(&client, &instance_id, &final_ami, &options, config).await {
         warn!("Failed to tag instance {}: {}", instance_id, e);
         if output_format != "json" {
             println!("  Instance created but tagging failed. You can tag manually if needed.");
         }
     }
+    if let Some(minutes) = autostop_minutes.filter(|_| !replayed) {
+        if let Err(e) =
+            autostop::mark_instance(&client, &instance_id, minutes, aws_cfg.autostop_action).await
+        {
+            warn!("Failed to configure autostop on {}: {}", instance_id, e);
+        }
+    }
+    if !replayed {
+        events::record(
+            Event::new(&instance_id, EventKind::Created)
+                .detail(format!("on-demand {}", options.instance_type)),
+        );
+    }
 
     // Auto-attach data volume if requested
-    if let Some(data_size) = options.data_volume_size {
+    if let Some(data_size) = options.data_volume_size.filter(|_| !replayed) {
         if output_format != "json" {
             println!("   Creating and attaching {}GB data volume...", data_size);
         }
@@ -621,201 +815,6 @@ pub async fn create_instance(
     Ok(())
 }
 
-/// Find latest Deep Learning AMI for GPU instances
-async fn find_deep_learning_ami(client: &Ec2Client, _region: &str) -> Result<String> {
-    use aws_sdk_ec2::types::Filter;
-
-    // Try multiple Deep Learning AMI patterns
-    let patterns = vec![
-        "Deep Learning AMI GPU PyTorch * (Amazon Linux 2)*",
-        "Deep Learning AMI GPU PyTorch *",
-        "Deep Learning AMI (Amazon Linux 2)*",
-        "Deep Learning Base AMI (Amazon Linux 2)*",
-    ];
-
-    for pattern in patterns {
-        let response = client
-            .describe_images()
-            .owners("amazon")
-            .filters(Filter::builder().name("name").values(pattern).build())
-            .filters(Filter::builder().name("state").values("available").build())
-            .send()
-            .await
-            .map_err(|e| {
-                TrainctlError::Aws(format!("Failed to search for Deep Learning AMI: {}", e))
-            })?;
-
-        let images = response.images();
-        if !images.is_empty() {
-            // Sort by creation date (newest first)
-            let mut sorted: Vec<_> = images.iter().collect();
-            sorted.sort_by(|a, b| {
-                let a_date = a.creation_date().unwrap_or("");
-                let b_date = b.creation_date().unwrap_or("");
-                b_date.cmp(a_date)
-            });
-
-            return Ok(sorted[0]
-                .image_id()
-                .ok_or_else(|| TrainctlError::Aws("AMI has no image ID".to_string()))?
-                .to_string());
-        }
-    }
-
-    Err(TrainctlError::CloudProvider {
-        provider: "aws".to_string(),
-        message: "No Deep Learning AMI found with any pattern".to_string(),
-        source: None,
-    })
-}
-
-/// Generate user data script for instance initialization
-fn generate_user_data(project_name: &str, _has_data_volume: bool) -> String {
-    format!(
-        r#"#!/bin/bash
-set -e
-
-# Log all output for debugging
-exec > >(tee /var/log/user-data.log)
-exec 2>&1
-
-echo "Starting instance setup..."
-
-# Detect OS (Ubuntu vs Amazon Linux)
-if [ -f /etc/os-release ]; then
-    . /etc/os-release
-    OS=$ID
-else
-    OS="unknown"
-fi
-
-echo "Detected OS: $OS"
-
-# Update system
-if [ "$OS" = "ubuntu" ]; then
-    export DEBIAN_FRONTEND=noninteractive
-apt-get update -y
-    apt-get upgrade -y -qq
-    apt-get install -y python3-pip python3-venv git curl build-essential
-    USER="ubuntu"
-    HOME_DIR="/home/ubuntu"
-elif [ "$OS" = "amzn" ] || [ "$OS" = "rhel" ]; then
-    yum update -y -q
-    # Install Python 3 and pip
-    yum install -y python3 python3-pip git curl gcc gcc-c++ make
-    # Ensure pip3 is available
-    if ! command -v pip3 &> /dev/null; then
-        # Try alternative installation methods
-        if command -v python3 &> /dev/null; then
-            curl -sS https://bootstrap.pypa.io/get-pip.py | python3
-        fi
-    fi
-    USER="ec2-user"
-    HOME_DIR="/home/ec2-user"
-else
-    echo "WARNING: Unknown OS, using defaults"
-    USER="ubuntu"
-    HOME_DIR="/home/ubuntu"
-fi
-
-# Install uv for Python package management
-echo "Installing uv..."
-curl -LsSf https://astral.sh/uv/install.sh | sh
-export PATH="$HOME_DIR/.local/bin:$HOME_DIR/.cargo/bin:$PATH"
-echo 'export PATH="$HOME/.local/bin:$HOME/.cargo/bin:$PATH"' >> $HOME_DIR/.bashrc
-
-# Pre-install common ML libraries (cached for faster training startup)
-echo "Pre-installing common ML libraries..."
-if command -v uv &> /dev/null; then
-    uv pip install --system --quiet numpy pandas || pip3 install --quiet --user numpy pandas
-else
-    pip3 install --quiet --user numpy pandas || python3 -m pip install --quiet --user numpy pandas
-fi
-
-# Create dependency cache directory
-mkdir -p /opt/runctl-cache
-chmod 777 /opt/runctl-cache
-echo "Dependency cache: /opt/runctl-cache"
-
-# Setup data volume if attached
-if [ -b /dev/nvme1n1 ] || [ -b /dev/xvdf ]; then
-    echo "Setting up data volume..."
-    DEVICE=$(lsblk -o NAME,TYPE,SIZE | grep -E '^nvme[0-9]+n1' | grep -v nvme0n1 | awk '{{print $1}}' | head -1)
-    if [ -z "$DEVICE" ]; then
-        for dev in /dev/xvdf /dev/sdf /dev/nvme1n1; do
-            if [ -b "$dev" ]; then
-                DEVICE=$(basename $dev)
-                break
-            fi
-        done
-    fi
-    
-    if [ -n "$DEVICE" ]; then
-        FULL_DEVICE="/dev/$DEVICE"
-        MOUNT_POINT="/mnt/data"
-        
-        # Format if not already formatted
-        if ! blkid $FULL_DEVICE > /dev/null 2>&1; then
-            echo "   Formatting volume..."
-            mkfs.ext4 -F $FULL_DEVICE
-        fi
-        
-        # Mount
-        mkdir -p $MOUNT_POINT
-        if ! mountpoint -q $MOUNT_POINT; then
-            mount $FULL_DEVICE $MOUNT_POINT
-            UUID=$(blkid -s UUID -o value $FULL_DEVICE)
-            echo "UUID=$UUID $MOUNT_POINT ext4 defaults,nofail 0 2" >> /etc/fstab
-        fi
-        
-        chown -R $USER:$USER $MOUNT_POINT
-        echo "Data volume mounted at $MOUNT_POINT"
-    fi
-fi
-
-# Create project directory
-PROJECT_DIR="$HOME_DIR/{project_name}"
-mkdir -p $PROJECT_DIR
-chown $USER:$USER $PROJECT_DIR
-
-# Create data directory (use mounted volume if available, else local)
-if [ -d /mnt/data ]; then
-    DATA_DIR="/mnt/data"
-else
-    DATA_DIR="$HOME_DIR/data"
-fi
-mkdir -p $DATA_DIR
-chown $USER:$USER $DATA_DIR
-
-# Setup Python environment
-export PYTHONPATH=$PROJECT_DIR:$PYTHONPATH
-echo "export PYTHONPATH=$PROJECT_DIR:\$PYTHONPATH" >> $HOME_DIR/.bashrc
-
-# Create helper script for training
-cat > $HOME_DIR/start_training.sh << 'TRAIN_SCRIPT'
-#!/bin/bash
-cd $PROJECT_DIR
-export PATH="$HOME/.local/bin:$HOME/.cargo/bin:$PATH"
-export PYTHONPATH=$PROJECT_DIR:$PYTHONPATH
-
-if command -v uv &> /dev/null; then
-    uv run python3 -m training.train_lightning "$@"
-else
-    python3 -m training.train_lightning "$@"
-fi
-TRAIN_SCRIPT
-chmod +x $HOME_DIR/start_training.sh
-chown $USER:$USER $HOME_DIR/start_training.sh
-
-echo "Instance setup complete"
-echo "   Project directory: $PROJECT_DIR"
-echo "   Data directory: $DATA_DIR"
-echo "   To start training: $HOME_DIR/start_training.sh"
-"#,
-        project_name = project_name
-    )
-}
-
 /// Auto-attach and setup data volume
 async fn auto_attach_data_volume(
     client: &Ec2Client,
@@ -924,6 +923,37 @@ async fn auto_attach_data_volume(
     Ok(())
 }
 
+/// Report a reused instance the way a fresh `create` would
+async fn report_reused_instance(
+    client: &Ec2Client,
+    instance_id: &str,
+    options: &CreateInstanceOptions,
+    aws_config: &aws_config::SdkConfig,
+    output_format: &str,
+) -> Result<()> {
+    match output_format {
+        "json" => {
+            let instance_info =
+                get_instance_info_json(client, instance_id, &options.instance_type).await?;
+            println!("{}", serde_json::to_string_pretty(&instance_info)?);
+        }
+        "instance-id" => println!("{}", instance_id),
+        _ => println!("Reusing instance: {}", instance_id),
+    }
+
+    if options.wait {
+        if let Err(e) =
+            crate::aws_utils::wait_for_instance_running(client, instance_id, Some(aws_config)).await
+        {
+            warn!("Failed to wait for instance ready: {}", e);
+            if output_format == "text" {
+                println!("  Check status: runctl aws wait {}", instance_id);
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Create a spot instance
 async fn create_spot_instance(
     client: &Ec2Client,
@@ -946,11 +976,17 @@ async fn create_spot_instance(
     if let Some(sg) = &options.security_group {
         spec_builder = spec_builder.security_groups(sg);
     }
+    if let Some(zone) = &options.availability_zone {
+        spec_builder = spec_builder.placement(
+            aws_sdk_ec2::types::SpotPlacement::builder()
+                .availability_zone(zone)
+                .build(),
+        );
+    }
 
-    // Configure root volume size (device name depends on AMI - try both common ones)
-    // For Ubuntu: /dev/sda1, for Amazon Linux: /dev/xvda
+    // Resize the root volume under the AMI's own root device name
     let block_device = aws_sdk_ec2::types::BlockDeviceMapping::builder()
-        .device_name("/dev/sda1") // Ubuntu default
+        .device_name(root_device_name(client, &options.ami_id).await?)
         .ebs(
             aws_sdk_ec2::types::EbsBlockDevice::builder()
                 .volume_size(options.root_volume_size)
@@ -975,7 +1011,8 @@ async fn create_spot_instance(
     let mut spot_request = client
         .request_spot_instances()
         .instance_count(1)
-        .launch_specification(spec);
+        .launch_specification(spec)
+        .set_client_token(options.client_token.clone());
 
     // Set spot price if provided
     if let Some(price) = &options.max_price {
@@ -997,7 +1034,7 @@ async fn create_spot_instance(
             if error_msg.contains("InsufficientInstanceCapacity") {
                 detailed_msg.push_str("\n\nTo resolve:\n  1. Try on-demand instance (remove --spot flag)\n  2. Try a different instance type\n  3. Try a different availability zone or region\n  4. Check AWS spot instance availability");
             } else if error_msg.contains("SpotPrice") || error_msg.contains("price") {
-                detailed_msg.push_str("\n\nTo resolve:\n  1. Increase --spot-max-price (current may be too low)\n  2. Try on-demand instance (remove --spot flag)\n  3. Check current spot prices: aws ec2 describe-spot-price-history");
+                detailed_msg.push_str("\n\nTo resolve:\n  1. Increase --spot-max-price (current may be too low)\n  2. Try on-demand instance (remove --spot flag)\n  3. Check current spot prices: runctl aws spot-advisor <instance-type>");
             } else if error_msg.contains("InvalidParameter") {
                 detailed_msg.push_str("\n\nTo resolve:\n  1. Verify instance type is valid for your region\n  2. Check IAM permissions for spot instance requests\n  3. Verify security group and key pair exist");
             }
@@ -1019,11 +1056,9 @@ async fn create_spot_instance(
     // Use progress bar for non-JSON output
     let pb = if output_format != "json" {
         let pb = ProgressBar::new(MAX_ATTEMPTS as u64);
-        pb.set_style(
-            ProgressStyle::default_spinner()
-                .template("{spinner:.green} [{elapsed_precise}] {msg}")
-                .expect("Progress bar template should be valid"),
-        );
+        pb.set_style(crate::output_style::spinner(
+            "{spinner:.green} [{elapsed_precise}] {msg}",
+        ));
         pb.set_message(format!(
             "Waiting for spot instance (request: {})...",
             spot_request_id
@@ -1049,6 +1084,10 @@ async fn create_spot_instance(
                 attempts, MAX_ATTEMPTS
             ));
         }
+        crate::progress::emit(
+            "create.spot_request",
+            format!("Waiting for spot request {}", spot_request_id),
+        );
 
         let describe_response = client
             .describe_spot_instance_requests()
@@ -1075,6 +1114,7 @@ async fn create_spot_instance(
                         TrainctlError::Aws("No instance ID in fulfilled request".to_string())
                     })?
                     .to_string();
+                crate::progress::emit("create.launched", format!("Launched {}", instance_id));
                 return Ok(instance_id);
             }
             Some("open") | Some("active") => {
@@ -1140,8 +1180,10 @@ async fn create_ondemand_instance(
     user_data: &str,
     key_name: Option<&str>,
     security_group: Option<&str>,
+    availability_zone: Option<&str>,
     root_volume_size: i32,
     iam_instance_profile: Option<&str>,
+    client_token: Option<&str>,
 ) -> Result<String> {
     // Base64 encode user data
     let user_data_b64 = base64::engine::general_purpose::STANDARD.encode(user_data);
@@ -1153,7 +1195,8 @@ async fn create_ondemand_instance(
         .min_count(1)
         .max_count(1)
         .user_data(&user_data_b64)
-        .ebs_optimized(true); // Enable EBS optimization for better I/O performance
+        .ebs_optimized(true) // Enable EBS optimization for better I/O performance
+        .set_client_token(client_token.map(str::to_string));
 
     if let Some(key) = key_name {
         run_request = run_request.key_name(key);
@@ -1161,6 +1204,13 @@ async fn create_ondemand_instance(
     if let Some(sg) = security_group {
         run_request = run_request.security_group_ids(sg);
     }
+    if let Some(zone) = availability_zone {
+        run_request = run_request.placement(
+            aws_sdk_ec2::types::Placement::builder()
+                .availability_zone(zone)
+                .build(),
+        );
+    }
 
     // Add IAM instance profile if provided
     if let Some(profile_name) = iam_instance_profile {
@@ -1171,9 +1221,9 @@ async fn create_ondemand_instance(
         );
     }
 
-    // Configure root volume size (device name depends on AMI)
+    // Resize the root volume under the AMI's own root device name
     let block_device = aws_sdk_ec2::types::BlockDeviceMapping::builder()
-        .device_name("/dev/sda1") // Ubuntu default, works for most AMIs
+        .device_name(root_device_name(client, ami_id).await?)
         .ebs(
             aws_sdk_ec2::types::EbsBlockDevice::builder()
                 .volume_size(root_volume_size)
@@ -1195,6 +1245,7 @@ async fn create_ondemand_instance(
         .and_then(|inst| inst.instance_id())
         .ok_or_else(|| TrainctlError::Aws("No instance ID in response".to_string()))?
         .to_string();
+    crate::progress::emit("create.launched", format!("Launched {}", instance_id));
 
     Ok(instance_id)
 }
@@ -1203,18 +1254,21 @@ async fn create_ondemand_instance(
 async fn tag_instance(
     client: &Ec2Client,
     instance_id: &str,
-    project_name: &str,
+    ami_id: &str,
+    options: &CreateInstanceOptions,
     config: &Config,
 ) -> Result<()> {
     use aws_sdk_ec2::types::Tag;
 
+    let project_name = options.project_name.as_str();
+
     let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string();
     let user_id = get_user_id(config);
 
     // config is used via get_user_id() call above
     let name_tag = format!("runctl-{}-{}-{}", user_id, project_name, &instance_id[..8]);
 
-    client
+    let mut request = client
         .create_tags()
         .resources(instance_id)
         .tags(Tag::builder().key("Name").value(&name_tag).build())
@@ -1231,7 +1285,31 @@ async fn tag_instance(
                 .build(),
         )
         .tags(Tag::builder().key("runctl:user").value(&user_id).build())
-        .tags(Tag::builder().key("CreatedBy").value("runctl").build())
+        .tags(
+            Tag::builder()
+                .key(FINGERPRINT_TAG)
+                .value(launch_fingerprint(options))
+                .build(),
+        )
+        .tags(Tag::builder().key("CreatedBy").value("runctl").build());
+    if let Some(preset) = &options.preset {
+        request = request.tags(Tag::builder().key(PRESET_TAG).value(preset).build());
+    }
+    let ssh_user = match &options.ssh_user {
+        Some(user) => Some(user.clone()),
+        None => user_for_ami(client, ami_id)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("{}", e);
+                None
+            })
+            .map(str::to_string),
+    };
+    if let Some(user) = ssh_user {
+        request = request.tags(Tag::builder().key(SSH_USER_TAG).value(user).build());
+    }
+
+    request
         .send()
         .await
         .map_err(|e| TrainctlError::Aws(format!("Failed to tag instance: {}", e)))?;
@@ -1288,7 +1366,7 @@ pub async fn terminate_instance(
     let block_devices = instance.block_device_mappings();
     let has_data_volumes = block_devices.iter().any(|bd| {
         bd.device_name()
-            .map(|d| d != "/dev/xvda" && d != "/dev/sda1")
+            .map(|d| is_data_device(d, instance.root_device_name()))
             .unwrap_or(false)
     });
 
@@ -1492,76 +1570,19 @@ pub async fn stop_instance(
         });
     }
 
-    // Graceful shutdown: save checkpoints and stop training cleanly
-    if !force {
-        if let Some(_iam_profile) = instance.iam_instance_profile() {
-            let graceful_stop_cmd = r#"
-# Check for training and gracefully stop
-if [ -f training.pid ]; then
-    PID=$(cat training.pid 2>/dev/null)
-    if ps -p $PID > /dev/null 2>&1; then
-        echo "TRAINING_RUNNING:$PID"
-        # Send SIGTERM for graceful shutdown
-        kill -TERM $PID 2>/dev/null || true
-        # Wait up to 30 seconds for graceful shutdown
-        for i in {1..30}; do
-            if ! ps -p $PID > /dev/null 2>&1; then
-                echo "TRAINING_STOPPED_GRACEFULLY"
-                exit 0
-            fi
-            sleep 1
-        done
-        # Force kill if still running
-        kill -9 $PID 2>/dev/null || true
-        echo "TRAINING_FORCE_STOPPED"
-    else
-        echo "TRAINING_STOPPED"
-    fi
-else
-    # Check for training processes
-    TRAINING_PID=$(pgrep -f "python.*train\|python.*training\|python.*main.py" | head -1)
-    if [ -n "$TRAINING_PID" ]; then
-        echo "TRAINING_RUNNING:$TRAINING_PID"
-        # Send SIGTERM for graceful shutdown
-        kill -TERM $TRAINING_PID 2>/dev/null || true
-        # Wait up to 30 seconds
-        for i in {1..30}; do
-            if ! ps -p $TRAINING_PID > /dev/null 2>&1; then
-                echo "TRAINING_STOPPED_GRACEFULLY"
-                exit 0
-            fi
-            sleep 1
-        done
-        # Force kill if still running
-        kill -9 $TRAINING_PID 2>/dev/null || true
-        echo "TRAINING_FORCE_STOPPED"
-    else
-        echo "NO_TRAINING"
-    fi
-fi
-"#;
-
-            match crate::aws_utils::execute_ssm_command(
-                &ssm_client,
-                &instance_id,
-                graceful_stop_cmd,
-            )
+    // Graceful shutdown: checkpoint at the next boundary, or SIGTERM, before stopping
+    if !force && instance.iam_instance_profile().is_some() {
+        match crate::aws_utils::stop_training_gracefully(&ssm_client, &instance_id, config, "stop")
             .await
-            {
-                Ok(output) => {
-                    if output.contains("TRAINING_RUNNING") {
-                        println!(
-                            "Training detected on instance {}, attempting graceful shutdown...",
-                            instance_id
-                        );
-                    } else if output.contains("TRAINING_STOPPED_GRACEFULLY") {
-                        println!("Training stopped gracefully on instance {}", instance_id);
-                    } else if output.contains("TRAINING_FORCE_STOPPED") {
-                        println!("WARNING: Training force-stopped on instance {} (graceful shutdown timeout)", instance_id);
-                    }
-                }
-                Err(e) => {
-                    println!("WARNING: Could not gracefully stop training: {}", e);
+        {
+            Some(StopOutcome::NoTraining) | None => {}
+            Some(StopOutcome::Killed) => println!(
+                "WARNING: Training force-stopped on instance {} (graceful shutdown timeout)",
+                instance_id
+            ),
+            Some(outcome) => {
+                if output_format != "json" {
+                    println!("Training on {}: {}", instance_id, outcome);
                 }
             }
         }
@@ -1755,8 +1776,14 @@ pub async fn start_instance(
 }
 
 /// Show instance status and training state
+///
+/// Merges EC2 state with the bootstrap tags, cost so far, attached volumes,
+/// the local session that launched the job, and the training process.
+/// Training state and metrics come from the `runctl agent` report in S3 when
+/// it is fresh, otherwise from a probe over SSM (see `status`).
 pub async fn show_instance_status(
     instance_id: String,
+    config: &Config,
     aws_config: &aws_config::SdkConfig,
     output_format: &str,
 ) -> Result<()> {
@@ -1787,6 +1814,7 @@ pub async fn show_instance_status(
         .unwrap_or("unknown");
 
     let public_ip = instance.public_ip_address().map(|s| s.to_string());
+    let public_ipv6 = crate::aws::instance_ipv6(instance).map(str::to_string);
     let private_ip = instance.private_ip_address().map(|s| s.to_string());
     let instance_type = instance
         .instance_type()
@@ -1796,26 +1824,87 @@ pub async fn show_instance_status(
     // Check if SSM is available
     let ssm_available = instance.iam_instance_profile().is_some();
 
-    // Try to get training status if instance is running and SSM is available
-    let training_status = if state == "running" && ssm_available {
-        // Check for training process
-        let check_cmd = "if [ -f ~/training.pid ]; then PID=$(cat ~/training.pid 2>/dev/null) && ps -p $PID > /dev/null 2>&1 && echo 'RUNNING' || echo 'COMPLETE'; else echo 'NO_TRAINING'; fi";
-        match crate::aws_utils::execute_ssm_command(&ssm_client, &instance_id, check_cmd).await {
-            Ok(output) => {
-                let trimmed = output.trim();
-                if trimmed == "RUNNING" {
-                    Some("running".to_string())
-                } else if trimmed == "COMPLETE" {
-                    Some("completed".to_string())
-                } else {
-                    Some("not_started".to_string())
-                }
-            }
-            Err(_) => None,
+    // Bootstrap outcome reported by the instance itself
+    let bootstrap_status = bootstrap_status_from_tags(&instance_tags(instance));
+    if let Some(status) = &bootstrap_status {
+        record_bootstrap_event(&instance_id, status);
+    }
+
+    // Catch up on stops and starts made outside runctl before reporting cost
+    crate::aws_pricing::prefetch(&ec2_client, std::iter::once(instance)).await;
+    let tracked = match &config.resource_tracker {
+        Some(tracker) => {
+            crate::aws::helpers::reconcile_instance(tracker, instance).await;
+            tracker.get_by_id(&instance_id).await
         }
+        None => None,
+    };
+
+    // A fresh report from `runctl agent` saves the SSM round-trip
+    let agent_status = if state == "running" {
+        crate::agent::fetch_fresh_status(config, &aws_sdk_s3::Client::new(aws_config), &instance_id)
+            .await
+    } else {
+        None
+    };
+
+    // Otherwise probe the project directory `train` used over SSM
+    let training_probe = if agent_status.is_none() && state == "running" && ssm_available {
+        let user = crate::aws::resolve_ssh_user(&ec2_client, instance, None).await;
+        let log_path = crate::aws::monitor::log_path_from_tags(
+            &instance_tags(instance),
+            &user,
+            &crate::aws::get_project_name(None, config),
+        );
+        let project_dir = log_path
+            .rsplit_once('/')
+            .map(|(dir, _)| dir.to_string())
+            .unwrap_or_default();
+        crate::aws_utils::execute_ssm_command(
+            &ssm_client,
+            &instance_id,
+            &crate::aws::status::probe_script(&project_dir),
+        )
+        .await
+        .ok()
+        .map(|output| crate::aws::status::parse_probe(&output))
     } else {
         None
     };
+    let training_status = match (&agent_status, &training_probe) {
+        (Some(agent), _) => Some(agent.training.clone()),
+        (None, Some(probe)) => Some(probe.summary()),
+        (None, None) => None,
+    };
+    let last_checkpoint = match (&agent_status, &training_probe) {
+        (Some(agent), _) => agent
+            .latest_checkpoint
+            .as_ref()
+            .map(|c| (c.name.clone(), Some(c.modified))),
+        (None, Some(probe)) => probe
+            .checkpoint
+            .clone()
+            .map(|name| (name, probe.checkpoint_time)),
+        (None, None) => None,
+    };
+    if let Some((name, modified)) = &last_checkpoint {
+        // Observed rather than reported, so timestamp it with the file's time
+        let event = Event::new(&instance_id, EventKind::CheckpointSaved).detail(name);
+        events::record_once(match modified {
+            Some(modified) => event.at(*modified),
+            None => event,
+        });
+    }
+
+    let launch_time = instance
+        .launch_time()
+        .and_then(|lt| chrono::DateTime::<chrono::Utc>::from_timestamp(lt.secs(), 0));
+    let uptime_secs = launch_time
+        .filter(|_| state == "running")
+        .map(|lt| (chrono::Utc::now() - lt).num_seconds().max(0) as u64);
+    let volumes = crate::aws::status::attached_volumes(&ec2_client, instance).await;
+    let session = crate::aws::status::local_session(std::path::Path::new(".runctl"), &instance_id)
+        .unwrap_or_default();
 
     if output_format == "json" {
         let status = json!({
@@ -1824,18 +1913,50 @@ pub async fn show_instance_status(
             "state": state,
             "instance_type": instance_type,
             "public_ip": public_ip,
+            "public_ipv6": public_ipv6,
             "private_ip": private_ip,
             "ssm_available": ssm_available,
+            "launch_time": launch_time,
+            "uptime_secs": uptime_secs,
             "training_status": training_status,
+            "training": training_probe,
+            "last_checkpoint": last_checkpoint.as_ref().map(|(name, time)| json!({
+                "name": name,
+                "modified": time,
+            })),
+            "volumes": volumes,
+            "session": session.as_ref().map(|s| json!({
+                "id": s.id,
+                "status": s.status,
+                "started_at": s.started_at,
+                "script": s.script,
+            })),
+            "bootstrap_status": bootstrap_status.as_ref().map(|s| s.as_str()),
+            "bootstrap_error": bootstrap_status.as_ref().and_then(|s| s.error()),
+            "agent": agent_status,
+            "cost_per_hour": tracked
+                .as_ref()
+                .map_or_else(|| crate::aws_pricing::instance_hourly_price(instance), |t| t.status.cost_per_hour),
+            "accumulated_cost": tracked.as_ref().map(|t| t.accumulated_cost),
         });
         println!("{}", serde_json::to_string_pretty(&status)?);
     } else {
         println!("Instance: {}", instance_id);
-        println!("  State: {}", state);
+        match uptime_secs {
+            Some(secs) => println!(
+                "  State: {} (up {})",
+                state,
+                crate::utils::format_duration(secs)
+            ),
+            None => println!("  State: {}", state),
+        }
         println!("  Type: {}", instance_type);
         if let Some(ip) = public_ip {
             println!("  Public IP: {}", ip);
         }
+        if let Some(ip) = public_ipv6 {
+            println!("  IPv6: {}", ip);
+        }
         if let Some(ip) = private_ip {
             println!("  Private IP: {}", ip);
         }
@@ -1843,17 +1964,122 @@ pub async fn show_instance_status(
             "  SSM Available: {}",
             if ssm_available { "Yes" } else { "No" }
         );
+        match &tracked {
+            Some(tracked) => println!(
+                "  Cost: ${:.4}/hr, ${:.2} so far",
+                tracked.status.cost_per_hour, tracked.accumulated_cost
+            ),
+            None => println!(
+                "  Cost: ${:.4}/hr",
+                crate::aws_pricing::instance_hourly_price(instance)
+            ),
+        }
+        if let Some(status) = &bootstrap_status {
+            match status.error() {
+                Some(error) => println!("  Bootstrap: failed ({})", error),
+                None => println!("  Bootstrap: {}", status.as_str()),
+            }
+        }
         if let Some(status) = training_status {
             println!("  Training Status: {}", status);
         }
+        if let Some(probe) = &training_probe {
+            if let Some(pid) = probe.pid {
+                match probe.elapsed_secs {
+                    Some(secs) => println!(
+                        "    PID: {} (running {})",
+                        pid,
+                        crate::utils::format_duration(secs)
+                    ),
+                    None => println!("    PID: {}", pid),
+                }
+            }
+        }
+        if let Some(agent) = &agent_status {
+            print_agent_status(agent);
+        } else if let Some((name, modified)) = &last_checkpoint {
+            match modified {
+                Some(time) => println!(
+                    "  Latest Checkpoint: {} ({})",
+                    name,
+                    time.format("%Y-%m-%d %H:%M:%S UTC")
+                ),
+                None => println!("  Latest Checkpoint: {}", name),
+            }
+        }
+        if !volumes.is_empty() {
+            println!("  Volumes:");
+            for volume in &volumes {
+                let size = volume
+                    .size_gb
+                    .map(|gb| format!("{} GB", gb))
+                    .unwrap_or_else(|| "? GB".to_string());
+                println!(
+                    "    {} {} {} {}{}",
+                    volume.device.as_deref().unwrap_or("-"),
+                    volume.volume_id,
+                    size,
+                    volume.volume_type.as_deref().unwrap_or(""),
+                    if volume.delete_on_termination == Some(false) {
+                        " (kept on terminate)"
+                    } else {
+                        ""
+                    }
+                );
+            }
+        }
+        if let Some(session) = &session {
+            println!(
+                "  Session: {} ({:?}, started {})",
+                session.id,
+                session.status,
+                session.started_at.format("%Y-%m-%d %H:%M UTC")
+            );
+        }
     }
 
     Ok(())
 }
 
+fn print_agent_status(agent: &crate::agent::AgentStatus) {
+    println!(
+        "  Agent: heartbeat {}s ago",
+        agent.age_secs(chrono::Utc::now())
+    );
+    if let Some(progress) = agent.progress_summary() {
+        println!("    Progress: {}", progress);
+    }
+    if let (Some(cpu), Some(memory)) = (agent.cpu_percent, agent.memory_percent) {
+        println!("    CPU: {:.1}%  Memory: {:.1}%", cpu, memory);
+    }
+    if let (Some(gpu), Some(gpu_memory)) = (agent.gpu_utilization, agent.gpu_memory_percent) {
+        println!("    GPU: {:.1}%  GPU Memory: {:.1}%", gpu, gpu_memory);
+    }
+    if let Some(checkpoint) = &agent.latest_checkpoint {
+        println!(
+            "    Latest Checkpoint: {} ({}, {} total)",
+            checkpoint.name,
+            checkpoint.modified.format("%Y-%m-%d %H:%M:%S UTC"),
+            agent.checkpoint_count
+        );
+    }
+}
+
+/// Bootstrap progress polling: 60 attempts * 10s = 10 minutes max
+const BOOTSTRAP_POLL_MAX_ATTEMPTS: u32 = 60;
+const BOOTSTRAP_POLL_INTERVAL_SECS: u64 = 10;
+/// Time in one bootstrap phase before `--verbose` flags it as possibly stuck
+const BOOTSTRAP_STALL_SECS: u64 = 300;
+
 /// Wait for instance to be ready
+///
+/// Fails if the instance reports a failed bootstrap via its `runctl:bootstrap`
+/// tag. With `verbose`, keeps polling after the instance is running and reports
+/// bootstrap phases from the user-data log (requires SSM; falls back to tags
+/// otherwise), so a slow install can be told apart from a hung one.
 pub async fn wait_for_instance(
     instance_id: String,
+    verbose: bool,
     aws_config: &aws_config::SdkConfig,
     output_format: &str,
 ) -> Result<()> {
@@ -1865,22 +2091,23 @@ pub async fn wait_for_instance(
         println!("Waiting for instance {} to be ready...", instance_id);
     }
 
-    match crate::aws_utils::wait_for_instance_running(&client, &instance_id, Some(aws_config)).await
-    {
-        Ok(_) => {
-            if output_format == "json" {
-                let result = json!({
-                    "success": true,
-                    "instance_id": instance_id,
-                    "state": "running",
-                    "message": "Instance is ready and SSM is connected (if IAM profile configured)"
-                });
-                println!("{}", serde_json::to_string_pretty(&result)?);
-            } else {
-                println!("Instance {} is ready", instance_id);
+    let result: Result<(Option<BootstrapPhase>, Option<BootstrapTagStatus>)> = async {
+        crate::aws_utils::wait_for_instance_running(&client, &instance_id, Some(aws_config))
+            .await?;
+        if verbose {
+            wait_for_bootstrap(&client, &instance_id, aws_config, output_format).await
+        } else {
+            let tag_status = fetch_bootstrap_tag_status(&client, &instance_id).await?;
+            if let Some(BootstrapTagStatus::Failed(summary)) = &tag_status {
+                return Err(bootstrap_failed_error(&instance_id, summary));
             }
-            Ok(())
+            Ok((None, tag_status))
         }
+    }
+    .await;
+
+    let (bootstrap_phase, tag_status) = match result {
+        Ok(r) => r,
         Err(e) => {
             if output_format == "json" {
                 let result = json!({
@@ -1890,7 +2117,201 @@ pub async fn wait_for_instance(
                 });
                 println!("{}", serde_json::to_string_pretty(&result)?);
             }
-            Err(e)
+            return Err(e);
         }
+    };
+
+    if output_format == "json" {
+        let result = json!({
+            "success": true,
+            "instance_id": instance_id,
+            "state": "running",
+            "bootstrap_phase": bootstrap_phase.map(|p| p.marker()),
+            "bootstrap_status": tag_status.as_ref().map(|s| s.as_str()),
+            "message": "Instance is ready and SSM is connected (if IAM profile configured)"
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        println!("Instance {} is ready", instance_id);
+        if tag_status == Some(BootstrapTagStatus::Running) {
+            println!("   Bootstrap still running - use --verbose to wait for it to finish");
+        }
+    }
+    Ok(())
+}
+
+/// Read the `runctl:bootstrap` tag status for an instance
+async fn fetch_bootstrap_tag_status(
+    client: &Ec2Client,
+    instance_id: &str,
+) -> Result<Option<BootstrapTagStatus>> {
+    let response = client
+        .describe_instances()
+        .instance_ids(instance_id)
+        .send()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to describe instance: {}", e)))?;
+    let status = crate::aws::helpers::find_instance_in_response(&response, instance_id)
+        .and_then(|i| bootstrap_status_from_tags(&instance_tags(i)));
+    if let Some(status) = &status {
+        record_bootstrap_event(instance_id, status);
+    }
+    Ok(status)
+}
+
+/// Add the end of bootstrap to the instance's timeline the first time it's seen
+fn record_bootstrap_event(instance_id: &str, status: &BootstrapTagStatus) {
+    let event = match status {
+        BootstrapTagStatus::Running => return,
+        BootstrapTagStatus::Complete => Event::new(instance_id, EventKind::Bootstrapped),
+        BootstrapTagStatus::Failed(summary) => {
+            Event::new(instance_id, EventKind::BootstrapFailed).detail(summary)
+        }
+    };
+    events::record_once(event);
+}
+
+/// Collect instance tags as key/value pairs
+/// Whether a block device mapping is a data volume rather than the root
+///
+/// Compares against the instance's reported root device; instances that don't
+/// report one fall back to the Ubuntu and Amazon Linux defaults.
+fn is_data_device(device: &str, root_device: Option<&str>) -> bool {
+    match root_device {
+        Some(root) => device != root,
+        None => device != "/dev/xvda" && device != "/dev/sda1",
+    }
+}
+
+fn instance_tags(instance: &aws_sdk_ec2::types::Instance) -> Vec<(String, String)> {
+    instance
+        .tags()
+        .iter()
+        .filter_map(|tag| {
+            tag.key()
+                .zip(tag.value())
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+        })
+        .collect()
+}
+
+fn bootstrap_failed_error(instance_id: &str, summary: &str) -> TrainctlError {
+    TrainctlError::Resource {
+        resource_type: "instance".to_string(),
+        operation: "bootstrap".to_string(),
+        resource_id: Some(instance_id.to_string()),
+        message: format!(
+            "Instance {} failed bootstrap: {}\n\nTo resolve:\n  1. Inspect /var/log/user-data.log on the instance\n  2. Preview the script: runctl aws render-user-data\n  3. Terminate and recreate once fixed: runctl aws terminate {}",
+            instance_id, summary, instance_id
+        ),
+        source: None,
     }
 }
+
+/// Poll bootstrap progress until the user-data script completes or fails
+///
+/// Uses log markers over SSM when available and the `runctl:bootstrap` tag
+/// otherwise. Returns the last observed phase (if SSM was used) and tag status.
+async fn wait_for_bootstrap(
+    client: &Ec2Client,
+    instance_id: &str,
+    aws_config: &aws_config::SdkConfig,
+    output_format: &str,
+) -> Result<(Option<BootstrapPhase>, Option<BootstrapTagStatus>)> {
+    let response = client
+        .describe_instances()
+        .instance_ids(instance_id)
+        .send()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to describe instance: {}", e)))?;
+    let has_ssm = crate::aws::helpers::find_instance_in_response(&response, instance_id)
+        .and_then(|i| i.iam_instance_profile())
+        .is_some();
+
+    if !has_ssm && output_format != "json" {
+        println!("   No IAM profile for SSM; tracking bootstrap via instance tags only");
+    }
+
+    let ssm_client = SsmClient::new(aws_config);
+    let started = std::time::Instant::now();
+    let mut phase_started = started;
+    let mut last_phase: Option<BootstrapPhase> = None;
+    let mut last_tag: Option<BootstrapTagStatus> = None;
+    let mut stall_reported = false;
+
+    for _ in 0..BOOTSTRAP_POLL_MAX_ATTEMPTS {
+        let tag_status = fetch_bootstrap_tag_status(client, instance_id).await?;
+        if let Some(BootstrapTagStatus::Failed(summary)) = &tag_status {
+            return Err(bootstrap_failed_error(instance_id, summary));
+        }
+        if !has_ssm && tag_status != last_tag {
+            if let Some(status) = &tag_status {
+                if output_format != "json" {
+                    println!(
+                        "   [{:>4}s] bootstrap {}",
+                        started.elapsed().as_secs(),
+                        status.as_str()
+                    );
+                }
+            }
+            last_tag = tag_status.clone();
+        }
+        if !has_ssm && tag_status == Some(BootstrapTagStatus::Complete) {
+            return Ok((None, tag_status));
+        }
+
+        if has_ssm {
+            match fetch_bootstrap_phase(&ssm_client, instance_id).await {
+                Ok(phase) => {
+                    if last_phase != Some(phase) {
+                        phase_started = std::time::Instant::now();
+                        stall_reported = false;
+                        if output_format != "json" {
+                            println!(
+                                "   [{:>4}s] {:<12} {}",
+                                started.elapsed().as_secs(),
+                                phase.marker(),
+                                phase.description()
+                            );
+                        }
+                        last_phase = Some(phase);
+                    }
+                    if phase == BootstrapPhase::Complete {
+                        return Ok((last_phase, tag_status));
+                    }
+                    let in_phase = phase_started.elapsed().as_secs();
+                    if !stall_reported && in_phase >= BOOTSTRAP_STALL_SECS {
+                        stall_reported = true;
+                        if output_format != "json" {
+                            println!(
+                                "   Still in phase '{}' after {}s - check /var/log/user-data.log",
+                                phase.marker(),
+                                in_phase
+                            );
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed to read bootstrap progress: {}", e);
+                }
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(BOOTSTRAP_POLL_INTERVAL_SECS)).await;
+    }
+
+    Err(TrainctlError::Resource {
+        resource_type: "instance".to_string(),
+        operation: "bootstrap".to_string(),
+        resource_id: Some(instance_id.to_string()),
+        message: format!(
+            "Bootstrap did not complete within {} minutes (last phase: {}). Check /var/log/user-data.log on instance {}",
+            BOOTSTRAP_POLL_MAX_ATTEMPTS as u64 * BOOTSTRAP_POLL_INTERVAL_SECS / 60,
+            last_phase
+                .map(|p| p.to_string())
+                .or_else(|| last_tag.map(|t| t.as_str().to_string()))
+                .unwrap_or_else(|| "unknown".to_string()),
+            instance_id
+        ),
+        source: None,
+    })
+}
@@ -635,7 +635,7 @@ async fn find_latest_checkpoint_in_s3(
     }
 
     // Sort by modification time (newest first)
-    checkpoints.sort_by(|a, b| b.1.cmp(&a.1));
+    checkpoints.sort_by_key(|b| std::cmp::Reverse(b.1));
 
     Ok(checkpoints.first().map(|(path, _)| path.clone()))
 }
@@ -6,10 +6,26 @@
 //!
 //! The AWS module is organized into focused submodules:
 //! - `instance`: Instance lifecycle (create, start, stop, terminate)
+//! - `adopt`: Bring manually created instances under runctl management
+//! - `autostop`: Idle watchdog that stops or terminates instances (`autostop`)
+//! - `ssh_key`: Private key lookup for key pairs and `ssh-check`
+//! - `ssh`: Interactive sessions (`ssh`) and `~/.ssh/config` entries (`ssh-config`)
+//! - `session`: SSM shells (`session`) and port forwarding (`forward`)
+//! - `tunnel`: TensorBoard and Jupyter on the instance through a local tunnel
+//! - `idempotency`: Client tokens and duplicate detection for `create`
+//! - `bulk`: Selector-based stop/start/terminate across many instances
+//! - `fleet`: Auto Scaling group worker fleets for the queue (`fleet`)
 //! - `training`: Training operations (train_on_instance, sync_code)
+//! - `monitor`: Training log tailing over SSM or SSH (monitor_instance)
+//! - `completion`: Configurable completion markers and health checks for `train --wait`
+//! - `debug`: Instance details, console output and logs for `jobs collect-debug`
 //! - `processes`: Process monitoring (show_processes)
+//! - `status`: Training probe, volumes and local session for `status`
+//! - `recording`: Process/GPU sampling to JSONL and run summaries
+//! - `spot_advisor`: Spot price comparison across zones and regions (`spot-advisor`)
 //! - `helpers`: Utility functions (status conversion, user/project detection)
 //! - `types`: Shared type definitions (options structs)
+//! - `user_data`: Bootstrap script rendering from the user-data template
 //!
 //! ## Design Philosophy
 //!
@@ -29,29 +45,75 @@
 //! - Spot instance fallback to on-demand (unless `--no-fallback`)
 //! - Automatic Deep Learning AMI detection for GPU instances
 
+mod adopt;
+mod ami;
 mod auto_resume;
+mod autostop;
+mod bootstrap;
+mod bulk;
+mod cluster;
+mod completion;
+mod debug;
+mod fleet;
 mod helpers;
+mod idempotency;
 mod instance;
+mod monitor;
 mod processes;
+mod recording;
+mod session;
+mod spot_advisor;
 mod spot_monitor;
+mod ssh;
+mod ssh_key;
+mod ssh_user;
 mod ssm_sync;
+mod status;
+mod teardown;
 mod training;
+mod tunnel;
 mod types;
+mod user_data;
 
+pub use crate::ami_filter::AmiFilter;
+pub use crate::config::AutostopAction;
+pub use bootstrap::{
+    bootstrap_status_from_tags, parse_bootstrap_log, BootstrapPhase, BootstrapTagStatus,
+    BOOTSTRAP_ERROR_TAG, BOOTSTRAP_TAG,
+};
+pub use bulk::{BulkCommands, InstanceSelector};
+pub use fleet::FleetCommands;
 // Re-export helpers that are used by other modules (pub(crate) for crate-internal use)
-pub(crate) use helpers::ec2_instance_to_resource_status;
+pub(crate) use auto_resume::{handle_auto_resume_command, interruption_checkpoint_prefix};
+pub(crate) use debug::collect_instance;
+pub(crate) use fleet::{idle_surplus, FleetPool};
 pub use helpers::get_project_name;
+pub(crate) use helpers::{
+    ec2_instance_to_resource_status, find_instance_in_response, instance_ipv6, public_address,
+    reconcile_account_instance, reconcile_instance,
+};
 pub use instance::{
     create_instance, create_instance_and_get_id, start_instance, stop_instance, terminate_instance,
 };
+#[cfg(feature = "dashboard")]
+pub(crate) use monitor::tail_log;
+#[cfg(feature = "dashboard")]
+pub(crate) use ssh::ssh_instance;
+pub(crate) use ssh_user::resolve_ssh_user;
+pub(crate) use ssm_sync::sync_code_via_ssm;
 // show_instance_status and wait_for_instance are used via instance:: prefix, no need to import
+pub use monitor::monitor_instance;
 pub use processes::show_processes;
-pub use training::{monitor_instance, train_on_instance};
-pub use types::{CreateInstanceOptions, TrainInstanceOptions};
+pub use spot_advisor::{SpotAdvisorOptions, SpotChoice};
+pub use training::train_on_instance;
+pub use types::{
+    CreateInstanceOptions, MonitorOptions, ProcessesOptions, TrainInstanceOptions,
+    TrainMultiOptions,
+};
+pub use user_data::render_user_data;
 
-use crate::config::Config;
-use crate::error::Result;
-use aws_config::BehaviorVersion;
+use crate::config::{Config, PresetConfig};
+use crate::error::{Result, TrainctlError};
 use clap::Subcommand;
 use std::path::PathBuf;
 
@@ -66,6 +128,7 @@ pub enum AwsCommands {
     ///   runctl aws create t3.medium
     ///   runctl aws create g4dn.xlarge --spot
     ///   runctl aws create p3.2xlarge --spot --data-volume-size 500
+    ///   runctl aws create --preset g5-dev
     #[command(alias = "new", alias = "spawn")]
     Create {
         /// EC2 instance type (e.g., t3.medium, g4dn.xlarge, p3.2xlarge)
@@ -73,8 +136,24 @@ pub enum AwsCommands {
         /// Common types:
         ///   - CPU: t3.medium, t3.large, m5.xlarge
         ///   - GPU: g4dn.xlarge, p3.2xlarge, p4d.24xlarge
-        #[arg(value_name = "INSTANCE_TYPE")]
-        instance_type: String,
+        ///
+        /// Optional when --preset supplies an instance type.
+        #[arg(
+            value_name = "INSTANCE_TYPE",
+            required_unless_present = "preset",
+            value_parser = crate::validation::instance_type_arg
+        )]
+        instance_type: Option<String>,
+
+        /// Named preset from `[presets.<name>]` in .runctl.toml
+        ///
+        /// Presets bundle instance type, spot, volumes, AMI, security group, and
+        /// bootstrap extras. Explicit flags override preset values.
+        ///
+        /// Example:
+        ///   runctl aws create --preset g5-dev
+        #[arg(long, value_name = "NAME")]
+        preset: Option<String>,
 
         /// Request spot instance (cheaper, can be interrupted)
         ///
@@ -83,11 +162,15 @@ pub enum AwsCommands {
         #[arg(long)]
         spot: bool,
 
+        /// Launch on-demand even if the preset requests spot
+        #[arg(long, conflicts_with = "spot")]
+        on_demand: bool,
+
         /// Maximum spot price per hour (e.g., 0.10)
         ///
         /// If not set, uses the current on-demand price as maximum.
         /// Set lower to save money, but may reduce availability.
-        #[arg(long, value_name = "PRICE")]
+        #[arg(long, value_name = "PRICE", value_parser = crate::validation::spot_price_arg)]
         spot_max_price: Option<String>,
 
         /// Don't fall back to on-demand if spot request fails
@@ -97,6 +180,13 @@ pub enum AwsCommands {
         #[arg(long)]
         no_fallback: bool,
 
+        /// Availability zone to launch in (e.g. us-east-1b)
+        ///
+        /// Spot launches without it use the zone saved by
+        /// `aws spot-advisor --apply` for this instance type, if any.
+        #[arg(long, value_name = "ZONE")]
+        availability_zone: Option<String>,
+
         /// SSH key pair name (for EC2 Key Pairs)
         #[arg(long, value_name = "KEY_NAME")]
         key_name: Option<String>,
@@ -109,15 +199,31 @@ pub enum AwsCommands {
         #[arg(long, value_name = "AMI_ID")]
         ami_id: Option<String>,
 
-        /// Root volume size in GB (default: 30, increased for GPU instances)
-        #[arg(long, value_name = "SIZE_GB")]
+        /// Look up the latest Deep Learning AMI instead of using the cached one
+        ///
+        /// Lookups are cached in .runctl/ami_cache.json for [aws] ami_cache_ttl_secs
+        /// (default 24h).
+        #[arg(long, conflicts_with = "ami_id")]
+        refresh_ami: bool,
+
+        /// Deep Learning AMI constraints, e.g. "pytorch=2.3,cuda=12.1,os=ubuntu22"
+        ///
+        /// Keys: pytorch, tensorflow, cuda, os (ubuntu20, ubuntu22, ubuntu24,
+        /// al2, al2023). Fails if no AMI satisfies them instead of taking the
+        /// newest image.
+        #[arg(long, value_name = "CONSTRAINTS", conflicts_with = "ami_id")]
+        ami_filter: Option<AmiFilter>,
+
+        /// Root volume size, e.g. 100 (GiB) or 200GB (default: 30, increased for GPU instances)
+        #[arg(long, value_name = "SIZE", value_parser = crate::units::parse_gib)]
         root_volume_size: Option<i32>,
 
-        /// Auto-attach EBS volume for data/cache (size in GB)
+        /// Auto-attach EBS volume for data/cache (e.g. 500, 500GB, 1TiB)
         ///
         /// Creates and attaches an additional EBS volume for datasets, checkpoints, etc.
         /// The volume persists after instance termination unless explicitly deleted.
-        #[arg(long, value_name = "SIZE_GB")]
+        /// Bare numbers are GiB.
+        #[arg(long, value_name = "SIZE", value_parser = crate::units::parse_gib)]
         data_volume_size: Option<i32>,
 
         /// Project directory name (default: current directory name)
@@ -150,6 +256,220 @@ pub enum AwsCommands {
         /// Without this flag, instance creation returns immediately after launch.
         #[arg(long)]
         wait: bool,
+
+        /// EC2 client token for idempotent creation (max 64 ASCII characters)
+        ///
+        /// By default a token is derived from the launch parameters, so re-running
+        /// an identical create within ~10 minutes returns the original instance
+        /// instead of launching another. Pass a token to pin it across retries.
+        #[arg(long, value_name = "TOKEN")]
+        client_token: Option<String>,
+
+        /// Always launch a new instance
+        ///
+        /// Skips the check for a recent instance with the same project and
+        /// settings, and the derived client token.
+        #[arg(long)]
+        allow_duplicate: bool,
+
+        /// SSH login user to record for the instance (default: from the AMI)
+        ///
+        /// Stored in the runctl:ssh-user tag and used by train, monitor and
+        /// the other SSH-based commands. Needed only for custom AMIs whose
+        /// name and description don't identify the distribution.
+        #[arg(long, value_name = "USER", value_parser = ssh_user::parse_ssh_user)]
+        ssh_user: Option<String>,
+    },
+    /// Adopt an existing instance that runctl didn't create
+    ///
+    /// Tags the instance with runctl metadata (project, user, timestamps),
+    /// registers it with the resource tracker, and verifies SSM/SSH access so
+    /// it can be used with train, monitor, and the other commands.
+    ///
+    /// Project and user are inferred from existing tags (Project, Owner, ...)
+    /// or fall back to the current directory and configured user.
+    ///
+    /// Examples:
+    ///   runctl aws adopt i-1234567890abcdef0
+    ///   runctl aws adopt i-1234567890abcdef0 --project-name llm --dry-run
+    #[command(alias = "import")]
+    Adopt {
+        /// EC2 instance ID
+        #[arg(value_name = "INSTANCE_ID")]
+        instance_id: String,
+
+        /// Project name (default: inferred from tags, then current directory)
+        #[arg(long, value_name = "NAME")]
+        project_name: Option<String>,
+
+        /// Owner to record (default: inferred from tags, then config/$USER)
+        #[arg(long, value_name = "USER")]
+        user: Option<String>,
+
+        /// Show the tags that would be written without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Check SSH access to an instance before training
+    ///
+    /// Resolves the login user and private key the way train does (key
+    /// lookup: SSH_KEY_PATH, [ssh.keys], then [ssh] key_dir), then checks the
+    /// key's permissions, port 22 and an actual login. Exits non-zero if any
+    /// check fails.
+    ///
+    /// Examples:
+    ///   runctl aws ssh-check i-1234567890abcdef0
+    ///   runctl aws ssh-check i-1234567890abcdef0 --ssh-user ubuntu
+    SshCheck {
+        /// EC2 instance ID
+        #[arg(value_name = "INSTANCE_ID")]
+        instance_id: String,
+
+        /// SSH login user (default: the runctl:ssh-user tag, then the AMI)
+        #[arg(long, value_name = "USER", value_parser = ssh_user::parse_ssh_user)]
+        ssh_user: Option<String>,
+    },
+    /// Open an SSH session on an instance
+    ///
+    /// Looks up the instance's address, login user and private key the way
+    /// train does, pins its host key, and runs the system ssh client. Pass a
+    /// command after `--` to run it instead of an interactive shell.
+    ///
+    /// Examples:
+    ///   runctl aws ssh i-1234567890abcdef0
+    ///   runctl aws ssh i-1234567890abcdef0 -- nvidia-smi
+    Ssh {
+        /// EC2 instance ID
+        #[arg(value_name = "INSTANCE_ID")]
+        instance_id: String,
+
+        /// SSH login user (default: the runctl:ssh-user tag, then the AMI)
+        #[arg(long, value_name = "USER", value_parser = ssh_user::parse_ssh_user)]
+        ssh_user: Option<String>,
+
+        /// Command to run instead of a login shell
+        #[arg(last = true, value_name = "COMMAND")]
+        command: Vec<String>,
+    },
+    /// Print ~/.ssh/config Host entries for runctl instances
+    ///
+    /// One entry per instance tagged by runctl, named by instance ID (and
+    /// `runctl-<Name tag>`), with its address, user, key, pinned host key and
+    /// [ssh] bastion. Public IPs change when an instance stops; rerun after
+    /// starting one. --write keeps the entries in a marked block of the file.
+    ///
+    /// Examples:
+    ///   runctl aws ssh-config >> ~/.ssh/config
+    ///   runctl aws ssh-config --write
+    SshConfig {
+        /// Update the runctl block in ~/.ssh/config instead of printing
+        #[arg(long)]
+        write: bool,
+
+        /// Config file to update with --write (default: ~/.ssh/config)
+        #[arg(long, requires = "write")]
+        file: Option<PathBuf>,
+    },
+    /// Open an interactive SSM session on an instance
+    ///
+    /// Needs no key pair, public IP or inbound security group rule: the
+    /// instance needs an instance profile with SSM access, and the AWS CLI
+    /// and Session Manager plugin must be installed locally.
+    ///
+    /// Examples:
+    ///   runctl aws session i-1234567890abcdef0
+    Session {
+        /// EC2 instance ID
+        #[arg(value_name = "INSTANCE_ID")]
+        instance_id: String,
+    },
+    /// Forward a local port to an instance port over SSM
+    ///
+    /// For TensorBoard, Jupyter and other services listening on the
+    /// instance, without opening the port in the security group. Runs until
+    /// interrupted.
+    ///
+    /// Examples:
+    ///   runctl aws forward i-1234567890abcdef0 --remote 8888
+    ///   runctl aws forward i-1234567890abcdef0 --remote 6006 --local 16006
+    Forward {
+        /// EC2 instance ID
+        #[arg(value_name = "INSTANCE_ID")]
+        instance_id: String,
+
+        /// Port on the instance
+        #[arg(long, value_name = "PORT", value_parser = clap::value_parser!(u16).range(1..))]
+        remote: u16,
+
+        /// Local port (default: same as --remote)
+        #[arg(long, value_name = "PORT", value_parser = clap::value_parser!(u16).range(1..))]
+        local: Option<u16>,
+    },
+    /// Open TensorBoard on an instance in the local browser
+    ///
+    /// Starts TensorBoard on the instance (bound to 127.0.0.1, in the project
+    /// directory `train` used) unless it is already running, forwards a local
+    /// port to it over SSM or SSH, and opens the browser. Ctrl-C closes the
+    /// tunnel; TensorBoard keeps running.
+    ///
+    /// Examples:
+    ///   runctl aws tensorboard i-1234567890abcdef0
+    ///   runctl aws tensorboard i-1234567890abcdef0 --logdir runs/ --local-port 16006
+    Tensorboard {
+        /// EC2 instance ID
+        #[arg(value_name = "INSTANCE_ID")]
+        instance_id: String,
+
+        /// Log directory, relative to the project directory
+        #[arg(long, value_name = "DIR", default_value = "runs")]
+        logdir: String,
+
+        /// Port TensorBoard listens on (default: 6006)
+        #[arg(long, value_name = "PORT", value_parser = clap::value_parser!(u16).range(1..))]
+        port: Option<u16>,
+
+        /// Local port (default: same as --port)
+        #[arg(long, value_name = "PORT", value_parser = clap::value_parser!(u16).range(1..))]
+        local_port: Option<u16>,
+
+        /// Print the URL without opening a browser
+        #[arg(long)]
+        no_browser: bool,
+
+        /// SSH login user (default: the runctl:ssh-user tag, then the AMI)
+        #[arg(long, value_name = "USER", value_parser = ssh_user::parse_ssh_user)]
+        ssh_user: Option<String>,
+    },
+    /// Open JupyterLab on an instance in the local browser
+    ///
+    /// Starts JupyterLab on the instance (bound to 127.0.0.1, in the project
+    /// directory, with a token kept in .runctl-jupyter-token) unless it is
+    /// already running, forwards a local port to it over SSM or SSH, and opens
+    /// the browser. Ctrl-C closes the tunnel; JupyterLab keeps running.
+    ///
+    /// Examples:
+    ///   runctl aws jupyter i-1234567890abcdef0
+    ///   runctl aws jupyter i-1234567890abcdef0 --local-port 18888 --no-browser
+    Jupyter {
+        /// EC2 instance ID
+        #[arg(value_name = "INSTANCE_ID")]
+        instance_id: String,
+
+        /// Port JupyterLab listens on (default: 8888)
+        #[arg(long, value_name = "PORT", value_parser = clap::value_parser!(u16).range(1..))]
+        port: Option<u16>,
+
+        /// Local port (default: same as --port)
+        #[arg(long, value_name = "PORT", value_parser = clap::value_parser!(u16).range(1..))]
+        local_port: Option<u16>,
+
+        /// Print the URL without opening a browser
+        #[arg(long)]
+        no_browser: bool,
+
+        /// SSH login user (default: the runctl:ssh-user tag, then the AMI)
+        #[arg(long, value_name = "USER", value_parser = ssh_user::parse_ssh_user)]
+        ssh_user: Option<String>,
     },
     /// Start training job on an EC2 instance
     ///
@@ -172,13 +492,14 @@ pub enum AwsCommands {
         /// S3 path for training data (s3://bucket/path)
         ///
         /// If provided, data will be downloaded before training starts.
-        #[arg(long, value_name = "S3_PATH")]
+        #[arg(long, value_name = "S3_PATH", value_parser = crate::validation::s3_uri_arg)]
         data_s3: Option<String>,
 
         /// S3 path for output/checkpoints (s3://bucket/path)
         ///
-        /// If provided, checkpoints will be uploaded to S3 after training.
-        #[arg(long, value_name = "S3_PATH")]
+        /// Where --stop-on-complete/--terminate-on-complete upload the
+        /// checkpoints and training.log before shutting down.
+        #[arg(long, value_name = "S3_PATH", value_parser = crate::validation::s3_uri_arg)]
         _output_s3: Option<String>,
 
         /// Sync code before training (default: true)
@@ -197,10 +518,50 @@ pub enum AwsCommands {
         #[arg(long, value_name = "PATTERN")]
         include_pattern: Vec<String>,
 
+        /// Never sync files matching this pattern (gitignore syntax)
+        ///
+        /// Added to `[sync] exclude`. Can be specified multiple times.
+        ///
+        /// Example: --exclude-pattern wandb/ --exclude-pattern '*.ckpt'
+        #[arg(long, value_name = "PATTERN")]
+        exclude_pattern: Vec<String>,
+
+        /// Skip files larger than this when syncing (e.g. 100MB)
+        ///
+        /// Overrides `[sync] max_file_size`.
+        #[arg(long, value_name = "SIZE", value_parser = crate::units::parse_size)]
+        max_file_size: Option<u64>,
+
+        /// Skip the confirmation for syncs over `[sync] confirm_above`
+        #[arg(short, long)]
+        yes: bool,
+
         /// Project directory name (default: current directory name)
         #[arg(long, value_name = "NAME")]
         project_name: Option<String>,
 
+        /// SSH login user (default: the runctl:ssh-user tag, then the AMI)
+        #[arg(long, value_name = "USER", value_parser = ssh_user::parse_ssh_user)]
+        ssh_user: Option<String>,
+
+        /// Resume from the latest checkpoint
+        ///
+        /// Looks in the checkpoint directory on the instance, the local
+        /// `[checkpoint] dir` and the `checkpoint sync` prefix in S3, takes
+        /// the newest, copies it to the instance if needed and passes its
+        /// path to the script after --resume-arg. Needs an instance profile.
+        #[arg(long)]
+        resume: bool,
+
+        /// Script flag that takes the checkpoint path with --resume
+        #[arg(
+            long,
+            value_name = "FLAG",
+            default_value = "--resume-from",
+            requires = "resume"
+        )]
+        resume_arg: String,
+
         /// Additional arguments to pass to training script
         ///
         /// IMPORTANT: Use '--' (double dash) to separate runctl args from script args.
@@ -214,10 +575,26 @@ pub enum AwsCommands {
         #[arg(last = true, value_name = "ARGS")]
         script_args: Vec<String>,
 
+        /// Environment variable for the training script (repeatable)
+        ///
+        /// Merged over `[env]` and --env-file. Values may be secret://name
+        /// references; values of keys like *_TOKEN or *_API_KEY are masked
+        /// in logs.
+        ///
+        /// Example: --env WANDB_PROJECT=vision --env HF_TOKEN=secret://hf-token
+        #[arg(long = "env", value_name = "KEY=VALUE", value_parser = crate::env_vars::parse_env_arg)]
+        env: Vec<(String, String)>,
+
+        /// Load environment variables for the training script from a .env file
+        #[arg(long, value_name = "PATH")]
+        env_file: Option<PathBuf>,
+
         /// Wait for training to complete before returning
         ///
         /// Blocks until training completes (checks for completion markers, checkpoints, or process status).
         /// Without this flag, training starts in background and command returns immediately.
+        /// The exit code reports the outcome: 0 success, 10 failed, 11 timed out,
+        /// 13 health checks failed.
         #[arg(long)]
         wait: bool,
 
@@ -231,28 +608,168 @@ pub enum AwsCommands {
         #[arg(long, value_name = "MINUTES", default_value = "120")]
         timeout: u64,
 
-        /// Run training in Docker container
+        /// Run training in a Docker container built from the project's Dockerfile
         ///
-        /// If provided, training will run in a Docker container using an ECR image.
-        /// Requires Dockerfile in project root and ECR repository configured.
-        /// Automatically detects Dockerfile and builds/pushes if needed.
+        /// Builds the Dockerfile (see `runctl docker build`), pushes it to
+        /// ECR as `<project>:latest`, and runs it like --image.
         #[arg(long)]
         docker: bool,
 
-        /// ECR image to use for Docker training (default: auto-detect from Dockerfile)
+        /// Run training in this container image
         ///
-        /// If not provided, will build and push image from Dockerfile.
-        /// Format: `{account-id}.dkr.ecr.{region}.amazonaws.com/{repository}:{tag}`
-        #[arg(long, value_name = "ECR_IMAGE")]
+        /// Docker is installed if the AMI lacks it, ECR images are pulled
+        /// with the instance profile's credentials, and the script runs in
+        /// the image with the synced code at /workspace, data volumes
+        /// mounted and GPUs passed through. Output goes to training.log, so
+        /// monitor, --wait and the completion hooks work unchanged.
+        /// Example: 123456789012.dkr.ecr.us-east-1.amazonaws.com/train:latest
+        #[arg(long = "image", visible_alias = "docker-image", value_name = "IMAGE")]
         docker_image: Option<String>,
+
+        /// Treat this file (relative to the project dir) as the completion marker
+        ///
+        /// Overrides `[job.completion] marker_file`. When any completion method
+        /// is set, the built-in heuristics are not used.
+        #[arg(long, value_name = "PATH")]
+        complete_marker: Option<String>,
+
+        /// Treat a training.log line matching this regex as completion
+        ///
+        /// Overrides `[job.completion] log_regex`.
+        #[arg(long, value_name = "REGEX")]
+        complete_log_regex: Option<String>,
+
+        /// Use the wrapper's exit code for completion (non-zero fails the wait)
+        #[arg(long)]
+        complete_on_exit: bool,
+
+        /// Start `runctl agent` on the instance to report heartbeat, metrics and progress
+        ///
+        /// `aws status`, `top` and `watch` then read the status from
+        /// s3://<[aws] s3_bucket>/runctl/agent/ instead of polling over SSM.
+        /// Needs runctl installed on the instance and an instance profile.
+        #[arg(long)]
+        agent: bool,
+
+        /// Terminate the instance once training exits
+        ///
+        /// A hook on the instance waits for the training process, uploads the
+        /// checkpoints and training.log to S3 (--output-s3, else
+        /// s3://<[aws] s3_bucket>/runctl/checkpoints/<project>/<instance>),
+        /// then shuts down. Works without this command staying connected.
+        #[arg(long, conflicts_with = "stop_on_complete")]
+        terminate_on_complete: bool,
+
+        /// Stop the instance once training exits (see --terminate-on-complete)
+        #[arg(long)]
+        stop_on_complete: bool,
+    },
+    /// Launch a multi-node cluster and start a distributed training run
+    ///
+    /// Creates N instances in one cluster placement group, sets up
+    /// passwordless SSH from node 0 to every node (plus ~/.runctl/hostfile),
+    /// syncs the code to all nodes and starts torchrun or DeepSpeed on each
+    /// with MASTER_ADDR, MASTER_PORT, NNODES and NODE_RANK set. Setup runs
+    /// over SSM, so an instance profile is required.
+    ///
+    /// Examples:
+    ///   runctl aws train-multi train.py --nodes 2 --instance-type p4d.24xlarge
+    ///   runctl aws train-multi train.py --nodes 4 --instance-type p5.48xlarge --efa \
+    ///     --security-group sg-0123 --launcher deepspeed --wait -- --epochs 10
+    TrainMulti {
+        /// Training script path (Python script)
+        #[arg(value_name = "SCRIPT")]
+        script: PathBuf,
+
+        /// Number of nodes (2-64)
+        #[arg(long, value_name = "N")]
+        nodes: u32,
+
+        /// EC2 instance type for every node (e.g., p4d.24xlarge)
+        #[arg(long, value_name = "TYPE")]
+        instance_type: String,
+
+        /// Distributed launcher: torchrun or deepspeed
+        #[arg(long, value_name = "LAUNCHER", default_value = "torchrun")]
+        launcher: cluster::Launcher,
+
+        /// Processes per node (default: one per GPU)
+        #[arg(long, value_name = "N")]
+        gpus_per_node: Option<u32>,
+
+        /// Rendezvous port on node 0
+        #[arg(long, value_name = "PORT", default_value_t = cluster::DEFAULT_MASTER_PORT)]
+        master_port: u16,
+
+        /// Attach an Elastic Fabric Adapter to every node (p4d, p5, ...)
+        ///
+        /// Requires --security-group with a rule allowing all traffic from
+        /// the group itself.
+        #[arg(long)]
+        efa: bool,
+
+        /// SSH key pair name (for EC2 Key Pairs)
+        #[arg(long, value_name = "KEY_NAME")]
+        key_name: Option<String>,
+
+        /// Security group ID for every node
+        #[arg(long, value_name = "SECURITY_GROUP")]
+        security_group: Option<String>,
+
+        /// IAM instance profile name for SSM access (default: [aws] iam_instance_profile)
+        #[arg(long, value_name = "PROFILE_NAME")]
+        iam_instance_profile: Option<String>,
+
+        /// AMI ID (default: latest Deep Learning AMI)
+        #[arg(long, value_name = "AMI_ID")]
+        ami_id: Option<String>,
+
+        /// Root volume size, e.g. 100 (GiB) or 200GB (default: 100)
+        #[arg(long, value_name = "SIZE", value_parser = crate::units::parse_gib)]
+        root_volume_size: Option<i32>,
+
+        /// Project directory name (default: current directory name)
+        #[arg(long, value_name = "NAME")]
+        project_name: Option<String>,
+
+        /// Include patterns even if gitignored (e.g., data/)
+        #[arg(long, value_name = "PATTERN")]
+        include_pattern: Vec<String>,
+
+        /// Never sync files matching this pattern (gitignore syntax)
+        #[arg(long, value_name = "PATTERN")]
+        exclude_pattern: Vec<String>,
+
+        /// Skip the confirmation for syncs over `[sync] confirm_above`
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Wait for every node to finish, printing each node's latest log line
+        ///
+        /// Fails as soon as one node exits non-zero.
+        #[arg(long)]
+        wait: bool,
+
+        /// Minutes to wait with --wait (0 disables the timeout)
+        #[arg(long, value_name = "MINUTES", default_value = "120")]
+        timeout: u64,
+
+        /// Additional arguments to pass to the training script (after '--')
+        #[arg(last = true, value_name = "ARGS")]
+        script_args: Vec<String>,
     },
     /// Monitor training progress on an instance
     ///
-    /// Shows training logs and checkpoint progress. Use --follow for continuous updates.
+    /// Shows the end of the training log, read over SSM or (without an
+    /// instance profile) SSH. The log path is taken from the instance's tags.
+    /// Use --follow for continuous updates; it stops when the training process
+    /// exits and returns 0, or 10 if the process exited non-zero. With
+    /// --output json each log line is a JSON object.
     ///
     /// Examples:
     ///   runctl aws monitor i-1234567890abcdef0
-    ///   runctl aws monitor i-1234567890abcdef0 --follow
+    ///   runctl aws monitor i-1234567890abcdef0 --follow --lines 200
+    ///   runctl --output json aws monitor i-1234567890abcdef0 --follow
     #[command(alias = "watch", alias = "logs")]
     Monitor {
         /// EC2 instance ID
@@ -262,6 +779,14 @@ pub enum AwsCommands {
         /// Follow mode (continuous updates, like tail -f)
         #[arg(short, long)]
         follow: bool,
+
+        /// Lines of existing output to show
+        #[arg(short = 'n', long, value_name = "N", default_value_t = 50)]
+        lines: usize,
+
+        /// SSH login user (default: the runctl:ssh-user tag, then the AMI)
+        #[arg(long, value_name = "USER", value_parser = ssh_user::parse_ssh_user)]
+        ssh_user: Option<String>,
     },
 
     /// Stop an instance (preserves data, can be restarted)
@@ -272,6 +797,7 @@ pub enum AwsCommands {
     /// Examples:
     ///   runctl aws stop i-1234567890abcdef0
     ///   runctl aws stop i-1234567890abcdef0 --force
+    ///   runctl aws stop i-1234567890abcdef0 --checkpoint-wait 10m
     #[command(alias = "pause")]
     Stop {
         /// EC2 instance ID
@@ -283,6 +809,11 @@ pub enum AwsCommands {
         /// Skips checks for running training jobs. Use with caution.
         #[arg(long)]
         force: bool,
+
+        /// How long the training script may take to checkpoint at its next
+        /// boundary before SIGTERM (default: [checkpoint] stop_wait_secs; 0 = SIGTERM now)
+        #[arg(long, value_name = "DURATION", value_parser = crate::units::parse_secs)]
+        checkpoint_wait: Option<u64>,
     },
 
     /// Start a stopped instance
@@ -335,25 +866,133 @@ pub enum AwsCommands {
     /// Examples:
     ///   runctl aws processes i-1234567890abcdef0
     ///   runctl aws processes i-1234567890abcdef0 --watch --detailed
+    ///   runctl aws processes i-1234567890abcdef0 --tree
+    ///   runctl aws processes i-1234567890abcdef0 --record run.jsonl --interval 10
+    ///   runctl aws processes --replay run.jsonl
     Processes {
         /// EC2 instance ID (e.g., i-1234567890abcdef0)
-        #[arg(value_name = "INSTANCE_ID")]
-        instance_id: String,
+        #[arg(value_name = "INSTANCE_ID", required_unless_present = "replay")]
+        instance_id: Option<String>,
         /// Show detailed process information
         #[arg(short, long)]
         detailed: bool,
         /// Watch mode (auto-refresh)
         #[arg(short, long)]
         watch: bool,
-        /// Refresh interval for watch mode (seconds)
-        #[arg(long, default_value = "2")]
+        /// Refresh interval for watch mode (e.g. 2, 10s, 1m; bare numbers are seconds)
+        #[arg(long, default_value = "2", value_parser = crate::units::parse_secs)]
         interval: u64,
+        /// Show the process tree rooted at the training process
+        ///
+        /// Includes DataLoader workers with per-process CPU/memory and flags
+        /// defunct (zombie) workers.
+        #[arg(long)]
+        tree: bool,
+        /// Root the tree at this PID instead of the detected training process
+        #[arg(long, value_name = "PID", requires = "tree")]
+        pid: Option<u32>,
+        /// Record samples every --interval seconds to a JSONL file (Ctrl+C to stop)
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["tree", "watch"])]
+        record: Option<PathBuf>,
+        /// Summarize a recording: peak memory, average GPU utilization, CPU bottlenecks
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["record", "tree", "watch"])]
+        replay: Option<PathBuf>,
     },
     /// EBS volume management
     Ebs {
         #[command(subcommand)]
         subcommand: crate::ebs::EbsCommands,
     },
+    /// Stop, start, or terminate many instances by selector
+    ///
+    /// Selects instances by project, user, age, and type, shows a preview
+    /// table, and asks for confirmation before acting.
+    ///
+    /// Examples:
+    ///   runctl aws bulk stop --project llm --older-than 24h --type 'g4dn.*'
+    ///   runctl aws bulk terminate --project sweep-42 --dry-run
+    Bulk {
+        #[command(subcommand)]
+        subcommand: BulkCommands,
+    },
+    /// Auto Scaling group fleets of long-lived training workers
+    ///
+    /// Workers come from a launch template built from a preset and stay up
+    /// between jobs; `runctl queue run --fleet <name>` runs queued jobs on
+    /// idle ones.
+    ///
+    /// Examples:
+    ///   runctl aws fleet create workers --preset g5-dev --size 4
+    ///   runctl aws fleet scale workers 8
+    ///   runctl aws fleet refresh workers --preset g6-dev
+    ///   runctl aws fleet status workers
+    Fleet {
+        #[command(subcommand)]
+        subcommand: FleetCommands,
+    },
+    /// Compare spot prices across zones and regions and pick where to launch
+    ///
+    /// Shows current, min, and max spot prices per availability zone over the
+    /// last few days, the Spot Instance Advisor interruption frequency, and
+    /// the savings over on-demand. Recommends the cheapest zone and a
+    /// --spot-max-price with 20% headroom over the highest recent price.
+    ///
+    /// With --apply, `aws create --spot` for this instance type uses the
+    /// recommended zone (and max price, unless given) for the next 24 hours.
+    ///
+    /// Examples:
+    ///   runctl aws spot-advisor g5.xlarge
+    ///   runctl aws spot-advisor g5.xlarge --regions us-east-1,us-west-2 --days 14
+    ///   runctl aws spot-advisor p3.2xlarge --regions all --apply
+    SpotAdvisor {
+        /// EC2 instance type (e.g., g5.xlarge)
+        #[arg(value_name = "INSTANCE_TYPE", value_parser = crate::validation::instance_type_arg)]
+        instance_type: String,
+
+        /// Regions to compare, comma-separated, or "all" (default: current region)
+        #[arg(long, value_name = "REGIONS", value_delimiter = ',')]
+        regions: Vec<String>,
+
+        /// Days of price history to consider
+        #[arg(long, default_value_t = 7, value_name = "DAYS")]
+        days: u32,
+
+        /// Use the recommended zone for subsequent `aws create --spot` calls
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Stop or terminate an instance automatically once it sits idle
+    ///
+    /// Installs a watchdog (over SSM) that checks GPU utilization and training
+    /// processes every minute and shuts the instance down after the idle
+    /// threshold. Set `autostop_idle_minutes` under [aws] to install it on
+    /// every new instance at creation time.
+    ///
+    /// Examples:
+    ///   runctl aws autostop i-1234567890abcdef0 --idle-minutes 30
+    ///   runctl aws autostop i-1234567890abcdef0 --idle-minutes 60 --action terminate
+    ///   runctl aws autostop i-1234567890abcdef0 --disable
+    Autostop {
+        /// Instance ID
+        #[arg(value_name = "INSTANCE_ID")]
+        instance_id: String,
+
+        /// Minutes without GPU load or a training process before shutdown
+        #[arg(long, default_value_t = 30, value_name = "MINUTES")]
+        idle_minutes: u64,
+
+        /// What to do once idle: stop (keeps volumes) or terminate
+        #[arg(long, value_enum, default_value_t = AutostopAction::Stop)]
+        action: AutostopAction,
+
+        /// GPU utilization percent at or above which the instance counts as busy
+        #[arg(long, default_value_t = autostop::DEFAULT_GPU_BUSY_PERCENT, value_name = "PERCENT")]
+        gpu_threshold: u32,
+
+        /// Remove a previously installed watchdog
+        #[arg(long, conflicts_with_all = ["action", "gpu_threshold"])]
+        disable: bool,
+    },
     /// Show instance status and training state
     ///
     /// Displays current instance state, training status, and resource usage.
@@ -366,17 +1005,52 @@ pub enum AwsCommands {
         #[arg(value_name = "INSTANCE_ID")]
         instance_id: String,
     },
+    /// Show the lifecycle events of an instance in order
+    ///
+    /// Lists when the instance was created, finished bootstrapping, started
+    /// training, saved checkpoints, got a spot interruption warning, and was
+    /// stopped, started or terminated, with the time between events. Events
+    /// come from `.runctl/events.jsonl` and the cost ledger.
+    ///
+    /// Examples:
+    ///   runctl aws timeline i-1234567890abcdef0
+    ///   runctl --output json aws timeline i-1234567890abcdef0
+    Timeline {
+        /// EC2 instance ID
+        #[arg(value_name = "INSTANCE_ID")]
+        instance_id: String,
+    },
     /// Wait for instance to be ready
     ///
     /// Blocks until instance is running and SSM is connected (if IAM profile configured).
+    /// With the global --verbose flag, also reports bootstrap progress (cloud-init,
+    /// packages, uv, data volume, SSM agent) from the user-data log until setup completes.
     ///
     /// Examples:
     ///   runctl aws wait i-1234567890abcdef0
+    ///   runctl aws wait i-1234567890abcdef0 --verbose
     Wait {
         /// EC2 instance ID
         #[arg(value_name = "INSTANCE_ID")]
         instance_id: String,
     },
+    /// Preview the user-data bootstrap script for new instances
+    ///
+    /// Renders the script exactly as `aws create` would, including [bootstrap]
+    /// hooks, extra packages, and custom mounts from config.
+    ///
+    /// Examples:
+    ///   runctl aws render-user-data
+    ///   runctl aws render-user-data --project-name my-project --data-volume > user-data.sh
+    RenderUserData {
+        /// Project directory name (default: current directory name)
+        #[arg(long, value_name = "NAME")]
+        project_name: Option<String>,
+
+        /// Render as if a data volume will be attached
+        #[arg(long)]
+        data_volume: bool,
+    },
     /// Auto-resume training on a new instance after spot interruption
     ///
     /// This command is typically called internally by the spot monitoring system.
@@ -398,41 +1072,201 @@ pub enum AwsCommands {
     },
 }
 
-pub async fn handle_command(cmd: AwsCommands, config: &Config, output_format: &str) -> Result<()> {
-    let aws_config = aws_config::load_defaults(BehaviorVersion::latest()).await;
+pub async fn handle_command(
+    cmd: AwsCommands,
+    config: &Config,
+    output_format: &str,
+    verbose: bool,
+) -> Result<()> {
+    let aws_config = crate::aws_utils::shared_sdk_config().await;
 
     match cmd {
         AwsCommands::Create {
             instance_type,
+            preset,
             spot,
+            on_demand,
             spot_max_price,
             no_fallback,
+            availability_zone,
             key_name,
             security_group,
             ami_id,
+            refresh_ami,
+            ami_filter,
             root_volume_size,
             data_volume_size,
             project_name,
             iam_instance_profile,
             wait,
+            client_token,
+            allow_duplicate,
+            ssh_user,
         } => {
             let final_project_name = helpers::get_project_name(project_name, config);
             crate::validation::validate_project_name(&final_project_name)?;
+
+            // Explicit flags win over preset values; preset bootstrap extras
+            // are appended to the global [bootstrap] section.
+            let (preset_config, effective_config) = match preset.as_deref() {
+                Some(name) => {
+                    let preset_config = config.preset(name)?.clone();
+                    let mut effective = config.clone();
+                    effective.bootstrap = config.bootstrap.merged_with(&preset_config.bootstrap);
+                    (preset_config, effective)
+                }
+                None => (PresetConfig::default(), config.clone()),
+            };
+            let instance_type = instance_type
+                .or(preset_config.instance_type)
+                .ok_or_else(|| TrainctlError::Validation {
+                    field: "instance_type".to_string(),
+                    reason: format!(
+                        "Preset '{}' does not set instance_type; pass INSTANCE_TYPE explicitly",
+                        preset.as_deref().unwrap_or_default()
+                    ),
+                })?;
+
+            let use_spot = !on_demand && (spot || preset_config.spot.unwrap_or(false));
+            let mut spot_max_price = spot_max_price.or(preset_config.spot_max_price);
+            let mut availability_zone = availability_zone;
+            // A zone picked with `spot-advisor --apply` steers later spot launches
+            let region = aws_config.region().map(|r| r.as_ref().to_string());
+            if let (true, None, Some(region)) = (use_spot, &availability_zone, region) {
+                if let Some(choice) = spot_advisor::applied_choice(&instance_type, &region) {
+                    if output_format != "json" {
+                        println!(
+                            "Using {} from `aws spot-advisor --apply` (max price ${})",
+                            choice.zone,
+                            spot_max_price.as_deref().unwrap_or(&choice.max_price)
+                        );
+                    }
+                    spot_max_price = spot_max_price.or(Some(choice.max_price));
+                    availability_zone = Some(choice.zone);
+                }
+            }
+
             let options = CreateInstanceOptions {
                 instance_type,
-                use_spot: spot,
+                use_spot,
                 spot_max_price,
                 no_fallback,
-                key_name,
-                security_group,
-                ami_id,
-                root_volume_size,
-                data_volume_size,
+                availability_zone,
+                key_name: key_name.or(preset_config.key_name),
+                security_group: security_group.or(preset_config.security_group),
+                // A command-line filter overrides a preset's pinned AMI
+                ami_id: ami_id.or(preset_config.ami_id.filter(|_| ami_filter.is_none())),
+                root_volume_size: root_volume_size.or(preset_config.root_volume_size),
+                data_volume_size: data_volume_size.or(preset_config.data_volume_size),
                 project_name: final_project_name,
-                iam_instance_profile,
+                iam_instance_profile: iam_instance_profile.or(preset_config.iam_instance_profile),
                 wait,
+                preset,
+                client_token,
+                allow_duplicate,
+                refresh_ami,
+                ami_filter: ami_filter.or(preset_config.ami_filter),
+                ssh_user,
+            };
+            create_instance(options, &effective_config, &aws_config, output_format).await
+        }
+        AwsCommands::Adopt {
+            instance_id,
+            project_name,
+            user,
+            dry_run,
+        } => {
+            crate::validation::validate_instance_id(&instance_id)?;
+            let options = adopt::AdoptOptions {
+                instance_id,
+                project_name,
+                user,
+                dry_run,
+            };
+            adopt::adopt_instance(options, config, &aws_config, output_format).await
+        }
+        AwsCommands::SshCheck {
+            instance_id,
+            ssh_user,
+        } => {
+            crate::validation::validate_instance_id(&instance_id)?;
+            ssh_key::ssh_check(
+                &instance_id,
+                ssh_user.as_deref(),
+                config,
+                &aws_config,
+                output_format,
+            )
+            .await
+        }
+        AwsCommands::Ssh {
+            instance_id,
+            ssh_user,
+            command,
+        } => {
+            crate::validation::validate_instance_id(&instance_id)?;
+            ssh::ssh_instance(
+                &instance_id,
+                ssh_user.as_deref(),
+                &command,
+                config,
+                &aws_config,
+            )
+            .await
+        }
+        AwsCommands::SshConfig { write, file } => {
+            ssh::ssh_config(write, file, config, &aws_config, output_format).await
+        }
+        AwsCommands::Session { instance_id } => {
+            crate::validation::validate_instance_id(&instance_id)?;
+            session::start_session(&instance_id, &aws_config).await
+        }
+        AwsCommands::Forward {
+            instance_id,
+            remote,
+            local,
+        } => {
+            crate::validation::validate_instance_id(&instance_id)?;
+            session::forward_port(&instance_id, remote, local, &aws_config, output_format).await
+        }
+        AwsCommands::Tensorboard {
+            instance_id,
+            logdir,
+            port,
+            local_port,
+            no_browser,
+            ssh_user,
+        } => {
+            crate::validation::validate_instance_id(&instance_id)?;
+            let options = tunnel::TunnelOptions {
+                instance_id,
+                tool: tunnel::Tool::Tensorboard,
+                logdir,
+                remote_port: port.unwrap_or(tunnel::Tool::Tensorboard.default_port()),
+                local_port,
+                open_browser: !no_browser,
+                ssh_user,
             };
-            create_instance(options, config, &aws_config, output_format).await
+            tunnel::open_tunnel(options, config, &aws_config, output_format).await
+        }
+        AwsCommands::Jupyter {
+            instance_id,
+            port,
+            local_port,
+            no_browser,
+            ssh_user,
+        } => {
+            crate::validation::validate_instance_id(&instance_id)?;
+            let options = tunnel::TunnelOptions {
+                instance_id,
+                tool: tunnel::Tool::Jupyter,
+                logdir: String::new(),
+                remote_port: port.unwrap_or(tunnel::Tool::Jupyter.default_port()),
+                local_port,
+                open_browser: !no_browser,
+                ssh_user,
+            };
+            tunnel::open_tunnel(options, config, &aws_config, output_format).await
         }
         AwsCommands::Train {
             instance_id,
@@ -441,15 +1275,39 @@ pub async fn handle_command(cmd: AwsCommands, config: &Config, output_format: &s
             _output_s3,
             sync_code,
             include_pattern,
+            exclude_pattern,
+            max_file_size,
+            yes,
             project_name,
+            ssh_user,
+            resume,
+            resume_arg,
             script_args,
+            env,
+            env_file,
             wait,
             timeout,
             docker,
             docker_image,
+            complete_marker,
+            complete_log_regex,
+            complete_on_exit,
+            agent,
+            terminate_on_complete,
+            stop_on_complete,
         } => {
             crate::validation::validate_instance_id(&instance_id)?;
             let final_project_name = helpers::get_project_name(project_name, config);
+            // Command-line completion methods override the config one by one
+            let mut job = config.job.clone();
+            if complete_marker.is_some() {
+                job.completion.marker_file = complete_marker;
+            }
+            if complete_log_regex.is_some() {
+                job.completion.log_regex = complete_log_regex;
+            }
+            job.completion.exit_code |= complete_on_exit;
+            let env = crate::env_vars::collect(config, env_file.as_deref(), &env)?;
             let options = TrainInstanceOptions {
                 instance_id,
                 script,
@@ -457,33 +1315,114 @@ pub async fn handle_command(cmd: AwsCommands, config: &Config, output_format: &s
                 output_s3: _output_s3,
                 sync_code,
                 include_patterns: include_pattern,
+                exclude_patterns: exclude_pattern,
+                max_file_size,
+                yes,
                 project_name: final_project_name,
+                ssh_user,
                 script_args,
+                resume: resume.then_some(resume_arg),
+                env,
                 wait,
                 timeout_minutes: timeout,
-                docker,
+                docker: docker || docker_image.is_some(),
                 docker_image,
+                job,
+                agent,
+                launcher: None,
+                on_complete: if terminate_on_complete {
+                    Some(AutostopAction::Terminate)
+                } else if stop_on_complete {
+                    Some(AutostopAction::Stop)
+                } else {
+                    None
+                },
             };
             train_on_instance(options, config, &aws_config, output_format).await
         }
+        AwsCommands::TrainMulti {
+            script,
+            nodes,
+            instance_type,
+            launcher,
+            gpus_per_node,
+            master_port,
+            efa,
+            key_name,
+            security_group,
+            iam_instance_profile,
+            ami_id,
+            root_volume_size,
+            project_name,
+            include_pattern,
+            exclude_pattern,
+            yes,
+            wait,
+            timeout,
+            script_args,
+        } => {
+            let options = TrainMultiOptions {
+                script,
+                script_args,
+                nodes,
+                instance_type,
+                launcher,
+                gpus_per_node,
+                master_port,
+                efa,
+                key_name,
+                security_group,
+                iam_instance_profile,
+                ami_id,
+                root_volume_size,
+                project_name: helpers::get_project_name(project_name, config),
+                include_patterns: include_pattern,
+                exclude_patterns: exclude_pattern,
+                yes,
+                wait,
+                timeout_minutes: timeout,
+            };
+            cluster::train_multi(options, config, &aws_config, output_format).await
+        }
         AwsCommands::Status { instance_id } => {
             crate::validation::validate_instance_id(&instance_id)?;
-            instance::show_instance_status(instance_id, &aws_config, output_format).await
+            instance::show_instance_status(instance_id, config, &aws_config, output_format).await
+        }
+        AwsCommands::Timeline { instance_id } => {
+            crate::validation::validate_instance_id(&instance_id)?;
+            let events = crate::events::timeline(&instance_id)?;
+            crate::events::print_timeline(&instance_id, &events, output_format)
         }
         AwsCommands::Wait { instance_id } => {
             crate::validation::validate_instance_id(&instance_id)?;
-            instance::wait_for_instance(instance_id, &aws_config, output_format).await
+            instance::wait_for_instance(instance_id, verbose, &aws_config, output_format).await
         }
         AwsCommands::Monitor {
             instance_id,
             follow,
+            lines,
+            ssh_user,
         } => {
             crate::validation::validate_instance_id(&instance_id)?;
-            monitor_instance(instance_id, follow, &aws_config, output_format).await
+            let options = MonitorOptions {
+                instance_id,
+                follow,
+                lines,
+                ssh_user,
+            };
+            monitor_instance(options, config, &aws_config, output_format).await
         }
-        AwsCommands::Stop { instance_id, force } => {
+        AwsCommands::Stop {
+            instance_id,
+            force,
+            checkpoint_wait,
+        } => {
             crate::validation::validate_instance_id(&instance_id)?;
-            stop_instance(instance_id, force, &aws_config, output_format, config).await
+            let mut config = config.clone();
+            if let Some(wait) = checkpoint_wait {
+                config.checkpoint.stop_wait_secs = wait;
+            }
+            stop_instance(instance_id, force, &aws_config, output_format, &config).await
         }
         AwsCommands::Start { instance_id, wait } => {
             crate::validation::validate_instance_id(&instance_id)?;
@@ -498,21 +1437,89 @@ pub async fn handle_command(cmd: AwsCommands, config: &Config, output_format: &s
             detailed,
             watch,
             interval,
+            tree,
+            pid,
+            record,
+            replay,
         } => {
+            if let Some(path) = replay {
+                return recording::replay_recording(&path, output_format);
+            }
+            let instance_id = instance_id.ok_or_else(|| TrainctlError::Validation {
+                field: "instance_id".to_string(),
+                reason: "INSTANCE_ID is required unless --replay is given".to_string(),
+            })?;
             crate::validation::validate_instance_id(&instance_id)?;
-            show_processes(
+            let options = ProcessesOptions {
                 instance_id,
                 detailed,
                 watch,
                 interval,
-                &aws_config,
-                output_format,
-            )
-            .await
+                tree,
+                root_pid: pid,
+                record,
+            };
+            show_processes(options, &aws_config, output_format).await
         }
         AwsCommands::Ebs { subcommand } => {
             crate::ebs::handle_command(subcommand, config, output_format).await
         }
+        AwsCommands::Bulk { subcommand } => {
+            bulk::handle_command(subcommand, config, &aws_config, output_format).await
+        }
+        AwsCommands::Fleet { subcommand } => {
+            fleet::handle_command(subcommand, config, &aws_config, output_format).await
+        }
+        AwsCommands::SpotAdvisor {
+            instance_type,
+            regions,
+            days,
+            apply,
+        } => {
+            let options = SpotAdvisorOptions {
+                instance_type,
+                regions,
+                days,
+                apply,
+            };
+            spot_advisor::run_spot_advisor(options, config, &aws_config, output_format).await
+        }
+        AwsCommands::Autostop {
+            instance_id,
+            idle_minutes,
+            action,
+            gpu_threshold,
+            disable,
+        } => {
+            crate::validation::validate_instance_id(&instance_id)?;
+            let options = autostop::AutostopOptions {
+                instance_id,
+                idle_minutes,
+                action,
+                gpu_busy_percent: gpu_threshold,
+                disable,
+            };
+            autostop::run_autostop(options, config, &aws_config, output_format).await
+        }
+        AwsCommands::RenderUserData {
+            project_name,
+            data_volume,
+        } => {
+            let final_project_name = helpers::get_project_name(project_name, config);
+            crate::validation::validate_project_name(&final_project_name)?;
+            let script = render_user_data(&final_project_name, data_volume, &config.bootstrap)?;
+            if output_format == "json" {
+                let result = serde_json::json!({
+                    "success": true,
+                    "project_name": final_project_name,
+                    "user_data": script,
+                });
+                println!("{}", serde_json::to_string_pretty(&result)?);
+            } else {
+                print!("{}", script);
+            }
+            Ok(())
+        }
         AwsCommands::AutoResume {
             original_instance_id,
             script,
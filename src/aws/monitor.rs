@@ -0,0 +1,462 @@
+//! Remote training logs (`runctl aws monitor`)
+//!
+//! Reads `training.log` on the instance over SSM when it has an instance
+//! profile, otherwise over SSH with the same user, key, host key checks
+//! and `[ssh] bastion` route as `train`. The log path comes from the `runctl:log-path` tag written by
+//! `train`, falling back to `/home/<user>/<project>/training.log` with the
+//! project from the `runctl:project` (or `Project`) tag.
+//!
+//! `--follow` polls for new bytes by offset and stops once the training
+//! process has exited, returning its exit status. With `--output json` every
+//! log line is printed as its own JSON object.
+//!
+//! Lines are also parsed for training progress (see `training_progress`).
+//! Whenever it changes, a `>> [#####---] 45% ... ETA` line follows the log
+//! lines in text mode, and a `{"instance_id", "progress": {...}}` object in
+//! JSON mode.
+
+use crate::aws::helpers::get_project_name;
+use crate::aws::ssh_key::{learn_host_keys, resolve_key_path};
+use crate::aws::ssh_user::resolve_ssh_user;
+use crate::aws::types::MonitorOptions;
+use crate::aws_utils::execute_ssm_command;
+use crate::config::Config;
+use crate::error::{JobOutcome, Result, TrainctlError};
+use crate::host_keys::HostKeys;
+use crate::ssh_proxy::SshProxy;
+use crate::training_progress::ProgressTracker;
+use aws_sdk_ec2::types::Instance;
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_ssm::Client as SsmClient;
+use std::io::Write;
+use std::time::Duration;
+use tracing::warn;
+
+/// Tag recording where `train` started the log
+pub(crate) const LOG_PATH_TAG: &str = "runctl:log-path";
+
+/// Tags naming the project directory, in priority order
+const PROJECT_TAGS: &[&str] = &["runctl:project", "Project"];
+
+/// Most log bytes read per poll (SSM truncates output at 24000 characters)
+const CHUNK_BYTES: u64 = 16_000;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How commands reach the instance (also used by `tunnel`)
+pub(crate) enum Transport {
+    Ssm(SsmClient),
+    Ssh {
+        key: String,
+        /// Public IP, private IP or instance ID, depending on `proxy`
+        host: String,
+        user: String,
+        host_keys: HostKeys,
+        proxy: SshProxy,
+    },
+}
+
+impl Transport {
+    pub(crate) async fn connect(
+        ec2: &Ec2Client,
+        instance: &Instance,
+        user: &str,
+        config: &Config,
+        aws_config: &aws_config::SdkConfig,
+    ) -> Result<Self> {
+        let ssm = SsmClient::new(aws_config);
+        if instance.iam_instance_profile().is_some() {
+            return Ok(Self::Ssm(ssm));
+        }
+        let instance_id = instance.instance_id().unwrap_or_default();
+        let proxy = SshProxy::from_config(config)?;
+        let host = proxy.target(
+            instance_id,
+            crate::aws::public_address(instance),
+            instance.private_ip_address(),
+        )?;
+        let key = resolve_key_path(instance.key_name(), &config.ssh)?;
+        let host_keys = HostKeys::for_host(&config.ssh, instance_id);
+        if learn_host_keys(ec2, &ssm, instance, &host_keys)
+            .await
+            .is_none()
+        {
+            host_keys.require_known()?;
+        }
+        Ok(Self::Ssh {
+            key,
+            host,
+            user: user.to_string(),
+            host_keys,
+            proxy,
+        })
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::Ssm(_) => "ssm",
+            Self::Ssh { .. } => "ssh",
+        }
+    }
+
+    /// Run `command` and return its stdout
+    pub(crate) async fn run(&self, instance_id: &str, command: &str) -> Result<String> {
+        match self {
+            Self::Ssm(client) => execute_ssm_command(client, instance_id, command).await,
+            Self::Ssh {
+                key,
+                host,
+                user,
+                host_keys,
+                proxy,
+            } => {
+                let output = tokio::process::Command::new("ssh")
+                    .args(host_keys.ssh_args())
+                    .args(proxy.ssh_args())
+                    .args(["-o", "BatchMode=yes", "-o", "ConnectTimeout=10"])
+                    .arg("-i")
+                    .arg(key)
+                    .arg(format!("{}@{}", user, host))
+                    .arg(command)
+                    .output()
+                    .await?;
+                if !output.status.success() {
+                    return Err(TrainctlError::CloudProvider {
+                        provider: "aws".to_string(),
+                        message: format!(
+                            "SSH command failed: {}",
+                            String::from_utf8_lossy(&output.stderr).trim()
+                        ),
+                        source: None,
+                    });
+                }
+                Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+            }
+        }
+    }
+}
+
+/// Remote log path for an instance with `tags`, logging in as `user`
+pub(crate) fn log_path_from_tags(
+    tags: &[(String, String)],
+    user: &str,
+    default_project: &str,
+) -> String {
+    let tag = |key: &str| {
+        tags.iter()
+            .find(|(k, v)| k == key && !v.is_empty())
+            .map(|(_, v)| v.as_str())
+    };
+    if let Some(path) = tag(LOG_PATH_TAG) {
+        return path.to_string();
+    }
+    let project = PROJECT_TAGS
+        .iter()
+        .find_map(|key| tag(key))
+        .unwrap_or(default_project);
+    format!("/home/{}/{}/training.log", user, project)
+}
+
+/// Last `lines` lines of an instance's training log
+///
+/// Finds and reads the log the same way as `aws monitor`.
+#[cfg(feature = "dashboard")]
+pub(crate) async fn tail_log(
+    instance_id: &str,
+    lines: usize,
+    config: &Config,
+    aws_config: &aws_config::SdkConfig,
+) -> Result<Vec<String>> {
+    let ec2_client = Ec2Client::new(aws_config);
+    let instance = crate::aws::adopt::describe(&ec2_client, instance_id).await?;
+    let user = resolve_ssh_user(&ec2_client, &instance, None).await;
+    let tags: Vec<(String, String)> = instance
+        .tags()
+        .iter()
+        .filter_map(|t| Some((t.key()?.to_string(), t.value()?.to_string())))
+        .collect();
+    let log_path = log_path_from_tags(&tags, &user, &get_project_name(None, config));
+    let transport = Transport::connect(&ec2_client, &instance, &user, config, aws_config).await?;
+    let output = transport
+        .run(
+            instance_id,
+            &format!(
+                "tail -n {} {} 2>/dev/null || true",
+                lines,
+                crate::utils::shell_quote(&log_path)
+            ),
+        )
+        .await?;
+    Ok(output.lines().map(str::to_string).collect())
+}
+
+/// Split `buffer` into complete lines, keeping a trailing partial line
+fn take_lines(buffer: &mut String) -> Vec<String> {
+    let Some(end) = buffer.rfind('\n') else {
+        return Vec::new();
+    };
+    let rest = buffer.split_off(end + 1);
+    let complete = std::mem::replace(buffer, rest);
+    complete.lines().map(str::to_string).collect()
+}
+
+/// Print log lines, then the parsed progress if they changed it
+fn print_lines(
+    instance_id: &str,
+    lines: &[String],
+    progress: &mut ProgressTracker,
+    output_format: &str,
+) -> Result<()> {
+    for line in lines {
+        if output_format == "json" {
+            let json = serde_json::json!({
+                "instance_id": instance_id,
+                "line": line,
+            });
+            println!("{}", serde_json::to_string(&json)?);
+        } else {
+            println!("{}", crate::output_style::sanitize(line));
+        }
+    }
+    if progress.observe(lines) && !progress.progress().is_empty() {
+        if output_format == "json" {
+            let json = serde_json::json!({
+                "instance_id": instance_id,
+                "progress": progress.progress(),
+            });
+            println!("{}", serde_json::to_string(&json)?);
+        } else {
+            println!(">> {}", progress.progress().render());
+        }
+    }
+    std::io::stdout().flush().ok();
+    Ok(())
+}
+
+/// Show (and with `follow`, stream) the training log of an instance
+pub async fn monitor_instance(
+    options: MonitorOptions,
+    config: &Config,
+    aws_config: &aws_config::SdkConfig,
+    output_format: &str,
+) -> Result<()> {
+    let instance_id = options.instance_id.as_str();
+    let ec2_client = Ec2Client::new(aws_config);
+    let instance_response = ec2_client
+        .describe_instances()
+        .instance_ids(instance_id)
+        .send()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to describe instance: {}", e)))?;
+    let instance = crate::aws::helpers::find_instance_in_response(&instance_response, instance_id)
+        .ok_or_else(|| TrainctlError::ResourceNotFound {
+            resource_type: "instance".to_string(),
+            resource_id: instance_id.to_string(),
+        })?;
+
+    let user = resolve_ssh_user(&ec2_client, instance, options.ssh_user.as_deref()).await;
+    let tags: Vec<(String, String)> = instance
+        .tags()
+        .iter()
+        .filter_map(|t| Some((t.key()?.to_string(), t.value()?.to_string())))
+        .collect();
+    let log_path = log_path_from_tags(&tags, &user, &get_project_name(None, config));
+    let project_dir = log_path
+        .rsplit_once('/')
+        .map(|(dir, _)| dir.to_string())
+        .unwrap_or_default();
+    let transport = Transport::connect(&ec2_client, instance, &user, config, aws_config).await?;
+    let text = output_format != "json";
+    let quoted_log = crate::utils::shell_quote(&log_path);
+
+    // Size first, then the last lines of exactly that many bytes, so
+    // following starts where the snapshot ended
+    let snapshot_cmd = format!(
+        "f={log}; [ -f \"$f\" ] || exit 0; s=$(wc -c < \"$f\"); echo \"$s\"; \
+         head -c \"$s\" \"$f\" | tail -n {lines}",
+        log = quoted_log,
+        lines = options.lines
+    );
+    let snapshot = transport.run(instance_id, &snapshot_cmd).await?;
+    let (mut offset, existing) = match snapshot.split_once('\n') {
+        Some((size, rest)) => (size.trim().parse::<u64>().unwrap_or(0), rest.to_string()),
+        None => (0, String::new()),
+    };
+
+    if text {
+        let mode = if options.follow { " (following)" } else { "" };
+        println!(
+            "Training log {} on {} via {}{}",
+            log_path,
+            instance_id,
+            transport.name(),
+            mode
+        );
+        if offset == 0 && existing.is_empty() {
+            println!("   Log is empty or missing; training may not have started yet");
+        }
+    }
+    let mut progress = ProgressTracker::new();
+    let mut buffer = existing;
+    if !options.follow && !buffer.is_empty() && !buffer.ends_with('\n') {
+        buffer.push('\n');
+    }
+    print_lines(
+        instance_id,
+        &take_lines(&mut buffer),
+        &mut progress,
+        output_format,
+    )?;
+    if !options.follow {
+        return Ok(());
+    }
+    if text {
+        println!("Press Ctrl+C to stop");
+    }
+
+    let mut exit_status = None;
+    loop {
+        let chunk_cmd = format!(
+            "tail -c +{} {} 2>/dev/null | head -c {}",
+            offset + 1,
+            quoted_log,
+            CHUNK_BYTES
+        );
+        match transport.run(instance_id, &chunk_cmd).await {
+            Ok(chunk) if !chunk.is_empty() => {
+                offset += chunk.len() as u64;
+                buffer.push_str(&chunk);
+                print_lines(
+                    instance_id,
+                    &take_lines(&mut buffer),
+                    &mut progress,
+                    output_format,
+                )?;
+                // More may be waiting; read it before checking the process
+                if chunk.len() as u64 >= CHUNK_BYTES {
+                    continue;
+                }
+            }
+            Ok(_) => {}
+            Err(e) if text => warn!("Error reading log: {}", e),
+            Err(e) => return Err(e),
+        }
+
+        // One more read after the process exits picks up its last lines
+        if let Some(code) = exit_status {
+            if !buffer.is_empty() {
+                buffer.push('\n');
+                print_lines(
+                    instance_id,
+                    &take_lines(&mut buffer),
+                    &mut progress,
+                    output_format,
+                )?;
+            }
+            return follow_result(instance_id, code, output_format);
+        }
+        exit_status = finished_exit_code(&transport, instance_id, &project_dir).await;
+        if exit_status.is_none() {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => return Ok(()),
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            }
+        }
+    }
+}
+
+/// Exit status of a finished training process, or None while it runs
+///
+/// Only reports once the PID is gone and the wrapper has written
+/// `training_exit_code.txt`.
+async fn finished_exit_code(
+    transport: &Transport,
+    instance_id: &str,
+    project_dir: &str,
+) -> Option<i32> {
+    let cmd = format!(
+        "cd {dir} 2>/dev/null || exit 0; \
+         if [ -f training.pid ] && ps -p $(cat training.pid) >/dev/null 2>&1; then exit 0; fi; \
+         [ -f training_exit_code.txt ] && cat training_exit_code.txt || true",
+        dir = crate::utils::shell_quote(project_dir)
+    );
+    transport
+        .run(instance_id, &cmd)
+        .await
+        .ok()
+        .and_then(|output| output.trim().parse().ok())
+}
+
+/// Turn a followed job's exit status into the command result
+fn follow_result(instance_id: &str, code: i32, output_format: &str) -> Result<()> {
+    if output_format == "json" {
+        let json = serde_json::json!({
+            "instance_id": instance_id,
+            "finished": true,
+            "exit_code": code,
+        });
+        println!("{}", serde_json::to_string(&json)?);
+    } else {
+        println!("\nTraining process exited with status {}", code);
+    }
+    if code == 0 {
+        Ok(())
+    } else {
+        Err(TrainctlError::Job {
+            job_id: instance_id.to_string(),
+            outcome: JobOutcome::Failed,
+            message: format!("exit code {}", code),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_log_path_prefers_tag() {
+        let tags = tags(&[
+            ("runctl:project", "vision"),
+            (LOG_PATH_TAG, "/home/ubuntu/vision/training.log"),
+        ]);
+        assert_eq!(
+            log_path_from_tags(&tags, "ec2-user", "cwd"),
+            "/home/ubuntu/vision/training.log"
+        );
+    }
+
+    #[test]
+    fn test_log_path_from_project_tags() {
+        assert_eq!(
+            log_path_from_tags(&tags(&[("runctl:project", "vision")]), "ubuntu", "cwd"),
+            "/home/ubuntu/vision/training.log"
+        );
+        assert_eq!(
+            log_path_from_tags(&tags(&[("Project", "legacy")]), "ubuntu", "cwd"),
+            "/home/ubuntu/legacy/training.log"
+        );
+        assert_eq!(
+            log_path_from_tags(&[], "ubuntu", "cwd"),
+            "/home/ubuntu/cwd/training.log"
+        );
+    }
+
+    #[test]
+    fn test_take_lines_keeps_partial_line() {
+        let mut buffer = "epoch 1\nepoch 2\nepo".to_string();
+        assert_eq!(take_lines(&mut buffer), vec!["epoch 1", "epoch 2"]);
+        assert_eq!(buffer, "epo");
+        buffer.push_str("ch 3\n");
+        assert_eq!(take_lines(&mut buffer), vec!["epoch 3"]);
+        assert!(buffer.is_empty());
+        assert!(take_lines(&mut buffer).is_empty());
+    }
+}
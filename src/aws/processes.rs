@@ -1,27 +1,61 @@
 //! Process monitoring and resource usage display
 //!
 //! Shows running processes, resource usage (CPU, memory, disk, GPU), and system statistics
-//! on EC2 instances, similar to top/htop/nvidia-smi.
+//! on EC2 instances, similar to top/htop/nvidia-smi. With `--tree`, shows the
+//! process tree rooted at the training process (like `ps --forest`), which
+//! makes DataLoader worker crashes visible as defunct children.
 
 use crate::aws::types::{
     DiskUsage, GpuDetailJson, GpuInfoJson, ProcessInfo, ProcessListResult, ProcessResourceUsage,
+    ProcessTreeResult, ProcessesOptions,
+};
+use crate::diagnostics::{
+    get_instance_resource_usage, get_process_tree, ProcessTree, ProcessTreeNode,
 };
-use crate::diagnostics::get_instance_resource_usage;
 use crate::error::Result;
 use aws_sdk_ssm::Client as SsmClient;
 use std::io::{self, Write};
 
 /// Show processes and resource usage on an instance
 pub async fn show_processes(
-    instance_id: String,
-    detailed: bool,
-    watch: bool,
-    interval: u64,
+    options: ProcessesOptions,
     aws_config: &aws_config::SdkConfig,
     output_format: &str,
 ) -> Result<()> {
+    let ProcessesOptions {
+        instance_id,
+        detailed,
+        watch,
+        interval,
+        tree,
+        root_pid,
+        record,
+    } = options;
     let ssm_client = SsmClient::new(aws_config);
 
+    if let Some(path) = record {
+        return crate::aws::recording::record_processes(
+            &ssm_client,
+            &instance_id,
+            &path,
+            interval,
+            output_format,
+        )
+        .await;
+    }
+
+    if tree {
+        return show_process_tree(
+            &ssm_client,
+            &instance_id,
+            root_pid,
+            watch,
+            interval,
+            output_format,
+        )
+        .await;
+    }
+
     let display_usage = |usage: &crate::diagnostics::ResourceUsage| -> Result<()> {
         if output_format == "json" {
             // JSON output
@@ -66,6 +100,9 @@ pub async fn show_processes(
                     memory_mb: p.memory_mb,
                     memory_percent: p.memory_percent,
                     runtime: p.runtime.clone(),
+                    gpu_memory_mb: p.gpu_memory_mb,
+                    gpu_index: p.gpu_index,
+                    gpu_hog: p.gpu_hog,
                 })
                 .collect();
 
@@ -165,13 +202,20 @@ pub async fn show_processes(
 
         // Top processes - like top/ps
         if !usage.top_processes.is_empty() {
+            let gpu_column =
+                |proc: &crate::diagnostics::ProcessInfo| match (proc.gpu_index, proc.gpu_memory_mb)
+                {
+                    (Some(index), Some(mb)) => format!("{}:{:.1}G", index, mb as f64 / 1024.0),
+                    _ => "-".to_string(),
+                };
+
             println!("\nPROCESSES:");
             if detailed {
                 println!(
-                    "{:<8} {:<12} {:<40} {:>6} {:>10} {:>6} {:>10}",
-                    "PID", "USER", "COMMAND", "CPU%", "MEM(MB)", "MEM%", "RUNTIME"
+                    "{:<8} {:<12} {:<40} {:>6} {:>10} {:>6} {:>10} {:>9}",
+                    "PID", "USER", "COMMAND", "CPU%", "MEM(MB)", "MEM%", "RUNTIME", "GPU"
                 );
-                println!("{}", "-".repeat(100));
+                println!("{}", "-".repeat(110));
                 for proc in &usage.top_processes {
                     let cmd_display = if proc.command.len() > 38 {
                         format!("{}...", &proc.command[..35])
@@ -179,34 +223,63 @@ pub async fn show_processes(
                         format!("{:<38}", proc.command)
                     };
                     println!(
-                        "{:<8} {:<12} {:<40} {:>6.1} {:>10.1} {:>6.1} {:>10}",
+                        "{:<8} {:<12} {:<40} {:>6.1} {:>10.1} {:>6.1} {:>10} {:>9}",
                         proc.pid,
                         proc.user,
                         cmd_display,
                         proc.cpu_percent,
                         proc.memory_mb,
                         proc.memory_percent,
-                        proc.runtime
+                        proc.runtime,
+                        gpu_column(proc)
                     );
                 }
             } else {
                 println!(
-                    "{:<8} {:<50} {:>6} {:>10}",
-                    "PID", "COMMAND", "CPU%", "MEM(MB)"
+                    "{:<8} {:<50} {:>6} {:>10} {:>9}",
+                    "PID", "COMMAND", "CPU%", "MEM(MB)", "GPU"
                 );
-                println!("{}", "-".repeat(80));
-                for proc in usage.top_processes.iter().take(10) {
+                println!("{}", "-".repeat(90));
+                // Top 10 by CPU, plus any process holding GPU memory
+                for proc in usage
+                    .top_processes
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, p)| *i < 10 || p.gpu_memory_mb.is_some())
+                    .map(|(_, p)| p)
+                {
                     let cmd_display = if proc.command.len() > 48 {
                         format!("{}...", &proc.command[..45])
                     } else {
                         proc.command.clone()
                     };
                     println!(
-                        "{:<8} {:<50} {:>6.1} {:>10.1}",
-                        proc.pid, cmd_display, proc.cpu_percent, proc.memory_mb
+                        "{:<8} {:<50} {:>6.1} {:>10.1} {:>9}",
+                        proc.pid,
+                        cmd_display,
+                        proc.cpu_percent,
+                        proc.memory_mb,
+                        gpu_column(proc)
                     );
                 }
             }
+
+            let hogs: Vec<_> = usage.top_processes.iter().filter(|p| p.gpu_hog).collect();
+            if !hogs.is_empty() {
+                println!("\nWARNING: non-training processes holding GPU memory:");
+                for proc in hogs {
+                    println!(
+                        "  PID {} on GPU {}: {:.1}GB - {}",
+                        proc.pid,
+                        proc.gpu_index.map(|i| i.to_string()).unwrap_or_default(),
+                        proc.gpu_memory_mb.unwrap_or(0) as f64 / 1024.0,
+                        proc.command
+                    );
+                }
+                println!(
+                    "  Stale notebook kernels are a common cause; kill them to free GPU memory."
+                );
+            }
         }
 
         // Network stats - like ifconfig/ip
@@ -252,3 +325,137 @@ pub async fn show_processes(
 
     Ok(())
 }
+
+/// Show the process tree rooted at the training process
+async fn show_process_tree(
+    ssm_client: &SsmClient,
+    instance_id: &str,
+    root_pid: Option<u32>,
+    watch: bool,
+    interval: u64,
+    output_format: &str,
+) -> Result<()> {
+    loop {
+        match get_process_tree(ssm_client, instance_id, root_pid).await {
+            Ok(tree) => display_process_tree(instance_id, &tree, watch, interval, output_format)?,
+            Err(e) if watch => eprintln!("ERROR: Failed to get process tree: {}", e),
+            Err(e) => return Err(e),
+        }
+        if !watch {
+            return Ok(());
+        }
+        tokio::time::sleep(tokio::time::Duration::from_secs(interval)).await;
+    }
+}
+
+fn display_process_tree(
+    instance_id: &str,
+    tree: &ProcessTree,
+    watch: bool,
+    interval: u64,
+    output_format: &str,
+) -> Result<()> {
+    let defunct = tree.defunct_workers();
+
+    if output_format == "json" {
+        let result = ProcessTreeResult {
+            success: true,
+            instance_id: instance_id.to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            process_tree: tree.clone(),
+            defunct_workers: defunct.len(),
+        };
+        if watch {
+            println!("{}", serde_json::to_string(&result)?);
+        } else {
+            println!("{}", serde_json::to_string_pretty(&result)?);
+        }
+        return Ok(());
+    }
+
+    if watch {
+        print!("\x1B[2J\x1B[1;1H");
+        io::stdout().flush()?;
+    }
+
+    let Some(root) = &tree.root else {
+        match tree.root_pid {
+            Some(pid) => println!("Process {} not found on {}", pid, instance_id),
+            None => println!("No training process found on {}", instance_id),
+        }
+        println!("Tip: pass --pid <PID> to root the tree at a specific process");
+        return Ok(());
+    };
+
+    println!(
+        "INSTANCE: {} | PROCESS TREE (root PID {})",
+        instance_id, root.pid
+    );
+    println!("{}", "=".repeat(80));
+    println!(
+        "{:<8} {:<5} {:>6} {:>10} {:<10} COMMAND",
+        "PID", "STAT", "CPU%", "MEM(MB)", "ROLE"
+    );
+    println!("{}", "-".repeat(80));
+    print_tree_node(root, "");
+
+    let descendants = tree.descendants();
+    let workers: Vec<_> = descendants
+        .iter()
+        .filter(|n| n.role == "dataloader")
+        .collect();
+    let total_cpu: f64 = root.cpu_percent + descendants.iter().map(|n| n.cpu_percent).sum::<f64>();
+    let total_mem: f64 = root.memory_mb + descendants.iter().map(|n| n.memory_mb).sum::<f64>();
+
+    println!("{}", "-".repeat(80));
+    println!(
+        "{} child process(es), {} DataLoader worker(s) | total cpu: {:.1}% | total mem: {:.1}MB",
+        descendants.len(),
+        workers.len(),
+        total_cpu,
+        total_mem
+    );
+
+    if !defunct.is_empty() {
+        println!(
+            "\nWARNING: {} defunct worker(s) - exited but not reaped by the parent:",
+            defunct.len()
+        );
+        for node in &defunct {
+            println!("  PID {} (parent {})", node.pid, node.ppid);
+        }
+        println!("This usually means a DataLoader worker crashed (OOM kill, segfault in a");
+        println!("dataset transform). Check dmesg for OOM kills and the training log.");
+    }
+
+    if watch {
+        println!("\n{}", "-".repeat(80));
+        println!("refresh: {}s | [Ctrl+C] to stop", interval);
+    }
+
+    Ok(())
+}
+
+fn print_tree_node(node: &ProcessTreeNode, indent: &str) {
+    let branch = if indent.is_empty() { "" } else { "\\_ " };
+    let label = format!("{}{}{}", indent, branch, node.command);
+    let label = if label.chars().count() > 60 {
+        format!("{}...", label.chars().take(57).collect::<String>())
+    } else {
+        label
+    };
+    println!(
+        "{:<8} {:<5} {:>6.1} {:>10.1} {:<10} {}{}",
+        node.pid,
+        node.state,
+        node.cpu_percent,
+        node.memory_mb,
+        node.role,
+        label,
+        if node.defunct { "  [DEFUNCT]" } else { "" }
+    );
+    let child_indent = format!("{}   ", indent);
+    for child in &node.children {
+        print_tree_node(child, &child_indent);
+    }
+}
@@ -0,0 +1,459 @@
+//! Historical process sampling
+//!
+//! `runctl aws processes <id> --record run.jsonl` samples resource usage
+//! (CPU, memory, GPUs, top processes) at `--interval` into a JSONL file, one
+//! `ResourceUsage` per line, until Ctrl+C. `--replay run.jsonl` summarizes a
+//! recording after the run: peak memory, average GPU utilization, and periods
+//! where the CPU was saturated while GPUs sat idle (typically data loading).
+
+use crate::diagnostics::{get_instance_resource_usage, ResourceUsage};
+use crate::error::{Result, TrainctlError};
+use aws_sdk_ssm::Client as SsmClient;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+/// CPU usage at or above this marks a sample as CPU-bound
+const CPU_BOTTLENECK_CPU_PERCENT: f64 = 90.0;
+
+/// Average GPU utilization below this (while CPU-bound) marks GPUs as starved
+const CPU_BOTTLENECK_GPU_UTIL_PERCENT: f64 = 50.0;
+
+/// Summary of a recorded run
+#[derive(Debug, Serialize)]
+pub struct RecordingSummary {
+    pub instance_id: String,
+    pub samples: usize,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub duration_secs: i64,
+    pub peak_memory_used_gb: f64,
+    pub peak_memory_percent: f64,
+    pub avg_cpu_percent: f64,
+    pub gpus: Vec<GpuRecordingSummary>,
+    /// Process with the largest resident memory seen
+    pub peak_process: Option<PeakProcess>,
+    pub cpu_bottlenecks: Vec<BottleneckPeriod>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GpuRecordingSummary {
+    pub index: usize,
+    pub name: String,
+    pub avg_utilization_percent: f64,
+    pub peak_memory_used_mb: u64,
+    pub memory_total_mb: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PeakProcess {
+    pub pid: u32,
+    pub command: String,
+    pub memory_mb: f64,
+    pub at: DateTime<Utc>,
+}
+
+/// Contiguous run of CPU-bound samples
+#[derive(Debug, Serialize, PartialEq)]
+pub struct BottleneckPeriod {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub samples: usize,
+    pub avg_cpu_percent: f64,
+    pub avg_gpu_utilization_percent: Option<f64>,
+}
+
+/// Sample resource usage into a JSONL file until Ctrl+C
+pub async fn record_processes(
+    ssm_client: &SsmClient,
+    instance_id: &str,
+    path: &Path,
+    interval: u64,
+    output_format: &str,
+) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| {
+            TrainctlError::Io(std::io::Error::other(format!(
+                "Failed to open recording file {}: {}",
+                path.display(),
+                e
+            )))
+        })?;
+
+    if output_format != "json" {
+        println!(
+            "Recording {} every {}s to {} (Ctrl+C to stop)",
+            instance_id,
+            interval,
+            path.display()
+        );
+    }
+
+    let mut samples = 0usize;
+    loop {
+        match get_instance_resource_usage(ssm_client, instance_id).await {
+            Ok(usage) => {
+                writeln!(file, "{}", serde_json::to_string(&usage)?)?;
+                file.flush()?;
+                samples += 1;
+                if output_format != "json" {
+                    println!(
+                        "[{}] {} cpu: {:5.1}% | mem: {:5.1}% | gpu util: {}",
+                        samples,
+                        usage.timestamp.format("%H:%M:%S"),
+                        usage.cpu_percent,
+                        usage.memory_percent,
+                        avg_gpu_utilization(&usage)
+                            .map(|u| format!("{:5.1}%", u))
+                            .unwrap_or_else(|| "-".to_string())
+                    );
+                }
+            }
+            Err(e) => eprintln!("WARNING: Failed to sample resource usage: {}", e),
+        }
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            _ = tokio::time::sleep(tokio::time::Duration::from_secs(interval)) => {}
+        }
+    }
+
+    if output_format == "json" {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "success": true,
+                "instance_id": instance_id,
+                "file": path.display().to_string(),
+                "samples": samples,
+            }))?
+        );
+    } else {
+        println!("\nRecorded {} sample(s) to {}", samples, path.display());
+        println!(
+            "Summarize with: runctl aws processes --replay {}",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Load a recording and print its summary
+pub fn replay_recording(path: &Path, output_format: &str) -> Result<()> {
+    let samples = load_recording(path)?;
+    let summary = summarize_samples(&samples).ok_or_else(|| TrainctlError::Validation {
+        field: "replay".to_string(),
+        reason: format!("Recording {} contains no samples", path.display()),
+    })?;
+
+    if output_format == "json" {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
+
+    println!(
+        "RECORDING: {} | INSTANCE: {}",
+        path.display(),
+        summary.instance_id
+    );
+    println!("{}", "=".repeat(80));
+    println!(
+        "  {} sample(s) from {} to {} ({}m{:02}s)",
+        summary.samples,
+        summary.start.format("%Y-%m-%d %H:%M:%S UTC"),
+        summary.end.format("%H:%M:%S UTC"),
+        summary.duration_secs / 60,
+        summary.duration_secs % 60
+    );
+    println!("\nCPU / MEMORY:");
+    println!("  avg cpu:  {:5.1}%", summary.avg_cpu_percent);
+    println!(
+        "  peak mem: {:5.1}GB ({:5.1}%)",
+        summary.peak_memory_used_gb, summary.peak_memory_percent
+    );
+    if let Some(peak) = &summary.peak_process {
+        println!(
+            "  largest process: PID {} {:.1}MB at {} - {}",
+            peak.pid,
+            peak.memory_mb,
+            peak.at.format("%H:%M:%S"),
+            peak.command
+        );
+    }
+
+    if !summary.gpus.is_empty() {
+        println!("\nGPU:");
+        for gpu in &summary.gpus {
+            println!(
+                "  [{}] {} | avg util: {:5.1}% | peak mem: {:5.1}GB / {:5.1}GB",
+                gpu.index,
+                gpu.name,
+                gpu.avg_utilization_percent,
+                gpu.peak_memory_used_mb as f64 / 1024.0,
+                gpu.memory_total_mb as f64 / 1024.0
+            );
+        }
+    }
+
+    println!("\nCPU BOTTLENECKS:");
+    if summary.cpu_bottlenecks.is_empty() {
+        println!("  none detected");
+    } else {
+        for period in &summary.cpu_bottlenecks {
+            println!(
+                "  {} - {} ({} sample(s)) cpu: {:5.1}% | gpu util: {}",
+                period.start.format("%H:%M:%S"),
+                period.end.format("%H:%M:%S"),
+                period.samples,
+                period.avg_cpu_percent,
+                period
+                    .avg_gpu_utilization_percent
+                    .map(|u| format!("{:5.1}%", u))
+                    .unwrap_or_else(|| "-".to_string())
+            );
+        }
+        println!("  CPU saturated while GPUs were underused usually means the input");
+        println!(
+            "  pipeline is the bottleneck: raise DataLoader num_workers or preprocess offline."
+        );
+    }
+
+    Ok(())
+}
+
+fn load_recording(path: &Path) -> Result<Vec<ResourceUsage>> {
+    let file = std::fs::File::open(path).map_err(|e| {
+        TrainctlError::Io(std::io::Error::other(format!(
+            "Failed to open recording {}: {}",
+            path.display(),
+            e
+        )))
+    })?;
+
+    let mut samples = Vec::new();
+    for (line_no, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<ResourceUsage>(&line) {
+            Ok(sample) => samples.push(sample),
+            // A run interrupted mid-write leaves a truncated last line
+            Err(e) => tracing::warn!(
+                "Skipping unreadable sample on line {} of {}: {}",
+                line_no + 1,
+                path.display(),
+                e
+            ),
+        }
+    }
+    samples.sort_by_key(|s| s.timestamp);
+    Ok(samples)
+}
+
+fn avg_gpu_utilization(usage: &ResourceUsage) -> Option<f64> {
+    let gpus = &usage.gpu_info.as_ref()?.gpus;
+    if gpus.is_empty() {
+        return None;
+    }
+    Some(gpus.iter().map(|g| g.utilization_percent).sum::<f64>() / gpus.len() as f64)
+}
+
+/// Summarize recorded samples (must be sorted by timestamp)
+pub fn summarize_samples(samples: &[ResourceUsage]) -> Option<RecordingSummary> {
+    let first = samples.first()?;
+    let last = samples.last()?;
+    let n = samples.len() as f64;
+
+    let peak_mem = samples
+        .iter()
+        .max_by(|a, b| a.memory_used_gb.total_cmp(&b.memory_used_gb))?;
+
+    let mut gpus: Vec<GpuRecordingSummary> = Vec::new();
+    let mut gpu_samples: Vec<usize> = Vec::new();
+    for sample in samples {
+        for gpu in sample.gpu_info.iter().flat_map(|g| g.gpus.iter()) {
+            let pos = match gpus.iter().position(|s| s.index == gpu.index) {
+                Some(pos) => pos,
+                None => {
+                    gpus.push(GpuRecordingSummary {
+                        index: gpu.index,
+                        name: gpu.name.clone(),
+                        avg_utilization_percent: 0.0,
+                        peak_memory_used_mb: 0,
+                        memory_total_mb: gpu.memory_total_mb,
+                    });
+                    gpu_samples.push(0);
+                    gpus.len() - 1
+                }
+            };
+            // Accumulate the sum here; divided into an average below
+            gpus[pos].avg_utilization_percent += gpu.utilization_percent;
+            gpus[pos].peak_memory_used_mb = gpus[pos].peak_memory_used_mb.max(gpu.memory_used_mb);
+            gpu_samples[pos] += 1;
+        }
+    }
+    for (gpu, count) in gpus.iter_mut().zip(&gpu_samples) {
+        gpu.avg_utilization_percent /= *count as f64;
+    }
+    gpus.sort_by_key(|g| g.index);
+
+    let peak_process = samples
+        .iter()
+        .flat_map(|s| s.top_processes.iter().map(move |p| (s.timestamp, p)))
+        .max_by(|(_, a), (_, b)| a.memory_mb.total_cmp(&b.memory_mb))
+        .map(|(at, p)| PeakProcess {
+            pid: p.pid,
+            command: p.command.clone(),
+            memory_mb: p.memory_mb,
+            at,
+        });
+
+    Some(RecordingSummary {
+        instance_id: first.instance_id.clone(),
+        samples: samples.len(),
+        start: first.timestamp,
+        end: last.timestamp,
+        duration_secs: (last.timestamp - first.timestamp).num_seconds(),
+        peak_memory_used_gb: peak_mem.memory_used_gb,
+        peak_memory_percent: peak_mem.memory_percent,
+        avg_cpu_percent: samples.iter().map(|s| s.cpu_percent).sum::<f64>() / n,
+        gpus,
+        peak_process,
+        cpu_bottlenecks: find_cpu_bottlenecks(samples),
+    })
+}
+
+/// Find runs of samples where CPU was saturated and GPUs (if any) were starved
+fn find_cpu_bottlenecks(samples: &[ResourceUsage]) -> Vec<BottleneckPeriod> {
+    let is_bottleneck = |s: &ResourceUsage| {
+        s.cpu_percent >= CPU_BOTTLENECK_CPU_PERCENT
+            && avg_gpu_utilization(s)
+                .map(|u| u < CPU_BOTTLENECK_GPU_UTIL_PERCENT)
+                .unwrap_or(true)
+    };
+
+    let mut periods = Vec::new();
+    let mut run: Vec<&ResourceUsage> = Vec::new();
+    for sample in samples {
+        if is_bottleneck(sample) {
+            run.push(sample);
+            continue;
+        }
+        if let Some(period) = close_period(&run) {
+            periods.push(period);
+        }
+        run.clear();
+    }
+    if let Some(period) = close_period(&run) {
+        periods.push(period);
+    }
+    periods
+}
+
+fn close_period(run: &[&ResourceUsage]) -> Option<BottleneckPeriod> {
+    let first = run.first()?;
+    let last = run.last()?;
+    let gpu_utils: Vec<f64> = run.iter().filter_map(|s| avg_gpu_utilization(s)).collect();
+    Some(BottleneckPeriod {
+        start: first.timestamp,
+        end: last.timestamp,
+        samples: run.len(),
+        avg_cpu_percent: run.iter().map(|s| s.cpu_percent).sum::<f64>() / run.len() as f64,
+        avg_gpu_utilization_percent: if gpu_utils.is_empty() {
+            None
+        } else {
+            Some(gpu_utils.iter().sum::<f64>() / gpu_utils.len() as f64)
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::{GpuDetail, GpuInfo};
+
+    fn sample(secs: i64, cpu: f64, mem_gb: f64, gpu_util: Option<f64>) -> ResourceUsage {
+        ResourceUsage {
+            instance_id: "i-123".to_string(),
+            cpu_percent: cpu,
+            memory_total_gb: 64.0,
+            memory_used_gb: mem_gb,
+            memory_percent: mem_gb / 64.0 * 100.0,
+            disk_usage: vec![],
+            gpu_info: gpu_util.map(|util| GpuInfo {
+                gpu_count: 1,
+                gpus: vec![GpuDetail {
+                    index: 0,
+                    name: "NVIDIA A10G".to_string(),
+                    memory_total_mb: 23028,
+                    memory_used_mb: (util * 100.0) as u64,
+                    memory_percent: 0.0,
+                    utilization_percent: util,
+                    temperature_c: None,
+                    power_draw_w: None,
+                    processes: vec![],
+                }],
+            }),
+            top_processes: vec![],
+            network_stats: None,
+            timestamp: DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_summarize_peaks_and_averages() {
+        let samples = vec![
+            sample(0, 50.0, 10.0, Some(90.0)),
+            sample(10, 60.0, 30.0, Some(70.0)),
+            sample(20, 40.0, 20.0, Some(80.0)),
+        ];
+        let summary = summarize_samples(&samples).unwrap();
+        assert_eq!(summary.samples, 3);
+        assert_eq!(summary.duration_secs, 20);
+        assert_eq!(summary.peak_memory_used_gb, 30.0);
+        assert_eq!(summary.avg_cpu_percent, 50.0);
+        assert_eq!(summary.gpus.len(), 1);
+        assert_eq!(summary.gpus[0].avg_utilization_percent, 80.0);
+        assert_eq!(summary.gpus[0].peak_memory_used_mb, 9000);
+        assert!(summary.cpu_bottlenecks.is_empty());
+    }
+
+    #[test]
+    fn test_cpu_bottleneck_periods() {
+        let samples = vec![
+            sample(0, 50.0, 10.0, Some(90.0)),
+            sample(10, 98.0, 10.0, Some(20.0)),
+            sample(20, 95.0, 10.0, Some(10.0)),
+            sample(30, 50.0, 10.0, Some(90.0)),
+            // CPU-bound but GPU busy: not a bottleneck
+            sample(40, 95.0, 10.0, Some(95.0)),
+            sample(50, 99.0, 10.0, Some(5.0)),
+        ];
+        let periods = find_cpu_bottlenecks(&samples);
+        assert_eq!(periods.len(), 2);
+        assert_eq!(periods[0].samples, 2);
+        assert_eq!(periods[0].start, samples[1].timestamp);
+        assert_eq!(periods[0].end, samples[2].timestamp);
+        assert_eq!(periods[0].avg_gpu_utilization_percent, Some(15.0));
+        assert_eq!(periods[1].samples, 1);
+    }
+
+    #[test]
+    fn test_cpu_only_bottleneck() {
+        let samples = vec![sample(0, 95.0, 10.0, None), sample(10, 20.0, 10.0, None)];
+        let periods = find_cpu_bottlenecks(&samples);
+        assert_eq!(periods.len(), 1);
+        assert_eq!(periods[0].avg_gpu_utilization_percent, None);
+    }
+
+    #[test]
+    fn test_summarize_empty() {
+        assert!(summarize_samples(&[]).is_none());
+    }
+}
@@ -0,0 +1,185 @@
+//! SSM sessions: `runctl aws session` and `runctl aws forward`
+//!
+//! Both run `aws ssm start-session` with the Session Manager plugin, so they
+//! work for instances without a key pair, public IP or open security group
+//! port. The instance needs an instance profile with SSM access
+//! (`aws create --iam-instance-profile`).
+
+use crate::aws::adopt::describe;
+use crate::error::{Result, TrainctlError};
+use aws_sdk_ec2::Client as Ec2Client;
+
+/// Document that forwards a local port to a port on the instance
+const PORT_FORWARD_DOCUMENT: &str = "AWS-StartPortForwardingSession";
+
+/// Arguments to `aws` for an interactive shell session
+pub(crate) fn session_args(instance_id: &str, region: Option<&str>) -> Vec<String> {
+    let mut args = vec![
+        "ssm".to_string(),
+        "start-session".to_string(),
+        "--target".to_string(),
+        instance_id.to_string(),
+    ];
+    if let Some(region) = region {
+        args.extend(["--region".to_string(), region.to_string()]);
+    }
+    args
+}
+
+/// Arguments to `aws` for forwarding `local` to `remote` on the instance
+pub(crate) fn forward_args(
+    instance_id: &str,
+    remote: u16,
+    local: u16,
+    region: Option<&str>,
+) -> Vec<String> {
+    let mut args = session_args(instance_id, region);
+    args.extend([
+        "--document-name".to_string(),
+        PORT_FORWARD_DOCUMENT.to_string(),
+        "--parameters".to_string(),
+        format!("portNumber={},localPortNumber={}", remote, local),
+    ]);
+    args
+}
+
+/// Check the local tools and the instance before starting a session
+pub(super) async fn preflight(instance_id: &str, aws_config: &aws_config::SdkConfig) -> Result<()> {
+    for (tool, install) in [
+        ("aws", "https://aws.amazon.com/cli/"),
+        (
+            "session-manager-plugin",
+            "https://docs.aws.amazon.com/systems-manager/latest/userguide/session-manager-working-with-install-plugin.html",
+        ),
+    ] {
+        if which::which(tool).is_err() {
+            return Err(TrainctlError::Validation {
+                field: tool.to_string(),
+                reason: format!("`{}` is not on PATH; install it from {}", tool, install),
+            });
+        }
+    }
+
+    let client = Ec2Client::new(aws_config);
+    let instance = describe(&client, instance_id).await?;
+    let state = instance
+        .state()
+        .and_then(|s| s.name())
+        .map(|s| s.as_str().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    if state != "running" {
+        return Err(TrainctlError::Resource {
+            resource_type: "instance".to_string(),
+            operation: "session".to_string(),
+            resource_id: Some(instance_id.to_string()),
+            message: format!(
+                "Instance is {}; start it with: runctl aws start {} --wait",
+                state, instance_id
+            ),
+            source: None,
+        });
+    }
+    if instance.iam_instance_profile().is_none() {
+        return Err(TrainctlError::Aws(format!(
+            "{} has no instance profile, so SSM can't reach it.\n\n\
+            To resolve:\n\
+              1. Attach a profile with AmazonSSMManagedInstanceCore, or create the instance with --iam-instance-profile\n\
+              2. Or use SSH: runctl aws ssh {}",
+            instance_id, instance_id
+        )));
+    }
+    Ok(())
+}
+
+async fn run_aws(args: &[String]) -> Result<()> {
+    let status = tokio::process::Command::new("aws")
+        .args(args)
+        .status()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to run aws: {}", e)))?;
+    if !status.success() {
+        return Err(TrainctlError::Ssm(format!(
+            "aws ssm start-session exited with {}",
+            status
+                .code()
+                .map(|c| c.to_string())
+                .unwrap_or_else(|| "a signal".to_string())
+        )));
+    }
+    Ok(())
+}
+
+/// Open an interactive SSM shell on `instance_id`
+pub(crate) async fn start_session(
+    instance_id: &str,
+    aws_config: &aws_config::SdkConfig,
+) -> Result<()> {
+    preflight(instance_id, aws_config).await?;
+    let region = aws_config.region().map(|r| r.to_string());
+    run_aws(&session_args(instance_id, region.as_deref())).await
+}
+
+/// Forward `localhost:<local>` to `<remote>` on `instance_id` until interrupted
+pub(crate) async fn forward_port(
+    instance_id: &str,
+    remote: u16,
+    local: Option<u16>,
+    aws_config: &aws_config::SdkConfig,
+    output_format: &str,
+) -> Result<()> {
+    preflight(instance_id, aws_config).await?;
+    let local = local.unwrap_or(remote);
+    if output_format == "json" {
+        println!(
+            "{}",
+            serde_json::json!({
+                "instance_id": instance_id,
+                "local_port": local,
+                "remote_port": remote,
+                "url": format!("http://localhost:{}", local),
+            })
+        );
+    } else {
+        println!(
+            "Forwarding localhost:{} -> {}:{} over SSM (Ctrl-C to stop)",
+            local, instance_id, remote
+        );
+    }
+    let region = aws_config.region().map(|r| r.to_string());
+    run_aws(&forward_args(instance_id, remote, local, region.as_deref())).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_args() {
+        assert_eq!(
+            session_args("i-0abc", Some("us-west-2")),
+            vec![
+                "ssm",
+                "start-session",
+                "--target",
+                "i-0abc",
+                "--region",
+                "us-west-2"
+            ]
+        );
+        assert_eq!(session_args("i-0abc", None).len(), 4);
+    }
+
+    #[test]
+    fn test_forward_args() {
+        let args = forward_args("i-0abc", 6006, 16006, None);
+        assert_eq!(
+            args[4..],
+            [
+                "--document-name",
+                "AWS-StartPortForwardingSession",
+                "--parameters",
+                "portNumber=6006,localPortNumber=16006"
+            ]
+        );
+    }
+}
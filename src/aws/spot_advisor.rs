@@ -0,0 +1,573 @@
+//! Spot price advisor (`runctl aws spot-advisor`)
+//!
+//! Reads the spot price history of one instance type in every availability
+//! zone of the selected regions, adds the interruption frequency band AWS
+//! publishes for the Spot Instance Advisor, and recommends the cheapest zone
+//! together with a `--spot-max-price` that leaves headroom over the highest
+//! recent price.
+//!
+//! With `--apply` the recommendation is saved to `.runctl/spot_choice.json`.
+//! For the next [`CHOICE_TTL_HOURS`] hours, `aws create --spot` with the same
+//! instance type in the same region launches in that zone, and uses the
+//! suggested max price unless `--spot-max-price` is given.
+
+use crate::config::Config;
+use crate::error::{Result, TrainctlError};
+use aws_config::BehaviorVersion;
+use aws_sdk_ec2::types::InstanceType;
+use aws_sdk_ec2::Client as Ec2Client;
+use chrono::{DateTime, Utc};
+use comfy_table::{Cell, Table};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Public data behind the AWS Spot Instance Advisor
+const ADVISOR_DATA_URL: &str = "https://spot-bid-advisor.s3.amazonaws.com/spot-advisor-data.json";
+
+/// Suggested max price = highest price in the window times this
+const MAX_PRICE_HEADROOM: f64 = 1.2;
+
+/// Interruption band (index into [`INTERRUPTION_BANDS`]) avoided when possible
+const WORST_BAND: u8 = 4;
+
+/// Interruption frequency bands used by the Spot Instance Advisor
+pub const INTERRUPTION_BANDS: [&str; 5] = ["<5%", "5-10%", "10-15%", "15-20%", ">20%"];
+
+/// How long an applied choice steers `aws create --spot`
+pub const CHOICE_TTL_HOURS: i64 = 24;
+
+fn choice_path() -> PathBuf {
+    PathBuf::from(".runctl").join("spot_choice.json")
+}
+
+/// Options for `aws spot-advisor`
+#[derive(Debug, Clone)]
+pub struct SpotAdvisorOptions {
+    pub instance_type: String,
+    /// Regions to compare; empty means the current region, `["all"]` every
+    /// enabled region
+    pub regions: Vec<String>,
+    /// Days of price history to look at
+    pub days: u32,
+    /// Save the recommendation for later `aws create --spot` calls
+    pub apply: bool,
+}
+
+/// Spot price history of one availability zone
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoneStats {
+    pub zone: String,
+    pub region: String,
+    pub current: f64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Price changes in the window, a rough volatility signal
+    pub changes: usize,
+    /// Interruption band of the region (see [`INTERRUPTION_BANDS`])
+    pub interruption: Option<u8>,
+}
+
+impl ZoneStats {
+    /// Summarize `(timestamp, price)` points; `None` when there are none
+    pub fn from_history(zone: &str, mut points: Vec<(DateTime<Utc>, f64)>) -> Option<Self> {
+        points.sort_by_key(|(at, _)| *at);
+        let (_, current) = *points.last()?;
+        let prices = points.iter().map(|(_, price)| *price);
+        Some(Self {
+            zone: zone.to_string(),
+            region: crate::aws_pricing::region_of_zone(zone).to_string(),
+            current,
+            min: prices.clone().fold(f64::INFINITY, f64::min),
+            max: prices.clone().fold(0.0, f64::max),
+            mean: prices.sum::<f64>() / points.len() as f64,
+            changes: points.len() - 1,
+            interruption: None,
+        })
+    }
+}
+
+/// Label of an interruption band, e.g. "5-10%"
+pub fn interruption_label(band: Option<u8>) -> &'static str {
+    band.and_then(|b| INTERRUPTION_BANDS.get(b as usize).copied())
+        .unwrap_or("unknown")
+}
+
+/// Interruption band of `instance_type` in each region, from Spot Instance
+/// Advisor data (`spot_advisor.<region>.Linux.<type>.r`)
+pub fn parse_interruption_bands(
+    data: &serde_json::Value,
+    instance_type: &str,
+) -> BTreeMap<String, u8> {
+    let Some(regions) = data.get("spot_advisor").and_then(|v| v.as_object()) else {
+        return BTreeMap::new();
+    };
+    regions
+        .iter()
+        .filter_map(|(region, by_os)| {
+            let band = by_os.get("Linux")?.get(instance_type)?.get("r")?.as_u64()?;
+            Some((region.clone(), u8::try_from(band).ok()?))
+        })
+        .collect()
+}
+
+/// Max price with headroom over `highest`, capped at the on-demand price and
+/// rounded up to a tenth of a cent
+pub fn suggest_max_price(highest: f64, on_demand: Option<f64>) -> f64 {
+    let mut price = highest * MAX_PRICE_HEADROOM;
+    if let Some(cap) = on_demand.filter(|cap| *cap >= highest) {
+        price = price.min(cap);
+    }
+    // The epsilon keeps float noise (0.36 -> 360.00000000000006) from rounding up
+    (price * 1000.0 - 1e-9).ceil() / 1000.0
+}
+
+/// The advisor's pick
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recommendation {
+    pub zone: String,
+    pub region: String,
+    pub price: f64,
+    pub max_price: f64,
+    pub on_demand: Option<f64>,
+}
+
+/// Cheapest zone by current price, avoiding the >20% interruption band
+/// unless every zone is in it
+pub fn recommend(zones: &[ZoneStats], on_demand: &BTreeMap<String, f64>) -> Option<Recommendation> {
+    let best = zones.iter().min_by(|a, b| {
+        let worst = |z: &ZoneStats| z.interruption == Some(WORST_BAND);
+        worst(a)
+            .cmp(&worst(b))
+            .then(a.current.total_cmp(&b.current))
+    })?;
+    let region_on_demand = on_demand.get(&best.region).copied();
+    Some(Recommendation {
+        zone: best.zone.clone(),
+        region: best.region.clone(),
+        price: best.current,
+        max_price: suggest_max_price(best.max, region_on_demand),
+        on_demand: region_on_demand,
+    })
+}
+
+/// A recommendation saved with `--apply`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpotChoice {
+    pub instance_type: String,
+    pub zone: String,
+    pub max_price: String,
+    pub chosen_at: DateTime<Utc>,
+}
+
+impl SpotChoice {
+    pub fn load(path: &Path) -> Option<Self> {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            crate::utils::ensure_dir(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Whether this choice should steer a spot launch of `instance_type` in
+    /// `region` at `now`
+    pub fn applies_to(&self, instance_type: &str, region: &str, now: DateTime<Utc>) -> bool {
+        self.instance_type == instance_type
+            && crate::aws_pricing::region_of_zone(&self.zone) == region
+            && now - self.chosen_at < chrono::Duration::hours(CHOICE_TTL_HOURS)
+    }
+}
+
+/// The saved choice for a spot launch, if one applies
+pub(crate) fn applied_choice(instance_type: &str, region: &str) -> Option<SpotChoice> {
+    SpotChoice::load(&choice_path()).filter(|c| c.applies_to(instance_type, region, Utc::now()))
+}
+
+/// Spot price points per availability zone since `since`
+async fn zone_history(
+    client: &Ec2Client,
+    instance_type: &str,
+    since: DateTime<Utc>,
+) -> Result<BTreeMap<String, Vec<(DateTime<Utc>, f64)>>> {
+    let mut history: BTreeMap<String, Vec<(DateTime<Utc>, f64)>> = BTreeMap::new();
+    let mut next_token = None;
+    loop {
+        let response = client
+            .describe_spot_price_history()
+            .instance_types(InstanceType::from(instance_type))
+            .product_descriptions("Linux/UNIX")
+            .start_time(aws_sdk_ec2::primitives::DateTime::from_secs(
+                since.timestamp(),
+            ))
+            .set_next_token(next_token)
+            .send()
+            .await
+            .map_err(|e| {
+                TrainctlError::Aws(format!(
+                    "Failed to get spot price history for {}: {}",
+                    instance_type, e
+                ))
+            })?;
+        for entry in response.spot_price_history() {
+            let (Some(zone), Some(price), Some(at)) = (
+                entry.availability_zone(),
+                entry.spot_price().and_then(|p| p.parse::<f64>().ok()),
+                entry
+                    .timestamp()
+                    .and_then(|t| DateTime::<Utc>::from_timestamp(t.secs(), 0)),
+            ) else {
+                continue;
+            };
+            history
+                .entry(zone.to_string())
+                .or_default()
+                .push((at, price));
+        }
+        next_token = response
+            .next_token()
+            .filter(|token| !token.is_empty())
+            .map(str::to_string);
+        if next_token.is_none() {
+            break;
+        }
+    }
+    Ok(history)
+}
+
+/// Interruption bands per region; empty if the advisor data can't be fetched
+async fn fetch_interruption_bands(instance_type: &str) -> BTreeMap<String, u8> {
+    let data = async {
+        reqwest::get(ADVISOR_DATA_URL)
+            .await?
+            .error_for_status()?
+            .json::<serde_json::Value>()
+            .await
+    }
+    .await;
+    match data {
+        Ok(data) => parse_interruption_bands(&data, instance_type),
+        Err(e) => {
+            debug!("Spot Instance Advisor data unavailable: {}", e);
+            BTreeMap::new()
+        }
+    }
+}
+
+/// Regions named by `--regions`: the current one by default, or all enabled
+async fn resolve_regions(
+    requested: &[String],
+    client: &Ec2Client,
+    default_region: &str,
+) -> Result<Vec<String>> {
+    match requested {
+        [] => Ok(vec![default_region.to_string()]),
+        [all] if all == "all" => {
+            let response = client
+                .describe_regions()
+                .send()
+                .await
+                .map_err(|e| TrainctlError::Aws(format!("Failed to list regions: {}", e)))?;
+            let mut regions: Vec<String> = response
+                .regions()
+                .iter()
+                .filter_map(|r| r.region_name().map(str::to_string))
+                .collect();
+            regions.sort();
+            Ok(regions)
+        }
+        regions => Ok(regions.to_vec()),
+    }
+}
+
+/// Compare spot prices for an instance type and recommend a zone
+pub async fn run_spot_advisor(
+    options: SpotAdvisorOptions,
+    config: &Config,
+    aws_config: &aws_config::SdkConfig,
+    output_format: &str,
+) -> Result<()> {
+    let default_region = aws_config
+        .region()
+        .map(|r| r.as_ref().to_string())
+        .or_else(|| config.aws.as_ref().map(|a| a.region.clone()))
+        .unwrap_or_else(|| "us-east-1".to_string());
+    let regions = resolve_regions(
+        &options.regions,
+        &Ec2Client::new(aws_config),
+        &default_region,
+    )
+    .await?;
+    let since = Utc::now() - chrono::Duration::days(i64::from(options.days.max(1)));
+    let bands = fetch_interruption_bands(&options.instance_type).await;
+
+    let mut zones = Vec::new();
+    let mut on_demand = BTreeMap::new();
+    for region in &regions {
+        let sdk_config = aws_config::defaults(BehaviorVersion::latest())
+            .region(aws_sdk_ec2::config::Region::new(region.clone()))
+            .load()
+            .await;
+        let history =
+            match zone_history(&Ec2Client::new(&sdk_config), &options.instance_type, since).await {
+                Ok(history) => history,
+                // One unreachable region shouldn't hide the others
+                Err(e) if regions.len() > 1 => {
+                    debug!("Skipping {}: {}", region, e);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+        zones.extend(history.into_iter().filter_map(|(zone, points)| {
+            let mut stats = ZoneStats::from_history(&zone, points)?;
+            stats.interruption = bands.get(region).copied();
+            Some(stats)
+        }));
+        let pricing = crate::aws_pricing::pricing_client().await;
+        match crate::aws_pricing::fetch_on_demand_price(pricing, region, &options.instance_type)
+            .await
+        {
+            Ok(Some(price)) => {
+                on_demand.insert(region.clone(), price);
+            }
+            Ok(None) => {}
+            Err(e) => debug!("No on-demand price for {}: {}", region, e),
+        }
+    }
+    zones.sort_by(|a, b| a.current.total_cmp(&b.current));
+
+    let recommendation =
+        recommend(&zones, &on_demand).ok_or_else(|| TrainctlError::ResourceNotFound {
+            resource_type: "spot price history".to_string(),
+            resource_id: format!("{} in {}", options.instance_type, regions.join(", ")),
+        })?;
+    if options.apply {
+        SpotChoice {
+            instance_type: options.instance_type.clone(),
+            zone: recommendation.zone.clone(),
+            max_price: format!("{:.3}", recommendation.max_price),
+            chosen_at: Utc::now(),
+        }
+        .save(&choice_path())?;
+    }
+
+    if output_format == "json" {
+        let zones_json: Vec<_> = zones
+            .iter()
+            .map(|z| {
+                serde_json::json!({
+                    "zone": z.zone,
+                    "region": z.region,
+                    "current": z.current,
+                    "min": z.min,
+                    "max": z.max,
+                    "mean": z.mean,
+                    "changes": z.changes,
+                    "interruption_frequency": z.interruption.map(|b| interruption_label(Some(b))),
+                })
+            })
+            .collect();
+        let result = serde_json::json!({
+            "instance_type": options.instance_type,
+            "days": options.days,
+            "zones": zones_json,
+            "on_demand": on_demand,
+            "recommendation": {
+                "zone": recommendation.zone,
+                "region": recommendation.region,
+                "price": recommendation.price,
+                "spot_max_price": format!("{:.3}", recommendation.max_price),
+                "on_demand": recommendation.on_demand,
+            },
+            "applied": options.apply,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    println!(
+        "Spot prices for {} over the last {} day(s)",
+        options.instance_type, options.days
+    );
+    let mut table = Table::new();
+    table.set_header(vec![
+        "Zone",
+        "Current",
+        "Min",
+        "Max",
+        "Changes",
+        "Interruptions",
+        "vs on-demand",
+    ]);
+    for z in &zones {
+        let savings = on_demand
+            .get(&z.region)
+            .map(|od| format!("-{:.0}%", (1.0 - z.current / od) * 100.0))
+            .unwrap_or_else(|| "-".to_string());
+        table.add_row(vec![
+            Cell::new(&z.zone),
+            Cell::new(format!("${:.4}", z.current)),
+            Cell::new(format!("${:.4}", z.min)),
+            Cell::new(format!("${:.4}", z.max)),
+            Cell::new(z.changes),
+            Cell::new(interruption_label(z.interruption)),
+            Cell::new(savings),
+        ]);
+    }
+    println!("{}", table);
+    println!();
+    println!(
+        "Recommended: {} at ${:.4}/hr (interruptions {})",
+        recommendation.zone,
+        recommendation.price,
+        interruption_label(bands.get(&recommendation.region).copied())
+    );
+    println!(
+        "  Suggested --spot-max-price {:.3} ({:.0}% over the highest price in the window{})",
+        recommendation.max_price,
+        (MAX_PRICE_HEADROOM - 1.0) * 100.0,
+        if recommendation.on_demand.is_some() {
+            ", capped at on-demand"
+        } else {
+            ""
+        }
+    );
+    if options.apply {
+        println!(
+            "  Saved: `aws create {} --spot` in {} uses this zone for the next {}h",
+            options.instance_type, recommendation.region, CHOICE_TTL_HOURS
+        );
+    } else {
+        println!(
+            "  runctl aws create {} --spot --availability-zone {} --spot-max-price {:.3}",
+            options.instance_type, recommendation.zone, recommendation.max_price
+        );
+        println!("  Add --apply to use this zone for `aws create --spot` automatically");
+    }
+    if recommendation.region != default_region {
+        println!(
+            "  NOTE: {} is not your current region; set AWS_REGION={} to launch there",
+            recommendation.region, recommendation.region
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap()
+    }
+
+    fn zone(name: &str, current: f64, max: f64, band: Option<u8>) -> ZoneStats {
+        ZoneStats {
+            zone: name.to_string(),
+            region: crate::aws_pricing::region_of_zone(name).to_string(),
+            current,
+            min: current,
+            max,
+            mean: current,
+            changes: 0,
+            interruption: band,
+        }
+    }
+
+    #[test]
+    fn test_zone_stats_from_history() {
+        let stats = ZoneStats::from_history(
+            "us-east-1a",
+            vec![(at(200), 0.30), (at(0), 0.50), (at(100), 0.40)],
+        )
+        .unwrap();
+        assert_eq!(stats.region, "us-east-1");
+        assert_eq!(stats.current, 0.30);
+        assert_eq!(stats.min, 0.30);
+        assert_eq!(stats.max, 0.50);
+        assert!((stats.mean - 0.40).abs() < 1e-9);
+        assert_eq!(stats.changes, 2);
+        assert!(ZoneStats::from_history("us-east-1a", vec![]).is_none());
+    }
+
+    #[test]
+    fn test_parse_interruption_bands() {
+        let data = serde_json::json!({
+            "spot_advisor": {
+                "us-east-1": {"Linux": {"g5.xlarge": {"s": 70, "r": 1}}},
+                "us-west-2": {"Linux": {"g5.xlarge": {"s": 68, "r": 4}}},
+                "eu-west-1": {"Linux": {"t3.micro": {"s": 70, "r": 0}}}
+            }
+        });
+        let bands = parse_interruption_bands(&data, "g5.xlarge");
+        assert_eq!(bands.len(), 2);
+        assert_eq!(bands["us-east-1"], 1);
+        assert_eq!(interruption_label(bands.get("us-west-2").copied()), ">20%");
+        assert_eq!(
+            interruption_label(bands.get("eu-west-1").copied()),
+            "unknown"
+        );
+    }
+
+    #[test]
+    fn test_suggest_max_price() {
+        assert_eq!(suggest_max_price(0.5, None), 0.6);
+        // Capped at on-demand
+        assert_eq!(suggest_max_price(0.9, Some(1.006)), 1.006);
+        // A cap below recent prices is ignored
+        assert_eq!(suggest_max_price(1.1, Some(1.0)), 1.32);
+    }
+
+    #[test]
+    fn test_recommend_avoids_worst_interruption_band() {
+        let zones = vec![
+            zone("us-west-2a", 0.20, 0.25, Some(4)),
+            zone("us-east-1b", 0.30, 0.35, Some(1)),
+            zone("us-east-1a", 0.32, 0.40, Some(1)),
+        ];
+        let on_demand = BTreeMap::from([("us-east-1".to_string(), 1.0)]);
+        let pick = recommend(&zones, &on_demand).unwrap();
+        assert_eq!(pick.zone, "us-east-1b");
+        assert_eq!(pick.on_demand, Some(1.0));
+        assert_eq!(pick.max_price, 0.42);
+
+        let only_risky = vec![zone("us-west-2a", 0.20, 0.25, Some(4))];
+        assert_eq!(
+            recommend(&only_risky, &BTreeMap::new()).unwrap().zone,
+            "us-west-2a"
+        );
+        assert!(recommend(&[], &on_demand).is_none());
+    }
+
+    #[test]
+    fn test_choice_applies_to_same_type_region_and_recent() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".runctl").join("spot_choice.json");
+        let choice = SpotChoice {
+            instance_type: "g5.xlarge".to_string(),
+            zone: "us-east-1b".to_string(),
+            max_price: "0.420".to_string(),
+            chosen_at: Utc::now(),
+        };
+        choice.save(&path).unwrap();
+        let loaded = SpotChoice::load(&path).unwrap();
+        assert_eq!(loaded, choice);
+
+        let now = Utc::now();
+        assert!(loaded.applies_to("g5.xlarge", "us-east-1", now));
+        assert!(!loaded.applies_to("g5.2xlarge", "us-east-1", now));
+        assert!(!loaded.applies_to("g5.xlarge", "us-west-2", now));
+        assert!(!loaded.applies_to(
+            "g5.xlarge",
+            "us-east-1",
+            now + chrono::Duration::hours(CHOICE_TTL_HOURS + 1)
+        ));
+    }
+}
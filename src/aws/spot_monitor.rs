@@ -145,6 +145,21 @@ fi
 
                     // Parse interruption details
                     let interruption_info = parse_interruption_info(&output);
+                    let detail = match interruption_info.action_time {
+                        Some(time) => format!(
+                            "{} at {}",
+                            interruption_info.action,
+                            time.format("%H:%M:%S UTC")
+                        ),
+                        None => interruption_info.action.clone(),
+                    };
+                    crate::events::record(
+                        crate::events::Event::new(
+                            instance_id,
+                            crate::events::EventKind::SpotWarning,
+                        )
+                        .detail(detail),
+                    );
 
                     // Handle interruption
                     if let Err(e) = handle_spot_interruption(
@@ -339,6 +354,15 @@ fi
                 .find(|l| l.starts_with("CHECKPOINT_SAVED:"))
                 .and_then(|l| l.strip_prefix("CHECKPOINT_SAVED:"))
                 .map(|s| s.trim().to_string());
+            if let Some(path) = &checkpoint_path {
+                crate::events::record(
+                    crate::events::Event::new(
+                        instance_id,
+                        crate::events::EventKind::CheckpointSaved,
+                    )
+                    .detail(format!("{} (before interruption)", path)),
+                );
+            }
 
             // Step 2: Upload checkpoint to S3 if configured
             if let (Some(bucket), Some(path)) = (s3_bucket, checkpoint_path.as_ref()) {
@@ -0,0 +1,400 @@
+//! `runctl aws ssh` and `runctl aws ssh-config`
+//!
+//! Both reach instances the way `train` does: the login user from
+//! `resolve_ssh_user`, the private key from `ssh_key::resolve_key_path`,
+//! host keys pinned under the instance ID (see `host_keys`) and the
+//! `[ssh] bastion` proxy. `ssh` opens an interactive session with the system
+//! `ssh` client; `ssh-config` writes the same settings as `~/.ssh/config`
+//! Host entries so plain `ssh`, `scp` and editors' remote modes work too.
+
+use crate::aws::adopt::describe;
+use crate::aws::ssh_key::{learn_host_keys, resolve_key_path};
+use crate::aws::ssh_user::resolve_ssh_user;
+use crate::config::Config;
+use crate::error::{Result, TrainctlError};
+use crate::host_keys::HostKeys;
+use crate::ssh_proxy::SshProxy;
+use aws_sdk_ec2::types::{Filter, Instance};
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_ssm::Client as SsmClient;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Markers around the entries `ssh-config --write` manages
+const BEGIN_MARKER: &str = "# BEGIN runctl (generated by `runctl aws ssh-config`)";
+const END_MARKER: &str = "# END runctl";
+
+/// ssh exits with 255 when it could not connect or authenticate; other
+/// codes come from the remote shell
+const SSH_CONNECTION_ERROR: i32 = 255;
+
+/// Open an interactive shell (or run `command`) on `instance_id`
+pub(crate) async fn ssh_instance(
+    instance_id: &str,
+    ssh_user: Option<&str>,
+    command: &[String],
+    config: &Config,
+    aws_config: &aws_config::SdkConfig,
+) -> Result<()> {
+    let client = Ec2Client::new(aws_config);
+    let instance = describe(&client, instance_id).await?;
+    let state = instance
+        .state()
+        .and_then(|s| s.name())
+        .map(|s| s.as_str().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    if state != "running" {
+        return Err(TrainctlError::Resource {
+            resource_type: "instance".to_string(),
+            operation: "ssh".to_string(),
+            resource_id: Some(instance_id.to_string()),
+            message: format!(
+                "Instance is {}; start it with: runctl aws start {} --wait",
+                state, instance_id
+            ),
+            source: None,
+        });
+    }
+
+    let user = resolve_ssh_user(&client, &instance, ssh_user).await;
+    let key = resolve_key_path(instance.key_name(), &config.ssh)?;
+    let proxy = SshProxy::from_config(config)?;
+    let host = proxy.target(
+        instance_id,
+        crate::aws::public_address(&instance),
+        instance.private_ip_address(),
+    )?;
+
+    let host_keys = HostKeys::for_host(&config.ssh, instance_id);
+    if !host_keys.is_known() {
+        let ssm = SsmClient::new(aws_config);
+        learn_host_keys(&client, &ssm, &instance, &host_keys).await;
+    }
+    host_keys.require_known()?;
+
+    let status = tokio::process::Command::new("ssh")
+        .args(host_keys.ssh_args())
+        .args(proxy.ssh_args())
+        .args(["-o", "ConnectTimeout=10"])
+        .arg("-i")
+        .arg(&key)
+        .arg(format!("{}@{}", user, host))
+        .args(command)
+        .status()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to run ssh: {}", e)))?;
+    if status.code() == Some(SSH_CONNECTION_ERROR) {
+        return Err(TrainctlError::CloudProvider {
+            provider: "aws".to_string(),
+            message: format!(
+                "ssh to {}@{} failed; diagnose with: runctl aws ssh-check {}",
+                user, host, instance_id
+            ),
+            source: None,
+        });
+    }
+    Ok(())
+}
+
+/// One `Host` entry of the generated config
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct HostEntry {
+    pub instance_id: String,
+    /// Extra `Host` pattern from the instance's Name tag
+    pub name: Option<String>,
+    pub host_name: String,
+    pub user: String,
+    pub identity_file: Option<String>,
+    /// `ssh_config` options as (keyword, value)
+    pub options: Vec<(String, String)>,
+}
+
+impl HostEntry {
+    fn render(&self) -> String {
+        let mut patterns = vec![self.instance_id.clone()];
+        patterns.extend(self.name.iter().map(|n| format!("runctl-{}", n)));
+        let mut out = format!("Host {}\n", patterns.join(" "));
+        out.push_str(&format!("    HostName {}\n", self.host_name));
+        out.push_str(&format!("    User {}\n", self.user));
+        if let Some(key) = &self.identity_file {
+            out.push_str(&format!("    IdentityFile \"{}\"\n", key));
+            out.push_str("    IdentitiesOnly yes\n");
+        }
+        for (keyword, value) in &self.options {
+            out.push_str(&format!("    {} {}\n", keyword, value));
+        }
+        out
+    }
+}
+
+/// `-o Keyword=value` arguments as config (keyword, value) pairs
+fn config_options(args: &[String]) -> Vec<(String, String)> {
+    args.iter()
+        .filter(|arg| *arg != "-o")
+        .filter_map(|arg| arg.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+/// A Name tag usable as a Host pattern (no spaces or wildcards)
+fn host_alias(name: &str) -> Option<String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    valid.then(|| name.to_string())
+}
+
+/// Replace the runctl block in an ssh config, or add one at the top
+pub(crate) fn replace_block(existing: &str, block: &str) -> String {
+    let managed = format!("{}\n{}{}\n", BEGIN_MARKER, block, END_MARKER);
+    if let (Some(start), Some(end)) = (existing.find(BEGIN_MARKER), existing.find(END_MARKER)) {
+        if start < end {
+            let after = &existing[end + END_MARKER.len()..];
+            let after = after.strip_prefix('\n').unwrap_or(after);
+            return format!("{}{}{}", &existing[..start], managed, after);
+        }
+    }
+    // ssh uses the first value it sees, so put runctl's entries first
+    if existing.is_empty() {
+        managed
+    } else {
+        format!("{}\n{}", managed, existing)
+    }
+}
+
+/// Instances runctl created or adopted that aren't terminated
+async fn runctl_instances(client: &Ec2Client) -> Result<Vec<Instance>> {
+    let mut instances = Vec::new();
+    let mut next_token: Option<String> = None;
+    loop {
+        let response = client
+            .describe_instances()
+            .filters(
+                Filter::builder()
+                    .name("tag-key")
+                    .values("runctl:created")
+                    .build(),
+            )
+            .filters(
+                Filter::builder()
+                    .name("instance-state-name")
+                    .values("pending")
+                    .values("running")
+                    .values("stopping")
+                    .values("stopped")
+                    .build(),
+            )
+            .set_next_token(next_token)
+            .send()
+            .await
+            .map_err(|e| TrainctlError::Aws(format!("Failed to describe instances: {}", e)))?;
+        instances.extend(
+            response
+                .reservations()
+                .iter()
+                .flat_map(|r| r.instances())
+                .cloned(),
+        );
+        next_token = response.next_token().map(str::to_string);
+        if next_token.is_none() {
+            return Ok(instances);
+        }
+    }
+}
+
+/// Print (or with `write`, install) ssh config entries for runctl instances
+pub(crate) async fn ssh_config(
+    write: bool,
+    file: Option<PathBuf>,
+    config: &Config,
+    aws_config: &aws_config::SdkConfig,
+    output_format: &str,
+) -> Result<()> {
+    let client = Ec2Client::new(aws_config);
+    let proxy = SshProxy::from_config(config)?;
+    let mut entries = Vec::new();
+    let mut skipped = Vec::new();
+
+    for instance in runctl_instances(&client).await? {
+        let Some(instance_id) = instance.instance_id() else {
+            continue;
+        };
+        // Public IPs change across stop/start; rerun after starting
+        let host_name = match proxy.target(
+            instance_id,
+            crate::aws::public_address(&instance),
+            instance.private_ip_address(),
+        ) {
+            Ok(host) => host,
+            Err(_) => {
+                let state = instance
+                    .state()
+                    .and_then(|s| s.name())
+                    .map(|s| s.as_str())
+                    .unwrap_or("unknown");
+                skipped.push(format!("{} ({}): no address", instance_id, state));
+                continue;
+            }
+        };
+        let identity_file = match resolve_key_path(instance.key_name(), &config.ssh) {
+            Ok(path) => Some(path),
+            Err(_) => {
+                skipped.push(format!(
+                    "{}: private key for key pair '{}' not found locally; entry has no IdentityFile",
+                    instance_id,
+                    instance.key_name().unwrap_or("-")
+                ));
+                None
+            }
+        };
+        let name = instance
+            .tags()
+            .iter()
+            .find(|t| t.key() == Some("Name"))
+            .and_then(|t| t.value())
+            .and_then(host_alias);
+        let mut options = config_options(&HostKeys::for_host(&config.ssh, instance_id).ssh_args());
+        options.extend(config_options(&proxy.ssh_args()));
+        entries.push(HostEntry {
+            instance_id: instance_id.to_string(),
+            name,
+            host_name,
+            user: resolve_ssh_user(&client, &instance, None).await,
+            identity_file,
+            options,
+        });
+    }
+
+    if output_format == "json" && !write {
+        let result = serde_json::json!({ "hosts": entries, "skipped": skipped });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    let block: String = entries
+        .iter()
+        .map(HostEntry::render)
+        .collect::<Vec<_>>()
+        .join("\n");
+    if !write {
+        for note in &skipped {
+            println!("# {}", note);
+        }
+        print!("{}", block);
+        return Ok(());
+    }
+
+    let path = match file {
+        Some(path) => path,
+        None => dirs::home_dir()
+            .ok_or_else(|| TrainctlError::Validation {
+                field: "file".to_string(),
+                reason: "no home directory; pass --file".to_string(),
+            })?
+            .join(".ssh")
+            .join("config"),
+    };
+    write_config(&path, &block)?;
+    if output_format == "json" {
+        let result = serde_json::json!({
+            "success": true,
+            "file": path.display().to_string(),
+            "hosts": entries.len(),
+            "skipped": skipped,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        for note in &skipped {
+            println!("Skipped {}", note);
+        }
+        println!(
+            "Wrote {} host entr{} to {}",
+            entries.len(),
+            if entries.len() == 1 { "y" } else { "ies" },
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+fn write_config(path: &Path, block: &str) -> Result<()> {
+    let existing = if path.exists() {
+        std::fs::read_to_string(path)?
+    } else {
+        String::new()
+    };
+    if let Some(dir) = path.parent() {
+        crate::utils::ensure_dir(dir)?;
+    }
+    std::fs::write(path, replace_block(&existing, block))?;
+    // ssh refuses a config others can write
+    #[cfg(unix)]
+    if existing.is_empty() {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry() -> HostEntry {
+        HostEntry {
+            instance_id: "i-0abc".to_string(),
+            name: Some("trainer".to_string()),
+            host_name: "203.0.113.7".to_string(),
+            user: "ubuntu".to_string(),
+            identity_file: Some("/home/me/.ssh/team.pem".to_string()),
+            options: config_options(&[
+                "-o".to_string(),
+                "HostKeyAlias=i-0abc".to_string(),
+                "-o".to_string(),
+                "ProxyCommand=aws ssm start-session --target %h --parameters portNumber=%p"
+                    .to_string(),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_render_entry() {
+        let text = entry().render();
+        assert!(text.starts_with("Host i-0abc runctl-trainer\n"));
+        assert!(text.contains("    HostName 203.0.113.7\n"));
+        assert!(text.contains("    User ubuntu\n"));
+        assert!(text.contains("    IdentityFile \"/home/me/.ssh/team.pem\"\n"));
+        assert!(text.contains("    HostKeyAlias i-0abc\n"));
+        assert!(text.contains(
+            "    ProxyCommand aws ssm start-session --target %h --parameters portNumber=%p\n"
+        ));
+    }
+
+    #[test]
+    fn test_host_alias_rejects_patterns() {
+        assert_eq!(host_alias("gpu-box_1.a"), Some("gpu-box_1.a".to_string()));
+        assert_eq!(host_alias("my box"), None);
+        assert_eq!(host_alias("box*"), None);
+        assert_eq!(host_alias(""), None);
+    }
+
+    #[test]
+    fn test_replace_block_prepends_then_replaces() {
+        let user_config = "Host github.com\n    User git\n";
+        let first = replace_block(user_config, "Host i-1\n");
+        assert!(first.starts_with(BEGIN_MARKER));
+        assert!(first.ends_with(user_config));
+
+        let second = replace_block(&first, "Host i-2\n");
+        assert!(!second.contains("Host i-1"));
+        assert!(second.contains("Host i-2\n"));
+        assert_eq!(second.matches(BEGIN_MARKER).count(), 1);
+        assert!(second.ends_with(user_config));
+
+        assert_eq!(
+            replace_block("", "Host i-3\n"),
+            format!("{}\nHost i-3\n{}\n", BEGIN_MARKER, END_MARKER)
+        );
+    }
+}
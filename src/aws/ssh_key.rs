@@ -0,0 +1,418 @@
+//! SSH private keys for EC2 key pairs and `runctl aws ssh-check`
+//!
+//! An instance only records the name of its key pair, so the private key
+//! is looked up locally: `SSH_KEY_PATH` if set, then `[ssh.keys]`, then
+//! `<name>.pem` and `<name>` in `[ssh] key_dir` (default `~/.ssh`). The key
+//! pair name is also kept on the tracker record under `runctl:key-name`.
+//!
+//! Host keys are learned before connecting (see `host_keys`): over SSM when
+//! the instance has an instance profile, otherwise from the cloud-init host
+//! key block in the EC2 console output.
+//!
+//! `ssh-check` resolves the user and key the same way `train` does and
+//! tries them before a long sync: key file, host key, port 22, then a real
+//! login.
+
+use crate::aws::adopt::{describe, AccessCheck};
+use crate::aws::ssh_user::resolve_ssh_user;
+use crate::config::{Config, SshConfig};
+use crate::error::{Result, TrainctlError};
+use crate::host_keys::HostKeys;
+use crate::ssh_proxy::SshProxy;
+use aws_sdk_ec2::types::Instance;
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_ssm::Client as SsmClient;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::debug;
+
+/// Environment variable naming the key to use for every instance
+pub(crate) const SSH_KEY_ENV: &str = "SSH_KEY_PATH";
+
+/// Tracker tag holding the instance's key pair name
+pub(crate) const KEY_NAME_TAG: &str = "runctl:key-name";
+
+/// How long `ssh-check` waits for port 22 and for the login
+const PORT_TIMEOUT: Duration = Duration::from_secs(5);
+const LOGIN_TIMEOUT_SECS: u64 = 10;
+
+fn expand(path: &Path) -> PathBuf {
+    PathBuf::from(shellexpand::tilde(&path.to_string_lossy()).into_owned())
+}
+
+/// Key files to try for `key_name`, in lookup order (without `SSH_KEY_PATH`)
+pub(crate) fn key_candidates(key_name: &str, ssh: &SshConfig) -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Some(path) = ssh.keys.get(key_name) {
+        candidates.push(expand(path));
+    }
+    let dir = expand(&ssh.key_dir);
+    candidates.push(dir.join(format!("{}.pem", key_name)));
+    candidates.push(dir.join(key_name));
+    candidates
+}
+
+/// Private key for an instance launched with `key_name`
+pub(crate) fn resolve_key_path(key_name: Option<&str>, ssh: &SshConfig) -> Result<String> {
+    if let Ok(path) = std::env::var(SSH_KEY_ENV) {
+        let path = expand(Path::new(&path));
+        if path.is_file() {
+            return Ok(path.to_string_lossy().into_owned());
+        }
+        return Err(TrainctlError::Validation {
+            field: SSH_KEY_ENV.to_string(),
+            reason: format!("{} does not exist", path.display()),
+        });
+    }
+
+    let Some(key_name) = key_name else {
+        return Err(TrainctlError::Aws(format!(
+            "Instance has no key pair, so it can't be reached over SSH.\n\n\
+            To resolve:\n\
+              1. Set {} to a key authorized on the instance\n\
+              2. Or use SSM: create the instance with --iam-instance-profile and set [aws] s3_bucket",
+            SSH_KEY_ENV
+        )));
+    };
+    let candidates = key_candidates(key_name, ssh);
+    if let Some(found) = candidates.iter().find(|p| p.is_file()) {
+        return Ok(found.to_string_lossy().into_owned());
+    }
+    let tried: Vec<String> = candidates
+        .iter()
+        .map(|p| format!("  {}", p.display()))
+        .collect();
+    Err(TrainctlError::Aws(format!(
+        "Could not find the private key for key pair '{}'. Tried:\n{}\n\n\
+        To resolve:\n\
+          1. Map it in .runctl.toml:\n\
+             [ssh.keys]\n\
+             {} = \"/path/to/key.pem\"\n\
+          2. Or set [ssh] key_dir to the directory holding {}.pem\n\
+          3. Or set {}=/path/to/key.pem for this shell\n\
+          4. Or use SSM: create the instance with --iam-instance-profile and set [aws] s3_bucket",
+        key_name,
+        tried.join("\n"),
+        key_name,
+        key_name,
+        SSH_KEY_ENV
+    )))
+}
+
+/// Record `instance`'s host keys from AWS, returning where they came from
+///
+/// Returns None when no keys could be learned (console output appears a
+/// few minutes after boot); the caller then relies on trust on first use
+/// or, in strict mode, refuses to connect.
+pub(crate) async fn learn_host_keys(
+    ec2: &Ec2Client,
+    ssm: &SsmClient,
+    instance: &Instance,
+    host_keys: &HostKeys,
+) -> Option<&'static str> {
+    let instance_id = instance.instance_id()?;
+    if instance.iam_instance_profile().is_some() {
+        match crate::aws_utils::execute_ssm_command(
+            ssm,
+            instance_id,
+            "cat /etc/ssh/ssh_host_*_key.pub",
+        )
+        .await
+        {
+            Ok(output) => match host_keys.record(&output) {
+                Ok(n) if n > 0 => return Some("SSM"),
+                Ok(_) => debug!("No host keys in SSM output for {}", instance_id),
+                Err(e) => debug!("Failed to record host keys for {}: {}", instance_id, e),
+            },
+            Err(e) => debug!("Could not read host keys over SSM: {}", e),
+        }
+    }
+
+    let output = match ec2
+        .get_console_output()
+        .instance_id(instance_id)
+        .send()
+        .await
+    {
+        Ok(response) => response.output().map(str::to_string)?,
+        Err(e) => {
+            debug!("Could not read console output of {}: {}", instance_id, e);
+            return None;
+        }
+    };
+    use base64::Engine;
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(output.trim())
+        .ok()?;
+    let text = String::from_utf8_lossy(&decoded);
+    let block = console_host_key_block(&text)?;
+    match host_keys.record(block) {
+        Ok(n) if n > 0 => Some("console output"),
+        Ok(_) => None,
+        Err(e) => {
+            debug!("Failed to record host keys for {}: {}", instance_id, e);
+            None
+        }
+    }
+}
+
+/// The cloud-init `BEGIN SSH HOST KEY KEYS` block of a console log
+fn console_host_key_block(console: &str) -> Option<&str> {
+    let start = console.find("-----BEGIN SSH HOST KEY KEYS-----")?;
+    let rest = &console[start..];
+    let end = rest.find("-----END SSH HOST KEY KEYS-----")?;
+    Some(&rest[..end])
+}
+
+/// Problem with a private key's permissions that makes ssh refuse it
+#[cfg(unix)]
+fn key_permission_problem(path: &Path) -> Option<String> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = std::fs::metadata(path).ok()?.permissions().mode() & 0o777;
+    (mode & 0o077 != 0).then(|| {
+        format!(
+            "{} has mode {:o}; ssh ignores keys others can read (chmod 600 {})",
+            path.display(),
+            mode,
+            path.display()
+        )
+    })
+}
+
+#[cfg(not(unix))]
+fn key_permission_problem(_path: &Path) -> Option<String> {
+    None
+}
+
+/// Check that `instance_id` can be reached over SSH as `train` would
+pub(crate) async fn ssh_check(
+    instance_id: &str,
+    ssh_user: Option<&str>,
+    config: &Config,
+    aws_config: &aws_config::SdkConfig,
+    output_format: &str,
+) -> Result<()> {
+    let client = Ec2Client::new(aws_config);
+    let instance = describe(&client, instance_id).await?;
+    let state = instance
+        .state()
+        .and_then(|s| s.name())
+        .map(|s| s.as_str().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    if state != "running" {
+        return Err(TrainctlError::Resource {
+            resource_type: "instance".to_string(),
+            operation: "ssh-check".to_string(),
+            resource_id: Some(instance_id.to_string()),
+            message: format!(
+                "Instance is {}; start it with: runctl aws start {} --wait",
+                state, instance_id
+            ),
+            source: None,
+        });
+    }
+
+    let user = resolve_ssh_user(&client, &instance, ssh_user).await;
+    let key_name = instance.key_name();
+    let proxy = SshProxy::from_config(config)?;
+    let host = proxy
+        .target(
+            instance_id,
+            crate::aws::public_address(&instance),
+            instance.private_ip_address(),
+        )
+        .ok();
+    let mut checks = Vec::new();
+
+    let key = match resolve_key_path(key_name, &config.ssh) {
+        Ok(path) => {
+            let problem = key_permission_problem(Path::new(&path));
+            checks.push(AccessCheck {
+                method: "key".to_string(),
+                ok: problem.is_none(),
+                detail: problem
+                    .unwrap_or_else(|| format!("{} (key pair {})", path, key_name.unwrap_or("-"))),
+            });
+            Some(path)
+        }
+        Err(e) => {
+            checks.push(AccessCheck {
+                method: "key".to_string(),
+                ok: false,
+                detail: e.to_string().lines().next().unwrap_or_default().to_string(),
+            });
+            None
+        }
+    };
+
+    let host_keys = HostKeys::for_host(&config.ssh, instance_id);
+    let ssm = SsmClient::new(aws_config);
+    let known = host_keys.known_hosts.display();
+    let (host_key_ok, detail) = match learn_host_keys(&client, &ssm, &instance, &host_keys).await {
+        Some(source) => (true, format!("learned from {} ({})", source, known)),
+        None if host_keys.is_known() => (true, format!("recorded in {}", known)),
+        None if host_keys.strict => (
+            false,
+            "not recorded and not learnable yet; strict_host_keys refuses it".to_string(),
+        ),
+        None => (
+            true,
+            "not recorded yet; recorded on first connect".to_string(),
+        ),
+    };
+    checks.push(AccessCheck {
+        method: "host-key".to_string(),
+        ok: host_key_ok,
+        detail,
+    });
+
+    let port_open = match (&host, proxy.is_direct()) {
+        (Some(ip), true) => {
+            let reachable = tokio::time::timeout(
+                PORT_TIMEOUT,
+                tokio::net::TcpStream::connect((ip.as_str(), 22)),
+            )
+            .await;
+            let address = crate::utils::bracket_ipv6(ip);
+            let (ok, detail) = match reachable {
+                Ok(Ok(_)) => (true, format!("{}:22 open", address)),
+                Ok(Err(e)) => (false, format!("{}:22 refused: {}", address, e)),
+                Err(_) => (
+                    false,
+                    format!("{}:22 timed out (check the security group)", address),
+                ),
+            };
+            checks.push(AccessCheck {
+                method: "port".to_string(),
+                ok,
+                detail,
+            });
+            ok
+        }
+        // The port is only reachable through the proxy; the login checks it
+        (Some(host), false) => {
+            checks.push(AccessCheck {
+                method: "port".to_string(),
+                ok: true,
+                detail: format!(
+                    "{} via {}",
+                    host,
+                    config.ssh.bastion.as_deref().unwrap_or("-")
+                ),
+            });
+            true
+        }
+        (None, _) => {
+            checks.push(AccessCheck {
+                method: "port".to_string(),
+                ok: false,
+                detail: "no public IP (set [ssh] bastion to reach private instances)".to_string(),
+            });
+            false
+        }
+    };
+
+    if let (Some(key), Some(ip), true) = (&key, &host, port_open && host_key_ok) {
+        let output = tokio::process::Command::new("ssh")
+            .args(["-o", "BatchMode=yes"])
+            .args(host_keys.ssh_args())
+            .args(proxy.ssh_args())
+            .args(["-o", &format!("ConnectTimeout={}", LOGIN_TIMEOUT_SECS)])
+            .arg("-i")
+            .arg(key)
+            .arg(format!("{}@{}", user, ip))
+            .arg("true")
+            .output()
+            .await?;
+        let detail = if output.status.success() {
+            format!("logged in as {}", user)
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let reason = stderr
+                .lines()
+                .rfind(|l| !l.trim().is_empty() && !l.starts_with("Warning:"))
+                .unwrap_or("ssh failed");
+            format!("{}@{}: {}", user, ip, reason.trim())
+        };
+        checks.push(AccessCheck {
+            method: "login".to_string(),
+            ok: output.status.success(),
+            detail,
+        });
+    }
+
+    let ok = checks.len() == 4 && checks.iter().all(|c| c.ok);
+    if output_format == "json" {
+        let result = serde_json::json!({
+            "success": ok,
+            "instance_id": instance_id,
+            "user": user,
+            "key_name": key_name,
+            "checks": checks,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        println!("SSH check for {} (user {}):", instance_id, user);
+        for check in &checks {
+            let mark = if check.ok { "ok  " } else { "FAIL" };
+            println!("  [{}] {}: {}", mark, check.method, check.detail);
+        }
+    }
+    if ok {
+        Ok(())
+    } else {
+        Err(TrainctlError::CloudProvider {
+            provider: "aws".to_string(),
+            message: format!("{} is not reachable over SSH", instance_id),
+            source: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_candidates_order() {
+        let mut ssh = SshConfig {
+            key_dir: PathBuf::from("/keys"),
+            ..Default::default()
+        };
+        ssh.keys
+            .insert("team".to_string(), PathBuf::from("/secure/team.pem"));
+        assert_eq!(
+            key_candidates("team", &ssh),
+            vec![
+                PathBuf::from("/secure/team.pem"),
+                PathBuf::from("/keys/team.pem"),
+                PathBuf::from("/keys/team"),
+            ]
+        );
+        assert_eq!(
+            key_candidates("other", &ssh),
+            vec![
+                PathBuf::from("/keys/other.pem"),
+                PathBuf::from("/keys/other")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_key_path_uses_key_dir() {
+        if std::env::var(SSH_KEY_ENV).is_ok() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("gpu"), "key").unwrap();
+        let ssh = SshConfig {
+            key_dir: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let path = resolve_key_path(Some("gpu"), &ssh).unwrap();
+        assert_eq!(Path::new(&path), dir.path().join("gpu"));
+
+        let err = resolve_key_path(Some("missing"), &ssh).unwrap_err();
+        assert!(err.to_string().contains("missing.pem"));
+    }
+}
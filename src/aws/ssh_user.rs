@@ -0,0 +1,173 @@
+//! SSH login user for EC2 instances
+//!
+//! The login user depends on the AMI's distribution (`ubuntu` on Ubuntu
+//! and the Ubuntu Deep Learning AMIs, `ec2-user` on Amazon Linux), which
+//! can't be read from the image ID. `aws create` reads the AMI's name and
+//! description and records the user in the `runctl:ssh-user` tag; later
+//! commands use the tag, falling back to the AMI's metadata for instances
+//! created without it. `--ssh-user` overrides both.
+
+use crate::error::{Result, TrainctlError};
+use aws_sdk_ec2::types::Instance;
+use aws_sdk_ec2::Client as Ec2Client;
+use tracing::{debug, warn};
+
+/// Tag recording the login user, written at create time
+pub(crate) const SSH_USER_TAG: &str = "runctl:ssh-user";
+
+/// User assumed when neither the tag nor the AMI identifies one
+pub(crate) const DEFAULT_SSH_USER: &str = "ec2-user";
+
+/// Parse `--ssh-user`: a POSIX login name
+pub(crate) fn parse_ssh_user(value: &str) -> std::result::Result<String, String> {
+    let valid = !value.is_empty()
+        && value.len() <= 32
+        && value
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && value
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+    if valid {
+        Ok(value.to_string())
+    } else {
+        Err(format!(
+            "'{}' is not a valid login name (lowercase letters, digits, '_' and '-')",
+            value
+        ))
+    }
+}
+
+/// Login user for an AMI, judged by its name and description
+pub(crate) fn user_for_image(name: &str, description: &str) -> Option<&'static str> {
+    let text = format!("{} {}", name, description).to_lowercase();
+    if text.contains("ubuntu") {
+        Some("ubuntu")
+    } else if text.contains("debian") {
+        Some("admin")
+    } else if ["amazon linux", "amzn", "al2023", "rhel", "red hat"]
+        .iter()
+        .any(|marker| text.contains(marker))
+    {
+        Some("ec2-user")
+    } else {
+        None
+    }
+}
+
+/// Login user for `ami_id`, or None if its metadata doesn't say
+pub(crate) async fn user_for_ami(client: &Ec2Client, ami_id: &str) -> Result<Option<&'static str>> {
+    let response = client
+        .describe_images()
+        .image_ids(ami_id)
+        .send()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to describe AMI {}: {}", ami_id, e)))?;
+    Ok(response.images().first().and_then(|image| {
+        user_for_image(
+            image.name().unwrap_or(""),
+            image.description().unwrap_or(""),
+        )
+    }))
+}
+
+/// SSH user for `instance`: `override_user`, then the `runctl:ssh-user`
+/// tag, then the AMI's name and description, then `ec2-user`
+pub(crate) async fn resolve_ssh_user(
+    client: &Ec2Client,
+    instance: &Instance,
+    override_user: Option<&str>,
+) -> String {
+    if let Some(user) = override_user {
+        return user.to_string();
+    }
+    let tagged = instance
+        .tags()
+        .iter()
+        .find(|t| t.key() == Some(SSH_USER_TAG))
+        .and_then(|t| t.value())
+        .filter(|user| parse_ssh_user(user).is_ok());
+    if let Some(user) = tagged {
+        return user.to_string();
+    }
+
+    let instance_id = instance.instance_id().unwrap_or("unknown");
+    let Some(ami_id) = instance.image_id() else {
+        warn!(
+            "Instance {} has no image ID; assuming SSH user {}",
+            instance_id, DEFAULT_SSH_USER
+        );
+        return DEFAULT_SSH_USER.to_string();
+    };
+    match user_for_ami(client, ami_id).await {
+        Ok(Some(user)) => {
+            debug!("SSH user for {} from AMI {}: {}", instance_id, ami_id, user);
+            user.to_string()
+        }
+        Ok(None) => {
+            warn!(
+                "Could not tell the login user of AMI {}; assuming {} (override with --ssh-user)",
+                ami_id, DEFAULT_SSH_USER
+            );
+            DEFAULT_SSH_USER.to_string()
+        }
+        Err(e) => {
+            warn!(
+                "{}; assuming SSH user {} (override with --ssh-user)",
+                e, DEFAULT_SSH_USER
+            );
+            DEFAULT_SSH_USER.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_for_ubuntu_dlami() {
+        assert_eq!(
+            user_for_image(
+                "Deep Learning OSS Nvidia Driver AMI GPU PyTorch 2.3.0 (Ubuntu 20.04) 20240611",
+                ""
+            ),
+            Some("ubuntu")
+        );
+        assert_eq!(
+            user_for_image("ubuntu/images/hvm-ssd/ubuntu-jammy-22.04-amd64-server", ""),
+            Some("ubuntu")
+        );
+    }
+
+    #[test]
+    fn test_user_for_amazon_linux() {
+        assert_eq!(
+            user_for_image("al2023-ami-2023.4.20240611.0-kernel-6.1-x86_64", ""),
+            Some("ec2-user")
+        );
+        assert_eq!(
+            user_for_image(
+                "Deep Learning AMI GPU PyTorch 2.0.1",
+                "Supported EC2 instances: G4dn. Amazon Linux 2"
+            ),
+            Some("ec2-user")
+        );
+    }
+
+    #[test]
+    fn test_parse_ssh_user() {
+        assert_eq!(parse_ssh_user("ubuntu").unwrap(), "ubuntu");
+        assert_eq!(parse_ssh_user("ec2-user").unwrap(), "ec2-user");
+        assert!(parse_ssh_user("").is_err());
+        assert!(parse_ssh_user("Ubuntu").is_err());
+        assert!(parse_ssh_user("root; rm -rf /").is_err());
+    }
+
+    #[test]
+    fn test_user_for_unknown_image() {
+        assert_eq!(user_for_image("my-custom-image", "built by packer"), None);
+        assert_eq!(user_for_image("debian-12-amd64", ""), Some("admin"));
+    }
+}
@@ -6,104 +6,14 @@
 use crate::aws_utils::execute_ssm_command;
 use crate::config::Config;
 use crate::error::{Result, TrainctlError};
+use crate::progress::ProgressEvent;
+use crate::sync_filter::{write_code_archive, SyncFilter};
 use aws_sdk_s3::Client as S3Client;
 use aws_sdk_ssm::Client as SsmClient;
-use flate2::write::GzEncoder;
-use flate2::Compression;
-use ignore::gitignore::GitignoreBuilder;
-use ignore::WalkBuilder;
-use indicatif::{ProgressBar, ProgressStyle};
-use std::fs::File;
-use std::path::{Path, PathBuf};
-use tar::Builder;
+use indicatif::ProgressBar;
+use std::path::Path;
 use tracing::{info, warn};
 
-/// Collect files to sync (similar to ssh_sync logic)
-fn collect_files_to_sync(project_root: &Path, include_patterns: &[String]) -> Result<Vec<PathBuf>> {
-    // Build gitignore matcher
-    let mut builder = GitignoreBuilder::new(project_root);
-
-    // Add .gitignore if it exists
-    let gitignore_path = project_root.join(".gitignore");
-    if gitignore_path.exists() {
-        if let Ok(content) = std::fs::read_to_string(&gitignore_path) {
-            for line in content.lines() {
-                builder.add_line(None, line).map_err(|e| {
-                    TrainctlError::Io(std::io::Error::other(format!(
-                        "Failed to add gitignore line: {}",
-                        e
-                    )))
-                })?;
-            }
-        }
-    }
-
-    // Add negations for include patterns (override gitignore)
-    for pattern in include_patterns {
-        let normalized_pattern = if pattern.ends_with('/') {
-            format!("!{}**", pattern)
-        } else {
-            format!("!{}", pattern)
-        };
-        builder.add_line(None, &normalized_pattern).map_err(|e| {
-            TrainctlError::Io(std::io::Error::other(format!(
-                "Failed to add include pattern '{}': {}",
-                pattern, e
-            )))
-        })?;
-    }
-
-    let gitignore = builder.build().map_err(|e| {
-        TrainctlError::Io(std::io::Error::other(format!(
-            "Failed to build gitignore: {}",
-            e
-        )))
-    })?;
-
-    // Walk all files
-    let files: Vec<PathBuf> = WalkBuilder::new(project_root)
-        .git_ignore(false)
-        .git_global(false)
-        .git_exclude(false)
-        .build()
-        .filter_map(|entry| {
-            let entry = entry.ok()?;
-            let path = entry.path();
-
-            if !path.is_file() {
-                return None;
-            }
-
-            let rel_path = match path.strip_prefix(project_root) {
-                Ok(p) => p,
-                Err(_) => return None,
-            };
-
-            // Check if matches include pattern
-            let matches_include = include_patterns.iter().any(|pattern| {
-                let pattern_path = Path::new(pattern);
-                rel_path.starts_with(pattern_path)
-                    || rel_path
-                        .parent()
-                        .map(|p| p == pattern_path || p.starts_with(pattern_path))
-                        .unwrap_or(false)
-            });
-
-            // Check gitignore
-            let matched = gitignore.matched(rel_path, false);
-
-            // Include if matches include pattern or not gitignored
-            if matches_include || !matched.is_ignore() {
-                Some(path.to_path_buf())
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    Ok(files)
-}
-
 /// Sync code to instance via SSM using S3 as intermediate storage
 ///
 /// Strategy:
@@ -117,7 +27,7 @@ pub async fn sync_code_via_ssm(
     instance_id: &str,
     project_dir: &str,
     script_path: &Path,
-    include_patterns: &[String],
+    filter: &SyncFilter,
     s3_client: &S3Client,
     ssm_client: &SsmClient,
     config: &Config,
@@ -136,11 +46,9 @@ pub async fn sync_code_via_ssm(
 
     let pb = if output_format != "json" {
         let pb = ProgressBar::new_spinner();
-        pb.set_style(
-            ProgressStyle::default_spinner()
-                .template("{spinner:.green} [{elapsed_precise}] {msg}")
-                .expect("Progress bar template should be valid"),
-        );
+        pb.set_style(crate::output_style::spinner(
+            "{spinner:.green} [{elapsed_precise}] {msg}",
+        ));
         pb.set_message("Creating code archive...");
         Some(pb)
     } else {
@@ -152,7 +60,7 @@ pub async fn sync_code_via_ssm(
         p.set_message("Creating code archive...");
     }
 
-    let files_to_sync = collect_files_to_sync(project_root, include_patterns)?;
+    let files_to_sync = filter.select(project_root)?.files;
 
     if files_to_sync.is_empty() {
         return Err(TrainctlError::CloudProvider {
@@ -167,62 +75,24 @@ pub async fn sync_code_via_ssm(
     if let Some(ref p) = pb {
         p.set_message(format!("Archiving {} files...", files_to_sync.len()));
     }
+    crate::progress::emit(
+        "sync.archive",
+        format!("Archiving {} files", files_to_sync.len()),
+    );
 
     let temp_archive =
         std::env::temp_dir().join(format!("runctl-code-{}.tar.gz", uuid::Uuid::new_v4()));
-
-    {
-        let file = File::create(&temp_archive).map_err(|e| {
-            TrainctlError::Io(std::io::Error::other(format!(
-                "Failed to create archive: {}",
-                e
-            )))
-        })?;
-        let encoder = GzEncoder::new(file, Compression::default());
-        let mut tar = Builder::new(encoder);
-
-        let mut files_added = 0;
-        for file_path in &files_to_sync {
-            // Skip if file doesn't exist (might have been deleted)
-            if !file_path.exists() {
-                warn!("Skipping non-existent file: {}", file_path.display());
-                continue;
-            }
-
-            let relative_path = file_path.strip_prefix(project_root).map_err(|e| {
-                TrainctlError::Io(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Failed to get relative path: {}", e),
-                ))
-            })?;
-
-            tar.append_path_with_name(file_path, relative_path)
-                .map_err(|e| {
-                    TrainctlError::Io(std::io::Error::other(format!(
-                        "Failed to add file to archive: {}",
-                        e
-                    )))
-                })?;
-            files_added += 1;
-        }
-
-        if files_added == 0 {
-            return Err(TrainctlError::CloudProvider {
-                provider: "aws".to_string(),
-                message: "No files were added to archive. All files may have been deleted or inaccessible.".to_string(),
-                source: None,
-            });
-        }
-
-        info!("Added {} files to archive", files_added);
-
-        tar.finish().map_err(|e| {
-            TrainctlError::Io(std::io::Error::other(format!(
-                "Failed to finalize archive: {}",
-                e
-            )))
-        })?;
+    let files_added = write_code_archive(project_root, &files_to_sync, &temp_archive)?;
+    if files_added == 0 {
+        return Err(TrainctlError::CloudProvider {
+            provider: "aws".to_string(),
+            message:
+                "No files were added to archive. All files may have been deleted or inaccessible."
+                    .to_string(),
+            source: None,
+        });
     }
+    info!("Added {} files to archive", files_added);
 
     let archive_size = std::fs::metadata(&temp_archive)
         .map_err(|e| {
@@ -244,6 +114,9 @@ pub async fn sync_code_via_ssm(
     if let Some(ref p) = pb {
         p.set_message("Uploading to S3...");
     }
+    ProgressEvent::new("sync.upload", "Uploading code archive to S3")
+        .bytes(0, Some(archive_size))
+        .emit();
 
     let s3_key = format!(
         "runctl-temp/{}/{}.tar.gz",
@@ -267,6 +140,9 @@ pub async fn sync_code_via_ssm(
         .map_err(|e| TrainctlError::S3(format!("Failed to upload to S3: {}", e)))?;
 
     info!("Uploaded code archive to {}", s3_path);
+    ProgressEvent::new("sync.upload", "Uploaded code archive to S3")
+        .bytes(archive_size, Some(archive_size))
+        .emit();
 
     // Clean up local archive
     if let Err(e) = std::fs::remove_file(&temp_archive) {
@@ -281,6 +157,7 @@ pub async fn sync_code_via_ssm(
     if let Some(ref p) = pb {
         p.set_message("Downloading and extracting on instance...");
     }
+    crate::progress::emit("sync.extract", "Downloading and extracting on instance");
 
     // Create project directory
     let mkdir_cmd = format!("mkdir -p {}", project_dir);
@@ -359,6 +236,9 @@ pub async fn sync_code_via_ssm(
     if let Some(ref p) = pb {
         p.finish_with_message("Code sync complete");
     }
+    ProgressEvent::new("sync.done", "Code sync complete")
+        .done()
+        .emit();
 
     Ok(())
 }
@@ -0,0 +1,201 @@
+//! Details merged into `runctl aws status`
+//!
+//! Besides what EC2 reports, status shows the training process on the
+//! instance (PID, uptime, exit code and newest checkpoint, read over SSM from
+//! the project directory `train` used), the attached EBS volumes, and the
+//! local session that launched the job, if any (`.runctl/sessions`).
+
+use crate::error::Result;
+use crate::training::TrainingSession;
+use aws_sdk_ec2::types::Instance;
+use aws_sdk_ec2::Client as Ec2Client;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::path::Path;
+
+/// Checkpoint file extensions, as `jobs migrate` looks for them
+const CHECKPOINT_GLOBS: &[&str] = &["*.pt", "*.pth", "*.ckpt", "*.safetensors"];
+
+/// Training process state read from the instance
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub(crate) struct TrainingProbe {
+    /// `running`, `completed`, `not_started`, or `no_project`
+    pub state: String,
+    pub pid: Option<u32>,
+    /// How long the process has been running
+    pub elapsed_secs: Option<u64>,
+    /// Written by the training wrapper when the process exits
+    pub exit_code: Option<i32>,
+    pub checkpoint: Option<String>,
+    pub checkpoint_time: Option<DateTime<Utc>>,
+}
+
+impl TrainingProbe {
+    /// Status in the terms `training_status` has always used
+    pub fn summary(&self) -> String {
+        match (self.state.as_str(), self.exit_code) {
+            ("completed", Some(code)) if code != 0 => format!("failed (exit {})", code),
+            (state, _) => state.to_string(),
+        }
+    }
+}
+
+/// Shell script printing `key=value` lines about the job in `project_dir`
+pub(crate) fn probe_script(project_dir: &str) -> String {
+    let checkpoints: Vec<String> = CHECKPOINT_GLOBS
+        .iter()
+        .map(|glob| format!("checkpoints/{}", glob))
+        .collect();
+    format!(
+        "cd {dir} 2>/dev/null || {{ echo state=no_project; exit 0; }}\n\
+         if [ -f training.pid ]; then\n\
+         \x20 PID=$(cat training.pid 2>/dev/null); echo pid=$PID\n\
+         \x20 if ps -p $PID >/dev/null 2>&1; then\n\
+         \x20   echo state=running; echo elapsed=$(ps -o etimes= -p $PID | tr -d ' ')\n\
+         \x20 else echo state=completed; fi\n\
+         else echo state=not_started; fi\n\
+         [ -f training_exit_code.txt ] && echo exit_code=$(cat training_exit_code.txt)\n\
+         CKPT=$(ls -t {checkpoints} 2>/dev/null | head -1)\n\
+         if [ -n \"$CKPT\" ]; then echo checkpoint=$(basename \"$CKPT\"); echo checkpoint_time=$(stat -c %Y \"$CKPT\"); fi\n\
+         true",
+        dir = crate::utils::shell_quote(project_dir),
+        checkpoints = checkpoints.join(" "),
+    )
+}
+
+/// Parse the output of `probe_script`; unknown or malformed lines are ignored
+pub(crate) fn parse_probe(output: &str) -> TrainingProbe {
+    let mut probe = TrainingProbe::default();
+    for (key, value) in output
+        .lines()
+        .filter_map(|line| line.trim().split_once('='))
+    {
+        match key {
+            "state" => probe.state = value.to_string(),
+            "pid" => probe.pid = value.parse().ok(),
+            "elapsed" => probe.elapsed_secs = value.parse().ok(),
+            "exit_code" => probe.exit_code = value.trim().parse().ok(),
+            "checkpoint" if !value.is_empty() => probe.checkpoint = Some(value.to_string()),
+            "checkpoint_time" => {
+                probe.checkpoint_time = value
+                    .parse::<i64>()
+                    .ok()
+                    .and_then(|secs| DateTime::from_timestamp(secs, 0))
+            }
+            _ => {}
+        }
+    }
+    if probe.state.is_empty() {
+        probe.state = "unknown".to_string();
+    }
+    probe
+}
+
+/// An EBS volume attached to the instance
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct AttachedVolume {
+    pub volume_id: String,
+    pub device: Option<String>,
+    pub size_gb: Option<i32>,
+    pub volume_type: Option<String>,
+    pub delete_on_termination: Option<bool>,
+}
+
+/// Volumes from the instance's block device mappings, with sizes and types
+///
+/// If the volumes can't be described, they're returned without them.
+pub(crate) async fn attached_volumes(
+    client: &Ec2Client,
+    instance: &Instance,
+) -> Vec<AttachedVolume> {
+    let mut volumes: Vec<AttachedVolume> = instance
+        .block_device_mappings()
+        .iter()
+        .filter_map(|mapping| {
+            let ebs = mapping.ebs()?;
+            Some(AttachedVolume {
+                volume_id: ebs.volume_id()?.to_string(),
+                device: mapping.device_name().map(str::to_string),
+                size_gb: None,
+                volume_type: None,
+                delete_on_termination: ebs.delete_on_termination(),
+            })
+        })
+        .collect();
+    if volumes.is_empty() {
+        return volumes;
+    }
+
+    let ids: Vec<String> = volumes.iter().map(|v| v.volume_id.clone()).collect();
+    match client
+        .describe_volumes()
+        .set_volume_ids(Some(ids))
+        .send()
+        .await
+    {
+        Ok(response) => {
+            for described in response.volumes() {
+                if let Some(volume) = volumes
+                    .iter_mut()
+                    .find(|v| Some(v.volume_id.as_str()) == described.volume_id())
+                {
+                    volume.size_gb = described.size();
+                    volume.volume_type = described.volume_type().map(|t| t.as_str().to_string());
+                }
+            }
+        }
+        Err(e) => tracing::debug!("Failed to describe volumes: {}", e),
+    }
+    volumes
+}
+
+/// The newest local session (from `runctl run` or `JobBuilder`) that launched `instance_id`
+pub(crate) fn local_session(
+    sessions_dir: &Path,
+    instance_id: &str,
+) -> Result<Option<TrainingSession>> {
+    Ok(TrainingSession::list_sessions(sessions_dir)?
+        .into_iter()
+        .filter(|s| s.launch.as_ref().and_then(|l| l.resource_id.as_deref()) == Some(instance_id))
+        .max_by_key(|s| s.started_at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_running_probe() {
+        let probe = parse_probe(
+            "pid=4242\nstate=running\nelapsed=3725\ncheckpoint=epoch_3.pt\ncheckpoint_time=1767225600\n",
+        );
+        assert_eq!(probe.state, "running");
+        assert_eq!(probe.pid, Some(4242));
+        assert_eq!(probe.elapsed_secs, Some(3725));
+        assert_eq!(probe.checkpoint.as_deref(), Some("epoch_3.pt"));
+        assert_eq!(
+            probe.checkpoint_time,
+            DateTime::from_timestamp(1_767_225_600, 0)
+        );
+        assert_eq!(probe.summary(), "running");
+    }
+
+    #[test]
+    fn test_parse_failed_and_empty_probes() {
+        let probe = parse_probe("pid=17\nstate=completed\nexit_code=3\n");
+        assert_eq!(probe.summary(), "failed (exit 3)");
+        assert_eq!(
+            parse_probe("state=completed\nexit_code=0").summary(),
+            "completed"
+        );
+        assert_eq!(parse_probe("").state, "unknown");
+        assert_eq!(parse_probe("garbage\npid=\n").pid, None);
+    }
+
+    #[test]
+    fn test_probe_script_quotes_project_dir() {
+        let script = probe_script("/home/ubuntu/my project");
+        assert!(script.starts_with("cd '/home/ubuntu/my project'"));
+        assert!(script.contains("checkpoints/*.safetensors"));
+    }
+}
@@ -0,0 +1,200 @@
+//! Teardown on training completion
+//!
+//! `aws train --stop-on-complete` / `--terminate-on-complete` start a small
+//! hook on the instance, as a transient systemd unit, right after training
+//! starts. The hook waits for the training wrapper's PID (`training.pid`) to
+//! exit, uploads the checkpoint directory and `training.log` to S3, and then
+//! runs `shutdown -h now`; the EC2 shutdown behavior is set to match the flag,
+//! so that stops or terminates the instance.
+//!
+//! If the upload fails after a few attempts the instance is left running, so
+//! a finished run never loses its checkpoints to a teardown. Without an S3
+//! destination (no instance profile or bucket) nothing is uploaded.
+
+use crate::config::AutostopAction;
+use crate::utils::shell_quote;
+use aws_sdk_ec2::types::Tag;
+use aws_sdk_ec2::Client as Ec2Client;
+use base64::Engine;
+
+/// Where the hook script is installed on the instance
+const SCRIPT_PATH: &str = "/usr/local/bin/runctl-teardown";
+
+/// Transient systemd unit running the hook
+const UNIT_NAME: &str = "runctl-teardown";
+
+/// Tag recording what happens when training finishes: `stop` or `terminate`
+pub(crate) const ON_COMPLETE_TAG: &str = "runctl:on-complete";
+
+/// Printed by the install command so success can be told apart from SSM noise
+pub(crate) const INSTALLED_MARKER: &str = "RUNCTL_TEARDOWN_INSTALLED";
+
+/// Seconds between checks of the training process
+const POLL_SECS: u32 = 30;
+
+/// What the hook needs to know about the run
+#[derive(Debug, Clone)]
+pub(crate) struct TeardownHook {
+    pub action: AutostopAction,
+    /// Project directory holding `training.pid` and `training.log`
+    pub project_dir: String,
+    /// Checkpoint directory on the instance
+    pub checkpoint_dir: String,
+    /// `s3://bucket/prefix` to upload to; `None` skips the upload
+    pub s3_dest: Option<String>,
+}
+
+impl TeardownHook {
+    /// Default upload destination: `s3://<bucket>/runctl/checkpoints/<project>/<instance>`
+    pub fn default_dest(bucket: &str, project: &str, instance_id: &str) -> String {
+        format!(
+            "s3://{}/runctl/checkpoints/{}/{}",
+            bucket, project, instance_id
+        )
+    }
+
+    /// Render the hook script
+    pub fn script(&self) -> String {
+        format!(
+            r#"#!/bin/bash
+# runctl completion hook: {action} the instance once training exits
+DIR={dir}
+CHECKPOINTS={checkpoints}
+DEST={dest}
+PID=$(cat "$DIR/training.pid" 2>/dev/null)
+if [ -z "$PID" ]; then
+    logger -t {unit} "no training.pid in $DIR; not tearing down"
+    exit 1
+fi
+while kill -0 "$PID" 2>/dev/null; do
+    sleep {poll}
+done
+code=$(cat "$DIR/training_exit_code.txt" 2>/dev/null || echo unknown)
+logger -t {unit} "training exited ($code)"
+if [ -n "$DEST" ]; then
+    uploaded=""
+    for attempt in 1 2 3; do
+        if {{ [ ! -d "$CHECKPOINTS" ] || aws s3 sync "$CHECKPOINTS" "$DEST/checkpoints/" --only-show-errors; }} \
+            && aws s3 cp "$DIR/training.log" "$DEST/training.log" --only-show-errors; then
+            uploaded=1
+            break
+        fi
+        sleep 30
+    done
+    if [ -z "$uploaded" ]; then
+        logger -t {unit} "upload to $DEST failed; leaving the instance running"
+        exit 1
+    fi
+fi
+logger -t {unit} "training done, shutting down ({action})"
+shutdown -h now
+"#,
+            action = self.action,
+            dir = shell_quote(&self.project_dir),
+            checkpoints = shell_quote(&self.checkpoint_dir),
+            dest = shell_quote(self.s3_dest.as_deref().unwrap_or("")),
+            unit = UNIT_NAME,
+            poll = POLL_SECS,
+        )
+    }
+
+    /// Shell command that installs the script and starts it in the background
+    ///
+    /// Works as root (SSM) or as a user with passwordless sudo (SSH). A hook
+    /// left over from an earlier run is replaced.
+    pub fn install_command(&self) -> String {
+        let script = base64::engine::general_purpose::STANDARD.encode(self.script());
+        format!(
+            r#"set -e
+SUDO=""
+[ "$(id -u)" -eq 0 ] || SUDO="sudo -n"
+echo '{script}' | base64 -d | $SUDO tee {path} > /dev/null
+$SUDO chmod 755 {path}
+$SUDO systemctl stop {unit} 2>/dev/null || true
+$SUDO systemctl reset-failed {unit} 2>/dev/null || true
+$SUDO systemd-run --unit {unit} --description "runctl teardown on training completion" {path}
+echo {marker}
+"#,
+            script = script,
+            path = SCRIPT_PATH,
+            unit = UNIT_NAME,
+            marker = INSTALLED_MARKER,
+        )
+    }
+}
+
+/// Set the shutdown behavior for `action` and tag the instance with it
+pub(crate) async fn mark_instance(
+    client: &Ec2Client,
+    instance_id: &str,
+    action: AutostopAction,
+) -> crate::error::Result<()> {
+    crate::aws::autostop::set_shutdown_behavior(client, instance_id, action).await?;
+    client
+        .create_tags()
+        .resources(instance_id)
+        .tags(
+            Tag::builder()
+                .key(ON_COMPLETE_TAG)
+                .value(action.to_string())
+                .build(),
+        )
+        .send()
+        .await
+        .map_err(|e| crate::error::TrainctlError::Aws(format!("Failed to tag instance: {}", e)))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hook(s3_dest: Option<&str>) -> TeardownHook {
+        TeardownHook {
+            action: AutostopAction::Terminate,
+            project_dir: "/home/ubuntu/demo".to_string(),
+            checkpoint_dir: "/home/ubuntu/demo/checkpoints".to_string(),
+            s3_dest: s3_dest.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_script_waits_uploads_then_shuts_down() {
+        let script = hook(Some("s3://bucket/runctl/checkpoints/demo/i-0abc")).script();
+        assert!(script.starts_with("#!/bin/bash"));
+        assert!(script.contains("DIR='/home/ubuntu/demo'"));
+        assert!(script.contains("DEST='s3://bucket/runctl/checkpoints/demo/i-0abc'"));
+        assert!(script.contains("while kill -0 \"$PID\""));
+        let upload = script.find("aws s3 sync").unwrap();
+        let shutdown = script.find("shutdown -h now").unwrap();
+        assert!(upload < shutdown);
+        assert!(script.contains("leaving the instance running"));
+
+        assert!(hook(None).script().contains("DEST=''"));
+    }
+
+    #[test]
+    fn test_install_command_round_trips_script() {
+        let hook = hook(None);
+        let cmd = hook.install_command();
+        let encoded = cmd
+            .lines()
+            .find_map(|l| l.strip_prefix("echo '"))
+            .and_then(|l| l.split('\'').next())
+            .unwrap();
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .unwrap();
+        assert_eq!(String::from_utf8(decoded).unwrap(), hook.script());
+        assert!(cmd.contains("systemd-run --unit runctl-teardown"));
+        assert!(cmd.trim_end().ends_with(INSTALLED_MARKER));
+    }
+
+    #[test]
+    fn test_default_dest() {
+        assert_eq!(
+            TeardownHook::default_dest("bucket", "demo", "i-0abc"),
+            "s3://bucket/runctl/checkpoints/demo/i-0abc"
+        );
+    }
+}
@@ -1,20 +1,34 @@
 //! Training operations on EC2 instances
 //!
-//! Handles starting training jobs, syncing code, and monitoring training progress.
+//! Handles starting training jobs and syncing code. Reading the log is in `monitor`.
 
 // Use fully qualified path for spot_monitor to minimize circular dependency risk
+use crate::aws::completion::{CompletionOutcome, CompletionSpec, HealthChecks};
+use crate::aws::monitor::LOG_PATH_TAG;
+use crate::aws::ssh_key::{learn_host_keys, resolve_key_path};
+use crate::aws::ssh_user::resolve_ssh_user;
 use crate::aws::ssm_sync::sync_code_via_ssm;
+use crate::aws::teardown::{TeardownHook, INSTALLED_MARKER as TEARDOWN_INSTALLED};
 use crate::aws::types::{TrainInstanceOptions, TrainingInfo};
 use crate::aws_utils::execute_ssm_command;
+use crate::checkpoint::resume::{self, ResumeSource};
+use crate::checkpoint::{CheckpointStore, S3CheckpointStore};
 use crate::config::Config;
-use crate::docker::{detect_dockerfile, run_training_in_container};
-use crate::error::{Result, TrainctlError};
+use crate::docker::{detect_dockerfile, IMAGE_READY_MARKER};
+use crate::error::{JobOutcome, Result, TrainctlError};
+use crate::host_keys::HostKeys;
+use crate::ssh_proxy::SshProxy;
+use crate::sync_filter::SyncFilter;
+use crate::utils::shell_quote;
 use aws_sdk_ec2::Client as Ec2Client;
 use aws_sdk_s3::Client as S3Client;
 use aws_sdk_ssm::Client as SsmClient;
 use std::time::Duration;
 use tracing::{info, warn};
 
+/// S3 prefix (under `[aws] s3_bucket`) local checkpoints are staged under for `--resume`
+const RESUME_STAGING_PREFIX: &str = "runctl-resume";
+
 /// Start training on an instance
 pub async fn train_on_instance(
     options: TrainInstanceOptions,
@@ -126,67 +140,34 @@ pub async fn train_on_instance(
         ));
     }
 
-    // Only require public IP and SSH key if not using SSM
-    let (public_ip, key_path) = if !use_ssm_for_sync {
-        let ip = instance.public_ip_address().ok_or_else(|| {
-            TrainctlError::Aws(format!(
-                "Instance {} has no public IP address.\n\n\
-                To resolve:\n\
-                  1. Check if instance is in a public subnet with internet gateway\n\
-                  2. Verify security groups allow SSH (port 22)\n\
-                  3. Check instance state: runctl aws processes {}\n\
-                  4. Use SSM instead: Create instance with --iam-instance-profile and configure s3_bucket in config",
-                options.instance_id, options.instance_id
-            ))
-        })?;
+    let host_keys = HostKeys::for_host(&config.ssh, &options.instance_id);
+    let proxy = SshProxy::from_config(config)?;
 
-        let key_name = instance.key_name();
-        let key = key_name
-            .and_then(|k| {
-                let paths = [
-                    format!("~/.ssh/{}.pem", k),
-                    format!("~/.ssh/{}", k),
-                    "~/.ssh/id_rsa".to_string(),
-                ];
-                paths.iter().find_map(|p| {
-                    let expanded = shellexpand::tilde(p).to_string();
-                    if std::path::Path::new(&expanded).exists() {
-                        Some(expanded)
-                    } else {
-                        None
-                    }
-                })
-            })
-            .ok_or_else(|| {
-                let key_name_str = key_name.unwrap_or("unknown");
-                TrainctlError::Aws(format!(
-                    "Could not find SSH key for key pair '{}'.\n\n\
-                    To resolve:\n\
-                      1. Set SSH_KEY_PATH environment variable: export SSH_KEY_PATH=~/.ssh/{}.pem\n\
-                      2. Place key in standard location: ~/.ssh/{}.pem or ~/.ssh/{}\n\
-                      3. Set correct permissions: chmod 600 ~/.ssh/{}.pem\n\
-                      4. Use SSM instead (recommended):\n\
-                         a. Setup SSM (one-time): ./scripts/setup-ssm-role.sh\n\
-                         b. Create instance with: --iam-instance-profile runctl-ssm-profile\n\
-                         c. Configure S3 bucket in .runctl.toml: [aws] s3_bucket = \"your-bucket\"",
-                    key_name_str, key_name_str, key_name_str, key_name_str, key_name_str
-                ))
-            })?;
+    // Only require an SSH route and key if not using SSM
+    let (ssh_host, key_path) = if !use_ssm_for_sync {
+        let ip = proxy.target(
+            &options.instance_id,
+            crate::aws::public_address(instance),
+            instance.private_ip_address(),
+        )?;
+
+        let key = resolve_key_path(instance.key_name(), &config.ssh)?;
+        if learn_host_keys(&ec2_client, &ssm_client, instance, &host_keys)
+            .await
+            .is_none()
+        {
+            host_keys.require_known()?;
+        }
         (Some(ip), Some(key))
     } else {
-        (instance.public_ip_address(), None)
+        (
+            crate::aws::public_address(instance).map(str::to_string),
+            None,
+        )
     };
 
-    // Determine user based on AMI
-    let user = if instance
-        .image_id()
-        .map(|id| id.contains("ubuntu") || id.contains("Ubuntu"))
-        .unwrap_or(false)
-    {
-        "ubuntu"
-    } else {
-        "ec2-user"
-    };
+    let ssh_user = resolve_ssh_user(&ec2_client, instance, options.ssh_user.as_deref()).await;
+    let user = ssh_user.as_str();
 
     let project_dir = format!("/home/{}/{}", user, options.project_name);
 
@@ -286,6 +267,11 @@ pub async fn train_on_instance(
         if output_format != "json" {
             println!("Syncing code to instance...");
         }
+        let sync_filter = SyncFilter::from_config(&config.sync)?.with_overrides(
+            &options.include_patterns,
+            &options.exclude_patterns,
+            options.max_file_size,
+        );
 
         // Get project root for syncing
         let script_dir = options
@@ -333,6 +319,12 @@ pub async fn train_on_instance(
             }
         };
 
+        let selection = sync_filter.select(&project_root)?;
+        if !sync_filter.confirm(&selection, &project_root, options.yes, output_format)? {
+            println!("Cancelled");
+            return Ok(());
+        }
+
         if use_ssm_for_sync {
             // Use SSM-based sync (via S3)
             if let Err(e) = sync_code_via_ssm(
@@ -340,7 +332,7 @@ pub async fn train_on_instance(
                 &options.instance_id,
                 &project_dir,
                 &options.script,
-                &options.include_patterns,
+                &sync_filter,
                 &s3_client,
                 &ssm_client,
                 config,
@@ -371,18 +363,20 @@ pub async fn train_on_instance(
             let kp = key_path.as_ref().ok_or_else(|| {
                 TrainctlError::Aws("SSH key required for SSH-based code sync".to_string())
             })?;
-            let ip = public_ip.as_ref().ok_or_else(|| {
-                TrainctlError::Aws("Public IP required for SSH-based code sync".to_string())
+            let ip = ssh_host.as_ref().ok_or_else(|| {
+                TrainctlError::Aws("No SSH route for SSH-based code sync".to_string())
             })?;
 
             if let Err(e) = sync_code_to_instance(
                 kp,
                 ip,
                 user,
+                &host_keys,
+                &proxy,
                 &project_dir,
                 &options.script,
                 output_format,
-                &options.include_patterns,
+                &sync_filter,
             )
             .await
             {
@@ -408,6 +402,32 @@ pub async fn train_on_instance(
         }
     }
 
+    // Resume from the newest checkpoint, unless the script args already name one
+    let mut script_args = options.script_args.clone();
+    if let Some(flag) = &options.resume {
+        let given = script_args
+            .iter()
+            .any(|arg| arg == flag || arg.starts_with(&format!("{}=", flag)));
+        if given {
+            if output_format != "json" {
+                println!("{} already given; not looking for a checkpoint", flag);
+            }
+        } else if let Some(path) = prepare_resume(
+            &options,
+            config,
+            aws_config,
+            &ssm_client,
+            &project_dir,
+            use_ssm_for_sync,
+            output_format,
+        )
+        .await?
+        {
+            script_args.push(flag.clone());
+            script_args.push(path);
+        }
+    }
+
     // Build training command
     // Calculate relative path from project root to script (preserve subdirectory structure)
     // We already found project_root during sync, but need to recalculate here for consistency
@@ -494,13 +514,12 @@ pub async fn train_on_instance(
     // Use nohup to run in background and capture output
     // Properly quote/escape script arguments to handle spaces and special characters
     // Save PID to training.pid for process tracking and cleanup
-    let script_args_str = if options.script_args.is_empty() {
+    let script_args_str = if script_args.is_empty() {
         String::new()
     } else {
         // Quote each argument to handle spaces and special characters
         // Use single quotes and escape single quotes within arguments
-        let quoted_args: Vec<String> = options
-            .script_args
+        let quoted_args: Vec<String> = script_args
             .iter()
             .map(|arg| {
                 // Escape single quotes by replacing ' with '\''
@@ -529,104 +548,98 @@ pub async fn train_on_instance(
         project_dir
     );
 
-    // Run setup first (best effort - don't fail if it doesn't work)
-    if use_ssm {
-        if output_format != "json" {
-            println!("   Installing dependencies (this may take a few minutes)...");
-        }
-        if let Err(e) = execute_ssm_command(&ssm_client, &options.instance_id, &setup_cmd).await {
-            warn!("Setup command failed (non-critical): {}", e);
-        }
-    } else if let (Some(kp), Some(ip)) = (key_path.as_ref(), public_ip.as_ref()) {
-        if let Err(e) = execute_via_ssh(kp, ip, user, &setup_cmd).await {
-            warn!("Setup command failed (non-critical): {}", e);
+    // Container runs: resolve the image (building it from the Dockerfile if
+    // none was given) and pull it before starting
+    let image = if options.docker {
+        let image = match &options.docker_image {
+            Some(image) => image.clone(),
+            None => build_project_image(&options, config, aws_config, output_format).await?,
+        };
+        // Pull by digest so the run records exactly which image it used
+        let image = crate::image::resolve_digest(aws_config, &image).await?;
+        if let Err(e) = ec2_client
+            .create_tags()
+            .resources(&options.instance_id)
+            .tags(
+                aws_sdk_ec2::types::Tag::builder()
+                    .key(crate::image::IMAGE_TAG)
+                    .value(&image)
+                    .build(),
+            )
+            .send()
+            .await
+        {
+            warn!(
+                "Failed to tag {} with its image: {}",
+                options.instance_id, e
+            );
         }
-    }
-
-    // Check if Docker training is requested
-    if options.docker {
-        if !use_ssm {
-            return Err(TrainctlError::Aws(
-                "Docker training requires SSM. Use --iam-instance-profile when creating instance."
-                    .to_string(),
-            ));
+        if output_format != "json" {
+            println!("   Pulling {} on the instance...", image);
         }
-
-        // Determine ECR image
-        let ecr_image = if let Some(img) = &options.docker_image {
-            img.clone()
+        let prepare = crate::docker::prepare_image_command(&image);
+        let prepared = if use_ssm {
+            execute_ssm_command(&ssm_client, &options.instance_id, &prepare)
+                .await
+                .and_then(|output| {
+                    if output.contains(IMAGE_READY_MARKER) {
+                        Ok(())
+                    } else {
+                        Err(TrainctlError::Ssm(output.trim().to_string()))
+                    }
+                })
+        } else if let (Some(kp), Some(ip)) = (key_path.as_ref(), ssh_host.as_ref()) {
+            execute_via_ssh(kp, ip, user, &host_keys, &proxy, &prepare).await
         } else {
-            // Auto-detect: build and push from Dockerfile
-            let project_root = std::env::current_dir().map_err(|e| {
-                TrainctlError::Io(std::io::Error::other(format!(
-                    "Failed to get current directory: {}",
-                    e
-                )))
-            })?;
-
-            let _dockerfile = detect_dockerfile(&project_root).ok_or_else(|| {
-                TrainctlError::CloudProvider {
-                    provider: "docker".to_string(),
-                    message: "No Dockerfile found. Use --docker-image to specify ECR image, or create a Dockerfile.".to_string(),
-                    source: None,
-                }
-            })?;
-
-            let aws_cfg = config.aws.as_ref().ok_or_else(|| {
-                TrainctlError::Config(crate::error::ConfigError::MissingField("aws".to_string()))
-            })?;
-
-            let region = aws_cfg.region.as_str();
-            let repository_name = options.project_name.clone();
-            let tag = "latest";
-
+            Err(TrainctlError::Aws(
+                "No SSM or SSH route to the instance".to_string(),
+            ))
+        };
+        prepared.map_err(|e| TrainctlError::CloudProvider {
+            provider: "docker".to_string(),
+            message: format!("Failed to prepare image {}: {}", image, e),
+            source: None,
+        })?;
+        Some(image)
+    } else {
+        // Run setup first (best effort - don't fail if it doesn't work)
+        if use_ssm {
             if output_format != "json" {
-                println!("Building and pushing Docker image to ECR...");
+                println!("   Installing dependencies (this may take a few minutes)...");
+            }
+            if let Err(e) = execute_ssm_command(&ssm_client, &options.instance_id, &setup_cmd).await
+            {
+                warn!("Setup command failed (non-critical): {}", e);
+            }
+        } else if let (Some(kp), Some(ip)) = (key_path.as_ref(), ssh_host.as_ref()) {
+            if let Err(e) = execute_via_ssh(kp, ip, user, &host_keys, &proxy, &setup_cmd).await {
+                warn!("Setup command failed (non-critical): {}", e);
             }
-
-            crate::docker::build_and_push_to_ecr(
-                &project_root,
-                &repository_name,
-                tag,
-                region,
-                aws_config,
-            )
-            .await?
-        };
-
-        // Run training in Docker container
-        run_training_in_container(
-            &options.instance_id,
-            &ecr_image,
-            &options.script,
-            &options.script_args,
-            &project_dir,
-            &ssm_client,
-            Some(&ec2_client),
-        )
-        .await?;
-
-        if output_format == "json" {
-            println!(
-                "{{\"success\": true, \"method\": \"docker\", \"ecr_image\": \"{}\"}}",
-                ecr_image
-            );
-        } else {
-            println!("Training completed in Docker container: {}", ecr_image);
-        }
-
-        // Wait for completion if requested (Docker runs synchronously, so this is a no-op)
-        if options.wait && output_format != "json" {
-            println!("Training completed in Docker container");
         }
+        None
+    };
 
-        return Ok(());
-    }
+    let launcher = options.launcher.as_deref().unwrap_or("python3");
+    let run = match &image {
+        // The project is mounted at /workspace inside the container
+        Some(image) => crate::docker::container_run_command(
+            image,
+            &project_dir,
+            &format!(
+                "{} /workspace/{}{}",
+                launcher,
+                script_relative.display(),
+                script_args_str
+            ),
+            &options.env,
+        ),
+        None => format!("{} {}{}", launcher, script_path, script_args_str),
+    };
 
     let command = format!(
         "cd {} && \
-        export PATH=\"$HOME/.local/bin:$PATH\" && \
-        (nohup python3 {}{} > training.log 2>&1; echo $? > training_exit_code.txt) & \
+        export PATH=\"$HOME/.local/bin:$PATH\" && {}\
+        (nohup {} > training.log 2>&1; echo $? > training_exit_code.txt) & \
         echo $! > training.pid && \
         sleep 2 && \
         if ps -p $(cat training.pid 2>/dev/null) > /dev/null 2>&1; then \
@@ -634,7 +647,9 @@ pub async fn train_on_instance(
         else \
             echo 'WARNING: Training process may have failed - check training.log'; \
         fi",
-        project_dir, script_path, script_args_str
+        project_dir,
+        crate::utils::env_exports(&options.env),
+        run
     );
 
     // use_ssm already determined above for dependency installation
@@ -653,8 +668,8 @@ pub async fn train_on_instance(
                     println!("WARNING: SSM failed: {}, trying SSH...", e);
                 }
                 // Fallback to SSH (if available)
-                if let (Some(kp), Some(ip)) = (&key_path, &public_ip) {
-                    execute_via_ssh(kp, ip, user, &command).await?;
+                if let (Some(kp), Some(ip)) = (&key_path, &ssh_host) {
+                    execute_via_ssh(kp, ip, user, &host_keys, &proxy, &command).await?;
                     TrainingInfo {
                         success: true,
                         method: "ssh".to_string(),
@@ -680,11 +695,11 @@ pub async fn train_on_instance(
         let kp = key_path.as_ref().ok_or_else(|| {
             TrainctlError::Aws("SSH key required when SSM is not available".to_string())
         })?;
-        let ip = public_ip
+        let ip = ssh_host
             .as_ref()
-            .ok_or_else(|| TrainctlError::Aws("Public IP required for SSH".to_string()))?;
+            .ok_or_else(|| TrainctlError::Aws("No SSH route to the instance".to_string()))?;
 
-        execute_via_ssh(kp, ip, user, &command).await?;
+        execute_via_ssh(kp, ip, user, &host_keys, &proxy, &command).await?;
         TrainingInfo {
             success: true,
             method: "ssh".to_string(),
@@ -694,17 +709,136 @@ pub async fn train_on_instance(
         }
     };
 
+    // Lets `monitor` find the log without knowing the project name
+    if let Err(e) = ec2_client
+        .create_tags()
+        .resources(&options.instance_id)
+        .tags(
+            aws_sdk_ec2::types::Tag::builder()
+                .key(LOG_PATH_TAG)
+                .value(&training_info.log_path)
+                .build(),
+        )
+        .send()
+        .await
+    {
+        warn!(
+            "Failed to tag {} with its log path: {}",
+            options.instance_id, e
+        );
+    }
+
+    if let Some(action) = options.on_complete {
+        let hook = TeardownHook {
+            action,
+            project_dir: project_dir.clone(),
+            checkpoint_dir: if config.checkpoint.dir.is_absolute() {
+                config.checkpoint.dir.display().to_string()
+            } else {
+                format!("{}/{}", project_dir, config.checkpoint.dir.display())
+            },
+            // Uploading needs the instance profile's credentials
+            s3_dest: options
+                .output_s3
+                .clone()
+                .or_else(|| {
+                    config
+                        .aws
+                        .as_ref()
+                        .and_then(|c| c.s3_bucket.as_deref())
+                        .map(|bucket| {
+                            TeardownHook::default_dest(
+                                bucket,
+                                &options.project_name,
+                                &options.instance_id,
+                            )
+                        })
+                })
+                .filter(|_| has_iam_profile),
+        };
+        install_teardown_hook(
+            &hook,
+            &ec2_client,
+            &ssm_client,
+            &options.instance_id,
+            use_ssm,
+            key_path.as_deref().zip(ssh_host.as_deref()),
+            user,
+            &host_keys,
+            &proxy,
+        )
+        .await?;
+        if output_format != "json" {
+            match &hook.s3_dest {
+                Some(dest) => println!(
+                    "   When training exits: upload checkpoints to {}, then {} the instance",
+                    dest, action
+                ),
+                None => println!(
+                    "   When training exits: {} the instance (no instance profile or S3 bucket, so checkpoints are not uploaded)",
+                    action
+                ),
+            }
+        }
+    }
+
+    // The agent reports to S3 with the instance profile's credentials
+    let agent_started = if options.agent && use_ssm {
+        let s3_bucket = config.aws.as_ref().and_then(|c| c.s3_bucket.as_deref());
+        match crate::agent::start_via_ssm(
+            &ssm_client,
+            &options.instance_id,
+            &project_dir,
+            s3_bucket,
+        )
+        .await
+        {
+            Ok(true) => true,
+            Ok(false) => {
+                warn!(
+                    "--agent: runctl is not installed on {} (PATH or ~/.runctl/bin/runctl); \
+                     install it in [bootstrap] post_hooks or the AMI",
+                    options.instance_id
+                );
+                false
+            }
+            Err(e) => {
+                warn!("Failed to start the agent: {}", e);
+                false
+            }
+        }
+    } else {
+        if options.agent {
+            warn!("--agent needs SSM; create the instance with --iam-instance-profile");
+        }
+        false
+    };
+
+    crate::events::record(
+        crate::events::Event::new(
+            &options.instance_id,
+            crate::events::EventKind::TrainingStarted,
+        )
+        .detail(options.script.display()),
+    );
+
     if output_format == "json" {
         println!("{}", serde_json::to_string_pretty(&training_info)?);
     } else {
         println!("Training started");
-        if let (Some(kp), Some(ip)) = (key_path, public_ip) {
+        if let (Some(kp), Some(ip), true) = (key_path, ssh_host, proxy.is_direct()) {
             println!(
                 "   Monitor: ssh -i {} {}@{} 'tail -f {}/training.log'",
                 kp, user, ip, project_dir
             );
         }
         println!("   Or: runctl aws monitor {}", options.instance_id);
+        if agent_started {
+            println!(
+                "   Agent started: runctl aws status {}",
+                options.instance_id
+            );
+        }
     }
 
     // Automatically start spot monitoring if instance is a spot instance
@@ -770,6 +904,7 @@ pub async fn train_on_instance(
                 &project_dir,
                 output_format,
                 options.timeout_minutes,
+                &options.job,
             )
             .await?;
         } else {
@@ -785,14 +920,119 @@ pub async fn train_on_instance(
 /// Sync code to instance using native Rust SSH and tar
 ///
 /// Uses incremental sync if code already exists, full sync otherwise.
+/// Find the newest checkpoint for `aws train --resume` and put it on the
+/// instance if it isn't there yet
+///
+/// Returns the path to pass to the script, relative to the project
+/// directory, or None to start from scratch.
+async fn prepare_resume(
+    options: &TrainInstanceOptions,
+    config: &Config,
+    aws_config: &aws_config::SdkConfig,
+    ssm_client: &SsmClient,
+    project_dir: &str,
+    use_ssm: bool,
+    output_format: &str,
+) -> Result<Option<String>> {
+    if !use_ssm {
+        return Err(TrainctlError::Validation {
+            field: "resume".to_string(),
+            reason: "--resume copies checkpoints over SSM: the instance needs an instance profile and [aws] s3_bucket must be set".to_string(),
+        });
+    }
+    let text = output_format != "json";
+    let checkpoint_dir = config.checkpoint.dir.display().to_string();
+    let remote_dir = if config.checkpoint.dir.is_absolute() {
+        checkpoint_dir.clone()
+    } else {
+        format!("{}/{}", project_dir, checkpoint_dir)
+    };
+    if text {
+        println!("Looking for the latest checkpoint...");
+    }
+    let listing = execute_ssm_command(
+        ssm_client,
+        &options.instance_id,
+        &resume::remote_listing_command(&remote_dir),
+    )
+    .await?;
+    let on_instance = resume::parse_remote_listing(&listing, &remote_dir);
+    let Some(found) = resume::find_latest(config, on_instance).await? else {
+        if text {
+            println!("   No checkpoint found; starting from scratch");
+        }
+        return Ok(None);
+    };
+
+    let name = found.checkpoint.name.clone();
+    let uri = match found.source {
+        ResumeSource::Instance => None,
+        ResumeSource::S3 => Some(found.checkpoint.location.clone()),
+        ResumeSource::Local => {
+            // Staged in S3, like code sync
+            let bucket = config
+                .aws
+                .as_ref()
+                .and_then(|aws| aws.s3_bucket.as_deref())
+                .unwrap_or_default();
+            let staging = S3CheckpointStore::new(
+                S3Client::new(aws_config),
+                &format!(
+                    "s3://{}/{}/{}/",
+                    bucket, RESUME_STAGING_PREFIX, options.instance_id
+                ),
+            )?;
+            if text {
+                println!("   Uploading {}...", found.checkpoint.location);
+            }
+            let staged = staging
+                .put(std::path::Path::new(&found.checkpoint.location), None)
+                .await?;
+            Some(staged.location)
+        }
+    };
+    if let Some(uri) = &uri {
+        if text {
+            println!("   Copying {} to the instance...", uri);
+        }
+        let cmd = format!(
+            "mkdir -p {dir} && aws s3 cp {uri} {dir}/{name} --only-show-errors",
+            dir = shell_quote(&remote_dir),
+            uri = shell_quote(uri),
+            name = shell_quote(&name)
+        );
+        execute_ssm_command(ssm_client, &options.instance_id, &cmd).await?;
+    }
+    if text {
+        println!(
+            "   Resuming from {} ({}{})",
+            name,
+            found.source,
+            found
+                .checkpoint
+                .epoch
+                .map(|epoch| format!(", epoch {}", epoch))
+                .unwrap_or_default()
+        );
+    }
+    // Training runs from the project directory
+    Ok(Some(if config.checkpoint.dir.is_absolute() {
+        format!("{}/{}", remote_dir, name)
+    } else {
+        format!("{}/{}", checkpoint_dir, name)
+    }))
+}
+
 async fn sync_code_to_instance(
     key_path: &str,
     ip: &str,
     user: &str,
+    host_keys: &HostKeys,
+    proxy: &SshProxy,
     project_dir: &str,
     script_path: &std::path::Path,
     output_format: &str,
-    include_patterns: &[String],
+    filter: &SyncFilter,
 ) -> Result<()> {
     // Get project root (parent of script's directory)
     let script_dir = script_path
@@ -849,10 +1089,12 @@ async fn sync_code_to_instance(
         key_path,
         ip,
         user,
+        host_keys,
+        proxy,
         project_dir,
         &project_root,
         output_format,
-        include_patterns,
+        filter,
     )
     .await
     .map_err(|e| {
@@ -869,13 +1111,100 @@ async fn sync_code_to_instance(
     })
 }
 
+/// Start the teardown hook on the instance and set its shutdown behavior
+///
+/// Training is already running, so a failure here is reported rather than
+/// leaving an instance that was meant to shut itself down billing silently.
+#[allow(clippy::too_many_arguments)]
+async fn install_teardown_hook(
+    hook: &TeardownHook,
+    ec2_client: &Ec2Client,
+    ssm_client: &SsmClient,
+    instance_id: &str,
+    use_ssm: bool,
+    ssh: Option<(&str, &str)>,
+    user: &str,
+    host_keys: &HostKeys,
+    proxy: &SshProxy,
+) -> Result<()> {
+    crate::aws::teardown::mark_instance(ec2_client, instance_id, hook.action).await?;
+    let command = hook.install_command();
+    let installed = if use_ssm {
+        execute_ssm_command(ssm_client, instance_id, &command)
+            .await
+            .and_then(|output| {
+                if output.contains(TEARDOWN_INSTALLED) {
+                    Ok(())
+                } else {
+                    Err(TrainctlError::Ssm(output.trim().to_string()))
+                }
+            })
+    } else if let Some((key_path, ip)) = ssh {
+        execute_via_ssh(key_path, ip, user, host_keys, proxy, &command).await
+    } else {
+        Err(TrainctlError::Aws("no SSM or SSH route".to_string()))
+    };
+    installed.map_err(|e| {
+        TrainctlError::Aws(format!(
+            "Training is running on {}, but the --{}-on-complete hook could not be installed: {}\n\
+             Stop or terminate the instance yourself when training finishes.",
+            instance_id, hook.action, e
+        ))
+    })
+}
+
+/// Build the project's Dockerfile and push it to ECR as `<project>:latest`
+async fn build_project_image(
+    options: &TrainInstanceOptions,
+    config: &Config,
+    aws_config: &aws_config::SdkConfig,
+    output_format: &str,
+) -> Result<String> {
+    let project_root = std::env::current_dir().map_err(|e| {
+        TrainctlError::Io(std::io::Error::other(format!(
+            "Failed to get current directory: {}",
+            e
+        )))
+    })?;
+    if detect_dockerfile(&project_root).is_none() {
+        return Err(TrainctlError::CloudProvider {
+            provider: "docker".to_string(),
+            message:
+                "No Dockerfile found. Use --image to run an existing image, or create a Dockerfile."
+                    .to_string(),
+            source: None,
+        });
+    }
+    let aws_cfg = config.aws.as_ref().ok_or_else(|| {
+        TrainctlError::Config(crate::error::ConfigError::MissingField("aws".to_string()))
+    })?;
+    if output_format != "json" {
+        println!("Building and pushing Docker image to ECR...");
+    }
+    crate::docker::build_and_push_to_ecr(
+        &project_root,
+        &options.project_name,
+        "latest",
+        &aws_cfg.region,
+        aws_config,
+    )
+    .await
+}
+
 /// Execute command via SSH
-async fn execute_via_ssh(key_path: &str, ip: &str, user: &str, command: &str) -> Result<()> {
+async fn execute_via_ssh(
+    key_path: &str,
+    ip: &str,
+    user: &str,
+    host_keys: &HostKeys,
+    proxy: &SshProxy,
+    command: &str,
+) -> Result<()> {
     use std::process::Command;
 
     let mut cmd = Command::new("ssh");
-    cmd.arg("-o")
-        .arg("StrictHostKeyChecking=no")
+    cmd.args(host_keys.ssh_args())
+        .args(proxy.ssh_args())
         .arg("-o")
         .arg("ConnectTimeout=10")
         .arg("-i")
@@ -897,133 +1226,41 @@ async fn execute_via_ssh(key_path: &str, ip: &str, user: &str, command: &str) ->
     }
 
     if !output.stdout.is_empty() {
-        print!("{}", String::from_utf8_lossy(&output.stdout));
+        print!(
+            "{}",
+            crate::output_style::sanitize(&String::from_utf8_lossy(&output.stdout))
+        );
     }
 
     Ok(())
 }
 
-/// Monitor training progress on an instance
-pub async fn monitor_instance(
-    instance_id: String,
-    follow: bool,
-    aws_config: &aws_config::SdkConfig,
-    output_format: &str,
-) -> Result<()> {
-    let ssm_client = SsmClient::new(aws_config);
-
-    // Get instance details to determine user and project directory
-    let ec2_client = Ec2Client::new(aws_config);
-    let instance_response = ec2_client
-        .describe_instances()
-        .instance_ids(&instance_id)
-        .send()
+/// Exit status written by the training wrapper, once the process has exited
+async fn read_exit_code(
+    ssm_client: &SsmClient,
+    instance_id: &str,
+    project_dir: &str,
+) -> Option<i32> {
+    let cmd = format!(
+        "if [ -f {dir}/training_exit_code.txt ]; then cat {dir}/training_exit_code.txt; fi",
+        dir = project_dir
+    );
+    crate::aws_utils::execute_ssm_command(ssm_client, instance_id, &cmd)
         .await
-        .map_err(|e| TrainctlError::Aws(format!("Failed to describe instance: {}", e)))?;
-
-    let instance = crate::aws::helpers::find_instance_in_response(&instance_response, &instance_id)
-        .ok_or_else(|| TrainctlError::Aws(format!("Instance {} not found", instance_id)))?;
-
-    let user = if instance
-        .image_id()
-        .map(|id| id.contains("ubuntu") || id.contains("Ubuntu"))
-        .unwrap_or(false)
-    {
-        "ubuntu"
-    } else {
-        "ec2-user"
-    };
-
-    // Try to detect project name from instance tags
-    let project_name = instance
-        .tags()
-        .iter()
-        .find(|t| t.key().map(|k| k == "Project").unwrap_or(false))
-        .and_then(|t| t.value())
-        .unwrap_or("runctl");
-
-    let project_dir = format!("/home/{}/{}", user, project_name);
-    let log_path = format!("{}/training.log", project_dir);
-
-    if follow {
-        // Poll log file periodically
-        if output_format != "json" {
-            println!("Monitoring training log: {} (following)", log_path);
-            println!("Press Ctrl+C to stop");
-        }
-
-        let mut last_size = 0u64;
-        loop {
-            let cmd = format!(
-                "tail -c +{} {} 2>/dev/null || echo ''",
-                last_size + 1,
-                log_path
-            );
-
-            match execute_ssm_command(&ssm_client, &instance_id, &cmd).await {
-                Ok(output) => {
-                    if !output.trim().is_empty() {
-                        if output_format == "json" {
-                            let json = serde_json::json!({
-                                "instance_id": instance_id,
-                                "log_path": log_path,
-                                "output": output
-                            });
-                            println!("{}", serde_json::to_string(&json)?);
-                        } else {
-                            print!("{}", output);
-                            use std::io::Write;
-                            std::io::stdout().flush().ok();
-                        }
-                        last_size += output.len() as u64;
-                    }
-                }
-                Err(e) => {
-                    if output_format != "json" {
-                        eprintln!("Error reading log: {}", e);
-                    } else {
-                        return Err(e);
-                    }
-                }
-            }
-
-            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-        }
-    } else {
-        // Show recent log output
-        if output_format != "json" {
-            println!("Recent training log from: {}", log_path);
-        }
-        let cmd = format!(
-            "tail -50 {} 2>/dev/null || echo 'Log file not found or empty'",
-            log_path
-        );
+        .ok()
+        .and_then(|output| output.trim().parse().ok())
+}
 
-        match execute_ssm_command(&ssm_client, &instance_id, &cmd).await {
-            Ok(output) => {
-                if output_format == "json" {
-                    let json = serde_json::json!({
-                        "instance_id": instance_id,
-                        "log_path": log_path,
-                        "output": output
-                    });
-                    println!("{}", serde_json::to_string_pretty(&json)?);
-                } else {
-                    println!("{}", output);
-                }
-            }
-            Err(e) => {
-                if output_format != "json" {
-                    println!("Could not read log: {}", e);
-                    println!("Use AWS Console or SSM Session Manager to view logs");
-                } else {
-                    return Err(e);
-                }
-            }
-        }
+/// Completed, or failed if the wrapper recorded a non-zero exit status
+async fn finished_outcome(
+    ssm_client: &SsmClient,
+    instance_id: &str,
+    project_dir: &str,
+) -> CompletionOutcome {
+    match read_exit_code(ssm_client, instance_id, project_dir).await {
+        Some(code) if code != 0 => CompletionOutcome::Failed(format!("exit code {}", code)),
+        _ => CompletionOutcome::Completed("built-in heuristics".to_string()),
     }
-
-    Ok(())
 }
 
 /// Check if training has completed
@@ -1032,13 +1269,12 @@ pub async fn monitor_instance(
 /// 1. Check for training_complete.txt marker
 /// 2. Check if training process (PID) is still running
 /// 3. Check training.log for completion indicators
-/// 4. Check exit code if available
-/// 5. Verify checkpoints were created (optional validation)
+/// 4. Check exit code if available (non-zero = failed)
 async fn check_training_completion(
     ssm_client: &SsmClient,
     instance_id: &str,
     project_dir: &str,
-) -> Result<bool> {
+) -> Result<CompletionOutcome> {
     // Method 1: Check for training_complete.txt marker
     // Use atomic check: verify file exists AND is readable (not being written)
     // Also check file size > 0 to avoid false positives from empty files
@@ -1083,31 +1319,12 @@ async fn check_training_completion(
                 {
                     if stable_output.trim() == "UNSTABLE" {
                         warn!("Marker file exists but was recently modified, waiting for stability...");
-                        // Return false to continue checking - file might still be written
-                        return Ok(false);
+                        // Keep checking - file might still be written
+                        return Ok(CompletionOutcome::Running);
                     }
                 }
 
-                // Also check exit code if available
-                let exit_code_cmd = format!(
-                    "if [ -f {}/training_exit_code.txt ]; then cat {}/training_exit_code.txt; else echo '0'; fi",
-                    project_dir, project_dir
-                );
-                if let Ok(exit_code_str) =
-                    crate::aws_utils::execute_ssm_command(ssm_client, instance_id, &exit_code_cmd)
-                        .await
-                {
-                    if let Ok(exit_code) = exit_code_str.trim().parse::<i32>() {
-                        if exit_code != 0 {
-                            warn!(
-                                "Training completed but exit code is {} (non-zero)",
-                                exit_code
-                            );
-                            // Still return true - training completed, but may have failed
-                        }
-                    }
-                }
-                return Ok(true);
+                return Ok(finished_outcome(ssm_client, instance_id, project_dir).await);
             }
         }
         Err(_) => {
@@ -1134,7 +1351,7 @@ async fn check_training_completion(
         Ok(output) => {
             if output.trim() == "COMPLETE" {
                 info!("Training process completed (PID file indicates process finished)");
-                return Ok(true);
+                return Ok(finished_outcome(ssm_client, instance_id, project_dir).await);
             } else if output.trim() == "NO_PID" {
                 // No PID file - check training.log for completion indicators
                 let check_log_cmd = format!(
@@ -1156,7 +1373,7 @@ async fn check_training_completion(
                     Ok(log_output) => {
                         if log_output.trim() == "COMPLETE" {
                             info!("Training completion detected in log file");
-                            return Ok(true);
+                            return Ok(finished_outcome(ssm_client, instance_id, project_dir).await);
                         }
                     }
                     Err(_) => {
@@ -1170,16 +1387,20 @@ async fn check_training_completion(
         }
     }
 
-    Ok(false)
+    Ok(CompletionOutcome::Running)
 }
 
 /// Wait for training to complete
+///
+/// Uses the job's configured completion methods when present, otherwise the
+/// built-in heuristics, and runs the job's health checks as they come due.
 async fn wait_for_training_completion(
     ssm_client: &SsmClient,
     instance_id: &str,
     project_dir: &str,
     output_format: &str,
     timeout_minutes: u64,
+    job: &crate::config::JobConfig,
 ) -> Result<()> {
     use serde_json::json;
     use std::time::Duration;
@@ -1204,27 +1425,58 @@ async fn wait_for_training_completion(
         u64::MAX / check_interval.as_secs() // Effectively no timeout, but prevent overflow
     };
     let max_timeout_minutes = timeout_minutes;
+    let completion = CompletionSpec::from_config(&job.completion)?;
+    let mut health_checks = HealthChecks::new(&job.health_checks);
 
     loop {
         sleep(check_interval).await;
         check_count += 1;
 
-        match check_training_completion(ssm_client, instance_id, project_dir).await {
-            Ok(true) => {
+        let outcome = match &completion {
+            Some(spec) => spec.check(ssm_client, instance_id, project_dir).await,
+            None => check_training_completion(ssm_client, instance_id, project_dir).await,
+        };
+
+        match outcome {
+            Ok(CompletionOutcome::Completed(reason)) => {
                 if output_format == "json" {
                     let result = json!({
                         "success": true,
                         "instance_id": instance_id,
-                        "message": "Training completed successfully"
+                        "message": "Training completed successfully",
+                        "detected_by": reason
                     });
                     println!("{}", serde_json::to_string_pretty(&result)?);
                 } else {
-                    println!("Training completed successfully");
+                    println!("Training completed successfully ({})", reason);
                 }
+                crate::progress::ProgressEvent::new(
+                    "wait.done",
+                    format!("Training completed ({})", reason),
+                )
+                .done()
+                .emit();
                 return Ok(());
             }
-            Ok(false) => {
+            Ok(CompletionOutcome::Failed(reason)) => {
+                return Err(TrainctlError::Job {
+                    job_id: instance_id.to_string(),
+                    outcome: JobOutcome::Failed,
+                    message: format!("{}. Check logs: runctl aws monitor {}", reason, instance_id),
+                });
+            }
+            Ok(CompletionOutcome::Running) => {
                 // Still running
+                if check_count % 5 == 0 {
+                    crate::progress::emit(
+                        "wait.running",
+                        format!(
+                            "Training running on {} ({}s elapsed)",
+                            instance_id,
+                            check_count * check_interval.as_secs()
+                        ),
+                    );
+                }
                 if check_count % 30 == 0 && output_format != "json" {
                     // Print status every minute (30 checks * 2 seconds = 60 seconds)
                     let elapsed_minutes = (check_count * check_interval.as_secs()) / 60;
@@ -1240,18 +1492,47 @@ async fn wait_for_training_completion(
             }
         }
 
+        if !health_checks.is_empty() {
+            for result in health_checks
+                .run_due(ssm_client, instance_id, project_dir)
+                .await
+            {
+                if result.passed {
+                    info!("Health check '{}' passed", result.name);
+                    continue;
+                }
+                if output_format != "json" {
+                    println!(
+                        "WARNING: Health check '{}' failed ({}/{}): {}",
+                        result.name,
+                        result.consecutive_failures,
+                        result.max_failures,
+                        result.detail
+                    );
+                }
+                if result.exhausted() {
+                    return Err(TrainctlError::Job {
+                        job_id: instance_id.to_string(),
+                        outcome: JobOutcome::Unhealthy,
+                        message: format!(
+                            "Health check '{}' failed {} times in a row: {}. Training may still be running; check: runctl aws monitor {}",
+                            result.name, result.consecutive_failures, result.detail, instance_id
+                        ),
+                    });
+                }
+            }
+        }
+
         if check_count >= max_checks {
-            return Err(TrainctlError::Resource {
-                resource_type: "training".to_string(),
-                operation: "wait_for_completion".to_string(),
-                resource_id: Some(instance_id.to_string()),
+            return Err(TrainctlError::Job {
+                job_id: instance_id.to_string(),
+                outcome: JobOutcome::TimedOut,
                 message: format!(
                     "Training did not complete within {} minutes ({} hours). Check manually: runctl aws monitor {}",
                     max_timeout_minutes,
                     max_timeout_minutes / 60,
                     instance_id
                 ),
-                source: None,
             });
         }
     }
@@ -0,0 +1,428 @@
+//! Browser tools over a tunnel: `runctl aws tensorboard` and `runctl aws jupyter`
+//!
+//! Starts the server on the instance in the project directory `train` used,
+//! bound to 127.0.0.1 so it is never reachable from outside, forwards a local
+//! port to it and opens the browser. The connection is resolved the way
+//! `train` and `monitor` resolve it: SSM port forwarding when the instance has
+//! an instance profile, otherwise `ssh -L` with the same user, key, host keys
+//! and `[ssh] bastion` route. A server already listening on the port is
+//! reused, so rerunning the command just reconnects.
+
+use crate::aws::adopt::describe;
+use crate::aws::helpers::get_project_name;
+use crate::aws::monitor::{log_path_from_tags, Transport};
+use crate::aws::ssh_user::resolve_ssh_user;
+use crate::config::Config;
+use crate::error::{Result, TrainctlError};
+use crate::utils::shell_quote;
+use aws_sdk_ec2::Client as Ec2Client;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// How long to wait for the local end of the tunnel to accept connections
+const TUNNEL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Token file for Jupyter, in the project directory so reruns reuse it
+const JUPYTER_TOKEN_FILE: &str = ".runctl-jupyter-token";
+
+/// Server to run on the instance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Tool {
+    Tensorboard,
+    Jupyter,
+}
+
+impl Tool {
+    pub fn default_port(self) -> u16 {
+        match self {
+            Self::Tensorboard => 6006,
+            Self::Jupyter => 8888,
+        }
+    }
+
+    fn display_name(self) -> &'static str {
+        match self {
+            Self::Tensorboard => "TensorBoard",
+            Self::Jupyter => "Jupyter",
+        }
+    }
+
+    fn command(self) -> &'static str {
+        match self {
+            Self::Tensorboard => "tensorboard",
+            Self::Jupyter => "jupyter",
+        }
+    }
+}
+
+/// Options shared by `aws tensorboard` and `aws jupyter`
+pub(crate) struct TunnelOptions {
+    pub instance_id: String,
+    pub tool: Tool,
+    /// TensorBoard log directory, relative to the project directory
+    pub logdir: String,
+    pub remote_port: u16,
+    pub local_port: Option<u16>,
+    pub open_browser: bool,
+    pub ssh_user: Option<String>,
+}
+
+/// What the start script reported
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct ServerStart {
+    /// False when a server was already listening on the port
+    pub started: bool,
+    pub listening: bool,
+    pub not_installed: bool,
+    pub token: Option<String>,
+}
+
+/// Shell script that starts `tool` on 127.0.0.1:`port` unless something already listens there
+pub(crate) fn start_script(tool: Tool, project_dir: &str, port: u16, logdir: &str) -> String {
+    let listening = format!("(exec 3<>/dev/tcp/127.0.0.1/{}) 2>/dev/null", port);
+    let (setup, launch) = match tool {
+        Tool::Tensorboard => (
+            String::new(),
+            format!(
+                "tensorboard --logdir {} --host 127.0.0.1 --port {}",
+                shell_quote(logdir),
+                port
+            ),
+        ),
+        Tool::Jupyter => (
+            format!(
+                "[ -s {f} ] || head -c 16 /dev/urandom | od -An -tx1 | tr -d ' \\n' > {f}\n\
+                 TOKEN=$(cat {f}); echo token=$TOKEN\n",
+                f = JUPYTER_TOKEN_FILE
+            ),
+            format!(
+                "jupyter lab --no-browser --ip 127.0.0.1 --port {} --ServerApp.token=$TOKEN",
+                port
+            ),
+        ),
+    };
+    format!(
+        "cd {dir} 2>/dev/null || cd ~\n\
+         export PATH=\"$HOME/.local/bin:$PATH\"\n\
+         {setup}\
+         if {listening}; then echo started=false; else\n\
+         \x20 command -v {cmd} >/dev/null 2>&1 || {{ echo not_installed=true; exit 0; }}\n\
+         \x20 nohup {launch} > {cmd}.log 2>&1 < /dev/null &\n\
+         \x20 for i in $(seq 1 30); do {listening} && break; sleep 1; done\n\
+         \x20 echo started=true\n\
+         fi\n\
+         {listening} && echo listening=true\n\
+         true",
+        dir = shell_quote(project_dir),
+        cmd = tool.command(),
+    )
+}
+
+/// Parse the output of `start_script`; unknown lines are ignored
+pub(crate) fn parse_start(output: &str) -> ServerStart {
+    let mut start = ServerStart::default();
+    for (key, value) in output
+        .lines()
+        .filter_map(|line| line.trim().split_once('='))
+    {
+        match key {
+            "started" => start.started = value == "true",
+            "listening" => start.listening = value == "true",
+            "not_installed" => start.not_installed = value == "true",
+            "token" if !value.is_empty() => start.token = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    start
+}
+
+/// Browser URL for the local end of the tunnel
+pub(crate) fn local_url(local_port: u16, token: Option<&str>) -> String {
+    match token {
+        Some(token) => format!("http://localhost:{}/lab?token={}", local_port, token),
+        None => format!("http://localhost:{}/", local_port),
+    }
+}
+
+/// Local forwarding process for `transport`
+fn tunnel_command(
+    transport: &Transport,
+    instance_id: &str,
+    remote: u16,
+    local: u16,
+    region: Option<&str>,
+) -> tokio::process::Command {
+    match transport {
+        Transport::Ssm(_) => {
+            let mut cmd = tokio::process::Command::new("aws");
+            cmd.args(crate::aws::session::forward_args(
+                instance_id,
+                remote,
+                local,
+                region,
+            ));
+            cmd
+        }
+        Transport::Ssh {
+            key,
+            host,
+            user,
+            host_keys,
+            proxy,
+        } => {
+            let mut cmd = tokio::process::Command::new("ssh");
+            cmd.args(host_keys.ssh_args())
+                .args(proxy.ssh_args())
+                .args(["-N", "-o", "ExitOnForwardFailure=yes"])
+                .args(["-o", "ConnectTimeout=10"])
+                .arg("-L")
+                .arg(format!("{}:127.0.0.1:{}", local, remote))
+                .arg("-i")
+                .arg(key)
+                .arg(format!("{}@{}", user, host));
+            cmd
+        }
+    }
+}
+
+/// Open `url` with the desktop's default browser; failures are only logged
+fn open_in_browser(url: &str) {
+    let mut cmd = if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else if cfg!(windows) {
+        let mut cmd = std::process::Command::new("cmd");
+        cmd.args(["/C", "start", ""]);
+        cmd
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    if let Err(e) = cmd
+        .arg(url)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+    {
+        tracing::debug!("Failed to open a browser: {}", e);
+    }
+}
+
+/// Start the tool on the instance and forward a local port to it until interrupted
+pub(crate) async fn open_tunnel(
+    options: TunnelOptions,
+    config: &Config,
+    aws_config: &aws_config::SdkConfig,
+    output_format: &str,
+) -> Result<()> {
+    let instance_id = options.instance_id.as_str();
+    let tool = options.tool;
+    let client = Ec2Client::new(aws_config);
+    let instance = describe(&client, instance_id).await?;
+    let state = instance
+        .state()
+        .and_then(|s| s.name())
+        .map(|s| s.as_str().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    if state != "running" {
+        return Err(TrainctlError::Resource {
+            resource_type: "instance".to_string(),
+            operation: tool.command().to_string(),
+            resource_id: Some(instance_id.to_string()),
+            message: format!(
+                "Instance is {}; start it with: runctl aws start {} --wait",
+                state, instance_id
+            ),
+            source: None,
+        });
+    }
+
+    let user = resolve_ssh_user(&client, &instance, options.ssh_user.as_deref()).await;
+    let tags: Vec<(String, String)> = instance
+        .tags()
+        .iter()
+        .filter_map(|t| Some((t.key()?.to_string(), t.value()?.to_string())))
+        .collect();
+    let log_path = log_path_from_tags(&tags, &user, &get_project_name(None, config));
+    let project_dir = log_path
+        .rsplit_once('/')
+        .map(|(dir, _)| dir.to_string())
+        .unwrap_or_default();
+
+    let transport = Transport::connect(&client, &instance, &user, config, aws_config).await?;
+    if matches!(transport, Transport::Ssm(_)) {
+        crate::aws::session::preflight(instance_id, aws_config).await?;
+    }
+
+    let text = output_format != "json";
+    if text {
+        println!(
+            "Starting {} on {} (port {})...",
+            tool.display_name(),
+            instance_id,
+            options.remote_port
+        );
+    }
+    let script = start_script(tool, &project_dir, options.remote_port, &options.logdir);
+    // SSM runs scripts with sh, which lacks /dev/tcp
+    let command = format!("bash -c {}", shell_quote(&script));
+    let server = parse_start(&transport.run(instance_id, &command).await?);
+    if server.not_installed {
+        return Err(TrainctlError::Validation {
+            field: tool.command().to_string(),
+            reason: format!(
+                "`{}` is not installed on {}; add it to requirements.txt and rerun `runctl aws train`",
+                tool.command(),
+                instance_id
+            ),
+        });
+    }
+    if !server.listening {
+        return Err(TrainctlError::CloudProvider {
+            provider: "aws".to_string(),
+            message: format!(
+                "{} did not start listening on port {}; see {}/{}.log on the instance",
+                tool.display_name(),
+                options.remote_port,
+                project_dir,
+                tool.command()
+            ),
+            source: None,
+        });
+    }
+
+    let local = options.local_port.unwrap_or(options.remote_port);
+    let region = aws_config.region().map(|r| r.to_string());
+    let mut child = tunnel_command(
+        &transport,
+        instance_id,
+        options.remote_port,
+        local,
+        region.as_deref(),
+    )
+    .stdout(std::process::Stdio::null())
+    .kill_on_drop(true)
+    .spawn()
+    .map_err(|e| TrainctlError::Aws(format!("Failed to start the tunnel: {}", e)))?;
+
+    // The tunnel is up once its local end accepts connections
+    let started = Instant::now();
+    loop {
+        if tokio::net::TcpStream::connect(("127.0.0.1", local))
+            .await
+            .is_ok()
+        {
+            break;
+        }
+        if let Some(status) = child.try_wait()? {
+            return Err(TrainctlError::CloudProvider {
+                provider: "aws".to_string(),
+                message: format!(
+                    "{} tunnel exited with {} before port {} was forwarded",
+                    transport.name(),
+                    status,
+                    local
+                ),
+                source: None,
+            });
+        }
+        if started.elapsed() > TUNNEL_TIMEOUT {
+            return Err(TrainctlError::CloudProvider {
+                provider: "aws".to_string(),
+                message: format!(
+                    "Timed out forwarding localhost:{} to {}:{}",
+                    local, instance_id, options.remote_port
+                ),
+                source: None,
+            });
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    let url = local_url(local, server.token.as_deref());
+    if text {
+        println!(
+            "{} {} on {}, forwarded over {}",
+            tool.display_name(),
+            if server.started {
+                "started"
+            } else {
+                "already running"
+            },
+            instance_id,
+            transport.name()
+        );
+        println!("   {}", url);
+        println!("   Ctrl-C to close the tunnel; the server keeps running on the instance");
+    } else {
+        println!(
+            "{}",
+            serde_json::json!({
+                "tool": tool,
+                "instance_id": instance_id,
+                "transport": transport.name(),
+                "started": server.started,
+                "local_port": local,
+                "remote_port": options.remote_port,
+                "url": url,
+            })
+        );
+    }
+    if options.open_browser {
+        open_in_browser(&url);
+    }
+
+    tokio::select! {
+        status = child.wait() => {
+            let status = status?;
+            if !status.success() {
+                return Err(TrainctlError::CloudProvider {
+                    provider: "aws".to_string(),
+                    message: format!("{} tunnel exited with {}", transport.name(), status),
+                    source: None,
+                });
+            }
+        }
+        _ = tokio::signal::ctrl_c() => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tensorboard_script() {
+        let script = start_script(Tool::Tensorboard, "/home/ubuntu/my proj", 6006, "runs/");
+        assert!(script.starts_with("cd '/home/ubuntu/my proj'"));
+        assert!(script.contains("tensorboard --logdir 'runs/' --host 127.0.0.1 --port 6006"));
+        assert!(script.contains("/dev/tcp/127.0.0.1/6006"));
+        assert!(!script.contains("token"));
+    }
+
+    #[test]
+    fn test_jupyter_script_keeps_token() {
+        let script = start_script(Tool::Jupyter, "/home/ubuntu/proj", 8888, "runs");
+        assert!(script.contains(JUPYTER_TOKEN_FILE));
+        assert!(script.contains("--ip 127.0.0.1 --port 8888 --ServerApp.token=$TOKEN"));
+    }
+
+    #[test]
+    fn test_parse_start() {
+        let start = parse_start("token=abc123\nstarted=false\nlistening=true\n");
+        assert!(!start.started);
+        assert!(start.listening);
+        assert_eq!(start.token.as_deref(), Some("abc123"));
+        assert!(parse_start("not_installed=true").not_installed);
+        assert_eq!(parse_start(""), ServerStart::default());
+    }
+
+    #[test]
+    fn test_local_url() {
+        assert_eq!(local_url(6006, None), "http://localhost:6006/");
+        assert_eq!(
+            local_url(18888, Some("abc")),
+            "http://localhost:18888/lab?token=abc"
+        );
+    }
+}
@@ -10,6 +10,7 @@ pub struct InstanceInfo {
     pub instance_id: String,
     pub instance_type: String,
     pub public_ip: Option<String>,
+    pub public_ipv6: Option<String>,
     pub private_ip: Option<String>,
     pub state: String,
     pub cost_per_hour: f64,
@@ -137,6 +138,43 @@ pub struct ProcessInfo {
     pub memory_mb: f64,
     pub memory_percent: f64,
     pub runtime: String,
+    pub gpu_memory_mb: Option<u64>,
+    pub gpu_index: Option<usize>,
+    pub gpu_hog: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ProcessTreeResult {
+    pub success: bool,
+    pub instance_id: String,
+    pub timestamp: String,
+    pub process_tree: crate::diagnostics::ProcessTree,
+    pub defunct_workers: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct MonitorOptions {
+    pub instance_id: String,
+    /// Keep printing new output until the training process exits
+    pub follow: bool,
+    /// Lines of existing output to show first
+    pub lines: usize,
+    /// SSH login user when the log is read over SSH
+    pub ssh_user: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcessesOptions {
+    pub instance_id: String,
+    pub detailed: bool,
+    pub watch: bool,
+    pub interval: u64,
+    /// Render the process tree rooted at the training process
+    pub tree: bool,
+    /// Explicit tree root (defaults to the training process)
+    pub root_pid: Option<u32>,
+    /// Record samples to this JSONL file instead of displaying them
+    pub record: Option<std::path::PathBuf>,
 }
 
 #[derive(Debug, Clone)]
@@ -145,6 +183,8 @@ pub struct CreateInstanceOptions {
     pub use_spot: bool,
     pub spot_max_price: Option<String>,
     pub no_fallback: bool,
+    /// Availability zone to launch in (any zone if unset)
+    pub availability_zone: Option<String>,
     pub key_name: Option<String>,
     pub security_group: Option<String>,
     pub ami_id: Option<String>,
@@ -153,6 +193,18 @@ pub struct CreateInstanceOptions {
     pub project_name: String,
     pub iam_instance_profile: Option<String>,
     pub wait: bool,
+    /// Preset the options came from (tagged and part of the launch fingerprint)
+    pub preset: Option<String>,
+    /// Explicit EC2 client token (derived from the launch parameters if unset)
+    pub client_token: Option<String>,
+    /// Skip duplicate detection and derived client tokens
+    pub allow_duplicate: bool,
+    /// Look up the Deep Learning AMI again instead of using the cache
+    pub refresh_ami: bool,
+    /// Framework/CUDA/OS constraints on the Deep Learning AMI
+    pub ami_filter: Option<crate::ami_filter::AmiFilter>,
+    /// SSH login user to tag (detected from the AMI if unset)
+    pub ssh_user: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -161,16 +213,64 @@ pub struct TrainInstanceOptions {
     pub script: std::path::PathBuf,
     #[allow(dead_code)] // Reserved for future S3 data source support
     pub data_s3: Option<String>,
-    #[allow(dead_code)] // Reserved for future S3 output support
+    /// Where `on_complete` uploads checkpoints (default: under `[aws] s3_bucket`)
     pub output_s3: Option<String>,
     pub sync_code: bool,
+    /// Paths to sync even if gitignored, added to `[sync] include`
     pub include_patterns: Vec<String>,
+    /// Patterns never synced, added to `[sync] exclude`
+    pub exclude_patterns: Vec<String>,
+    /// Overrides `[sync] max_file_size` (bytes)
+    pub max_file_size: Option<u64>,
+    /// Skip the sync size confirmation
+    pub yes: bool,
     pub project_name: String,
+    /// SSH login user (from the instance's tag or AMI if unset)
+    pub ssh_user: Option<String>,
     pub script_args: Vec<String>,
+    /// Resume from the latest checkpoint, passing its path after this flag
+    /// (e.g. `--resume-from`); see `checkpoint::resume`
+    pub resume: Option<String>,
+    /// Environment variables exported for the training script
+    pub env: Vec<(String, String)>,
     pub wait: bool,
     pub timeout_minutes: u64,
+    /// Run the script in a container (`docker_image`, or one built from the Dockerfile)
     pub docker: bool,
     pub docker_image: Option<String>,
+    /// Completion detection and health checks used by `wait`
+    pub job: crate::config::JobConfig,
+    /// Start `runctl agent` on the instance next to the job
+    pub agent: bool,
+    /// Command the script is run with instead of `python3` (e.g. a torchrun invocation)
+    pub launcher: Option<String>,
+    /// Stop or terminate the instance once training exits (see `aws::teardown`)
+    pub on_complete: Option<crate::aws::AutostopAction>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrainMultiOptions {
+    pub script: std::path::PathBuf,
+    pub script_args: Vec<String>,
+    pub nodes: u32,
+    pub instance_type: String,
+    pub launcher: crate::aws::cluster::Launcher,
+    /// Processes per node (default: every GPU the node has)
+    pub gpus_per_node: Option<u32>,
+    pub master_port: u16,
+    /// Attach an Elastic Fabric Adapter to every node
+    pub efa: bool,
+    pub key_name: Option<String>,
+    pub security_group: Option<String>,
+    pub iam_instance_profile: Option<String>,
+    pub ami_id: Option<String>,
+    pub root_volume_size: Option<i32>,
+    pub project_name: String,
+    pub include_patterns: Vec<String>,
+    pub exclude_patterns: Vec<String>,
+    pub yes: bool,
+    pub wait: bool,
+    pub timeout_minutes: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -179,10 +279,12 @@ pub(crate) struct CreateSpotInstanceOptions {
     pub ami_id: String,
     pub user_data: String,
     pub max_price: Option<String>,
+    pub availability_zone: Option<String>,
     pub key_name: Option<String>,
     pub security_group: Option<String>,
     pub root_volume_size: i32,
     pub iam_instance_profile: Option<String>,
+    pub client_token: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -0,0 +1,238 @@
+//! User-data script rendering
+//!
+//! The instance bootstrap script is a minijinja template
+//! (`templates/user_data.sh.j2`) rendered with the project name and the
+//! `[bootstrap]` config section. Teams inject pre/post hooks, extra packages,
+//! and custom mounts through config instead of patching the script.
+//!
+//! Hooks are trusted shell (they come from the user's own config) and are
+//! inserted verbatim. Package names and mount paths are validated because
+//! they are interpolated into commands runctl itself controls.
+
+use crate::config::{BootstrapConfig, MountConfig};
+use crate::error::{ConfigError, Result, TrainctlError};
+use minijinja::{context, Environment};
+use serde::Serialize;
+
+const USER_DATA_TEMPLATE: &str = include_str!("templates/user_data.sh.j2");
+
+/// Mount entry as seen by the template (derived fields precomputed)
+#[derive(Serialize)]
+struct MountContext<'a> {
+    device: &'a str,
+    mount_point: &'a str,
+    fs_type: &'a str,
+    format: bool,
+    options: &'a str,
+    mkfs_command: String,
+}
+
+impl<'a> MountContext<'a> {
+    fn from_config(mount: &'a MountConfig) -> Self {
+        // ext* uses -F to force, xfs/btrfs use -f
+        let force_flag = if mount.fs_type.starts_with("ext") {
+            "-F"
+        } else {
+            "-f"
+        };
+        Self {
+            device: &mount.device,
+            mount_point: &mount.mount_point,
+            fs_type: &mount.fs_type,
+            format: mount.format,
+            options: mount.options.as_deref().unwrap_or("defaults"),
+            mkfs_command: format!("mkfs.{} {} {}", mount.fs_type, force_flag, mount.device),
+        }
+    }
+}
+
+/// Render the user-data bootstrap script
+///
+/// # Errors
+///
+/// Returns `TrainctlError::Validation` for unsafe package names or mount
+/// paths, and `ConfigError::InvalidValue` if the template fails to render.
+pub fn render_user_data(
+    project_name: &str,
+    has_data_volume: bool,
+    bootstrap: &BootstrapConfig,
+) -> Result<String> {
+    validate_bootstrap_config(bootstrap)?;
+
+    let mounts: Vec<MountContext> = bootstrap
+        .mounts
+        .iter()
+        .map(MountContext::from_config)
+        .collect();
+
+    let mut env = Environment::new();
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    env.set_keep_trailing_newline(true);
+
+    env.render_str(
+        USER_DATA_TEMPLATE,
+        context! {
+            project_name => project_name,
+            has_data_volume => has_data_volume,
+            pre_hooks => &bootstrap.pre_hooks,
+            post_hooks => &bootstrap.post_hooks,
+            extra_packages => &bootstrap.extra_packages,
+            mounts => mounts,
+        },
+    )
+    .map_err(|e| {
+        TrainctlError::Config(ConfigError::InvalidValue {
+            field: "bootstrap".to_string(),
+            reason: format!("Failed to render user-data template: {}", e),
+        })
+    })
+}
+
+fn validate_bootstrap_config(bootstrap: &BootstrapConfig) -> Result<()> {
+    for package in &bootstrap.extra_packages {
+        let valid = !package.is_empty()
+            && !package.starts_with('-')
+            && package
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "._+-:=".contains(c));
+        if !valid {
+            return Err(TrainctlError::Validation {
+                field: "bootstrap.extra_packages".to_string(),
+                reason: format!("Invalid package name: '{}'", package),
+            });
+        }
+    }
+
+    let is_safe_path = |p: &str| {
+        p.starts_with('/')
+            && p.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "/._-".contains(c))
+    };
+    for mount in &bootstrap.mounts {
+        if !is_safe_path(&mount.device) {
+            return Err(TrainctlError::Validation {
+                field: "bootstrap.mounts.device".to_string(),
+                reason: format!("Device must be an absolute path, got: '{}'", mount.device),
+            });
+        }
+        if !is_safe_path(&mount.mount_point) || mount.mount_point == "/" {
+            return Err(TrainctlError::Validation {
+                field: "bootstrap.mounts.mount_point".to_string(),
+                reason: format!(
+                    "Mount point must be an absolute, non-root path, got: '{}'",
+                    mount.mount_point
+                ),
+            });
+        }
+        if !mount.fs_type.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(TrainctlError::Validation {
+                field: "bootstrap.mounts.fs_type".to_string(),
+                reason: format!("Invalid filesystem type: '{}'", mount.fs_type),
+            });
+        }
+        if let Some(opts) = &mount.options {
+            if !opts
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || ",=_-".contains(c))
+            {
+                return Err(TrainctlError::Validation {
+                    field: "bootstrap.mounts.options".to_string(),
+                    reason: format!("Invalid mount options: '{}'", opts),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_default_config() {
+        let script = render_user_data("my-project", false, &BootstrapConfig::default()).unwrap();
+        assert!(script.starts_with("#!/bin/bash"));
+        assert!(script.contains("PROJECT_DIR=\"$HOME_DIR/my-project\""));
+        assert!(!script.contains("runctl_find_data_device"));
+        assert!(script.contains("RUNCTL_PHASE:complete"));
+        assert!(!script.contains("pre-bootstrap hooks"));
+        assert!(!script.contains("{%"));
+    }
+
+    #[test]
+    fn test_render_hooks_and_packages() {
+        let bootstrap = BootstrapConfig {
+            pre_hooks: vec!["update-ca-certificates".to_string()],
+            post_hooks: vec!["echo post-hook-ran".to_string()],
+            extra_packages: vec!["htop".to_string(), "nvtop".to_string()],
+            mounts: vec![],
+        };
+        let script = render_user_data("proj", false, &bootstrap).unwrap();
+
+        let pre = script.find("update-ca-certificates").unwrap();
+        let packages = script.find("apt-get update").unwrap();
+        let post = script.find("echo post-hook-ran").unwrap();
+        let complete = script.find("RUNCTL_PHASE:complete").unwrap();
+        assert!(pre < packages);
+        assert!(post < complete);
+        assert!(script.contains("apt-get install -y htop nvtop"));
+        assert!(script.contains("yum install -y htop nvtop"));
+    }
+
+    #[test]
+    fn test_render_custom_mount() {
+        let bootstrap = BootstrapConfig {
+            mounts: vec![MountConfig {
+                device: "/dev/nvme2n1".to_string(),
+                mount_point: "/mnt/scratch".to_string(),
+                fs_type: "xfs".to_string(),
+                format: true,
+                options: None,
+            }],
+            ..Default::default()
+        };
+        let script = render_user_data("proj", true, &bootstrap).unwrap();
+        assert!(script.contains("mkfs.xfs -f /dev/nvme2n1"));
+        assert!(script.contains("mount -o defaults /dev/nvme2n1 /mnt/scratch"));
+        // The data volume search never picks a configured mount's device
+        assert!(script.contains("\"$(readlink -f /dev/nvme2n1)\" ] && continue"));
+    }
+
+    #[test]
+    fn test_render_data_volume_waits_for_ebs_device() {
+        let script = render_user_data("proj", true, &BootstrapConfig::default()).unwrap();
+        let find = script.find("runctl_find_data_device() {").unwrap();
+        let wait = script.find("for _ in $(seq 1 60)").unwrap();
+        assert!(find < wait);
+        assert!(script.contains("awk '/Elastic Block Store/ {print $1}'"));
+        assert!(script.contains("mount $DATA_DEVICE $MOUNT_POINT"));
+        assert!(!script.contains("{%"));
+    }
+
+    #[test]
+    fn test_rejects_unsafe_package_name() {
+        let bootstrap = BootstrapConfig {
+            extra_packages: vec!["htop; rm -rf /".to_string()],
+            ..Default::default()
+        };
+        assert!(render_user_data("proj", false, &bootstrap).is_err());
+    }
+
+    #[test]
+    fn test_rejects_relative_mount_point() {
+        let bootstrap = BootstrapConfig {
+            mounts: vec![MountConfig {
+                device: "/dev/nvme2n1".to_string(),
+                mount_point: "scratch".to_string(),
+                fs_type: "ext4".to_string(),
+                format: false,
+                options: None,
+            }],
+            ..Default::default()
+        };
+        assert!(render_user_data("proj", false, &bootstrap).is_err());
+    }
+}
@@ -0,0 +1,469 @@
+//! Live EC2 prices from the AWS Pricing API and spot price history
+//!
+//! On-demand prices come from the Pricing API (Linux, shared tenancy, no
+//! pre-installed software) and spot prices from `describe_spot_price_history`.
+//! Both are cached per region (spot per availability zone) in
+//! `.runctl/pricing_cache.json`: on-demand prices for a week, spot prices for
+//! an hour.
+//!
+//! Lookups never call AWS. Commands that show costs call [`prefetch`] with
+//! the instances they are about to display, which refreshes stale entries;
+//! [`hourly_price`] and [`instance_hourly_price`] then read the cache. Types
+//! with no cached price (offline, or no `pricing:GetProducts` permission)
+//! fall back to the built-in table in [`crate::utils::get_instance_cost`].
+
+use crate::aws_utils::EnvCache;
+use crate::error::{Result, TrainctlError};
+use aws_config::BehaviorVersion;
+use aws_sdk_ec2::types::{Instance as Ec2Instance, InstanceLifecycleType, InstanceType};
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_pricing::types::{Filter, FilterType};
+use aws_sdk_pricing::Client as PricingClient;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+use tracing::{debug, warn};
+
+/// How long an on-demand price is reused
+pub const ON_DEMAND_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// How long a spot price is reused
+pub const SPOT_TTL_SECS: i64 = 60 * 60;
+
+/// The Pricing API is only served from a few regions; prices cover all of them
+const PRICING_API_REGION: &str = "us-east-1";
+
+/// Set after a Pricing API failure so one command doesn't retry it per type
+static PRICING_UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+
+fn cache_path() -> PathBuf {
+    PathBuf::from(".runctl").join("pricing_cache.json")
+}
+
+/// Which price an entry holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceKind {
+    OnDemand,
+    Spot,
+}
+
+impl PriceKind {
+    fn ttl_secs(self) -> i64 {
+        match self {
+            PriceKind::OnDemand => ON_DEMAND_TTL_SECS,
+            PriceKind::Spot => SPOT_TTL_SECS,
+        }
+    }
+}
+
+impl std::fmt::Display for PriceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PriceKind::OnDemand => write!(f, "on-demand"),
+            PriceKind::Spot => write!(f, "spot"),
+        }
+    }
+}
+
+/// One fetched price (USD/hour)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedPrice {
+    pub price: f64,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Fetched prices, keyed by `<kind>/<region or AZ>/<instance type>`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PriceCache {
+    entries: BTreeMap<String, CachedPrice>,
+}
+
+impl PriceCache {
+    /// Load the cache, starting empty if it is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            crate::utils::ensure_dir(parent)?;
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    fn key(kind: PriceKind, location: &str, instance_type: &str) -> String {
+        format!("{}/{}/{}", kind, location, instance_type)
+    }
+
+    /// Cached price, however old; `location` is a region, or an AZ for spot
+    pub fn get(&self, kind: PriceKind, location: &str, instance_type: &str) -> Option<f64> {
+        self.entries
+            .get(&Self::key(kind, location, instance_type))
+            .map(|entry| entry.price)
+    }
+
+    /// Whether the entry exists and is younger than its kind's TTL
+    pub fn is_fresh(
+        &self,
+        kind: PriceKind,
+        location: &str,
+        instance_type: &str,
+        now: DateTime<Utc>,
+    ) -> bool {
+        self.entries
+            .get(&Self::key(kind, location, instance_type))
+            .is_some_and(|entry| (now - entry.fetched_at).num_seconds() < kind.ttl_secs())
+    }
+
+    pub fn insert(
+        &mut self,
+        kind: PriceKind,
+        location: &str,
+        instance_type: &str,
+        price: f64,
+        now: DateTime<Utc>,
+    ) {
+        self.entries.insert(
+            Self::key(kind, location, instance_type),
+            CachedPrice {
+                price,
+                fetched_at: now,
+            },
+        );
+    }
+}
+
+/// The cache shared by lookups in this process, loaded on first use
+fn shared_cache() -> &'static Mutex<PriceCache> {
+    static CACHE: OnceLock<Mutex<PriceCache>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(PriceCache::load(&cache_path())))
+}
+
+/// Pricing API client, created on first use and again if the profile changes
+pub(crate) async fn pricing_client() -> PricingClient {
+    static CLIENT: EnvCache<PricingClient> = EnvCache::new();
+    CLIENT
+        .get_or_init(|| async {
+            let aws_config = aws_config::defaults(BehaviorVersion::latest())
+                .region(aws_config::Region::new(PRICING_API_REGION))
+                .load()
+                .await;
+            PricingClient::new(&aws_config)
+        })
+        .await
+}
+
+/// Region of an availability zone (`us-east-1a` -> `us-east-1`)
+pub fn region_of_zone(zone: &str) -> &str {
+    zone.trim_end_matches(|c: char| c.is_ascii_alphabetic())
+}
+
+/// Hourly price of `instance_type`, from the cache or the built-in table
+///
+/// With `spot_zone`, the current spot price in that zone is preferred.
+/// Without `region`, any cached on-demand price for the type is used.
+pub fn hourly_price(instance_type: &str, region: Option<&str>, spot_zone: Option<&str>) -> f64 {
+    let cache = shared_cache().lock().unwrap_or_else(|e| e.into_inner());
+    let spot = spot_zone.and_then(|zone| cache.get(PriceKind::Spot, zone, instance_type));
+    let on_demand = || match region {
+        Some(region) => cache.get(PriceKind::OnDemand, region, instance_type),
+        None => {
+            let suffix = format!("/{}", instance_type);
+            cache
+                .entries
+                .iter()
+                .find(|(key, _)| key.starts_with("on-demand/") && key.ends_with(&suffix))
+                .map(|(_, entry)| entry.price)
+        }
+    };
+    spot.or_else(on_demand)
+        .unwrap_or_else(|| crate::utils::get_instance_cost(instance_type))
+}
+
+/// What a described instance is billed as: (type, region, spot zone)
+fn billing_of(instance: &Ec2Instance) -> Option<(String, Option<String>, Option<String>)> {
+    let instance_type = instance.instance_type()?.as_str().to_string();
+    let zone = instance
+        .placement()
+        .and_then(|p| p.availability_zone())
+        .map(str::to_string);
+    let region = zone.as_deref().map(|z| region_of_zone(z).to_string());
+    let is_spot = instance.instance_lifecycle() == Some(&InstanceLifecycleType::Spot);
+    Some((instance_type, region, zone.filter(|_| is_spot)))
+}
+
+/// Hourly price of a described instance (spot price for spot instances)
+pub fn instance_hourly_price(instance: &Ec2Instance) -> f64 {
+    match billing_of(instance) {
+        Some((instance_type, region, spot_zone)) => {
+            hourly_price(&instance_type, region.as_deref(), spot_zone.as_deref())
+        }
+        None => crate::utils::get_instance_cost("unknown"),
+    }
+}
+
+/// On-demand price in a Pricing API product document
+///
+/// Each entry of `GetProducts`' price list is a JSON document whose
+/// `terms.OnDemand.<offer>.priceDimensions.<rate>.pricePerUnit.USD` holds
+/// the hourly price.
+pub fn parse_on_demand_price(product: &str) -> Option<f64> {
+    let product: serde_json::Value = serde_json::from_str(product).ok()?;
+    product
+        .get("terms")?
+        .get("OnDemand")?
+        .as_object()?
+        .values()
+        .filter_map(|offer| offer.get("priceDimensions")?.as_object())
+        .flat_map(|dimensions| dimensions.values())
+        .filter_map(|dimension| dimension.get("pricePerUnit")?.get("USD")?.as_str())
+        .filter_map(|usd| usd.parse::<f64>().ok())
+        .find(|price| *price > 0.0)
+}
+
+fn term_filter(field: &str, value: &str) -> Result<Filter> {
+    Filter::builder()
+        .r#type(FilterType::TermMatch)
+        .field(field)
+        .value(value)
+        .build()
+        .map_err(|e| TrainctlError::Aws(format!("Invalid pricing filter: {}", e)))
+}
+
+/// Linux on-demand price of `instance_type` in `region`, if listed
+pub async fn fetch_on_demand_price(
+    client: &PricingClient,
+    region: &str,
+    instance_type: &str,
+) -> Result<Option<f64>> {
+    let mut request = client
+        .get_products()
+        .service_code("AmazonEC2")
+        .max_results(10);
+    for (field, value) in [
+        ("instanceType", instance_type),
+        ("regionCode", region),
+        ("operatingSystem", "Linux"),
+        ("tenancy", "Shared"),
+        ("preInstalledSw", "NA"),
+        ("capacitystatus", "Used"),
+    ] {
+        request = request.filters(term_filter(field, value)?);
+    }
+    let response = request.send().await.map_err(|e| {
+        TrainctlError::Aws(format!(
+            "Failed to get price of {} in {}: {}",
+            instance_type, region, e
+        ))
+    })?;
+    Ok(response
+        .price_list()
+        .iter()
+        .find_map(|product| parse_on_demand_price(product)))
+}
+
+/// Current Linux spot price of `instance_type` in each availability zone
+pub async fn fetch_spot_prices(
+    client: &Ec2Client,
+    instance_type: &str,
+) -> Result<BTreeMap<String, f64>> {
+    let response = client
+        .describe_spot_price_history()
+        .instance_types(InstanceType::from(instance_type))
+        .product_descriptions("Linux/UNIX")
+        .start_time(aws_sdk_ec2::primitives::DateTime::from(SystemTime::now()))
+        .send()
+        .await
+        .map_err(|e| {
+            TrainctlError::Aws(format!(
+                "Failed to get spot price history for {}: {}",
+                instance_type, e
+            ))
+        })?;
+    let mut prices = BTreeMap::new();
+    for entry in response.spot_price_history() {
+        let (Some(zone), Some(price)) = (entry.availability_zone(), entry.spot_price()) else {
+            continue;
+        };
+        if let Ok(price) = price.parse::<f64>() {
+            // Entries are newest first; keep the current price per zone
+            prices.entry(zone.to_string()).or_insert(price);
+        }
+    }
+    Ok(prices)
+}
+
+/// Refresh stale cached prices for these instances
+///
+/// Failures are logged and leave the cache as it was, so callers fall back
+/// to older prices or the built-in table.
+pub async fn prefetch<'a>(
+    client: &Ec2Client,
+    instances: impl IntoIterator<Item = &'a Ec2Instance>,
+) {
+    let mut on_demand: BTreeSet<(String, String)> = BTreeSet::new();
+    let mut spot: BTreeSet<String> = BTreeSet::new();
+    let default_region = client.config().region().map(|r| r.as_ref().to_string());
+    {
+        let cache = shared_cache().lock().unwrap_or_else(|e| e.into_inner());
+        let now = Utc::now();
+        for (instance_type, region, spot_zone) in instances.into_iter().filter_map(billing_of) {
+            if let Some(zone) = spot_zone {
+                if !cache.is_fresh(PriceKind::Spot, &zone, &instance_type, now) {
+                    spot.insert(instance_type.clone());
+                }
+            }
+            if let Some(region) = region.or_else(|| default_region.clone()) {
+                if !cache.is_fresh(PriceKind::OnDemand, &region, &instance_type, now) {
+                    on_demand.insert((region, instance_type));
+                }
+            }
+        }
+    }
+    if on_demand.is_empty() && spot.is_empty() {
+        return;
+    }
+
+    let mut fetched: Vec<(PriceKind, String, String, f64)> = Vec::new();
+    if !on_demand.is_empty() && !PRICING_UNAVAILABLE.load(Ordering::Relaxed) {
+        let pricing = pricing_client().await;
+        for (region, instance_type) in &on_demand {
+            match fetch_on_demand_price(pricing, region, instance_type).await {
+                Ok(Some(price)) => fetched.push((
+                    PriceKind::OnDemand,
+                    region.clone(),
+                    instance_type.clone(),
+                    price,
+                )),
+                Ok(None) => debug!(
+                    "No on-demand price listed for {} in {}",
+                    instance_type, region
+                ),
+                Err(e) => {
+                    debug!("{}; using estimated prices", e);
+                    PRICING_UNAVAILABLE.store(true, Ordering::Relaxed);
+                    break;
+                }
+            }
+        }
+    }
+    for instance_type in &spot {
+        match fetch_spot_prices(client, instance_type).await {
+            Ok(prices) => fetched.extend(
+                prices
+                    .into_iter()
+                    .map(|(zone, price)| (PriceKind::Spot, zone, instance_type.clone(), price)),
+            ),
+            Err(e) => debug!("{}; using estimated prices", e),
+        }
+    }
+    if fetched.is_empty() {
+        return;
+    }
+
+    let mut cache = shared_cache().lock().unwrap_or_else(|e| e.into_inner());
+    let now = Utc::now();
+    for (kind, location, instance_type, price) in fetched {
+        cache.insert(kind, &location, &instance_type, price, now);
+    }
+    if let Err(e) = cache.save(&cache_path()) {
+        warn!("Failed to save pricing cache: {}", e);
+    }
+}
+
+/// Refresh prices for every instance in a describe_instances response
+pub async fn prefetch_reservations(
+    client: &Ec2Client,
+    reservations: &[aws_sdk_ec2::types::Reservation],
+) {
+    prefetch(client, reservations.iter().flat_map(|r| r.instances())).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_on_demand_price() {
+        let product = r#"{
+            "product": {"attributes": {"instanceType": "g5.xlarge"}},
+            "terms": {"OnDemand": {"ABC.JRTCKXETXF": {
+                "priceDimensions": {"ABC.JRTCKXETXF.6YS6EN2CT7": {
+                    "unit": "Hrs",
+                    "pricePerUnit": {"USD": "1.0060000000"}
+                }}
+            }}}
+        }"#;
+        assert_eq!(parse_on_demand_price(product), Some(1.006));
+        assert_eq!(parse_on_demand_price(r#"{"terms": {}}"#), None);
+        assert_eq!(parse_on_demand_price("not json"), None);
+    }
+
+    #[test]
+    fn test_cache_freshness_by_kind() {
+        let now = Utc::now();
+        let mut cache = PriceCache::default();
+        let two_hours_ago = now - chrono::Duration::hours(2);
+        cache.insert(
+            PriceKind::OnDemand,
+            "us-east-1",
+            "g5.xlarge",
+            1.006,
+            two_hours_ago,
+        );
+        cache.insert(
+            PriceKind::Spot,
+            "us-east-1a",
+            "g5.xlarge",
+            0.42,
+            two_hours_ago,
+        );
+
+        assert!(cache.is_fresh(PriceKind::OnDemand, "us-east-1", "g5.xlarge", now));
+        assert!(!cache.is_fresh(PriceKind::Spot, "us-east-1a", "g5.xlarge", now));
+        // Stale prices are still returned; they beat the built-in estimate
+        assert_eq!(
+            cache.get(PriceKind::Spot, "us-east-1a", "g5.xlarge"),
+            Some(0.42)
+        );
+        assert_eq!(
+            cache.get(PriceKind::OnDemand, "eu-west-1", "g5.xlarge"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(".runctl").join("pricing_cache.json");
+        let now = Utc::now();
+        let mut cache = PriceCache::default();
+        cache.insert(PriceKind::OnDemand, "us-west-2", "p4d.24xlarge", 32.77, now);
+        cache.save(&path).unwrap();
+
+        let loaded = PriceCache::load(&path);
+        assert_eq!(
+            loaded.get(PriceKind::OnDemand, "us-west-2", "p4d.24xlarge"),
+            Some(32.77)
+        );
+        assert!(PriceCache::load(&dir.path().join("missing.json"))
+            .entries
+            .is_empty());
+    }
+
+    #[test]
+    fn test_region_of_zone() {
+        assert_eq!(region_of_zone("us-east-1a"), "us-east-1");
+        assert_eq!(region_of_zone("eu-central-1c"), "eu-central-1");
+    }
+}
@@ -3,11 +3,13 @@
 //! This module provides reusable functions for AWS operations to reduce
 //! code duplication and ensure consistent behavior.
 
+use crate::checkpoint::coordinate::{self, StopOutcome};
+use crate::config::Config;
 use crate::error::{Result, TrainctlError};
 use crate::retry::{ExponentialBackoffPolicy, RetryPolicy};
 use aws_sdk_ec2::Client as Ec2Client;
 use aws_sdk_ssm::Client as SsmClient;
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::ProgressBar;
 use std::time::Duration;
 use tokio::time::sleep;
 use tracing::{info, warn};
@@ -34,6 +36,59 @@ const VOLUME_ATTACH_POLL_INTERVAL_SECS: u64 = 2;
 const VOLUME_DETACH_MAX_ATTEMPTS: u32 = 30;
 const VOLUME_DETACH_POLL_INTERVAL_SECS: u64 = 2;
 
+/// Environment variables that select the AWS profile and region
+const SDK_ENV_VARS: &[&str] = &["AWS_PROFILE", "AWS_REGION", "AWS_DEFAULT_REGION"];
+
+/// Default AWS SDK config, loaded once per process and profile
+///
+/// Loading resolves credentials and region from the environment and files,
+/// which `runctl shell` would otherwise repeat for every command. The cached
+/// config is reloaded when `AWS_PROFILE` or the region variables change.
+pub async fn shared_sdk_config() -> aws_config::SdkConfig {
+    static CACHE: EnvCache<aws_config::SdkConfig> = EnvCache::new();
+    CACHE
+        .get_or_init(|| aws_config::load_defaults(aws_config::BehaviorVersion::latest()))
+        .await
+}
+
+/// A value built from the ambient AWS profile and region
+///
+/// Like `shared_sdk_config`, clients and configs cached for the process are
+/// rebuilt when `SDK_ENV_VARS` change, so `use profile` in `runctl shell`
+/// takes effect for every command.
+pub(crate) struct EnvCache<T> {
+    cached: tokio::sync::Mutex<Option<(Vec<Option<String>>, T)>>,
+}
+
+impl<T: Clone> EnvCache<T> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            cached: tokio::sync::Mutex::const_new(None),
+        }
+    }
+
+    /// The cached value, or a new one from `init` if the environment changed
+    pub(crate) async fn get_or_init<F, Fut>(&self, init: F) -> T
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = T>,
+    {
+        let key: Vec<Option<String>> = SDK_ENV_VARS
+            .iter()
+            .map(|var| std::env::var(var).ok())
+            .collect();
+        let mut cached = self.cached.lock().await;
+        if let Some((cached_key, value)) = cached.as_ref() {
+            if *cached_key == key {
+                return value.clone();
+            }
+        }
+        let value = init().await;
+        *cached = Some((key, value.clone()));
+        value
+    }
+}
+
 /// Execute SSM command and poll for completion
 ///
 /// This is a unified implementation used by both `aws.rs` and `data_transfer.rs`
@@ -45,7 +100,8 @@ pub async fn execute_ssm_command(
 ) -> Result<String> {
     info!(
         "Executing SSM command on instance {}: {}",
-        instance_id, command
+        instance_id,
+        crate::env_vars::redact(command)
     );
 
     // Send command
@@ -91,12 +147,9 @@ pub async fn execute_ssm_command(
 
     // Create progress bar for long-running commands
     let pb = ProgressBar::new(max_attempts as u64);
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}")
-            .expect("Progress bar template should be valid")
-            .progress_chars("#>-"),
-    );
+    pb.set_style(crate::output_style::bar(
+        "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}",
+    ));
     pb.set_message("Waiting for command completion...");
 
     for attempt in 0..max_attempts {
@@ -212,6 +265,41 @@ pub async fn execute_ssm_command(
     )))
 }
 
+/// Let training on `instance_id` checkpoint and exit before it is stopped
+///
+/// Asks a listening script to checkpoint at its next boundary, waiting up to
+/// `[checkpoint] stop_wait_secs`, then falls back to SIGTERM and SIGKILL (see
+/// `checkpoint::coordinate`). Best effort: an instance without SSM is only
+/// logged (None), and the caller stops it anyway.
+pub async fn stop_training_gracefully(
+    client: &SsmClient,
+    instance_id: &str,
+    config: &Config,
+    reason: &str,
+) -> Option<StopOutcome> {
+    let wait = coordinate::stop_wait_secs(config);
+    match execute_ssm_command(client, instance_id, &coordinate::stop_command(wait, reason)).await {
+        Ok(output) => {
+            let outcome = coordinate::parse_stop_output(&output);
+            info!("Training on {}: {}", instance_id, outcome);
+            Some(outcome)
+        }
+        Err(e) => {
+            warn!(
+                "Failed to send graceful shutdown command to {} (non-critical): {}",
+                instance_id, e
+            );
+            None
+        }
+    }
+}
+
+fn emit_instance_ready(instance_id: &str) {
+    crate::progress::ProgressEvent::new("create.ready", format!("{} is ready", instance_id))
+        .done()
+        .emit();
+}
+
 /// Wait for instance to reach running state
 ///
 /// Polls EC2 API until instance is running and SSM is ready.
@@ -225,11 +313,9 @@ pub async fn wait_for_instance_running(
     const POLL_INTERVAL: Duration = Duration::from_secs(INSTANCE_WAIT_POLL_INTERVAL_SECS);
 
     let pb = ProgressBar::new(u64::from(MAX_ATTEMPTS));
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.green} [{elapsed_precise}] {msg}")
-            .expect("Progress bar template should be valid"),
-    );
+    pb.set_style(crate::output_style::spinner(
+        "{spinner:.green} [{elapsed_precise}] {msg}",
+    ));
     pb.set_message("Waiting for instance to start...");
 
     for attempt in 0..MAX_ATTEMPTS {
@@ -263,6 +349,10 @@ pub async fn wait_for_instance_running(
                 if has_iam_profile {
                     if let Some(config) = aws_config {
                         pb.set_message("Instance running, verifying SSM connectivity...");
+                        crate::progress::emit(
+                            "create.ssm",
+                            format!("{} running, verifying SSM connectivity", instance_id),
+                        );
                         // Verify SSM is actually ready by attempting a simple command
                         // This is more reliable than just waiting a fixed time
                         let ssm_client = SsmClient::new(config);
@@ -283,6 +373,7 @@ pub async fn wait_for_instance_running(
                                 Ok(_) => {
                                     // SSM command accepted - SSM is ready
                                     pb.finish_with_message("Instance ready and SSM connected");
+                                    emit_instance_ready(instance_id);
                                     return Ok(());
                                 }
                                 Err(_e) => {
@@ -293,6 +384,7 @@ pub async fn wait_for_instance_running(
                                         pb.finish_with_message(
                                             "Instance running (SSM may not be ready yet)",
                                         );
+                                        emit_instance_ready(instance_id);
                                         // Don't fail - instance is running, SSM may become ready later
                                         return Ok(());
                                     }
@@ -309,11 +401,13 @@ pub async fn wait_for_instance_running(
                     } else {
                         // No aws_config provided - can't verify SSM, just assume ready
                         pb.finish_with_message("Instance ready (SSM verification skipped)");
+                        emit_instance_ready(instance_id);
                         return Ok(());
                     }
                 } else {
                     // No IAM profile - SSM won't work, instance is ready for SSH
                     pb.finish_with_message("Instance ready (SSM not available, use SSH)");
+                    emit_instance_ready(instance_id);
                     return Ok(());
                 }
             }
@@ -333,6 +427,10 @@ pub async fn wait_for_instance_running(
             _ => {
                 let state_str = state.as_ref().map(|s| s.as_str()).unwrap_or("unknown");
                 pb.set_message(format!("State: {}...", state_str));
+                crate::progress::emit(
+                    "create.starting",
+                    format!("{} is {}", instance_id, state_str),
+                );
             }
         }
     }
@@ -364,11 +462,9 @@ pub async fn wait_for_volume_attachment(
     const POLL_INTERVAL: Duration = Duration::from_secs(VOLUME_ATTACH_POLL_INTERVAL_SECS);
 
     let pb = ProgressBar::new(u64::from(MAX_ATTEMPTS));
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.green} [{elapsed_precise}] {msg}")
-            .expect("Progress bar template should be valid"),
-    );
+    pb.set_style(crate::output_style::spinner(
+        "{spinner:.green} [{elapsed_precise}] {msg}",
+    ));
     pb.set_message("Attaching volume...");
 
     for attempt in 0..MAX_ATTEMPTS {
@@ -431,11 +527,9 @@ pub async fn wait_for_volume_detached(client: &Ec2Client, volume_id: &str) -> Re
     const POLL_INTERVAL: Duration = Duration::from_secs(VOLUME_DETACH_POLL_INTERVAL_SECS);
 
     let pb = ProgressBar::new(u64::from(MAX_ATTEMPTS));
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.green} [{elapsed_precise}] {msg}")
-            .expect("Progress bar template should be valid"),
-    );
+    pb.set_style(crate::output_style::spinner(
+        "{spinner:.green} [{elapsed_precise}] {msg}",
+    ));
     pb.set_message("Detaching volume...");
 
     for attempt in 0..MAX_ATTEMPTS {
@@ -0,0 +1,529 @@
+//! Azure integration
+//!
+//! Provides `runctl azure` for NC/ND-series GPU VMs:
+//! - Create, list and terminate VMs (Azure CLI, see `providers::AzureClient`)
+//! - Sync the project and run training over SSH
+//! - Monitor the training log and download results
+//! - Estimated hourly cost for known GPU sizes (`runctl azure sizes`)
+//!
+//! Needs the Azure CLI logged in (`az login`) and an SSH key pair at
+//! `[azure] ssh_key` (default `~/.ssh/id_rsa`).
+
+use crate::config::{AzureConfig, Config};
+use crate::error::{Result, TrainctlError};
+use crate::host_keys::HostKeys;
+use crate::providers::{gpu_vm_size, AzureClient, CreateVmRequest, GPU_VM_SIZES};
+use clap::Subcommand;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::{info, warn};
+
+#[derive(Subcommand, Clone)]
+pub enum AzureCommands {
+    /// Create a GPU VM and wait until it's provisioned
+    ///
+    /// Examples:
+    ///   runctl azure create
+    ///   runctl azure create --size Standard_NC24ads_A100_v4 --spot --name sweep-1
+    Create {
+        /// VM name (default: runctl-<random>)
+        #[arg(long)]
+        name: Option<String>,
+        /// VM size (default: [azure] default_vm_size)
+        #[arg(long)]
+        size: Option<String>,
+        /// Use a spot VM (deleted on eviction)
+        #[arg(long)]
+        spot: bool,
+        /// Image URN (default: [azure] image)
+        #[arg(long)]
+        image: Option<String>,
+        /// OS disk size, e.g. 256 (GiB) or 512GB (default: [azure] os_disk_gb)
+        #[arg(long, value_parser = crate::units::parse_gib)]
+        disk: Option<i32>,
+        /// Project name tag (default: current directory name)
+        #[arg(long)]
+        project_name: Option<String>,
+    },
+    /// List VMs in the resource group
+    List,
+    /// List known GPU VM sizes with estimated hourly prices
+    Sizes,
+    /// Sync the project to a VM and run a script there
+    ///
+    /// Examples:
+    ///   runctl azure train runctl-1a2b3c4d train.py -- --epochs 50
+    Train {
+        vm: String,
+        script: PathBuf,
+        /// Start training and return instead of streaming its output
+        #[arg(long)]
+        background: bool,
+        /// Arguments passed to the script (after --)
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    /// Show the training log on a VM
+    Monitor {
+        vm: String,
+        #[arg(long)]
+        follow: bool,
+    },
+    /// Copy a file or directory from a VM
+    Download {
+        vm: String,
+        remote: PathBuf,
+        local: PathBuf,
+    },
+    /// Delete a VM with its disk, NIC and public IP
+    Terminate { vm: String },
+}
+
+pub async fn handle_command(
+    cmd: AzureCommands,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    match cmd {
+        AzureCommands::Create {
+            name,
+            size,
+            spot,
+            image,
+            disk,
+            project_name,
+        } => {
+            let settings = config.azure.clone().unwrap_or_default();
+            let request = CreateVmRequest {
+                name: name.unwrap_or_else(generate_vm_name),
+                size: size.unwrap_or_else(|| settings.default_vm_size.clone()),
+                image: image.unwrap_or_else(|| settings.image.clone()),
+                admin_username: settings.admin_username.clone(),
+                ssh_public_key: public_key_path(&settings)?,
+                os_disk_gb: disk.map(|d| d as u32).unwrap_or(settings.os_disk_gb),
+                spot,
+                tags: vec![
+                    ("runctl:managed".to_string(), "true".to_string()),
+                    (
+                        "runctl:project".to_string(),
+                        crate::utils::get_project_name(project_name, config),
+                    ),
+                ],
+            };
+            create_vm(&request, config, output_format).await
+        }
+        AzureCommands::List => list_vms(config, output_format).await,
+        AzureCommands::Sizes => list_sizes(output_format),
+        AzureCommands::Train {
+            vm,
+            script,
+            background,
+            args,
+        } => train_on_vm(&vm, &script, &args, &[], background, config, output_format).await,
+        AzureCommands::Monitor { vm, follow } => monitor_vm(&vm, follow, config).await,
+        AzureCommands::Download { vm, remote, local } => {
+            download_from_vm(&vm, &remote, &local, config).await
+        }
+        AzureCommands::Terminate { vm } => {
+            AzureClient::from_config(config)?.delete_vm(&vm).await?;
+            if output_format == "json" {
+                let result = serde_json::json!({
+                    "success": true,
+                    "vm": vm,
+                    "action": "terminated",
+                });
+                println!("{}", serde_json::to_string_pretty(&result)?);
+            } else {
+                println!("VM {} terminated", vm);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Random VM name, e.g. `runctl-1a2b3c4d`
+pub(crate) fn generate_vm_name() -> String {
+    format!("runctl-{}", &uuid::Uuid::new_v4().to_string()[..8])
+}
+
+fn expand_path(path: &Path) -> PathBuf {
+    PathBuf::from(shellexpand::tilde(&path.to_string_lossy()).into_owned())
+}
+
+/// `<ssh_key>.pub`, which must exist for `az vm create`
+pub(crate) fn public_key_path(settings: &AzureConfig) -> Result<String> {
+    let mut path = expand_path(&settings.ssh_key).into_os_string();
+    path.push(".pub");
+    let path = PathBuf::from(path);
+    if !path.exists() {
+        return Err(TrainctlError::Config(
+            crate::error::ConfigError::InvalidValue {
+                field: "azure.ssh_key".to_string(),
+                reason: format!(
+                    "public key {} not found; create a key pair with ssh-keygen or set [azure] ssh_key",
+                    path.display()
+                ),
+            },
+        ));
+    }
+    Ok(path.to_string_lossy().into_owned())
+}
+
+async fn create_vm(request: &CreateVmRequest, config: &Config, output_format: &str) -> Result<()> {
+    let text = output_format != "json";
+    let client = AzureClient::from_config(config)?;
+    let price = gpu_vm_size(&request.size).map(|size| size.price_per_hour);
+    if !crate::providers::is_gpu_series(&request.size) {
+        warn!(
+            "{} is not an NC/ND-series size and may have no GPU",
+            request.size
+        );
+    }
+
+    info!(
+        "Creating Azure VM {}: size={}, spot={}",
+        request.name, request.size, request.spot
+    );
+    if text {
+        println!(
+            "Creating {} ({}{}) in {}...",
+            request.name,
+            request.size,
+            if request.spot { ", spot" } else { "" },
+            client.resource_group()
+        );
+        match price {
+            Some(price) => println!(
+                "   Estimated cost: ${:.3}/hr (pay-as-you-go list price)",
+                price
+            ),
+            None => println!("   Estimated cost: unknown for {}", request.size),
+        }
+    }
+    crate::progress::emit("create.request", format!("Requesting {} VM", request.size));
+    let vm = client.create_vm(request).await?;
+    crate::progress::ProgressEvent::new("create.ready", format!("{} is ready", vm.name))
+        .done()
+        .emit();
+
+    if text {
+        println!(
+            "VM ready: {} ({}, {})",
+            vm.name,
+            vm.ssh_address().unwrap_or("no public IP"),
+            vm.state()
+        );
+        println!(
+            "   Train with: runctl azure train {} <script> -- <args>",
+            vm.name
+        );
+    } else {
+        println!("{}", serde_json::to_string_pretty(&vm)?);
+    }
+    Ok(())
+}
+
+async fn list_vms(config: &Config, output_format: &str) -> Result<()> {
+    let vms = AzureClient::from_config(config)?.list_vms().await?;
+    if output_format == "json" {
+        println!("{}", serde_json::to_string_pretty(&vms)?);
+        return Ok(());
+    }
+    if vms.is_empty() {
+        println!("No VMs found");
+        return Ok(());
+    }
+    println!(
+        "{:<24} {:<28} {:<12} {:<16} {:>8}",
+        "NAME", "SIZE", "STATE", "PUBLIC IP", "$/HR"
+    );
+    for vm in &vms {
+        println!(
+            "{:<24} {:<28} {:<12} {:<16} {:>8.3}{}",
+            vm.name,
+            vm.vm_size(),
+            vm.state(),
+            vm.ssh_address().unwrap_or("-"),
+            vm.cost_per_hour(),
+            if vm.is_spot() { "  spot" } else { "" }
+        );
+    }
+    Ok(())
+}
+
+fn list_sizes(output_format: &str) -> Result<()> {
+    if output_format == "json" {
+        println!("{}", serde_json::to_string_pretty(GPU_VM_SIZES)?);
+        return Ok(());
+    }
+    println!("{:<28} {:>4}  {:<12} {:>10}", "SIZE", "GPUS", "GPU", "$/HR");
+    for size in GPU_VM_SIZES {
+        println!(
+            "{:<28} {:>4}  {:<12} {:>10}",
+            size.name,
+            size.gpus,
+            size.gpu,
+            format!("${:.3}", size.price_per_hour)
+        );
+    }
+    println!("\nPay-as-you-go Linux list prices in East US; spot and other regions differ.");
+    Ok(())
+}
+
+/// How to reach a VM over SSH
+struct SshTarget {
+    ip: String,
+    user: String,
+    key: String,
+    /// Host key checking, keyed by VM name
+    host_keys: HostKeys,
+    /// Project directory on the VM
+    project_dir: String,
+}
+
+impl SshTarget {
+    async fn resolve(vm_name: &str, config: &Config) -> Result<Self> {
+        let settings = config.azure.clone().unwrap_or_default();
+        let vm = AzureClient::from_config(config)?.get_vm(vm_name).await?;
+        let ip = vm
+            .ssh_address()
+            .ok_or_else(|| TrainctlError::CloudProvider {
+                provider: "azure".to_string(),
+                message: format!("VM {} has no public IP (state: {})", vm_name, vm.state()),
+                source: None,
+            })?
+            .to_string();
+        let project = crate::utils::get_project_name(None, config);
+        Ok(Self {
+            ip,
+            project_dir: format!("/home/{}/{}", settings.admin_username, project),
+            user: settings.admin_username,
+            key: expand_path(&settings.ssh_key)
+                .to_string_lossy()
+                .into_owned(),
+            host_keys: HostKeys::for_host(&config.ssh, vm_name),
+        })
+    }
+
+    fn ssh(&self, remote_command: &str) -> Command {
+        let mut cmd = Command::new("ssh");
+        cmd.args(self.host_keys.ssh_args())
+            .args(["-o", "ConnectTimeout=10"])
+            .args(["-i", &self.key])
+            .arg(format!("{}@{}", self.user, self.ip))
+            .arg(remote_command);
+        cmd
+    }
+
+    fn log_path(&self) -> String {
+        format!("{}/training.log", self.project_dir)
+    }
+
+    fn exit_code_path(&self) -> String {
+        format!("{}/training.exit", self.project_dir)
+    }
+}
+
+/// Sync the current project to a VM and run `script` with `script_args`
+///
+/// The script must be inside the current directory, which is synced to
+/// `/home/<admin_username>/<project>` using the `[sync]` rules.
+pub(crate) async fn train_on_vm(
+    vm_name: &str,
+    script: &Path,
+    script_args: &[String],
+    env: &[(String, String)],
+    background: bool,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    let text = output_format != "json";
+    let project_root = std::env::current_dir()?.canonicalize()?;
+    let script_abs = script.canonicalize().map_err(|_| {
+        TrainctlError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("Script not found: {}", script.display()),
+        ))
+    })?;
+    let relative = script_abs
+        .strip_prefix(&project_root)
+        .map_err(|_| TrainctlError::Validation {
+            field: "script".to_string(),
+            reason: format!(
+                "{} is outside the current directory ({}), which is what gets synced",
+                script.display(),
+                project_root.display()
+            ),
+        })?
+        .to_path_buf();
+
+    let target = SshTarget::resolve(vm_name, config).await?;
+    info!("Starting training on Azure VM {} ({})", vm_name, target.ip);
+    if text {
+        println!("Syncing {} to {}...", project_root.display(), vm_name);
+    }
+    let filter = crate::sync_filter::SyncFilter::from_config(&config.sync)?;
+    crate::ssh_sync::sync_code_native(
+        &target.key,
+        &target.ip,
+        &target.user,
+        &target.host_keys,
+        &crate::ssh_proxy::SshProxy::Direct,
+        &target.project_dir,
+        &project_root,
+        output_format,
+        &filter,
+    )
+    .await?;
+
+    let interpreter = match relative.extension().and_then(|e| e.to_str()) {
+        Some("py") => "python3",
+        _ => "bash",
+    };
+    let args: String = script_args
+        .iter()
+        .map(|arg| format!(" {}", crate::utils::shell_quote(arg)))
+        .collect();
+    let run = format!(
+        "{}{} {}{}",
+        crate::utils::env_exports(env),
+        interpreter,
+        crate::utils::shell_quote(&relative.to_string_lossy()),
+        args
+    );
+    let log = target.log_path();
+    let remote = if background {
+        // Record the exit code so `training_exit_code` can tell when it's done
+        let exit_path = target.exit_code_path();
+        let run = format!("{}; echo $? > {}", run, exit_path);
+        format!(
+            "cd {} && rm -f {} && nohup bash -c {} > {} 2>&1 < /dev/null & echo $! > {}/training.pid",
+            target.project_dir,
+            exit_path,
+            crate::utils::shell_quote(&run),
+            log,
+            target.project_dir
+        )
+    } else {
+        format!(
+            "cd {} && set -o pipefail && {} 2>&1 | tee {}",
+            target.project_dir, run, log
+        )
+    };
+
+    crate::progress::emit(
+        "train.start",
+        format!("{} on {}", relative.display(), vm_name),
+    );
+    if background {
+        let output = target.ssh(&remote).output()?;
+        if !output.status.success() {
+            return Err(TrainctlError::CloudProvider {
+                provider: "azure".to_string(),
+                message: format!(
+                    "Failed to start training: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+                source: None,
+            });
+        }
+        if text {
+            println!("Training started in background");
+            println!("   Monitor with: runctl azure monitor {} --follow", vm_name);
+        }
+    } else {
+        let status = target.ssh(&remote).status()?;
+        if !status.success() {
+            return Err(TrainctlError::CloudProvider {
+                provider: "azure".to_string(),
+                message: format!("Training exited with {}", status),
+                source: None,
+            });
+        }
+        if text {
+            println!("Training completed");
+        }
+    }
+    Ok(())
+}
+
+/// Exit code of the last background training run, or None while it runs
+pub(crate) async fn training_exit_code(vm_name: &str, config: &Config) -> Result<Option<i32>> {
+    let target = SshTarget::resolve(vm_name, config).await?;
+    let output = target
+        .ssh(&format!(
+            "cat {} 2>/dev/null || true",
+            target.exit_code_path()
+        ))
+        .output()?;
+    if !output.status.success() {
+        return Err(TrainctlError::CloudProvider {
+            provider: "azure".to_string(),
+            message: format!(
+                "Failed to check training on {}: {}",
+                vm_name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            source: None,
+        });
+    }
+    crate::utils::parse_exit_code(&String::from_utf8_lossy(&output.stdout))
+}
+
+pub(crate) async fn monitor_vm(vm_name: &str, follow: bool, config: &Config) -> Result<()> {
+    let target = SshTarget::resolve(vm_name, config).await?;
+    let log = target.log_path();
+    if follow {
+        println!("Following log on {} (Ctrl+C to stop)...", vm_name);
+        target.ssh(&format!("tail -f {}", log)).status()?;
+    } else {
+        let output = target.ssh(&format!("tail -n 50 {}", log)).output()?;
+        print!(
+            "{}",
+            crate::output_style::sanitize(&String::from_utf8_lossy(&output.stdout))
+        );
+    }
+    Ok(())
+}
+
+pub(crate) async fn download_from_vm(
+    vm_name: &str,
+    remote: &Path,
+    local: &Path,
+    config: &Config,
+) -> Result<()> {
+    let target = SshTarget::resolve(vm_name, config).await?;
+    // Relative paths are relative to the project directory
+    let remote = if remote.is_absolute() {
+        remote.to_path_buf()
+    } else {
+        Path::new(&target.project_dir).join(remote)
+    };
+    println!(
+        "Downloading from {}: {} -> {}",
+        vm_name,
+        remote.display(),
+        local.display()
+    );
+    let status = Command::new("scp")
+        .arg("-r")
+        .args(target.host_keys.ssh_args())
+        .args(["-i", &target.key])
+        .arg(format!(
+            "{}@{}:{}",
+            target.user,
+            crate::utils::bracket_ipv6(&target.ip),
+            remote.display()
+        ))
+        .arg(local)
+        .status()?;
+    if !status.success() {
+        return Err(TrainctlError::CloudProvider {
+            provider: "azure".to_string(),
+            message: "Download failed".to_string(),
+            source: None,
+        });
+    }
+    println!("Download complete");
+    Ok(())
+}
@@ -0,0 +1,273 @@
+//! AWS Batch setup (`runctl batch`)
+//!
+//! Training on Batch goes through the provider (`runctl run --provider
+//! batch`, see `providers::BatchProvider`); this module only prepares what it
+//! submits to. `runctl batch setup` reuses the `[batch] job_queue` if it
+//! exists, and otherwise creates it together with a managed EC2 compute
+//! environment sized by `[batch] max_vcpus`, `subnets` and
+//! `security_group_ids`.
+
+use crate::config::{BatchConfig, Config};
+use crate::error::{ConfigError, Result, TrainctlError};
+use aws_sdk_batch::types::{
+    CeState, CeType, ComputeEnvironmentOrder, ComputeResource, CrAllocationStrategy, CrType,
+    JqState,
+};
+use aws_sdk_batch::Client as BatchApi;
+use clap::Subcommand;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// How long `setup` waits for a new compute environment to become VALID
+const COMPUTE_ENVIRONMENT_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Subcommand, Clone)]
+pub enum BatchCommands {
+    /// Create the job queue and compute environment, or check existing ones
+    ///
+    /// Examples:
+    ///   runctl batch setup
+    ///   runctl batch setup --instance-types g5.xlarge,g5.2xlarge
+    Setup {
+        /// Instance types for a new compute environment
+        /// (default: [batch] default_instance_type)
+        #[arg(long, value_delimiter = ',')]
+        instance_types: Vec<String>,
+    },
+}
+
+pub async fn handle_command(
+    cmd: BatchCommands,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    match cmd {
+        BatchCommands::Setup { instance_types } => {
+            let settings = config.batch.clone().ok_or_else(|| {
+                TrainctlError::Config(ConfigError::MissingField("batch".to_string()))
+            })?;
+            let instance_types = if instance_types.is_empty() {
+                vec![settings.default_instance_type.clone()]
+            } else {
+                instance_types
+            };
+            let sdk_config = crate::aws_utils::shared_sdk_config().await;
+            let client = BatchApi::new(&sdk_config);
+            setup(&client, &settings, &instance_types, output_format).await
+        }
+    }
+}
+
+/// Status and state of a job queue or compute environment
+#[derive(Debug, serde::Serialize)]
+struct BatchResource {
+    name: String,
+    status: String,
+    state: String,
+    created: bool,
+}
+
+async fn setup(
+    client: &BatchApi,
+    settings: &BatchConfig,
+    instance_types: &[String],
+    output_format: &str,
+) -> Result<()> {
+    let text = output_format != "json";
+    if let Some(queue) = describe_queue(client, &settings.job_queue).await? {
+        if text {
+            println!(
+                "Job queue {} exists ({}, {})",
+                queue.name, queue.state, queue.status
+            );
+        } else {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&serde_json::json!({ "job_queue": queue }))?
+            );
+        }
+        return Ok(());
+    }
+
+    let environment = match describe_environment(client, &settings.compute_environment).await? {
+        Some(environment) => environment,
+        None => {
+            if settings.subnets.is_empty() {
+                return Err(TrainctlError::Config(ConfigError::MissingField(
+                    "batch.subnets".to_string(),
+                )));
+            }
+            if text {
+                println!(
+                    "Creating compute environment {} ({}, up to {} vCPUs)...",
+                    settings.compute_environment,
+                    instance_types.join(", "),
+                    settings.max_vcpus
+                );
+            }
+            create_environment(client, settings, instance_types).await?;
+            wait_for_environment(client, &settings.compute_environment).await?
+        }
+    };
+
+    if text {
+        println!("Creating job queue {}...", settings.job_queue);
+    }
+    let order = ComputeEnvironmentOrder::builder()
+        .order(1)
+        .compute_environment(&environment.name)
+        .build()
+        .map_err(|e| TrainctlError::Aws(format!("Invalid compute environment order: {}", e)))?;
+    client
+        .create_job_queue()
+        .job_queue_name(&settings.job_queue)
+        .state(JqState::Enabled)
+        .priority(1)
+        .compute_environment_order(order)
+        .tags("runctl:managed", "true")
+        .send()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to create job queue: {}", e)))?;
+    info!("Created job queue {}", settings.job_queue);
+
+    if text {
+        println!(
+            "Job queue {} is ready; train with: runctl run --provider batch train.py",
+            settings.job_queue
+        );
+    } else {
+        let queue = BatchResource {
+            name: settings.job_queue.clone(),
+            status: "CREATING".to_string(),
+            state: "ENABLED".to_string(),
+            created: true,
+        };
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "job_queue": queue,
+                "compute_environment": environment,
+            }))?
+        );
+    }
+    Ok(())
+}
+
+async fn describe_queue(client: &BatchApi, name: &str) -> Result<Option<BatchResource>> {
+    let response = client
+        .describe_job_queues()
+        .job_queues(name)
+        .send()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to describe job queue: {}", e)))?;
+    Ok(response.job_queues().first().map(|queue| BatchResource {
+        name: name.to_string(),
+        status: queue
+            .status()
+            .map(|s| s.as_str().to_string())
+            .unwrap_or_default(),
+        state: queue
+            .state()
+            .map(|s| s.as_str().to_string())
+            .unwrap_or_default(),
+        created: false,
+    }))
+}
+
+async fn describe_environment(client: &BatchApi, name: &str) -> Result<Option<BatchResource>> {
+    let response = client
+        .describe_compute_environments()
+        .compute_environments(name)
+        .send()
+        .await
+        .map_err(|e| {
+            TrainctlError::Aws(format!("Failed to describe compute environment: {}", e))
+        })?;
+    Ok(response
+        .compute_environments()
+        .first()
+        .map(|environment| BatchResource {
+            name: name.to_string(),
+            status: environment
+                .status()
+                .map(|s| s.as_str().to_string())
+                .unwrap_or_default(),
+            state: environment
+                .state()
+                .map(|s| s.as_str().to_string())
+                .unwrap_or_default(),
+            created: false,
+        }))
+}
+
+async fn create_environment(
+    client: &BatchApi,
+    settings: &BatchConfig,
+    instance_types: &[String],
+) -> Result<()> {
+    let (kind, strategy) = if settings.spot {
+        (CrType::Spot, CrAllocationStrategy::SpotCapacityOptimized)
+    } else {
+        (CrType::Ec2, CrAllocationStrategy::BestFitProgressive)
+    };
+    let resources = ComputeResource::builder()
+        .r#type(kind)
+        .allocation_strategy(strategy)
+        .minv_cpus(0)
+        .maxv_cpus(settings.max_vcpus)
+        .set_instance_types(Some(instance_types.to_vec()))
+        .set_subnets(Some(settings.subnets.clone()))
+        .set_security_group_ids(Some(settings.security_group_ids.clone()))
+        .instance_role(&settings.instance_role)
+        .build()
+        .map_err(|e| TrainctlError::Aws(format!("Invalid compute resources: {}", e)))?;
+    client
+        .create_compute_environment()
+        .compute_environment_name(&settings.compute_environment)
+        .r#type(CeType::Managed)
+        .state(CeState::Enabled)
+        .compute_resources(resources)
+        .tags("runctl:managed", "true")
+        .send()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to create compute environment: {}", e)))?;
+    info!(
+        "Created compute environment {}",
+        settings.compute_environment
+    );
+    Ok(())
+}
+
+/// Poll until the compute environment is VALID; a queue can't attach before
+async fn wait_for_environment(client: &BatchApi, name: &str) -> Result<BatchResource> {
+    let started = Instant::now();
+    loop {
+        if let Some(mut environment) = describe_environment(client, name).await? {
+            match environment.status.as_str() {
+                "VALID" => {
+                    environment.created = true;
+                    return Ok(environment);
+                }
+                "INVALID" => {
+                    return Err(TrainctlError::CloudProvider {
+                        provider: "batch".to_string(),
+                        message: format!(
+                            "Compute environment {} is INVALID; check its status reason in the Batch console",
+                            name
+                        ),
+                        source: None,
+                    })
+                }
+                _ => {}
+            }
+        }
+        if started.elapsed() >= COMPUTE_ENVIRONMENT_TIMEOUT {
+            return Err(TrainctlError::CloudProvider {
+                provider: "batch".to_string(),
+                message: format!("Compute environment {} did not become VALID", name),
+                source: None,
+            });
+        }
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+}
@@ -0,0 +1,1064 @@
+//! Dataset cache tiering on training instances
+//!
+//! `runctl cache get <key>...` makes dataset objects local and prints their
+//! paths, so a data loader can read shards that don't all fit on local disk.
+//! Objects live in one of two tiers:
+//!
+//! - **scratch**: instance-store NVMe (`[cache] scratch_dir`), fastest, lost on stop
+//! - **ebs**: an EBS volume (`[cache] ebs_dir`), survives stop/start
+//!
+//! A miss fetches the object from `[cache] origin` (an S3 prefix, or a
+//! directory such as an EFS mount) into scratch. When scratch is full its
+//! least recently used objects are demoted to EBS; when EBS is full its least
+//! recently used objects are dropped, since the origin still has them. An EBS
+//! hit moves back to scratch only if that evicts nothing, so repeated epochs
+//! over a dataset larger than scratch read the overflow from EBS instead of
+//! shuffling it between tiers, and hit every time once both tiers together
+//! hold the dataset.
+//!
+//! The index (entries, tier capacities and hit counters) is
+//! `<ebs_dir>/.runctl-cache.json`, guarded by a file lock so data loader
+//! workers can call `cache get` concurrently; downloads run outside the lock.
+//! `runctl agent` includes the index summary in its status document, and
+//! `runctl cache status <instance-id>` reads it from there or over SSM.
+
+use crate::config::{CacheConfig, Config};
+use crate::error::{Result, TrainctlError};
+use crate::retry::{ExponentialBackoffPolicy, RetryPolicy};
+use chrono::{DateTime, Utc};
+use clap::Subcommand;
+use comfy_table::Table;
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use tracing::{debug, info};
+
+#[cfg(feature = "aws")]
+use aws_sdk_s3::Client as S3Client;
+
+/// Index file in the EBS tier directory
+pub const INDEX_NAME: &str = ".runctl-cache.json";
+
+const LOCK_NAME: &str = ".runctl-cache.lock";
+
+/// Downloads in progress, inside each tier directory
+const PARTIAL_DIR: &str = ".partial";
+
+/// Share of a disk's free space a tier may use when its size isn't set
+const DEFAULT_FILL_PERCENT: u64 = 90;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum CacheCommands {
+    /// Make objects local (fetching on a miss) and print their paths
+    ///
+    /// Keys are paths relative to `[cache] origin`. Prints one local path
+    /// per key, in order.
+    ///
+    /// Examples:
+    ///   runctl cache get train/shard-0001.tar train/shard-0002.tar
+    ///   runctl cache get --origin s3://datasets/imagenet val/shard-0000.tar
+    Get {
+        /// Object keys relative to the origin
+        #[arg(value_name = "KEY", required = true)]
+        keys: Vec<String>,
+        /// Origin to fetch from (overrides `[cache] origin`)
+        #[arg(long, value_name = "S3_PATH|DIR")]
+        origin: Option<String>,
+    },
+    /// Show tier usage, hit rate and evictions
+    ///
+    /// With an instance ID, reads the summary `runctl agent` reports, or the
+    /// index over SSM; without one, reads this machine's cache.
+    ///
+    /// Examples:
+    ///   runctl cache status i-1234567890abcdef0
+    ///   runctl cache status
+    Status {
+        /// EC2 instance ID (default: this machine)
+        #[arg(value_name = "INSTANCE_ID")]
+        instance_id: Option<String>,
+    },
+    /// Delete every cached object and reset the index
+    Clear {
+        /// Skip the confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+/// Cache tier, fastest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tier {
+    Scratch,
+    Ebs,
+}
+
+impl std::fmt::Display for Tier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Tier::Scratch => write!(f, "scratch"),
+            Tier::Ebs => write!(f, "ebs"),
+        }
+    }
+}
+
+/// Where a `get` found the object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HitSource {
+    Scratch,
+    Ebs,
+    Origin,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub tier: Tier,
+    pub size_bytes: u64,
+    pub last_access: DateTime<Utc>,
+    pub hits: u64,
+}
+
+/// Counters since the index was created (or cleared)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheStats {
+    pub scratch_hits: u64,
+    pub ebs_hits: u64,
+    pub misses: u64,
+    /// EBS objects moved to scratch
+    pub promotions: u64,
+    /// Scratch objects moved to EBS to make room
+    pub demotions: u64,
+    /// Objects dropped from EBS (or too big for it)
+    pub evictions: u64,
+    pub bytes_fetched: u64,
+}
+
+impl CacheStats {
+    /// Share of gets served from a tier, once there has been one
+    pub fn hit_rate(&self) -> Option<f64> {
+        let hits = self.scratch_hits + self.ebs_hits;
+        let total = hits + self.misses;
+        (total > 0).then(|| hits as f64 / total as f64)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TierUsage {
+    pub tier: Tier,
+    pub dir: PathBuf,
+    pub capacity_bytes: u64,
+    pub used_bytes: u64,
+    pub objects: usize,
+}
+
+/// What `cache status` and the agent report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheSummary {
+    pub origin: String,
+    pub tiers: Vec<TierUsage>,
+    pub stats: CacheStats,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct TierDir {
+    dir: PathBuf,
+    /// Zero when the tier is unavailable
+    capacity_bytes: u64,
+}
+
+/// A file operation that makes room in a tier
+#[derive(Debug, Clone, PartialEq)]
+enum Move {
+    /// Scratch to EBS
+    Demote(String),
+    /// Delete from the tier
+    Evict(String, Tier),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheIndex {
+    origin: String,
+    scratch: TierDir,
+    ebs: TierDir,
+    entries: BTreeMap<String, CacheEntry>,
+    stats: CacheStats,
+    updated_at: DateTime<Utc>,
+}
+
+impl CacheIndex {
+    fn new(origin: &str, scratch: TierDir, ebs: TierDir) -> Self {
+        Self {
+            origin: origin.to_string(),
+            scratch,
+            ebs,
+            entries: BTreeMap::new(),
+            stats: CacheStats::default(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn tier_dir(&self, tier: Tier) -> &TierDir {
+        match tier {
+            Tier::Scratch => &self.scratch,
+            Tier::Ebs => &self.ebs,
+        }
+    }
+
+    fn used(&self, tier: Tier) -> u64 {
+        self.entries
+            .values()
+            .filter(|e| e.tier == tier)
+            .map(|e| e.size_bytes)
+            .sum()
+    }
+
+    fn fits(&self, tier: Tier, size: u64) -> bool {
+        self.used(tier) + size <= self.tier_dir(tier).capacity_bytes
+    }
+
+    /// Least recently used object in `tier` other than `keep`
+    fn lru(&self, tier: Tier, keep: &str) -> Option<String> {
+        self.entries
+            .iter()
+            .filter(|(key, e)| e.tier == tier && key.as_str() != keep)
+            .min_by_key(|(_, e)| e.last_access)
+            .map(|(key, _)| key.clone())
+    }
+
+    /// Free `size` bytes in `tier`, updating the index; returns the file
+    /// operations to apply, in order
+    ///
+    /// Scratch victims are demoted to EBS (evicting there as needed), EBS
+    /// victims are dropped. `size` must not exceed the tier's capacity.
+    fn make_room(&mut self, tier: Tier, size: u64, keep: &str) -> Vec<Move> {
+        let mut moves = Vec::new();
+        while !self.fits(tier, size) {
+            let Some(victim) = self.lru(tier, keep) else {
+                break;
+            };
+            let victim_size = self.entries[&victim].size_bytes;
+            if tier == Tier::Scratch && victim_size <= self.ebs.capacity_bytes {
+                moves.extend(self.make_room(Tier::Ebs, victim_size, keep));
+                if let Some(entry) = self.entries.get_mut(&victim) {
+                    entry.tier = Tier::Ebs;
+                }
+                self.stats.demotions += 1;
+                moves.push(Move::Demote(victim));
+            } else {
+                self.entries.remove(&victim);
+                self.stats.evictions += 1;
+                moves.push(Move::Evict(victim, tier));
+            }
+        }
+        moves
+    }
+
+    /// Reserve room for a new object: scratch if it fits there at all, else EBS
+    ///
+    /// None when the object is larger than both tiers.
+    fn place(&mut self, key: &str, size: u64, now: DateTime<Utc>) -> Option<(Tier, Vec<Move>)> {
+        // Another process is already fetching it
+        if let Some(entry) = self.entries.get(key) {
+            return Some((entry.tier, Vec::new()));
+        }
+        let tier = if size <= self.scratch.capacity_bytes {
+            Tier::Scratch
+        } else if size <= self.ebs.capacity_bytes {
+            Tier::Ebs
+        } else {
+            return None;
+        };
+        let moves = self.make_room(tier, size, key);
+        self.entries.insert(
+            key.to_string(),
+            CacheEntry {
+                tier,
+                size_bytes: size,
+                last_access: now,
+                hits: 0,
+            },
+        );
+        Some((tier, moves))
+    }
+
+    /// Record a hit; an EBS object moves to scratch if that evicts nothing
+    ///
+    /// Returns the tier the object was found in and whether it was promoted.
+    fn hit(&mut self, key: &str, now: DateTime<Utc>) -> Option<(Tier, bool)> {
+        let entry = self.entries.get(key)?.clone();
+        let found = entry.tier;
+        let promote = found == Tier::Ebs && self.fits(Tier::Scratch, entry.size_bytes);
+        match found {
+            Tier::Scratch => self.stats.scratch_hits += 1,
+            Tier::Ebs => self.stats.ebs_hits += 1,
+        }
+        if promote {
+            self.stats.promotions += 1;
+        }
+        let entry = self.entries.get_mut(key)?;
+        entry.last_access = now;
+        entry.hits += 1;
+        if promote {
+            entry.tier = Tier::Scratch;
+        }
+        Some((found, promote))
+    }
+
+    fn summary(&self) -> CacheSummary {
+        let usage = |tier: Tier| TierUsage {
+            tier,
+            dir: self.tier_dir(tier).dir.clone(),
+            capacity_bytes: self.tier_dir(tier).capacity_bytes,
+            used_bytes: self.used(tier),
+            objects: self.entries.values().filter(|e| e.tier == tier).count(),
+        };
+        CacheSummary {
+            origin: self.origin.clone(),
+            tiers: vec![usage(Tier::Scratch), usage(Tier::Ebs)],
+            stats: self.stats.clone(),
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+/// Rejects keys that would escape the tier directories
+fn validate_key(key: &str) -> Result<()> {
+    let path = Path::new(key);
+    let inside = !key.is_empty()
+        && path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if !inside || key.starts_with(PARTIAL_DIR) || key.starts_with(".runctl-cache") {
+        return Err(TrainctlError::Validation {
+            field: "key".to_string(),
+            reason: format!("'{}' is not a relative object path", key),
+        });
+    }
+    Ok(())
+}
+
+fn expand(path: &Path) -> PathBuf {
+    PathBuf::from(shellexpand::tilde(&path.to_string_lossy()).into_owned())
+}
+
+fn remove_if_exists(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Rename, or copy and delete when `to` is on another filesystem
+fn move_file(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        crate::utils::ensure_dir(parent)?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to)?;
+    fs::remove_file(from)?;
+    Ok(())
+}
+
+/// Where objects come from on a miss
+enum Origin {
+    Local(PathBuf),
+    #[cfg(feature = "aws")]
+    S3 {
+        client: S3Client,
+        bucket: String,
+        /// Key prefix, empty or ending in `/`
+        prefix: String,
+    },
+}
+
+impl Origin {
+    async fn open(location: &str) -> Result<Self> {
+        if location.starts_with("s3://") {
+            #[cfg(feature = "aws")]
+            {
+                let (bucket, prefix) = crate::data_transfer::parse_s3_path(location)?;
+                let prefix = if prefix.is_empty() || prefix.ends_with('/') {
+                    prefix
+                } else {
+                    format!("{}/", prefix)
+                };
+                let aws_config = crate::aws_utils::shared_sdk_config().await;
+                return Ok(Origin::S3 {
+                    client: S3Client::new(&aws_config),
+                    bucket,
+                    prefix,
+                });
+            }
+            #[cfg(not(feature = "aws"))]
+            return Err(crate::error::ConfigError::FeatureNotCompiled("aws".to_string()).into());
+        }
+        let dir = expand(Path::new(location));
+        if !dir.is_dir() {
+            return Err(TrainctlError::ResourceNotFound {
+                resource_type: "cache origin".to_string(),
+                resource_id: location.to_string(),
+            });
+        }
+        Ok(Origin::Local(dir))
+    }
+
+    async fn size(&self, key: &str) -> Result<u64> {
+        match self {
+            Origin::Local(dir) => match fs::metadata(dir.join(key)) {
+                Ok(metadata) if metadata.is_file() => Ok(metadata.len()),
+                _ => Err(TrainctlError::ResourceNotFound {
+                    resource_type: "cache object".to_string(),
+                    resource_id: dir.join(key).display().to_string(),
+                }),
+            },
+            #[cfg(feature = "aws")]
+            Origin::S3 {
+                client,
+                bucket,
+                prefix,
+            } => {
+                let key = format!("{}{}", prefix, key);
+                let head = client
+                    .head_object()
+                    .bucket(bucket)
+                    .key(&key)
+                    .send()
+                    .await
+                    .map_err(|e| {
+                        if e.as_service_error().is_some_and(|e| e.is_not_found()) {
+                            TrainctlError::ResourceNotFound {
+                                resource_type: "cache object".to_string(),
+                                resource_id: format!("s3://{}/{}", bucket, key),
+                            }
+                        } else {
+                            TrainctlError::S3(format!(
+                                "Failed to stat s3://{}/{}: {}",
+                                bucket, key, e
+                            ))
+                        }
+                    })?;
+                Ok(head.content_length().unwrap_or(0).max(0) as u64)
+            }
+        }
+    }
+
+    /// Copy `key` to `dest`; failures are retryable
+    async fn download(&self, key: &str, dest: &Path) -> Result<()> {
+        match self {
+            Origin::Local(dir) => {
+                fs::copy(dir.join(key), dest)?;
+                Ok(())
+            }
+            #[cfg(feature = "aws")]
+            Origin::S3 {
+                client,
+                bucket,
+                prefix,
+            } => {
+                use tokio::io::AsyncWriteExt;
+
+                let key = format!("{}{}", prefix, key);
+                let read_error = |e: String| TrainctlError::CloudProvider {
+                    provider: "aws".to_string(),
+                    message: format!("Failed to download s3://{}/{}: {}", bucket, key, e),
+                    source: None,
+                };
+                let response = client
+                    .get_object()
+                    .bucket(bucket)
+                    .key(&key)
+                    .send()
+                    .await
+                    .map_err(|e| read_error(e.to_string()))?;
+                let mut body = response.body;
+                let mut file = tokio::fs::File::create(dest).await?;
+                while let Some(bytes) = body
+                    .try_next()
+                    .await
+                    .map_err(|e| read_error(e.to_string()))?
+                {
+                    file.write_all(&bytes).await?;
+                }
+                file.flush().await?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A `get` result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedObject {
+    pub key: String,
+    pub path: PathBuf,
+    pub source: HitSource,
+}
+
+/// Exclusive lock on the index, released on drop
+struct IndexLock {
+    _file: fs::File,
+}
+
+/// The cache on this machine
+pub struct CacheManager {
+    origin_name: String,
+    origin: Option<Origin>,
+    scratch: TierDir,
+    ebs: TierDir,
+    scratch_size: Option<u64>,
+    ebs_size: Option<u64>,
+}
+
+impl CacheManager {
+    /// Open the cache described by `config`; `origin` overrides `[cache] origin`
+    pub async fn open(config: &CacheConfig, origin: Option<&str>) -> Result<Self> {
+        let location = origin
+            .map(str::to_string)
+            .or_else(|| config.origin.clone())
+            .ok_or_else(|| {
+                crate::error::ConfigError::MissingField(
+                    "cache.origin (or --origin) to fetch misses from".to_string(),
+                )
+            })?;
+        let mut cache = Self::inspect(config)?;
+        cache.origin = Some(Origin::open(&location).await?);
+        cache.origin_name = location;
+        Ok(cache)
+    }
+
+    /// The cache described by `config`, for `summary` and `clear` only
+    pub fn inspect(config: &CacheConfig) -> Result<Self> {
+        let parse = |field: &str, value: &Option<String>| {
+            value
+                .as_deref()
+                .map(crate::units::parse_size)
+                .transpose()
+                .map_err(|reason| TrainctlError::Validation {
+                    field: format!("cache.{}", field),
+                    reason,
+                })
+        };
+        let ebs_dir = expand(&config.ebs_dir);
+        crate::utils::ensure_dir(&ebs_dir)?;
+        Ok(Self {
+            origin: None,
+            origin_name: String::new(),
+            scratch: TierDir {
+                dir: expand(&config.scratch_dir),
+                capacity_bytes: 0,
+            },
+            ebs: TierDir {
+                dir: ebs_dir,
+                capacity_bytes: 0,
+            },
+            scratch_size: parse("scratch_size", &config.scratch_size)?,
+            ebs_size: parse("ebs_size", &config.ebs_size)?,
+        })
+    }
+
+    fn path(&self, tier: Tier, key: &str) -> PathBuf {
+        match tier {
+            Tier::Scratch => self.scratch.dir.join(key),
+            Tier::Ebs => self.ebs.dir.join(key),
+        }
+    }
+
+    fn lock(&self) -> Result<IndexLock> {
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(self.ebs.dir.join(LOCK_NAME))?;
+        file.lock_exclusive()?;
+        Ok(IndexLock { _file: file })
+    }
+
+    /// Capacity of a tier: the configured size, else a share of the free
+    /// space plus what the cache already holds there
+    fn capacity(dir: &Path, configured: Option<u64>, used: u64, create: bool) -> u64 {
+        if create && fs::create_dir_all(dir).is_err() {
+            return 0;
+        }
+        if !dir.is_dir() {
+            return 0;
+        }
+        configured.unwrap_or_else(|| {
+            fs2::available_space(dir)
+                .map(|free| (free + used) / 100 * DEFAULT_FILL_PERCENT)
+                .unwrap_or(0)
+        })
+    }
+
+    /// Load the index (under the lock), refreshing tier capacities
+    fn load(&self) -> Result<CacheIndex> {
+        let path = self.ebs.dir.join(INDEX_NAME);
+        let mut index = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str::<CacheIndex>(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                CacheIndex::new(&self.origin_name, self.scratch.clone(), self.ebs.clone())
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if !self.origin_name.is_empty() && index.origin != self.origin_name {
+            if index.entries.is_empty() {
+                index.origin = self.origin_name.clone();
+            } else {
+                return Err(TrainctlError::Validation {
+                    field: "origin".to_string(),
+                    reason: format!(
+                        "the cache holds objects from {}; run `runctl cache clear` to switch to {}",
+                        index.origin, self.origin_name
+                    ),
+                });
+            }
+        }
+        // Scratch is only used on its mount point, never created on the root volume
+        let scratch_mounted = self.scratch.dir.parent().is_some_and(Path::is_dir);
+        index.scratch = TierDir {
+            dir: self.scratch.dir.clone(),
+            capacity_bytes: Self::capacity(
+                &self.scratch.dir,
+                self.scratch_size,
+                index.used(Tier::Scratch),
+                scratch_mounted,
+            ),
+        };
+        index.ebs = TierDir {
+            dir: self.ebs.dir.clone(),
+            capacity_bytes: Self::capacity(
+                &self.ebs.dir,
+                self.ebs_size,
+                index.used(Tier::Ebs),
+                true,
+            ),
+        };
+        Ok(index)
+    }
+
+    fn save(&self, index: &mut CacheIndex) -> Result<()> {
+        index.updated_at = Utc::now();
+        let path = self.ebs.dir.join(INDEX_NAME);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(index)?)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    fn apply(&self, moves: &[Move]) -> Result<()> {
+        for m in moves {
+            match m {
+                Move::Demote(key) => {
+                    debug!("Demoting {} to EBS", key);
+                    move_file(&self.path(Tier::Scratch, key), &self.path(Tier::Ebs, key))?;
+                }
+                Move::Evict(key, tier) => {
+                    debug!("Evicting {} from {}", key, tier);
+                    remove_if_exists(&self.path(*tier, key))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Make `key` local, fetching it from the origin on a miss
+    pub async fn get(&self, key: &str) -> Result<CachedObject> {
+        validate_key(key)?;
+        {
+            let _lock = self.lock()?;
+            let mut index = self.load()?;
+            if let Some(entry) = index.entries.get(key).cloned() {
+                if self.path(entry.tier, key).is_file() {
+                    let (found, promoted) =
+                        index.hit(key, Utc::now()).unwrap_or((entry.tier, false));
+                    if promoted {
+                        move_file(&self.path(Tier::Ebs, key), &self.path(Tier::Scratch, key))?;
+                    }
+                    self.save(&mut index)?;
+                    return Ok(CachedObject {
+                        key: key.to_string(),
+                        path: self.path(index.entries[key].tier, key),
+                        source: match found {
+                            Tier::Scratch => HitSource::Scratch,
+                            Tier::Ebs => HitSource::Ebs,
+                        },
+                    });
+                }
+                // Deleted behind our back, or still being fetched by another process
+                if entry.hits > 0 {
+                    index.entries.remove(key);
+                    self.save(&mut index)?;
+                }
+            }
+        }
+
+        let origin = self
+            .origin
+            .as_ref()
+            .ok_or_else(|| crate::error::ConfigError::MissingField("cache.origin".to_string()))?;
+        let policy = ExponentialBackoffPolicy::new(4);
+        let size = policy.execute_with_retry(|| origin.size(key)).await?;
+
+        // Reserve room, then fetch without holding the lock
+        let tier = {
+            let _lock = self.lock()?;
+            let mut index = self.load()?;
+            let (tier, moves) =
+                index
+                    .place(key, size, Utc::now())
+                    .ok_or_else(|| TrainctlError::Validation {
+                        field: "key".to_string(),
+                        reason: format!(
+                            "'{}' ({} bytes) is larger than every cache tier",
+                            key, size
+                        ),
+                    })?;
+            self.apply(&moves)?;
+            index.stats.misses += 1;
+            self.save(&mut index)?;
+            tier
+        };
+
+        let partial_dir = self.path(tier, PARTIAL_DIR);
+        crate::utils::ensure_dir(&partial_dir)?;
+        let partial = partial_dir.join(uuid::Uuid::new_v4().to_string());
+        let fetched = policy
+            .execute_with_retry(|| origin.download(key, &partial))
+            .await;
+
+        let _lock = self.lock()?;
+        let mut index = self.load()?;
+        let path = self.path(tier, key);
+        let result = fetched.and_then(|()| move_file(&partial, &path));
+        match result {
+            Ok(()) => {
+                index.stats.bytes_fetched += size;
+                self.save(&mut index)?;
+                info!("Cached {} in {} ({} bytes)", key, tier, size);
+                Ok(CachedObject {
+                    key: key.to_string(),
+                    path,
+                    source: HitSource::Origin,
+                })
+            }
+            Err(e) => {
+                let _ = fs::remove_file(&partial);
+                if !path.is_file() {
+                    index.entries.remove(key);
+                    self.save(&mut index)?;
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Current index summary
+    pub fn summary(&self) -> Result<CacheSummary> {
+        let _lock = self.lock()?;
+        Ok(self.load()?.summary())
+    }
+
+    /// Delete every cached object and reset the index
+    pub fn clear(&self) -> Result<usize> {
+        let _lock = self.lock()?;
+        let index = self.load()?;
+        let count = index.entries.len();
+        for (key, entry) in &index.entries {
+            remove_if_exists(&self.path(entry.tier, key))?;
+        }
+        for tier in [Tier::Scratch, Tier::Ebs] {
+            let _ = fs::remove_dir_all(self.path(tier, PARTIAL_DIR));
+        }
+        let mut index = CacheIndex::new(&self.origin_name, index.scratch, index.ebs);
+        self.save(&mut index)?;
+        Ok(count)
+    }
+}
+
+/// Summary from the index in `ebs_dir`, if there is a cache there
+///
+/// Used by `runctl agent`; reads without the lock since the index is
+/// replaced atomically.
+pub fn read_summary(ebs_dir: &Path) -> Option<CacheSummary> {
+    let content = fs::read_to_string(expand(ebs_dir).join(INDEX_NAME)).ok()?;
+    serde_json::from_str::<CacheIndex>(&content)
+        .ok()
+        .map(|index| index.summary())
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const GIB: f64 = (1u64 << 30) as f64;
+    const MIB: f64 = (1u64 << 20) as f64;
+    if bytes as f64 >= GIB {
+        format!("{:.1} GiB", bytes as f64 / GIB)
+    } else {
+        format!("{:.1} MiB", bytes as f64 / MIB)
+    }
+}
+
+/// Print a summary as a table (or JSON)
+pub fn print_summary(summary: &CacheSummary, output_format: &str) -> Result<()> {
+    if output_format == "json" {
+        println!("{}", serde_json::to_string_pretty(summary)?);
+        return Ok(());
+    }
+    println!("Origin: {}", summary.origin);
+    let mut table = Table::new();
+    table.set_header(vec!["Tier", "Directory", "Used", "Capacity", "Objects"]);
+    for tier in &summary.tiers {
+        let capacity = if tier.capacity_bytes == 0 {
+            "unavailable".to_string()
+        } else {
+            format_bytes(tier.capacity_bytes)
+        };
+        table.add_row(vec![
+            tier.tier.to_string(),
+            tier.dir.display().to_string(),
+            format_bytes(tier.used_bytes),
+            capacity,
+            tier.objects.to_string(),
+        ]);
+    }
+    println!("{}", table);
+    let stats = &summary.stats;
+    match stats.hit_rate() {
+        Some(rate) => println!(
+            "Hit rate: {:.1}% ({} scratch, {} ebs, {} misses)",
+            rate * 100.0,
+            stats.scratch_hits,
+            stats.ebs_hits,
+            stats.misses
+        ),
+        None => println!("Hit rate: no reads yet"),
+    }
+    println!(
+        "Promotions: {}, demotions: {}, evictions: {}, fetched: {}",
+        stats.promotions,
+        stats.demotions,
+        stats.evictions,
+        format_bytes(stats.bytes_fetched)
+    );
+    println!(
+        "Updated: {}",
+        summary.updated_at.format("%Y-%m-%d %H:%M:%S UTC")
+    );
+    Ok(())
+}
+
+/// Cache summary of a remote instance: the agent's report, else the index over SSM
+#[cfg(feature = "aws")]
+async fn remote_summary(instance_id: &str, config: &Config) -> Result<CacheSummary> {
+    let aws_config = crate::aws_utils::shared_sdk_config().await;
+    let s3_client = S3Client::new(&aws_config);
+    if let Some(summary) = crate::agent::fetch_fresh_status(config, &s3_client, instance_id)
+        .await
+        .and_then(|status| status.cache)
+    {
+        return Ok(summary);
+    }
+
+    // SSM runs as root, so look in every user's home for the default location
+    let ebs_dir = config.cache.ebs_dir.to_string_lossy();
+    let candidates = match ebs_dir.strip_prefix("~/") {
+        Some(rest) => format!(
+            "/home/*/{rest}/{name} /root/{rest}/{name}",
+            name = INDEX_NAME
+        ),
+        None => crate::utils::shell_quote(&format!("{}/{}", ebs_dir, INDEX_NAME)),
+    };
+    let command = format!(
+        "for f in {}; do if [ -f \"$f\" ]; then cat \"$f\"; exit 0; fi; done; echo CACHE:missing",
+        candidates
+    );
+    let ssm_client = aws_sdk_ssm::Client::new(&aws_config);
+    let output = crate::aws_utils::execute_ssm_command(&ssm_client, instance_id, &command).await?;
+    if output.contains("CACHE:missing") {
+        return Err(TrainctlError::ResourceNotFound {
+            resource_type: "cache index".to_string(),
+            resource_id: format!("{} on {}", INDEX_NAME, instance_id),
+        });
+    }
+    let index: CacheIndex = serde_json::from_str(output.trim())?;
+    Ok(index.summary())
+}
+
+pub async fn handle_command(
+    cmd: CacheCommands,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    match cmd {
+        CacheCommands::Get { keys, origin } => {
+            let cache = CacheManager::open(&config.cache, origin.as_deref()).await?;
+            let mut objects = Vec::with_capacity(keys.len());
+            for key in &keys {
+                objects.push(cache.get(key).await?);
+            }
+            if output_format == "json" {
+                println!("{}", serde_json::to_string_pretty(&objects)?);
+            } else {
+                for object in &objects {
+                    println!("{}", object.path.display());
+                }
+            }
+            Ok(())
+        }
+        CacheCommands::Status { instance_id } => {
+            let summary = match instance_id {
+                #[cfg(feature = "aws")]
+                Some(instance_id) => {
+                    crate::validation::validate_instance_id(&instance_id)?;
+                    remote_summary(&instance_id, config).await?
+                }
+                #[cfg(not(feature = "aws"))]
+                Some(_) => {
+                    return Err(
+                        crate::error::ConfigError::FeatureNotCompiled("aws".to_string()).into(),
+                    )
+                }
+                None => CacheManager::inspect(&config.cache)?.summary()?,
+            };
+            print_summary(&summary, output_format)
+        }
+        CacheCommands::Clear { yes } => {
+            let cache = CacheManager::inspect(&config.cache)?;
+            let summary = cache.summary()?;
+            let objects: usize = summary.tiers.iter().map(|t| t.objects).sum();
+            if !yes && !crate::prompt::confirm(&format!("Delete {} cached objects?", objects))? {
+                println!("Cancelled");
+                return Ok(());
+            }
+            let removed = cache.clear()?;
+            if output_format == "json" {
+                println!("{}", serde_json::json!({ "removed": removed }));
+            } else {
+                println!("Removed {} cached objects", removed);
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index_with(scratch: u64, ebs: u64) -> CacheIndex {
+        CacheIndex::new(
+            "s3://datasets/x",
+            TierDir {
+                dir: PathBuf::from("/scratch"),
+                capacity_bytes: scratch,
+            },
+            TierDir {
+                dir: PathBuf::from("/ebs"),
+                capacity_bytes: ebs,
+            },
+        )
+    }
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap()
+    }
+
+    #[test]
+    fn test_lru_demotes_to_ebs_then_evicts() {
+        let mut index = index_with(20, 20);
+        for (i, key) in ["a", "b", "c", "d", "e"].iter().enumerate() {
+            index.place(key, 10, at(i as i64)).unwrap();
+        }
+        // a, b were demoted for c, d; then a was evicted from EBS for c's demotion
+        assert_eq!(index.entries["e"].tier, Tier::Scratch);
+        assert_eq!(index.entries["d"].tier, Tier::Scratch);
+        assert_eq!(index.entries["c"].tier, Tier::Ebs);
+        assert_eq!(index.entries["b"].tier, Tier::Ebs);
+        assert!(!index.entries.contains_key("a"));
+        assert_eq!(index.stats.demotions, 3);
+        assert_eq!(index.stats.evictions, 1);
+
+        // Touching b makes c the EBS victim
+        assert_eq!(index.hit("b", at(10)), Some((Tier::Ebs, false)));
+        let (tier, moves) = index.place("f", 10, at(11)).unwrap();
+        assert_eq!(tier, Tier::Scratch);
+        assert_eq!(
+            moves,
+            vec![
+                Move::Evict("c".to_string(), Tier::Ebs),
+                Move::Demote("d".to_string())
+            ]
+        );
+
+        // Too big for scratch goes straight to EBS; too big for both is refused
+        let mut index = index_with(10, 20);
+        assert_eq!(index.place("big", 15, at(0)).unwrap().0, Tier::Ebs);
+        assert!(index.place("huge", 30, at(1)).is_none());
+    }
+
+    #[test]
+    fn test_ebs_hits_promote_only_into_free_scratch() {
+        let mut index = index_with(20, 100);
+        index.place("a", 10, at(0)).unwrap();
+        index.place("b", 10, at(1)).unwrap();
+        index.place("c", 10, at(2)).unwrap();
+        assert_eq!(index.entries["a"].tier, Tier::Ebs);
+        // Scratch is full: a stays in EBS
+        assert_eq!(index.hit("a", at(3)), Some((Tier::Ebs, false)));
+        index.entries.remove("b");
+        assert_eq!(index.hit("a", at(4)), Some((Tier::Ebs, true)));
+        assert_eq!(index.entries["a"].tier, Tier::Scratch);
+        assert_eq!(index.stats.hit_rate(), Some(1.0));
+        assert_eq!(index.summary().tiers[0].used_bytes, 20);
+        assert!(validate_key("../etc/passwd").is_err());
+        assert!(validate_key("/abs").is_err());
+        assert!(validate_key(".partial/x").is_err());
+        assert!(validate_key("train/shard-0001.tar").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_epochs_over_a_dataset_larger_than_scratch_hit_after_the_first() {
+        let root = tempfile::tempdir().unwrap();
+        let origin = root.path().join("origin");
+        fs::create_dir_all(origin.join("train")).unwrap();
+        for i in 0..4 {
+            fs::write(origin.join(format!("train/{}.bin", i)), vec![i as u8; 100]).unwrap();
+        }
+        let config = CacheConfig {
+            origin: Some(origin.display().to_string()),
+            scratch_dir: root.path().join("nvme/cache"),
+            scratch_size: Some("200".to_string()),
+            ebs_dir: root.path().join("ebs"),
+            ebs_size: Some("300".to_string()),
+        };
+        // No instance store mounted: everything lands on EBS
+        let cache = CacheManager::open(&config, None).await.unwrap();
+        let object = cache.get("train/0.bin").await.unwrap();
+        assert_eq!(object.source, HitSource::Origin);
+        assert!(object.path.starts_with(root.path().join("ebs")));
+        cache.clear().unwrap();
+
+        fs::create_dir_all(root.path().join("nvme")).unwrap();
+        let cache = CacheManager::open(&config, None).await.unwrap();
+        for epoch in 0..3 {
+            for i in 0..4 {
+                let object = cache.get(&format!("train/{}.bin", i)).await.unwrap();
+                assert_eq!(fs::read(&object.path).unwrap(), vec![i as u8; 100]);
+                if epoch > 0 {
+                    assert_ne!(object.source, HitSource::Origin);
+                }
+            }
+        }
+        let summary = cache.summary().unwrap();
+        assert_eq!(summary.stats.misses, 4);
+        assert_eq!(summary.stats.scratch_hits + summary.stats.ebs_hits, 8);
+        assert_eq!(summary.tiers[0].used_bytes, 200);
+        assert_eq!(read_summary(&config.ebs_dir), Some(summary));
+    }
+}
@@ -0,0 +1,268 @@
+//! Checkpoint-at-boundary coordination before planned stops
+//!
+//! A planned stop (`aws stop`, a scheduled stop, `aws bulk stop`, budget and
+//! cleanup actions) can ask the training script to checkpoint at its next
+//! epoch or step boundary instead of sending SIGTERM in the middle of a step.
+//! The protocol is a few files in the script's working directory, next to
+//! `training.pid`:
+//!
+//! 1. At startup the script creates `checkpoint_listening` to say it honors
+//!    requests. Without it, runctl goes straight to SIGTERM.
+//! 2. runctl writes `checkpoint_request.json` (`{"id", "reason", "deadline"}`).
+//! 3. At each boundary the script checks for the request. When one is there,
+//!    it saves a checkpoint, writes `checkpoint_done.txt` with the request ID
+//!    on the first line and the checkpoint path on the second, and exits.
+//! 4. runctl waits up to `[checkpoint] stop_wait_secs` for the done file or
+//!    the process to exit, then falls back to SIGTERM and, 30 seconds later,
+//!    SIGKILL.
+//!
+//! `training/runctl_helper.py` implements the script side.
+
+use crate::config::Config;
+use crate::utils::shell_quote;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Created by the script when it handles checkpoint requests
+pub const LISTENING_FILE: &str = "checkpoint_listening";
+
+/// Written by runctl to ask for a checkpoint
+pub const REQUEST_FILE: &str = "checkpoint_request.json";
+
+/// Written by the script once the requested checkpoint is saved
+pub const DONE_FILE: &str = "checkpoint_done.txt";
+
+/// Longest wait: the stop runs as one SSM command, which is polled for
+/// about ten minutes including the SIGTERM grace period
+pub const MAX_STOP_WAIT_SECS: u64 = 480;
+
+/// Seconds between SIGTERM and SIGKILL
+const SIGTERM_GRACE_SECS: u64 = 30;
+
+/// What `checkpoint_request.json` holds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointRequest {
+    pub id: String,
+    /// Why training is being stopped, e.g. `stop`, `schedule`, `budget`
+    pub reason: String,
+    /// runctl sends SIGTERM after this
+    pub deadline: DateTime<Utc>,
+}
+
+/// How a graceful stop went, parsed from the stop command's output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopOutcome {
+    NoTraining,
+    /// The script saved a checkpoint at a boundary (path, if it reported one)
+    Checkpointed(Option<String>),
+    /// The script exited after SIGTERM
+    Terminated,
+    /// The script ignored SIGTERM and was killed
+    Killed,
+}
+
+/// The stop wait from `[checkpoint] stop_wait_secs`, capped at `MAX_STOP_WAIT_SECS`
+pub fn stop_wait_secs(config: &Config) -> u64 {
+    let wait = config.checkpoint.stop_wait_secs;
+    if wait > MAX_STOP_WAIT_SECS {
+        warn!(
+            "[checkpoint] stop_wait_secs = {} is over the {}s limit; using {}s",
+            wait, MAX_STOP_WAIT_SECS, MAX_STOP_WAIT_SECS
+        );
+        return MAX_STOP_WAIT_SECS;
+    }
+    wait
+}
+
+/// Shell script that stops training on an instance, asking for a checkpoint first
+///
+/// Finds the training process from `training.pid` in the working directory
+/// or a project under `/home` or `/root`, falling back to `pgrep`. The
+/// request is only sent when the script listens (see the module docs) and
+/// `wait_secs` is non-zero. Prints markers read by `parse_stop_output`.
+pub fn stop_command(wait_secs: u64, reason: &str) -> String {
+    let request = CheckpointRequest {
+        id: uuid::Uuid::new_v4().to_string(),
+        reason: reason.to_string(),
+        deadline: Utc::now() + chrono::Duration::seconds(wait_secs as i64),
+    };
+    let request_json = serde_json::to_string(&request).unwrap_or_default();
+    format!(
+        r#"
+PID=""
+DIR=""
+for f in training.pid /home/*/*/training.pid /root/*/training.pid; do
+    [ -f "$f" ] || continue
+    p=$(cat "$f" 2>/dev/null)
+    if [ -n "$p" ] && ps -p "$p" > /dev/null 2>&1; then
+        PID=$p
+        DIR=$(dirname "$f")
+        break
+    fi
+done
+if [ -z "$PID" ]; then
+    PID=$(pgrep -f "python.*train\|python.*training\|python.*main.py" | head -1)
+fi
+if [ -z "$PID" ]; then
+    echo "NO_TRAINING"
+    exit 0
+fi
+echo "TRAINING_RUNNING:$PID"
+if [ -n "$DIR" ] && [ {wait} -gt 0 ] && [ -f "$DIR/{listening}" ]; then
+    rm -f "$DIR/{done}"
+    printf '%s\n' {request} > "$DIR/{request_file}"
+    echo "CHECKPOINT_REQUESTED"
+    for i in $(seq 1 {wait}); do
+        if [ "$(head -n 1 "$DIR/{done}" 2>/dev/null)" = {id} ]; then break; fi
+        if ! ps -p $PID > /dev/null 2>&1; then break; fi
+        sleep 1
+    done
+    if [ "$(head -n 1 "$DIR/{done}" 2>/dev/null)" = {id} ]; then
+        echo "CHECKPOINT_SAVED:$(sed -n 2p "$DIR/{done}")"
+        # Let the script exit on its own before signalling it
+        for i in $(seq 1 {grace}); do
+            if ! ps -p $PID > /dev/null 2>&1; then break; fi
+            sleep 1
+        done
+    else
+        echo "CHECKPOINT_TIMEOUT"
+    fi
+    rm -f "$DIR/{request_file}"
+fi
+if ps -p $PID > /dev/null 2>&1; then
+    kill -TERM $PID 2>/dev/null || true
+    for i in $(seq 1 {grace}); do
+        if ! ps -p $PID > /dev/null 2>&1; then break; fi
+        sleep 1
+    done
+fi
+if ps -p $PID > /dev/null 2>&1; then
+    kill -9 $PID 2>/dev/null || true
+    echo "TRAINING_FORCE_STOPPED"
+else
+    echo "TRAINING_STOPPED_GRACEFULLY"
+fi
+"#,
+        wait = wait_secs,
+        grace = SIGTERM_GRACE_SECS,
+        listening = LISTENING_FILE,
+        done = DONE_FILE,
+        request_file = REQUEST_FILE,
+        request = shell_quote(&request_json),
+        id = shell_quote(&request.id),
+    )
+}
+
+/// Outcome of `stop_command` from its output
+pub fn parse_stop_output(output: &str) -> StopOutcome {
+    if let Some(line) = output
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("CHECKPOINT_SAVED:"))
+    {
+        let path = line.trim();
+        return StopOutcome::Checkpointed((!path.is_empty()).then(|| path.to_string()));
+    }
+    if output.contains("TRAINING_FORCE_STOPPED") {
+        StopOutcome::Killed
+    } else if output.contains("TRAINING_RUNNING") {
+        StopOutcome::Terminated
+    } else {
+        StopOutcome::NoTraining
+    }
+}
+
+impl std::fmt::Display for StopOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StopOutcome::NoTraining => write!(f, "no training running"),
+            StopOutcome::Checkpointed(Some(path)) => {
+                write!(f, "checkpointed at a boundary ({})", path)
+            }
+            StopOutcome::Checkpointed(None) => write!(f, "checkpointed at a boundary"),
+            StopOutcome::Terminated => write!(f, "stopped with SIGTERM"),
+            StopOutcome::Killed => write!(f, "killed after ignoring SIGTERM"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn test_parses_stop_markers() {
+        assert_eq!(parse_stop_output("NO_TRAINING\n"), StopOutcome::NoTraining);
+        assert_eq!(
+            parse_stop_output(
+                "TRAINING_RUNNING:42\nCHECKPOINT_REQUESTED\nCHECKPOINT_SAVED:ckpt/epoch_3.pt\nTRAINING_STOPPED_GRACEFULLY\n"
+            ),
+            StopOutcome::Checkpointed(Some("ckpt/epoch_3.pt".to_string()))
+        );
+        assert_eq!(
+            parse_stop_output(
+                "TRAINING_RUNNING:42\nCHECKPOINT_SAVED:\nTRAINING_STOPPED_GRACEFULLY"
+            ),
+            StopOutcome::Checkpointed(None)
+        );
+        assert_eq!(
+            parse_stop_output(
+                "TRAINING_RUNNING:42\nCHECKPOINT_TIMEOUT\nTRAINING_STOPPED_GRACEFULLY"
+            ),
+            StopOutcome::Terminated
+        );
+        assert_eq!(
+            parse_stop_output("TRAINING_RUNNING:42\nTRAINING_FORCE_STOPPED"),
+            StopOutcome::Killed
+        );
+    }
+
+    #[test]
+    fn test_listening_script_checkpoints_at_the_next_boundary() {
+        let dir = tempfile::tempdir().unwrap();
+        // A "training loop" that checks for a request once per step
+        let script = format!(
+            "touch {listening}; \
+             while true; do \
+               if [ -f {request} ]; then \
+                 id=$(sed 's/.*\"id\":\"\\([^\"]*\\)\".*/\\1/' {request}); \
+                 printf '%s\\nckpt/step.pt\\n' \"$id\" > {done}; exit 0; \
+               fi; sleep 0.1; \
+             done",
+            listening = LISTENING_FILE,
+            request = REQUEST_FILE,
+            done = DONE_FILE,
+        );
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(&script)
+            .current_dir(dir.path())
+            .spawn()
+            .unwrap();
+        std::fs::write(dir.path().join("training.pid"), child.id().to_string()).unwrap();
+        // Reap it as soon as it exits, so `ps -p` stops seeing it
+        let waiter = std::thread::spawn(move || {
+            let mut child = child;
+            child.wait()
+        });
+        while !dir.path().join(LISTENING_FILE).exists() {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(stop_command(10, "stop"))
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        waiter.join().unwrap().unwrap();
+        let output = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(
+            parse_stop_output(&output),
+            StopOutcome::Checkpointed(Some("ckpt/step.pt".to_string()))
+        );
+        assert!(output.contains("TRAINING_STOPPED_GRACEFULLY"));
+        assert!(!dir.path().join(REQUEST_FILE).exists());
+    }
+}
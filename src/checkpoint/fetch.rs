@@ -0,0 +1,773 @@
+//! Partial checkpoint fetch for multi-node resume
+//!
+//! A sharded checkpoint is usually far more than one node needs: with
+//! tensor or pipeline parallelism each node loads its own shards, and a
+//! loader that seeks (safetensors, memory-mapped shards) only touches the
+//! byte ranges of its tensors. `runctl checkpoint fetch` reads a manifest
+//! that maps node ranks to files and byte ranges, and downloads only this
+//! node's parts (plus the shared ones), split into chunks fetched in
+//! parallel with ranged reads.
+//!
+//! ## Manifest
+//!
+//! JSON, passed with `--manifest` or stored next to the checkpoint as
+//! `runctl-fetch.json`. A part is a file name, or a file with a byte range:
+//!
+//! ```json
+//! {
+//!   "shared": ["model.safetensors.index.json"],
+//!   "nodes": [
+//!     { "rank": 0, "parts": ["model-00001-of-00002.safetensors"] },
+//!     { "rank": 1, "parts": [
+//!       "model-00002-of-00002.safetensors",
+//!       { "file": "optimizer.bin", "offset": 1073741824, "length": 1073741824 }
+//!     ] }
+//!   ]
+//! }
+//! ```
+//!
+//! Byte ranges are written at their offsets in a local file of the full
+//! size, so offsets in the checkpoint stay valid; the rest of the file is
+//! left empty (sparse on most filesystems).
+//!
+//! ## Resume
+//!
+//! Finished chunks are recorded in `.runctl-fetch.json` in the destination
+//! directory. Rerunning the fetch skips them, unless the source or a file's
+//! size changed.
+
+use crate::error::{Result, TrainctlError};
+use crate::retry::{ExponentialBackoffPolicy, RetryPolicy};
+#[cfg(feature = "aws")]
+use aws_sdk_s3::Client as S3Client;
+use futures::StreamExt;
+use indicatif::ProgressBar;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::Instant;
+use tracing::{debug, info};
+
+/// Manifest looked for in the source when `--manifest` isn't given
+pub const MANIFEST_NAME: &str = "runctl-fetch.json";
+
+/// Progress of a fetch, kept in the destination directory
+const STATE_NAME: &str = ".runctl-fetch.json";
+
+/// Size of the ranged reads a fetch is split into
+pub const DEFAULT_CHUNK_SIZE: u64 = 16 << 20;
+
+/// A file, or a byte range of one, that a node needs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FetchPart {
+    File(String),
+    Range {
+        file: String,
+        offset: u64,
+        length: u64,
+    },
+}
+
+impl FetchPart {
+    pub fn file(&self) -> &str {
+        match self {
+            FetchPart::File(file) | FetchPart::Range { file, .. } => file,
+        }
+    }
+}
+
+/// Parts of the checkpoint one node rank loads
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeParts {
+    pub rank: u32,
+    pub parts: Vec<FetchPart>,
+}
+
+/// Which parts of a checkpoint each node needs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FetchManifest {
+    /// Parts every node needs (index and metadata files)
+    #[serde(default)]
+    pub shared: Vec<FetchPart>,
+    #[serde(default)]
+    pub nodes: Vec<NodeParts>,
+}
+
+impl FetchManifest {
+    pub fn parse(contents: &str) -> Result<Self> {
+        let manifest: Self =
+            serde_json::from_str(contents).map_err(|e| TrainctlError::Validation {
+                field: "manifest".to_string(),
+                reason: format!("invalid checkpoint fetch manifest: {}", e),
+            })?;
+        for part in manifest
+            .shared
+            .iter()
+            .chain(manifest.nodes.iter().flat_map(|n| &n.parts))
+        {
+            validate_file(part.file())?;
+        }
+        Ok(manifest)
+    }
+
+    /// Shared parts plus the parts of `rank`
+    pub fn parts_for(&self, rank: u32) -> Result<Vec<FetchPart>> {
+        let mut parts = self.shared.clone();
+        let node = self.nodes.iter().find(|n| n.rank == rank);
+        match node {
+            Some(node) => parts.extend(node.parts.iter().cloned()),
+            None if !self.nodes.is_empty() => {
+                return Err(TrainctlError::Validation {
+                    field: "rank".to_string(),
+                    reason: format!(
+                        "the manifest has no parts for rank {} (ranks: {})",
+                        rank,
+                        self.nodes
+                            .iter()
+                            .map(|n| n.rank.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                })
+            }
+            None => {}
+        }
+        Ok(parts)
+    }
+}
+
+/// Byte span `[offset, offset + length)` of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Span {
+    pub offset: u64,
+    pub length: u64,
+}
+
+impl Span {
+    fn end(&self) -> u64 {
+        self.offset + self.length
+    }
+}
+
+/// Sort spans and merge the ones that overlap or touch
+fn merge(mut spans: Vec<Span>) -> Vec<Span> {
+    spans.retain(|s| s.length > 0);
+    spans.sort();
+    let mut merged: Vec<Span> = Vec::with_capacity(spans.len());
+    for span in spans {
+        match merged.last_mut() {
+            Some(last) if span.offset <= last.end() => {
+                last.length = last.end().max(span.end()) - last.offset;
+            }
+            _ => merged.push(span),
+        }
+    }
+    merged
+}
+
+/// Whether merged spans `done` cover all of `span`
+fn covered(done: &[Span], span: Span) -> bool {
+    done.iter()
+        .any(|d| d.offset <= span.offset && span.end() <= d.end())
+}
+
+/// Spans of each file to fetch, given the files' sizes
+pub fn plan(
+    parts: &[FetchPart],
+    sizes: &BTreeMap<String, u64>,
+) -> Result<BTreeMap<String, Vec<Span>>> {
+    let mut spans: BTreeMap<String, Vec<Span>> = BTreeMap::new();
+    for part in parts {
+        let size = sizes[part.file()];
+        let span = match part {
+            FetchPart::File(_) => Span {
+                offset: 0,
+                length: size,
+            },
+            FetchPart::Range {
+                file,
+                offset,
+                length,
+            } => {
+                if offset.saturating_add(*length) > size {
+                    return Err(TrainctlError::Validation {
+                        field: "manifest".to_string(),
+                        reason: format!(
+                            "range {}+{} of {} is past its end ({} bytes)",
+                            offset, length, file, size
+                        ),
+                    });
+                }
+                Span {
+                    offset: *offset,
+                    length: *length,
+                }
+            }
+        };
+        spans.entry(part.file().to_string()).or_default().push(span);
+    }
+    Ok(spans
+        .into_iter()
+        .map(|(file, spans)| (file, merge(spans)))
+        .collect())
+}
+
+/// Split spans into reads of at most `chunk_size` bytes
+pub fn chunk(spans: &[Span], chunk_size: u64) -> Vec<Span> {
+    let chunk_size = chunk_size.max(1);
+    let mut chunks = Vec::new();
+    for span in spans {
+        let mut offset = span.offset;
+        while offset < span.end() {
+            let length = chunk_size.min(span.end() - offset);
+            chunks.push(Span { offset, length });
+            offset += length;
+        }
+    }
+    chunks
+}
+
+/// Reject paths that would escape the checkpoint directory
+fn validate_file(file: &str) -> Result<()> {
+    let path = Path::new(file);
+    let inside = !file.is_empty()
+        && path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if !inside {
+        return Err(TrainctlError::Validation {
+            field: "file".to_string(),
+            reason: format!("'{}' is not a path inside the checkpoint", file),
+        });
+    }
+    Ok(())
+}
+
+/// Where the checkpoint is read from
+enum Source {
+    Local(PathBuf),
+    #[cfg(feature = "aws")]
+    S3 {
+        client: S3Client,
+        bucket: String,
+        /// Key prefix, empty or ending in `/`
+        prefix: String,
+    },
+}
+
+impl Source {
+    async fn open(location: &str) -> Result<Self> {
+        if location.starts_with("s3://") {
+            #[cfg(feature = "aws")]
+            {
+                let (bucket, prefix) = crate::data_transfer::parse_s3_path(location)?;
+                let prefix = if prefix.is_empty() || prefix.ends_with('/') {
+                    prefix
+                } else {
+                    format!("{}/", prefix)
+                };
+                let aws_config = crate::aws_utils::shared_sdk_config().await;
+                return Ok(Source::S3 {
+                    client: S3Client::new(&aws_config),
+                    bucket,
+                    prefix,
+                });
+            }
+            #[cfg(not(feature = "aws"))]
+            return Err(crate::error::ConfigError::FeatureNotCompiled("aws".to_string()).into());
+        }
+        let dir = PathBuf::from(location);
+        if !dir.is_dir() {
+            return Err(TrainctlError::ResourceNotFound {
+                resource_type: "checkpoint directory".to_string(),
+                resource_id: location.to_string(),
+            });
+        }
+        Ok(Source::Local(dir))
+    }
+
+    async fn size(&self, file: &str) -> Result<u64> {
+        match self {
+            Source::Local(dir) => {
+                let path = dir.join(file);
+                match fs::metadata(&path) {
+                    Ok(metadata) if metadata.is_file() => Ok(metadata.len()),
+                    _ => Err(TrainctlError::ResourceNotFound {
+                        resource_type: "checkpoint file".to_string(),
+                        resource_id: path.display().to_string(),
+                    }),
+                }
+            }
+            #[cfg(feature = "aws")]
+            Source::S3 {
+                client,
+                bucket,
+                prefix,
+            } => {
+                let key = format!("{}{}", prefix, file);
+                let head = client
+                    .head_object()
+                    .bucket(bucket)
+                    .key(&key)
+                    .send()
+                    .await
+                    .map_err(|e| {
+                        if e.as_service_error().is_some_and(|e| e.is_not_found()) {
+                            TrainctlError::ResourceNotFound {
+                                resource_type: "checkpoint file".to_string(),
+                                resource_id: format!("s3://{}/{}", bucket, key),
+                            }
+                        } else {
+                            TrainctlError::S3(format!(
+                                "Failed to stat s3://{}/{}: {}",
+                                bucket, key, e
+                            ))
+                        }
+                    })?;
+                Ok(head.content_length().unwrap_or(0).max(0) as u64)
+            }
+        }
+    }
+
+    /// Read `span` of `file`; failures are retryable
+    async fn read(&self, file: &str, span: Span) -> Result<Vec<u8>> {
+        match self {
+            Source::Local(dir) => {
+                let mut handle = fs::File::open(dir.join(file))?;
+                handle.seek(SeekFrom::Start(span.offset))?;
+                let mut data = vec![0; span.length as usize];
+                handle.read_exact(&mut data)?;
+                Ok(data)
+            }
+            #[cfg(feature = "aws")]
+            Source::S3 {
+                client,
+                bucket,
+                prefix,
+            } => {
+                let key = format!("{}{}", prefix, file);
+                let read_error = |e: String| TrainctlError::CloudProvider {
+                    provider: "aws".to_string(),
+                    message: format!(
+                        "Failed to read bytes {}-{} of s3://{}/{}: {}",
+                        span.offset,
+                        span.end() - 1,
+                        bucket,
+                        key,
+                        e
+                    ),
+                    source: None,
+                };
+                let response = client
+                    .get_object()
+                    .bucket(bucket)
+                    .key(&key)
+                    .range(format!("bytes={}-{}", span.offset, span.end() - 1))
+                    .send()
+                    .await
+                    .map_err(|e| read_error(e.to_string()))?;
+                let data = response
+                    .body
+                    .collect()
+                    .await
+                    .map_err(|e| read_error(e.to_string()))?
+                    .into_bytes();
+                if data.len() as u64 != span.length {
+                    return Err(read_error(format!(
+                        "got {} bytes, expected {}",
+                        data.len(),
+                        span.length
+                    )));
+                }
+                Ok(data.to_vec())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FileState {
+    size: u64,
+    /// Fetched spans, merged
+    done: Vec<Span>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FetchState {
+    source: String,
+    files: BTreeMap<String, FileState>,
+}
+
+impl FetchState {
+    fn load(dest: &Path, source: &str) -> Self {
+        let state = fs::read_to_string(dest.join(STATE_NAME))
+            .ok()
+            .and_then(|s| serde_json::from_str::<FetchState>(&s).ok());
+        match state {
+            Some(state) if state.source == source => state,
+            _ => FetchState {
+                source: source.to_string(),
+                files: BTreeMap::new(),
+            },
+        }
+    }
+
+    fn save(&self, dest: &Path) -> Result<()> {
+        let path = dest.join(STATE_NAME);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+}
+
+/// What to fetch and where
+#[derive(Debug, Clone)]
+pub struct FetchOptions {
+    /// Checkpoint directory: `s3://bucket/prefix/` or a local path
+    pub source: String,
+    pub dest: PathBuf,
+    /// Local manifest; default: `runctl-fetch.json` in the source
+    pub manifest: Option<PathBuf>,
+    pub rank: u32,
+    /// Extra whole files to fetch
+    pub files: Vec<String>,
+    pub parallel: usize,
+    pub chunk_size: u64,
+}
+
+/// Outcome of a fetch
+#[derive(Debug, Serialize)]
+pub struct FetchReport {
+    pub source: String,
+    pub dest: PathBuf,
+    pub rank: u32,
+    pub files: usize,
+    /// Bytes downloaded by this run
+    pub fetched_bytes: u64,
+    /// Bytes already fetched by an earlier run
+    pub skipped_bytes: u64,
+    /// Files only some byte ranges of were fetched
+    pub partial: Vec<String>,
+    pub elapsed_secs: f64,
+}
+
+async fn load_manifest(options: &FetchOptions, source: &Source) -> Result<Option<FetchManifest>> {
+    if let Some(path) = &options.manifest {
+        return FetchManifest::parse(&fs::read_to_string(path)?).map(Some);
+    }
+    if !options.files.is_empty() {
+        return Ok(None);
+    }
+    let size = source
+        .size(MANIFEST_NAME)
+        .await
+        .map_err(|_| TrainctlError::Validation {
+            field: "manifest".to_string(),
+            reason: format!(
+                "{} has no {}; pass --manifest or --file",
+                options.source, MANIFEST_NAME
+            ),
+        })?;
+    let data = source
+        .read(
+            MANIFEST_NAME,
+            Span {
+                offset: 0,
+                length: size,
+            },
+        )
+        .await?;
+    FetchManifest::parse(&String::from_utf8_lossy(&data)).map(Some)
+}
+
+/// Create (or resize) `path` to `size` bytes without touching its contents
+fn prepare_file(path: &Path, size: u64) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        crate::utils::ensure_dir(parent)?;
+    }
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?;
+    if file.metadata()?.len() != size {
+        file.set_len(size)?;
+    }
+    Ok(())
+}
+
+fn write_at(path: &Path, offset: u64, data: &[u8]) -> Result<()> {
+    let mut file = fs::OpenOptions::new().write(true).open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    file.write_all(data)?;
+    Ok(())
+}
+
+/// Fetch this node's parts of a checkpoint
+pub async fn fetch(options: &FetchOptions) -> Result<FetchReport> {
+    let started = Instant::now();
+    let source = Source::open(&options.source).await?;
+    let mut parts = match load_manifest(options, &source).await? {
+        Some(manifest) => manifest.parts_for(options.rank)?,
+        None => Vec::new(),
+    };
+    for file in &options.files {
+        validate_file(file)?;
+        parts.push(FetchPart::File(file.clone()));
+    }
+
+    let mut sizes = BTreeMap::new();
+    let mut files: Vec<&str> = parts.iter().map(FetchPart::file).collect();
+    files.sort();
+    files.dedup();
+    let source_ref = &source;
+    let mut stats = futures::stream::iter(files)
+        .map(|file| async move { source_ref.size(file).await.map(|size| (file, size)) })
+        .buffer_unordered(options.parallel.max(1));
+    while let Some(result) = stats.next().await {
+        let (file, size) = result?;
+        sizes.insert(file.to_string(), size);
+    }
+    drop(stats);
+    let spans = plan(&parts, &sizes)?;
+
+    crate::utils::ensure_dir(&options.dest)?;
+    let mut state = FetchState::load(&options.dest, &options.source);
+    let mut pending = Vec::new();
+    let mut skipped_bytes = 0;
+    let mut partial = Vec::new();
+    for (file, file_spans) in &spans {
+        let size = sizes[file];
+        let file_state = state.files.entry(file.clone()).or_default();
+        if file_state.size != size {
+            // A different checkpoint under the same name: start over
+            *file_state = FileState {
+                size,
+                done: Vec::new(),
+            };
+        }
+        prepare_file(&options.dest.join(file), size)?;
+        if file_spans.iter().map(|s| s.length).sum::<u64>() < size {
+            partial.push(file.clone());
+        }
+        for span in chunk(file_spans, options.chunk_size) {
+            if covered(&file_state.done, span) {
+                skipped_bytes += span.length;
+            } else {
+                pending.push((file.clone(), span));
+            }
+        }
+    }
+    state.save(&options.dest)?;
+
+    let total: u64 = pending.iter().map(|(_, span)| span.length).sum();
+    debug!(
+        "Fetching {} chunk(s), {} bytes ({} already fetched)",
+        pending.len(),
+        total,
+        skipped_bytes
+    );
+    let pb = ProgressBar::new(total);
+    pb.set_style(
+        crate::output_style::bar("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})"),
+    );
+    if pending.is_empty() {
+        pb.finish_and_clear();
+    }
+
+    let policy = ExponentialBackoffPolicy::new(4);
+    let dest = &options.dest;
+    let source = &source;
+    let policy = &policy;
+    let mut chunks = futures::stream::iter(pending)
+        .map(|(file, span)| async move {
+            let data = policy
+                .execute_with_retry(|| source.read(&file, span))
+                .await?;
+            write_at(&dest.join(&file), span.offset, &data)?;
+            Ok::<_, TrainctlError>((file, span))
+        })
+        .buffer_unordered(options.parallel.max(1));
+    let mut fetched_bytes = 0;
+    while let Some(result) = chunks.next().await {
+        let (file, span) = match result {
+            Ok(done) => done,
+            Err(e) => {
+                pb.abandon();
+                return Err(e);
+            }
+        };
+        fetched_bytes += span.length;
+        pb.inc(span.length);
+        let file_state = state.files.entry(file).or_default();
+        file_state.done.push(span);
+        file_state.done = merge(std::mem::take(&mut file_state.done));
+        state.save(dest)?;
+    }
+    pb.finish_and_clear();
+
+    info!(
+        "Fetched {} bytes of {} file(s) for rank {}",
+        fetched_bytes,
+        spans.len(),
+        options.rank
+    );
+    Ok(FetchReport {
+        source: options.source.clone(),
+        dest: options.dest.clone(),
+        rank: options.rank,
+        files: spans.len(),
+        fetched_bytes,
+        skipped_bytes,
+        partial,
+        elapsed_secs: started.elapsed().as_secs_f64(),
+    })
+}
+
+/// `runctl checkpoint fetch`
+pub(crate) async fn run_fetch(options: FetchOptions, output_format: &str) -> Result<()> {
+    let report = fetch(&options).await?;
+    if output_format == "json" {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    println!(
+        "Fetched {} of {} file(s) for rank {} into {} in {:.1}s",
+        super::format_size(report.fetched_bytes),
+        report.files,
+        report.rank,
+        report.dest.display(),
+        report.elapsed_secs
+    );
+    if report.skipped_bytes > 0 {
+        println!(
+            "  {} already fetched by an earlier run",
+            super::format_size(report.skipped_bytes)
+        );
+    }
+    if !report.partial.is_empty() {
+        println!("  Byte ranges only: {}", report.partial.join(", "));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(offset: u64, length: u64) -> Span {
+        Span { offset, length }
+    }
+
+    #[test]
+    fn test_manifest_parts_for_rank() {
+        let manifest = FetchManifest::parse(
+            r#"{
+                "shared": ["index.json"],
+                "nodes": [
+                    {"rank": 0, "parts": ["shard-0.safetensors"]},
+                    {"rank": 1, "parts": [{"file": "optim.bin", "offset": 8, "length": 4}]}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            manifest.parts_for(1).unwrap(),
+            vec![
+                FetchPart::File("index.json".to_string()),
+                FetchPart::Range {
+                    file: "optim.bin".to_string(),
+                    offset: 8,
+                    length: 4
+                }
+            ]
+        );
+        assert!(manifest.parts_for(2).is_err());
+        assert!(FetchManifest::parse(r#"{"shared": ["../secrets"]}"#).is_err());
+        assert!(FetchManifest::parse(r#"{"shared": ["/etc/passwd"]}"#).is_err());
+    }
+
+    #[test]
+    fn test_plan_merges_and_checks_ranges() {
+        let parts = vec![
+            FetchPart::Range {
+                file: "a.bin".to_string(),
+                offset: 10,
+                length: 10,
+            },
+            FetchPart::Range {
+                file: "a.bin".to_string(),
+                offset: 0,
+                length: 12,
+            },
+            FetchPart::File("b.bin".to_string()),
+        ];
+        let sizes = BTreeMap::from([("a.bin".to_string(), 100), ("b.bin".to_string(), 7)]);
+        let spans = plan(&parts, &sizes).unwrap();
+        assert_eq!(spans["a.bin"], vec![span(0, 20)]);
+        assert_eq!(spans["b.bin"], vec![span(0, 7)]);
+
+        let too_long = vec![FetchPart::Range {
+            file: "b.bin".to_string(),
+            offset: 4,
+            length: 4,
+        }];
+        assert!(plan(&too_long, &sizes).is_err());
+    }
+
+    #[test]
+    fn test_chunks_and_coverage() {
+        assert_eq!(
+            chunk(&[span(0, 10), span(20, 3)], 4),
+            vec![span(0, 4), span(4, 4), span(8, 2), span(20, 3)]
+        );
+        let done = merge(vec![span(4, 4), span(0, 4), span(20, 3)]);
+        assert_eq!(done, vec![span(0, 8), span(20, 3)]);
+        assert!(covered(&done, span(2, 5)));
+        assert!(!covered(&done, span(6, 4)));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_local_ranges_and_resume() {
+        let source = tempfile::tempdir().unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        let data: Vec<u8> = (0..100u8).collect();
+        fs::create_dir(source.path().join("shards")).unwrap();
+        fs::write(source.path().join("shards/optim.bin"), &data).unwrap();
+        fs::write(source.path().join("index.json"), b"{}").unwrap();
+        fs::write(
+            source.path().join(MANIFEST_NAME),
+            r#"{"shared": ["index.json"],
+                "nodes": [{"rank": 1, "parts": [{"file": "shards/optim.bin", "offset": 30, "length": 25}]}]}"#,
+        )
+        .unwrap();
+
+        let options = FetchOptions {
+            source: source.path().display().to_string(),
+            dest: dest.path().to_path_buf(),
+            manifest: None,
+            rank: 1,
+            files: Vec::new(),
+            parallel: 3,
+            chunk_size: 10,
+        };
+        let report = fetch(&options).await.unwrap();
+        assert_eq!(report.files, 2);
+        assert_eq!(report.fetched_bytes, 27);
+        assert_eq!(report.partial, vec!["shards/optim.bin".to_string()]);
+
+        let fetched = fs::read(dest.path().join("shards/optim.bin")).unwrap();
+        assert_eq!(fetched.len(), 100);
+        assert_eq!(&fetched[30..55], &data[30..55]);
+        assert!(fetched[..30].iter().all(|b| *b == 0));
+        assert_eq!(fs::read(dest.path().join("index.json")).unwrap(), b"{}");
+
+        let again = fetch(&options).await.unwrap();
+        assert_eq!(again.fetched_bytes, 0);
+        assert_eq!(again.skipped_bytes, 27);
+    }
+}
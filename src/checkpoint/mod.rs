@@ -20,18 +20,31 @@
 //! - **Info**: Display checkpoint metadata (size, modification time, embedded data)
 //! - **Resume**: Run training script with checkpoint path as argument
 //! - **Cleanup**: Remove old checkpoints (keeps last N, removes others)
+//! - **Push**: Queue a checkpoint for background upload from a training script
+//! - **Sync**: Mirror a checkpoint directory to S3 with retention rules
+//! - **Fetch**: Download only one node's files and byte ranges of a sharded
+//!   checkpoint, in parallel chunks, for multi-node resume
+//! - **Resume discovery**: Find the newest checkpoint on an instance, locally or
+//!   in S3, for `aws train --resume` (see [`resume`])
+//!
+//! Rust callers can use the same storage directly through [`CheckpointStore`],
+//! implemented for local directories and S3 prefixes.
 //!
 //! ## Usage
 //!
 //! ```rust,no_run
-//! use runctl::checkpoint;
+//! use runctl::{checkpoint, Config};
 //!
 //! # async fn example() -> runctl::error::Result<()> {
+//! let config = Config::load(None)?;
+//!
 //! // List checkpoints
 //! checkpoint::handle_command(
 //!     checkpoint::CheckpointCommands::List {
 //!         dir: "./checkpoints".into(),
 //!     },
+//!     &config,
+//!     None,
 //!     "text"
 //! ).await?;
 //!
@@ -41,12 +54,34 @@
 //!         path: "./checkpoints/epoch_10.pt".into(),
 //!         script: "train.py".into(),
 //!     },
+//!     &config,
+//!     None,
 //!     "text"
 //! ).await?;
 //! # Ok(())
 //! # }
 //! ```
 
+pub mod coordinate;
+mod fetch;
+#[cfg(feature = "aws")]
+mod push;
+pub mod resume;
+mod store;
+mod sync;
+
+pub use fetch::{
+    FetchManifest, FetchOptions, FetchPart, FetchReport, NodeParts, DEFAULT_CHUNK_SIZE,
+    MANIFEST_NAME,
+};
+#[cfg(feature = "aws")]
+pub use push::PushEntry;
+#[cfg(feature = "aws")]
+pub use store::S3CheckpointStore;
+pub use store::{CheckpointMeta, CheckpointStore, LocalCheckpointStore};
+pub use sync::{select_kept, SyncReport, SyncedCheckpoint};
+
+use crate::config::Config;
 use crate::error::{Result, TrainctlError};
 use chrono::{DateTime, Utc};
 use clap::Subcommand;
@@ -139,9 +174,131 @@ pub enum CheckpointCommands {
         #[arg(long)]
         dry_run: bool,
     },
+    /// Publish a checkpoint to S3 from a training script
+    ///
+    /// Returns immediately and prints nothing: the file is snapshotted and
+    /// queued, and a background uploader pushes it with retries. Call it right
+    /// after saving a checkpoint you want kept. The destination defaults to
+    /// s3://<aws.s3_bucket>/checkpoints/<job>/<file>.
+    ///
+    /// Examples:
+    ///   runctl checkpoint push checkpoints/epoch_10.pt --job $RUNCTL_JOB_ID
+    ///   runctl checkpoint push best.pt --job 3f2a9c1e --dest s3://bucket/runs/3f2a9c1e/
+    ///   runctl checkpoint push final.pt --job 3f2a9c1e --wait
+    Push {
+        /// Checkpoint file to publish
+        #[arg(value_name = "FILE", required_unless_present = "worker")]
+        file: Option<PathBuf>,
+        /// Job the checkpoint belongs to
+        #[arg(
+            long,
+            value_name = "JOB_ID",
+            env = "RUNCTL_JOB_ID",
+            required_unless_present = "worker"
+        )]
+        job: Option<String>,
+        /// Destination object, or prefix ending in '/'
+        #[arg(long, value_name = "S3_PATH", value_parser = crate::validation::s3_uri_arg)]
+        dest: Option<String>,
+        /// Encrypt client-side before upload (see [encryption] config)
+        #[arg(long)]
+        encrypt: bool,
+        /// Upload now and wait for it instead of queueing
+        #[arg(long)]
+        wait: bool,
+        /// Run as the background uploader (started automatically)
+        #[arg(long, hide = true)]
+        worker: bool,
+    },
+    /// Upload checkpoints to S3 and apply retention rules to both copies
+    ///
+    /// Uploads checkpoints missing from the S3 prefix (large files in
+    /// resumable parts), then deletes checkpoints the retention rules drop,
+    /// locally and in S3. Rules come from [checkpoint.retention] and the
+    /// flags below; a checkpoint is kept if any rule keeps it, and the newest
+    /// is always kept. Epochs and metrics are read from file names such as
+    /// epoch_12_val_loss=0.213.pt.
+    ///
+    /// Examples:
+    ///   runctl checkpoint sync ./checkpoints --dest s3://bucket/runs/exp1/
+    ///   runctl checkpoint sync --watch --keep-last 3 --keep-best 2 --metric val_loss
+    ///   runctl checkpoint sync --keep-every 10 --dry-run
+    Sync {
+        /// Checkpoint directory (default: [checkpoint] dir)
+        #[arg(value_name = "DIRECTORY")]
+        dir: Option<PathBuf>,
+        /// S3 prefix (default: [checkpoint] sync_dest, then s3://<aws.s3_bucket>/checkpoints/<project>/)
+        #[arg(long, value_name = "S3_PATH", value_parser = crate::validation::s3_uri_arg)]
+        dest: Option<String>,
+        /// Keep syncing until Ctrl-C
+        #[arg(long)]
+        watch: bool,
+        /// Time between passes with --watch (e.g. 30, 2m)
+        #[arg(long, value_name = "DURATION", default_value_t = 30, value_parser = crate::units::parse_secs)]
+        interval: u64,
+        /// Keep the N most recent checkpoints
+        #[arg(long, value_name = "N")]
+        keep_last: Option<usize>,
+        /// Keep the N best checkpoints by --metric
+        #[arg(long, value_name = "N")]
+        keep_best: Option<usize>,
+        /// Metric name in checkpoint file names (default: loss)
+        #[arg(long, value_name = "NAME")]
+        metric: Option<String>,
+        /// Higher --metric values are better (e.g. accuracy)
+        #[arg(long)]
+        maximize: bool,
+        /// Keep checkpoints whose epoch is a multiple of N
+        #[arg(long, value_name = "N")]
+        keep_every: Option<u32>,
+        /// Show what would be uploaded and deleted without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Download one node's parts of a sharded checkpoint
+    ///
+    /// Reads a manifest mapping node ranks to files and byte ranges (--manifest,
+    /// or runctl-fetch.json in the source) and downloads only this node's parts
+    /// plus the shared ones, as ranged reads in parallel. Byte ranges are
+    /// written at their offsets in a file of the full size. Rerunning skips
+    /// chunks fetched before.
+    ///
+    /// Examples:
+    ///   runctl checkpoint fetch s3://bucket/runs/exp1/step_4000/ ./ckpt --rank 1
+    ///   runctl checkpoint fetch s3://bucket/runs/exp1/step_4000/ ./ckpt --manifest shards.json
+    ///   runctl checkpoint fetch /mnt/shared/step_4000 ./ckpt --file model-00002-of-00004.safetensors
+    Fetch {
+        /// Checkpoint directory: s3://bucket/prefix/ or a local path
+        #[arg(value_name = "SOURCE")]
+        source: String,
+        /// Local directory to fetch into
+        #[arg(value_name = "DIRECTORY")]
+        dest: PathBuf,
+        /// Manifest of parts per node (default: runctl-fetch.json in SOURCE)
+        #[arg(long, value_name = "FILE")]
+        manifest: Option<PathBuf>,
+        /// Node rank whose parts to fetch
+        #[arg(long, env = "NODE_RANK", default_value_t = 0)]
+        rank: u32,
+        /// Fetch this whole file too (repeatable)
+        #[arg(long = "file", value_name = "NAME")]
+        files: Vec<String>,
+        /// Chunks downloaded at once
+        #[arg(long, default_value_t = 8)]
+        parallel: usize,
+        /// Size of each ranged read (e.g. 16MiB)
+        #[arg(long, value_name = "SIZE", default_value = "16MiB", value_parser = crate::units::parse_size)]
+        chunk_size: u64,
+    },
 }
 
-pub async fn handle_command(cmd: CheckpointCommands, output_format: &str) -> Result<()> {
+#[cfg_attr(not(feature = "aws"), allow(unused_variables))]
+pub async fn handle_command(
+    cmd: CheckpointCommands,
+    config: &Config,
+    config_path: Option<&Path>,
+    output_format: &str,
+) -> Result<()> {
     match cmd {
         CheckpointCommands::List { dir } => {
             crate::validation::validate_path_path(&dir)?;
@@ -164,6 +321,97 @@ pub async fn handle_command(cmd: CheckpointCommands, output_format: &str) -> Res
             crate::validation::validate_path_path(&dir)?;
             cleanup_checkpoints(&dir, keep_last_n, dry_run, output_format).await
         }
+        #[cfg(not(feature = "aws"))]
+        CheckpointCommands::Push { .. } => {
+            Err(crate::error::ConfigError::FeatureNotCompiled("aws".to_string()).into())
+        }
+        #[cfg(feature = "aws")]
+        CheckpointCommands::Push {
+            file,
+            job,
+            dest,
+            encrypt,
+            wait,
+            worker,
+        } => {
+            if worker {
+                return push::run_worker(config).await;
+            }
+            let (Some(file), Some(job_id)) = (file, job) else {
+                return Err(TrainctlError::Validation {
+                    field: "file".to_string(),
+                    reason: "checkpoint push needs a file and --job".to_string(),
+                });
+            };
+            let options = push::PushOptions {
+                file,
+                job_id,
+                dest,
+                encrypt,
+                wait,
+            };
+            push::push_checkpoint(options, config, config_path, output_format).await
+        }
+        CheckpointCommands::Sync {
+            dir,
+            dest,
+            watch,
+            interval,
+            keep_last,
+            keep_best,
+            metric,
+            maximize,
+            keep_every,
+            dry_run,
+        } => {
+            let dir = dir.unwrap_or_else(|| config.checkpoint.dir.clone());
+            crate::validation::validate_path_path(&dir)?;
+            // Flags override the configured rules one by one
+            let mut retention = config.checkpoint.retention.clone();
+            if keep_last.is_some() {
+                retention.keep_last = keep_last;
+            }
+            if keep_best.is_some() {
+                retention.keep_best = keep_best;
+            }
+            if metric.is_some() {
+                retention.metric = metric;
+            }
+            retention.maximize |= maximize;
+            if keep_every.is_some() {
+                retention.keep_every = keep_every;
+            }
+            let options = sync::SyncOptions {
+                dir,
+                dest: sync::resolve_sync_dest(dest, config)?,
+                retention,
+                watch,
+                interval_secs: interval,
+                dry_run,
+            };
+            sync::run_sync(options, output_format).await
+        }
+        CheckpointCommands::Fetch {
+            source,
+            dest,
+            manifest,
+            rank,
+            files,
+            parallel,
+            chunk_size,
+        } => {
+            crate::validation::validate_path_path(&dest)?;
+            let options = FetchOptions {
+                source,
+                dest,
+                manifest,
+                rank,
+                files,
+                parallel,
+                chunk_size,
+            };
+            fetch::run_fetch(options, output_format).await
+        }
     }
 }
 
@@ -212,7 +460,7 @@ async fn list_checkpoints(dir: &Path, output_format: &str) -> Result<()> {
         }
     }
 
-    checkpoints.sort_by(|a, b| b.1.cmp(&a.1));
+    checkpoints.sort_by_key(|b| std::cmp::Reverse(b.1));
 
     if output_format == "json" {
         let mut items = Vec::new();
@@ -374,7 +622,7 @@ async fn cleanup_checkpoints(
     }
 
     // Sort by modification time (newest first)
-    checkpoints.sort_by(|a, b| b.1.cmp(&a.1));
+    checkpoints.sort_by_key(|b| std::cmp::Reverse(b.1));
 
     if checkpoints.len() <= keep_last_n {
         println!(
@@ -0,0 +1,533 @@
+//! Publish checkpoints from a training script
+//!
+//! `runctl checkpoint push <file> --job <id>` is meant to be called from the
+//! training loop right after a checkpoint is written. It returns in
+//! milliseconds: the file is staged (hard-linked when possible, so a later
+//! save to the same path doesn't change what gets uploaded) and queued in
+//! `.runctl/checkpoint-push/`, and a background uploader started on demand
+//! pushes the queue to S3 with retries, then exits once the queue stays empty.
+//!
+//! Queue entries are claimed by renaming `<id>.json` to `<id>.inflight`, so
+//! concurrent uploaders never push the same checkpoint twice. Entries that
+//! keep failing are moved to `failed/` with their last error.
+
+use crate::config::Config;
+use crate::error::{Result, TrainctlError};
+use crate::retry::{ExponentialBackoffPolicy, RetryPolicy};
+use aws_sdk_s3::Client as S3Client;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use sysinfo::{Pid, ProcessesToUpdate, System};
+use tracing::{info, warn};
+
+/// Upload rounds (each with its own backoff retries) before giving up
+const MAX_ROUNDS: u32 = 3;
+
+/// Uploader exits after the queue has been empty this long
+const IDLE_EXIT_SECS: u64 = 30;
+
+/// Queue poll interval for the uploader
+const POLL_INTERVAL_SECS: u64 = 2;
+
+/// A checkpoint waiting to be uploaded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PushEntry {
+    pub id: String,
+    pub job_id: String,
+    /// File the training script pushed
+    pub source: PathBuf,
+    /// Staged copy that is actually uploaded
+    pub staged: PathBuf,
+    /// Destination object (`s3://bucket/key`)
+    pub destination: String,
+    #[serde(default)]
+    pub encrypt: bool,
+    pub enqueued_at: DateTime<Utc>,
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+fn push_dir() -> PathBuf {
+    PathBuf::from(".runctl").join("checkpoint-push")
+}
+
+fn queue_dir(root: &Path) -> PathBuf {
+    root.join("queue")
+}
+
+fn staged_dir(root: &Path) -> PathBuf {
+    root.join("staged")
+}
+
+fn failed_dir(root: &Path) -> PathBuf {
+    root.join("failed")
+}
+
+fn worker_pid_path(root: &Path) -> PathBuf {
+    root.join("worker.pid")
+}
+
+/// Resolve where a checkpoint goes
+///
+/// An explicit destination ending in `/` is a prefix and gets the file name
+/// appended. Without one, checkpoints go to
+/// `s3://<aws.s3_bucket>/checkpoints/<job>/<file>`.
+pub fn resolve_destination(
+    file: &Path,
+    job_id: &str,
+    dest: Option<&str>,
+    config: &Config,
+) -> Result<String> {
+    let file_name = file
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| TrainctlError::Validation {
+            field: "file".to_string(),
+            reason: format!("{} has no file name", file.display()),
+        })?;
+    match dest {
+        Some(dest) if dest.ends_with('/') => Ok(format!("{}{}", dest, file_name)),
+        Some(dest) => Ok(dest.to_string()),
+        None => {
+            let bucket = config
+                .aws
+                .as_ref()
+                .and_then(|aws| aws.s3_bucket.as_deref())
+                .ok_or_else(|| TrainctlError::Validation {
+                    field: "dest".to_string(),
+                    reason:
+                        "No destination: pass --dest s3://bucket/prefix/ or set [aws] s3_bucket"
+                            .to_string(),
+                })?;
+            Ok(format!(
+                "s3://{}/checkpoints/{}/{}",
+                bucket, job_id, file_name
+            ))
+        }
+    }
+}
+
+/// Snapshot `file` into the staging area
+///
+/// Hard links are instant and keep the pushed contents even if the script
+/// later writes a new file to the same path; across filesystems it falls
+/// back to a copy.
+fn stage_file(file: &Path, staged: &Path) -> Result<()> {
+    if let Some(parent) = staged.parent() {
+        crate::utils::ensure_dir(parent)?;
+    }
+    if fs::hard_link(file, staged).is_err() {
+        fs::copy(file, staged)?;
+    }
+    Ok(())
+}
+
+fn write_entry(path: &Path, entry: &PushEntry) -> Result<()> {
+    // Write then rename so an uploader never reads a half-written entry
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(entry)?)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Stage and queue a checkpoint
+pub fn enqueue_in(
+    root: &Path,
+    file: &Path,
+    job_id: &str,
+    destination: String,
+    encrypt: bool,
+) -> Result<PushEntry> {
+    let id = format!(
+        "{}-{}",
+        Utc::now().format("%Y%m%dT%H%M%S%3f"),
+        &uuid::Uuid::new_v4().simple().to_string()[..8]
+    );
+    let file_name = file
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "checkpoint".to_string());
+    let staged = staged_dir(root).join(format!("{}-{}", id, file_name));
+    stage_file(file, &staged)?;
+
+    let entry = PushEntry {
+        id: id.clone(),
+        job_id: job_id.to_string(),
+        source: file.to_path_buf(),
+        staged,
+        destination,
+        encrypt,
+        enqueued_at: Utc::now(),
+        attempts: 0,
+        last_error: None,
+    };
+    let queue = queue_dir(root);
+    crate::utils::ensure_dir(&queue)?;
+    write_entry(&queue.join(format!("{}.json", id)), &entry)?;
+    Ok(entry)
+}
+
+/// Queued entries, oldest first
+pub fn pending_in(root: &Path) -> Result<Vec<PushEntry>> {
+    let queue = queue_dir(root);
+    if !queue.exists() {
+        return Ok(Vec::new());
+    }
+    let mut entries: Vec<PushEntry> = fs::read_dir(&queue)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("json"))
+        .filter_map(|p| fs::read_to_string(p).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+    entries.sort_by(|a, b| a.enqueued_at.cmp(&b.enqueued_at));
+    Ok(entries)
+}
+
+/// Claim an entry for upload; `false` if another uploader got it first
+fn claim(root: &Path, id: &str) -> bool {
+    let queue = queue_dir(root);
+    fs::rename(
+        queue.join(format!("{}.json", id)),
+        queue.join(format!("{}.inflight", id)),
+    )
+    .is_ok()
+}
+
+/// Put in-flight entries back in the queue (after an uploader died)
+fn requeue_inflight(root: &Path) -> Result<usize> {
+    let queue = queue_dir(root);
+    if !queue.exists() {
+        return Ok(0);
+    }
+    let mut count = 0;
+    for entry in fs::read_dir(&queue)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) == Some("inflight") {
+            fs::rename(&path, path.with_extension("json"))?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Record the outcome of an upload attempt for a claimed entry
+fn finish(root: &Path, mut entry: PushEntry, outcome: Result<()>) -> Result<()> {
+    let inflight = queue_dir(root).join(format!("{}.inflight", entry.id));
+    match outcome {
+        Ok(()) => {
+            let _ = fs::remove_file(&entry.staged);
+            fs::remove_file(&inflight)?;
+        }
+        Err(e) => {
+            entry.attempts += 1;
+            entry.last_error = Some(e.to_string());
+            if entry.attempts >= MAX_ROUNDS {
+                warn!(
+                    "Giving up on {} after {} attempts: {}",
+                    entry.destination, entry.attempts, e
+                );
+                let failed = failed_dir(root);
+                crate::utils::ensure_dir(&failed)?;
+                write_entry(&failed.join(format!("{}.json", entry.id)), &entry)?;
+            } else {
+                write_entry(&queue_dir(root).join(format!("{}.json", entry.id)), &entry)?;
+            }
+            fs::remove_file(&inflight)?;
+        }
+    }
+    Ok(())
+}
+
+async fn upload(client: &S3Client, entry: &PushEntry, config: &Config) -> Result<()> {
+    let (bucket, mut key) = crate::data_transfer::parse_s3_path(&entry.destination)?;
+
+    // Encrypt at upload time so the script never waits on it
+    let stage = if entry.encrypt {
+        let encryption_key = crate::encryption::EncryptionKey::load(config)?;
+        key = crate::encryption::encrypted_name(&key);
+        Some(crate::encryption::stage_for_upload(
+            &encryption_key,
+            &entry.staged,
+        )?)
+    } else {
+        None
+    };
+    let path = stage
+        .as_ref()
+        .map(|s| s.path().to_path_buf())
+        .unwrap_or_else(|| entry.staged.clone());
+
+    let policy = ExponentialBackoffPolicy::for_cloud_api();
+    policy
+        .execute_with_retry(|| {
+            let (path, bucket, key) = (path.clone(), bucket.clone(), key.clone());
+            async move {
+                let body = aws_sdk_s3::primitives::ByteStream::from_path(&path)
+                    .await
+                    .map_err(|e| TrainctlError::S3(format!("Failed to read checkpoint: {}", e)))?;
+                client
+                    .put_object()
+                    .bucket(&bucket)
+                    .key(&key)
+                    .body(body)
+                    .send()
+                    .await
+                    .map_err(|e| TrainctlError::CloudProvider {
+                        provider: "aws".to_string(),
+                        message: format!("Failed to upload s3://{}/{}: {}", bucket, key, e),
+                        source: None,
+                    })?;
+                Ok(())
+            }
+        })
+        .await
+}
+
+fn worker_pid(root: &Path) -> Option<u32> {
+    let pid: u32 = fs::read_to_string(worker_pid_path(root))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    let mut system = System::new();
+    let pid = Pid::from_u32(pid);
+    (system.refresh_processes(ProcessesToUpdate::Some(&[pid])) > 0).then_some(pid.as_u32())
+}
+
+/// Start the background uploader unless one is already running
+fn ensure_worker(root: &Path, config_path: Option<&Path>) -> Result<()> {
+    if worker_pid(root).is_some() {
+        return Ok(());
+    }
+    let exe = std::env::current_exe()?;
+    let mut cmd = std::process::Command::new(exe);
+    if let Some(path) = config_path {
+        cmd.arg("--config").arg(path);
+    }
+    cmd.args(["checkpoint", "push", "--worker"])
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    cmd.spawn().map_err(|e| {
+        TrainctlError::Io(std::io::Error::other(format!(
+            "Failed to start checkpoint uploader: {}",
+            e
+        )))
+    })?;
+    Ok(())
+}
+
+/// Uploader loop: drain the queue, exit after it stays empty
+pub(crate) async fn run_worker(config: &Config) -> Result<()> {
+    let root = push_dir();
+    crate::utils::ensure_dir(&root)?;
+    if worker_pid(&root).is_some() {
+        return Ok(());
+    }
+    let pid_file = worker_pid_path(&root);
+    fs::write(&pid_file, std::process::id().to_string())?;
+    let requeued = requeue_inflight(&root)?;
+    if requeued > 0 {
+        info!("Requeued {} interrupted checkpoint uploads", requeued);
+    }
+
+    let aws_config = crate::aws_utils::shared_sdk_config().await;
+    let client = S3Client::new(&aws_config);
+    let mut idle = Duration::ZERO;
+    let poll = Duration::from_secs(POLL_INTERVAL_SECS);
+
+    let result = loop {
+        let pending = match pending_in(&root) {
+            Ok(pending) => pending,
+            Err(e) => break Err(e),
+        };
+        if pending.is_empty() {
+            if idle >= Duration::from_secs(IDLE_EXIT_SECS) {
+                break Ok(());
+            }
+            idle += poll;
+            tokio::time::sleep(poll).await;
+            continue;
+        }
+        idle = Duration::ZERO;
+        for entry in pending {
+            if !claim(&root, &entry.id) {
+                continue;
+            }
+            let outcome = upload(&client, &entry, config).await;
+            if outcome.is_ok() {
+                info!("Pushed {} to {}", entry.source.display(), entry.destination);
+            }
+            if let Err(e) = finish(&root, entry, outcome) {
+                warn!("Failed to update checkpoint push queue: {}", e);
+            }
+        }
+        // Give failed entries a pause before their next round
+        tokio::time::sleep(poll).await;
+    };
+
+    let _ = fs::remove_file(&pid_file);
+    result
+}
+
+/// Options for `runctl checkpoint push`
+pub struct PushOptions {
+    pub file: PathBuf,
+    pub job_id: String,
+    pub dest: Option<String>,
+    pub encrypt: bool,
+    /// Upload in the foreground instead of queueing
+    pub wait: bool,
+}
+
+/// Queue (or, with `wait`, upload) a checkpoint
+pub(crate) async fn push_checkpoint(
+    options: PushOptions,
+    config: &Config,
+    config_path: Option<&Path>,
+    output_format: &str,
+) -> Result<()> {
+    crate::validation::validate_path_path(&options.file)?;
+    if !options.file.is_file() {
+        return Err(TrainctlError::ResourceNotFound {
+            resource_type: "checkpoint".to_string(),
+            resource_id: options.file.display().to_string(),
+        });
+    }
+    let destination = resolve_destination(
+        &options.file,
+        &options.job_id,
+        options.dest.as_deref(),
+        config,
+    )?;
+    crate::validation::validate_s3_path(&destination)?;
+
+    let root = push_dir();
+    let entry = enqueue_in(
+        &root,
+        &options.file,
+        &options.job_id,
+        destination,
+        options.encrypt,
+    )?;
+
+    if options.wait {
+        if !claim(&root, &entry.id) {
+            return Ok(());
+        }
+        let aws_config = crate::aws_utils::shared_sdk_config().await;
+        let client = S3Client::new(&aws_config);
+        let outcome = upload(&client, &entry, config).await;
+        let error = outcome.as_ref().err().map(|e| e.to_string());
+        finish(&root, entry.clone(), outcome)?;
+        if let Some(error) = error {
+            return Err(TrainctlError::S3(format!(
+                "Checkpoint push failed (queued for retry): {}",
+                error
+            )));
+        }
+    } else {
+        ensure_worker(&root, config_path)?;
+    }
+
+    // Quiet by default: training logs shouldn't fill up with push chatter
+    if output_format == "json" {
+        println!("{}", serde_json::to_string_pretty(&entry)?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_destination() {
+        let mut config = Config::default();
+        let file = Path::new("checkpoints/epoch_3.pt");
+        assert_eq!(
+            resolve_destination(file, "job1", Some("s3://b/runs/"), &config).unwrap(),
+            "s3://b/runs/epoch_3.pt"
+        );
+        assert_eq!(
+            resolve_destination(file, "job1", Some("s3://b/best.pt"), &config).unwrap(),
+            "s3://b/best.pt"
+        );
+        if let Some(aws) = config.aws.as_mut() {
+            aws.s3_bucket = Some("ml".to_string());
+        }
+        assert_eq!(
+            resolve_destination(file, "job1", None, &config).unwrap(),
+            "s3://ml/checkpoints/job1/epoch_3.pt"
+        );
+        config.aws = None;
+        assert!(resolve_destination(file, "job1", None, &config).is_err());
+    }
+
+    #[test]
+    fn test_queue_lifecycle() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("push");
+        let file = dir.path().join("epoch_1.pt");
+        fs::write(&file, b"v1").unwrap();
+
+        let entry = enqueue_in(&root, &file, "job1", "s3://b/e1.pt".to_string(), false).unwrap();
+        // The staged snapshot survives the script replacing the file
+        fs::remove_file(&file).unwrap();
+        fs::write(&file, b"v2").unwrap();
+        assert_eq!(fs::read(&entry.staged).unwrap(), b"v1");
+        assert_eq!(pending_in(&root).unwrap(), vec![entry.clone()]);
+
+        // Only one uploader can claim an entry
+        assert!(claim(&root, &entry.id));
+        assert!(!claim(&root, &entry.id));
+        assert!(pending_in(&root).unwrap().is_empty());
+
+        // Failures go back to the queue until the last round
+        let mut current = entry.clone();
+        for round in 1..MAX_ROUNDS {
+            finish(&root, current, Err(TrainctlError::S3("boom".into()))).unwrap();
+            let pending = pending_in(&root).unwrap();
+            assert_eq!(pending.len(), 1);
+            assert_eq!(pending[0].attempts, round);
+            current = pending[0].clone();
+            assert!(claim(&root, &entry.id));
+        }
+        finish(&root, current, Err(TrainctlError::S3("boom".into()))).unwrap();
+        assert!(pending_in(&root).unwrap().is_empty());
+        assert!(failed_dir(&root)
+            .join(format!("{}.json", entry.id))
+            .exists());
+    }
+
+    #[test]
+    fn test_success_and_requeue() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("push");
+        let file = dir.path().join("best.pt");
+        fs::write(&file, b"weights").unwrap();
+
+        let a = enqueue_in(&root, &file, "job1", "s3://b/a.pt".to_string(), false).unwrap();
+        let b = enqueue_in(&root, &file, "job1", "s3://b/b.pt".to_string(), false).unwrap();
+        assert!(claim(&root, &a.id));
+        assert!(claim(&root, &b.id));
+
+        finish(&root, a.clone(), Ok(())).unwrap();
+        assert!(!a.staged.exists());
+
+        // An uploader died holding `b`
+        assert_eq!(requeue_inflight(&root).unwrap(), 1);
+        assert_eq!(pending_in(&root).unwrap(), vec![b]);
+    }
+}
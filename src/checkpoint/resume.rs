@@ -0,0 +1,180 @@
+//! Finding the checkpoint to resume from
+//!
+//! `aws train --resume` looks in three places and takes the most recently
+//! modified checkpoint: the checkpoint directory on the instance, the local
+//! `[checkpoint] dir`, and the S3 prefix `checkpoint sync` uploads to. A
+//! checkpoint already on the instance wins over copies of it elsewhere, so
+//! it isn't downloaded again.
+
+use super::store::sort_newest_first;
+use super::sync::is_checkpoint;
+use super::{CheckpointMeta, CheckpointStore, LocalCheckpointStore};
+use crate::config::Config;
+use crate::error::Result;
+use crate::utils::shell_quote;
+use std::fmt;
+use std::time::{Duration, UNIX_EPOCH};
+#[cfg(feature = "aws")]
+use tracing::warn;
+
+/// Where a checkpoint to resume from was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResumeSource {
+    Instance,
+    Local,
+    S3,
+}
+
+impl fmt::Display for ResumeSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ResumeSource::Instance => "instance",
+            ResumeSource::Local => "local",
+            ResumeSource::S3 => "s3",
+        })
+    }
+}
+
+/// The checkpoint a run resumes from
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResumeCheckpoint {
+    pub source: ResumeSource,
+    pub checkpoint: CheckpointMeta,
+}
+
+/// Shell command listing the files in `dir` as `<mtime> <size> <name>` lines
+pub fn remote_listing_command(dir: &str) -> String {
+    format!(
+        "find {} -maxdepth 1 -type f -printf '%T@ %s %f\\n' 2>/dev/null || true",
+        shell_quote(dir)
+    )
+}
+
+/// Checkpoints in the output of `remote_listing_command(dir)`, newest first
+pub fn parse_remote_listing(output: &str, dir: &str) -> Vec<CheckpointMeta> {
+    let dir = dir.trim_end_matches('/');
+    let mut checkpoints: Vec<CheckpointMeta> = output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.trim().splitn(3, ' ');
+            let mtime: f64 = fields.next()?.parse().ok()?;
+            let size: u64 = fields.next()?.parse().ok()?;
+            let name = fields.next()?;
+            if !is_checkpoint(name) || !mtime.is_finite() || mtime < 0.0 {
+                return None;
+            }
+            Some(CheckpointMeta::new(
+                name.to_string(),
+                format!("{}/{}", dir, name),
+                size,
+                UNIX_EPOCH + Duration::from_secs_f64(mtime),
+            ))
+        })
+        .collect();
+    sort_newest_first(&mut checkpoints);
+    checkpoints
+}
+
+/// Newest of `candidates`, preferring the instance's copy of a checkpoint
+/// found in several places
+pub fn pick_latest(candidates: Vec<ResumeCheckpoint>) -> Option<ResumeCheckpoint> {
+    let on_instance: Vec<String> = candidates
+        .iter()
+        .filter(|c| c.source == ResumeSource::Instance)
+        .map(|c| c.checkpoint.name.clone())
+        .collect();
+    candidates
+        .into_iter()
+        .filter(|c| c.source == ResumeSource::Instance || !on_instance.contains(&c.checkpoint.name))
+        .max_by_key(|c| c.checkpoint.modified)
+}
+
+/// Newest checkpoint among `on_instance`, the local `[checkpoint] dir` and
+/// the `checkpoint sync` prefix in S3
+///
+/// The S3 prefix is skipped when none is configured or it can't be listed.
+pub async fn find_latest(
+    config: &Config,
+    on_instance: Vec<CheckpointMeta>,
+) -> Result<Option<ResumeCheckpoint>> {
+    let mut candidates: Vec<ResumeCheckpoint> = on_instance
+        .into_iter()
+        .map(|checkpoint| ResumeCheckpoint {
+            source: ResumeSource::Instance,
+            checkpoint,
+        })
+        .collect();
+    if let Some(checkpoint) = LocalCheckpointStore::new(&config.checkpoint.dir)
+        .latest()
+        .await?
+    {
+        candidates.push(ResumeCheckpoint {
+            source: ResumeSource::Local,
+            checkpoint,
+        });
+    }
+    #[cfg(feature = "aws")]
+    if let Ok(dest) = super::sync::resolve_sync_dest(None, config) {
+        let aws_config = crate::aws_utils::shared_sdk_config().await;
+        let client = aws_sdk_s3::Client::new(&aws_config);
+        let store = super::S3CheckpointStore::new(client, &dest)?;
+        match store.latest().await {
+            Ok(Some(checkpoint)) => candidates.push(ResumeCheckpoint {
+                source: ResumeSource::S3,
+                checkpoint,
+            }),
+            Ok(None) => {}
+            Err(e) => warn!("Skipping checkpoints in {}: {}", dest, e),
+        }
+    }
+    Ok(pick_latest(candidates))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_picks_the_newest_checkpoint_preferring_the_instance() {
+        let listing = "1700000100.5 2048 epoch_3.pt\n1700000000.0 1024 epoch_2.pt\n1700000200 10 notes.txt\ngarbage\n";
+        let remote = parse_remote_listing(listing, "/home/ubuntu/proj/checkpoints/");
+        assert_eq!(
+            remote.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(),
+            vec!["epoch_3.pt", "epoch_2.pt"]
+        );
+        assert_eq!(
+            remote[0].location,
+            "/home/ubuntu/proj/checkpoints/epoch_3.pt"
+        );
+        assert_eq!(remote[0].epoch, Some(3));
+
+        let at = |name: &str, secs: u64, source| ResumeCheckpoint {
+            source,
+            checkpoint: CheckpointMeta::new(
+                name.to_string(),
+                name.to_string(),
+                1,
+                UNIX_EPOCH + Duration::from_secs(secs),
+            ),
+        };
+        // The S3 copy of epoch_3 was uploaded later, but the instance has it
+        let latest = pick_latest(vec![
+            at("epoch_3.pt", 100, ResumeSource::Instance),
+            at("epoch_3.pt", 150, ResumeSource::S3),
+            at("epoch_1.pt", 50, ResumeSource::Local),
+        ])
+        .unwrap();
+        assert_eq!(latest.source, ResumeSource::Instance);
+
+        let latest = pick_latest(vec![
+            at("epoch_3.pt", 100, ResumeSource::Instance),
+            at("epoch_4.pt", 200, ResumeSource::S3),
+        ])
+        .unwrap();
+        assert_eq!(
+            (latest.source, latest.checkpoint.name.as_str()),
+            (ResumeSource::S3, "epoch_4.pt")
+        );
+        assert!(pick_latest(Vec::new()).is_none());
+    }
+}
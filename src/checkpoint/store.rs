@@ -0,0 +1,427 @@
+//! Programmatic access to checkpoint storage
+//!
+//! [`CheckpointStore`] is the library-side counterpart of the `runctl
+//! checkpoint` commands: list, fetch, upload, delete and pick the latest or
+//! best checkpoint without shelling out to the CLI. Two backends are
+//! provided, [`LocalCheckpointStore`] for a directory on disk and
+//! [`S3CheckpointStore`] for an S3 prefix.
+//!
+//! Stores are flat: a checkpoint is a file directly in the directory (or
+//! object directly under the prefix) whose extension is a known checkpoint
+//! format. Epoch and metric values are parsed from the file name, using the
+//! same rules as `runctl checkpoint sync`.
+//!
+//! ```rust,no_run
+//! use runctl::checkpoint::{CheckpointStore, LocalCheckpointStore, S3CheckpointStore};
+//!
+//! # async fn example() -> runctl::error::Result<()> {
+//! let local = LocalCheckpointStore::new("./checkpoints");
+//! let remote = S3CheckpointStore::from_env("s3://bucket/runs/demo/").await?;
+//!
+//! if let Some(best) = local.best("val_loss", false).await? {
+//!     remote.put(&local.path_of(&best.name)?, None).await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+#[cfg(feature = "aws")]
+use super::sync::list_remote;
+use super::sync::{
+    is_checkpoint, parse_epoch, parse_metric, select_kept, SyncedCheckpoint, DEFAULT_METRIC,
+};
+use crate::config::RetentionConfig;
+use crate::error::{Result, TrainctlError};
+use async_trait::async_trait;
+#[cfg(feature = "aws")]
+use aws_config::BehaviorVersion;
+#[cfg(feature = "aws")]
+use aws_sdk_s3::Client as S3Client;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// A checkpoint in a store
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CheckpointMeta {
+    /// File name, unique within the store
+    pub name: String,
+    /// Where it lives: a local path or an `s3://` URL
+    pub location: String,
+    pub size: u64,
+    /// Local modification time, or upload time for S3
+    pub modified: DateTime<Utc>,
+    /// Epoch parsed from the name (`epoch_12.pt`, `epoch=12-step=400.ckpt`)
+    pub epoch: Option<u32>,
+}
+
+impl CheckpointMeta {
+    pub(super) fn new(name: String, location: String, size: u64, modified: SystemTime) -> Self {
+        Self {
+            epoch: parse_epoch(&name),
+            name,
+            location,
+            size,
+            modified: modified.into(),
+        }
+    }
+
+    /// Value of `metric` parsed from the name (`val_loss=0.213.pt`)
+    pub fn metric(&self, metric: &str) -> Option<f64> {
+        parse_metric(&self.name, metric)
+    }
+}
+
+/// Storage for training checkpoints
+#[async_trait]
+pub trait CheckpointStore: Send + Sync {
+    /// Checkpoints in the store, newest first
+    async fn list(&self) -> Result<Vec<CheckpointMeta>>;
+
+    /// Metadata of one checkpoint
+    async fn stat(&self, name: &str) -> Result<CheckpointMeta> {
+        self.list()
+            .await?
+            .into_iter()
+            .find(|c| c.name == name)
+            .ok_or_else(|| TrainctlError::ResourceNotFound {
+                resource_type: "checkpoint".to_string(),
+                resource_id: name.to_string(),
+            })
+    }
+
+    /// Copy checkpoint `name` to the local file `dest`
+    async fn get(&self, name: &str, dest: &Path) -> Result<()>;
+
+    /// Store the local file `src`, under `name` or its own file name
+    async fn put(&self, src: &Path, name: Option<&str>) -> Result<CheckpointMeta>;
+
+    /// Remove checkpoint `name`
+    async fn delete(&self, name: &str) -> Result<()>;
+
+    /// Most recently modified checkpoint
+    async fn latest(&self) -> Result<Option<CheckpointMeta>> {
+        Ok(self.list().await?.into_iter().next())
+    }
+
+    /// Checkpoint with the lowest (or, with `maximize`, highest) `metric`
+    ///
+    /// Checkpoints whose name carries no value for `metric` are ignored; ties
+    /// go to the newer checkpoint.
+    async fn best(&self, metric: &str, maximize: bool) -> Result<Option<CheckpointMeta>> {
+        let mut best: Option<(CheckpointMeta, f64)> = None;
+        for checkpoint in self.list().await? {
+            let Some(value) = checkpoint.metric(metric) else {
+                continue;
+            };
+            let better = match &best {
+                None => true,
+                Some((_, current)) if maximize => value > *current,
+                Some((_, current)) => value < *current,
+            };
+            if better {
+                best = Some((checkpoint, value));
+            }
+        }
+        Ok(best.map(|(c, _)| c))
+    }
+
+    /// Delete the checkpoints `retention` doesn't keep; returns their names
+    ///
+    /// Uses the same rules as `runctl checkpoint sync`, so the newest
+    /// checkpoint is always kept.
+    async fn prune(&self, retention: &RetentionConfig) -> Result<Vec<String>> {
+        let checkpoints = self.list().await?;
+        let metric = retention.metric.as_deref().unwrap_or(DEFAULT_METRIC);
+        let synced: Vec<SyncedCheckpoint> = checkpoints
+            .iter()
+            .map(|c| SyncedCheckpoint {
+                name: c.name.clone(),
+                modified: c.modified.into(),
+                epoch: c.epoch,
+                metric: c.metric(metric),
+                local_size: None,
+                remote_size: Some(c.size),
+            })
+            .collect();
+        let kept = select_kept(&synced, retention);
+        let mut deleted = Vec::new();
+        for checkpoint in checkpoints {
+            if !kept.contains(&checkpoint.name) {
+                self.delete(&checkpoint.name).await?;
+                deleted.push(checkpoint.name);
+            }
+        }
+        Ok(deleted)
+    }
+}
+
+/// Reject names that would escape the store or aren't checkpoints
+fn validate_name(name: &str) -> Result<()> {
+    let flat = !name.is_empty() && !name.contains('/') && !name.contains('\\');
+    if !flat {
+        return Err(TrainctlError::Validation {
+            field: "name".to_string(),
+            reason: format!("'{}' is not a plain file name", name),
+        });
+    }
+    if !is_checkpoint(name) {
+        return Err(TrainctlError::Validation {
+            field: "name".to_string(),
+            reason: format!(
+                "'{}' does not have a checkpoint extension (.pt, .pth, .ckpt, .safetensors, .bin, .h5)",
+                name
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Name to store `src` under
+fn target_name(src: &Path, name: Option<&str>) -> Result<String> {
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => src
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    };
+    validate_name(&name)?;
+    Ok(name)
+}
+
+pub(super) fn sort_newest_first(checkpoints: &mut [CheckpointMeta]) {
+    checkpoints.sort_by(|a, b| b.modified.cmp(&a.modified).then(b.name.cmp(&a.name)));
+}
+
+/// Checkpoints in a local directory
+#[derive(Debug, Clone)]
+pub struct LocalCheckpointStore {
+    dir: PathBuf,
+}
+
+impl LocalCheckpointStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path of checkpoint `name` in the directory
+    pub fn path_of(&self, name: &str) -> Result<PathBuf> {
+        validate_name(name)?;
+        Ok(self.dir.join(name))
+    }
+
+    fn meta(&self, name: &str) -> Result<CheckpointMeta> {
+        let path = self.path_of(name)?;
+        let metadata = match fs::metadata(&path) {
+            Ok(metadata) if metadata.is_file() => metadata,
+            _ => {
+                return Err(TrainctlError::ResourceNotFound {
+                    resource_type: "checkpoint".to_string(),
+                    resource_id: path.display().to_string(),
+                })
+            }
+        };
+        Ok(CheckpointMeta::new(
+            name.to_string(),
+            path.display().to_string(),
+            metadata.len(),
+            metadata.modified()?,
+        ))
+    }
+}
+
+#[async_trait]
+impl CheckpointStore for LocalCheckpointStore {
+    async fn list(&self) -> Result<Vec<CheckpointMeta>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut checkpoints = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let metadata = entry.metadata()?;
+            if !metadata.is_file() || !is_checkpoint(&name) {
+                continue;
+            }
+            checkpoints.push(CheckpointMeta::new(
+                name,
+                entry.path().display().to_string(),
+                metadata.len(),
+                metadata.modified()?,
+            ));
+        }
+        sort_newest_first(&mut checkpoints);
+        Ok(checkpoints)
+    }
+
+    async fn stat(&self, name: &str) -> Result<CheckpointMeta> {
+        self.meta(name)
+    }
+
+    async fn get(&self, name: &str, dest: &Path) -> Result<()> {
+        let src = self.meta(name)?;
+        if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+            crate::utils::ensure_dir(parent)?;
+        }
+        fs::copy(&src.location, dest)?;
+        Ok(())
+    }
+
+    async fn put(&self, src: &Path, name: Option<&str>) -> Result<CheckpointMeta> {
+        let name = target_name(src, name)?;
+        crate::utils::ensure_dir(&self.dir)?;
+        let dest = self.dir.join(&name);
+        // Copy next to the target and rename, so readers never see a partial file
+        let tmp = self.dir.join(format!(".{}.tmp", name));
+        fs::copy(src, &tmp)?;
+        fs::rename(&tmp, &dest)?;
+        self.meta(&name)
+    }
+
+    async fn delete(&self, name: &str) -> Result<()> {
+        let path = self.meta(name)?.location;
+        fs::remove_file(path)?;
+        Ok(())
+    }
+}
+
+/// Checkpoints under an S3 prefix
+#[cfg(feature = "aws")]
+#[derive(Debug, Clone)]
+pub struct S3CheckpointStore {
+    client: S3Client,
+    bucket: String,
+    /// Key prefix, empty or ending in `/`
+    prefix: String,
+    /// Where multipart upload progress is recorded
+    state_dir: PathBuf,
+}
+
+#[cfg(feature = "aws")]
+impl S3CheckpointStore {
+    /// Store at `dest` (`s3://bucket/prefix/`) using `client`
+    pub fn new(client: S3Client, dest: &str) -> Result<Self> {
+        let (bucket, prefix) = crate::data_transfer::parse_s3_path(dest)?;
+        let prefix = if prefix.is_empty() || prefix.ends_with('/') {
+            prefix
+        } else {
+            format!("{}/", prefix)
+        };
+        Ok(Self {
+            client,
+            bucket,
+            prefix,
+            state_dir: PathBuf::from(".runctl").join("uploads"),
+        })
+    }
+
+    /// Store at `dest` with credentials from the environment
+    pub async fn from_env(dest: &str) -> Result<Self> {
+        let aws_config = aws_config::load_defaults(BehaviorVersion::latest()).await;
+        Self::new(S3Client::new(&aws_config), dest)
+    }
+
+    /// Record resumable upload state under `dir` instead of `.runctl/uploads`
+    pub fn with_state_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.state_dir = dir.into();
+        self
+    }
+
+    /// `s3://` URL of checkpoint `name`
+    pub fn url_of(&self, name: &str) -> Result<String> {
+        validate_name(name)?;
+        Ok(format!("s3://{}/{}{}", self.bucket, self.prefix, name))
+    }
+
+    fn key_of(&self, name: &str) -> Result<String> {
+        validate_name(name)?;
+        Ok(format!("{}{}", self.prefix, name))
+    }
+}
+
+#[cfg(feature = "aws")]
+#[async_trait]
+impl CheckpointStore for S3CheckpointStore {
+    async fn list(&self) -> Result<Vec<CheckpointMeta>> {
+        let mut checkpoints: Vec<CheckpointMeta> =
+            list_remote(&self.client, &self.bucket, &self.prefix)
+                .await?
+                .into_iter()
+                .map(|(name, size, modified)| {
+                    let location = format!("s3://{}/{}{}", self.bucket, self.prefix, name);
+                    CheckpointMeta::new(name, location, size, modified)
+                })
+                .collect();
+        sort_newest_first(&mut checkpoints);
+        Ok(checkpoints)
+    }
+
+    async fn get(&self, name: &str, dest: &Path) -> Result<()> {
+        let key = self.key_of(name)?;
+        let response = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(&key)
+            .send()
+            .await
+            .map_err(|e| {
+                TrainctlError::S3(format!(
+                    "Failed to download s3://{}/{}: {}",
+                    self.bucket, key, e
+                ))
+            })?;
+        let data = response.body.collect().await.map_err(|e| {
+            TrainctlError::S3(format!(
+                "Failed to read s3://{}/{}: {}",
+                self.bucket, key, e
+            ))
+        })?;
+        if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+            crate::utils::ensure_dir(parent)?;
+        }
+        fs::write(dest, data.into_bytes())?;
+        Ok(())
+    }
+
+    async fn put(&self, src: &Path, name: Option<&str>) -> Result<CheckpointMeta> {
+        let name = target_name(src, name)?;
+        let key = self.key_of(&name)?;
+        let size = fs::metadata(src)?.len();
+        crate::data_transfer::upload_file_resumable(
+            &self.client,
+            &self.bucket,
+            &key,
+            src,
+            &self.state_dir,
+        )
+        .await?;
+        let location = self.url_of(&name)?;
+        Ok(CheckpointMeta::new(name, location, size, SystemTime::now()))
+    }
+
+    async fn delete(&self, name: &str) -> Result<()> {
+        let key = self.key_of(name)?;
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(&key)
+            .send()
+            .await
+            .map_err(|e| {
+                TrainctlError::S3(format!(
+                    "Failed to delete s3://{}/{}: {}",
+                    self.bucket, key, e
+                ))
+            })?;
+        Ok(())
+    }
+}
@@ -0,0 +1,549 @@
+//! Keep a checkpoint directory in sync with S3
+//!
+//! `runctl checkpoint sync [DIR] --watch` uploads new checkpoints to an S3
+//! prefix and applies the retention rules (`[checkpoint.retention]` or the
+//! `--keep-*` flags) to both copies. Each pass:
+//!
+//! 1. lists the local directory and the S3 prefix
+//! 2. decides what to keep over the union of both, so a checkpoint dropped
+//!    locally is also dropped from S3 and the other way round
+//! 3. uploads kept checkpoints that are missing from S3 or differ in size,
+//!    using resumable multipart uploads for large files
+//! 4. deletes the rest from S3 and from disk
+//!
+//! Files modified in the last few seconds are left for the next pass, since
+//! the training script may still be writing them.
+
+use crate::config::{Config, RetentionConfig};
+use crate::error::{Result, TrainctlError};
+#[cfg(feature = "aws")]
+use aws_sdk_s3::Client as S3Client;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+#[cfg(feature = "aws")]
+use tracing::warn;
+
+/// File extensions treated as checkpoints
+const CHECKPOINT_EXTENSIONS: &[&str] = &["pt", "pth", "ckpt", "safetensors", "bin", "h5"];
+
+/// Files modified more recently than this are skipped until the next pass
+const SETTLE_SECS: u64 = 10;
+
+/// Metric used by `keep_best` when none is configured
+pub(super) const DEFAULT_METRIC: &str = "loss";
+
+/// A checkpoint known locally, in S3, or both
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncedCheckpoint {
+    pub name: String,
+    pub modified: SystemTime,
+    pub epoch: Option<u32>,
+    pub metric: Option<f64>,
+    pub local_size: Option<u64>,
+    pub remote_size: Option<u64>,
+}
+
+/// Options for `runctl checkpoint sync`
+#[cfg_attr(not(feature = "aws"), allow(dead_code))]
+pub struct SyncOptions {
+    pub dir: PathBuf,
+    /// S3 prefix, ending in `/`
+    pub dest: String,
+    pub retention: RetentionConfig,
+    pub watch: bool,
+    pub interval_secs: u64,
+    pub dry_run: bool,
+}
+
+/// What one pass did (or, with `dry_run`, would do)
+#[derive(Debug, Default, Serialize)]
+pub struct SyncReport {
+    pub uploaded: Vec<String>,
+    pub deleted_local: Vec<String>,
+    pub deleted_remote: Vec<String>,
+    pub kept: usize,
+    pub dry_run: bool,
+}
+
+impl SyncReport {
+    #[cfg_attr(not(feature = "aws"), allow(dead_code))]
+    fn is_empty(&self) -> bool {
+        self.uploaded.is_empty() && self.deleted_local.is_empty() && self.deleted_remote.is_empty()
+    }
+}
+
+/// Epoch from names like `epoch_12.pt`, `epoch=12-step=400.ckpt` or `ep12.pt`
+pub fn parse_epoch(name: &str) -> Option<u32> {
+    let re = Regex::new(r"(?i)(?:^|[^a-z])(?:epoch|ep)[_=\-]?(\d+)").ok()?;
+    re.captures(name)?.get(1)?.as_str().parse().ok()
+}
+
+/// Value of `metric` from names like `val_loss=0.213.pt` or `acc_0.91.pt`
+pub fn parse_metric(name: &str, metric: &str) -> Option<f64> {
+    let stem = CHECKPOINT_EXTENSIONS
+        .iter()
+        .find_map(|ext| name.strip_suffix(&format!(".{}", ext)))
+        .unwrap_or(name);
+    let pattern = format!(
+        r"(?:^|[^A-Za-z0-9]){}[_=\-]?(-?\d+(?:\.\d+)?(?:[eE][-+]?\d+)?)",
+        regex::escape(metric)
+    );
+    let re = Regex::new(&pattern).ok()?;
+    re.captures(stem)?.get(1)?.as_str().parse().ok()
+}
+
+pub(super) fn is_checkpoint(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| CHECKPOINT_EXTENSIONS.contains(&ext))
+}
+
+/// Names of the checkpoints to keep; `checkpoints` may be in any order
+pub fn select_kept(
+    checkpoints: &[SyncedCheckpoint],
+    retention: &RetentionConfig,
+) -> BTreeSet<String> {
+    let no_rules = retention.keep_last.is_none()
+        && retention.keep_best.is_none()
+        && retention.keep_every.is_none();
+    if no_rules {
+        return checkpoints.iter().map(|c| c.name.clone()).collect();
+    }
+
+    let mut newest_first: Vec<&SyncedCheckpoint> = checkpoints.iter().collect();
+    newest_first.sort_by(|a, b| b.modified.cmp(&a.modified).then(b.name.cmp(&a.name)));
+
+    let mut kept = BTreeSet::new();
+    // Always keep the newest, so a run can resume from it
+    if let Some(newest) = newest_first.first() {
+        kept.insert(newest.name.clone());
+    }
+    if let Some(n) = retention.keep_last {
+        kept.extend(newest_first.iter().take(n).map(|c| c.name.clone()));
+    }
+    if let Some(n) = retention.keep_best {
+        let mut scored: Vec<(&SyncedCheckpoint, f64)> = newest_first
+            .iter()
+            .filter_map(|c| Some((*c, c.metric?)))
+            .collect();
+        scored.sort_by(|a, b| {
+            let order = a.1.total_cmp(&b.1);
+            if retention.maximize {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+        kept.extend(scored.iter().take(n).map(|(c, _)| c.name.clone()));
+    }
+    if let Some(every) = retention.keep_every.filter(|k| *k > 0) {
+        kept.extend(
+            checkpoints
+                .iter()
+                .filter(|c| c.epoch.is_some_and(|e| e % every == 0))
+                .map(|c| c.name.clone()),
+        );
+    }
+    kept
+}
+
+/// Merge local files and S3 objects into one list keyed by file name
+#[cfg_attr(not(feature = "aws"), allow(dead_code))]
+fn merge(
+    local: Vec<(String, u64, SystemTime)>,
+    remote: Vec<(String, u64, SystemTime)>,
+    metric: &str,
+) -> Vec<SyncedCheckpoint> {
+    let mut by_name: BTreeMap<String, SyncedCheckpoint> = BTreeMap::new();
+    let remote = remote.into_iter().map(|(n, s, m)| (n, s, m, false));
+    for (name, size, modified, is_local) in local
+        .into_iter()
+        .map(|(n, s, m)| (n, s, m, true))
+        .chain(remote)
+    {
+        let checkpoint = by_name
+            .entry(name.clone())
+            .or_insert_with(|| SyncedCheckpoint {
+                epoch: parse_epoch(&name),
+                metric: parse_metric(&name, metric),
+                name,
+                modified,
+                local_size: None,
+                remote_size: None,
+            });
+        // Local mtime wins: S3's is the upload time
+        if is_local {
+            checkpoint.local_size = Some(size);
+        } else {
+            checkpoint.remote_size = Some(size);
+        }
+    }
+    by_name.into_values().collect()
+}
+
+/// Settled checkpoint files in `dir` as (name, size, modified)
+#[cfg_attr(not(feature = "aws"), allow(dead_code))]
+fn scan_local(dir: &Path) -> Result<Vec<(String, u64, SystemTime)>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let settle = Duration::from_secs(SETTLE_SECS);
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let metadata = entry.metadata()?;
+        if !metadata.is_file() || !is_checkpoint(&name) {
+            continue;
+        }
+        let modified = metadata.modified()?;
+        if modified.elapsed().unwrap_or_default() < settle {
+            continue;
+        }
+        files.push((name, metadata.len(), modified));
+    }
+    Ok(files)
+}
+
+/// Checkpoint objects directly under the prefix as (name, size, modified)
+#[cfg(feature = "aws")]
+pub(super) async fn list_remote(
+    client: &S3Client,
+    bucket: &str,
+    prefix: &str,
+) -> Result<Vec<(String, u64, SystemTime)>> {
+    let mut objects = Vec::new();
+    let mut continuation_token: Option<String> = None;
+    loop {
+        let mut request = client.list_objects_v2().bucket(bucket).prefix(prefix);
+        if let Some(token) = continuation_token.as_ref() {
+            request = request.continuation_token(token);
+        }
+        let response = request.send().await.map_err(|e| {
+            TrainctlError::S3(format!("Failed to list s3://{}/{}: {}", bucket, prefix, e))
+        })?;
+        for object in response.contents() {
+            let Some(name) = object.key().and_then(|k| k.strip_prefix(prefix)) else {
+                continue;
+            };
+            if name.contains('/') || !is_checkpoint(name) {
+                continue;
+            }
+            let modified = object
+                .last_modified()
+                .and_then(|t| SystemTime::try_from(*t).ok())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            objects.push((
+                name.to_string(),
+                object.size().unwrap_or(0).max(0) as u64,
+                modified,
+            ));
+        }
+        if response.is_truncated() != Some(true) {
+            break;
+        }
+        continuation_token = response.next_continuation_token().map(|s| s.to_string());
+    }
+    Ok(objects)
+}
+
+/// Run one sync pass
+#[cfg(feature = "aws")]
+pub async fn sync_once(client: &S3Client, options: &SyncOptions) -> Result<SyncReport> {
+    let (bucket, prefix) = crate::data_transfer::parse_s3_path(&options.dest)?;
+    let metric = options
+        .retention
+        .metric
+        .as_deref()
+        .unwrap_or(DEFAULT_METRIC);
+    let checkpoints = merge(
+        scan_local(&options.dir)?,
+        list_remote(client, &bucket, &prefix).await?,
+        metric,
+    );
+    let kept = select_kept(&checkpoints, &options.retention);
+
+    let mut report = SyncReport {
+        kept: kept.len(),
+        dry_run: options.dry_run,
+        ..Default::default()
+    };
+    let state_dir = PathBuf::from(".runctl").join("uploads");
+    for checkpoint in &checkpoints {
+        let key = format!("{}{}", prefix, checkpoint.name);
+        let local = options.dir.join(&checkpoint.name);
+        if kept.contains(&checkpoint.name) {
+            let needs_upload =
+                checkpoint.local_size.is_some() && checkpoint.local_size != checkpoint.remote_size;
+            if needs_upload {
+                if !options.dry_run {
+                    crate::data_transfer::upload_file_resumable(
+                        client, &bucket, &key, &local, &state_dir,
+                    )
+                    .await?;
+                }
+                report.uploaded.push(checkpoint.name.clone());
+            }
+            continue;
+        }
+
+        if checkpoint.remote_size.is_some() {
+            if !options.dry_run {
+                client
+                    .delete_object()
+                    .bucket(&bucket)
+                    .key(&key)
+                    .send()
+                    .await
+                    .map_err(|e| {
+                        TrainctlError::S3(format!(
+                            "Failed to delete s3://{}/{}: {}",
+                            bucket, key, e
+                        ))
+                    })?;
+            }
+            report.deleted_remote.push(checkpoint.name.clone());
+        }
+        if checkpoint.local_size.is_some() {
+            if !options.dry_run {
+                fs::remove_file(&local).map_err(|e| {
+                    TrainctlError::Io(std::io::Error::other(format!(
+                        "Failed to delete {}: {}",
+                        local.display(),
+                        e
+                    )))
+                })?;
+            }
+            report.deleted_local.push(checkpoint.name.clone());
+        }
+    }
+    Ok(report)
+}
+
+#[cfg_attr(not(feature = "aws"), allow(dead_code))]
+fn print_report(report: &SyncReport, dest: &str, output_format: &str) -> Result<()> {
+    if output_format == "json" {
+        println!("{}", serde_json::to_string(report)?);
+        return Ok(());
+    }
+    let prefix = if report.dry_run { "[DRY RUN] " } else { "" };
+    for name in &report.uploaded {
+        println!("{}Uploaded {} -> {}{}", prefix, name, dest, name);
+    }
+    for name in &report.deleted_remote {
+        println!("{}Deleted {}{}", prefix, dest, name);
+    }
+    for name in &report.deleted_local {
+        println!("{}Deleted local {}", prefix, name);
+    }
+    Ok(())
+}
+
+/// Default destination: `s3://<aws.s3_bucket>/checkpoints/<project>/`
+pub fn resolve_sync_dest(dest: Option<String>, config: &Config) -> Result<String> {
+    let dest = match dest.or_else(|| config.checkpoint.sync_dest.clone()) {
+        Some(dest) => dest,
+        None => {
+            let bucket = config
+                .aws
+                .as_ref()
+                .and_then(|aws| aws.s3_bucket.as_deref())
+                .ok_or_else(|| TrainctlError::Validation {
+                    field: "dest".to_string(),
+                    reason: "No destination: pass --dest s3://bucket/prefix/, set [checkpoint] sync_dest or [aws] s3_bucket".to_string(),
+                })?;
+            format!(
+                "s3://{}/checkpoints/{}/",
+                bucket,
+                crate::utils::get_project_name(None, config)
+            )
+        }
+    };
+    crate::validation::validate_s3_path(&dest)?;
+    Ok(if dest.ends_with('/') {
+        dest
+    } else {
+        format!("{}/", dest)
+    })
+}
+
+/// Sync once, or with `watch` every `interval_secs` until Ctrl-C
+#[cfg(feature = "aws")]
+pub(crate) async fn run_sync(options: SyncOptions, output_format: &str) -> Result<()> {
+    let aws_config = crate::aws_utils::shared_sdk_config().await;
+    let client = S3Client::new(&aws_config);
+
+    if !options.watch {
+        let report = sync_once(&client, &options).await?;
+        print_report(&report, &options.dest, output_format)?;
+        if output_format != "json" {
+            println!(
+                "{} checkpoint(s) kept in {} and {}",
+                report.kept,
+                options.dir.display(),
+                options.dest
+            );
+        }
+        return Ok(());
+    }
+
+    if output_format != "json" {
+        println!(
+            "Syncing {} to {} every {}s (Ctrl-C to stop)",
+            options.dir.display(),
+            options.dest,
+            options.interval_secs
+        );
+    }
+    let interval = Duration::from_secs(options.interval_secs.max(1));
+    loop {
+        match sync_once(&client, &options).await {
+            Ok(report) if !report.is_empty() => {
+                print_report(&report, &options.dest, output_format)?
+            }
+            Ok(_) => {}
+            // Keep watching through transient S3 errors; resumable uploads pick up next pass
+            Err(e) => warn!("Checkpoint sync failed: {}", e),
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    // One last pass so the final checkpoint isn't left behind
+    let report = sync_once(&client, &options).await?;
+    print_report(&report, &options.dest, output_format)
+}
+
+/// `checkpoint sync` uploads to S3, which this build can't reach
+#[cfg(not(feature = "aws"))]
+pub(crate) async fn run_sync(_options: SyncOptions, _output_format: &str) -> Result<()> {
+    Err(crate::error::ConfigError::FeatureNotCompiled("aws".to_string()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkpoint(name: &str, age_secs: u64) -> SyncedCheckpoint {
+        SyncedCheckpoint {
+            name: name.to_string(),
+            modified: SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000 - age_secs),
+            epoch: parse_epoch(name),
+            metric: parse_metric(name, "val_loss"),
+            local_size: Some(1),
+            remote_size: None,
+        }
+    }
+
+    fn names(kept: &BTreeSet<String>) -> Vec<&str> {
+        kept.iter().map(|s| s.as_str()).collect()
+    }
+
+    #[test]
+    fn test_parse_epoch() {
+        assert_eq!(parse_epoch("epoch_12.pt"), Some(12));
+        assert_eq!(parse_epoch("epoch=3-step=400.ckpt"), Some(3));
+        assert_eq!(parse_epoch("model_ep7.pt"), Some(7));
+        assert_eq!(parse_epoch("best.pt"), None);
+        assert_eq!(parse_epoch("deep12.pt"), None);
+    }
+
+    #[test]
+    fn test_parse_metric() {
+        assert_eq!(
+            parse_metric("epoch_1_val_loss=0.213.pt", "val_loss"),
+            Some(0.213)
+        );
+        assert_eq!(parse_metric("acc_0.91.safetensors", "acc"), Some(0.91));
+        assert_eq!(parse_metric("loss-1e-3.pt", "loss"), Some(0.001));
+        assert_eq!(parse_metric("epoch_1.pt", "val_loss"), None);
+    }
+
+    #[test]
+    fn test_select_kept_without_rules_keeps_everything() {
+        let checkpoints = vec![checkpoint("epoch_1.pt", 20), checkpoint("epoch_2.pt", 10)];
+        assert_eq!(
+            select_kept(&checkpoints, &RetentionConfig::default()).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_select_kept_combines_rules() {
+        let checkpoints = vec![
+            checkpoint("epoch_1_val_loss=0.9.pt", 50),
+            checkpoint("epoch_2_val_loss=0.2.pt", 40),
+            checkpoint("epoch_3_val_loss=0.5.pt", 30),
+            checkpoint("epoch_4_val_loss=0.4.pt", 20),
+            checkpoint("epoch_5_val_loss=0.6.pt", 10),
+        ];
+        let retention = RetentionConfig {
+            keep_last: Some(1),
+            keep_best: Some(1),
+            metric: Some("val_loss".to_string()),
+            keep_every: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            names(&select_kept(&checkpoints, &retention)),
+            vec![
+                "epoch_2_val_loss=0.2.pt",
+                "epoch_3_val_loss=0.5.pt",
+                "epoch_5_val_loss=0.6.pt"
+            ]
+        );
+
+        let maximize = RetentionConfig {
+            keep_best: Some(1),
+            maximize: true,
+            ..Default::default()
+        };
+        // Best by highest value, plus the newest
+        assert_eq!(
+            names(&select_kept(&checkpoints, &maximize)),
+            vec!["epoch_1_val_loss=0.9.pt", "epoch_5_val_loss=0.6.pt"]
+        );
+    }
+
+    #[test]
+    fn test_merge_tracks_both_sides() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let merged = merge(
+            vec![("epoch_1.pt".to_string(), 10, now)],
+            vec![
+                ("epoch_1.pt".to_string(), 10, now),
+                ("epoch_0.pt".to_string(), 8, now),
+            ],
+            "loss",
+        );
+        assert_eq!(merged.len(), 2);
+        let epoch_1 = merged.iter().find(|c| c.name == "epoch_1.pt").unwrap();
+        assert_eq!(
+            (epoch_1.local_size, epoch_1.remote_size),
+            (Some(10), Some(10))
+        );
+        let epoch_0 = merged.iter().find(|c| c.name == "epoch_0.pt").unwrap();
+        assert_eq!((epoch_0.local_size, epoch_0.remote_size), (None, Some(8)));
+    }
+
+    #[test]
+    fn test_resolve_sync_dest() {
+        let mut config = Config::default();
+        config.checkpoint.sync_dest = Some("s3://bucket/runs/a".to_string());
+        assert_eq!(
+            resolve_sync_dest(None, &config).unwrap(),
+            "s3://bucket/runs/a/"
+        );
+        assert_eq!(
+            resolve_sync_dest(Some("s3://other/x/".to_string()), &config).unwrap(),
+            "s3://other/x/"
+        );
+    }
+}
@@ -0,0 +1,431 @@
+//! SHA-256 checksum manifests for dataset transfers
+//!
+//! A manifest lists the SHA-256 of every file under a directory as
+//! `<hex>  <relative path>` lines, the `sha256sum` format, so a copy can also
+//! be checked with `sha256sum -c SHA256SUMS`. `runctl transfer --manifest`
+//! computes one before uploading and stores it next to the data:
+//! `<prefix>/SHA256SUMS` for a directory, `<key>.sha256` for a single file.
+//! Downloads with `--manifest` check the files written against it, and
+//! `runctl s3 verify` audits an existing local copy.
+//!
+//! Hashes are of the files as they are locally, so a manifest for an
+//! `--encrypt` upload describes the decrypted files a download produces.
+
+use crate::error::{Result, TrainctlError};
+use indicatif::ProgressBar;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+#[cfg(feature = "aws")]
+use aws_sdk_s3::Client as S3Client;
+
+/// Name of the manifest stored under a directory's prefix
+pub const MANIFEST_NAME: &str = "SHA256SUMS";
+
+/// Suffix of the manifest stored next to a single object
+pub const SINGLE_FILE_SUFFIX: &str = ".sha256";
+
+/// SHA-256 of every file in a directory (or of one file), by relative path
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChecksumManifest {
+    files: BTreeMap<String, String>,
+}
+
+/// Outcome of checking files against a manifest
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Verification {
+    /// Files whose hash matched
+    pub verified: usize,
+    /// Files whose contents differ from the manifest
+    pub mismatched: Vec<String>,
+    /// Files in the manifest that don't exist locally
+    pub missing: Vec<String>,
+    /// Local files the manifest doesn't list (reported, not failures)
+    pub extra: Vec<String>,
+}
+
+impl Verification {
+    /// Whether every file in the manifest is present and matches
+    pub fn is_ok(&self) -> bool {
+        self.mismatched.is_empty() && self.missing.is_empty()
+    }
+
+    /// Print the files that failed, then error unless everything matched
+    pub fn check(&self, location: &str) -> Result<()> {
+        for name in &self.mismatched {
+            eprintln!("   MISMATCH {}", name);
+        }
+        for name in &self.missing {
+            eprintln!("   MISSING  {}", name);
+        }
+        if self.is_ok() {
+            return Ok(());
+        }
+        Err(TrainctlError::DataTransfer(format!(
+            "Checksum verification failed for {}: {} mismatched, {} missing of {} files",
+            location,
+            self.mismatched.len(),
+            self.missing.len(),
+            self.verified + self.mismatched.len() + self.missing.len()
+        )))
+    }
+}
+
+/// SHA-256 of a file's contents, as lowercase hex
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Files under `root` as (relative path with `/` separators, path), sorted
+///
+/// A manifest at the top of `root` is left out.
+pub(crate) fn list_files(root: &Path) -> Vec<(String, PathBuf)> {
+    let mut files: Vec<_> = walkdir::WalkDir::new(root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let relative = e.path().strip_prefix(root).ok()?;
+            let name = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            (name != MANIFEST_NAME).then(|| (name, e.path().to_path_buf()))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Hash `files` on a few threads, with a progress bar over their total size
+fn hash_files(files: &[(String, PathBuf)]) -> Result<Vec<String>> {
+    let total_bytes: u64 = files
+        .iter()
+        .filter_map(|(_, path)| std::fs::metadata(path).ok())
+        .map(|m| m.len())
+        .sum();
+    let pb = ProgressBar::new(total_bytes);
+    pb.set_style(crate::output_style::bar(
+        "Hashing [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})",
+    ));
+    let progress =
+        crate::progress::TransferProgress::new("checksums.hash", files.len() as u64, total_bytes);
+
+    let workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+        .min(files.len())
+        .max(1);
+    let next = AtomicUsize::new(0);
+    let hashes: Mutex<Vec<Option<Result<String>>>> =
+        Mutex::new((0..files.len()).map(|_| None).collect());
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some((name, path)) = files.get(i) else {
+                    break;
+                };
+                let result = sha256_file(path);
+                let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+                pb.inc(size);
+                progress.file_done(name, size);
+                hashes.lock().unwrap_or_else(|e| e.into_inner())[i] = Some(result);
+            });
+        }
+    });
+    pb.finish_and_clear();
+
+    hashes
+        .into_inner()
+        .unwrap_or_else(|e| e.into_inner())
+        .into_iter()
+        .map(|hash| hash.expect("every file is hashed"))
+        .collect()
+}
+
+impl ChecksumManifest {
+    /// Hash every file under `path`, or `path` itself when it is a file
+    pub fn compute(path: &Path) -> Result<Self> {
+        let files = if path.is_dir() {
+            list_files(path)
+        } else {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .ok_or_else(|| TrainctlError::Validation {
+                    field: "path".to_string(),
+                    reason: format!("Not a file or directory: {}", path.display()),
+                })?;
+            vec![(name, path.to_path_buf())]
+        };
+        let hashes = hash_files(&files)?;
+        Ok(Self {
+            files: files
+                .into_iter()
+                .map(|(name, _)| name)
+                .zip(hashes)
+                .collect(),
+        })
+    }
+
+    /// Parse `sha256sum` output (a `*` before the name, binary mode, is accepted)
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut files = BTreeMap::new();
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let parsed = line.split_once(' ').and_then(|(hash, rest)| {
+                let name = rest.strip_prefix(' ').or_else(|| rest.strip_prefix('*'))?;
+                let valid = hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit());
+                (valid && !name.is_empty()).then(|| (name.to_string(), hash.to_lowercase()))
+            });
+            let Some((name, hash)) = parsed else {
+                return Err(TrainctlError::Validation {
+                    field: "manifest".to_string(),
+                    reason: format!("Line {} is not '<sha256>  <path>': {}", i + 1, line),
+                });
+            };
+            files.insert(name, hash);
+        }
+        Ok(Self { files })
+    }
+
+    /// The manifest in `sha256sum` format
+    pub fn to_text(&self) -> String {
+        self.files
+            .iter()
+            .map(|(name, hash)| format!("{}  {}\n", hash, name))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Check `path` against a manifest for a single file, whatever its name
+    pub fn verify_file(&self, path: &Path) -> Result<Verification> {
+        let [(_, expected)] = self.files.iter().collect::<Vec<_>>()[..] else {
+            return Err(TrainctlError::Validation {
+                field: "manifest".to_string(),
+                reason: format!(
+                    "Manifest for a single file lists {} files",
+                    self.files.len()
+                ),
+            });
+        };
+        let name = path.display().to_string();
+        let mut verification = Verification::default();
+        if !path.is_file() {
+            verification.missing.push(name);
+        } else if sha256_file(path)? == *expected {
+            verification.verified = 1;
+        } else {
+            verification.mismatched.push(name);
+        }
+        Ok(verification)
+    }
+
+    /// Check the files under `root` against the manifest
+    pub fn verify(&self, root: &Path) -> Result<Verification> {
+        let present: Vec<(String, PathBuf)> = self
+            .files
+            .keys()
+            .map(|name| (name.clone(), root.join(name)))
+            .filter(|(_, path)| path.is_file())
+            .collect();
+        let hashes = hash_files(&present)?;
+
+        let mut verification = Verification {
+            missing: self
+                .files
+                .keys()
+                .filter(|name| !root.join(name).is_file())
+                .cloned()
+                .collect(),
+            ..Verification::default()
+        };
+        for ((name, _), hash) in present.iter().zip(hashes) {
+            if self.files[name] == hash {
+                verification.verified += 1;
+            } else {
+                verification.mismatched.push(name.clone());
+            }
+        }
+        if root.is_dir() {
+            verification.extra = list_files(root)
+                .into_iter()
+                .map(|(name, _)| name)
+                .filter(|name| !self.files.contains_key(name))
+                .collect();
+        }
+        Ok(verification)
+    }
+}
+
+/// Key of the manifest for an upload to `key`
+pub fn manifest_key(key: &str, is_dir: bool) -> String {
+    if is_dir {
+        let prefix = key.trim_end_matches('/');
+        if prefix.is_empty() {
+            MANIFEST_NAME.to_string()
+        } else {
+            format!("{}/{}", prefix, MANIFEST_NAME)
+        }
+    } else {
+        format!("{}{}", key, SINGLE_FILE_SUFFIX)
+    }
+}
+
+/// Where a download of the single object `key` to `destination` is written
+pub fn download_target(key: &str, destination: &Path) -> PathBuf {
+    if destination.is_dir() {
+        destination.join(key.rsplit('/').next().unwrap_or(key))
+    } else {
+        destination.to_path_buf()
+    }
+}
+
+/// Store `manifest` in S3 for an upload to `key`
+#[cfg(feature = "aws")]
+pub async fn upload_manifest(
+    client: &S3Client,
+    bucket: &str,
+    key: &str,
+    is_dir: bool,
+    manifest: &ChecksumManifest,
+) -> Result<String> {
+    let manifest_key = manifest_key(key, is_dir);
+    client
+        .put_object()
+        .bucket(bucket)
+        .key(&manifest_key)
+        .content_type("text/plain")
+        .body(manifest.to_text().into_bytes().into())
+        .send()
+        .await
+        .map_err(|e| {
+            TrainctlError::S3(format!(
+                "Failed to upload checksum manifest s3://{}/{}: {}",
+                bucket, manifest_key, e
+            ))
+        })?;
+    Ok(manifest_key)
+}
+
+/// The manifest for `key`, and whether it describes a single object
+///
+/// A key that doesn't end in `/` is first looked up as a single file
+/// (`<key>.sha256`), then as a directory (`<key>/SHA256SUMS`). `None` when
+/// neither exists.
+#[cfg(feature = "aws")]
+pub async fn fetch_manifest(
+    client: &S3Client,
+    bucket: &str,
+    key: &str,
+) -> Result<Option<(ChecksumManifest, bool)>> {
+    let mut candidates = Vec::new();
+    if !key.is_empty() && !key.ends_with('/') {
+        candidates.push((manifest_key(key, false), true));
+    }
+    candidates.push((manifest_key(key, true), false));
+
+    for (manifest_key, single) in candidates {
+        let response = match client
+            .get_object()
+            .bucket(bucket)
+            .key(&manifest_key)
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                let e = e.into_service_error();
+                if e.is_no_such_key() {
+                    continue;
+                }
+                return Err(TrainctlError::S3(format!(
+                    "Failed to read checksum manifest s3://{}/{}: {}",
+                    bucket, manifest_key, e
+                )));
+            }
+        };
+        let body = response.body.collect().await.map_err(|e| {
+            TrainctlError::S3(format!(
+                "Failed to read checksum manifest s3://{}/{}: {}",
+                bucket, manifest_key, e
+            ))
+        })?;
+        let text = String::from_utf8_lossy(&body.into_bytes()).into_owned();
+        return Ok(Some((ChecksumManifest::parse(&text)?, single)));
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_parse_and_verify() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("train")).unwrap();
+        std::fs::write(dir.path().join("train/a.bin"), b"abc").unwrap();
+        std::fs::write(dir.path().join("b.txt"), b"").unwrap();
+
+        let manifest = ChecksumManifest::compute(dir.path()).unwrap();
+        let text = manifest.to_text();
+        assert_eq!(
+            text,
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  b.txt\n\
+             ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  train/a.bin\n"
+        );
+        assert_eq!(ChecksumManifest::parse(&text).unwrap(), manifest);
+        assert!(ChecksumManifest::parse("abc  file\n").is_err());
+
+        // A stored manifest is not part of the dataset
+        std::fs::write(dir.path().join(MANIFEST_NAME), &text).unwrap();
+        let verification = manifest.verify(dir.path()).unwrap();
+        assert!(verification.is_ok());
+        assert_eq!(verification.verified, 2);
+        assert!(verification.extra.is_empty());
+
+        std::fs::write(dir.path().join("train/a.bin"), b"abd").unwrap();
+        std::fs::remove_file(dir.path().join("b.txt")).unwrap();
+        std::fs::write(dir.path().join("c.txt"), b"new").unwrap();
+        let verification = manifest.verify(dir.path()).unwrap();
+        assert!(!verification.is_ok());
+        assert_eq!(verification.mismatched, vec!["train/a.bin"]);
+        assert_eq!(verification.missing, vec!["b.txt"]);
+        assert_eq!(verification.extra, vec!["c.txt"]);
+    }
+
+    #[test]
+    fn test_manifest_keys() {
+        assert_eq!(manifest_key("data/train", true), "data/train/SHA256SUMS");
+        assert_eq!(manifest_key("data/train/", true), "data/train/SHA256SUMS");
+        assert_eq!(manifest_key("", true), "SHA256SUMS");
+        assert_eq!(manifest_key("models/m.pt", false), "models/m.pt.sha256");
+    }
+}
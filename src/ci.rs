@@ -0,0 +1,330 @@
+//! CI integration
+//!
+//! `runctl ci generate github` writes a GitHub Actions workflow that runs a
+//! training job end to end with runctl: assume an AWS role over OIDC, create
+//! an instance, train with `--wait` (the step fails with the job's outcome,
+//! see `exit_codes`), upload the training log as a workflow artifact, and
+//! terminate the instance even when an earlier step failed.
+//!
+//! `--output github` makes any command report failures as workflow commands
+//! (`::error ...`, `::notice ...`) so they show up as annotations on the run.
+
+use crate::config::Config;
+use crate::error::{JobOutcome, Result, TrainctlError};
+use clap::Subcommand;
+use std::path::{Path, PathBuf};
+
+/// Default location for the generated workflow
+const DEFAULT_WORKFLOW_PATH: &str = ".github/workflows/runctl-train.yml";
+
+#[derive(Subcommand, Clone)]
+pub enum CiCommands {
+    /// Generate a CI workflow that trains with runctl
+    ///
+    /// Examples:
+    ///   runctl ci generate github --script training/train.py
+    ///   runctl ci generate github --script train.py --instance-type g5.xlarge --spot
+    ///   runctl ci generate github --script train.py --stdout
+    Generate {
+        #[command(subcommand)]
+        target: GenerateTarget,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+pub enum GenerateTarget {
+    /// GitHub Actions workflow (OIDC role, train, artifacts, teardown)
+    Github(GithubWorkflowArgs),
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct GithubWorkflowArgs {
+    /// Training script, relative to the repository root
+    #[arg(long, value_name = "SCRIPT")]
+    pub script: String,
+
+    /// EC2 instance type (default: [aws] default_instance_type)
+    #[arg(long, value_name = "INSTANCE_TYPE", value_parser = crate::validation::instance_type_arg)]
+    pub instance_type: Option<String>,
+
+    /// Use a spot instance
+    #[arg(long)]
+    pub spot: bool,
+
+    /// AWS region (default: [aws] region)
+    #[arg(long)]
+    pub region: Option<String>,
+
+    /// IAM role to assume via OIDC (default: the AWS_ROLE_ARN repository secret)
+    #[arg(long, value_name = "ARN")]
+    pub role_arn: Option<String>,
+
+    /// Training timeout in minutes
+    #[arg(long, value_name = "MINUTES", default_value = "120")]
+    pub timeout: u64,
+
+    /// Where to write the workflow
+    #[arg(long, value_name = "PATH", default_value = DEFAULT_WORKFLOW_PATH)]
+    pub path: PathBuf,
+
+    /// Print the workflow instead of writing it
+    #[arg(long)]
+    pub stdout: bool,
+
+    /// Overwrite an existing workflow file
+    #[arg(long)]
+    pub force: bool,
+}
+
+pub async fn handle_command(cmd: CiCommands, config: &Config, output_format: &str) -> Result<()> {
+    match cmd {
+        CiCommands::Generate {
+            target: GenerateTarget::Github(args),
+        } => {
+            let workflow = github_workflow(&args, config);
+            if args.stdout {
+                print!("{}", workflow);
+                return Ok(());
+            }
+            write_workflow(&args.path, &workflow, args.force)?;
+            if output_format == "json" {
+                let result = serde_json::json!({
+                    "success": true,
+                    "path": args.path,
+                });
+                println!("{}", serde_json::to_string_pretty(&result)?);
+            } else {
+                println!("Wrote {}", args.path.display());
+                if args.role_arn.is_none() {
+                    println!(
+                        "Add an AWS_ROLE_ARN repository secret with a role that trusts \
+                         token.actions.githubusercontent.com"
+                    );
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+fn write_workflow(path: &Path, workflow: &str, force: bool) -> Result<()> {
+    if path.exists() && !force {
+        return Err(TrainctlError::ResourceExists {
+            resource_type: "workflow".to_string(),
+            resource_id: format!("{} (use --force to overwrite)", path.display()),
+        });
+    }
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        crate::utils::ensure_dir(parent)?;
+    }
+    std::fs::write(path, workflow)?;
+    Ok(())
+}
+
+/// Render the GitHub Actions workflow
+pub fn github_workflow(args: &GithubWorkflowArgs, config: &Config) -> String {
+    let aws = config.aws.as_ref();
+    let instance_type = args
+        .instance_type
+        .clone()
+        .or_else(|| aws.map(|a| a.default_instance_type.clone()))
+        .unwrap_or_else(|| "t3.medium".to_string());
+    let region = args
+        .region
+        .clone()
+        .or_else(|| aws.map(|a| a.region.clone()))
+        .unwrap_or_else(|| "us-east-1".to_string());
+    let role = args
+        .role_arn
+        .clone()
+        .unwrap_or_else(|| "${{ secrets.AWS_ROLE_ARN }}".to_string());
+    let spot = if args.spot { " --spot" } else { "" };
+
+    format!(
+        r#"# Generated by `runctl ci generate github`
+name: runctl-train
+
+on:
+  workflow_dispatch:
+
+permissions:
+  id-token: write # OIDC role assumption
+  contents: read
+
+jobs:
+  train:
+    runs-on: ubuntu-latest
+    timeout-minutes: {job_timeout}
+    env:
+      RUNCTL_NON_INTERACTIVE: "1"
+      TRAIN_SCRIPT: {script}
+    steps:
+      - uses: actions/checkout@v4
+
+      - name: Configure AWS credentials
+        uses: aws-actions/configure-aws-credentials@v4
+        with:
+          role-to-assume: {role}
+          aws-region: {region}
+
+      - name: Install runctl
+        run: cargo install --locked --git https://github.com/arclabs561/runctl runctl
+
+      - name: Create instance
+        id: instance
+        run: |
+          INSTANCE_ID=$(runctl aws create {instance_type}{spot} --wait --output instance-id)
+          echo "id=$INSTANCE_ID" >> "$GITHUB_OUTPUT"
+
+      - name: Train
+        run: >-
+          runctl --output github aws train ${{{{ steps.instance.outputs.id }}}}
+          "$TRAIN_SCRIPT" --sync-code --wait --timeout {timeout}
+
+      - name: Collect training log
+        if: always() && steps.instance.outputs.id != ''
+        run: runctl aws monitor ${{{{ steps.instance.outputs.id }}}} > training.log || true
+
+      - name: Upload artifacts
+        if: always() && steps.instance.outputs.id != ''
+        uses: actions/upload-artifact@v4
+        with:
+          name: training-log
+          path: training.log
+
+      - name: Tear down
+        if: always() && steps.instance.outputs.id != ''
+        run: runctl --output github aws terminate ${{{{ steps.instance.outputs.id }}}} --force
+"#,
+        // Leave room for instance startup and teardown around the training timeout
+        job_timeout = args.timeout + 30,
+        script = yaml_quote(&args.script),
+        timeout = args.timeout,
+    )
+}
+
+/// Quote a value for YAML when it isn't a plain scalar
+fn yaml_quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '.' | '_' | '-'));
+    if plain {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+}
+
+/// Escape data for a workflow command message
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a workflow command property value
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Format a GitHub Actions workflow command (`::error title=...::message`)
+pub fn github_annotation(level: &str, title: &str, message: &str) -> String {
+    format!(
+        "::{} title={}::{}",
+        level,
+        escape_property(title),
+        escape_data(message)
+    )
+}
+
+/// Annotations for a failed command under `--output github`
+///
+/// Job timeouts add a notice that the job keeps running, since the wait
+/// giving up does not stop training.
+pub fn github_error_annotations(error: &anyhow::Error, exit_code: i32) -> Vec<String> {
+    let mut lines = vec![github_annotation(
+        "error",
+        &format!("runctl failed (exit {})", exit_code),
+        &format!("{:#}", error),
+    )];
+    if let Some(TrainctlError::Job {
+        job_id,
+        outcome: JobOutcome::TimedOut,
+        ..
+    }) = error.downcast_ref::<TrainctlError>()
+    {
+        lines.push(github_annotation(
+            "notice",
+            "Training still running",
+            &format!(
+                "{} was not stopped; check it with `runctl jobs wait {}` or terminate it",
+                job_id, job_id
+            ),
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args() -> GithubWorkflowArgs {
+        GithubWorkflowArgs {
+            script: "training/train.py".to_string(),
+            instance_type: Some("g5.xlarge".to_string()),
+            spot: true,
+            region: None,
+            role_arn: None,
+            timeout: 90,
+            path: PathBuf::from(DEFAULT_WORKFLOW_PATH),
+            stdout: true,
+            force: false,
+        }
+    }
+
+    #[test]
+    fn test_github_workflow() {
+        let workflow = github_workflow(&args(), &Config::default());
+        assert!(workflow.contains("id-token: write"));
+        assert!(workflow.contains("role-to-assume: ${{ secrets.AWS_ROLE_ARN }}"));
+        assert!(workflow.contains("aws-region: us-east-1"));
+        assert!(workflow.contains("runctl aws create g5.xlarge --spot --wait --output instance-id"));
+        assert!(workflow.contains("TRAIN_SCRIPT: training/train.py"));
+        assert!(workflow.contains("--wait --timeout 90"));
+        assert!(workflow.contains("timeout-minutes: 120"));
+        assert!(workflow.contains("aws terminate ${{ steps.instance.outputs.id }} --force"));
+        assert_eq!(workflow.matches("\n      - ").count(), 8);
+        assert!(!workflow.contains('\t'));
+    }
+
+    #[test]
+    fn test_yaml_quote() {
+        assert_eq!(yaml_quote("train.py"), "train.py");
+        assert_eq!(yaml_quote("my script.py"), "'my script.py'");
+        assert_eq!(yaml_quote("it's.py"), "'it''s.py'");
+    }
+
+    #[test]
+    fn test_github_annotation_escaping() {
+        assert_eq!(
+            github_annotation("error", "runctl: failed, badly", "50% done\nthen broke"),
+            "::error title=runctl%3A failed%2C badly::50%25 done%0Athen broke"
+        );
+    }
+
+    #[test]
+    fn test_github_error_annotations_timeout_notice() {
+        let error = anyhow::Error::from(TrainctlError::Job {
+            job_id: "i-0123456789abcdef0".to_string(),
+            outcome: JobOutcome::TimedOut,
+            message: "still running".to_string(),
+        });
+        let lines = github_error_annotations(&error, 11);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("::error title=runctl failed (exit 11)::"));
+        assert!(lines[1].starts_with("::notice "));
+    }
+}
@@ -12,9 +12,26 @@
 //!
 //! - `[aws]`: AWS-specific settings (region, instance types, spot pricing)
 //! - `[runpod]`: RunPod API configuration
+//! - `[azure]`: Azure resource group, region and VM defaults (`runctl azure`)
+//! - `[batch]`: AWS Batch job queue and container image (`--provider batch`)
+//! - `[kubernetes]`: Cluster context, namespace and pod shape (`--provider k8s`)
 //! - `[local]`: Local execution settings
 //! - `[checkpoint]`: Checkpoint management defaults
 //! - `[monitoring]`: Logging and monitoring configuration
+//! - `[bootstrap]`: Instance user-data customization (hooks, packages, mounts)
+//! - `[presets.<name>]`: Named machine shapes for `aws create --preset <name>`
+//! - `[notifications]`: Desktop notifications for job completion, failure, and alerts
+//! - `[accounts.<name>]`: Extra AWS accounts aggregated by resource listings
+//! - `[budget]`: Per-project and per-account cost limits and what happens when one is exceeded
+//! - `[job]`: Completion markers and health checks for `aws train --wait`
+//! - `[transfer]`: Egress cost confirmation threshold, part size, parallelism and bandwidth for transfers
+//! - `[encryption]`: Key source for client-side encryption (`--encrypt`)
+//! - `[secrets]`: Where `runctl secrets` stores API keys and tokens
+//! - `[env]`: Environment variables exported to training scripts (`local`, `exec`, `aws train`)
+//! - `[sync]`: Which files code sync ships to instances
+//! - `[cache]`: Dataset cache tiers on instances (`runctl cache`)
+//! - `[cli]`: Non-interactive default for CI and cron
+//! - `[logging]`: JSON log file for the CLI's own tracing output
 //!
 //! ## Defaults
 //!
@@ -32,6 +49,7 @@ use crate::error::{ConfigError, Result, TrainctlError};
 use crate::resource_tracking::ResourceTracker;
 use clap::Subcommand;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -39,9 +57,51 @@ use std::sync::Arc;
 pub struct Config {
     pub runpod: Option<RunpodConfig>,
     pub aws: Option<AwsConfig>,
+    #[serde(default)]
+    pub azure: Option<AzureConfig>,
+    #[serde(default)]
+    pub batch: Option<BatchConfig>,
+    #[serde(default)]
+    pub kubernetes: Option<KubernetesConfig>,
     pub local: Option<LocalConfig>,
     pub checkpoint: CheckpointConfig,
     pub monitoring: MonitoringConfig,
+    #[serde(default)]
+    pub bootstrap: BootstrapConfig,
+    #[serde(default)]
+    pub presets: BTreeMap<String, PresetConfig>,
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+    #[serde(default)]
+    pub accounts: BTreeMap<String, AccountConfig>,
+    #[serde(default)]
+    pub budget: BudgetConfig,
+    #[serde(default)]
+    pub job: JobConfig,
+    #[serde(default)]
+    pub transfer: TransferConfig,
+    #[serde(default)]
+    pub encryption: EncryptionConfig,
+    #[serde(default)]
+    pub secrets: SecretsConfig,
+    /// Environment variables for training scripts (see `crate::env_vars`)
+    ///
+    /// Values may be `secret://name` references.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub sync: SyncConfig,
+    #[serde(default)]
+    pub cache: CacheConfig,
+    #[serde(default)]
+    pub ssh: SshConfig,
+    #[serde(default)]
+    pub cli: CliConfig,
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    /// Command aliases expanded before argument parsing (see `aliases`)
+    #[serde(default)]
+    pub aliases: BTreeMap<String, crate::aliases::AliasValue>,
     #[serde(skip)]
     pub resource_tracker: Option<Arc<ResourceTracker>>,
 }
@@ -51,9 +111,29 @@ impl std::fmt::Debug for Config {
         f.debug_struct("Config")
             .field("runpod", &self.runpod)
             .field("aws", &self.aws)
+            .field("azure", &self.azure)
+            .field("batch", &self.batch)
+            .field("kubernetes", &self.kubernetes)
             .field("local", &self.local)
             .field("checkpoint", &self.checkpoint)
             .field("monitoring", &self.monitoring)
+            .field("bootstrap", &self.bootstrap)
+            .field("presets", &self.presets)
+            .field("notifications", &self.notifications)
+            .field("accounts", &self.accounts)
+            .field("budget", &self.budget)
+            .field("job", &self.job)
+            .field("transfer", &self.transfer)
+            .field("encryption", &self.encryption)
+            .field("secrets", &self.secrets)
+            // Values can be credentials
+            .field("env", &self.env.keys().collect::<Vec<_>>())
+            .field("cli", &self.cli)
+            .field("logging", &self.logging)
+            .field("sync", &self.sync)
+            .field("cache", &self.cache)
+            .field("ssh", &self.ssh)
+            .field("aliases", &self.aliases)
             .field(
                 "resource_tracker",
                 &if self.resource_tracker.is_some() {
@@ -87,6 +167,199 @@ pub struct AwsConfig {
     pub default_project_name: Option<String>,
     /// User identifier for multi-user environments (auto-detected from username if not set)
     pub user_id: Option<String>,
+    /// How long a resolved Deep Learning AMI is reused: `86400` or `"24h"`; 0 disables the cache
+    #[serde(
+        default = "default_ami_cache_ttl",
+        deserialize_with = "crate::units::deserialize_secs"
+    )]
+    pub ami_cache_ttl_secs: u64,
+    /// Install the idle watchdog on every new instance (see `runctl aws autostop`)
+    #[serde(default)]
+    pub autostop_idle_minutes: Option<u64>,
+    /// What the watchdog does once the instance is idle: `stop` or `terminate`
+    #[serde(default)]
+    pub autostop_action: AutostopAction,
+}
+
+fn default_ami_cache_ttl() -> u64 {
+    24 * 60 * 60
+}
+
+/// Azure VM settings for `runctl azure`
+///
+/// Uses the Azure CLI (`az`) and its login; run `az login` first. VMs, and a
+/// shared `runctl-vnet`/`runctl-nsg`, are created in `resource_group`, which
+/// is created in `location` if it doesn't exist.
+///
+/// ```toml
+/// [azure]
+/// subscription = "00000000-0000-0000-0000-000000000000"
+/// resource_group = "runctl"
+/// location = "eastus"
+/// default_vm_size = "Standard_NC4as_T4_v3"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AzureConfig {
+    /// Subscription ID or name (default: the `az` CLI's current subscription)
+    pub subscription: Option<String>,
+    pub resource_group: String,
+    pub location: String,
+    pub default_vm_size: String,
+    /// Image URN; the default Ubuntu HPC image ships NVIDIA drivers and CUDA
+    pub image: String,
+    pub admin_username: String,
+    /// Private key for SSH; `<ssh_key>.pub` is installed on new VMs
+    pub ssh_key: PathBuf,
+    pub os_disk_gb: u32,
+}
+
+impl Default for AzureConfig {
+    fn default() -> Self {
+        Self {
+            subscription: None,
+            resource_group: "runctl".to_string(),
+            location: "eastus".to_string(),
+            default_vm_size: "Standard_NC4as_T4_v3".to_string(),
+            image: "microsoft-dsvm:ubuntu-hpc:2204:latest".to_string(),
+            admin_username: "azureuser".to_string(),
+            ssh_key: PathBuf::from("~/.ssh/id_rsa"),
+            os_disk_gb: 128,
+        }
+    }
+}
+
+/// AWS Batch settings for `runctl run --provider batch`
+///
+/// Training runs as a Batch job in `job_queue`, from a container `image`
+/// that has Python and the AWS CLI. The project is shipped as a tarball in
+/// `s3_bucket` (default: `[aws] s3_bucket`), so `job_role_arn` needs read
+/// access to it. `runctl batch setup` creates the queue and a managed EC2
+/// compute environment if they don't exist yet.
+///
+/// ```toml
+/// [batch]
+/// job_queue = "runctl"
+/// image = "123456789012.dkr.ecr.us-east-1.amazonaws.com/train:latest"
+/// job_role_arn = "arn:aws:iam::123456789012:role/runctl-batch-job"
+/// default_instance_type = "g4dn.xlarge"
+/// subnets = ["subnet-0abc"]
+/// security_group_ids = ["sg-0abc"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BatchConfig {
+    pub job_queue: String,
+    /// Container image for job definitions (overridden by `--image`)
+    pub image: Option<String>,
+    /// IAM role the container runs as
+    pub job_role_arn: Option<String>,
+    /// Instance type whose vCPUs, memory and GPUs a job requests
+    pub default_instance_type: String,
+    /// Bucket for code tarballs (default: `[aws] s3_bucket`)
+    pub s3_bucket: Option<String>,
+    /// Compute environment `batch setup` creates and attaches to the queue
+    pub compute_environment: String,
+    /// Subnets and security groups for a new compute environment
+    pub subnets: Vec<String>,
+    pub security_group_ids: Vec<String>,
+    /// ECS instance profile for a new compute environment
+    pub instance_role: String,
+    pub max_vcpus: i32,
+    /// Use spot capacity in a new compute environment
+    pub spot: bool,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            job_queue: "runctl".to_string(),
+            image: None,
+            job_role_arn: None,
+            default_instance_type: "g4dn.xlarge".to_string(),
+            s3_bucket: None,
+            compute_environment: "runctl".to_string(),
+            subnets: Vec::new(),
+            security_group_ids: Vec::new(),
+            instance_role: "ecsInstanceRole".to_string(),
+            max_vcpus: 64,
+            spot: false,
+        }
+    }
+}
+
+/// Kubernetes settings for `runctl run --provider k8s`
+///
+/// Training runs as a `batch/v1` Job, or as a Kubeflow `PyTorchJob` with
+/// `kind = "pytorchjob"` (the training operator must be installed), in
+/// `namespace` of the kubeconfig `context` (default: the current context).
+/// Each pod requests and is limited to `gpus` NVIDIA GPUs. runctl copies the
+/// project into the pods before the script starts, so `image` only needs
+/// Python and the script's dependencies. `kubectl` must be installed.
+///
+/// ```toml
+/// [kubernetes]
+/// context = "gpu-cluster"
+/// namespace = "ml"
+/// image = "pytorch/pytorch:2.4.0-cuda12.1-cudnn9-runtime"
+/// gpus = 1
+/// memory = "32Gi"
+/// kind = "pytorchjob"
+/// workers = 1
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KubernetesConfig {
+    /// kubeconfig context (default: the current context)
+    pub context: Option<String>,
+    /// kubeconfig file (default: `KUBECONFIG` or `~/.kube/config`)
+    pub kubeconfig: Option<PathBuf>,
+    pub namespace: String,
+    /// Container image the script runs in
+    pub image: String,
+    pub kind: KubernetesJobKind,
+    /// `nvidia.com/gpu` requested and limited per pod
+    pub gpus: u32,
+    /// CPU requested and memory requested and limited per pod, e.g. "8" and
+    /// "32Gi"
+    pub cpu: Option<String>,
+    pub memory: Option<String>,
+    /// Worker replicas of a PyTorchJob, besides the master
+    pub workers: u32,
+    /// Node instance type to schedule on (`node.kubernetes.io/instance-type`);
+    /// "any" schedules anywhere
+    pub default_instance_type: String,
+    /// Extra node labels pods must match
+    pub node_selector: BTreeMap<String, String>,
+}
+
+impl Default for KubernetesConfig {
+    fn default() -> Self {
+        Self {
+            context: None,
+            kubeconfig: None,
+            namespace: "default".to_string(),
+            image: "pytorch/pytorch:2.4.0-cuda12.1-cudnn9-runtime".to_string(),
+            kind: KubernetesJobKind::Job,
+            gpus: 1,
+            cpu: None,
+            memory: None,
+            workers: 0,
+            default_instance_type: "any".to_string(),
+            node_selector: BTreeMap::new(),
+        }
+    }
+}
+
+/// Workload a Kubernetes training run is submitted as
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KubernetesJobKind {
+    /// `batch/v1` Job with a single pod
+    #[default]
+    Job,
+    /// Kubeflow `PyTorchJob` with a master and `workers` worker pods
+    PyTorchJob,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -100,15 +373,726 @@ pub struct CheckpointConfig {
     pub dir: PathBuf,
     pub save_interval: u32,
     pub keep_last_n: u32,
+    /// S3 prefix `checkpoint sync` uploads to (default: s3://<aws.s3_bucket>/checkpoints/<project>/)
+    #[serde(default)]
+    pub sync_dest: Option<String>,
+    /// Which checkpoints `checkpoint sync` keeps, locally and in S3
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    /// How long a planned stop waits for the script to checkpoint at its next
+    /// epoch or step boundary: `300` or `"5m"`, at most 8 minutes; 0 sends
+    /// SIGTERM right away (see `checkpoint::coordinate`)
+    #[serde(
+        default = "default_stop_wait",
+        deserialize_with = "crate::units::deserialize_secs"
+    )]
+    pub stop_wait_secs: u64,
+}
+
+fn default_stop_wait() -> u64 {
+    300
+}
+
+/// Checkpoint retention rules for `checkpoint sync`
+///
+/// A checkpoint is kept if any rule keeps it; the newest checkpoint is always
+/// kept. With no rules set, nothing is deleted. Epochs and metrics are read
+/// from file names such as `epoch_12_val_loss=0.213.pt`.
+///
+/// ```toml
+/// [checkpoint.retention]
+/// keep_last = 3
+/// keep_best = 2
+/// metric = "val_loss"
+/// keep_every = 10
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// Keep the N most recent checkpoints
+    pub keep_last: Option<usize>,
+    /// Keep the N best checkpoints by `metric`
+    pub keep_best: Option<usize>,
+    /// Metric name in checkpoint file names (default: loss)
+    pub metric: Option<String>,
+    /// Higher metric values are better (default: lower is better)
+    pub maximize: bool,
+    /// Keep checkpoints whose epoch is a multiple of N
+    pub keep_every: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MonitoringConfig {
     pub log_dir: PathBuf,
+    #[serde(deserialize_with = "crate::units::deserialize_secs")]
     pub update_interval_secs: u64,
     pub enable_warnings: bool,
 }
 
+/// Instance bootstrap customization
+///
+/// Rendered into the generated user-data script. Hooks are inserted verbatim
+/// as shell commands; use them for proxy certificates, internal package
+/// mirrors, or anything else that must happen during instance setup.
+///
+/// ```toml
+/// [bootstrap]
+/// pre_hooks = ["cp /etc/pki/corp-ca.pem /usr/local/share/ca-certificates/ && update-ca-certificates"]
+/// post_hooks = ["pip config set global.index-url https://pypi.internal/simple"]
+/// extra_packages = ["htop", "nvtop"]
+///
+/// [[bootstrap.mounts]]
+/// device = "/dev/nvme2n1"
+/// mount_point = "/mnt/scratch"
+/// format = true
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BootstrapConfig {
+    /// Shell commands run after OS detection, before system packages are installed
+    pub pre_hooks: Vec<String>,
+    /// Shell commands run after setup, before bootstrap is marked complete
+    pub post_hooks: Vec<String>,
+    /// Additional system packages installed via apt-get/yum
+    pub extra_packages: Vec<String>,
+    /// Additional block devices to mount
+    pub mounts: Vec<MountConfig>,
+}
+
+/// Custom mount point for the bootstrap script
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MountConfig {
+    /// Block device path (e.g., /dev/nvme2n1)
+    pub device: String,
+    /// Absolute mount point (e.g., /mnt/scratch)
+    pub mount_point: String,
+    /// Filesystem type (default: ext4)
+    #[serde(default = "default_fs_type")]
+    pub fs_type: String,
+    /// Format the device if it has no filesystem
+    #[serde(default)]
+    pub format: bool,
+    /// Mount options (default: defaults)
+    #[serde(default)]
+    pub options: Option<String>,
+}
+
+fn default_fs_type() -> String {
+    "ext4".to_string()
+}
+
+impl BootstrapConfig {
+    /// Combine with another bootstrap config, appending its hooks, packages, and mounts
+    pub fn merged_with(&self, other: &BootstrapConfig) -> BootstrapConfig {
+        let mut merged = self.clone();
+        merged.pre_hooks.extend(other.pre_hooks.iter().cloned());
+        merged.post_hooks.extend(other.post_hooks.iter().cloned());
+        merged
+            .extra_packages
+            .extend(other.extra_packages.iter().cloned());
+        merged.mounts.extend(other.mounts.iter().cloned());
+        merged
+    }
+}
+
+/// Named machine preset for `runctl aws create --preset <name>`
+///
+/// Every field is optional; explicit command-line flags take precedence.
+/// Preset bootstrap extras are appended to the global `[bootstrap]` section.
+///
+/// ```toml
+/// [presets.g5-dev]
+/// instance_type = "g5.xlarge"
+/// spot = true
+/// root_volume_size = 100
+/// data_volume_size = "1TiB"
+/// ami_filter = "pytorch=2.3,cuda=12.1,os=ubuntu22"
+/// security_group = "sg-0123456789abcdef0"
+///
+/// [presets.g5-dev.bootstrap]
+/// extra_packages = ["nvtop"]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PresetConfig {
+    pub instance_type: Option<String>,
+    pub spot: Option<bool>,
+    pub spot_max_price: Option<String>,
+    /// GiB: `100` or a size with units (`"500GB"`, `"1TiB"`)
+    #[serde(deserialize_with = "crate::units::deserialize_gib_opt")]
+    pub root_volume_size: Option<i32>,
+    #[serde(deserialize_with = "crate::units::deserialize_gib_opt")]
+    pub data_volume_size: Option<i32>,
+    pub ami_id: Option<String>,
+    /// Deep Learning AMI constraints, e.g. `"pytorch=2.3,cuda=12.1"`
+    pub ami_filter: Option<crate::ami_filter::AmiFilter>,
+    pub security_group: Option<String>,
+    pub key_name: Option<String>,
+    pub iam_instance_profile: Option<String>,
+    pub bootstrap: BootstrapConfig,
+}
+
+/// Job completion detection and health checks
+///
+/// By default `aws train --wait` detects completion with built-in heuristics
+/// (`training_complete.txt`, the PID file, and common "training complete" log
+/// lines). Scripts that signal completion differently can say so here; when
+/// any `[job.completion]` method is set, only the configured methods are used.
+/// Paths are relative to the project directory on the instance.
+///
+/// ```toml
+/// [job.completion]
+/// marker_file = "outputs/DONE"
+/// log_regex = "(?i)finished \\d+ epochs"
+/// exit_code = true
+///
+/// [[job.health_checks]]
+/// name = "gpu-visible"
+/// command = "nvidia-smi -L"
+/// expect = "GPU 0"
+/// interval_secs = "5m"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JobConfig {
+    pub completion: CompletionConfig,
+    pub health_checks: Vec<HealthCheckConfig>,
+}
+
+/// How to tell that a job has finished
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompletionConfig {
+    /// File whose (non-empty) presence marks completion
+    pub marker_file: Option<String>,
+    /// Regex matched against recent `training.log` lines
+    pub log_regex: Option<String>,
+    /// Use the exit code recorded by runctl's wrapper (non-zero means failed)
+    pub exit_code: bool,
+}
+
+impl CompletionConfig {
+    /// Whether any completion method is configured
+    pub fn is_configured(&self) -> bool {
+        self.marker_file.is_some() || self.log_regex.is_some() || self.exit_code
+    }
+}
+
+/// Periodic check run on the instance while waiting for a job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthCheckConfig {
+    pub name: String,
+    /// Shell command, run from the project directory
+    pub command: String,
+    /// Substring the output must contain; without it, exit status 0 passes
+    #[serde(default)]
+    pub expect: Option<String>,
+    /// Seconds: `300` or a duration with units (`"5m"`)
+    #[serde(
+        default = "default_health_check_interval",
+        deserialize_with = "crate::units::deserialize_secs"
+    )]
+    pub interval_secs: u64,
+    /// Consecutive failures before `--wait` gives up
+    #[serde(default = "default_health_check_max_failures")]
+    pub max_failures: u32,
+}
+
+fn default_health_check_interval() -> u64 {
+    300
+}
+
+fn default_health_check_max_failures() -> u32 {
+    3
+}
+
+/// Data transfer settings
+///
+/// Transfers that move data out of AWS (to a local machine or another
+/// region) show an egress estimate; above this amount they need confirmation
+/// (or `--yes`). Large files move in parallel parts (see
+/// `crate::transfer_engine`); `transfer` flags override the part settings
+/// per command.
+///
+/// ```toml
+/// [transfer]
+/// confirm_egress_usd = 5.0
+/// part_size = "64MB"
+/// part_parallel = 8
+/// bandwidth_limit = "50MB/s"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransferConfig {
+    /// Estimated egress charge (USD) above which a transfer must be confirmed
+    pub confirm_egress_usd: f64,
+    /// Part size for multipart transfers (default: 16MB, raised for very large files)
+    #[serde(deserialize_with = "crate::units::deserialize_size_opt")]
+    pub part_size: Option<u64>,
+    /// Parts in flight per file (default: 4)
+    pub part_parallel: Option<usize>,
+    /// Cap on transfer throughput, in bytes per second
+    #[serde(deserialize_with = "crate::units::deserialize_rate_opt")]
+    pub bandwidth_limit: Option<u64>,
+}
+
+impl Default for TransferConfig {
+    fn default() -> Self {
+        Self {
+            confirm_egress_usd: 5.0,
+            part_size: None,
+            part_parallel: None,
+            bandwidth_limit: None,
+        }
+    }
+}
+
+/// Client-side encryption key source
+///
+/// Used by `--encrypt` uploads and to decrypt them on download. The key is
+/// 32 random bytes, base64 encoded (`openssl rand -base64 32`). The
+/// `RUNCTL_ENCRYPTION_KEY` environment variable takes precedence over both
+/// settings; `key_command` is tried before `key_file`.
+///
+/// ```toml
+/// [encryption]
+/// # macOS keychain
+/// key_command = "security find-generic-password -s runctl -w"
+/// # or a file readable only by you
+/// key_file = "~/.config/runctl/encryption.key"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EncryptionConfig {
+    /// File containing the base64 key
+    pub key_file: Option<PathBuf>,
+    /// Shell command that prints the base64 key
+    pub key_command: Option<String>,
+}
+
+/// Dataset cache on training instances (`runctl cache`)
+///
+/// Objects are fetched from `origin` into instance-store scratch, demoted to
+/// EBS when scratch fills up, and dropped from EBS when that fills up; see
+/// `cache`. Sizes default to 90% of the free space on each tier's disk.
+///
+/// ```toml
+/// [cache]
+/// origin = "s3://datasets/imagenet"
+/// scratch_dir = "/mnt/scratch/runctl-cache"   # instance store
+/// scratch_size = "800GB"
+/// ebs_dir = "~/.runctl/cache"
+/// ebs_size = "500GB"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Where objects come from on a miss: an S3 prefix or a directory
+    pub origin: Option<String>,
+    /// Instance-store directory; the tier is skipped when its parent (the
+    /// mount point) doesn't exist
+    pub scratch_dir: PathBuf,
+    /// Scratch capacity (e.g. "800GB")
+    pub scratch_size: Option<String>,
+    /// EBS directory, which also holds the cache index
+    pub ebs_dir: PathBuf,
+    /// EBS capacity (e.g. "500GB")
+    pub ebs_size: Option<String>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            origin: None,
+            scratch_dir: PathBuf::from("/mnt/scratch/runctl-cache"),
+            scratch_size: None,
+            ebs_dir: PathBuf::from("~/.runctl/cache"),
+            ebs_size: None,
+        }
+    }
+}
+
+/// Where `runctl secrets set` stores new secrets
+///
+/// Config and training environment values can reference a secret as
+/// `secret://NAME` instead of holding the value; see `secrets`.
+///
+/// ```toml
+/// [secrets]
+/// backend = "aws"         # default "keyring"
+/// aws_prefix = "runctl/"  # Secrets Manager names are <aws_prefix><NAME>
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecretsConfig {
+    pub backend: SecretBackend,
+    pub aws_prefix: String,
+}
+
+impl Default for SecretsConfig {
+    fn default() -> Self {
+        Self {
+            backend: SecretBackend::default(),
+            aws_prefix: "runctl/".to_string(),
+        }
+    }
+}
+
+/// Secret storage backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretBackend {
+    /// OS keyring: macOS Keychain, or the Secret Service (`secret-tool`) on Linux
+    #[default]
+    Keyring,
+    /// AWS Secrets Manager (needs the `aws` feature)
+    Aws,
+}
+
+impl std::fmt::Display for SecretBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SecretBackend::Keyring => write!(f, "keyring"),
+            SecretBackend::Aws => write!(f, "aws"),
+        }
+    }
+}
+
+/// SSH private keys and host key checking
+///
+/// SSH-based commands look up the key for an instance's key pair in
+/// `keys` first, then as `<key_dir>/<name>.pem` and `<key_dir>/<name>`.
+/// `SSH_KEY_PATH` overrides both. Paths may start with `~`. Host keys
+/// are checked against `known_hosts`; see `host_keys`. `bastion` routes
+/// connections to private instances; see `ssh_proxy`.
+///
+/// ```toml
+/// [ssh]
+/// key_dir = "~/.ssh"
+/// known_hosts = "~/.runctl/known_hosts"
+/// strict_host_keys = true
+/// bastion = "ec2-user@bastion.example.com"
+///
+/// [ssh.keys]
+/// team-gpu = "~/keys/team-gpu.pem"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SshConfig {
+    /// Directory searched for `<key pair>.pem` and `<key pair>`
+    pub key_dir: PathBuf,
+    /// Key pair name -> private key path
+    pub keys: BTreeMap<String, PathBuf>,
+    /// Host keys of instances runctl connects to (see `host_keys`)
+    pub known_hosts: PathBuf,
+    /// Refuse hosts whose key wasn't learned from the provider or recorded
+    /// before, instead of trusting them on first connect
+    pub strict_host_keys: bool,
+    /// Jump host (`[user@]host[:port]`) or "ssm" for instances without a
+    /// public IP (see `ssh_proxy`)
+    pub bastion: Option<String>,
+}
+
+impl Default for SshConfig {
+    fn default() -> Self {
+        Self {
+            key_dir: PathBuf::from("~/.ssh"),
+            keys: BTreeMap::new(),
+            known_hosts: PathBuf::from("~/.runctl/known_hosts"),
+            strict_host_keys: false,
+            bastion: None,
+        }
+    }
+}
+
+/// Command-line behaviour
+///
+/// `non_interactive = true` acts like passing `--yes` to every command:
+/// confirmations are answered automatically instead of prompting.
+/// `progress_events = true` acts like `--progress-events` (see `progress`).
+/// `ascii = true` acts like `--ascii` and `false` keeps Unicode output even
+/// where the locale looks non-UTF-8; unset detects it (see `output_style`).
+///
+/// ```toml
+/// [cli]
+/// non_interactive = true
+/// progress_events = true
+/// ascii = true
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CliConfig {
+    pub non_interactive: bool,
+    pub progress_events: bool,
+    pub ascii: Option<bool>,
+}
+
+/// Log file for the CLI's own tracing output
+///
+/// When `file` is set (or `--log-file` is passed), events at `level` and
+/// above are written there as JSON lines, independent of console verbosity
+/// (see `logging`). The file is rotated once it would exceed `max_size`,
+/// keeping `max_files` older files.
+///
+/// ```toml
+/// [logging]
+/// file = "~/.runctl/logs/runctl.log"
+/// level = "debug"
+/// max_size = "10MB"
+/// max_files = 5
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// Log file path (`~` is expanded)
+    pub file: Option<PathBuf>,
+    /// Level or filter directive for the file (e.g. "info", "runctl=debug")
+    pub level: String,
+    /// Rotate when the file would exceed this size (e.g. "10MB")
+    pub max_size: String,
+    /// Rotated files to keep (`runctl.log.1` .. `runctl.log.N`)
+    pub max_files: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            file: None,
+            level: "info".to_string(),
+            max_size: "10MB".to_string(),
+            max_files: 5,
+        }
+    }
+}
+
+/// Code sync file selection
+///
+/// Applied by both SSH and SSM sync (see `sync_filter`). `include` ships
+/// paths even if gitignored; `exclude` (gitignore syntax) always wins.
+/// `aws train --include-pattern/--exclude-pattern/--max-file-size` add to
+/// these per command. Syncs over `confirm_above` are shown and must be
+/// confirmed (or `--yes` passed).
+///
+/// ```toml
+/// [sync]
+/// include = ["data/"]
+/// exclude = [".venv", "wandb/", "*.ckpt"]
+/// follow_symlinks = false
+/// max_file_size = "100MB"
+/// confirm_above = "1GB"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncConfig {
+    /// Paths to sync even if gitignored (e.g. `data/`)
+    pub include: Vec<String>,
+    /// Patterns never synced
+    pub exclude: Vec<String>,
+    /// Follow symlinks when collecting files
+    pub follow_symlinks: bool,
+    /// Skip files larger than this (e.g. "100MB")
+    pub max_file_size: Option<String>,
+    /// Ask before syncing more than this in total (e.g. "1GB")
+    pub confirm_above: Option<String>,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            include: Vec::new(),
+            exclude: [
+                ".git",
+                ".venv",
+                "venv",
+                "__pycache__",
+                "*.pyc",
+                ".mypy_cache",
+                ".pytest_cache",
+                ".ipynb_checkpoints",
+                "node_modules",
+                "target",
+                "wandb",
+                "mlruns",
+                ".runctl",
+                ".DS_Store",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+            follow_symlinks: false,
+            max_file_size: None,
+            confirm_above: Some("1GB".to_string()),
+        }
+    }
+}
+
+/// Local desktop notifications
+///
+/// Sent by `runctl watch` (foreground or detached) when a job finishes or an
+/// alert fires. Requires a build with the `desktop-notifications` feature;
+/// otherwise enabling them only logs at debug level.
+///
+/// ```toml
+/// [notifications]
+/// desktop = true
+/// on_complete = true
+/// on_failure = true
+/// on_alert = false
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationConfig {
+    /// Master switch for desktop notifications
+    pub desktop: bool,
+    /// Notify when a job completes successfully
+    pub on_complete: bool,
+    /// Notify when a job fails or exits unexpectedly
+    pub on_failure: bool,
+    /// Notify on alerts (stalled log output, lost contact with the job)
+    pub on_alert: bool,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            desktop: false,
+            on_complete: true,
+            on_failure: true,
+            on_alert: true,
+        }
+    }
+}
+
+/// An AWS account that resource listings, summaries and budgets cover
+///
+/// With no `[accounts]` entries, runctl only sees the account of the ambient
+/// credentials. Once any are configured, `resources list`, `resources summary`
+/// and `resources budget` query exactly these accounts and label resources
+/// with the entry's name. An entry with neither `profile` nor `role_arn`
+/// uses the ambient credentials.
+///
+/// ```toml
+/// [accounts.main]
+///
+/// [accounts.sandbox-a]
+/// profile = "sandbox-a"
+///
+/// [accounts.shared-gpu]
+/// role_arn = "arn:aws:iam::123456789012:role/runctl-readonly"
+/// region = "us-west-2"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccountConfig {
+    /// Named profile from `~/.aws/config`
+    pub profile: Option<String>,
+    /// Role to assume, starting from `profile` or the ambient credentials
+    pub role_arn: Option<String>,
+    /// External ID required by the role's trust policy
+    pub external_id: Option<String>,
+    /// Region to list (default: the profile's or the ambient region)
+    pub region: Option<String>,
+}
+
+/// Per-project and per-account cost budgets
+///
+/// Projects are matched by the `runctl:project` tag. `hourly` caps the
+/// combined rate of a project's running resources; `daily` and `monthly` cap
+/// spend since midnight UTC and since the first of the month. `default`
+/// applies to projects without their own entry. `accounts` caps whole
+/// `[accounts]` entries the same way, whatever the project.
+///
+/// ```toml
+/// [budget.default]
+/// daily = 50.0
+///
+/// [budget.projects.llm-finetune]
+/// hourly = 20.0
+/// monthly = 2000.0
+/// actions = ["warn", "refuse", "stop", "notify"]
+///
+/// [budget.accounts.sandbox-a]
+/// monthly = 500.0
+/// actions = ["warn", "notify"]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BudgetConfig {
+    /// Limits for projects without an entry in `projects`
+    pub default: Option<BudgetLimits>,
+    /// Limits keyed by project name
+    pub projects: BTreeMap<String, BudgetLimits>,
+    /// Limits keyed by `[accounts]` entry; `refuse` doesn't apply
+    pub accounts: BTreeMap<String, BudgetLimits>,
+}
+
+impl BudgetConfig {
+    /// Whether any budget is configured
+    pub fn is_empty(&self) -> bool {
+        self.default.is_none() && self.projects.is_empty() && self.accounts.is_empty()
+    }
+
+    /// Limits that apply to `project`
+    pub fn limits_for(&self, project: &str) -> Option<&BudgetLimits> {
+        self.projects.get(project).or(self.default.as_ref())
+    }
+}
+
+/// Spending limits for one project, in USD
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetLimits {
+    /// Combined hourly rate of running resources
+    #[serde(default)]
+    pub hourly: Option<f64>,
+    /// Spend since midnight UTC
+    #[serde(default)]
+    pub daily: Option<f64>,
+    /// Spend since the first of the month (UTC)
+    #[serde(default)]
+    pub monthly: Option<f64>,
+    /// What to do when a limit is exceeded
+    #[serde(default = "default_budget_actions")]
+    pub actions: Vec<BudgetAction>,
+}
+
+fn default_budget_actions() -> Vec<BudgetAction> {
+    vec![BudgetAction::Warn]
+}
+
+/// Response to an exceeded budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetAction {
+    /// Print a warning (summary, `aws create`, `resources budget`)
+    Warn,
+    /// Refuse `aws create` for the project
+    Refuse,
+    /// Stop the project's running instances (`resources budget --enforce`)
+    Stop,
+    /// Send a desktop alert (`resources budget --enforce`)
+    Notify,
+}
+
+/// What happens to an instance once it has been idle past the threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum AutostopAction {
+    /// Stop the instance; EBS volumes are kept and it can be started again
+    #[default]
+    Stop,
+    /// Terminate the instance
+    Terminate,
+}
+
+impl std::fmt::Display for AutostopAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AutostopAction::Stop => write!(f, "stop"),
+            AutostopAction::Terminate => write!(f, "terminate"),
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -129,7 +1113,13 @@ impl Default for Config {
                 s3_bucket: None,
                 default_project_name: None, // Auto-detect from current directory
                 user_id: None,              // Auto-detect from username
+                ami_cache_ttl_secs: default_ami_cache_ttl(),
+                autostop_idle_minutes: None,
+                autostop_action: Default::default(),
             }),
+            azure: None,
+            batch: None,
+            kubernetes: None,
             local: Some(LocalConfig {
                 default_device: "auto".to_string(),
                 checkpoint_dir: PathBuf::from("checkpoints"),
@@ -138,17 +1128,45 @@ impl Default for Config {
                 dir: PathBuf::from("checkpoints"),
                 save_interval: 5,
                 keep_last_n: 10,
+                sync_dest: None,
+                retention: RetentionConfig::default(),
+                stop_wait_secs: default_stop_wait(),
             },
             monitoring: MonitoringConfig {
                 log_dir: PathBuf::from("logs"),
                 update_interval_secs: 10,
                 enable_warnings: true,
             },
+            bootstrap: BootstrapConfig::default(),
+            presets: BTreeMap::new(),
+            notifications: NotificationConfig::default(),
+            accounts: BTreeMap::new(),
+            budget: BudgetConfig::default(),
+            job: JobConfig::default(),
+            transfer: TransferConfig::default(),
+            encryption: EncryptionConfig::default(),
+            secrets: SecretsConfig::default(),
+            env: BTreeMap::new(),
+            cache: CacheConfig::default(),
+            sync: SyncConfig::default(),
+            ssh: SshConfig::default(),
+            cli: CliConfig::default(),
+            logging: LoggingConfig::default(),
+            aliases: BTreeMap::new(),
             resource_tracker: Some(Arc::new(ResourceTracker::new())),
         }
     }
 }
 
+/// The CLI's tracker: shared store in the project, ledger in the home directory
+fn default_tracker() -> ResourceTracker {
+    let tracker = ResourceTracker::persistent(crate::resource_tracking::DEFAULT_STORE_PATH);
+    match crate::costs::default_ledger_path() {
+        Some(path) => tracker.with_ledger(crate::costs::Ledger::new(path)),
+        None => tracker,
+    }
+}
+
 impl Config {
     pub fn load(path: Option<&Path>) -> Result<Self> {
         let config_path = if let Some(p) = path {
@@ -180,10 +1198,8 @@ impl Config {
                 .map_err(|_e| TrainctlError::Config(ConfigError::ParseError(
                     format!("Failed to parse config: {}\n  Common issues:\n    - Invalid TOML syntax\n    - Missing required fields\n    - Incorrect value types\n  Tip: Run 'runctl init' to create a new config file", config_path.display())
                 )))?;
-            // Initialize resource tracker if not present
-            if config.resource_tracker.is_none() {
-                config.resource_tracker = Some(Arc::new(ResourceTracker::new()));
-            }
+            // Share tracked resources with concurrent runctl processes
+            config.resource_tracker = Some(Arc::new(default_tracker()));
             Ok(config)
         } else {
             // Use defaults but warn if user explicitly provided a path
@@ -193,15 +1209,29 @@ impl Config {
                     "   Using default configuration. Run 'runctl init' to create a config file."
                 );
             }
-            let mut config = Config::default();
-            // Ensure resource tracker is initialized
-            if config.resource_tracker.is_none() {
-                config.resource_tracker = Some(Arc::new(ResourceTracker::new()));
-            }
-            Ok(config)
+            Ok(Config {
+                // Share tracked resources with concurrent runctl processes
+                resource_tracker: Some(Arc::new(default_tracker())),
+                ..Config::default()
+            })
         }
     }
 
+    /// Look up a named machine preset
+    pub fn preset(&self, name: &str) -> Result<&PresetConfig> {
+        self.presets.get(name).ok_or_else(|| {
+            let available = if self.presets.is_empty() {
+                "none defined (add [presets.<name>] to .runctl.toml)".to_string()
+            } else {
+                self.presets.keys().cloned().collect::<Vec<_>>().join(", ")
+            };
+            TrainctlError::Config(ConfigError::InvalidValue {
+                field: "preset".to_string(),
+                reason: format!("Unknown preset '{}'. Available: {}", name, available),
+            })
+        })
+    }
+
     pub fn save(&self, path: &Path) -> Result<()> {
         let content = toml::to_string_pretty(self).map_err(|e| {
             TrainctlError::Config(ConfigError::ParseError(format!(
@@ -306,6 +1336,13 @@ pub async fn handle_command(
                     if let Some(user) = &aws.user_id {
                         println!("    User ID: {}", user);
                     }
+                    println!("    AMI Cache TTL: {}s", aws.ami_cache_ttl_secs);
+                    if let Some(minutes) = aws.autostop_idle_minutes {
+                        println!(
+                            "    Autostop: {} after {}m idle",
+                            aws.autostop_action, minutes
+                        );
+                    }
                 }
                 if let Some(runpod) = &config.runpod {
                     println!("  RunPod:");
@@ -313,6 +1350,24 @@ pub async fn handle_command(
                     println!("    Default Disk: {} GB", runpod.default_disk_gb);
                     println!("    Default Image: {}", runpod.default_image);
                 }
+                if let Some(azure) = &config.azure {
+                    println!("  Azure:");
+                    if let Some(subscription) = &azure.subscription {
+                        println!("    Subscription: {}", subscription);
+                    }
+                    println!("    Resource Group: {}", azure.resource_group);
+                    println!("    Location: {}", azure.location);
+                    println!("    Default VM Size: {}", azure.default_vm_size);
+                    println!("    Image: {}", azure.image);
+                }
+                if let Some(batch) = &config.batch {
+                    println!("  Batch:");
+                    println!("    Job Queue: {}", batch.job_queue);
+                    if let Some(image) = &batch.image {
+                        println!("    Image: {}", image);
+                    }
+                    println!("    Default Instance Type: {}", batch.default_instance_type);
+                }
                 if let Some(local) = &config.local {
                     println!("  Local:");
                     println!("    Default Device: {}", local.default_device);
@@ -332,6 +1387,129 @@ pub async fn handle_command(
                     config.monitoring.update_interval_secs
                 );
                 println!("    Enable Warnings: {}", config.monitoring.enable_warnings);
+                println!("  Transfer:");
+                println!(
+                    "    Confirm Egress Above: ${:.2}",
+                    config.transfer.confirm_egress_usd
+                );
+                println!("  Sync:");
+                if !config.sync.include.is_empty() {
+                    println!("    Include: {}", config.sync.include.join(", "));
+                }
+                println!("    Exclude: {}", config.sync.exclude.join(", "));
+                println!("    Follow Symlinks: {}", config.sync.follow_symlinks);
+                if let Some(max) = &config.sync.max_file_size {
+                    println!("    Max File Size: {}", max);
+                }
+                if let Some(limit) = &config.sync.confirm_above {
+                    println!("    Confirm Above: {}", limit);
+                }
+                println!("  Encryption:");
+                if let Some(command) = &config.encryption.key_command {
+                    println!("    Key Command: {}", command);
+                }
+                if let Some(path) = &config.encryption.key_file {
+                    println!("    Key File: {}", path.display());
+                }
+                if config.encryption.key_command.is_none() && config.encryption.key_file.is_none() {
+                    println!("    Key: not configured (RUNCTL_ENCRYPTION_KEY)");
+                }
+                println!("  CLI:");
+                println!("    Non-interactive: {}", config.cli.non_interactive);
+                println!("    Progress events: {}", config.cli.progress_events);
+                match config.cli.ascii {
+                    Some(ascii) => println!("    ASCII output: {}", ascii),
+                    None => println!("    ASCII output: auto"),
+                }
+                if !config.aliases.is_empty() {
+                    println!("  Aliases:");
+                    for (name, alias) in &config.aliases {
+                        println!("    {} = {}", name, alias);
+                    }
+                }
+                println!("  Logging:");
+                match &config.logging.file {
+                    Some(path) => println!(
+                        "    File: {} (level {}, rotate at {}, keep {})",
+                        path.display(),
+                        config.logging.level,
+                        config.logging.max_size,
+                        config.logging.max_files
+                    ),
+                    None => println!("    File: not configured (--log-file)"),
+                }
+                let notifications = &config.notifications;
+                println!("  Notifications:");
+                println!("    Desktop: {}", notifications.desktop);
+                if notifications.desktop {
+                    println!(
+                        "    Events: complete={}, failure={}, alert={}",
+                        notifications.on_complete, notifications.on_failure, notifications.on_alert
+                    );
+                }
+                if !config.budget.is_empty() {
+                    println!("  Budgets:");
+                    let entries = config
+                        .budget
+                        .default
+                        .iter()
+                        .map(|limits| ("(default)", limits))
+                        .chain(
+                            config
+                                .budget
+                                .projects
+                                .iter()
+                                .map(|(name, limits)| (name.as_str(), limits)),
+                        );
+                    let accounts = config
+                        .budget
+                        .accounts
+                        .iter()
+                        .map(|(name, limits)| (format!("account {}", name), limits));
+                    let entries = entries
+                        .map(|(name, limits)| (name.to_string(), limits))
+                        .chain(accounts);
+                    for (name, limits) in entries {
+                        let caps: Vec<String> = [
+                            limits.hourly.map(|v| format!("${:.2}/hr", v)),
+                            limits.daily.map(|v| format!("${:.2}/day", v)),
+                            limits.monthly.map(|v| format!("${:.2}/month", v)),
+                        ]
+                        .into_iter()
+                        .flatten()
+                        .collect();
+                        println!("    {}: {}", name, caps.join(", "));
+                    }
+                }
+                if !config.accounts.is_empty() {
+                    println!("  Accounts:");
+                    for (name, account) in &config.accounts {
+                        let source = match (&account.role_arn, &account.profile) {
+                            (Some(role), _) => format!("role {}", role),
+                            (None, Some(profile)) => format!("profile {}", profile),
+                            (None, None) => "ambient credentials".to_string(),
+                        };
+                        match &account.region {
+                            Some(region) => println!("    {}: {} ({})", name, source, region),
+                            None => println!("    {}: {}", name, source),
+                        }
+                    }
+                }
+                if !config.presets.is_empty() {
+                    println!("  Presets:");
+                    for (name, preset) in &config.presets {
+                        println!(
+                            "    {}: {}{}",
+                            name,
+                            preset.instance_type.as_deref().unwrap_or("(default type)"),
+                            if preset.spot == Some(true) {
+                                ", spot"
+                            } else {
+                                ""
+                            }
+                        );
+                    }
+                }
             }
             Ok(())
         }
@@ -456,7 +1634,7 @@ pub async fn handle_command(
                         });
                         eprintln!("{}", serde_json::to_string_pretty(&result)?);
                     } else {
-                        eprintln!("✗ Configuration validation failed:");
+                        eprintln!("Error: Configuration validation failed:");
                         eprintln!("  {}", e);
                     }
                     Err(e)
@@ -518,6 +1696,52 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_config_presets() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("presets.toml");
+        let mut content = toml::to_string_pretty(&Config::default()).unwrap();
+        content.push_str(
+            r#"
+[presets.g5-dev]
+instance_type = "g5.xlarge"
+spot = true
+root_volume_size = "200GB"
+data_volume_size = 500
+
+[presets.g5-dev.bootstrap]
+extra_packages = ["nvtop"]
+"#,
+        );
+        std::fs::write(&config_path, content).unwrap();
+
+        let config = Config::load(Some(&config_path)).unwrap();
+        let preset = config.preset("g5-dev").unwrap();
+        assert_eq!(preset.instance_type.as_deref(), Some("g5.xlarge"));
+        assert_eq!(preset.spot, Some(true));
+        assert_eq!(preset.root_volume_size, Some(200));
+        assert_eq!(preset.data_volume_size, Some(500));
+        assert!(preset.security_group.is_none());
+        assert!(config.preset("missing").is_err());
+    }
+
+    #[test]
+    fn test_bootstrap_merged_with() {
+        let base = BootstrapConfig {
+            pre_hooks: vec!["a".to_string()],
+            extra_packages: vec!["htop".to_string()],
+            ..Default::default()
+        };
+        let extra = BootstrapConfig {
+            pre_hooks: vec!["b".to_string()],
+            extra_packages: vec!["nvtop".to_string()],
+            ..Default::default()
+        };
+        let merged = base.merged_with(&extra);
+        assert_eq!(merged.pre_hooks, vec!["a", "b"]);
+        assert_eq!(merged.extra_packages, vec!["htop", "nvtop"]);
+    }
+
     #[test]
     fn test_init_config() {
         let temp_dir = TempDir::new().unwrap();
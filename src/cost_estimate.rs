@@ -0,0 +1,396 @@
+//! Pre-launch cost estimates (`runctl costs estimate`)
+//!
+//! Prices a run described by a manifest (see `manifest`) before anything is
+//! launched, so a budget can be approved up front. Each candidate instance
+//! type (the manifest's, plus any given with `--types`) is priced for the
+//! planned duration as:
+//!
+//! - **Compute**: on-demand and spot. On-demand prices come from the Pricing
+//!   API, spot prices from the cheapest zone in the region; types AWS has no
+//!   price for fall back to `aws_pricing`'s cache and built-in table.
+//! - **Storage**: the root volume (`disk_gb`) at the gp3 list price.
+//! - **Data transfer**: copying `data.input` into the region when the bucket
+//!   lives elsewhere, with the rates in `data_locality`. Writing outputs to
+//!   S3 is ingress and free.
+//!
+//! Spot interruptions and restarts are not modelled; the spot total is what
+//! an uninterrupted run would cost at today's price.
+
+use crate::config::Config;
+use crate::data_locality::{
+    estimate_egress, s3_bucket_region, s3_client_for_region, s3_prefix_bytes, Site,
+};
+use crate::data_transfer::parse_s3_path;
+use crate::error::{Result, TrainctlError};
+use crate::manifest::Manifest;
+use aws_config::BehaviorVersion;
+use aws_sdk_ec2::Client as Ec2Client;
+use serde::Serialize;
+use std::path::PathBuf;
+use tracing::{debug, warn};
+
+/// gp3 list price per GB-month (us-east-1)
+const GP3_PER_GB_MONTH: f64 = 0.08;
+
+/// Hours AWS bills a month of storage over
+const HOURS_PER_MONTH: f64 = 730.0;
+
+/// Root volume size `aws create` uses for GPU types when none is given
+const DEFAULT_GPU_DISK_GB: u32 = 50;
+const DEFAULT_DISK_GB: u32 = 30;
+
+/// What `costs estimate` was asked to price
+#[derive(Debug, Clone)]
+pub struct EstimateOptions {
+    pub spec: PathBuf,
+    pub duration_secs: u64,
+    /// Extra instance types to compare against the manifest's
+    pub types: Vec<String>,
+    /// Default: the configured AWS region
+    pub region: Option<String>,
+}
+
+/// Data copied into the region before training
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DataTransfer {
+    pub source: Option<String>,
+    pub bytes: u64,
+    pub cost: f64,
+}
+
+/// Price of the run on one instance type
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CandidateEstimate {
+    pub instance_type: String,
+    pub on_demand_per_hour: f64,
+    /// Cheapest current spot price in the region, if AWS lists one
+    pub spot_per_hour: Option<f64>,
+    pub spot_zone: Option<String>,
+    /// Whether the prices came from AWS rather than the cache or built-in table
+    pub live_prices: bool,
+    pub storage: f64,
+    pub data_transfer: f64,
+    pub total_on_demand: f64,
+    pub total_spot: Option<f64>,
+}
+
+impl CandidateEstimate {
+    /// Total for the pricing the manifest asks for
+    pub fn planned_total(&self, spot: bool) -> f64 {
+        match (spot, self.total_spot) {
+            (true, Some(total)) => total,
+            _ => self.total_on_demand,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CostEstimate {
+    pub name: String,
+    pub region: String,
+    pub duration_hours: f64,
+    pub disk_gb: u32,
+    /// Whether the manifest launches spot instances
+    pub spot: bool,
+    pub data: DataTransfer,
+    /// Cheapest planned total first
+    pub candidates: Vec<CandidateEstimate>,
+}
+
+/// Root volume cost for `hours`
+pub fn storage_cost(disk_gb: u32, hours: f64) -> f64 {
+    f64::from(disk_gb) * GP3_PER_GB_MONTH * hours / HOURS_PER_MONTH
+}
+
+/// Root volume size the launch will use
+pub fn planned_disk_gb(manifest_disk_gb: Option<u32>, instance_type: &str) -> u32 {
+    manifest_disk_gb.unwrap_or_else(|| {
+        if instance_type.starts_with('g') || instance_type.starts_with('p') {
+            DEFAULT_GPU_DISK_GB
+        } else {
+            DEFAULT_DISK_GB
+        }
+    })
+}
+
+/// Combine prices into the estimate for one instance type
+pub fn estimate_candidate(
+    instance_type: &str,
+    on_demand_per_hour: f64,
+    spot: Option<(String, f64)>,
+    live_prices: bool,
+    hours: f64,
+    disk_gb: u32,
+    data: &DataTransfer,
+) -> CandidateEstimate {
+    let storage = storage_cost(disk_gb, hours);
+    let fixed = storage + data.cost;
+    let (spot_zone, spot_per_hour) = match spot {
+        Some((zone, price)) => (Some(zone), Some(price)),
+        None => (None, None),
+    };
+    CandidateEstimate {
+        instance_type: instance_type.to_string(),
+        on_demand_per_hour,
+        spot_per_hour,
+        spot_zone,
+        live_prices,
+        storage,
+        data_transfer: data.cost,
+        total_on_demand: on_demand_per_hour * hours + fixed,
+        total_spot: spot_per_hour.map(|price| price * hours + fixed),
+    }
+}
+
+/// Manifest type first, then `extra`, without duplicates
+fn candidate_types(manifest: &Manifest, extra: &[String], config: &Config) -> Vec<String> {
+    let planned = manifest.instance_type.clone().unwrap_or_else(|| {
+        config
+            .aws
+            .as_ref()
+            .map(|a| a.default_instance_type.clone())
+            .unwrap_or_else(|| "t3.medium".to_string())
+    });
+    let mut types = vec![planned];
+    for t in extra {
+        if !types.contains(t) {
+            types.push(t.clone());
+        }
+    }
+    types
+}
+
+/// Size and egress cost of the manifest's S3 input, as seen from `region`
+async fn input_transfer(
+    manifest: &Manifest,
+    region: &str,
+    aws_config: &aws_config::SdkConfig,
+) -> DataTransfer {
+    let Some(input) = manifest.data.input.as_deref() else {
+        return DataTransfer::default();
+    };
+    if !input.starts_with("s3://") {
+        return DataTransfer {
+            source: Some(input.to_string()),
+            ..Default::default()
+        };
+    }
+    let sized = async {
+        let client = aws_sdk_s3::Client::new(aws_config);
+        let (bucket, prefix) = parse_s3_path(input)?;
+        let bucket_region = s3_bucket_region(&client, &bucket).await?;
+        let regional = s3_client_for_region(aws_config, &bucket_region);
+        let bytes = s3_prefix_bytes(&regional, &bucket, &prefix).await?;
+        Ok::<_, TrainctlError>((bucket_region, bytes))
+    }
+    .await;
+    match sized {
+        Ok((bucket_region, bytes)) => {
+            let from = Site::Aws {
+                region: bucket_region,
+            };
+            let to = Site::Aws {
+                region: region.to_string(),
+            };
+            DataTransfer {
+                source: Some(input.to_string()),
+                bytes,
+                cost: estimate_egress(&from, &to, bytes).map_or(0.0, |e| e.cost_usd),
+            }
+        }
+        Err(e) => {
+            warn!(
+                "Could not size {}: {}; data transfer not included",
+                input, e
+            );
+            DataTransfer {
+                source: Some(input.to_string()),
+                ..Default::default()
+            }
+        }
+    }
+}
+
+/// On-demand and cheapest spot price of `instance_type` in `region`
+async fn fetch_prices(
+    ec2: &Ec2Client,
+    region: &str,
+    instance_type: &str,
+) -> (f64, Option<(String, f64)>, bool) {
+    let pricing = crate::aws_pricing::pricing_client().await;
+    let on_demand =
+        match crate::aws_pricing::fetch_on_demand_price(pricing, region, instance_type).await {
+            Ok(price) => price,
+            Err(e) => {
+                debug!("No on-demand price for {}: {}", instance_type, e);
+                None
+            }
+        };
+    let spot = match crate::aws_pricing::fetch_spot_prices(ec2, instance_type).await {
+        Ok(prices) => prices
+            .into_iter()
+            .filter(|(zone, _)| crate::aws_pricing::region_of_zone(zone) == region)
+            .min_by(|a, b| a.1.total_cmp(&b.1)),
+        Err(e) => {
+            debug!("No spot price for {}: {}", instance_type, e);
+            None
+        }
+    };
+    let live = on_demand.is_some();
+    let on_demand = on_demand
+        .unwrap_or_else(|| crate::aws_pricing::hourly_price(instance_type, Some(region), None));
+    (on_demand, spot, live)
+}
+
+/// Price the manifest at `options.spec` on every candidate type
+pub async fn estimate(options: &EstimateOptions, config: &Config) -> Result<CostEstimate> {
+    let manifest = Manifest::load(&options.spec)?;
+    if manifest.provider != "aws" {
+        return Err(TrainctlError::Validation {
+            field: "provider".to_string(),
+            reason: format!(
+                "cost estimates need AWS prices; {} uses provider '{}'",
+                options.spec.display(),
+                manifest.provider
+            ),
+        });
+    }
+    let region = options
+        .region
+        .clone()
+        .or_else(|| config.aws.as_ref().map(|a| a.region.clone()))
+        .unwrap_or_else(|| "us-east-1".to_string());
+    let aws_config = aws_config::defaults(BehaviorVersion::latest())
+        .region(aws_sdk_ec2::config::Region::new(region.clone()))
+        .load()
+        .await;
+    let ec2 = Ec2Client::new(&aws_config);
+
+    let hours = options.duration_secs as f64 / 3600.0;
+    let types = candidate_types(&manifest, &options.types, config);
+    let disk_gb = planned_disk_gb(manifest.disk_gb, &types[0]);
+    let data = input_transfer(&manifest, &region, &aws_config).await;
+
+    let mut candidates = Vec::new();
+    for instance_type in &types {
+        let (on_demand, spot, live) = fetch_prices(&ec2, &region, instance_type).await;
+        candidates.push(estimate_candidate(
+            instance_type,
+            on_demand,
+            spot,
+            live,
+            hours,
+            disk_gb,
+            &data,
+        ));
+    }
+    let spot = manifest.spot.enabled;
+    candidates.sort_by(|a, b| a.planned_total(spot).total_cmp(&b.planned_total(spot)));
+
+    Ok(CostEstimate {
+        name: manifest.name,
+        region,
+        duration_hours: hours,
+        disk_gb,
+        spot,
+        data,
+        candidates,
+    })
+}
+
+pub fn print_estimate(estimate: &CostEstimate) {
+    println!(
+        "Estimated cost of {} for {} in {}",
+        estimate.name,
+        crate::utils::format_duration((estimate.duration_hours * 3600.0) as u64),
+        estimate.region
+    );
+    println!("  Storage: {} GB gp3 root volume", estimate.disk_gb);
+    match &estimate.data.source {
+        Some(source) if estimate.data.bytes > 0 => println!(
+            "  Data: {:.1} GB from {}",
+            estimate.data.bytes as f64 / (1024.0 * 1024.0 * 1024.0),
+            source
+        ),
+        Some(source) => println!("  Data: {} (not sized)", source),
+        None => {}
+    }
+    println!();
+    println!(
+        "  {:<16} {:>10} {:>10} {:>9} {:>9} {:>12} {:>12}",
+        "INSTANCE TYPE", "OD/HOUR", "SPOT/HOUR", "STORAGE", "DATA", "ON-DEMAND", "SPOT"
+    );
+    let money = |v: Option<f64>| v.map_or_else(|| "-".to_string(), |v| format!("${:.2}", v));
+    for c in &estimate.candidates {
+        let marker = if c.live_prices { "" } else { "*" };
+        println!(
+            "  {:<16} {:>10} {:>10} {:>9} {:>9} {:>12} {:>12}",
+            format!("{}{}", c.instance_type, marker),
+            format!("${:.3}", c.on_demand_per_hour),
+            c.spot_per_hour
+                .map_or_else(|| "-".to_string(), |p| format!("${:.3}", p)),
+            format!("${:.2}", c.storage),
+            format!("${:.2}", c.data_transfer),
+            format!("${:.2}", c.total_on_demand),
+            money(c.total_spot),
+        );
+    }
+    if estimate.candidates.iter().any(|c| !c.live_prices) {
+        println!("  * estimated price (AWS Pricing API unavailable for this type)");
+    }
+    if let Some(best) = estimate.candidates.first() {
+        println!();
+        println!(
+            "Cheapest as planned ({}): {} at ${:.2}",
+            if estimate.spot { "spot" } else { "on-demand" },
+            best.instance_type,
+            best.planned_total(estimate.spot)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_cost_prorates_monthly_price() {
+        // 100 GB for a full month is $8
+        assert!((storage_cost(100, HOURS_PER_MONTH) - 8.0).abs() < 1e-9);
+        assert!((storage_cost(100, 73.0) - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_estimate_candidate_totals() {
+        let data = DataTransfer {
+            source: Some("s3://bucket/data".to_string()),
+            bytes: 0,
+            cost: 2.0,
+        };
+        let c = estimate_candidate(
+            "g5.xlarge",
+            1.0,
+            Some(("us-east-1a".to_string(), 0.4)),
+            true,
+            48.0,
+            0,
+            &data,
+        );
+        assert!((c.total_on_demand - 50.0).abs() < 1e-9);
+        assert!((c.total_spot.unwrap() - 21.2).abs() < 1e-9);
+        assert!((c.planned_total(true) - 21.2).abs() < 1e-9);
+        assert!((c.planned_total(false) - 50.0).abs() < 1e-9);
+
+        // Without a spot price, spot plans fall back to on-demand
+        let c = estimate_candidate("p3.2xlarge", 3.0, None, false, 1.0, 0, &data);
+        assert_eq!(c.total_spot, None);
+        assert!((c.planned_total(true) - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_planned_disk_matches_create_defaults() {
+        assert_eq!(planned_disk_gb(Some(200), "g5.xlarge"), 200);
+        assert_eq!(planned_disk_gb(None, "g5.xlarge"), 50);
+        assert_eq!(planned_disk_gb(None, "t3.medium"), 30);
+    }
+}
@@ -0,0 +1,97 @@
+//! Local cost history
+//!
+//! Append-only record of charges runctl caused beyond instance runtime
+//! (currently data egress from transfers), stored as JSON lines in
+//! `.runctl/cost_history.jsonl`. Instance costs are derived from launch time
+//! by the resource tracker and are not duplicated here.
+
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// One recorded charge
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostRecord {
+    pub timestamp: DateTime<Utc>,
+    /// What kind of charge this is (e.g. "egress")
+    pub category: String,
+    pub description: String,
+    /// Bytes moved, for data charges
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+    pub cost_usd: f64,
+}
+
+impl CostRecord {
+    pub fn egress(description: impl Into<String>, bytes: u64, cost_usd: f64) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            category: "egress".to_string(),
+            description: description.into(),
+            bytes: Some(bytes),
+            cost_usd,
+        }
+    }
+}
+
+pub fn history_path() -> PathBuf {
+    PathBuf::from(".runctl").join("cost_history.jsonl")
+}
+
+/// Append a record to the default history file
+pub fn append(record: &CostRecord) -> Result<()> {
+    append_to(&history_path(), record)
+}
+
+pub fn append_to(path: &Path, record: &CostRecord) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        crate::utils::ensure_dir(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+    Ok(())
+}
+
+/// Load all records, skipping lines that don't parse
+pub fn load_from(path: &Path) -> Result<Vec<CostRecord>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("cost_history.jsonl");
+
+        assert!(load_from(&path).unwrap().is_empty());
+
+        let first = CostRecord::egress("s3://a/x -> ./x", 1024, 0.5);
+        let second = CostRecord::egress("s3://a/y -> ./y", 2048, 1.25);
+        append_to(&path, &first).unwrap();
+        append_to(&path, &second).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"not json\n")
+            .unwrap();
+
+        assert_eq!(load_from(&path).unwrap(), vec![first, second]);
+    }
+}
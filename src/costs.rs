@@ -0,0 +1,568 @@
+//! Historical cost ledger (`runctl costs`)
+//!
+//! The resource tracker only knows what resources cost while they are
+//! tracked; once an instance is terminated and removed, its cost is gone.
+//! The ledger keeps it: an append-only JSON lines file at
+//! `~/.runctl/ledger.jsonl`, shared by every project on the machine.
+//!
+//! ## Entries
+//!
+//! The tracker appends an entry on every lifecycle change it sees (launch,
+//! stop, start, terminate) and a snapshot for each running resource at most
+//! once per clock hour (on `refresh_costs()` and `reconcile()`). Every entry
+//! carries the resource's accumulated cost at that moment, which only grows,
+//! so the cost in any window is the difference between two entries.
+//!
+//! ## Reports
+//!
+//! `runctl costs report --since 30d --group-by project` sums the cost each
+//! resource accrued inside the window and groups it by project, user,
+//! account (`[accounts]` entry), instance type or resource. Data egress from `cost_history` is reported
+//! alongside.
+//!
+//! `runctl costs estimate` looks forward instead: it prices a manifest
+//! before launch (see `cost_estimate`).
+
+use crate::config::Config;
+use crate::error::{Result, TrainctlError};
+use crate::resource_tracking::TrackedResource;
+use chrono::{DateTime, Duration, Timelike, Utc};
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Ledger location, `~/.runctl/ledger.jsonl`
+pub fn default_ledger_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".runctl").join("ledger.jsonl"))
+}
+
+/// What a ledger entry records
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerEvent {
+    Launched,
+    Started,
+    Stopped,
+    Terminated,
+    /// Periodic cost reading of a running resource
+    Snapshot,
+}
+
+impl LedgerEvent {
+    /// Whether the resource accrues cost after this entry
+    fn is_running(self) -> bool {
+        matches!(
+            self,
+            LedgerEvent::Launched | LedgerEvent::Started | LedgerEvent::Snapshot
+        )
+    }
+}
+
+/// One line of the ledger
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub timestamp: DateTime<Utc>,
+    pub resource_id: String,
+    pub event: LedgerEvent,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// `[accounts]` entry the resource was found in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance_type: Option<String>,
+    pub cost_per_hour: f64,
+    /// Everything the resource has cost up to `timestamp`
+    pub accumulated_cost: f64,
+}
+
+impl LedgerEntry {
+    pub fn from_tracked(
+        resource: &TrackedResource,
+        event: LedgerEvent,
+        timestamp: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            timestamp,
+            resource_id: resource.status.id.clone(),
+            event,
+            project: resource.tags.get("runctl:project").cloned(),
+            user: resource.tags.get("runctl:user").cloned(),
+            account: resource
+                .tags
+                .get(crate::resource_tracking::budget::ACCOUNT_TAG)
+                .cloned(),
+            instance_type: resource.status.instance_type.clone(),
+            cost_per_hour: resource.status.cost_per_hour,
+            accumulated_cost: resource.accumulated_cost,
+        }
+    }
+}
+
+/// Append-only cost ledger file
+pub struct Ledger {
+    path: PathBuf,
+    /// Time of the newest entry per resource, read on first use
+    latest: std::sync::Mutex<Option<HashMap<String, DateTime<Utc>>>>,
+}
+
+impl Ledger {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            latest: std::sync::Mutex::new(None),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append entries, one line each
+    pub fn append(&self, entries: &[LedgerEntry]) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            crate::utils::ensure_dir(parent)?;
+        }
+        let mut lines = String::new();
+        for entry in entries {
+            lines.push_str(&serde_json::to_string(entry)?);
+            lines.push('\n');
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        // A single write keeps lines from concurrent processes whole
+        file.write_all(lines.as_bytes())?;
+
+        let mut latest = self.latest.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(latest) = latest.as_mut() {
+            for entry in entries {
+                latest.insert(entry.resource_id.clone(), entry.timestamp);
+            }
+        }
+        Ok(())
+    }
+
+    /// All entries, skipping lines that don't parse
+    pub fn load(&self) -> Result<Vec<LedgerEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = std::fs::read_to_string(&self.path)?;
+        Ok(content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Snapshot running resources not yet recorded in the current clock hour
+    ///
+    /// Returns how many snapshots were written.
+    pub fn record_snapshots(
+        &self,
+        resources: &[TrackedResource],
+        now: DateTime<Utc>,
+    ) -> Result<usize> {
+        let hour = start_of_hour(now);
+        let due: Vec<LedgerEntry> = {
+            let mut latest = self.latest.lock().unwrap_or_else(|e| e.into_inner());
+            if latest.is_none() {
+                let mut newest: HashMap<String, DateTime<Utc>> = HashMap::new();
+                for entry in self.load()? {
+                    let ts = newest.entry(entry.resource_id).or_insert(entry.timestamp);
+                    *ts = (*ts).max(entry.timestamp);
+                }
+                *latest = Some(newest);
+            }
+            let latest = latest.as_ref();
+            resources
+                .iter()
+                .filter(|r| {
+                    matches!(
+                        r.status.state,
+                        crate::provider::ResourceState::Running
+                            | crate::provider::ResourceState::Starting
+                    )
+                })
+                .filter(|r| {
+                    latest
+                        .and_then(|l| l.get(&r.status.id))
+                        .is_none_or(|ts| *ts < hour)
+                })
+                .map(|r| LedgerEntry::from_tracked(r, LedgerEvent::Snapshot, now))
+                .collect()
+        };
+        self.append(&due)?;
+        Ok(due.len())
+    }
+}
+
+fn start_of_hour(time: DateTime<Utc>) -> DateTime<Utc> {
+    time.with_minute(0)
+        .and_then(|t| t.with_second(0))
+        .and_then(|t| t.with_nanosecond(0))
+        .unwrap_or(time)
+}
+
+/// How `costs report` groups resources
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GroupBy {
+    Project,
+    User,
+    Account,
+    InstanceType,
+    Resource,
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "project" => Ok(GroupBy::Project),
+            "user" => Ok(GroupBy::User),
+            "account" => Ok(GroupBy::Account),
+            "instance-type" | "type" => Ok(GroupBy::InstanceType),
+            "resource" | "instance" => Ok(GroupBy::Resource),
+            other => Err(format!(
+                "unknown grouping '{}' (use project, user, account, instance-type or resource)",
+                other
+            )),
+        }
+    }
+}
+
+/// One group in a cost report
+#[derive(Debug, Clone, Serialize)]
+pub struct CostReportRow {
+    pub key: String,
+    pub resources: usize,
+    pub cost: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CostReport {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub group_by: GroupBy,
+    /// Highest cost first
+    pub rows: Vec<CostReportRow>,
+    pub total: f64,
+    /// Data egress recorded in `cost_history` within the window
+    pub egress: f64,
+}
+
+/// Accumulated cost of a resource at `time`
+///
+/// Between entries of a running resource, cost grows at its hourly rate,
+/// never past the next entry's reading. `entries` must be in time order.
+fn cost_at(entries: &[&LedgerEntry], time: DateTime<Utc>) -> f64 {
+    let Some(before) = entries.iter().rev().find(|e| e.timestamp <= time) else {
+        return 0.0;
+    };
+    let mut cost = before.accumulated_cost;
+    if before.event.is_running() {
+        let hours = (time - before.timestamp).num_seconds() as f64 / 3600.0;
+        cost += before.cost_per_hour * hours;
+        if let Some(next) = entries.iter().find(|e| e.timestamp > time) {
+            cost = cost.min(next.accumulated_cost);
+        }
+    }
+    cost
+}
+
+/// Cost a resource accrued between `since` and `until`
+fn cost_in_window(entries: &[&LedgerEntry], since: DateTime<Utc>, until: DateTime<Utc>) -> f64 {
+    (cost_at(entries, until) - cost_at(entries, since)).max(0.0)
+}
+
+/// Sum each resource's cost in the window into groups
+pub fn build_report(
+    entries: &[LedgerEntry],
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+    group_by: GroupBy,
+) -> CostReport {
+    let mut by_resource: BTreeMap<&str, Vec<&LedgerEntry>> = BTreeMap::new();
+    for entry in entries {
+        by_resource
+            .entry(entry.resource_id.as_str())
+            .or_default()
+            .push(entry);
+    }
+
+    let mut groups: BTreeMap<String, (usize, f64)> = BTreeMap::new();
+    for (resource_id, mut history) in by_resource {
+        history.sort_by_key(|e| e.timestamp);
+        let cost = cost_in_window(&history, since, until);
+        if cost <= 0.0 {
+            continue;
+        }
+        // Labels from the newest entry; tags may have been added later
+        let latest = history.last().expect("grouped entries are never empty");
+        let key = match group_by {
+            GroupBy::Project => latest.project.clone(),
+            GroupBy::User => latest.user.clone(),
+            GroupBy::Account => latest.account.clone(),
+            GroupBy::InstanceType => latest.instance_type.clone(),
+            GroupBy::Resource => Some(resource_id.to_string()),
+        }
+        .unwrap_or_else(|| "(untagged)".to_string());
+        let group = groups.entry(key).or_insert((0, 0.0));
+        group.0 += 1;
+        group.1 += cost;
+    }
+
+    let mut rows: Vec<CostReportRow> = groups
+        .into_iter()
+        .map(|(key, (resources, cost))| CostReportRow {
+            key,
+            resources,
+            cost,
+        })
+        .collect();
+    rows.sort_by(|a, b| b.cost.total_cmp(&a.cost));
+    let total = rows.iter().map(|r| r.cost).sum();
+    CostReport {
+        since,
+        until,
+        group_by,
+        rows,
+        total,
+        egress: 0.0,
+    }
+}
+
+#[derive(Subcommand, Clone)]
+pub enum CostsCommands {
+    /// Cost breakdown from the ledger (~/.runctl/ledger.jsonl)
+    ///
+    /// Includes terminated instances. Costs come from lifecycle events and
+    /// hourly snapshots recorded by the resource tracker, so resources runctl
+    /// never saw are missing.
+    ///
+    /// Examples:
+    ///   runctl costs report
+    ///   runctl costs report --since 7d --group-by user
+    ///   runctl costs report --group-by account
+    ///   runctl costs report --since 90d --group-by instance-type --output json
+    Report {
+        /// Start of the window, as a duration before now (e.g. 24h, 30d, 12w)
+        #[arg(long, default_value = "30d", value_parser = crate::units::parse_secs)]
+        since: u64,
+        /// Group by project, user, account, instance-type or resource
+        #[arg(long, default_value = "project")]
+        group_by: GroupBy,
+    },
+    /// Estimate what a run will cost before launching it
+    ///
+    /// Prices the manifest's instance type and any --types alternatives for
+    /// the given duration: on-demand and spot compute, the root volume, and
+    /// copying the manifest's S3 input into the region.
+    ///
+    /// Examples:
+    ///   runctl costs estimate --spec job.yaml --duration 48h
+    ///   runctl costs estimate --spec job.yaml --duration 7d --types g5.2xlarge,g6.xlarge,p3.2xlarge
+    ///   runctl costs estimate --spec job.yaml --duration 48h --region us-west-2 --output json
+    Estimate {
+        /// Run manifest (YAML, or TOML with a .toml extension)
+        #[arg(long, value_name = "FILE")]
+        spec: PathBuf,
+        /// How long the run will take (e.g. 12h, 48h, 7d)
+        #[arg(long, value_parser = crate::units::parse_secs)]
+        duration: u64,
+        /// Other instance types to compare, comma-separated
+        #[arg(long, value_name = "TYPES", value_delimiter = ',', value_parser = crate::validation::instance_type_arg)]
+        types: Vec<String>,
+        /// Region to price in (default: [aws] region)
+        #[arg(long)]
+        region: Option<String>,
+    },
+}
+
+pub async fn handle_command(
+    cmd: CostsCommands,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    match cmd {
+        CostsCommands::Report { since, group_by } => {
+            let path = default_ledger_path().ok_or_else(|| {
+                TrainctlError::CostTracking("Home directory not found for the ledger".to_string())
+            })?;
+            let ledger = Ledger::new(path);
+            // Bring running resources up to date before reading
+            if let Some(tracker) = &config.resource_tracker {
+                ledger.record_snapshots(&tracker.get_running().await, Utc::now())?;
+            }
+
+            let until = Utc::now();
+            let since = until - Duration::seconds(since.min(i64::MAX as u64) as i64);
+            let mut report = build_report(&ledger.load()?, since, until, group_by);
+            report.egress = crate::cost_history::load_from(&crate::cost_history::history_path())?
+                .iter()
+                .filter(|r| r.timestamp >= since && r.timestamp <= until)
+                .map(|r| r.cost_usd)
+                .sum();
+
+            if output_format == "json" {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print_report(&report, ledger.path());
+            }
+            Ok(())
+        }
+        #[cfg(not(feature = "aws"))]
+        CostsCommands::Estimate { .. } => {
+            Err(crate::error::ConfigError::FeatureNotCompiled("aws".to_string()).into())
+        }
+        #[cfg(feature = "aws")]
+        CostsCommands::Estimate {
+            spec,
+            duration,
+            types,
+            region,
+        } => {
+            let options = crate::cost_estimate::EstimateOptions {
+                spec,
+                duration_secs: duration,
+                types,
+                region,
+            };
+            let estimate = crate::cost_estimate::estimate(&options, config).await?;
+            if output_format == "json" {
+                println!("{}", serde_json::to_string_pretty(&estimate)?);
+            } else {
+                crate::cost_estimate::print_estimate(&estimate);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn print_report(report: &CostReport, ledger_path: &Path) {
+    println!(
+        "Costs from {} to {}",
+        report.since.format("%Y-%m-%d %H:%M UTC"),
+        report.until.format("%Y-%m-%d %H:%M UTC")
+    );
+    if report.rows.is_empty() {
+        println!("  No instance costs recorded in {}", ledger_path.display());
+    } else {
+        let heading = match report.group_by {
+            GroupBy::Project => "PROJECT",
+            GroupBy::User => "USER",
+            GroupBy::Account => "ACCOUNT",
+            GroupBy::InstanceType => "INSTANCE TYPE",
+            GroupBy::Resource => "RESOURCE",
+        };
+        println!("  {:<28} {:>9} {:>12}", heading, "RESOURCES", "COST");
+        for row in &report.rows {
+            println!(
+                "  {:<28} {:>9} {:>12}",
+                row.key,
+                row.resources,
+                format!("${:.2}", row.cost)
+            );
+        }
+        println!(
+            "  {:<28} {:>9} {:>12}",
+            "TOTAL",
+            "",
+            format!("${:.2}", report.total)
+        );
+    }
+    if report.egress > 0.0 {
+        println!("  Data egress (this directory): ${:.2}", report.egress);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(
+        id: &str,
+        project: &str,
+        event: LedgerEvent,
+        hour: u32,
+        accumulated_cost: f64,
+    ) -> LedgerEntry {
+        LedgerEntry {
+            timestamp: Utc.with_ymd_and_hms(2026, 5, 1, hour, 0, 0).unwrap(),
+            resource_id: id.to_string(),
+            event,
+            project: Some(project.to_string()),
+            user: None,
+            account: None,
+            instance_type: Some("g5.xlarge".to_string()),
+            cost_per_hour: 1.0,
+            accumulated_cost,
+        }
+    }
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 5, 1, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_report_counts_only_the_window() {
+        let entries = vec![
+            entry("i-a", "alpha", LedgerEvent::Launched, 0, 0.0),
+            entry("i-a", "alpha", LedgerEvent::Snapshot, 2, 2.0),
+            entry("i-a", "alpha", LedgerEvent::Terminated, 6, 6.0),
+            entry("i-b", "beta", LedgerEvent::Launched, 5, 0.0),
+            entry("i-b", "beta", LedgerEvent::Stopped, 8, 3.0),
+        ];
+
+        // 04:00-07:00: alpha ran 2h of it, beta 2h
+        let report = build_report(&entries, at(4), at(7), GroupBy::Project);
+        let costs: Vec<(&str, f64)> = report
+            .rows
+            .iter()
+            .map(|r| (r.key.as_str(), r.cost))
+            .collect();
+        assert_eq!(costs.len(), 2);
+        assert!((costs[0].1 - 2.0).abs() < 1e-9);
+        assert!((costs[1].1 - 2.0).abs() < 1e-9);
+        assert!((report.total - 4.0).abs() < 1e-9);
+
+        // Terminated resources stay in the report
+        let report = build_report(&entries, at(0), at(9), GroupBy::Resource);
+        assert!((report.total - 9.0).abs() < 1e-9);
+        assert_eq!(report.rows[0].key, "i-a");
+    }
+
+    #[test]
+    fn test_stopped_resources_cost_nothing_later() {
+        let entries = vec![
+            entry("i-a", "alpha", LedgerEvent::Launched, 0, 0.0),
+            entry("i-a", "alpha", LedgerEvent::Stopped, 1, 1.0),
+        ];
+        let report = build_report(&entries, at(3), at(9), GroupBy::Project);
+        assert!(report.rows.is_empty());
+    }
+
+    #[test]
+    fn test_group_by_parses() {
+        assert_eq!("project".parse::<GroupBy>().unwrap(), GroupBy::Project);
+        assert_eq!("account".parse::<GroupBy>().unwrap(), GroupBy::Account);
+        assert_eq!(
+            "instance-type".parse::<GroupBy>().unwrap(),
+            GroupBy::InstanceType
+        );
+        assert!("region".parse::<GroupBy>().is_err());
+    }
+}
@@ -1,25 +1,30 @@
-//! Interactive dashboard for monitoring resources and processes
+//! Interactive dashboard for monitoring resources and training jobs
 //!
-//! Provides a ratatui-based TUI (Terminal User Interface) dashboard showing:
-//! - Running resources (instances, pods, processes)
-//! - Current costs and billing estimates
-//! - Process resource usage (top-like view)
-//! - Real-time updates with configurable refresh interval
+//! Provides a ratatui-based TUI (Terminal User Interface) dashboard with four
+//! panels:
+//! - **Instances**: running and stopped EC2 instances with CPU/memory/GPU
+//!   usage, a GPU utilization sparkline per instance, and a detail pane with
+//!   the selected instance's tags and training status
+//! - **Jobs**: training status and progress reported by `runctl agent`
+//! - **Costs**: hourly rate, accumulated cost, and per-instance breakdown
+//! - **Logs**: tail of the selected instance's training log
 //!
-//! ## Features
-//!
-//! - **Multi-tab interface**: Switch between resources, processes, and cost views
-//! - **Real-time updates**: Automatically refreshes at specified intervals
-//! - **Interactive navigation**: Use arrow keys and tab to navigate
-//! - **Cost tracking**: Shows per-instance and total costs
-//! - **Process monitoring**: Displays CPU, memory, and GPU usage
+//! Usage comes from a fresh `runctl agent` report when there is one and from
+//! SSM otherwise. GPU history is kept for the life of the dashboard.
 //!
 //! ## Controls
 //!
-//! - `q` or `Ctrl+C`: Quit the dashboard
-//! - `Tab`: Switch between tabs
-//! - Arrow keys: Navigate within tabs
-//! - `Enter`: Select instance (in resources tab)
+//! - `q` or `Esc`: Quit the dashboard
+//! - `Tab` / `Shift+Tab`, `h` / `l`, `1`-`4`: Switch panels
+//! - `Up` / `Down`, `k` / `j`: Select an instance
+//! - `Enter`: SSH into the selected instance (the dashboard resumes on exit)
+//! - `s`: Stop the selected instance (asks for confirmation)
+//! - `t`: Terminate the selected instance (asks for confirmation)
+//! - `r`: Refresh now
+//!
+//! Stop and terminate run the same code as `runctl aws stop` and
+//! `runctl aws terminate`, including graceful training shutdown and
+//! protection checks, with the dashboard suspended while they print.
 //!
 //! ## Usage
 //!
@@ -34,10 +39,11 @@
 //! # }
 //! ```
 
-use crate::aws_utils;
+use crate::agent::AgentStatus;
 use crate::config::Config;
 use crate::diagnostics;
 use crate::error::{Result, TrainctlError};
+use crate::output_style::{self, Glyph};
 use crate::resource_tracking::ResourceUsage as TrackedResourceUsage;
 use aws_config::BehaviorVersion;
 use aws_sdk_ec2::Client as Ec2Client;
@@ -52,20 +58,78 @@ use ratatui::{
     backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
+    symbols,
     text::{Line, Span},
-    widgets::{Block, Borders, Cell, Gauge, Paragraph, Row, Table, Tabs},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Sparkline, Table, TableState, Tabs, Wrap},
     Frame, Terminal,
 };
-use std::io;
+use std::collections::HashMap;
+use std::io::{self, BufRead};
 use std::time::{Duration, Instant};
 
+const TABS: [&str; 4] = ["Instances", "Jobs", "Costs", "Logs"];
+const LOGS_TAB: usize = 3;
+/// Box and sparkline characters for `--ascii`
+const ASCII_BORDER: symbols::border::Set = symbols::border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+const ASCII_BARS: symbols::bar::Set = symbols::bar::Set {
+    full: "#",
+    seven_eighths: "#",
+    three_quarters: "=",
+    five_eighths: "=",
+    half: "-",
+    three_eighths: "-",
+    one_quarter: ".",
+    one_eighth: ".",
+    empty: " ",
+};
+/// GPU samples kept per instance (one per refresh)
+const GPU_HISTORY_LEN: usize = 60;
+/// Log lines fetched for the Logs panel
+const LOG_TAIL_LINES: usize = 200;
+
+/// Instance action bound to a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Action {
+    Ssh,
+    Stop,
+    Terminate,
+}
+
+impl Action {
+    pub(crate) fn verb(self) -> &'static str {
+        match self {
+            Action::Ssh => "ssh into",
+            Action::Stop => "stop",
+            Action::Terminate => "terminate",
+        }
+    }
+}
+
 struct DashboardState {
     selected_tab: usize,
-    selected_instance: Option<String>,
-    last_update: Instant,
+    /// Index into `instances`
+    selected: usize,
+    last_update: Option<Instant>,
     update_interval: Duration,
     instances: Vec<InstanceInfo>,
-    processes: Vec<ProcessInfo>,
+    /// GPU utilization samples by instance ID, oldest first
+    gpu_history: HashMap<String, Vec<u64>>,
+    /// Instance the Logs panel was fetched for
+    log_instance: Option<String>,
+    log_lines: Vec<String>,
+    /// Action waiting for `y`
+    pending: Option<(Action, String)>,
+    /// Last error or action outcome, shown in the footer
+    message: Option<String>,
     total_cost: f64,
     running_count: usize,
 }
@@ -74,17 +138,56 @@ impl Default for DashboardState {
     fn default() -> Self {
         Self {
             selected_tab: 0,
-            selected_instance: None,
-            last_update: Instant::now(),
+            selected: 0,
+            last_update: None,
             update_interval: Duration::from_secs(5),
             instances: Vec::new(),
-            processes: Vec::new(),
+            gpu_history: HashMap::new(),
+            log_instance: None,
+            log_lines: Vec::new(),
+            pending: None,
+            message: None,
             total_cost: 0.0,
             running_count: 0,
         }
     }
 }
 
+impl DashboardState {
+    fn selected_instance(&self) -> Option<&InstanceInfo> {
+        self.instances.get(self.selected)
+    }
+
+    fn is_due(&self) -> bool {
+        match self.last_update {
+            Some(t) => t.elapsed() >= self.update_interval,
+            None => true,
+        }
+    }
+
+    /// Whether the Logs panel shows something other than the selected instance
+    fn logs_stale(&self) -> bool {
+        self.selected_tab == LOGS_TAB
+            && self.selected_instance().map(|i| &i.id) != self.log_instance.as_ref()
+    }
+
+    /// Append one refresh worth of GPU samples and forget vanished instances
+    fn record_gpu_samples(&mut self) {
+        let mut history = std::mem::take(&mut self.gpu_history);
+        history.retain(|id, _| self.instances.iter().any(|i| &i.id == id));
+        for inst in &self.instances {
+            if let Some(gpu) = inst.gpu_usage {
+                let samples = history.entry(inst.id.clone()).or_default();
+                samples.push(gpu.clamp(0.0, 100.0).round() as u64);
+                if samples.len() > GPU_HISTORY_LEN {
+                    samples.remove(0);
+                }
+            }
+        }
+        self.gpu_history = history;
+    }
+}
+
 struct InstanceInfo {
     id: String,
     instance_type: String,
@@ -95,23 +198,38 @@ struct InstanceInfo {
     cpu_usage: f64,
     memory_usage: f64,
     gpu_usage: Option<f64>,
+    /// Sorted by key
+    tags: Vec<(String, String)>,
+    /// Fresh `runctl agent` report, if any
+    agent: Option<AgentStatus>,
 }
 
-struct ProcessInfo {
-    pid: String,
-    user: String,
-    cpu: f64,
-    memory: f64,
-    command: String,
-    #[allow(dead_code)]
-    instance_id: Option<String>,
+impl InstanceInfo {
+    fn tag(&self, key: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn name(&self) -> &str {
+        self.tag("Name").unwrap_or("-")
+    }
+
+    /// Training status as the agent reports it
+    fn training(&self) -> &str {
+        self.agent
+            .as_ref()
+            .map(|a| a.training.as_str())
+            .unwrap_or("no agent report")
+    }
 }
 
 /// Run the interactive resource monitoring dashboard
 ///
-/// Launches a full-screen TUI dashboard showing running resources, processes,
-/// and cost information. The dashboard updates automatically at the specified
-/// interval.
+/// Launches a full-screen TUI dashboard with Instances, Jobs, Costs and Logs
+/// panels. The dashboard updates automatically at the specified interval, and
+/// the selected instance can be stopped, terminated or opened over SSH.
 ///
 /// # Arguments
 ///
@@ -120,8 +238,9 @@ struct ProcessInfo {
 ///
 /// # Errors
 ///
-/// Returns `TrainctlError` if terminal initialization fails, AWS API calls fail,
-/// or if the dashboard cannot be rendered.
+/// Returns `TrainctlError` if terminal initialization fails or if the
+/// dashboard cannot be rendered. AWS errors during a refresh are shown in the
+/// dashboard instead.
 ///
 /// # Examples
 ///
@@ -136,53 +255,120 @@ struct ProcessInfo {
 /// # }
 /// ```
 pub async fn run_dashboard(config: &Config, update_interval_secs: u64) -> Result<()> {
+    let region_str = config
+        .aws
+        .as_ref()
+        .map(|a| a.region.clone())
+        .unwrap_or_else(|| "us-east-1".to_string());
+    let sdk_config = aws_config::defaults(BehaviorVersion::latest())
+        .region(aws_sdk_ec2::config::Region::new(region_str))
+        .load()
+        .await;
+
     let mut terminal = init_terminal()?;
     let mut state = DashboardState {
-        update_interval: Duration::from_secs(update_interval_secs),
+        update_interval: Duration::from_secs(update_interval_secs.max(1)),
         ..Default::default()
     };
 
-    loop {
-        // Update data
-        update_state(&mut state, config).await?;
-
-        // Render
-        terminal.draw(|f| render_dashboard(f, &state))?;
-
-        // Handle input
-        if crossterm::event::poll(state.update_interval)? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc => break,
-                        KeyCode::Char('h') | KeyCode::Left => {
-                            state.selected_tab = state.selected_tab.saturating_sub(1);
-                        }
-                        KeyCode::Char('l') | KeyCode::Right => {
-                            state.selected_tab = (state.selected_tab + 1).min(3);
-                        }
-                        KeyCode::Up => {
-                            // Navigate instances/processes
-                        }
-                        KeyCode::Down => {
-                            // Navigate instances/processes
-                        }
-                        KeyCode::Char('r') => {
-                            // Force refresh
-                            state.last_update = Instant::now() - state.update_interval;
-                        }
-                        _ => {}
+    let result: Result<()> = async {
+        loop {
+            if state.is_due() {
+                match update_state(&mut state, config, &sdk_config).await {
+                    Ok(()) => {
+                        state.record_gpu_samples();
+                        state.message = None;
                     }
+                    Err(e) => state.message = Some(e.to_string()),
                 }
+                state.last_update = Some(Instant::now());
+            }
+            if state.logs_stale() {
+                update_logs(&mut state, config, &sdk_config).await;
+            }
+
+            terminal.draw(|f| render_dashboard(f, &state))?;
+
+            if !event::poll(Duration::from_millis(250))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            if let Some((action, instance_id)) = state.pending.take() {
+                if key.code == KeyCode::Char('y') {
+                    run_suspended(&mut terminal, action, &instance_id, config, &sdk_config).await?;
+                    state.message = None;
+                    state.last_update = None;
+                    state.log_instance = None;
+                } else {
+                    state.message = Some(format!("Did not {} {}", action.verb(), instance_id));
+                }
+                continue;
+            }
+
+            let count = state.instances.len();
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => break,
+                KeyCode::Tab | KeyCode::Char('l') | KeyCode::Right => {
+                    state.selected_tab = (state.selected_tab + 1) % TABS.len();
+                }
+                KeyCode::BackTab | KeyCode::Char('h') | KeyCode::Left => {
+                    state.selected_tab = (state.selected_tab + TABS.len() - 1) % TABS.len();
+                }
+                KeyCode::Char(c @ '1'..='4') => {
+                    state.selected_tab = c as usize - '1' as usize;
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    state.selected = state.selected.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    state.selected = (state.selected + 1).min(count.saturating_sub(1));
+                }
+                KeyCode::Char('r') => {
+                    state.last_update = None;
+                    state.log_instance = None;
+                }
+                KeyCode::Enter => {
+                    if let Some(inst) = state.selected_instance() {
+                        let instance_id = inst.id.clone();
+                        run_suspended(
+                            &mut terminal,
+                            Action::Ssh,
+                            &instance_id,
+                            config,
+                            &sdk_config,
+                        )
+                        .await?;
+                        state.last_update = None;
+                    }
+                }
+                KeyCode::Char('s') | KeyCode::Char('t') => {
+                    let action = if key.code == KeyCode::Char('s') {
+                        Action::Stop
+                    } else {
+                        Action::Terminate
+                    };
+                    if let Some(inst) = state.selected_instance() {
+                        state.pending = Some((action, inst.id.clone()));
+                    }
+                }
+                _ => {}
             }
         }
+        Ok(())
     }
+    .await;
 
     restore_terminal(&mut terminal)?;
-    Ok(())
+    result
 }
 
-fn init_terminal() -> Result<Terminal<CrosstermBackend<io::Stdout>>> {
+pub(crate) fn init_terminal() -> Result<Terminal<CrosstermBackend<io::Stdout>>> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
@@ -190,7 +376,9 @@ fn init_terminal() -> Result<Terminal<CrosstermBackend<io::Stdout>>> {
     Ok(Terminal::new(backend)?)
 }
 
-fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
+pub(crate) fn restore_terminal(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+) -> Result<()> {
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
@@ -201,26 +389,64 @@ fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Re
     Ok(())
 }
 
-async fn update_state(state: &mut DashboardState, config: &Config) -> Result<()> {
-    let now = Instant::now();
-    if now.duration_since(state.last_update) < state.update_interval {
-        return Ok(());
+/// Leave the TUI, run `action` on the normal terminal, then come back
+///
+/// Stop and terminate print their progress (and may ask questions), so the
+/// dashboard waits for Enter before redrawing over it.
+pub(crate) async fn run_suspended(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    action: Action,
+    instance_id: &str,
+    config: &Config,
+    sdk_config: &aws_config::SdkConfig,
+) -> Result<()> {
+    restore_terminal(terminal)?;
+
+    let result = match action {
+        Action::Ssh => crate::aws::ssh_instance(instance_id, None, &[], config, sdk_config).await,
+        Action::Stop => {
+            crate::aws::stop_instance(instance_id.to_string(), false, sdk_config, "text", config)
+                .await
+        }
+        Action::Terminate => {
+            crate::aws::terminate_instance(
+                instance_id.to_string(),
+                false,
+                sdk_config,
+                "text",
+                config,
+            )
+            .await
+        }
+    };
+    if let Err(e) = &result {
+        eprintln!("ERROR: Could not {} {}: {}", action.verb(), instance_id, e);
+    }
+    if action != Action::Ssh || result.is_err() {
+        println!("\nPress Enter to return to the dashboard");
+        let mut line = String::new();
+        io::stdin().lock().read_line(&mut line)?;
     }
 
-    // Load AWS instances
-    let region_str = config
-        .aws
-        .as_ref()
-        .map(|a| a.region.clone())
-        .unwrap_or_else(|| "us-east-1".to_string());
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture
+    )?;
+    terminal.clear()?;
+    Ok(())
+}
 
-    let sdk_config = aws_config::defaults(BehaviorVersion::latest())
-        .region(aws_sdk_ec2::config::Region::new(region_str))
-        .load()
-        .await;
-    let ec2_client = Ec2Client::new(&sdk_config);
+async fn update_state(
+    state: &mut DashboardState,
+    config: &Config,
+    sdk_config: &aws_config::SdkConfig,
+) -> Result<()> {
+    let ec2_client = Ec2Client::new(sdk_config);
+    let s3_client = aws_sdk_s3::Client::new(sdk_config);
 
-    // Get running instances with retry logic
+    // Stopped instances stay listed so they can still be selected and terminated
     use crate::retry::{ExponentialBackoffPolicy, RetryPolicy};
     let response = ExponentialBackoffPolicy::for_cloud_api()
         .execute_with_retry(|| async {
@@ -228,13 +454,17 @@ async fn update_state(state: &mut DashboardState, config: &Config) -> Result<()>
                 .describe_instances()
                 .set_filters(Some(vec![aws_sdk_ec2::types::Filter::builder()
                     .name("instance-state-name")
+                    .values("pending")
                     .values("running")
+                    .values("stopping")
+                    .values("stopped")
                     .build()]))
                 .send()
                 .await
                 .map_err(|e| TrainctlError::Aws(format!("Failed to describe instances: {}", e)))
         })
         .await?;
+    crate::aws_pricing::prefetch_reservations(&ec2_client, response.reservations()).await;
 
     let mut instances = Vec::new();
     let mut total_cost = 0.0;
@@ -243,7 +473,6 @@ async fn update_state(state: &mut DashboardState, config: &Config) -> Result<()>
     for reservation in response.reservations() {
         for instance in reservation.instances() {
             if let Some(instance_id) = instance.instance_id() {
-                running_count += 1;
                 let instance_type = instance
                     .instance_type()
                     .map(|t| format!("{}", t))
@@ -253,6 +482,15 @@ async fn update_state(state: &mut DashboardState, config: &Config) -> Result<()>
                     .and_then(|s| s.name())
                     .map(|n| n.as_str().to_string())
                     .unwrap_or_else(|| "unknown".to_string());
+                if state == "running" {
+                    running_count += 1;
+                }
+                let mut tags: Vec<(String, String)> = instance
+                    .tags()
+                    .iter()
+                    .filter_map(|t| Some((t.key()?.to_string(), t.value()?.to_string())))
+                    .collect();
+                tags.sort();
 
                 // Calculate runtime duration (needed for both cost and display)
                 let launch_time = instance
@@ -265,6 +503,9 @@ async fn update_state(state: &mut DashboardState, config: &Config) -> Result<()>
                 };
 
                 // Get costs from ResourceTracker if available, otherwise calculate
+                if let Some(tracker) = &config.resource_tracker {
+                    crate::aws::reconcile_instance(tracker, instance).await;
+                }
                 let instance_id_string = instance_id.to_string();
                 let (cost_per_hour, accumulated_cost) =
                     crate::utils::get_instance_cost_with_tracker(
@@ -278,8 +519,18 @@ async fn update_state(state: &mut DashboardState, config: &Config) -> Result<()>
                 total_cost += accumulated_cost;
 
                 // Get resource usage (async, but don't block on errors)
+                let mut agent = None;
                 let (cpu_usage, memory_usage, gpu_usage) = if state == "running" {
-                    let usage_result = get_instance_usage(&sdk_config, instance_id).await;
+                    // A fresh `runctl agent` report saves the SSM round-trip
+                    agent = crate::agent::fetch_fresh_status(config, &s3_client, instance_id).await;
+                    let usage_result = match &agent {
+                        Some(agent) => Ok((
+                            agent.cpu_percent.unwrap_or(0.0),
+                            agent.memory_percent.unwrap_or(0.0),
+                            agent.gpu_utilization,
+                        )),
+                        None => get_instance_usage(sdk_config, instance_id).await,
+                    };
 
                     // Update ResourceTracker with usage data if available
                     if let (Ok((cpu, mem, gpu)), Some(tracker)) =
@@ -313,10 +564,13 @@ async fn update_state(state: &mut DashboardState, config: &Config) -> Result<()>
                     cpu_usage,
                     memory_usage,
                     gpu_usage,
+                    tags,
+                    agent,
                 });
             }
         }
     }
+    instances.sort_by(|a, b| a.id.cmp(&b.id));
 
     // Use ResourceTracker total cost if available
     let final_total_cost = if let Some(tracker) = &config.resource_tracker {
@@ -325,21 +579,46 @@ async fn update_state(state: &mut DashboardState, config: &Config) -> Result<()>
         total_cost
     };
 
+    // Keep the same instance selected when the list changes
+    let selected_id = state.selected_instance().map(|i| i.id.clone());
+    state.selected = selected_id
+        .and_then(|id| instances.iter().position(|i| i.id == id))
+        .unwrap_or(state.selected)
+        .min(instances.len().saturating_sub(1));
     state.instances = instances;
     state.total_cost = final_total_cost;
     state.running_count = running_count;
-    state.last_update = now;
 
-    // Load processes for selected instance
-    if let Some(instance_id) = &state.selected_instance {
-        state.processes = get_instance_processes(&sdk_config, instance_id)
-            .await
-            .unwrap_or_default();
+    if state.selected_tab == LOGS_TAB {
+        update_logs(state, config, sdk_config).await;
     }
 
     Ok(())
 }
 
+/// Fetch the training log tail of the selected instance
+async fn update_logs(
+    state: &mut DashboardState,
+    config: &Config,
+    sdk_config: &aws_config::SdkConfig,
+) {
+    let Some(inst) = state.selected_instance() else {
+        state.log_instance = None;
+        state.log_lines.clear();
+        return;
+    };
+    let (instance_id, instance_state) = (inst.id.clone(), inst.state.clone());
+    state.log_lines = if instance_state == "running" {
+        match crate::aws::tail_log(&instance_id, LOG_TAIL_LINES, config, sdk_config).await {
+            Ok(lines) => lines,
+            Err(e) => vec![format!("Could not read the training log: {}", e)],
+        }
+    } else {
+        vec![format!("Instance is {}", instance_state)]
+    };
+    state.log_instance = Some(instance_id);
+}
+
 async fn get_instance_usage(
     sdk_config: &aws_config::SdkConfig,
     instance_id: &str,
@@ -348,41 +627,18 @@ async fn get_instance_usage(
     let ssm_client = SsmClient::new(sdk_config);
     let usage = diagnostics::get_instance_resource_usage(&ssm_client, instance_id).await?;
 
-    let gpu_usage = usage
-        .gpu_info
-        .as_ref()
-        .and_then(|gpu| gpu.gpus.first())
-        .map(|gpu| gpu.utilization_percent);
-
-    Ok((usage.cpu_percent, usage.memory_percent, gpu_usage))
-}
-
-async fn get_instance_processes(
-    sdk_config: &aws_config::SdkConfig,
-    instance_id: &str,
-) -> Result<Vec<ProcessInfo>> {
-    // Get top processes via SSM
-    let ssm_client = SsmClient::new(sdk_config);
-
-    let command = r"ps aux --sort=-%cpu | head -20 | awk '{print $2,$1,$3,$4,$11}'";
-    let output = aws_utils::execute_ssm_command(&ssm_client, instance_id, command).await?;
-
-    let mut processes = Vec::new();
-    for line in output.lines().skip(1) {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 5 {
-            processes.push(ProcessInfo {
-                pid: parts[0].to_string(),
-                user: parts[1].to_string(),
-                cpu: parts[2].parse().unwrap_or(0.0),
-                memory: parts[3].parse().unwrap_or(0.0),
-                command: parts[4..].join(" "),
-                instance_id: Some(instance_id.to_string()),
-            });
+    // Average over all GPUs, as the agent reports it
+    let gpu_usage = usage.gpu_info.as_ref().and_then(|gpu| {
+        if gpu.gpus.is_empty() {
+            None
+        } else {
+            Some(
+                gpu.gpus.iter().map(|g| g.utilization_percent).sum::<f64>() / gpu.gpus.len() as f64,
+            )
         }
-    }
+    });
 
-    Ok(processes)
+    Ok((usage.cpu_percent, usage.memory_percent, gpu_usage))
 }
 
 fn format_runtime(duration: chrono::Duration) -> String {
@@ -399,132 +655,129 @@ fn format_runtime(duration: chrono::Duration) -> String {
     }
 }
 
-fn render_dashboard(f: &mut Frame, state: &DashboardState) {
-    let size = f.size();
-
-    // Tabs
-    let tabs = Tabs::new(vec!["Overview", "Instances", "Processes", "Costs"])
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("runctl Dashboard"),
-        )
-        .select(state.selected_tab)
-        .style(Style::default().fg(Color::White))
-        .highlight_style(
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        );
-
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Min(0)])
-        .split(size);
-
-    f.render_widget(tabs, chunks[0]);
+/// Text sparkline of the last `width` percentages, for table cells
+fn spark_text(samples: &[u64], width: usize) -> String {
+    let start = samples.len().saturating_sub(width);
+    samples[start..]
+        .iter()
+        .map(|&s| output_style::spark(s))
+        .collect()
+}
 
-    match state.selected_tab {
-        0 => render_overview(f, chunks[1], state),
-        1 => render_instances(f, chunks[1], state),
-        2 => render_processes(f, chunks[1], state),
-        3 => render_costs(f, chunks[1], state),
-        _ => {}
+/// A block with borders drawn in the current output style
+fn bordered<'a>() -> Block<'a> {
+    let block = Block::default().borders(Borders::ALL);
+    if output_style::is_ascii() {
+        block.border_set(ASCII_BORDER)
+    } else {
+        block
     }
 }
 
-fn render_overview(f: &mut Frame, area: Rect, state: &DashboardState) {
+fn header_style() -> Style {
+    Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD)
+}
+
+fn render_dashboard(f: &mut Frame, state: &DashboardState) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(3),
-            Constraint::Length(5),
             Constraint::Min(0),
+            Constraint::Length(1),
         ])
-        .split(area);
-
-    // Summary
-    let summary = Paragraph::new(vec![Line::from(vec![
-        Span::styled("Running: ", Style::default().fg(Color::Cyan)),
-        Span::styled(
-            format!("{} instances", state.running_count),
-            Style::default().fg(Color::Green),
-        ),
-        Span::raw(" | "),
-        Span::styled("Total Cost: ", Style::default().fg(Color::Cyan)),
-        Span::styled(
-            format!("${:.2}", state.total_cost),
-            Style::default().fg(Color::Yellow),
-        ),
-        Span::raw(" | "),
-        Span::styled("Last Update: ", Style::default().fg(Color::Cyan)),
-        Span::styled(
-            format!("{}s ago", state.last_update.elapsed().as_secs()),
-            Style::default().fg(Color::White),
-        ),
-    ])])
-    .block(Block::default().borders(Borders::ALL).title("Summary"));
-
-    f.render_widget(summary, chunks[0]);
-
-    // Cost gauge (show as percentage of $100/day budget)
-    let daily_estimate = state.total_cost * 24.0;
-    let cost_percent = ((daily_estimate.min(100.0) / 100.0) * 100.0) as u16;
-    let cost_gauge = Gauge::default()
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Daily Cost Estimate"),
-        )
-        .gauge_style(Style::default().fg(Color::Yellow))
-        .percent(cost_percent)
-        .label(format!("${:.2}/day est.", daily_estimate));
+        .split(f.size());
+
+    let title = format!(
+        "runctl Dashboard | {} running | ${:.2} total | updated {}s ago",
+        state.running_count,
+        state.total_cost,
+        state
+            .last_update
+            .map(|t| t.elapsed().as_secs())
+            .unwrap_or(0),
+    );
+    let tabs = Tabs::new(
+        TABS.iter()
+            .enumerate()
+            .map(|(i, name)| format!("{} {}", i + 1, name))
+            .collect::<Vec<_>>(),
+    )
+    .block(bordered().title(title))
+    .divider(output_style::glyph(Glyph::Divider))
+    .select(state.selected_tab)
+    .style(Style::default().fg(Color::White))
+    .highlight_style(header_style());
+    f.render_widget(tabs, chunks[0]);
 
-    f.render_widget(cost_gauge, chunks[1]);
+    match state.selected_tab {
+        0 => render_instances(f, chunks[1], state),
+        1 => render_jobs(f, chunks[1], state),
+        2 => render_costs(f, chunks[1], state),
+        LOGS_TAB => render_logs(f, chunks[1], state),
+        _ => {}
+    }
 
-    // Quick instance list
-    let rows: Vec<Row> = state
-        .instances
-        .iter()
-        .take(10)
-        .map(|inst| {
-            Row::new(vec![
-                Cell::from(inst.id.clone()),
-                Cell::from(inst.instance_type.clone()),
-                Cell::from(inst.state.clone()),
-                Cell::from(format!("${:.2}/h", inst.cost_per_hour)),
-                Cell::from(format!("${:.2}", inst.accumulated_cost)),
-                Cell::from(inst.runtime.clone()),
-            ])
-        })
-        .collect();
+    render_footer(f, chunks[2], state);
+}
 
-    let widths = [
-        Constraint::Length(20),
-        Constraint::Length(15),
-        Constraint::Length(10),
-        Constraint::Length(10),
-        Constraint::Length(10),
-        Constraint::Length(15),
-    ];
-    let table = Table::new(rows, widths)
-        .block(Block::default().borders(Borders::ALL).title("Instances"))
-        .header(
-            Row::new(vec!["ID", "Type", "State", "Cost/h", "Total", "Runtime"]).style(
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
+fn render_footer(f: &mut Frame, area: Rect, state: &DashboardState) {
+    let line = if let Some((action, instance_id)) = &state.pending {
+        Line::from(Span::styled(
+            format!(
+                "{} {}? Press y to confirm, any other key to cancel",
+                capitalize(action.verb()),
+                instance_id
             ),
-        );
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ))
+    } else if let Some(message) = &state.message {
+        Line::from(Span::styled(
+            message.clone(),
+            Style::default().fg(Color::Red),
+        ))
+    } else {
+        Line::from(Span::styled(
+            "q quit | tab/1-4 panels | j/k select | enter ssh | s stop | t terminate | r refresh",
+            Style::default().fg(Color::DarkGray),
+        ))
+    };
+    f.render_widget(Paragraph::new(line), area);
+}
 
-    f.render_widget(table, chunks[2]);
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn table_state(state: &DashboardState) -> TableState {
+    TableState::default().with_selected(if state.instances.is_empty() {
+        None
+    } else {
+        Some(state.selected)
+    })
 }
 
 fn render_instances(f: &mut Frame, area: Rect, state: &DashboardState) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+        .split(area);
+
     let rows: Vec<Row> = state
         .instances
         .iter()
         .map(|inst| {
+            let history = state
+                .gpu_history
+                .get(&inst.id)
+                .map(|samples| spark_text(samples, 12))
+                .unwrap_or_default();
             Row::new(vec![
                 Cell::from(inst.id.clone()),
                 Cell::from(inst.instance_type.clone()),
@@ -536,92 +789,170 @@ fn render_instances(f: &mut Frame, area: Rect, state: &DashboardState) {
                         .map(|g| format!("{:.1}%", g))
                         .unwrap_or_else(|| "N/A".to_string()),
                 ),
+                Cell::from(history).style(Style::default().fg(Color::Green)),
                 Cell::from(format!("${:.2}/h", inst.cost_per_hour)),
-                Cell::from(format!("${:.2}", inst.accumulated_cost)),
             ])
         })
         .collect();
 
     let widths = [
         Constraint::Length(20),
-        Constraint::Length(15),
-        Constraint::Length(10),
-        Constraint::Length(8),
-        Constraint::Length(8),
-        Constraint::Length(8),
-        Constraint::Length(10),
-        Constraint::Length(10),
+        Constraint::Length(13),
+        Constraint::Length(9),
+        Constraint::Length(7),
+        Constraint::Length(7),
+        Constraint::Length(7),
+        Constraint::Length(12),
+        Constraint::Length(9),
     ];
     let table = Table::new(rows, widths)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Instances (Press 'r' to refresh)"),
-        )
+        .block(bordered().title("Instances"))
         .header(
             Row::new(vec![
-                "ID", "Type", "State", "CPU", "Mem", "GPU", "Cost/h", "Total",
+                "ID", "Type", "State", "CPU", "Mem", "GPU", "GPU hist", "Cost/h",
             ])
-            .style(
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            ),
-        );
+            .style(header_style()),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
 
-    f.render_widget(table, area);
+    f.render_stateful_widget(table, chunks[0], &mut table_state(state));
+    render_detail(f, chunks[1], state);
 }
 
-fn render_processes(f: &mut Frame, area: Rect, state: &DashboardState) {
-    if state.processes.is_empty() {
-        let msg = if state.selected_instance.is_some() {
-            "No processes found. Select an instance first."
-        } else {
-            "Select an instance to view processes (navigate with arrow keys)"
-        };
-
-        let paragraph =
-            Paragraph::new(msg).block(Block::default().borders(Borders::ALL).title("Processes"));
+/// Tags, training status and GPU history of the selected instance
+fn render_detail(f: &mut Frame, area: Rect, state: &DashboardState) {
+    let Some(inst) = state.selected_instance() else {
+        let paragraph = Paragraph::new("No instances").block(bordered().title("Details"));
         f.render_widget(paragraph, area);
         return;
+    };
+
+    let block = bordered().title(format!("Details: {}", inst.id));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let parts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(5)])
+        .split(inner);
+
+    let label = Style::default().fg(Color::Cyan);
+    let mut lines = vec![
+        Line::from(vec![Span::styled("Name: ", label), Span::raw(inst.name())]),
+        Line::from(vec![
+            Span::styled("Runtime: ", label),
+            Span::raw(inst.runtime.clone()),
+            Span::raw(format!(" (${:.2} so far)", inst.accumulated_cost)),
+        ]),
+        Line::from(vec![
+            Span::styled("Training: ", label),
+            Span::raw(inst.training().to_string()),
+        ]),
+    ];
+    if let Some(agent) = &inst.agent {
+        if let Some(progress) = agent.progress_summary() {
+            lines.push(Line::from(vec![
+                Span::styled("Progress: ", label),
+                Span::raw(progress),
+            ]));
+        }
+        lines.push(Line::from(vec![
+            Span::styled("Checkpoints: ", label),
+            Span::raw(agent.checkpoint_count.to_string()),
+        ]));
+    }
+    lines.push(Line::from(Span::styled("Tags:", label)));
+    for (key, value) in &inst.tags {
+        lines.push(Line::from(format!("  {} = {}", key, value)));
+    }
+    f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), parts[0]);
+
+    let empty = Vec::new();
+    let samples = state.gpu_history.get(&inst.id).unwrap_or(&empty);
+    let start = samples.len().saturating_sub(parts[1].width as usize);
+    let mut sparkline = Sparkline::default()
+        .block(Block::default().title("GPU utilization"))
+        .data(&samples[start..])
+        .max(100)
+        .style(Style::default().fg(Color::Green));
+    if output_style::is_ascii() {
+        sparkline = sparkline.bar_set(ASCII_BARS);
     }
+    f.render_widget(sparkline, parts[1]);
+}
 
+fn render_jobs(f: &mut Frame, area: Rect, state: &DashboardState) {
     let rows: Vec<Row> = state
-        .processes
+        .instances
         .iter()
-        .map(|proc| {
+        .map(|inst| {
+            let (progress, checkpoints) = match &inst.agent {
+                Some(agent) => (
+                    agent.progress_summary().unwrap_or_else(|| "-".to_string()),
+                    agent.checkpoint_count.to_string(),
+                ),
+                None => ("-".to_string(), "-".to_string()),
+            };
+            let color = match inst.training() {
+                "running" => Color::Green,
+                "completed" => Color::Blue,
+                s if s.starts_with("failed") => Color::Red,
+                _ => Color::Gray,
+            };
             Row::new(vec![
-                Cell::from(proc.pid.clone()),
-                Cell::from(proc.user.clone()),
-                Cell::from(format!("{:.1}%", proc.cpu)),
-                Cell::from(format!("{:.1}%", proc.memory)),
-                Cell::from(proc.command.clone()),
+                Cell::from(inst.id.clone()),
+                Cell::from(inst.tag("runctl:project").unwrap_or("-").to_string()),
+                Cell::from(inst.training().to_string()).style(Style::default().fg(color)),
+                Cell::from(progress),
+                Cell::from(checkpoints),
+                Cell::from(inst.runtime.clone()),
             ])
         })
         .collect();
 
     let widths = [
-        Constraint::Length(8),
-        Constraint::Length(10),
-        Constraint::Length(8),
-        Constraint::Length(8),
+        Constraint::Length(20),
+        Constraint::Length(16),
+        Constraint::Length(16),
         Constraint::Min(30),
+        Constraint::Length(11),
+        Constraint::Length(12),
     ];
     let table = Table::new(rows, widths)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Top Processes (like top)"),
-        )
+        .block(bordered().title("Jobs (from runctl agent reports)"))
         .header(
-            Row::new(vec!["PID", "User", "CPU%", "Mem%", "Command"]).style(
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            ),
-        );
+            Row::new(vec![
+                "Instance",
+                "Project",
+                "Training",
+                "Progress",
+                "Checkpoints",
+                "Runtime",
+            ])
+            .style(header_style()),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
 
-    f.render_widget(table, area);
+    f.render_stateful_widget(table, area, &mut table_state(state));
+}
+
+fn render_logs(f: &mut Frame, area: Rect, state: &DashboardState) {
+    let title = match &state.log_instance {
+        Some(id) => format!("Training log: {}", id),
+        None => "Training log".to_string(),
+    };
+    // Show the newest lines that fit
+    let height = area.height.saturating_sub(2) as usize;
+    let start = state.log_lines.len().saturating_sub(height);
+    let lines: Vec<Line> = state.log_lines[start..]
+        .iter()
+        .map(|l| Line::raw(output_style::sanitize(l)))
+        .collect();
+    let paragraph = if lines.is_empty() {
+        Paragraph::new("No log output (select a running instance)")
+    } else {
+        Paragraph::new(lines)
+    };
+    f.render_widget(paragraph.block(bordered().title(title)), area);
 }
 
 fn render_costs(f: &mut Frame, area: Rect, state: &DashboardState) {
@@ -653,7 +984,7 @@ fn render_costs(f: &mut Frame, area: Rect, state: &DashboardState) {
             ),
         ]),
     ])
-    .block(Block::default().borders(Borders::ALL).title("Cost Summary"));
+    .block(bordered().title("Cost Summary"));
 
     f.render_widget(total, chunks[0]);
 
@@ -666,7 +997,7 @@ fn render_costs(f: &mut Frame, area: Rect, state: &DashboardState) {
             Style::default().fg(Color::Green),
         ),
     ])])
-    .block(Block::default().borders(Borders::ALL).title("Current Rate"));
+    .block(bordered().title("Current Rate"));
 
     f.render_widget(hourly_para, chunks[1]);
 
@@ -693,18 +1024,25 @@ fn render_costs(f: &mut Frame, area: Rect, state: &DashboardState) {
         Constraint::Length(15),
     ];
     let table = Table::new(rows, widths)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Cost Breakdown"),
-        )
+        .block(bordered().title("Cost Breakdown"))
         .header(
-            Row::new(vec!["Instance", "Type", "Rate", "Accumulated", "Runtime"]).style(
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            ),
+            Row::new(vec!["Instance", "Type", "Rate", "Accumulated", "Runtime"])
+                .style(header_style()),
         );
 
     f.render_widget(table, chunks[2]);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spark_text_scales_and_keeps_newest() {
+        assert_eq!(spark_text(&[0, 50, 100], 10), "▁▅█");
+        assert_eq!(spark_text(&[100, 0, 0], 2), "▁▁");
+        assert_eq!(spark_text(&[], 5), "");
+        // Out-of-range samples are clamped
+        assert_eq!(spark_text(&[250], 1), "█");
+    }
+}
@@ -0,0 +1,451 @@
+//! Data locality checks for job placement
+//!
+//! Training next to the dataset is the difference between a free,
+//! fast read and a slow cross-region (or cross-provider) copy billed per GB.
+//! Before a job is placed, the dataset location is resolved (S3 bucket
+//! region, EFS region, RunPod network volume) and compared with where the
+//! job would run:
+//!
+//! - Same region/provider: proceed
+//! - Different AWS region: move the job to the dataset's region, unless the
+//!   region is pinned, in which case warn with an egress estimate
+//! - Different provider: warn with an egress estimate
+//!
+//! Estimates use list prices and a nominal throughput; they are meant to flag
+//! expensive placements, not to bill anything.
+
+use crate::error::{Result, TrainctlError};
+use aws_sdk_s3::Client as S3Client;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+
+/// AWS inter-region transfer, $/GB
+const AWS_INTER_REGION_PER_GB: f64 = 0.02;
+
+/// AWS transfer out to the internet (first 10 TB tier), $/GB
+const AWS_INTERNET_EGRESS_PER_GB: f64 = 0.09;
+
+/// RunPod does not charge for egress
+const RUNPOD_EGRESS_PER_GB: f64 = 0.0;
+
+/// Nominal throughput for cross-region copies within AWS, bytes/s
+const INTER_REGION_BYTES_PER_SEC: f64 = 100.0 * 1024.0 * 1024.0;
+
+/// Nominal throughput for copies over the internet, bytes/s
+const INTERNET_BYTES_PER_SEC: f64 = 40.0 * 1024.0 * 1024.0;
+
+const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Where a dataset lives
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DatasetLocation {
+    /// `s3://bucket/prefix`
+    S3 { bucket: String, prefix: String },
+    /// `efs://fs-12345678` or `efs://us-west-2/fs-12345678`
+    Efs {
+        file_system_id: String,
+        region: Option<String>,
+    },
+    /// `runpod-volume://<volume-id>`
+    RunpodVolume { volume_id: String },
+    /// Anything else is a local path
+    Local { path: PathBuf },
+}
+
+impl DatasetLocation {
+    pub fn parse(location: &str) -> Result<Self> {
+        let invalid = |reason: &str| TrainctlError::Validation {
+            field: "data".to_string(),
+            reason: format!("{}: '{}'", reason, location),
+        };
+        if let Some(rest) = location.strip_prefix("s3://") {
+            let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+            if bucket.is_empty() {
+                return Err(invalid("Missing bucket name"));
+            }
+            return Ok(DatasetLocation::S3 {
+                bucket: bucket.to_string(),
+                prefix: prefix.to_string(),
+            });
+        }
+        if let Some(rest) = location.strip_prefix("efs://") {
+            let (region, fs_id) = match rest.split_once('/') {
+                Some((region, fs_id)) => (Some(region.to_string()), fs_id),
+                None => (None, rest),
+            };
+            if !fs_id.starts_with("fs-") {
+                return Err(invalid("Expected an EFS file system ID (fs-...)"));
+            }
+            return Ok(DatasetLocation::Efs {
+                file_system_id: fs_id.trim_end_matches('/').to_string(),
+                region,
+            });
+        }
+        if let Some(volume_id) = location.strip_prefix("runpod-volume://") {
+            if volume_id.is_empty() {
+                return Err(invalid("Missing RunPod volume ID"));
+            }
+            return Ok(DatasetLocation::RunpodVolume {
+                volume_id: volume_id.trim_end_matches('/').to_string(),
+            });
+        }
+        if location.contains("://") {
+            return Err(invalid(
+                "Unsupported data location (expected s3://, efs://, runpod-volume:// or a local path)",
+            ));
+        }
+        Ok(DatasetLocation::Local {
+            path: PathBuf::from(location),
+        })
+    }
+}
+
+impl fmt::Display for DatasetLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DatasetLocation::S3 { bucket, prefix } => write!(f, "s3://{}/{}", bucket, prefix),
+            DatasetLocation::Efs {
+                file_system_id,
+                region: Some(region),
+            } => write!(f, "efs://{}/{}", region, file_system_id),
+            DatasetLocation::Efs { file_system_id, .. } => write!(f, "efs://{}", file_system_id),
+            DatasetLocation::RunpodVolume { volume_id } => {
+                write!(f, "runpod-volume://{}", volume_id)
+            }
+            DatasetLocation::Local { path } => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Where a job runs (or where its data lives)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "provider", rename_all = "lowercase")]
+pub enum Site {
+    Aws { region: String },
+    Runpod,
+    Local,
+}
+
+impl fmt::Display for Site {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Site::Aws { region } => write!(f, "aws:{}", region),
+            Site::Runpod => write!(f, "runpod"),
+            Site::Local => write!(f, "local"),
+        }
+    }
+}
+
+/// Estimated cost and time of moving a dataset to the compute site
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EgressEstimate {
+    pub bytes: u64,
+    pub cost_usd: f64,
+    pub transfer_secs: u64,
+}
+
+/// Price per GB and throughput for moving data from one site to another
+fn egress_rate(from: &Site, to: &Site) -> Option<(f64, f64)> {
+    match (from, to) {
+        (a, b) if a == b => None,
+        (Site::Aws { .. }, Site::Aws { .. }) => {
+            Some((AWS_INTER_REGION_PER_GB, INTER_REGION_BYTES_PER_SEC))
+        }
+        (Site::Aws { .. }, _) => Some((AWS_INTERNET_EGRESS_PER_GB, INTERNET_BYTES_PER_SEC)),
+        // Ingress to AWS is free; RunPod does not bill egress
+        (Site::Runpod, _) => Some((RUNPOD_EGRESS_PER_GB, INTERNET_BYTES_PER_SEC)),
+        (Site::Local, _) => Some((0.0, INTERNET_BYTES_PER_SEC)),
+    }
+}
+
+/// Estimate moving `bytes` from `from` to `to`; `None` when no copy is needed
+pub fn estimate_egress(from: &Site, to: &Site, bytes: u64) -> Option<EgressEstimate> {
+    let (per_gb, bytes_per_sec) = egress_rate(from, to)?;
+    Some(EgressEstimate {
+        bytes,
+        cost_usd: bytes as f64 / GB * per_gb,
+        transfer_secs: (bytes as f64 / bytes_per_sec).ceil() as u64,
+    })
+}
+
+/// Outcome of comparing the dataset's site with the planned compute site
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum Placement {
+    /// Data and compute are co-located
+    Local,
+    /// Run in the dataset's site instead of the planned one
+    Move { to: Site },
+    /// Stay, but the data must be copied
+    Remote {
+        data_site: Site,
+        /// `None` when the dataset size is unknown
+        estimate: Option<EgressEstimate>,
+        /// Constraint the user should know about (e.g. EFS is regional)
+        note: Option<String>,
+    },
+}
+
+/// Decide where to run given the dataset's site
+///
+/// `pinned` means the planned site was chosen explicitly and must not change.
+/// Only AWS region moves are made automatically; changing provider changes
+/// instance types and pricing, so that is left to the user.
+pub fn assess(
+    dataset: &DatasetLocation,
+    data_site: &Site,
+    planned: &Site,
+    size_bytes: Option<u64>,
+    pinned: bool,
+) -> Placement {
+    if data_site == planned || *data_site == Site::Local {
+        return Placement::Local;
+    }
+    if let (Site::Aws { .. }, Site::Aws { .. }) = (data_site, planned) {
+        if !pinned {
+            return Placement::Move {
+                to: data_site.clone(),
+            };
+        }
+    }
+    let note = match dataset {
+        DatasetLocation::Efs { .. } => Some(
+            "EFS can only be mounted in its own region; the data must be copied first".to_string(),
+        ),
+        DatasetLocation::RunpodVolume { .. } => Some(
+            "RunPod network volumes only attach to pods; the data must be copied first".to_string(),
+        ),
+        _ => None,
+    };
+    Placement::Remote {
+        data_site: data_site.clone(),
+        estimate: size_bytes.and_then(|bytes| estimate_egress(data_site, planned, bytes)),
+        note,
+    }
+}
+
+/// Normalize a GetBucketLocation constraint to a region name
+fn bucket_region(constraint: Option<&str>) -> String {
+    match constraint {
+        None | Some("") => "us-east-1".to_string(),
+        Some("EU") => "eu-west-1".to_string(),
+        Some(region) => region.to_string(),
+    }
+}
+
+/// Region a bucket lives in
+pub async fn s3_bucket_region(client: &S3Client, bucket: &str) -> Result<String> {
+    let location = client
+        .get_bucket_location()
+        .bucket(bucket)
+        .send()
+        .await
+        .map_err(|e| {
+            TrainctlError::S3(format!(
+                "Failed to get location of bucket {}: {}",
+                bucket, e
+            ))
+        })?;
+    Ok(bucket_region(
+        location.location_constraint().map(|c| c.as_str()),
+    ))
+}
+
+/// S3 client for `region`, built from `aws_config`
+///
+/// The Rust SDK does not follow S3's PermanentRedirect, so objects in a
+/// bucket outside the configured region must be listed with a client for
+/// the bucket's own region.
+pub fn s3_client_for_region(aws_config: &aws_config::SdkConfig, region: &str) -> S3Client {
+    let config = aws_config
+        .to_builder()
+        .region(aws_sdk_s3::config::Region::new(region.to_string()))
+        .build();
+    S3Client::new(&config)
+}
+
+/// Total size of the objects under a prefix
+pub async fn s3_prefix_bytes(client: &S3Client, bucket: &str, prefix: &str) -> Result<u64> {
+    let mut total = 0u64;
+    let mut token: Option<String> = None;
+    loop {
+        let mut request = client.list_objects_v2().bucket(bucket);
+        if !prefix.is_empty() {
+            request = request.prefix(prefix);
+        }
+        if let Some(t) = &token {
+            request = request.continuation_token(t);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| TrainctlError::S3(format!("Failed to list objects: {}", e)))?;
+        total += response
+            .contents()
+            .iter()
+            .map(|o| o.size().unwrap_or(0).max(0) as u64)
+            .sum::<u64>();
+        match response.next_continuation_token() {
+            Some(next) => token = Some(next.to_string()),
+            None => break,
+        }
+    }
+    Ok(total)
+}
+
+/// Resolve the dataset's site and, where it can be listed, its size
+///
+/// `default_region` is used for EFS locations without an explicit region.
+pub async fn resolve(
+    dataset: &DatasetLocation,
+    aws_config: &aws_config::SdkConfig,
+    default_region: &str,
+) -> Result<(Site, Option<u64>)> {
+    match dataset {
+        DatasetLocation::S3 { bucket, prefix } => {
+            let client = S3Client::new(aws_config);
+            let region = s3_bucket_region(&client, bucket).await?;
+            let regional = s3_client_for_region(aws_config, &region);
+            let total = s3_prefix_bytes(&regional, bucket, prefix).await?;
+            Ok((Site::Aws { region }, Some(total)))
+        }
+        DatasetLocation::Efs { region, .. } => Ok((
+            Site::Aws {
+                region: region.clone().unwrap_or_else(|| default_region.to_string()),
+            },
+            None,
+        )),
+        DatasetLocation::RunpodVolume { .. } => Ok((Site::Runpod, None)),
+        DatasetLocation::Local { .. } => Ok((Site::Local, None)),
+    }
+}
+
+pub fn format_estimate(estimate: &EgressEstimate) -> String {
+    format!(
+        "{:.1} GB, ~${:.2} egress, ~{} to transfer",
+        estimate.bytes as f64 / GB,
+        estimate.cost_usd,
+        crate::utils::format_duration(estimate.transfer_secs)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aws(region: &str) -> Site {
+        Site::Aws {
+            region: region.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_locations() {
+        assert_eq!(
+            DatasetLocation::parse("s3://data/imagenet/").unwrap(),
+            DatasetLocation::S3 {
+                bucket: "data".to_string(),
+                prefix: "imagenet/".to_string()
+            }
+        );
+        assert_eq!(
+            DatasetLocation::parse("s3://data").unwrap(),
+            DatasetLocation::S3 {
+                bucket: "data".to_string(),
+                prefix: String::new()
+            }
+        );
+        assert_eq!(
+            DatasetLocation::parse("efs://us-west-2/fs-0abc").unwrap(),
+            DatasetLocation::Efs {
+                file_system_id: "fs-0abc".to_string(),
+                region: Some("us-west-2".to_string())
+            }
+        );
+        assert!(matches!(
+            DatasetLocation::parse("runpod-volume://vol123").unwrap(),
+            DatasetLocation::RunpodVolume { .. }
+        ));
+        assert!(matches!(
+            DatasetLocation::parse("./data").unwrap(),
+            DatasetLocation::Local { .. }
+        ));
+        assert!(DatasetLocation::parse("efs://nope").is_err());
+        assert!(DatasetLocation::parse("gs://bucket/x").is_err());
+        assert!(DatasetLocation::parse("s3://").is_err());
+    }
+
+    #[test]
+    fn test_bucket_region() {
+        assert_eq!(bucket_region(None), "us-east-1");
+        assert_eq!(bucket_region(Some("")), "us-east-1");
+        assert_eq!(bucket_region(Some("EU")), "eu-west-1");
+        assert_eq!(bucket_region(Some("ap-south-1")), "ap-south-1");
+    }
+
+    #[test]
+    fn test_assess_same_region_is_local() {
+        let data = DatasetLocation::parse("s3://data/x").unwrap();
+        assert_eq!(
+            assess(&data, &aws("us-east-1"), &aws("us-east-1"), Some(1), false),
+            Placement::Local
+        );
+    }
+
+    #[test]
+    fn test_assess_moves_unpinned_region() {
+        let data = DatasetLocation::parse("s3://data/x").unwrap();
+        assert_eq!(
+            assess(&data, &aws("us-west-2"), &aws("us-east-1"), None, false),
+            Placement::Move {
+                to: aws("us-west-2")
+            }
+        );
+    }
+
+    #[test]
+    fn test_assess_pinned_region_estimates_egress() {
+        let data = DatasetLocation::parse("s3://data/x").unwrap();
+        let bytes = 100 * 1024 * 1024 * 1024;
+        match assess(
+            &data,
+            &aws("us-west-2"),
+            &aws("us-east-1"),
+            Some(bytes),
+            true,
+        ) {
+            Placement::Remote {
+                estimate: Some(estimate),
+                note: None,
+                ..
+            } => {
+                assert!((estimate.cost_usd - 2.0).abs() < 1e-9);
+                assert_eq!(estimate.transfer_secs, 1024);
+            }
+            other => panic!("unexpected placement: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_assess_cross_provider_is_never_moved() {
+        let data = DatasetLocation::parse("runpod-volume://vol1").unwrap();
+        match assess(&data, &Site::Runpod, &aws("us-east-1"), None, false) {
+            Placement::Remote {
+                estimate: None,
+                note: Some(_),
+                ..
+            } => {}
+            other => panic!("unexpected placement: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_estimate_egress_to_internet() {
+        let estimate =
+            estimate_egress(&aws("us-east-1"), &Site::Runpod, 10 * 1024 * 1024 * 1024).unwrap();
+        assert!((estimate.cost_usd - 0.9).abs() < 1e-9);
+        assert!(estimate_egress(&Site::Runpod, &Site::Runpod, 1).is_none());
+    }
+}
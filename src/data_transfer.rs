@@ -5,14 +5,18 @@
 
 use crate::config::Config;
 use crate::error::{Result, TrainctlError};
+use crate::transfer_engine::{EngineSettings, TransferEngine};
+use crate::transfer_session::{self, SessionHandle, TransferSession};
 use crate::validation as validate;
-use aws_config::{BehaviorVersion, SdkConfig};
+use aws_config::SdkConfig;
 use aws_sdk_s3::Client as S3Client;
 use aws_sdk_ssm::Client as SsmClient;
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::ProgressBar;
 use std::path::{Path, PathBuf};
 use tracing::{info, warn};
 
+pub use crate::transfer_engine::MULTIPART_THRESHOLD;
+
 /// Data transfer source or destination location
 ///
 /// Represents different types of storage locations that can be used as sources
@@ -47,7 +51,7 @@ pub enum DataLocation {
 /// Some fields are reserved for future implementation:
 /// - `compression`: Future support for compressed transfers
 /// - `verify`: Future checksum verification
-/// - `resume`: Future resume capability for interrupted transfers
+/// - `resume`: Resuming is handled by transfer sessions (`transfer_session`)
 /// - `exclude`: Future pattern-based exclusions
 pub struct TransferOptions {
     pub parallel: Option<usize>, // Number of parallel transfers
@@ -59,6 +63,12 @@ pub struct TransferOptions {
     pub resume: bool, // Resume interrupted transfers
     #[allow(dead_code)]
     pub exclude: Vec<String>, // Patterns to exclude
+    /// Part size for multipart transfers (`None`: `[transfer] part_size`)
+    pub part_size: Option<u64>,
+    /// Parts in flight per file (`None`: `[transfer] part_parallel`)
+    pub part_parallel: Option<usize>,
+    /// Bytes per second across the transfer (`None`: `[transfer] bandwidth_limit`)
+    pub bandwidth_limit: Option<u64>,
 }
 
 impl Default for TransferOptions {
@@ -69,12 +79,15 @@ impl Default for TransferOptions {
             verify: true,
             resume: true,
             exclude: vec!["*.pyc".to_string(), "__pycache__".to_string()],
+            part_size: None,
+            part_parallel: None,
+            bandwidth_limit: None,
         }
     }
 }
 
 /// Parse location string into DataLocation
-fn parse_location(loc: &str) -> Result<DataLocation> {
+pub(crate) fn parse_location(loc: &str) -> Result<DataLocation> {
     if loc.starts_with("s3://") {
         validate::validate_s3_path(loc)?;
         Ok(DataLocation::S3(loc.to_string()))
@@ -118,21 +131,77 @@ fn parse_location(loc: &str) -> Result<DataLocation> {
     }
 }
 
+/// What `runctl transfer` was asked to copy, and how
+#[derive(Debug, Clone)]
+pub struct TransferRequest {
+    /// Source location (local path, `s3://bucket/key`, or `instance-id:/path`)
+    pub source: String,
+    /// Destination location (same formats as source)
+    pub destination: String,
+    /// Number of parallel transfers
+    pub parallel: Option<usize>,
+    /// Enable compression during transfer (not yet implemented)
+    pub compress: bool,
+    /// Verify checksums after transfer
+    pub verify: bool,
+    /// Continue an unfinished session for the same source and destination
+    pub resume: bool,
+    /// Skip the egress cost confirmation
+    pub yes: bool,
+    /// Encrypt files client-side before uploading to S3
+    pub encrypt: bool,
+    /// Run the transfer in a background process
+    pub detach: bool,
+    /// Part size for multipart transfers
+    pub part_size: Option<u64>,
+    /// Parts in flight per file
+    pub part_parallel: Option<usize>,
+    /// Cap on throughput, in bytes per second
+    pub bandwidth_limit: Option<u64>,
+    /// Store a SHA-256 manifest with an upload, or check a download against
+    /// it (see [`crate::checksums`])
+    pub manifest: bool,
+}
+
+impl TransferRequest {
+    pub fn new(source: impl Into<String>, destination: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            destination: destination.into(),
+            parallel: None,
+            compress: false,
+            verify: true,
+            resume: true,
+            yes: false,
+            encrypt: false,
+            detach: false,
+            part_size: None,
+            part_parallel: None,
+            bandwidth_limit: None,
+            manifest: false,
+        }
+    }
+}
+
 /// Handle data transfer between different storage locations
 ///
 /// Transfers data between local storage, S3 buckets, and training instances.
 /// Supports parallel transfers, compression, checksum verification, and resumable
-/// operations.
+/// operations. Large files to and from S3 move in parallel parts (see
+/// [`crate::transfer_engine`]).
 ///
-/// # Arguments
+/// Every transfer is recorded as a session (see [`crate::transfer_session`]).
+/// With `resume`, an interrupted or failed session for the same source and
+/// destination is continued instead of starting over. `config_path` is passed
+/// on to the background process of a detached transfer.
 ///
-/// * `source` - Source location (local path, `s3://bucket/key`, or `instance-id:/path`)
-/// * `destination` - Destination location (same formats as source)
-/// * `parallel` - Number of parallel transfers (default: 10)
-/// * `compress` - Enable compression during transfer (not yet implemented)
-/// * `verify` - Verify checksums after transfer (default: true)
-/// * `resume` - Resume interrupted transfers (default: true)
-/// * `config` - Configuration containing AWS and transfer settings
+/// Transfers out of S3 (to a local path, an instance in another region, or
+/// another region's bucket) print an egress estimate first and need
+/// confirmation above `[transfer] confirm_egress_usd`.
+///
+/// With `manifest`, an upload from a local path to S3 stores the SHA-256 of
+/// every file next to the data, and a download from S3 is checked against
+/// the stored manifest once it finishes (see [`crate::checksums`]).
 ///
 /// # Errors
 ///
@@ -143,65 +212,123 @@ fn parse_location(loc: &str) -> Result<DataLocation> {
 /// # Examples
 ///
 /// ```rust,no_run
-/// use runctl::{data_transfer, Config};
+/// use runctl::data_transfer::{self, TransferRequest};
+/// use runctl::Config;
 ///
 /// # async fn example() -> runctl::error::Result<()> {
 /// let config = Config::load(None)?;
 ///
 /// // Transfer from local to S3
-/// data_transfer::handle_transfer(
-///     "./data".to_string(),
-///     "s3://my-bucket/data/".to_string(),
-///     Some(10),
-///     false,
-///     true,
-///     true,
-///     &config
-/// ).await?;
+/// let request = TransferRequest {
+///     parallel: Some(10),
+///     ..TransferRequest::new("./data", "s3://my-bucket/data/")
+/// };
+/// data_transfer::handle_transfer(request, &config, "text", None).await?;
 ///
 /// // Transfer from instance to local
-/// data_transfer::handle_transfer(
-///     "i-123:/mnt/data".to_string(),
-///     "./local_data/".to_string(),
-///     None,
-///     false,
-///     true,
-///     true,
-///     &config
-/// ).await?;
+/// let request = TransferRequest::new("i-123:/mnt/data", "./local_data/");
+/// data_transfer::handle_transfer(request, &config, "text", None).await?;
 /// # Ok(())
 /// # }
 /// ```
 pub async fn handle_transfer(
-    source: String,
-    destination: String,
-    parallel: Option<usize>,
-    compress: bool,
-    verify: bool,
-    resume: bool,
+    request: TransferRequest,
     config: &Config,
+    output_format: &str,
+    config_path: Option<&Path>,
 ) -> Result<()> {
-    let src = parse_location(&source)?;
-    let dst = parse_location(&destination)?;
-
-    let aws_config = aws_config::load_defaults(BehaviorVersion::latest()).await;
-
-    // Create DataTransfer with config reference
-    // Note: DataTransfer needs to own Config, so we clone it
-    let transfer = DataTransfer::new(config.clone(), Some(&aws_config));
+    let src = parse_location(&request.source)?;
+    let dst = parse_location(&request.destination)?;
+    if request.encrypt && !matches!((&src, &dst), (DataLocation::Local(_), DataLocation::S3(_))) {
+        return Err(TrainctlError::Validation {
+            field: "encrypt".to_string(),
+            reason: "--encrypt applies to transfers from a local path to S3".to_string(),
+        });
+    }
+    if request.manifest
+        && !matches!(
+            (&src, &dst),
+            (DataLocation::Local(_), DataLocation::S3(_))
+                | (DataLocation::S3(_), DataLocation::Local(_))
+        )
+    {
+        return Err(TrainctlError::Validation {
+            field: "manifest".to_string(),
+            reason: "--manifest applies to transfers between a local path and S3".to_string(),
+        });
+    }
 
-    let options = TransferOptions {
-        parallel,
-        compression: compress,
-        verify,
-        resume,
-        exclude: vec!["*.pyc".to_string(), "__pycache__".to_string()],
+    let dir = transfer_session::transfers_dir();
+    let resumable = if request.resume {
+        transfer_session::find_resumable(&dir, &request.source, &request.destination)?
+    } else {
+        None
+    };
+    let session = match resumable {
+        Some(session) => {
+            println!(
+                "Resuming transfer session {} ({}/{} files done)",
+                session.id,
+                session.files_done(),
+                session.files.len()
+            );
+            session
+        }
+        None => {
+            let aws_config = crate::aws_utils::shared_sdk_config().await;
+            let description = format!("{} -> {}", request.source, request.destination);
+            let egress = crate::egress::estimate_transfer(&src, &dst, &aws_config)
+                .await
+                .unwrap_or_else(|e| {
+                    warn!("Could not estimate egress for {}: {}", description, e);
+                    None
+                });
+            if let Some(estimate) = &egress {
+                if !crate::egress::confirm_egress(
+                    estimate,
+                    &description,
+                    config,
+                    request.yes,
+                    output_format,
+                )? {
+                    println!("Cancelled");
+                    return Ok(());
+                }
+            }
+            let mut session = TransferSession::new(request.source, request.destination);
+            session.parallel = request.parallel;
+            session.compress = request.compress;
+            session.verify = request.verify;
+            session.encrypt = request.encrypt;
+            session.part_size = request.part_size;
+            session.part_parallel = request.part_parallel;
+            session.bandwidth_limit = request.bandwidth_limit;
+            session.manifest = request.manifest;
+            session.egress = egress;
+            session.save(&dir)?;
+            session
+        }
     };
 
-    transfer.transfer(&src, &dst, options).await?;
+    let id = session.id.clone();
+    if request.detach {
+        let pid = transfer_session::detach(session, config_path)?;
+        println!("Transfer {} running in the background (PID {})", id, pid);
+        println!("   Check on it with: runctl transfer sessions show {}", id);
+        return Ok(());
+    }
 
-    println!("Transfer complete: {} -> {}", source, destination);
-    Ok(())
+    let (source, destination) = (session.source.clone(), session.destination.clone());
+    match transfer_session::run_session(session, config).await {
+        Ok(()) => {
+            println!("Transfer complete: {} -> {}", source, destination);
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("   Resume with: runctl transfer resume {}", id);
+            Err(e)
+        }
+    }
 }
 
 /// Transfer data between locations
@@ -209,6 +336,8 @@ pub struct DataTransfer {
     s3_client: Option<S3Client>,
     ssm_client: Option<SsmClient>,
     config: Config,
+    /// Session recording per-file progress, if the transfer has one
+    session: Option<SessionHandle>,
 }
 
 impl DataTransfer {
@@ -219,9 +348,61 @@ impl DataTransfer {
             s3_client,
             ssm_client,
             config,
+            session: None,
         }
     }
 
+    /// Record per-file progress in `session`, skipping files it already has
+    pub(crate) fn with_session(mut self, session: SessionHandle) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    /// Run `transfer` as one unit of the session, unless an earlier run finished it
+    async fn tracked<F>(&self, name: &str, size: u64, transfer: F) -> Result<()>
+    where
+        F: std::future::Future<Output = Result<()>>,
+    {
+        let Some(session) = &self.session else {
+            return transfer.await;
+        };
+        if session.is_done(name, size) {
+            info!("Skipping {} (already transferred)", name);
+            return Ok(());
+        }
+        session.start_file(name, size);
+        let result = transfer.await;
+        session.finish_file(name, &result);
+        result
+    }
+
+    fn is_resumed(&self) -> bool {
+        self.session.as_ref().is_some_and(|s| s.is_resumed())
+    }
+
+    /// Engine for the S3 side of a transfer: `options`, then `[transfer]`
+    fn engine(&self, client: &S3Client, options: &TransferOptions) -> TransferEngine {
+        let config = EngineSettings::from_config(&self.config);
+        TransferEngine::new(
+            client.clone(),
+            EngineSettings {
+                part_size: options.part_size.or(config.part_size),
+                part_parallel: options.part_parallel.or(config.part_parallel),
+                bandwidth_limit: options.bandwidth_limit.or(config.bandwidth_limit),
+            },
+        )
+    }
+
+    /// s5cmd is used when available, unless the transfer is rate limited
+    fn use_s5cmd(&self, options: &TransferOptions) -> bool {
+        check_s5cmd()
+            && options.parallel.is_some()
+            && options
+                .bandwidth_limit
+                .or(self.config.transfer.bandwidth_limit)
+                .is_none()
+    }
+
     /// Transfer data from source to destination
     pub async fn transfer(
         &self,
@@ -265,16 +446,21 @@ impl DataTransfer {
         let (bucket, key) = parse_s3_path(s3_path)?;
 
         // Use s5cmd for faster parallel uploads if available
-        if check_s5cmd() && options.parallel.is_some() {
-            return self.s5cmd_upload(source, s3_path, options).await;
+        if self.use_s5cmd(&options) {
+            return self
+                .tracked(s3_path, 0, self.s5cmd_upload(source, s3_path, options))
+                .await;
         }
 
         // Fallback to AWS SDK
+        let engine = self.engine(client, &options);
         if source.is_dir() {
-            self.upload_directory(client, source, &bucket, &key, options)
+            self.upload_directory(&engine, source, &bucket, &key, options)
                 .await
         } else {
-            self.upload_file(client, source, &bucket, &key).await
+            let size = std::fs::metadata(source)?.len();
+            self.tracked(&key, size, engine.upload_file(source, &bucket, &key))
+                .await
         }
     }
 
@@ -293,12 +479,16 @@ impl DataTransfer {
         let (bucket, key) = parse_s3_path(s3_path)?;
 
         // Use s5cmd for faster parallel downloads
-        if check_s5cmd() && options.parallel.is_some() {
-            return self.s5cmd_download(s3_path, destination, options).await;
+        if self.use_s5cmd(&options) {
+            let name = destination.display().to_string();
+            return self
+                .tracked(&name, 0, self.s5cmd_download(s3_path, destination, options))
+                .await;
         }
 
         // Fallback to AWS SDK
-        self.download_from_s3(client, &bucket, &key, destination)
+        let engine = self.engine(client, &options);
+        self.download_objects(client, &engine, &bucket, &key, destination, options)
             .await
     }
 
@@ -369,7 +559,13 @@ impl DataTransfer {
             remote_path.display()
         );
 
-        execute_ssm_command(ssm_client, instance_id, &download_cmd).await?;
+        let name = format!("{}:{}", instance_id, remote_path.display());
+        self.tracked(&name, 0, async {
+            execute_ssm_command(ssm_client, instance_id, &download_cmd)
+                .await
+                .map(|_| ())
+        })
+        .await?;
 
         info!(
             "Data transferred to instance {}:{}",
@@ -417,7 +613,13 @@ impl DataTransfer {
             s3_path, remote_path.display(), s3_path, remote_path.display()
         );
 
-        execute_ssm_command(ssm_client, instance_id, &s5cmd_cmd).await?;
+        let name = format!("{}:{}", instance_id, remote_path.display());
+        self.tracked(&name, 0, async {
+            execute_ssm_command(ssm_client, instance_id, &s5cmd_cmd)
+                .await
+                .map(|_| ())
+        })
+        .await?;
 
         info!(
             "Data transferred to instance {}:{}",
@@ -430,7 +632,7 @@ impl DataTransfer {
     /// Upload directory with parallel transfers
     async fn upload_directory(
         &self,
-        client: &S3Client,
+        engine: &TransferEngine,
         source: &Path,
         bucket: &str,
         prefix: &str,
@@ -444,29 +646,56 @@ impl DataTransfer {
             .filter(|e| e.file_type().is_file())
             .collect();
 
-        let pb = ProgressBar::new(files.len() as u64);
-        pb.set_style(
-            ProgressStyle::default_bar()
-                .template(
-                    "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})",
-                )
-                .expect("Progress bar template should be valid"),
+        let pb = engine.progress().add(ProgressBar::new(files.len() as u64));
+        pb.set_style(crate::output_style::bar(
+            "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})",
+        ));
+
+        let total_bytes = files
+            .iter()
+            .filter_map(|e| e.metadata().ok())
+            .map(|m| m.len())
+            .sum();
+        let progress = crate::progress::TransferProgress::new(
+            "transfer.upload",
+            files.len() as u64,
+            total_bytes,
         );
 
         let parallel = options.parallel.unwrap_or(4);
+        let total = files.len();
+        let mut failed = 0;
         let mut handles = Vec::new();
 
         for file in files {
-            let client = client.clone();
+            let engine = engine.clone();
             let bucket = bucket.to_string();
             let source_path = file.path().to_path_buf();
             let relative = source_path.strip_prefix(source).unwrap_or(&source_path);
             let key = format!("{}/{}", prefix, relative.display());
+            let size = file.metadata().map(|m| m.len()).unwrap_or(0);
             let pb = pb.clone();
+            let progress = progress.clone();
+            let session = self.session.clone();
+
+            if session.as_ref().is_some_and(|s| s.is_done(&key, size)) {
+                pb.inc(1);
+                progress.file_done(&key, size);
+                continue;
+            }
 
             let handle = tokio::spawn(async move {
-                let result = upload_single_file(&client, &bucket, &key, &source_path).await;
+                if let Some(session) = &session {
+                    session.start_file(&key, size);
+                }
+                let result = engine.upload_file(&source_path, &bucket, &key).await;
+                if let Some(session) = &session {
+                    session.finish_file(&key, &result);
+                }
                 pb.inc(1);
+                if result.is_ok() {
+                    progress.file_done(&key, size);
+                }
                 result
             });
 
@@ -478,8 +707,14 @@ impl DataTransfer {
                 let (result, _idx, remaining) = futures::future::select_all(handles).await;
                 match result {
                     Ok(Ok(())) => {}
-                    Ok(Err(e)) => warn!("Upload failed: {}", e),
-                    Err(e) => warn!("Task join error: {}", e),
+                    Ok(Err(e)) => {
+                        warn!("Upload failed: {}", e);
+                        failed += 1;
+                    }
+                    Err(e) => {
+                        warn!("Task join error: {}", e);
+                        failed += 1;
+                    }
                 }
                 handles = remaining;
             }
@@ -489,47 +724,142 @@ impl DataTransfer {
         for handle in handles {
             match handle.await {
                 Ok(Ok(())) => {}
-                Ok(Err(e)) => warn!("Upload failed: {}", e),
-                Err(e) => warn!("Task join error: {}", e),
+                Ok(Err(e)) => {
+                    warn!("Upload failed: {}", e);
+                    failed += 1;
+                }
+                Err(e) => {
+                    warn!("Task join error: {}", e);
+                    failed += 1;
+                }
             }
         }
 
         pb.finish_with_message("Upload complete");
+        if failed > 0 {
+            return Err(TrainctlError::DataTransfer(format!(
+                "{} of {} uploads failed",
+                failed, total
+            )));
+        }
         Ok(())
     }
 
-    async fn upload_file(
-        &self,
-        client: &S3Client,
-        source: &Path,
-        bucket: &str,
-        key: &str,
-    ) -> Result<()> {
-        upload_single_file(client, bucket, key, source).await
-    }
-
-    async fn download_from_s3(
+    /// Download an object, or every object under a prefix, into `destination`
+    ///
+    /// A key naming a single object is written to `destination` (or into
+    /// it, when it is a directory). Otherwise objects under `key/` keep their
+    /// paths relative to the prefix.
+    async fn download_objects(
         &self,
         client: &S3Client,
+        engine: &TransferEngine,
         bucket: &str,
         key: &str,
         destination: &Path,
+        options: TransferOptions,
     ) -> Result<()> {
-        let response = client
-            .get_object()
-            .bucket(bucket)
-            .key(key)
-            .send()
-            .await
-            .map_err(|e| TrainctlError::S3(format!("Download failed: {}", e)))?;
+        use futures::stream::{self, StreamExt};
+
+        if !key.is_empty() && !key.ends_with('/') {
+            if let Ok(head) = client.head_object().bucket(bucket).key(key).send().await {
+                let target = crate::checksums::download_target(key, destination);
+                let size = head.content_length().unwrap_or(0).max(0) as u64;
+                let name = target.display().to_string();
+                return self
+                    .tracked(&name, size, engine.download_file(bucket, key, &target))
+                    .await;
+            }
+        }
 
-        let data = response
-            .body
+        let prefix = if key.is_empty() || key.ends_with('/') {
+            key.to_string()
+        } else {
+            format!("{}/", key)
+        };
+        let mut objects = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let mut request = client.list_objects_v2().bucket(bucket).prefix(&prefix);
+            if let Some(t) = &token {
+                request = request.continuation_token(t);
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| TrainctlError::S3(format!("Failed to list objects: {}", e)))?;
+            for object in response.contents() {
+                let Some(relative) = object.key().and_then(|k| k.strip_prefix(prefix.as_str()))
+                else {
+                    continue;
+                };
+                // Skip folder markers
+                if relative.is_empty() || relative.ends_with('/') {
+                    continue;
+                }
+                let size = object.size().unwrap_or(0).max(0) as u64;
+                objects.push((
+                    format!("{}{}", prefix, relative),
+                    destination.join(relative),
+                    size,
+                ));
+            }
+            token = response.next_continuation_token().map(String::from);
+            if token.is_none() {
+                break;
+            }
+        }
+        if objects.is_empty() {
+            return Err(TrainctlError::ResourceNotFound {
+                resource_type: "S3 object".to_string(),
+                resource_id: format!("s3://{}/{}", bucket, key),
+            });
+        }
+
+        let pb = engine
+            .progress()
+            .add(ProgressBar::new(objects.len() as u64));
+        pb.set_style(crate::output_style::bar(
+            "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})",
+        ));
+        let total = objects.len();
+        let total_bytes = objects.iter().map(|(_, _, size)| size).sum();
+        let progress =
+            crate::progress::TransferProgress::new("transfer.download", total as u64, total_bytes);
+
+        let results: Vec<Result<()>> = stream::iter(objects)
+            .map(|(object_key, path, size)| {
+                let pb = pb.clone();
+                let progress = progress.clone();
+                async move {
+                    let name = path.display().to_string();
+                    let result = self
+                        .tracked(
+                            &name,
+                            size,
+                            engine.download_file(bucket, &object_key, &path),
+                        )
+                        .await;
+                    pb.inc(1);
+                    match &result {
+                        Ok(()) => progress.file_done(&name, size),
+                        Err(e) => warn!("Download failed: {}", e),
+                    }
+                    result
+                }
+            })
+            .buffer_unordered(options.parallel.unwrap_or(4).max(1))
             .collect()
-            .await
-            .map_err(|e| TrainctlError::S3(format!("Failed to read response: {}", e)))?;
+            .await;
 
-        std::fs::write(destination, data.into_bytes())?;
+        pb.finish_with_message("Download complete");
+        let failed = results.iter().filter(|r| r.is_err()).count();
+        if failed > 0 {
+            return Err(TrainctlError::DataTransfer(format!(
+                "{} of {} downloads failed",
+                failed, total
+            )));
+        }
         Ok(())
     }
 
@@ -548,6 +878,11 @@ impl DataTransfer {
             cmd.arg("--concurrency").arg(parallel.to_string());
         }
 
+        // A resumed session skips files that already made it across
+        if self.is_resumed() {
+            cmd.arg("--if-size-differ");
+        }
+
         if source.is_dir() {
             cmd.arg("--recursive");
         }
@@ -588,6 +923,10 @@ impl DataTransfer {
             cmd.arg("--concurrency").arg(parallel.to_string());
         }
 
+        if self.is_resumed() {
+            cmd.arg("--if-size-differ");
+        }
+
         cmd.arg("--recursive");
         cmd.arg(s3_path);
         cmd.arg(destination.to_string_lossy().as_ref());
@@ -611,52 +950,44 @@ impl DataTransfer {
     }
 }
 
-async fn upload_single_file(
+/// Upload a file to S3, in resumable parts when it is large
+///
+/// Files under [`MULTIPART_THRESHOLD`] are a single PUT. Larger files use a
+/// multipart upload whose ID and finished parts are recorded under
+/// `state_dir`, so an interrupted upload of the same (unchanged) file
+/// continues where it stopped instead of starting over.
+pub async fn upload_file_resumable(
     client: &S3Client,
     bucket: &str,
     key: &str,
     file_path: &Path,
+    state_dir: &Path,
 ) -> Result<()> {
-    let body = aws_sdk_s3::primitives::ByteStream::from_path(file_path)
-        .await
-        .map_err(|e| {
-            TrainctlError::Io(std::io::Error::other(format!("Failed to read file: {}", e)))
-        })?;
-
-    client
-        .put_object()
-        .bucket(bucket)
-        .key(key)
-        .body(body)
-        .send()
+    TransferEngine::new(client.clone(), EngineSettings::default())
+        .with_upload_state_dir(state_dir)
+        .quiet()
+        .upload_file(file_path, bucket, key)
         .await
-        .map_err(|e| TrainctlError::S3(format!("Upload failed: {}", e)))?;
-
-    Ok(())
 }
 
 // Use shared AWS utilities
 use crate::aws_utils::execute_ssm_command;
+pub use crate::utils::parse_s3_path;
 
-pub fn parse_s3_path(s3_path: &str) -> Result<(String, String)> {
-    if !s3_path.starts_with("s3://") {
-        return Err(TrainctlError::S3(
-            "S3 path must start with s3://".to_string(),
-        ));
-    }
+fn check_s5cmd() -> bool {
+    which::which("s5cmd").is_ok()
+}
 
-    let path = &s3_path[5..];
-    let parts: Vec<&str> = path.splitn(2, '/').collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    if parts.len() != 2 {
-        return Err(TrainctlError::S3(
-            "Invalid S3 path format. Expected s3://bucket/key".to_string(),
-        ));
+    #[test]
+    fn test_parse_s3_path() {
+        assert_eq!(
+            parse_s3_path("s3://bucket/a/b.pt").unwrap(),
+            ("bucket".to_string(), "a/b.pt".to_string())
+        );
+        assert!(parse_s3_path("bucket/a").is_err());
     }
-
-    Ok((parts[0].to_string(), parts[1].to_string()))
-}
-
-fn check_s5cmd() -> bool {
-    which::which("s5cmd").is_ok()
 }
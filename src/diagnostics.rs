@@ -4,7 +4,7 @@
 //! and diagnostic insights for instances and pods.
 
 use crate::aws_utils::execute_ssm_command;
-use crate::error::Result;
+use crate::error::{Result, TrainctlError};
 use aws_sdk_ssm::Client as SsmClient;
 use serde::{Deserialize, Serialize};
 
@@ -15,6 +15,8 @@ const HIGH_GPU_UTILIZATION_THRESHOLD_PERCENT: f64 = 80.0;
 const HIGH_GPU_MEMORY_THRESHOLD_PERCENT: f64 = 80.0;
 const ACTIVE_PROCESS_CPU_THRESHOLD_PERCENT: f64 = 10.0;
 const ACTIVE_PROCESS_MEMORY_THRESHOLD_MB: f64 = 1000.0;
+/// Non-training processes holding at least this much GPU memory are flagged
+const GPU_HOG_THRESHOLD_MB: u64 = 1024;
 
 /// Resource usage information for an instance
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -76,6 +78,12 @@ pub struct ProcessInfo {
     pub memory_mb: f64,
     pub memory_percent: f64,
     pub runtime: String,
+    /// GPU memory held by this process (from nvidia-smi compute apps)
+    pub gpu_memory_mb: Option<u64>,
+    /// Index of the GPU the process is using
+    pub gpu_index: Option<usize>,
+    /// Non-training process holding significant GPU memory (e.g. a stale notebook)
+    pub gpu_hog: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -86,13 +94,163 @@ pub struct NetworkStats {
     pub tx_packets: u64,
 }
 
-/// Check resource usage on an instance via SSM
-pub async fn get_instance_resource_usage(
+/// Process tree rooted at the training process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessTree {
+    /// Root PID (explicit, from training.pid, or the oldest training process)
+    pub root_pid: Option<u32>,
+    pub root: Option<ProcessTreeNode>,
+}
+
+/// One process in a process tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessTreeNode {
+    pub pid: u32,
+    pub ppid: u32,
+    /// `ps` state code (e.g. `S`, `R`, `Z`)
+    pub state: String,
+    pub user: String,
+    pub cpu_percent: f64,
+    pub memory_mb: f64,
+    pub elapsed_secs: u64,
+    pub command: String,
+    /// `main` for the root, `dataloader` for worker copies of the parent, else `child`
+    pub role: String,
+    pub defunct: bool,
+    pub children: Vec<ProcessTreeNode>,
+}
+
+impl ProcessTree {
+    /// All processes below the root, depth-first
+    pub fn descendants(&self) -> Vec<&ProcessTreeNode> {
+        fn walk<'a>(node: &'a ProcessTreeNode, out: &mut Vec<&'a ProcessTreeNode>) {
+            for child in &node.children {
+                out.push(child);
+                walk(child, out);
+            }
+        }
+        let mut out = Vec::new();
+        if let Some(root) = &self.root {
+            walk(root, &mut out);
+        }
+        out
+    }
+
+    /// Descendants that have exited but were never reaped
+    pub fn defunct_workers(&self) -> Vec<&ProcessTreeNode> {
+        self.descendants()
+            .into_iter()
+            .filter(|n| n.defunct)
+            .collect()
+    }
+}
+
+/// Get the process tree rooted at the training process via SSM
+///
+/// If `root_pid` is `None`, the root is read from `training.pid` (current
+/// directory or a project directory under /home or /root), falling back to
+/// the oldest running training process.
+pub async fn get_process_tree(
     ssm_client: &SsmClient,
     instance_id: &str,
-) -> Result<ResourceUsage> {
-    // Collect system metrics via SSM
-    let metrics_cmd = r#"
+    root_pid: Option<u32>,
+) -> Result<ProcessTree> {
+    let tree_cmd = format!(
+        r#"
+ROOT_PID="{}"
+if [ -z "$ROOT_PID" ]; then
+    for f in training.pid /home/*/*/training.pid /root/*/training.pid; do
+        if [ -f "$f" ]; then
+            P=$(cat "$f" 2>/dev/null)
+            if [ -n "$P" ] && ps -p "$P" > /dev/null 2>&1; then ROOT_PID=$P; break; fi
+        fi
+    done
+fi
+if [ -z "$ROOT_PID" ]; then
+    ROOT_PID=$(pgrep -o -f "python.*train|python.*training|python.*main.py" || true)
+fi
+echo "ROOT:$ROOT_PID"
+ps -eo pid=,ppid=,stat=,user=,pcpu=,rss=,etimes=,args=
+"#,
+        root_pid.map(|p| p.to_string()).unwrap_or_default()
+    );
+
+    let output = execute_ssm_command(ssm_client, instance_id, &tree_cmd).await?;
+    Ok(parse_process_tree(&output))
+}
+
+/// Parse `ROOT:<pid>` plus `ps -eo pid,ppid,stat,user,pcpu,rss,etimes,args` output
+fn parse_process_tree(output: &str) -> ProcessTree {
+    let mut root_pid = None;
+    let mut rows = Vec::new();
+
+    for line in output.lines() {
+        if let Some(root) = line.strip_prefix("ROOT:") {
+            root_pid = root.trim().parse::<u32>().ok();
+        } else if let Some(row) = parse_ps_row(line) {
+            rows.push(row);
+        }
+    }
+
+    let root = root_pid.and_then(|pid| build_tree_node(pid, None, &rows, 0));
+    ProcessTree { root_pid, root }
+}
+
+/// Maximum depth rendered below the training process
+const PROCESS_TREE_MAX_DEPTH: usize = 16;
+
+fn build_tree_node(
+    pid: u32,
+    parent_command: Option<&str>,
+    rows: &[ProcessTreeNode],
+    depth: usize,
+) -> Option<ProcessTreeNode> {
+    if depth > PROCESS_TREE_MAX_DEPTH {
+        return None;
+    }
+    let mut node = rows.iter().find(|r| r.pid == pid)?.clone();
+    node.role = match parent_command {
+        None => "main".to_string(),
+        Some(parent) if node.command == parent || node.command.contains("pt_data_worker") => {
+            "dataloader".to_string()
+        }
+        Some(_) => "child".to_string(),
+    };
+    let command = node.command.clone();
+    node.children = rows
+        .iter()
+        .filter(|r| r.ppid == pid && r.pid != pid)
+        .filter_map(|r| build_tree_node(r.pid, Some(&command), rows, depth + 1))
+        .collect();
+    Some(node)
+}
+
+fn parse_ps_row(line: &str) -> Option<ProcessTreeNode> {
+    let mut rest = line.trim_start();
+    let mut fields = Vec::with_capacity(7);
+    for _ in 0..7 {
+        let end = rest.find(char::is_whitespace)?;
+        fields.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    let state = fields[2].to_string();
+    Some(ProcessTreeNode {
+        pid: fields[0].parse().ok()?,
+        ppid: fields[1].parse().ok()?,
+        defunct: state.starts_with('Z'),
+        state,
+        user: fields[3].to_string(),
+        cpu_percent: fields[4].parse().unwrap_or(0.0),
+        memory_mb: fields[5].parse::<f64>().unwrap_or(0.0) / 1024.0,
+        elapsed_secs: fields[6].parse().unwrap_or(0),
+        command: rest.trim_end().to_string(),
+        role: String::new(),
+        children: Vec::new(),
+    })
+}
+
+/// Shell script printing the system metrics parsed by `parse_resource_usage_output`
+const METRICS_SCRIPT: &str = r#"
 #!/bin/bash
 set -e
 
@@ -107,40 +265,71 @@ MEM_AVAIL=$(echo "$MEM_INFO" | grep Mem: | awk '{print $7}')
 MEM_PERCENT=$(echo "$MEM_INFO" | grep Mem: | awk '{printf "%.1f", ($3/$2)*100}')
 
 # Disk usage
-DF_OUTPUT=$(df -h | grep -E '^/dev/|^tmpfs' | awk '{print $1","$2","$3","$4","$5","$6}' | tr '\n' '|')
+DF_OUTPUT=$(df -h | grep -E '^/dev/|^tmpfs' | awk '{print $1","$2","$3","$4","$5","$6}' | tr '\n' ';')
 
-# Top processes by CPU
-TOP_CPU=$(ps aux --sort=-%cpu | head -n 11 | tail -n 10 | awk '{printf "%s:%s:%s:%.1f:%.1f:%.1f:", $2, $1, $11, $3, $4, $10}' | tr '\n' '|')
+# Top processes by CPU: pid:user:cpu:mem%:rss_kb:elapsed_secs:command;...
+PS_FIELDS='{cmd=$7; for(i=8;i<=NF;i++) cmd=cmd" "$i; gsub(/[;|]/," ",cmd); printf "%s:%s:%s:%s:%s:%s:%s;", $1, $2, $3, $4, $5, $6, substr(cmd, 1, 200)}'
+TOP_CPU=$(ps -eo pid=,user=,pcpu=,pmem=,rss=,etimes=,args= --sort=-pcpu | head -n 10 | awk "$PS_FIELDS")
 
 # Top processes by memory
-TOP_MEM=$(ps aux --sort=-%mem | head -n 11 | tail -n 10 | awk '{printf "%s:%s:%s:%.1f:%.1f:%.1f:", $2, $1, $11, $3, $4, $10}' | tr '\n' '|')
+TOP_MEM=$(ps -eo pid=,user=,pcpu=,pmem=,rss=,etimes=,args= --sort=-rss | head -n 10 | awk "$PS_FIELDS")
 
 # GPU info (if nvidia-smi available)
 GPU_INFO=""
 if command -v nvidia-smi &> /dev/null; then
     GPU_COUNT=$(nvidia-smi --list-gpus | wc -l)
-    GPU_INFO="${GPU_COUNT}|"
+    GPU_INFO="${GPU_COUNT};"
     for i in $(seq 0 $((GPU_COUNT-1))); do
         GPU_DETAIL=$(nvidia-smi --id=$i --query-gpu=name,memory.total,memory.used,memory.free,utilization.gpu,temperature.gpu,power.draw --format=csv,noheader,nounits 2>/dev/null || echo "N/A,N/A,N/A,N/A,N/A,N/A,N/A")
-        GPU_INFO="${GPU_INFO}${GPU_DETAIL}|"
+        GPU_INFO="${GPU_INFO}${GPU_DETAIL};"
     done
+    # GPU index -> UUID, and per-process GPU memory: pid,uuid,used_mb,command;...
+    GPU_UUIDS=$(nvidia-smi --query-gpu=index,uuid --format=csv,noheader,nounits 2>/dev/null | tr -d ' ' | tr '\n' ';')
+    GPU_APPS=$(nvidia-smi --query-compute-apps=pid,gpu_uuid,used_memory --format=csv,noheader,nounits 2>/dev/null | tr -d ' ' | while IFS=, read -r APP_PID APP_UUID APP_MEM; do
+        APP_CMD=$(ps -o args= -p "$APP_PID" 2>/dev/null | tr ';|' '  ' | cut -c1-200)
+        printf "%s,%s,%s,%s;" "$APP_PID" "$APP_UUID" "$APP_MEM" "$APP_CMD"
+    done)
 else
-    GPU_INFO="0|"
+    GPU_INFO="0;"
 fi
 
 # Network stats
 NET_STATS=$(cat /proc/net/dev | grep -E 'eth0|ens5' | awk '{print $2","$10","$3","$11}' || echo "0,0,0,0")
 
 # Output JSON-like structure
-echo "CPU:$CPU|MEM_TOTAL:$MEM_TOTAL|MEM_USED:$MEM_USED|MEM_AVAIL:$MEM_AVAIL|MEM_PERCENT:$MEM_PERCENT|DF:$DF_OUTPUT|TOP_CPU:$TOP_CPU|TOP_MEM:$TOP_MEM|GPU:$GPU_INFO|NET:$NET_STATS"
+echo "CPU:$CPU|MEM_TOTAL:$MEM_TOTAL|MEM_USED:$MEM_USED|MEM_AVAIL:$MEM_AVAIL|MEM_PERCENT:$MEM_PERCENT|DF:$DF_OUTPUT|TOP_CPU:$TOP_CPU|TOP_MEM:$TOP_MEM|GPU:$GPU_INFO|GPU_UUIDS:$GPU_UUIDS|GPU_APPS:$GPU_APPS|NET:$NET_STATS"
 "#;
 
-    let output = execute_ssm_command(ssm_client, instance_id, metrics_cmd).await?;
+/// Check resource usage on an instance via SSM
+pub async fn get_instance_resource_usage(
+    ssm_client: &SsmClient,
+    instance_id: &str,
+) -> Result<ResourceUsage> {
+    let output = execute_ssm_command(ssm_client, instance_id, METRICS_SCRIPT).await?;
 
     // Parse output
     parse_resource_usage_output(instance_id, &output)
 }
 
+/// Check resource usage of the machine this runs on (Linux)
+///
+/// Used by `runctl agent` on the instance; same script and fields as the
+/// SSM version.
+pub async fn local_resource_usage(instance_id: &str) -> Result<ResourceUsage> {
+    let output = tokio::process::Command::new("bash")
+        .arg("-c")
+        .arg(METRICS_SCRIPT)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(TrainctlError::Io(std::io::Error::other(format!(
+            "metrics script failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))));
+    }
+    parse_resource_usage_output(instance_id, &String::from_utf8_lossy(&output.stdout))
+}
+
 fn parse_resource_usage_output(instance_id: &str, output: &str) -> Result<ResourceUsage> {
     let mut cpu_percent = 0.0;
     let mut memory_total_gb = 0.0;
@@ -149,6 +338,8 @@ fn parse_resource_usage_output(instance_id: &str, output: &str) -> Result<Resour
     let mut disk_usage = Vec::new();
     let mut top_processes = Vec::new();
     let mut gpu_info = None;
+    let mut gpu_uuids = "";
+    let mut gpu_apps = "";
     let mut network_stats = None;
 
     // Parse output line by line
@@ -185,6 +376,10 @@ fn parse_resource_usage_output(instance_id: &str, output: &str) -> Result<Resour
                 } else if part.starts_with("TOP_CPU:") {
                     let top_data = part.strip_prefix("TOP_CPU:").unwrap_or("");
                     top_processes = parse_top_processes(top_data);
+                } else if let Some(data) = part.strip_prefix("GPU_UUIDS:") {
+                    gpu_uuids = data;
+                } else if let Some(data) = part.strip_prefix("GPU_APPS:") {
+                    gpu_apps = data;
                 } else if part.starts_with("GPU:") {
                     let gpu_data = part.strip_prefix("GPU:").unwrap_or("");
                     gpu_info = parse_gpu_info(gpu_data);
@@ -196,6 +391,12 @@ fn parse_resource_usage_output(instance_id: &str, output: &str) -> Result<Resour
         }
     }
 
+    attribute_gpu_processes(
+        &mut top_processes,
+        gpu_info.as_mut(),
+        &parse_gpu_apps(gpu_uuids, gpu_apps),
+    );
+
     Ok(ResourceUsage {
         instance_id: instance_id.to_string(),
         cpu_percent,
@@ -212,7 +413,7 @@ fn parse_resource_usage_output(instance_id: &str, output: &str) -> Result<Resour
 
 fn parse_disk_usage(data: &str) -> Vec<DiskUsage> {
     let mut disks = Vec::new();
-    for entry in data.split('|') {
+    for entry in data.split(';') {
         if entry.is_empty() {
             continue;
         }
@@ -267,44 +468,144 @@ fn parse_size_gb(size_str: &str) -> Result<f64> {
 
 fn parse_top_processes(data: &str) -> Vec<ProcessInfo> {
     let mut processes = Vec::new();
-    for entry in data.split('|') {
+    for entry in data.split(';') {
         if entry.is_empty() {
             continue;
         }
-        let parts: Vec<&str> = entry.split(':').collect();
-        // Explicitly check bounds before accessing
-        if parts.len() >= 6 {
-            // Use get() for safe access (first() is more idiomatic than get(0))
-            let pid_str = parts.first().copied().unwrap_or("0");
-            let user = parts.get(1).copied().unwrap_or("unknown");
-            let command = parts.get(2).copied().unwrap_or("");
-            let cpu_str = parts.get(3).copied().unwrap_or("0");
-            let mem_str = parts.get(4).copied().unwrap_or("0");
-            let runtime_str = parts.get(5).copied().unwrap_or("0");
-
-            if let (Ok(pid), Ok(cpu), Ok(mem), Ok(runtime)) = (
-                pid_str.parse::<u32>(),
-                cpu_str.parse::<f64>(),
-                mem_str.parse::<f64>(),
-                runtime_str.parse::<f64>(),
-            ) {
+        // pid:user:cpu:mem%:rss_kb:elapsed_secs:command (command may contain ':')
+        let parts: Vec<&str> = entry.splitn(7, ':').collect();
+        if parts.len() < 7 {
+            continue;
+        }
+        if let (Ok(pid), Ok(cpu), Ok(mem_percent), Ok(rss_kb), Ok(elapsed)) = (
+            parts[0].trim().parse::<u32>(),
+            parts[2].trim().parse::<f64>(),
+            parts[3].trim().parse::<f64>(),
+            parts[4].trim().parse::<f64>(),
+            parts[5].trim().parse::<u64>(),
+        ) {
+            processes.push(ProcessInfo {
+                pid,
+                user: parts[1].to_string(),
+                command: parts[6].trim().to_string(),
+                cpu_percent: cpu,
+                memory_mb: rss_kb / 1024.0,
+                memory_percent: mem_percent,
+                runtime: format_elapsed(elapsed),
+                gpu_memory_mb: None,
+                gpu_index: None,
+                gpu_hog: false,
+            });
+        }
+    }
+    processes
+}
+
+fn format_elapsed(secs: u64) -> String {
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Parse nvidia-smi compute apps (`pid,uuid,used_mb,command;...`), resolving
+/// GPU UUIDs to indices via `index,uuid;...`
+fn parse_gpu_apps(uuids: &str, apps: &str) -> Vec<(usize, GpuProcess)> {
+    let index_by_uuid: std::collections::HashMap<&str, usize> = uuids
+        .split(';')
+        .filter_map(|entry| {
+            let (index, uuid) = entry.split_once(',')?;
+            Some((uuid.trim(), index.trim().parse().ok()?))
+        })
+        .collect();
+
+    apps.split(';')
+        .filter_map(|entry| {
+            let fields: Vec<&str> = entry.splitn(4, ',').collect();
+            if fields.len() < 3 {
+                return None;
+            }
+            let index = *index_by_uuid.get(fields[1].trim())?;
+            Some((
+                index,
+                GpuProcess {
+                    pid: fields[0].trim().parse().ok()?,
+                    name: fields
+                        .get(3)
+                        .map(|c| c.trim().to_string())
+                        .unwrap_or_default(),
+                    memory_used_mb: fields[2].trim().parse().ok()?,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Whether a command line looks like a training job (vs. notebooks, shells, etc.)
+fn is_training_process(command: &str) -> bool {
+    [
+        "train",
+        "main.py",
+        "torchrun",
+        "accelerate",
+        "deepspeed",
+        "pt_data_worker",
+    ]
+    .iter()
+    .any(|marker| command.contains(marker))
+}
+
+/// Join per-process GPU memory into the process list and flag GPU hogs
+///
+/// GPU processes missing from the top-N list (often idle notebooks holding
+/// memory without using CPU) are appended so they stay visible.
+fn attribute_gpu_processes(
+    processes: &mut Vec<ProcessInfo>,
+    mut gpu_info: Option<&mut GpuInfo>,
+    gpu_apps: &[(usize, GpuProcess)],
+) {
+    for (index, app) in gpu_apps {
+        if let Some(gpu) = gpu_info
+            .as_deref_mut()
+            .and_then(|g| g.gpus.iter_mut().find(|d| d.index == *index))
+        {
+            gpu.processes.push(app.clone());
+        }
+
+        let pos = match processes.iter().position(|p| p.pid == app.pid) {
+            Some(pos) => pos,
+            None => {
                 processes.push(ProcessInfo {
-                    pid,
-                    user: user.to_string(),
-                    command: command.to_string(),
-                    cpu_percent: cpu,
-                    memory_mb: mem,
-                    memory_percent: mem,
-                    runtime: format!("{:.1}s", runtime),
+                    pid: app.pid,
+                    user: String::new(),
+                    command: app.name.clone(),
+                    cpu_percent: 0.0,
+                    memory_mb: 0.0,
+                    memory_percent: 0.0,
+                    runtime: String::new(),
+                    gpu_memory_mb: None,
+                    gpu_index: None,
+                    gpu_hog: false,
                 });
+                processes.len() - 1
             }
+        };
+        let proc = &mut processes[pos];
+        // A process on several GPUs reports its largest allocation
+        if proc.gpu_memory_mb.unwrap_or(0) < app.memory_used_mb {
+            proc.gpu_memory_mb = Some(app.memory_used_mb);
+            proc.gpu_index = Some(*index);
         }
+        proc.gpu_hog = proc.gpu_memory_mb.unwrap_or(0) >= GPU_HOG_THRESHOLD_MB
+            && !is_training_process(&proc.command);
     }
-    processes
 }
 
 fn parse_gpu_info(data: &str) -> Option<GpuInfo> {
-    let parts: Vec<&str> = data.split('|').collect();
+    let parts: Vec<&str> = data.split(';').collect();
     if parts.is_empty() || parts[0] == "0" {
         return None;
     }
@@ -321,12 +622,11 @@ fn parse_gpu_info(data: &str) -> Option<GpuInfo> {
         }
         let fields: Vec<&str> = part.split(',').collect();
         if fields.len() >= 7 {
-            if let (Ok(mem_total), Ok(mem_used), Ok(util), Ok(temp), Ok(power)) = (
+            // Temperature and power read "[N/A]" on some GPUs
+            if let (Ok(mem_total), Ok(mem_used), Ok(util)) = (
                 fields[1].trim().parse::<u64>(),
                 fields[2].trim().parse::<u64>(),
                 fields[4].trim().parse::<f64>(),
-                fields[5].trim().parse::<u32>(),
-                fields[6].trim().parse::<f64>(),
             ) {
                 let mem_percent = if mem_total > 0 {
                     (mem_used as f64 / mem_total as f64) * 100.0
@@ -341,9 +641,9 @@ fn parse_gpu_info(data: &str) -> Option<GpuInfo> {
                     memory_used_mb: mem_used,
                     memory_percent: mem_percent,
                     utilization_percent: util,
-                    temperature_c: Some(temp),
-                    power_draw_w: Some(power),
-                    processes: Vec::new(), // Would need separate nvidia-smi query
+                    temperature_c: fields[5].trim().parse::<u32>().ok(),
+                    power_draw_w: fields[6].trim().parse::<f64>().ok(),
+                    processes: Vec::new(), // Filled by attribute_gpu_processes
                 });
             }
         }
@@ -456,3 +756,80 @@ pub async fn check_high_resource_usage(
         Ok(Some(warnings.join("\n")))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PS_OUTPUT: &str = "ROOT:100
+    1     0 Ss   root      0.0  1024 90000 /sbin/init
+  100     1 Sl   ubuntu   95.0 2097152 3600 python train.py --epochs 10
+  101   100 Sl   ubuntu   40.0 524288 3500 python train.py --epochs 10
+  102   100 Z    ubuntu    0.0     0 3500 [python] <defunct>
+  103   100 S    ubuntu    1.0  10240 3500 tee train.log
+  200     1 S    ubuntu    0.0  2048 100 bash
+";
+
+    #[test]
+    fn test_parse_process_tree() {
+        let tree = parse_process_tree(PS_OUTPUT);
+        assert_eq!(tree.root_pid, Some(100));
+        let root = tree.root.as_ref().unwrap();
+        assert_eq!(root.role, "main");
+        assert_eq!(root.command, "python train.py --epochs 10");
+        assert_eq!(root.memory_mb, 2048.0);
+        assert_eq!(root.children.len(), 3);
+
+        let worker = root.children.iter().find(|c| c.pid == 101).unwrap();
+        assert_eq!(worker.role, "dataloader");
+        let tee = root.children.iter().find(|c| c.pid == 103).unwrap();
+        assert_eq!(tee.role, "child");
+
+        assert_eq!(tree.descendants().len(), 3);
+        let defunct = tree.defunct_workers();
+        assert_eq!(defunct.len(), 1);
+        assert_eq!(defunct[0].pid, 102);
+    }
+
+    #[test]
+    fn test_parse_resource_usage_with_gpu_processes() {
+        let output = "CPU:55.0|MEM_TOTAL:64|MEM_USED:20|MEM_AVAIL:44|MEM_PERCENT:31.2|\
+DF:/dev/root,97G,40G,57G,42%,/;|\
+TOP_CPU:100:ubuntu:95.0:3.1:2097152:3700:python train.py --lr 1e-4;\
+200:root:0.5:0.1:10240:90:/usr/bin/amazon-ssm-agent;|TOP_MEM:|\
+GPU:2;NVIDIA A10G,23028,20000,3028,98,60,180.5;NVIDIA A10G,23028,6000,17028,0,35,[N/A];|\
+GPU_UUIDS:0,GPU-aaa;1,GPU-bbb;|\
+GPU_APPS:100,GPU-aaa,19800,python train.py --lr 1e-4;300,GPU-bbb,5900,/opt/conda/bin/python -m ipykernel_launcher -f kernel.json;|\
+NET:1,2,3,4";
+        let usage = parse_resource_usage_output("i-123", output).unwrap();
+
+        assert_eq!(usage.disk_usage.len(), 1);
+        let gpus = usage.gpu_info.as_ref().unwrap();
+        assert_eq!(gpus.gpus.len(), 2);
+        assert_eq!(gpus.gpus[1].power_draw_w, None);
+        assert_eq!(gpus.gpus[0].processes.len(), 1);
+
+        let trainer = usage.top_processes.iter().find(|p| p.pid == 100).unwrap();
+        assert_eq!(trainer.command, "python train.py --lr 1e-4");
+        assert_eq!(trainer.memory_mb, 2048.0);
+        assert_eq!(trainer.gpu_index, Some(0));
+        assert_eq!(trainer.gpu_memory_mb, Some(19800));
+        assert!(!trainer.gpu_hog);
+
+        // Idle notebook not in the top-CPU list is appended and flagged
+        let notebook = usage.top_processes.iter().find(|p| p.pid == 300).unwrap();
+        assert_eq!(notebook.gpu_index, Some(1));
+        assert!(notebook.gpu_hog);
+
+        let agent = usage.top_processes.iter().find(|p| p.pid == 200).unwrap();
+        assert_eq!(agent.gpu_memory_mb, None);
+    }
+
+    #[test]
+    fn test_parse_process_tree_without_root() {
+        let tree = parse_process_tree("ROOT:\n    1     0 Ss   root 0.0 1024 90000 /sbin/init\n");
+        assert!(tree.root_pid.is_none());
+        assert!(tree.root.is_none());
+        assert!(tree.descendants().is_empty());
+    }
+}
@@ -4,6 +4,7 @@
 //! Supports AWS ECR for image storage and retrieval.
 
 use crate::error::{Result, TrainctlError};
+use crate::utils::shell_quote;
 use aws_config::SdkConfig;
 use aws_sdk_ecr::Client as EcrClient;
 use std::path::{Path, PathBuf};
@@ -290,7 +291,7 @@ pub async fn push_to_ecr(
 }
 
 /// Get AWS account ID
-async fn get_account_id(aws_config: &SdkConfig) -> Result<String> {
+pub(crate) async fn get_account_id(aws_config: &SdkConfig) -> Result<String> {
     use aws_sdk_sts::Client as StsClient;
 
     let sts_client = StsClient::new(aws_config);
@@ -307,7 +308,10 @@ async fn get_account_id(aws_config: &SdkConfig) -> Result<String> {
 }
 
 /// Create or get ECR repository
-async fn ensure_ecr_repository(ecr_client: &EcrClient, repository_name: &str) -> Result<()> {
+pub(crate) async fn ensure_ecr_repository(
+    ecr_client: &EcrClient,
+    repository_name: &str,
+) -> Result<()> {
     // Try to describe repository (will fail if doesn't exist)
     match ecr_client
         .describe_repositories()
@@ -471,7 +475,7 @@ pub async fn run_training_in_container(
                         mounts.len()
                     );
                     warn!(
-                        "⚠️  EBS Volume Safety: {} EBS volume(s) detected and will be mounted in container. \
+                        "EBS Volume Safety: {} EBS volume(s) detected and will be mounted in container. \
                          Ensure these volumes are NOT attached to other instances. \
                          Concurrent write access from multiple instances will cause filesystem corruption.",
                         mounts.len()
@@ -484,7 +488,7 @@ pub async fn run_training_in_container(
                         volume_mounts
                             .push_str(&format!("    -v {}:{} \\\n", host_path, container_path));
                     }
-                    println!("   ⚠️  WARNING: EBS volumes mounted in container. Ensure volumes are not shared with other instances.");
+                    println!("   WARNING: EBS volumes mounted in container. Ensure volumes are not shared with other instances.");
                 } else {
                     info!("No EBS volumes detected on instance {}", instance_id);
                 }
@@ -526,6 +530,102 @@ docker run --rm \
     Ok(())
 }
 
+/// Name of the training container, so a leftover one can be replaced
+pub const TRAINING_CONTAINER: &str = "runctl-train";
+
+/// Printed by [`prepare_image_command`] once the image is pulled
+pub const IMAGE_READY_MARKER: &str = "RUNCTL_IMAGE_READY";
+
+/// Registry host and region of an ECR image reference
+///
+/// `123456789012.dkr.ecr.us-east-1.amazonaws.com/train:latest` gives
+/// `("123456789012.dkr.ecr.us-east-1.amazonaws.com", "us-east-1")`; other
+/// registries give `None`.
+pub fn ecr_registry(image: &str) -> Option<(&str, &str)> {
+    let (host, _) = image.split_once('/')?;
+    let mut parts = host.split('.');
+    let (_account, dkr, ecr, region) = (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+    if dkr != "dkr" || ecr != "ecr" || !host.ends_with(".amazonaws.com") {
+        return None;
+    }
+    Some((host, region))
+}
+
+/// Shell that makes `image` runnable on the instance
+///
+/// Installs Docker if the AMI lacks it (Deep Learning AMIs ship it), refuses
+/// GPU instances without the NVIDIA Container Toolkit, logs in to ECR with the
+/// instance profile's credentials, and pulls the image. Other registries must
+/// allow anonymous pulls or already be logged in.
+pub fn prepare_image_command(image: &str) -> String {
+    let login = ecr_registry(image)
+        .map(|(host, region)| {
+            format!(
+                "aws ecr get-login-password --region {} | $SUDO docker login --username AWS --password-stdin {}\n",
+                region, host
+            )
+        })
+        .unwrap_or_default();
+    format!(
+        r#"set -e
+SUDO=""
+[ "$(id -u)" -eq 0 ] || SUDO="sudo -n"
+if ! command -v docker >/dev/null 2>&1; then
+    echo 'Docker not found, installing...'
+    curl -fsSL https://get.docker.com | $SUDO sh
+fi
+$SUDO systemctl start docker 2>/dev/null || true
+if command -v nvidia-smi >/dev/null 2>&1 && ! command -v nvidia-container-cli >/dev/null 2>&1 && ! command -v nvidia-ctk >/dev/null 2>&1; then
+    echo 'ERROR: GPU instance without the NVIDIA Container Toolkit; use a Deep Learning AMI or install nvidia-container-toolkit' >&2
+    exit 1
+fi
+{login}$SUDO docker pull {image}
+echo {marker}
+"#,
+        login = login,
+        image = shell_quote(image),
+        marker = IMAGE_READY_MARKER,
+    )
+}
+
+/// `docker run` for the training script, run in place of `python3 <script>`
+///
+/// `command` runs in the image with the project directory mounted at
+/// `/workspace` (the working directory), and with data volumes mounted on the
+/// host at `/mnt/data`, `/mnt/checkpoints`, `/data` or `/checkpoints` at the
+/// same path. GPUs are passed through when the host has them. `env` names are
+/// forwarded from the calling shell, which exports them. Output goes to the
+/// caller, so it lands in `training.log`, and the exit code is the script's.
+pub fn container_run_command(
+    image: &str,
+    project_dir: &str,
+    command: &str,
+    env: &[(String, String)],
+) -> String {
+    let env_flags: String = env
+        .iter()
+        .map(|(name, _)| format!(" -e {}", name))
+        .collect();
+    let script = format!(
+        r#"SUDO=""
+[ "$(id -u)" -eq 0 ] || SUDO="sudo -n"
+GPUS=""
+command -v nvidia-smi >/dev/null 2>&1 && GPUS="--gpus all"
+MOUNTS=""
+for m in /mnt/data /mnt/checkpoints /data /checkpoints; do
+    mountpoint -q "$m" 2>/dev/null && MOUNTS="$MOUNTS -v $m:$m"
+done
+$SUDO docker rm -f {name} >/dev/null 2>&1
+exec $SUDO docker run --rm --name {name} --ipc=host $GPUS $MOUNTS -v {dir}:/workspace -w /workspace{env} {image} {command}"#,
+        name = TRAINING_CONTAINER,
+        dir = shell_quote(project_dir),
+        env = env_flags,
+        image = shell_quote(image),
+        command = command,
+    );
+    format!("bash -c {}", shell_quote(&script))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -658,4 +758,44 @@ mod tests {
         let result = detect_dockerfile(temp_dir.path());
         assert_eq!(result, None);
     }
+
+    #[test]
+    fn test_ecr_registry() {
+        assert_eq!(
+            ecr_registry("123456789012.dkr.ecr.us-east-1.amazonaws.com/train:latest"),
+            Some(("123456789012.dkr.ecr.us-east-1.amazonaws.com", "us-east-1"))
+        );
+        assert_eq!(ecr_registry("my-registry.example.com/train:latest"), None);
+        assert_eq!(ecr_registry("pytorch/pytorch:latest"), None);
+        assert_eq!(ecr_registry("ubuntu"), None);
+    }
+
+    #[test]
+    fn test_prepare_image_command_logs_in_to_ecr_only() {
+        let ecr = prepare_image_command("123456789012.dkr.ecr.eu-west-1.amazonaws.com/train:v2");
+        assert!(ecr.contains("aws ecr get-login-password --region eu-west-1"));
+        assert!(ecr.contains("docker pull '123456789012.dkr.ecr.eu-west-1.amazonaws.com/train:v2'"));
+        assert!(ecr.trim_end().ends_with(IMAGE_READY_MARKER));
+
+        let public = prepare_image_command("pytorch/pytorch:latest");
+        assert!(!public.contains("docker login"));
+        assert!(public.contains("docker pull 'pytorch/pytorch:latest'"));
+    }
+
+    #[test]
+    fn test_container_run_command() {
+        let cmd = container_run_command(
+            "my-registry/train:latest",
+            "/home/ubuntu/demo",
+            "python3 /workspace/train.py '--epochs' '3'",
+            &[("WANDB_MODE".to_string(), "offline".to_string())],
+        );
+        assert!(cmd.starts_with("bash -c '"));
+        assert!(cmd.contains("exec $SUDO docker run --rm --name runctl-train"));
+        assert!(cmd.contains("-w /workspace -e WANDB_MODE"));
+        assert!(cmd.contains("train.py"));
+        assert!(cmd.contains("--gpus all"));
+        // Values come from the exported environment, not the command line
+        assert!(!cmd.contains("offline"));
+    }
 }
@@ -9,7 +9,6 @@ use crate::aws_utils::{
 };
 use crate::config::Config;
 use crate::error::{Result, TrainctlError};
-use aws_config::BehaviorVersion;
 use aws_sdk_ec2::types::VolumeType;
 use aws_sdk_ec2::Client as Ec2Client;
 use aws_sdk_ssm::Client as SsmClient;
@@ -20,8 +19,8 @@ use tracing::info;
 pub enum EbsCommands {
     /// Create a new EBS volume
     Create {
-        /// Volume size in GB
-        #[arg(long)]
+        /// Volume size, e.g. 500 (GiB), 500GB or 1TiB
+        #[arg(long, value_parser = crate::units::parse_gib)]
         size: i32,
         /// Volume type (gp3, gp2, io2, st1, sc1)
         #[arg(long, default_value = "gp3")]
@@ -121,8 +120,8 @@ pub enum EbsCommands {
     Restore {
         /// Snapshot ID
         snapshot_id: String,
-        /// Volume size in GB (defaults to snapshot size)
-        #[arg(long)]
+        /// Volume size, e.g. 500 (GiB) or 1TiB (defaults to snapshot size)
+        #[arg(long, value_parser = crate::units::parse_gib)]
         size: Option<i32>,
         /// Volume type
         #[arg(long, default_value = "gp3")]
@@ -137,7 +136,7 @@ pub enum EbsCommands {
 }
 
 pub async fn handle_command(cmd: EbsCommands, config: &Config, output_format: &str) -> Result<()> {
-    let aws_config = aws_config::load_defaults(BehaviorVersion::latest()).await;
+    let aws_config = crate::aws_utils::shared_sdk_config().await;
     let client = Ec2Client::new(&aws_config);
     let ssm_client = SsmClient::new(&aws_config);
 
@@ -855,14 +854,21 @@ async fn pre_warm_volume(
     let mount_and_sync_cmd = format!(
         r#"
 set -e
-# Detect device (could be /dev/sdf, /dev/nvme1n1, etc.)
+# Nitro instances expose EBS volumes as NVMe disks whose serial is the volume
+# ID without the dash; match on it so other NVMe volumes are never picked
 DEVICE=""
-for dev in /dev/nvme1n1 /dev/xvdf /dev/sdf; do
-    if [ -b "$dev" ] && [ "$(lsblk -o MOUNTPOINT -n $dev)" = "" ]; then
-        DEVICE="$dev"
-        break
-    fi
+for dev in $(lsblk -dno NAME,SERIAL | awk '$2 == "{serial}" {{print $1}}'); do
+    DEVICE="/dev/$dev"
 done
+# Xen instances use the attachment name directly
+if [ -z "$DEVICE" ]; then
+    for dev in {device} /dev/xvdf; do
+        if [ -b "$dev" ] && [ "$(lsblk -o MOUNTPOINT -n $dev)" = "" ]; then
+            DEVICE="$dev"
+            break
+        fi
+    done
+fi
 
 if [ -z "$DEVICE" ]; then
     echo "ERROR: Could not find unmounted device"
@@ -895,7 +901,9 @@ echo "Sync complete. Data size:"
 du -sh {mount}
 "#,
         mount = final_mount_point,
-        s3 = s3_source
+        s3 = s3_source,
+        serial = nvme_serial(&volume_id),
+        device = device_name,
     );
 
     execute_ssm_command(ssm_client, &temp_instance_id, &mount_and_sync_cmd).await?;
@@ -1199,3 +1207,19 @@ async fn restore_from_snapshot(
 
     Ok(())
 }
+
+/// Serial number a Nitro instance reports for an attached EBS volume
+/// (`vol-0abc...` becomes `vol0abc...`)
+fn nvme_serial(volume_id: &str) -> String {
+    volume_id.replacen('-', "", 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nvme_serial_drops_dash() {
+        assert_eq!(nvme_serial("vol-0123456789abcdef0"), "vol0123456789abcdef0");
+    }
+}
@@ -254,5 +254,8 @@ Recommendations:
   • General purpose: gp3 (default)
   • Archive: sc1 or gp3 (if random access needed)
 "
-    .to_string()
+    .replace(
+        '•',
+        crate::output_style::glyph(crate::output_style::Glyph::Bullet),
+    )
 }
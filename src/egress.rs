@@ -0,0 +1,139 @@
+//! Egress cost checks for data transfers
+//!
+//! Data leaving AWS is billed per GB, both to the internet (downloads to a
+//! workstation or another provider) and between regions. Before such a
+//! transfer starts, runctl sizes the source, estimates the charge with the
+//! rates in `data_locality`, and asks for confirmation when it exceeds
+//! `[transfer] confirm_egress_usd`. Completed transfers are recorded in the
+//! cost history (see `cost_history`).
+
+use crate::config::Config;
+use crate::cost_history::{self, CostRecord};
+use crate::data_locality::{
+    estimate_egress, format_estimate, s3_bucket_region, s3_client_for_region, s3_prefix_bytes,
+    EgressEstimate, Site,
+};
+use crate::data_transfer::{parse_s3_path, DataLocation};
+use crate::error::{Result, TrainctlError};
+use aws_sdk_s3::Client as S3Client;
+use tracing::warn;
+
+/// Size of an S3 object, or of everything under a prefix when `recursive`
+pub async fn s3_source_bytes(
+    client: &S3Client,
+    bucket: &str,
+    key: &str,
+    recursive: bool,
+) -> Result<u64> {
+    if recursive || key.is_empty() || key.ends_with('/') {
+        return s3_prefix_bytes(client, bucket, key).await;
+    }
+    let head = client
+        .head_object()
+        .bucket(bucket)
+        .key(key)
+        .send()
+        .await
+        .map_err(|e| TrainctlError::S3(format!("Failed to read s3://{}/{}: {}", bucket, key, e)))?;
+    Ok(head.content_length().unwrap_or(0).max(0) as u64)
+}
+
+/// Estimate the egress charge of a transfer; `None` when it is free
+///
+/// Uploads are ingress and free. Instances are assumed to run in the SDK's
+/// configured region.
+pub async fn estimate_transfer(
+    source: &DataLocation,
+    destination: &DataLocation,
+    aws_config: &aws_config::SdkConfig,
+) -> Result<Option<EgressEstimate>> {
+    let DataLocation::S3(uri) = source else {
+        return Ok(None);
+    };
+    let client = S3Client::new(aws_config);
+    let (bucket, key) = parse_s3_path(uri)?;
+    let source_region = s3_bucket_region(&client, &bucket).await?;
+    let from = Site::Aws {
+        region: source_region.clone(),
+    };
+    let to = match destination {
+        DataLocation::Local(_) => Site::Local,
+        DataLocation::TrainingInstance(..) => Site::Aws {
+            region: aws_config
+                .region()
+                .map(|r| r.to_string())
+                .unwrap_or_else(|| "us-east-1".to_string()),
+        },
+        DataLocation::S3(dest) => {
+            let (dest_bucket, _) = parse_s3_path(dest)?;
+            Site::Aws {
+                region: s3_bucket_region(&client, &dest_bucket).await?,
+            }
+        }
+    };
+    if from == to {
+        return Ok(None);
+    }
+    let regional = s3_client_for_region(aws_config, &source_region);
+    let bytes = s3_source_bytes(&regional, &bucket, &key, true).await?;
+    Ok(estimate_egress(&from, &to, bytes).filter(|e| e.cost_usd > 0.0))
+}
+
+/// Estimate the egress charge of downloading from S3 to this machine
+pub async fn estimate_download(
+    aws_config: &aws_config::SdkConfig,
+    bucket: &str,
+    key: &str,
+    recursive: bool,
+) -> Result<Option<EgressEstimate>> {
+    let region = s3_bucket_region(&S3Client::new(aws_config), bucket).await?;
+    let client = s3_client_for_region(aws_config, &region);
+    let bytes = s3_source_bytes(&client, bucket, key, recursive).await?;
+    let from = Site::Aws { region };
+    Ok(estimate_egress(&from, &Site::Local, bytes).filter(|e| e.cost_usd > 0.0))
+}
+
+/// Show the estimate and confirm it when it exceeds the configured threshold
+///
+/// Returns `false` if the user declined. Without a terminal, a transfer over
+/// the threshold fails unless `yes` is set.
+pub fn confirm_egress(
+    estimate: &EgressEstimate,
+    description: &str,
+    config: &Config,
+    yes: bool,
+    output_format: &str,
+) -> Result<bool> {
+    let threshold = config.transfer.confirm_egress_usd;
+    let summary = format!("{}: {}", description, format_estimate(estimate));
+    if yes || crate::prompt::is_non_interactive() || estimate.cost_usd < threshold {
+        if output_format == "text" {
+            println!("Estimated egress for {}", summary);
+        }
+        return Ok(true);
+    }
+
+    if output_format != "text" || !crate::prompt::can_prompt() {
+        return Err(TrainctlError::Validation {
+            field: "yes".to_string(),
+            reason: format!(
+                "{} exceeds the ${:.2} egress confirmation threshold; pass --yes to proceed \
+                 or raise [transfer] confirm_egress_usd",
+                summary, threshold
+            ),
+        });
+    }
+
+    crate::prompt::confirm(&format!(
+        "WARNING: {} (over ${:.2}). Continue?",
+        summary, threshold
+    ))
+}
+
+/// Record a completed transfer's egress in the cost history
+pub fn record_egress(estimate: &EgressEstimate, description: &str) {
+    let record = CostRecord::egress(description, estimate.bytes, estimate.cost_usd);
+    if let Err(e) = cost_history::append(&record) {
+        warn!("Failed to record egress cost: {}", e);
+    }
+}
@@ -0,0 +1,519 @@
+//! Client-side encryption for uploads
+//!
+//! `--encrypt` on `s3 upload` and `transfer` encrypts files with AES-256-GCM
+//! before they leave the machine, so S3 (and anyone with bucket access) only
+//! ever sees ciphertext. Encrypted objects get a `.enc` suffix and start with
+//! a magic header, which is how downloads recognise and transparently decrypt
+//! them.
+//!
+//! ## Format
+//!
+//! `RUNCTLE1` magic, a 7-byte random nonce prefix, then the plaintext in
+//! 1 MiB chunks, each sealed separately. A chunk's nonce is the prefix, a
+//! big-endian chunk counter and a final-chunk flag, so reordered, dropped or
+//! truncated chunks fail authentication instead of decrypting to a corrupted
+//! checkpoint.
+//!
+//! ## Key
+//!
+//! A 32-byte key, base64 encoded, taken from the first of:
+//! 1. `RUNCTL_ENCRYPTION_KEY`
+//! 2. `[encryption] key_command` output (e.g. a keychain lookup)
+//! 3. `[encryption] key_file` contents
+//!
+//! Generate one with `openssl rand -base64 32`.
+
+use crate::config::Config;
+use crate::error::{ConfigError, Result, TrainctlError};
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::Engine;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+const MAGIC: &[u8; 8] = b"RUNCTLE1";
+const PREFIX_LEN: usize = 7;
+const HEADER_LEN: usize = MAGIC.len() + PREFIX_LEN;
+const CHUNK_SIZE: usize = 1024 * 1024;
+const TAG_LEN: usize = 16;
+
+/// Suffix added to encrypted object names
+pub const ENCRYPTED_SUFFIX: &str = ".enc";
+
+/// Environment variable holding a base64 key
+pub const KEY_ENV: &str = "RUNCTL_ENCRYPTION_KEY";
+
+/// A 256-bit AES-GCM key
+pub struct EncryptionKey {
+    cipher: Aes256Gcm,
+}
+
+impl EncryptionKey {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let cipher = Aes256Gcm::new_from_slice(bytes).map_err(|_| {
+            TrainctlError::Config(ConfigError::InvalidValue {
+                field: "encryption key".to_string(),
+                reason: format!("expected 32 bytes, got {}", bytes.len()),
+            })
+        })?;
+        Ok(Self { cipher })
+    }
+
+    pub fn from_base64(encoded: &str) -> Result<Self> {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| {
+                TrainctlError::Config(ConfigError::InvalidValue {
+                    field: "encryption key".to_string(),
+                    reason: format!("not valid base64: {}", e),
+                })
+            })?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Resolve the key from the environment or `[encryption]` config
+    pub fn load(config: &Config) -> Result<Self> {
+        if let Ok(value) = std::env::var(KEY_ENV) {
+            if !value.trim().is_empty() {
+                return Self::from_base64(&value);
+            }
+        }
+
+        if let Some(command) = &config.encryption.key_command {
+            let output = std::process::Command::new("sh")
+                .arg("-c")
+                .arg(command)
+                .output()?;
+            if !output.status.success() {
+                return Err(TrainctlError::Config(ConfigError::InvalidValue {
+                    field: "encryption.key_command".to_string(),
+                    reason: format!(
+                        "command failed: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    ),
+                }));
+            }
+            return Self::from_base64(&String::from_utf8_lossy(&output.stdout));
+        }
+
+        if let Some(path) = &config.encryption.key_file {
+            let expanded = shellexpand::tilde(&path.to_string_lossy()).to_string();
+            let content = std::fs::read_to_string(&expanded).map_err(|e| {
+                TrainctlError::Config(ConfigError::InvalidValue {
+                    field: "encryption.key_file".to_string(),
+                    reason: format!("cannot read {}: {}", expanded, e),
+                })
+            })?;
+            return Self::from_base64(&content);
+        }
+
+        Err(TrainctlError::Config(ConfigError::MissingField(format!(
+            "encryption key (set {}, or [encryption] key_command or key_file)",
+            KEY_ENV
+        ))))
+    }
+
+    fn nonce(prefix: &[u8; PREFIX_LEN], counter: u32, last: bool) -> [u8; 12] {
+        let mut nonce = [0u8; 12];
+        nonce[..PREFIX_LEN].copy_from_slice(prefix);
+        nonce[PREFIX_LEN..11].copy_from_slice(&counter.to_be_bytes());
+        nonce[11] = u8::from(last);
+        nonce
+    }
+}
+
+/// Fill `buf` as far as the reader allows; returns the bytes read
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+fn tampered(path: &Path) -> TrainctlError {
+    TrainctlError::Validation {
+        field: "encryption".to_string(),
+        reason: format!(
+            "{} failed authentication (wrong key, or the file is corrupted or truncated)",
+            path.display()
+        ),
+    }
+}
+
+/// Encrypt `src` into `dst`
+pub fn encrypt_file(key: &EncryptionKey, src: &Path, dst: &Path) -> Result<()> {
+    let mut reader = BufReader::new(File::open(src)?);
+    let mut writer = BufWriter::new(File::create(dst)?);
+
+    let mut prefix = [0u8; PREFIX_LEN];
+    OsRng.fill_bytes(&mut prefix);
+    let mut header = [0u8; HEADER_LEN];
+    header[..MAGIC.len()].copy_from_slice(MAGIC);
+    header[MAGIC.len()..].copy_from_slice(&prefix);
+    writer.write_all(&header)?;
+
+    // Read one chunk ahead so the final chunk can be flagged
+    let mut current = vec![0u8; CHUNK_SIZE];
+    let mut next = vec![0u8; CHUNK_SIZE];
+    let mut current_len = read_full(&mut reader, &mut current)?;
+    let mut counter: u32 = 0;
+    loop {
+        let next_len = if current_len == CHUNK_SIZE {
+            read_full(&mut reader, &mut next)?
+        } else {
+            0
+        };
+        let last = next_len == 0;
+        let nonce = EncryptionKey::nonce(&prefix, counter, last);
+        let sealed = key
+            .cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &current[..current_len],
+                    aad: &header,
+                },
+            )
+            .map_err(|_| TrainctlError::Validation {
+                field: "encryption".to_string(),
+                reason: format!("failed to encrypt {}", src.display()),
+            })?;
+        writer.write_all(&sealed)?;
+        if last {
+            break;
+        }
+        std::mem::swap(&mut current, &mut next);
+        current_len = next_len;
+        counter = counter
+            .checked_add(1)
+            .ok_or_else(|| TrainctlError::Validation {
+                field: "encryption".to_string(),
+                reason: format!("{} is too large to encrypt", src.display()),
+            })?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Decrypt `src` into `dst`
+///
+/// `dst` is only written through a temporary file, so a failed
+/// authentication never leaves partial plaintext behind.
+pub fn decrypt_file(key: &EncryptionKey, src: &Path, dst: &Path) -> Result<()> {
+    let mut reader = BufReader::new(File::open(src)?);
+    let mut header = [0u8; HEADER_LEN];
+    if read_full(&mut reader, &mut header)? < HEADER_LEN || &header[..MAGIC.len()] != MAGIC {
+        return Err(TrainctlError::Validation {
+            field: "encryption".to_string(),
+            reason: format!("{} is not a runctl-encrypted file", src.display()),
+        });
+    }
+    let partial = dst.with_file_name(format!(
+        ".{}.partial",
+        dst.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    match decrypt_chunks(key, &mut reader, &header, &partial, src) {
+        Ok(()) => {
+            std::fs::rename(&partial, dst)?;
+            Ok(())
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&partial);
+            Err(e)
+        }
+    }
+}
+
+/// Authenticate and decrypt the chunks following the header into `partial`
+fn decrypt_chunks(
+    key: &EncryptionKey,
+    reader: &mut impl Read,
+    header: &[u8; HEADER_LEN],
+    partial: &Path,
+    src: &Path,
+) -> Result<()> {
+    let mut prefix = [0u8; PREFIX_LEN];
+    prefix.copy_from_slice(&header[MAGIC.len()..]);
+    let mut writer = BufWriter::new(File::create(partial)?);
+    let mut current = vec![0u8; CHUNK_SIZE + TAG_LEN];
+    let mut next = vec![0u8; CHUNK_SIZE + TAG_LEN];
+    let mut current_len = read_full(reader, &mut current)?;
+    let mut counter: u32 = 0;
+    loop {
+        let next_len = if current_len == current.len() {
+            read_full(reader, &mut next)?
+        } else {
+            0
+        };
+        let last = next_len == 0;
+        let nonce = EncryptionKey::nonce(&prefix, counter, last);
+        let plain = key
+            .cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &current[..current_len],
+                    aad: header,
+                },
+            )
+            .map_err(|_| tampered(src))?;
+        writer.write_all(&plain)?;
+        if last {
+            break;
+        }
+        std::mem::swap(&mut current, &mut next);
+        current_len = next_len;
+        counter = counter.checked_add(1).ok_or_else(|| tampered(src))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Whether a file starts with the encryption header
+pub fn is_encrypted(path: &Path) -> Result<bool> {
+    let mut magic = [0u8; 8];
+    let mut file = File::open(path)?;
+    Ok(read_full(&mut file, &mut magic)? == MAGIC.len() && &magic == MAGIC)
+}
+
+/// Encrypted copies of upload sources, removed on drop
+pub struct EncryptedStage {
+    root: PathBuf,
+    path: PathBuf,
+}
+
+impl EncryptedStage {
+    /// The encrypted file or directory to upload in place of the source
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for EncryptedStage {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
+
+/// Append `.enc` to a name unless it is already there
+pub fn encrypted_name(name: &str) -> String {
+    if name.ends_with(ENCRYPTED_SUFFIX) {
+        name.to_string()
+    } else {
+        format!("{}{}", name, ENCRYPTED_SUFFIX)
+    }
+}
+
+/// Encrypt a file or directory tree into a temporary staging area
+///
+/// Every file gets the `.enc` suffix; a directory keeps its layout.
+pub fn stage_for_upload(key: &EncryptionKey, source: &Path) -> Result<EncryptedStage> {
+    let root = std::env::temp_dir().join(format!("runctl-enc-{}", uuid::Uuid::new_v4()));
+    crate::utils::ensure_dir(&root)?;
+    let name = source
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "data".to_string());
+
+    let stage = if source.is_dir() {
+        let staged_dir = root.join(&name);
+        let stage = EncryptedStage {
+            root,
+            path: staged_dir.clone(),
+        };
+        let mut count = 0;
+        for entry in walkdir::WalkDir::new(source) {
+            let entry = entry.map_err(|e| TrainctlError::Io(e.into()))?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let relative = entry.path().strip_prefix(source).unwrap_or(entry.path());
+            let mut target = staged_dir.join(relative);
+            let file_name = target
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            target.set_file_name(encrypted_name(&file_name));
+            if let Some(parent) = target.parent() {
+                crate::utils::ensure_dir(parent)?;
+            }
+            encrypt_file(key, entry.path(), &target)?;
+            count += 1;
+        }
+        info!("Encrypted {} files from {}", count, source.display());
+        stage
+    } else {
+        let staged_file = root.join(encrypted_name(&name));
+        let stage = EncryptedStage {
+            root,
+            path: staged_file.clone(),
+        };
+        encrypt_file(key, source, &staged_file)?;
+        stage
+    };
+    Ok(stage)
+}
+
+/// Decrypt any runctl-encrypted files under a downloaded path, in place
+///
+/// Encrypted files are replaced by their plaintext with the `.enc` suffix
+/// stripped. The key is only loaded if an encrypted file is found, so plain
+/// downloads never need one. Returns the number of files decrypted.
+pub fn decrypt_downloaded(config: &Config, path: &Path) -> Result<usize> {
+    let files: Vec<PathBuf> = if path.is_dir() {
+        walkdir::WalkDir::new(path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .collect()
+    } else if path.is_file() {
+        vec![path.to_path_buf()]
+    } else {
+        Vec::new()
+    };
+
+    let mut encrypted = Vec::new();
+    for file in files {
+        if is_encrypted(&file)? {
+            encrypted.push(file);
+        }
+    }
+    if encrypted.is_empty() {
+        return Ok(0);
+    }
+
+    let key = EncryptionKey::load(config)?;
+    for file in &encrypted {
+        let name = file
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        match name.strip_suffix(ENCRYPTED_SUFFIX) {
+            Some(plain) if !plain.is_empty() => {
+                let target = file.with_file_name(plain);
+                decrypt_file(&key, file, &target)?;
+                std::fs::remove_file(file)?;
+            }
+            // No suffix to strip: decrypt over the original
+            _ => decrypt_file(&key, file, file)?,
+        }
+    }
+    info!(
+        "Decrypted {} files under {}",
+        encrypted.len(),
+        path.display()
+    );
+    Ok(encrypted.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_key(byte: u8) -> EncryptionKey {
+        EncryptionKey::from_bytes(&[byte; 32]).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = test_key(7);
+        // Empty, sub-chunk, exact-chunk and multi-chunk sizes
+        for size in [0, 100, CHUNK_SIZE, CHUNK_SIZE * 2 + 5] {
+            let plain: Vec<u8> = (0..size).map(|i| (i % 251) as u8).collect();
+            let src = dir.path().join("plain.bin");
+            let enc = dir.path().join("plain.bin.enc");
+            let out = dir.path().join("out.bin");
+            std::fs::write(&src, &plain).unwrap();
+
+            encrypt_file(&key, &src, &enc).unwrap();
+            assert!(is_encrypted(&enc).unwrap());
+            assert!(!is_encrypted(&src).unwrap());
+            decrypt_file(&key, &enc, &out).unwrap();
+            assert_eq!(std::fs::read(&out).unwrap(), plain, "size {}", size);
+        }
+    }
+
+    #[test]
+    fn test_wrong_key_and_tampering() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("model.pt");
+        let enc = dir.path().join("model.pt.enc");
+        let out = dir.path().join("model.out");
+        std::fs::write(&src, vec![42u8; CHUNK_SIZE + 10]).unwrap();
+        encrypt_file(&test_key(1), &src, &enc).unwrap();
+
+        assert!(decrypt_file(&test_key(2), &enc, &out).is_err());
+        assert!(!out.exists());
+
+        // Dropping the final chunk must not decrypt to a shorter file
+        let bytes = std::fs::read(&enc).unwrap();
+        std::fs::write(&enc, &bytes[..HEADER_LEN + CHUNK_SIZE + TAG_LEN]).unwrap();
+        assert!(decrypt_file(&test_key(1), &enc, &out).is_err());
+
+        let mut flipped = bytes.clone();
+        flipped[HEADER_LEN + 3] ^= 1;
+        std::fs::write(&enc, &flipped).unwrap();
+        assert!(decrypt_file(&test_key(1), &enc, &out).is_err());
+        assert!(!out.exists());
+    }
+
+    #[test]
+    fn test_stage_and_decrypt_downloaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("checkpoints");
+        std::fs::create_dir_all(source.join("epoch_1")).unwrap();
+        std::fs::write(source.join("epoch_1/model.pt"), b"weights").unwrap();
+        std::fs::write(source.join("config.json"), b"{}").unwrap();
+
+        let key = test_key(9);
+        let stage = stage_for_upload(&key, &source).unwrap();
+        assert!(stage.path().join("epoch_1/model.pt.enc").exists());
+        assert!(stage.path().join("config.json.enc").exists());
+
+        // Simulate the download of the staged tree, then decrypt it
+        let downloaded = dir.path().join("downloaded");
+        std::fs::create_dir_all(downloaded.join("epoch_1")).unwrap();
+        std::fs::copy(
+            stage.path().join("epoch_1/model.pt.enc"),
+            downloaded.join("epoch_1/model.pt.enc"),
+        )
+        .unwrap();
+        std::fs::write(downloaded.join("notes.txt"), b"plain").unwrap();
+
+        let mut config = Config::default();
+        config.encryption.key_command = Some(format!(
+            "echo {}",
+            base64::engine::general_purpose::STANDARD.encode([9u8; 32])
+        ));
+        std::env::remove_var(KEY_ENV);
+        assert_eq!(decrypt_downloaded(&config, &downloaded).unwrap(), 1);
+        assert_eq!(
+            std::fs::read(downloaded.join("epoch_1/model.pt")).unwrap(),
+            b"weights"
+        );
+        assert!(!downloaded.join("epoch_1/model.pt.enc").exists());
+        assert_eq!(
+            std::fs::read(downloaded.join("notes.txt")).unwrap(),
+            b"plain"
+        );
+
+        let staged_root = stage.path().to_path_buf();
+        drop(stage);
+        assert!(!staged_root.exists());
+    }
+
+    #[test]
+    fn test_encrypted_name() {
+        assert_eq!(encrypted_name("model.pt"), "model.pt.enc");
+        assert_eq!(encrypted_name("model.pt.enc"), "model.pt.enc");
+    }
+}
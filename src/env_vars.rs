@@ -0,0 +1,282 @@
+//! Environment variables for training runs
+//!
+//! `local`, `exec` and `aws train` export the same set of variables to the
+//! training script, merged in this order (later wins):
+//!
+//! 1. `[env]` in `.runctl.toml`
+//! 2. `--env-file .env`
+//! 3. `--env KEY=VALUE` (repeatable)
+//!
+//! Values may be `secret://name` references (see `crate::secrets`). Values of
+//! keys that look like credentials (`is_secret_key`) are registered for
+//! masking, and `redact` replaces them with `****` before a command is
+//! logged or printed.
+
+use crate::config::Config;
+use crate::error::{Result, TrainctlError};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+/// Replacement shown in logs for masked values
+pub const MASK: &str = "****";
+
+/// Key fragments that mark a variable as a credential
+const SECRET_PATTERNS: &[&str] = &[
+    "SECRET",
+    "TOKEN",
+    "PASSWORD",
+    "PASSWD",
+    "CREDENTIAL",
+    "API_KEY",
+    "APIKEY",
+    "ACCESS_KEY",
+    "PRIVATE_KEY",
+];
+
+/// `KEY=VALUE` from the command line (clap value parser)
+pub fn parse_env_arg(value: &str) -> std::result::Result<(String, String), String> {
+    let (key, value) = value
+        .split_once('=')
+        .ok_or_else(|| format!("invalid '{}' (expected KEY=VALUE)", value))?;
+    let key = key.trim();
+    if !crate::utils::is_env_name(key) {
+        return Err(format!("invalid environment variable name '{}'", key));
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// Parses `.env` contents
+///
+/// One `KEY=VALUE` per line; blank lines, `#` comments and a leading
+/// `export ` are ignored. Single-quoted values are taken literally, double
+/// quotes allow `\n`, `\"` and `\\` escapes, and unquoted values end at ` #`.
+pub fn parse_env_file(content: &str) -> std::result::Result<Vec<(String, String)>, String> {
+    let mut vars = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+        let (key, raw) = parse_env_arg(line).map_err(|e| format!("line {}: {}", index + 1, e))?;
+        let raw = raw.trim();
+        let value = if let Some(inner) = raw.strip_prefix('\'') {
+            inner
+                .strip_suffix('\'')
+                .ok_or_else(|| format!("line {}: unterminated quote", index + 1))?
+                .to_string()
+        } else if let Some(inner) = raw.strip_prefix('"') {
+            let inner = inner
+                .strip_suffix('"')
+                .ok_or_else(|| format!("line {}: unterminated quote", index + 1))?;
+            unescape(inner)
+        } else {
+            raw.split(" #")
+                .next()
+                .unwrap_or_default()
+                .trim_end()
+                .to_string()
+        };
+        vars.push((key, value));
+    }
+    Ok(vars)
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Reads and parses a `.env` file
+pub fn load_env_file(path: &Path) -> Result<Vec<(String, String)>> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        TrainctlError::Io(std::io::Error::other(format!(
+            "Failed to read env file {}: {}",
+            path.display(),
+            e
+        )))
+    })?;
+    parse_env_file(&content).map_err(|reason| TrainctlError::Validation {
+        field: "env_file".to_string(),
+        reason: format!("{}: {}", path.display(), reason),
+    })
+}
+
+/// Variables for a training run: `[env]`, then `env_file`, then `env`
+///
+/// `secret://` references are resolved, and values of secret-looking keys
+/// are registered for `redact`.
+pub fn collect(
+    config: &Config,
+    env_file: Option<&Path>,
+    env: &[(String, String)],
+) -> Result<Vec<(String, String)>> {
+    let mut merged: Vec<(String, String)> = config
+        .env
+        .iter()
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    if let Some(key) = merged
+        .iter()
+        .map(|(k, _)| k)
+        .find(|k| !crate::utils::is_env_name(k))
+    {
+        return Err(TrainctlError::Validation {
+            field: "env".to_string(),
+            reason: format!("invalid environment variable name '{}' in [env]", key),
+        });
+    }
+    let from_file = match env_file {
+        Some(path) => load_env_file(path)?,
+        None => Vec::new(),
+    };
+    for (key, value) in from_file.into_iter().chain(env.iter().cloned()) {
+        match merged.iter_mut().find(|(k, _)| *k == key) {
+            Some(existing) => existing.1 = value,
+            None => merged.push((key, value)),
+        }
+    }
+
+    let resolved = crate::secrets::resolve_env(&merged, config)?;
+    for ((key, raw), (_, value)) in merged.iter().zip(&resolved) {
+        if is_secret_key(key) || raw.starts_with(crate::secrets::SCHEME) {
+            register_mask(value);
+        }
+    }
+    Ok(resolved)
+}
+
+/// Whether `key` names a credential whose value must not be logged
+pub fn is_secret_key(key: &str) -> bool {
+    let upper = key.to_ascii_uppercase();
+    SECRET_PATTERNS
+        .iter()
+        .any(|pattern| upper.contains(pattern))
+        || upper.ends_with("_KEY")
+        || upper == "KEY"
+}
+
+fn masked() -> &'static Mutex<Vec<String>> {
+    static MASKED: OnceLock<Mutex<Vec<String>>> = OnceLock::new();
+    MASKED.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Masks `value` wherever `redact` is applied for the rest of the process
+pub fn register_mask(value: &str) {
+    // Very short values would mask unrelated text
+    if value.len() < 4 {
+        return;
+    }
+    if let Ok(mut masked) = masked().lock() {
+        if !masked.iter().any(|v| v == value) {
+            masked.push(value.to_string());
+            // Longest first, so a value containing another is masked whole
+            masked.sort_by_key(|v| std::cmp::Reverse(v.len()));
+        }
+    }
+}
+
+/// `text` with every registered secret value replaced by `MASK`
+///
+/// Values are matched both raw and shell-quoted (`utils::shell_quote`), as
+/// they appear in remote commands.
+pub fn redact(text: &str) -> String {
+    let Ok(masked) = masked().lock() else {
+        return text.to_string();
+    };
+    let mut out = text.to_string();
+    for value in masked.iter() {
+        let quoted = crate::utils::shell_quote(value);
+        let quoted = &quoted[1..quoted.len() - 1];
+        if quoted != value.as_str() {
+            out = out.replace(quoted, MASK);
+        }
+        out = out.replace(value.as_str(), MASK);
+    }
+    out
+}
+
+/// `KEY=VALUE` pairs for display, with secret values masked
+pub fn display_pairs(env: &[(String, String)]) -> Vec<String> {
+    env.iter()
+        .map(|(key, value)| {
+            if is_secret_key(key) {
+                format!("{}={}", key, MASK)
+            } else {
+                format!("{}={}", key, redact(value))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_env_files() {
+        let content = "# comment\n\nexport WANDB_PROJECT=vision\nLR=0.001 # tuned\nMSG=\"a \\\"b\\\"\\nc\"\nRAW='x # y'\n";
+        let vars = parse_env_file(content).unwrap();
+        assert_eq!(
+            vars,
+            vec![
+                ("WANDB_PROJECT".to_string(), "vision".to_string()),
+                ("LR".to_string(), "0.001".to_string()),
+                ("MSG".to_string(), "a \"b\"\nc".to_string()),
+                ("RAW".to_string(), "x # y".to_string()),
+            ]
+        );
+        assert!(parse_env_file("1BAD=x").is_err());
+        assert!(parse_env_file("NOVALUE").is_err());
+        assert!(parse_env_file("Q='open").is_err());
+        assert_eq!(
+            parse_env_arg("A=b=c").unwrap(),
+            ("A".to_string(), "b=c".to_string())
+        );
+    }
+
+    #[test]
+    fn test_later_sources_win_and_secrets_are_masked() {
+        let mut config = Config::default();
+        config.env.insert("EPOCHS".to_string(), "10".to_string());
+        config
+            .env
+            .insert("HF_TOKEN".to_string(), "hf-config-token".to_string());
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(".env");
+        std::fs::write(&file, "EPOCHS=20\nWANDB_API_KEY=wandb-secret-value\n").unwrap();
+
+        let env = collect(
+            &config,
+            Some(&file),
+            &[("EPOCHS".to_string(), "30".to_string())],
+        )
+        .unwrap();
+        assert_eq!(env[0], ("EPOCHS".to_string(), "30".to_string()));
+        assert_eq!(env.len(), 3);
+
+        let command = crate::utils::env_exports(&env);
+        let logged = redact(&command);
+        assert!(!logged.contains("wandb-secret-value"));
+        assert!(!logged.contains("hf-config-token"));
+        assert!(logged.contains("EPOCHS='30'"));
+        assert!(display_pairs(&env).contains(&"HF_TOKEN=****".to_string()));
+
+        assert!(is_secret_key("AWS_SECRET_ACCESS_KEY"));
+        assert!(is_secret_key("db_password"));
+        assert!(!is_secret_key("BATCH_SIZE"));
+    }
+}
@@ -27,6 +27,8 @@
 //!
 //! - `ConfigError`: Configuration parsing and validation issues
 //!   - Automatically converted to `TrainctlError::Config` via `#[from]`
+//!   - `FeatureNotCompiled` when a command needs a cargo feature this build
+//!     left out
 //!
 //! - `CloudProvider`: Generic cloud API failures (provider-agnostic)
 //!   - Use for provider-agnostic errors that could occur with any cloud
@@ -43,6 +45,10 @@
 //! - `Validation`: Input validation failures
 //!   - Use for user input validation (instance IDs, paths, etc.)
 //!   - Not retryable (invalid input won't become valid)
+//!
+//! - `Job`: A waited-on training job ended badly (failed, timed out,
+//!   interrupted, or failed health checks)
+//!   - Each outcome has its own process exit code (see `exit_codes`)
 
 use crate::provider::ResourceId;
 use thiserror::Error;
@@ -110,7 +116,6 @@ pub enum TrainctlError {
     Validation { field: String, reason: String },
 
     #[error("Cost tracking error: {0}")]
-    #[allow(dead_code)] // Reserved for future cost tracking
     CostTracking(String),
 
     #[error("Cleanup error: {0}")]
@@ -122,6 +127,37 @@ pub enum TrainctlError {
 
     #[error("JSON serialization error: {0}")]
     Json(#[from] serde_json::Error),
+
+    #[error("Training job {job_id} {outcome}: {message}")]
+    Job {
+        job_id: String,
+        outcome: JobOutcome,
+        message: String,
+    },
+}
+
+/// How a training job ended, when it did not succeed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobOutcome {
+    /// The training process exited non-zero or reported failure
+    Failed,
+    /// The wait timed out; the job may still be running
+    TimedOut,
+    /// The job stopped without finishing (instance stopped, process killed)
+    Interrupted,
+    /// Health checks failed repeatedly
+    Unhealthy,
+}
+
+impl std::fmt::Display for JobOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            JobOutcome::Failed => "failed",
+            JobOutcome::TimedOut => "timed out",
+            JobOutcome::Interrupted => "was interrupted",
+            JobOutcome::Unhealthy => "failed health checks",
+        })
+    }
 }
 
 /// Configuration-specific errors
@@ -143,6 +179,9 @@ pub enum ConfigError {
 
     #[error("Failed to parse config: {0}")]
     ParseError(String),
+
+    #[error("runctl was built without the `{0}` feature; rebuild with `--features {0}`")]
+    FeatureNotCompiled(String),
 }
 
 /// Result type alias
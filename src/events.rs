@@ -0,0 +1,282 @@
+//! Lifecycle events per instance (`aws timeline`, `jobs timeline`)
+//!
+//! Commands append events (created, bootstrapped, training started,
+//! checkpoint saved, spot warning) as JSON lines to `.runctl/events.jsonl`.
+//! A timeline merges them with the start, stop and terminate entries the cost
+//! ledger already keeps, so the order of what happened to an overnight run can
+//! be read back in one place.
+
+use crate::costs::{LedgerEntry, LedgerEvent};
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// What happened to the resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Created,
+    Bootstrapped,
+    BootstrapFailed,
+    TrainingStarted,
+    CheckpointSaved,
+    SpotWarning,
+    Started,
+    Stopped,
+    Terminated,
+}
+
+impl std::fmt::Display for EventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Created => "created",
+            Self::Bootstrapped => "bootstrapped",
+            Self::BootstrapFailed => "bootstrap failed",
+            Self::TrainingStarted => "training started",
+            Self::CheckpointSaved => "checkpoint saved",
+            Self::SpotWarning => "spot warning",
+            Self::Started => "started",
+            Self::Stopped => "stopped",
+            Self::Terminated => "terminated",
+        };
+        f.pad(name)
+    }
+}
+
+/// One event in a resource's timeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    pub timestamp: DateTime<Utc>,
+    pub resource_id: String,
+    pub kind: EventKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl Event {
+    pub fn new(resource_id: &str, kind: EventKind) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            resource_id: resource_id.to_string(),
+            kind,
+            detail: None,
+        }
+    }
+
+    pub fn detail(mut self, detail: impl ToString) -> Self {
+        self.detail = Some(detail.to_string());
+        self
+    }
+
+    pub fn at(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+}
+
+pub fn log_path() -> PathBuf {
+    PathBuf::from(".runctl").join("events.jsonl")
+}
+
+/// Append an event to the default log
+///
+/// Events are a record, not part of the command, so failures are only logged.
+pub fn record(event: Event) {
+    if let Err(e) = append_to(&log_path(), &event) {
+        tracing::debug!("Failed to record {} event: {}", event.kind, e);
+    }
+}
+
+/// Append an event unless the same kind and detail is already recorded for the resource
+///
+/// For events that are observed rather than caused, such as a checkpoint seen
+/// by `aws status`, which would otherwise be recorded on every call.
+pub fn record_once(event: Event) {
+    let path = log_path();
+    let seen = load_from(&path).map(|events| {
+        events.iter().any(|e| {
+            e.resource_id == event.resource_id && e.kind == event.kind && e.detail == event.detail
+        })
+    });
+    if !matches!(seen, Ok(true)) {
+        record(event);
+    }
+}
+
+pub fn append_to(path: &Path, event: &Event) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        crate::utils::ensure_dir(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+    Ok(())
+}
+
+/// Load all events, skipping lines that don't parse
+pub fn load_from(path: &Path) -> Result<Vec<Event>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Lifecycle changes from the cost ledger as events (snapshots are left out)
+fn from_ledger(entry: &LedgerEntry) -> Option<Event> {
+    let kind = match entry.event {
+        LedgerEvent::Launched => EventKind::Created,
+        LedgerEvent::Started => EventKind::Started,
+        LedgerEvent::Stopped => EventKind::Stopped,
+        LedgerEvent::Terminated => EventKind::Terminated,
+        LedgerEvent::Snapshot => return None,
+    };
+    let mut event = Event::new(&entry.resource_id, kind).at(entry.timestamp);
+    event.detail = entry.instance_type.clone();
+    Some(event)
+}
+
+/// Events for `resource_id`, oldest first
+///
+/// A launch is usually in both sources; the ledger's copy is dropped when the
+/// event log already has one.
+pub fn merge(resource_id: &str, events: Vec<Event>, ledger: &[LedgerEntry]) -> Vec<Event> {
+    let mut timeline: Vec<Event> = events
+        .into_iter()
+        .filter(|e| e.resource_id == resource_id)
+        .collect();
+    let has_created = timeline.iter().any(|e| e.kind == EventKind::Created);
+    timeline.extend(
+        ledger
+            .iter()
+            .filter(|entry| entry.resource_id == resource_id)
+            .filter_map(from_ledger)
+            .filter(|e| !(has_created && e.kind == EventKind::Created)),
+    );
+    timeline.sort_by_key(|e| e.timestamp);
+    timeline
+}
+
+/// Timeline of `resource_id` from the default event log and ledger
+pub fn timeline(resource_id: &str) -> Result<Vec<Event>> {
+    let events = load_from(&log_path())?;
+    let ledger = match crate::costs::default_ledger_path() {
+        Some(path) => crate::costs::Ledger::new(path).load()?,
+        None => Vec::new(),
+    };
+    Ok(merge(resource_id, events, &ledger))
+}
+
+/// Print a timeline as text lines or a JSON array
+pub fn print_timeline(resource_id: &str, events: &[Event], output_format: &str) -> Result<()> {
+    if output_format == "json" {
+        println!("{}", serde_json::to_string_pretty(events)?);
+        return Ok(());
+    }
+    if events.is_empty() {
+        println!("No events recorded for {}", resource_id);
+        return Ok(());
+    }
+    println!("Timeline for {}:", resource_id);
+    let mut previous: Option<DateTime<Utc>> = None;
+    for event in events {
+        let gap = previous
+            .map(|p| {
+                format!(
+                    " (+{})",
+                    crate::utils::format_duration((event.timestamp - p).num_seconds().max(0) as u64)
+                )
+            })
+            .unwrap_or_default();
+        println!(
+            "  {}  {:<17}{}{}",
+            event.timestamp.format("%Y-%m-%d %H:%M:%S UTC"),
+            event.kind,
+            event.detail.as_deref().unwrap_or(""),
+            gap
+        );
+        previous = Some(event.timestamp);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(h: u32, m: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, h, m, 0).unwrap()
+    }
+
+    fn ledger_entry(id: &str, event: LedgerEvent, timestamp: DateTime<Utc>) -> LedgerEntry {
+        LedgerEntry {
+            timestamp,
+            resource_id: id.to_string(),
+            event,
+            project: None,
+            user: None,
+            account: None,
+            instance_type: Some("g5.xlarge".to_string()),
+            cost_per_hour: 1.0,
+            accumulated_cost: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_record_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+        let event = Event::new("i-0abc", EventKind::CheckpointSaved).detail("epoch_3.pt");
+        append_to(&path, &event).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .and_then(|mut f| writeln!(f, "not json"))
+            .unwrap();
+
+        assert_eq!(load_from(&path).unwrap(), vec![event]);
+        assert!(load_from(&dir.path().join("missing.jsonl"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_merge_orders_and_dedupes_launch() {
+        let events = vec![
+            Event::new("i-0abc", EventKind::TrainingStarted).at(at(1, 10)),
+            Event::new("i-0abc", EventKind::Created).at(at(1, 0)),
+            Event::new("i-0other", EventKind::SpotWarning).at(at(2, 0)),
+        ];
+        let ledger = vec![
+            ledger_entry("i-0abc", LedgerEvent::Launched, at(1, 0)),
+            ledger_entry("i-0abc", LedgerEvent::Snapshot, at(2, 0)),
+            ledger_entry("i-0abc", LedgerEvent::Stopped, at(6, 30)),
+        ];
+        let kinds: Vec<EventKind> = merge("i-0abc", events, &ledger)
+            .into_iter()
+            .map(|e| e.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                EventKind::Created,
+                EventKind::TrainingStarted,
+                EventKind::Stopped
+            ]
+        );
+
+        // Without a local launch event, the ledger's stands in
+        let merged = merge("i-0abc", Vec::new(), &ledger);
+        assert_eq!(merged[0].kind, EventKind::Created);
+        assert_eq!(merged[0].detail.as_deref(), Some("g5.xlarge"));
+    }
+}
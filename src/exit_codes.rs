@@ -1,7 +1,7 @@
 //! Exit code standardization for runctl
 //!
 //! Provides consistent exit codes for different error types to enable
-//! reliable programmatic error detection by AI tools and scripts.
+//! reliable programmatic error detection by scripts and CI pipelines.
 //!
 //! ## Exit Code Convention
 //!
@@ -9,8 +9,15 @@
 //! - `1` = User error (invalid input, validation failure, resource not found)
 //! - `2` = System error (AWS API failure, network error, cloud provider error)
 //! - `3` = Configuration error (missing config, invalid credentials, config parse error)
+//! - `10` = Training job failed (non-zero exit status or failure detected)
+//! - `11` = Timed out waiting for the training job
+//! - `12` = Training job interrupted (instance stopped, process killed)
+//! - `13` = Training job failed its health checks
+//!
+//! The job codes come from `aws train --wait`, `aws monitor --follow` and
+//! `jobs wait`, so a CI step can gate on the training outcome.
 
-use crate::error::TrainctlError;
+use crate::error::{JobOutcome, TrainctlError};
 
 /// Standard exit codes for runctl
 pub mod codes {
@@ -23,6 +30,14 @@ pub mod codes {
     pub const SYSTEM_ERROR: i32 = 2;
     /// Configuration error (missing config, invalid credentials)
     pub const CONFIG_ERROR: i32 = 3;
+    /// Training job failed
+    pub const JOB_FAILED: i32 = 10;
+    /// Timed out waiting for the training job
+    pub const JOB_TIMED_OUT: i32 = 11;
+    /// Training job interrupted before finishing
+    pub const JOB_INTERRUPTED: i32 = 12;
+    /// Training job failed its health checks
+    pub const JOB_UNHEALTHY: i32 = 13;
 }
 
 /// Map a TrainctlError to an appropriate exit code
@@ -34,12 +49,12 @@ pub fn exit_code_for_error(error: &TrainctlError) -> i32 {
     match error {
         // Configuration errors
         Config(_) => codes::CONFIG_ERROR,
-        
+
         // User errors (invalid input, validation failures)
         Validation { .. } => codes::USER_ERROR,
         ResourceNotFound { .. } => codes::USER_ERROR,
         ResourceExists { .. } => codes::USER_ERROR,
-        
+
         // System errors (cloud provider, network, I/O)
         CloudProvider { .. } => codes::SYSTEM_ERROR,
         Aws(_) => codes::SYSTEM_ERROR,
@@ -47,10 +62,18 @@ pub fn exit_code_for_error(error: &TrainctlError) -> i32 {
         Ssm(_) => codes::SYSTEM_ERROR,
         Io(_) => codes::SYSTEM_ERROR,
         Retryable { .. } => codes::SYSTEM_ERROR,
-        
+
         // Resource errors - depends on context, default to user error
         Resource { .. } => codes::USER_ERROR,
-        
+
+        // Training job outcomes
+        Job { outcome, .. } => match outcome {
+            JobOutcome::Failed => codes::JOB_FAILED,
+            JobOutcome::TimedOut => codes::JOB_TIMED_OUT,
+            JobOutcome::Interrupted => codes::JOB_INTERRUPTED,
+            JobOutcome::Unhealthy => codes::JOB_UNHEALTHY,
+        },
+
         // Other errors - default to system error
         CostTracking(_) => codes::SYSTEM_ERROR,
         Cleanup(_) => codes::SYSTEM_ERROR,
@@ -59,6 +82,16 @@ pub fn exit_code_for_error(error: &TrainctlError) -> i32 {
     }
 }
 
+/// Exit code for an error surfaced at the CLI boundary
+///
+/// Errors that did not originate as a `TrainctlError` are user errors.
+pub fn exit_code_for_anyhow(error: &anyhow::Error) -> i32 {
+    error
+        .downcast_ref::<TrainctlError>()
+        .map(exit_code_for_error)
+        .unwrap_or(codes::USER_ERROR)
+}
+
 /// Exit with appropriate code based on error type
 ///
 /// This is a convenience function for use in main() to exit with the correct code.
@@ -68,3 +101,30 @@ pub fn exit_with_code(error: &TrainctlError) -> ! {
     std::process::exit(code);
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_outcomes_have_distinct_codes() {
+        let job = |outcome| TrainctlError::Job {
+            job_id: "i-0123456789abcdef0".to_string(),
+            outcome,
+            message: String::new(),
+        };
+        assert_eq!(exit_code_for_error(&job(JobOutcome::Failed)), 10);
+        assert_eq!(exit_code_for_error(&job(JobOutcome::TimedOut)), 11);
+        assert_eq!(exit_code_for_error(&job(JobOutcome::Interrupted)), 12);
+        assert_eq!(exit_code_for_error(&job(JobOutcome::Unhealthy)), 13);
+    }
+
+    #[test]
+    fn test_anyhow_errors_keep_their_code() {
+        let error = anyhow::Error::from(TrainctlError::Aws("throttled".to_string()));
+        assert_eq!(exit_code_for_anyhow(&error), codes::SYSTEM_ERROR);
+        assert_eq!(
+            exit_code_for_anyhow(&anyhow::anyhow!("plain")),
+            codes::USER_ERROR
+        );
+    }
+}
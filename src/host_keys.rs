@@ -0,0 +1,278 @@
+//! SSH host key verification
+//!
+//! Every SSH connection runctl makes (the `ssh` CLI and the native ssh2
+//! sync) checks the host key against a runctl-managed known_hosts file,
+//! `[ssh] known_hosts` (default `~/.runctl/known_hosts`). Entries are keyed
+//! by resource ID rather than IP (`HostKeyAlias`), so a public IP reused by
+//! a later instance never matches the old instance's key.
+//!
+//! Keys get into the file in two ways:
+//! - learned from the provider before connecting (for EC2, the cloud-init
+//!   host key block in the console output, or `/etc/ssh` over SSM)
+//! - recorded on first connect (trust on first use), unless
+//!   `[ssh] strict_host_keys = true`, in which case unknown hosts are refused
+//!
+//! A key that differs from the recorded one is always refused.
+
+use crate::config::SshConfig;
+use crate::error::{Result, TrainctlError};
+use std::path::{Path, PathBuf};
+
+/// Key types accepted in known_hosts lines
+const KEY_TYPE_PREFIXES: &[&str] = &["ssh-", "ecdsa-", "sk-"];
+
+/// How to verify the host key of one resource
+#[derive(Debug, Clone)]
+pub struct HostKeys {
+    /// Name the keys are recorded under (the resource ID)
+    pub alias: String,
+    pub known_hosts: PathBuf,
+    /// Refuse hosts without a recorded key instead of trusting on first use
+    pub strict: bool,
+}
+
+impl HostKeys {
+    pub fn for_host(ssh: &SshConfig, alias: &str) -> Self {
+        Self {
+            alias: alias.to_string(),
+            known_hosts: PathBuf::from(
+                shellexpand::tilde(&ssh.known_hosts.to_string_lossy()).into_owned(),
+            ),
+            strict: ssh.strict_host_keys,
+        }
+    }
+
+    /// `-o` options for the `ssh` and `scp` CLIs
+    pub fn ssh_args(&self) -> Vec<String> {
+        let checking = if self.strict { "yes" } else { "accept-new" };
+        [
+            format!("UserKnownHostsFile={}", self.known_hosts.display()),
+            format!("StrictHostKeyChecking={}", checking),
+            format!("HostKeyAlias={}", self.alias),
+            "HashKnownHosts=no".to_string(),
+        ]
+        .into_iter()
+        .flat_map(|option| ["-o".to_string(), option])
+        .collect()
+    }
+
+    /// Whether a key is recorded for this host
+    pub fn is_known(&self) -> bool {
+        std::fs::read_to_string(&self.known_hosts)
+            .map(|content| content.lines().any(|line| self.is_own_entry(line)))
+            .unwrap_or(false)
+    }
+
+    /// Fail early in strict mode when no key is recorded
+    pub fn require_known(&self) -> Result<()> {
+        if self.strict && !self.is_known() {
+            return Err(TrainctlError::Validation {
+                field: "ssh.strict_host_keys".to_string(),
+                reason: format!(
+                    "no host key recorded for {} in {} and strict host key checking is on. \
+                     For EC2 the key is learned from the console output, which appears a few \
+                     minutes after boot; retry then, or add the key to that file yourself",
+                    self.alias,
+                    self.known_hosts.display()
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// Replace the recorded keys with the public keys in `text`
+    ///
+    /// `text` holds lines like `ssh-ed25519 AAAA... root@host`, as in
+    /// `/etc/ssh/ssh_host_*_key.pub` or the console output; other lines are
+    /// skipped. Returns how many keys were recorded (none leaves the file as
+    /// it was).
+    pub fn record(&self, text: &str) -> Result<usize> {
+        let keys = parse_public_keys(text);
+        if keys.is_empty() {
+            return Ok(0);
+        }
+        let existing = std::fs::read_to_string(&self.known_hosts).unwrap_or_default();
+        let mut content: String = existing
+            .lines()
+            .filter(|line| !self.is_own_entry(line))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        for (key_type, key) in &keys {
+            content.push_str(&format!("{} {} {}\n", self.alias, key_type, key));
+        }
+        self.write(&content)?;
+        Ok(keys.len())
+    }
+
+    /// Check the host key of an ssh2 session, recording it on first use
+    pub fn verify_session(&self, session: &ssh2::Session) -> Result<()> {
+        use ssh2::{CheckResult, KnownHostFileKind};
+
+        let (key, key_type) = session.host_key().ok_or_else(|| {
+            TrainctlError::Ssm(format!("{} did not present a host key", self.alias))
+        })?;
+        let mut known = session
+            .known_hosts()
+            .map_err(|e| TrainctlError::Ssm(format!("Failed to load known hosts: {}", e)))?;
+        if self.known_hosts.exists() {
+            known
+                .read_file(&self.known_hosts, KnownHostFileKind::OpenSSH)
+                .map_err(|e| {
+                    TrainctlError::Ssm(format!(
+                        "Failed to read {}: {}",
+                        self.known_hosts.display(),
+                        e
+                    ))
+                })?;
+        }
+
+        match known.check(&self.alias, key) {
+            CheckResult::Match => Ok(()),
+            CheckResult::Mismatch => Err(TrainctlError::Ssm(format!(
+                "Host key for {} does not match the one recorded in {}. If the host was \
+                 rebuilt, remove its lines from that file; otherwise the connection may be \
+                 intercepted",
+                self.alias,
+                self.known_hosts.display()
+            ))),
+            CheckResult::NotFound | CheckResult::Failure => {
+                self.require_known()?;
+                known
+                    .add(&self.alias, key, "recorded by runctl", key_type.into())
+                    .map_err(|e| TrainctlError::Ssm(format!("Failed to add host key: {}", e)))?;
+                if let Some(parent) = self.known_hosts.parent() {
+                    crate::utils::ensure_dir(parent)?;
+                }
+                known
+                    .write_file(&self.known_hosts, KnownHostFileKind::OpenSSH)
+                    .map_err(|e| {
+                        TrainctlError::Ssm(format!(
+                            "Failed to write {}: {}",
+                            self.known_hosts.display(),
+                            e
+                        ))
+                    })?;
+                Ok(())
+            }
+        }
+    }
+
+    fn is_own_entry(&self, line: &str) -> bool {
+        line.split_whitespace()
+            .next()
+            .is_some_and(|hosts| hosts.split(',').any(|host| host == self.alias))
+    }
+
+    fn write(&self, content: &str) -> Result<()> {
+        if let Some(parent) = self.known_hosts.parent() {
+            crate::utils::ensure_dir(parent)?;
+        }
+        write_private(&self.known_hosts, content)
+    }
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, content: &str) -> Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    file.write_all(content.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, content: &str) -> Result<()> {
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// `(type, base64 key)` pairs found in `text`, one per line at most
+pub fn parse_public_keys(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .filter_map(|line| {
+            let mut tokens = line.split_whitespace();
+            let key_type = tokens.find(|t| KEY_TYPE_PREFIXES.iter().any(|p| t.starts_with(p)))?;
+            let key = tokens.next()?;
+            key.starts_with("AAAA")
+                .then(|| (key_type.to_string(), key.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host_keys(dir: &Path, strict: bool) -> HostKeys {
+        HostKeys {
+            alias: "i-0abc".to_string(),
+            known_hosts: dir.join("known_hosts"),
+            strict,
+        }
+    }
+
+    #[test]
+    fn test_parse_console_output_keys() {
+        let console = "\
+[   12.3] cloud-init[1234]: Cloud-init v. 23.1 running\n\
+-----BEGIN SSH HOST KEY KEYS-----\n\
+ecdsa-sha2-nistp256 AAAAE2VjZHNh root@ip-10-0-0-1\n\
+ssh-ed25519 AAAAC3NzaC1lZDI1NTE5 root@ip-10-0-0-1\n\
+-----END SSH HOST KEY KEYS-----\n\
+ec2: ssh-rsa AAAAB3NzaC1yc2E\n";
+        let keys = parse_public_keys(console);
+        assert_eq!(keys.len(), 3);
+        assert_eq!(
+            keys[1],
+            (
+                "ssh-ed25519".to_string(),
+                "AAAAC3NzaC1lZDI1NTE5".to_string()
+            )
+        );
+        assert_eq!(keys[2].0, "ssh-rsa");
+    }
+
+    #[test]
+    fn test_record_replaces_own_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let keys = host_keys(dir.path(), false);
+        std::fs::write(
+            &keys.known_hosts,
+            "i-0abc ssh-ed25519 AAAAold\ni-0def ssh-ed25519 AAAAother\n",
+        )
+        .unwrap();
+
+        assert_eq!(keys.record("ssh-ed25519 AAAAnew root@host\n").unwrap(), 1);
+        let content = std::fs::read_to_string(&keys.known_hosts).unwrap();
+        assert_eq!(
+            content,
+            "i-0def ssh-ed25519 AAAAother\ni-0abc ssh-ed25519 AAAAnew\n"
+        );
+        assert_eq!(keys.record("no keys here").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_require_known_only_in_strict_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(host_keys(dir.path(), false).require_known().is_ok());
+        let strict = host_keys(dir.path(), true);
+        assert!(strict.require_known().is_err());
+        strict.record("ssh-ed25519 AAAAkey").unwrap();
+        assert!(strict.is_known());
+        assert!(strict.require_known().is_ok());
+    }
+
+    #[test]
+    fn test_ssh_args() {
+        let dir = tempfile::tempdir().unwrap();
+        let args = host_keys(dir.path(), true).ssh_args();
+        assert!(args.contains(&"StrictHostKeyChecking=yes".to_string()));
+        assert!(args.contains(&"HostKeyAlias=i-0abc".to_string()));
+        assert_eq!(args.iter().filter(|a| *a == "-o").count(), 4);
+    }
+}
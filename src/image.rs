@@ -0,0 +1,555 @@
+//! Training images in ECR (`runctl image`)
+//!
+//! `runctl image build` builds the project's Dockerfile, locally or on an EC2
+//! instance (`--on`, for machines without Docker or with a slow uplink), tags
+//! it with the git commit, and with `--push` pushes it to an ECR repository
+//! named after the project. `runctl image push` pushes an image built some
+//! other way.
+//!
+//! Every push is recorded, digest included, in `.runctl/images.jsonl`, and
+//! `runctl image list` shows the history. `aws train --image` resolves ECR
+//! tags to their digest before pulling, so a run always records exactly which
+//! image it used even if the tag is pushed again later.
+
+use crate::config::Config;
+use crate::docker::ecr_registry;
+use crate::error::{ConfigError, Result, TrainctlError};
+use crate::sync_filter::SyncFilter;
+use aws_sdk_ecr::types::ImageIdentifier;
+use aws_sdk_ecr::Client as EcrClient;
+use chrono::{DateTime, Utc};
+use clap::Subcommand;
+use comfy_table::Table;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Instance tag recording the pinned image an `aws train --image` run uses
+pub const IMAGE_TAG: &str = "runctl:image";
+
+/// Printed by the remote build once the image is pushed
+const REMOTE_PUSHED_MARKER: &str = "RUNCTL_IMAGE_PUSHED";
+
+#[derive(Subcommand, Clone)]
+pub enum ImageCommands {
+    /// Build the project's Dockerfile, and with --push push it to ECR
+    ///
+    /// Examples:
+    ///   runctl image build
+    ///   runctl image build --push
+    ///   runctl image build --push --tag v3 --repository llm-train
+    ///   runctl image build --on i-0123456789abcdef0
+    Build {
+        /// Dockerfile (default: auto-detect)
+        #[arg(long, value_name = "PATH")]
+        dockerfile: Option<PathBuf>,
+
+        /// ECR repository (default: the project name)
+        #[arg(long, value_name = "NAME")]
+        repository: Option<String>,
+
+        /// Image tag (default: the git commit, with a timestamp if the tree is dirty)
+        #[arg(long, value_name = "TAG")]
+        tag: Option<String>,
+
+        /// Push to ECR after building
+        #[arg(long)]
+        push: bool,
+
+        /// Build and push on this EC2 instance instead of locally
+        ///
+        /// The project is synced over SSM; the instance needs Docker and an
+        /// instance profile that can push to ECR. Implies --push.
+        #[arg(long, value_name = "INSTANCE_ID")]
+        on: Option<String>,
+    },
+    /// Push a local image to ECR
+    ///
+    /// Examples:
+    ///   runctl image push llm-train:dev
+    ///   runctl image push llm-train:dev --repository llm-train --tag v3
+    Push {
+        /// Local image, e.g. llm-train:dev
+        #[arg(value_name = "IMAGE")]
+        image: String,
+
+        /// ECR repository (default: the local image's name)
+        #[arg(long, value_name = "NAME")]
+        repository: Option<String>,
+
+        /// Tag in ECR (default: the local image's tag)
+        #[arg(long, value_name = "TAG")]
+        tag: Option<String>,
+    },
+    /// Show pushed images and their digests
+    List {
+        /// Only this repository
+        #[arg(long, value_name = "NAME")]
+        repository: Option<String>,
+    },
+}
+
+/// A pushed image
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageRecord {
+    pub pushed_at: DateTime<Utc>,
+    pub repository: String,
+    pub tag: String,
+    /// `<registry>/<repository>:<tag>`
+    pub uri: String,
+    /// `sha256:...`
+    pub digest: String,
+    /// Commit the image was built from, when built by `image build`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_commit: Option<String>,
+    /// Built from a tree with uncommitted changes
+    #[serde(default)]
+    pub git_dirty: bool,
+    /// "local" or the instance ID it was built on
+    pub built_on: String,
+}
+
+impl ImageRecord {
+    /// Reference that always pulls this exact image
+    pub fn pinned(&self) -> String {
+        pinned_reference(&self.uri, &self.digest)
+    }
+}
+
+/// `image` with its tag replaced by `@digest`
+pub fn pinned_reference(image: &str, digest: &str) -> String {
+    let name_start = image.rfind('/').map(|i| i + 1).unwrap_or(0);
+    let repository = match image[name_start..].find(['@', ':']) {
+        Some(i) => &image[..name_start + i],
+        None => image,
+    };
+    format!("{}@{}", repository, digest)
+}
+
+/// Repository and tag of an image reference (`latest` when untagged)
+fn split_reference(image: &str) -> (&str, &str) {
+    let name_start = image.rfind('/').map(|i| i + 1).unwrap_or(0);
+    match image[name_start..].rfind(':') {
+        Some(i) => (&image[..name_start + i], &image[name_start + i + 1..]),
+        None => (image, "latest"),
+    }
+}
+
+/// Default tag: the commit, or `<commit>-dirty-<time>` / `<time>` when the
+/// tree can't be identified by a commit alone
+pub fn default_tag(commit: Option<&str>, dirty: bool, now: DateTime<Utc>) -> String {
+    let time = now.format("%Y%m%d-%H%M%S");
+    match commit {
+        Some(commit) if !dirty => commit.to_string(),
+        Some(commit) => format!("{}-dirty-{}", commit, time),
+        None => time.to_string(),
+    }
+}
+
+pub fn history_path() -> PathBuf {
+    PathBuf::from(".runctl").join("images.jsonl")
+}
+
+pub fn append_to(path: &Path, record: &ImageRecord) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        crate::utils::ensure_dir(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+    Ok(())
+}
+
+/// Load all records, skipping lines that don't parse
+pub fn load_from(path: &Path) -> Result<Vec<ImageRecord>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Short commit and whether the tree has uncommitted changes
+fn git_state(project_root: &Path) -> Option<(String, bool)> {
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(project_root)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+    };
+    let commit = git(&["rev-parse", "--short=12", "HEAD"])?;
+    let dirty = git(&["status", "--porcelain"]).is_some_and(|s| !s.is_empty());
+    Some((commit, dirty))
+}
+
+/// Digest of `repository:tag` in ECR
+async fn ecr_digest(ecr: &EcrClient, repository: &str, tag: &str) -> Result<String> {
+    let response = ecr
+        .describe_images()
+        .repository_name(repository)
+        .image_ids(ImageIdentifier::builder().image_tag(tag).build())
+        .send()
+        .await
+        .map_err(|e| {
+            TrainctlError::Aws(format!(
+                "Failed to look up {}:{} in ECR: {}",
+                repository, tag, e
+            ))
+        })?;
+    response
+        .image_details()
+        .first()
+        .and_then(|d| d.image_digest())
+        .map(str::to_string)
+        .ok_or_else(|| TrainctlError::ResourceNotFound {
+            resource_type: "ECR image".to_string(),
+            resource_id: format!("{}:{}", repository, tag),
+        })
+}
+
+/// Pin an ECR image to its current digest
+///
+/// Other registries and references that already carry a digest are returned
+/// unchanged.
+pub async fn resolve_digest(aws_config: &aws_config::SdkConfig, image: &str) -> Result<String> {
+    let Some((host, region)) = ecr_registry(image).filter(|_| !image.contains('@')) else {
+        return Ok(image.to_string());
+    };
+    let (repository, tag) = split_reference(image);
+    let repository = repository
+        .strip_prefix(host)
+        .map(|r| r.trim_start_matches('/'))
+        .unwrap_or(repository);
+    let ecr_config = aws_config
+        .to_builder()
+        .region(aws_sdk_ecr::config::Region::new(region.to_string()))
+        .build();
+    let digest = ecr_digest(&EcrClient::new(&ecr_config), repository, tag).await?;
+    Ok(pinned_reference(image, &digest))
+}
+
+pub async fn handle_command(
+    cmd: ImageCommands,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    let aws_config = crate::aws_utils::shared_sdk_config().await;
+    let region = config
+        .aws
+        .as_ref()
+        .map(|a| a.region.clone())
+        .ok_or_else(|| TrainctlError::Config(ConfigError::MissingField("aws".to_string())))?;
+    let project_root = std::env::current_dir()?;
+    let ecr = EcrClient::new(&aws_config);
+
+    let record = match cmd {
+        ImageCommands::List { repository } => {
+            let records: Vec<ImageRecord> = load_from(&history_path())?
+                .into_iter()
+                .filter(|r| repository.is_none() || repository.as_ref() == Some(&r.repository))
+                .collect();
+            if output_format == "json" {
+                println!("{}", serde_json::to_string_pretty(&records)?);
+            } else if records.is_empty() {
+                println!("No images pushed yet. Push one with: runctl image build --push");
+            } else {
+                let mut table = Table::new();
+                table.set_header(vec!["Pushed", "Image", "Digest", "Commit", "Built on"]);
+                for record in records.iter().rev() {
+                    table.add_row(vec![
+                        record.pushed_at.format("%Y-%m-%d %H:%M").to_string(),
+                        format!("{}:{}", record.repository, record.tag),
+                        record.digest.chars().take(19).collect(),
+                        match &record.git_commit {
+                            Some(commit) if record.git_dirty => format!("{} (dirty)", commit),
+                            Some(commit) => commit.clone(),
+                            None => "-".to_string(),
+                        },
+                        record.built_on.clone(),
+                    ]);
+                }
+                println!("{}", table);
+            }
+            return Ok(());
+        }
+        ImageCommands::Push {
+            image,
+            repository,
+            tag,
+        } => {
+            let (local_repository, local_tag) = split_reference(&image);
+            let repository = repository.unwrap_or_else(|| {
+                local_repository
+                    .rsplit('/')
+                    .next()
+                    .unwrap_or(local_repository)
+                    .to_string()
+            });
+            let tag = tag.unwrap_or_else(|| local_tag.to_string());
+            crate::docker::ensure_ecr_repository(&ecr, &repository).await?;
+            let uri =
+                crate::docker::push_to_ecr(&image, &repository, &tag, &region, &aws_config).await?;
+            ImageRecord {
+                pushed_at: Utc::now(),
+                digest: ecr_digest(&ecr, &repository, &tag).await?,
+                repository,
+                tag,
+                uri,
+                git_commit: None,
+                git_dirty: false,
+                built_on: "local".to_string(),
+            }
+        }
+        ImageCommands::Build {
+            dockerfile,
+            repository,
+            tag,
+            push,
+            on,
+        } => {
+            let dockerfile = match dockerfile {
+                Some(path) => path,
+                None => crate::docker::detect_dockerfile(&project_root).ok_or_else(|| {
+                    TrainctlError::CloudProvider {
+                        provider: "docker".to_string(),
+                        message: "No Dockerfile found. Use --dockerfile to specify one."
+                            .to_string(),
+                        source: None,
+                    }
+                })?,
+            };
+            let repository =
+                repository.unwrap_or_else(|| crate::aws::get_project_name(None, config));
+            let git = git_state(&project_root);
+            let tag = tag.unwrap_or_else(|| {
+                default_tag(
+                    git.as_ref().map(|(commit, _)| commit.as_str()),
+                    git.as_ref().is_some_and(|(_, dirty)| *dirty),
+                    Utc::now(),
+                )
+            });
+            let (git_commit, git_dirty) = match git {
+                Some((commit, dirty)) => (Some(commit), dirty),
+                None => (None, false),
+            };
+
+            let (built_on, uri) = match on {
+                Some(instance_id) => {
+                    crate::validation::validate_instance_id(&instance_id)?;
+                    crate::docker::ensure_ecr_repository(&ecr, &repository).await?;
+                    let account = crate::docker::get_account_id(&aws_config).await?;
+                    let uri = format!(
+                        "{}.dkr.ecr.{}.amazonaws.com/{}:{}",
+                        account, region, repository, tag
+                    );
+                    build_remote(
+                        &instance_id,
+                        &project_root,
+                        &dockerfile,
+                        &uri,
+                        config,
+                        &aws_config,
+                        output_format,
+                    )
+                    .await?;
+                    (instance_id, uri)
+                }
+                None => {
+                    let local = format!("{}:{}", repository, tag);
+                    crate::docker::build_image(&dockerfile, &local, &project_root)?;
+                    if output_format != "json" {
+                        println!("Built {}", local);
+                    }
+                    if !push {
+                        if output_format == "json" {
+                            println!("{}", serde_json::json!({ "image": local }));
+                        } else {
+                            println!("   Push it with: runctl image push {}", local);
+                        }
+                        return Ok(());
+                    }
+                    crate::docker::ensure_ecr_repository(&ecr, &repository).await?;
+                    let uri =
+                        crate::docker::push_to_ecr(&local, &repository, &tag, &region, &aws_config)
+                            .await?;
+                    ("local".to_string(), uri)
+                }
+            };
+            ImageRecord {
+                pushed_at: Utc::now(),
+                digest: ecr_digest(&ecr, &repository, &tag).await?,
+                repository,
+                tag,
+                uri,
+                git_commit,
+                git_dirty,
+                built_on,
+            }
+        }
+    };
+
+    append_to(&history_path(), &record)?;
+    if output_format == "json" {
+        println!("{}", serde_json::to_string_pretty(&record)?);
+    } else {
+        println!("Pushed {}", record.uri);
+        println!("   Digest: {}", record.digest);
+        println!(
+            "   Train with: runctl aws train <instance-id> <script> --image {}",
+            record.pinned()
+        );
+    }
+    Ok(())
+}
+
+/// Sync the project to `instance_id`, then build and push `uri` there
+async fn build_remote(
+    instance_id: &str,
+    project_root: &Path,
+    dockerfile: &Path,
+    uri: &str,
+    config: &Config,
+    aws_config: &aws_config::SdkConfig,
+    output_format: &str,
+) -> Result<()> {
+    let (registry, region) = ecr_registry(uri).ok_or_else(|| TrainctlError::Validation {
+        field: "repository".to_string(),
+        reason: format!("{} is not an ECR image", uri),
+    })?;
+    let build_dir = format!(
+        "/tmp/runctl-image-build/{}",
+        crate::aws::get_project_name(None, config)
+    );
+    let dockerfile_relative = dockerfile
+        .strip_prefix(project_root)
+        .unwrap_or(dockerfile)
+        .display()
+        .to_string();
+
+    if output_format != "json" {
+        println!("Syncing the build context to {}...", instance_id);
+    }
+    let filter = SyncFilter::from_config(&config.sync)?;
+    let ssm = aws_sdk_ssm::Client::new(aws_config);
+    crate::aws::sync_code_via_ssm(
+        project_root,
+        instance_id,
+        &build_dir,
+        dockerfile,
+        &filter,
+        &aws_sdk_s3::Client::new(aws_config),
+        &ssm,
+        config,
+        output_format,
+    )
+    .await?;
+
+    if output_format != "json" {
+        println!("Building {} on {}...", uri, instance_id);
+    }
+    let command = format!(
+        r#"set -e
+cd {dir}
+docker build -f {dockerfile} -t {uri} .
+aws ecr get-login-password --region {region} | docker login --username AWS --password-stdin {registry}
+docker push {uri}
+echo {marker}
+"#,
+        dir = crate::utils::shell_quote(&build_dir),
+        dockerfile = crate::utils::shell_quote(&dockerfile_relative),
+        uri = crate::utils::shell_quote(uri),
+        region = region,
+        registry = registry,
+        marker = REMOTE_PUSHED_MARKER,
+    );
+    let output = crate::aws_utils::execute_ssm_command(&ssm, instance_id, &command).await?;
+    if !output.contains(REMOTE_PUSHED_MARKER) {
+        return Err(TrainctlError::CloudProvider {
+            provider: "docker".to_string(),
+            message: format!("Build on {} failed:\n{}", instance_id, output.trim()),
+            source: None,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_pinned_reference() {
+        let digest = "sha256:0123abcd";
+        assert_eq!(
+            pinned_reference(
+                "123456789012.dkr.ecr.us-east-1.amazonaws.com/train:latest",
+                digest
+            ),
+            "123456789012.dkr.ecr.us-east-1.amazonaws.com/train@sha256:0123abcd"
+        );
+        assert_eq!(
+            pinned_reference("localhost:5000/train", digest),
+            "localhost:5000/train@sha256:0123abcd"
+        );
+        assert_eq!(
+            pinned_reference("train@sha256:old", digest),
+            "train@sha256:0123abcd"
+        );
+    }
+
+    #[test]
+    fn test_split_reference() {
+        assert_eq!(split_reference("llm-train:dev"), ("llm-train", "dev"));
+        assert_eq!(split_reference("llm-train"), ("llm-train", "latest"));
+        assert_eq!(
+            split_reference("localhost:5000/llm-train"),
+            ("localhost:5000/llm-train", "latest")
+        );
+    }
+
+    #[test]
+    fn test_default_tag() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 4, 5, 6, 7).unwrap();
+        assert_eq!(
+            default_tag(Some("abc123def456"), false, now),
+            "abc123def456"
+        );
+        assert_eq!(
+            default_tag(Some("abc123def456"), true, now),
+            "abc123def456-dirty-20260304-050607"
+        );
+        assert_eq!(default_tag(None, false, now), "20260304-050607");
+    }
+
+    #[test]
+    fn test_history_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("images.jsonl");
+        let record = ImageRecord {
+            pushed_at: Utc::now(),
+            repository: "train".to_string(),
+            tag: "abc123def456".to_string(),
+            uri: "123456789012.dkr.ecr.us-east-1.amazonaws.com/train:abc123def456".to_string(),
+            digest: "sha256:0123abcd".to_string(),
+            git_commit: Some("abc123def456".to_string()),
+            git_dirty: false,
+            built_on: "local".to_string(),
+        };
+        append_to(&path, &record).unwrap();
+        append_to(&path, &record).unwrap();
+        assert_eq!(load_from(&path).unwrap(), vec![record.clone(), record]);
+        assert!(load_from(&dir.path().join("missing.jsonl"))
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,520 @@
+//! Submit jobs from Rust (`JobBuilder`)
+//!
+//! The library counterpart of `runctl run --detach`: describe a job, submit
+//! it to a registered provider, and get back a [`JobHandle`] for checking
+//! status, streaming logs, waiting and cancelling. Launch progress is saved
+//! as a `TrainingSession` exactly as `runctl run` does, so jobs submitted
+//! from Rust show up in `runctl jobs recover` if the caller dies mid-launch,
+//! and a handle can be re-attached later from its session ID.
+//!
+//! ```rust,no_run
+//! use runctl::jobs::{CheckpointPolicy, JobBuilder};
+//! use runctl::Config;
+//! use std::time::Duration;
+//!
+//! # async fn example() -> runctl::error::Result<()> {
+//! let config = Config::load(None)?;
+//! let mut job = JobBuilder::new("aws", "train.py")
+//!     .instance_type("g5.xlarge")
+//!     .spot(true)
+//!     .args(["--epochs", "10"])
+//!     .env("WANDB_PROJECT", "demo")
+//!     .data("s3://bucket/datasets/demo/")
+//!     .checkpoints(CheckpointPolicy::new("checkpoints").sync_to("s3://bucket/ckpt/demo/"))
+//!     .submit(&config)
+//!     .await?;
+//!
+//! println!("{} running on {}", job.session_id(), job.resource_id());
+//! job.wait(Duration::from_secs(30), Some(Duration::from_secs(6 * 3600))).await?;
+//! job.cancel().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+#[cfg(feature = "aws")]
+use crate::checkpoint::{CheckpointStore, S3CheckpointStore};
+use crate::config::{Config, RetentionConfig};
+use crate::error::{JobOutcome, Result, TrainctlError};
+use crate::provider::{
+    CreateResourceOptions, ExecutionStatus, ResourceId, ResourceStatus, TrainingJob,
+    TrainingProvider,
+};
+use crate::providers::ProviderRegistry;
+use crate::training::{LaunchStage, TrainingSession, TrainingStatus};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Where a job writes checkpoints and what happens to them
+#[derive(Debug, Clone, Default)]
+pub struct CheckpointPolicy {
+    /// Checkpoint directory, relative to the project directory on the resource
+    pub dir: PathBuf,
+    /// S3 prefix checkpoints are uploaded to (`s3://bucket/prefix/`)
+    pub dest: Option<String>,
+    /// Which checkpoints `JobHandle::prune_checkpoints` keeps in `dest`
+    pub retention: RetentionConfig,
+}
+
+impl CheckpointPolicy {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Upload checkpoints to an S3 prefix
+    pub fn sync_to(mut self, dest: impl Into<String>) -> Self {
+        self.dest = Some(dest.into());
+        self
+    }
+
+    pub fn retention(mut self, retention: RetentionConfig) -> Self {
+        self.retention = retention;
+        self
+    }
+}
+
+/// A training job to submit
+#[derive(Debug, Clone)]
+pub struct JobBuilder {
+    provider: String,
+    script: PathBuf,
+    args: Vec<String>,
+    env: Vec<(String, String)>,
+    instance_type: Option<String>,
+    spot: bool,
+    spot_max_price: Option<String>,
+    image: Option<String>,
+    disk_gb: Option<u32>,
+    data: Option<String>,
+    output: Option<String>,
+    checkpoints: Option<CheckpointPolicy>,
+    project: Option<String>,
+    tags: Vec<(String, String)>,
+    sessions_dir: PathBuf,
+}
+
+impl JobBuilder {
+    /// Job running `script` on a registered provider (aws, runpod, azure, batch, k8s, lyceum)
+    pub fn new(provider: impl Into<String>, script: impl Into<PathBuf>) -> Self {
+        Self {
+            provider: provider.into(),
+            script: script.into(),
+            args: Vec::new(),
+            env: Vec::new(),
+            instance_type: None,
+            spot: false,
+            spot_max_price: None,
+            image: None,
+            disk_gb: None,
+            data: None,
+            output: None,
+            checkpoints: None,
+            project: None,
+            tags: Vec::new(),
+            sessions_dir: PathBuf::from(".runctl"),
+        }
+    }
+
+    /// Provider-specific type (EC2 instance type, RunPod GPU, Azure VM size);
+    /// defaults to the provider's default
+    pub fn instance_type(mut self, instance_type: impl Into<String>) -> Self {
+        self.instance_type = Some(instance_type.into());
+        self
+    }
+
+    pub fn spot(mut self, spot: bool) -> Self {
+        self.spot = spot;
+        self
+    }
+
+    pub fn spot_max_price(mut self, price: impl Into<String>) -> Self {
+        self.spot_max_price = Some(price.into());
+        self
+    }
+
+    /// Machine image or container image, where the provider supports one
+    pub fn image(mut self, image: impl Into<String>) -> Self {
+        self.image = Some(image.into());
+        self
+    }
+
+    pub fn disk_gb(mut self, disk_gb: u32) -> Self {
+        self.disk_gb = Some(disk_gb);
+        self
+    }
+
+    /// Append one script argument
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Append script arguments
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set an environment variable for the training script; `secret://NAME`
+    /// values are resolved when the job is submitted
+    pub fn env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((name.into(), value.into()));
+        self
+    }
+
+    /// Dataset to download before training (`s3://...`)
+    pub fn data(mut self, source: impl Into<String>) -> Self {
+        self.data = Some(source.into());
+        self
+    }
+
+    /// Where outputs are uploaded when training ends (`s3://...`)
+    pub fn output(mut self, dest: impl Into<String>) -> Self {
+        self.output = Some(dest.into());
+        self
+    }
+
+    pub fn checkpoints(mut self, policy: CheckpointPolicy) -> Self {
+        self.checkpoints = Some(policy);
+        self
+    }
+
+    /// Project the resource is billed to (default: from config or directory)
+    pub fn project(mut self, project: impl Into<String>) -> Self {
+        self.project = Some(project.into());
+        self
+    }
+
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.push((key.into(), value.into()));
+        self
+    }
+
+    /// Where the session is saved (default: `.runctl`)
+    pub fn sessions_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.sessions_dir = dir.into();
+        self
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !self.script.exists() {
+            return Err(TrainctlError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Script not found: {}", self.script.display()),
+            )));
+        }
+        if let Some((name, _)) = self
+            .env
+            .iter()
+            .find(|(name, _)| !crate::utils::is_env_name(name))
+        {
+            return Err(TrainctlError::Validation {
+                field: "env".to_string(),
+                reason: format!("'{}' is not a valid environment variable name", name),
+            });
+        }
+        let s3_paths = [
+            ("data", self.data.as_deref()),
+            ("output", self.output.as_deref()),
+            (
+                "checkpoints",
+                self.checkpoints.as_ref().and_then(|c| c.dest.as_deref()),
+            ),
+        ];
+        for (field, path) in s3_paths
+            .into_iter()
+            .filter_map(|(field, path)| Some((field, path?)))
+        {
+            crate::utils::parse_s3_path(path).map_err(|e| TrainctlError::Validation {
+                field: field.to_string(),
+                reason: e.to_string(),
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Provision a resource on the configured provider and start training
+    pub async fn submit(self, config: &Config) -> Result<JobHandle> {
+        // Fail fast, before providers are set up
+        self.validate()?;
+        let registry = ProviderRegistry::from_config(config).await?;
+        let provider = registry.select(&self.provider)?;
+        self.submit_to(provider, config).await
+    }
+
+    /// Submit to a specific provider instance instead of the registry's
+    ///
+    /// A resource that was created but failed to start training is
+    /// terminated before the error is returned.
+    pub async fn submit_to(
+        self,
+        provider: Arc<dyn TrainingProvider>,
+        config: &Config,
+    ) -> Result<JobHandle> {
+        self.validate()?;
+        // Resolve secret:// values before anything is provisioned
+        let environment = crate::secrets::resolve_env(&self.env, config)?;
+        let instance_type = self
+            .instance_type
+            .clone()
+            .or_else(|| provider.default_instance_type())
+            .ok_or_else(|| TrainctlError::Validation {
+                field: "instance_type".to_string(),
+                reason: format!(
+                    "{} has no default instance type; set one with JobBuilder::instance_type",
+                    provider.name()
+                ),
+            })?;
+
+        let checkpoint_dir = self
+            .checkpoints
+            .as_ref()
+            .map(|c| c.dir.clone())
+            .unwrap_or_else(|| config.checkpoint.dir.clone());
+        let mut session = TrainingSession::launching(
+            provider.name().to_string(),
+            self.script.clone(),
+            checkpoint_dir.clone(),
+            instance_type.clone(),
+            self.spot,
+            self.args.clone(),
+        );
+        record_session(&session, &self.sessions_dir);
+
+        let project = crate::utils::get_project_name(self.project.clone(), config);
+        let mut tags = vec![("runctl:project".to_string(), project)];
+        tags.extend(self.tags.iter().cloned());
+        let create_options = CreateResourceOptions {
+            use_spot: self.spot,
+            spot_max_price: self.spot_max_price.clone(),
+            image: self.image.clone(),
+            disk_gb: self.disk_gb,
+            tags,
+            ..Default::default()
+        };
+        let resource_id = match provider
+            .create_resource(&instance_type, create_options)
+            .await
+        {
+            Ok(id) => id,
+            Err(e) => {
+                session.status = TrainingStatus::Failed(e.to_string());
+                record_session(&session, &self.sessions_dir);
+                return Err(e);
+            }
+        };
+        session.advance(LaunchStage::ResourceCreated, Some(resource_id.clone()));
+        record_session(&session, &self.sessions_dir);
+
+        let checkpoint_dest = self.checkpoints.as_ref().and_then(|c| c.dest.clone());
+        let job = TrainingJob {
+            script: self.script.clone(),
+            args: self.args.clone(),
+            data_source: self.data.clone(),
+            output_dest: self.output.clone().or_else(|| checkpoint_dest.clone()),
+            checkpoint_dir: Some(checkpoint_dir),
+            environment,
+        };
+        if let Err(e) = provider.train(&resource_id, job).await {
+            session.status = TrainingStatus::Failed(e.to_string());
+            record_session(&session, &self.sessions_dir);
+            if let Err(terminate_error) = provider.terminate(&resource_id).await {
+                warn!(
+                    "Failed to terminate {} after a failed start: {}",
+                    resource_id, terminate_error
+                );
+            }
+            return Err(e);
+        }
+        session.advance(LaunchStage::TrainingStarted, None);
+        record_session(&session, &self.sessions_dir);
+
+        Ok(JobHandle {
+            provider,
+            resource_id,
+            session,
+            sessions_dir: self.sessions_dir,
+            checkpoints: self.checkpoints,
+        })
+    }
+}
+
+/// A submitted job
+pub struct JobHandle {
+    provider: Arc<dyn TrainingProvider>,
+    resource_id: ResourceId,
+    session: TrainingSession,
+    sessions_dir: PathBuf,
+    #[cfg_attr(not(feature = "aws"), allow(dead_code))]
+    checkpoints: Option<CheckpointPolicy>,
+}
+
+impl std::fmt::Debug for JobHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JobHandle")
+            .field("provider", &self.provider.name())
+            .field("resource_id", &self.resource_id)
+            .field("session_id", &self.session.id)
+            .finish()
+    }
+}
+
+impl JobHandle {
+    /// Handle for a job submitted earlier, from its saved session
+    pub async fn attach(config: &Config, sessions_dir: &Path, session_id: &str) -> Result<Self> {
+        let session = TrainingSession::load(sessions_dir, session_id)?;
+        let registry = ProviderRegistry::from_config(config).await?;
+        let provider = registry.select(&session.platform)?;
+        Self::from_session(provider, session, sessions_dir)
+    }
+
+    /// Handle for a saved session on a specific provider instance
+    pub fn from_session(
+        provider: Arc<dyn TrainingProvider>,
+        session: TrainingSession,
+        sessions_dir: &Path,
+    ) -> Result<Self> {
+        let resource_id = session.resource_id().map(String::from).ok_or_else(|| {
+            TrainctlError::ResourceNotFound {
+                resource_type: "job resource".to_string(),
+                resource_id: session.id.clone(),
+            }
+        })?;
+        Ok(Self {
+            provider,
+            resource_id,
+            session,
+            sessions_dir: sessions_dir.to_path_buf(),
+            checkpoints: None,
+        })
+    }
+
+    /// Resource the job runs on (the ID `runctl watch` and `jobs wait` take)
+    pub fn resource_id(&self) -> &str {
+        &self.resource_id
+    }
+
+    /// Session ID in `.runctl/sessions`
+    pub fn session_id(&self) -> &str {
+        &self.session.id
+    }
+
+    pub fn provider(&self) -> &str {
+        self.provider.name()
+    }
+
+    pub fn session(&self) -> &TrainingSession {
+        &self.session
+    }
+
+    /// Current status of the training script
+    pub async fn status(&self) -> Result<ExecutionStatus> {
+        self.provider.training_status(&self.resource_id).await
+    }
+
+    /// Current state and cost of the resource
+    pub async fn resource_status(&self) -> Result<ResourceStatus> {
+        self.provider.get_resource_status(&self.resource_id).await
+    }
+
+    /// Print the training log; with `follow`, keep streaming it
+    pub async fn logs(&self, follow: bool) -> Result<()> {
+        self.provider.monitor(&self.resource_id, follow).await
+    }
+
+    /// Poll every `interval` until training finishes or `timeout` passes
+    ///
+    /// Records the outcome in the session. Fails with `TrainctlError::Job`
+    /// when the job fails, is cancelled or times out; a timed-out job keeps
+    /// running.
+    pub async fn wait(&mut self, interval: Duration, timeout: Option<Duration>) -> Result<()> {
+        let started = Instant::now();
+        let outcome = loop {
+            match self.status().await? {
+                ExecutionStatus::Pending | ExecutionStatus::Running => {
+                    let elapsed = started.elapsed();
+                    if timeout.is_some_and(|t| elapsed >= t) {
+                        break Err((
+                            JobOutcome::TimedOut,
+                            format!(
+                                "still running after {}",
+                                crate::utils::format_duration(elapsed.as_secs())
+                            ),
+                        ));
+                    }
+                }
+                ExecutionStatus::Completed => break Ok(()),
+                ExecutionStatus::Failed(reason) => break Err((JobOutcome::Failed, reason)),
+                ExecutionStatus::Cancelled => {
+                    break Err((JobOutcome::Interrupted, "cancelled".to_string()))
+                }
+            }
+            tokio::time::sleep(interval).await;
+        };
+
+        match &outcome {
+            Ok(()) => self.set_status(TrainingStatus::Completed),
+            Err((JobOutcome::TimedOut, _)) => {}
+            Err((JobOutcome::Interrupted, _)) => self.set_status(TrainingStatus::Interrupted),
+            Err((_, reason)) => self.set_status(TrainingStatus::Failed(reason.clone())),
+        }
+        outcome.map_err(|(outcome, message)| TrainctlError::Job {
+            job_id: self.resource_id.clone(),
+            outcome,
+            message,
+        })
+    }
+
+    /// Stop the job by terminating its resource
+    pub async fn cancel(&mut self) -> Result<()> {
+        self.provider.terminate(&self.resource_id).await?;
+        if matches!(
+            self.session.status,
+            TrainingStatus::Launching | TrainingStatus::Running
+        ) {
+            self.set_status(TrainingStatus::Interrupted);
+        }
+        Ok(())
+    }
+
+    /// Store holding the job's uploaded checkpoints, if it syncs them to S3
+    #[cfg(feature = "aws")]
+    pub async fn checkpoint_store(&self) -> Result<Option<S3CheckpointStore>> {
+        match self.checkpoints.as_ref().and_then(|c| c.dest.as_deref()) {
+            Some(dest) => Ok(Some(S3CheckpointStore::from_env(dest).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Delete uploaded checkpoints the policy's retention rules don't keep
+    ///
+    /// Returns the names of the deleted checkpoints.
+    #[cfg(feature = "aws")]
+    pub async fn prune_checkpoints(&self) -> Result<Vec<String>> {
+        let (Some(policy), Some(store)) = (&self.checkpoints, self.checkpoint_store().await?)
+        else {
+            return Ok(Vec::new());
+        };
+        store.prune(&policy.retention).await
+    }
+
+    fn set_status(&mut self, status: TrainingStatus) {
+        self.session.status = status;
+        record_session(&self.session, &self.sessions_dir);
+    }
+}
+
+/// Save launch progress; a failed write must not fail the submission
+fn record_session(session: &TrainingSession, sessions_dir: &Path) {
+    if let Err(e) = session.save(sessions_dir) {
+        warn!("Failed to save session {}: {}", session.id, e);
+    }
+}
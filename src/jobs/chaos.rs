@@ -0,0 +1,477 @@
+//! Spot interruption drills (`runctl chaos interrupt`)
+//!
+//! Does to a running job what EC2 does when it reclaims a spot instance, so
+//! checkpointing and auto-resume can be verified before a week-long run
+//! depends on them:
+//!
+//! 1. **Signal**: SIGTERM the training process (the same graceful stop used
+//!    for real interruptions and migrations), wait up to the grace period,
+//!    and check that a checkpoint was written after the signal
+//! 2. **Stage**: copy that checkpoint to
+//!    `s3://<bucket>/checkpoints/spot-interruptions/<id>/`, where
+//!    `aws auto-resume` looks for it
+//! 3. **Terminate**: terminate the instance, without the usual safety checks
+//! 4. **Resume** (with `--resume <script>`): run `aws auto-resume`, which
+//!    creates a replacement spot instance and restarts the script there
+//!
+//! The drill records the same timeline events as a real interruption and
+//! ends with a verdict. It fails (non-zero exit) if the job ignored the
+//! signal, was killed, left no fresh checkpoint, or could not be resumed.
+
+use super::migrate::{
+    aws_home, parse_stop_report, stop_and_checkpoint_script, validate_checkpoint_dir, StopReport,
+};
+use crate::aws::{
+    find_instance_in_response, get_project_name, handle_auto_resume_command,
+    interruption_checkpoint_prefix, terminate_instance,
+};
+use crate::aws_utils::execute_ssm_command;
+use crate::config::Config;
+use crate::error::{Result, TrainctlError};
+use crate::events::{Event, EventKind};
+use crate::utils::shell_quote;
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_ssm::Client as SsmClient;
+use clap::Subcommand;
+use serde::Serialize;
+use std::path::PathBuf;
+
+#[derive(Subcommand, Clone)]
+pub enum ChaosCommands {
+    /// Simulate a spot interruption of a running job
+    ///
+    /// Sends SIGTERM so the script can save a checkpoint, waits up to the
+    /// grace period (EC2 gives two minutes), stages the newest checkpoint
+    /// where `aws auto-resume` finds it, and terminates the instance. With
+    /// --resume, then runs auto-resume on a replacement spot instance.
+    ///
+    /// Fails if the job did not exit on SIGTERM, left no checkpoint written
+    /// after the signal, or could not be resumed.
+    ///
+    /// Examples:
+    ///   runctl chaos interrupt i-1234567890abcdef0 --dry-run
+    ///   runctl chaos interrupt i-1234567890abcdef0 --resume train.py
+    ///   runctl --yes chaos interrupt i-1234567890abcdef0 --grace-period 60
+    Interrupt {
+        /// Job to interrupt (EC2 instance ID)
+        #[arg(value_name = "JOB_ID")]
+        job_id: String,
+
+        /// Checkpoint directory, relative to the project directory
+        #[arg(long, value_name = "DIR", default_value = "checkpoints")]
+        checkpoint_dir: String,
+
+        /// Project name (default: instance tags, then current directory)
+        #[arg(long, value_name = "NAME")]
+        project_name: Option<String>,
+
+        /// Seconds to wait for the job to exit after SIGTERM
+        #[arg(long, value_name = "SECS", default_value = "120")]
+        grace_period: u64,
+
+        /// Resume with this script on a replacement instance afterwards
+        #[arg(long, value_name = "SCRIPT")]
+        resume: Option<PathBuf>,
+
+        /// Show what the drill would do without touching the job
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct InterruptOptions {
+    pub instance_id: String,
+    pub checkpoint_dir: String,
+    pub project_name: Option<String>,
+    pub grace_period: u64,
+    pub resume: Option<PathBuf>,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct DrillReport {
+    instance_id: String,
+    spot: bool,
+    was_running: bool,
+    forced: bool,
+    checkpoint: Option<String>,
+    checkpoint_fresh: bool,
+    staged: Option<String>,
+    terminated: bool,
+    resumed_with: Option<PathBuf>,
+    problems: Vec<String>,
+    passed: bool,
+}
+
+/// What the stop showed to be missing from the job's interruption handling
+fn stop_problems(report: &StopReport, checkpoint_dir: &str, grace_period: u64) -> Vec<String> {
+    let mut problems = Vec::new();
+    if !report.was_running {
+        problems.push("No training process was running, so nothing was signaled".to_string());
+        return problems;
+    }
+    if report.forced {
+        problems.push(format!(
+            "The job did not exit within {}s of SIGTERM and was killed; exit once the checkpoint is saved",
+            grace_period
+        ));
+    }
+    match &report.checkpoint {
+        None => problems.push(format!(
+            "No checkpoint (*.pt, *.pth, *.ckpt, *.safetensors) found in {}/",
+            checkpoint_dir
+        )),
+        Some(checkpoint) if !report.fresh => problems.push(format!(
+            "The newest checkpoint ({}) predates the SIGTERM; save one in the signal handler \
+             or a resume loses the progress since then",
+            checkpoint
+        )),
+        Some(_) => {}
+    }
+    problems
+}
+
+pub async fn handle_chaos_command(
+    cmd: ChaosCommands,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    match cmd {
+        ChaosCommands::Interrupt {
+            job_id,
+            checkpoint_dir,
+            project_name,
+            grace_period,
+            resume,
+            dry_run,
+        } => {
+            let options = InterruptOptions {
+                instance_id: job_id,
+                checkpoint_dir,
+                project_name,
+                grace_period,
+                resume,
+                dry_run,
+            };
+            interrupt(options, config, output_format).await
+        }
+    }
+}
+
+fn step(text: bool, n: u32, total: u32, message: &str) {
+    if text {
+        println!("[{}/{}] {}", n, total, message);
+    }
+}
+
+/// Run an interruption drill against a running EC2 job
+pub(crate) async fn interrupt(
+    options: InterruptOptions,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    let text = output_format != "json";
+    let instance_id = options.instance_id.as_str();
+
+    // Preflight: nothing below may fail after the job has been signaled
+    crate::validation::validate_instance_id(instance_id)?;
+    validate_checkpoint_dir(&options.checkpoint_dir)?;
+    if let Some(script) = &options.resume {
+        if !script.exists() {
+            return Err(TrainctlError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Script not found: {}", script.display()),
+            )));
+        }
+    }
+    let aws_config = crate::aws_utils::shared_sdk_config().await;
+    let ec2 = Ec2Client::new(&aws_config);
+    let response = ec2
+        .describe_instances()
+        .instance_ids(instance_id)
+        .send()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to describe instance: {}", e)))?;
+    let instance = find_instance_in_response(&response, instance_id).ok_or_else(|| {
+        TrainctlError::ResourceNotFound {
+            resource_type: "instance".to_string(),
+            resource_id: instance_id.to_string(),
+        }
+    })?;
+    let state = instance
+        .state()
+        .and_then(|s| s.name())
+        .map(|s| s.as_str().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    if state != "running" {
+        return Err(TrainctlError::Validation {
+            field: "job_id".to_string(),
+            reason: format!(
+                "Instance {} is {}; a drill needs a running job",
+                instance_id, state
+            ),
+        });
+    }
+    let spot = instance.spot_instance_request_id().is_some();
+    let (user, tag_project) = aws_home(&ec2, instance_id).await?;
+    let project = options
+        .project_name
+        .clone()
+        .or(tag_project)
+        .unwrap_or_else(|| get_project_name(None, config));
+    let project_dir = format!("/home/{}/{}", user, project);
+    let bucket = config.aws.as_ref().and_then(|c| c.s3_bucket.clone());
+    let total = if options.resume.is_some() { 4 } else { 3 };
+
+    if options.dry_run {
+        if text {
+            println!("Interruption drill for {}:", instance_id);
+            println!(
+                "  1. Send SIGTERM, wait up to {}s, and find the newest checkpoint in {}/{}/",
+                options.grace_period, project_dir, options.checkpoint_dir
+            );
+            match &bucket {
+                Some(bucket) => println!(
+                    "  2. Stage it at s3://{}/{}",
+                    bucket,
+                    interruption_checkpoint_prefix(instance_id)
+                ),
+                None => println!("  2. Skip staging: [aws] s3_bucket is not set"),
+            }
+            println!("  3. Terminate {}", instance_id);
+            if let Some(script) = &options.resume {
+                println!(
+                    "  4. Resume {} on a replacement spot instance",
+                    script.display()
+                );
+            }
+            if !spot {
+                println!();
+                println!(
+                    "NOTE: {} is not a spot instance; `aws train` only watches spot instances for interruptions",
+                    instance_id
+                );
+            }
+            println!();
+            println!("DRY RUN: nothing was changed");
+        } else {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&serde_json::json!({
+                    "instance_id": instance_id,
+                    "spot": spot,
+                    "project_dir": project_dir,
+                    "checkpoint_dir": options.checkpoint_dir,
+                    "grace_period": options.grace_period,
+                    "stage_prefix": bucket.as_ref().map(|b| format!(
+                        "s3://{}/{}",
+                        b,
+                        interruption_checkpoint_prefix(instance_id)
+                    )),
+                    "resume": options.resume,
+                    "dry_run": true,
+                }))?
+            );
+        }
+        return Ok(());
+    }
+
+    if !crate::prompt::confirm(&format!(
+        "Interrupt the job on {} and terminate the instance?",
+        instance_id
+    ))? {
+        if text {
+            println!("Cancelled");
+        }
+        return Ok(());
+    }
+
+    crate::events::record(
+        Event::new(instance_id, EventKind::SpotWarning).detail("simulated by runctl chaos"),
+    );
+
+    // 1. Signal the job and find its checkpoint
+    step(
+        text,
+        1,
+        total,
+        &format!(
+            "Sending SIGTERM to the job (up to {}s)",
+            options.grace_period
+        ),
+    );
+    let ssm = SsmClient::new(&aws_config);
+    let script =
+        stop_and_checkpoint_script(&project_dir, &options.checkpoint_dir, options.grace_period);
+    let stop = parse_stop_report(&execute_ssm_command(&ssm, instance_id, &script).await?);
+    let mut problems = stop_problems(&stop, &options.checkpoint_dir, options.grace_period);
+    if let Some(checkpoint) = &stop.checkpoint {
+        crate::events::record(
+            Event::new(instance_id, EventKind::CheckpointSaved)
+                .detail(format!("{} (before interruption)", checkpoint)),
+        );
+    }
+
+    // 2. Stage the checkpoint where auto-resume looks for it
+    let mut staged = None;
+    match (&stop.checkpoint, &bucket) {
+        (Some(checkpoint), Some(bucket)) => {
+            step(text, 2, total, &format!("Staging {}", checkpoint));
+            let name = checkpoint.rsplit('/').next().unwrap_or(checkpoint);
+            let uri = format!(
+                "s3://{}/{}{}",
+                bucket,
+                interruption_checkpoint_prefix(instance_id),
+                name
+            );
+            let cmd = format!(
+                "cd {} && aws s3 cp {} {}",
+                shell_quote(&project_dir),
+                shell_quote(checkpoint),
+                shell_quote(&uri)
+            );
+            match execute_ssm_command(&ssm, instance_id, &cmd).await {
+                Ok(_) => staged = Some(uri),
+                Err(e) => problems.push(format!("Could not copy the checkpoint to S3: {}", e)),
+            }
+        }
+        (Some(_), None) => {
+            step(text, 2, total, "Skipping staging");
+            problems.push(
+                "[aws] s3_bucket is not set, so auto-resume cannot find the checkpoint".to_string(),
+            );
+        }
+        (None, _) => step(text, 2, total, "Nothing to stage"),
+    }
+
+    // 3. Terminate, as EC2 would
+    step(text, 3, total, &format!("Terminating {}", instance_id));
+    terminate_instance(
+        instance_id.to_string(),
+        true,
+        &aws_config,
+        output_format,
+        config,
+    )
+    .await?;
+
+    // 4. Resume on a replacement
+    if let Some(script) = &options.resume {
+        step(text, 4, total, "Resuming on a replacement spot instance");
+        if let Err(e) = handle_auto_resume_command(
+            instance_id.to_string(),
+            script.clone(),
+            staged.clone(),
+            config,
+            &aws_config,
+            output_format,
+        )
+        .await
+        {
+            problems.push(format!("Auto-resume failed: {}", e));
+        }
+    }
+
+    let report = DrillReport {
+        instance_id: instance_id.to_string(),
+        spot,
+        was_running: stop.was_running,
+        forced: stop.forced,
+        checkpoint: stop.checkpoint.clone(),
+        checkpoint_fresh: stop.fresh,
+        staged,
+        terminated: true,
+        resumed_with: options.resume.clone(),
+        passed: problems.is_empty(),
+        problems,
+    };
+    if text {
+        println!();
+        println!(
+            "Drill {} for {}",
+            if report.passed { "PASSED" } else { "FAILED" },
+            instance_id
+        );
+        if let Some(checkpoint) = &report.checkpoint {
+            println!("  Checkpoint: {}", checkpoint);
+        }
+        if let Some(uri) = &report.staged {
+            println!("  Staged:     {}", uri);
+        }
+        for problem in &report.problems {
+            println!("  - {}", problem);
+        }
+        if report.resumed_with.is_none() && report.passed {
+            println!(
+                "  Resume:     runctl aws auto-resume {} <script> --checkpoint {}",
+                instance_id,
+                report.staged.as_deref().unwrap_or("<checkpoint>")
+            );
+        }
+        println!("  Timeline:   runctl aws timeline {}", instance_id);
+    } else {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    }
+
+    if !report.passed {
+        return Err(TrainctlError::Resource {
+            resource_type: "job".to_string(),
+            operation: "interrupt drill".to_string(),
+            resource_id: Some(instance_id.to_string()),
+            message: format!(
+                "{} problem(s) with interruption handling: {}",
+                report.problems.len(),
+                report.problems.join("; ")
+            ),
+            source: None,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(
+        was_running: bool,
+        forced: bool,
+        checkpoint: Option<&str>,
+        fresh: bool,
+    ) -> StopReport {
+        StopReport {
+            was_running,
+            forced,
+            checkpoint: checkpoint.map(str::to_string),
+            fresh,
+        }
+    }
+
+    #[test]
+    fn test_graceful_stop_with_fresh_checkpoint_passes() {
+        let stop = report(true, false, Some("checkpoints/epoch_3.pt"), true);
+        assert!(stop_problems(&stop, "checkpoints", 120).is_empty());
+    }
+
+    #[test]
+    fn test_stop_problems() {
+        assert_eq!(
+            stop_problems(&report(false, false, None, false), "checkpoints", 120).len(),
+            1
+        );
+
+        let problems = stop_problems(&report(true, true, None, false), "ckpt", 60);
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].contains("within 60s"));
+        assert!(problems[1].contains("ckpt/"));
+
+        let problems = stop_problems(
+            &report(true, false, Some("checkpoints/epoch_1.pt"), false),
+            "checkpoints",
+            120,
+        );
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("predates the SIGTERM"));
+    }
+}
@@ -0,0 +1,288 @@
+//! Post-mortem bundles (`runctl jobs collect-debug`)
+//!
+//! Gathers what is needed to work out why a job failed into one tarball that
+//! can be attached to a bug report or handed to a teammate:
+//!
+//! - from the instance (see `aws::debug`): its EC2 description and console
+//!   output, and while it runs, the training log tail, dmesg, nvidia-smi, the
+//!   user-data log, pip freeze, disk usage and processes
+//! - from this machine: the local session, the event timeline, the applied
+//!   manifest, and for local jobs the training log
+//!
+//! Whatever can't be collected is listed in `SKIPPED.txt` in the bundle.
+
+use crate::config::Config;
+use crate::error::{Result, TrainctlError};
+use crate::training::TrainingSession;
+use chrono::Utc;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// A file for the bundle
+#[derive(Debug, Clone)]
+pub(crate) struct DebugFile {
+    pub name: String,
+    pub content: Vec<u8>,
+}
+
+impl DebugFile {
+    pub fn new(name: &str, content: impl Into<Vec<u8>>) -> Self {
+        Self {
+            name: name.to_string(),
+            content: content.into(),
+        }
+    }
+}
+
+/// A file that could not be collected, and why
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Skipped {
+    pub name: String,
+    pub reason: String,
+}
+
+impl Skipped {
+    pub fn new(name: &str, reason: impl ToString) -> Self {
+        Self {
+            name: name.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CollectOptions {
+    /// EC2 instance ID or local session ID (prefix)
+    pub job_id: String,
+    /// Bundle path (default: `runctl-debug-<id>-<time>.tar.gz` here)
+    pub output: Option<PathBuf>,
+    /// Lines kept from each log
+    #[cfg_attr(not(feature = "aws"), allow(dead_code))]
+    pub lines: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct CollectResult {
+    path: PathBuf,
+    files: Vec<String>,
+    skipped: Vec<Skipped>,
+}
+
+/// Write `files` into a gzipped tarball under the directory `prefix`
+pub(crate) fn write_bundle(path: &Path, prefix: &str, files: &[DebugFile]) -> Result<()> {
+    let encoder = GzEncoder::new(File::create(path)?, Compression::default());
+    let mut tar = tar::Builder::new(encoder);
+    let mtime = Utc::now().timestamp().max(0) as u64;
+    for file in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(file.content.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(mtime);
+        header.set_cksum();
+        tar.append_data(
+            &mut header,
+            format!("{}/{}", prefix, file.name),
+            file.content.as_slice(),
+        )?;
+    }
+    tar.into_inner()?.finish()?;
+    Ok(())
+}
+
+/// `SKIPPED.txt`: one line per file that couldn't be collected
+fn skipped_report(skipped: &[Skipped]) -> String {
+    skipped
+        .iter()
+        .map(|s| format!("{}: {}\n", s.name, s.reason))
+        .collect()
+}
+
+/// Local files about the job; returns the instance to collect from, if any
+fn collect_local(
+    job_id: &str,
+    sessions_dir: &Path,
+    files: &mut Vec<DebugFile>,
+    skipped: &mut Vec<Skipped>,
+) -> Result<(String, Option<String>)> {
+    let (label, instance_id) = if job_id.starts_with("i-") {
+        crate::validation::validate_instance_id(job_id)?;
+        let session = TrainingSession::list_sessions(sessions_dir)?
+            .into_iter()
+            .filter(|s| s.launch.as_ref().and_then(|l| l.resource_id.as_deref()) == Some(job_id))
+            .max_by_key(|s| s.started_at);
+        if let Some(session) = session {
+            files.push(DebugFile::new(
+                "session.json",
+                serde_json::to_string_pretty(&session)?,
+            ));
+        }
+        (job_id.to_string(), Some(job_id.to_string()))
+    } else {
+        let session = crate::watch::resolve_local_session(sessions_dir, job_id)?;
+        files.push(DebugFile::new(
+            "session.json",
+            serde_json::to_string_pretty(&session)?,
+        ));
+        let resource_id = session.launch.as_ref().and_then(|l| l.resource_id.clone());
+        if session.platform == "local" {
+            match &session.log_file {
+                Some(log) => match std::fs::read(log) {
+                    Ok(content) => files.push(DebugFile::new("training.log", content)),
+                    Err(e) => skipped.push(Skipped::new("training.log", e)),
+                },
+                None => skipped.push(Skipped::new("training.log", "session has no log file")),
+            }
+        } else if resource_id.is_none() {
+            skipped.push(Skipped::new(
+                "instance",
+                "the launch never got as far as creating a resource",
+            ));
+        }
+        let instance_id = resource_id.filter(|id| id.starts_with("i-"));
+        (session.id, instance_id)
+    };
+
+    let timeline_id = instance_id.as_deref().unwrap_or(label.as_str());
+    files.push(DebugFile::new(
+        "timeline.json",
+        serde_json::to_string_pretty(&crate::events::timeline(timeline_id)?)?,
+    ));
+    if let Some(state) = crate::manifest::AppliedState::for_resource(timeline_id)? {
+        files.push(DebugFile::new(
+            "manifest.json",
+            serde_json::to_string_pretty(&state)?,
+        ));
+    }
+    Ok((label, instance_id))
+}
+
+pub async fn collect_debug(
+    options: CollectOptions,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    let text = output_format != "json";
+    let mut files = Vec::new();
+    let mut skipped = Vec::new();
+    let (label, instance_id) = collect_local(
+        &options.job_id,
+        Path::new(".runctl"),
+        &mut files,
+        &mut skipped,
+    )?;
+
+    #[cfg(feature = "aws")]
+    if let Some(instance_id) = &instance_id {
+        if text {
+            println!("Collecting from {}...", instance_id);
+        }
+        let aws_config = crate::aws_utils::shared_sdk_config().await;
+        let (remote, remote_skipped) =
+            crate::aws::collect_instance(instance_id, options.lines, config, &aws_config).await?;
+        files.extend(remote);
+        skipped.extend(remote_skipped);
+    }
+    #[cfg(not(feature = "aws"))]
+    if let Some(instance_id) = &instance_id {
+        let _ = config;
+        skipped.push(Skipped::new(instance_id, "built without the `aws` feature"));
+    }
+    if !skipped.is_empty() {
+        files.push(DebugFile::new("SKIPPED.txt", skipped_report(&skipped)));
+    }
+
+    let short_id: String = label.chars().take(19).collect();
+    let prefix = format!(
+        "runctl-debug-{}-{}",
+        short_id,
+        Utc::now().format("%Y%m%d-%H%M%S")
+    );
+    let path = options
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("{}.tar.gz", prefix)));
+    write_bundle(&path, &prefix, &files).map_err(|e| {
+        TrainctlError::Io(std::io::Error::other(format!(
+            "Failed to write {}: {}",
+            path.display(),
+            e
+        )))
+    })?;
+
+    let result = CollectResult {
+        path,
+        files: files.iter().map(|f| f.name.clone()).collect(),
+        skipped,
+    };
+    if text {
+        println!(
+            "Wrote {} ({} files)",
+            result.path.display(),
+            result.files.len()
+        );
+        for s in &result.skipped {
+            println!("   skipped {}: {}", s.name, s.reason);
+        }
+    } else {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn test_write_bundle_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bundle.tar.gz");
+        let files = vec![
+            DebugFile::new("training.log", "epoch 1 loss=0.5\n"),
+            DebugFile::new(
+                "SKIPPED.txt",
+                skipped_report(&[Skipped::new("dmesg.txt", "instance is stopped")]),
+            ),
+        ];
+        write_bundle(&path, "runctl-debug-i-0abc", &files).unwrap();
+
+        let mut archive = tar::Archive::new(GzDecoder::new(File::open(&path).unwrap()));
+        let mut entries: Vec<(String, String)> = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let name = entry.path().unwrap().to_string_lossy().into_owned();
+                let mut content = String::new();
+                entry.read_to_string(&mut content).unwrap();
+                (name, content)
+            })
+            .collect();
+        entries.sort();
+        assert_eq!(
+            entries,
+            vec![
+                (
+                    "runctl-debug-i-0abc/SKIPPED.txt".to_string(),
+                    "dmesg.txt: instance is stopped\n".to_string()
+                ),
+                (
+                    "runctl-debug-i-0abc/training.log".to_string(),
+                    "epoch 1 loss=0.5\n".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collect_local_for_unknown_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut files = Vec::new();
+        let mut skipped = Vec::new();
+        assert!(collect_local("3f2a9c1e", dir.path(), &mut files, &mut skipped).is_err());
+    }
+}
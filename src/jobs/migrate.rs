@@ -0,0 +1,1034 @@
+//! Cross-provider job migration
+//!
+//! `runctl jobs migrate <job-id> --to aws:g5.xlarge` moves a running job when
+//! its current provider runs out of capacity (or gets too expensive):
+//!
+//! 1. **Checkpoint**: SIGTERM the training process (the same graceful stop
+//!    used for spot interruptions), wait for it to exit, and locate the newest
+//!    checkpoint in the checkpoint directory
+//! 2. **Stage**: copy the checkpoint off the source — to S3 for EC2 sources,
+//!    to `.runctl/migrations/<id>/` for RunPod sources
+//! 3. **Provision**: create the target instance or pod
+//! 4. **Transfer**: copy the checkpoint into the target's checkpoint directory
+//! 5. **Resume**: start the script there with `--resume <checkpoint>`; code is
+//!    synced from the local project the same way `aws train` and
+//!    `runpod train` do it
+//!
+//! Prerequisites (local script, `[aws] s3_bucket`, `runpodctl`, `[runpod]
+//! api_key`) are checked before the job is touched. Each step updates
+//! `.runctl/migrations/<id>.json`, so a failed migration records how far it
+//! got and which target it created.
+
+use crate::aws::{
+    create_instance_and_get_id, get_project_name, terminate_instance, train_on_instance,
+    CreateInstanceOptions, TrainInstanceOptions,
+};
+use crate::aws_utils::execute_ssm_command;
+use crate::config::Config;
+use crate::data_transfer::{DataLocation, DataTransfer, TransferOptions};
+use crate::error::{ConfigError, Result, TrainctlError};
+#[cfg(feature = "runpod")]
+use crate::runpod;
+use crate::utils::shell_quote;
+use aws_config::BehaviorVersion;
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_ssm::Client as SsmClient;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Working directory of RunPod jobs started by runctl
+const RUNPOD_WORKSPACE: &str = "/workspace";
+
+/// S3 prefix for checkpoints in transit
+const S3_PREFIX: &str = "runctl-migrations";
+
+/// Default container disk for RunPod targets without `--disk`
+const DEFAULT_RUNPOD_DISK_GB: u32 = 50;
+
+/// Stand-ins for the RunPod helpers in builds without the `runpod` feature
+///
+/// Unreachable in practice: `migrate_job` rejects RunPod jobs up front.
+#[cfg(not(feature = "runpod"))]
+mod runpod {
+    use crate::config::Config;
+    use crate::error::{ConfigError, Result};
+    use std::path::{Path, PathBuf};
+
+    fn not_compiled<T>() -> Result<T> {
+        Err(ConfigError::FeatureNotCompiled("runpod".to_string()).into())
+    }
+
+    pub(super) fn exec_on_pod(_: &str, _: &str) -> Result<String> {
+        not_compiled()
+    }
+
+    pub(super) fn upload_to_pod(_: &str, _: &Path, _: &str) -> Result<()> {
+        not_compiled()
+    }
+
+    pub(super) async fn download_from_pod(_: String, _: PathBuf, _: PathBuf) -> Result<()> {
+        not_compiled()
+    }
+
+    pub(super) async fn create_pod(
+        _: Option<String>,
+        _: String,
+        _: u32,
+        _: &Config,
+    ) -> Result<String> {
+        not_compiled()
+    }
+
+    pub(super) async fn train_on_pod(
+        _: String,
+        _: PathBuf,
+        _: bool,
+        _: &[String],
+        _: &[(String, String)],
+        _: &Config,
+    ) -> Result<()> {
+        not_compiled()
+    }
+
+    pub(super) async fn remove_pod(_: &str, _: &Config) -> Result<()> {
+        not_compiled()
+    }
+}
+
+fn migrations_dir() -> PathBuf {
+    PathBuf::from(".runctl").join("migrations")
+}
+
+/// Provider a job runs on or moves to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetProvider {
+    Aws,
+    Runpod,
+}
+
+impl fmt::Display for TargetProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TargetProvider::Aws => write!(f, "aws"),
+            TargetProvider::Runpod => write!(f, "runpod"),
+        }
+    }
+}
+
+/// Where a migrated job should run, parsed from `PROVIDER:TYPE`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationTarget {
+    pub provider: TargetProvider,
+    /// EC2 instance type or RunPod GPU type
+    pub instance_type: String,
+}
+
+impl MigrationTarget {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let invalid = |reason: String| TrainctlError::Validation {
+            field: "to".to_string(),
+            reason,
+        };
+        let (provider, instance_type) = spec.split_once(':').ok_or_else(|| {
+            invalid(format!(
+                "Expected PROVIDER:TYPE (e.g. aws:g5.xlarge, runpod:RTX 4090), got '{}'",
+                spec
+            ))
+        })?;
+        let instance_type = instance_type.trim();
+        if instance_type.is_empty() {
+            return Err(invalid(format!("Missing instance type in '{}'", spec)));
+        }
+        let provider = match provider.trim().to_lowercase().as_str() {
+            "aws" | "ec2" => {
+                if instance_type.matches('.').count() != 1 || instance_type.contains(' ') {
+                    return Err(invalid(format!(
+                        "'{}' is not an EC2 instance type (expected e.g. g5.xlarge)",
+                        instance_type
+                    )));
+                }
+                TargetProvider::Aws
+            }
+            "runpod" => TargetProvider::Runpod,
+            other => {
+                return Err(invalid(format!(
+                    "Unknown provider '{}' (supported: aws, runpod)",
+                    other
+                )))
+            }
+        };
+        Ok(Self {
+            provider,
+            instance_type: instance_type.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for MigrationTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.provider, self.instance_type)
+    }
+}
+
+/// A running job, identified by the resource it runs on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "provider", content = "id", rename_all = "lowercase")]
+pub enum JobLocation {
+    Aws(String),
+    Runpod(String),
+}
+
+impl JobLocation {
+    /// EC2 instance IDs start with `i-`; anything else is a RunPod pod ID
+    pub fn from_job_id(job_id: &str) -> Result<Self> {
+        if job_id.starts_with("i-") {
+            crate::validation::validate_instance_id(job_id)?;
+            return Ok(JobLocation::Aws(job_id.to_string()));
+        }
+        if !job_id.is_empty()
+            && job_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Ok(JobLocation::Runpod(job_id.to_string()));
+        }
+        Err(TrainctlError::Validation {
+            field: "job_id".to_string(),
+            reason: format!(
+                "'{}' is neither an EC2 instance ID nor a RunPod pod ID",
+                job_id
+            ),
+        })
+    }
+
+    pub fn provider(&self) -> TargetProvider {
+        match self {
+            JobLocation::Aws(_) => TargetProvider::Aws,
+            JobLocation::Runpod(_) => TargetProvider::Runpod,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        match self {
+            JobLocation::Aws(id) | JobLocation::Runpod(id) => id,
+        }
+    }
+}
+
+impl fmt::Display for JobLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.provider(), self.id())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct MigrateOptions {
+    pub source: JobLocation,
+    pub target: MigrationTarget,
+    pub script: PathBuf,
+    pub checkpoint_dir: String,
+    pub resume_arg: String,
+    pub project_name: Option<String>,
+    pub spot: bool,
+    pub disk: Option<u32>,
+    pub grace_period: u64,
+    pub allow_no_checkpoint: bool,
+    pub terminate_source: bool,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum MigrationStatus {
+    Planned,
+    CheckpointSaved,
+    Provisioned,
+    Resumed,
+    Failed,
+}
+
+/// Progress of one migration, persisted after every step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MigrationRecord {
+    id: String,
+    source: JobLocation,
+    target: MigrationTarget,
+    script: PathBuf,
+    status: MigrationStatus,
+    /// Checkpoint path on the source
+    checkpoint: Option<String>,
+    /// Where the checkpoint is staged (S3 URI or local path)
+    staged: Option<String>,
+    /// Instance or pod created for the target
+    target_id: Option<String>,
+    /// Checkpoint path passed to the script on the target
+    resume_path: Option<String>,
+    error: Option<String>,
+    started_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl MigrationRecord {
+    fn new(options: &MigrateOptions) -> Self {
+        let now = Utc::now();
+        Self {
+            id: format!("mig-{}", &uuid::Uuid::new_v4().simple().to_string()[..8]),
+            source: options.source.clone(),
+            target: options.target.clone(),
+            script: options.script.clone(),
+            status: MigrationStatus::Planned,
+            checkpoint: None,
+            staged: None,
+            target_id: None,
+            resume_path: None,
+            error: None,
+            started_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn save(&mut self) -> Result<()> {
+        self.updated_at = Utc::now();
+        let dir = migrations_dir();
+        crate::utils::ensure_dir(&dir)?;
+        std::fs::write(
+            dir.join(format!("{}.json", self.id)),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// A checkpoint copied off the source
+#[derive(Debug, Clone)]
+enum Staged {
+    S3(String),
+    Local(PathBuf),
+}
+
+impl fmt::Display for Staged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Staged::S3(uri) => write!(f, "{}", uri),
+            Staged::Local(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// What the stop-and-checkpoint script reported
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(super) struct StopReport {
+    pub was_running: bool,
+    pub forced: bool,
+    pub checkpoint: Option<String>,
+    /// The checkpoint was written after the SIGTERM
+    pub fresh: bool,
+}
+
+/// Shell script that stops training gracefully and prints the newest checkpoint
+pub(super) fn stop_and_checkpoint_script(
+    project_dir: &str,
+    checkpoint_dir: &str,
+    grace_secs: u64,
+) -> String {
+    let ckpt = shell_quote(checkpoint_dir);
+    format!(
+        r#"cd {dir} 2>/dev/null || {{ echo NO_PROJECT_DIR; exit 0; }}
+PID=""
+[ -f training.pid ] && PID=$(cat training.pid 2>/dev/null)
+if [ -z "$PID" ] || ! ps -p "$PID" >/dev/null 2>&1; then
+    PID=$(pgrep -f "python.*train|python.*main.py" | head -1)
+fi
+if [ -n "$PID" ] && ps -p "$PID" >/dev/null 2>&1; then
+    echo "TRAINING_RUNNING:$PID"
+    SIGNALED=$(date +%s)
+    kill -TERM "$PID" 2>/dev/null || true
+    for i in $(seq 1 {grace}); do
+        ps -p "$PID" >/dev/null 2>&1 || break
+        sleep 1
+    done
+    if ps -p "$PID" >/dev/null 2>&1; then
+        kill -9 "$PID" 2>/dev/null || true
+        echo TRAINING_FORCE_STOPPED
+    fi
+else
+    echo NO_TRAINING
+fi
+LATEST=$(ls -t {ckpt}/*.pt {ckpt}/*.pth {ckpt}/*.ckpt {ckpt}/*.safetensors 2>/dev/null | head -1)
+if [ -n "$LATEST" ]; then echo "CHECKPOINT:$LATEST"; else echo NO_CHECKPOINT; fi
+if [ -n "$LATEST" ] && [ -n "$SIGNALED" ] && [ "$(stat -c %Y "$LATEST" 2>/dev/null || echo 0)" -ge "$SIGNALED" ]; then
+    echo CHECKPOINT_FRESH
+fi
+"#,
+        dir = shell_quote(project_dir),
+        grace = grace_secs.max(1),
+        ckpt = ckpt,
+    )
+}
+
+pub(super) fn parse_stop_report(output: &str) -> StopReport {
+    let mut report = StopReport::default();
+    for line in output.lines().map(str::trim) {
+        if line.starts_with("TRAINING_RUNNING:") {
+            report.was_running = true;
+        } else if line == "TRAINING_FORCE_STOPPED" {
+            report.forced = true;
+        } else if line == "CHECKPOINT_FRESH" {
+            report.fresh = true;
+        } else if let Some(path) = line.strip_prefix("CHECKPOINT:") {
+            report.checkpoint = Some(path.trim().to_string()).filter(|p| !p.is_empty());
+        }
+    }
+    report
+}
+
+/// Reject checkpoint dirs that would escape the project directory
+pub(super) fn validate_checkpoint_dir(dir: &str) -> Result<()> {
+    if dir.is_empty() || dir.starts_with('/') || dir.split('/').any(|p| p == "..") {
+        return Err(TrainctlError::Validation {
+            field: "checkpoint_dir".to_string(),
+            reason: format!(
+                "Must be a path relative to the project directory, got '{}'",
+                dir
+            ),
+        });
+    }
+    Ok(())
+}
+
+fn file_name(path: &str) -> Result<String> {
+    Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(str::to_string)
+        .ok_or_else(|| TrainctlError::Validation {
+            field: "checkpoint".to_string(),
+            reason: format!("Checkpoint path has no file name: '{}'", path),
+        })
+}
+
+/// Login user and project tag of an EC2 instance
+pub(super) async fn aws_home(
+    client: &Ec2Client,
+    instance_id: &str,
+) -> Result<(String, Option<String>)> {
+    let response = client
+        .describe_instances()
+        .instance_ids(instance_id)
+        .send()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to describe instance: {}", e)))?;
+    let instance =
+        crate::aws::find_instance_in_response(&response, instance_id).ok_or_else(|| {
+            TrainctlError::ResourceNotFound {
+                resource_type: "instance".to_string(),
+                resource_id: instance_id.to_string(),
+            }
+        })?;
+    let user = crate::aws::resolve_ssh_user(client, instance, None).await;
+    let tag = |key: &str| {
+        instance
+            .tags()
+            .iter()
+            .find(|t| t.key() == Some(key))
+            .and_then(|t| t.value())
+            .map(str::to_string)
+    };
+    let project = tag("runctl:project").or_else(|| tag("Project"));
+    Ok((user, project))
+}
+
+fn step(text: bool, n: u32, message: &str) {
+    if text {
+        println!("[{}/5] {}", n, message);
+    }
+}
+
+/// Migrate a running job to another provider or instance type
+pub(crate) async fn migrate_job(
+    options: MigrateOptions,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    let text = output_format != "json";
+    let involves_aws = options.source.provider() == TargetProvider::Aws
+        || options.target.provider == TargetProvider::Aws;
+    let involves_runpod = options.source.provider() == TargetProvider::Runpod
+        || options.target.provider == TargetProvider::Runpod;
+
+    // Preflight: nothing below may fail after the job has been stopped
+    validate_checkpoint_dir(&options.checkpoint_dir)?;
+    if !options.script.exists() {
+        return Err(TrainctlError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("Script not found: {}", options.script.display()),
+        )));
+    }
+    let s3_bucket = if involves_aws {
+        Some(
+            config
+                .aws
+                .as_ref()
+                .and_then(|c| c.s3_bucket.clone())
+                .ok_or_else(|| {
+                    TrainctlError::Config(ConfigError::InvalidValue {
+                        field: "aws.s3_bucket".to_string(),
+                        reason: "Migrations to or from AWS stage checkpoints in S3; set [aws] s3_bucket in .runctl.toml".to_string(),
+                    })
+                })?,
+        )
+    } else {
+        None
+    };
+    #[cfg(not(feature = "runpod"))]
+    if involves_runpod {
+        return Err(ConfigError::FeatureNotCompiled("runpod".to_string()).into());
+    }
+    if involves_runpod && which::which("runpodctl").is_err() {
+        return Err(TrainctlError::CloudProvider {
+            provider: "runpod".to_string(),
+            message: "runpodctl not found. Install from: https://github.com/runpod/runpodctl"
+                .to_string(),
+            source: None,
+        });
+    }
+    #[cfg(feature = "runpod")]
+    if involves_runpod {
+        // Pods are created and removed through the API
+        crate::providers::RunpodClient::from_config(config)?;
+    }
+
+    let mut record = MigrationRecord::new(&options);
+
+    if options.dry_run {
+        if text {
+            println!(
+                "Migration plan for {} -> {}:",
+                options.source, options.target
+            );
+            println!(
+                "  1. Stop the job (SIGTERM, up to {}s) and find the newest checkpoint in {}/",
+                options.grace_period, options.checkpoint_dir
+            );
+            match &s3_bucket {
+                Some(bucket) => println!(
+                    "  2. Stage the checkpoint via s3://{}/{}/{}/",
+                    bucket, S3_PREFIX, record.id
+                ),
+                None => println!(
+                    "  2. Stage the checkpoint in {}",
+                    migrations_dir().join(&record.id).display()
+                ),
+            }
+            println!("  3. Provision {}", options.target);
+            println!("  4. Copy the checkpoint into {}/", options.checkpoint_dir);
+            println!(
+                "  5. Resume {} with {} <checkpoint>",
+                options.script.display(),
+                options.resume_arg
+            );
+            if options.terminate_source {
+                println!("  Then terminate {}", options.source);
+            }
+            println!();
+            println!("DRY RUN: nothing was changed");
+        } else {
+            println!("{}", serde_json::to_string_pretty(&record)?);
+        }
+        return Ok(());
+    }
+
+    record.save()?;
+    let result = run_migration(
+        &options,
+        config,
+        &mut record,
+        s3_bucket.as_deref(),
+        output_format,
+    )
+    .await;
+
+    if let Err(e) = &result {
+        record.status = MigrationStatus::Failed;
+        record.error = Some(e.to_string());
+        if let Err(save_err) = record.save() {
+            warn!("Failed to save migration record: {}", save_err);
+        }
+        if text {
+            eprintln!();
+            eprintln!(
+                "Migration {} failed; progress saved in {}",
+                record.id,
+                migrations_dir()
+                    .join(format!("{}.json", record.id))
+                    .display()
+            );
+            if let Some(target_id) = &record.target_id {
+                eprintln!(
+                    "  Target {} was provisioned and is still running",
+                    target_id
+                );
+            }
+            if let Some(staged) = &record.staged {
+                eprintln!("  Checkpoint staged at {}", staged);
+            }
+        }
+        return result;
+    }
+
+    if text {
+        println!();
+        println!(
+            "Migrated {} -> {} ({})",
+            record.source,
+            record.target,
+            record.target_id.as_deref().unwrap_or("-")
+        );
+        match (record.target.provider, record.target_id.as_deref()) {
+            (TargetProvider::Aws, Some(id)) => println!("  Watch: runctl watch {}", id),
+            (TargetProvider::Runpod, Some(id)) => {
+                println!("  Monitor: runctl runpod monitor {} --follow", id)
+            }
+            _ => {}
+        }
+    } else {
+        println!("{}", serde_json::to_string_pretty(&record)?);
+    }
+    Ok(())
+}
+
+async fn run_migration(
+    options: &MigrateOptions,
+    config: &Config,
+    record: &mut MigrationRecord,
+    s3_bucket: Option<&str>,
+    output_format: &str,
+) -> Result<()> {
+    let text = output_format != "json";
+    let aws_config = match s3_bucket {
+        Some(_) => Some(aws_config::load_defaults(BehaviorVersion::latest()).await),
+        None => None,
+    };
+    let aws = |what: &str| {
+        aws_config
+            .as_ref()
+            .ok_or_else(|| TrainctlError::Aws(format!("AWS configuration required to {}", what)))
+    };
+
+    // 1. Stop the job and find its checkpoint
+    step(
+        text,
+        1,
+        &format!("Stopping {} and saving a checkpoint", options.source),
+    );
+    let (project_name, source_dir) = match &options.source {
+        JobLocation::Aws(instance_id) => {
+            let (user, tag_project) =
+                aws_home(&Ec2Client::new(aws("reach the source")?), instance_id).await?;
+            let project = options
+                .project_name
+                .clone()
+                .or(tag_project)
+                .unwrap_or_else(|| get_project_name(None, config));
+            let dir = format!("/home/{}/{}", user, project);
+            (project, dir)
+        }
+        JobLocation::Runpod(_) => (
+            options
+                .project_name
+                .clone()
+                .unwrap_or_else(|| get_project_name(None, config)),
+            RUNPOD_WORKSPACE.to_string(),
+        ),
+    };
+    let script =
+        stop_and_checkpoint_script(&source_dir, &options.checkpoint_dir, options.grace_period);
+    let output = match &options.source {
+        JobLocation::Aws(instance_id) => {
+            let ssm = SsmClient::new(aws("reach the source")?);
+            execute_ssm_command(&ssm, instance_id, &script).await?
+        }
+        JobLocation::Runpod(pod_id) => runpod::exec_on_pod(pod_id, &script)?,
+    };
+    let report = parse_stop_report(&output);
+    if text {
+        match (report.was_running, report.forced) {
+            (false, _) => println!("   Job was not running"),
+            (true, false) => println!("   Job stopped gracefully"),
+            (true, true) => println!(
+                "   WARNING: job did not exit within {}s and was killed; the checkpoint may be older",
+                options.grace_period
+            ),
+        }
+    }
+
+    // 2. Stage the checkpoint
+    let staged = match &report.checkpoint {
+        Some(checkpoint) => {
+            step(text, 2, &format!("Staging checkpoint {}", checkpoint));
+            let name = file_name(checkpoint)?;
+            let staged = match &options.source {
+                JobLocation::Aws(instance_id) => {
+                    let uri = format!(
+                        "s3://{}/{}/{}/{}",
+                        s3_bucket.unwrap_or_default(),
+                        S3_PREFIX,
+                        record.id,
+                        name
+                    );
+                    let ssm = SsmClient::new(aws("stage the checkpoint")?);
+                    let cmd = format!(
+                        "cd {} && aws s3 cp {} {}",
+                        shell_quote(&source_dir),
+                        shell_quote(checkpoint),
+                        shell_quote(&uri)
+                    );
+                    execute_ssm_command(&ssm, instance_id, &cmd).await?;
+                    Staged::S3(uri)
+                }
+                JobLocation::Runpod(pod_id) => {
+                    let dir = migrations_dir().join(&record.id);
+                    crate::utils::ensure_dir(&dir)?;
+                    let local = dir.join(&name);
+                    let remote = if checkpoint.starts_with('/') {
+                        PathBuf::from(checkpoint)
+                    } else {
+                        Path::new(RUNPOD_WORKSPACE).join(checkpoint)
+                    };
+                    runpod::download_from_pod(pod_id.clone(), remote, local.clone()).await?;
+                    Staged::Local(local)
+                }
+            };
+            record.checkpoint = Some(checkpoint.clone());
+            record.staged = Some(staged.to_string());
+            Some((name, staged))
+        }
+        None if options.allow_no_checkpoint => {
+            step(
+                text,
+                2,
+                "No checkpoint found; the target will start from scratch",
+            );
+            None
+        }
+        None => {
+            return Err(TrainctlError::Resource {
+                resource_type: "checkpoint".to_string(),
+                operation: "migrate".to_string(),
+                resource_id: Some(options.source.id().to_string()),
+                message: format!(
+                    "No checkpoint found in {}/{} after stopping the job. The job is stopped; \
+                     make sure the script saves checkpoints on SIGTERM, or pass \
+                     --allow-no-checkpoint to start from scratch on the target",
+                    source_dir, options.checkpoint_dir
+                ),
+                source: None,
+            });
+        }
+    };
+    record.status = MigrationStatus::CheckpointSaved;
+    record.save()?;
+
+    // 3. Provision the target
+    step(text, 3, &format!("Provisioning {}", options.target));
+    let target_id = match options.target.provider {
+        TargetProvider::Aws => {
+            let iam_instance_profile = config
+                .aws
+                .as_ref()
+                .and_then(|c| c.iam_instance_profile.clone());
+            let create_options = CreateInstanceOptions {
+                instance_type: options.target.instance_type.clone(),
+                use_spot: options.spot,
+                spot_max_price: None,
+                no_fallback: false,
+                availability_zone: None,
+                key_name: None,
+                security_group: None,
+                ami_id: None,
+                root_volume_size: None,
+                data_volume_size: None,
+                project_name: project_name.clone(),
+                iam_instance_profile,
+                wait: true,
+                preset: None,
+                client_token: None,
+                allow_duplicate: false,
+                refresh_ami: false,
+                ami_filter: None,
+                ssh_user: None,
+            };
+            create_instance_and_get_id(create_options, config, aws("provision the target")?).await?
+        }
+        TargetProvider::Runpod => {
+            let disk = options
+                .disk
+                .or_else(|| config.runpod.as_ref().map(|r| r.default_disk_gb))
+                .unwrap_or(DEFAULT_RUNPOD_DISK_GB);
+            runpod::create_pod(
+                Some(format!("runctl-{}", record.id)),
+                options.target.instance_type.clone(),
+                disk,
+                config,
+            )
+            .await?
+        }
+    };
+    record.target_id = Some(target_id.clone());
+    record.status = MigrationStatus::Provisioned;
+    record.save()?;
+
+    // 4. Copy the checkpoint into place
+    if let Some((name, staged)) = staged {
+        step(text, 4, &format!("Copying checkpoint to {}", target_id));
+        let resume_path = match options.target.provider {
+            TargetProvider::Aws => {
+                let aws_config = aws("transfer the checkpoint")?;
+                let uri = match staged {
+                    Staged::S3(uri) => uri,
+                    Staged::Local(path) => {
+                        let uri = format!(
+                            "s3://{}/{}/{}/{}",
+                            s3_bucket.unwrap_or_default(),
+                            S3_PREFIX,
+                            record.id,
+                            name
+                        );
+                        DataTransfer::new(config.clone(), Some(aws_config))
+                            .transfer(
+                                &DataLocation::Local(path),
+                                &DataLocation::S3(uri.clone()),
+                                TransferOptions::default(),
+                            )
+                            .await?;
+                        uri
+                    }
+                };
+                let (user, _) = aws_home(&Ec2Client::new(aws_config), &target_id).await?;
+                let target_dir =
+                    format!("/home/{}/{}/{}", user, project_name, options.checkpoint_dir);
+                let cmd = format!(
+                    "mkdir -p {dir} && aws s3 cp {uri} {dir}/{name}",
+                    dir = shell_quote(&target_dir),
+                    uri = shell_quote(&uri),
+                    name = shell_quote(&name)
+                );
+                execute_ssm_command(&SsmClient::new(aws_config), &target_id, &cmd).await?;
+                // Training runs from the project directory
+                format!("{}/{}", options.checkpoint_dir, name)
+            }
+            TargetProvider::Runpod => {
+                let local = match staged {
+                    Staged::Local(path) => path,
+                    Staged::S3(uri) => {
+                        let dir = migrations_dir().join(&record.id);
+                        crate::utils::ensure_dir(&dir)?;
+                        let local = dir.join(&name);
+                        DataTransfer::new(config.clone(), aws_config.as_ref())
+                            .transfer(
+                                &DataLocation::S3(uri),
+                                &DataLocation::Local(local.clone()),
+                                TransferOptions::default(),
+                            )
+                            .await?;
+                        local
+                    }
+                };
+                let remote_dir = format!("{}/{}", RUNPOD_WORKSPACE, options.checkpoint_dir);
+                runpod::exec_on_pod(
+                    &target_id,
+                    &format!("mkdir -p {}", shell_quote(&remote_dir)),
+                )?;
+                let remote = format!("{}/{}", remote_dir, name);
+                runpod::upload_to_pod(&target_id, &local, &remote)?;
+                remote
+            }
+        };
+        record.resume_path = Some(resume_path);
+        record.save()?;
+    } else {
+        step(text, 4, "No checkpoint to copy");
+    }
+
+    // 5. Resume training
+    step(
+        text,
+        5,
+        &format!("Resuming {} on {}", options.script.display(), target_id),
+    );
+    let script_args = record
+        .resume_path
+        .as_ref()
+        .map(|path| vec![options.resume_arg.clone(), path.clone()])
+        .unwrap_or_default();
+    match options.target.provider {
+        TargetProvider::Aws => {
+            let train_options = TrainInstanceOptions {
+                instance_id: target_id.clone(),
+                script: options.script.clone(),
+                data_s3: None,
+                output_s3: None,
+                sync_code: true,
+                include_patterns: vec![],
+                exclude_patterns: vec![],
+                max_file_size: None,
+                yes: true,
+                project_name: project_name.clone(),
+                ssh_user: None,
+                script_args,
+                resume: None,
+                env: vec![],
+                wait: false,
+                timeout_minutes: 120,
+                docker: false,
+                docker_image: None,
+                job: config.job.clone(),
+                agent: false,
+                launcher: None,
+                on_complete: None,
+            };
+            train_on_instance(
+                train_options,
+                config,
+                aws("resume training")?,
+                output_format,
+            )
+            .await?;
+        }
+        TargetProvider::Runpod => {
+            runpod::train_on_pod(
+                target_id.clone(),
+                options.script.clone(),
+                true,
+                &script_args,
+                &[],
+                config,
+            )
+            .await?;
+        }
+    }
+    record.status = MigrationStatus::Resumed;
+    record.save()?;
+
+    if options.terminate_source {
+        let outcome = match &options.source {
+            JobLocation::Aws(instance_id) => {
+                terminate_instance(
+                    instance_id.clone(),
+                    false,
+                    aws("terminate the source")?,
+                    output_format,
+                    config,
+                )
+                .await
+            }
+            JobLocation::Runpod(pod_id) => runpod::remove_pod(pod_id, config).await,
+        };
+        if let Err(e) = outcome {
+            warn!("Failed to terminate source {}: {}", options.source, e);
+            if text {
+                println!(
+                    "WARNING: training resumed, but {} could not be terminated: {}",
+                    options.source, e
+                );
+            }
+        }
+    } else if text {
+        match &options.source {
+            JobLocation::Aws(id) => {
+                println!(
+                    "   Source left running; stop it with: runctl aws terminate {}",
+                    id
+                )
+            }
+            JobLocation::Runpod(id) => {
+                println!(
+                    "   Source left running; remove it with: runctl runpod terminate {}",
+                    id
+                )
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target() {
+        let target = MigrationTarget::parse("aws:g5.xlarge").unwrap();
+        assert_eq!(target.provider, TargetProvider::Aws);
+        assert_eq!(target.instance_type, "g5.xlarge");
+        assert_eq!(target.to_string(), "aws:g5.xlarge");
+
+        let target = MigrationTarget::parse("runpod:RTX 4090").unwrap();
+        assert_eq!(target.provider, TargetProvider::Runpod);
+        assert_eq!(target.instance_type, "RTX 4090");
+
+        assert!(MigrationTarget::parse("g5.xlarge").is_err());
+        assert!(MigrationTarget::parse("aws:").is_err());
+        assert!(MigrationTarget::parse("aws:RTX 4090").is_err());
+        assert!(MigrationTarget::parse("gcp:a2-highgpu-1g").is_err());
+    }
+
+    #[test]
+    fn test_job_location() {
+        assert_eq!(
+            JobLocation::from_job_id("i-1234567890abcdef0").unwrap(),
+            JobLocation::Aws("i-1234567890abcdef0".to_string())
+        );
+        assert_eq!(
+            JobLocation::from_job_id("abc123xyz").unwrap().provider(),
+            TargetProvider::Runpod
+        );
+        assert!(JobLocation::from_job_id("").is_err());
+        assert!(JobLocation::from_job_id("../etc").is_err());
+    }
+
+    #[test]
+    fn test_parse_stop_report() {
+        let report =
+            parse_stop_report("TRAINING_RUNNING:4242\nCHECKPOINT:checkpoints/epoch_7.pt\n");
+        assert_eq!(
+            report,
+            StopReport {
+                was_running: true,
+                forced: false,
+                checkpoint: Some("checkpoints/epoch_7.pt".to_string()),
+                fresh: false,
+            }
+        );
+
+        let report = parse_stop_report(
+            "TRAINING_RUNNING:4242\nCHECKPOINT:checkpoints/epoch_8.pt\nCHECKPOINT_FRESH\n",
+        );
+        assert!(report.fresh);
+
+        let report =
+            parse_stop_report("TRAINING_RUNNING:1\nTRAINING_FORCE_STOPPED\nNO_CHECKPOINT\n");
+        assert!(report.forced);
+        assert!(report.checkpoint.is_none());
+    }
+
+    #[test]
+    fn test_stop_script_quotes_paths() {
+        let script = stop_and_checkpoint_script("/home/ubuntu/my proj", "ckpt's", 30);
+        assert!(script.starts_with("cd '/home/ubuntu/my proj'"));
+        assert!(script.contains(r"'ckpt'\''s'/*.pt"));
+        assert!(script.contains("seq 1 30"));
+    }
+
+    #[test]
+    fn test_checkpoint_dir_must_be_relative() {
+        assert!(validate_checkpoint_dir("checkpoints").is_ok());
+        assert!(validate_checkpoint_dir("outputs/ckpt").is_ok());
+        assert!(validate_checkpoint_dir("/tmp").is_err());
+        assert!(validate_checkpoint_dir("../elsewhere").is_err());
+    }
+}
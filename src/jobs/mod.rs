@@ -0,0 +1,291 @@
+//! Provider-independent job operations
+//!
+//! Commands that act on a training job regardless of where it runs. A job ID
+//! is an EC2 instance ID (`i-...`) or a RunPod pod ID, the same IDs accepted
+//! by `runctl watch`.
+//!
+//! ## Commands
+//!
+//! - **Migrate**: checkpoint a running job and resume it on another provider
+//!   or instance type (see `migrate`)
+//! - **Wait**: block until a job finishes and exit with its outcome (see `wait`)
+//! - **Recover**: continue or roll back launches interrupted by a crash
+//!   (see `recover`)
+//! - **Timeline**: lifecycle events of a job in order (see `timeline`)
+//! - **CollectDebug**: bundle logs and diagnostics of a failed job into a
+//!   tarball (see `debug`)
+//! - **Chaos**: simulate a spot interruption to test checkpointing and
+//!   auto-resume (`runctl chaos interrupt`, see `chaos`)
+//!
+//! Rust callers submit jobs with [`JobBuilder`] and follow them through the
+//! returned [`JobHandle`] (see `builder`).
+
+mod builder;
+#[cfg(feature = "aws")]
+mod chaos;
+mod debug;
+#[cfg(feature = "aws")]
+mod migrate;
+mod recover;
+mod timeline;
+mod wait;
+
+pub use builder::{CheckpointPolicy, JobBuilder, JobHandle};
+#[cfg(feature = "aws")]
+pub use chaos::{handle_chaos_command, ChaosCommands};
+#[cfg(feature = "aws")]
+pub(crate) use debug::{DebugFile, Skipped};
+#[cfg(feature = "aws")]
+pub use migrate::{JobLocation, MigrationTarget, TargetProvider};
+#[cfg(feature = "aws")]
+pub(crate) use wait::classify;
+
+use crate::config::Config;
+use crate::error::Result;
+use clap::Subcommand;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Subcommand, Clone)]
+pub enum JobsCommands {
+    /// Move a running job to another provider or instance type
+    ///
+    /// Stops the job gracefully (SIGTERM, so the script can save a
+    /// checkpoint), copies the newest checkpoint to a newly provisioned
+    /// target, and resumes training there with `--resume <checkpoint>`.
+    ///
+    /// Checkpoints move through S3 when AWS is involved ([aws] s3_bucket must
+    /// be set) and through a local staging directory for RunPod. Progress is
+    /// recorded in .runctl/migrations/<id>.json.
+    ///
+    /// Examples:
+    ///   runctl jobs migrate abc123xyz --to aws:g5.xlarge --script train.py
+    ///   runctl jobs migrate i-1234567890abcdef0 --to runpod:"RTX 4090" --script train.py
+    ///   runctl jobs migrate abc123xyz --to aws:g5.xlarge --script train.py --dry-run
+    Migrate {
+        /// Job to migrate (EC2 instance ID or RunPod pod ID)
+        #[arg(value_name = "JOB_ID")]
+        job_id: String,
+
+        /// Target as PROVIDER:TYPE (aws:g5.xlarge, runpod:"RTX 4090")
+        #[arg(long, value_name = "PROVIDER:TYPE")]
+        to: String,
+
+        /// Training script to resume on the target
+        #[arg(long, value_name = "SCRIPT")]
+        script: PathBuf,
+
+        /// Checkpoint directory, relative to the project directory
+        #[arg(long, value_name = "DIR", default_value = "checkpoints")]
+        checkpoint_dir: String,
+
+        /// Argument that passes the checkpoint path to the script
+        #[arg(long, value_name = "FLAG", default_value = "--resume")]
+        resume_arg: String,
+
+        /// Project name (default: source instance tags, then current directory)
+        #[arg(long, value_name = "NAME")]
+        project_name: Option<String>,
+
+        /// Use a spot instance for an AWS target
+        #[arg(long)]
+        spot: bool,
+
+        /// Container disk size in GB for a RunPod target
+        #[arg(long, value_name = "GB")]
+        disk: Option<u32>,
+
+        /// Seconds to wait for the job to exit after SIGTERM
+        #[arg(long, value_name = "SECS", default_value = "90")]
+        grace_period: u64,
+
+        /// Start from scratch on the target if no checkpoint is found
+        #[arg(long)]
+        allow_no_checkpoint: bool,
+
+        /// Terminate the source instance or pod after training resumes
+        #[arg(long)]
+        terminate_source: bool,
+
+        /// Show the migration plan without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Wait for a job to finish and exit with its outcome
+    ///
+    /// JOB_ID is an EC2 instance ID or a local session ID (prefix), as for
+    /// `runctl watch`. Exit codes: 0 completed, 10 failed, 11 timed out,
+    /// 12 interrupted (process gone without a result, instance stopped).
+    ///
+    /// Examples:
+    ///   runctl jobs wait i-1234567890abcdef0
+    ///   runctl jobs wait i-1234567890abcdef0 --timeout 8h
+    ///   runctl jobs wait 3f2a9c1e --interval 30s --output json
+    Wait {
+        /// EC2 instance ID or local session ID
+        #[arg(value_name = "JOB_ID")]
+        job_id: String,
+
+        /// Give up after this long (e.g. 90m, 8h); the job keeps running
+        #[arg(long, value_name = "DURATION", value_parser = crate::units::parse_secs)]
+        timeout: Option<u64>,
+
+        /// Time between status checks
+        #[arg(
+            long,
+            value_name = "DURATION",
+            default_value = "10",
+            value_parser = crate::units::parse_secs
+        )]
+        interval: u64,
+    },
+    /// Continue or roll back launches interrupted by a crash
+    ///
+    /// Finds `runctl run` sessions whose runctl process died after the
+    /// resource was created but before training started. For each one,
+    /// asks whether to start training on the resource or terminate it.
+    /// Use --continue or --rollback to decide without asking.
+    ///
+    /// Examples:
+    ///   runctl jobs recover
+    ///   runctl jobs recover 3f2a9c1e --rollback
+    ///   runctl jobs recover --dry-run --output json
+    Recover {
+        /// Session ID (or prefix); default: every interrupted launch
+        #[arg(value_name = "SESSION_ID")]
+        session_id: Option<String>,
+
+        /// Start training on the resource that was created
+        #[arg(long = "continue", conflicts_with = "rollback")]
+        resume: bool,
+
+        /// Terminate the resource that was created
+        #[arg(long)]
+        rollback: bool,
+
+        /// Show what would be done without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show what happened to a job, in order
+    ///
+    /// Lists the job's lifecycle events (created, bootstrapped, training
+    /// started, checkpoints saved, spot warnings, stopped, terminated) with
+    /// their times. JOB_ID is an EC2 instance ID or a local session ID
+    /// (prefix); for a session, the events of the instance it launched are
+    /// included.
+    ///
+    /// Examples:
+    ///   runctl jobs timeline i-1234567890abcdef0
+    ///   runctl jobs timeline 3f2a9c1e --output json
+    Timeline {
+        /// EC2 instance ID or local session ID
+        #[arg(value_name = "JOB_ID")]
+        job_id: String,
+    },
+    /// Bundle a job's logs and diagnostics into a tarball
+    ///
+    /// Collects the instance's description and console output, and while it
+    /// runs, the training log tail, dmesg, nvidia-smi, the user-data log,
+    /// pip freeze, disk usage and processes. Adds the local session, event
+    /// timeline and applied manifest. Works on running, stopped and local
+    /// jobs; whatever can't be collected is listed in SKIPPED.txt.
+    ///
+    /// Examples:
+    ///   runctl jobs collect-debug i-1234567890abcdef0
+    ///   runctl jobs collect-debug 3f2a9c1e --output debug.tar.gz --lines 2000
+    CollectDebug {
+        /// EC2 instance ID or local session ID
+        #[arg(value_name = "JOB_ID")]
+        job_id: String,
+
+        /// Bundle path (default: runctl-debug-<id>-<time>.tar.gz)
+        #[arg(long, short = 'o', value_name = "PATH")]
+        output: Option<PathBuf>,
+
+        /// Lines kept from each log
+        #[arg(long, value_name = "N", default_value = "500")]
+        lines: usize,
+    },
+}
+
+pub async fn handle_command(cmd: JobsCommands, config: &Config, output_format: &str) -> Result<()> {
+    match cmd {
+        #[cfg(not(feature = "aws"))]
+        JobsCommands::Migrate { .. } => {
+            Err(crate::error::ConfigError::FeatureNotCompiled("aws".to_string()).into())
+        }
+        #[cfg(feature = "aws")]
+        JobsCommands::Migrate {
+            job_id,
+            to,
+            script,
+            checkpoint_dir,
+            resume_arg,
+            project_name,
+            spot,
+            disk,
+            grace_period,
+            allow_no_checkpoint,
+            terminate_source,
+            dry_run,
+        } => {
+            let options = migrate::MigrateOptions {
+                source: JobLocation::from_job_id(&job_id)?,
+                target: MigrationTarget::parse(&to)?,
+                script,
+                checkpoint_dir,
+                resume_arg,
+                project_name,
+                spot,
+                disk,
+                grace_period,
+                allow_no_checkpoint,
+                terminate_source,
+                dry_run,
+            };
+            migrate::migrate_job(options, config, output_format).await
+        }
+        JobsCommands::Wait {
+            job_id,
+            timeout,
+            interval,
+        } => {
+            wait::wait_for_job(
+                &job_id,
+                timeout.map(Duration::from_secs),
+                Duration::from_secs(interval),
+                config,
+                output_format,
+            )
+            .await
+        }
+        JobsCommands::Recover {
+            session_id,
+            resume,
+            rollback,
+            dry_run,
+        } => {
+            let options = recover::RecoverOptions {
+                session_id,
+                resume,
+                rollback,
+                dry_run,
+            };
+            recover::recover(options, config, output_format).await
+        }
+        JobsCommands::Timeline { job_id } => timeline::show_timeline(&job_id, output_format),
+        JobsCommands::CollectDebug {
+            job_id,
+            output,
+            lines,
+        } => {
+            let options = debug::CollectOptions {
+                job_id,
+                output,
+                lines,
+            };
+            debug::collect_debug(options, config, output_format).await
+        }
+    }
+}
@@ -0,0 +1,251 @@
+//! Recover launches interrupted by a crash (`runctl jobs recover`)
+//!
+//! `runctl run` saves its session after each launch step. A session still
+//! in `Launching` whose runctl process is gone was cut off between
+//! provisioning and starting training. Its resource may be running and
+//! billing with nothing on it.
+//!
+//! For each such session, recovery either continues the launch, starting the
+//! training script on the existing resource, or rolls it back by terminating
+//! the resource. A launch that stopped before the provider returned a
+//! resource ID can only be closed; nothing is known to roll back.
+
+use crate::config::Config;
+use crate::error::{Result, TrainctlError};
+use crate::provider::{ResourceState, TrainingJob, TrainingProvider};
+use crate::providers::ProviderRegistry;
+use crate::training::{LaunchStage, TrainingSession, TrainingStatus};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+pub struct RecoverOptions {
+    /// Session ID or prefix (default: every interrupted launch)
+    pub session_id: Option<String>,
+    /// Start training on the created resource without asking
+    pub resume: bool,
+    /// Terminate the created resource without asking
+    pub rollback: bool,
+    pub dry_run: bool,
+}
+
+/// What recovery did (or would do) with one session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Action {
+    /// Training started on the existing resource
+    Continued,
+    /// Resource terminated
+    RolledBack,
+    /// Nothing left to recover; session marked finished
+    Closed,
+    /// Left as is (declined, or no decision possible)
+    Skipped,
+}
+
+#[derive(Debug, Serialize)]
+struct Recovery {
+    session_id: String,
+    provider: String,
+    stage: Option<LaunchStage>,
+    resource_id: Option<String>,
+    action: Action,
+    dry_run: bool,
+}
+
+pub async fn recover(options: RecoverOptions, config: &Config, output_format: &str) -> Result<()> {
+    let text = output_format != "json";
+    let sessions_dir = PathBuf::from(".runctl");
+    let mut sessions = TrainingSession::abandoned_launches(&sessions_dir)?;
+    if let Some(prefix) = &options.session_id {
+        sessions.retain(|s| s.id.starts_with(prefix.as_str()));
+        if sessions.is_empty() {
+            return Err(TrainctlError::ResourceNotFound {
+                resource_type: "interrupted launch".to_string(),
+                resource_id: prefix.clone(),
+            });
+        }
+    }
+    if sessions.is_empty() {
+        if text {
+            println!("No interrupted launches found");
+        } else {
+            println!("[]");
+        }
+        return Ok(());
+    }
+
+    let registry = ProviderRegistry::from_config(config).await?;
+    let mut recoveries = Vec::new();
+    for mut session in sessions {
+        let action =
+            recover_session(&mut session, &registry, &options, &sessions_dir, text).await?;
+        recoveries.push(Recovery {
+            session_id: session.id.clone(),
+            provider: session.platform.clone(),
+            stage: session.launch.as_ref().map(|l| l.stage),
+            resource_id: session.resource_id().map(String::from),
+            action,
+            dry_run: options.dry_run,
+        });
+    }
+
+    if !text {
+        println!("{}", serde_json::to_string_pretty(&recoveries)?);
+    }
+    Ok(())
+}
+
+async fn recover_session(
+    session: &mut TrainingSession,
+    registry: &ProviderRegistry,
+    options: &RecoverOptions,
+    sessions_dir: &Path,
+    text: bool,
+) -> Result<Action> {
+    let short_id = session.id[..session.id.len().min(8)].to_string();
+    if text {
+        println!(
+            "Session {} ({}): {}, started {}",
+            short_id,
+            session.platform,
+            session.script.display(),
+            session.started_at.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+    }
+
+    let Some(resource_id) = session.resource_id().map(String::from) else {
+        if text {
+            println!(
+                "  Interrupted before {} returned a resource ID.",
+                session.platform
+            );
+            println!(
+                "  Check 'runctl resources list --platform {}' for a resource to remove.",
+                session.platform
+            );
+        }
+        if !options.dry_run {
+            session.status =
+                TrainingStatus::Failed("launch interrupted before a resource was created".into());
+            session.save(sessions_dir)?;
+        }
+        return Ok(Action::Closed);
+    };
+
+    let provider = registry.select(&session.platform)?;
+    let gone = match provider.get_resource_status(&resource_id).await {
+        Ok(status) => matches!(
+            status.state,
+            ResourceState::Terminated | ResourceState::Terminating
+        ),
+        Err(TrainctlError::ResourceNotFound { .. }) => true,
+        Err(e) => return Err(e),
+    };
+    if gone {
+        if text {
+            println!("  {} no longer exists; nothing to roll back.", resource_id);
+        }
+        if !options.dry_run {
+            session.status = TrainingStatus::RolledBack;
+            session.save(sessions_dir)?;
+        }
+        return Ok(Action::Closed);
+    }
+    if text {
+        println!("  {} was created but training never started.", resource_id);
+    }
+
+    let action = decide(options, &resource_id)?;
+    if options.dry_run {
+        if text {
+            let plan = match action {
+                Action::Continued => format!("start training on {}", resource_id),
+                Action::RolledBack => format!("terminate {}", resource_id),
+                _ => "leave it as is".to_string(),
+            };
+            println!("  [DRY RUN] Would {}", plan);
+        }
+        return Ok(action);
+    }
+
+    match action {
+        Action::Continued => {
+            continue_launch(session, provider.as_ref(), &resource_id).await?;
+            session.save(sessions_dir)?;
+            if text {
+                println!("  Training started on {}", resource_id);
+                println!("  Follow it with: runctl watch {}", resource_id);
+            }
+        }
+        Action::RolledBack => {
+            provider.terminate(&resource_id).await?;
+            session.status = TrainingStatus::RolledBack;
+            session.save(sessions_dir)?;
+            if text {
+                println!("  Terminated {}", resource_id);
+            }
+        }
+        _ => {
+            if text {
+                println!(
+                    "  Left as is. Re-run with --continue or --rollback (session {}).",
+                    short_id
+                );
+            }
+        }
+    }
+    Ok(action)
+}
+
+/// Continue or roll back, from the flags or by asking
+fn decide(options: &RecoverOptions, resource_id: &str) -> Result<Action> {
+    if options.resume {
+        return Ok(Action::Continued);
+    }
+    if options.rollback {
+        return Ok(Action::RolledBack);
+    }
+    if !crate::prompt::can_prompt() {
+        return Ok(Action::Skipped);
+    }
+    if crate::prompt::confirm(&format!("  Continue training on {}?", resource_id))? {
+        return Ok(Action::Continued);
+    }
+    if crate::prompt::confirm(&format!("  Terminate {} instead?", resource_id))? {
+        return Ok(Action::RolledBack);
+    }
+    Ok(Action::Skipped)
+}
+
+async fn continue_launch(
+    session: &mut TrainingSession,
+    provider: &dyn TrainingProvider,
+    resource_id: &str,
+) -> Result<()> {
+    if !session.script.exists() {
+        return Err(TrainctlError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!(
+                "Script not found: {} (run recover from the project directory)",
+                session.script.display()
+            ),
+        )));
+    }
+    let job = TrainingJob {
+        script: session.script.clone(),
+        args: session
+            .launch
+            .as_ref()
+            .map(|l| l.script_args.clone())
+            .unwrap_or_default(),
+        data_source: None,
+        output_dest: None,
+        checkpoint_dir: None,
+        environment: vec![],
+    };
+    provider.train(&resource_id.to_string(), job).await?;
+    session.runctl_pid = Some(std::process::id());
+    session.advance(LaunchStage::TrainingStarted, None);
+    Ok(())
+}
@@ -0,0 +1,34 @@
+//! `runctl jobs timeline`: lifecycle events of a job in order
+//!
+//! An instance ID shows that instance's events. A local session ID (or
+//! prefix) shows the events recorded for the session and for the resource it
+//! launched, so a `runctl run` job can be followed by the ID it printed.
+
+use crate::error::Result;
+use crate::events::{self, Event};
+use std::path::Path;
+
+/// Events of `job_id` (instance ID or local session ID), oldest first
+pub(crate) fn job_events(job_id: &str, sessions_dir: &Path) -> Result<(String, Vec<Event>)> {
+    if job_id.starts_with("i-") {
+        crate::validation::validate_instance_id(job_id)?;
+        return Ok((job_id.to_string(), events::timeline(job_id)?));
+    }
+    let session = crate::watch::resolve_local_session(sessions_dir, job_id)?;
+    let mut timeline = events::timeline(&session.id)?;
+    let resource_id = session.launch.as_ref().and_then(|l| l.resource_id.clone());
+    if let Some(resource_id) = &resource_id {
+        timeline.extend(events::timeline(resource_id)?);
+        timeline.sort_by_key(|e| e.timestamp);
+    }
+    let label = match resource_id {
+        Some(resource_id) => format!("{} ({})", session.id, resource_id),
+        None => session.id,
+    };
+    Ok((label, timeline))
+}
+
+pub(crate) fn show_timeline(job_id: &str, output_format: &str) -> Result<()> {
+    let (label, timeline) = job_events(job_id, Path::new(".runctl"))?;
+    events::print_timeline(&label, &timeline, output_format)
+}
@@ -0,0 +1,111 @@
+//! Block until a job finishes
+//!
+//! `runctl jobs wait <id>` polls the same job view as `runctl watch` and
+//! exits with the job's outcome (see `exit_codes`): 0 when it completes, and
+//! a distinct non-zero code when it fails, is interrupted, or the wait times
+//! out. This lets CI gate on a training run without keeping a terminal open.
+
+use crate::config::Config;
+use crate::error::{JobOutcome, Result, TrainctlError};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// Final state of a job, or None while it may still finish
+pub(crate) fn classify(status: &str) -> Option<std::result::Result<(), JobOutcome>> {
+    match status {
+        "completed" => Some(Ok(())),
+        s if s.starts_with("failed") => Some(Err(JobOutcome::Failed)),
+        // Process gone without a recorded result, or the instance went away
+        "interrupted" | "exited" | "stopped" | "stopping" | "shutting-down" | "terminated" => {
+            Some(Err(JobOutcome::Interrupted))
+        }
+        _ => None,
+    }
+}
+
+#[derive(Serialize)]
+struct WaitResult<'a> {
+    success: bool,
+    job_id: &'a str,
+    status: &'a str,
+    elapsed_secs: u64,
+}
+
+/// Wait for a job to finish; `timeout` of None waits indefinitely
+pub(crate) async fn wait_for_job(
+    job_id: &str,
+    timeout: Option<Duration>,
+    interval: Duration,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    let (mut snapshot, mut source) = crate::watch::resolve_job(job_id, config).await?;
+    let started = Instant::now();
+    let mut last_status = String::new();
+
+    loop {
+        crate::watch::refresh(&mut snapshot, &mut source, config, None).await;
+        if let Some(error) = &snapshot.error {
+            tracing::debug!("Failed to refresh {}: {}", job_id, error);
+        }
+        if snapshot.status != last_status && !snapshot.status.is_empty() {
+            if output_format != "json" {
+                println!("{}: {}", snapshot.job_id, snapshot.status);
+            }
+            crate::progress::emit(
+                "wait.status",
+                format!("{}: {}", snapshot.job_id, snapshot.status),
+            );
+            last_status = snapshot.status.clone();
+        }
+
+        let elapsed = started.elapsed();
+        let outcome = match classify(&snapshot.status) {
+            Some(outcome) => outcome,
+            None if timeout.is_some_and(|t| elapsed >= t) => Err(JobOutcome::TimedOut),
+            None => {
+                tokio::time::sleep(interval).await;
+                continue;
+            }
+        };
+
+        if output_format == "json" {
+            let result = WaitResult {
+                success: outcome.is_ok(),
+                job_id: &snapshot.job_id,
+                status: &snapshot.status,
+                elapsed_secs: elapsed.as_secs(),
+            };
+            println!("{}", serde_json::to_string_pretty(&result)?);
+        }
+        return outcome.map_err(|outcome| TrainctlError::Job {
+            job_id: snapshot.job_id.clone(),
+            outcome,
+            message: match outcome {
+                JobOutcome::TimedOut => format!(
+                    "still {} after {}s; the job keeps running",
+                    snapshot.status,
+                    elapsed.as_secs()
+                ),
+                _ => format!("status {}", snapshot.status),
+            },
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("completed"), Some(Ok(())));
+        assert_eq!(classify("failed (exit 2)"), Some(Err(JobOutcome::Failed)));
+        assert_eq!(classify("terminated"), Some(Err(JobOutcome::Interrupted)));
+        assert_eq!(classify("exited"), Some(Err(JobOutcome::Interrupted)));
+        assert_eq!(classify("running"), None);
+        assert_eq!(classify("pending"), None);
+        assert_eq!(classify("unknown"), None);
+        assert_eq!(classify(""), None);
+    }
+}
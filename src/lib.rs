@@ -50,7 +50,8 @@
 //!
 //! ### Provider Trait (Future)
 //!
-//! The provider trait system is defined but not yet used by the CLI. When
+//! The provider trait system is used by the job queue scheduler
+//! (`runctl queue run`); other commands still call providers directly. When
 //! multi-cloud support is enabled:
 //!
 //! ```rust,no_run
@@ -64,37 +65,98 @@
 //! # }
 //! ```
 
+#[cfg(feature = "aws")]
+pub mod agent;
+pub mod aliases;
+pub mod ami_filter;
+pub mod audit;
+#[cfg(feature = "aws")]
 pub mod aws;
+#[cfg(feature = "aws")]
+pub mod aws_pricing;
+#[cfg(feature = "aws")]
 pub mod aws_utils;
+pub mod azure;
+#[cfg(feature = "aws")]
+pub mod batch;
+pub mod cache;
 pub mod checkpoint;
+pub mod checksums;
+pub mod ci;
 pub mod config;
+#[cfg(feature = "aws")]
+pub mod cost_estimate;
+pub mod cost_history;
+pub mod costs;
+#[cfg(feature = "dashboard")]
 pub mod dashboard;
+#[cfg(feature = "aws")]
+pub mod data_locality;
+#[cfg(feature = "aws")]
 pub mod data_transfer;
+#[cfg(feature = "aws")]
 pub mod diagnostics;
+#[cfg(feature = "aws")]
 pub mod docker;
+#[cfg(feature = "aws")]
 pub mod ebs;
 pub mod ebs_optimization;
+#[cfg(feature = "aws")]
+pub mod egress;
+pub mod encryption;
+pub mod env_vars;
 pub mod error;
 pub mod error_helpers;
+pub mod events;
+pub mod exit_codes;
 pub mod fast_data_loading;
+pub mod host_keys;
+#[cfg(feature = "aws")]
+pub mod image;
+pub mod jobs;
 pub mod local;
+pub mod logging;
+pub mod manifest;
 pub mod monitor;
+pub mod notifications;
+pub mod output_style;
+pub mod progress;
+pub mod prompt;
 pub mod provider;
 pub mod providers;
+#[cfg(feature = "aws")]
+pub mod queue;
 pub mod resource_tracking;
 pub mod resources;
 pub mod retry;
+pub mod run;
+#[cfg(feature = "runpod")]
 pub mod runpod;
+#[cfg(feature = "aws")]
 pub mod s3;
 pub mod safe_cleanup;
+#[cfg(feature = "aws")]
+pub mod scan;
+pub mod schedule;
+pub mod secrets;
+pub mod ssh_proxy;
 pub mod ssh_sync;
+pub mod sync_filter;
 pub mod training;
+pub mod training_progress;
+pub mod transfer_engine;
+#[cfg(feature = "aws")]
+pub mod transfer_session;
+pub mod units;
 pub mod utils;
 pub mod validation;
+pub mod watch;
+#[cfg(feature = "aws")]
 pub mod workflow;
 
 // Re-export commonly used types
 pub use error::{ConfigError, IsRetryable, Result, TrainctlError};
+pub use jobs::{CheckpointPolicy, JobBuilder, JobHandle};
 pub use provider::{
     CreateResourceOptions, ResourceState, ResourceStatus, TrainingJob, TrainingProvider,
 };
@@ -106,6 +168,7 @@ pub use training::{TrainingSession, TrainingStatus};
 pub use validation::{validate_path, validate_path_path};
 
 // Re-export commonly used types for convenience
+#[cfg(feature = "aws")]
 pub use aws::{CreateInstanceOptions, TrainInstanceOptions};
 pub use config::Config;
 pub use resources::estimate_instance_cost;
@@ -7,7 +7,8 @@
 //! ## Features
 //!
 //! - **Automatic Python detection**: Detects `.py` files and uses appropriate interpreter
-//! - **Environment variables**: Sets `TRAINCTL_CHECKPOINT_DIR` and `TRAINCTL_DEVICE` from config
+//! - **Environment variables**: Sets `TRAINCTL_CHECKPOINT_DIR` and `TRAINCTL_DEVICE` from config,
+//!   plus `[env]` and `--env`/`--env-file` (see `crate::env_vars`)
 //! - **Session tracking**: Creates and saves training session metadata
 //! - **Helpful error messages**: Provides suggestions when scripts fail or are not found
 //!
@@ -25,7 +26,7 @@
 
 use crate::config::Config;
 use crate::error::{Result, TrainctlError};
-use crate::training::TrainingSession;
+use crate::training::{TrainingSession, TrainingStatus};
 use crate::utils::ensure_dir;
 use std::path::PathBuf;
 use std::process::Command;
@@ -68,6 +69,21 @@ use tracing::info;
 /// # }
 /// ```
 pub async fn train(script: PathBuf, args: Vec<String>, config: &Config) -> Result<()> {
+    let env = crate::env_vars::collect(config, None, &[])?;
+    train_with_env(script, args, &env, config).await
+}
+
+/// `train` with extra environment variables for the script
+///
+/// `env` is exported as given; build it with `env_vars::collect` to include
+/// `[env]` and resolve `secret://` references. Secret values are masked in
+/// the logged command.
+pub async fn train_with_env(
+    script: PathBuf,
+    args: Vec<String>,
+    env: &[(String, String)],
+    config: &Config,
+) -> Result<()> {
     crate::validation::validate_path_path(&script)?;
 
     if !script.exists() {
@@ -123,16 +139,11 @@ pub async fn train(script: PathBuf, args: Vec<String>, config: &Config) -> Resul
         .unwrap_or_else(|| PathBuf::from("checkpoints"));
     ensure_dir(&checkpoint_dir)?;
 
-    let session = TrainingSession::new("local".to_string(), script.clone(), checkpoint_dir.clone());
+    let mut session =
+        TrainingSession::new("local".to_string(), script.clone(), checkpoint_dir.clone());
 
-    // Save session metadata
+    // Session metadata lives in .runctl/sessions; zombie cleanup reads it back
     let sessions_dir = PathBuf::from(".runctl");
-    session.save(&sessions_dir).map_err(|e| {
-        TrainctlError::Io(std::io::Error::other(format!(
-            "Failed to save training session: {}",
-            e
-        )))
-    })?;
 
     // Check if script is Python and use uv if available
     let is_python = script
@@ -165,10 +176,17 @@ pub async fn train(script: PathBuf, args: Vec<String>, config: &Config) -> Resul
         cmd.env("TRAINCTL_CHECKPOINT_DIR", &local_config.checkpoint_dir);
         cmd.env("TRAINCTL_DEVICE", &local_config.default_device);
     }
+    cmd.envs(env.iter().map(|(key, value)| (key, value)));
 
-    info!("Executing: {:?}", cmd);
+    // Not `{:?}` of the command, which would print the environment values
+    info!(
+        "Executing: {:?} {:?} (env: {})",
+        cmd.get_program(),
+        cmd.get_args().collect::<Vec<_>>(),
+        crate::env_vars::display_pairs(env).join(" ")
+    );
 
-    let status = cmd.status().map_err(|e| {
+    let mut child = cmd.spawn().map_err(|e| {
         TrainctlError::Io(std::io::Error::other(format!(
             "Failed to execute script {}: {}",
             script.display(),
@@ -176,6 +194,26 @@ pub async fn train(script: PathBuf, args: Vec<String>, config: &Config) -> Resul
         )))
     })?;
 
+    // Record PIDs so `resources cleanup` can spot orphaned or stale runs
+    session.pid = Some(child.id());
+    session.runctl_pid = Some(std::process::id());
+    save_session(&session, &sessions_dir)?;
+
+    let status = child.wait().map_err(|e| {
+        TrainctlError::Io(std::io::Error::other(format!(
+            "Failed to wait for script {}: {}",
+            script.display(),
+            e
+        )))
+    })?;
+
+    session.status = if status.success() {
+        TrainingStatus::Completed
+    } else {
+        TrainingStatus::Failed(format!("exit code {:?}", status.code()))
+    };
+    save_session(&session, &sessions_dir)?;
+
     if !status.success() {
         let mut err = format!("Training failed with exit code: {:?}", status.code());
 
@@ -209,3 +247,12 @@ pub async fn train(script: PathBuf, args: Vec<String>, config: &Config) -> Resul
     info!("Training completed successfully");
     Ok(())
 }
+
+fn save_session(session: &TrainingSession, sessions_dir: &std::path::Path) -> Result<()> {
+    session.save(sessions_dir).map_err(|e| {
+        TrainctlError::Io(std::io::Error::other(format!(
+            "Failed to save training session: {}",
+            e
+        )))
+    })
+}
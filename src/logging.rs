@@ -0,0 +1,198 @@
+//! Tracing setup and the log file
+//!
+//! Console output follows `--verbose` (warnings by default, debug with
+//! `--verbose`). `--log-file <PATH>` or `[logging] file` additionally writes
+//! every event at `[logging] level` to disk as JSON lines, regardless of
+//! console verbosity, so a failed overnight workflow can be diagnosed after
+//! the fact. The file is rotated when it would exceed `max_size`: `runctl.log`
+//! becomes `runctl.log.1`, and at most `max_files` old files are kept.
+
+use crate::config::LoggingConfig;
+use crate::error::{ConfigError, Result, TrainctlError};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+/// Install the global tracing subscriber
+///
+/// `log_file` (from `--log-file`) takes precedence over `[logging] file`.
+pub fn init(verbose: bool, log_file: Option<&Path>, config: &LoggingConfig) -> Result<()> {
+    // Suppress INFO on the console by default, only show warnings and errors
+    let console_filter = if verbose {
+        EnvFilter::new("debug")
+    } else {
+        EnvFilter::new("warn")
+    };
+    let console = tracing_subscriber::fmt::layer()
+        .with_target(false)
+        .with_filter(console_filter);
+
+    let path = log_file
+        .map(Path::to_path_buf)
+        .or_else(|| config.file.as_ref().map(|p| expand_path(p)));
+    let file_layer = match path {
+        Some(path) => {
+            let filter = EnvFilter::try_new(&config.level).map_err(|e| {
+                TrainctlError::Config(ConfigError::InvalidValue {
+                    field: "logging.level".to_string(),
+                    reason: format!("'{}': {}", config.level, e),
+                })
+            })?;
+            let max_size = crate::units::parse_size(&config.max_size).map_err(|reason| {
+                TrainctlError::Config(ConfigError::InvalidValue {
+                    field: "logging.max_size".to_string(),
+                    reason,
+                })
+            })?;
+            let writer = RotatingFile::open(&path, max_size, config.max_files)?;
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_writer(Arc::new(writer))
+                    .with_filter(filter),
+            )
+        }
+        None => None,
+    };
+
+    tracing_subscriber::registry()
+        .with(console)
+        .with(file_layer)
+        .init();
+    Ok(())
+}
+
+fn expand_path(path: &Path) -> PathBuf {
+    PathBuf::from(shellexpand::tilde(&path.to_string_lossy()).into_owned())
+}
+
+/// Append-only log file rotated by size
+#[derive(Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    state: Mutex<FileState>,
+}
+
+#[derive(Debug)]
+struct FileState {
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    /// Open `path` for appending, creating its directory if needed
+    pub fn open(path: &Path, max_size: u64, max_files: usize) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            crate::utils::ensure_dir(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_size,
+            max_files,
+            state: Mutex::new(FileState { file, size }),
+        })
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    /// Shift `log.N-1` to `log.N` (dropping the oldest) and start a new file
+    fn rotate(&self, state: &mut FileState) -> io::Result<()> {
+        state.file.flush()?;
+        if self.max_files == 0 {
+            let _ = std::fs::remove_file(&self.path);
+        } else {
+            let _ = std::fs::remove_file(self.rotated_path(self.max_files));
+            for index in (1..self.max_files).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    std::fs::rename(&from, self.rotated_path(index + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, self.rotated_path(1))?;
+        }
+        state.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        state.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for &RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| io::Error::other("log file lock poisoned"))?;
+        // Each event is written in one call, so rotating here never splits a line
+        if self.max_size > 0 && state.size > 0 && state.size + buf.len() as u64 > self.max_size {
+            self.rotate(&mut state)?;
+        }
+        let written = state.file.write(buf)?;
+        state.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.state
+            .lock()
+            .map_err(|_| io::Error::other("log file lock poisoned"))?
+            .file
+            .flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotating_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("runctl.log");
+        let log = RotatingFile::open(&path, 16, 2).unwrap();
+
+        for line in [
+            "first line\n",
+            "second line\n",
+            "third line\n",
+            "fourth line\n",
+        ] {
+            (&log).write_all(line.as_bytes()).unwrap();
+        }
+        (&log).flush().unwrap();
+
+        let read = |p: PathBuf| std::fs::read_to_string(p).unwrap();
+        assert_eq!(read(path.clone()), "fourth line\n");
+        assert_eq!(read(log.rotated_path(1)), "third line\n");
+        assert_eq!(read(log.rotated_path(2)), "second line\n");
+        assert!(!log.rotated_path(3).exists());
+    }
+
+    #[test]
+    fn test_reopen_keeps_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("runctl.log");
+        std::fs::write(&path, "0123456789").unwrap();
+        let log = RotatingFile::open(&path, 16, 1).unwrap();
+        (&log).write_all(b"abcdefgh").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "abcdefgh");
+        assert_eq!(
+            std::fs::read_to_string(log.rotated_path(1)).unwrap(),
+            "0123456789"
+        );
+    }
+}
@@ -13,11 +13,14 @@
 //!
 //! Commands are organized by platform and functionality:
 //!
-//! - **Platform commands**: `local`, `aws`, `runpod` - Execute training on different platforms
-//! - **Resource management**: `resources`, `status`, `top` - Monitor and manage resources
+//! - **Platform commands**: `local`, `aws`, `azure`, `runpod` - Execute training on different platforms
+//! - **Provider-agnostic runs**: `run --provider <name>` - Same workflow on any registered provider
+//! - **Run manifests**: `apply`, `plan`, `destroy` - Declarative, reproducible launches
+//! - **Resource management**: `resources`, `status`, `top`, `watch` - Monitor and manage resources
 //! - **Data operations**: `s3`, `transfer` - Handle data movement
-//! - **Training utilities**: `checkpoint`, `monitor`, `workflow` - Training-specific tools
+//! - **Training utilities**: `checkpoint`, `monitor`, `workflow`, `agent` - Training-specific tools
 //! - **Configuration**: `config`, `init` - Manage settings
+//! - **Interactive**: `shell` - REPL that keeps config, AWS credentials and context between commands
 //!
 //! ## Error Handling
 //!
@@ -27,11 +30,12 @@
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
-use tracing_subscriber::EnvFilter;
+use std::path::{Path, PathBuf};
 
 // Only binary-specific modules are declared here
+#[cfg(feature = "aws")]
 mod docker_cli;
+mod shell_cli;
 
 #[derive(Parser)]
 #[command(name = "runctl")]
@@ -52,9 +56,45 @@ struct Cli {
     #[arg(short, long, global = true)]
     verbose: bool,
 
-    /// Output format (text, json)
+    /// Output format (text, json, github)
+    ///
+    /// `github` prints text and reports failures as GitHub Actions
+    /// annotations (::error / ::notice).
     #[arg(long, global = true, default_value = "text")]
     output: String,
+
+    /// Answer confirmations with yes and never prompt (for CI and cron)
+    ///
+    /// Must come before the subcommand: `runctl --yes resources stop-all`.
+    /// Also enabled by RUNCTL_NON_INTERACTIVE=1 or `[cli] non_interactive = true`.
+    #[arg(
+        long = "yes",
+        visible_alias = "non-interactive",
+        env = "RUNCTL_NON_INTERACTIVE"
+    )]
+    non_interactive: bool,
+
+    /// Write JSONL progress events to stderr for long operations
+    ///
+    /// Each line has stage, percent, bytes, message and timestamp. Also
+    /// enabled by RUNCTL_PROGRESS_EVENTS=1 or `[cli] progress_events = true`.
+    #[arg(long, global = true, env = "RUNCTL_PROGRESS_EVENTS")]
+    progress_events: bool,
+
+    /// Only print ASCII: no Unicode symbols, bars or spinners
+    ///
+    /// For terminals and CI logs that don't render UTF-8. On by default when
+    /// the locale isn't UTF-8 or TERM=dumb. Also enabled by RUNCTL_ASCII=1
+    /// or `[cli] ascii = true`; `[cli] ascii = false` forces Unicode.
+    #[arg(long, global = true, env = "RUNCTL_ASCII")]
+    ascii: bool,
+
+    /// Also write JSON logs to this file (rotated by size)
+    ///
+    /// Logs at `[logging] level` (default info) regardless of --verbose.
+    /// Also set by RUNCTL_LOG_FILE or `[logging] file`.
+    #[arg(long, global = true, value_name = "PATH", env = "RUNCTL_LOG_FILE")]
+    log_file: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
@@ -68,6 +108,7 @@ enum Commands {
     ///   runctl local train.py
     ///   runctl local train.py -- --epochs 50 --batch-size 32
     ///   runctl local scripts/train_model.py -- --lr 0.001
+    ///   runctl local train.py --env WANDB_PROJECT=vision --env-file .env
     Local {
         /// Training script path (Python script or executable)
         #[arg(value_name = "SCRIPT")]
@@ -78,17 +119,173 @@ enum Commands {
         ///   runctl local train.py -- --epochs 50
         #[arg(last = true, value_name = "ARGS")]
         args: Vec<String>,
+        /// Environment variable for the script (repeatable; overrides [env])
+        #[arg(long = "env", value_name = "KEY=VALUE", value_parser = runctl::env_vars::parse_env_arg)]
+        env: Vec<(String, String)>,
+        /// Load environment variables from a .env file (--env wins)
+        #[arg(long, value_name = "PATH")]
+        env_file: Option<PathBuf>,
     },
     /// Train on RunPod
+    #[cfg(feature = "runpod")]
     Runpod {
         #[command(subcommand)]
         subcommand: runctl::runpod::RunpodCommands,
     },
+    /// Train on RunPod (not included in this build)
+    #[cfg(not(feature = "runpod"))]
+    Runpod {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, hide = true)]
+        args: Vec<String>,
+    },
     /// Train on AWS EC2
+    #[cfg(feature = "aws")]
     Aws {
         #[command(subcommand)]
         subcommand: runctl::aws::AwsCommands,
     },
+    /// Train on AWS EC2 (not included in this build)
+    #[cfg(not(feature = "aws"))]
+    Aws {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, hide = true)]
+        args: Vec<String>,
+    },
+    /// Train on Azure GPU VMs (NC/ND series)
+    Azure {
+        #[command(subcommand)]
+        subcommand: runctl::azure::AzureCommands,
+    },
+    /// Prepare AWS Batch for `runctl run --provider batch`
+    #[cfg(feature = "aws")]
+    Batch {
+        #[command(subcommand)]
+        subcommand: runctl::batch::BatchCommands,
+    },
+    /// Prepare AWS Batch for `runctl run --provider batch` (not included in this build)
+    #[cfg(not(feature = "aws"))]
+    Batch {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, hide = true)]
+        args: Vec<String>,
+    },
+    /// Run a training script end to end on any configured provider
+    ///
+    /// Creates a resource, syncs code, trains, waits for the script to exit
+    /// and terminates the resource (unless --keep or --detach). Progress is
+    /// saved locally; if this command dies mid-run, --resume picks it up.
+    ///
+    /// Examples:
+    ///   runctl run --provider aws train.py -- --epochs 50
+    ///   runctl run train.py --provider aws --instance-type g4dn.xlarge --spot --terminate-on-complete
+    ///   runctl run --provider runpod train.py --instance-type "NVIDIA RTX A4000"
+    ///   runctl run --provider azure train.py --spot --timeout 6h
+    ///   runctl run --provider batch train.py --instance-type g5.xlarge
+    ///   runctl run --resume
+    Run {
+        /// Provider to run on (aws, runpod, azure, batch, k8s, lyceum)
+        #[arg(long, value_name = "PROVIDER", required_unless_present = "resume")]
+        provider: Option<String>,
+        /// Training script path
+        #[arg(value_name = "SCRIPT", required_unless_present = "resume")]
+        script: Option<PathBuf>,
+        /// Instance type (defaults to the provider's configured default)
+        #[arg(long, value_name = "TYPE")]
+        instance_type: Option<String>,
+        /// Use spot/interruptible capacity where the provider supports it
+        #[arg(long)]
+        spot: bool,
+        /// Keep the resource running after training finishes
+        #[arg(long)]
+        keep: bool,
+        /// Terminate only once training succeeds; keep the resource after a
+        /// failure or timeout for debugging
+        #[arg(long, conflicts_with = "keep")]
+        terminate_on_complete: bool,
+        /// Return once training has started (implies --keep)
+        #[arg(long, conflicts_with_all = ["keep", "terminate_on_complete"])]
+        detach: bool,
+        /// Continue an interrupted run: the latest one, or SESSION (ID prefix)
+        #[arg(
+            long,
+            value_name = "SESSION",
+            num_args = 0..=1,
+            default_missing_value = "",
+            conflicts_with_all = ["provider", "script", "instance_type", "spot", "keep", "terminate_on_complete", "detach", "args"]
+        )]
+        resume: Option<String>,
+        /// Seconds between status checks (e.g. 30, 1m)
+        #[arg(long, value_name = "DURATION", default_value_t = 30, value_parser = runctl::units::parse_secs)]
+        interval: u64,
+        /// Stop waiting and clean up after this long (e.g. 6h)
+        #[arg(long, value_name = "DURATION", value_parser = runctl::units::parse_secs)]
+        timeout: Option<u64>,
+        /// Arguments passed to the script (after '--')
+        #[arg(last = true, value_name = "ARGS")]
+        args: Vec<String>,
+    },
+    /// Apply a run manifest: create, replace or retrain to match it
+    ///
+    /// Examples:
+    ///   runctl apply run.yaml
+    ///   runctl apply run.toml --yes
+    Apply {
+        /// Manifest file (YAML, or TOML with a .toml extension)
+        #[arg(value_name = "MANIFEST")]
+        manifest: PathBuf,
+        /// Apply without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Show what `apply` would change for a run manifest
+    ///
+    /// Examples:
+    ///   runctl plan run.yaml
+    Plan {
+        /// Manifest file (YAML, or TOML with a .toml extension)
+        #[arg(value_name = "MANIFEST")]
+        manifest: PathBuf,
+    },
+    /// Terminate the resource applied from a run manifest
+    ///
+    /// Examples:
+    ///   runctl destroy run.yaml
+    Destroy {
+        /// Manifest file (YAML, or TOML with a .toml extension)
+        #[arg(value_name = "MANIFEST")]
+        manifest: PathBuf,
+        /// Destroy without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Check run manifests before provisioning anything
+    ///
+    /// Parses each manifest as `apply` would, checks that the script and
+    /// hook files exist, that the instance type exists and is offered in the
+    /// region, and that the S3 buckets in `data` exist (with objects under
+    /// data.input), and estimates the cost of the instance type. Fails when
+    /// any manifest has errors.
+    ///
+    /// Examples:
+    ///   runctl validate job.yaml
+    ///   runctl validate sweeps/*.yaml --duration 12h
+    ///   runctl validate job.yaml --offline --strict
+    ///   runctl validate --schema > runctl-manifest.schema.json
+    Validate {
+        /// Manifest files (YAML, or TOML with a .toml extension)
+        #[arg(value_name = "MANIFEST", required_unless_present = "schema")]
+        manifests: Vec<PathBuf>,
+        /// Print the manifest JSON Schema instead
+        #[arg(long, conflicts_with = "manifests")]
+        schema: bool,
+        /// Skip the checks and cost estimate that call AWS
+        #[arg(long)]
+        offline: bool,
+        /// Planned run time to estimate the total cost for (e.g. 12h, 2d)
+        #[arg(long, value_parser = runctl::units::parse_secs)]
+        duration: Option<u64>,
+        /// Fail on warnings as well as errors
+        #[arg(long)]
+        strict: bool,
+    },
     /// Docker operations (build, push, container training)
     ///
     /// Build and push Docker images to ECR, and run training in containers.
@@ -97,10 +294,35 @@ enum Commands {
     ///   runctl docker build
     ///   runctl docker build --push --repository runctl-training
     ///   runctl docker build-push --repository runctl-training --tag v1.0
+    #[cfg(feature = "aws")]
     Docker {
         #[command(subcommand)]
         subcommand: docker_cli::DockerCommands,
     },
+    /// Build and push Docker images to ECR (not included in this build)
+    #[cfg(not(feature = "aws"))]
+    Docker {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, hide = true)]
+        args: Vec<String>,
+    },
+    /// Build training images and push them to ECR, recording their digests
+    ///
+    /// Examples:
+    ///   runctl image build --push
+    ///   runctl image build --on i-0123456789abcdef0
+    ///   runctl image push llm-train:dev
+    ///   runctl image list
+    #[cfg(feature = "aws")]
+    Image {
+        #[command(subcommand)]
+        subcommand: runctl::image::ImageCommands,
+    },
+    /// Build training images and push them to ECR (not included in this build)
+    #[cfg(not(feature = "aws"))]
+    Image {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, hide = true)]
+        args: Vec<String>,
+    },
     /// Monitor training progress
     ///
     /// Monitors training logs and checkpoint updates. Use --follow for continuous
@@ -110,6 +332,7 @@ enum Commands {
     ///   runctl monitor --log training.log
     ///   runctl monitor --checkpoint ./checkpoints/ --follow
     ///   runctl monitor --log training.log --checkpoint ./checkpoints/ --follow
+    ///   runctl monitor --provider k8s --job runctl-my-model-1a2b3c4d --follow
     Monitor {
         /// Training log file path to monitor
         #[arg(long, value_name = "LOG_PATH")]
@@ -122,22 +345,79 @@ enum Commands {
         /// Continuously monitors for new log entries and checkpoint updates.
         #[arg(short, long)]
         follow: bool,
+        /// Stream the log of a job on this provider instead (e.g. k8s)
+        #[arg(long, requires = "job", conflicts_with_all = ["log", "checkpoint"])]
+        provider: Option<String>,
+        /// Job (resource ID) to stream the log of, with --provider
+        #[arg(long, requires = "provider")]
+        job: Option<String>,
     },
     /// Manage checkpoints
     Checkpoint {
         #[command(subcommand)]
         subcommand: runctl::checkpoint::CheckpointCommands,
     },
+    /// Manage API keys and tokens (OS keyring or AWS Secrets Manager)
+    ///
+    /// Config and training environment values can reference a stored secret
+    /// as secret://NAME instead of holding it in plaintext.
+    ///
+    /// Examples:
+    ///   runctl secrets set runpod
+    ///   runctl secrets list
+    Secrets {
+        #[command(subcommand)]
+        subcommand: runctl::secrets::SecretsCommands,
+    },
+    /// Dataset cache on training instances (scratch, EBS, then S3)
+    ///
+    /// Fetches dataset objects through instance-store and EBS tiers with LRU
+    /// eviction, so repeated epochs over data larger than local disk read
+    /// locally.
+    ///
+    /// Examples:
+    ///   runctl cache get train/shard-0001.tar
+    ///   runctl cache status i-1234567890abcdef0
+    Cache {
+        #[command(subcommand)]
+        subcommand: runctl::cache::CacheCommands,
+    },
     /// S3 operations (upload, download, sync, cleanup)
+    #[cfg(feature = "aws")]
     S3 {
         #[command(subcommand)]
         subcommand: runctl::s3::S3Commands,
     },
+    /// S3 operations (not included in this build)
+    #[cfg(not(feature = "aws"))]
+    S3 {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, hide = true)]
+        args: Vec<String>,
+    },
     /// Review and manage resources (AWS, RunPod, local)
     Resources {
         #[command(subcommand)]
         subcommand: runctl::resources::ResourceCommands,
     },
+    /// Apply stop/start schedules saved with `resources schedule`
+    ///
+    /// Examples:
+    ///   runctl scheduler daemon
+    ///   runctl scheduler daemon --once
+    Scheduler {
+        #[command(subcommand)]
+        subcommand: runctl::schedule::SchedulerCommands,
+    },
+    /// Historical costs, including terminated instances, and pre-launch estimates
+    ///
+    /// Examples:
+    ///   runctl costs report --since 30d --group-by project
+    ///   runctl cost estimate --spec job.yaml --duration 48h
+    #[command(visible_alias = "cost")]
+    Costs {
+        #[command(subcommand)]
+        subcommand: runctl::costs::CostsCommands,
+    },
     /// Manage configuration
     ///
     /// View, set, and validate configuration settings. Use 'init' to create a new config file.
@@ -155,10 +435,16 @@ enum Commands {
     /// Creates a new configuration file with default values. The config file can be
     /// placed in the current directory (.runctl.toml) or in the user config directory.
     ///
+    /// `runctl init job` scaffolds a run manifest and training script instead.
+    ///
     /// Examples:
     ///   runctl init
     ///   runctl init --config-path ~/.config/runctl/config.toml
+    ///   runctl init job --template pytorch-ddp
+    #[command(args_conflicts_with_subcommands = true)]
     Init {
+        #[command(subcommand)]
+        subcommand: Option<runctl::manifest::scaffold::InitCommands>,
         /// Output path for config file
         #[arg(long, default_value = ".runctl.toml")]
         config_path: PathBuf,
@@ -169,29 +455,201 @@ enum Commands {
         #[arg(short, long)]
         detailed: bool,
     },
-    /// Interactive top-like dashboard (ratatui) - shows resources, costs, and processes
+    /// Check the account for risky defaults (public buckets, open SSH, ...)
     ///
-    /// Real-time monitoring of instances, processes, and costs. Similar to 'top' command
-    /// but for cloud training resources. Press 'q' to quit.
+    /// Looks at checkpoint buckets, security groups, EBS volumes, GPU
+    /// instances without runctl tags and fleets with idle workers, and
+    /// reports each finding with a severity. With --fail-on, exits non-zero
+    /// when a finding at or above that severity is found.
+    ///
+    /// Examples:
+    ///   runctl scan
+    ///   runctl scan --bucket s3://datasets --min-severity medium
+    ///   runctl scan --output json --fail-on high
+    #[cfg(feature = "aws")]
+    Scan {
+        /// Also check this bucket (repeatable; configured buckets are always checked)
+        #[arg(long = "bucket", value_name = "BUCKET")]
+        buckets: Vec<String>,
+        /// Hide findings below this severity
+        #[arg(long, value_enum, default_value_t = runctl::scan::Severity::Low)]
+        min_severity: runctl::scan::Severity,
+        /// Exit non-zero when a finding at or above this severity is found
+        #[arg(long, value_enum)]
+        fail_on: Option<runctl::scan::Severity>,
+    },
+    /// Check the account for risky defaults (not included in this build)
+    #[cfg(not(feature = "aws"))]
+    Scan {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, hide = true)]
+        args: Vec<String>,
+    },
+    /// Interactive top-like dashboard (ratatui) - shows instances, jobs, costs, and logs
+    ///
+    /// Real-time monitoring of instances, training jobs, costs, and training logs, with
+    /// per-instance GPU sparklines. Similar to 'top' command but for cloud training
+    /// resources. Select an instance with j/k, then Enter to SSH, s to stop, t to
+    /// terminate. Press 'q' to quit.
     ///
     /// Examples:
     ///   runctl top
     ///   runctl top --interval 2
     Top {
-        /// Update interval in seconds
-        #[arg(short, long, default_value_t = 5)]
+        /// Update interval (e.g. 5, 30s, 1m; bare numbers are seconds)
+        #[arg(short, long, default_value_t = 5, value_parser = runctl::units::parse_secs)]
+        interval: u64,
+    },
+    /// Live dashboard for a single training job
+    ///
+    /// Shows the log tail, parsed metrics (epoch/step/loss), CPU/GPU utilization,
+    /// cost so far, and ETA for one job. JOB_ID is an EC2 instance ID or a local
+    /// session ID (prefix). Press 'q' to quit.
+    ///
+    /// Use --detach to keep watching in the background and --attach to come
+    /// back later with recent log lines and events preserved.
+    ///
+    /// Examples:
+    ///   runctl watch i-1234567890abcdef0
+    ///   runctl watch 3f2a9c1e --log training.log
+    ///   runctl watch i-1234567890abcdef0 --output json
+    ///   runctl watch i-1234567890abcdef0 --detach
+    ///   runctl watch --attach i-1234567890abcdef0
+    ///   runctl watch --stop i-1234567890abcdef0
+    Watch {
+        /// EC2 instance ID or local session ID
+        #[arg(value_name = "JOB_ID")]
+        job_id: String,
+        /// Log file to tail (local jobs without a recorded log file)
+        #[arg(long, value_name = "LOG_PATH")]
+        log: Option<PathBuf>,
+        /// Update interval (e.g. 5, 30s, 1m; bare numbers are seconds)
+        #[arg(short, long, default_value_t = 5, value_parser = runctl::units::parse_secs)]
         interval: u64,
+        /// Keep watching in a background daemon and return immediately
+        #[arg(long, conflicts_with_all = ["attach", "stop"])]
+        detach: bool,
+        /// Re-attach to a detached watch session
+        #[arg(long, conflicts_with = "stop")]
+        attach: bool,
+        /// Stop a detached watch session's daemon
+        #[arg(long)]
+        stop: bool,
+        /// Run as the background daemon (used internally by --detach)
+        #[arg(long, hide = true)]
+        detached_worker: bool,
+    },
+    /// Report training status from the instance (runs on the instance)
+    ///
+    /// Writes a heartbeat, CPU/GPU/memory usage, progress parsed from the
+    /// training log, and the newest checkpoint every --interval to a local
+    /// file and, with --s3, to S3, where `aws status`, `top` and `watch` read
+    /// it. Exits once training has finished. Started by `aws train --agent`.
+    ///
+    /// Examples:
+    ///   runctl agent --instance-id i-1234567890abcdef0 --project-dir ~/my-project
+    ///   runctl agent --instance-id i-1234567890abcdef0 --project-dir ~/my-project --s3 s3://my-bucket/runctl/agent
+    #[cfg(feature = "aws")]
+    Agent {
+        /// ID the status is reported under (the EC2 instance ID)
+        #[arg(long, value_name = "INSTANCE_ID")]
+        instance_id: String,
+        /// Directory with training.log, training.pid and the exit code file
+        #[arg(long, value_name = "DIR", default_value = ".")]
+        project_dir: PathBuf,
+        /// Training log (default: <project-dir>/training.log)
+        #[arg(long, value_name = "PATH")]
+        log: Option<PathBuf>,
+        /// Checkpoint directory (default: <project-dir>/checkpoints)
+        #[arg(long, value_name = "DIR")]
+        checkpoint_dir: Option<PathBuf>,
+        /// Report interval (e.g. 15, 30s, 1m; bare numbers are seconds)
+        #[arg(short, long, default_value_t = runctl::agent::DEFAULT_INTERVAL_SECS, value_parser = runctl::units::parse_secs)]
+        interval: u64,
+        /// S3 prefix to upload the status to (s3://bucket/prefix)
+        #[arg(long, value_name = "S3_PATH")]
+        s3: Option<String>,
+        /// Local status file (default: <project-dir>/agent-status.json)
+        #[arg(long, value_name = "PATH")]
+        status_file: Option<PathBuf>,
+        /// Report once and exit
+        #[arg(long)]
+        once: bool,
+        /// Dataset cache directory to report on (default: ~/.runctl/cache)
+        #[arg(long, value_name = "DIR")]
+        cache_dir: Option<PathBuf>,
+    },
+    /// Report training status from the instance (not included in this build)
+    #[cfg(not(feature = "aws"))]
+    Agent {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, hide = true)]
+        args: Vec<String>,
     },
     /// Workflow commands (complete training workflows)
     ///
     /// High-level commands that orchestrate multiple operations.
     /// Examples:
     ///   runctl workflow train training/train.py --instance-type g4dn.xlarge
+    #[cfg(feature = "aws")]
     Workflow {
         #[command(subcommand)]
         subcommand: runctl::workflow::WorkflowCommands,
     },
-    /// Data transfer operations (local ↔ S3 ↔ training instances)
+    /// Workflow commands (not included in this build)
+    #[cfg(not(feature = "aws"))]
+    Workflow {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, hide = true)]
+        args: Vec<String>,
+    },
+    /// Job commands that work across providers
+    ///
+    /// Act on a running job by its instance or pod ID.
+    /// Examples:
+    ///   runctl jobs migrate abc123xyz --to aws:g5.xlarge --script train.py
+    Jobs {
+        #[command(subcommand)]
+        subcommand: runctl::jobs::JobsCommands,
+    },
+    /// Fault-injection drills for testing a job's recovery setup
+    ///
+    /// Examples:
+    ///   runctl chaos interrupt i-1234567890abcdef0 --resume train.py
+    #[cfg(feature = "aws")]
+    Chaos {
+        #[command(subcommand)]
+        subcommand: runctl::jobs::ChaosCommands,
+    },
+    /// Fault-injection drills (not included in this build)
+    #[cfg(not(feature = "aws"))]
+    Chaos {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, hide = true)]
+        args: Vec<String>,
+    },
+    /// Queue training jobs and run them as instances become available
+    ///
+    /// Examples:
+    ///   runctl queue submit train.py --target aws:g5.xlarge -- --epochs 50
+    ///   runctl queue list
+    ///   runctl queue run --max-concurrent 2
+    #[cfg(feature = "aws")]
+    Queue {
+        #[command(subcommand)]
+        subcommand: runctl::queue::QueueCommands,
+    },
+    /// Queue training jobs (not included in this build)
+    #[cfg(not(feature = "aws"))]
+    Queue {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, hide = true)]
+        args: Vec<String>,
+    },
+    /// CI integration
+    ///
+    /// Examples:
+    ///   runctl ci generate github --script training/train.py --spot
+    Ci {
+        #[command(subcommand)]
+        subcommand: runctl::ci::CiCommands,
+    },
+    /// Data transfer operations (local <-> S3 <-> training instances)
     ///
     /// Transfers data between local storage, S3, and training instances.
     /// Supports parallel transfers, compression, and resumable operations.
@@ -203,13 +661,23 @@ enum Commands {
     ///   runctl transfer ./data/ s3://bucket/data/
     ///   runctl transfer s3://bucket/checkpoints/ ./checkpoints/ --parallel 10
     ///   runctl transfer instance:i-123:/mnt/data ./local_data/
+    ///   runctl transfer ./checkpoints/ s3://bucket/checkpoints/ --encrypt
+    ///   runctl transfer ./data/ s3://bucket/data/ --detach
+    ///   runctl transfer ./dataset.tar s3://bucket/data/ --part-size 64MB --bandwidth-limit 50MB/s
+    ///   runctl transfer ./data/ s3://bucket/data/ --manifest
+    ///   runctl transfer resume t-1a2b3c4d
+    ///   runctl transfer sessions list
+    #[cfg(feature = "aws")]
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Transfer {
+        #[command(subcommand)]
+        subcommand: Option<runctl::transfer_session::TransferCommands>,
         /// Source location (local path, s3://bucket/key, or instance:path)
-        #[arg(value_name = "SOURCE")]
-        source: String,
+        #[arg(value_name = "SOURCE", required = true)]
+        source: Option<String>,
         /// Destination location (local path, s3://bucket/key, or instance:path)
-        #[arg(value_name = "DESTINATION")]
-        destination: String,
+        #[arg(value_name = "DESTINATION", required = true)]
+        destination: Option<String>,
         /// Number of parallel transfers (default: 10)
         #[arg(long, value_name = "COUNT")]
         parallel: Option<usize>,
@@ -222,7 +690,47 @@ enum Commands {
         /// Resume interrupted transfers (default: true)
         #[arg(long, default_value_t = true)]
         resume: bool,
+        /// Skip the egress cost confirmation
+        #[arg(short, long)]
+        yes: bool,
+        /// Encrypt files client-side before uploading to S3 (see [encryption] config)
+        #[arg(long)]
+        encrypt: bool,
+        /// Run the transfer in the background (see `transfer sessions`)
+        #[arg(long)]
+        detach: bool,
+        /// Part size for large files, e.g. 64MB (default: [transfer] part_size or 16MB)
+        #[arg(long, value_name = "SIZE", value_parser = runctl::units::parse_size)]
+        part_size: Option<u64>,
+        /// Parts of each large file in flight at once (default: 4)
+        #[arg(long, value_name = "COUNT")]
+        part_parallel: Option<usize>,
+        /// Cap throughput across the transfer, e.g. 50MB/s (disables s5cmd)
+        #[arg(long, value_name = "RATE", value_parser = runctl::units::parse_rate)]
+        bandwidth_limit: Option<u64>,
+        /// Store SHA-256 checksums with an upload (SHA256SUMS) and check downloads against them
+        #[arg(long)]
+        manifest: bool,
+    },
+    /// Data transfer operations (not included in this build)
+    #[cfg(not(feature = "aws"))]
+    Transfer {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, hide = true)]
+        args: Vec<String>,
     },
+    /// Interactive shell with persistent context and tab completion
+    ///
+    /// Runs runctl commands without the `runctl` prefix in one process, so the
+    /// config and AWS credentials are loaded once. `use instance <id>`,
+    /// `use project <name>` and `use profile <name>` select context for later
+    /// commands, where `$instance` and `$project` expand to the selection.
+    /// History is kept in ~/.runctl/shell_history.
+    ///
+    /// Examples:
+    ///   runctl shell
+    ///   runctl> use instance i-1234567890abcdef0
+    ///   runctl> aws status $instance
+    Shell,
     /// Execute a training script or command (generic executor)
     ///
     /// Executes a command with runctl environment setup. Useful for running
@@ -243,134 +751,522 @@ enum Commands {
         ///   runctl exec train -- --epochs 50
         #[arg(last = true, value_name = "ARGS")]
         args: Vec<String>,
+        /// Environment variable for the command (repeatable; overrides [env])
+        #[arg(long = "env", value_name = "KEY=VALUE", value_parser = runctl::env_vars::parse_env_arg)]
+        env: Vec<(String, String)>,
+        /// Load environment variables from a .env file (--env wins)
+        #[arg(long, value_name = "PATH")]
+        env_file: Option<PathBuf>,
     },
+    /// `runctl-<name>` executable on PATH (see `runctl::aliases`)
+    #[command(external_subcommand)]
+    External(Vec<String>),
+}
+
+/// Names (and clap aliases) of the built-in commands, which aliases can't shadow
+fn builtin_commands() -> Vec<String> {
+    use clap::CommandFactory;
+    let mut names = vec!["help".to_string()];
+    for command in Cli::command().get_subcommands() {
+        names.push(command.get_name().to_string());
+        names.extend(command.get_all_aliases().map(str::to_string));
+    }
+    names
+}
+
+/// Run `runctl-<name>` from PATH for a command that isn't built in or aliased
+fn run_external_command(args: &[String], config_path: Option<&Path>) -> Result<()> {
+    let Some((name, rest)) = args.split_first() else {
+        return Ok(());
+    };
+    let Some(program) = runctl::aliases::find_external(name) else {
+        return Err(runctl::error::TrainctlError::Validation {
+            field: "command".to_string(),
+            reason: format!(
+                "unrecognized command '{}': not a built-in command, an [aliases] entry, or a `{}{}` executable on PATH (see `runctl --help`)",
+                name,
+                runctl::aliases::EXTERNAL_PREFIX,
+                name
+            ),
+        }
+        .into());
+    };
+    let code = runctl::aliases::run_external(&program, rest, config_path)?;
+    if code != 0 {
+        std::process::exit(code);
+    }
+    Ok(())
+}
+
+/// Error for a command whose cargo feature this build left out
+#[cfg(not(all(feature = "aws", feature = "dashboard", feature = "runpod")))]
+fn feature_not_compiled(feature: &str) -> anyhow::Error {
+    runctl::error::TrainctlError::from(runctl::error::ConfigError::FeatureNotCompiled(
+        feature.to_string(),
+    ))
+    .into()
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
+    // Aliases live in the config, so load it before clap sees the arguments
+    let raw_args: Vec<String> = std::env::args().collect();
+    // Listing commands fetch EC2 state while the config loads and args parse
+    #[cfg(feature = "aws")]
+    runctl::resources::prefetch::start(&raw_args);
+    let config = runctl::config::Config::load(runctl::aliases::config_flag(&raw_args).as_deref())?;
+    let builtins = builtin_commands();
+    let cli = Cli::parse_from(runctl::aliases::expand(
+        raw_args,
+        &config.aliases,
+        &builtins,
+    )?);
+
+    // Setup logging: console follows --verbose, the log file its own level
+    runctl::logging::init(cli.verbose, cli.log_file.as_deref(), &config.logging)?;
+    runctl::prompt::set_non_interactive(cli.non_interactive || config.cli.non_interactive);
+    runctl::progress::set_enabled(cli.progress_events || config.cli.progress_events);
+    runctl::output_style::set_ascii(
+        cli.ascii
+            || config
+                .cli
+                .ascii
+                .unwrap_or_else(runctl::output_style::detect),
+    );
+    for name in runctl::aliases::shadowed(&config.aliases, &builtins) {
+        tracing::warn!(
+            "alias '{}' is ignored: a built-in command has that name",
+            name
+        );
+    }
 
-    // Setup logging - suppress INFO by default, only show warnings and errors
-    let filter = if cli.verbose {
-        EnvFilter::new("debug")
+    // `runctl shell` runs further commands through `execute` itself
+    let output_format = cli.output.clone();
+    let result = if matches!(cli.command, Commands::Shell) {
+        shell_cli::run(config, cli.config.clone(), builtins).await
     } else {
-        EnvFilter::new("warn") // Changed from "info" to "warn" to suppress INFO logs
+        execute(cli, &config).await
     };
 
-    tracing_subscriber::fmt()
-        .with_env_filter(filter)
-        .with_target(false)
-        .init();
+    if let Err(e) = result {
+        std::process::exit(report_error(&e, &output_format));
+    }
+
+    Ok(())
+}
 
-    // Load config
-    let config = runctl::config::Config::load(cli.config.as_deref())?;
+/// Run one parsed command (from the process arguments or a `runctl shell` line)
+async fn execute(cli: Cli, config: &runctl::config::Config) -> Result<()> {
+    // `--output github` runs commands in text mode and annotates failures
+    let output = if cli.output == "github" {
+        "text"
+    } else {
+        cli.output.as_str()
+    };
 
-    // Execute command with error handling for JSON output
     // Preserve error context by using anyhow::Error::from which preserves the error chain
-    let result: anyhow::Result<()> = match cli.command {
-        Commands::Local { script, args } => runctl::local::train(script, args, &config)
+    match cli.command {
+        Commands::Local {
+            script,
+            args,
+            env,
+            env_file,
+        } => {
+            let env = runctl::env_vars::collect(config, env_file.as_deref(), &env)?;
+            runctl::local::train_with_env(script, args, &env, config)
+                .await
+                .map_err(anyhow::Error::from)
+        }
+        #[cfg(feature = "runpod")]
+        Commands::Runpod { subcommand } => {
+            runctl::runpod::handle_command(subcommand, config, output)
+                .await
+                .map_err(anyhow::Error::from)
+        }
+        #[cfg(not(feature = "runpod"))]
+        Commands::Runpod { .. } => Err(feature_not_compiled("runpod")),
+        #[cfg(feature = "aws")]
+        Commands::Aws { subcommand } => {
+            runctl::aws::handle_command(subcommand, config, output, cli.verbose)
+                .await
+                .map_err(anyhow::Error::from)
+        }
+        #[cfg(not(feature = "aws"))]
+        Commands::Aws { .. } => Err(feature_not_compiled("aws")),
+        Commands::Azure { subcommand } => runctl::azure::handle_command(subcommand, config, output)
             .await
             .map_err(anyhow::Error::from),
-        Commands::Runpod { subcommand } => runctl::runpod::handle_command(subcommand, &config)
+        #[cfg(feature = "aws")]
+        Commands::Batch { subcommand } => runctl::batch::handle_command(subcommand, config, output)
             .await
             .map_err(anyhow::Error::from),
-        Commands::Aws { subcommand } => {
-            runctl::aws::handle_command(subcommand, &config, &cli.output)
+        #[cfg(not(feature = "aws"))]
+        Commands::Batch { .. } => Err(feature_not_compiled("aws")),
+        Commands::Run {
+            provider,
+            script,
+            instance_type,
+            spot,
+            keep,
+            terminate_on_complete,
+            detach,
+            resume,
+            interval,
+            timeout,
+            args,
+        } => {
+            let interval = std::time::Duration::from_secs(interval);
+            let timeout = timeout.map(std::time::Duration::from_secs);
+            if let Some(session) = resume {
+                let session = Some(session.as_str()).filter(|s| !s.is_empty());
+                runctl::run::resume(session, interval, timeout, config, output)
+                    .await
+                    .map_err(anyhow::Error::from)
+            } else {
+                let cleanup = if keep {
+                    runctl::training::Cleanup::Never
+                } else if terminate_on_complete {
+                    runctl::training::Cleanup::OnSuccess
+                } else {
+                    runctl::training::Cleanup::Always
+                };
+                let options = runctl::run::RunOptions {
+                    // Both are required unless --resume
+                    provider: provider.unwrap_or_default(),
+                    script: script.unwrap_or_default(),
+                    script_args: args,
+                    instance_type,
+                    spot,
+                    cleanup,
+                    detach,
+                    interval,
+                    timeout,
+                };
+                runctl::run::run(options, config, output)
+                    .await
+                    .map_err(anyhow::Error::from)
+            }
+        }
+        Commands::Apply { manifest, yes } => {
+            runctl::manifest::apply(&manifest, yes, config, output)
                 .await
                 .map_err(anyhow::Error::from)
         }
-        Commands::Docker { subcommand } => {
-            docker_cli::handle_command(subcommand, &config, &cli.output)
+        Commands::Plan { manifest } => runctl::manifest::show_plan(&manifest, config, output)
+            .await
+            .map_err(anyhow::Error::from),
+        Commands::Destroy { manifest, yes } => {
+            runctl::manifest::destroy(&manifest, yes, config, output)
                 .await
                 .map_err(anyhow::Error::from)
         }
+        Commands::Validate { schema: true, .. } => {
+            println!("{}", runctl::manifest::json_schema()?);
+            Ok(())
+        }
+        Commands::Validate {
+            manifests,
+            offline,
+            duration,
+            strict,
+            ..
+        } => {
+            let options = runctl::manifest::ValidateOptions {
+                offline,
+                duration_secs: duration,
+                strict,
+            };
+            runctl::manifest::validate(&manifests, &options, config, output)
+                .await
+                .map_err(anyhow::Error::from)
+        }
+        #[cfg(feature = "aws")]
+        Commands::Docker { subcommand } => docker_cli::handle_command(subcommand, config, output)
+            .await
+            .map_err(anyhow::Error::from),
+        #[cfg(not(feature = "aws"))]
+        Commands::Docker { .. } => Err(feature_not_compiled("aws")),
+        #[cfg(feature = "aws")]
+        Commands::Image { subcommand } => runctl::image::handle_command(subcommand, config, output)
+            .await
+            .map_err(anyhow::Error::from),
+        #[cfg(not(feature = "aws"))]
+        Commands::Image { .. } => Err(feature_not_compiled("aws")),
         Commands::Monitor {
             log,
             checkpoint,
             follow,
-        } => runctl::monitor::monitor(log, checkpoint, follow)
-            .await
-            .map_err(anyhow::Error::from),
+            provider,
+            job,
+        } => match (provider, job) {
+            (Some(provider), Some(job)) => {
+                runctl::monitor::monitor_job(&provider, &job, follow, config).await
+            }
+            _ => runctl::monitor::monitor(log, checkpoint, follow).await,
+        }
+        .map_err(anyhow::Error::from),
         Commands::Checkpoint { subcommand } => {
-            runctl::checkpoint::handle_command(subcommand, &cli.output)
+            runctl::checkpoint::handle_command(subcommand, config, cli.config.as_deref(), output)
+                .await
+                .map_err(anyhow::Error::from)
+        }
+        Commands::Secrets { subcommand } => {
+            runctl::secrets::handle_command(subcommand, config, output)
                 .await
                 .map_err(anyhow::Error::from)
         }
+        Commands::Cache { subcommand } => runctl::cache::handle_command(subcommand, config, output)
+            .await
+            .map_err(anyhow::Error::from),
         Commands::Config { subcommand } => {
-            runctl::config::handle_command(subcommand, cli.config.as_deref(), &cli.output)
+            runctl::config::handle_command(subcommand, cli.config.as_deref(), output)
                 .await
                 .map_err(anyhow::Error::from)
         }
-        Commands::S3 { subcommand } => runctl::s3::handle_command(subcommand, &config, &cli.output)
+        #[cfg(feature = "aws")]
+        Commands::S3 { subcommand } => runctl::s3::handle_command(subcommand, config, output)
             .await
             .map_err(anyhow::Error::from),
+        #[cfg(not(feature = "aws"))]
+        Commands::S3 { .. } => Err(feature_not_compiled("aws")),
         Commands::Resources { subcommand } => {
-            runctl::resources::handle_command(subcommand, &config, &cli.output)
+            runctl::resources::handle_command(subcommand, config, output)
                 .await
                 .map_err(anyhow::Error::from)
         }
-        Commands::Init { config_path } => {
+        Commands::Scheduler { subcommand } => {
+            runctl::schedule::handle_command(subcommand, config, output)
+                .await
+                .map_err(anyhow::Error::from)
+        }
+        Commands::Costs { subcommand } => runctl::costs::handle_command(subcommand, config, output)
+            .await
+            .map_err(anyhow::Error::from),
+        Commands::Init {
+            subcommand: Some(subcommand),
+            ..
+        } => runctl::manifest::scaffold::handle_command(subcommand).map_err(anyhow::Error::from),
+        Commands::Init {
+            subcommand: None,
+            config_path,
+        } => {
             runctl::config::init_config(&config_path).map_err(anyhow::Error::from)?;
             Ok(())
         }
+        #[cfg(feature = "aws")]
         Commands::Status { detailed } => {
-            runctl::resources::show_quick_status(detailed, &config, &cli.output)
+            runctl::resources::show_quick_status(detailed, config, output)
                 .await
                 .map_err(anyhow::Error::from)
         }
-        Commands::Top { interval } => runctl::dashboard::run_dashboard(&config, interval)
+        #[cfg(not(feature = "aws"))]
+        Commands::Status { .. } => Err(feature_not_compiled("aws")),
+        #[cfg(feature = "aws")]
+        Commands::Scan {
+            buckets,
+            min_severity,
+            fail_on,
+        } => {
+            let options = runctl::scan::ScanOptions {
+                buckets,
+                min_severity,
+                fail_on,
+            };
+            runctl::scan::run(options, config, output)
+                .await
+                .map_err(anyhow::Error::from)
+        }
+        #[cfg(not(feature = "aws"))]
+        Commands::Scan { .. } => Err(feature_not_compiled("aws")),
+        #[cfg(feature = "dashboard")]
+        Commands::Top { interval } => runctl::dashboard::run_dashboard(config, interval)
             .await
             .map_err(anyhow::Error::from),
+        #[cfg(not(feature = "dashboard"))]
+        Commands::Top { .. } => Err(feature_not_compiled("dashboard")),
+        Commands::Watch {
+            job_id,
+            log,
+            interval,
+            detach,
+            attach,
+            stop,
+            detached_worker,
+        } => {
+            use runctl::watch::WatchMode;
+            let mode = if detached_worker {
+                WatchMode::DaemonWorker
+            } else if detach {
+                WatchMode::Detach {
+                    config_path: cli.config.clone(),
+                }
+            } else if attach {
+                WatchMode::Attach
+            } else if stop {
+                WatchMode::Stop
+            } else {
+                WatchMode::Interactive
+            };
+            runctl::watch::run_job_watch(&job_id, log, interval, mode, config, output)
+                .await
+                .map_err(anyhow::Error::from)
+        }
+        #[cfg(feature = "aws")]
+        Commands::Agent {
+            instance_id,
+            project_dir,
+            log,
+            checkpoint_dir,
+            interval,
+            s3,
+            status_file,
+            once,
+            cache_dir,
+        } => {
+            let options = runctl::agent::AgentOptions {
+                instance_id,
+                project_dir,
+                log,
+                checkpoint_dir,
+                interval_secs: interval,
+                s3,
+                status_file,
+                once,
+                cache_dir,
+            };
+            runctl::agent::run_agent(options, output)
+                .await
+                .map_err(anyhow::Error::from)
+        }
+        #[cfg(not(feature = "aws"))]
+        Commands::Agent { .. } => Err(feature_not_compiled("aws")),
+        #[cfg(feature = "aws")]
         Commands::Workflow { subcommand } => {
-            runctl::workflow::handle_command(subcommand, &config, &cli.output)
+            runctl::workflow::handle_command(subcommand, config, output)
+                .await
+                .map_err(anyhow::Error::from)
+        }
+        #[cfg(not(feature = "aws"))]
+        Commands::Workflow { .. } => Err(feature_not_compiled("aws")),
+        Commands::Jobs { subcommand } => runctl::jobs::handle_command(subcommand, config, output)
+            .await
+            .map_err(anyhow::Error::from),
+        #[cfg(feature = "aws")]
+        Commands::Chaos { subcommand } => {
+            runctl::jobs::handle_chaos_command(subcommand, config, output)
                 .await
                 .map_err(anyhow::Error::from)
         }
+        #[cfg(not(feature = "aws"))]
+        Commands::Chaos { .. } => Err(feature_not_compiled("aws")),
+        #[cfg(feature = "aws")]
+        Commands::Queue { subcommand } => runctl::queue::handle_command(subcommand, config, output)
+            .await
+            .map_err(anyhow::Error::from),
+        #[cfg(not(feature = "aws"))]
+        Commands::Queue { .. } => Err(feature_not_compiled("aws")),
+        Commands::Ci { subcommand } => runctl::ci::handle_command(subcommand, config, output)
+            .await
+            .map_err(anyhow::Error::from),
+        #[cfg(feature = "aws")]
         Commands::Transfer {
+            subcommand: Some(subcommand),
+            ..
+        } => runctl::transfer_session::handle_command(
+            subcommand,
+            config,
+            cli.config.as_deref(),
+            output,
+        )
+        .await
+        .map_err(anyhow::Error::from),
+        #[cfg(feature = "aws")]
+        Commands::Transfer {
+            subcommand: None,
             source,
             destination,
             parallel,
             compress,
             verify,
             resume,
-        } => runctl::data_transfer::handle_transfer(
-            source,
-            destination,
-            parallel,
-            compress,
-            verify,
-            resume,
-            &config,
-        )
-        .await
-        .map_err(anyhow::Error::from),
-        Commands::Exec { command, args } => {
+            yes,
+            encrypt,
+            detach,
+            part_size,
+            part_parallel,
+            bandwidth_limit,
+            manifest,
+        } => {
+            // clap requires both locations when no subcommand is given
+            let request = runctl::data_transfer::TransferRequest {
+                parallel,
+                compress,
+                verify,
+                resume,
+                yes,
+                encrypt,
+                detach,
+                part_size,
+                part_parallel,
+                bandwidth_limit,
+                manifest,
+                ..runctl::data_transfer::TransferRequest::new(
+                    source.unwrap_or_default(),
+                    destination.unwrap_or_default(),
+                )
+            };
+            runctl::data_transfer::handle_transfer(request, config, output, cli.config.as_deref())
+                .await
+                .map_err(anyhow::Error::from)
+        }
+        #[cfg(not(feature = "aws"))]
+        Commands::Transfer { .. } => Err(feature_not_compiled("aws")),
+        Commands::Exec {
+            command,
+            args,
+            env,
+            env_file,
+        } => {
             // Exec command - run arbitrary command with runctl environment
             // For now, treat as local training with the command as script
             let script = PathBuf::from(&command);
-            runctl::local::train(script, args, &config)
+            let env = runctl::env_vars::collect(config, env_file.as_deref(), &env)?;
+            runctl::local::train_with_env(script, args, &env, config)
                 .await
                 .map_err(anyhow::Error::from)
         }
-    };
+        Commands::External(args) => run_external_command(&args, cli.config.as_deref()),
+        Commands::Shell => Err(anyhow::anyhow!("already in a runctl shell")),
+    }
+}
 
-    // Handle errors with JSON format if requested
-    if let Err(e) = result {
-        if cli.output == "json" {
-            use serde_json::json;
-            let error_json = json!({
-                "success": false,
-                "error": {
-                    "message": format!("{}", e),
-                    "source": e.source().map(|s| format!("{}", s)).unwrap_or_else(|| "unknown".to_string()),
-                }
-            });
-            eprintln!("{}", serde_json::to_string_pretty(&error_json)?);
-            std::process::exit(1);
-        } else {
-            return Err(e);
+/// Print a command error in the requested output format and return its exit code
+///
+/// The exit code reflects the error category (and a waited-on job's outcome),
+/// see runctl::exit_codes.
+fn report_error(e: &anyhow::Error, output_format: &str) -> i32 {
+    let code = runctl::exit_codes::exit_code_for_anyhow(e);
+    if output_format == "json" {
+        use serde_json::json;
+        let error_json = json!({
+            "success": false,
+            "error": {
+                "message": format!("{}", e),
+                "source": e.source().map(|s| format!("{}", s)).unwrap_or_else(|| "unknown".to_string()),
+            },
+            "exit_code": code,
+        });
+        eprintln!(
+            "{}",
+            serde_json::to_string_pretty(&error_json).unwrap_or_default()
+        );
+    } else if output_format == "github" {
+        for line in runctl::ci::github_error_annotations(e, code) {
+            println!("{}", line);
         }
+        eprintln!("Error: {:?}", e);
+    } else {
+        eprintln!("Error: {:?}", e);
     }
-
-    Ok(())
+    code
 }
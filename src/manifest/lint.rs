@@ -0,0 +1,523 @@
+//! `runctl validate`: check run manifests before anything is provisioned
+//!
+//! A manifest is parsed the way `apply` parses it (unknown fields, bad names
+//! and malformed S3 paths are errors), then checked against the machine and
+//! the account:
+//!
+//! - the script exists, and hooks that run a local file point at one
+//! - the instance type is well formed, exists, and is offered in the region
+//! - the S3 buckets in `data` exist and are readable, and `data.input` has
+//!   objects under it
+//! - `spot.max_price` is a price, and is only set for spot runs
+//!
+//! The account checks and the cost estimate (see [`crate::cost_estimate`])
+//! need AWS credentials; `--offline` skips them. `runctl validate --schema`
+//! prints the manifest's JSON Schema for editors and CI.
+
+use super::Manifest;
+use crate::config::Config;
+use crate::error::{ConfigError, Result, TrainctlError};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// One problem found in a manifest
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    /// Manifest field the finding is about (`manifest` for the whole file)
+    pub field: String,
+    pub message: String,
+}
+
+impl Finding {
+    fn error(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+
+    fn warning(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Estimated price of the manifest's instance type, as it will be launched
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostSummary {
+    pub instance_type: String,
+    pub region: String,
+    pub on_demand_per_hour: f64,
+    pub spot_per_hour: Option<f64>,
+    /// Planned duration, when one was given
+    pub hours: Option<f64>,
+    /// Compute, storage and data transfer for `hours`, as planned
+    pub total: Option<f64>,
+}
+
+/// Everything `validate` found for one manifest
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationReport {
+    pub path: PathBuf,
+    pub name: Option<String>,
+    pub findings: Vec<Finding>,
+    pub cost: Option<CostSummary>,
+}
+
+impl ValidationReport {
+    pub fn errors(&self) -> usize {
+        self.count(Severity::Error)
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.count(Severity::Warning)
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == severity)
+            .count()
+    }
+}
+
+/// What `runctl validate` was asked to check
+#[derive(Debug, Clone, Default)]
+pub struct ValidateOptions {
+    /// Skip the checks and cost estimate that call AWS
+    pub offline: bool,
+    /// Planned run time for the cost estimate, in seconds
+    pub duration_secs: Option<u64>,
+    /// Fail on warnings too
+    pub strict: bool,
+}
+
+/// The manifest's JSON Schema
+pub fn json_schema() -> Result<String> {
+    Ok(serde_json::to_string_pretty(&schemars::schema_for!(
+        Manifest
+    ))?)
+}
+
+/// Parse `path` as `apply` would, turning the first problem into a finding
+fn load(path: &Path) -> std::result::Result<Manifest, Finding> {
+    Manifest::load(path).map_err(|e| match e {
+        TrainctlError::Validation { field, reason } => Finding::error(&field, reason),
+        TrainctlError::Config(ConfigError::InvalidValue { field, reason }) => {
+            Finding::error(&field, reason)
+        }
+        other => Finding::error("manifest", other.to_string()),
+    })
+}
+
+/// First word of a hook when it runs a file by path (`./x.sh`, `/opt/x`)
+fn hook_program(hook: &str) -> Option<&str> {
+    let program = hook.split_whitespace().next()?;
+    (program.starts_with("./") || program.starts_with("../") || program.starts_with('/'))
+        .then_some(program)
+}
+
+/// Checks that need nothing but the manifest and the local file system
+pub fn check_local(manifest: &Manifest) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    if !manifest.script.is_file() {
+        findings.push(Finding::error(
+            "script",
+            format!("{} not found", manifest.script.display()),
+        ));
+    }
+    for (stage, hooks) in [
+        ("hooks.before_apply", &manifest.hooks.before_apply),
+        ("hooks.after_apply", &manifest.hooks.after_apply),
+        ("hooks.before_destroy", &manifest.hooks.before_destroy),
+        ("hooks.after_destroy", &manifest.hooks.after_destroy),
+    ] {
+        for program in hooks.iter().filter_map(|h| hook_program(h)) {
+            if !Path::new(program).exists() {
+                findings.push(Finding::error(stage, format!("{} not found", program)));
+            }
+        }
+    }
+
+    if let Some(instance_type) = &manifest.instance_type {
+        if manifest.provider == "aws" {
+            match crate::validation::validate_instance_type(instance_type) {
+                Ok(()) => {
+                    if let Some(warning) = crate::validation::unknown_family_warning(instance_type)
+                    {
+                        findings.push(Finding::warning("instance_type", warning));
+                    }
+                }
+                Err(e) => findings.push(Finding::error("instance_type", reason(e))),
+            }
+        }
+    }
+
+    if let Some(price) = &manifest.spot.max_price {
+        if let Err(e) = crate::validation::validate_spot_price(price) {
+            findings.push(Finding::error("spot.max_price", reason(e)));
+        } else if !manifest.spot.enabled {
+            findings.push(Finding::warning(
+                "spot.max_price",
+                "set, but spot.enabled is false, so it is ignored",
+            ));
+        }
+    }
+    findings
+}
+
+fn reason(e: TrainctlError) -> String {
+    match e {
+        TrainctlError::Validation { reason, .. } => reason,
+        other => other.to_string(),
+    }
+}
+
+/// Instance type exists and is offered in the account's region
+#[cfg(feature = "aws")]
+async fn check_instance_type(
+    ec2: &aws_sdk_ec2::Client,
+    instance_type: &str,
+    region: &str,
+) -> Option<Finding> {
+    use aws_sdk_ec2::error::ProvideErrorMetadata;
+    use aws_sdk_ec2::types::{Filter, InstanceType, LocationType};
+
+    if let Err(e) = ec2
+        .describe_instance_types()
+        .instance_types(InstanceType::from(instance_type))
+        .send()
+        .await
+    {
+        let e = e.into_service_error();
+        return Some(if e.code() == Some("InvalidInstanceType") {
+            Finding::error(
+                "instance_type",
+                format!("{} is not an EC2 instance type", instance_type),
+            )
+        } else {
+            Finding::warning(
+                "instance_type",
+                format!("could not check {}: {}", instance_type, e),
+            )
+        });
+    }
+    match ec2
+        .describe_instance_type_offerings()
+        .location_type(LocationType::Region)
+        .filters(
+            Filter::builder()
+                .name("instance-type")
+                .values(instance_type)
+                .build(),
+        )
+        .send()
+        .await
+    {
+        Ok(response) if response.instance_type_offerings().is_empty() => Some(Finding::error(
+            "instance_type",
+            format!("{} is not offered in {}", instance_type, region),
+        )),
+        Ok(_) => None,
+        Err(e) => Some(Finding::warning(
+            "instance_type",
+            format!(
+                "could not check {} offerings: {}",
+                instance_type,
+                e.into_service_error()
+            ),
+        )),
+    }
+}
+
+/// Bucket of an S3 path exists and is readable; inputs have objects
+#[cfg(feature = "aws")]
+async fn check_s3_path(
+    s3: &aws_sdk_s3::Client,
+    field: &str,
+    uri: &str,
+    is_input: bool,
+) -> Option<Finding> {
+    let (bucket, prefix) = crate::utils::parse_s3_path(uri).ok()?;
+    if let Err(e) = s3.head_bucket().bucket(&bucket).send().await {
+        let status = e.raw_response().map(|r| r.status().as_u16());
+        return Some(match status {
+            Some(404) => Finding::error(field, format!("bucket {} does not exist", bucket)),
+            Some(403) => Finding::error(field, format!("no access to bucket {}", bucket)),
+            _ => Finding::warning(
+                field,
+                format!(
+                    "could not check bucket {}: {}",
+                    bucket,
+                    e.into_service_error()
+                ),
+            ),
+        });
+    }
+    if !is_input {
+        return None;
+    }
+    match s3
+        .list_objects_v2()
+        .bucket(&bucket)
+        .prefix(&prefix)
+        .max_keys(1)
+        .send()
+        .await
+    {
+        Ok(response) if response.contents().is_empty() => {
+            Some(Finding::error(field, format!("no objects under {}", uri)))
+        }
+        Ok(_) => None,
+        Err(e) => Some(Finding::warning(
+            field,
+            format!("could not list {}: {}", uri, e.into_service_error()),
+        )),
+    }
+}
+
+/// Checks against the AWS account: instance type and S3 paths
+#[cfg(feature = "aws")]
+async fn check_aws(manifest: &Manifest, config: &Config) -> Vec<Finding> {
+    let aws_config = crate::aws_utils::shared_sdk_config().await;
+    let mut findings = Vec::new();
+    if manifest.provider == "aws" {
+        let instance_type = manifest
+            .instance_type
+            .clone()
+            .or_else(|| config.aws.as_ref().map(|a| a.default_instance_type.clone()));
+        if let Some(instance_type) = instance_type {
+            let region = aws_config
+                .region()
+                .map(|r| r.to_string())
+                .unwrap_or_else(|| "the configured region".to_string());
+            let ec2 = aws_sdk_ec2::Client::new(&aws_config);
+            findings.extend(check_instance_type(&ec2, &instance_type, &region).await);
+        }
+    }
+    let s3 = aws_sdk_s3::Client::new(&aws_config);
+    for (field, uri, is_input) in [
+        ("data.input", &manifest.data.input, true),
+        ("data.output", &manifest.data.output, false),
+    ] {
+        if let Some(uri) = uri {
+            findings.extend(check_s3_path(&s3, field, uri, is_input).await);
+        }
+    }
+    findings
+}
+
+/// Price the manifest's own instance type with `costs estimate`'s model
+#[cfg(feature = "aws")]
+async fn estimate_cost(
+    path: &Path,
+    duration_secs: Option<u64>,
+    config: &Config,
+) -> Result<CostSummary> {
+    let options = crate::cost_estimate::EstimateOptions {
+        spec: path.to_path_buf(),
+        duration_secs: duration_secs.unwrap_or(3600),
+        types: Vec::new(),
+        region: None,
+    };
+    let estimate = crate::cost_estimate::estimate(&options, config).await?;
+    let candidate = estimate
+        .candidates
+        .first()
+        .ok_or_else(|| TrainctlError::Validation {
+            field: "instance_type".to_string(),
+            reason: "no price for the instance type".to_string(),
+        })?;
+    Ok(CostSummary {
+        instance_type: candidate.instance_type.clone(),
+        region: estimate.region.clone(),
+        on_demand_per_hour: candidate.on_demand_per_hour,
+        spot_per_hour: candidate.spot_per_hour,
+        hours: duration_secs.map(|_| estimate.duration_hours),
+        total: duration_secs.map(|_| candidate.planned_total(estimate.spot)),
+    })
+}
+
+/// Validate one manifest file
+pub async fn validate_file(
+    path: &Path,
+    options: &ValidateOptions,
+    config: &Config,
+) -> ValidationReport {
+    let mut report = ValidationReport {
+        path: path.to_path_buf(),
+        name: None,
+        findings: Vec::new(),
+        cost: None,
+    };
+    let manifest = match load(path) {
+        Ok(manifest) => manifest,
+        Err(finding) => {
+            report.findings.push(finding);
+            return report;
+        }
+    };
+    report.name = Some(manifest.name.clone());
+    report.findings = check_local(&manifest);
+
+    #[cfg(feature = "aws")]
+    if !options.offline {
+        report.findings.extend(check_aws(&manifest, config).await);
+        let priced = report.errors() == 0 && manifest.provider == "aws";
+        if priced {
+            match estimate_cost(path, options.duration_secs, config).await {
+                Ok(cost) => report.cost = Some(cost),
+                Err(e) => report.findings.push(Finding::warning(
+                    "cost",
+                    format!("could not estimate cost: {}", e),
+                )),
+            }
+        }
+    }
+    #[cfg(not(feature = "aws"))]
+    let _ = (options, config);
+    report
+}
+
+fn print_report(report: &ValidationReport) {
+    let status = match (report.errors(), report.warnings()) {
+        (0, 0) => "OK".to_string(),
+        (0, w) => format!("OK, {} warning(s)", w),
+        (e, w) => format!("{} error(s), {} warning(s)", e, w),
+    };
+    match &report.name {
+        Some(name) => println!("{} ({}): {}", report.path.display(), name, status),
+        None => println!("{}: {}", report.path.display(), status),
+    }
+    for finding in &report.findings {
+        let label = match finding.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        println!("  {:<8} {}: {}", label, finding.field, finding.message);
+    }
+    if let Some(cost) = &report.cost {
+        let spot = cost
+            .spot_per_hour
+            .map_or_else(String::new, |p| format!(", ${:.3}/hour spot", p));
+        println!(
+            "  cost     {} in {}: ${:.3}/hour on-demand{}",
+            cost.instance_type, cost.region, cost.on_demand_per_hour, spot
+        );
+        if let (Some(hours), Some(total)) = (cost.hours, cost.total) {
+            println!(
+                "           ${:.2} for {} as planned, with storage and data transfer",
+                total,
+                crate::utils::format_duration((hours * 3600.0) as u64)
+            );
+        }
+    }
+}
+
+/// `runctl validate`: check manifests and fail if any has errors
+pub async fn validate(
+    paths: &[PathBuf],
+    options: &ValidateOptions,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    let mut reports = Vec::new();
+    for path in paths {
+        reports.push(validate_file(path, options, config).await);
+    }
+    if output_format == "json" {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    } else {
+        for report in &reports {
+            print_report(report);
+        }
+    }
+
+    let failed = reports
+        .iter()
+        .filter(|r| r.errors() > 0 || (options.strict && r.warnings() > 0))
+        .count();
+    if failed > 0 {
+        return Err(TrainctlError::Validation {
+            field: "manifest".to_string(),
+            reason: format!(
+                "{} of {} manifest(s) failed validation",
+                failed,
+                paths.len()
+            ),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_checks() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("train.py");
+        std::fs::write(&script, "").unwrap();
+        let manifest = Manifest::parse(
+            &format!(
+                "name: x\nprovider: aws\ninstance_type: g5.xlarge\nscript: {}\n\
+                 spot:\n  max_price: \"0.50\"\nhooks:\n  before_apply: [\"echo hi\", \"./missing.sh --flag\"]\n",
+                script.display()
+            ),
+            "yaml",
+        )
+        .unwrap();
+        let findings = check_local(&manifest);
+        assert_eq!(
+            findings,
+            vec![
+                Finding::error("hooks.before_apply", "./missing.sh not found"),
+                Finding::warning(
+                    "spot.max_price",
+                    "set, but spot.enabled is false, so it is ignored"
+                ),
+            ]
+        );
+
+        let manifest = Manifest::parse(
+            "name: x\nprovider: aws\ninstance_type: g5\nscript: nope.py\nspot:\n  max_price: free\n",
+            "yaml",
+        )
+        .unwrap();
+        let fields: Vec<_> = check_local(&manifest)
+            .into_iter()
+            .map(|f| (f.severity, f.field))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                (Severity::Error, "script".to_string()),
+                (Severity::Error, "instance_type".to_string()),
+                (Severity::Error, "spot.max_price".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schema_describes_manifest() {
+        let schema: serde_json::Value = serde_json::from_str(&json_schema().unwrap()).unwrap();
+        let required = schema["required"].as_array().unwrap();
+        assert!(required.contains(&"name".into()));
+        assert!(required.contains(&"script".into()));
+        assert_eq!(schema["additionalProperties"], false);
+        assert!(schema["properties"]["spot"].is_object());
+    }
+}
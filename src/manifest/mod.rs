@@ -0,0 +1,646 @@
+//! Declarative run manifests
+//!
+//! A manifest (YAML, or TOML with a `.toml` extension) describes a training
+//! launch so it can be reproduced:
+//!
+//! ```yaml
+//! name: resnet-baseline
+//! provider: aws              # aws, runpod, azure, batch, k8s, lyceum
+//! instance_type: g5.xlarge   # default: the provider's default type
+//! spot:
+//!   enabled: true
+//!   max_price: "0.60"
+//! disk_gb: 200
+//! script: training/train.py
+//! args: ["--epochs", "50"]
+//! env:
+//!   WANDB_PROJECT: vision
+//! data:
+//!   input: s3://bucket/datasets/imagenet
+//!   output: s3://bucket/runs/resnet-baseline
+//! hooks:
+//!   before_apply: ["./scripts/check_data.sh"]
+//!   after_destroy: ["echo done"]
+//! ```
+//!
+//! `runctl plan` compares the manifest with what was last applied under the
+//! same `name` (`.runctl/manifests/<name>.json`) and the resource's current
+//! state; `runctl apply` carries the plan out through the provider layer and
+//! records the result; `runctl destroy` terminates the resource and forgets
+//! it. Changing the provider, instance type, spot settings or disk replaces
+//! the resource. Changing the script, arguments, environment or data paths
+//! starts training again on the same resource, unless it is still training.
+//!
+//! Hooks are local shell commands run with `RUNCTL_MANIFEST`,
+//! `RUNCTL_PROVIDER` and `RUNCTL_RESOURCE_ID` set. A failing `before_*` hook
+//! stops the command.
+//!
+//! `runctl init job --template single-gpu|pytorch-ddp|sweep` scaffolds a
+//! manifest and a training script to start from (see [`scaffold`]).
+//! `runctl validate` checks a manifest, the files and S3 paths it refers
+//! to, and its instance type, and estimates its cost, without provisioning
+//! anything (see [`validate`]).
+
+mod lint;
+mod plan;
+pub mod scaffold;
+
+pub use lint::{
+    json_schema, validate, validate_file, CostSummary, Finding, Severity, ValidateOptions,
+    ValidationReport,
+};
+pub use plan::{plan, Action, FieldChange, LiveState, Plan};
+
+use crate::config::Config;
+use crate::error::{ConfigError, Result, TrainctlError};
+use crate::provider::{
+    CreateResourceOptions, ExecutionStatus, ResourceState, TrainingJob, TrainingProvider,
+};
+use crate::providers::{ProviderRegistry, KNOWN_PROVIDERS};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::warn;
+
+/// A training launch described in a manifest file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// Identifies the deployment; one resource is applied per name
+    pub name: String,
+    pub provider: String,
+    /// Default: the provider's default instance type
+    pub instance_type: Option<String>,
+    #[serde(default)]
+    pub spot: SpotSettings,
+    pub disk_gb: Option<u32>,
+    /// Training script, relative to the directory runctl runs in
+    pub script: PathBuf,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Environment variables exported for the script
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub data: DataPaths,
+    #[serde(default)]
+    pub hooks: Hooks,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SpotSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Maximum hourly price (USD)
+    pub max_price: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DataPaths {
+    /// S3 path downloaded before training
+    pub input: Option<String>,
+    /// S3 path for outputs and checkpoints
+    pub output: Option<String>,
+}
+
+/// Local shell commands run around apply and destroy
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Hooks {
+    #[serde(default)]
+    pub before_apply: Vec<String>,
+    #[serde(default)]
+    pub after_apply: Vec<String>,
+    #[serde(default)]
+    pub before_destroy: Vec<String>,
+    #[serde(default)]
+    pub after_destroy: Vec<String>,
+}
+
+impl Manifest {
+    /// Parse and validate a manifest in `format` ("yaml" or "toml")
+    pub fn parse(content: &str, format: &str) -> Result<Self> {
+        let manifest: Manifest = match format {
+            "toml" => toml::from_str(content).map_err(|e| e.to_string()),
+            _ => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+        }
+        .map_err(|reason| {
+            TrainctlError::Config(ConfigError::InvalidValue {
+                field: "manifest".to_string(),
+                reason,
+            })
+        })?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    /// Load a manifest, choosing TOML for `.toml` files and YAML otherwise
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            TrainctlError::Io(std::io::Error::new(
+                e.kind(),
+                format!("Failed to read manifest {}: {}", path.display(), e),
+            ))
+        })?;
+        let format = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => "toml",
+            _ => "yaml",
+        };
+        Self::parse(&content, format)
+    }
+
+    fn validate(&self) -> Result<()> {
+        let invalid = |field: &str, reason: String| {
+            Err(TrainctlError::Validation {
+                field: field.to_string(),
+                reason,
+            })
+        };
+        let name_ok = !self.name.is_empty()
+            && self.name.len() <= 64
+            && self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !name_ok {
+            return invalid(
+                "name",
+                format!("'{}' must be 1-64 letters, digits, '-' or '_'", self.name),
+            );
+        }
+        if !KNOWN_PROVIDERS.contains(&self.provider.as_str()) {
+            return invalid(
+                "provider",
+                format!(
+                    "unknown provider '{}' (expected one of {})",
+                    self.provider,
+                    KNOWN_PROVIDERS.join(", ")
+                ),
+            );
+        }
+        if let Some(name) = self
+            .env
+            .keys()
+            .find(|name| !crate::utils::is_env_name(name))
+        {
+            return invalid("env", format!("'{}' is not a valid variable name", name));
+        }
+        for (field, path) in [
+            ("data.input", &self.data.input),
+            ("data.output", &self.data.output),
+        ] {
+            if let Some(path) = path {
+                if let Err(reason) = crate::validation::s3_uri_arg(path) {
+                    return invalid(field, reason);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn create_options(&self, config: &Config) -> CreateResourceOptions {
+        CreateResourceOptions {
+            use_spot: self.spot.enabled,
+            spot_max_price: self.spot.max_price.clone(),
+            disk_gb: self.disk_gb,
+            tags: vec![
+                (
+                    "runctl:project".to_string(),
+                    crate::utils::get_project_name(None, config),
+                ),
+                ("runctl:manifest".to_string(), self.name.clone()),
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn training_job(&self) -> TrainingJob {
+        TrainingJob {
+            script: self.script.clone(),
+            args: self.args.clone(),
+            data_source: self.data.input.clone(),
+            output_dest: self.data.output.clone(),
+            checkpoint_dir: None,
+            environment: self.env.clone().into_iter().collect(),
+        }
+    }
+}
+
+/// What `apply` last did for a manifest name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedState {
+    pub name: String,
+    pub provider: String,
+    pub resource_id: String,
+    /// Instance type the resource was created with
+    pub instance_type: String,
+    /// The manifest as applied
+    pub manifest: Manifest,
+    pub applied_at: DateTime<Utc>,
+}
+
+impl AppliedState {
+    fn path(name: &str) -> PathBuf {
+        state_dir().join(format!("{}.json", name))
+    }
+
+    /// State for `name`, if it has been applied
+    pub fn load(name: &str) -> Result<Option<Self>> {
+        let path = Self::path(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&std::fs::read_to_string(path)?)?))
+    }
+
+    /// State whose resource is `resource_id`, if any manifest applied it
+    pub fn for_resource(resource_id: &str) -> Result<Option<Self>> {
+        let dir = state_dir();
+        if !dir.exists() {
+            return Ok(None);
+        }
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Ok(state) = serde_json::from_str::<Self>(&std::fs::read_to_string(&path)?) else {
+                continue;
+            };
+            if state.resource_id == resource_id {
+                return Ok(Some(state));
+            }
+        }
+        Ok(None)
+    }
+
+    fn save(&self) -> Result<()> {
+        crate::utils::ensure_dir(&state_dir())?;
+        let path = Self::path(&self.name);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    fn remove(name: &str) -> Result<()> {
+        let path = Self::path(name);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn state_dir() -> PathBuf {
+    PathBuf::from(".runctl").join("manifests")
+}
+
+/// A loaded manifest with everything needed to plan and apply it
+struct Prepared {
+    manifest: Manifest,
+    registry: ProviderRegistry,
+    provider: Arc<dyn TrainingProvider>,
+    state: Option<AppliedState>,
+    plan: Plan,
+}
+
+async fn prepare(path: &Path, config: &Config) -> Result<Prepared> {
+    let manifest = Manifest::load(path)?;
+    let registry = ProviderRegistry::from_config(config).await?;
+    let provider = registry.select(&manifest.provider)?;
+    let instance_type = manifest
+        .instance_type
+        .clone()
+        .or_else(|| provider.default_instance_type())
+        .ok_or_else(|| TrainctlError::Validation {
+            field: "instance_type".to_string(),
+            reason: format!(
+                "{} has no default instance type; set instance_type in the manifest",
+                manifest.provider
+            ),
+        })?;
+
+    let state = AppliedState::load(&manifest.name)?;
+    let live = match &state {
+        Some(state) => live_state(&registry, state).await,
+        None => LiveState::Missing,
+    };
+    let plan = plan(&manifest, &instance_type, state.as_ref(), live);
+    Ok(Prepared {
+        manifest,
+        registry,
+        provider,
+        state,
+        plan,
+    })
+}
+
+/// Whether the applied resource still exists and is training
+async fn live_state(registry: &ProviderRegistry, state: &AppliedState) -> LiveState {
+    let provider = match registry.select(&state.provider) {
+        Ok(provider) => provider,
+        Err(e) => {
+            warn!("Cannot check {}: {}", state.resource_id, e);
+            return LiveState::Training;
+        }
+    };
+    match provider.get_resource_status(&state.resource_id).await {
+        Ok(status) => match status.state {
+            ResourceState::Terminating | ResourceState::Terminated => return LiveState::Missing,
+            ResourceState::Running => {}
+            _ => return LiveState::Training,
+        },
+        Err(TrainctlError::ResourceNotFound { .. }) => return LiveState::Missing,
+        Err(e) => {
+            warn!("Cannot check {}: {}", state.resource_id, e);
+            return LiveState::Training;
+        }
+    }
+    match provider.training_status(&state.resource_id).await {
+        Ok(ExecutionStatus::Pending | ExecutionStatus::Running) | Err(_) => LiveState::Training,
+        Ok(_) => LiveState::Idle,
+    }
+}
+
+/// Run `hooks` locally, stopping at the first failure
+fn run_hooks(
+    stage: &str,
+    hooks: &[String],
+    manifest: &Manifest,
+    resource_id: Option<&str>,
+) -> Result<()> {
+    for hook in hooks {
+        crate::progress::emit("manifest.hook", format!("{}: {}", stage, hook));
+        let status = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(hook)
+            .env("RUNCTL_MANIFEST", &manifest.name)
+            .env("RUNCTL_PROVIDER", &manifest.provider)
+            .env("RUNCTL_RESOURCE_ID", resource_id.unwrap_or(""))
+            .status()?;
+        if !status.success() {
+            return Err(TrainctlError::Io(std::io::Error::other(format!(
+                "{} hook '{}' failed ({})",
+                stage, hook, status
+            ))));
+        }
+    }
+    Ok(())
+}
+
+/// `runctl plan`: show what `apply` would do
+pub async fn show_plan(path: &Path, config: &Config, output_format: &str) -> Result<()> {
+    let prepared = prepare(path, config).await?;
+    if output_format == "json" {
+        println!("{}", serde_json::to_string_pretty(&prepared.plan)?);
+    } else {
+        prepared.plan.print();
+    }
+    Ok(())
+}
+
+/// `runctl apply`: create, replace or retrain to match the manifest
+pub async fn apply(path: &Path, yes: bool, config: &Config, output_format: &str) -> Result<()> {
+    let text = output_format != "json";
+    let Prepared {
+        manifest,
+        registry,
+        provider,
+        state,
+        plan,
+    } = prepare(path, config).await?;
+    if text {
+        plan.print();
+    }
+
+    if plan.action == Action::NoChange {
+        // Keep the recorded manifest current (hooks may have changed)
+        if let Some(mut state) = state {
+            state.manifest = manifest;
+            state.save()?;
+        }
+        if !text {
+            println!("{}", serde_json::to_string_pretty(&plan)?);
+        }
+        return Ok(());
+    }
+    if !manifest.script.exists() {
+        return Err(TrainctlError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("Script not found: {}", manifest.script.display()),
+        )));
+    }
+    // Resolve secret:// values before anything is provisioned; the recorded
+    // manifest keeps the references
+    let mut job = manifest.training_job();
+    job.environment = crate::secrets::resolve_env(&job.environment, config)?;
+    if !yes && !crate::prompt::confirm(&format!("Apply this plan ({})?", plan.action))? {
+        println!("Aborted");
+        return Ok(());
+    }
+
+    run_hooks(
+        "before_apply",
+        &manifest.hooks.before_apply,
+        &manifest,
+        plan.resource_id.as_deref(),
+    )?;
+
+    if let (Action::Replace, Some(old)) = (plan.action, &state) {
+        if text {
+            println!("Terminating {}...", old.resource_id);
+        }
+        registry
+            .select(&old.provider)?
+            .terminate(&old.resource_id)
+            .await?;
+        AppliedState::remove(&manifest.name)?;
+    }
+
+    let resource_id = match (plan.action, state) {
+        (Action::Retrain, Some(state)) => state.resource_id,
+        _ => {
+            crate::progress::emit(
+                "manifest.create",
+                format!("{}: {}", manifest.provider, plan.instance_type),
+            );
+            let resource_id = provider
+                .create_resource(&plan.instance_type, manifest.create_options(config))
+                .await?;
+            if text {
+                println!("Created {}", resource_id);
+            }
+            resource_id
+        }
+    };
+    let mut applied = AppliedState {
+        name: manifest.name.clone(),
+        provider: manifest.provider.clone(),
+        resource_id: resource_id.clone(),
+        instance_type: plan.instance_type.clone(),
+        manifest: manifest.clone(),
+        applied_at: Utc::now(),
+    };
+    // Record the resource before training so `destroy` can always find it
+    applied.save()?;
+
+    provider.train(&resource_id, job).await?;
+    applied.applied_at = Utc::now();
+    applied.save()?;
+
+    run_hooks(
+        "after_apply",
+        &manifest.hooks.after_apply,
+        &manifest,
+        Some(&resource_id),
+    )?;
+
+    if text {
+        println!("Applied '{}': training on {}", manifest.name, resource_id);
+        println!("   Tear down with: runctl destroy {}", path.display());
+    } else {
+        let result = serde_json::json!({
+            "success": true,
+            "plan": plan,
+            "resource_id": resource_id,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    }
+    Ok(())
+}
+
+/// `runctl destroy`: terminate the manifest's resource and forget it
+pub async fn destroy(path: &Path, yes: bool, config: &Config, output_format: &str) -> Result<()> {
+    let manifest = Manifest::load(path)?;
+    let state =
+        AppliedState::load(&manifest.name)?.ok_or_else(|| TrainctlError::ResourceNotFound {
+            resource_type: "applied manifest".to_string(),
+            resource_id: manifest.name.clone(),
+        })?;
+    if !yes
+        && !crate::prompt::confirm(&format!(
+            "Terminate {} ({}) for '{}'?",
+            state.resource_id, state.provider, manifest.name
+        ))?
+    {
+        println!("Aborted");
+        return Ok(());
+    }
+
+    run_hooks(
+        "before_destroy",
+        &manifest.hooks.before_destroy,
+        &manifest,
+        Some(&state.resource_id),
+    )?;
+    let registry = ProviderRegistry::from_config(config).await?;
+    match registry
+        .select(&state.provider)?
+        .terminate(&state.resource_id)
+        .await
+    {
+        Ok(()) | Err(TrainctlError::ResourceNotFound { .. }) => {}
+        Err(e) => return Err(e),
+    }
+    AppliedState::remove(&manifest.name)?;
+    run_hooks(
+        "after_destroy",
+        &manifest.hooks.after_destroy,
+        &manifest,
+        Some(&state.resource_id),
+    )?;
+
+    if output_format == "json" {
+        let result = serde_json::json!({
+            "success": true,
+            "name": manifest.name,
+            "resource_id": state.resource_id,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        println!(
+            "Destroyed '{}' (terminated {})",
+            manifest.name, state.resource_id
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_yaml_manifest() {
+        let manifest = Manifest::parse(
+            r#"
+name: resnet-baseline
+provider: aws
+instance_type: g5.xlarge
+spot:
+  enabled: true
+  max_price: "0.60"
+script: train.py
+args: ["--epochs", "50"]
+env:
+  WANDB_PROJECT: vision
+data:
+  input: s3://bucket/data
+hooks:
+  before_apply: ["echo hi"]
+"#,
+            "yaml",
+        )
+        .unwrap();
+        assert!(manifest.spot.enabled);
+        assert_eq!(manifest.spot.max_price.as_deref(), Some("0.60"));
+        assert_eq!(manifest.args, vec!["--epochs", "50"]);
+        assert_eq!(manifest.hooks.before_apply, vec!["echo hi"]);
+
+        let job = manifest.training_job();
+        assert_eq!(job.data_source.as_deref(), Some("s3://bucket/data"));
+        assert_eq!(
+            job.environment,
+            vec![("WANDB_PROJECT".to_string(), "vision".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_parse_toml_manifest() {
+        let manifest = Manifest::parse(
+            "name = \"sweep\"\nprovider = \"runpod\"\nscript = \"train.py\"\n\n[env]\nSEED = \"1\"\n",
+            "toml",
+        )
+        .unwrap();
+        assert_eq!(manifest.provider, "runpod");
+        assert!(manifest.instance_type.is_none());
+        assert!(!manifest.spot.enabled);
+    }
+
+    #[test]
+    fn test_rejects_invalid_manifests() {
+        let base = "name: x\nscript: train.py\n";
+        assert!(Manifest::parse(&format!("{}provider: gcp\n", base), "yaml").is_err());
+        assert!(
+            Manifest::parse("name: bad name\nprovider: aws\nscript: train.py\n", "yaml").is_err()
+        );
+        assert!(Manifest::parse(
+            &format!("{}provider: aws\nenv:\n  BAD-NAME: x\n", base),
+            "yaml"
+        )
+        .is_err());
+        assert!(Manifest::parse(
+            &format!("{}provider: aws\ndata:\n  input: /local/path\n", base),
+            "yaml"
+        )
+        .is_err());
+        // Typos are errors rather than silently ignored
+        assert!(Manifest::parse(&format!("{}provider: aws\nspots: true\n", base), "yaml").is_err());
+    }
+}
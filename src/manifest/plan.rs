@@ -0,0 +1,320 @@
+//! Diff a manifest against its applied state
+
+use super::{AppliedState, Manifest};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// What the resource behind an applied manifest is doing right now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiveState {
+    /// Terminated, stopped or no longer known to the provider
+    Missing,
+    /// Running with no training in progress
+    Idle,
+    /// Training (or status unknown, which is treated the same)
+    Training,
+}
+
+/// What `apply` will do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Create a resource and start training
+    Create,
+    /// Terminate the resource, create a new one and start training
+    Replace,
+    /// Start training again on the existing resource
+    Retrain,
+    /// Nothing to do
+    NoChange,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Action::Create => "create",
+            Action::Replace => "replace",
+            Action::Retrain => "retrain",
+            Action::NoChange => "no change",
+        })
+    }
+}
+
+/// One field that differs between the manifest and its applied state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub from: String,
+    pub to: String,
+    /// The resource must be recreated for this change to take effect
+    pub forces_replacement: bool,
+}
+
+/// Result of comparing a manifest with what was last applied
+#[derive(Debug, Clone, Serialize)]
+pub struct Plan {
+    pub name: String,
+    pub provider: String,
+    pub action: Action,
+    /// Resource the plan acts on (replaced or retrained), if any
+    pub resource_id: Option<String>,
+    /// Instance type to create (for create and replace)
+    pub instance_type: String,
+    pub changes: Vec<FieldChange>,
+    /// Why the action was chosen when the changes alone don't say
+    pub reason: Option<String>,
+}
+
+impl Plan {
+    /// Print the plan for people
+    pub fn print(&self) {
+        println!("Plan for '{}' ({}):", self.name, self.provider);
+        for change in &self.changes {
+            println!(
+                "  ~ {}: {} -> {}{}",
+                change.field,
+                change.from,
+                change.to,
+                if change.forces_replacement {
+                    " (forces replacement)"
+                } else {
+                    ""
+                }
+            );
+        }
+        let resource = self.resource_id.as_deref().unwrap_or("-");
+        let summary = match self.action {
+            Action::Create => format!("create {} and start training", self.instance_type),
+            Action::Replace => format!(
+                "terminate {}, create {} and start training",
+                resource, self.instance_type
+            ),
+            Action::Retrain => format!("start training again on {}", resource),
+            Action::NoChange => format!("nothing to do ({} is up to date)", resource),
+        };
+        println!("Action: {}", summary);
+        if let Some(reason) = &self.reason {
+            println!("   {}", reason);
+        }
+    }
+}
+
+/// Compare `manifest` (with its resolved `instance_type`) to `state`
+///
+/// `live` describes the applied resource and is ignored without a state.
+pub fn plan(
+    manifest: &Manifest,
+    instance_type: &str,
+    state: Option<&AppliedState>,
+    live: LiveState,
+) -> Plan {
+    let mut plan = Plan {
+        name: manifest.name.clone(),
+        provider: manifest.provider.clone(),
+        action: Action::Create,
+        resource_id: None,
+        instance_type: instance_type.to_string(),
+        changes: Vec::new(),
+        reason: None,
+    };
+    let Some(state) = state else {
+        return plan;
+    };
+    if live == LiveState::Missing {
+        plan.reason = Some(format!("{} no longer exists", state.resource_id));
+        return plan;
+    }
+
+    plan.resource_id = Some(state.resource_id.clone());
+    plan.changes = diff(
+        &state.manifest,
+        &state.instance_type,
+        manifest,
+        instance_type,
+    );
+    plan.action = if plan.changes.iter().any(|c| c.forces_replacement) {
+        Action::Replace
+    } else if plan.changes.is_empty() {
+        Action::NoChange
+    } else if live == LiveState::Training {
+        plan.reason = Some(format!(
+            "{} is still training, so it is replaced rather than reused",
+            state.resource_id
+        ));
+        Action::Replace
+    } else {
+        Action::Retrain
+    };
+    plan
+}
+
+/// Fields that differ; hooks only run around apply and destroy, so they are left out
+fn diff(old: &Manifest, old_type: &str, new: &Manifest, new_type: &str) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    let mut compare = |field: &str, from: String, to: String, forces_replacement: bool| {
+        if from != to {
+            changes.push(FieldChange {
+                field: field.to_string(),
+                from,
+                to,
+                forces_replacement,
+            });
+        }
+    };
+    let opt = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+
+    compare("provider", old.provider.clone(), new.provider.clone(), true);
+    compare(
+        "instance_type",
+        old_type.to_string(),
+        new_type.to_string(),
+        true,
+    );
+    compare(
+        "spot.enabled",
+        old.spot.enabled.to_string(),
+        new.spot.enabled.to_string(),
+        true,
+    );
+    compare(
+        "spot.max_price",
+        opt(&old.spot.max_price),
+        opt(&new.spot.max_price),
+        true,
+    );
+    compare(
+        "disk_gb",
+        old.disk_gb.map_or("-".to_string(), |d| d.to_string()),
+        new.disk_gb.map_or("-".to_string(), |d| d.to_string()),
+        true,
+    );
+    compare(
+        "script",
+        old.script.display().to_string(),
+        new.script.display().to_string(),
+        false,
+    );
+    compare(
+        "args",
+        format!("{:?}", old.args),
+        format!("{:?}", new.args),
+        false,
+    );
+    compare(
+        "data.input",
+        opt(&old.data.input),
+        opt(&new.data.input),
+        false,
+    );
+    compare(
+        "data.output",
+        opt(&old.data.output),
+        opt(&new.data.output),
+        false,
+    );
+    // Values may be secrets, so only names are shown
+    let names: BTreeSet<&String> = old.env.keys().chain(new.env.keys()).collect();
+    for name in names {
+        let (from, to) = match (old.env.get(name), new.env.get(name)) {
+            (Some(a), Some(b)) if a != b => ("(set)", "(changed)"),
+            (None, Some(_)) => ("-", "(set)"),
+            (Some(_), None) => ("(set)", "-"),
+            _ => continue,
+        };
+        compare(
+            &format!("env.{}", name),
+            from.to_string(),
+            to.to_string(),
+            false,
+        );
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn manifest() -> Manifest {
+        Manifest::parse(
+            "name: resnet\nprovider: aws\ninstance_type: g5.xlarge\nscript: train.py\nargs: [\"--epochs\", \"10\"]\nenv:\n  WANDB_PROJECT: vision\n",
+            "yaml",
+        )
+        .unwrap()
+    }
+
+    fn applied(manifest: &Manifest) -> AppliedState {
+        AppliedState {
+            name: manifest.name.clone(),
+            provider: manifest.provider.clone(),
+            resource_id: "i-0abc".to_string(),
+            instance_type: "g5.xlarge".to_string(),
+            manifest: manifest.clone(),
+            applied_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_plan_without_state_creates() {
+        let plan = plan(&manifest(), "g5.xlarge", None, LiveState::Missing);
+        assert_eq!(plan.action, Action::Create);
+        assert!(plan.changes.is_empty());
+    }
+
+    #[test]
+    fn test_plan_unchanged() {
+        let m = manifest();
+        let plan = plan(&m, "g5.xlarge", Some(&applied(&m)), LiveState::Training);
+        assert_eq!(plan.action, Action::NoChange);
+        assert_eq!(plan.resource_id.as_deref(), Some("i-0abc"));
+    }
+
+    #[test]
+    fn test_plan_recreates_missing_resource() {
+        let m = manifest();
+        let plan = plan(&m, "g5.xlarge", Some(&applied(&m)), LiveState::Missing);
+        assert_eq!(plan.action, Action::Create);
+        assert!(plan.reason.unwrap().contains("i-0abc"));
+    }
+
+    #[test]
+    fn test_plan_instance_type_forces_replacement() {
+        let m = manifest();
+        let state = applied(&m);
+        let plan = plan(&m, "g5.2xlarge", Some(&state), LiveState::Idle);
+        assert_eq!(plan.action, Action::Replace);
+        assert_eq!(plan.changes.len(), 1);
+        assert!(plan.changes[0].forces_replacement);
+    }
+
+    #[test]
+    fn test_plan_args_retrain_when_idle() {
+        let old = manifest();
+        let state = applied(&old);
+        let mut new = old.clone();
+        new.args = vec!["--epochs".to_string(), "20".to_string()];
+        new.env
+            .insert("WANDB_PROJECT".to_string(), "detection".to_string());
+
+        let idle = plan(&new, "g5.xlarge", Some(&state), LiveState::Idle);
+        assert_eq!(idle.action, Action::Retrain);
+        let fields: Vec<&str> = idle.changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["args", "env.WANDB_PROJECT"]);
+        assert!(!idle.changes[1].to.contains("detection"));
+
+        let busy = plan(&new, "g5.xlarge", Some(&state), LiveState::Training);
+        assert_eq!(busy.action, Action::Replace);
+    }
+
+    #[test]
+    fn test_plan_ignores_hooks() {
+        let old = manifest();
+        let state = applied(&old);
+        let mut new = old.clone();
+        new.hooks.after_apply = vec!["echo done".to_string()];
+        let plan = plan(&new, "g5.xlarge", Some(&state), LiveState::Idle);
+        assert_eq!(plan.action, Action::NoChange);
+    }
+}
@@ -0,0 +1,222 @@
+//! `runctl init job`: run manifests from templates
+//!
+//! Each template is a manifest (`templates/<name>.yaml.j2`, rendered with
+//! minijinja) and a training script (`templates/<name>.py`) that follows
+//! runctl's conventions: progress lines `runctl watch` parses,
+//! `checkpoint_epoch_N.pt` checkpoints, `--resume-from`, and the
+//! checkpoint-before-stop protocol through `runctl_helper.py`, which is
+//! written next to the script.
+
+use super::Manifest;
+use crate::error::{ConfigError, Result, TrainctlError};
+use clap::Subcommand;
+use minijinja::{context, Environment};
+use std::path::{Path, PathBuf};
+
+/// Script side of the checkpoint-before-stop protocol
+const HELPER_SCRIPT: &str = include_str!("../../training/runctl_helper.py");
+const HELPER_NAME: &str = "runctl_helper.py";
+
+#[derive(Subcommand, Clone)]
+pub enum InitCommands {
+    /// Scaffold a run manifest and a training script from a template
+    ///
+    /// Writes the manifest, the training script (unless it exists) and
+    /// runctl_helper.py next to the script. Templates: single-gpu,
+    /// pytorch-ddp (one process per GPU on one instance) and sweep (one
+    /// trial per manifest, hyperparameters in `env`).
+    ///
+    /// Examples:
+    ///   runctl init job
+    ///   runctl init job --template pytorch-ddp --name resnet-ddp
+    ///   runctl init job sweeps/lr-1e-3.yaml --template sweep --script sweep.py
+    Job {
+        /// Where to write the manifest
+        #[arg(value_name = "MANIFEST", default_value = "job.yaml")]
+        path: PathBuf,
+        /// Template to start from
+        #[arg(long, value_enum, default_value_t = JobTemplate::SingleGpu)]
+        template: JobTemplate,
+        /// Manifest name (default: the template's)
+        #[arg(long)]
+        name: Option<String>,
+        /// Provider to launch on
+        #[arg(long, default_value = "aws")]
+        provider: String,
+        /// Training script the manifest runs
+        #[arg(long, default_value = "train.py")]
+        script: PathBuf,
+        /// Overwrite existing files
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// A job template
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum JobTemplate {
+    SingleGpu,
+    PytorchDdp,
+    Sweep,
+}
+
+impl JobTemplate {
+    fn manifest_template(self) -> &'static str {
+        match self {
+            JobTemplate::SingleGpu => include_str!("templates/single-gpu.yaml.j2"),
+            JobTemplate::PytorchDdp => include_str!("templates/pytorch-ddp.yaml.j2"),
+            JobTemplate::Sweep => include_str!("templates/sweep.yaml.j2"),
+        }
+    }
+
+    fn script(self) -> &'static str {
+        match self {
+            JobTemplate::SingleGpu => include_str!("templates/single-gpu.py"),
+            JobTemplate::PytorchDdp => include_str!("templates/pytorch-ddp.py"),
+            JobTemplate::Sweep => include_str!("templates/sweep.py"),
+        }
+    }
+
+    fn default_name(self) -> &'static str {
+        match self {
+            JobTemplate::SingleGpu => "train",
+            JobTemplate::PytorchDdp => "train-ddp",
+            JobTemplate::Sweep => "sweep-trial-1",
+        }
+    }
+}
+
+/// Manifest text for `template`, checked to parse as a manifest
+pub fn render_manifest(
+    template: JobTemplate,
+    name: &str,
+    provider: &str,
+    path: &Path,
+    script: &Path,
+) -> Result<String> {
+    let mut env = Environment::new();
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    env.set_keep_trailing_newline(true);
+    let rendered = env
+        .render_str(
+            template.manifest_template(),
+            context! {
+                name => name,
+                provider => provider,
+                path => path.display().to_string(),
+                script => script.display().to_string(),
+            },
+        )
+        .map_err(|e| {
+            TrainctlError::Config(ConfigError::InvalidValue {
+                field: "template".to_string(),
+                reason: format!("Failed to render job template: {}", e),
+            })
+        })?;
+    Manifest::parse(&rendered, "yaml")?;
+    Ok(rendered)
+}
+
+fn write_new(path: &Path, content: &str, force: bool) -> Result<bool> {
+    if path.exists() && !force {
+        return Ok(false);
+    }
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        crate::utils::ensure_dir(parent)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(true)
+}
+
+pub fn handle_command(cmd: InitCommands) -> Result<()> {
+    match cmd {
+        InitCommands::Job {
+            template,
+            name,
+            path,
+            provider,
+            script,
+            force,
+        } => {
+            let name = name.unwrap_or_else(|| template.default_name().to_string());
+            let manifest = render_manifest(template, &name, &provider, &path, &script)?;
+            if !write_new(&path, &manifest, force)? {
+                return Err(TrainctlError::Validation {
+                    field: "path".to_string(),
+                    reason: format!(
+                        "{} already exists (use --force to overwrite)",
+                        path.display()
+                    ),
+                });
+            }
+            println!("Created manifest: {}", path.display());
+
+            if write_new(&script, template.script(), force)? {
+                println!("Created training script: {}", script.display());
+            } else {
+                println!("Kept existing script: {}", script.display());
+            }
+            let helper = script.with_file_name(HELPER_NAME);
+            if write_new(&helper, HELPER_SCRIPT, force)? {
+                println!("Created {}", helper.display());
+            }
+
+            println!();
+            println!("Next steps:");
+            println!("  runctl plan {}", path.display());
+            println!("  runctl apply {}", path.display());
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_templates_render_valid_manifests() {
+        for template in [
+            JobTemplate::SingleGpu,
+            JobTemplate::PytorchDdp,
+            JobTemplate::Sweep,
+        ] {
+            let text = render_manifest(
+                template,
+                template.default_name(),
+                "aws",
+                Path::new("job.yaml"),
+                Path::new("train.py"),
+            )
+            .unwrap();
+            let manifest = Manifest::parse(&text, "yaml").unwrap();
+            assert_eq!(manifest.name, template.default_name());
+            assert!(manifest.instance_type.is_some());
+            assert_eq!(manifest.script, PathBuf::from("train.py"));
+        }
+
+        // Other providers fall back to their default instance type
+        let text = render_manifest(
+            JobTemplate::Sweep,
+            "lr-1e-3",
+            "runpod",
+            Path::new("job.yaml"),
+            Path::new("sweep.py"),
+        )
+        .unwrap();
+        let manifest = Manifest::parse(&text, "yaml").unwrap();
+        assert_eq!(manifest.instance_type, None);
+        assert!(manifest.spot.enabled);
+        assert_eq!(manifest.env["LR"], "0.001");
+
+        assert!(render_manifest(
+            JobTemplate::SingleGpu,
+            "bad name",
+            "aws",
+            Path::new("job.yaml"),
+            Path::new("train.py"),
+        )
+        .is_err());
+    }
+}
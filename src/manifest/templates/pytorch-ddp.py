@@ -0,0 +1,162 @@
+#!/usr/bin/env python3
+"""
+Multi-GPU DDP training script scaffolded by `runctl init job --template pytorch-ddp`.
+
+The script starts one process per visible GPU itself, so runctl runs it
+like any other script. Replace build_model() and batches() with your
+model and data; the rest is how runctl expects a script to behave:
+
+- Rank 0 prints progress lines like `Epoch 3/10 step 120/500 loss=0.4213`,
+  which `runctl watch` and `runctl monitor` parse.
+- Rank 0 writes `<checkpoint-dir>/checkpoint_epoch_N.pt`; `--resume-from
+  PATH` (what `runctl aws train --resume` passes) loads it on every rank.
+- Before a planned stop runctl asks for a checkpoint at the next epoch
+  boundary (see runctl_helper.py). Rank 0 checks for the request and the
+  decision is broadcast, so every rank stops at the same boundary. SIGTERM
+  to the main process is forwarded to the workers and handled the same way.
+"""
+
+import argparse
+import os
+import signal
+import sys
+from pathlib import Path
+
+import torch
+import torch.distributed as dist
+import torch.multiprocessing as mp
+import torch.nn as nn
+from torch.nn.parallel import DistributedDataParallel as DDP
+
+import runctl_helper
+
+stop_requested = False
+
+
+def handle_sigterm(signum, frame):
+    global stop_requested
+    stop_requested = True
+
+
+def build_model():
+    # Replace with your model
+    return nn.Sequential(nn.Linear(32, 64), nn.ReLU(), nn.Linear(64, 1))
+
+
+def batches(steps, device, rank, world_size):
+    # Replace with your DataLoader and a DistributedSampler
+    generator = torch.Generator().manual_seed(rank)
+    for _ in range(steps):
+        x = torch.randn(64, 32, generator=generator).to(device)
+        yield x, x.sum(dim=1, keepdim=True)
+
+
+def save_checkpoint(model, optimizer, epoch, checkpoint_dir):
+    checkpoint_dir.mkdir(parents=True, exist_ok=True)
+    path = checkpoint_dir / f"checkpoint_epoch_{epoch}.pt"
+    tmp = path.with_suffix(".tmp")
+    torch.save(
+        {
+            "epoch": epoch,
+            "model_state_dict": model.module.state_dict(),
+            "optimizer_state_dict": optimizer.state_dict(),
+        },
+        tmp,
+    )
+    tmp.replace(path)
+    print(f"Saved checkpoint: {path}", flush=True)
+    return path
+
+
+def should_stop(device):
+    """Whether to stop after this epoch, agreed on by every rank."""
+    flag = torch.tensor([1 if stop_requested else 0], device=device)
+    if dist.get_rank() == 0 and runctl_helper.checkpoint_requested() is not None:
+        flag.fill_(1)
+    dist.all_reduce(flag, op=dist.ReduceOp.MAX)
+    return bool(flag.item())
+
+
+def worker(rank, world_size, args):
+    signal.signal(signal.SIGTERM, handle_sigterm)
+    use_cuda = torch.cuda.is_available()
+    dist.init_process_group(
+        "nccl" if use_cuda else "gloo", rank=rank, world_size=world_size
+    )
+    device = torch.device(f"cuda:{rank}" if use_cuda else "cpu")
+    if use_cuda:
+        torch.cuda.set_device(device)
+
+    model = build_model().to(device)
+    optimizer = torch.optim.AdamW(model.parameters(), lr=args.lr)
+    start_epoch = 0
+    if args.resume_from:
+        checkpoint = torch.load(args.resume_from, map_location=device)
+        model.load_state_dict(checkpoint["model_state_dict"])
+        optimizer.load_state_dict(checkpoint["optimizer_state_dict"])
+        start_epoch = checkpoint["epoch"]
+        if rank == 0:
+            print(f"Resumed from {args.resume_from} (epoch {start_epoch})", flush=True)
+    model = DDP(model, device_ids=[rank] if use_cuda else None)
+    criterion = nn.MSELoss()
+
+    for epoch in range(start_epoch, args.epochs):
+        model.train()
+        for step, (x, y) in enumerate(
+            batches(args.steps_per_epoch, device, rank, world_size), 1
+        ):
+            optimizer.zero_grad()
+            loss = criterion(model(x), y)
+            loss.backward()
+            optimizer.step()
+            if rank == 0 and (step % 10 == 0 or step == args.steps_per_epoch):
+                print(
+                    f"Epoch {epoch + 1}/{args.epochs} "
+                    f"step {step}/{args.steps_per_epoch} loss={loss.item():.4f}",
+                    flush=True,
+                )
+
+        path = None
+        if rank == 0:
+            path = save_checkpoint(model, optimizer, epoch + 1, args.checkpoint_dir)
+        dist.barrier()
+        if should_stop(device):
+            if rank == 0:
+                print(f"Stopping after epoch {epoch + 1}", flush=True)
+                runctl_helper.checkpoint_done(path)
+            break
+
+    dist.destroy_process_group()
+
+
+def main():
+    parser = argparse.ArgumentParser()
+    parser.add_argument("--epochs", type=int, default=10)
+    parser.add_argument("--steps-per-epoch", type=int, default=100)
+    parser.add_argument("--lr", type=float, default=1e-3)
+    parser.add_argument("--checkpoint-dir", type=Path, default=Path("checkpoints"))
+    parser.add_argument("--resume-from", type=Path, default=None)
+    args = parser.parse_args()
+
+    world_size = max(torch.cuda.device_count(), 1)
+    os.environ.setdefault("MASTER_ADDR", "127.0.0.1")
+    os.environ.setdefault("MASTER_PORT", "29500")
+
+    # This process is the one runctl tracks: it listens for checkpoint
+    # requests and forwards SIGTERM to the workers
+    runctl_helper.listen()
+    context = mp.spawn(worker, args=(world_size, args), nprocs=world_size, join=False)
+
+    def forward_sigterm(signum, frame):
+        for process in context.processes:
+            if process.is_alive():
+                os.kill(process.pid, signal.SIGTERM)
+
+    signal.signal(signal.SIGTERM, forward_sigterm)
+    while not context.join():
+        pass
+    sys.exit(0)
+
+
+if __name__ == "__main__":
+    main()
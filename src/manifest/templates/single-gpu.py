@@ -0,0 +1,120 @@
+#!/usr/bin/env python3
+"""
+Single-GPU training script scaffolded by `runctl init job --template single-gpu`.
+
+Replace build_model() and batches() with your model and data; the rest is
+how runctl expects a script to behave:
+
+- Progress lines like `Epoch 3/10 step 120/500 loss=0.4213` are parsed by
+  `runctl watch` and `runctl monitor` (epoch and step as N/M, metrics as
+  key=value).
+- Checkpoints are `<checkpoint-dir>/checkpoint_epoch_N.pt`, which
+  `runctl checkpoint` commands list and sync.
+- `--resume-from PATH` is the flag `runctl aws train --resume` passes.
+- Before a planned stop runctl asks for a checkpoint at the next epoch
+  boundary (see runctl_helper.py); SIGTERM, e.g. a spot interruption,
+  saves one at the next boundary too.
+"""
+
+import argparse
+import signal
+import sys
+from pathlib import Path
+
+import torch
+import torch.nn as nn
+
+import runctl_helper
+
+stop_requested = False
+
+
+def handle_sigterm(signum, frame):
+    global stop_requested
+    stop_requested = True
+
+
+def build_model():
+    # Replace with your model
+    return nn.Sequential(nn.Linear(32, 64), nn.ReLU(), nn.Linear(64, 1))
+
+
+def batches(steps, device):
+    # Replace with your DataLoader
+    for _ in range(steps):
+        x = torch.randn(64, 32, device=device)
+        yield x, x.sum(dim=1, keepdim=True)
+
+
+def save_checkpoint(model, optimizer, epoch, checkpoint_dir):
+    checkpoint_dir.mkdir(parents=True, exist_ok=True)
+    path = checkpoint_dir / f"checkpoint_epoch_{epoch}.pt"
+    tmp = path.with_suffix(".tmp")
+    torch.save(
+        {
+            "epoch": epoch,
+            "model_state_dict": model.state_dict(),
+            "optimizer_state_dict": optimizer.state_dict(),
+        },
+        tmp,
+    )
+    # Never leave a half-written checkpoint under a checkpoint name
+    tmp.replace(path)
+    print(f"Saved checkpoint: {path}", flush=True)
+    return path
+
+
+def main():
+    parser = argparse.ArgumentParser()
+    parser.add_argument("--epochs", type=int, default=10)
+    parser.add_argument("--steps-per-epoch", type=int, default=100)
+    parser.add_argument("--lr", type=float, default=1e-3)
+    parser.add_argument("--checkpoint-dir", type=Path, default=Path("checkpoints"))
+    parser.add_argument("--resume-from", type=Path, default=None)
+    args = parser.parse_args()
+
+    signal.signal(signal.SIGTERM, handle_sigterm)
+    runctl_helper.listen()
+
+    device = torch.device("cuda" if torch.cuda.is_available() else "cpu")
+    model = build_model().to(device)
+    optimizer = torch.optim.AdamW(model.parameters(), lr=args.lr)
+    criterion = nn.MSELoss()
+
+    start_epoch = 0
+    if args.resume_from:
+        checkpoint = torch.load(args.resume_from, map_location=device)
+        model.load_state_dict(checkpoint["model_state_dict"])
+        optimizer.load_state_dict(checkpoint["optimizer_state_dict"])
+        start_epoch = checkpoint["epoch"]
+        print(f"Resumed from {args.resume_from} (epoch {start_epoch})", flush=True)
+
+    for epoch in range(start_epoch, args.epochs):
+        model.train()
+        for step, (x, y) in enumerate(batches(args.steps_per_epoch, device), 1):
+            optimizer.zero_grad()
+            loss = criterion(model(x), y)
+            loss.backward()
+            optimizer.step()
+            if step % 10 == 0 or step == args.steps_per_epoch:
+                print(
+                    f"Epoch {epoch + 1}/{args.epochs} "
+                    f"step {step}/{args.steps_per_epoch} loss={loss.item():.4f}",
+                    flush=True,
+                )
+
+        path = save_checkpoint(model, optimizer, epoch + 1, args.checkpoint_dir)
+        request = runctl_helper.checkpoint_requested()
+        if request is not None:
+            print(f"Stopping after epoch {epoch + 1} ({request['reason']})", flush=True)
+            runctl_helper.checkpoint_done(path)
+            sys.exit(0)
+        if stop_requested:
+            print(f"Stopping after epoch {epoch + 1} (SIGTERM)", flush=True)
+            sys.exit(0)
+
+    print("Training complete", flush=True)
+
+
+if __name__ == "__main__":
+    main()
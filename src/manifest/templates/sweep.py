@@ -0,0 +1,135 @@
+#!/usr/bin/env python3
+"""
+Sweep trial script scaffolded by `runctl init job --template sweep`.
+
+Each trial is one run manifest whose `env` sets the hyperparameters read
+below (LR, BATCH_SIZE, SEED). Replace build_model() and batches() with
+your model and data; the rest is how runctl expects a script to behave:
+
+- The trial's hyperparameters are printed first, then progress lines like
+  `Epoch 3/10 step 120/500 loss=0.4213`, which `runctl watch` and
+  `runctl monitor` parse. The last line reports `final_loss=...` so trials
+  can be compared from their logs.
+- Checkpoints are `<checkpoint-dir>/checkpoint_epoch_N.pt`; `--resume-from
+  PATH` (what `runctl aws train --resume` passes) continues a trial.
+- Spot trials get SIGTERM when interrupted and runctl asks for a checkpoint
+  before planned stops (see runctl_helper.py); both save one at the next
+  epoch boundary.
+"""
+
+import argparse
+import os
+import signal
+import sys
+from pathlib import Path
+
+import torch
+import torch.nn as nn
+
+import runctl_helper
+
+stop_requested = False
+
+
+def handle_sigterm(signum, frame):
+    global stop_requested
+    stop_requested = True
+
+
+def hyperparameters():
+    return {
+        "lr": float(os.environ.get("LR", "0.001")),
+        "batch_size": int(os.environ.get("BATCH_SIZE", "64")),
+        "seed": int(os.environ.get("SEED", "1")),
+    }
+
+
+def build_model():
+    # Replace with your model
+    return nn.Sequential(nn.Linear(32, 64), nn.ReLU(), nn.Linear(64, 1))
+
+
+def batches(steps, batch_size, device):
+    # Replace with your DataLoader
+    for _ in range(steps):
+        x = torch.randn(batch_size, 32, device=device)
+        yield x, x.sum(dim=1, keepdim=True)
+
+
+def save_checkpoint(model, optimizer, epoch, hparams, checkpoint_dir):
+    checkpoint_dir.mkdir(parents=True, exist_ok=True)
+    path = checkpoint_dir / f"checkpoint_epoch_{epoch}.pt"
+    tmp = path.with_suffix(".tmp")
+    torch.save(
+        {
+            "epoch": epoch,
+            "hyperparameters": hparams,
+            "model_state_dict": model.state_dict(),
+            "optimizer_state_dict": optimizer.state_dict(),
+        },
+        tmp,
+    )
+    tmp.replace(path)
+    print(f"Saved checkpoint: {path}", flush=True)
+    return path
+
+
+def main():
+    parser = argparse.ArgumentParser()
+    parser.add_argument("--epochs", type=int, default=10)
+    parser.add_argument("--steps-per-epoch", type=int, default=100)
+    parser.add_argument("--checkpoint-dir", type=Path, default=Path("checkpoints"))
+    parser.add_argument("--resume-from", type=Path, default=None)
+    args = parser.parse_args()
+
+    hparams = hyperparameters()
+    print(" ".join(f"{key}={value}" for key, value in hparams.items()), flush=True)
+    torch.manual_seed(hparams["seed"])
+
+    signal.signal(signal.SIGTERM, handle_sigterm)
+    runctl_helper.listen()
+
+    device = torch.device("cuda" if torch.cuda.is_available() else "cpu")
+    model = build_model().to(device)
+    optimizer = torch.optim.AdamW(model.parameters(), lr=hparams["lr"])
+    criterion = nn.MSELoss()
+
+    start_epoch = 0
+    if args.resume_from:
+        checkpoint = torch.load(args.resume_from, map_location=device)
+        model.load_state_dict(checkpoint["model_state_dict"])
+        optimizer.load_state_dict(checkpoint["optimizer_state_dict"])
+        start_epoch = checkpoint["epoch"]
+        print(f"Resumed from {args.resume_from} (epoch {start_epoch})", flush=True)
+
+    loss = None
+    for epoch in range(start_epoch, args.epochs):
+        model.train()
+        for step, (x, y) in enumerate(
+            batches(args.steps_per_epoch, hparams["batch_size"], device), 1
+        ):
+            optimizer.zero_grad()
+            loss = criterion(model(x), y)
+            loss.backward()
+            optimizer.step()
+            if step % 10 == 0 or step == args.steps_per_epoch:
+                print(
+                    f"Epoch {epoch + 1}/{args.epochs} "
+                    f"step {step}/{args.steps_per_epoch} loss={loss.item():.4f}",
+                    flush=True,
+                )
+
+        path = save_checkpoint(model, optimizer, epoch + 1, hparams, args.checkpoint_dir)
+        request = runctl_helper.checkpoint_requested()
+        if request is not None or stop_requested:
+            reason = request["reason"] if request is not None else "SIGTERM"
+            print(f"Stopping after epoch {epoch + 1} ({reason})", flush=True)
+            runctl_helper.checkpoint_done(path)
+            sys.exit(0)
+
+    if loss is not None:
+        print(f"final_loss={loss.item():.4f}", flush=True)
+
+
+if __name__ == "__main__":
+    main()
@@ -10,6 +10,10 @@
 //! - **Checkpoint detection**: Monitor checkpoint directories for new `.pt` files
 //! - **Follow mode**: Continuous updates (similar to `tail -f`)
 //! - **One-time mode**: Display last N lines and exit
+//! - **Progress**: Epoch/step, loss trend, throughput and ETA parsed from the
+//!   log (see `training_progress`), shown as a `>>` progress bar line
+//! - **Provider jobs**: `monitor_job` streams a job's log through its provider
+//!   (e.g. pod logs for `k8s`)
 //!
 //! ## Usage
 //!
@@ -33,13 +37,31 @@
 //! # }
 //! ```
 
+use crate::config::Config;
 use crate::error::{Result, TrainctlError};
+use crate::training_progress::ProgressTracker;
 use notify::{Event, EventKind, RecursiveMode, Watcher};
 use std::fs;
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 use tokio::time::{sleep, Duration};
 
+/// Show (or with `follow`, stream) the log of a job running on a provider
+///
+/// `runctl monitor --provider k8s --job <id>` streams the job's pod log.
+pub async fn monitor_job(
+    provider: &str,
+    resource_id: &str,
+    follow: bool,
+    config: &Config,
+) -> Result<()> {
+    let registry = crate::providers::ProviderRegistry::from_config(config).await?;
+    registry
+        .select(provider)?
+        .monitor(&resource_id.to_string(), follow)
+        .await
+}
+
 /// Monitor training logs and/or checkpoints
 ///
 /// Monitors log files and checkpoint directories for updates. Can operate in
@@ -151,6 +173,7 @@ async fn monitor_log(log_path: &Path, follow: bool) -> Result<()> {
                 )))
             })?;
 
+        let mut progress = ProgressTracker::new();
         let mut last_pos = 0u64;
         loop {
             tokio::select! {
@@ -162,10 +185,13 @@ async fn monitor_log(log_path: &Path, follow: bool) -> Result<()> {
                         let mut reader = BufReader::new(file);
 
                         let mut line = String::new();
+                        let mut new_lines = Vec::new();
                         while reader.read_line(&mut line)? > 0 {
-                            print!("{}", line);
+                            print!("{}", crate::output_style::sanitize(&line));
+                            new_lines.push(line.trim_end_matches('\n').to_string());
                             line.clear();
                         }
+                        print_progress(&mut progress, &new_lines);
 
                         last_pos = reader.stream_position()?;
                     }
@@ -190,14 +216,23 @@ async fn monitor_log(log_path: &Path, follow: bool) -> Result<()> {
 
             println!("Last {} lines:", last_n);
             for line in &lines[start..] {
-                print!("{}", line);
+                println!("{}", crate::output_style::sanitize(line));
             }
+            // Progress counts the whole log, so the loss trend has history
+            print_progress(&mut ProgressTracker::new(), &lines);
         }
     }
 
     Ok(())
 }
 
+/// Print the training progress parsed from `lines`, if they changed it
+fn print_progress(progress: &mut ProgressTracker, lines: &[String]) {
+    if progress.observe(lines) && !progress.progress().is_empty() {
+        println!(">> {}", progress.progress().render());
+    }
+}
+
 async fn monitor_checkpoint(checkpoint_dir: &Path) -> Result<()> {
     if !checkpoint_dir.exists() {
         println!(
@@ -224,7 +259,7 @@ async fn monitor_checkpoint(checkpoint_dir: &Path) -> Result<()> {
             }
         }
 
-        checkpoints.sort_by(|a, b| b.1.cmp(&a.1));
+        checkpoints.sort_by_key(|b| std::cmp::Reverse(b.1));
 
         // Check for new checkpoints
         if checkpoints != last_checkpoints {
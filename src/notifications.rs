@@ -0,0 +1,99 @@
+//! Desktop notifications
+//!
+//! Native notifications for local users, sent when a watched job completes,
+//! fails, or raises an alert. Each event type can be toggled in the
+//! `[notifications]` config section.
+//!
+//! Delivery uses `notify-rust` and is only compiled in with the
+//! `desktop-notifications` feature, so headless/server builds don't pull in
+//! D-Bus support. Without the feature (or when delivery fails, e.g. no
+//! notification daemon) events are logged at debug level and otherwise
+//! ignored; a missing notification must never interrupt monitoring.
+
+use crate::config::NotificationConfig;
+
+/// Kinds of events that can trigger a notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyEvent {
+    /// Job finished successfully
+    Completed,
+    /// Job failed or exited unexpectedly
+    Failed,
+    /// Something needs attention while the job runs
+    Alert,
+}
+
+/// Sends notifications allowed by the user's config
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    config: NotificationConfig,
+}
+
+impl Notifier {
+    pub fn new(config: NotificationConfig) -> Self {
+        Self { config }
+    }
+
+    /// Whether `event` would produce a notification
+    pub fn is_enabled(&self, event: NotifyEvent) -> bool {
+        self.config.desktop
+            && match event {
+                NotifyEvent::Completed => self.config.on_complete,
+                NotifyEvent::Failed => self.config.on_failure,
+                NotifyEvent::Alert => self.config.on_alert,
+            }
+    }
+
+    /// Send a notification if `event` is enabled
+    pub fn notify(&self, event: NotifyEvent, summary: &str, body: &str) {
+        if !self.is_enabled(event) {
+            return;
+        }
+        if let Err(e) = send_desktop(summary, body) {
+            tracing::debug!("Desktop notification not delivered: {}", e);
+        }
+    }
+}
+
+#[cfg(feature = "desktop-notifications")]
+fn send_desktop(summary: &str, body: &str) -> Result<(), String> {
+    notify_rust::Notification::new()
+        .appname("runctl")
+        .summary(summary)
+        .body(body)
+        .show()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "desktop-notifications"))]
+fn send_desktop(summary: &str, _body: &str) -> Result<(), String> {
+    Err(format!(
+        "built without the desktop-notifications feature (would have sent: {})",
+        summary
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_by_default() {
+        let notifier = Notifier::new(NotificationConfig::default());
+        assert!(!notifier.is_enabled(NotifyEvent::Completed));
+        assert!(!notifier.is_enabled(NotifyEvent::Failed));
+    }
+
+    #[test]
+    fn test_per_event_toggles() {
+        let notifier = Notifier::new(NotificationConfig {
+            desktop: true,
+            on_alert: false,
+            ..Default::default()
+        });
+        assert!(notifier.is_enabled(NotifyEvent::Completed));
+        assert!(notifier.is_enabled(NotifyEvent::Failed));
+        assert!(!notifier.is_enabled(NotifyEvent::Alert));
+    }
+}
@@ -0,0 +1,195 @@
+//! Terminal glyphs and ASCII output mode
+//!
+//! runctl prints no emoji: status markers are words (`WARNING:`, `Error:`)
+//! so they survive any terminal and grep cleanly. The few symbols it does
+//! draw (bullets, rules, tab dividers, progress bars and spinners,
+//! sparklines) come from here, and `--ascii` (or `RUNCTL_ASCII=1`, or
+//! `[cli] ascii = true`) swaps them for plain ASCII. Without any of those,
+//! ASCII mode is picked when the locale is not UTF-8 (`LC_ALL`, `LC_CTYPE`,
+//! `LANG`) or `TERM=dumb`, which is where multi-byte output turns into
+//! mojibake like `ðŸ'»`; `[cli] ascii = false` forces Unicode.
+//!
+//! Text runctl echoes from elsewhere (training logs, remote command output)
+//! goes through `sanitize`, which transliterates common symbols and replaces
+//! anything else outside ASCII with `?` in ASCII mode.
+
+use indicatif::ProgressStyle;
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+/// Progress bar fill characters, ASCII in both modes
+const BAR_CHARS: &str = "#>-";
+/// Spinner frames for ASCII mode (the last is the finished state)
+const ASCII_TICKS: &str = "|/-\\ ";
+/// Sparkline levels from lowest to highest
+const SPARK_UNICODE: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const SPARK_ASCII: [char; 8] = ['_', '.', ':', '-', '=', '+', '*', '#'];
+
+/// Use ASCII glyphs (true) or Unicode ones for this process
+pub fn set_ascii(ascii: bool) {
+    ASCII.store(ascii, Ordering::Relaxed);
+}
+
+/// Whether output is restricted to ASCII
+pub fn is_ascii() -> bool {
+    ASCII.load(Ordering::Relaxed)
+}
+
+/// Whether the environment looks unable to show UTF-8
+///
+/// True for `TERM=dumb` and, on Unix, when the effective locale (the first
+/// of `LC_ALL`, `LC_CTYPE` and `LANG` that is set) does not name UTF-8,
+/// including when none is set and the C locale applies.
+pub fn detect() -> bool {
+    let var = |name| std::env::var(name).ok().filter(|v| !v.is_empty());
+    if var("TERM").as_deref() == Some("dumb") {
+        return true;
+    }
+    if cfg!(windows) {
+        return false;
+    }
+    let locale = var("LC_ALL")
+        .or_else(|| var("LC_CTYPE"))
+        .or_else(|| var("LANG"));
+    !locale.is_some_and(|l| is_utf8_locale(&l))
+}
+
+fn is_utf8_locale(locale: &str) -> bool {
+    let lower = locale.to_ascii_lowercase();
+    lower.contains("utf-8") || lower.contains("utf8")
+}
+
+/// A symbol runctl draws
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Glyph {
+    /// List item marker
+    Bullet,
+    /// One cell of a horizontal rule
+    Rule,
+    /// Separator between inline items, such as tab titles
+    Divider,
+}
+
+/// The text for `glyph` in the current mode
+pub fn glyph(glyph: Glyph) -> &'static str {
+    glyph_for(glyph, is_ascii())
+}
+
+fn glyph_for(glyph: Glyph, ascii: bool) -> &'static str {
+    match (glyph, ascii) {
+        (Glyph::Bullet, false) => "•",
+        (Glyph::Bullet, true) => "*",
+        (Glyph::Rule, false) => "─",
+        (Glyph::Rule, true) => "-",
+        (Glyph::Divider, false) => "│",
+        (Glyph::Divider, true) => "|",
+    }
+}
+
+/// A horizontal rule `width` cells wide
+pub fn rule(width: usize) -> String {
+    glyph(Glyph::Rule).repeat(width)
+}
+
+/// Sparkline level for a 0-100 percentage (clamped)
+pub fn spark(percent: u64) -> char {
+    let levels = if is_ascii() {
+        &SPARK_ASCII
+    } else {
+        &SPARK_UNICODE
+    };
+    levels[(percent.min(100) as usize * (levels.len() - 1) + 50) / 100]
+}
+
+/// Progress bar style for `template`
+pub fn bar(template: &str) -> ProgressStyle {
+    ProgressStyle::default_bar()
+        .template(template)
+        .expect("Progress bar template should be valid")
+        .progress_chars(BAR_CHARS)
+}
+
+/// Spinner style for `template`
+pub fn spinner(template: &str) -> ProgressStyle {
+    let style = ProgressStyle::default_spinner()
+        .template(template)
+        .expect("Progress bar template should be valid");
+    if is_ascii() {
+        style.tick_chars(ASCII_TICKS)
+    } else {
+        style
+    }
+}
+
+/// `text` as it should be printed in the current mode
+pub fn sanitize(text: &str) -> Cow<'_, str> {
+    if is_ascii() {
+        to_ascii(text)
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Transliterate common symbols and replace other non-ASCII with `?`
+pub fn to_ascii(text: &str) -> Cow<'_, str> {
+    if text.is_ascii() {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii() {
+            out.push(c);
+            continue;
+        }
+        match c {
+            // Emoji presentation selectors and joiners carry no text
+            '\u{fe0e}' | '\u{fe0f}' | '\u{200d}' => {}
+            '→' => out.push_str("->"),
+            '←' => out.push_str("<-"),
+            '↔' => out.push_str("<->"),
+            '•' | '·' => out.push('*'),
+            '─' | '━' | '–' | '—' => out.push('-'),
+            '│' | '┃' => out.push('|'),
+            '✓' | '✔' => out.push_str("OK"),
+            '✗' | '✘' => out.push('x'),
+            '…' => out.push_str("..."),
+            '‘' | '’' => out.push('\''),
+            '“' | '”' => out.push('"'),
+            '\u{a0}' => out.push(' '),
+            _ => match SPARK_UNICODE.iter().position(|&s| s == c) {
+                Some(level) => out.push(SPARK_ASCII[level]),
+                None => out.push('?'),
+            },
+        }
+    }
+    Cow::Owned(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_ascii() {
+        assert!(matches!(to_ascii("epoch 3/10"), Cow::Borrowed(_)));
+        assert_eq!(to_ascii("local ↔ S3 → done ✓"), "local <-> S3 -> done OK");
+        assert_eq!(to_ascii("⚠️ low disk"), "? low disk");
+        assert_eq!(to_ascii("ðŸ'»"), "??'?");
+        assert_eq!(to_ascii("▁▅█"), "_=#");
+        assert!(SPARK_ASCII.iter().all(char::is_ascii));
+    }
+
+    #[test]
+    fn test_glyphs_and_locale() {
+        for g in [Glyph::Bullet, Glyph::Rule, Glyph::Divider] {
+            assert!(glyph_for(g, true).is_ascii());
+            assert!(!glyph_for(g, false).is_ascii());
+        }
+        assert!(is_utf8_locale("en_US.UTF-8"));
+        assert!(is_utf8_locale("C.utf8"));
+        assert!(!is_utf8_locale("C"));
+        assert!(!is_utf8_locale("en_US.ISO-8859-1"));
+    }
+}
@@ -0,0 +1,187 @@
+//! Machine-readable progress events
+//!
+//! `runctl --progress-events` (or `RUNCTL_PROGRESS_EVENTS=1`, or
+//! `[cli] progress_events = true`) writes one JSON object per line to stderr
+//! as long operations advance: instance creation, code sync, S3 transfers and
+//! job waits. Wrapping tools and UIs can read these instead of scraping the
+//! human-oriented output on stdout.
+//!
+//! ```text
+//! {"stage":"sync.upload","percent":null,"bytes":1048576,"total_bytes":null,"message":"Uploading to S3","timestamp":"2026-01-01T00:00:00Z"}
+//! ```
+//!
+//! Stages are `<operation>.<step>`; `percent` is 0-100 when the total is
+//! known. Progress bars are only drawn when stderr is a terminal, so the
+//! stream stays clean when stderr is piped.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable progress events for this process
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether progress events are being written
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// A single progress event
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgressEvent {
+    pub stage: String,
+    pub percent: Option<f64>,
+    pub bytes: Option<u64>,
+    pub total_bytes: Option<u64>,
+    pub message: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl ProgressEvent {
+    pub fn new(stage: &str, message: impl Into<String>) -> Self {
+        Self {
+            stage: stage.to_string(),
+            percent: None,
+            bytes: None,
+            total_bytes: None,
+            message: message.into(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Set the percentage from a count of completed steps
+    pub fn steps(mut self, done: u64, total: u64) -> Self {
+        if total > 0 {
+            self.percent = Some((done.min(total) as f64 * 100.0 / total as f64).round());
+        }
+        self
+    }
+
+    /// Set the byte count, and the percentage when the total is known
+    ///
+    /// With no total the percentage is left as is.
+    pub fn bytes(mut self, bytes: u64, total_bytes: Option<u64>) -> Self {
+        self.bytes = Some(bytes);
+        self.total_bytes = total_bytes;
+        if let Some(total) = total_bytes {
+            self = self.steps(bytes, total);
+        }
+        self
+    }
+
+    /// Mark the step as finished
+    pub fn done(mut self) -> Self {
+        self.percent = Some(100.0);
+        self
+    }
+
+    /// Write the event to stderr if progress events are enabled
+    pub fn emit(self) {
+        if !is_enabled() {
+            return;
+        }
+        if let Ok(line) = serde_json::to_string(&self) {
+            // One write per line so concurrent tasks don't interleave events
+            let _ = writeln!(io::stderr().lock(), "{}", line);
+        }
+    }
+}
+
+/// Emit an event with only a stage and message
+pub fn emit(stage: &str, message: impl Into<String>) {
+    if is_enabled() {
+        ProgressEvent::new(stage, message).emit();
+    }
+}
+
+/// Progress across the files of a parallel transfer
+///
+/// Clones share counters, so each task can report the file it finished.
+/// Events are emitted when the whole percentage advances rather than per
+/// file, so large directory trees don't flood the stream.
+#[derive(Debug, Clone)]
+pub struct TransferProgress {
+    stage: &'static str,
+    total_files: u64,
+    total_bytes: u64,
+    files: Arc<AtomicU64>,
+    bytes: Arc<AtomicU64>,
+    last_percent: Arc<AtomicU64>,
+}
+
+impl TransferProgress {
+    pub fn new(stage: &'static str, total_files: u64, total_bytes: u64) -> Self {
+        emit(stage, format!("Transferring {} files", total_files));
+        Self {
+            stage,
+            total_files,
+            total_bytes,
+            files: Arc::new(AtomicU64::new(0)),
+            bytes: Arc::new(AtomicU64::new(0)),
+            last_percent: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Record a finished file of `size` bytes
+    pub fn file_done(&self, name: &str, size: u64) {
+        let files = self.files.fetch_add(1, Ordering::Relaxed) + 1;
+        let bytes = self.bytes.fetch_add(size, Ordering::Relaxed) + size;
+        if !is_enabled() {
+            return;
+        }
+        let event = if self.total_bytes > 0 {
+            ProgressEvent::new(self.stage, name).bytes(bytes, Some(self.total_bytes))
+        } else {
+            ProgressEvent::new(self.stage, name)
+                .steps(files, self.total_files)
+                .bytes(bytes, None)
+        };
+        let percent = event.percent.unwrap_or(0.0) as u64;
+        let last = files == self.total_files;
+        if last || percent > self.last_percent.fetch_max(percent, Ordering::Relaxed) {
+            event.emit();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_percent() {
+        let event = ProgressEvent::new("transfer.upload", "files").steps(3, 4);
+        assert_eq!(event.percent, Some(75.0));
+        let event = ProgressEvent::new("transfer.upload", "files").steps(5, 4);
+        assert_eq!(event.percent, Some(100.0));
+        let event = ProgressEvent::new("wait.status", "running").steps(1, 0);
+        assert_eq!(event.percent, None);
+        let event = ProgressEvent::new("sync.upload", "archive").bytes(512, Some(2048));
+        assert_eq!(event.percent, Some(25.0));
+        assert_eq!(event.total_bytes, Some(2048));
+    }
+
+    #[test]
+    fn test_event_json_fields() {
+        let event = ProgressEvent::new("sync.upload", "Uploading").bytes(10, None);
+        let value = serde_json::to_value(&event).unwrap();
+        for field in [
+            "stage",
+            "percent",
+            "bytes",
+            "total_bytes",
+            "message",
+            "timestamp",
+        ] {
+            assert!(value.get(field).is_some(), "missing {}", field);
+        }
+        assert_eq!(value["bytes"], 10);
+        assert!(value["percent"].is_null());
+    }
+}
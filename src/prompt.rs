@@ -0,0 +1,52 @@
+//! Confirmation prompts and non-interactive mode
+//!
+//! `runctl --yes` (alias `--non-interactive`, or `RUNCTL_NON_INTERACTIVE=1`, or
+//! `[cli] non_interactive = true` in config) answers every confirmation with
+//! yes. Without it, confirmations prompt when stdin is a terminal and fail
+//! with a hint to pass `--yes` otherwise, so runs under CI or cron never hang
+//! on stdin or quietly skip work.
+
+use crate::error::{Result, TrainctlError};
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static NON_INTERACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable non-interactive mode for this process
+pub fn set_non_interactive(enabled: bool) {
+    NON_INTERACTIVE.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether confirmations are answered automatically
+pub fn is_non_interactive() -> bool {
+    NON_INTERACTIVE.load(Ordering::Relaxed)
+}
+
+/// Whether a question can be put to the user right now
+pub fn can_prompt() -> bool {
+    !is_non_interactive() && io::stdin().is_terminal()
+}
+
+/// Ask a yes/no question, defaulting to no
+///
+/// Returns true without asking in non-interactive mode, and errors instead of
+/// reading stdin when it is not a terminal.
+pub fn confirm(question: &str) -> Result<bool> {
+    if is_non_interactive() {
+        return Ok(true);
+    }
+    if !io::stdin().is_terminal() {
+        return Err(TrainctlError::Validation {
+            field: "yes".to_string(),
+            reason: format!(
+                "'{}' needs confirmation but stdin is not a terminal; pass --yes to proceed",
+                question
+            ),
+        });
+    }
+    print!("{} (y/N): ", question);
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(matches!(input.trim().to_lowercase().as_str(), "y" | "yes"))
+}
@@ -2,9 +2,10 @@
 //!
 //! ## Position Statement
 //!
-//! This trait system is **defined but not yet used** by the CLI. The CLI currently
-//! uses direct implementations (`aws::handle_command()`, etc.). This is intentional
-//! technical debt - see rationale below.
+//! This trait system backs the provider-agnostic commands (`runctl run
+//! --provider`, `queue run`, and the non-AWS sections of `resources list`).
+//! Platform commands (`aws::handle_command()`, etc.) still use direct
+//! implementations. This is intentional technical debt - see rationale below.
 //!
 //! ## Why This Approach?
 //!
@@ -29,18 +30,19 @@
 //!
 //! ## Current Status
 //!
-//! The trait system is defined but not yet used by the CLI. The CLI currently uses
-//! direct implementations in `aws.rs`, `runpod.rs`, etc. This follows the pragmatic
-//! pattern where abstraction layers are prepared but not forced until multi-cloud
-//! support is actually needed.
+//! `ProviderRegistry::from_config` registers every configured provider.
+//! `runctl run --provider <name>` drives create → sync → train → wait →
+//! cleanup through the trait alone, so it works on any registered provider
+//! that reports `training_status`. Platform commands still use direct
+//! implementations in `aws.rs`, `runpod.rs`, etc.
 //!
 //! **Decision**: See `docs/PROVIDER_TRAIT_DECISION.md` for detailed rationale.
 //!
 //! ## Future Evolution Path
 //!
 //! When multi-cloud support becomes a priority:
-//! 1. Complete provider implementations (currently skeletons)
-//! 2. Add `ProviderRegistry` for dynamic provider selection
+//! 1. Complete provider implementations (Lyceum is still a skeleton)
+//! 2. Select providers through `ProviderRegistry` (done for `run`, `queue`, `resources list`)
 //! 3. Gradually migrate CLI commands to use providers
 //! 4. Support both systems during transition (like Pulumi does)
 //!
@@ -58,10 +60,6 @@ use std::path::{Path, PathBuf};
 pub type ResourceId = String;
 
 /// Training job configuration
-///
-/// This struct is part of the provider trait API and is kept for future use
-/// when the provider trait system is fully integrated.
-#[allow(dead_code)] // Reserved for future provider trait integration
 #[derive(Debug, Clone)]
 pub struct TrainingJob {
     pub script: PathBuf,
@@ -82,6 +80,8 @@ pub struct ResourceStatus {
     pub launch_time: Option<DateTime<Utc>>,
     pub cost_per_hour: f64,
     pub public_ip: Option<String>,
+    /// Globally routable IPv6 address (dual-stack or IPv6-only instances)
+    pub public_ipv6: Option<String>,
     pub tags: Vec<(String, String)>,
 }
 
@@ -98,10 +98,6 @@ pub enum ResourceState {
 }
 
 /// Status of a running training job
-///
-/// This struct is part of the provider trait API and is kept for future use
-/// when the provider trait system is fully integrated.
-#[allow(dead_code)] // Reserved for future provider trait integration
 #[derive(Debug, Clone)]
 pub struct TrainingStatus {
     pub job_id: Option<String>,
@@ -111,10 +107,6 @@ pub struct TrainingStatus {
 }
 
 /// Execution status of a training job
-///
-/// This enum is part of the provider trait API and is kept for future use
-/// when the provider trait system is fully integrated.
-#[allow(dead_code)] // Reserved for future provider trait integration
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ExecutionStatus {
     Pending,
@@ -159,10 +151,8 @@ pub enum ExecutionStatus {
 /// # }
 /// ```
 #[async_trait]
-#[allow(dead_code)] // Reserved for future provider trait integration
 pub trait TrainingProvider: Send + Sync {
     /// Provider name (e.g., "aws", "runpod", "lyceum")
-    #[allow(dead_code)] // Reserved for future provider trait integration
     fn name(&self) -> &'static str;
 
     /// Create a new compute resource (instance, pod, etc.)
@@ -224,13 +214,25 @@ pub trait TrainingProvider: Send + Sync {
 
     /// Get cost estimate for a resource type
     fn estimate_cost(&self, instance_type: &str, hours: f64) -> f64;
+
+    /// Instance type to use when the caller doesn't pass one
+    fn default_instance_type(&self) -> Option<String> {
+        None
+    }
+
+    /// Status of the training job last started on a resource with `train`
+    ///
+    /// Providers that can't tell return an error.
+    async fn training_status(&self, _resource_id: &ResourceId) -> Result<ExecutionStatus> {
+        Err(crate::error::TrainctlError::CloudProvider {
+            provider: self.name().to_string(),
+            message: "Training status is not supported by this provider".to_string(),
+            source: None,
+        })
+    }
 }
 
 /// Options for creating resources
-///
-/// This struct is part of the provider trait API and is kept for future use
-/// when the provider trait system is fully integrated.
-#[allow(dead_code)] // Reserved for future provider trait integration
 #[derive(Debug, Clone, Default)]
 pub struct CreateResourceOptions {
     pub use_spot: bool,
@@ -242,6 +244,29 @@ pub struct CreateResourceOptions {
     pub custom: std::collections::HashMap<String, String>,
 }
 
+impl std::fmt::Display for ResourceState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResourceState::Running => write!(f, "running"),
+            ResourceState::Starting => write!(f, "starting"),
+            ResourceState::Stopped => write!(f, "stopped"),
+            ResourceState::Terminating => write!(f, "terminating"),
+            ResourceState::Terminated => write!(f, "terminated"),
+            ResourceState::Error(e) => write!(f, "error: {}", e),
+            ResourceState::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Training status from a job's recorded exit code (None while it runs)
+pub fn status_from_exit_code(code: Option<i32>) -> ExecutionStatus {
+    match code {
+        None => ExecutionStatus::Running,
+        Some(0) => ExecutionStatus::Completed,
+        Some(code) => ExecutionStatus::Failed(format!("exit code {}", code)),
+    }
+}
+
 /// Helper to convert provider-specific states to ResourceState
 pub fn normalize_state(state_str: &str) -> ResourceState {
     let state_lower = state_str.to_lowercase();
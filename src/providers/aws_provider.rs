@@ -1,10 +1,14 @@
 //! AWS EC2 provider implementation
 
+use crate::aws::{
+    create_instance_and_get_id, ec2_instance_to_resource_status, get_project_name,
+    train_on_instance, CreateInstanceOptions, TrainInstanceOptions,
+};
 use crate::config::Config;
 use crate::error::{Result, TrainctlError};
 use crate::provider::*;
 use async_trait::async_trait;
-use aws_config::BehaviorVersion;
+use aws_config::{BehaviorVersion, SdkConfig};
 use aws_sdk_ec2::Client as Ec2Client;
 use aws_sdk_ssm::Client as SsmClient;
 use chrono::{DateTime, Utc};
@@ -12,30 +16,41 @@ use std::path::Path;
 
 /// AWS EC2 provider implementation
 ///
-/// Currently unused - CLI uses direct AWS implementations in aws.rs.
-/// Kept for potential future refactoring to use provider trait system.
-#[allow(dead_code)]
+/// Used by `runctl run --provider aws` and the job queue scheduler
+/// (`runctl queue run`) to provision, train on and terminate instances;
+/// other commands call the AWS module directly. Creation and training
+/// delegate to `crate::aws`, so instances get the same AMI selection, tags
+/// and code sync as `aws create`/`aws train`.
 pub struct AwsProvider {
+    aws_config: SdkConfig,
     ec2_client: Ec2Client,
     #[allow(dead_code)]
     ssm_client: SsmClient,
-    #[allow(dead_code)]
     config: Config,
 }
 
 impl AwsProvider {
-    #[allow(dead_code)] // Reserved for future provider initialization
     pub async fn new(config: Config) -> Result<Self> {
         let aws_config = aws_config::load_defaults(BehaviorVersion::latest()).await;
         let ec2_client = Ec2Client::new(&aws_config);
         let ssm_client = SsmClient::new(&aws_config);
 
         Ok(Self {
+            aws_config,
             ec2_client,
             ssm_client,
             config,
         })
     }
+
+    /// Project name from the `runctl:project` tag, else the configured default
+    fn project_name(&self, tags: &[(String, String)]) -> String {
+        let tagged = tags
+            .iter()
+            .find(|(key, _)| key == "runctl:project")
+            .map(|(_, value)| value.clone());
+        get_project_name(tagged, &self.config)
+    }
 }
 
 #[async_trait]
@@ -46,21 +61,37 @@ impl TrainingProvider for AwsProvider {
 
     async fn create_resource(
         &self,
-        _instance_type: &str,
-        _options: CreateResourceOptions,
+        instance_type: &str,
+        options: CreateResourceOptions,
     ) -> Result<ResourceId> {
-        let _aws_cfg = self.config.aws.as_ref().ok_or_else(|| {
+        let aws_cfg = self.config.aws.as_ref().ok_or_else(|| {
             TrainctlError::Config(crate::error::ConfigError::MissingField("aws".to_string()))
         })?;
 
-        // Implementation would call create_instance logic
-        // For now, return a placeholder
-        Err(TrainctlError::CloudProvider {
-            provider: "aws".to_string(),
-            message: "AWS instance creation not yet fully implemented in provider trait"
-                .to_string(),
-            source: None,
-        })
+        let create_options = CreateInstanceOptions {
+            instance_type: instance_type.to_string(),
+            use_spot: options.use_spot,
+            spot_max_price: options.spot_max_price,
+            no_fallback: false,
+            availability_zone: None,
+            key_name: None,
+            security_group: None,
+            ami_id: options.image,
+            root_volume_size: options.disk_gb.map(|gb| gb as i32),
+            data_volume_size: None,
+            project_name: self.project_name(&options.tags),
+            iam_instance_profile: aws_cfg.iam_instance_profile.clone(),
+            wait: true,
+            preset: None,
+            // Callers pass their own token (e.g. a queue job ID) in `custom`;
+            // identical launches are expected, so skip duplicate detection
+            client_token: options.custom.get("client_token").cloned(),
+            allow_duplicate: true,
+            refresh_ami: false,
+            ami_filter: None,
+            ssh_user: None,
+        };
+        create_instance_and_get_id(create_options, &self.config, &self.aws_config).await
     }
 
     async fn get_resource_status(&self, resource_id: &ResourceId) -> Result<ResourceStatus> {
@@ -120,9 +151,10 @@ impl TrainingProvider for AwsProvider {
         });
 
         let public_ip = instance.public_ip_address().map(|ip| ip.to_string());
+        let public_ipv6 = crate::aws::instance_ipv6(instance).map(str::to_string);
 
-        let cost_per_hour =
-            crate::resources::estimate_instance_cost(instance_type.as_deref().unwrap_or("unknown"));
+        crate::aws_pricing::prefetch(&self.ec2_client, std::iter::once(instance)).await;
+        let cost_per_hour = crate::aws_pricing::instance_hourly_price(instance);
 
         Ok(ResourceStatus {
             id: resource_id.clone(),
@@ -135,36 +167,68 @@ impl TrainingProvider for AwsProvider {
             launch_time,
             cost_per_hour,
             public_ip,
+            public_ipv6,
             tags,
         })
     }
 
     async fn list_resources(&self) -> Result<Vec<ResourceStatus>> {
-        // Delegate to existing list_aws_instances logic
-        // This would be refactored to use the provider trait
-        Err(TrainctlError::CloudProvider {
-            provider: "aws".to_string(),
-            message: "List resources not yet fully implemented in provider trait".to_string(),
-            source: None,
-        })
+        let response = crate::resources::describe_instances(&self.ec2_client).await?;
+        response
+            .reservations()
+            .iter()
+            .flat_map(|reservation| reservation.instances())
+            .filter_map(|instance| {
+                instance
+                    .instance_id()
+                    .map(|id| ec2_instance_to_resource_status(instance, id))
+            })
+            .collect()
     }
 
-    async fn train(&self, _resource_id: &ResourceId, _job: TrainingJob) -> Result<TrainingStatus> {
-        // Implementation would use SSM to execute training
-        Err(TrainctlError::CloudProvider {
-            provider: "aws".to_string(),
-            message: "Training not yet fully implemented in provider trait".to_string(),
-            source: None,
+    async fn train(&self, resource_id: &ResourceId, job: TrainingJob) -> Result<TrainingStatus> {
+        let train_options = TrainInstanceOptions {
+            instance_id: resource_id.clone(),
+            script: job.script,
+            data_s3: job.data_source,
+            output_s3: job.output_dest,
+            sync_code: true,
+            include_patterns: vec![],
+            exclude_patterns: vec![],
+            max_file_size: None,
+            yes: true,
+            project_name: self.project_name(&[]),
+            ssh_user: None,
+            script_args: job.args,
+            resume: None,
+            env: job.environment,
+            wait: false,
+            timeout_minutes: 120,
+            docker: false,
+            docker_image: None,
+            job: self.config.job.clone(),
+            agent: false,
+            launcher: None,
+            on_complete: None,
+        };
+        train_on_instance(train_options, &self.config, &self.aws_config, "text").await?;
+
+        Ok(TrainingStatus {
+            job_id: Some(resource_id.clone()),
+            status: ExecutionStatus::Running,
+            log_output: None,
+            checkpoint_path: job.checkpoint_dir,
         })
     }
 
-    async fn monitor(&self, _resource_id: &ResourceId, _follow: bool) -> Result<()> {
-        // Implementation would use SSM to tail logs
-        Err(TrainctlError::CloudProvider {
-            provider: "aws".to_string(),
-            message: "Monitoring not yet fully implemented in provider trait".to_string(),
-            source: None,
-        })
+    async fn monitor(&self, resource_id: &ResourceId, follow: bool) -> Result<()> {
+        let options = crate::aws::MonitorOptions {
+            instance_id: resource_id.clone(),
+            follow,
+            lines: 50,
+            ssh_user: None,
+        };
+        crate::aws::monitor_instance(options, &self.config, &self.aws_config, "text").await
     }
 
     async fn download(
@@ -200,6 +264,26 @@ impl TrainingProvider for AwsProvider {
     }
 
     fn estimate_cost(&self, instance_type: &str, hours: f64) -> f64 {
-        crate::resources::estimate_instance_cost(instance_type) * hours
+        let region = self.config.aws.as_ref().map(|aws| aws.region.as_str());
+        crate::aws_pricing::hourly_price(instance_type, region, None) * hours
+    }
+
+    fn default_instance_type(&self) -> Option<String> {
+        self.config
+            .aws
+            .as_ref()
+            .map(|aws| aws.default_instance_type.clone())
+    }
+
+    async fn training_status(&self, resource_id: &ResourceId) -> Result<ExecutionStatus> {
+        // Same completion detection as `aws wait` and `watch`
+        let (snapshot, _) = crate::watch::resolve_job(resource_id, &self.config).await?;
+        Ok(match crate::jobs::classify(&snapshot.status) {
+            None => ExecutionStatus::Running,
+            Some(Ok(())) => ExecutionStatus::Completed,
+            Some(Err(outcome)) => {
+                ExecutionStatus::Failed(format!("{} (status {})", outcome, snapshot.status))
+            }
+        })
     }
 }
@@ -0,0 +1,684 @@
+//! Azure provider implementation
+//!
+//! `AzureClient` drives the Azure CLI (`az ... -o json`) for VM lifecycle:
+//! create, show, list and delete VMs in `[azure] resource_group`. It uses the
+//! CLI's login (`az login`) rather than managing credentials itself, the same
+//! way RunPod commands lean on `runpodctl` for what its API can't do.
+//!
+//! Training, log tailing and downloads go over SSH to the VM's public IP
+//! (see `crate::azure`).
+
+use crate::config::{AzureConfig, Config};
+use crate::error::{Result, TrainctlError};
+use crate::provider::*;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::{debug, warn};
+
+/// Virtual network and security group shared by every runctl VM, so deleting
+/// a VM only leaves its public IP to clean up
+const SHARED_VNET: &str = "runctl-vnet";
+const SHARED_NSG: &str = "runctl-nsg";
+
+/// A GPU VM size with its list price
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct VmSize {
+    pub name: &'static str,
+    pub gpus: u32,
+    pub gpu: &'static str,
+    /// Pay-as-you-go Linux price in East US, USD per hour
+    pub price_per_hour: f64,
+}
+
+/// NC/ND-series sizes runctl knows prices for
+///
+/// Approximate list prices; spot VMs and other regions differ.
+pub const GPU_VM_SIZES: &[VmSize] = &[
+    VmSize {
+        name: "Standard_NC4as_T4_v3",
+        gpus: 1,
+        gpu: "T4",
+        price_per_hour: 0.526,
+    },
+    VmSize {
+        name: "Standard_NC8as_T4_v3",
+        gpus: 1,
+        gpu: "T4",
+        price_per_hour: 0.752,
+    },
+    VmSize {
+        name: "Standard_NC16as_T4_v3",
+        gpus: 1,
+        gpu: "T4",
+        price_per_hour: 1.204,
+    },
+    VmSize {
+        name: "Standard_NC64as_T4_v3",
+        gpus: 4,
+        gpu: "T4",
+        price_per_hour: 4.352,
+    },
+    VmSize {
+        name: "Standard_NC6s_v3",
+        gpus: 1,
+        gpu: "V100",
+        price_per_hour: 3.06,
+    },
+    VmSize {
+        name: "Standard_NC12s_v3",
+        gpus: 2,
+        gpu: "V100",
+        price_per_hour: 6.12,
+    },
+    VmSize {
+        name: "Standard_NC24s_v3",
+        gpus: 4,
+        gpu: "V100",
+        price_per_hour: 12.24,
+    },
+    VmSize {
+        name: "Standard_NC24ads_A100_v4",
+        gpus: 1,
+        gpu: "A100 80GB",
+        price_per_hour: 3.673,
+    },
+    VmSize {
+        name: "Standard_NC48ads_A100_v4",
+        gpus: 2,
+        gpu: "A100 80GB",
+        price_per_hour: 7.346,
+    },
+    VmSize {
+        name: "Standard_NC96ads_A100_v4",
+        gpus: 4,
+        gpu: "A100 80GB",
+        price_per_hour: 14.692,
+    },
+    VmSize {
+        name: "Standard_NC40ads_H100_v5",
+        gpus: 1,
+        gpu: "H100 NVL",
+        price_per_hour: 6.98,
+    },
+    VmSize {
+        name: "Standard_ND96asr_v4",
+        gpus: 8,
+        gpu: "A100 40GB",
+        price_per_hour: 27.197,
+    },
+    VmSize {
+        name: "Standard_ND96amsr_A100_v4",
+        gpus: 8,
+        gpu: "A100 80GB",
+        price_per_hour: 32.77,
+    },
+    VmSize {
+        name: "Standard_ND96isr_H100_v5",
+        gpus: 8,
+        gpu: "H100",
+        price_per_hour: 98.32,
+    },
+];
+
+/// Known GPU size by name (any case)
+pub fn gpu_vm_size(name: &str) -> Option<&'static VmSize> {
+    GPU_VM_SIZES
+        .iter()
+        .find(|size| size.name.eq_ignore_ascii_case(name))
+}
+
+/// Estimated hourly price of a VM size, if known
+pub fn vm_hourly_price(name: &str) -> Option<f64> {
+    gpu_vm_size(name).map(|size| size.price_per_hour)
+}
+
+/// Whether a size is in the NC or ND (GPU compute) series
+pub fn is_gpu_series(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    lower.starts_with("standard_nc") || lower.starts_with("standard_nd")
+}
+
+/// A VM as returned by `az vm show -d` / `az vm list -d`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VirtualMachine {
+    pub name: String,
+    #[serde(default)]
+    pub location: String,
+    pub hardware_profile: Option<HardwareProfile>,
+    /// e.g. "VM running", "VM deallocated"
+    pub power_state: Option<String>,
+    /// Comma-separated when the VM has several
+    pub public_ips: Option<String>,
+    /// `null` when the VM has no tags
+    pub tags: Option<BTreeMap<String, String>>,
+    pub time_created: Option<DateTime<Utc>>,
+    /// "Regular" or "Spot"
+    pub priority: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HardwareProfile {
+    pub vm_size: String,
+}
+
+impl VirtualMachine {
+    pub fn vm_size(&self) -> &str {
+        self.hardware_profile
+            .as_ref()
+            .map(|h| h.vm_size.as_str())
+            .unwrap_or("")
+    }
+
+    /// Power state without the "VM " prefix ("running", "deallocated", ...)
+    pub fn state(&self) -> String {
+        self.power_state
+            .as_deref()
+            .map(|s| s.trim_start_matches("VM ").to_lowercase())
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    /// First public IPv4 address
+    pub fn public_ip(&self) -> Option<&str> {
+        self.public_ips()
+            .find(|ip| ip.parse::<std::net::Ipv4Addr>().is_ok())
+    }
+
+    /// First public IPv6 address
+    pub fn public_ipv6(&self) -> Option<&str> {
+        self.public_ips()
+            .find(|ip| ip.parse::<std::net::Ipv6Addr>().is_ok())
+    }
+
+    /// Address to SSH to: the IPv4 address, or the IPv6 one on IPv6-only VMs
+    pub fn ssh_address(&self) -> Option<&str> {
+        self.public_ip().or_else(|| self.public_ipv6())
+    }
+
+    fn public_ips(&self) -> impl Iterator<Item = &str> {
+        self.public_ips
+            .as_deref()
+            .unwrap_or("")
+            .split(',')
+            .map(str::trim)
+            .filter(|ip| !ip.is_empty())
+    }
+
+    pub fn is_spot(&self) -> bool {
+        self.priority
+            .as_deref()
+            .is_some_and(|p| p.eq_ignore_ascii_case("spot"))
+    }
+
+    /// Estimated cost while running; deallocated VMs don't bill compute
+    pub fn cost_per_hour(&self) -> f64 {
+        if self.state() == "running" {
+            vm_hourly_price(self.vm_size()).unwrap_or(0.0)
+        } else {
+            0.0
+        }
+    }
+
+    pub fn tag(&self, key: &str) -> Option<&str> {
+        self.tags.as_ref()?.get(key).map(String::as_str)
+    }
+
+    fn to_resource_status(&self) -> ResourceStatus {
+        ResourceStatus {
+            id: self.name.clone(),
+            name: Some(self.name.clone()),
+            state: match self.state().as_str() {
+                "deallocated" | "deallocating" => ResourceState::Stopped,
+                "deleting" => ResourceState::Terminating,
+                state => normalize_state(state),
+            },
+            instance_type: Some(self.vm_size().to_string()).filter(|s| !s.is_empty()),
+            launch_time: self.time_created,
+            cost_per_hour: self.cost_per_hour(),
+            public_ip: self.public_ip().map(str::to_string),
+            public_ipv6: self.public_ipv6().map(str::to_string),
+            tags: self
+                .tags
+                .iter()
+                .flatten()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        }
+    }
+}
+
+/// VM to create
+#[derive(Debug, Clone)]
+pub struct CreateVmRequest {
+    pub name: String,
+    pub size: String,
+    /// Image URN or alias
+    pub image: String,
+    pub admin_username: String,
+    /// Path to the public key installed for `admin_username`
+    pub ssh_public_key: String,
+    pub os_disk_gb: u32,
+    /// Spot priority, evicted by deletion, capped at the on-demand price
+    pub spot: bool,
+    pub tags: Vec<(String, String)>,
+}
+
+/// Client for the Azure CLI
+#[derive(Debug, Clone)]
+pub struct AzureClient {
+    subscription: Option<String>,
+    resource_group: String,
+    location: String,
+}
+
+impl AzureClient {
+    pub fn new(settings: &AzureConfig) -> Self {
+        Self {
+            subscription: settings.subscription.clone(),
+            resource_group: settings.resource_group.clone(),
+            location: settings.location.clone(),
+        }
+    }
+
+    /// Build a client from `[azure]` (or its defaults), checking `az` is installed
+    pub fn from_config(config: &Config) -> Result<Self> {
+        if which::which("az").is_err() {
+            return Err(TrainctlError::CloudProvider {
+                provider: "azure".to_string(),
+                message: "Azure CLI (az) not found. Install it from \
+                    https://learn.microsoft.com/cli/azure/install-azure-cli and run `az login`"
+                    .to_string(),
+                source: None,
+            });
+        }
+        Ok(Self::new(&config.azure.clone().unwrap_or_default()))
+    }
+
+    pub fn resource_group(&self) -> &str {
+        &self.resource_group
+    }
+
+    /// Run `az <args> -o json` and return stdout
+    async fn run(&self, args: &[&str]) -> Result<String> {
+        let mut cmd = tokio::process::Command::new("az");
+        cmd.args(args).args(["-o", "json", "--only-show-errors"]);
+        if let Some(subscription) = &self.subscription {
+            cmd.args(["--subscription", subscription]);
+        }
+        debug!("az {}", args.join(" "));
+        let output = cmd.output().await.map_err(|e| {
+            TrainctlError::Io(std::io::Error::other(format!(
+                "Failed to execute az: {}",
+                e
+            )))
+        })?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(az_error(stderr.trim()));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    async fn query<T: DeserializeOwned>(&self, args: &[&str]) -> Result<T> {
+        let stdout = self.run(args).await?;
+        serde_json::from_str(&stdout)
+            .map_err(|e| az_error(&format!("Unexpected output from az {}: {}", args[0], e)))
+    }
+
+    /// Create the resource group if it doesn't exist (no-op otherwise)
+    pub async fn ensure_resource_group(&self) -> Result<()> {
+        self.run(&[
+            "group",
+            "create",
+            "--name",
+            &self.resource_group,
+            "--location",
+            &self.location,
+        ])
+        .await?;
+        Ok(())
+    }
+
+    /// List VMs in the resource group (empty if the group doesn't exist yet)
+    pub async fn list_vms(&self) -> Result<Vec<VirtualMachine>> {
+        match self
+            .query(&["vm", "list", "--show-details", "-g", &self.resource_group])
+            .await
+        {
+            Err(TrainctlError::CloudProvider { message, .. })
+                if message.contains("ResourceGroupNotFound") =>
+            {
+                Ok(Vec::new())
+            }
+            result => result,
+        }
+    }
+
+    /// Look up a VM by name
+    pub async fn get_vm(&self, name: &str) -> Result<VirtualMachine> {
+        match self
+            .query(&[
+                "vm",
+                "show",
+                "--show-details",
+                "-g",
+                &self.resource_group,
+                "-n",
+                name,
+            ])
+            .await
+        {
+            Err(TrainctlError::CloudProvider { message, .. })
+                if message.contains("ResourceNotFound")
+                    || message.contains("ResourceGroupNotFound") =>
+            {
+                Err(TrainctlError::ResourceNotFound {
+                    resource_type: "Azure VM".to_string(),
+                    resource_id: name.to_string(),
+                })
+            }
+            result => result,
+        }
+    }
+
+    /// Create a VM and wait until it is provisioned
+    pub async fn create_vm(&self, request: &CreateVmRequest) -> Result<VirtualMachine> {
+        self.ensure_resource_group().await?;
+        let disk = request.os_disk_gb.to_string();
+        let tags: Vec<String> = request
+            .tags
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect();
+        let mut args = vec![
+            "vm",
+            "create",
+            "-g",
+            &self.resource_group,
+            "-n",
+            &request.name,
+            "--location",
+            &self.location,
+            "--image",
+            &request.image,
+            "--size",
+            &request.size,
+            "--admin-username",
+            &request.admin_username,
+            "--ssh-key-values",
+            &request.ssh_public_key,
+            "--os-disk-size-gb",
+            &disk,
+            "--os-disk-delete-option",
+            "Delete",
+            "--nic-delete-option",
+            "Delete",
+            "--vnet-name",
+            SHARED_VNET,
+            "--subnet",
+            "default",
+            "--nsg",
+            SHARED_NSG,
+            "--public-ip-sku",
+            "Standard",
+        ];
+        if request.spot {
+            args.extend([
+                "--priority",
+                "Spot",
+                "--eviction-policy",
+                "Delete",
+                "--max-price",
+                "-1",
+            ]);
+        }
+        if !tags.is_empty() {
+            args.push("--tags");
+            args.extend(tags.iter().map(String::as_str));
+        }
+        self.run(&args).await?;
+        self.get_vm(&request.name).await
+    }
+
+    /// Delete a VM with its OS disk and NIC, then its public IP
+    pub async fn delete_vm(&self, name: &str) -> Result<()> {
+        self.run(&[
+            "vm",
+            "delete",
+            "-g",
+            &self.resource_group,
+            "-n",
+            name,
+            "--yes",
+        ])
+        .await?;
+        // `az vm create` names the IP `<vm>PublicIP`; it outlives the VM
+        let ip_name = format!("{}PublicIP", name);
+        if let Err(e) = self
+            .run(&[
+                "network",
+                "public-ip",
+                "delete",
+                "-g",
+                &self.resource_group,
+                "-n",
+                &ip_name,
+            ])
+            .await
+        {
+            warn!("Failed to delete public IP {}: {}", ip_name, e);
+        }
+        Ok(())
+    }
+}
+
+fn az_error(message: &str) -> TrainctlError {
+    let message = if message.contains("az login") {
+        format!("{} (run `az login`)", message)
+    } else {
+        message.to_string()
+    };
+    TrainctlError::CloudProvider {
+        provider: "azure".to_string(),
+        message,
+        source: None,
+    }
+}
+
+/// Azure provider implementation
+pub struct AzureProvider {
+    config: Config,
+}
+
+impl AzureProvider {
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+
+    fn client(&self) -> Result<AzureClient> {
+        AzureClient::from_config(&self.config)
+    }
+}
+
+#[async_trait]
+impl TrainingProvider for AzureProvider {
+    fn name(&self) -> &'static str {
+        "azure"
+    }
+
+    async fn create_resource(
+        &self,
+        instance_type: &str, // VM size
+        options: CreateResourceOptions,
+    ) -> Result<ResourceId> {
+        let settings = self.config.azure.clone().unwrap_or_default();
+        let mut tags = options.tags;
+        tags.push(("runctl:managed".to_string(), "true".to_string()));
+        let request = CreateVmRequest {
+            name: options
+                .custom
+                .get("name")
+                .cloned()
+                .unwrap_or_else(crate::azure::generate_vm_name),
+            size: instance_type.to_string(),
+            image: options.image.unwrap_or(settings.image.clone()),
+            admin_username: settings.admin_username.clone(),
+            ssh_public_key: crate::azure::public_key_path(&settings)?,
+            os_disk_gb: options.disk_gb.unwrap_or(settings.os_disk_gb),
+            spot: options.use_spot,
+            tags,
+        };
+        Ok(self.client()?.create_vm(&request).await?.name)
+    }
+
+    async fn get_resource_status(&self, resource_id: &ResourceId) -> Result<ResourceStatus> {
+        Ok(self
+            .client()?
+            .get_vm(resource_id)
+            .await?
+            .to_resource_status())
+    }
+
+    async fn list_resources(&self) -> Result<Vec<ResourceStatus>> {
+        Ok(self
+            .client()?
+            .list_vms()
+            .await?
+            .iter()
+            .map(VirtualMachine::to_resource_status)
+            .collect())
+    }
+
+    async fn train(&self, resource_id: &ResourceId, job: TrainingJob) -> Result<TrainingStatus> {
+        crate::azure::train_on_vm(
+            resource_id,
+            &job.script,
+            &job.args,
+            &job.environment,
+            true,
+            &self.config,
+            "text",
+        )
+        .await?;
+        Ok(TrainingStatus {
+            job_id: Some(resource_id.clone()),
+            status: ExecutionStatus::Running,
+            log_output: None,
+            checkpoint_path: job.checkpoint_dir,
+        })
+    }
+
+    async fn monitor(&self, resource_id: &ResourceId, follow: bool) -> Result<()> {
+        crate::azure::monitor_vm(resource_id, follow, &self.config).await
+    }
+
+    async fn download(
+        &self,
+        resource_id: &ResourceId,
+        remote_path: &Path,
+        local_path: &Path,
+    ) -> Result<()> {
+        crate::azure::download_from_vm(resource_id, remote_path, local_path, &self.config).await
+    }
+
+    async fn terminate(&self, resource_id: &ResourceId) -> Result<()> {
+        self.client()?.delete_vm(resource_id).await
+    }
+
+    fn estimate_cost(&self, instance_type: &str, hours: f64) -> f64 {
+        vm_hourly_price(instance_type).unwrap_or(0.0) * hours
+    }
+
+    fn default_instance_type(&self) -> Option<String> {
+        Some(
+            self.config
+                .azure
+                .clone()
+                .unwrap_or_default()
+                .default_vm_size,
+        )
+    }
+
+    async fn training_status(&self, resource_id: &ResourceId) -> Result<ExecutionStatus> {
+        let vm = self.client()?.get_vm(resource_id).await?;
+        if vm.state() != "running" {
+            return Ok(ExecutionStatus::Failed(format!("VM is {}", vm.state())));
+        }
+        let code = crate::azure::training_exit_code(resource_id, &self.config).await?;
+        Ok(status_from_exit_code(code))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vm_from_az_output() {
+        let body = r#"[
+            {"name":"runctl-1a2b3c4d","location":"eastus",
+             "hardwareProfile":{"vmSize":"Standard_NC4as_T4_v3"},
+             "powerState":"VM running","publicIps":"20.1.2.3","priority":"Spot",
+             "tags":{"runctl:managed":"true","runctl:project":"demo"},
+             "timeCreated":"2026-10-01T12:00:00.000000+00:00"},
+            {"name":"old","location":"eastus","hardwareProfile":{"vmSize":"Standard_ND96asr_v4"},
+             "powerState":"VM deallocated","publicIps":"","priority":null,"tags":null}
+        ]"#;
+        let vms: Vec<VirtualMachine> = serde_json::from_str(body).unwrap();
+        assert_eq!(vms.len(), 2);
+
+        let running = &vms[0];
+        assert_eq!(running.state(), "running");
+        assert_eq!(running.public_ip(), Some("20.1.2.3"));
+        assert!(running.is_spot());
+        assert_eq!(running.tag("runctl:project"), Some("demo"));
+        assert_eq!(running.cost_per_hour(), 0.526);
+        assert_eq!(running.to_resource_status().state, ResourceState::Running);
+
+        let stopped = &vms[1];
+        assert_eq!(stopped.state(), "deallocated");
+        assert_eq!(stopped.public_ip(), None);
+        assert_eq!(stopped.ssh_address(), None);
+        assert!(!stopped.is_spot());
+        assert_eq!(stopped.cost_per_hour(), 0.0);
+        assert_eq!(stopped.to_resource_status().state, ResourceState::Stopped);
+    }
+
+    #[test]
+    fn test_public_addresses() {
+        let vm = |ips: &str| VirtualMachine {
+            name: "vm".to_string(),
+            location: String::new(),
+            hardware_profile: None,
+            power_state: None,
+            public_ips: Some(ips.to_string()),
+            tags: None,
+            time_created: None,
+            priority: None,
+        };
+        let dual = vm("2603:1030::5, 20.1.2.3");
+        assert_eq!(dual.public_ip(), Some("20.1.2.3"));
+        assert_eq!(dual.public_ipv6(), Some("2603:1030::5"));
+        assert_eq!(dual.ssh_address(), Some("20.1.2.3"));
+        let v6_only = vm("2603:1030::5");
+        assert_eq!(v6_only.public_ip(), None);
+        assert_eq!(v6_only.ssh_address(), Some("2603:1030::5"));
+    }
+
+    #[test]
+    fn test_vm_size_pricing() {
+        assert_eq!(vm_hourly_price("standard_nc24ads_a100_v4"), Some(3.673));
+        assert_eq!(vm_hourly_price("Standard_D4s_v5"), None);
+        assert!(is_gpu_series("Standard_ND96isr_H100_v5"));
+        assert!(!is_gpu_series("Standard_NV36ads_A10_v5"));
+        assert!(GPU_VM_SIZES.iter().all(|size| is_gpu_series(size.name)));
+
+        let provider = AzureProvider::new(Config::default());
+        assert_eq!(provider.estimate_cost("Standard_NC6s_v3", 2.0), 6.12);
+    }
+}
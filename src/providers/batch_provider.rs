@@ -0,0 +1,806 @@
+//! AWS Batch provider implementation
+//!
+//! For platforms that mandate Batch over raw EC2. A "resource" is a job
+//! definition revision: `create_resource` registers one sized from an EC2
+//! instance type, `train` ships the project to S3 and submits a single job
+//! against it, and `terminate` cancels that job and deregisters the revision.
+//! Each revision runs at most one job, so the job is found again by filtering
+//! the queue on the definition ARN rather than by keeping local state.
+//!
+//! Job logs come from CloudWatch Logs (`/aws/batch/job`), where Batch sends
+//! container output by default.
+
+use crate::config::{BatchConfig, Config};
+use crate::error::{ConfigError, Result, TrainctlError};
+use crate::provider::*;
+use crate::sync_filter::SyncFilter;
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_batch::types::{
+    ContainerOverrides, ContainerProperties, JobDefinitionType, KeyValuePair, KeyValuesPair,
+    ResourceRequirement, ResourceType,
+};
+use aws_sdk_batch::Client as BatchApi;
+use aws_sdk_cloudwatchlogs::Client as LogsClient;
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_s3::Client as S3Client;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Log group Batch writes container output to
+pub const BATCH_LOG_GROUP: &str = "/aws/batch/job";
+
+/// Working directory the code tarball is unpacked into inside the container
+const CONTAINER_WORKDIR: &str = "/opt/runctl/code";
+
+/// How often `monitor --follow` polls CloudWatch Logs
+const LOG_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// vCPUs, memory and GPUs a job definition requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobResources {
+    pub vcpus: i32,
+    pub memory_mib: i64,
+    pub gpus: i32,
+}
+
+impl JobResources {
+    /// Resources for a job that should fill one instance of a type
+    ///
+    /// The ECS agent and OS take some memory, and a job asking for the
+    /// instance's full memory never gets placed, so 10% is left free.
+    pub fn for_instance(vcpus: i32, memory_mib: i64, gpus: i32) -> Self {
+        Self {
+            vcpus,
+            memory_mib: memory_mib * 9 / 10,
+            gpus,
+        }
+    }
+
+    fn requirements(&self) -> Result<Vec<ResourceRequirement>> {
+        let mut requirements = vec![
+            (ResourceType::Vcpu, self.vcpus.to_string()),
+            (ResourceType::Memory, self.memory_mib.to_string()),
+        ];
+        if self.gpus > 0 {
+            requirements.push((ResourceType::Gpu, self.gpus.to_string()));
+        }
+        requirements
+            .into_iter()
+            .map(|(kind, value)| {
+                ResourceRequirement::builder()
+                    .r#type(kind)
+                    .value(value)
+                    .build()
+                    .map_err(|e| batch_error(format!("Invalid resource requirement: {}", e)))
+            })
+            .collect()
+    }
+}
+
+/// Job definition name for a project: `runctl-<project>`
+///
+/// Batch allows letters, digits, hyphens and underscores, up to 128 chars.
+pub fn job_definition_name(project: &str) -> String {
+    let sanitized: String = project
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let mut name = format!("runctl-{}", sanitized);
+    name.truncate(128);
+    name
+}
+
+/// Name and revision from a job definition ARN
+/// (`arn:aws:batch:<region>:<account>:job-definition/<name>:<revision>`)
+pub fn parse_definition_arn(arn: &str) -> Option<(&str, u32)> {
+    let (_, definition) = arn.rsplit_once("job-definition/")?;
+    let (name, revision) = definition.rsplit_once(':')?;
+    Some((name, revision.parse().ok()?))
+}
+
+/// S3 key of the code tarball for a job definition revision
+fn code_key(name: &str, revision: u32) -> String {
+    format!("runctl-batch/{}/{}/code.tar.gz", name, revision)
+}
+
+/// Container command: fetch and unpack the code, then run the script
+pub fn container_command(code_uri: &str, script: &Path, args: &[String]) -> Vec<String> {
+    let script = script.to_string_lossy();
+    let interpreter = if script.ends_with(".py") {
+        "python3"
+    } else {
+        "bash"
+    };
+    let args: String = args
+        .iter()
+        .map(|arg| format!(" {}", crate::utils::shell_quote(arg)))
+        .collect();
+    let run = format!(
+        "set -e; mkdir -p {dir} && cd {dir} && aws s3 cp {uri} code.tar.gz --only-show-errors \
+         && tar -xzf code.tar.gz && rm -f code.tar.gz && exec {interpreter} {script}{args}",
+        dir = CONTAINER_WORKDIR,
+        uri = crate::utils::shell_quote(code_uri),
+        interpreter = interpreter,
+        script = crate::utils::shell_quote(&script),
+        args = args,
+    );
+    vec!["bash".to_string(), "-c".to_string(), run]
+}
+
+/// Map a Batch job status onto the runctl job model
+pub fn execution_status(status: &str, reason: Option<&str>) -> ExecutionStatus {
+    match status {
+        "SUBMITTED" | "PENDING" | "RUNNABLE" | "STARTING" => ExecutionStatus::Pending,
+        "RUNNING" => ExecutionStatus::Running,
+        "SUCCEEDED" => ExecutionStatus::Completed,
+        "FAILED" => match reason {
+            Some(reason) if reason.contains("Terminated by runctl") => ExecutionStatus::Cancelled,
+            Some(reason) => ExecutionStatus::Failed(reason.to_string()),
+            None => ExecutionStatus::Failed("job failed".to_string()),
+        },
+        other => ExecutionStatus::Failed(format!("unknown Batch job status {}", other)),
+    }
+}
+
+/// Resource state of a job definition, from its job's status (if any)
+pub fn resource_state(job_status: Option<&str>, reason: Option<&str>) -> ResourceState {
+    match job_status {
+        // Registered, nothing submitted yet
+        None => ResourceState::Stopped,
+        Some("SUBMITTED" | "PENDING" | "RUNNABLE" | "STARTING") => ResourceState::Starting,
+        Some("RUNNING") => ResourceState::Running,
+        Some("SUCCEEDED") => ResourceState::Stopped,
+        Some("FAILED") => ResourceState::Error(reason.unwrap_or("job failed").to_string()),
+        Some(_) => ResourceState::Unknown,
+    }
+}
+
+fn batch_error(message: impl Into<String>) -> TrainctlError {
+    TrainctlError::CloudProvider {
+        provider: "batch".to_string(),
+        message: message.into(),
+        source: None,
+    }
+}
+
+/// The job submitted against a job definition revision
+#[derive(Debug, Clone)]
+pub struct BatchJob {
+    pub job_id: String,
+    pub status: String,
+    pub status_reason: Option<String>,
+    pub log_stream: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl BatchJob {
+    pub fn is_finished(&self) -> bool {
+        matches!(self.status.as_str(), "SUCCEEDED" | "FAILED")
+    }
+}
+
+/// Batch, CloudWatch Logs, EC2 and S3 clients plus the `[batch]` settings
+pub struct BatchClient {
+    batch: BatchApi,
+    logs: LogsClient,
+    ec2: Ec2Client,
+    s3: S3Client,
+    settings: BatchConfig,
+    bucket: Option<String>,
+}
+
+impl BatchClient {
+    pub fn new(sdk_config: &SdkConfig, config: &Config) -> Self {
+        let settings = config.batch.clone().unwrap_or_default();
+        let bucket = settings
+            .s3_bucket
+            .clone()
+            .or_else(|| config.aws.as_ref().and_then(|aws| aws.s3_bucket.clone()));
+        Self {
+            batch: BatchApi::new(sdk_config),
+            logs: LogsClient::new(sdk_config),
+            ec2: Ec2Client::new(sdk_config),
+            s3: S3Client::new(sdk_config),
+            settings,
+            bucket,
+        }
+    }
+
+    pub fn settings(&self) -> &BatchConfig {
+        &self.settings
+    }
+
+    fn bucket(&self) -> Result<&str> {
+        self.bucket.as_deref().ok_or_else(|| {
+            TrainctlError::Config(ConfigError::MissingField(
+                "batch.s3_bucket (or aws.s3_bucket)".to_string(),
+            ))
+        })
+    }
+
+    /// vCPUs, memory and GPUs of an EC2 instance type
+    pub async fn instance_resources(&self, instance_type: &str) -> Result<JobResources> {
+        let response = self
+            .ec2
+            .describe_instance_types()
+            .instance_types(aws_sdk_ec2::types::InstanceType::from(instance_type))
+            .send()
+            .await
+            .map_err(|e| {
+                TrainctlError::Aws(format!(
+                    "Failed to describe instance type {}: {}",
+                    instance_type, e
+                ))
+            })?;
+        let info = response
+            .instance_types()
+            .first()
+            .ok_or_else(|| TrainctlError::Validation {
+                field: "instance_type".to_string(),
+                reason: format!("unknown EC2 instance type {}", instance_type),
+            })?;
+        let vcpus = info
+            .v_cpu_info()
+            .and_then(|v| v.default_v_cpus())
+            .unwrap_or(1);
+        let memory_mib = info
+            .memory_info()
+            .and_then(|m| m.size_in_mib())
+            .unwrap_or(2048);
+        let gpus = info
+            .gpu_info()
+            .map(|g| g.gpus().iter().filter_map(|d| d.count()).sum())
+            .unwrap_or(0);
+        Ok(JobResources::for_instance(vcpus, memory_mib, gpus))
+    }
+
+    /// Register a new revision of the project's job definition
+    pub async fn register_definition(
+        &self,
+        project: &str,
+        image: &str,
+        resources: JobResources,
+        tags: HashMap<String, String>,
+    ) -> Result<String> {
+        let container = ContainerProperties::builder()
+            .image(image)
+            .set_job_role_arn(self.settings.job_role_arn.clone())
+            .set_resource_requirements(Some(resources.requirements()?))
+            .build();
+        let response = self
+            .batch
+            .register_job_definition()
+            .job_definition_name(job_definition_name(project))
+            .r#type(JobDefinitionType::Container)
+            .container_properties(container)
+            .propagate_tags(true)
+            .set_tags(Some(tags))
+            .send()
+            .await
+            .map_err(|e| TrainctlError::Aws(format!("Failed to register job definition: {}", e)))?;
+        Ok(response.job_definition_arn().to_string())
+    }
+
+    /// Upload the project and submit one job against `definition_arn`
+    ///
+    /// `script` is relative to `project_root`, which is shipped using `filter`.
+    pub async fn submit(
+        &self,
+        definition_arn: &str,
+        job: &TrainingJob,
+        script: &Path,
+        project_root: &Path,
+        filter: &SyncFilter,
+    ) -> Result<String> {
+        let (name, revision) =
+            parse_definition_arn(definition_arn).ok_or_else(|| TrainctlError::Validation {
+                field: "resource_id".to_string(),
+                reason: format!("{} is not a Batch job definition ARN", definition_arn),
+            })?;
+        let bucket = self.bucket()?;
+        let key = code_key(name, revision);
+        self.upload_code(project_root, filter, bucket, &key).await?;
+
+        let command = container_command(&format!("s3://{}/{}", bucket, key), script, &job.args);
+        let environment = job
+            .environment
+            .iter()
+            .map(|(name, value)| KeyValuePair::builder().name(name).value(value).build())
+            .collect();
+        let response = self
+            .batch
+            .submit_job()
+            .job_name(format!("{}-{}", name, revision))
+            .job_queue(&self.settings.job_queue)
+            .job_definition(definition_arn)
+            .container_overrides(
+                ContainerOverrides::builder()
+                    .set_command(Some(command))
+                    .set_environment(Some(environment))
+                    .build(),
+            )
+            .send()
+            .await
+            .map_err(|e| {
+                TrainctlError::Aws(format!(
+                    "Failed to submit job to queue {}: {}",
+                    self.settings.job_queue, e
+                ))
+            })?;
+        Ok(response.job_id().to_string())
+    }
+
+    async fn upload_code(
+        &self,
+        project_root: &Path,
+        filter: &SyncFilter,
+        bucket: &str,
+        key: &str,
+    ) -> Result<()> {
+        let files = filter.select(project_root)?.files;
+        let archive =
+            std::env::temp_dir().join(format!("runctl-code-{}.tar.gz", uuid::Uuid::new_v4()));
+        let added = crate::sync_filter::write_code_archive(project_root, &files, &archive)?;
+        if added == 0 {
+            return Err(batch_error(format!(
+                "No files to ship from {}",
+                project_root.display()
+            )));
+        }
+        crate::progress::emit(
+            "sync.upload",
+            format!("Uploading {} files to s3://{}/{}", added, bucket, key),
+        );
+        let body = aws_sdk_s3::primitives::ByteStream::from_path(&archive)
+            .await
+            .map_err(|e| TrainctlError::S3(format!("Failed to read archive: {}", e)))?;
+        let result = self
+            .s3
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| TrainctlError::S3(format!("Failed to upload code to S3: {}", e)));
+        if let Err(e) = std::fs::remove_file(&archive) {
+            warn!("Failed to remove {}: {}", archive.display(), e);
+        }
+        result.map(|_| ())
+    }
+
+    /// The job submitted against a job definition revision, if any
+    pub async fn find_job(&self, definition_arn: &str) -> Result<Option<BatchJob>> {
+        let filter = KeyValuesPair::builder()
+            .name("JOB_DEFINITION")
+            .values(definition_arn)
+            .build();
+        let response = self
+            .batch
+            .list_jobs()
+            .job_queue(&self.settings.job_queue)
+            .filters(filter)
+            .send()
+            .await
+            .map_err(|e| TrainctlError::Aws(format!("Failed to list Batch jobs: {}", e)))?;
+        let Some(summary) = response
+            .job_summary_list()
+            .iter()
+            .max_by_key(|job| job.created_at().unwrap_or(0))
+        else {
+            return Ok(None);
+        };
+        self.describe_job(summary.job_id()).await.map(Some)
+    }
+
+    pub async fn describe_job(&self, job_id: &str) -> Result<BatchJob> {
+        let response = self
+            .batch
+            .describe_jobs()
+            .jobs(job_id)
+            .send()
+            .await
+            .map_err(|e| TrainctlError::Aws(format!("Failed to describe job {}: {}", job_id, e)))?;
+        let detail = response
+            .jobs()
+            .first()
+            .ok_or_else(|| TrainctlError::ResourceNotFound {
+                resource_type: "Batch job".to_string(),
+                resource_id: job_id.to_string(),
+            })?;
+        Ok(BatchJob {
+            job_id: job_id.to_string(),
+            status: detail
+                .status()
+                .map(|s| s.as_str().to_string())
+                .unwrap_or_default(),
+            status_reason: detail.status_reason().map(str::to_string),
+            log_stream: detail
+                .container()
+                .and_then(|c| c.log_stream_name())
+                .map(str::to_string),
+            created_at: detail
+                .created_at()
+                .and_then(DateTime::<Utc>::from_timestamp_millis),
+        })
+    }
+
+    /// Print log events after `token`; returns the token to continue from
+    pub async fn print_logs(
+        &self,
+        log_stream: &str,
+        token: Option<String>,
+    ) -> Result<Option<String>> {
+        let response = self
+            .logs
+            .get_log_events()
+            .log_group_name(BATCH_LOG_GROUP)
+            .log_stream_name(log_stream)
+            .start_from_head(true)
+            .set_next_token(token.clone())
+            .send()
+            .await
+            .map_err(|e| TrainctlError::Aws(format!("Failed to read job log: {}", e)))?;
+        for event in response.events() {
+            if let Some(message) = event.message() {
+                println!("{}", crate::output_style::sanitize(message));
+            }
+        }
+        Ok(response.next_forward_token().map(str::to_string).or(token))
+    }
+
+    /// Cancel the job (if still active), deregister the definition and remove its code
+    pub async fn delete(&self, definition_arn: &str) -> Result<()> {
+        if let Some(job) = self.find_job(definition_arn).await? {
+            if !job.is_finished() {
+                self.batch
+                    .terminate_job()
+                    .job_id(&job.job_id)
+                    .reason("Terminated by runctl")
+                    .send()
+                    .await
+                    .map_err(|e| {
+                        TrainctlError::Aws(format!("Failed to terminate job {}: {}", job.job_id, e))
+                    })?;
+                info!("Terminated Batch job {}", job.job_id);
+            }
+        }
+        self.batch
+            .deregister_job_definition()
+            .job_definition(definition_arn)
+            .send()
+            .await
+            .map_err(|e| {
+                TrainctlError::Aws(format!(
+                    "Failed to deregister job definition {}: {}",
+                    definition_arn, e
+                ))
+            })?;
+        if let (Ok(bucket), Some((name, revision))) =
+            (self.bucket(), parse_definition_arn(definition_arn))
+        {
+            if let Err(e) = self
+                .s3
+                .delete_object()
+                .bucket(bucket)
+                .key(code_key(name, revision))
+                .send()
+                .await
+            {
+                warn!(
+                    "Failed to delete code tarball for {}: {}",
+                    definition_arn, e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Active job definitions registered by runctl
+    pub async fn list_definitions(&self) -> Result<Vec<(String, HashMap<String, String>)>> {
+        let mut definitions = Vec::new();
+        let mut token = None;
+        loop {
+            let response = self
+                .batch
+                .describe_job_definitions()
+                .status("ACTIVE")
+                .set_next_token(token)
+                .send()
+                .await
+                .map_err(|e| {
+                    TrainctlError::Aws(format!("Failed to list job definitions: {}", e))
+                })?;
+            for definition in response.job_definitions() {
+                let tags = definition.tags().cloned().unwrap_or_default();
+                if tags.get("runctl:managed").map(String::as_str) == Some("true") {
+                    definitions.push((definition.job_definition_arn().to_string(), tags));
+                }
+            }
+            token = response.next_token().map(str::to_string);
+            if token.is_none() {
+                break;
+            }
+        }
+        Ok(definitions)
+    }
+
+    async fn resource_status(
+        &self,
+        definition_arn: &str,
+        tags: HashMap<String, String>,
+    ) -> Result<ResourceStatus> {
+        let job = self.find_job(definition_arn).await?;
+        let instance_type = tags.get("runctl:instance-type").cloned();
+        let cost_per_hour = match (&job, &instance_type) {
+            (Some(job), Some(instance_type)) if job.status == "RUNNING" => {
+                crate::aws_pricing::hourly_price(instance_type, None, None)
+            }
+            _ => 0.0,
+        };
+        let mut tags: Vec<(String, String)> = tags.into_iter().collect();
+        tags.sort();
+        Ok(ResourceStatus {
+            id: definition_arn.to_string(),
+            name: job.as_ref().map(|job| job.job_id.clone()),
+            state: resource_state(
+                job.as_ref().map(|job| job.status.as_str()),
+                job.as_ref().and_then(|job| job.status_reason.as_deref()),
+            ),
+            instance_type,
+            launch_time: job.as_ref().and_then(|job| job.created_at),
+            cost_per_hour,
+            public_ip: None,
+            public_ipv6: None,
+            tags,
+        })
+    }
+}
+
+/// AWS Batch provider implementation
+///
+/// Used by `runctl run --provider batch`; `runctl batch setup` prepares the
+/// job queue it submits to.
+pub struct BatchProvider {
+    client: BatchClient,
+    config: Config,
+}
+
+impl BatchProvider {
+    pub async fn new(config: Config) -> Result<Self> {
+        let sdk_config = crate::aws_utils::shared_sdk_config().await;
+        Ok(Self {
+            client: BatchClient::new(&sdk_config, &config),
+            config,
+        })
+    }
+}
+
+#[async_trait]
+impl TrainingProvider for BatchProvider {
+    fn name(&self) -> &'static str {
+        "batch"
+    }
+
+    async fn create_resource(
+        &self,
+        instance_type: &str,
+        options: CreateResourceOptions,
+    ) -> Result<ResourceId> {
+        let image = options
+            .image
+            .or_else(|| self.client.settings().image.clone())
+            .ok_or_else(|| {
+                TrainctlError::Config(ConfigError::MissingField("batch.image".to_string()))
+            })?;
+        let resources = self.client.instance_resources(instance_type).await?;
+        let mut tags: HashMap<String, String> = options.tags.into_iter().collect();
+        tags.insert("runctl:managed".to_string(), "true".to_string());
+        tags.insert(
+            "runctl:instance-type".to_string(),
+            instance_type.to_string(),
+        );
+        let project = tags
+            .get("runctl:project")
+            .cloned()
+            .unwrap_or_else(|| crate::aws::get_project_name(None, &self.config));
+        let arn = self
+            .client
+            .register_definition(&project, &image, resources, tags)
+            .await?;
+        info!(
+            "Registered job definition {} ({} vCPU, {} MiB, {} GPU)",
+            arn, resources.vcpus, resources.memory_mib, resources.gpus
+        );
+        Ok(arn)
+    }
+
+    async fn get_resource_status(&self, resource_id: &ResourceId) -> Result<ResourceStatus> {
+        let tags = self
+            .client
+            .list_definitions()
+            .await?
+            .into_iter()
+            .find(|(arn, _)| arn == resource_id)
+            .map(|(_, tags)| tags)
+            .ok_or_else(|| TrainctlError::ResourceNotFound {
+                resource_type: "Batch job definition".to_string(),
+                resource_id: resource_id.clone(),
+            })?;
+        self.client.resource_status(resource_id, tags).await
+    }
+
+    async fn list_resources(&self) -> Result<Vec<ResourceStatus>> {
+        let mut resources = Vec::new();
+        for (arn, tags) in self.client.list_definitions().await? {
+            resources.push(self.client.resource_status(&arn, tags).await?);
+        }
+        Ok(resources)
+    }
+
+    async fn train(&self, resource_id: &ResourceId, job: TrainingJob) -> Result<TrainingStatus> {
+        let project_root = std::env::current_dir()?.canonicalize()?;
+        let script_abs = job.script.canonicalize().map_err(|_| {
+            TrainctlError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Script not found: {}", job.script.display()),
+            ))
+        })?;
+        let script =
+            script_abs
+                .strip_prefix(&project_root)
+                .map_err(|_| TrainctlError::Validation {
+                    field: "script".to_string(),
+                    reason: format!(
+                        "{} is outside the current directory ({}), which is what gets shipped",
+                        job.script.display(),
+                        project_root.display()
+                    ),
+                })?;
+        let filter = SyncFilter::from_config(&self.config.sync)?;
+        let job_id = self
+            .client
+            .submit(resource_id, &job, script, &project_root, &filter)
+            .await?;
+        info!("Submitted Batch job {} for {}", job_id, resource_id);
+        Ok(TrainingStatus {
+            job_id: Some(job_id),
+            status: ExecutionStatus::Pending,
+            log_output: None,
+            checkpoint_path: job.checkpoint_dir,
+        })
+    }
+
+    async fn monitor(&self, resource_id: &ResourceId, follow: bool) -> Result<()> {
+        let mut token = None;
+        loop {
+            let job = self.client.find_job(resource_id).await?.ok_or_else(|| {
+                TrainctlError::ResourceNotFound {
+                    resource_type: "Batch job".to_string(),
+                    resource_id: resource_id.clone(),
+                }
+            })?;
+            match &job.log_stream {
+                Some(stream) => token = self.client.print_logs(stream, token).await?,
+                None if !follow => println!("Job {} is {}; no logs yet", job.job_id, job.status),
+                None => {}
+            }
+            if !follow || job.is_finished() {
+                return Ok(());
+            }
+            tokio::time::sleep(LOG_POLL_INTERVAL).await;
+        }
+    }
+
+    async fn download(
+        &self,
+        resource_id: &ResourceId,
+        _remote_path: &Path,
+        _local_path: &Path,
+    ) -> Result<()> {
+        Err(batch_error(format!(
+            "Batch containers are gone once {} finishes; have the script write results to S3",
+            resource_id
+        )))
+    }
+
+    async fn terminate(&self, resource_id: &ResourceId) -> Result<()> {
+        self.client.delete(resource_id).await
+    }
+
+    fn estimate_cost(&self, instance_type: &str, hours: f64) -> f64 {
+        let region = self.config.aws.as_ref().map(|aws| aws.region.as_str());
+        crate::aws_pricing::hourly_price(instance_type, region, None) * hours
+    }
+
+    fn default_instance_type(&self) -> Option<String> {
+        Some(self.client.settings().default_instance_type.clone())
+    }
+
+    async fn training_status(&self, resource_id: &ResourceId) -> Result<ExecutionStatus> {
+        let job = self.client.find_job(resource_id).await?.ok_or_else(|| {
+            TrainctlError::ResourceNotFound {
+                resource_type: "Batch job".to_string(),
+                resource_id: resource_id.clone(),
+            }
+        })?;
+        Ok(execution_status(&job.status, job.status_reason.as_deref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_resources() {
+        let g4dn = JobResources::for_instance(4, 16384, 1);
+        assert_eq!(g4dn.memory_mib, 14745);
+        assert_eq!(g4dn.requirements().unwrap().len(), 3);
+        let cpu = JobResources::for_instance(2, 8192, 0);
+        assert_eq!(cpu.requirements().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_definition_names() {
+        assert_eq!(job_definition_name("my model.v2"), "runctl-my-model-v2");
+        let arn = "arn:aws:batch:us-east-1:123456789012:job-definition/runctl-demo:7";
+        assert_eq!(parse_definition_arn(arn), Some(("runctl-demo", 7)));
+        assert_eq!(parse_definition_arn("i-0123456789abcdef0"), None);
+        assert_eq!(
+            code_key("runctl-demo", 7),
+            "runctl-batch/runctl-demo/7/code.tar.gz"
+        );
+    }
+
+    #[test]
+    fn test_container_command() {
+        let command = container_command(
+            "s3://bucket/runctl-batch/runctl-demo/7/code.tar.gz",
+            Path::new("src/train.py"),
+            &["--epochs".to_string(), "5 10".to_string()],
+        );
+        assert_eq!(command[..2], ["bash", "-c"]);
+        assert!(
+            command[2].contains("aws s3 cp 's3://bucket/runctl-batch/runctl-demo/7/code.tar.gz'")
+        );
+        assert!(command[2].ends_with("exec python3 'src/train.py' '--epochs' '5 10'"));
+    }
+
+    #[test]
+    fn test_status_mapping() {
+        assert_eq!(execution_status("RUNNABLE", None), ExecutionStatus::Pending);
+        assert_eq!(execution_status("RUNNING", None), ExecutionStatus::Running);
+        assert_eq!(
+            execution_status("SUCCEEDED", None),
+            ExecutionStatus::Completed
+        );
+        assert_eq!(
+            execution_status("FAILED", Some("Essential container in task exited")),
+            ExecutionStatus::Failed("Essential container in task exited".to_string())
+        );
+        assert_eq!(
+            execution_status("FAILED", Some("Terminated by runctl")),
+            ExecutionStatus::Cancelled
+        );
+        assert_eq!(resource_state(None, None), ResourceState::Stopped);
+        assert_eq!(
+            resource_state(Some("STARTING"), None),
+            ResourceState::Starting
+        );
+        assert_eq!(
+            resource_state(Some("RUNNING"), None),
+            ResourceState::Running
+        );
+    }
+}
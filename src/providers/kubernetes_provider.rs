@@ -0,0 +1,1124 @@
+//! Kubernetes provider implementation
+//!
+//! `KubeClient` drives `kubectl` (`-o json`) against the kubeconfig context
+//! and namespace in `[kubernetes]`, using whatever credentials the context
+//! carries, the same way `AzureClient` leans on the `az` login.
+//!
+//! A "resource" is a ConfigMap named after the job: `create_resource` records
+//! the node type and image in it, `train` submits a Job (or PyTorchJob) of
+//! the same name, and `terminate` deletes both. Pods start with an init
+//! container that waits until runctl has copied the project into a shared
+//! volume (`kubectl cp`), so the image doesn't need the code baked in and no
+//! bucket is involved.
+//!
+//! Logs come from `kubectl logs` on the job's first pod (the PyTorchJob
+//! master).
+
+use crate::config::{Config, KubernetesConfig, KubernetesJobKind};
+use crate::error::{ConfigError, Result, TrainctlError};
+use crate::provider::*;
+use crate::sync_filter::SyncFilter;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use tracing::{debug, info, warn};
+
+/// Directory the project is unpacked into inside the pods
+const CODE_DIR: &str = "/opt/runctl/code";
+
+/// Init container that holds the pod until the code has been copied in
+const SYNC_CONTAINER: &str = "runctl-sync";
+const SYNC_IMAGE: &str = "busybox:1.36";
+
+/// Label every runctl object carries, and the one tying pods to their job
+const MANAGED_LABEL: &str = "runctl/managed";
+const JOB_LABEL: &str = "runctl/job";
+
+/// How long pods may take to be scheduled and pull their images
+const POD_START_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+const POD_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Fully qualified kubectl resource for a job kind
+fn kind_resource(kind: KubernetesJobKind) -> &'static str {
+    match kind {
+        KubernetesJobKind::Job => "jobs.batch",
+        KubernetesJobKind::PyTorchJob => "pytorchjobs.kubeflow.org",
+    }
+}
+
+/// Training container name; the training operator requires "pytorch"
+fn container_name(kind: KubernetesJobKind) -> &'static str {
+    match kind {
+        KubernetesJobKind::Job => "train",
+        KubernetesJobKind::PyTorchJob => "pytorch",
+    }
+}
+
+/// Job name for a project: `runctl-<project>-<random>`, a valid DNS label
+pub fn job_name(project: &str) -> String {
+    let mut sanitized: String = project
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    sanitized.truncate(40);
+    let sanitized = sanitized.trim_matches('-');
+    let id = &uuid::Uuid::new_v4().simple().to_string()[..8];
+    if sanitized.is_empty() {
+        format!("runctl-{}", id)
+    } else {
+        format!("runctl-{}-{}", sanitized, id)
+    }
+}
+
+/// runctl tags as Kubernetes labels (`runctl:project` -> `runctl/project`)
+///
+/// Label values are limited to 63 alphanumerics, `-`, `_` and `.`.
+pub fn tags_to_labels(tags: &[(String, String)]) -> BTreeMap<String, String> {
+    tags.iter()
+        .map(|(key, value)| {
+            let mut value: String = value
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                        c
+                    } else {
+                        '-'
+                    }
+                })
+                .collect();
+            value.truncate(63);
+            let value = value.trim_matches(|c: char| !c.is_ascii_alphanumeric());
+            (key.replacen(':', "/", 1), value.to_string())
+        })
+        .collect()
+}
+
+/// Command running the script from the unpacked project
+pub fn container_command(script: &Path, args: &[String]) -> Vec<String> {
+    let script = script.to_string_lossy().into_owned();
+    let interpreter = if script.ends_with(".py") {
+        "python3"
+    } else {
+        "bash"
+    };
+    let mut command = vec![interpreter.to_string(), script];
+    command.extend(args.iter().cloned());
+    command
+}
+
+/// Object metadata kubectl returns
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectMeta {
+    pub name: String,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    pub creation_timestamp: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Condition {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub status: String,
+    pub reason: Option<String>,
+    pub message: Option<String>,
+}
+
+/// Status of a Job or PyTorchJob; the fields runctl reads are shared
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct JobStatus {
+    #[serde(default)]
+    pub active: u32,
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+}
+
+impl JobStatus {
+    fn holds(&self, kind: &str) -> Option<&Condition> {
+        self.conditions
+            .iter()
+            .find(|c| c.kind == kind && c.status == "True")
+    }
+}
+
+/// A Job or PyTorchJob
+#[derive(Debug, Clone, Deserialize)]
+pub struct KubeJob {
+    pub metadata: ObjectMeta,
+    #[serde(default)]
+    pub status: JobStatus,
+}
+
+/// A ConfigMap standing for a created resource
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResourceRecord {
+    pub metadata: ObjectMeta,
+    #[serde(default)]
+    pub data: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ResourceRequirements {
+    #[serde(default)]
+    pub requests: BTreeMap<String, String>,
+    #[serde(default)]
+    pub limits: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Container {
+    pub name: String,
+    #[serde(default)]
+    pub resources: ResourceRequirements,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PodSpec {
+    #[serde(default)]
+    pub containers: Vec<Container>,
+    pub node_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContainerStatus {
+    pub name: String,
+    /// One of `waiting`, `running` or `terminated`
+    #[serde(default)]
+    pub state: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PodStatus {
+    pub phase: Option<String>,
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub init_container_statuses: Vec<ContainerStatus>,
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Pod {
+    pub metadata: ObjectMeta,
+    #[serde(default)]
+    pub spec: PodSpec,
+    #[serde(default)]
+    pub status: PodStatus,
+}
+
+impl Pod {
+    pub fn phase(&self) -> &str {
+        self.status.phase.as_deref().unwrap_or("Unknown")
+    }
+
+    /// `nvidia.com/gpu` requested and limited across the pod's containers
+    pub fn gpus(&self) -> (u32, u32) {
+        let sum = |pick: fn(&ResourceRequirements) -> &BTreeMap<String, String>| {
+            self.spec
+                .containers
+                .iter()
+                .filter_map(|c| pick(&c.resources).get("nvidia.com/gpu"))
+                .filter_map(|q| q.parse::<u32>().ok())
+                .sum()
+        };
+        (sum(|r| &r.requests), sum(|r| &r.limits))
+    }
+
+    /// Whether the sync init container is waiting for the code
+    fn awaiting_code(&self) -> bool {
+        self.status
+            .init_container_statuses
+            .iter()
+            .any(|s| s.name == SYNC_CONTAINER && s.state.contains_key("running"))
+    }
+
+    /// Whether the code was already copied in (the init container finished)
+    fn has_code(&self) -> bool {
+        self.status
+            .init_container_statuses
+            .iter()
+            .any(|s| s.name == SYNC_CONTAINER && s.state.contains_key("terminated"))
+    }
+
+    /// Why the pod is stuck, e.g. "Unschedulable: 0/3 nodes are available"
+    fn pending_reason(&self) -> Option<String> {
+        self.status
+            .conditions
+            .iter()
+            .find(|c| c.status == "False" && c.reason.is_some())
+            .map(|c| {
+                format!(
+                    "{}: {}",
+                    c.reason.as_deref().unwrap_or_default(),
+                    c.message.as_deref().unwrap_or_default()
+                )
+            })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct List<T> {
+    items: Vec<T>,
+}
+
+/// Map a Job or PyTorchJob status onto the runctl job model
+pub fn execution_status(status: &JobStatus) -> ExecutionStatus {
+    if status.holds("Complete").is_some() || status.holds("Succeeded").is_some() {
+        return ExecutionStatus::Completed;
+    }
+    if let Some(failed) = status.holds("Failed") {
+        return ExecutionStatus::Failed(
+            failed
+                .message
+                .clone()
+                .or_else(|| failed.reason.clone())
+                .unwrap_or_else(|| "job failed".to_string()),
+        );
+    }
+    if status.holds("Running").is_some() || status.active > 0 {
+        ExecutionStatus::Running
+    } else {
+        ExecutionStatus::Pending
+    }
+}
+
+/// Resource state of a created resource, from its job's status (if any)
+pub fn resource_state(status: Option<&JobStatus>) -> ResourceState {
+    match status.map(execution_status) {
+        // Created, nothing submitted yet
+        None => ResourceState::Stopped,
+        Some(ExecutionStatus::Pending) => ResourceState::Starting,
+        Some(ExecutionStatus::Running) => ResourceState::Running,
+        Some(ExecutionStatus::Completed | ExecutionStatus::Cancelled) => ResourceState::Stopped,
+        Some(ExecutionStatus::Failed(reason)) => ResourceState::Error(reason),
+    }
+}
+
+fn pod_state(pod: &Pod) -> ResourceState {
+    match pod.phase() {
+        "Pending" => ResourceState::Starting,
+        "Running" => ResourceState::Running,
+        "Succeeded" => ResourceState::Stopped,
+        "Failed" => ResourceState::Error(
+            pod.status
+                .reason
+                .clone()
+                .unwrap_or_else(|| "pod failed".to_string()),
+        ),
+        _ => ResourceState::Unknown,
+    }
+}
+
+fn kube_error(message: impl Into<String>) -> TrainctlError {
+    TrainctlError::CloudProvider {
+        provider: "k8s".to_string(),
+        message: message.into(),
+        source: None,
+    }
+}
+
+/// Pod template shared by Job and PyTorchJob replicas
+fn pod_template(
+    name: &str,
+    settings: &KubernetesConfig,
+    image: &str,
+    node_type: Option<&str>,
+    command: &[String],
+    environment: &[(String, String)],
+) -> serde_json::Value {
+    let mut quantities = serde_json::Map::new();
+    if settings.gpus > 0 {
+        quantities.insert(
+            "nvidia.com/gpu".to_string(),
+            settings.gpus.to_string().into(),
+        );
+    }
+    if let Some(cpu) = &settings.cpu {
+        quantities.insert("cpu".to_string(), cpu.clone().into());
+    }
+    if let Some(memory) = &settings.memory {
+        quantities.insert("memory".to_string(), memory.clone().into());
+    }
+    // GPUs can't be overcommitted: the limit has to equal the request
+    let limits: serde_json::Map<_, _> = quantities
+        .iter()
+        .filter(|(key, _)| key.as_str() != "cpu")
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+
+    let mut node_selector = settings.node_selector.clone();
+    if let Some(node_type) = node_type {
+        node_selector.insert(
+            "node.kubernetes.io/instance-type".to_string(),
+            node_type.to_string(),
+        );
+    }
+    let env: Vec<_> = environment
+        .iter()
+        .map(|(name, value)| serde_json::json!({ "name": name, "value": value }))
+        .collect();
+    let volume_mount = serde_json::json!([{ "name": "runctl-code", "mountPath": CODE_DIR }]);
+
+    serde_json::json!({
+        "metadata": {
+            "labels": { MANAGED_LABEL: "true", JOB_LABEL: name }
+        },
+        "spec": {
+            "restartPolicy": "Never",
+            "nodeSelector": node_selector,
+            "volumes": [{ "name": "runctl-code", "emptyDir": {} }],
+            "initContainers": [{
+                "name": SYNC_CONTAINER,
+                "image": SYNC_IMAGE,
+                "command": [
+                    "sh",
+                    "-c",
+                    format!("until [ -f {}/.ready ]; do sleep 1; done", CODE_DIR)
+                ],
+                "volumeMounts": volume_mount,
+            }],
+            "containers": [{
+                "name": container_name(settings.kind),
+                "image": image,
+                "workingDir": CODE_DIR,
+                "command": command,
+                "env": env,
+                "resources": { "requests": quantities, "limits": limits },
+                "volumeMounts": volume_mount,
+            }],
+        }
+    })
+}
+
+/// Job or PyTorchJob manifest for a training run
+pub fn job_manifest(
+    name: &str,
+    settings: &KubernetesConfig,
+    image: &str,
+    node_type: Option<&str>,
+    labels: &BTreeMap<String, String>,
+    command: &[String],
+    environment: &[(String, String)],
+) -> serde_json::Value {
+    let template = pod_template(name, settings, image, node_type, command, environment);
+    let mut labels = labels.clone();
+    labels.insert(MANAGED_LABEL.to_string(), "true".to_string());
+    labels.insert(JOB_LABEL.to_string(), name.to_string());
+    let metadata = serde_json::json!({
+        "name": name,
+        "namespace": settings.namespace,
+        "labels": labels,
+    });
+
+    match settings.kind {
+        KubernetesJobKind::Job => serde_json::json!({
+            "apiVersion": "batch/v1",
+            "kind": "Job",
+            "metadata": metadata,
+            "spec": { "backoffLimit": 0, "template": template },
+        }),
+        KubernetesJobKind::PyTorchJob => {
+            let replica = |replicas: u32| {
+                serde_json::json!({
+                    "replicas": replicas,
+                    "restartPolicy": "Never",
+                    "template": template,
+                })
+            };
+            let mut replicas = serde_json::Map::new();
+            replicas.insert("Master".to_string(), replica(1));
+            if settings.workers > 0 {
+                replicas.insert("Worker".to_string(), replica(settings.workers));
+            }
+            serde_json::json!({
+                "apiVersion": "kubeflow.org/v1",
+                "kind": "PyTorchJob",
+                "metadata": metadata,
+                "spec": {
+                    // Keep finished pods around for their logs
+                    "runPolicy": { "backoffLimit": 0, "cleanPodPolicy": "None" },
+                    "pytorchReplicaSpecs": replicas,
+                },
+            })
+        }
+    }
+}
+
+/// Client for `kubectl`
+#[derive(Debug, Clone)]
+pub struct KubeClient {
+    settings: KubernetesConfig,
+}
+
+impl KubeClient {
+    pub fn new(settings: &KubernetesConfig) -> Self {
+        Self {
+            settings: settings.clone(),
+        }
+    }
+
+    /// Build a client from `[kubernetes]`, checking `kubectl` is installed
+    pub fn from_config(config: &Config) -> Result<Self> {
+        if which::which("kubectl").is_err() {
+            return Err(kube_error(
+                "kubectl not found. Install it from https://kubernetes.io/docs/tasks/tools/",
+            ));
+        }
+        let settings = config.kubernetes.as_ref().ok_or_else(|| {
+            TrainctlError::Config(ConfigError::MissingField("kubernetes".to_string()))
+        })?;
+        Ok(Self::new(settings))
+    }
+
+    pub fn settings(&self) -> &KubernetesConfig {
+        &self.settings
+    }
+
+    fn command(&self, args: &[&str]) -> tokio::process::Command {
+        let mut cmd = tokio::process::Command::new("kubectl");
+        if let Some(path) = &self.settings.kubeconfig {
+            cmd.arg("--kubeconfig").arg(path);
+        }
+        if let Some(context) = &self.settings.context {
+            cmd.args(["--context", context]);
+        }
+        cmd.args(["--namespace", &self.settings.namespace]);
+        cmd.args(args);
+        debug!("kubectl {}", args.join(" "));
+        cmd
+    }
+
+    /// Run `kubectl <args>`, feeding `input` on stdin, and return stdout
+    async fn run_with_input(&self, args: &[&str], input: Option<&str>) -> Result<String> {
+        let mut cmd = self.command(args);
+        cmd.stdin(if input.is_some() {
+            std::process::Stdio::piped()
+        } else {
+            std::process::Stdio::null()
+        })
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped());
+        let mut child = cmd.spawn().map_err(|e| {
+            TrainctlError::Io(std::io::Error::other(format!(
+                "Failed to execute kubectl: {}",
+                e
+            )))
+        })?;
+        if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+            stdin.write_all(input.as_bytes()).await?;
+        }
+        let output = child.wait_with_output().await?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(kube_error(stderr.trim()));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    async fn run(&self, args: &[&str]) -> Result<String> {
+        self.run_with_input(args, None).await
+    }
+
+    async fn query<T: DeserializeOwned>(&self, args: &[&str]) -> Result<T> {
+        let mut args = args.to_vec();
+        args.extend(["-o", "json"]);
+        let stdout = self.run(&args).await?;
+        serde_json::from_str(&stdout)
+            .map_err(|e| kube_error(format!("Unexpected output from kubectl {}: {}", args[0], e)))
+    }
+
+    /// Get one object, `None` if it doesn't exist
+    async fn get<T: DeserializeOwned>(&self, resource: &str, name: &str) -> Result<Option<T>> {
+        match self.query(&["get", resource, name]).await {
+            Err(TrainctlError::CloudProvider { message, .. }) if message.contains("NotFound") => {
+                Ok(None)
+            }
+            result => result.map(Some),
+        }
+    }
+
+    async fn list<T: DeserializeOwned>(&self, resource: &str, selector: &str) -> Result<Vec<T>> {
+        let list: List<T> = self.query(&["get", resource, "-l", selector]).await?;
+        Ok(list.items)
+    }
+
+    /// Create or update an object from its manifest
+    pub async fn apply(&self, manifest: &serde_json::Value) -> Result<()> {
+        let body = serde_json::to_string(manifest)?;
+        self.run_with_input(&["apply", "-f", "-"], Some(&body))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_record(&self, name: &str) -> Result<Option<ResourceRecord>> {
+        self.get("configmaps", name).await
+    }
+
+    pub async fn get_job(&self, name: &str) -> Result<Option<KubeJob>> {
+        self.get(kind_resource(self.settings.kind), name).await
+    }
+
+    pub async fn list_records(&self) -> Result<Vec<ResourceRecord>> {
+        self.list("configmaps", &format!("{}=true", MANAGED_LABEL))
+            .await
+    }
+
+    pub async fn list_jobs(&self) -> Result<Vec<KubeJob>> {
+        self.list(
+            kind_resource(self.settings.kind),
+            &format!("{}=true", MANAGED_LABEL),
+        )
+        .await
+    }
+
+    /// Pods of a job (or of every runctl job), first pod (the master) first
+    pub async fn list_pods(&self, job: Option<&str>) -> Result<Vec<Pod>> {
+        let selector = match job {
+            Some(job) => format!("{}={}", JOB_LABEL, job),
+            None => format!("{}=true", MANAGED_LABEL),
+        };
+        let mut pods: Vec<Pod> = self.list("pods", &selector).await?;
+        pods.sort_by(|a, b| a.metadata.name.cmp(&b.metadata.name));
+        Ok(pods)
+    }
+
+    /// Delete a job and its record; pods go with the job
+    pub async fn delete(&self, name: &str) -> Result<()> {
+        self.run(&[
+            "delete",
+            kind_resource(self.settings.kind),
+            name,
+            "--ignore-not-found",
+        ])
+        .await?;
+        self.run(&["delete", "configmap", name, "--ignore-not-found"])
+            .await?;
+        Ok(())
+    }
+
+    /// Copy the code archive into a waiting pod and release its init container
+    async fn ship_code(&self, pod: &str, archive: &Path) -> Result<()> {
+        let target = format!("{}:{}/code.tar.gz", pod, CODE_DIR);
+        let archive = archive.to_string_lossy();
+        self.run(&["cp", &archive, &target, "-c", SYNC_CONTAINER])
+            .await?;
+        let unpack = format!(
+            "cd {} && tar -xzf code.tar.gz && rm -f code.tar.gz && touch .ready",
+            CODE_DIR
+        );
+        self.run(&["exec", pod, "-c", SYNC_CONTAINER, "--", "sh", "-c", &unpack])
+            .await?;
+        Ok(())
+    }
+
+    /// Copy the project into every pod of `job` as they start
+    ///
+    /// Waits for `expected` pods, so a PyTorchJob's workers get the code too.
+    pub async fn sync_pods(&self, job: &str, expected: usize, archive: &Path) -> Result<()> {
+        let started = Instant::now();
+        let mut shipped = 0;
+        loop {
+            let pods = self.list_pods(Some(job)).await?;
+            for pod in pods.iter().filter(|p| p.awaiting_code()) {
+                crate::progress::emit(
+                    "sync.upload",
+                    format!("Copying code into pod {}", pod.metadata.name),
+                );
+                self.ship_code(&pod.metadata.name, archive).await?;
+                shipped += 1;
+            }
+            let ready = pods.iter().filter(|p| p.has_code()).count() + shipped;
+            if ready >= expected {
+                info!("Copied code into {} pod(s) of {}", shipped, job);
+                return Ok(());
+            }
+            if let Some(pod) = pods.iter().find(|p| p.phase() == "Failed") {
+                return Err(kube_error(format!(
+                    "Pod {} failed before training started",
+                    pod.metadata.name
+                )));
+            }
+            if started.elapsed() >= POD_START_TIMEOUT {
+                let reason = pods
+                    .iter()
+                    .find_map(Pod::pending_reason)
+                    .unwrap_or_else(|| format!("{} of {} pods started", ready, expected));
+                return Err(kube_error(format!(
+                    "Pods of {} did not start within {}: {}",
+                    job,
+                    crate::utils::format_duration(POD_START_TIMEOUT.as_secs()),
+                    reason
+                )));
+            }
+            tokio::time::sleep(POD_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Print (or with `follow`, stream) a pod's training container log
+    pub async fn logs(&self, pod: &str, follow: bool) -> Result<()> {
+        let container = container_name(self.settings.kind);
+        let mut args = vec!["logs", pod, "-c", container];
+        if follow {
+            args.push("--follow");
+        }
+        let status = self.command(&args).status().await.map_err(|e| {
+            TrainctlError::Io(std::io::Error::other(format!(
+                "Failed to execute kubectl: {}",
+                e
+            )))
+        })?;
+        if !status.success() {
+            return Err(kube_error(format!("kubectl logs {} failed", pod)));
+        }
+        Ok(())
+    }
+
+    /// Copy a path out of a pod; the pod has to be running
+    pub async fn copy_from_pod(&self, pod: &str, remote: &Path, local: &Path) -> Result<()> {
+        let source = format!("{}:{}", pod, remote.display());
+        let local = local.to_string_lossy();
+        self.run(&[
+            "cp",
+            &source,
+            &local,
+            "-c",
+            container_name(self.settings.kind),
+        ])
+        .await?;
+        Ok(())
+    }
+}
+
+/// Kubernetes provider implementation
+///
+/// Used by `runctl run --provider k8s`; `resources list --platform k8s`
+/// shows its pods with their GPU requests and limits.
+pub struct KubernetesProvider {
+    config: Config,
+}
+
+impl KubernetesProvider {
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+
+    fn client(&self) -> Result<KubeClient> {
+        KubeClient::from_config(&self.config)
+    }
+
+    /// Pods expected once a job is submitted
+    fn expected_pods(settings: &KubernetesConfig) -> usize {
+        match settings.kind {
+            KubernetesJobKind::Job => 1,
+            KubernetesJobKind::PyTorchJob => 1 + settings.workers as usize,
+        }
+    }
+
+    fn status_of(
+        record: &ResourceRecord,
+        job: Option<&KubeJob>,
+        pod: Option<&Pod>,
+    ) -> ResourceStatus {
+        let name = record.metadata.name.clone();
+        let (state, instance_type) = match pod {
+            Some(pod) => {
+                let (requested, limit) = pod.gpus();
+                (
+                    pod_state(pod),
+                    Some(format!("gpu {} (limit {})", requested, limit)),
+                )
+            }
+            None => (
+                resource_state(job.map(|j| &j.status)),
+                record.data.get("instance-type").cloned(),
+            ),
+        };
+        let mut tags: Vec<(String, String)> = record
+            .metadata
+            .labels
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        if let Some(node) = pod.and_then(|p| p.spec.node_name.clone()) {
+            tags.push(("node".to_string(), node));
+        }
+        ResourceStatus {
+            id: name.clone(),
+            name: Some(pod.map(|p| p.metadata.name.clone()).unwrap_or(name)),
+            state,
+            instance_type,
+            launch_time: record.metadata.creation_timestamp,
+            cost_per_hour: 0.0,
+            public_ip: None,
+            public_ipv6: None,
+            tags,
+        }
+    }
+}
+
+#[async_trait]
+impl TrainingProvider for KubernetesProvider {
+    fn name(&self) -> &'static str {
+        "k8s"
+    }
+
+    async fn create_resource(
+        &self,
+        instance_type: &str, // node instance type, or "any"
+        options: CreateResourceOptions,
+    ) -> Result<ResourceId> {
+        let client = self.client()?;
+        let project = options
+            .tags
+            .iter()
+            .find(|(k, _)| k == "runctl:project")
+            .map(|(_, v)| v.clone())
+            .unwrap_or_else(|| crate::utils::get_project_name(None, &self.config));
+        let name = options
+            .custom
+            .get("name")
+            .cloned()
+            .unwrap_or_else(|| job_name(&project));
+        let mut labels = tags_to_labels(&options.tags);
+        labels.insert(MANAGED_LABEL.to_string(), "true".to_string());
+        let image = options
+            .image
+            .unwrap_or_else(|| client.settings().image.clone());
+        client
+            .apply(&serde_json::json!({
+                "apiVersion": "v1",
+                "kind": "ConfigMap",
+                "metadata": {
+                    "name": name,
+                    "namespace": client.settings().namespace,
+                    "labels": labels,
+                },
+                "data": { "instance-type": instance_type, "image": image },
+            }))
+            .await?;
+        Ok(name)
+    }
+
+    async fn get_resource_status(&self, resource_id: &ResourceId) -> Result<ResourceStatus> {
+        let client = self.client()?;
+        let record = client.get_record(resource_id).await?.ok_or_else(|| {
+            TrainctlError::ResourceNotFound {
+                resource_type: "Kubernetes job".to_string(),
+                resource_id: resource_id.clone(),
+            }
+        })?;
+        let job = client.get_job(resource_id).await?;
+        let pods = client.list_pods(Some(resource_id)).await?;
+        let mut status = Self::status_of(&record, job.as_ref(), None);
+        if let Some(pod) = pods.first() {
+            let (requested, limit) = pod.gpus();
+            status.instance_type = Some(format!("gpu {} (limit {})", requested, limit));
+        }
+        Ok(status)
+    }
+
+    async fn list_resources(&self) -> Result<Vec<ResourceStatus>> {
+        let client = self.client()?;
+        let (records, jobs, pods) = tokio::try_join!(
+            client.list_records(),
+            client.list_jobs(),
+            client.list_pods(None)
+        )?;
+        let mut resources = Vec::new();
+        for record in &records {
+            let name = &record.metadata.name;
+            let job = jobs.iter().find(|j| &j.metadata.name == name);
+            let job_pods: Vec<&Pod> = pods
+                .iter()
+                .filter(|p| p.metadata.labels.get(JOB_LABEL) == Some(name))
+                .collect();
+            if job_pods.is_empty() {
+                resources.push(Self::status_of(record, job, None));
+            }
+            for pod in job_pods {
+                resources.push(Self::status_of(record, job, Some(pod)));
+            }
+        }
+        Ok(resources)
+    }
+
+    async fn train(&self, resource_id: &ResourceId, job: TrainingJob) -> Result<TrainingStatus> {
+        let client = self.client()?;
+        let record = client.get_record(resource_id).await?.ok_or_else(|| {
+            TrainctlError::ResourceNotFound {
+                resource_type: "Kubernetes job".to_string(),
+                resource_id: resource_id.clone(),
+            }
+        })?;
+        let project_root = std::env::current_dir()?.canonicalize()?;
+        let script_abs = job.script.canonicalize().map_err(|_| {
+            TrainctlError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Script not found: {}", job.script.display()),
+            ))
+        })?;
+        let script =
+            script_abs
+                .strip_prefix(&project_root)
+                .map_err(|_| TrainctlError::Validation {
+                    field: "script".to_string(),
+                    reason: format!(
+                        "{} is outside the current directory ({}), which is what gets shipped",
+                        job.script.display(),
+                        project_root.display()
+                    ),
+                })?;
+
+        let filter = SyncFilter::from_config(&self.config.sync)?;
+        let files = filter.select(&project_root)?.files;
+        let archive: PathBuf =
+            std::env::temp_dir().join(format!("runctl-code-{}.tar.gz", uuid::Uuid::new_v4()));
+        if crate::sync_filter::write_code_archive(&project_root, &files, &archive)? == 0 {
+            return Err(kube_error(format!(
+                "No files to ship from {}",
+                project_root.display()
+            )));
+        }
+
+        let settings = client.settings();
+        let image = record
+            .data
+            .get("image")
+            .cloned()
+            .unwrap_or_else(|| settings.image.clone());
+        let node_type = record
+            .data
+            .get("instance-type")
+            .map(String::as_str)
+            .filter(|t| *t != "any");
+        let labels: BTreeMap<String, String> = record
+            .metadata
+            .labels
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        let manifest = job_manifest(
+            resource_id,
+            settings,
+            &image,
+            node_type,
+            &labels,
+            &container_command(script, &job.args),
+            &job.environment,
+        );
+        let result = async {
+            client.apply(&manifest).await?;
+            info!("Submitted {} {}", kind_resource(settings.kind), resource_id);
+            client
+                .sync_pods(resource_id, Self::expected_pods(settings), &archive)
+                .await
+        }
+        .await;
+        if let Err(e) = std::fs::remove_file(&archive) {
+            warn!("Failed to remove {}: {}", archive.display(), e);
+        }
+        result?;
+
+        Ok(TrainingStatus {
+            job_id: Some(resource_id.clone()),
+            status: ExecutionStatus::Running,
+            log_output: None,
+            checkpoint_path: job.checkpoint_dir,
+        })
+    }
+
+    async fn monitor(&self, resource_id: &ResourceId, follow: bool) -> Result<()> {
+        let client = self.client()?;
+        match client.list_pods(Some(resource_id)).await?.first() {
+            Some(pod) => client.logs(&pod.metadata.name, follow).await,
+            None => {
+                println!("Job {} has no pods yet", resource_id);
+                Ok(())
+            }
+        }
+    }
+
+    async fn download(
+        &self,
+        resource_id: &ResourceId,
+        remote_path: &Path,
+        local_path: &Path,
+    ) -> Result<()> {
+        let client = self.client()?;
+        let pods = client.list_pods(Some(resource_id)).await?;
+        let pod = pods
+            .iter()
+            .find(|p| p.phase() == "Running")
+            .ok_or_else(|| {
+                kube_error(format!(
+                    "{} has no running pod to copy from; have the script write results to a volume or bucket",
+                    resource_id
+                ))
+            })?;
+        client
+            .copy_from_pod(&pod.metadata.name, remote_path, local_path)
+            .await
+    }
+
+    async fn terminate(&self, resource_id: &ResourceId) -> Result<()> {
+        self.client()?.delete(resource_id).await
+    }
+
+    fn estimate_cost(&self, _instance_type: &str, _hours: f64) -> f64 {
+        // Cluster capacity isn't billed per job
+        0.0
+    }
+
+    fn default_instance_type(&self) -> Option<String> {
+        Some(
+            self.config
+                .kubernetes
+                .clone()
+                .unwrap_or_default()
+                .default_instance_type,
+        )
+    }
+
+    async fn training_status(&self, resource_id: &ResourceId) -> Result<ExecutionStatus> {
+        let job = self.client()?.get_job(resource_id).await?.ok_or_else(|| {
+            TrainctlError::ResourceNotFound {
+                resource_type: "Kubernetes job".to_string(),
+                resource_id: resource_id.clone(),
+            }
+        })?;
+        Ok(execution_status(&job.status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_names_and_labels() {
+        let name = job_name("My Model.v2");
+        assert!(name.starts_with("runctl-my-model-v2-"));
+        assert_eq!(name.len(), "runctl-my-model-v2-".len() + 8);
+        assert!(job_name("__").starts_with("runctl-"));
+
+        let labels = tags_to_labels(&[
+            ("runctl:project".to_string(), "my model".to_string()),
+            ("runctl:user".to_string(), "a@b.c".to_string()),
+        ]);
+        assert_eq!(labels["runctl/project"], "my-model");
+        assert_eq!(labels["runctl/user"], "a-b.c");
+    }
+
+    #[test]
+    fn test_job_manifest() {
+        let settings = KubernetesConfig {
+            gpus: 2,
+            memory: Some("32Gi".to_string()),
+            ..Default::default()
+        };
+        let command = container_command(Path::new("src/train.py"), &["--epochs".to_string()]);
+        assert_eq!(command, ["python3", "src/train.py", "--epochs"]);
+        let manifest = job_manifest(
+            "runctl-demo-1a2b3c4d",
+            &settings,
+            "pytorch:latest",
+            Some("g5.xlarge"),
+            &BTreeMap::new(),
+            &command,
+            &[("WANDB_MODE".to_string(), "offline".to_string())],
+        );
+        assert_eq!(manifest["kind"], "Job");
+        assert_eq!(manifest["spec"]["backoffLimit"], 0);
+        let pod = &manifest["spec"]["template"];
+        assert_eq!(pod["metadata"]["labels"][JOB_LABEL], "runctl-demo-1a2b3c4d");
+        assert_eq!(
+            pod["spec"]["nodeSelector"]["node.kubernetes.io/instance-type"],
+            "g5.xlarge"
+        );
+        let container = &pod["spec"]["containers"][0];
+        assert_eq!(container["name"], "train");
+        assert_eq!(container["resources"]["requests"]["nvidia.com/gpu"], "2");
+        assert_eq!(container["resources"]["limits"]["nvidia.com/gpu"], "2");
+        assert_eq!(container["resources"]["limits"]["memory"], "32Gi");
+        assert_eq!(container["env"][0]["name"], "WANDB_MODE");
+        assert_eq!(pod["spec"]["initContainers"][0]["name"], SYNC_CONTAINER);
+
+        let pytorch = KubernetesConfig {
+            kind: KubernetesJobKind::PyTorchJob,
+            workers: 3,
+            ..Default::default()
+        };
+        let manifest = job_manifest(
+            "runctl-demo-1a2b3c4d",
+            &pytorch,
+            "pytorch:latest",
+            None,
+            &BTreeMap::new(),
+            &command,
+            &[],
+        );
+        let replicas = &manifest["spec"]["pytorchReplicaSpecs"];
+        assert_eq!(replicas["Master"]["replicas"], 1);
+        assert_eq!(replicas["Worker"]["replicas"], 3);
+        assert_eq!(
+            replicas["Worker"]["template"]["spec"]["containers"][0]["name"],
+            "pytorch"
+        );
+        assert_eq!(KubernetesProvider::expected_pods(&pytorch), 4);
+    }
+
+    #[test]
+    fn test_status_mapping() {
+        let status = |body: &str| -> JobStatus { serde_json::from_str(body).unwrap() };
+        assert_eq!(execution_status(&status("{}")), ExecutionStatus::Pending);
+        assert_eq!(
+            execution_status(&status(r#"{"active":1}"#)),
+            ExecutionStatus::Running
+        );
+        assert_eq!(
+            execution_status(&status(
+                r#"{"conditions":[{"type":"Complete","status":"True"}]}"#
+            )),
+            ExecutionStatus::Completed
+        );
+        // PyTorchJob conditions
+        assert_eq!(
+            execution_status(&status(
+                r#"{"conditions":[{"type":"Created","status":"True"},{"type":"Running","status":"True"}]}"#
+            )),
+            ExecutionStatus::Running
+        );
+        assert_eq!(
+            execution_status(&status(
+                r#"{"conditions":[{"type":"Running","status":"False"},{"type":"Failed","status":"True","reason":"BackoffLimitExceeded","message":"Job has reached the specified backoff limit"}]}"#
+            )),
+            ExecutionStatus::Failed("Job has reached the specified backoff limit".to_string())
+        );
+        assert_eq!(resource_state(None), ResourceState::Stopped);
+    }
+
+    #[test]
+    fn test_pod_gpus_and_sync_state() {
+        let pod: Pod = serde_json::from_str(
+            r#"{"metadata":{"name":"runctl-demo-1a2b3c4d-master-0"},
+                "spec":{"nodeName":"gpu-node-1","containers":[{"name":"pytorch",
+                  "resources":{"requests":{"nvidia.com/gpu":"2","cpu":"8"},"limits":{"nvidia.com/gpu":"2"}}}]},
+                "status":{"phase":"Pending","initContainerStatuses":[
+                  {"name":"runctl-sync","state":{"running":{"startedAt":"2026-10-01T12:00:00Z"}}}]}}"#,
+        )
+        .unwrap();
+        assert_eq!(pod.gpus(), (2, 2));
+        assert!(pod.awaiting_code());
+        assert!(!pod.has_code());
+        assert_eq!(pod_state(&pod), ResourceState::Starting);
+    }
+}
@@ -3,45 +3,68 @@
 //! This module contains provider trait implementations following industry patterns
 //! similar to Terraform's plugin system and Pulumi's component model.
 //!
-//! **Current Status**: Provider trait system is defined but CLI uses direct implementations.
-//! This follows the pragmatic pattern seen in mature tools (Terraform, Pulumi) where
-//! abstraction layers are prepared but not forced until multi-cloud support is needed.
+//! **Current Status**: `ProviderRegistry::from_config` registers every configured
+//! provider. `runctl run --provider <name>`, the job queue scheduler and the
+//! non-AWS sections of `resources list` go through it; platform commands
+//! (`runctl aws`, `runctl runpod`, `runctl azure`) still call their modules
+//! directly. `RunpodClient` (RunPod API) and `AzureClient` (Azure CLI) back both
+//! the providers and those commands; `KubeClient` (kubectl) backs `k8s`.
 //!
 //! **Architecture Decision**: See `docs/PROVIDER_TRAIT_DECISION.md` for rationale.
 //!
 //! ## Provider Registry Pattern
 //!
-//! When multi-cloud support becomes a priority, use `ProviderRegistry` to:
-//! - Register available providers
-//! - Select providers at runtime based on configuration or flags
-//! - Enable gradual migration from direct implementations to trait-based code
+//! `ProviderRegistry` is used to:
+//! - Register the providers the config enables
+//! - Select providers at runtime by name (`--provider`)
+//! - Enumerate providers generically (`resources list`)
 //!
 //! See `src/provider.rs` for the `TrainingProvider` trait definition.
 
+#[cfg(feature = "aws")]
 mod aws_provider;
+mod azure_provider;
+#[cfg(feature = "aws")]
+mod batch_provider;
+mod kubernetes_provider;
 mod lyceum_provider;
+#[cfg(feature = "runpod")]
 mod runpod_provider;
 
 // Re-export providers for external use (e.g., in tests)
-// These are reserved for future multi-cloud support - see PROVIDER_TRAIT_DECISION.md
-#[allow(unused_imports)]
+#[cfg(feature = "aws")]
 pub use aws_provider::AwsProvider;
-#[allow(unused_imports)]
+pub use azure_provider::AzureProvider;
+#[cfg(feature = "aws")]
+pub use batch_provider::{BatchClient, BatchProvider};
+pub use kubernetes_provider::{KubeClient, KubernetesProvider};
 pub use lyceum_provider::LyceumProvider;
-#[allow(unused_imports)]
+#[cfg(feature = "runpod")]
 pub use runpod_provider::RunpodProvider;
+// RunPod API client, used directly by `runctl runpod` and `resources`
+#[cfg(feature = "runpod")]
+pub use runpod_provider::{CreatePodRequest, GpuType, Pod, RunpodClient};
+// Azure CLI client and GPU size pricing, used directly by `runctl azure` and `resources`
+pub use azure_provider::{
+    gpu_vm_size, is_gpu_series, vm_hourly_price, AzureClient, CreateVmRequest, VirtualMachine,
+    VmSize, GPU_VM_SIZES,
+};
 
+use crate::config::Config;
 use crate::error::{Result, TrainctlError};
 use crate::provider::TrainingProvider;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
+/// Every provider runctl knows, whether or not it is configured
+pub const KNOWN_PROVIDERS: &[&str] = &["aws", "azure", "batch", "k8s", "lyceum", "runpod"];
+
 /// Provider registry for managing multiple cloud providers
 ///
 /// Similar to Terraform's plugin registry, this enables dynamic provider discovery
-/// and selection. Currently reserved for future multi-cloud support.
+/// and selection. Providers are kept in name order.
 ///
-/// ## Usage Pattern (Future)
+/// ## Usage Pattern
 ///
 /// ```rust,no_run
 /// use runctl::providers::{ProviderRegistry, AwsProvider, RunpodProvider};
@@ -61,18 +84,54 @@ use std::sync::Arc;
 /// # Ok(())
 /// # }
 /// ```
-#[allow(dead_code)] // Reserved for future multi-cloud support
 pub struct ProviderRegistry {
-    providers: HashMap<String, Arc<dyn TrainingProvider>>,
+    providers: BTreeMap<String, Arc<dyn TrainingProvider>>,
 }
 
-#[allow(dead_code)] // Reserved for future multi-cloud support
 impl ProviderRegistry {
     /// Create a new empty provider registry
     pub fn new() -> Self {
         Self {
-            providers: HashMap::new(),
+            providers: BTreeMap::new(),
+        }
+    }
+
+    /// Register every provider the config enables
+    ///
+    /// - `aws`: an `[aws]` section (needs the `aws` feature)
+    /// - `runpod`: `[runpod] api_key` or `RUNPOD_API_KEY` (needs the `runpod`
+    ///   feature)
+    /// - `azure`: an `[azure]` section
+    /// - `batch`: a `[batch]` section (needs the `aws` feature)
+    /// - `k8s`: a `[kubernetes]` section
+    /// - `lyceum`: `LYCEUM_API_KEY`
+    pub async fn from_config(config: &Config) -> Result<Self> {
+        let mut registry = Self::new();
+        #[cfg(feature = "aws")]
+        if config.aws.is_some() {
+            registry.register("aws", Arc::new(AwsProvider::new(config.clone()).await?))?;
+        }
+        #[cfg(feature = "runpod")]
+        if RunpodClient::from_config(config).is_ok() {
+            registry.register("runpod", Arc::new(RunpodProvider::new(config.clone())))?;
+        }
+        if config.azure.is_some() {
+            registry.register("azure", Arc::new(AzureProvider::new(config.clone())))?;
+        }
+        #[cfg(feature = "aws")]
+        if config.batch.is_some() {
+            registry.register("batch", Arc::new(BatchProvider::new(config.clone()).await?))?;
+        }
+        if config.kubernetes.is_some() {
+            registry.register("k8s", Arc::new(KubernetesProvider::new(config.clone())))?;
         }
+        if let Ok(api_key) = std::env::var("LYCEUM_API_KEY") {
+            registry.register(
+                "lyceum",
+                Arc::new(LyceumProvider::new(config.clone(), Some(api_key))),
+            )?;
+        }
+        Ok(registry)
     }
 
     /// Register a provider with the registry
@@ -116,7 +175,46 @@ impl ProviderRegistry {
             })
     }
 
-    /// List all registered provider names
+    /// Like `get`, but explains whether the provider is unknown or unconfigured
+    pub fn select(&self, name: &str) -> Result<Arc<dyn TrainingProvider>> {
+        if let Ok(provider) = self.get(name) {
+            return Ok(provider);
+        }
+        #[cfg(not(feature = "runpod"))]
+        if name == "runpod" {
+            return Err(crate::error::ConfigError::FeatureNotCompiled(name.to_string()).into());
+        }
+        #[cfg(not(feature = "aws"))]
+        if name == "aws" || name == "batch" {
+            return Err(crate::error::ConfigError::FeatureNotCompiled("aws".to_string()).into());
+        }
+        let reason = if KNOWN_PROVIDERS.contains(&name) {
+            format!(
+                "'{}' is not configured (configured: {})",
+                name,
+                self.list().join(", ")
+            )
+        } else {
+            format!(
+                "unknown provider '{}' (expected one of {})",
+                name,
+                KNOWN_PROVIDERS.join(", ")
+            )
+        };
+        Err(TrainctlError::Validation {
+            field: "provider".to_string(),
+            reason,
+        })
+    }
+
+    /// Registered providers in name order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Arc<dyn TrainingProvider>)> {
+        self.providers
+            .iter()
+            .map(|(name, provider)| (name.as_str(), provider))
+    }
+
+    /// List all registered provider names, sorted
     pub fn list(&self) -> Vec<String> {
         self.providers.keys().cloned().collect()
     }
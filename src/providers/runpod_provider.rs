@@ -1,31 +1,415 @@
 //! RunPod provider implementation
 //!
-//! **Status**: Skeleton implementation - not yet used by CLI.
+//! `RunpodClient` talks to the RunPod GraphQL API directly (create, list,
+//! stop and terminate pods, GPU types and pricing), authenticated with
+//! `[runpod] api_key` or `RUNPOD_API_KEY`. `runctl runpod` and
+//! `resources list` use it instead of parsing `runpodctl` output.
 //!
-//! The CLI currently uses direct implementations. This provider is kept for future
-//! migration to a fully provider-agnostic architecture (see `docs/PROVIDER_TRAIT_DECISION.md`).
+//! The API has no command execution or file transfer, so training, log
+//! tailing and downloads still go through `runpodctl exec/send/receive`
+//! (see `crate::runpod`).
 
 use crate::config::Config;
-use crate::error::{Result, TrainctlError};
+use crate::error::{ConfigError, Result, TrainctlError};
 use crate::provider::*;
 use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::path::Path;
+use std::time::Duration;
+
+/// RunPod GraphQL endpoint
+const GRAPHQL_URL: &str = "https://api.runpod.io/graphql";
+
+/// Fields fetched for every pod
+const POD_FIELDS: &str = "id name desiredStatus imageName costPerHr gpuCount \
+    machine { gpuDisplayName } runtime { uptimeInSeconds }";
+
+/// A RunPod pod as returned by the API
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pod {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    /// RUNNING, EXITED or TERMINATED
+    #[serde(default)]
+    pub desired_status: String,
+    pub image_name: Option<String>,
+    pub cost_per_hr: Option<f64>,
+    pub gpu_count: Option<u32>,
+    pub machine: Option<PodMachine>,
+    /// Present once the container has started
+    pub runtime: Option<PodRuntime>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PodMachine {
+    pub gpu_display_name: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PodRuntime {
+    pub uptime_in_seconds: Option<u64>,
+}
+
+impl Pod {
+    /// Whether the pod is meant to be running (it may still be starting)
+    pub fn is_running(&self) -> bool {
+        self.desired_status.eq_ignore_ascii_case("running")
+    }
+
+    /// Whether the pod is running and its container has started
+    pub fn is_ready(&self) -> bool {
+        self.is_running() && self.runtime.is_some()
+    }
+
+    /// GPU display name, if known
+    pub fn gpu(&self) -> &str {
+        self.machine
+            .as_ref()
+            .and_then(|m| m.gpu_display_name.as_deref())
+            .unwrap_or("")
+    }
+
+    /// Status for display: "starting" until the container is up
+    pub fn status(&self) -> String {
+        if self.is_running() && self.runtime.is_none() {
+            "starting".to_string()
+        } else {
+            self.desired_status.to_lowercase()
+        }
+    }
+
+    fn to_resource_status(&self) -> ResourceStatus {
+        ResourceStatus {
+            id: self.id.clone(),
+            name: Some(self.name.clone()).filter(|n| !n.is_empty()),
+            state: normalize_state(&self.status()),
+            instance_type: Some(self.gpu().to_string()).filter(|g| !g.is_empty()),
+            launch_time: self
+                .runtime
+                .as_ref()
+                .and_then(|r| r.uptime_in_seconds)
+                .map(|secs| chrono::Utc::now() - chrono::Duration::seconds(secs as i64)),
+            cost_per_hour: self.cost_per_hr.unwrap_or(0.0),
+            public_ip: None,
+            public_ipv6: None,
+            tags: Vec::new(),
+        }
+    }
+}
+
+/// A GPU type with its hourly prices
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GpuType {
+    /// API identifier, e.g. "NVIDIA GeForce RTX 4090"
+    pub id: String,
+    pub display_name: String,
+    pub memory_in_gb: Option<u32>,
+    pub secure_price: Option<f64>,
+    pub community_price: Option<f64>,
+}
+
+impl GpuType {
+    /// Whether `name` refers to this GPU (id or display name, any case)
+    pub fn matches(&self, name: &str) -> bool {
+        self.id.eq_ignore_ascii_case(name) || self.display_name.eq_ignore_ascii_case(name)
+    }
+
+    /// Cheapest available hourly price
+    pub fn lowest_price(&self) -> Option<f64> {
+        [self.secure_price, self.community_price]
+            .into_iter()
+            .flatten()
+            .filter(|p| *p > 0.0)
+            .reduce(f64::min)
+    }
+}
+
+/// Pod to create
+#[derive(Debug, Clone)]
+pub struct CreatePodRequest {
+    pub name: String,
+    pub image: String,
+    /// GPU id or display name (resolved against `gpu_types`)
+    pub gpu: String,
+    pub gpu_count: u32,
+    pub container_disk_gb: u32,
+    pub min_memory_gb: u32,
+}
+
+#[derive(Deserialize)]
+struct GraphqlResponse<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<GraphqlError>,
+}
+
+#[derive(Deserialize)]
+struct GraphqlError {
+    message: String,
+}
+
+/// Client for the RunPod GraphQL API
+#[derive(Clone)]
+pub struct RunpodClient {
+    http: reqwest::Client,
+    api_key: String,
+    endpoint: String,
+}
+
+impl std::fmt::Debug for RunpodClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RunpodClient")
+            .field("endpoint", &self.endpoint)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RunpodClient {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            api_key: api_key.into(),
+            endpoint: GRAPHQL_URL.to_string(),
+        }
+    }
+
+    /// Build a client from `[runpod] api_key`, falling back to `RUNPOD_API_KEY`
+    ///
+    /// Either may be a `secret://NAME` reference (see `secrets`).
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let api_key = config
+            .runpod
+            .as_ref()
+            .and_then(|r| r.api_key.clone())
+            .or_else(|| std::env::var("RUNPOD_API_KEY").ok())
+            .filter(|key| !key.trim().is_empty())
+            .ok_or_else(|| {
+                TrainctlError::Config(ConfigError::MissingField(
+                    "runpod.api_key (or RUNPOD_API_KEY)".to_string(),
+                ))
+            })?;
+        Ok(Self::new(crate::secrets::resolve(&api_key, config)?))
+    }
+
+    async fn query<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<T> {
+        let response = self
+            .http
+            .post(&self.endpoint)
+            .bearer_auth(&self.api_key)
+            .json(&json!({ "query": query, "variables": variables }))
+            .send()
+            .await
+            .map_err(|e| api_error(format!("Request failed: {}", e)))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+            return Err(TrainctlError::Config(ConfigError::InvalidValue {
+                field: "runpod.api_key".to_string(),
+                reason: format!("rejected by the RunPod API ({})", status),
+            }));
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(api_error(format!("HTTP {}: {}", status, body.trim())));
+        }
+
+        let body: GraphqlResponse<T> = response
+            .json()
+            .await
+            .map_err(|e| api_error(format!("Invalid response: {}", e)))?;
+        if !body.errors.is_empty() {
+            let messages: Vec<_> = body.errors.into_iter().map(|e| e.message).collect();
+            return Err(api_error(messages.join("; ")));
+        }
+        body.data
+            .ok_or_else(|| api_error("Response contained no data".to_string()))
+    }
+
+    /// List all pods on the account
+    pub async fn list_pods(&self) -> Result<Vec<Pod>> {
+        #[derive(Deserialize)]
+        struct Data {
+            myself: Myself,
+        }
+        #[derive(Deserialize)]
+        struct Myself {
+            #[serde(default)]
+            pods: Vec<Pod>,
+        }
+        let query = format!("query {{ myself {{ pods {{ {} }} }} }}", POD_FIELDS);
+        let data: Data = self.query(&query, json!({})).await?;
+        Ok(data.myself.pods)
+    }
+
+    /// Look up a single pod
+    pub async fn get_pod(&self, pod_id: &str) -> Result<Pod> {
+        #[derive(Deserialize)]
+        struct Data {
+            pod: Option<Pod>,
+        }
+        let query = format!(
+            "query Pod($input: PodFilter) {{ pod(input: $input) {{ {} }} }}",
+            POD_FIELDS
+        );
+        let data: Data = self
+            .query(&query, json!({ "input": { "podId": pod_id } }))
+            .await?;
+        data.pod.ok_or_else(|| TrainctlError::ResourceNotFound {
+            resource_type: "pod".to_string(),
+            resource_id: pod_id.to_string(),
+        })
+    }
+
+    /// GPU types with current on-demand prices
+    pub async fn gpu_types(&self) -> Result<Vec<GpuType>> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Data {
+            gpu_types: Vec<GpuType>,
+        }
+        let query = "query { gpuTypes { id displayName memoryInGb securePrice communityPrice } }";
+        let data: Data = self.query(query, json!({})).await?;
+        Ok(data.gpu_types)
+    }
+
+    /// Resolve a GPU id or display name to its API id
+    pub async fn resolve_gpu(&self, name: &str) -> Result<GpuType> {
+        let types = self.gpu_types().await?;
+        types
+            .iter()
+            .find(|t| t.matches(name))
+            .cloned()
+            .ok_or_else(|| TrainctlError::Validation {
+                field: "gpu".to_string(),
+                reason: format!(
+                    "Unknown RunPod GPU type '{}'. See `runctl runpod gpus` for available types",
+                    name
+                ),
+            })
+    }
+
+    /// Create an on-demand pod
+    pub async fn create_pod(&self, request: &CreatePodRequest) -> Result<Pod> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Data {
+            pod_find_and_deploy_on_demand: Option<Pod>,
+        }
+        let gpu = self.resolve_gpu(&request.gpu).await?;
+        let query = format!(
+            "mutation Deploy($input: PodFindAndDeployOnDemandInput) {{ \
+             podFindAndDeployOnDemand(input: $input) {{ {} }} }}",
+            POD_FIELDS
+        );
+        let input = json!({
+            "name": request.name,
+            "imageName": request.image,
+            "gpuTypeId": gpu.id,
+            "gpuCount": request.gpu_count,
+            "cloudType": "ALL",
+            "containerDiskInGb": request.container_disk_gb,
+            "volumeInGb": 0,
+            "minMemoryInGb": request.min_memory_gb,
+            "volumeMountPath": "/workspace",
+        });
+        let data: Data = self.query(&query, json!({ "input": input })).await?;
+        data.pod_find_and_deploy_on_demand
+            .ok_or_else(|| TrainctlError::CloudProvider {
+                provider: "runpod".to_string(),
+                message: format!(
+                    "No {} capacity available right now; try another GPU type",
+                    gpu.display_name
+                ),
+                source: None,
+            })
+    }
+
+    /// Stop a pod (the container disk is discarded, volumes are kept)
+    pub async fn stop_pod(&self, pod_id: &str) -> Result<()> {
+        let query = "mutation Stop($input: PodStopInput!) { podStop(input: $input) { id } }";
+        let _: serde_json::Value = self
+            .query(query, json!({ "input": { "podId": pod_id } }))
+            .await?;
+        Ok(())
+    }
+
+    /// Terminate (remove) a pod
+    pub async fn terminate_pod(&self, pod_id: &str) -> Result<()> {
+        let query =
+            "mutation Terminate($input: PodTerminateInput!) { podTerminate(input: $input) }";
+        let _: serde_json::Value = self
+            .query(query, json!({ "input": { "podId": pod_id } }))
+            .await?;
+        Ok(())
+    }
+
+    /// Poll until the pod's container is up
+    pub async fn wait_until_ready(&self, pod_id: &str, timeout: Duration) -> Result<Pod> {
+        let started = std::time::Instant::now();
+        loop {
+            let pod = self.get_pod(pod_id).await?;
+            if pod.is_ready() {
+                return Ok(pod);
+            }
+            if !pod.is_running() {
+                return Err(TrainctlError::Resource {
+                    resource_type: "pod".to_string(),
+                    operation: "wait_until_ready".to_string(),
+                    resource_id: Some(pod_id.to_string()),
+                    message: format!("Pod is {} instead of starting", pod.status()),
+                    source: None,
+                });
+            }
+            if started.elapsed() >= timeout {
+                return Err(TrainctlError::Resource {
+                    resource_type: "pod".to_string(),
+                    operation: "wait_until_ready".to_string(),
+                    resource_id: Some(pod_id.to_string()),
+                    message: format!(
+                        "Pod not ready after {}s; check it with `runctl runpod list`",
+                        timeout.as_secs()
+                    ),
+                    source: None,
+                });
+            }
+            crate::progress::emit("create.starting", format!("{} is starting", pod_id));
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        }
+    }
+}
+
+fn api_error(message: String) -> TrainctlError {
+    TrainctlError::CloudProvider {
+        provider: "runpod".to_string(),
+        message,
+        source: None,
+    }
+}
 
 /// RunPod provider implementation
-///
-/// Currently a stub - not yet implemented.
-/// Kept for future RunPod integration.
-#[allow(dead_code)]
 pub struct RunpodProvider {
-    #[allow(dead_code)]
     config: Config,
 }
 
 impl RunpodProvider {
-    #[allow(dead_code)] // Reserved for future provider initialization
     pub fn new(config: Config) -> Self {
         Self { config }
     }
+
+    fn client(&self) -> Result<RunpodClient> {
+        RunpodClient::from_config(&self.config)
+    }
 }
 
 #[async_trait]
@@ -36,79 +420,99 @@ impl TrainingProvider for RunpodProvider {
 
     async fn create_resource(
         &self,
-        _instance_type: &str, // GPU type for RunPod
-        _options: CreateResourceOptions,
+        instance_type: &str, // GPU type for RunPod
+        options: CreateResourceOptions,
     ) -> Result<ResourceId> {
-        // Implementation would use runpodctl
-        Err(TrainctlError::CloudProvider {
-            provider: "runpod".to_string(),
-            message: "RunPod resource creation not yet fully implemented in provider trait"
-                .to_string(),
-            source: None,
-        })
+        let defaults = self.config.runpod.as_ref();
+        let image = options
+            .image
+            .or_else(|| defaults.map(|r| r.default_image.clone()))
+            .ok_or_else(|| {
+                TrainctlError::Config(ConfigError::MissingField("runpod".to_string()))
+            })?;
+        let request = CreatePodRequest {
+            name: format!("runctl-{}", &uuid::Uuid::new_v4().to_string()[..8]),
+            image,
+            gpu: instance_type.to_string(),
+            gpu_count: 1,
+            container_disk_gb: options
+                .disk_gb
+                .or_else(|| defaults.map(|r| r.default_disk_gb))
+                .unwrap_or(30),
+            min_memory_gb: options.memory_gb.unwrap_or(32),
+        };
+        // Training needs the container up, so wait like `runpod create` does
+        let client = self.client()?;
+        let pod = client.create_pod(&request).await?;
+        client
+            .wait_until_ready(
+                &pod.id,
+                Duration::from_secs(crate::runpod::POD_READY_TIMEOUT_SECS),
+            )
+            .await?;
+        Ok(pod.id)
     }
 
-    async fn get_resource_status(&self, _resource_id: &ResourceId) -> Result<ResourceStatus> {
-        // Implementation would parse runpodctl output
-        Err(TrainctlError::CloudProvider {
-            provider: "runpod".to_string(),
-            message: "RunPod status not yet fully implemented in provider trait".to_string(),
-            source: None,
-        })
+    async fn get_resource_status(&self, resource_id: &ResourceId) -> Result<ResourceStatus> {
+        Ok(self
+            .client()?
+            .get_pod(resource_id)
+            .await?
+            .to_resource_status())
     }
 
     async fn list_resources(&self) -> Result<Vec<ResourceStatus>> {
-        // Implementation would parse runpodctl get pod output
-        Err(TrainctlError::CloudProvider {
-            provider: "runpod".to_string(),
-            message: "List resources not yet fully implemented in provider trait".to_string(),
-            source: None,
-        })
+        Ok(self
+            .client()?
+            .list_pods()
+            .await?
+            .iter()
+            .map(Pod::to_resource_status)
+            .collect())
     }
 
-    async fn train(&self, _resource_id: &ResourceId, _job: TrainingJob) -> Result<TrainingStatus> {
-        // Implementation would use runpodctl exec
-        Err(TrainctlError::CloudProvider {
-            provider: "runpod".to_string(),
-            message: "Training not yet fully implemented in provider trait".to_string(),
-            source: None,
+    async fn train(&self, resource_id: &ResourceId, job: TrainingJob) -> Result<TrainingStatus> {
+        crate::runpod::train_on_pod(
+            resource_id.clone(),
+            job.script,
+            true,
+            &job.args,
+            &job.environment,
+            &self.config,
+        )
+        .await?;
+        Ok(TrainingStatus {
+            job_id: Some(resource_id.clone()),
+            status: ExecutionStatus::Running,
+            log_output: None,
+            checkpoint_path: job.checkpoint_dir,
         })
     }
 
-    async fn monitor(&self, _resource_id: &ResourceId, _follow: bool) -> Result<()> {
-        // Implementation would use runpodctl logs
-        Err(TrainctlError::CloudProvider {
-            provider: "runpod".to_string(),
-            message: "Monitoring not yet fully implemented in provider trait".to_string(),
-            source: None,
-        })
+    async fn monitor(&self, resource_id: &ResourceId, follow: bool) -> Result<()> {
+        crate::runpod::monitor_pod(resource_id.clone(), follow).await
     }
 
     async fn download(
         &self,
-        _resource_id: &ResourceId,
-        _remote_path: &Path,
-        _local_path: &Path,
+        resource_id: &ResourceId,
+        remote_path: &Path,
+        local_path: &Path,
     ) -> Result<()> {
-        // Implementation would use runpodctl download
-        Err(TrainctlError::CloudProvider {
-            provider: "runpod".to_string(),
-            message: "Download not yet fully implemented in provider trait".to_string(),
-            source: None,
-        })
+        crate::runpod::download_from_pod(
+            resource_id.clone(),
+            remote_path.to_path_buf(),
+            local_path.to_path_buf(),
+        )
+        .await
     }
 
-    async fn terminate(&self, _resource_id: &ResourceId) -> Result<()> {
-        // Implementation would use runpodctl remove pod
-        Err(TrainctlError::CloudProvider {
-            provider: "runpod".to_string(),
-            message: "Terminate not yet fully implemented in provider trait".to_string(),
-            source: None,
-        })
+    async fn terminate(&self, resource_id: &ResourceId) -> Result<()> {
+        self.client()?.terminate_pod(resource_id).await
     }
 
     fn estimate_cost(&self, instance_type: &str, hours: f64) -> f64 {
-        // RunPod pricing (simplified - would use actual pricing API)
+        // Offline fallback; `RunpodClient::gpu_types` has live prices
         let cost_per_hour = match instance_type {
             "RTX 4080" | "RTX 4080 SUPER" => 0.79,
             "RTX 4090" => 1.39,
@@ -117,4 +521,87 @@ impl TrainingProvider for RunpodProvider {
         };
         cost_per_hour * hours
     }
+
+    fn default_instance_type(&self) -> Option<String> {
+        self.config.runpod.as_ref().map(|r| r.default_gpu.clone())
+    }
+
+    async fn training_status(&self, resource_id: &ResourceId) -> Result<ExecutionStatus> {
+        let pod = self.client()?.get_pod(resource_id).await?;
+        if !pod.is_running() {
+            return Ok(ExecutionStatus::Failed(format!("pod is {}", pod.status())));
+        }
+        let pod_id = resource_id.clone();
+        let code = tokio::task::spawn_blocking(move || crate::runpod::training_exit_code(&pod_id))
+            .await
+            .map_err(|e| TrainctlError::Io(std::io::Error::other(e)))??;
+        Ok(status_from_exit_code(code))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pod_from_api() {
+        let body = r#"{"data":{"myself":{"pods":[
+            {"id":"abc123def456","name":"runctl-1","desiredStatus":"RUNNING","imageName":"runpod/pytorch",
+             "costPerHr":0.69,"gpuCount":1,"machine":{"gpuDisplayName":"RTX 4090"},"runtime":null},
+            {"id":"xyz789uvw012","name":"old","desiredStatus":"EXITED","imageName":null,
+             "costPerHr":0.0,"gpuCount":1,"machine":null,"runtime":null}
+        ]}}}"#;
+        #[derive(Deserialize)]
+        struct Data {
+            myself: Myself,
+        }
+        #[derive(Deserialize)]
+        struct Myself {
+            pods: Vec<Pod>,
+        }
+        let response: GraphqlResponse<Data> = serde_json::from_str(body).unwrap();
+        let pods = response.data.unwrap().myself.pods;
+        assert_eq!(pods.len(), 2);
+        assert_eq!(pods[0].gpu(), "RTX 4090");
+        assert_eq!(pods[0].status(), "starting");
+        assert!(pods[0].is_running() && !pods[0].is_ready());
+        assert_eq!(pods[0].to_resource_status().state, ResourceState::Starting);
+        assert_eq!(pods[1].status(), "exited");
+        assert_eq!(pods[1].gpu(), "");
+    }
+
+    #[test]
+    fn test_graphql_errors() {
+        let body =
+            r#"{"data":null,"errors":[{"message":"There are no longer any instances available"}]}"#;
+        let response: GraphqlResponse<serde_json::Value> = serde_json::from_str(body).unwrap();
+        assert!(response.data.is_none());
+        assert_eq!(response.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_gpu_type_matching_and_price() {
+        let gpu = GpuType {
+            id: "NVIDIA GeForce RTX 4090".to_string(),
+            display_name: "RTX 4090".to_string(),
+            memory_in_gb: Some(24),
+            secure_price: Some(0.69),
+            community_price: Some(0.44),
+        };
+        assert!(gpu.matches("rtx 4090"));
+        assert!(gpu.matches("NVIDIA GeForce RTX 4090"));
+        assert!(!gpu.matches("RTX 4080"));
+        assert_eq!(gpu.lowest_price(), Some(0.44));
+        let unavailable = GpuType {
+            community_price: Some(0.0),
+            ..gpu.clone()
+        };
+        assert_eq!(unavailable.lowest_price(), Some(0.69));
+        let unpriced = GpuType {
+            community_price: Some(0.0),
+            secure_price: None,
+            ..gpu
+        };
+        assert_eq!(unpriced.lowest_price(), None);
+    }
 }
@@ -0,0 +1,662 @@
+//! Persistent training job queue
+//!
+//! `runctl queue submit train.py --target aws:g5.xlarge` records a job and
+//! returns; `runctl queue run` is the scheduler that works through queued jobs
+//! in submission order, at most `--max-concurrent` at a time. For each job it
+//! provisions an instance through the provider layer (`TrainingProvider`),
+//! starts training with code sync, polls the job the same way `runctl jobs
+//! wait` does, and terminates the instance when the job finishes. The
+//! scheduler exits once nothing is queued or running.
+//!
+//! With `--fleet`, jobs run on idle workers of an `aws fleet` Auto Scaling
+//! group instead: a worker is claimed (protected from scale-in) for the job
+//! and released, not terminated, when it finishes.
+//!
+//! Each job is stored in `.runctl/queue/<id>.json`, so `submit` and `cancel`
+//! can run while a scheduler is working the queue without overwriting its
+//! updates. A scheduler that is restarted picks up jobs left running.
+//!
+//! `--after` and `--after-any` chain jobs: a job waits until the jobs it
+//! names have completed (or, with `--after-any`, finished in any state), so
+//! a preprocess, train, eval chain can be submitted up front. A job whose
+//! `--after` dependency fails or is cancelled fails too.
+//!
+//! Only AWS targets are scheduled for now: job outcomes come from the same
+//! status source as `runctl watch`, which does not cover RunPod pods.
+
+mod scheduler;
+
+use crate::config::Config;
+use crate::error::{Result, TrainctlError};
+use crate::jobs::{MigrationTarget, TargetProvider};
+use crate::provider::TrainingProvider;
+use crate::providers::AwsProvider;
+use chrono::{DateTime, Utc};
+use clap::Subcommand;
+use comfy_table::Table;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Subcommand, Clone)]
+pub enum QueueCommands {
+    /// Add a training job to the queue
+    ///
+    /// Examples:
+    ///   runctl queue submit train.py --target aws:g5.xlarge
+    ///   runctl queue submit train.py --target aws:g5.xlarge --spot -- --lr 0.001
+    ///   runctl queue submit train.py --timeout 6h
+    ///   runctl queue submit eval.py --after q-1a2b3c4d
+    Submit {
+        /// Training script, synced with the project code
+        #[arg(value_name = "SCRIPT")]
+        script: PathBuf,
+
+        /// Where to run as PROVIDER:TYPE (default: aws:[aws] default_instance_type)
+        #[arg(long, value_name = "PROVIDER:TYPE")]
+        target: Option<String>,
+
+        /// Use a spot instance
+        #[arg(long)]
+        spot: bool,
+
+        /// Fail the job (and terminate its instance) after this long, e.g. 6h
+        #[arg(long, value_name = "DURATION", value_parser = crate::units::parse_secs)]
+        timeout: Option<u64>,
+
+        /// Start only after this job completes successfully (repeatable)
+        #[arg(long, value_name = "JOB_ID")]
+        after: Vec<String>,
+
+        /// Start only after this job finishes, whatever its outcome (repeatable)
+        #[arg(long, value_name = "JOB_ID")]
+        after_any: Vec<String>,
+
+        /// Arguments passed to the script (after '--')
+        #[arg(last = true, value_name = "ARGS")]
+        args: Vec<String>,
+    },
+    /// List jobs in the queue
+    List,
+    /// Cancel a job
+    ///
+    /// A running job's instance is terminated.
+    Cancel {
+        /// Job ID (or a unique prefix)
+        #[arg(value_name = "JOB_ID")]
+        job_id: String,
+    },
+    /// Queue a failed or cancelled job again
+    Retry {
+        /// Job ID (or a unique prefix)
+        #[arg(value_name = "JOB_ID")]
+        job_id: String,
+    },
+    /// Run queued jobs until the queue drains
+    ///
+    /// Provisions an instance per job, trains, and terminates the instance
+    /// when the job completes, fails or times out. With --fleet, jobs run on
+    /// idle workers of an `aws fleet` instead, which stay up afterwards.
+    ///
+    /// Examples:
+    ///   runctl queue run
+    ///   runctl queue run --max-concurrent 3 --interval 1m
+    ///   runctl queue run --fleet workers
+    Run {
+        /// Jobs to run at the same time (default: 1, or one per idle
+        /// worker with --fleet)
+        #[arg(long, value_name = "COUNT")]
+        max_concurrent: Option<usize>,
+
+        /// Run jobs on this fleet's workers (see `runctl aws fleet`)
+        ///
+        /// Job targets are ignored: every job runs on the fleet's instance
+        /// type. The scheduler waits for a worker while all are busy.
+        #[arg(long, value_name = "NAME")]
+        fleet: Option<String>,
+
+        /// Time between status checks
+        #[arg(
+            long,
+            value_name = "DURATION",
+            default_value = "30",
+            value_parser = crate::units::parse_secs
+        )]
+        interval: u64,
+    },
+}
+
+/// Where a queued job is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueStatus {
+    Queued,
+    Provisioning,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl QueueStatus {
+    /// Whether the job has reached a final state
+    pub fn is_finished(self) -> bool {
+        matches!(
+            self,
+            QueueStatus::Completed | QueueStatus::Failed | QueueStatus::Cancelled
+        )
+    }
+}
+
+impl fmt::Display for QueueStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            QueueStatus::Queued => "queued",
+            QueueStatus::Provisioning => "provisioning",
+            QueueStatus::Running => "running",
+            QueueStatus::Completed => "completed",
+            QueueStatus::Failed => "failed",
+            QueueStatus::Cancelled => "cancelled",
+        })
+    }
+}
+
+/// A job in the queue, persisted after every state change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedJob {
+    pub id: String,
+    pub script: PathBuf,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub target: MigrationTarget,
+    #[serde(default)]
+    pub spot: bool,
+    /// Seconds the job may run before it is failed
+    pub timeout_secs: Option<u64>,
+    /// Jobs that must complete successfully before this one starts
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Jobs that must finish, in any state, before this one starts
+    #[serde(default)]
+    pub after_any: Vec<String>,
+    pub status: QueueStatus,
+    /// Instance created (or fleet worker claimed) for the current attempt
+    pub resource_id: Option<String>,
+    /// Fleet whose worker runs the current attempt; the worker is released
+    /// rather than terminated when the job finishes
+    #[serde(default)]
+    pub fleet: Option<String>,
+    /// Times the job has been started
+    #[serde(default)]
+    pub attempts: u32,
+    pub error: Option<String>,
+    pub submitted_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl QueuedJob {
+    pub fn new(script: PathBuf, args: Vec<String>, target: MigrationTarget) -> Self {
+        Self {
+            id: format!("q-{}", &uuid::Uuid::new_v4().simple().to_string()[..8]),
+            script,
+            args,
+            target,
+            spot: false,
+            timeout_secs: None,
+            after: Vec::new(),
+            after_any: Vec::new(),
+            status: QueueStatus::Queued,
+            resource_id: None,
+            fleet: None,
+            attempts: 0,
+            error: None,
+            submitted_at: Utc::now(),
+            started_at: None,
+            finished_at: None,
+        }
+    }
+
+    fn path(dir: &Path, id: &str) -> PathBuf {
+        dir.join(format!("{}.json", id))
+    }
+
+    /// Write the job to `dir`, replacing the file atomically
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        crate::utils::ensure_dir(dir)?;
+        let path = Self::path(dir, &self.id);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// Mark the job finished
+    fn finish(&mut self, status: QueueStatus, error: Option<String>) {
+        self.status = status;
+        self.error = error;
+        self.finished_at = Some(Utc::now());
+    }
+
+    /// Put a failed or cancelled job back in the queue
+    pub fn requeue(&mut self) -> Result<()> {
+        if !matches!(self.status, QueueStatus::Failed | QueueStatus::Cancelled) {
+            return Err(TrainctlError::Validation {
+                field: "job_id".to_string(),
+                reason: format!(
+                    "{} is {}; only failed or cancelled jobs can be retried",
+                    self.id, self.status
+                ),
+            });
+        }
+        self.status = QueueStatus::Queued;
+        self.resource_id = None;
+        self.fleet = None;
+        self.error = None;
+        self.started_at = None;
+        self.finished_at = None;
+        Ok(())
+    }
+
+    /// Whether this job can start, given the other jobs in the queue
+    pub fn dependencies(&self, jobs: &[QueuedJob]) -> Dependencies {
+        let status_of = |id: &String| jobs.iter().find(|job| &job.id == id).map(|j| j.status);
+        for id in &self.after {
+            match status_of(id) {
+                Some(QueueStatus::Completed) => {}
+                Some(status) if status.is_finished() => {
+                    return Dependencies::Broken(format!("dependency {} {}", id, status))
+                }
+                Some(_) => return Dependencies::Waiting,
+                None => return Dependencies::Broken(format!("dependency {} not found", id)),
+            }
+        }
+        for id in &self.after_any {
+            match status_of(id) {
+                Some(status) if status.is_finished() => {}
+                Some(_) => return Dependencies::Waiting,
+                // Removed from the queue, so it is not going to run again
+                None => {}
+            }
+        }
+        Dependencies::Ready
+    }
+
+    /// `--after` and `--after-any` jobs for display
+    fn dependency_list(&self) -> String {
+        self.after
+            .iter()
+            .cloned()
+            .chain(self.after_any.iter().map(|id| format!("{} (any)", id)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Whether a queued job's `--after`/`--after-any` jobs let it start
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Dependencies {
+    Ready,
+    /// A dependency hasn't finished yet
+    Waiting,
+    /// An `--after` dependency failed, was cancelled or is gone; the job can
+    /// never start
+    Broken(String),
+}
+
+pub fn queue_dir() -> PathBuf {
+    PathBuf::from(".runctl").join("queue")
+}
+
+/// Load every job in `dir`, oldest submission first
+///
+/// Files that don't parse are skipped.
+pub fn load_jobs(dir: &Path) -> Result<Vec<QueuedJob>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut jobs: Vec<QueuedJob> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+    jobs.sort_by_key(|job| job.submitted_at);
+    Ok(jobs)
+}
+
+/// Reload a single job
+fn load_job(dir: &Path, id: &str) -> Result<QueuedJob> {
+    let path = QueuedJob::path(dir, id);
+    if !path.exists() {
+        return Err(TrainctlError::ResourceNotFound {
+            resource_type: "queued job".to_string(),
+            resource_id: id.to_string(),
+        });
+    }
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+/// Find a job by ID or unique ID prefix
+pub fn find_job(dir: &Path, id: &str) -> Result<QueuedJob> {
+    let mut matches: Vec<QueuedJob> = load_jobs(dir)?
+        .into_iter()
+        .filter(|job| job.id.starts_with(id))
+        .collect();
+    match matches.len() {
+        0 => Err(TrainctlError::ResourceNotFound {
+            resource_type: "queued job".to_string(),
+            resource_id: id.to_string(),
+        }),
+        1 => Ok(matches.remove(0)),
+        n => Err(TrainctlError::Validation {
+            field: "job_id".to_string(),
+            reason: format!("'{}' matches {} jobs; use more of the ID", id, n),
+        }),
+    }
+}
+
+/// Target for a new job, defaulting to the configured instance type on AWS
+fn resolve_target(target: Option<&str>, config: &Config) -> Result<MigrationTarget> {
+    let target = match target {
+        Some(spec) => MigrationTarget::parse(spec)?,
+        None => MigrationTarget {
+            provider: TargetProvider::Aws,
+            instance_type: config
+                .aws
+                .as_ref()
+                .map(|a| a.default_instance_type.clone())
+                .unwrap_or_else(|| "t3.medium".to_string()),
+        },
+    };
+    if target.provider != TargetProvider::Aws {
+        return Err(TrainctlError::Validation {
+            field: "target".to_string(),
+            reason: format!(
+                "The queue only schedules AWS targets for now ('{}' given)",
+                target
+            ),
+        });
+    }
+    Ok(target)
+}
+
+pub async fn handle_command(
+    cmd: QueueCommands,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    let dir = queue_dir();
+    match cmd {
+        QueueCommands::Submit {
+            script,
+            target,
+            spot,
+            timeout,
+            after,
+            after_any,
+            args,
+        } => {
+            if !script.exists() {
+                return Err(TrainctlError::Io(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("Script not found: {}", script.display()),
+                )));
+            }
+            let mut job = QueuedJob::new(script, args, resolve_target(target.as_deref(), config)?);
+            job.spot = spot;
+            job.timeout_secs = timeout;
+            // Full IDs, so a later job with the same prefix can't change the chain
+            job.after = after
+                .iter()
+                .map(|id| find_job(&dir, id).map(|j| j.id))
+                .collect::<Result<_>>()?;
+            job.after_any = after_any
+                .iter()
+                .map(|id| find_job(&dir, id).map(|j| j.id))
+                .collect::<Result<_>>()?;
+            job.save(&dir)?;
+
+            if output_format == "json" {
+                println!("{}", serde_json::to_string_pretty(&job)?);
+            } else {
+                println!(
+                    "Queued {} ({} on {})",
+                    job.id,
+                    job.script.display(),
+                    job.target
+                );
+                if !job.after.is_empty() || !job.after_any.is_empty() {
+                    println!("   Starts after {}", job.dependency_list());
+                }
+                println!("   Start the scheduler with: runctl queue run");
+            }
+            Ok(())
+        }
+        QueueCommands::List => {
+            let jobs = load_jobs(&dir)?;
+            if output_format == "json" {
+                println!("{}", serde_json::to_string_pretty(&jobs)?);
+                return Ok(());
+            }
+            if jobs.is_empty() {
+                println!("Queue is empty");
+                return Ok(());
+            }
+            let mut table = Table::new();
+            table.set_header(vec![
+                "ID",
+                "Status",
+                "Target",
+                "Script",
+                "Instance",
+                "Attempts",
+                "After",
+                "Submitted",
+                "Error",
+            ]);
+            for job in &jobs {
+                table.add_row(vec![
+                    job.id.clone(),
+                    job.status.to_string(),
+                    job.target.to_string(),
+                    job.script.display().to_string(),
+                    job.resource_id.clone().unwrap_or_else(|| "-".to_string()),
+                    job.attempts.to_string(),
+                    Some(job.dependency_list())
+                        .filter(|after| !after.is_empty())
+                        .unwrap_or_else(|| "-".to_string()),
+                    job.submitted_at.format("%Y-%m-%d %H:%M").to_string(),
+                    job.error.clone().unwrap_or_default(),
+                ]);
+            }
+            println!("{}", table);
+            Ok(())
+        }
+        QueueCommands::Cancel { job_id } => {
+            let mut job = find_job(&dir, &job_id)?;
+            if job.status.is_finished() {
+                return Err(TrainctlError::Validation {
+                    field: "job_id".to_string(),
+                    reason: format!("{} is already {}", job.id, job.status),
+                });
+            }
+            if let Some(resource_id) = job.resource_id.clone() {
+                if !crate::prompt::confirm(&format!(
+                    "Cancel {} and terminate {}?",
+                    job.id, resource_id
+                ))? {
+                    println!("Aborted");
+                    return Ok(());
+                }
+                AwsProvider::new(config.clone())
+                    .await?
+                    .terminate(&resource_id)
+                    .await?;
+            }
+            job.finish(QueueStatus::Cancelled, None);
+            job.save(&dir)?;
+
+            if output_format == "json" {
+                println!("{}", serde_json::to_string_pretty(&job)?);
+            } else {
+                match (&job.resource_id, &job.fleet) {
+                    (Some(resource_id), Some(fleet)) => println!(
+                        "Cancelled {} and terminated {} (fleet {} replaces it)",
+                        job.id, resource_id, fleet
+                    ),
+                    (Some(resource_id), None) => {
+                        println!("Cancelled {} and terminated {}", job.id, resource_id)
+                    }
+                    (None, _) => println!("Cancelled {}", job.id),
+                }
+            }
+            Ok(())
+        }
+        QueueCommands::Retry { job_id } => {
+            let mut job = find_job(&dir, &job_id)?;
+            job.requeue()?;
+            job.save(&dir)?;
+            if output_format == "json" {
+                println!("{}", serde_json::to_string_pretty(&job)?);
+            } else {
+                println!("Requeued {} (attempt {})", job.id, job.attempts + 1);
+            }
+            Ok(())
+        }
+        QueueCommands::Run {
+            max_concurrent,
+            fleet,
+            interval,
+        } => {
+            let max_concurrent =
+                max_concurrent.unwrap_or(if fleet.is_some() { usize::MAX } else { 1 });
+            if max_concurrent == 0 {
+                return Err(TrainctlError::Validation {
+                    field: "max_concurrent".to_string(),
+                    reason: "must be at least 1".to_string(),
+                });
+            }
+            let options = scheduler::SchedulerOptions {
+                max_concurrent,
+                fleet,
+                interval: Duration::from_secs(interval),
+            };
+            scheduler::run(&dir, options, config, output_format).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(target: &str) -> QueuedJob {
+        QueuedJob::new(
+            PathBuf::from("train.py"),
+            vec!["--epochs".to_string(), "5".to_string()],
+            MigrationTarget::parse(target).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_save_and_load_in_submission_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = job("aws:g5.xlarge");
+        let mut second = job("aws:t3.medium");
+        second.submitted_at = first.submitted_at + chrono::Duration::seconds(1);
+        second.save(dir.path()).unwrap();
+        first.save(dir.path()).unwrap();
+
+        let jobs = load_jobs(dir.path()).unwrap();
+        assert_eq!(
+            jobs.iter().map(|j| j.id.as_str()).collect::<Vec<_>>(),
+            vec![first.id.as_str(), second.id.as_str()]
+        );
+        assert_eq!(jobs[0].args, vec!["--epochs", "5"]);
+        assert!(load_jobs(&dir.path().join("missing")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_find_job_by_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut a = job("aws:g5.xlarge");
+        a.id = "q-aaaa1111".to_string();
+        let mut b = job("aws:g5.xlarge");
+        b.id = "q-aaaa2222".to_string();
+        a.save(dir.path()).unwrap();
+        b.save(dir.path()).unwrap();
+
+        assert_eq!(find_job(dir.path(), "q-aaaa1").unwrap().id, "q-aaaa1111");
+        assert!(matches!(
+            find_job(dir.path(), "q-aaaa"),
+            Err(TrainctlError::Validation { .. })
+        ));
+        assert!(matches!(
+            find_job(dir.path(), "q-bbbb"),
+            Err(TrainctlError::ResourceNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_requeue() {
+        let mut job = job("aws:g5.xlarge");
+        assert!(job.requeue().is_err());
+
+        job.attempts = 1;
+        job.resource_id = Some("i-0123456789abcdef0".to_string());
+        job.finish(QueueStatus::Failed, Some("failed".to_string()));
+        job.requeue().unwrap();
+        assert_eq!(job.status, QueueStatus::Queued);
+        assert_eq!(job.resource_id, None);
+        assert_eq!(job.error, None);
+        assert_eq!(job.attempts, 1);
+    }
+
+    #[test]
+    fn test_dependencies() {
+        let mut preprocess = job("aws:t3.medium");
+        let mut train = job("aws:g5.xlarge");
+        train.after = vec![preprocess.id.clone()];
+        let mut eval = job("aws:t3.medium");
+        eval.after_any = vec![train.id.clone()];
+
+        let jobs = vec![preprocess.clone(), train.clone(), eval.clone()];
+        assert_eq!(preprocess.dependencies(&jobs), Dependencies::Ready);
+        assert_eq!(train.dependencies(&jobs), Dependencies::Waiting);
+        assert_eq!(eval.dependencies(&jobs), Dependencies::Waiting);
+
+        preprocess.finish(QueueStatus::Failed, None);
+        let jobs = vec![preprocess.clone(), train.clone(), eval.clone()];
+        assert!(matches!(
+            train.dependencies(&jobs),
+            Dependencies::Broken(reason) if reason.contains("failed")
+        ));
+
+        train.finish(QueueStatus::Failed, None);
+        let jobs = vec![preprocess.clone(), train.clone(), eval.clone()];
+        assert_eq!(eval.dependencies(&jobs), Dependencies::Ready);
+
+        preprocess.status = QueueStatus::Completed;
+        train.status = QueueStatus::Queued;
+        let jobs = vec![preprocess, train.clone()];
+        assert_eq!(train.dependencies(&jobs), Dependencies::Ready);
+        assert!(matches!(train.dependencies(&[]), Dependencies::Broken(_)));
+    }
+
+    #[test]
+    fn test_resolve_target() {
+        let config = Config::default();
+        let target = resolve_target(None, &config).unwrap();
+        assert_eq!(target.provider, TargetProvider::Aws);
+        assert_eq!(
+            resolve_target(Some("aws:g5.xlarge"), &config)
+                .unwrap()
+                .instance_type,
+            "g5.xlarge"
+        );
+        assert!(resolve_target(Some("runpod:RTX 4090"), &config).is_err());
+    }
+}
@@ -0,0 +1,387 @@
+//! Queue scheduler: provision (or claim a fleet worker), train, poll, tear down
+
+use super::{load_job, load_jobs, Dependencies, QueueStatus, QueuedJob};
+use crate::aws::FleetPool;
+use crate::config::Config;
+use crate::error::{JobOutcome, Result};
+use crate::provider::{CreateResourceOptions, TrainingJob, TrainingProvider};
+use crate::providers::{AwsProvider, ProviderRegistry};
+use crate::watch::{JobSnapshot, JobSource};
+use chrono::Utc;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+pub(crate) struct SchedulerOptions {
+    pub max_concurrent: usize,
+    /// Run jobs on this fleet's idle workers instead of new instances
+    pub fleet: Option<String>,
+    pub interval: Duration,
+}
+
+/// A job whose training has started
+struct ActiveJob {
+    id: String,
+    resource_id: String,
+    snapshot: JobSnapshot,
+    source: JobSource,
+}
+
+/// Run queued jobs until nothing is queued or running
+pub(crate) async fn run(
+    dir: &Path,
+    options: SchedulerOptions,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    let text = output_format != "json";
+    let mut registry = ProviderRegistry::new();
+    registry.register("aws", Arc::new(AwsProvider::new(config.clone()).await?))?;
+
+    let pool = match &options.fleet {
+        Some(name) => Some(FleetPool::new(name).await?),
+        None => None,
+    };
+
+    let mut active = adopt_running(dir, config, text).await?;
+    let mut finished: Vec<QueuedJob> = Vec::new();
+    let mut waiting_for_worker = false;
+
+    loop {
+        // Poll running jobs, tearing down the ones that are done
+        let mut still_running = Vec::new();
+        for mut job in active.drain(..) {
+            let mut record = match load_job(dir, &job.id) {
+                Ok(record) => record,
+                Err(e) => {
+                    warn!("Dropping {} from the scheduler: {}", job.id, e);
+                    continue;
+                }
+            };
+            // `queue cancel` already terminated the instance
+            if record.status == QueueStatus::Cancelled {
+                continue;
+            }
+
+            crate::watch::refresh(&mut job.snapshot, &mut job.source, config, None).await;
+            let elapsed = record
+                .started_at
+                .map(|start| (Utc::now() - start).num_seconds().max(0) as u64)
+                .unwrap_or(0);
+            let outcome = match crate::jobs::classify(&job.snapshot.status) {
+                Some(outcome) => outcome,
+                None if record.timeout_secs.is_some_and(|t| elapsed >= t) => {
+                    Err(JobOutcome::TimedOut)
+                }
+                None => {
+                    still_running.push(job);
+                    continue;
+                }
+            };
+
+            let provider = registry.get(&record.target.provider.to_string())?;
+            teardown(provider.as_ref(), &record, &job.resource_id).await;
+            match outcome {
+                Ok(()) => record.finish(QueueStatus::Completed, None),
+                Err(outcome) => record.finish(
+                    QueueStatus::Failed,
+                    Some(format!("{} (status {})", outcome, job.snapshot.status)),
+                ),
+            }
+            record.save(dir)?;
+            report(&record, text);
+            finished.push(record);
+        }
+        active = still_running;
+
+        // Workers free for new jobs; claimed ones are protected, so they
+        // don't show up here
+        let mut idle = match &pool {
+            Some(pool) => pool.idle().await.unwrap_or_else(|e| {
+                warn!("Failed to list workers of fleet {}: {}", pool.name(), e);
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        idle.retain(|id| !active.iter().any(|job| &job.resource_id == id));
+
+        // Start queued jobs while there is room
+        let mut queued = false;
+        let mut blocked = false;
+        while active.len() < options.max_concurrent {
+            let (next, waiting) = next_ready(dir, text, &mut finished)?;
+            blocked = waiting;
+            let Some(mut record) = next else {
+                break;
+            };
+            queued = true;
+            let worker = match &pool {
+                Some(pool) => match idle.pop() {
+                    Some(instance_id) => Some((pool, instance_id)),
+                    None => {
+                        if text && !waiting_for_worker {
+                            println!("Waiting for an idle worker in fleet {}", pool.name());
+                        }
+                        waiting_for_worker = true;
+                        break;
+                    }
+                },
+                None => None,
+            };
+            waiting_for_worker = false;
+            let provider = registry.get(&record.target.provider.to_string())?;
+            match start(dir, &mut record, provider.as_ref(), worker, config, text).await {
+                Ok(Some(job)) => active.push(job),
+                Ok(None) => {}
+                Err(e) => {
+                    if let Some(resource_id) = record.resource_id.clone() {
+                        teardown(provider.as_ref(), &record, &resource_id).await;
+                    }
+                    record.finish(QueueStatus::Failed, Some(e.to_string()));
+                    record.save(dir)?;
+                    report(&record, text);
+                    finished.push(record);
+                }
+            }
+        }
+
+        // A fleet scheduler keeps waiting for workers while jobs are queued,
+        // and any scheduler for jobs whose dependencies run elsewhere
+        if active.is_empty() && !(pool.is_some() && queued) && !blocked {
+            break;
+        }
+        tokio::time::sleep(options.interval).await;
+    }
+
+    if text {
+        let completed = finished
+            .iter()
+            .filter(|job| job.status == QueueStatus::Completed)
+            .count();
+        println!(
+            "Queue drained: {} completed, {} failed",
+            completed,
+            finished.len() - completed
+        );
+    } else {
+        println!("{}", serde_json::to_string_pretty(&finished)?);
+    }
+    Ok(())
+}
+
+/// The first queued job whose dependencies are met, and whether other
+/// queued jobs are still waiting on theirs
+///
+/// Jobs whose `--after` dependencies failed are failed on the way.
+fn next_ready(
+    dir: &Path,
+    text: bool,
+    finished: &mut Vec<QueuedJob>,
+) -> Result<(Option<QueuedJob>, bool)> {
+    let jobs = load_jobs(dir)?;
+    let mut waiting = false;
+    let mut failed_any = false;
+    for job in jobs.iter().filter(|job| job.status == QueueStatus::Queued) {
+        match job.dependencies(&jobs) {
+            Dependencies::Ready => return Ok((Some(job.clone()), waiting)),
+            Dependencies::Waiting => waiting = true,
+            Dependencies::Broken(reason) => {
+                let mut record = job.clone();
+                record.finish(QueueStatus::Failed, Some(reason));
+                record.save(dir)?;
+                report(&record, text);
+                finished.push(record);
+                failed_any = true;
+            }
+        }
+    }
+    if failed_any {
+        // Jobs chained after the ones just failed
+        return next_ready(dir, text, finished);
+    }
+    Ok((None, waiting))
+}
+
+/// Resume tracking jobs left running by an earlier scheduler
+///
+/// Jobs stuck provisioning can't be resumed (the instance ID was never
+/// recorded), so they are failed for `queue retry`.
+async fn adopt_running(dir: &Path, config: &Config, text: bool) -> Result<Vec<ActiveJob>> {
+    let mut active = Vec::new();
+    for mut record in load_jobs(dir)? {
+        match (record.status, record.resource_id.clone()) {
+            (QueueStatus::Running, Some(resource_id)) => {
+                let (snapshot, source) = crate::watch::resolve_job(&resource_id, config).await?;
+                if text {
+                    println!("Resuming {} on {}", record.id, resource_id);
+                }
+                active.push(ActiveJob {
+                    id: record.id,
+                    resource_id,
+                    snapshot,
+                    source,
+                });
+            }
+            (QueueStatus::Provisioning | QueueStatus::Running, resource_id) => {
+                record.finish(
+                    QueueStatus::Failed,
+                    Some(match resource_id {
+                        Some(id) => format!("scheduler stopped before training started on {}", id),
+                        None => "scheduler stopped while provisioning".to_string(),
+                    }),
+                );
+                record.save(dir)?;
+                report(&record, text);
+            }
+            _ => {}
+        }
+    }
+    Ok(active)
+}
+
+/// Provision an instance for `record`, or claim the fleet `worker`, and start
+/// training on it
+///
+/// Returns None when the job was cancelled while provisioning.
+async fn start(
+    dir: &Path,
+    record: &mut QueuedJob,
+    provider: &dyn TrainingProvider,
+    worker: Option<(&FleetPool, String)>,
+    config: &Config,
+    text: bool,
+) -> Result<Option<ActiveJob>> {
+    record.status = QueueStatus::Provisioning;
+    record.attempts += 1;
+    record.started_at = Some(Utc::now());
+    record.save(dir)?;
+
+    let resource_id = match worker {
+        Some((pool, instance_id)) => {
+            if text {
+                println!(
+                    "[{}] Claiming {} in fleet {} for {}",
+                    record.id,
+                    instance_id,
+                    pool.name(),
+                    record.script.display()
+                );
+            }
+            crate::progress::emit(
+                "queue.provision",
+                format!("{}: {} ({})", record.id, instance_id, pool.name()),
+            );
+            pool.claim(&instance_id).await?;
+            record.fleet = Some(pool.name().to_string());
+            instance_id
+        }
+        None => {
+            if text {
+                println!(
+                    "[{}] Provisioning {} for {}",
+                    record.id,
+                    record.target,
+                    record.script.display()
+                );
+            }
+            crate::progress::emit(
+                "queue.provision",
+                format!("{}: {}", record.id, record.target),
+            );
+
+            let create_options = CreateResourceOptions {
+                use_spot: record.spot,
+                // One instance per attempt, even if the scheduler retries the call
+                custom: HashMap::from([(
+                    "client_token".to_string(),
+                    format!("runctl-{}-{}", record.id, record.attempts),
+                )]),
+                ..Default::default()
+            };
+            provider
+                .create_resource(&record.target.instance_type, create_options)
+                .await?
+        }
+    };
+    record.resource_id = Some(resource_id.clone());
+    record.save(dir)?;
+
+    if load_job(dir, &record.id)?.status == QueueStatus::Cancelled {
+        teardown(provider, record, &resource_id).await;
+        record.finish(QueueStatus::Cancelled, None);
+        record.save(dir)?;
+        report(record, text);
+        return Ok(None);
+    }
+
+    let job = TrainingJob {
+        script: record.script.clone(),
+        args: record.args.clone(),
+        data_source: None,
+        output_dest: None,
+        checkpoint_dir: None,
+        environment: vec![],
+    };
+    provider.train(&resource_id, job).await?;
+    record.status = QueueStatus::Running;
+    record.save(dir)?;
+    if text {
+        println!("[{}] Training on {}", record.id, resource_id);
+    }
+    crate::progress::emit("queue.running", format!("{}: {}", record.id, resource_id));
+
+    let (snapshot, source) = crate::watch::resolve_job(&resource_id, config).await?;
+    Ok(Some(ActiveJob {
+        id: record.id.clone(),
+        resource_id,
+        snapshot,
+        source,
+    }))
+}
+
+/// Terminate a job's instance, or release its fleet worker, logging rather
+/// than failing the scheduler
+async fn teardown(provider: &dyn TrainingProvider, record: &QueuedJob, resource_id: &str) {
+    if let Some(fleet) = &record.fleet {
+        let released = match FleetPool::new(fleet).await {
+            Ok(pool) => pool.release(resource_id).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = released {
+            warn!(
+                "Failed to release {} in fleet {}: {}",
+                resource_id, fleet, e
+            );
+            eprintln!(
+                "WARNING: could not release {} back to fleet {}: {}; it stays protected from scale-in",
+                resource_id, fleet, e
+            );
+        }
+        return;
+    }
+    if let Err(e) = provider.terminate(&resource_id.to_string()).await {
+        warn!("Failed to terminate {}: {}", resource_id, e);
+        eprintln!(
+            "WARNING: could not terminate {}: {}; terminate it manually",
+            resource_id, e
+        );
+    }
+}
+
+fn report(record: &QueuedJob, text: bool) {
+    let message = match &record.error {
+        Some(error) => format!("{}: {} ({})", record.id, record.status, error),
+        None => format!("{}: {}", record.id, record.status),
+    };
+    if text {
+        println!("[{}] {}", record.id, record.status);
+        if let Some(error) = &record.error {
+            println!("   {}", error);
+        }
+    }
+    crate::progress::ProgressEvent::new("queue.done", message)
+        .done()
+        .emit();
+}
@@ -0,0 +1,332 @@
+//! Per-project and per-account cost budgets
+//!
+//! Evaluates tracked resources against the `[budget]` config section. Spend
+//! is estimated from the tracker: a resource's current run counts from the
+//! later of its launch time and the start of the period, and runtime carried
+//! over from before a restart (`prior_cost`) counts only if the resource was
+//! created inside the period. Resources are grouped by their `runctl:project`
+//! tag; untagged resources belong to no project and are never limited.
+//! Account budgets group the same way by `runctl:account`, which the tracker
+//! records for resources found through an `[accounts]` entry.
+//!
+//! This module only decides. Acting on a breach (stopping instances, sending
+//! notifications) is left to the caller, e.g. `runctl resources budget`.
+
+use super::{cost_between, is_running, ResourceTracker, TrackedResource};
+use crate::config::{BudgetAction, BudgetConfig, BudgetLimits};
+use crate::error::{Result, TrainctlError};
+use crate::provider::ResourceId;
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Tag that assigns a resource to a project
+pub const PROJECT_TAG: &str = "runctl:project";
+
+/// Tracker tag naming the `[accounts]` entry a resource was found in
+///
+/// Set by runctl when it reconciles resources listed from a configured
+/// account; it isn't a tag on the EC2 instance itself.
+pub const ACCOUNT_TAG: &str = "runctl:account";
+
+/// Budget period a limit applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    Hourly,
+    Daily,
+    Monthly,
+}
+
+impl std::fmt::Display for BudgetPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BudgetPeriod::Hourly => write!(f, "hourly"),
+            BudgetPeriod::Daily => write!(f, "daily"),
+            BudgetPeriod::Monthly => write!(f, "monthly"),
+        }
+    }
+}
+
+/// What a project is spending
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProjectSpend {
+    /// Combined rate of running resources (USD/hour)
+    pub hourly_rate: f64,
+    /// Spend since midnight UTC
+    pub today: f64,
+    /// Spend since the first of the month (UTC)
+    pub this_month: f64,
+    /// Running resources, the ones a `stop` action applies to
+    pub running: Vec<ResourceId>,
+}
+
+impl ProjectSpend {
+    fn amount(&self, period: BudgetPeriod) -> f64 {
+        match period {
+            BudgetPeriod::Hourly => self.hourly_rate,
+            BudgetPeriod::Daily => self.today,
+            BudgetPeriod::Monthly => self.this_month,
+        }
+    }
+}
+
+/// A limit a project has exceeded
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetBreach {
+    pub period: BudgetPeriod,
+    pub limit: f64,
+    pub actual: f64,
+}
+
+impl std::fmt::Display for BudgetBreach {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.period {
+            BudgetPeriod::Hourly => write!(
+                f,
+                "hourly rate ${:.2}/hr exceeds ${:.2}/hr",
+                self.actual, self.limit
+            ),
+            period => write!(
+                f,
+                "{} spend ${:.2} exceeds ${:.2}",
+                period, self.actual, self.limit
+            ),
+        }
+    }
+}
+
+/// Budget state of one project with configured limits
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectBudget {
+    pub project: String,
+    pub spend: ProjectSpend,
+    pub limits: BudgetLimits,
+    pub breaches: Vec<BudgetBreach>,
+}
+
+impl ProjectBudget {
+    pub fn is_exceeded(&self) -> bool {
+        !self.breaches.is_empty()
+    }
+
+    /// Whether `action` should be taken for this project now
+    pub fn wants(&self, action: BudgetAction) -> bool {
+        self.is_exceeded() && self.limits.actions.contains(&action)
+    }
+}
+
+/// Project a tracked resource is billed to
+pub fn project_of(resource: &TrackedResource) -> Option<&str> {
+    resource.tags.get(PROJECT_TAG).map(String::as_str)
+}
+
+/// Midnight UTC on `now`'s day
+fn start_of_day(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), now.day(), 0, 0, 0)
+        .single()
+        .unwrap_or(now)
+}
+
+/// Midnight UTC on the first of `now`'s month
+fn start_of_month(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(now)
+}
+
+/// Estimated spend of `resource` between `start` and `now`
+pub fn spend_since(resource: &TrackedResource, start: DateTime<Utc>, now: DateTime<Utc>) -> f64 {
+    let launch = resource.status.launch_time;
+    let current = if is_running(&resource.status.state) {
+        cost_between(
+            resource.status.cost_per_hour,
+            launch.map(|l| l.max(start)),
+            now,
+        )
+    } else if launch.is_some_and(|l| l >= start) {
+        // The last run ended; its cost was frozen when it stopped
+        (resource.accumulated_cost - resource.prior_cost).max(0.0)
+    } else {
+        0.0
+    };
+    let earlier = if resource.created_at >= start {
+        resource.prior_cost
+    } else {
+        0.0
+    };
+    current + earlier
+}
+
+/// Spend of every tagged project
+pub fn project_spend(
+    resources: &[TrackedResource],
+    now: DateTime<Utc>,
+) -> BTreeMap<String, ProjectSpend> {
+    spend_by_tag(resources, PROJECT_TAG, now)
+}
+
+/// Spend of every configured account that has tracked resources
+pub fn account_spend(
+    resources: &[TrackedResource],
+    now: DateTime<Utc>,
+) -> BTreeMap<String, ProjectSpend> {
+    spend_by_tag(resources, ACCOUNT_TAG, now)
+}
+
+/// Spend grouped by the value of `tag`; resources without it are skipped
+fn spend_by_tag(
+    resources: &[TrackedResource],
+    tag: &str,
+    now: DateTime<Utc>,
+) -> BTreeMap<String, ProjectSpend> {
+    let day = start_of_day(now);
+    let month = start_of_month(now);
+    let mut groups: BTreeMap<String, ProjectSpend> = BTreeMap::new();
+    for resource in resources {
+        let Some(key) = resource.tags.get(tag) else {
+            continue;
+        };
+        let spend = groups.entry(key.clone()).or_default();
+        if is_running(&resource.status.state) {
+            spend.hourly_rate += resource.status.cost_per_hour;
+            spend.running.push(resource.status.id.clone());
+        }
+        spend.today += spend_since(resource, day, now);
+        spend.this_month += spend_since(resource, month, now);
+    }
+    groups
+}
+
+/// Limits `spend` exceeds
+pub fn breaches(limits: &BudgetLimits, spend: &ProjectSpend) -> Vec<BudgetBreach> {
+    [
+        (BudgetPeriod::Hourly, limits.hourly),
+        (BudgetPeriod::Daily, limits.daily),
+        (BudgetPeriod::Monthly, limits.monthly),
+    ]
+    .into_iter()
+    .filter_map(|(period, limit)| {
+        let limit = limit?;
+        let actual = spend.amount(period);
+        (actual > limit).then_some(BudgetBreach {
+            period,
+            limit,
+            actual,
+        })
+    })
+    .collect()
+}
+
+/// Budget state of every project that has limits
+///
+/// Projects named in `[budget.projects]` are always included, even with no
+/// spend; others only when `[budget.default]` is set and they have resources.
+pub fn evaluate(
+    config: &BudgetConfig,
+    resources: &[TrackedResource],
+    now: DateTime<Utc>,
+) -> Vec<ProjectBudget> {
+    let mut spend = project_spend(resources, now);
+    for name in config.projects.keys() {
+        spend.entry(name.clone()).or_default();
+    }
+    spend
+        .into_iter()
+        .filter_map(|(project, spend)| {
+            let limits = config.limits_for(&project)?.clone();
+            Some(ProjectBudget {
+                breaches: breaches(&limits, &spend),
+                project,
+                spend,
+                limits,
+            })
+        })
+        .collect()
+}
+
+/// Budget state of every account in `[budget.accounts]`
+///
+/// The result reuses `ProjectBudget`, with the account name in `project`.
+/// Accounts are listed even without tracked spend.
+pub fn evaluate_accounts(
+    config: &BudgetConfig,
+    resources: &[TrackedResource],
+    now: DateTime<Utc>,
+) -> Vec<ProjectBudget> {
+    let mut spend = account_spend(resources, now);
+    config
+        .accounts
+        .iter()
+        .map(|(account, limits)| {
+            let spend = spend.remove(account).unwrap_or_default();
+            ProjectBudget {
+                breaches: breaches(limits, &spend),
+                project: account.clone(),
+                spend,
+                limits: limits.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Check whether `project` may launch a resource costing `added_hourly`
+///
+/// Fails when the launch would exceed a limit whose actions include
+/// `refuse`. Otherwise returns the exceeded limits so the caller can warn.
+pub fn check_launch(
+    config: &BudgetConfig,
+    resources: &[TrackedResource],
+    project: &str,
+    added_hourly: f64,
+    now: DateTime<Utc>,
+) -> Result<Vec<BudgetBreach>> {
+    let Some(limits) = config.limits_for(project) else {
+        return Ok(Vec::new());
+    };
+    let mut spend = project_spend(resources, now)
+        .remove(project)
+        .unwrap_or_default();
+    spend.hourly_rate += added_hourly;
+
+    let exceeded = breaches(limits, &spend);
+    if !exceeded.is_empty() && limits.actions.contains(&BudgetAction::Refuse) {
+        let reasons: Vec<String> = exceeded.iter().map(|b| b.to_string()).collect();
+        return Err(TrainctlError::CostTracking(format!(
+            "Project '{}' is over budget: {}.\n  \
+             Stop instances with 'runctl resources stop-all --project {}' or raise the limit in [budget].",
+            project,
+            reasons.join("; "),
+            project
+        )));
+    }
+    Ok(exceeded)
+}
+
+impl ResourceTracker {
+    /// Budget state of every project with limits, from current tracked costs
+    pub async fn evaluate_budgets(&self, config: &BudgetConfig) -> Vec<ProjectBudget> {
+        evaluate(config, &self.get_all().await, Utc::now())
+    }
+
+    /// Budget state of every account with limits (see `evaluate_accounts`)
+    pub async fn evaluate_account_budgets(&self, config: &BudgetConfig) -> Vec<ProjectBudget> {
+        evaluate_accounts(config, &self.get_all().await, Utc::now())
+    }
+
+    /// Check a launch for `project` against its budget (see `check_launch`)
+    pub async fn check_launch_budget(
+        &self,
+        config: &BudgetConfig,
+        project: &str,
+        added_hourly: f64,
+    ) -> Result<Vec<BudgetBreach>> {
+        check_launch(
+            config,
+            &self.get_all().await,
+            project,
+            added_hourly,
+            Utc::now(),
+        )
+    }
+}
@@ -0,0 +1,1009 @@
+//! Resource tracking and cost awareness
+//!
+//! Tracks resource lifecycle and usage to enable cost awareness and safe cleanup.
+//!
+//! ## Design
+//!
+//! `ResourceTracker` maintains an in-memory map of resources. It's designed for
+//! single-process use (CLI tool). Resources are registered when created and
+//! updated as their state changes.
+//!
+//! ## Cost Calculation
+//!
+//! Accumulated cost is calculated on-demand when accessing resources via
+//! `get_running()`, `get_by_id()`, etc. This ensures costs are always current
+//! based on `launch_time` and `cost_per_hour`, without requiring periodic
+//! background tasks.
+//!
+//! ## Resource Lifecycle
+//!
+//! - `ResourceStatus`: Provider-agnostic resource state (from `provider` module)
+//! - `TrackedResource`: Internal tracking with usage history and accumulated cost
+//! - Resources are registered via `register()` when created
+//! - State updates via `update_state()` as resources transition
+//! - Usage metrics added via `update_usage()` for monitoring
+//!
+//! ## Thread Safety
+//!
+//! Uses `Arc<Mutex<HashMap>>` for thread-safe access. All methods are async
+//! to avoid blocking on the mutex.
+//!
+//! ## Persistence
+//!
+//! `ResourceTracker::persistent()` keeps the registry in a JSON file
+//! (`.runctl/resources.json` for the CLI) shared by concurrent runctl
+//! processes, e.g. `watch` running next to `aws create`:
+//!
+//! - Every change takes an exclusive advisory lock (flock) on
+//!   `resources.json.lock`, re-reads the file, applies the change and writes
+//!   it back, so one process never overwrites another's update
+//! - Writes go to a temp file that is fsynced and renamed over the original,
+//!   after the previous version is copied to `resources.json.bak`
+//! - The OS releases the lock when its holder exits, so a crashed process
+//!   never leaves the store locked
+//! - A file that can't be parsed is recovered from the backup; entries that
+//!   don't parse are skipped
+//!
+//! `runctl resources tracker repair` applies the same recovery explicitly
+//! and reports what it changed.
+//!
+//! ## Budgets
+//!
+//! The `budget` module checks tracked spend per project against `[budget]`
+//! limits; see `ResourceTracker::evaluate_budgets()`.
+//!
+//! ## Cost Ledger
+//!
+//! With `with_ledger()`, lifecycle changes and hourly cost snapshots are also
+//! appended to the cost ledger (see `crate::costs`), which keeps costs after
+//! resources are removed from the tracker.
+
+use crate::costs::{Ledger, LedgerEntry, LedgerEvent};
+use crate::error::{Result, TrainctlError};
+use crate::provider::{ResourceId, ResourceState, ResourceStatus};
+use chrono::{DateTime, Utc};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+pub mod budget;
+
+/// Where the CLI persists tracked resources
+pub const DEFAULT_STORE_PATH: &str = ".runctl/resources.json";
+
+/// Store file format version
+const STORE_VERSION: u32 = 1;
+
+/// How long a change waits for another process's lock
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Resource usage metrics at a point in time
+///
+/// Captures CPU, memory, GPU, and network usage for a resource at a specific
+/// timestamp. Used to track resource utilization over time and calculate costs.
+///
+/// ## Usage
+///
+/// Metrics are typically collected periodically (e.g., every 5 seconds) and
+/// added to a resource's usage history via `ResourceTracker::update_usage()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    pub cpu_percent: f64,
+    pub memory_mb: f64,
+    pub gpu_utilization: Option<f64>,
+    pub network_in_mb: f64,
+    pub network_out_mb: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Tracked resource with usage history and cost information
+///
+/// Extends `ResourceStatus` with tracking-specific information:
+/// - Creation timestamp for cost calculation
+/// - Usage history for monitoring and analysis
+/// - Accumulated cost based on runtime
+/// - Tags for organization and filtering
+///
+/// ## Cost Calculation
+///
+/// `accumulated_cost` is calculated on-demand when accessing resources. It's
+/// based on the resource's `cost_per_hour` and time elapsed since `created_at`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedResource {
+    pub status: ResourceStatus,
+    pub created_at: DateTime<Utc>,
+    pub usage_history: Vec<ResourceUsage>,
+    pub accumulated_cost: f64,
+    pub tags: HashMap<String, String>,
+    /// Cost of running periods before the current `launch_time`
+    ///
+    /// EC2 resets the launch time when a stopped instance starts again, so
+    /// earlier runtime is carried here by `reconcile()`.
+    #[serde(default)]
+    pub prior_cost: f64,
+}
+
+/// What `ResourceTracker::reconcile` changed for one resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reconciliation {
+    /// Not tracked before; now registered
+    Registered,
+    /// Tracked state already matched
+    Unchanged,
+    /// Details such as the public IP or the hourly price changed
+    Updated,
+    /// Stopped or terminated outside runctl; cost frozen at the stop time
+    Stopped,
+    /// Started again outside runctl; earlier runtime carried over
+    Restarted,
+}
+
+fn is_running(state: &ResourceState) -> bool {
+    matches!(state, ResourceState::Running | ResourceState::Starting)
+}
+
+/// Ledger event for a state change, if it is a lifecycle change
+fn transition_event(was_running: bool, state: &ResourceState) -> Option<LedgerEvent> {
+    match state {
+        ResourceState::Terminated | ResourceState::Terminating => Some(LedgerEvent::Terminated),
+        state if is_running(state) && !was_running => Some(LedgerEvent::Started),
+        state if !is_running(state) && was_running => Some(LedgerEvent::Stopped),
+        _ => None,
+    }
+}
+
+/// Cost of running at `cost_per_hour` from `start` to `end`
+fn cost_between(cost_per_hour: f64, start: Option<DateTime<Utc>>, end: DateTime<Utc>) -> f64 {
+    match start {
+        Some(start) => cost_per_hour * (end - start).num_seconds().max(0) as f64 / 3600.0,
+        None => 0.0,
+    }
+}
+
+/// Bring a tracked resource in line with its live status
+///
+/// `state_changed_at` is when the live state was entered, if the provider
+/// reports it (EC2 does for stops); without it a stop is dated `now`. When an
+/// instance was restarted between two refreshes the stop time is unknown,
+/// and the whole gap between the two launches is counted.
+pub fn reconcile_resource(
+    tracked: &mut TrackedResource,
+    live: ResourceStatus,
+    state_changed_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Reconciliation {
+    let before = (
+        tracked.status.state.clone(),
+        tracked.status.public_ip.clone(),
+        tracked.status.public_ipv6.clone(),
+        tracked.status.cost_per_hour,
+    );
+    let old_launch = tracked.status.launch_time;
+    let rate = if live.cost_per_hour > 0.0 {
+        live.cost_per_hour
+    } else {
+        tracked.status.cost_per_hour
+    };
+    let tracked_rate = tracked.status.cost_per_hour;
+
+    let outcome = match (is_running(&tracked.status.state), is_running(&live.state)) {
+        (true, false) => {
+            let stopped_at = state_changed_at
+                .filter(|t| *t <= now && old_launch.is_none_or(|l| *t >= l))
+                .unwrap_or(now);
+            tracked.accumulated_cost =
+                tracked.prior_cost + cost_between(tracked_rate, old_launch, stopped_at);
+            Reconciliation::Stopped
+        }
+        (false, true) => {
+            tracked.prior_cost = tracked.accumulated_cost;
+            Reconciliation::Restarted
+        }
+        (true, true) => match (old_launch, live.launch_time) {
+            (Some(old), Some(new)) if new > old => {
+                tracked.prior_cost += cost_between(tracked_rate, Some(old), new);
+                Reconciliation::Restarted
+            }
+            _ => Reconciliation::Unchanged,
+        },
+        (false, false) => Reconciliation::Unchanged,
+    };
+
+    // The account label comes from runctl, not the provider; a listing
+    // through the ambient credentials shouldn't drop it
+    let account = tracked.tags.remove(budget::ACCOUNT_TAG);
+    tracked.tags = live.tags.iter().cloned().collect();
+    if let Some(account) = account {
+        tracked
+            .tags
+            .entry(budget::ACCOUNT_TAG.to_string())
+            .or_insert(account);
+    }
+    tracked.status = ResourceStatus {
+        launch_time: live.launch_time.or(old_launch),
+        cost_per_hour: rate,
+        instance_type: live
+            .instance_type
+            .or_else(|| tracked.status.instance_type.take()),
+        ..live
+    };
+    if is_running(&tracked.status.state) {
+        tracked.accumulated_cost =
+            tracked.prior_cost + cost_between(rate, tracked.status.launch_time, now);
+    }
+
+    let after = (
+        tracked.status.state.clone(),
+        tracked.status.public_ip.clone(),
+        tracked.status.public_ipv6.clone(),
+        tracked.status.cost_per_hour,
+    );
+    if outcome == Reconciliation::Unchanged && before != after {
+        Reconciliation::Updated
+    } else {
+        outcome
+    }
+}
+
+/// Resource tracker for cost awareness and lifecycle management
+///
+/// Maintains an in-memory registry of resources with their states, usage history,
+/// and accumulated costs. Designed for single-process use (CLI tool).
+///
+/// ## Thread Safety
+///
+/// Uses `Arc<Mutex<HashMap>>` internally for thread-safe access. All methods
+/// are async to avoid blocking on the mutex.
+///
+/// ## Resource Lifecycle
+///
+/// 1. **Register**: Resources are registered when created via `register()`
+/// 2. **Update State**: State changes are tracked via `update_state()`
+/// 3. **Update Usage**: Usage metrics are added via `update_usage()`
+/// 4. **Query**: Access resources via `get_running()`, `get_by_id()`, etc.
+///
+/// ## Cost Calculation
+///
+/// Accumulated cost is calculated on-demand when accessing resources. This
+/// ensures costs are always current based on `launch_time` and `cost_per_hour`,
+/// without requiring periodic background tasks.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use runctl::resource_tracking::ResourceTracker;
+/// use runctl::provider::{ResourceStatus, ResourceState};
+///
+/// # async fn example() -> runctl::error::Result<()> {
+/// let tracker = ResourceTracker::new();
+///
+/// // Register a new resource
+/// let status = ResourceStatus {
+///     id: "i-123".to_string(),
+///     state: ResourceState::Running,
+///     // ... other fields
+/// };
+/// tracker.register(status).await?;
+///
+/// // Get all running resources with costs
+/// let running = tracker.get_running().await;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ResourceTracker {
+    resources: Arc<Mutex<HashMap<ResourceId, TrackedResource>>>,
+    store: Option<Store>,
+    ledger: Option<Ledger>,
+}
+
+impl ResourceTracker {
+    /// In-memory tracker, for a single process
+    pub fn new() -> Self {
+        Self {
+            resources: Arc::new(Mutex::new(HashMap::new())),
+            store: None,
+            ledger: None,
+        }
+    }
+
+    /// Tracker backed by a JSON file shared with other runctl processes
+    ///
+    /// Nothing is read until the tracker is first used.
+    pub fn persistent(path: impl Into<PathBuf>) -> Self {
+        Self {
+            resources: Arc::new(Mutex::new(HashMap::new())),
+            store: Some(Store {
+                path: path.into(),
+                loaded: std::sync::Mutex::new(None),
+            }),
+            ledger: None,
+        }
+    }
+
+    /// Also record lifecycle changes and hourly cost snapshots in `ledger`
+    pub fn with_ledger(mut self, ledger: Ledger) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+
+    /// Append a ledger entry for `resource`; ledger failures never fail the change
+    fn log_event(&self, resource: &TrackedResource, event: LedgerEvent) {
+        if let Some(ledger) = &self.ledger {
+            let entry = LedgerEntry::from_tracked(resource, event, Utc::now());
+            if let Err(e) = ledger.append(&[entry]) {
+                warn!("Failed to write cost ledger: {}", e);
+            }
+        }
+    }
+
+    /// Snapshot running resources in the ledger, at most once per hour each
+    fn log_snapshots<'a>(&self, resources: impl Iterator<Item = &'a TrackedResource>) {
+        if let Some(ledger) = &self.ledger {
+            let resources: Vec<TrackedResource> = resources.cloned().collect();
+            if let Err(e) = ledger.record_snapshots(&resources, Utc::now()) {
+                warn!("Failed to write cost ledger: {}", e);
+            }
+        }
+    }
+
+    /// File this tracker persists to, if any
+    pub fn store_path(&self) -> Option<&Path> {
+        self.store.as_ref().map(|s| s.path.as_path())
+    }
+
+    /// Pick up changes other processes wrote since the last load
+    fn refresh(&self, resources: &mut HashMap<ResourceId, TrackedResource>) {
+        if let Some(store) = &self.store {
+            if store.changed_on_disk() {
+                *resources = store.load();
+            }
+        }
+    }
+
+    /// Lock the store and load its latest state before a change
+    async fn begin_change(
+        &self,
+        resources: &mut HashMap<ResourceId, TrackedResource>,
+    ) -> Result<Option<StoreLock>> {
+        let Some(store) = &self.store else {
+            return Ok(None);
+        };
+        let lock = StoreLock::acquire(&lock_path(&store.path)).await?;
+        *resources = store.load();
+        Ok(Some(lock))
+    }
+
+    /// Write a change made under `begin_change`
+    fn commit(&self, resources: &HashMap<ResourceId, TrackedResource>) -> Result<()> {
+        match &self.store {
+            Some(store) => store.save(resources),
+            None => Ok(()),
+        }
+    }
+
+    /// Register a new resource
+    ///
+    /// The resource's accumulated_cost will be automatically calculated
+    /// when it is accessed via get_running(), get_by_id(), etc.
+    pub async fn register(&self, status: ResourceStatus) -> Result<()> {
+        let mut resources = self.resources.lock().await;
+        let _lock = self.begin_change(&mut resources).await?;
+
+        if resources.contains_key(&status.id) {
+            return Err(TrainctlError::ResourceExists {
+                resource_type: "resource".to_string(),
+                resource_id: status.id.clone(),
+            });
+        }
+
+        // Convert tags from Vec<(String, String)> to HashMap
+        // Note: We clone here because status.tags is needed for ResourceStatus.
+        // The optimization to use into_iter() would require restructuring to extract
+        // tags first, which adds complexity. Cloning is acceptable here since tags
+        // are typically small (<15 items based on research).
+        let tags: HashMap<String, String> = status.tags.iter().cloned().collect();
+
+        let mut resource = TrackedResource {
+            status,
+            created_at: Utc::now(),
+            usage_history: Vec::new(),
+            accumulated_cost: 0.0,
+            tags,
+            prior_cost: 0.0,
+        };
+        Self::update_resource_cost(&mut resource);
+        self.log_event(&resource, LedgerEvent::Launched);
+        resources.insert(resource.status.id.clone(), resource);
+
+        self.commit(&resources)
+    }
+
+    /// Update resource status and usage
+    ///
+    /// This adds a usage record to the resource's history.
+    /// Note: This does not update accumulated_cost - that is calculated
+    /// automatically when the resource is accessed via get_running(), get_by_id(), etc.
+    pub async fn update_usage(&self, resource_id: &ResourceId, usage: ResourceUsage) -> Result<()> {
+        let mut resources = self.resources.lock().await;
+        let _lock = self.begin_change(&mut resources).await?;
+
+        let resource =
+            resources
+                .get_mut(resource_id)
+                .ok_or_else(|| TrainctlError::ResourceNotFound {
+                    resource_type: "resource".to_string(),
+                    resource_id: resource_id.clone(),
+                })?;
+
+        resource.usage_history.push(usage);
+
+        // Keep only last 1000 usage records
+        if resource.usage_history.len() > 1000 {
+            resource.usage_history.remove(0);
+        }
+
+        self.commit(&resources)
+    }
+
+    /// Update resource state (e.g., when instance is stopped/started)
+    ///
+    /// Updates the state of a tracked resource. This is useful when resources
+    /// change state outside of the normal sync process.
+    ///
+    /// # Arguments
+    /// * `resource_id` - The ID of the resource to update
+    /// * `new_state` - The new state of the resource
+    ///
+    /// # Errors
+    /// Returns `ResourceNotFound` if the resource doesn't exist in the tracker.
+    pub async fn update_state(
+        &self,
+        resource_id: &ResourceId,
+        new_state: crate::provider::ResourceState,
+    ) -> Result<()> {
+        let mut resources = self.resources.lock().await;
+        let _lock = self.begin_change(&mut resources).await?;
+
+        let resource =
+            resources
+                .get_mut(resource_id)
+                .ok_or_else(|| TrainctlError::ResourceNotFound {
+                    resource_type: "resource".to_string(),
+                    resource_id: resource_id.clone(),
+                })?;
+
+        let was_running = is_running(&resource.status.state);
+        resource.status.state = new_state;
+        // Update cost since state affects cost calculation
+        Self::update_resource_cost(resource);
+        if let Some(event) = transition_event(was_running, &resource.status.state) {
+            self.log_event(resource, event);
+        }
+
+        self.commit(&resources)
+    }
+
+    /// Reconcile a resource with its live provider status
+    ///
+    /// Registers resources that aren't tracked yet. For tracked ones, stops
+    /// and restarts that happened outside runctl are applied to the cost:
+    /// a stop freezes the accumulated cost at `state_changed_at` (or now),
+    /// and a restart carries the cost so far into the new launch time.
+    pub async fn reconcile(
+        &self,
+        live: ResourceStatus,
+        state_changed_at: Option<DateTime<Utc>>,
+    ) -> Result<Reconciliation> {
+        let mut resources = self.resources.lock().await;
+        let _lock = self.begin_change(&mut resources).await?;
+
+        let id = live.id.clone();
+        let was_running = resources.get(&id).map(|r| is_running(&r.status.state));
+        let outcome = match resources.get_mut(&live.id) {
+            Some(tracked) => reconcile_resource(tracked, live, state_changed_at, Utc::now()),
+            None => {
+                let tags: HashMap<String, String> = live.tags.iter().cloned().collect();
+                resources.insert(
+                    live.id.clone(),
+                    TrackedResource {
+                        status: live,
+                        created_at: Utc::now(),
+                        usage_history: Vec::new(),
+                        accumulated_cost: 0.0,
+                        tags,
+                        prior_cost: 0.0,
+                    },
+                );
+                Reconciliation::Registered
+            }
+        };
+
+        if let Some(resource) = resources.get_mut(&id) {
+            let event = match outcome {
+                Reconciliation::Registered => {
+                    Self::update_resource_cost(resource);
+                    Some(LedgerEvent::Launched)
+                }
+                Reconciliation::Restarted => Some(LedgerEvent::Started),
+                _ => transition_event(was_running.unwrap_or(false), &resource.status.state),
+            };
+            if let Some(event) = event {
+                self.log_event(resource, event);
+            }
+            self.log_snapshots(std::iter::once(&*resource));
+        }
+
+        // Reading the same state again shouldn't rewrite a shared store
+        if outcome != Reconciliation::Unchanged {
+            self.commit(&resources)?;
+        }
+        Ok(outcome)
+    }
+
+    /// Calculate accumulated cost for a resource based on launch time
+    ///
+    /// For running resources, calculates cost from launch_time to now.
+    /// For stopped/terminated resources, preserves the existing accumulated_cost
+    /// (doesn't reset to 0.0) since those costs were already incurred.
+    fn calculate_accumulated_cost_for_resource(resource: &TrackedResource) -> f64 {
+        use crate::utils::calculate_accumulated_cost;
+        if matches!(
+            resource.status.state,
+            ResourceState::Running | ResourceState::Starting
+        ) {
+            // Running: earlier periods plus launch_time to now
+            resource.prior_cost
+                + calculate_accumulated_cost(
+                    resource.status.cost_per_hour,
+                    resource.status.launch_time,
+                )
+        } else {
+            // Stopped/Terminated: preserve existing accumulated cost
+            // (don't reset to 0.0 - those costs were already incurred)
+            resource.accumulated_cost
+        }
+    }
+
+    /// Update accumulated cost for a resource in-place
+    fn update_resource_cost(resource: &mut TrackedResource) {
+        resource.accumulated_cost = Self::calculate_accumulated_cost_for_resource(resource);
+    }
+
+    /// Get all running resources with automatically updated costs
+    ///
+    /// The accumulated_cost for each resource is recalculated based on
+    /// launch time and current time before returning.
+    pub async fn get_running(&self) -> Vec<TrackedResource> {
+        let mut resources = self.resources.lock().await;
+        self.refresh(&mut resources);
+        resources
+            .values_mut()
+            .filter(|r| {
+                matches!(
+                    r.status.state,
+                    ResourceState::Running | ResourceState::Starting
+                )
+            })
+            .map(|r| {
+                // Update accumulated cost before returning
+                Self::update_resource_cost(r);
+                r.clone()
+            })
+            .collect()
+    }
+
+    /// Get every tracked resource, whatever its state, with updated costs
+    pub async fn get_all(&self) -> Vec<TrackedResource> {
+        let mut resources = self.resources.lock().await;
+        self.refresh(&mut resources);
+        resources
+            .values_mut()
+            .map(|r| {
+                Self::update_resource_cost(r);
+                r.clone()
+            })
+            .collect()
+    }
+
+    /// Get total cost of all resources with automatically updated costs
+    ///
+    /// All resource costs are recalculated before summing.
+    pub async fn get_total_cost(&self) -> f64 {
+        let mut resources = self.resources.lock().await;
+        self.refresh(&mut resources);
+        resources
+            .values_mut()
+            .map(|r| {
+                // Update accumulated cost before summing
+                Self::update_resource_cost(r);
+                r.accumulated_cost
+            })
+            .sum()
+    }
+
+    /// Refresh costs for all resources
+    ///
+    /// Manually recalculates accumulated_cost for all resources.
+    /// This is useful for periodic updates or before generating reports.
+    /// Note: Costs are automatically updated when accessing resources via
+    /// get_running(), get_by_id(), etc., so this is usually not necessary.
+    pub async fn refresh_costs(&self) {
+        let mut resources = self.resources.lock().await;
+        self.refresh(&mut resources);
+        for resource in resources.values_mut() {
+            Self::update_resource_cost(resource);
+        }
+        self.log_snapshots(resources.values());
+    }
+
+    /// Get resources by tag with automatically updated costs
+    ///
+    /// The accumulated_cost for each matching resource is recalculated.
+    pub async fn get_by_tag(&self, key: &str, value: &str) -> Vec<TrackedResource> {
+        let mut resources = self.resources.lock().await;
+        self.refresh(&mut resources);
+        resources
+            .values_mut()
+            .filter(|r| r.tags.get(key).map(|v| v == value).unwrap_or(false))
+            .map(|r| {
+                // Update accumulated cost before returning
+                Self::update_resource_cost(r);
+                r.clone()
+            })
+            .collect()
+    }
+
+    /// Get resource by ID with automatically updated cost
+    ///
+    /// The accumulated_cost is recalculated based on launch time and current time.
+    pub async fn get_by_id(&self, resource_id: &ResourceId) -> Option<TrackedResource> {
+        let mut resources = self.resources.lock().await;
+        self.refresh(&mut resources);
+        resources.get_mut(resource_id).map(|r| {
+            // Update accumulated cost before returning
+            Self::update_resource_cost(r);
+            r.clone()
+        })
+    }
+
+    /// Check if resource exists
+    pub async fn exists(&self, resource_id: &ResourceId) -> bool {
+        let mut resources = self.resources.lock().await;
+        self.refresh(&mut resources);
+        resources.contains_key(resource_id)
+    }
+
+    /// Remove resource (after cleanup)
+    pub async fn remove(&self, resource_id: &ResourceId) -> Result<()> {
+        let mut resources = self.resources.lock().await;
+        let _lock = self.begin_change(&mut resources).await?;
+        let mut resource =
+            resources
+                .remove(resource_id)
+                .ok_or_else(|| TrainctlError::ResourceNotFound {
+                    resource_type: "resource".to_string(),
+                    resource_id: resource_id.clone(),
+                })?;
+        // Keep the final cost in the ledger unless termination was already logged
+        if !matches!(resource.status.state, ResourceState::Terminated) {
+            Self::update_resource_cost(&mut resource);
+            self.log_event(&resource, LedgerEvent::Terminated);
+        }
+        self.commit(&resources)
+    }
+}
+
+impl Default for ResourceTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// On-disk format of the tracker store
+#[derive(Serialize, Deserialize)]
+struct StoreFile<T> {
+    version: u32,
+    resources: Vec<T>,
+}
+
+/// JSON file backing a persistent tracker
+struct Store {
+    path: PathBuf,
+    /// Size and mtime of the file when last loaded or written
+    loaded: std::sync::Mutex<Option<(u64, SystemTime)>>,
+}
+
+fn lock_path(path: &Path) -> PathBuf {
+    sibling(path, "lock")
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    sibling(path, "bak")
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    sibling(path, "tmp")
+}
+
+/// `resources.json` -> `resources.json.<suffix>`
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+fn fingerprint(path: &Path) -> Option<(u64, SystemTime)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
+/// Parse a store file, skipping entries that don't deserialize
+///
+/// Returns the resources and the number of entries skipped.
+fn parse_store(content: &str) -> Result<(HashMap<ResourceId, TrackedResource>, usize)> {
+    let file: StoreFile<serde_json::Value> = serde_json::from_str(content)?;
+    let total = file.resources.len();
+    let resources: HashMap<ResourceId, TrackedResource> = file
+        .resources
+        .into_iter()
+        .filter_map(|value| serde_json::from_value::<TrackedResource>(value).ok())
+        .map(|r| (r.status.id.clone(), r))
+        .collect();
+    let skipped = total - resources.len();
+    Ok((resources, skipped))
+}
+
+fn read_store(path: &Path) -> Result<(HashMap<ResourceId, TrackedResource>, usize)> {
+    parse_store(&fs::read_to_string(path)?)
+}
+
+impl Store {
+    fn changed_on_disk(&self) -> bool {
+        let current = fingerprint(&self.path);
+        let loaded = self.loaded.lock().map(|l| *l).unwrap_or(None);
+        current.is_some() && current != loaded
+    }
+
+    /// Latest state on disk, recovering from the backup if the file is damaged
+    fn load(&self) -> HashMap<ResourceId, TrackedResource> {
+        let fingerprint_now = fingerprint(&self.path);
+        let resources = if fingerprint_now.is_none() {
+            HashMap::new()
+        } else {
+            match read_store(&self.path) {
+                Ok((resources, skipped)) => {
+                    if skipped > 0 {
+                        warn!(
+                            "Skipped {} unreadable entries in {}; run `runctl resources tracker repair`",
+                            skipped,
+                            self.path.display()
+                        );
+                    }
+                    resources
+                }
+                Err(e) => {
+                    warn!(
+                        "Resource tracker file {} is damaged ({}); using {}",
+                        self.path.display(),
+                        e,
+                        backup_path(&self.path).display()
+                    );
+                    read_store(&backup_path(&self.path))
+                        .map(|(resources, _)| resources)
+                        .unwrap_or_default()
+                }
+            }
+        };
+        if let Ok(mut loaded) = self.loaded.lock() {
+            *loaded = fingerprint_now;
+        }
+        resources
+    }
+
+    /// Atomically replace the file; the caller holds the store lock
+    fn save(&self, resources: &HashMap<ResourceId, TrackedResource>) -> Result<()> {
+        write_store(&self.path, resources)?;
+        if let Ok(mut loaded) = self.loaded.lock() {
+            *loaded = fingerprint(&self.path);
+        }
+        Ok(())
+    }
+}
+
+fn write_store(path: &Path, resources: &HashMap<ResourceId, TrackedResource>) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        crate::utils::ensure_dir(parent)?;
+    }
+    let mut sorted: Vec<&TrackedResource> = resources.values().collect();
+    sorted.sort_by(|a, b| a.status.id.cmp(&b.status.id));
+    let content = serde_json::to_string_pretty(&StoreFile {
+        version: STORE_VERSION,
+        resources: sorted,
+    })?;
+
+    let tmp = temp_path(path);
+    let mut file = fs::File::create(&tmp)?;
+    file.write_all(content.as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+
+    // Only a readable file is worth keeping as the backup
+    if read_store(path).is_ok() {
+        fs::copy(path, backup_path(path))?;
+    }
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Exclusive lock on a store, released on drop (or by the OS if the
+/// holder dies)
+struct StoreLock {
+    file: fs::File,
+}
+
+impl StoreLock {
+    /// Take the lock if no other process holds it
+    fn try_acquire(path: &Path) -> std::io::Result<Option<Self>> {
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        match file.try_lock_exclusive() {
+            Ok(()) => Ok(Some(Self { file })),
+            Err(e) if e.kind() == fs2::lock_contended_error().kind() => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn acquire(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            crate::utils::ensure_dir(parent)?;
+        }
+        let started = std::time::Instant::now();
+        loop {
+            if let Some(lock) = Self::try_acquire(path)? {
+                return Ok(lock);
+            }
+            if started.elapsed() >= LOCK_TIMEOUT {
+                return Err(TrainctlError::Io(std::io::Error::other(format!(
+                    "Resource tracker is locked by another runctl process ({})",
+                    path.display()
+                ))));
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+}
+
+impl Drop for StoreLock {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+/// What `repair_store` found and changed
+#[derive(Debug, Default, Serialize)]
+pub struct RepairReport {
+    pub path: PathBuf,
+    /// Resources in the repaired store
+    pub resources: usize,
+    /// `backup`, `temp` or `empty` when the main file could not be read
+    pub recovered_from: Option<String>,
+    /// Where the damaged file was moved
+    pub damaged_file: Option<PathBuf>,
+    /// Entries that did not parse and were dropped
+    pub dropped_entries: usize,
+    /// A temp file left by an interrupted write was removed
+    pub temp_file_removed: bool,
+    pub dry_run: bool,
+}
+
+impl RepairReport {
+    /// Nothing needed fixing
+    pub fn is_healthy(&self) -> bool {
+        self.recovered_from.is_none() && self.dropped_entries == 0 && !self.temp_file_removed
+    }
+}
+
+/// Check a tracker store and fix what a crash or concurrent write left behind
+///
+/// Removes temp files, recovers a damaged file from the backup (or the last
+/// temp file, or else starts empty) and drops entries that don't parse. The
+/// damaged file is kept next to the store as `<name>.damaged-<timestamp>`.
+/// Fails if another process holds the lock.
+pub async fn repair_store(path: &Path, dry_run: bool) -> Result<RepairReport> {
+    let mut report = RepairReport {
+        path: path.to_path_buf(),
+        dry_run,
+        ..Default::default()
+    };
+
+    let lock = lock_path(path);
+    let _guard = if dry_run {
+        None
+    } else {
+        match StoreLock::try_acquire(&lock)? {
+            Some(guard) => Some(guard),
+            None => {
+                return Err(TrainctlError::Validation {
+                    field: "lock".to_string(),
+                    reason: format!(
+                        "{} is held by another runctl process; stop it or retry",
+                        lock.display()
+                    ),
+                })
+            }
+        }
+    };
+
+    let tmp = temp_path(path);
+    let main = if path.exists() {
+        Some(read_store(path))
+    } else {
+        None
+    };
+    let repaired = match main {
+        // No store yet: nothing to repair
+        None => None,
+        Some(Ok((resources, dropped))) => {
+            report.dropped_entries = dropped;
+            Some(resources)
+        }
+        Some(Err(_)) => {
+            // The temp file is a write that never committed; use it only as a last resort
+            let recovered = [("backup", backup_path(path)), ("temp", tmp.clone())]
+                .into_iter()
+                .find_map(|(source, candidate)| {
+                    read_store(&candidate)
+                        .ok()
+                        .map(|(resources, dropped)| (source, resources, dropped))
+                });
+            match recovered {
+                Some((source, resources, dropped)) => {
+                    report.recovered_from = Some(source.to_string());
+                    report.dropped_entries = dropped;
+                    Some(resources)
+                }
+                None => {
+                    report.recovered_from = Some("empty".to_string());
+                    Some(HashMap::new())
+                }
+            }
+        }
+    };
+
+    let rewrite = report.recovered_from.is_some() || report.dropped_entries > 0;
+    if rewrite && path.exists() {
+        let damaged = sibling(
+            path,
+            &format!("damaged-{}", Utc::now().format("%Y%m%dT%H%M%S")),
+        );
+        if !dry_run {
+            fs::rename(path, &damaged)?;
+        }
+        report.damaged_file = Some(damaged);
+    }
+    if let Some(resources) = &repaired {
+        report.resources = resources.len();
+        if rewrite && !dry_run {
+            write_store(path, resources)?;
+        }
+    }
+    if tmp.exists() {
+        report.temp_file_removed = true;
+        if !dry_run {
+            fs::remove_file(&tmp)?;
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,141 @@
+//! Listing EC2 instances across `[accounts]`
+//!
+//! Without `[accounts]` in the config, listings describe the account of the
+//! ambient credentials, through the shared client and the prefetched
+//! snapshot. With accounts configured, each entry gets its own SDK config
+//! (a named profile, an assumed role, or both) and all of them are described
+//! concurrently. An account that can't be listed (an expired SSO session, a
+//! role that can't be assumed) is reported and skipped, so it doesn't hide
+//! the others.
+//!
+//! Instances found this way are reconciled into the tracker with their
+//! account in `runctl:account`, which account budgets and `costs report
+//! --group-by account` read.
+
+use super::aws::{describe_instances, ec2_client, sync_tracker_with_reservations};
+use crate::aws_utils::EnvCache;
+use crate::config::{AccountConfig, Config};
+use crate::error::Result;
+use aws_config::{BehaviorVersion, SdkConfig};
+use aws_sdk_ec2::operation::describe_instances::DescribeInstancesOutput;
+use aws_sdk_ec2::Client as Ec2Client;
+
+/// Session name shown in CloudTrail for assumed roles
+const SESSION_NAME: &str = "runctl";
+
+/// Instances of one account, or why they couldn't be listed
+pub(super) struct AccountInstances {
+    /// `[accounts]` entry; `None` when no accounts are configured
+    pub account: Option<String>,
+    pub result: Result<DescribeInstancesOutput>,
+}
+
+/// SDK config for one `[accounts]` entry
+///
+/// Loading doesn't contact AWS; a bad profile or role shows up on the first
+/// call made with the config.
+pub(crate) async fn load_sdk_config(account: &AccountConfig) -> SdkConfig {
+    let mut loader = aws_config::defaults(BehaviorVersion::latest());
+    if let Some(profile) = &account.profile {
+        loader = loader.profile_name(profile);
+    }
+    if let Some(region) = &account.region {
+        loader = loader.region(aws_config::Region::new(region.clone()));
+    }
+    let base = loader.load().await;
+    let Some(role_arn) = &account.role_arn else {
+        return base;
+    };
+
+    let mut role = aws_config::sts::AssumeRoleProvider::builder(role_arn)
+        .session_name(SESSION_NAME)
+        .configure(&base);
+    if let Some(external_id) = &account.external_id {
+        role = role.external_id(external_id);
+    }
+    let credentials = role.build().await;
+    let mut loader =
+        aws_config::defaults(BehaviorVersion::latest()).credentials_provider(credentials);
+    if let Some(region) = base.region().cloned() {
+        loader = loader.region(region);
+    }
+    loader.load().await
+}
+
+/// SDK configs of every configured account, loaded once per process
+///
+/// Watch mode refreshes reuse them, and with them the cached (assumed role)
+/// credentials. They are reloaded when the ambient AWS profile changes,
+/// which accounts without their own `profile` resolve through.
+async fn sdk_configs(config: &Config) -> Vec<(String, SdkConfig)> {
+    static CONFIGS: EnvCache<Vec<(String, SdkConfig)>> = EnvCache::new();
+    CONFIGS
+        .get_or_init(|| async {
+            let mut configs = Vec::with_capacity(config.accounts.len());
+            for (name, account) in &config.accounts {
+                configs.push((name.clone(), load_sdk_config(account).await));
+            }
+            configs
+        })
+        .await
+}
+
+/// SDK config to act on a resource listed from `account`
+///
+/// `None` (or an unknown name) means the ambient credentials.
+pub(crate) async fn sdk_config_for(config: &Config, account: Option<&str>) -> SdkConfig {
+    if let Some(account) = account {
+        if let Some((_, sdk_config)) = sdk_configs(config)
+            .await
+            .into_iter()
+            .find(|(name, _)| name == account)
+        {
+            return sdk_config;
+        }
+    }
+    crate::aws_utils::shared_sdk_config().await
+}
+
+/// Describe instances in every account and reconcile them into the tracker
+///
+/// With no `[accounts]`, the single result is the ambient account's.
+pub(super) async fn describe(config: &Config) -> Vec<AccountInstances> {
+    let tracker = config.resource_tracker.as_deref();
+    if config.accounts.is_empty() {
+        let result = super::prefetch::instances(&ec2_client().await).await;
+        if let (Ok(response), Some(tracker)) = (&result, tracker) {
+            sync_tracker_with_reservations(response.reservations(), tracker, None).await;
+        }
+        return vec![AccountInstances {
+            account: None,
+            result,
+        }];
+    }
+
+    let configs = sdk_configs(config).await;
+    let fetches = configs.iter().map(|(name, sdk_config)| async move {
+        let client = Ec2Client::new(sdk_config);
+        let result = describe_instances(&client).await;
+        if let Ok(response) = &result {
+            crate::aws_pricing::prefetch_reservations(&client, response.reservations()).await;
+        }
+        AccountInstances {
+            account: Some(name.clone()),
+            result,
+        }
+    });
+    let listings = futures::future::join_all(fetches).await;
+    if let Some(tracker) = tracker {
+        for listing in &listings {
+            if let Ok(response) = &listing.result {
+                sync_tracker_with_reservations(
+                    response.reservations(),
+                    tracker,
+                    listing.account.as_deref(),
+                )
+                .await;
+            }
+        }
+    }
+    listings
+}
@@ -1,44 +1,29 @@
 //! AWS resource listing and management
 
-use crate::aws::ec2_instance_to_resource_status;
+use crate::aws_utils::EnvCache;
 use crate::config::Config;
 use crate::error::{Result, TrainctlError};
-use crate::resource_tracking::ResourceTracker;
+use crate::resource_tracking::{Reconciliation, ResourceTracker};
 use crate::retry::{ExponentialBackoffPolicy, RetryPolicy};
 use crate::utils::{format_runtime, is_old_instance};
-use aws_config::BehaviorVersion;
+use aws_sdk_ec2::operation::describe_instances::DescribeInstancesOutput;
+use aws_sdk_ec2::types::Reservation;
 use aws_sdk_ec2::Client as Ec2Client;
 use comfy_table::{Cell, Table};
 use console::{style, Style};
 use std::collections::HashMap;
-use tracing::info;
+use std::time::Instant;
+use tracing::{debug, info};
 
 use super::export;
 use super::json;
 use super::local;
-use super::runpod;
+use super::providers;
 use super::types::{InstanceInfo, ListAwsInstancesOptions, ListResourcesOptions};
 
-/// Sync ResourceTracker with current AWS state
-///
-/// This function queries AWS for all EC2 instances and updates the ResourceTracker
-/// to match the current state. It's useful for:
-/// - Initializing the tracker with existing resources
-/// - Periodically syncing tracker state with actual AWS state
-/// - Recovering from tracker state inconsistencies
-///
-/// # Arguments
-/// * `client` - AWS EC2 client
-/// * `tracker` - ResourceTracker to sync
-///
-/// # Errors
-/// Returns an error if AWS API calls fail or if resource conversion fails.
-pub(crate) async fn sync_resource_tracker_with_aws(
-    client: &Ec2Client,
-    tracker: &ResourceTracker,
-) -> Result<()> {
-    // Use retry logic for describe_instances
-    let response = ExponentialBackoffPolicy::for_cloud_api()
+/// Describe all EC2 instances, with retries
+pub(crate) async fn describe_instances(client: &Ec2Client) -> Result<DescribeInstancesOutput> {
+    ExponentialBackoffPolicy::for_cloud_api()
         .execute_with_retry(|| async {
             client
                 .describe_instances()
@@ -46,46 +31,45 @@ pub(crate) async fn sync_resource_tracker_with_aws(
                 .await
                 .map_err(|e| TrainctlError::Aws(format!("Failed to list EC2 instances: {}", e)))
         })
-        .await?;
+        .await
+}
 
-    for reservation in response.reservations() {
-        for instance in reservation.instances() {
-            if let Some(instance_id) = instance.instance_id() {
-                // Use helper function from aws module to avoid duplication
-                let instance_id_str = instance_id.to_string();
-                match ec2_instance_to_resource_status(instance, &instance_id_str) {
-                    Ok(resource_status) => {
-                        let instance_id_string = instance_id_str.clone();
-                        if tracker.exists(&instance_id_string).await {
-                            // Use update_state to update the resource state
-                            if let Err(e) = tracker
-                                .update_state(&instance_id_string, resource_status.state)
-                                .await
-                            {
-                                info!("Failed to update resource state: {}", e);
-                            } else {
-                                info!("Synced resource {} to ResourceTracker", instance_id);
-                            }
-                        } else if let Err(e) = tracker.register(resource_status).await {
-                            // Ignore errors for resources that already exist (race condition)
-                            if !e.to_string().contains("already exists") {
-                                info!("Failed to sync resource {}: {}", instance_id, e);
-                            }
-                        } else {
-                            info!("Synced resource {} to ResourceTracker", instance_id);
-                        }
-                    }
-                    Err(e) => {
-                        info!(
-                            "Failed to convert instance {} to ResourceStatus: {}",
-                            instance_id, e
-                        );
-                    }
-                }
-            }
+/// Update the ResourceTracker from an already fetched describe_instances response
+///
+/// `account` is the `[accounts]` entry the response came from, if any.
+pub(super) async fn sync_tracker_with_reservations(
+    reservations: &[Reservation],
+    tracker: &ResourceTracker,
+    account: Option<&str>,
+) {
+    for instance in reservations.iter().flat_map(|r| r.instances()) {
+        match crate::aws::reconcile_account_instance(tracker, instance, account).await {
+            Some(Reconciliation::Stopped) => info!(
+                "{} was stopped outside runctl; froze its tracked cost",
+                instance.instance_id().unwrap_or("unknown")
+            ),
+            Some(Reconciliation::Restarted) => info!(
+                "{} was restarted outside runctl; carried its earlier cost over",
+                instance.instance_id().unwrap_or("unknown")
+            ),
+            _ => {}
         }
     }
-    Ok(())
+}
+
+/// EC2 client shared by resource listings
+///
+/// Loading the SDK config resolves the region and credentials, which is a
+/// large part of a listing's latency; watch mode reuses the client (and its
+/// cached credentials) across refreshes, until the AWS profile changes.
+pub(crate) async fn ec2_client() -> Ec2Client {
+    static CLIENT: EnvCache<Ec2Client> = EnvCache::new();
+    CLIENT
+        .get_or_init(|| async {
+            let aws_config = crate::aws_utils::shared_sdk_config().await;
+            Ec2Client::new(&aws_config)
+        })
+        .await
 }
 
 /// List resources across platforms
@@ -111,7 +95,18 @@ pub async fn list_resources(options: ListResourcesOptions, config: &Config) -> R
     println!("RESOURCE OVERVIEW");
     println!("{}", "=".repeat(80));
 
-    if options.platform == "all" || options.platform == "aws" {
+    // Fetch every platform at once and print each section, in order, as soon
+    // as its data is in, so a slow EC2 call no longer holds up other providers
+    let started = Instant::now();
+    let wants = |platform: &str| options.platform == "all" || options.platform == platform;
+    let fetches = providers::spawn_fetches(config, &options.platform).await?;
+    let processes = if wants("local") {
+        Some(tokio::task::spawn_blocking(local::find_training_processes))
+    } else {
+        None
+    };
+
+    if wants("aws") {
         let aws_options = ListAwsInstancesOptions {
             detailed: options.detailed,
             format: options.format.clone(),
@@ -121,60 +116,87 @@ pub async fn list_resources(options: ListResourcesOptions, config: &Config) -> R
             show_terminated: options.show_terminated,
             project_filter: options.project_filter.clone(),
             user_filter: options.user_filter.clone(),
+            account_filter: options.account_filter.clone(),
         };
-        list_aws_instances(aws_options, config).await?;
+        println!("\nAWS EC2 INSTANCES:");
+        println!("{}", "-".repeat(80));
+        let listing = fetch_aws_instances(config).await?;
+        debug!("AWS section ready after {:?}", started.elapsed());
+        render_aws_instances(&aws_options, &listing).await?;
     }
 
-    if options.platform == "all" || options.platform == "runpod" {
-        runpod::list_runpod_pods(options.detailed, config).await?;
+    for (name, fetch) in fetches {
+        let listing = fetch
+            .await
+            .map_err(|e| TrainctlError::Io(std::io::Error::other(e)))?;
+        debug!("{} section ready after {:?}", name, started.elapsed());
+        providers::render_provider_resources(&name, &listing, options.detailed);
     }
 
-    if options.platform == "all" || options.platform == "local" {
-        local::list_local_processes(options.detailed).await?;
+    if let Some(processes) = processes {
+        let processes = processes
+            .await
+            .map_err(|e| TrainctlError::Io(std::io::Error::other(e)))?;
+        debug!("Local section ready after {:?}", started.elapsed());
+        local::render_local_processes(&processes, options.detailed);
     }
 
     Ok(())
 }
 
-/// List AWS EC2 instances
-async fn list_aws_instances(options: ListAwsInstancesOptions, config: &Config) -> Result<()> {
-    let _section_style = Style::new().bold().yellow();
-    println!("\nAWS EC2 INSTANCES:");
-    println!("{}", "-".repeat(80));
-
-    let aws_config = aws_config::load_defaults(BehaviorVersion::latest()).await;
-    let client = Ec2Client::new(&aws_config);
+/// EC2 instances and running totals for the AWS section
+#[derive(Default)]
+pub(super) struct AwsListing {
+    pub(super) instances: Vec<InstanceInfo>,
+    total_instances: usize,
+    running_instances: usize,
+    total_hourly_cost: f64,
+    total_accumulated_cost: f64,
+    old_instances: usize,
+    /// `[accounts]` entries that couldn't be listed, with the error
+    pub(super) failed_accounts: Vec<(String, String)>,
+}
 
-    // Sync ResourceTracker with current AWS state if available
-    if let Some(tracker) = &config.resource_tracker {
-        if let Err(e) = sync_resource_tracker_with_aws(&client, tracker).await {
-            info!("Failed to sync ResourceTracker: {}", e);
-        }
+/// Fetch EC2 instances with their costs
+///
+/// One describe_instances call per account serves both the ResourceTracker
+/// sync and the listing. Without `[accounts]`, failing to list is an error;
+/// with them, failed accounts are recorded in the listing instead.
+pub(super) async fn fetch_aws_instances(config: &Config) -> Result<AwsListing> {
+    let mut listing = AwsListing::default();
+    for described in super::accounts::describe(config).await {
+        let response = match (described.result, described.account.as_deref()) {
+            (Ok(response), _) => response,
+            (Err(e), None) => return Err(e),
+            (Err(e), Some(account)) => {
+                listing
+                    .failed_accounts
+                    .push((account.to_string(), e.to_string()));
+                continue;
+            }
+        };
+        add_instances(
+            &mut listing,
+            &response,
+            described.account.as_deref(),
+            config,
+        )
+        .await;
     }
+    Ok(listing)
+}
 
-    // Use retry logic for describe_instances
-    let response = ExponentialBackoffPolicy::for_cloud_api()
-        .execute_with_retry(|| async {
-            client
-                .describe_instances()
-                .send()
-                .await
-                .map_err(|e| TrainctlError::Aws(format!("Failed to list EC2 instances: {}", e)))
-        })
-        .await?;
-
-    // Collect all instance info
-    let mut instances: Vec<InstanceInfo> = Vec::new();
-    let mut total_instances = 0;
-    let mut running_instances = 0;
-    let mut total_hourly_cost = 0.0;
-    let mut total_accumulated_cost = 0.0;
-    let mut old_instances = 0;
-
+/// Add one account's described instances to the listing
+async fn add_instances(
+    listing: &mut AwsListing,
+    response: &DescribeInstancesOutput,
+    account: Option<&str>,
+    config: &Config,
+) {
     let reservations = response.reservations();
     for reservation in reservations {
         for instance in reservation.instances() {
-            total_instances += 1;
+            listing.total_instances += 1;
             let state_str = instance
                 .state()
                 .and_then(|s| s.name())
@@ -182,7 +204,7 @@ async fn list_aws_instances(options: ListAwsInstancesOptions, config: &Config) -
                 .unwrap_or_else(|| "unknown".to_string());
 
             if state_str == "running" {
-                running_instances += 1;
+                listing.running_instances += 1;
             }
 
             let instance_id = instance.instance_id().unwrap_or("unknown").to_string();
@@ -205,8 +227,8 @@ async fn list_aws_instances(options: ListAwsInstancesOptions, config: &Config) -
             .await;
 
             if state_str == "running" {
-                total_hourly_cost += cost_per_hour;
-                total_accumulated_cost += accumulated_cost;
+                listing.total_hourly_cost += cost_per_hour;
+                listing.total_accumulated_cost += accumulated_cost;
             }
 
             // Check if spot instance
@@ -215,6 +237,7 @@ async fn list_aws_instances(options: ListAwsInstancesOptions, config: &Config) -
 
             // Get IP addresses
             let public_ip = instance.public_ip_address().map(|s| s.to_string());
+            let public_ipv6 = crate::aws::instance_ipv6(instance).map(str::to_string);
             let private_ip = instance.private_ip_address().map(|s| s.to_string());
 
             // Get tags
@@ -228,10 +251,10 @@ async fn list_aws_instances(options: ListAwsInstancesOptions, config: &Config) -
             // Check if old instance
             let is_old = is_old_instance(launch_time, 24);
             if is_old && state_str == "running" {
-                old_instances += 1;
+                listing.old_instances += 1;
             }
 
-            instances.push(InstanceInfo {
+            listing.instances.push(InstanceInfo {
                 id: instance_id,
                 instance_type: instance_type_str,
                 state: state_str,
@@ -242,14 +265,21 @@ async fn list_aws_instances(options: ListAwsInstancesOptions, config: &Config) -
                 is_spot,
                 _spot_request_id: spot_request_id,
                 public_ip,
+                public_ipv6,
                 private_ip,
                 tags,
                 is_old,
+                account: account.map(str::to_string),
             });
         }
     }
+}
 
-    // Apply filtering
+/// Instances matching the listing filters, sorted and limited as requested
+pub(super) fn select_instances<'a>(
+    options: &ListAwsInstancesOptions,
+    instances: &'a [InstanceInfo],
+) -> Vec<&'a InstanceInfo> {
     let mut filtered_instances: Vec<&InstanceInfo> = instances.iter().collect();
 
     // Filter by project
@@ -270,6 +300,11 @@ async fn list_aws_instances(options: ListAwsInstancesOptions, config: &Config) -
         });
     }
 
+    // Filter by account
+    if let Some(account) = &options.account_filter {
+        filtered_instances.retain(|inst| inst.account.as_deref() == Some(account.as_str()));
+    }
+
     // Filter by state
     if options.filter != "all" {
         filtered_instances.retain(|inst| {
@@ -329,6 +364,24 @@ async fn list_aws_instances(options: ListAwsInstancesOptions, config: &Config) -
         filtered_instances.truncate(limit_val);
     }
 
+    filtered_instances
+}
+
+/// Print the AWS section from fetched instances
+async fn render_aws_instances(
+    options: &ListAwsInstancesOptions,
+    listing: &AwsListing,
+) -> Result<()> {
+    for (account, error) in &listing.failed_accounts {
+        println!(
+            "  {} account '{}' not listed: {}",
+            style("WARNING:").yellow().bold(),
+            account,
+            error
+        );
+    }
+    let filtered_instances = select_instances(options, &listing.instances);
+
     // Table format
     if options.format == "table" {
         return display_table_format(&filtered_instances, options.detailed).await;
@@ -416,19 +469,19 @@ async fn list_aws_instances(options: ListAwsInstancesOptions, config: &Config) -
 
                 if uptime_hours > 24 {
                     warnings.push(format!(
-                        "⚠️  Running {} hours (${:.2} accumulated)",
+                        "WARNING: Running {} hours (${:.2} accumulated)",
                         uptime_hours, inst.accumulated_cost
                     ));
                 }
                 if inst.accumulated_cost > 10.0 {
                     warnings.push(format!(
-                        "⚠️  High cost: ${:.2} accumulated",
+                        "WARNING: High cost: ${:.2} accumulated",
                         inst.accumulated_cost
                     ));
                 }
                 if inst.cost_per_hour > 5.0 {
                     warnings.push(format!(
-                        "⚠️  High hourly cost: ${:.4}/hr",
+                        "WARNING: High hourly cost: ${:.4}/hr",
                         inst.cost_per_hour
                     ));
                 }
@@ -462,9 +515,15 @@ async fn list_aws_instances(options: ListAwsInstancesOptions, config: &Config) -
                     }
                 }
 
+                if let Some(account) = &inst.account {
+                    println!("      {} {}", style("Account:").dim(), account);
+                }
                 if let Some(public_ip) = &inst.public_ip {
                     println!("      {} {}", style("Public IP:").dim(), public_ip);
                 }
+                if let Some(public_ipv6) = &inst.public_ipv6 {
+                    println!("      {} {}", style("IPv6:").dim(), public_ipv6);
+                }
                 if let Some(private_ip) = &inst.private_ip {
                     println!("      {} {}", style("Private IP:").dim(), private_ip);
                 }
@@ -515,6 +574,9 @@ async fn list_aws_instances(options: ListAwsInstancesOptions, config: &Config) -
                     if let Some(public) = &inst.public_ip {
                         parts.push(format!("pub:{}", public));
                     }
+                    if let Some(ipv6) = &inst.public_ipv6 {
+                        parts.push(format!("v6:{}", ipv6));
+                    }
                     if let Some(private) = &inst.private_ip {
                         parts.push(format!("priv:{}", private));
                     }
@@ -550,29 +612,32 @@ async fn list_aws_instances(options: ListAwsInstancesOptions, config: &Config) -
                     }
                 }
 
-                // Show key tags in summary (Name, project, runctl tags)
-                if !inst.tags.is_empty() {
-                    let key_tags: Vec<String> = inst
-                        .tags
-                        .iter()
-                        .filter(|(k, _)| {
-                            k == "Name"
-                                || k == "runctl:project"
-                                || k == "runctl:created"
-                                || k == "CreatedBy"
-                        })
-                        .take(3)
-                        .map(|(k, v)| {
-                            // Clean up tag keys for display
-                            let display_key = if k == "runctl:project" {
-                                "project"
-                            } else if k == "runctl:created" {
-                                "created"
-                            } else {
-                                k
-                            };
-                            format!("{}={}", display_key, v)
-                        })
+                // Show key tags in summary (account, Name, project, runctl tags)
+                if !inst.tags.is_empty() || inst.account.is_some() {
+                    let account = inst.account.iter().map(|a| format!("account={}", a));
+                    let key_tags: Vec<String> = account
+                        .chain(
+                            inst.tags
+                                .iter()
+                                .filter(|(k, _)| {
+                                    k == "Name"
+                                        || k == "runctl:project"
+                                        || k == "runctl:created"
+                                        || k == "CreatedBy"
+                                })
+                                .take(3)
+                                .map(|(k, v)| {
+                                    // Clean up tag keys for display
+                                    let display_key = if k == "runctl:project" {
+                                        "project"
+                                    } else if k == "runctl:created" {
+                                        "created"
+                                    } else {
+                                        k
+                                    };
+                                    format!("{}={}", display_key, v)
+                                }),
+                        )
                         .collect();
                     if !key_tags.is_empty() {
                         println!("      {}", style(key_tags.join(", ")).cyan());
@@ -583,9 +648,9 @@ async fn list_aws_instances(options: ListAwsInstancesOptions, config: &Config) -
     }
 
     // Summary
-    println!("\n{}", "─".repeat(80));
+    println!("\n{}", crate::output_style::rule(80));
     let total_style = Style::new().bold();
-    let running_style = if running_instances > 0 {
+    let running_style = if listing.running_instances > 0 {
         Style::new().green()
     } else {
         Style::new()
@@ -593,12 +658,12 @@ async fn list_aws_instances(options: ListAwsInstancesOptions, config: &Config) -
     println!(
         "  {} {} instances ({} running)",
         total_style.apply_to("Total:"),
-        total_instances,
-        running_style.apply_to(running_instances)
+        listing.total_instances,
+        running_style.apply_to(listing.running_instances)
     );
 
-    if running_instances > 0 {
-        let cost_style = if total_hourly_cost > 10.0 {
+    if listing.running_instances > 0 {
+        let cost_style = if listing.total_hourly_cost > 10.0 {
             Style::new().red().bold()
         } else {
             Style::new().yellow()
@@ -606,13 +671,13 @@ async fn list_aws_instances(options: ListAwsInstancesOptions, config: &Config) -
         println!(
             "  {} {}  {} {}",
             style("Hourly cost:").dim(),
-            cost_style.apply_to(format!("${:.2}/hour", total_hourly_cost)),
+            cost_style.apply_to(format!("${:.2}/hour", listing.total_hourly_cost)),
             style("Accumulated:").dim(),
-            style(format!("${:.2}", total_accumulated_cost)).yellow()
+            style(format!("${:.2}", listing.total_accumulated_cost)).yellow()
         );
 
         // Project daily/weekly costs
-        let daily_cost = total_hourly_cost * 24.0;
+        let daily_cost = listing.total_hourly_cost * 24.0;
         let weekly_cost = daily_cost * 7.0;
         println!(
             "  {} {}  {} {}",
@@ -623,11 +688,11 @@ async fn list_aws_instances(options: ListAwsInstancesOptions, config: &Config) -
         );
     }
 
-    if old_instances > 0 {
+    if listing.old_instances > 0 {
         println!(
             "  {} {} instance(s) running >24h - consider terminating",
             style("!").red().bold(),
-            old_instances
+            listing.old_instances
         );
     }
 
@@ -638,9 +703,12 @@ async fn list_aws_instances(options: ListAwsInstancesOptions, config: &Config) -
 async fn display_table_format(instances: &[&InstanceInfo], detailed: bool) -> Result<()> {
     let mut table = Table::new();
     // Table uses default styling
+    // Listings from [accounts] get a column naming each instance's account
+    let with_account = instances.iter().any(|inst| inst.account.is_some());
+    let account_cell = |inst: &InstanceInfo| Cell::new(inst.account.as_deref().unwrap_or("-"));
 
     if detailed {
-        table.set_header(vec![
+        let mut header = vec![
             "Instance ID",
             "State",
             "Type",
@@ -650,7 +718,11 @@ async fn display_table_format(instances: &[&InstanceInfo], detailed: bool) -> Re
             "Total",
             "Public IP",
             "Tags",
-        ]);
+        ];
+        if with_account {
+            header.push("Account");
+        }
+        table.set_header(header);
 
         for inst in instances {
             let state_cell = match inst.state.as_str() {
@@ -662,7 +734,11 @@ async fn display_table_format(instances: &[&InstanceInfo], detailed: bool) -> Re
 
             let runtime = inst.runtime.as_deref().unwrap_or("N/A");
             let spot = if inst.is_spot { "SPOT" } else { "ON-DEMAND" };
-            let public_ip = inst.public_ip.as_deref().unwrap_or("-");
+            let public_ip = inst
+                .public_ip
+                .as_deref()
+                .or(inst.public_ipv6.as_deref())
+                .unwrap_or("-");
             // Prioritize Name tag, then show other key tags
             let name_tag = inst
                 .tags
@@ -684,7 +760,7 @@ async fn display_table_format(instances: &[&InstanceInfo], detailed: bool) -> Re
                 format!("{} ({})", name_tag, other_tags)
             };
 
-            table.add_row(vec![
+            let mut row = vec![
                 Cell::new(&inst.id),
                 state_cell,
                 Cell::new(&inst.instance_type),
@@ -694,12 +770,20 @@ async fn display_table_format(instances: &[&InstanceInfo], detailed: bool) -> Re
                 Cell::new(format!("${:.2}", inst.accumulated_cost)),
                 Cell::new(public_ip),
                 Cell::new(&tags),
-            ]);
+            ];
+            if with_account {
+                row.push(account_cell(inst));
+            }
+            table.add_row(row);
         }
     } else {
-        table.set_header(vec![
+        let mut header = vec![
             "Name", "ID", "State", "Type", "Runtime", "Cost/hr", "Total", "IP",
-        ]);
+        ];
+        if with_account {
+            header.push("Account");
+        }
+        table.set_header(header);
 
         for inst in instances {
             if inst.state != "running" {
@@ -708,7 +792,11 @@ async fn display_table_format(instances: &[&InstanceInfo], detailed: bool) -> Re
 
             let state_cell = Cell::new(&inst.state).fg(comfy_table::Color::Green);
             let runtime = inst.runtime.as_deref().unwrap_or("N/A");
-            let ip = inst.public_ip.as_deref().unwrap_or("-");
+            let ip = inst
+                .public_ip
+                .as_deref()
+                .or(inst.public_ipv6.as_deref())
+                .unwrap_or("-");
 
             // Extract Name tag or use instance ID prefix
             let name = inst
@@ -718,7 +806,7 @@ async fn display_table_format(instances: &[&InstanceInfo], detailed: bool) -> Re
                 .map(|(_, v)| v.as_str())
                 .unwrap_or_else(|| &inst.id[..12.min(inst.id.len())]);
 
-            table.add_row(vec![
+            let mut row = vec![
                 Cell::new(name),
                 Cell::new(&inst.id),
                 state_cell,
@@ -727,7 +815,11 @@ async fn display_table_format(instances: &[&InstanceInfo], detailed: bool) -> Re
                 Cell::new(format!("${:.4}", inst.cost_per_hour)),
                 Cell::new(format!("${:.2}", inst.accumulated_cost)),
                 Cell::new(ip),
-            ]);
+            ];
+            if with_account {
+                row.push(account_cell(inst));
+            }
+            table.add_row(row);
         }
     }
 
@@ -0,0 +1,192 @@
+//! Budget status and enforcement (`runctl resources budget`)
+//!
+//! Project budgets come from `[budget.projects]` / `[budget.default]`, account
+//! budgets from `[budget.accounts]`. Both are enforced the same way; stopping
+//! an instance uses the credentials of the account it was listed from.
+
+use crate::config::{BudgetAction, Config};
+use crate::error::Result;
+use crate::notifications::{Notifier, NotifyEvent};
+use crate::provider::ResourceState;
+use crate::resource_tracking::budget::{ProjectBudget, ACCOUNT_TAG};
+use crate::resource_tracking::ResourceTracker;
+use crate::resources::accounts;
+use console::style;
+use std::collections::HashSet;
+use tracing::warn;
+
+/// Show budget status and, with `enforce`, act on exceeded budgets
+pub async fn handle_budget(
+    config: &Config,
+    enforce: bool,
+    watch: bool,
+    interval: u64,
+    output_format: &str,
+) -> Result<()> {
+    if config.budget.is_empty() {
+        println!(
+            "No budgets configured. Add [budget.projects.<name>] or [budget.accounts.<name>] to .runctl.toml."
+        );
+        return Ok(());
+    }
+    let Some(tracker) = &config.resource_tracker else {
+        println!("Resource tracking is disabled; budgets can't be evaluated.");
+        return Ok(());
+    };
+
+    let notifier = Notifier::new(config.notifications.clone());
+    // Projects and accounts already notified, so a watch loop alerts once per breach
+    let mut notified: HashSet<String> = HashSet::new();
+
+    loop {
+        // Describing the accounts syncs the tracker with their instances
+        for listing in accounts::describe(config).await {
+            if let Err(e) = listing.result {
+                warn!(
+                    "Failed to list instances in account '{}': {}",
+                    listing.account.as_deref().unwrap_or("default"),
+                    e
+                );
+            }
+        }
+        let budgets = tracker.evaluate_budgets(&config.budget).await;
+        let account_budgets = tracker.evaluate_account_budgets(&config.budget).await;
+
+        if output_format == "json" && account_budgets.is_empty() {
+            println!("{}", serde_json::to_string_pretty(&budgets)?);
+        } else if output_format == "json" {
+            let report = serde_json::json!({
+                "projects": budgets,
+                "accounts": account_budgets,
+            });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            println!("BUDGETS:");
+            print_budgets(&budgets);
+            print_account_budgets(&account_budgets);
+        }
+
+        // Projects and accounts share the notification set, so label them
+        let scoped: Vec<(String, &ProjectBudget)> = budgets
+            .iter()
+            .map(|b| (format!("project '{}'", b.project), b))
+            .chain(
+                account_budgets
+                    .iter()
+                    .map(|b| (format!("account '{}'", b.project), b)),
+            )
+            .collect();
+        if enforce {
+            for (scope, budget) in &scoped {
+                enforce_budget(scope, budget, config, tracker, &notifier, &mut notified).await;
+            }
+        }
+        notified.retain(|scope| scoped.iter().any(|(s, b)| s == scope && b.is_exceeded()));
+
+        if !watch {
+            return Ok(());
+        }
+        tokio::time::sleep(tokio::time::Duration::from_secs(interval)).await;
+    }
+}
+
+/// Budget lines for `resources budget` and `resources summary`
+pub(crate) fn print_budgets(budgets: &[ProjectBudget]) {
+    for budget in budgets {
+        print_budget(&budget.project, budget);
+    }
+}
+
+/// Account budget lines, after the project ones
+pub(crate) fn print_account_budgets(budgets: &[ProjectBudget]) {
+    for budget in budgets {
+        print_budget(&format!("account {}", budget.project), budget);
+    }
+}
+
+fn print_budget(name: &str, budget: &ProjectBudget) {
+    let spend = &budget.spend;
+    let limits = &budget.limits;
+    let status = if budget.is_exceeded() {
+        style("OVER").red().bold()
+    } else {
+        style("ok").green()
+    };
+    println!("  {} [{}]", name, status);
+    let rows = [
+        ("hourly", spend.hourly_rate, limits.hourly, "/hr"),
+        ("today", spend.today, limits.daily, ""),
+        ("this month", spend.this_month, limits.monthly, ""),
+    ];
+    for (label, actual, limit, unit) in rows {
+        match limit {
+            Some(limit) => println!(
+                "    {:<11} ${:.2}{} of ${:.2}{}",
+                label, actual, unit, limit, unit
+            ),
+            None => println!("    {:<11} ${:.2}{}", label, actual, unit),
+        }
+    }
+    for breach in &budget.breaches {
+        println!("    {} {}", style("WARNING:").red().bold(), breach);
+    }
+}
+
+/// Apply `budget`'s actions; `scope` names it in messages ("project 'x'")
+async fn enforce_budget(
+    scope: &str,
+    budget: &ProjectBudget,
+    config: &Config,
+    tracker: &ResourceTracker,
+    notifier: &Notifier,
+    notified: &mut HashSet<String>,
+) {
+    if !budget.is_exceeded() {
+        return;
+    }
+    let reasons: Vec<String> = budget.breaches.iter().map(|b| b.to_string()).collect();
+
+    if budget.wants(BudgetAction::Notify) && notified.insert(scope.to_string()) {
+        notifier.notify(
+            NotifyEvent::Alert,
+            &format!("runctl: {} over budget", scope),
+            &reasons.join("\n"),
+        );
+    }
+
+    if budget.wants(BudgetAction::Stop) {
+        for id in &budget.spend.running {
+            if !id.starts_with("i-") {
+                println!(
+                    "  {} is not an EC2 instance; stop it manually",
+                    style(id).yellow()
+                );
+                continue;
+            }
+            // Stop through the account the instance was listed from
+            let account = tracker
+                .get_by_id(id)
+                .await
+                .and_then(|r| r.tags.get(ACCOUNT_TAG).cloned());
+            let sdk_config = accounts::sdk_config_for(config, account.as_deref()).await;
+            // Let training checkpoint first, as `resources stop-all` does
+            crate::aws_utils::stop_training_gracefully(
+                &aws_sdk_ssm::Client::new(&sdk_config),
+                id,
+                config,
+                "budget",
+            )
+            .await;
+            let client = aws_sdk_ec2::Client::new(&sdk_config);
+            match client.stop_instances().instance_ids(id).send().await {
+                Ok(_) => {
+                    println!("  Stopped {} ({} over budget)", id, scope);
+                    if let Err(e) = tracker.update_state(id, ResourceState::Stopped).await {
+                        warn!("Failed to record stop of {}: {}", id, e);
+                    }
+                }
+                Err(e) => eprintln!("  ERROR: Failed to stop {}: {}", id, e),
+            }
+        }
+    }
+}
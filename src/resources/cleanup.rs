@@ -1,15 +1,37 @@
 //! Cleanup operations for resources
 
 use crate::config::Config;
+#[cfg(not(feature = "runpod"))]
+use crate::error::ConfigError;
 use crate::error::{Result, TrainctlError};
-use crate::resources::utils::estimate_instance_cost;
-use aws_config::BehaviorVersion;
+#[cfg(feature = "runpod")]
+use crate::providers::{Pod, RunpodClient};
+use crate::resources::local::{
+    find_local_zombies, is_training_command, LocalZombieReason, LOCAL_STALL_THRESHOLD,
+};
+use crate::resources::types::StopAllOptions;
+use crate::training::TrainingStatus;
 use aws_sdk_ec2::Client as Ec2Client;
 use chrono::Utc;
 use std::io::{self, Write};
+use std::path::PathBuf;
+use sysinfo::{Pid, ProcessesToUpdate, Signal, System, Users};
 
 /// Cleanup zombie/orphaned resources
-pub async fn cleanup_zombies(dry_run: bool, force: bool, _config: &Config) -> Result<()> {
+pub async fn cleanup_zombies(
+    dry_run: bool,
+    force: bool,
+    platform: &str,
+    config: &Config,
+) -> Result<()> {
+    let platform = platform.to_lowercase();
+    if !matches!(platform.as_str(), "aws" | "local" | "all") {
+        return Err(TrainctlError::Validation {
+            field: "platform".to_string(),
+            reason: format!("Unknown platform '{}'. Use aws, local, or all", platform),
+        });
+    }
+
     println!("{}", "=".repeat(80));
     println!("Zombie Resource Cleanup");
     println!("{}", "=".repeat(80));
@@ -18,8 +40,21 @@ pub async fn cleanup_zombies(dry_run: bool, force: bool, _config: &Config) -> Re
         println!("[DRY RUN MODE - No resources will be deleted]");
     }
 
+    if platform != "aws" {
+        cleanup_local_zombies(dry_run, force, config).await?;
+    }
+    if platform != "local" {
+        cleanup_aws_zombies(dry_run, force).await?;
+    }
+    Ok(())
+}
+
+/// Cleanup orphaned AWS instances and volumes
+async fn cleanup_aws_zombies(dry_run: bool, force: bool) -> Result<()> {
+    println!("\nAWS:");
+
     // Find orphaned AWS instances (running > 24 hours without tags)
-    let aws_config = aws_config::load_defaults(BehaviorVersion::latest()).await;
+    let aws_config = crate::aws_utils::shared_sdk_config().await;
     let client = Ec2Client::new(&aws_config);
 
     let response = client
@@ -169,13 +204,14 @@ pub async fn cleanup_zombies(dry_run: bool, force: bool, _config: &Config) -> Re
         return Ok(());
     }
 
-    if !force {
-        println!(
-            "\nWARNING: This will terminate {} instance(s) and delete {} volume(s). Continue? (y/N): ",
-            zombies.len(), orphaned_volumes.len()
-        );
-        // In real implementation, would read from stdin
-        println!("  (Use --force to skip confirmation)");
+    if !force
+        && !crate::prompt::confirm(&format!(
+            "WARNING: This will terminate {} instance(s) and delete {} volume(s). Continue?",
+            zombies.len(),
+            orphaned_volumes.len()
+        ))?
+    {
+        println!("Cancelled");
         return Ok(());
     }
 
@@ -202,32 +238,349 @@ pub async fn cleanup_zombies(dry_run: bool, force: bool, _config: &Config) -> Re
     Ok(())
 }
 
-/// Stop all running instances
-pub async fn stop_all_instances(
-    dry_run: bool,
-    force: bool,
-    _platform: String,
-    _config: &Config,
-) -> Result<()> {
-    let aws_config = aws_config::load_defaults(BehaviorVersion::latest()).await;
-    let client = Ec2Client::new(&aws_config);
+/// Find and interactively clean up local training zombies
+///
+/// Live processes are killed (SIGTERM, then SIGKILL); their session manifests
+/// are marked interrupted. Stale manifests are marked interrupted and stale
+/// PID files removed. Each entry is confirmed unless `force` is set.
+async fn cleanup_local_zombies(dry_run: bool, force: bool, config: &Config) -> Result<()> {
+    println!("\nLocal:");
+    let sessions_dir = PathBuf::from(".runctl");
+    let checkpoint_dir = config
+        .local
+        .as_ref()
+        .map(|c| c.checkpoint_dir.clone())
+        .unwrap_or_else(|| PathBuf::from("checkpoints"));
+    let zombies = find_local_zombies(&sessions_dir, &checkpoint_dir, LOCAL_STALL_THRESHOLD);
+
+    if zombies.is_empty() {
+        println!("No local zombies found");
+        return Ok(());
+    }
 
-    // Get all running instances
-    let response = client
-        .describe_instances()
-        .filters(
+    println!("Found {} local zombie(s):", zombies.len());
+    for zombie in &zombies {
+        match zombie.pid {
+            Some(pid) => println!(
+                "  - PID {}: {} ({})",
+                pid,
+                zombie.command,
+                zombie.describe()
+            ),
+            None => println!("  - {} ({})", zombie.command, zombie.describe()),
+        }
+    }
+
+    if dry_run {
+        println!("[DRY RUN] Would clean up {} local zombie(s)", zombies.len());
+        return Ok(());
+    }
+
+    let mut approve_all = force || crate::prompt::is_non_interactive();
+    if !approve_all && !crate::prompt::can_prompt() {
+        return Err(TrainctlError::Validation {
+            field: "force".to_string(),
+            reason: "Local cleanup asks before each process but stdin is not a terminal; \
+                     pass --force or --yes to proceed"
+                .to_string(),
+        });
+    }
+    for zombie in &zombies {
+        if !approve_all {
+            let prompt = match zombie.pid {
+                Some(pid) => format!("Kill PID {} ({})?", pid, zombie.describe()),
+                None => format!("Clean up {}?", zombie.command),
+            };
+            print!("{} [y/N/a(ll)/q(uit)]: ", prompt);
+            io::stdout().flush()?;
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+            match input.trim().to_lowercase().as_str() {
+                "y" | "yes" => {}
+                "a" | "all" => approve_all = true,
+                "q" | "quit" => {
+                    println!("Stopped local cleanup");
+                    return Ok(());
+                }
+                _ => {
+                    println!("  Skipped");
+                    continue;
+                }
+            }
+        }
+
+        if let Some(pid) = zombie.pid {
+            if terminate_local_process(pid).await {
+                println!("  Killed PID {}", pid);
+            } else {
+                eprintln!("  ERROR: Failed to kill PID {}", pid);
+                continue;
+            }
+        }
+
+        if zombie.reason == LocalZombieReason::StalePidFile {
+            if let Some(path) = &zombie.path {
+                match std::fs::remove_file(path) {
+                    Ok(()) => println!("  Removed {}", path.display()),
+                    Err(e) => eprintln!("  ERROR: Failed to remove {}: {}", path.display(), e),
+                }
+            }
+        } else if let Some(session) = &zombie.session {
+            let mut session = session.clone();
+            session.status = TrainingStatus::Interrupted;
+            match session.save(&sessions_dir) {
+                Ok(()) => println!("  Marked session {} interrupted", session.id),
+                Err(e) => eprintln!("  ERROR: Failed to update session {}: {}", session.id, e),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Seconds to wait for a local training process to exit after SIGTERM
+const LOCAL_GRACEFUL_TIMEOUT_SECS: u64 = 30;
+
+/// Running RunPod pod selected for stopping
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(not(feature = "runpod"), allow(dead_code))]
+struct PodTarget {
+    id: String,
+    name: String,
+    status: String,
+}
+
+/// Local training process selected for termination
+#[derive(Debug, Clone)]
+struct LocalTarget {
+    pid: u32,
+    command: String,
+}
+
+/// Stop all running resources on the selected platform(s)
+///
+/// Routes by `options.platform` (aws, runpod, local, all) and scopes by
+/// project and user where the platform can attribute ownership:
+/// - AWS: `runctl:project` / `runctl:user` tags
+/// - RunPod: pod name contains the project (pods carry no user attribution,
+///   so a `--user` filter skips RunPod entirely)
+/// - Local: process working directory is the project directory; only
+///   processes owned by the invoking user are considered, and a `--user`
+///   filter must name that owner
+pub async fn stop_all_instances(options: StopAllOptions, config: &Config) -> Result<()> {
+    let platform = options.platform.to_lowercase();
+    if !matches!(platform.as_str(), "aws" | "runpod" | "local" | "all") {
+        return Err(TrainctlError::Validation {
+            field: "platform".to_string(),
+            reason: format!(
+                "Unknown platform '{}'. Use aws, runpod, local, or all",
+                options.platform
+            ),
+        });
+    }
+    let include = |p: &str| platform == "all" || platform == p;
+    let project = options.project_filter.as_deref();
+    let user = options.user_filter.as_deref();
+
+    // AWS targets
+    let mut aws_clients = None;
+    let mut instance_info = Vec::new();
+    if include("aws") {
+        let aws_config = crate::aws_utils::shared_sdk_config().await;
+        let client = Ec2Client::new(&aws_config);
+        instance_info = find_running_aws_instances(&client, project, user).await?;
+        aws_clients = Some((client, aws_sdk_ssm::Client::new(&aws_config)));
+    }
+
+    // RunPod targets
+    #[cfg(feature = "runpod")]
+    let (runpod_client, pods) = if include("runpod") {
+        find_runpod_targets(config, platform == "runpod", project, user).await?
+    } else {
+        (None, Vec::new())
+    };
+    #[cfg(not(feature = "runpod"))]
+    let pods: Vec<PodTarget> = if platform == "runpod" {
+        return Err(ConfigError::FeatureNotCompiled("runpod".to_string()).into());
+    } else {
+        Vec::new()
+    };
+
+    // Local targets
+    let mut local_processes = Vec::new();
+    if include("local") {
+        local_processes = find_local_training_processes(project, user);
+    }
+
+    if instance_info.is_empty() && pods.is_empty() && local_processes.is_empty() {
+        println!("No running resources found");
+        return Ok(());
+    }
+
+    if !instance_info.is_empty() {
+        // Calculate cost savings
+        let total_hourly_cost: f64 = instance_info.iter().map(|(_, _, cost)| cost).sum();
+        let nightly_savings = total_hourly_cost * 8.0; // Assume 8 hours of sleep
+
+        println!("RUNNING AWS INSTANCES:");
+        println!("{}", "-".repeat(80));
+        for (id, inst_type, cost) in &instance_info {
+            println!("  {}  {}  ${:.4}/hr", id, inst_type, cost);
+        }
+        println!("{}", "-".repeat(80));
+        println!("Total: {} instance(s)", instance_info.len());
+        println!("Hourly cost: ${:.2}/hr", total_hourly_cost);
+        println!("Estimated savings (8h): ${:.2}", nightly_savings);
+        println!();
+    }
+
+    if !pods.is_empty() {
+        println!("RUNNING RUNPOD PODS:");
+        println!("{}", "-".repeat(80));
+        for pod in &pods {
+            println!("  {}  {}  {}", pod.id, pod.name, pod.status);
+        }
+        println!("{}", "-".repeat(80));
+        println!("Total: {} pod(s)", pods.len());
+        println!();
+    }
+
+    if !local_processes.is_empty() {
+        println!("LOCAL TRAINING PROCESSES:");
+        println!("{}", "-".repeat(80));
+        for proc in &local_processes {
+            println!("  PID {}  {}", proc.pid, proc.command);
+        }
+        println!("{}", "-".repeat(80));
+        println!("Total: {} process(es)", local_processes.len());
+        println!();
+    }
+
+    let summary = format!(
+        "{} instance(s), {} pod(s), {} local process(es)",
+        instance_info.len(),
+        pods.len(),
+        local_processes.len()
+    );
+
+    if options.dry_run {
+        println!("DRY RUN: Would stop {}", summary);
+        return Ok(());
+    }
+
+    if !options.force && !crate::prompt::confirm(&format!("Stop {}?", summary))? {
+        println!("Cancelled");
+        return Ok(());
+    }
+
+    let mut failures = 0;
+
+    if let Some((client, ssm_client)) = &aws_clients {
+        if !instance_info.is_empty() {
+            println!("Stopping AWS instances gracefully...");
+        }
+        for (instance_id, _, _) in &instance_info {
+            // Try graceful shutdown (non-critical - instance might not have SSM)
+            crate::aws_utils::stop_training_gracefully(ssm_client, instance_id, config, "stop-all")
+                .await;
+
+            // Then stop the instance
+            match client
+                .stop_instances()
+                .instance_ids(instance_id)
+                .send()
+                .await
+            {
+                Ok(_) => {
+                    println!("  Stop requested: {}", instance_id);
+                }
+                Err(e) => {
+                    failures += 1;
+                    eprintln!("  ERROR: Failed to stop {}: {}", instance_id, e);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "runpod")]
+    if let Some(client) = runpod_client.as_ref().filter(|_| !pods.is_empty()) {
+        println!("Stopping RunPod pods...");
+        for pod in &pods {
+            match client.stop_pod(&pod.id).await {
+                Ok(()) => println!("  Stop requested: {} ({})", pod.id, pod.name),
+                Err(e) => {
+                    failures += 1;
+                    eprintln!("  ERROR: Failed to stop pod {}: {}", pod.id, e);
+                }
+            }
+        }
+    }
+
+    if !local_processes.is_empty() {
+        println!("Stopping local training processes...");
+        for proc in &local_processes {
+            if terminate_local_process(proc.pid).await {
+                println!("  Stopped PID {}", proc.pid);
+            } else {
+                failures += 1;
+                eprintln!("  ERROR: Failed to stop PID {}", proc.pid);
+            }
+        }
+    }
+
+    println!();
+    println!("Stop requested for {}", summary);
+    if !instance_info.is_empty() || !pods.is_empty() {
+        println!("Instances and pods can be restarted later");
+    }
+
+    if failures > 0 {
+        return Err(TrainctlError::CloudProvider {
+            provider: platform,
+            message: format!("Failed to stop {} resource(s)", failures),
+            source: None,
+        });
+    }
+
+    Ok(())
+}
+
+/// Find running AWS instances, scoped by runctl project/user tags
+async fn find_running_aws_instances(
+    client: &Ec2Client,
+    project: Option<&str>,
+    user: Option<&str>,
+) -> Result<Vec<(String, String, f64)>> {
+    let mut request = client.describe_instances().filters(
+        aws_sdk_ec2::types::Filter::builder()
+            .name("instance-state-name")
+            .values("running")
+            .build(),
+    );
+    if let Some(project) = project {
+        request = request.filters(
             aws_sdk_ec2::types::Filter::builder()
-                .name("instance-state-name")
-                .values("running")
+                .name("tag:runctl:project")
+                .values(project)
                 .build(),
-        )
+        );
+    }
+    if let Some(user) = user {
+        request = request.filters(
+            aws_sdk_ec2::types::Filter::builder()
+                .name("tag:runctl:user")
+                .values(user)
+                .build(),
+        );
+    }
+
+    let response = request
         .send()
         .await
         .map_err(|e| TrainctlError::Aws(format!("Failed to describe instances: {}", e)))?;
+    crate::aws_pricing::prefetch_reservations(client, response.reservations()).await;
 
-    let mut instance_ids = Vec::new();
     let mut instance_info = Vec::new();
-
     for reservation in response.reservations() {
         for instance in reservation.instances() {
             if let Some(instance_id) = instance.instance_id() {
@@ -235,110 +588,172 @@ pub async fn stop_all_instances(
                     .instance_type()
                     .map(|t| format!("{}", t))
                     .unwrap_or_else(|| "unknown".to_string());
-                let cost_per_hour = estimate_instance_cost(&instance_type);
-
-                instance_ids.push(instance_id.to_string());
+                let cost_per_hour = crate::aws_pricing::instance_hourly_price(instance);
                 instance_info.push((instance_id.to_string(), instance_type, cost_per_hour));
             }
         }
     }
+    Ok(instance_info)
+}
 
-    if instance_ids.is_empty() {
-        println!("No running instances found");
-        return Ok(());
-    }
-
-    // Calculate cost savings
-    let total_hourly_cost: f64 = instance_info.iter().map(|(_, _, cost)| cost).sum();
-    let nightly_savings = total_hourly_cost * 8.0; // Assume 8 hours of sleep
+/// Keep the pods that are running
+#[cfg(feature = "runpod")]
+fn running_pods(pods: Vec<Pod>) -> Vec<PodTarget> {
+    pods.into_iter()
+        .filter(Pod::is_running)
+        .map(|pod| PodTarget {
+            status: pod.status(),
+            id: pod.id,
+            name: pod.name,
+        })
+        .collect()
+}
 
-    println!("RUNNING INSTANCES:");
-    println!("{}", "-".repeat(80));
-    for (id, inst_type, cost) in &instance_info {
-        println!("  {}  {}  ${:.4}/hr", id, inst_type, cost);
+/// Find running RunPod pods whose name contains the project
+///
+/// A missing API key is only an error when RunPod was asked for explicitly.
+#[cfg(feature = "runpod")]
+async fn find_runpod_targets(
+    config: &Config,
+    explicit: bool,
+    project: Option<&str>,
+    user: Option<&str>,
+) -> Result<(Option<RunpodClient>, Vec<PodTarget>)> {
+    if user.is_some() {
+        println!("NOTE: RunPod pods have no user attribution; skipping RunPod for --user");
+        return Ok((None, Vec::new()));
     }
-    println!("{}", "-".repeat(80));
-    println!("Total: {} instance(s)", instance_ids.len());
-    println!("Hourly cost: ${:.2}/hr", total_hourly_cost);
-    println!("Estimated savings (8h): ${:.2}", nightly_savings);
-    println!();
-
-    if dry_run {
-        println!("DRY RUN: Would stop {} instance(s)", instance_ids.len());
-        return Ok(());
-    }
-
-    if !force {
-        print!("Stop all {} instance(s)? (y/N): ", instance_ids.len());
-        io::stdout().flush()?;
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        if input.trim().to_lowercase() != "y" {
-            println!("Cancelled");
-            return Ok(());
+    match RunpodClient::from_config(config) {
+        Ok(client) => {
+            let pods = running_pods(client.list_pods().await?)
+                .into_iter()
+                .filter(|pod| project.map(|p| pod.name.contains(p)).unwrap_or(true))
+                .collect();
+            Ok((Some(client), pods))
         }
+        Err(e) if explicit => Err(e),
+        Err(_) => Ok((None, Vec::new())),
     }
+}
 
-    println!("Stopping instances gracefully...");
-    let ssm_client = aws_sdk_ssm::Client::new(&aws_config);
-
-    for instance_id in &instance_ids {
-        // First, gracefully stop any training jobs
-        let graceful_stop_cmd = r#"
-if [ -f training.pid ]; then
-    PID=$(cat training.pid 2>/dev/null)
-    if ps -p $PID > /dev/null 2>&1; then
-        kill -TERM $PID 2>/dev/null || true
-        for i in {1..30}; do
-            if ! ps -p $PID > /dev/null 2>&1; then break; fi
-            sleep 1
-        done
-        kill -9 $PID 2>/dev/null || true
-    fi
-else
-    TRAINING_PID=$(pgrep -f "python.*train\|python.*training\|python.*main.py" | head -1)
-    if [ -n "$TRAINING_PID" ]; then
-        kill -TERM $TRAINING_PID 2>/dev/null || true
-        for i in {1..30}; do
-            if ! ps -p $TRAINING_PID > /dev/null 2>&1; then break; fi
-            sleep 1
-        done
-        kill -9 $TRAINING_PID 2>/dev/null || true
-    fi
-fi
-"#;
-
-        // Try graceful shutdown (non-critical - instance might not have SSM)
-        if let Err(e) =
-            crate::aws_utils::execute_ssm_command(&ssm_client, instance_id, graceful_stop_cmd).await
-        {
-            // Log but don't fail - instance might not have SSM configured
-            tracing::warn!(
-                "Failed to send graceful shutdown command to {} (non-critical): {}",
-                instance_id,
-                e
-            );
+/// Find the invoking user's local training processes, optionally limited to a
+/// project directory
+///
+/// With `user`, processes are kept only when their owner's name is `user`.
+fn find_local_training_processes(project: Option<&str>, user: Option<&str>) -> Vec<LocalTarget> {
+    let mut system = System::new_all();
+    system.refresh_all();
+    let current_pid = Pid::from_u32(std::process::id());
+    let Some(current_uid) = system
+        .process(current_pid)
+        .and_then(|p| p.user_id())
+        .cloned()
+    else {
+        tracing::warn!("Could not determine the current user; skipping local processes");
+        return Vec::new();
+    };
+    if let Some(user) = user {
+        let users = Users::new_with_refreshed_list();
+        let owner = users.get_user_by_id(&current_uid).map(|u| u.name());
+        if owner != Some(user) {
+            return Vec::new();
         }
+    }
 
-        // Then stop the instance
-        match client
-            .stop_instances()
-            .instance_ids(instance_id)
-            .send()
-            .await
-        {
-            Ok(_) => {
-                println!("  Stop requested: {}", instance_id);
+    let mut targets: Vec<LocalTarget> = system
+        .processes()
+        .iter()
+        .filter(|(pid, _)| **pid != current_pid)
+        .filter(|(_, process)| process.user_id() == Some(&current_uid))
+        .filter_map(|(pid, process)| {
+            let command = process
+                .cmd()
+                .iter()
+                .map(|s| s.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+            if !is_training_command(&command) {
+                return None;
             }
-            Err(e) => {
-                eprintln!("  ERROR: Failed to stop {}: {}", instance_id, e);
+            if let Some(project) = project {
+                let in_project = process
+                    .cwd()
+                    .and_then(|cwd| cwd.file_name())
+                    .map(|name| name.to_string_lossy() == project)
+                    .unwrap_or(false);
+                if !in_project {
+                    return None;
+                }
             }
+            Some(LocalTarget {
+                pid: pid.as_u32(),
+                command,
+            })
+        })
+        .collect();
+    targets.sort_by_key(|t| t.pid);
+    targets
+}
+
+/// SIGTERM a local process, escalating to SIGKILL after the graceful timeout
+async fn terminate_local_process(pid: u32) -> bool {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    if system.refresh_processes(ProcessesToUpdate::Some(&[pid])) == 0 {
+        // Already gone
+        return true;
+    }
+    let Some(process) = system.process(pid) else {
+        return true;
+    };
+    match process.kill_with(Signal::Term) {
+        Some(true) => {}
+        Some(false) => return false,
+        // SIGTERM unsupported on this platform
+        None => return process.kill(),
+    }
+
+    for _ in 0..LOCAL_GRACEFUL_TIMEOUT_SECS {
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        if system.refresh_processes(ProcessesToUpdate::Some(&[pid])) == 0 {
+            return true;
         }
     }
+    system.process(pid).map(|p| p.kill()).unwrap_or(true)
+}
 
-    println!();
-    println!("Stop requested for {} instance(s)", instance_ids.len());
-    println!("Instances can be restarted later");
+#[cfg(all(test, feature = "runpod"))]
+mod tests {
+    use super::*;
+
+    fn pod(id: &str, name: &str, status: &str) -> Pod {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": name,
+            "desiredStatus": status,
+            "runtime": { "uptimeInSeconds": 60 },
+        }))
+        .unwrap()
+    }
 
-    Ok(())
+    #[test]
+    fn test_running_pods() {
+        let pods = running_pods(vec![
+            pod("abc123def456", "llm-sweep", "RUNNING"),
+            pod("zzz999yyy888", "idle-pod", "EXITED"),
+        ]);
+        assert_eq!(
+            pods,
+            vec![PodTarget {
+                id: "abc123def456".to_string(),
+                name: "llm-sweep".to_string(),
+                status: "running".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_running_pods_empty() {
+        assert!(running_pods(Vec::new()).is_empty());
+    }
 }
@@ -5,14 +5,20 @@
 
 use crate::config::Config;
 use crate::error::Result;
-use crate::resources::types::{AwsInstance, LocalProcess, ResourceSummary, RunPodPod};
+use crate::resources::types::{AwsInstance, AzureVm, LocalProcess, ResourceSummary, RunPodPod};
 use chrono::Utc;
 
 /// Get complete resource summary as JSON
 pub async fn get_resource_summary_json(config: &Config) -> Result<serde_json::Value> {
-    let aws_instances_json = list_aws_instances_json(config).await?;
-    let runpod_pods_json = list_runpod_pods_json(config).await?;
-    let local_processes_json = list_local_processes_json().await?;
+    // Platforms are independent, so fetch them concurrently
+    let (aws, runpod, azure, local) = tokio::join!(
+        list_aws_instances_json(config),
+        list_runpod_pods_json(config),
+        list_azure_vms_json(config),
+        list_local_processes_json()
+    );
+    let (aws_instances_json, runpod_pods_json, azure_vms_json, local_processes_json) =
+        (aws?, runpod?, azure?, local?);
 
     let aws_instances: Vec<AwsInstance> = aws_instances_json
         .iter()
@@ -24,17 +30,24 @@ pub async fn get_resource_summary_json(config: &Config) -> Result<serde_json::Va
         .filter_map(|pod| serde_json::from_value(pod.clone()).ok())
         .collect();
 
+    let azure_vms: Vec<AzureVm> = azure_vms_json
+        .iter()
+        .filter_map(|vm| serde_json::from_value(vm.clone()).ok())
+        .collect();
+
     let local_processes: Vec<LocalProcess> = local_processes_json
         .iter()
         .filter_map(|proc| serde_json::from_value(proc.clone()).ok())
         .collect();
 
     let total_cost: f64 = aws_instances.iter().map(|i| i.cost_per_hour).sum::<f64>()
-        + runpod_pods.iter().map(|p| p.cost_per_hour).sum::<f64>();
+        + runpod_pods.iter().map(|p| p.cost_per_hour).sum::<f64>()
+        + azure_vms.iter().map(|v| v.cost_per_hour).sum::<f64>();
 
     let summary = ResourceSummary {
         aws_instances,
         runpod_pods,
+        azure_vms,
         local_processes,
         total_cost_estimate: total_cost,
         timestamp: Utc::now(),
@@ -44,22 +57,32 @@ pub async fn get_resource_summary_json(config: &Config) -> Result<serde_json::Va
 }
 
 /// List AWS instances as JSON
-pub async fn list_aws_instances_json(_config: &Config) -> Result<Vec<serde_json::Value>> {
-    use crate::error::TrainctlError;
-    use aws_config::BehaviorVersion;
-    use aws_sdk_ec2::Client as Ec2Client;
-
-    let aws_config = aws_config::load_defaults(BehaviorVersion::latest()).await;
-    let client = Ec2Client::new(&aws_config);
+///
+/// With `[accounts]`, covers every account that could be listed and adds an
+/// `account` field; without, failing to list is an error.
+pub async fn list_aws_instances_json(config: &Config) -> Result<Vec<serde_json::Value>> {
+    let mut instances = Vec::new();
 
-    let response = client
-        .describe_instances()
-        .send()
-        .await
-        .map_err(|e| TrainctlError::Aws(format!("Failed to list instances: {}", e)))?;
+    for listing in super::accounts::describe(config).await {
+        let response = match (listing.result, &listing.account) {
+            (Ok(response), _) => response,
+            (Err(e), None) => return Err(e),
+            (Err(e), Some(account)) => {
+                tracing::warn!("Failed to list instances in account '{}': {}", account, e);
+                continue;
+            }
+        };
+        add_instances_json(&mut instances, &response, listing.account.as_deref());
+    }
 
-    let mut instances = Vec::new();
+    Ok(instances)
+}
 
+fn add_instances_json(
+    instances: &mut Vec<serde_json::Value>,
+    response: &aws_sdk_ec2::operation::describe_instances::DescribeInstancesOutput,
+    account: Option<&str>,
+) {
     for reservation in response.reservations() {
         for instance in reservation.instances() {
             if let Some(instance_id) = instance.instance_id() {
@@ -89,9 +112,9 @@ pub async fn list_aws_instances_json(_config: &Config) -> Result<Vec<serde_json:
                     })
                     .collect();
 
-                let cost_per_hour = crate::utils::get_instance_cost(&instance_type);
+                let cost_per_hour = crate::aws_pricing::instance_hourly_price(instance);
 
-                let instance_json = serde_json::json!({
+                let mut instance_json = serde_json::json!({
                     "instance_id": instance_id,
                     "instance_type": instance_type,
                     "state": state,
@@ -99,75 +122,88 @@ pub async fn list_aws_instances_json(_config: &Config) -> Result<Vec<serde_json:
                     "tags": tags,
                     "cost_per_hour": cost_per_hour,
                 });
+                if let Some(account) = account {
+                    instance_json["account"] = serde_json::json!(account);
+                }
 
                 instances.push(instance_json);
             }
         }
     }
-
-    Ok(instances)
 }
 
 /// List RunPod pods as JSON
-pub async fn list_runpod_pods_json(_config: &Config) -> Result<Vec<serde_json::Value>> {
-    use crate::error::TrainctlError;
-    use std::process::Command;
-
-    let mut pods = Vec::new();
+///
+/// Empty when no RunPod API key is configured.
+#[cfg(feature = "runpod")]
+pub async fn list_runpod_pods_json(config: &Config) -> Result<Vec<serde_json::Value>> {
+    use crate::providers::RunpodClient;
+
+    let Ok(client) = RunpodClient::from_config(config) else {
+        return Ok(Vec::new());
+    };
+    let pods = client.list_pods().await?;
 
-    if which::which("runpodctl").is_err() {
-        return Ok(pods);
-    }
+    Ok(pods
+        .iter()
+        .map(|pod| {
+            serde_json::json!({
+                "pod_id": pod.id,
+                "name": pod.name,
+                "status": pod.status(),
+                "gpu_type": pod.gpu(),
+                "created_at": pod
+                    .runtime
+                    .as_ref()
+                    .and_then(|r| r.uptime_in_seconds)
+                    .map(|secs| Utc::now() - chrono::Duration::seconds(secs as i64)),
+                "cost_per_hour": pod.cost_per_hr.unwrap_or(0.0),
+            })
+        })
+        .collect())
+}
 
-    let output = Command::new("runpodctl")
-        .args(["get", "pod"])
-        .output()
-        .map_err(|e| {
-            TrainctlError::Io(std::io::Error::other(format!(
-                "Failed to run runpodctl: {}",
-                e
-            )))
-        })?;
-
-    if !output.status.success() {
-        return Ok(pods);
-    }
+/// List RunPod pods as JSON
+///
+/// Always empty: this build has no RunPod support.
+#[cfg(not(feature = "runpod"))]
+pub async fn list_runpod_pods_json(_config: &Config) -> Result<Vec<serde_json::Value>> {
+    Ok(Vec::new())
+}
 
-    // Parse runpodctl output
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    for line in stdout.lines() {
-        let trimmed = line.trim();
-        // Skip empty lines, headers, and help text
-        if trimmed.is_empty()
-            || trimmed.starts_with("NAME")
-            || trimmed.starts_with("ID")
-            || trimmed.contains("Available Commands")
-            || trimmed.contains("Usage:")
-            || trimmed.contains("Flags:")
-            || trimmed.contains("Use \"runpodctl")
-        {
-            continue;
-        }
+/// List Azure VMs as JSON
+///
+/// Empty when there is no `[azure]` section.
+pub async fn list_azure_vms_json(config: &Config) -> Result<Vec<serde_json::Value>> {
+    use crate::providers::AzureClient;
 
-        let parts: Vec<&str> = trimmed.split_whitespace().collect();
-        if parts.len() >= 2 {
-            pods.push(serde_json::json!({
-                "id": parts[0],
-                "status": parts.get(1).unwrap_or(&""),
-                "name": parts.get(2).unwrap_or(&""),
-            }));
-        }
+    if config.azure.is_none() {
+        return Ok(Vec::new());
     }
+    let vms = AzureClient::from_config(config)?.list_vms().await?;
 
-    Ok(pods)
+    Ok(vms
+        .iter()
+        .map(|vm| {
+            serde_json::json!({
+                "name": vm.name,
+                "vm_size": vm.vm_size(),
+                "state": vm.state(),
+                "public_ip": vm.public_ip(),
+                "public_ipv6": vm.public_ipv6(),
+                "spot": vm.is_spot(),
+                "created_at": vm.time_created,
+                "cost_per_hour": vm.cost_per_hour(),
+            })
+        })
+        .collect())
 }
 
 /// List local processes as JSON
 pub async fn list_local_processes_json() -> Result<Vec<serde_json::Value>> {
-    use sysinfo::System;
-
-    let mut system = System::new_all();
-    system.refresh_all();
+    let system = tokio::task::spawn_blocking(super::local::process_snapshot)
+        .await
+        .map_err(|e| crate::error::TrainctlError::Io(std::io::Error::other(e)))?;
 
     let mut processes = Vec::new();
 
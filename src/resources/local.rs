@@ -1,75 +1,393 @@
-//! Local process listing
+//! Local process listing and zombie detection
+//!
+//! Zombie detection flags local training runs that are still consuming the
+//! machine but are no longer useful or tracked:
+//! - processes whose launching runctl (or parent) has exited
+//! - long-running processes with no recent checkpoint writes
+//! - session manifests and `training.pid` files pointing at dead processes
 
-use crate::error::Result;
-use sysinfo::{Pid, System};
+use crate::training::{TrainingSession, TrainingStatus};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use sysinfo::{Pid, ProcessRefreshKind, RefreshKind, System};
+use walkdir::WalkDir;
 
-/// List local training processes
-pub async fn list_local_processes(detailed: bool) -> Result<()> {
+/// Training processes running this long without a checkpoint write are flagged
+pub(crate) const LOCAL_STALL_THRESHOLD: Duration = Duration::from_secs(2 * 60 * 60);
+
+/// Legacy manifests (no recorded PID) still marked running after this long are stale
+const LEGACY_MANIFEST_MAX_AGE_HOURS: i64 = 24;
+
+/// Why a local process or file was flagged as a zombie
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum LocalZombieReason {
+    /// Launching runctl process (or parent) exited; training keeps running
+    Orphaned { parent_pid: Option<u32> },
+    /// Running past the stall threshold with no recent checkpoint writes
+    NoRecentCheckpoints { idle: Duration },
+    /// Session manifest still says running but the process is gone
+    StaleManifest,
+    /// `training.pid` points at a process that no longer exists
+    StalePidFile,
+}
+
+/// A flagged local process, session manifest, or PID file
+#[derive(Debug, Clone)]
+pub(crate) struct LocalZombie {
+    /// Live process to kill, if any
+    pub pid: Option<u32>,
+    /// Command line (processes) or file description
+    pub command: String,
+    pub reason: LocalZombieReason,
+    /// Session manifest or PID file backing this entry
+    pub path: Option<PathBuf>,
+    /// Loaded session, for tracked runs
+    pub session: Option<TrainingSession>,
+}
+
+impl LocalZombie {
+    /// Short human-readable reason
+    pub fn describe(&self) -> String {
+        match &self.reason {
+            LocalZombieReason::Orphaned {
+                parent_pid: Some(parent),
+            } => format!("orphaned: launching runctl (PID {}) has exited", parent),
+            LocalZombieReason::Orphaned { parent_pid: None } => {
+                "orphaned: parent process has exited".to_string()
+            }
+            LocalZombieReason::NoRecentCheckpoints { idle } => format!(
+                "no checkpoint written in {}h{}m",
+                idle.as_secs() / 3600,
+                (idle.as_secs() % 3600) / 60
+            ),
+            LocalZombieReason::StaleManifest => {
+                "stale session manifest (process not running)".to_string()
+            }
+            LocalZombieReason::StalePidFile => "stale PID file (process not running)".to_string(),
+        }
+    }
+}
+
+/// Check whether a command line looks like a local training process
+///
+/// Skips runctl itself and common editor/tooling processes.
+pub(crate) fn is_training_command(cmd_str: &str) -> bool {
+    // Filter out system processes
+    if cmd_str.contains("runctl")
+        || cmd_str.contains("ps aux")
+        || cmd_str.contains("runpodctl")
+        || cmd_str.contains("ripgrep")
+        || cmd_str.contains("mypy")
+        || cmd_str.contains("lsp_server")
+        || cmd_str.contains("Cursor.app")
+    {
+        return false;
+    }
+
+    // Look for actual training scripts: Python scripts with train/epoch keywords, or .py files
+    (cmd_str.contains(".py")
+        && (cmd_str.contains("train") || cmd_str.contains("epoch") || cmd_str.contains("training")))
+        || (cmd_str.contains("python")
+            && cmd_str.contains(".py")
+            && (cmd_str.contains("train") || cmd_str.contains("epoch")))
+}
+
+/// A running local training process
+#[derive(Debug, Clone)]
+pub(crate) struct TrainingProcess {
+    pub pid: u32,
+    pub cpu_usage: f32,
+    pub memory_mb: f64,
+    pub command: String,
+}
+
+/// Snapshot of processes only
+///
+/// Much cheaper than `System::new_all()`, which also scans disks, networks
+/// and sensors.
+pub(crate) fn process_snapshot() -> System {
+    System::new_with_specifics(RefreshKind::new().with_processes(ProcessRefreshKind::everything()))
+}
+
+/// Find local training processes
+///
+/// Blocking; run it with `spawn_blocking` from async code.
+pub(crate) fn find_training_processes() -> Vec<TrainingProcess> {
+    let system = process_snapshot();
+    let current_pid = Pid::from_u32(std::process::id());
+
+    let mut processes: Vec<TrainingProcess> = system
+        .processes()
+        .iter()
+        // Skip current process
+        .filter(|(pid, _)| **pid != current_pid)
+        .filter_map(|(pid, process)| {
+            let command = process
+                .cmd()
+                .iter()
+                .map(|s| s.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+            is_training_command(&command).then(|| TrainingProcess {
+                pid: pid.as_u32(),
+                cpu_usage: process.cpu_usage(),
+                memory_mb: process.memory() as f64 / 1024.0 / 1024.0,
+                command,
+            })
+        })
+        .collect();
+    processes.sort_by_key(|p| p.pid);
+    processes
+}
+
+/// Print the local processes section
+pub(crate) fn render_local_processes(processes: &[TrainingProcess], detailed: bool) {
     println!("\nLocal Training Processes:");
     println!("{}", "-".repeat(80));
 
-    // Use sysinfo for native Rust process listing
+    if processes.is_empty() {
+        println!("  No training processes found");
+        return;
+    }
+
+    for process in processes {
+        if detailed {
+            println!(
+                "  PID: {}  CPU: {:.1}%  MEM: {:.1}MB  CMD: {}",
+                process.pid, process.cpu_usage, process.memory_mb, process.command
+            );
+        } else {
+            println!("  PID: {}  CMD: {}", process.pid, process.command);
+        }
+    }
+}
+
+/// Detect local training zombies
+///
+/// `sessions_dir` is the `.runctl` directory holding session manifests, and
+/// `default_checkpoint_dir` is used (relative to each process's working
+/// directory) for processes runctl did not launch.
+pub(crate) fn find_local_zombies(
+    sessions_dir: &Path,
+    default_checkpoint_dir: &Path,
+    stall_threshold: Duration,
+) -> Vec<LocalZombie> {
     let mut system = System::new_all();
     system.refresh_all();
+    let alive = |pid: u32| system.process(Pid::from_u32(pid)).is_some();
 
-    let current_pid = Pid::from_u32(std::process::id());
-    let mut found = false;
+    let mut zombies = Vec::new();
+    let mut flagged: HashSet<u32> = HashSet::new();
+    let mut tracked: Vec<(u32, TrainingSession)> = Vec::new();
+
+    // Session manifests written by `runctl local`
+    let sessions = TrainingSession::list_sessions(sessions_dir).unwrap_or_default();
+    for session in sessions {
+        if session.platform != "local" || !matches!(session.status, TrainingStatus::Running) {
+            continue;
+        }
+        let manifest = session.manifest_path(sessions_dir);
+        match session.pid {
+            Some(pid) if alive(pid) => {
+                if let Some(parent) = session.runctl_pid.filter(|p| !alive(*p)) {
+                    flagged.insert(pid);
+                    zombies.push(LocalZombie {
+                        pid: Some(pid),
+                        command: process_command(&system, pid),
+                        reason: LocalZombieReason::Orphaned {
+                            parent_pid: Some(parent),
+                        },
+                        path: Some(manifest),
+                        session: Some(session.clone()),
+                    });
+                }
+                tracked.push((pid, session));
+            }
+            Some(_) => zombies.push(LocalZombie {
+                pid: None,
+                command: session.script.display().to_string(),
+                reason: LocalZombieReason::StaleManifest,
+                path: Some(manifest),
+                session: Some(session),
+            }),
+            None => {
+                let age = chrono::Utc::now() - session.started_at;
+                if age > chrono::Duration::hours(LEGACY_MANIFEST_MAX_AGE_HOURS) {
+                    zombies.push(LocalZombie {
+                        pid: None,
+                        command: session.script.display().to_string(),
+                        reason: LocalZombieReason::StaleManifest,
+                        path: Some(manifest),
+                        session: Some(session),
+                    });
+                }
+            }
+        }
+    }
 
+    let current_pid = Pid::from_u32(std::process::id());
     for (pid, process) in system.processes() {
-        // Skip current process
         if *pid == current_pid {
             continue;
         }
-
-        let cmd: Vec<String> = process
-            .cmd()
+        let pid_u32 = pid.as_u32();
+        let command = process_command(&system, pid_u32);
+        if !is_training_command(&command) || flagged.contains(&pid_u32) {
+            continue;
+        }
+        let session = tracked
             .iter()
-            .map(|s| s.to_string_lossy().to_string())
-            .collect();
-        let cmd_str = cmd.join(" ");
-
-        // Filter out system processes
-        if cmd_str.contains("runctl")
-            || cmd_str.contains("ps aux")
-            || cmd_str.contains("runpodctl")
-            || cmd_str.contains("ripgrep")
-            || cmd_str.contains("mypy")
-            || cmd_str.contains("lsp_server")
-            || cmd_str.contains("Cursor.app")
-        {
+            .find(|(p, _)| *p == pid_u32)
+            .map(|(_, s)| s.clone());
+
+        // Untracked runs reparented to init have lost their launcher
+        let parent = process.parent().map(|p| p.as_u32());
+        if session.is_none() && matches!(parent, None | Some(1)) {
+            flagged.insert(pid_u32);
+            zombies.push(LocalZombie {
+                pid: Some(pid_u32),
+                command,
+                reason: LocalZombieReason::Orphaned { parent_pid: None },
+                path: None,
+                session: None,
+            });
             continue;
         }
 
-        // Look for actual training scripts: Python scripts with train/epoch keywords, or .py files
-        let is_training = (cmd_str.contains(".py")
-            && (cmd_str.contains("train")
-                || cmd_str.contains("epoch")
-                || cmd_str.contains("training")))
-            || (cmd_str.contains("python")
-                && cmd_str.contains(".py")
-                && (cmd_str.contains("train") || cmd_str.contains("epoch")));
-
-        if is_training {
-            found = true;
-            let cpu_usage = process.cpu_usage();
-            let memory_mb = process.memory() as f64 / 1024.0 / 1024.0;
-            if detailed {
-                println!(
-                    "  PID: {}  CPU: {:.1}%  MEM: {:.1}MB  CMD: {}",
-                    pid.as_u32(),
-                    cpu_usage,
-                    memory_mb,
-                    cmd_str
-                );
-            } else {
-                println!("  PID: {}  CMD: {}", pid.as_u32(), cmd_str);
+        if Duration::from_secs(process.run_time()) < stall_threshold {
+            continue;
+        }
+        let checkpoint_dir = match &session {
+            Some(s) if s.checkpoint_dir.is_absolute() => s.checkpoint_dir.clone(),
+            Some(s) => process
+                .cwd()
+                .map(|cwd| cwd.join(&s.checkpoint_dir))
+                .unwrap_or_else(|| s.checkpoint_dir.clone()),
+            None => match process.cwd() {
+                Some(cwd) => cwd.join(default_checkpoint_dir),
+                None => continue,
+            },
+        };
+        let idle = newest_write(&checkpoint_dir)
+            .and_then(|t| SystemTime::now().duration_since(t).ok())
+            .unwrap_or_else(|| Duration::from_secs(process.run_time()));
+        if idle >= stall_threshold {
+            flagged.insert(pid_u32);
+            zombies.push(LocalZombie {
+                pid: Some(pid_u32),
+                command,
+                reason: LocalZombieReason::NoRecentCheckpoints { idle },
+                path: session.as_ref().map(|s| s.manifest_path(sessions_dir)),
+                session,
+            });
+        }
+    }
+
+    // training.pid in the current project
+    let pid_file = PathBuf::from("training.pid");
+    if let Ok(content) = std::fs::read_to_string(&pid_file) {
+        if let Ok(pid) = content.trim().parse::<u32>() {
+            if !alive(pid) {
+                zombies.push(LocalZombie {
+                    pid: None,
+                    command: format!("{} (PID {})", pid_file.display(), pid),
+                    reason: LocalZombieReason::StalePidFile,
+                    path: Some(pid_file),
+                    session: None,
+                });
             }
         }
     }
 
-    if !found {
-        println!("  No training processes found");
+    zombies
+}
+
+fn process_command(system: &System, pid: u32) -> String {
+    system
+        .process(Pid::from_u32(pid))
+        .map(|p| {
+            p.cmd()
+                .iter()
+                .map(|s| s.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default()
+}
+
+/// Most recent modification time of any file under `dir`
+fn newest_write(dir: &Path) -> Option<SystemTime> {
+    WalkDir::new(dir)
+        .max_depth(3)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok()?.modified().ok())
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_is_training_command() {
+        assert!(is_training_command("python train.py --epochs 10"));
+        assert!(is_training_command("uv run scripts/training_loop.py"));
+        assert!(!is_training_command("python -m http.server"));
+        assert!(!is_training_command("runctl local train.py"));
     }
 
-    Ok(())
+    #[test]
+    fn test_stale_manifest_detected() {
+        let dir = TempDir::new().unwrap();
+        let mut session = TrainingSession::new(
+            "local".to_string(),
+            PathBuf::from("train.py"),
+            PathBuf::from("checkpoints"),
+        );
+        // PIDs near u32::MAX are never allocated
+        session.pid = Some(u32::MAX - 1);
+        session.runctl_pid = Some(u32::MAX - 2);
+        session.save(dir.path()).unwrap();
+
+        let zombies =
+            find_local_zombies(dir.path(), Path::new("checkpoints"), LOCAL_STALL_THRESHOLD);
+        let stale: Vec<_> = zombies
+            .iter()
+            .filter(|z| z.reason == LocalZombieReason::StaleManifest)
+            .collect();
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].path, Some(session.manifest_path(dir.path())));
+        assert!(stale[0].pid.is_none());
+    }
+
+    #[test]
+    fn test_finished_sessions_ignored() {
+        let dir = TempDir::new().unwrap();
+        let mut session = TrainingSession::new(
+            "local".to_string(),
+            PathBuf::from("train.py"),
+            PathBuf::from("checkpoints"),
+        );
+        session.pid = Some(u32::MAX - 1);
+        session.status = TrainingStatus::Completed;
+        session.save(dir.path()).unwrap();
+
+        let zombies =
+            find_local_zombies(dir.path(), Path::new("checkpoints"), LOCAL_STALL_THRESHOLD);
+        assert!(zombies
+            .iter()
+            .all(|z| z.reason != LocalZombieReason::StaleManifest));
+    }
+
+    #[test]
+    fn test_newest_write() {
+        let dir = TempDir::new().unwrap();
+        assert!(newest_write(dir.path()).is_none());
+        std::fs::write(dir.path().join("epoch_1.pt"), b"x").unwrap();
+        assert!(newest_write(dir.path()).is_some());
+    }
 }
@@ -1,23 +1,47 @@
 //! Resource management module
 //!
 //! Provides unified resource listing, management, and reporting across
-//! multiple platforms (AWS, RunPod, local).
+//! multiple platforms (AWS, every other registered provider, local).
 
+// Listings, cleanup and budgets all go through EC2; without the `aws`
+// feature only `tracker` and `schedule` (which reports the missing feature)
+// remain.
+#[cfg(feature = "aws")]
+mod accounts;
+#[cfg(feature = "aws")]
 mod aws;
+#[cfg(feature = "aws")]
+mod budget;
+#[cfg(feature = "aws")]
 mod cleanup;
+#[cfg(feature = "aws")]
 mod export;
+#[cfg(feature = "aws")]
 mod json;
+#[cfg(feature = "aws")]
 mod local;
-mod runpod;
+#[cfg(feature = "aws")]
+pub mod prefetch;
+#[cfg(feature = "aws")]
+mod providers;
+#[cfg(feature = "aws")]
 mod summary;
+mod tracker;
+#[cfg(feature = "aws")]
 mod types;
 pub mod utils; // Public for re-export
+#[cfg(feature = "aws")]
 mod watch;
+#[cfg(feature = "dashboard")]
+mod watch_tui;
 
 // Types are used internally via `types::` path.
 // External consumers can access types via `crate::resources::types::TypeName` if needed.
 
 // Re-export utility functions
+#[cfg(feature = "aws")]
+pub(crate) use aws::describe_instances;
+pub use tracker::TrackerCommands;
 pub use utils::estimate_instance_cost;
 
 use crate::config::Config;
@@ -26,12 +50,15 @@ use clap::Subcommand;
 
 #[derive(Subcommand, Clone)]
 pub enum ResourceCommands {
-    /// List all running resources (AWS, RunPod, local)
+    /// List all running resources (AWS, RunPod, Azure, Lyceum, local)
+    ///
+    /// With [accounts.<name>] entries in the config, EC2 instances are listed
+    /// from every configured account and labelled with it.
     List {
         /// Show detailed information
         #[arg(short, long)]
         detailed: bool,
-        /// Filter by platform (aws, runpod, local, all)
+        /// Filter by platform (aws, runpod, azure, batch, k8s, lyceum, local, all)
         #[arg(long, default_value = "all")]
         platform: String,
         /// Filter by project name (from runctl:project tag)
@@ -40,6 +67,9 @@ pub enum ResourceCommands {
         /// Filter by user (from runctl:user tag)
         #[arg(long)]
         user: Option<String>,
+        /// Filter by [accounts] entry
+        #[arg(long)]
+        account: Option<String>,
         /// Output format (table, compact, detailed)
         #[arg(long, default_value = "compact")]
         format: String,
@@ -56,10 +86,14 @@ pub enum ResourceCommands {
         #[arg(long)]
         show_terminated: bool,
         /// Watch mode (auto-refresh, like tail -f)
+        ///
+        /// On a terminal, EC2 instances can be selected with the arrow keys:
+        /// Enter shows details, l tails the training log, s stops and t
+        /// terminates (after confirming).
         #[arg(short, long)]
         watch: bool,
-        /// Refresh interval for watch mode (seconds)
-        #[arg(long, default_value = "5")]
+        /// Refresh interval for watch mode (e.g. 5, 30s, 1m; bare numbers are seconds)
+        #[arg(long, default_value = "5", value_parser = crate::units::parse_secs)]
         interval: u64,
         /// Export format (csv, html, json)
         #[arg(long)]
@@ -78,6 +112,12 @@ pub enum ResourceCommands {
         /// Force cleanup (skip confirmation)
         #[arg(short, long)]
         force: bool,
+        /// Platform to clean up (aws, local, all)
+        ///
+        /// Local cleanup finds orphaned or stalled training processes and
+        /// stale session manifests / PID files.
+        #[arg(long, default_value = "all")]
+        platform: String,
     },
     /// Stop all running instances (pause for cost savings)
     StopAll {
@@ -87,12 +127,73 @@ pub enum ResourceCommands {
         /// Force stop (skip confirmation)
         #[arg(short, long)]
         force: bool,
-        /// Platform to stop (aws, runpod, all)
+        /// Platform to stop (aws, runpod, local, all)
         #[arg(long, default_value = "all")]
         platform: String,
+        /// Only stop resources for this project (runctl:project tag, pod name, or working directory)
+        #[arg(long)]
+        project: Option<String>,
+        /// Only stop resources owned by this user (runctl:user tag)
+        #[arg(long)]
+        user: Option<String>,
     },
     /// Show resource insights and recommendations
     Insights,
+    /// Show spend against [budget] limits and act on exceeded budgets
+    ///
+    /// Without --enforce only reports. With --enforce, projects over a limit
+    /// get their configured actions: `stop` stops their running instances and
+    /// `notify` sends a desktop alert. Add --watch to keep enforcing.
+    ///
+    /// Examples:
+    ///   runctl resources budget
+    ///   runctl resources budget --enforce --watch --interval 5m
+    Budget {
+        /// Apply stop/notify actions to projects over budget
+        #[arg(long)]
+        enforce: bool,
+        /// Keep checking until interrupted
+        #[arg(short, long)]
+        watch: bool,
+        /// Check interval for watch mode (e.g. 60, 5m; bare numbers are seconds)
+        #[arg(long, default_value = "300", value_parser = crate::units::parse_secs)]
+        interval: u64,
+    },
+    /// Stop and start a project's instances on a cron schedule (UTC)
+    ///
+    /// Without --stop, --start or --remove, lists saved schedules. Local
+    /// schedules are applied by `runctl scheduler daemon`; with --eventbridge
+    /// they become EventBridge rules in the account instead.
+    ///
+    /// Examples:
+    ///   runctl resources schedule --stop "0 22 * * *" --start "0 8 * * 1-5" --project myproj
+    ///   runctl resources schedule --stop "0 22 * * *" --eventbridge --role-arn arn:aws:iam::123456789012:role/runctl-scheduler
+    ///   runctl resources schedule --remove --project myproj
+    Schedule {
+        /// When to stop the project's running instances (cron: min hour day month weekday)
+        #[arg(long, value_parser = crate::validation::cron_arg)]
+        stop: Option<String>,
+        /// When to start the project's stopped instances
+        #[arg(long, value_parser = crate::validation::cron_arg)]
+        start: Option<String>,
+        /// Project to schedule (runctl:project tag; defaults to the configured project)
+        #[arg(long)]
+        project: Option<String>,
+        /// Apply with EventBridge rules instead of the local daemon
+        #[arg(long)]
+        eventbridge: bool,
+        /// IAM role EventBridge assumes to run the stop/start automation
+        #[arg(long, requires = "eventbridge")]
+        role_arn: Option<String>,
+        /// Remove the project's schedule (and its EventBridge rules)
+        #[arg(long, conflicts_with_all = ["stop", "start", "eventbridge"])]
+        remove: bool,
+    },
+    /// Maintain the local resource tracker file (.runctl/resources.json)
+    Tracker {
+        #[command(subcommand)]
+        command: TrackerCommands,
+    },
 }
 
 pub async fn handle_command(
@@ -101,6 +202,7 @@ pub async fn handle_command(
     output_format: &str,
 ) -> Result<()> {
     match cmd {
+        #[cfg(feature = "aws")]
         ResourceCommands::List {
             detailed,
             platform,
@@ -115,6 +217,7 @@ pub async fn handle_command(
             export_file,
             project,
             user,
+            account,
         } => {
             if watch {
                 watch::list_resources_watch(
@@ -125,6 +228,7 @@ pub async fn handle_command(
                     interval,
                     project.as_deref(),
                     user.as_deref(),
+                    account.as_deref(),
                 )
                 .await
             } else {
@@ -141,6 +245,7 @@ pub async fn handle_command(
                     export_file: export_file.clone(),
                     project_filter: project.clone(),
                     user_filter: user.clone(),
+                    account_filter: account.clone(),
                 };
                 if let Some(export_format) = &list_options.export {
                     export::export_resources(
@@ -155,19 +260,66 @@ pub async fn handle_command(
                 }
             }
         }
+        #[cfg(feature = "aws")]
         ResourceCommands::Summary => summary::show_summary(config, output_format).await,
-        ResourceCommands::Cleanup { dry_run, force } => {
-            cleanup::cleanup_zombies(dry_run, force, config).await
-        }
+        #[cfg(feature = "aws")]
+        ResourceCommands::Cleanup {
+            dry_run,
+            force,
+            platform,
+        } => cleanup::cleanup_zombies(dry_run, force, &platform, config).await,
+        #[cfg(feature = "aws")]
         ResourceCommands::StopAll {
             dry_run,
             force,
             platform,
-        } => cleanup::stop_all_instances(dry_run, force, platform, config).await,
+            project,
+            user,
+        } => {
+            let options = types::StopAllOptions {
+                dry_run,
+                force,
+                platform,
+                project_filter: project,
+                user_filter: user,
+            };
+            cleanup::stop_all_instances(options, config).await
+        }
+        #[cfg(feature = "aws")]
         ResourceCommands::Insights => summary::show_insights(config, output_format).await,
+        #[cfg(feature = "aws")]
+        ResourceCommands::Budget {
+            enforce,
+            watch,
+            interval,
+        } => budget::handle_budget(config, enforce, watch, interval, output_format).await,
+        ResourceCommands::Schedule {
+            stop,
+            start,
+            project,
+            eventbridge,
+            role_arn,
+            remove,
+        } => {
+            let options = crate::schedule::ScheduleOptions {
+                project,
+                stop,
+                start,
+                eventbridge,
+                role_arn,
+                remove,
+            };
+            crate::schedule::handle_schedule(options, config, output_format).await
+        }
+        ResourceCommands::Tracker { command } => {
+            tracker::handle_command(command, config, output_format).await
+        }
+        #[cfg(not(feature = "aws"))]
+        _ => Err(crate::error::ConfigError::FeatureNotCompiled("aws".to_string()).into()),
     }
 }
 
+#[cfg(feature = "aws")]
 pub async fn show_quick_status(detailed: bool, config: &Config, output_format: &str) -> Result<()> {
     use crate::checkpoint;
     use console::Style;
@@ -0,0 +1,136 @@
+//! Background prefetch of EC2 state for listing commands
+//!
+//! `status` and `resources list|summary|insights` spend most of their time
+//! loading the SDK config, calling DescribeInstances and looking up prices.
+//! `start` kicks that work off from `main` before the config is loaded and the
+//! arguments are parsed, and `instances` hands the result to every caller on
+//! the command's path, so a command makes one DescribeInstances call instead
+//! of one per helper.
+
+use super::aws::{describe_instances, ec2_client};
+use crate::error::Result;
+use aws_sdk_ec2::operation::describe_instances::DescribeInstancesOutput;
+use aws_sdk_ec2::Client as Ec2Client;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// How long a fetched snapshot is reused; shorter than any watch interval
+/// worth using, so refreshes still see new state
+const MAX_AGE: Duration = Duration::from_secs(2);
+
+/// Commands (and subcommands) that list EC2 instances
+const PREFETCH_COMMANDS: &[(&str, Option<&str>)] = &[
+    ("status", None),
+    ("resources", Some("list")),
+    ("resources", Some("summary")),
+    ("resources", Some("insights")),
+];
+
+struct Snapshot {
+    fetched: Instant,
+    output: DescribeInstancesOutput,
+}
+
+static SNAPSHOT: Mutex<Option<Snapshot>> = Mutex::const_new(None);
+
+/// Whether the raw command line is a listing command that reads AWS state
+///
+/// Aliases aren't expanded yet at this point, so an alias for one of these
+/// commands just runs without the head start.
+pub fn wants_prefetch(args: &[String]) -> bool {
+    let Some(index) = crate::aliases::command_index(args) else {
+        return false;
+    };
+    let rest = &args[index + 1..];
+    if rest.iter().any(|a| a == "-h" || a == "--help") {
+        return false;
+    }
+    let subcommand = rest.first().map(String::as_str);
+    let listed = PREFETCH_COMMANDS
+        .iter()
+        .any(|(cmd, sub)| *cmd == args[index] && (sub.is_none() || *sub == subcommand));
+    if !listed {
+        return false;
+    }
+    // `--platform runpod` and the like never touch EC2
+    let platform = rest
+        .iter()
+        .position(|a| a == "--platform")
+        .and_then(|i| rest.get(i + 1).map(String::as_str))
+        .or_else(|| rest.iter().find_map(|a| a.strip_prefix("--platform=")));
+    matches!(platform, None | Some("aws") | Some("all"))
+}
+
+/// Start fetching instances and prices in the background if `args` will need them
+///
+/// Failures are left for the command itself to hit and report.
+pub fn start(args: &[String]) {
+    if !wants_prefetch(args) {
+        return;
+    }
+    tokio::spawn(async {
+        let client = ec2_client().await;
+        if let Err(e) = instances(&client).await {
+            tracing::debug!("Background instance prefetch failed: {}", e);
+        }
+    });
+}
+
+/// All EC2 instances, with their prices refreshed
+///
+/// Returns the prefetched snapshot while it is fresh, otherwise fetches (and
+/// stores) a new one. The lock is held across the fetch, so a caller that
+/// arrives while the background fetch is in flight waits for it rather than
+/// issuing its own.
+pub(crate) async fn instances(client: &Ec2Client) -> Result<DescribeInstancesOutput> {
+    let mut snapshot = SNAPSHOT.lock().await;
+    if let Some(s) = snapshot.as_ref().filter(|s| s.fetched.elapsed() < MAX_AGE) {
+        return Ok(s.output.clone());
+    }
+    let output = describe_instances(client).await?;
+    crate::aws_pricing::prefetch_reservations(client, output.reservations()).await;
+    *snapshot = Some(Snapshot {
+        fetched: Instant::now(),
+        output: output.clone(),
+    });
+    Ok(output)
+}
+
+/// Drop the snapshot so the next `instances` call fetches again
+///
+/// `runctl shell` calls this before each line, since the previous command
+/// may have changed instance state.
+pub async fn invalidate() {
+    *SNAPSHOT.lock().await = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        std::iter::once("runctl")
+            .chain(line.split_whitespace())
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_listing_commands_prefetch() {
+        assert!(wants_prefetch(&args("status")));
+        assert!(wants_prefetch(&args("status --detailed")));
+        assert!(wants_prefetch(&args("--output json resources list")));
+        assert!(wants_prefetch(&args("resources list --platform aws")));
+        assert!(wants_prefetch(&args("resources summary")));
+    }
+
+    #[test]
+    fn test_other_commands_do_not() {
+        assert!(!wants_prefetch(&args("")));
+        assert!(!wants_prefetch(&args("aws create g5.xlarge")));
+        assert!(!wants_prefetch(&args("resources cleanup")));
+        assert!(!wants_prefetch(&args("resources list --platform runpod")));
+        assert!(!wants_prefetch(&args("resources list --platform=local")));
+        assert!(!wants_prefetch(&args("status --help")));
+    }
+}
@@ -0,0 +1,96 @@
+//! Listing for every non-AWS provider in the registry
+//!
+//! AWS keeps its own richer section (costs, tracker sync, filters); every
+//! other provider is listed through `TrainingProvider::list_resources`, so a
+//! newly registered provider shows up here without changes to this module.
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::provider::ResourceStatus;
+use crate::providers::{ProviderRegistry, KNOWN_PROVIDERS};
+use tokio::task::JoinHandle;
+
+/// Resources fetched for one provider section
+pub(crate) enum ProviderListing {
+    /// The provider was asked for by name but is not configured
+    NotConfigured,
+    Resources(Vec<ResourceStatus>),
+    /// The API call failed; shown as a warning rather than failing the listing
+    Failed(String),
+}
+
+/// Start fetching every wanted provider in the background, in name order
+///
+/// Unconfigured providers are skipped unless `platform` names them directly.
+pub(crate) async fn spawn_fetches(
+    config: &Config,
+    platform: &str,
+) -> Result<Vec<(String, JoinHandle<ProviderListing>)>> {
+    let registry = ProviderRegistry::from_config(config).await?;
+    let mut fetches = Vec::new();
+    for &name in KNOWN_PROVIDERS {
+        if name == "aws" || (platform != "all" && platform != name) {
+            continue;
+        }
+        let handle = match registry.get(name) {
+            Ok(provider) => tokio::spawn(async move {
+                match provider.list_resources().await {
+                    Ok(resources) => ProviderListing::Resources(resources),
+                    Err(e) => ProviderListing::Failed(e.to_string()),
+                }
+            }),
+            Err(_) if platform == name => tokio::spawn(async { ProviderListing::NotConfigured }),
+            Err(_) => continue,
+        };
+        fetches.push((name.to_string(), handle));
+    }
+    Ok(fetches)
+}
+
+/// Print one provider section
+pub(crate) fn render_provider_resources(name: &str, listing: &ProviderListing, detailed: bool) {
+    println!("\n{} RESOURCES:", name.to_uppercase());
+    println!("{}", "-".repeat(80));
+
+    let resources = match listing {
+        ProviderListing::NotConfigured => {
+            println!("  Not configured");
+            return;
+        }
+        ProviderListing::Failed(e) => {
+            println!("WARNING: Failed to list resources: {}", e);
+            return;
+        }
+        ProviderListing::Resources(resources) => resources,
+    };
+
+    if resources.is_empty() {
+        println!("  No resources found");
+        return;
+    }
+
+    for resource in resources {
+        let name = resource.name.as_deref().unwrap_or("-");
+        let instance_type = resource.instance_type.as_deref().unwrap_or("-");
+        if detailed {
+            println!(
+                "  {}  {}  {}  {}  ${:.3}/hr  {}",
+                resource.id,
+                name,
+                instance_type,
+                resource.state,
+                resource.cost_per_hour,
+                resource
+                    .public_ip
+                    .as_deref()
+                    .or(resource.public_ipv6.as_deref())
+                    .unwrap_or("-")
+            );
+        } else {
+            println!(
+                "  {}  {}  {}  {}",
+                resource.id, name, instance_type, resource.state
+            );
+        }
+    }
+}
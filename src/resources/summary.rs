@@ -1,19 +1,17 @@
 //! Resource summary and insights
 
 use crate::config::Config;
-use crate::error::{Result, TrainctlError};
+use crate::error::Result;
 use crate::resources::json;
 use crate::resources::types::{AwsInstance, ResourceSummary};
-use crate::resources::utils::estimate_instance_cost;
 use crate::utils::calculate_accumulated_cost;
-use aws_config::BehaviorVersion;
-use aws_sdk_ec2::Client as Ec2Client;
 use chrono::Utc;
 use console::style;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use tracing::info;
 
-use super::aws;
+use super::{accounts, aws, local};
 
 /// Show resource summary
 pub async fn show_summary(config: &Config, output_format: &str) -> Result<()> {
@@ -23,13 +21,9 @@ pub async fn show_summary(config: &Config, output_format: &str) -> Result<()> {
         return Ok(());
     }
 
-    // Sync ResourceTracker with current AWS state if available
+    // Describing instances also syncs the ResourceTracker with AWS state
+    let described = accounts::describe(config).await;
     if let Some(tracker) = &config.resource_tracker {
-        let aws_config = aws_config::load_defaults(BehaviorVersion::latest()).await;
-        let client = Ec2Client::new(&aws_config);
-        if let Err(e) = aws::sync_resource_tracker_with_aws(&client, tracker).await {
-            info!("Failed to sync ResourceTracker: {}", e);
-        }
         // Refresh all costs before generating summary
         tracker.refresh_costs().await;
     }
@@ -37,16 +31,25 @@ pub async fn show_summary(config: &Config, output_format: &str) -> Result<()> {
     let mut summary = ResourceSummary {
         aws_instances: Vec::new(),
         runpod_pods: Vec::new(),
+        azure_vms: Vec::new(),
         local_processes: Vec::new(),
         total_cost_estimate: 0.0,
         timestamp: Utc::now(),
     };
 
     // Collect AWS instances
-    let aws_config = aws_config::load_defaults(BehaviorVersion::latest()).await;
-    let client = Ec2Client::new(&aws_config);
-
-    if let Ok(response) = client.describe_instances().send().await {
+    let mut failed_accounts = Vec::new();
+    for listing in described {
+        let response = match listing.result {
+            Ok(response) => response,
+            Err(e) => {
+                match listing.account {
+                    Some(account) => failed_accounts.push((account, e.to_string())),
+                    None => info!("Failed to list EC2 instances: {}", e),
+                }
+                continue;
+            }
+        };
         let reservations = response.reservations();
         for reservation in reservations {
             let instances = reservation.instances();
@@ -69,10 +72,10 @@ pub async fn show_summary(config: &Config, output_format: &str) -> Result<()> {
                         if let Some(tracked) = tracker.get_by_id(&instance_id).await {
                             tracked.status.cost_per_hour
                         } else {
-                            estimate_instance_cost(&instance_type)
+                            crate::aws_pricing::instance_hourly_price(instance)
                         }
                     } else {
-                        estimate_instance_cost(&instance_type)
+                        crate::aws_pricing::instance_hourly_price(instance)
                     };
                     summary.total_cost_estimate += cost;
 
@@ -85,6 +88,7 @@ pub async fn show_summary(config: &Config, output_format: &str) -> Result<()> {
                             .and_then(|t| chrono::DateTime::from_timestamp(t.secs(), 0)),
                         tags: Vec::new(),
                         cost_per_hour: cost,
+                        account: listing.account.clone(),
                     });
                 }
             }
@@ -100,6 +104,14 @@ pub async fn show_summary(config: &Config, output_format: &str) -> Result<()> {
     );
     println!();
     println!("AWS Instances: {} running", summary.aws_instances.len());
+    for (account, error) in &failed_accounts {
+        println!(
+            "  {} account '{}' not listed: {}",
+            style("WARNING:").yellow().bold(),
+            account,
+            error
+        );
+    }
     println!("RunPod Pods: {}", summary.runpod_pods.len());
     println!("Local Processes: {}", summary.local_processes.len());
     println!();
@@ -112,6 +124,7 @@ pub async fn show_summary(config: &Config, output_format: &str) -> Result<()> {
         0.0
     };
     let mut type_breakdown: HashMap<String, (usize, f64, f64)> = HashMap::new();
+    let mut account_breakdown: BTreeMap<String, (usize, f64, f64)> = BTreeMap::new();
 
     for inst in &summary.aws_instances {
         // Use ResourceTracker accumulated cost if available
@@ -146,6 +159,15 @@ pub async fn show_summary(config: &Config, output_format: &str) -> Result<()> {
         entry.0 += 1;
         entry.1 += inst.cost_per_hour;
         entry.2 += accumulated;
+
+        if let Some(account) = &inst.account {
+            let entry = account_breakdown
+                .entry(account.clone())
+                .or_insert((0, 0.0, 0.0));
+            entry.0 += 1;
+            entry.1 += inst.cost_per_hour;
+            entry.2 += accumulated;
+        }
     }
 
     // Cost threshold warnings
@@ -170,8 +192,15 @@ pub async fn show_summary(config: &Config, output_format: &str) -> Result<()> {
     println!("  daily:      ${:.2}", daily_cost);
     println!("  weekly:     ${:.2}", weekly_cost);
 
-    // Cost warnings
-    if summary.total_cost_estimate > hourly_threshold {
+    // Configured budgets replace the built-in thresholds
+    if let (false, Some(tracker)) = (config.budget.is_empty(), &config.resource_tracker) {
+        println!();
+        println!("BUDGETS:");
+        let budgets = tracker.evaluate_budgets(&config.budget).await;
+        super::budget::print_budgets(&budgets);
+        let account_budgets = tracker.evaluate_account_budgets(&config.budget).await;
+        super::budget::print_account_budgets(&account_budgets);
+    } else if summary.total_cost_estimate > hourly_threshold {
         println!();
         println!(
             "{} {}",
@@ -197,7 +226,7 @@ pub async fn show_summary(config: &Config, output_format: &str) -> Result<()> {
         );
     }
 
-    if daily_cost > daily_threshold {
+    if config.budget.is_empty() && daily_cost > daily_threshold {
         println!();
         println!(
             "{} {}",
@@ -211,7 +240,7 @@ pub async fn show_summary(config: &Config, output_format: &str) -> Result<()> {
         );
     }
 
-    if total_accumulated > accumulated_threshold {
+    if config.budget.is_empty() && total_accumulated > accumulated_threshold {
         println!();
         println!(
             "{} {}",
@@ -228,6 +257,24 @@ pub async fn show_summary(config: &Config, output_format: &str) -> Result<()> {
 
     println!();
 
+    if !config.accounts.is_empty() {
+        println!("Cost Breakdown by Account:");
+        for name in config.accounts.keys() {
+            let (count, hourly, accumulated) =
+                account_breakdown.get(name).copied().unwrap_or_default();
+            let failed = failed_accounts.iter().any(|(account, _)| account == name);
+            println!(
+                "  {}: {} instance(s), ${:.4}/hr, ${:.2} total{}",
+                style(name).cyan(),
+                count,
+                hourly,
+                accumulated,
+                if failed { " (not listed)" } else { "" }
+            );
+        }
+        println!();
+    }
+
     if !type_breakdown.is_empty() {
         println!("Cost Breakdown by Instance Type:");
         let mut type_keys: Vec<_> = type_breakdown.keys().collect();
@@ -253,9 +300,14 @@ pub async fn show_summary(config: &Config, output_format: &str) -> Result<()> {
             } else {
                 0.0
             };
+            let account = inst
+                .account
+                .as_deref()
+                .map(|a| format!(" [{}]", a))
+                .unwrap_or_default();
             println!(
-                "  {} ({}) - ${:.4}/hr (${:.2} total)",
-                inst.instance_id, inst.instance_type, inst.cost_per_hour, accumulated
+                "  {} ({}){} - ${:.4}/hr (${:.2} total)",
+                inst.instance_id, inst.instance_type, account, inst.cost_per_hour, accumulated
             );
         }
     }
@@ -276,14 +328,7 @@ pub async fn show_insights(config: &Config, output_format: &str) -> Result<()> {
     println!("{}", "=".repeat(80));
 
     // Analyze resources and provide recommendations
-    let aws_config = aws_config::load_defaults(BehaviorVersion::latest()).await;
-    let client = Ec2Client::new(&aws_config);
-
-    let response = client
-        .describe_instances()
-        .send()
-        .await
-        .map_err(|e| TrainctlError::Aws(format!("Failed to list instances: {}", e)))?;
+    let response = super::prefetch::instances(&aws::ec2_client().await).await?;
 
     let mut running = 0;
     let mut stopped = 0;
@@ -302,11 +347,7 @@ pub async fn show_insights(config: &Config, output_format: &str) -> Result<()> {
             match state_str.as_str() {
                 "running" => {
                     running += 1;
-                    let instance_type_str = instance
-                        .instance_type()
-                        .map(|t| format!("{}", t))
-                        .unwrap_or_else(|| "unknown".to_string());
-                    total_cost += estimate_instance_cost(&instance_type_str);
+                    total_cost += crate::aws_pricing::instance_hourly_price(instance);
 
                     // Check age
                     if let Some(lt) = instance
@@ -324,10 +365,22 @@ pub async fn show_insights(config: &Config, output_format: &str) -> Result<()> {
         }
     }
 
+    let checkpoint_dir = config
+        .local
+        .as_ref()
+        .map(|c| c.checkpoint_dir.clone())
+        .unwrap_or_else(|| PathBuf::from("checkpoints"));
+    let local_zombies = local::find_local_zombies(
+        Path::new(".runctl"),
+        &checkpoint_dir,
+        local::LOCAL_STALL_THRESHOLD,
+    );
+
     println!("\nCURRENT STATE:");
     println!("  Running instances: {}", running);
     println!("  Stopped instances: {}", stopped);
     println!("  Estimated hourly cost: ${:.2}", total_cost);
+    println!("  Local training zombies: {}", local_zombies.len());
 
     println!("\nRecommendations:");
 
@@ -352,6 +405,19 @@ pub async fn show_insights(config: &Config, output_format: &str) -> Result<()> {
         );
     }
 
+    if !local_zombies.is_empty() {
+        println!(
+            "WARNING: {} local training zombie(s) (orphaned, stalled, or stale files):",
+            local_zombies.len()
+        );
+        for zombie in &local_zombies {
+            match zombie.pid {
+                Some(pid) => println!("  PID {}: {}", pid, zombie.describe()),
+                None => println!("  {}: {}", zombie.command, zombie.describe()),
+            }
+        }
+    }
+
     if running == 0 {
         println!("No running instances");
     }
@@ -360,6 +426,9 @@ pub async fn show_insights(config: &Config, output_format: &str) -> Result<()> {
     println!("  runctl resources list --detailed    # See all resources");
     println!("  runctl resources cleanup --dry-run  # Preview cleanup");
     println!("  runctl resources cleanup --force    # Cleanup zombies");
+    if !local_zombies.is_empty() {
+        println!("  runctl resources cleanup --platform local  # Review and kill local zombies");
+    }
 
     Ok(())
 }
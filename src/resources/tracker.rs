@@ -0,0 +1,80 @@
+//! `runctl resources tracker` commands
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::resource_tracking::{repair_store, RepairReport, DEFAULT_STORE_PATH};
+use clap::Subcommand;
+use std::path::PathBuf;
+
+#[derive(Subcommand, Clone)]
+pub enum TrackerCommands {
+    /// Recover the tracker file after a crash or interrupted write
+    ///
+    /// Restores a damaged file from its backup, drops entries that don't
+    /// parse, and cleans up temp files. The damaged file is kept as resources.json.damaged-<time>.
+    ///
+    /// Examples:
+    ///   runctl resources tracker repair
+    ///   runctl resources tracker repair --dry-run
+    Repair {
+        /// Show what would change without touching any file
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+pub async fn handle_command(
+    cmd: TrackerCommands,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    match cmd {
+        TrackerCommands::Repair { dry_run } => {
+            let path = config
+                .resource_tracker
+                .as_ref()
+                .and_then(|t| t.store_path().map(|p| p.to_path_buf()))
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_STORE_PATH));
+            let report = repair_store(&path, dry_run).await?;
+            if output_format == "json" {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print_report(&report);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn print_report(report: &RepairReport) {
+    let prefix = if report.dry_run { "[DRY RUN] " } else { "" };
+    if report.is_healthy() {
+        println!(
+            "{} is healthy ({} resources)",
+            report.path.display(),
+            report.resources
+        );
+        return;
+    }
+    if let Some(source) = &report.recovered_from {
+        println!(
+            "{}Recovered {} from {}",
+            prefix,
+            report.path.display(),
+            source
+        );
+    }
+    if report.dropped_entries > 0 {
+        println!(
+            "{}Dropped {} unreadable entries",
+            prefix, report.dropped_entries
+        );
+    }
+    if let Some(damaged) = &report.damaged_file {
+        println!("{}Kept damaged file as {}", prefix, damaged.display());
+    }
+    if report.temp_file_removed {
+        println!("{}Removed temp file from an interrupted write", prefix);
+    }
+    println!("{} resources tracked", report.resources);
+}
@@ -1,7 +1,7 @@
 //! Type definitions for resource management
 //!
 //! Contains data structures for representing resources across different platforms
-//! (AWS, RunPod, local) and options for listing and filtering resources.
+//! (AWS, RunPod, Azure, local) and options for listing and filtering resources.
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -11,6 +11,8 @@ use serde::{Deserialize, Serialize};
 pub struct ResourceSummary {
     pub aws_instances: Vec<AwsInstance>,
     pub runpod_pods: Vec<RunPodPod>,
+    #[serde(default)]
+    pub azure_vms: Vec<AzureVm>,
     pub local_processes: Vec<LocalProcess>,
     pub total_cost_estimate: f64,
     pub timestamp: DateTime<Utc>,
@@ -25,6 +27,9 @@ pub struct AwsInstance {
     pub launch_time: Option<DateTime<Utc>>,
     pub tags: Vec<(String, String)>,
     pub cost_per_hour: f64,
+    /// `[accounts]` entry the instance was listed from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
 }
 
 /// RunPod pod information
@@ -38,6 +43,18 @@ pub struct RunPodPod {
     pub cost_per_hour: f64,
 }
 
+/// Azure VM information
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AzureVm {
+    pub name: String,
+    pub vm_size: String,
+    pub state: String,
+    pub public_ip: Option<String>,
+    pub spot: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub cost_per_hour: f64,
+}
+
 /// Local process information
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LocalProcess {
@@ -63,6 +80,19 @@ pub struct ListResourcesOptions {
     pub export_file: Option<String>,
     pub project_filter: Option<String>,
     pub user_filter: Option<String>,
+    /// Only instances listed from this `[accounts]` entry
+    pub account_filter: Option<String>,
+}
+
+/// Options for `resources stop-all`
+#[derive(Debug, Clone)]
+pub struct StopAllOptions {
+    pub dry_run: bool,
+    pub force: bool,
+    /// Platform to stop: aws, runpod, local, or all
+    pub platform: String,
+    pub project_filter: Option<String>,
+    pub user_filter: Option<String>,
 }
 
 /// AWS instance information for display
@@ -78,9 +108,12 @@ pub struct InstanceInfo {
     pub is_spot: bool,
     pub _spot_request_id: Option<String>,
     pub public_ip: Option<String>,
+    pub public_ipv6: Option<String>,
     pub private_ip: Option<String>,
     pub tags: Vec<(String, String)>,
     pub is_old: bool,
+    /// `[accounts]` entry the instance was listed from
+    pub account: Option<String>,
 }
 
 /// Options for listing AWS instances
@@ -94,4 +127,5 @@ pub struct ListAwsInstancesOptions {
     pub show_terminated: bool,
     pub project_filter: Option<String>,
     pub user_filter: Option<String>,
+    pub account_filter: Option<String>,
 }
@@ -3,11 +3,17 @@
 use crate::config::Config;
 use crate::error::Result;
 use crate::resources::aws;
+#[cfg(feature = "dashboard")]
+use crate::resources::types::ListAwsInstancesOptions;
 use crate::resources::types::ListResourcesOptions;
 use chrono::Utc;
 use std::io::{self, Write};
 
 /// List resources in watch mode (continuous updates)
+///
+/// On a terminal, with the `dashboard` feature, EC2 instances are shown in
+/// an interactive table that can stop, terminate and tail logs (see
+/// `watch_tui`). Otherwise the listing is reprinted every interval.
 pub async fn list_resources_watch(
     config: &Config,
     platform: &str,
@@ -16,7 +22,27 @@ pub async fn list_resources_watch(
     interval: u64,
     project_filter: Option<&str>,
     user_filter: Option<&str>,
+    account_filter: Option<&str>,
 ) -> Result<()> {
+    #[cfg(feature = "dashboard")]
+    if matches!(platform, "aws" | "all")
+        && crate::prompt::can_prompt()
+        && std::io::IsTerminal::is_terminal(&io::stdout())
+    {
+        let options = ListAwsInstancesOptions {
+            detailed: false,
+            format: "table".to_string(),
+            filter: filter.to_string(),
+            sort: sort.map(|s| s.to_string()),
+            limit: None,
+            show_terminated: false,
+            project_filter: project_filter.map(|s| s.to_string()),
+            user_filter: user_filter.map(|s| s.to_string()),
+            account_filter: account_filter.map(|s| s.to_string()),
+        };
+        return super::watch_tui::run(options, interval, config).await;
+    }
+
     loop {
         // Clear screen (ANSI escape code)
         print!("\x1B[2J\x1B[1;1H");
@@ -41,6 +67,7 @@ pub async fn list_resources_watch(
             export_file: None,
             project_filter: project_filter.map(|s| s.to_string()),
             user_filter: user_filter.map(|s| s.to_string()),
+            account_filter: account_filter.map(|s| s.to_string()),
         };
         aws::list_resources(list_options, config).await?;
 
@@ -0,0 +1,451 @@
+//! Interactive `resources list --watch` (needs the `dashboard` feature)
+//!
+//! The same refresh loop as the plain watch mode, drawn as a table of EC2
+//! instances that can be acted on:
+//!
+//! - `Up` / `Down` (`k` / `j`): select an instance
+//! - `Enter`: toggle a detail pane (tags, addresses, launch time, costs)
+//! - `l`: toggle a tail of the selected instance's training log
+//! - `s` / `t`: stop / terminate the selected instance, after `y` confirms
+//! - `r`: refresh now, `q` / `Esc`: quit
+//!
+//! Stop and terminate suspend the TUI and run `aws stop` / `aws terminate`,
+//! so their graceful shutdown and protection checks apply.
+
+use super::aws::{fetch_aws_instances, select_instances};
+use super::types::{InstanceInfo, ListAwsInstancesOptions};
+use crate::config::Config;
+use crate::dashboard::{init_terminal, restore_terminal, run_suspended, Action};
+use crate::error::Result;
+use chrono::Utc;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState, Wrap},
+    Frame,
+};
+use std::time::{Duration, Instant};
+
+/// Log lines shown by `l`
+const LOG_TAIL_LINES: usize = 100;
+
+/// What the lower pane shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    None,
+    Details,
+    Logs,
+}
+
+struct WatchState {
+    instances: Vec<InstanceInfo>,
+    selected: usize,
+    pane: Pane,
+    /// Instance and lines of the log pane
+    log: Option<(String, Vec<String>)>,
+    /// Action waiting for `y`
+    pending: Option<(Action, String)>,
+    message: Option<String>,
+    last_update: Option<Instant>,
+}
+
+impl WatchState {
+    fn selected_instance(&self) -> Option<&InstanceInfo> {
+        self.instances.get(self.selected)
+    }
+}
+
+pub(super) async fn run(
+    options: ListAwsInstancesOptions,
+    interval_secs: u64,
+    config: &Config,
+) -> Result<()> {
+    let interval = Duration::from_secs(interval_secs.max(1));
+    let sdk_config = crate::aws_utils::shared_sdk_config().await;
+    let mut terminal = init_terminal()?;
+    let mut state = WatchState {
+        instances: Vec::new(),
+        selected: 0,
+        pane: Pane::None,
+        log: None,
+        pending: None,
+        message: None,
+        last_update: None,
+    };
+
+    let result: Result<()> = async {
+        loop {
+            let due = match state.last_update {
+                Some(t) => t.elapsed() >= interval,
+                None => true,
+            };
+            if due {
+                refresh(&mut state, &options, config).await;
+                if state.pane == Pane::Logs {
+                    state.log = None;
+                }
+                state.last_update = Some(Instant::now());
+            }
+            let log_due = match (state.selected_instance(), &state.log) {
+                (Some(inst), Some((id, _))) => &inst.id != id,
+                (Some(_), None) => true,
+                (None, _) => false,
+            };
+            if state.pane == Pane::Logs && log_due {
+                if let Some(inst) = state.selected_instance() {
+                    let instance_id = inst.id.clone();
+                    let sdk_config = account_sdk_config(config, inst, &sdk_config).await;
+                    let lines = match crate::aws::tail_log(
+                        &instance_id,
+                        LOG_TAIL_LINES,
+                        config,
+                        &sdk_config,
+                    )
+                    .await
+                    {
+                        Ok(lines) => lines,
+                        Err(e) => vec![format!("Could not read the training log: {}", e)],
+                    };
+                    state.log = Some((instance_id, lines));
+                }
+            }
+
+            terminal.draw(|f| render(f, &state, interval_secs))?;
+
+            if !event::poll(Duration::from_millis(250))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            if let Some((action, instance_id)) = state.pending.take() {
+                if key.code == KeyCode::Char('y') {
+                    let sdk_config = match state.instances.iter().find(|i| i.id == instance_id) {
+                        Some(inst) => account_sdk_config(config, inst, &sdk_config).await,
+                        None => sdk_config.clone(),
+                    };
+                    run_suspended(&mut terminal, action, &instance_id, config, &sdk_config).await?;
+                    super::prefetch::invalidate().await;
+                    state.message = None;
+                    state.last_update = None;
+                } else {
+                    state.message = Some(format!("Did not {} {}", action.verb(), instance_id));
+                }
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => break,
+                KeyCode::Up | KeyCode::Char('k') => {
+                    state.selected = state.selected.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    state.selected =
+                        (state.selected + 1).min(state.instances.len().saturating_sub(1));
+                }
+                KeyCode::Enter => {
+                    state.pane = if state.pane == Pane::Details {
+                        Pane::None
+                    } else {
+                        Pane::Details
+                    };
+                }
+                KeyCode::Char('l') => {
+                    state.pane = if state.pane == Pane::Logs {
+                        Pane::None
+                    } else {
+                        state.log = None;
+                        Pane::Logs
+                    };
+                }
+                KeyCode::Char('r') => {
+                    super::prefetch::invalidate().await;
+                    state.last_update = None;
+                }
+                KeyCode::Char('s') | KeyCode::Char('t') => {
+                    let action = if key.code == KeyCode::Char('s') {
+                        Action::Stop
+                    } else {
+                        Action::Terminate
+                    };
+                    if let Some(inst) = state.selected_instance() {
+                        state.pending = Some((action, inst.id.clone()));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+    .await;
+
+    restore_terminal(&mut terminal)?;
+    result
+}
+
+/// Re-fetch instances, keeping the same one selected
+async fn refresh(state: &mut WatchState, options: &ListAwsInstancesOptions, config: &Config) {
+    let listing = match fetch_aws_instances(config).await {
+        Ok(listing) => listing,
+        Err(e) => {
+            state.message = Some(e.to_string());
+            return;
+        }
+    };
+    let selected_id = state.selected_instance().map(|i| i.id.clone());
+    let instances: Vec<InstanceInfo> = select_instances(options, &listing.instances)
+        .into_iter()
+        .cloned()
+        .collect();
+    state.selected = selected_id
+        .and_then(|id| instances.iter().position(|i| i.id == id))
+        .unwrap_or(state.selected)
+        .min(instances.len().saturating_sub(1));
+    state.instances = instances;
+    state.message = if listing.failed_accounts.is_empty() {
+        None
+    } else {
+        let accounts: Vec<&str> = listing
+            .failed_accounts
+            .iter()
+            .map(|(account, _)| account.as_str())
+            .collect();
+        Some(format!("Could not list accounts: {}", accounts.join(", ")))
+    };
+}
+
+/// SDK config for acting on `inst`: its `[accounts]` entry's, or the default
+async fn account_sdk_config(
+    config: &Config,
+    inst: &InstanceInfo,
+    default: &aws_config::SdkConfig,
+) -> aws_config::SdkConfig {
+    match inst.account.as_deref() {
+        Some(account) => super::accounts::sdk_config_for(config, Some(account)).await,
+        None => default.clone(),
+    }
+}
+
+fn render(f: &mut Frame, state: &WatchState, interval_secs: u64) {
+    let has_pane = state.pane != Pane::None;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            if has_pane {
+                Constraint::Percentage(50)
+            } else {
+                Constraint::Min(0)
+            },
+            if has_pane {
+                Constraint::Min(0)
+            } else {
+                Constraint::Length(0)
+            },
+            Constraint::Length(1),
+        ])
+        .split(f.size());
+
+    let hourly: f64 = state
+        .instances
+        .iter()
+        .filter(|i| i.state == "running")
+        .map(|i| i.cost_per_hour)
+        .sum();
+    f.render_widget(
+        Paragraph::new(format!(
+            "WATCH: refreshing every {}s | {} instances | ${:.2}/hr running | {}",
+            interval_secs,
+            state.instances.len(),
+            hourly,
+            Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+        )),
+        chunks[0],
+    );
+
+    render_table(f, chunks[1], state);
+    match state.pane {
+        Pane::Details => render_details(f, chunks[2], state),
+        Pane::Logs => render_log(f, chunks[2], state),
+        Pane::None => {}
+    }
+    render_footer(f, chunks[3], state);
+}
+
+fn state_color(state: &str) -> Color {
+    match state {
+        "running" => Color::Green,
+        "stopped" | "stopping" => Color::Yellow,
+        "terminated" | "shutting-down" => Color::Red,
+        _ => Color::White,
+    }
+}
+
+fn render_table(f: &mut Frame, area: Rect, state: &WatchState) {
+    let rows: Vec<Row> = state
+        .instances
+        .iter()
+        .map(|inst| {
+            let project = inst
+                .tags
+                .iter()
+                .find(|(k, _)| k == "runctl:project")
+                .map(|(_, v)| v.clone())
+                .unwrap_or_else(|| "-".to_string());
+            Row::new(vec![
+                Cell::from(inst.id.clone()),
+                Cell::from(inst.instance_type.clone()),
+                Cell::from(inst.state.clone()).style(Style::default().fg(state_color(&inst.state))),
+                Cell::from(if inst.is_spot { "spot" } else { "on-demand" }),
+                Cell::from(project),
+                Cell::from(inst.runtime.clone().unwrap_or_else(|| "-".to_string())),
+                Cell::from(format!("${:.4}", inst.cost_per_hour)),
+                Cell::from(format!("${:.2}", inst.accumulated_cost)),
+            ])
+        })
+        .collect();
+    let widths = [
+        Constraint::Length(20),
+        Constraint::Length(14),
+        Constraint::Length(11),
+        Constraint::Length(10),
+        Constraint::Min(12),
+        Constraint::Length(12),
+        Constraint::Length(9),
+        Constraint::Length(10),
+    ];
+    let table = Table::new(rows, widths)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("AWS EC2 INSTANCES"),
+        )
+        .header(
+            Row::new(vec![
+                "ID", "Type", "State", "Pricing", "Project", "Runtime", "$/hr", "Total",
+            ])
+            .style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut table_state = TableState::default().with_selected(if state.instances.is_empty() {
+        None
+    } else {
+        Some(state.selected)
+    });
+    f.render_stateful_widget(table, area, &mut table_state);
+}
+
+fn render_details(f: &mut Frame, area: Rect, state: &WatchState) {
+    let block = Block::default().borders(Borders::ALL).title("Details");
+    let Some(inst) = state.selected_instance() else {
+        f.render_widget(Paragraph::new("No instance selected").block(block), area);
+        return;
+    };
+    let label = Style::default().fg(Color::Cyan);
+    let field = |name: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{}: ", name), label),
+            Span::raw(value),
+        ])
+    };
+    let or_dash = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".to_string());
+    let mut lines = vec![
+        field("Instance", format!("{} ({})", inst.id, inst.instance_type)),
+        field("State", inst.state.clone()),
+        field(
+            "Launched",
+            inst.launch_time
+                .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+                .unwrap_or_else(|| "-".to_string()),
+        ),
+        field(
+            "Cost",
+            format!(
+                "${:.4}/hr, ${:.2} so far{}",
+                inst.cost_per_hour,
+                inst.accumulated_cost,
+                if inst.is_spot { " (spot)" } else { "" }
+            ),
+        ),
+        field("Account", or_dash(&inst.account)),
+        field(
+            "Addresses",
+            format!(
+                "public {}, private {}",
+                or_dash(&inst.public_ip),
+                or_dash(&inst.private_ip)
+            ),
+        ),
+        Line::from(Span::styled("Tags:", label)),
+    ];
+    let mut tags = inst.tags.clone();
+    tags.sort();
+    lines.extend(
+        tags.into_iter()
+            .map(|(k, v)| Line::from(format!("  {} = {}", k, v))),
+    );
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+fn render_log(f: &mut Frame, area: Rect, state: &WatchState) {
+    let (title, lines) = match &state.log {
+        Some((id, lines)) => (format!("Training log: {}", id), lines.as_slice()),
+        None => ("Training log".to_string(), &[][..]),
+    };
+    let height = area.height.saturating_sub(2) as usize;
+    let start = lines.len().saturating_sub(height);
+    let text: Vec<Line> = lines[start..]
+        .iter()
+        .map(|l| Line::from(l.as_str()))
+        .collect();
+    let paragraph = if state.log.is_none() {
+        Paragraph::new("Loading...")
+    } else if text.is_empty() {
+        Paragraph::new("No log output")
+    } else {
+        Paragraph::new(text)
+    };
+    f.render_widget(
+        paragraph.block(Block::default().borders(Borders::ALL).title(title)),
+        area,
+    );
+}
+
+fn render_footer(f: &mut Frame, area: Rect, state: &WatchState) {
+    let line = if let Some((action, instance_id)) = &state.pending {
+        Line::from(Span::styled(
+            format!(
+                "Really {} {}? Press y to confirm, any other key to cancel",
+                action.verb(),
+                instance_id
+            ),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ))
+    } else if let Some(message) = &state.message {
+        Line::from(Span::styled(
+            message.clone(),
+            Style::default().fg(Color::Red),
+        ))
+    } else {
+        Line::from(Span::styled(
+            "q quit | j/k select | enter details | l logs | s stop | t terminate | r refresh",
+            Style::default().fg(Color::DarkGray),
+        ))
+    };
+    f.render_widget(Paragraph::new(line), area);
+}
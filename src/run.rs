@@ -0,0 +1,501 @@
+//! Provider-agnostic training runs (`runctl run --provider <name>`)
+//!
+//! Drives the whole lifecycle through `ProviderRegistry` and the
+//! `TrainingProvider` trait alone, so the same command works on AWS, RunPod,
+//! Azure or any other registered provider:
+//!
+//! 1. `create_resource` (the provider's default instance type unless
+//!    `--instance-type` is given)
+//! 2. `train`: sync code and start the script in the background
+//! 3. poll `training_status` every `--interval` until the job finishes,
+//!    fails, times out or is interrupted with Ctrl+C, then show the log
+//!    tail with `monitor`
+//! 4. `terminate`, unless `--keep`; with `--terminate-on-complete` only
+//!    after training succeeds, so a failed run can still be inspected
+//!
+//! With `--detach` the run stops after step 2 and leaves the resource
+//! running.
+//!
+//! Progress is saved as a `TrainingSession` in `.runctl/sessions` after each
+//! step. If the CLI dies or loses contact with the provider, the resource is
+//! left as is and `runctl run --resume` picks the run up at the step it
+//! reached: it starts training on a created resource, or goes back to
+//! waiting on one that is already training, then cleans up as the original
+//! invocation would have.
+
+use crate::config::Config;
+use crate::error::{JobOutcome, Result, TrainctlError};
+use crate::provider::{
+    CreateResourceOptions, ExecutionStatus, ResourceId, ResourceState, TrainingJob,
+    TrainingProvider,
+};
+use crate::providers::ProviderRegistry;
+use crate::training::{Cleanup, LaunchStage, TrainingSession, TrainingStatus};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Options for `runctl run`
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    /// Registered provider name (aws, runpod, azure, batch, k8s, lyceum)
+    pub provider: String,
+    pub script: PathBuf,
+    pub script_args: Vec<String>,
+    /// Provider-specific type (EC2 instance type, RunPod GPU, Azure VM size)
+    pub instance_type: Option<String>,
+    pub spot: bool,
+    /// What to do with the resource when the run ends
+    pub cleanup: Cleanup,
+    /// Return once training has started
+    pub detach: bool,
+    pub interval: Duration,
+    pub timeout: Option<Duration>,
+}
+
+/// Create a resource, train on it, wait for the result and clean up
+pub async fn run(options: RunOptions, config: &Config, output_format: &str) -> Result<()> {
+    let text = output_format != "json";
+    if !options.script.exists() {
+        return Err(TrainctlError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("Script not found: {}", options.script.display()),
+        )));
+    }
+
+    let registry = ProviderRegistry::from_config(config).await?;
+    let provider = registry.select(&options.provider)?;
+    let instance_type = options
+        .instance_type
+        .clone()
+        .or_else(|| provider.default_instance_type())
+        .ok_or_else(|| TrainctlError::Validation {
+            field: "instance_type".to_string(),
+            reason: format!(
+                "{} has no default instance type; pass --instance-type",
+                provider.name()
+            ),
+        })?;
+
+    if text {
+        println!(
+            "Creating {} on {}{} (~${:.3}/hr)...",
+            instance_type,
+            provider.name(),
+            if options.spot { " (spot)" } else { "" },
+            provider.estimate_cost(&instance_type, 1.0)
+        );
+    }
+    crate::progress::emit(
+        "run.create",
+        format!("{}: {}", provider.name(), instance_type),
+    );
+    let sessions_dir = PathBuf::from(".runctl");
+    let mut session = TrainingSession::launching(
+        provider.name().to_string(),
+        options.script.clone(),
+        config.checkpoint.dir.clone(),
+        instance_type.clone(),
+        options.spot,
+        options.script_args.clone(),
+    );
+    if let Some(launch) = &mut session.launch {
+        launch.cleanup = options.cleanup;
+        launch.detached = options.detach;
+    }
+    record_session(&session, &sessions_dir);
+
+    let project = crate::utils::get_project_name(None, config);
+    let create_options = CreateResourceOptions {
+        use_spot: options.spot,
+        tags: vec![("runctl:project".to_string(), project)],
+        ..Default::default()
+    };
+    let resource_id = match provider
+        .create_resource(&instance_type, create_options)
+        .await
+    {
+        Ok(id) => id,
+        Err(e) => {
+            session.status = TrainingStatus::Failed(e.to_string());
+            record_session(&session, &sessions_dir);
+            return Err(e);
+        }
+    };
+    session.advance(LaunchStage::ResourceCreated, Some(resource_id.clone()));
+    record_session(&session, &sessions_dir);
+    if text {
+        println!("Created {}", resource_id);
+    }
+
+    finish(
+        provider.as_ref(),
+        &resource_id,
+        &options,
+        true,
+        session,
+        text,
+    )
+    .await
+}
+
+/// Continue an interrupted run (`runctl run --resume [SESSION]`)
+///
+/// Without a session ID this picks the latest run whose runctl process is
+/// gone. Naming a session also attaches to a `--detach` run. `interval` and
+/// `timeout` apply from now; everything else comes from the saved session.
+pub async fn resume(
+    session_id: Option<&str>,
+    interval: Duration,
+    timeout: Option<Duration>,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    let text = output_format != "json";
+    let sessions_dir = PathBuf::from(".runctl");
+    let mut session = find_resumable(&sessions_dir, session_id)?;
+    let launch = session
+        .launch
+        .clone()
+        .ok_or_else(|| TrainctlError::Validation {
+            field: "session".to_string(),
+            reason: format!("{} was not started by runctl run", session.id),
+        })?;
+    let options = RunOptions {
+        provider: session.platform.clone(),
+        script: session.script.clone(),
+        script_args: launch.script_args.clone(),
+        instance_type: Some(launch.instance_type.clone()),
+        spot: launch.spot,
+        cleanup: launch.cleanup,
+        detach: false,
+        interval,
+        timeout,
+    };
+
+    let Some(resource_id) = launch.resource_id.clone() else {
+        // Cut off before the provider returned an ID: nothing to pick up
+        if text {
+            println!(
+                "Run {} stopped before {} returned a resource; starting it again",
+                short_id(&session.id),
+                session.platform
+            );
+            println!(
+                "   Check 'runctl resources list --platform {}' for a stray resource",
+                session.platform
+            );
+        }
+        session.status =
+            TrainingStatus::Failed("launch interrupted before a resource was created".into());
+        record_session(&session, &sessions_dir);
+        return run(options, config, output_format).await;
+    };
+
+    let registry = ProviderRegistry::from_config(config).await?;
+    let provider = registry.select(&session.platform)?;
+    let gone = match provider.get_resource_status(&resource_id).await {
+        Ok(status) => matches!(
+            status.state,
+            ResourceState::Terminated | ResourceState::Terminating
+        ),
+        Err(TrainctlError::ResourceNotFound { .. }) => true,
+        Err(e) => return Err(e),
+    };
+    if gone {
+        session.status = TrainingStatus::Failed(format!("{} no longer exists", resource_id));
+        record_session(&session, &sessions_dir);
+        return Err(TrainctlError::ResourceNotFound {
+            resource_type: format!("{} resource", session.platform),
+            resource_id,
+        });
+    }
+
+    let start_training = launch.stage != LaunchStage::TrainingStarted;
+    if start_training && !options.script.exists() {
+        return Err(TrainctlError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!(
+                "Script not found: {} (resume from the project directory)",
+                options.script.display()
+            ),
+        )));
+    }
+    session.runctl_pid = Some(std::process::id());
+    if let Some(launch) = &mut session.launch {
+        launch.detached = false;
+    }
+    record_session(&session, &sessions_dir);
+    if text {
+        println!(
+            "Resuming run {} on {} ({}): {}",
+            short_id(&session.id),
+            resource_id,
+            provider.name(),
+            if start_training {
+                "starting training"
+            } else {
+                "waiting for training"
+            }
+        );
+    }
+    finish(
+        provider.as_ref(),
+        &resource_id,
+        &options,
+        start_training,
+        session,
+        text,
+    )
+    .await
+}
+
+/// The session `--resume` continues: by ID prefix, else the latest interrupted run
+fn find_resumable(sessions_dir: &Path, session_id: Option<&str>) -> Result<TrainingSession> {
+    let sessions = TrainingSession::list_sessions(sessions_dir)?;
+    let found = match session_id {
+        Some(prefix) => sessions.into_iter().find(|s| {
+            s.id.starts_with(prefix)
+                && s.launch.is_some()
+                && matches!(
+                    s.status,
+                    TrainingStatus::Launching | TrainingStatus::Running
+                )
+        }),
+        // Newest first
+        None => sessions
+            .into_iter()
+            .find(TrainingSession::is_interrupted_run),
+    };
+    found.ok_or_else(|| TrainctlError::ResourceNotFound {
+        resource_type: "interrupted run".to_string(),
+        resource_id: session_id.unwrap_or("latest").to_string(),
+    })
+}
+
+fn short_id(id: &str) -> &str {
+    &id[..id.len().min(8)]
+}
+
+/// Train (unless already started), wait, then clean up as `options.cleanup` says
+async fn finish(
+    provider: &dyn TrainingProvider,
+    resource_id: &ResourceId,
+    options: &RunOptions,
+    start_training: bool,
+    mut session: TrainingSession,
+    text: bool,
+) -> Result<()> {
+    let sessions_dir = PathBuf::from(".runctl");
+    let started = Instant::now();
+    let result = train_and_wait(
+        provider,
+        resource_id,
+        options,
+        start_training,
+        text,
+        &mut session,
+        &sessions_dir,
+    )
+    .await;
+
+    // Lost track of a job that is still training: leave both for --resume
+    let training_started = session
+        .launch
+        .as_ref()
+        .is_some_and(|l| l.stage == LaunchStage::TrainingStarted);
+    if let Err(e) = &result {
+        if training_started && !matches!(e, TrainctlError::Job { .. }) {
+            if text {
+                eprintln!("WARNING: lost track of training on {}: {}", resource_id, e);
+                eprintln!(
+                    "   It is still running; pick it up with: runctl run --resume {}",
+                    short_id(&session.id)
+                );
+            }
+            return result;
+        }
+    }
+
+    if !(options.detach && result.is_ok()) {
+        session.status = match &result {
+            Ok(()) => TrainingStatus::Completed,
+            Err(TrainctlError::Job {
+                outcome: JobOutcome::Interrupted,
+                ..
+            }) => TrainingStatus::Interrupted,
+            Err(e) => TrainingStatus::Failed(e.to_string()),
+        };
+        record_session(&session, &sessions_dir);
+    }
+
+    if options.detach && result.is_ok() {
+        if text {
+            println!("Training started on {} ({})", resource_id, provider.name());
+            println!(
+                "   Terminate when done: runctl {} terminate {}",
+                provider.name(),
+                resource_id
+            );
+        } else {
+            print_result(provider.name(), resource_id, "started", started, false)?;
+        }
+        return Ok(());
+    }
+
+    let kept = !options.cleanup.terminates(result.is_ok());
+    if kept {
+        if text {
+            let reason = match options.cleanup {
+                Cleanup::Never => "--keep",
+                _ => "training did not complete",
+            };
+            println!("Keeping {} ({})", resource_id, reason);
+            if options.cleanup == Cleanup::OnSuccess {
+                println!(
+                    "   Terminate when done: runctl {} terminate {}",
+                    provider.name(),
+                    resource_id
+                );
+            }
+        }
+    } else {
+        if text {
+            println!("Terminating {}...", resource_id);
+        }
+        if let Err(e) = provider.terminate(resource_id).await {
+            warn!("Failed to terminate {}: {}", resource_id, e);
+            eprintln!(
+                "WARNING: could not terminate {}: {}; terminate it manually",
+                resource_id, e
+            );
+        }
+    }
+
+    let status = if result.is_ok() {
+        "completed"
+    } else {
+        "failed"
+    };
+    crate::progress::ProgressEvent::new("run.done", format!("{}: {}", resource_id, status))
+        .done()
+        .emit();
+    if result.is_ok() {
+        if text {
+            println!(
+                "Run completed in {}",
+                crate::utils::format_duration(started.elapsed().as_secs())
+            );
+        } else {
+            print_result(provider.name(), resource_id, status, started, kept)?;
+        }
+    }
+    result
+}
+
+/// Start training and, unless detached, wait for it to finish
+async fn train_and_wait(
+    provider: &dyn TrainingProvider,
+    resource_id: &ResourceId,
+    options: &RunOptions,
+    start_training: bool,
+    text: bool,
+    session: &mut TrainingSession,
+    sessions_dir: &Path,
+) -> Result<()> {
+    if start_training {
+        let job = TrainingJob {
+            script: options.script.clone(),
+            args: options.script_args.clone(),
+            data_source: None,
+            output_dest: None,
+            checkpoint_dir: None,
+            environment: vec![],
+        };
+        provider.train(resource_id, job).await?;
+        session.advance(LaunchStage::TrainingStarted, None);
+        record_session(session, sessions_dir);
+    }
+    crate::progress::emit("run.training", format!("Training on {}", resource_id));
+    if options.detach {
+        return Ok(());
+    }
+
+    if text {
+        println!("Waiting for training to finish (Ctrl+C to stop)...");
+    }
+    let started = Instant::now();
+    let outcome = loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {
+                break Err((JobOutcome::Interrupted, "interrupted".to_string()));
+            }
+            _ = tokio::time::sleep(options.interval) => {}
+        }
+        match provider.training_status(resource_id).await? {
+            ExecutionStatus::Pending | ExecutionStatus::Running => {
+                let elapsed = started.elapsed();
+                if options.timeout.is_some_and(|t| elapsed >= t) {
+                    break Err((
+                        JobOutcome::TimedOut,
+                        format!(
+                            "still running after {}",
+                            crate::utils::format_duration(elapsed.as_secs())
+                        ),
+                    ));
+                }
+                crate::progress::emit(
+                    "run.training",
+                    format!(
+                        "{} running for {}",
+                        resource_id,
+                        crate::utils::format_duration(elapsed.as_secs())
+                    ),
+                );
+            }
+            ExecutionStatus::Completed => break Ok(()),
+            ExecutionStatus::Failed(reason) => break Err((JobOutcome::Failed, reason)),
+            ExecutionStatus::Cancelled => {
+                break Err((JobOutcome::Interrupted, "cancelled".to_string()))
+            }
+        }
+    };
+
+    if text {
+        // Best effort: show how the job ended before the resource goes away
+        if let Err(e) = provider.monitor(resource_id, false).await {
+            warn!("Failed to fetch the log from {}: {}", resource_id, e);
+        }
+    }
+    outcome.map_err(|(outcome, message)| TrainctlError::Job {
+        job_id: resource_id.clone(),
+        outcome,
+        message,
+    })
+}
+
+/// Save launch progress; a failed write must not abort the run
+fn record_session(session: &TrainingSession, sessions_dir: &Path) {
+    if let Err(e) = session.save(sessions_dir) {
+        warn!("Failed to save session {}: {}", session.id, e);
+    }
+}
+
+fn print_result(
+    provider: &str,
+    resource_id: &str,
+    status: &str,
+    started: Instant,
+    kept: bool,
+) -> Result<()> {
+    let result = serde_json::json!({
+        "success": true,
+        "provider": provider,
+        "resource_id": resource_id,
+        "status": status,
+        "elapsed_secs": started.elapsed().as_secs(),
+        "kept": kept,
+    });
+    println!("{}", serde_json::to_string_pretty(&result)?);
+    Ok(())
+}
@@ -1,31 +1,64 @@
 //! RunPod integration
 //!
 //! Provides functionality for managing RunPod GPU pods:
-//! - Create and manage pods
+//! - Create, list, stop and terminate pods (RunPod API, see `providers::RunpodClient`)
 //! - Execute training on pods
 //! - Monitor pod status
 //! - Download results from pods
+//!
+//! Pod lifecycle needs `[runpod] api_key` (or `RUNPOD_API_KEY`). Running
+//! commands on a pod and copying files still use `runpodctl`, since the API
+//! has no equivalent.
 
 use crate::config::Config;
 use crate::error::{Result, TrainctlError};
+use crate::providers::{CreatePodRequest, RunpodClient};
 use clap::Subcommand;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tracing::info;
 
+/// How long `create` waits for a new pod's container to start
+pub(crate) const POD_READY_TIMEOUT_SECS: u64 = 600;
+
+/// Where background training records its exit code
+const EXIT_CODE_PATH: &str = "/workspace/training.exit";
+
 #[derive(Subcommand, Clone)]
 pub enum RunpodCommands {
+    /// Create a pod and wait until it's running
+    ///
+    /// Examples:
+    ///   runctl runpod create --gpu "RTX 4090"
+    ///   runctl runpod create --gpu "NVIDIA A100 80GB PCIe" --disk 100 --name sweep-1
     Create {
+        /// Pod name (default: runctl-<random>)
+        #[arg(long)]
         name: Option<String>,
-        gpu: String,
-        disk: u32,
+        /// GPU type id or display name (default: [runpod] default_gpu)
+        #[arg(long)]
+        gpu: Option<String>,
+        /// Container disk in GB (default: [runpod] default_disk_gb)
+        #[arg(long)]
+        disk: Option<u32>,
     },
+    /// List pods on the account
+    List,
+    /// Stop a pod (the container disk is discarded)
+    Stop { pod_id: String },
+    /// Terminate (remove) a pod
+    Terminate { pod_id: String },
+    /// List GPU types with current hourly prices
+    Gpus,
     Train {
         pod_id: String,
         script: PathBuf,
+        #[arg(long)]
         background: bool,
     },
     Monitor {
         pod_id: String,
+        #[arg(long)]
         follow: bool,
     },
     Download {
@@ -35,14 +68,46 @@ pub enum RunpodCommands {
     },
 }
 
-pub async fn handle_command(cmd: RunpodCommands, config: &Config) -> Result<()> {
+pub async fn handle_command(
+    cmd: RunpodCommands,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
     match cmd {
-        RunpodCommands::Create { name, gpu, disk } => create_pod(name, gpu, disk, config).await,
+        RunpodCommands::Create { name, gpu, disk } => {
+            let defaults = config.runpod.as_ref();
+            let gpu = gpu
+                .or_else(|| defaults.map(|r| r.default_gpu.clone()))
+                .ok_or_else(|| {
+                    TrainctlError::Config(crate::error::ConfigError::MissingField(
+                        "runpod.default_gpu".to_string(),
+                    ))
+                })?;
+            let disk = disk
+                .or_else(|| defaults.map(|r| r.default_disk_gb))
+                .unwrap_or(30);
+            let pod_id = create_pod(name, gpu, disk, config).await?;
+            if output_format == "json" {
+                let pod = RunpodClient::from_config(config)?.get_pod(&pod_id).await?;
+                println!("{}", serde_json::to_string_pretty(&pod)?);
+            }
+            Ok(())
+        }
+        RunpodCommands::List => list_pods(config, output_format).await,
+        RunpodCommands::Stop { pod_id } => {
+            RunpodClient::from_config(config)?.stop_pod(&pod_id).await?;
+            print_result("stopped", &pod_id, output_format)
+        }
+        RunpodCommands::Terminate { pod_id } => {
+            remove_pod(&pod_id, config).await?;
+            print_result("terminated", &pod_id, output_format)
+        }
+        RunpodCommands::Gpus => list_gpu_types(config, output_format).await,
         RunpodCommands::Train {
             pod_id,
             script,
             background,
-        } => train_on_pod(pod_id, script, background, config).await,
+        } => train_on_pod(pod_id, script, background, &[], &[], config).await,
         RunpodCommands::Monitor { pod_id, follow } => monitor_pod(pod_id, follow).await,
         RunpodCommands::Download {
             pod_id,
@@ -52,19 +117,89 @@ pub async fn handle_command(cmd: RunpodCommands, config: &Config) -> Result<()>
     }
 }
 
-async fn create_pod(name: Option<String>, gpu: String, disk: u32, config: &Config) -> Result<()> {
-    info!("Creating RunPod pod: GPU={}, Disk={}GB", gpu, disk);
-
-    // Check for runpodctl
-    if which::which("runpodctl").is_err() {
-        return Err(TrainctlError::CloudProvider {
-            provider: "runpod".to_string(),
-            message: "runpodctl not found. Install from: https://github.com/runpod/runpodctl"
-                .to_string(),
-            source: None,
+fn print_result(action: &str, pod_id: &str, output_format: &str) -> Result<()> {
+    if output_format == "json" {
+        let result = serde_json::json!({
+            "success": true,
+            "pod_id": pod_id,
+            "action": action,
         });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        println!("Pod {} {}", pod_id, action);
+    }
+    Ok(())
+}
+
+async fn list_pods(config: &Config, output_format: &str) -> Result<()> {
+    let pods = RunpodClient::from_config(config)?.list_pods().await?;
+    if output_format == "json" {
+        println!("{}", serde_json::to_string_pretty(&pods)?);
+        return Ok(());
+    }
+    if pods.is_empty() {
+        println!("No pods found");
+        return Ok(());
+    }
+    println!(
+        "{:<16} {:<24} {:<22} {:<10} {:>8}",
+        "ID", "NAME", "GPU", "STATUS", "$/HR"
+    );
+    for pod in &pods {
+        println!(
+            "{:<16} {:<24} {:<22} {:<10} {:>8.3}",
+            pod.id,
+            pod.name,
+            pod.gpu(),
+            pod.status(),
+            pod.cost_per_hr.unwrap_or(0.0)
+        );
+    }
+    Ok(())
+}
+
+async fn list_gpu_types(config: &Config, output_format: &str) -> Result<()> {
+    let mut types = RunpodClient::from_config(config)?.gpu_types().await?;
+    types.sort_by(|a, b| {
+        a.lowest_price()
+            .unwrap_or(f64::MAX)
+            .total_cmp(&b.lowest_price().unwrap_or(f64::MAX))
+    });
+    if output_format == "json" {
+        println!("{}", serde_json::to_string_pretty(&types)?);
+        return Ok(());
+    }
+    println!(
+        "{:<36} {:<24} {:>6} {:>10} {:>10}",
+        "ID", "NAME", "GB", "SECURE", "COMMUNITY"
+    );
+    let price = |p: Option<f64>| match p.filter(|p| *p > 0.0) {
+        Some(p) => format!("${:.2}", p),
+        None => "-".to_string(),
+    };
+    for gpu in &types {
+        println!(
+            "{:<36} {:<24} {:>6} {:>10} {:>10}",
+            gpu.id,
+            gpu.display_name,
+            gpu.memory_in_gb.map(|m| m.to_string()).unwrap_or_default(),
+            price(gpu.secure_price),
+            price(gpu.community_price)
+        );
     }
+    Ok(())
+}
+
+/// Create a pod and return its ID once it's ready
+pub(crate) async fn create_pod(
+    name: Option<String>,
+    gpu: String,
+    disk: u32,
+    config: &Config,
+) -> Result<String> {
+    info!("Creating RunPod pod: GPU={}, Disk={}GB", gpu, disk);
 
+    let client = RunpodClient::from_config(config)?;
     let pod_name =
         name.unwrap_or_else(|| format!("runctl-{}", &uuid::Uuid::new_v4().to_string()[..8]));
 
@@ -74,57 +209,44 @@ async fn create_pod(name: Option<String>, gpu: String, disk: u32, config: &Confi
         ))
     })?;
 
-    let image = &runpod_config.default_image;
-
-    // Create pod using runpodctl
-    let mut cmd = std::process::Command::new("runpodctl");
-    cmd.args(["create", "pod"]);
-    cmd.arg("--name").arg(&pod_name);
-    cmd.arg("--imageName").arg(image);
-    cmd.arg("--gpuType").arg(&gpu);
-    cmd.arg("--containerDiskSize").arg(disk.to_string());
-    cmd.arg("--mem").arg("32");
-
-    info!("Executing: {:?}", cmd);
-
-    let output = cmd.output().map_err(|e| {
-        TrainctlError::Io(std::io::Error::other(format!(
-            "Failed to execute runpodctl: {}",
-            e
-        )))
-    })?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(TrainctlError::CloudProvider {
-            provider: "runpod".to_string(),
-            message: format!("Failed to create pod: {}", stderr),
-            source: None,
-        });
-    }
-
-    // Extract pod ID from output
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let pod_id = extract_pod_id(&stdout).ok_or_else(|| TrainctlError::CloudProvider {
-        provider: "runpod".to_string(),
-        message: "Could not extract pod ID from output".to_string(),
-        source: None,
-    })?;
+    let request = CreatePodRequest {
+        name: pod_name,
+        image: runpod_config.default_image.clone(),
+        gpu,
+        gpu_count: 1,
+        container_disk_gb: disk,
+        min_memory_gb: 32,
+    };
+    crate::progress::emit("create.request", format!("Requesting {} pod", request.gpu));
+    let pod = client.create_pod(&request).await?;
+    crate::progress::emit("create.launched", format!("Launched {}", pod.id));
 
-    println!("Pod created: {}", pod_id);
+    println!("Pod created: {}", pod.id);
     println!("   Waiting for pod to be ready...");
 
-    // Wait for pod to be ready (simplified - in real impl would poll status)
-    tokio::time::sleep(tokio::time::Duration::from_secs(30)).await;
+    let pod = client
+        .wait_until_ready(&pod.id, Duration::from_secs(POD_READY_TIMEOUT_SECS))
+        .await?;
+    crate::progress::ProgressEvent::new("create.ready", format!("{} is ready", pod.id))
+        .done()
+        .emit();
 
-    println!("Pod ready: {}", pod_id);
-    Ok(())
+    println!(
+        "Pod ready: {} ({}, ${:.3}/hr)",
+        pod.id,
+        pod.gpu(),
+        pod.cost_per_hr.unwrap_or(0.0)
+    );
+    Ok(pod.id)
 }
 
-async fn train_on_pod(
+/// Upload a script to a pod and run it with `script_args`
+pub(crate) async fn train_on_pod(
     pod_id: String,
     script: PathBuf,
     background: bool,
+    script_args: &[String],
+    env: &[(String, String)],
     _config: &Config,
 ) -> Result<()> {
     info!("Starting training on pod: {}", pod_id);
@@ -137,7 +259,7 @@ async fn train_on_pod(
     }
 
     // Upload script to pod
-    println!("📤 Uploading script to pod...");
+    println!("Uploading script to pod...");
     let mut upload_cmd = std::process::Command::new("runpodctl");
     upload_cmd.args(["send", pod_id.as_str()]);
     upload_cmd.arg(&script);
@@ -159,10 +281,24 @@ async fn train_on_pod(
     }
 
     // Execute training
+    let args: String = script_args
+        .iter()
+        .map(|arg| format!(" {}", crate::utils::shell_quote(arg)))
+        .collect();
+    let exports = crate::utils::env_exports(env);
     let exec_cmd = if background {
-        "nohup bash /workspace/training_script > /workspace/training.log 2>&1 &".to_string()
+        // Record the exit code so `training_exit_code` can tell when it's done
+        let run = format!(
+            "{}bash /workspace/training_script{}; echo $? > {}",
+            exports, args, EXIT_CODE_PATH
+        );
+        format!(
+            "rm -f {}; nohup bash -c {} > /workspace/training.log 2>&1 &",
+            EXIT_CODE_PATH,
+            crate::utils::shell_quote(&run)
+        )
     } else {
-        "bash /workspace/training_script".to_string()
+        format!("{}bash /workspace/training_script{}", exports, args)
     };
 
     let mut train_cmd = std::process::Command::new("runpodctl");
@@ -188,7 +324,7 @@ async fn train_on_pod(
     Ok(())
 }
 
-async fn monitor_pod(pod_id: String, follow: bool) -> Result<()> {
+pub(crate) async fn monitor_pod(pod_id: String, follow: bool) -> Result<()> {
     let log_path = "/workspace/training.log";
 
     if follow {
@@ -202,15 +338,22 @@ async fn monitor_pod(pod_id: String, follow: bool) -> Result<()> {
         cmd.args(["exec", &pod_id, "--"]);
         cmd.args(["tail", "-n", "50", log_path]);
         let output = cmd.output()?;
-        print!("{}", String::from_utf8_lossy(&output.stdout));
+        print!(
+            "{}",
+            crate::output_style::sanitize(&String::from_utf8_lossy(&output.stdout))
+        );
     }
 
     Ok(())
 }
 
-async fn download_from_pod(pod_id: String, remote: PathBuf, local: PathBuf) -> Result<()> {
+pub(crate) async fn download_from_pod(
+    pod_id: String,
+    remote: PathBuf,
+    local: PathBuf,
+) -> Result<()> {
     println!(
-        "📥 Downloading from pod {}: {} -> {}",
+        "Downloading from pod {}: {} -> {}",
         pod_id,
         remote.display(),
         local.display()
@@ -240,17 +383,67 @@ async fn download_from_pod(pod_id: String, remote: PathBuf, local: PathBuf) -> R
     Ok(())
 }
 
-fn extract_pod_id(output: &str) -> Option<String> {
-    // Try to extract pod ID from runpodctl output
-    // Pattern: "pod-xxxxx" or just the ID
-    let re = regex::Regex::new(r"pod-([a-z0-9]+)").ok()?;
-    if let Some(caps) = re.captures(output) {
-        return Some(caps.get(1)?.as_str().to_string());
+/// Run a shell script on a pod and return its stdout
+pub(crate) fn exec_on_pod(pod_id: &str, script: &str) -> Result<String> {
+    let output = std::process::Command::new("runpodctl")
+        .args(["exec", pod_id, "--", "bash", "-c", script])
+        .output()
+        .map_err(|e| {
+            TrainctlError::Io(std::io::Error::other(format!(
+                "Failed to execute runpodctl: {}",
+                e
+            )))
+        })?;
+    if !output.status.success() {
+        return Err(TrainctlError::CloudProvider {
+            provider: "runpod".to_string(),
+            message: format!(
+                "Command failed on pod {}: {}",
+                pod_id,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            source: None,
+        });
     }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Exit code of the last background training run, or None while it runs
+pub(crate) fn training_exit_code(pod_id: &str) -> Result<Option<i32>> {
+    let output = exec_on_pod(
+        pod_id,
+        &format!("cat {} 2>/dev/null || true", EXIT_CODE_PATH),
+    )?;
+    crate::utils::parse_exit_code(&output)
+}
+
+/// Upload a local file to a pod
+#[cfg_attr(not(feature = "aws"), allow(dead_code))]
+pub(crate) fn upload_to_pod(pod_id: &str, local: &Path, remote: &str) -> Result<()> {
+    let status = std::process::Command::new("runpodctl")
+        .args(["send", pod_id])
+        .arg(local)
+        .arg(remote)
+        .status()
+        .map_err(|e| {
+            TrainctlError::Io(std::io::Error::other(format!(
+                "Failed to upload to pod: {}",
+                e
+            )))
+        })?;
+    if !status.success() {
+        return Err(TrainctlError::CloudProvider {
+            provider: "runpod".to_string(),
+            message: format!("Upload of {} failed", local.display()),
+            source: None,
+        });
+    }
+    Ok(())
+}
 
-    // Try alternative pattern
-    let re = regex::Regex::new(r"([a-z0-9]{13,})").ok()?;
-    re.captures(output)
-        .and_then(|caps| caps.get(1))
-        .map(|m| m.as_str().to_string())
+/// Remove (terminate) a pod
+pub(crate) async fn remove_pod(pod_id: &str, config: &Config) -> Result<()> {
+    RunpodClient::from_config(config)?
+        .terminate_pod(pod_id)
+        .await
 }
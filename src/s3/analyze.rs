@@ -0,0 +1,508 @@
+//! Usage analytics for training buckets
+//!
+//! `runctl s3 analyze` lists everything under a prefix and summarizes where
+//! the bytes are: by sub-prefix, by extension and by storage class, plus how
+//! much hasn't changed in N days. S3 does not expose per-object access times
+//! outside of S3 Inventory/Storage Lens, so "stale" uses last-modified, which
+//! is accurate for write-once artifacts like checkpoints.
+//!
+//! Recommendations are lifecycle rules scoped to the sub-prefixes holding the
+//! stale data, with an estimated monthly saving at list prices.
+
+use super::{format_size, parse_s3_path};
+use crate::error::{Result, TrainctlError};
+use aws_sdk_s3::Client as S3Client;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Objects smaller than this are billed as 128 KB in the IA classes
+const IA_MIN_BILLABLE_BYTES: u64 = 128 * 1024;
+
+/// Stale data below this isn't worth a lifecycle rule
+const MIN_RECOMMENDATION_BYTES: u64 = 1024 * 1024 * 1024;
+
+const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+const CHECKPOINT_EXTENSIONS: &[&str] = &["pt", "pth", "ckpt", "safetensors", "bin", "h5"];
+
+/// List price per GB-month for a storage class (us-east-1)
+fn storage_price_per_gb(class: &str) -> f64 {
+    match class {
+        "STANDARD_IA" => 0.0125,
+        "ONEZONE_IA" => 0.01,
+        "GLACIER_IR" => 0.004,
+        "GLACIER" => 0.0036,
+        "DEEP_ARCHIVE" => 0.00099,
+        "REDUCED_REDUNDANCY" => 0.024,
+        // STANDARD, INTELLIGENT_TIERING (frequent tier) and unknown classes
+        _ => 0.023,
+    }
+}
+
+/// One listed object
+#[derive(Debug, Clone)]
+pub(crate) struct ObjectInfo {
+    pub key: String,
+    pub size: u64,
+    pub storage_class: String,
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+/// Objects and bytes in one group
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct UsageGroup {
+    pub name: String,
+    pub objects: usize,
+    pub bytes: u64,
+    /// Bytes not modified within the stale window
+    pub stale_bytes: u64,
+}
+
+/// Suggested lifecycle rule
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LifecycleRecommendation {
+    pub prefix: String,
+    /// "transition" or "expire"
+    pub action: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_class: Option<String>,
+    pub after_days: u64,
+    pub affected_bytes: u64,
+    pub monthly_savings_usd: f64,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct S3Analysis {
+    pub path: String,
+    pub objects: usize,
+    pub total_bytes: u64,
+    pub monthly_cost_usd: f64,
+    pub stale_days: u64,
+    pub stale_objects: usize,
+    pub stale_bytes: u64,
+    pub oldest: Option<DateTime<Utc>>,
+    pub newest: Option<DateTime<Utc>>,
+    pub by_prefix: Vec<UsageGroup>,
+    pub by_extension: Vec<UsageGroup>,
+    pub by_storage_class: Vec<UsageGroup>,
+    pub recommendations: Vec<LifecycleRecommendation>,
+}
+
+/// Sub-prefix of `key` below `base`, `depth` directories deep
+fn group_prefix(key: &str, base: &str, depth: usize) -> String {
+    let relative = key.strip_prefix(base).unwrap_or(key);
+    let dirs: Vec<&str> = relative.split('/').collect();
+    // The last component is the object name
+    let dirs = &dirs[..dirs.len().saturating_sub(1)];
+    if dirs.is_empty() || depth == 0 {
+        return "(root)".to_string();
+    }
+    let take = depth.min(dirs.len());
+    format!("{}{}/", base, dirs[..take].join("/"))
+}
+
+fn extension(key: &str) -> String {
+    Path::new(key)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_else(|| "(none)".to_string())
+}
+
+fn add(groups: &mut HashMap<String, UsageGroup>, name: String, size: u64, stale: bool) {
+    let group = groups.entry(name.clone()).or_insert_with(|| UsageGroup {
+        name,
+        ..Default::default()
+    });
+    group.objects += 1;
+    group.bytes += size;
+    if stale {
+        group.stale_bytes += size;
+    }
+}
+
+/// Largest groups first, keeping at most `top`
+fn sorted(groups: HashMap<String, UsageGroup>, top: usize) -> Vec<UsageGroup> {
+    let mut groups: Vec<UsageGroup> = groups.into_values().collect();
+    groups.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+    groups.truncate(top);
+    groups
+}
+
+/// Summarize listed objects
+pub(crate) fn analyze(
+    path: &str,
+    base: &str,
+    objects: &[ObjectInfo],
+    depth: usize,
+    stale_days: u64,
+    top: usize,
+    now: DateTime<Utc>,
+) -> S3Analysis {
+    let cutoff = now - chrono::Duration::days(stale_days as i64);
+    let mut by_prefix = HashMap::new();
+    let mut by_extension = HashMap::new();
+    let mut by_class = HashMap::new();
+    // Per prefix: stale STANDARD bytes that would be worth moving to IA, and
+    // whether the prefix holds checkpoints
+    let mut transferable: HashMap<String, u64> = HashMap::new();
+    let mut checkpoint_bytes: HashMap<String, u64> = HashMap::new();
+
+    let mut analysis = S3Analysis {
+        path: path.to_string(),
+        objects: objects.len(),
+        total_bytes: 0,
+        monthly_cost_usd: 0.0,
+        stale_days,
+        stale_objects: 0,
+        stale_bytes: 0,
+        oldest: None,
+        newest: None,
+        by_prefix: Vec::new(),
+        by_extension: Vec::new(),
+        by_storage_class: Vec::new(),
+        recommendations: Vec::new(),
+    };
+
+    for object in objects {
+        let stale = object.last_modified.map(|t| t < cutoff).unwrap_or(false);
+        let prefix = group_prefix(&object.key, base, depth);
+        let ext = extension(&object.key);
+
+        analysis.total_bytes += object.size;
+        analysis.monthly_cost_usd +=
+            object.size as f64 / GB * storage_price_per_gb(&object.storage_class);
+        if stale {
+            analysis.stale_objects += 1;
+            analysis.stale_bytes += object.size;
+            if object.storage_class == "STANDARD" && object.size >= IA_MIN_BILLABLE_BYTES {
+                *transferable.entry(prefix.clone()).or_default() += object.size;
+            }
+        }
+        if let Some(modified) = object.last_modified {
+            analysis.oldest = Some(analysis.oldest.map_or(modified, |o| o.min(modified)));
+            analysis.newest = Some(analysis.newest.map_or(modified, |n| n.max(modified)));
+        }
+        if CHECKPOINT_EXTENSIONS.contains(&ext.as_str()) {
+            *checkpoint_bytes.entry(prefix.clone()).or_default() += object.size;
+        }
+
+        add(&mut by_prefix, prefix, object.size, stale);
+        add(&mut by_extension, ext, object.size, stale);
+        add(
+            &mut by_class,
+            object.storage_class.clone(),
+            object.size,
+            stale,
+        );
+    }
+
+    analysis.recommendations = recommend(
+        base,
+        &by_prefix,
+        &transferable,
+        &checkpoint_bytes,
+        stale_days,
+    );
+    analysis.by_prefix = sorted(by_prefix, top);
+    analysis.by_extension = sorted(by_extension, top);
+    analysis.by_storage_class = sorted(by_class, usize::MAX);
+    analysis
+}
+
+fn recommend(
+    base: &str,
+    by_prefix: &HashMap<String, UsageGroup>,
+    transferable: &HashMap<String, u64>,
+    checkpoint_bytes: &HashMap<String, u64>,
+    stale_days: u64,
+) -> Vec<LifecycleRecommendation> {
+    let mut recommendations = Vec::new();
+    for (prefix, group) in by_prefix {
+        // Objects directly under the analyzed path are scoped to the path itself
+        let scope = if prefix == "(root)" {
+            base
+        } else {
+            prefix.as_str()
+        };
+        let movable = transferable.get(prefix).copied().unwrap_or(0);
+        if movable >= MIN_RECOMMENDATION_BYTES {
+            recommendations.push(LifecycleRecommendation {
+                prefix: scope.to_string(),
+                action: "transition".to_string(),
+                storage_class: Some("STANDARD_IA".to_string()),
+                after_days: stale_days,
+                affected_bytes: movable,
+                monthly_savings_usd: movable as f64 / GB
+                    * (storage_price_per_gb("STANDARD") - storage_price_per_gb("STANDARD_IA")),
+                reason: format!(
+                    "{} in STANDARD unchanged for {}+ days",
+                    format_size(movable),
+                    stale_days
+                ),
+            });
+        }
+
+        // Checkpoint prefixes grow without bound; old checkpoints are rarely
+        // read once a run has finished
+        let checkpoints = checkpoint_bytes.get(prefix).copied().unwrap_or(0);
+        let is_checkpoint_prefix = checkpoints * 2 >= group.bytes
+            || prefix.contains("checkpoint")
+            || prefix.contains("ckpt");
+        if is_checkpoint_prefix && group.stale_bytes >= MIN_RECOMMENDATION_BYTES {
+            let expire_after = (stale_days * 6).max(180);
+            recommendations.push(LifecycleRecommendation {
+                prefix: scope.to_string(),
+                action: "expire".to_string(),
+                storage_class: None,
+                after_days: expire_after,
+                affected_bytes: group.stale_bytes,
+                monthly_savings_usd: group.stale_bytes as f64 / GB
+                    * storage_price_per_gb("STANDARD_IA"),
+                reason: format!(
+                    "checkpoint prefix with {} unchanged for {}+ days; keep the ones you need elsewhere",
+                    format_size(group.stale_bytes),
+                    stale_days
+                ),
+            });
+        }
+    }
+    recommendations.sort_by(|a, b| {
+        b.monthly_savings_usd
+            .partial_cmp(&a.monthly_savings_usd)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.prefix.cmp(&b.prefix))
+    });
+    recommendations
+}
+
+async fn list_all(client: &S3Client, bucket: &str, prefix: &str) -> Result<Vec<ObjectInfo>> {
+    let mut objects = Vec::new();
+    let mut token: Option<String> = None;
+    loop {
+        let mut request = client.list_objects_v2().bucket(bucket);
+        if !prefix.is_empty() {
+            request = request.prefix(prefix);
+        }
+        if let Some(t) = &token {
+            request = request.continuation_token(t);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| TrainctlError::S3(format!("Failed to list objects: {}", e)))?;
+        for obj in response.contents() {
+            objects.push(ObjectInfo {
+                key: obj.key().unwrap_or("").to_string(),
+                size: obj.size().unwrap_or(0).max(0) as u64,
+                storage_class: obj
+                    .storage_class()
+                    .map(|c| c.as_str().to_string())
+                    .unwrap_or_else(|| "STANDARD".to_string()),
+                last_modified: obj
+                    .last_modified()
+                    .and_then(|t| DateTime::from_timestamp(t.secs(), 0)),
+            });
+        }
+        match response.next_continuation_token() {
+            Some(next) => token = Some(next.to_string()),
+            None => break,
+        }
+    }
+    Ok(objects)
+}
+
+fn print_groups(title: &str, groups: &[UsageGroup], total: u64) {
+    if groups.is_empty() {
+        return;
+    }
+    println!();
+    println!("{}:", title);
+    for group in groups {
+        let share = if total > 0 {
+            group.bytes as f64 * 100.0 / total as f64
+        } else {
+            0.0
+        };
+        println!(
+            "  {:<40} {:>8} objects {:>12} {:>5.1}%  stale {}",
+            group.name,
+            group.objects,
+            format_size(group.bytes),
+            share,
+            format_size(group.stale_bytes)
+        );
+    }
+}
+
+/// Analyze usage under an S3 path
+pub(crate) async fn analyze_s3(
+    path: String,
+    stale_days: u64,
+    depth: usize,
+    top: usize,
+    aws_config: &aws_config::SdkConfig,
+    output_format: &str,
+) -> Result<()> {
+    let client = S3Client::new(aws_config);
+    let (bucket, prefix) = parse_s3_path(&path)?;
+    if output_format != "json" {
+        println!("Listing {}...", path);
+    }
+    let objects = list_all(&client, &bucket, &prefix).await?;
+    let analysis = analyze(&path, &prefix, &objects, depth, stale_days, top, Utc::now());
+
+    if output_format == "json" {
+        println!("{}", serde_json::to_string_pretty(&analysis)?);
+        return Ok(());
+    }
+
+    println!("{}", "=".repeat(70));
+    println!("S3 Analysis: {}", path);
+    println!("{}", "=".repeat(70));
+    println!("Objects: {}", analysis.objects);
+    println!("Total size: {}", format_size(analysis.total_bytes));
+    println!(
+        "Estimated storage cost: ${:.2}/month",
+        analysis.monthly_cost_usd
+    );
+    if let (Some(oldest), Some(newest)) = (analysis.oldest, analysis.newest) {
+        println!(
+            "Last modified: {} .. {}",
+            oldest.format("%Y-%m-%d"),
+            newest.format("%Y-%m-%d")
+        );
+    }
+    println!(
+        "Stale (not modified in {} days): {} objects, {}",
+        stale_days,
+        analysis.stale_objects,
+        format_size(analysis.stale_bytes)
+    );
+
+    print_groups("By prefix", &analysis.by_prefix, analysis.total_bytes);
+    print_groups("By extension", &analysis.by_extension, analysis.total_bytes);
+    print_groups(
+        "By storage class",
+        &analysis.by_storage_class,
+        analysis.total_bytes,
+    );
+
+    println!();
+    if analysis.recommendations.is_empty() {
+        println!("No lifecycle changes recommended");
+    } else {
+        println!("Recommended lifecycle rules:");
+        for rec in &analysis.recommendations {
+            let prefix = if rec.prefix.is_empty() {
+                "(whole bucket)"
+            } else {
+                rec.prefix.as_str()
+            };
+            let action = match &rec.storage_class {
+                Some(class) => format!("transition to {} after {} days", class, rec.after_days),
+                None => format!("expire after {} days", rec.after_days),
+            };
+            println!(
+                "  {}: {} (saves ~${:.2}/month)",
+                prefix, action, rec.monthly_savings_usd
+            );
+            println!("      {}", rec.reason);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+
+    fn object(key: &str, size: u64, class: &str, age_days: i64, now: DateTime<Utc>) -> ObjectInfo {
+        ObjectInfo {
+            key: key.to_string(),
+            size,
+            storage_class: class.to_string(),
+            last_modified: Some(now - chrono::Duration::days(age_days)),
+        }
+    }
+
+    #[test]
+    fn test_group_prefix() {
+        assert_eq!(group_prefix("runs/a/ckpt.pt", "runs/", 1), "runs/a/");
+        assert_eq!(group_prefix("runs/a/b/ckpt.pt", "runs/", 2), "runs/a/b/");
+        assert_eq!(group_prefix("runs/a/b/ckpt.pt", "runs/", 5), "runs/a/b/");
+        assert_eq!(group_prefix("runs/top.log", "runs/", 1), "(root)");
+        assert_eq!(group_prefix("data/x.bin", "", 1), "data/");
+    }
+
+    #[test]
+    fn test_analyze_groups_and_stale() {
+        let now = Utc::now();
+        let objects = vec![
+            object("checkpoints/e1.pt", 3 * GIB, "STANDARD", 90, now),
+            object("checkpoints/e2.pt", 2 * GIB, "STANDARD", 5, now),
+            object("logs/train.log", 1024, "STANDARD", 90, now),
+            object("data/shard.tar", GIB, "GLACIER", 400, now),
+        ];
+        let analysis = analyze("s3://b/", "", &objects, 1, 30, 10, now);
+
+        assert_eq!(analysis.objects, 4);
+        assert_eq!(analysis.total_bytes, 6 * GIB + 1024);
+        assert_eq!(analysis.stale_objects, 3);
+        assert_eq!(analysis.stale_bytes, 4 * GIB + 1024);
+        assert_eq!(analysis.by_prefix[0].name, "checkpoints/");
+        assert_eq!(analysis.by_prefix[0].stale_bytes, 3 * GIB);
+        assert_eq!(analysis.by_extension[0].name, "pt");
+        assert_eq!(analysis.by_storage_class[0].name, "STANDARD");
+        assert_eq!(analysis.by_storage_class[1].name, "GLACIER");
+    }
+
+    #[test]
+    fn test_recommendations() {
+        let now = Utc::now();
+        let objects = vec![
+            object("checkpoints/e1.pt", 3 * GIB, "STANDARD", 90, now),
+            // Already archived; nothing to transition
+            object("archive/old.tar", 5 * GIB, "DEEP_ARCHIVE", 400, now),
+            // Fresh data is left alone
+            object("data/shard.tar", 5 * GIB, "STANDARD", 1, now),
+        ];
+        let analysis = analyze("s3://b/", "", &objects, 1, 30, 10, now);
+        let recs = &analysis.recommendations;
+
+        assert!(recs
+            .iter()
+            .all(|r| r.prefix == "checkpoints/" || r.prefix == "archive/"));
+        let transition = recs
+            .iter()
+            .find(|r| r.action == "transition")
+            .expect("transition rule");
+        assert_eq!(transition.prefix, "checkpoints/");
+        assert_eq!(transition.affected_bytes, 3 * GIB);
+        assert!((transition.monthly_savings_usd - 3.0 * 0.0105).abs() < 1e-9);
+        assert!(recs
+            .iter()
+            .any(|r| r.action == "expire" && r.prefix == "checkpoints/" && r.after_days == 180));
+        assert!(!recs
+            .iter()
+            .any(|r| r.prefix == "archive/" && r.action == "transition"));
+    }
+
+    #[test]
+    fn test_small_objects_not_transitioned() {
+        let now = Utc::now();
+        let objects: Vec<_> = (0..20_000)
+            .map(|i| object(&format!("logs/{}.json", i), 64 * 1024, "STANDARD", 90, now))
+            .collect();
+        let analysis = analyze("s3://b/", "", &objects, 1, 30, 10, now);
+        assert!(analysis
+            .recommendations
+            .iter()
+            .all(|r| r.action != "transition"));
+    }
+}
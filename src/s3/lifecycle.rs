@@ -0,0 +1,530 @@
+//! Lifecycle rules for runctl prefixes
+//!
+//! `runctl s3 lifecycle` manages bucket lifecycle rules whose IDs start with
+//! `runctl-`, one per prefix. S3 stores a single lifecycle configuration per
+//! bucket, so applying a rule rewrites the whole list; rules created outside
+//! runctl are carried over unchanged.
+
+use super::parse_s3_path;
+use crate::error::{Result, TrainctlError};
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::types::{
+    BucketLifecycleConfiguration, ExpirationStatus, LifecycleExpiration, LifecycleRule,
+    LifecycleRuleFilter, Transition, TransitionStorageClass,
+};
+use aws_sdk_s3::Client as S3Client;
+use clap::Subcommand;
+use serde::Serialize;
+
+const RULE_ID_PREFIX: &str = "runctl-";
+
+/// S3 rejects transitions to STANDARD_IA earlier than this
+const MIN_IA_DAYS: i32 = 30;
+
+#[derive(Subcommand, Clone)]
+pub enum LifecycleCommands {
+    /// Show the lifecycle rules of a bucket
+    ///
+    /// Examples:
+    ///   runctl s3 lifecycle show s3://bucket
+    Show {
+        /// Bucket (s3://bucket or bucket name)
+        #[arg(value_name = "BUCKET")]
+        bucket: String,
+    },
+    /// Create or update the runctl rule for a prefix
+    ///
+    /// Prints the change against the current configuration and asks before
+    /// applying. Use --diff to only print it.
+    ///
+    /// Examples:
+    ///   runctl s3 lifecycle apply s3://bucket --prefix checkpoints/ --transition-ia 30d --expire 180d
+    ///   runctl s3 lifecycle apply s3://bucket --prefix logs/ --expire 90d --diff
+    Apply {
+        /// Bucket (s3://bucket or bucket name)
+        #[arg(value_name = "BUCKET")]
+        bucket: String,
+        /// Key prefix the rule applies to
+        #[arg(long, value_name = "PREFIX")]
+        prefix: String,
+        /// Move objects to STANDARD_IA after this long (e.g. 30d, 8w)
+        #[arg(long, value_name = "DAYS", value_parser = crate::units::parse_days)]
+        transition_ia: Option<i32>,
+        /// Move objects to GLACIER after this long
+        #[arg(long, value_name = "DAYS", value_parser = crate::units::parse_days)]
+        transition_glacier: Option<i32>,
+        /// Delete objects after this long
+        #[arg(long, value_name = "DAYS", value_parser = crate::units::parse_days)]
+        expire: Option<i32>,
+        /// Show the change without applying it
+        #[arg(long)]
+        diff: bool,
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Remove the runctl rule for a prefix
+    ///
+    /// Examples:
+    ///   runctl s3 lifecycle remove s3://bucket --prefix checkpoints/
+    Remove {
+        /// Bucket (s3://bucket or bucket name)
+        #[arg(value_name = "BUCKET")]
+        bucket: String,
+        /// Key prefix of the rule to remove
+        #[arg(long, value_name = "PREFIX")]
+        prefix: String,
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+/// A lifecycle rule in the terms runctl manages
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleSpec {
+    pub id: String,
+    pub prefix: String,
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transition_ia_days: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transition_glacier_days: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expire_days: Option<i32>,
+    /// Transitions to other storage classes, e.g. "DEEP_ARCHIVE after 365d"
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub other_transitions: Vec<String>,
+}
+
+impl RuleSpec {
+    fn describe(&self) -> String {
+        let mut actions = Vec::new();
+        if let Some(days) = self.transition_ia_days {
+            actions.push(format!("STANDARD_IA after {}d", days));
+        }
+        if let Some(days) = self.transition_glacier_days {
+            actions.push(format!("GLACIER after {}d", days));
+        }
+        actions.extend(self.other_transitions.iter().cloned());
+        if let Some(days) = self.expire_days {
+            actions.push(format!("expire after {}d", days));
+        }
+        if actions.is_empty() {
+            actions.push("no actions".to_string());
+        }
+        format!(
+            "{}{}",
+            actions.join(", "),
+            if self.enabled { "" } else { " (disabled)" }
+        )
+    }
+}
+
+/// What applying a rule changes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum RuleChange {
+    Add,
+    Update { before: RuleSpec },
+    Unchanged,
+}
+
+/// Rule ID for a prefix
+pub fn rule_id(prefix: &str) -> String {
+    let trimmed = prefix.trim_matches('/');
+    if trimmed.is_empty() {
+        format!("{}bucket", RULE_ID_PREFIX)
+    } else {
+        format!("{}{}", RULE_ID_PREFIX, trimmed)
+    }
+}
+
+/// Build the desired rule and check it against S3's constraints
+pub fn desired_rule(
+    prefix: &str,
+    transition_ia_days: Option<i32>,
+    transition_glacier_days: Option<i32>,
+    expire_days: Option<i32>,
+) -> Result<RuleSpec> {
+    let invalid = |reason: String| TrainctlError::Validation {
+        field: "lifecycle".to_string(),
+        reason,
+    };
+    if transition_ia_days.is_none() && transition_glacier_days.is_none() && expire_days.is_none() {
+        return Err(invalid(
+            "Specify at least one of --transition-ia, --transition-glacier or --expire".to_string(),
+        ));
+    }
+    if let Some(ia) = transition_ia_days {
+        if ia < MIN_IA_DAYS {
+            return Err(invalid(format!(
+                "S3 requires at least {} days before a STANDARD_IA transition",
+                MIN_IA_DAYS
+            )));
+        }
+    }
+    if let (Some(ia), Some(glacier)) = (transition_ia_days, transition_glacier_days) {
+        if glacier <= ia {
+            return Err(invalid(
+                "--transition-glacier must come after --transition-ia".to_string(),
+            ));
+        }
+    }
+    let last_transition = transition_ia_days.max(transition_glacier_days);
+    if let (Some(transition), Some(expire)) = (last_transition, expire_days) {
+        if expire <= transition {
+            return Err(invalid(
+                "--expire must come after the last transition".to_string(),
+            ));
+        }
+    }
+    Ok(RuleSpec {
+        id: rule_id(prefix),
+        prefix: prefix.to_string(),
+        enabled: true,
+        transition_ia_days,
+        transition_glacier_days,
+        expire_days,
+        other_transitions: Vec::new(),
+    })
+}
+
+/// Compare the desired rule with the current rules
+pub fn plan(current: &[RuleSpec], desired: &RuleSpec) -> RuleChange {
+    match current.iter().find(|r| r.id == desired.id) {
+        None => RuleChange::Add,
+        Some(existing) if existing == desired => RuleChange::Unchanged,
+        Some(existing) => RuleChange::Update {
+            before: existing.clone(),
+        },
+    }
+}
+
+fn from_sdk(rule: &LifecycleRule) -> RuleSpec {
+    #[allow(deprecated)]
+    let prefix = rule
+        .filter()
+        .and_then(|f| f.prefix())
+        .or_else(|| rule.prefix())
+        .unwrap_or_default()
+        .to_string();
+    let mut spec = RuleSpec {
+        id: rule.id().unwrap_or_default().to_string(),
+        prefix,
+        enabled: rule.status() == &ExpirationStatus::Enabled,
+        transition_ia_days: None,
+        transition_glacier_days: None,
+        expire_days: rule.expiration().and_then(|e| e.days()),
+        other_transitions: Vec::new(),
+    };
+    for transition in rule.transitions() {
+        let days = transition.days();
+        match transition.storage_class() {
+            Some(TransitionStorageClass::StandardIa) => spec.transition_ia_days = days,
+            Some(TransitionStorageClass::Glacier) => spec.transition_glacier_days = days,
+            Some(class) => spec.other_transitions.push(format!(
+                "{} after {}d",
+                class.as_str(),
+                days.map(|d| d.to_string())
+                    .unwrap_or_else(|| "?".to_string())
+            )),
+            None => {}
+        }
+    }
+    spec
+}
+
+fn to_sdk(spec: &RuleSpec) -> Result<LifecycleRule> {
+    let mut transitions = Vec::new();
+    if let Some(days) = spec.transition_ia_days {
+        transitions.push(
+            Transition::builder()
+                .days(days)
+                .storage_class(TransitionStorageClass::StandardIa)
+                .build(),
+        );
+    }
+    if let Some(days) = spec.transition_glacier_days {
+        transitions.push(
+            Transition::builder()
+                .days(days)
+                .storage_class(TransitionStorageClass::Glacier)
+                .build(),
+        );
+    }
+    let mut builder = LifecycleRule::builder()
+        .id(&spec.id)
+        .filter(LifecycleRuleFilter::builder().prefix(&spec.prefix).build())
+        .status(ExpirationStatus::Enabled)
+        .set_transitions(if transitions.is_empty() {
+            None
+        } else {
+            Some(transitions)
+        });
+    if let Some(days) = spec.expire_days {
+        builder = builder.expiration(LifecycleExpiration::builder().days(days).build());
+    }
+    builder
+        .build()
+        .map_err(|e| TrainctlError::S3(format!("Invalid lifecycle rule: {}", e)))
+}
+
+/// Bucket name from `s3://bucket[/...]` or a bare name
+fn bucket_name(bucket: &str) -> Result<String> {
+    if bucket.starts_with("s3://") {
+        crate::validation::validate_s3_path(bucket)?;
+        Ok(parse_s3_path(bucket)?.0)
+    } else {
+        crate::validation::validate_s3_path(&format!("s3://{}", bucket))?;
+        Ok(bucket.to_string())
+    }
+}
+
+async fn load_rules(client: &S3Client, bucket: &str) -> Result<Vec<LifecycleRule>> {
+    match client
+        .get_bucket_lifecycle_configuration()
+        .bucket(bucket)
+        .send()
+        .await
+    {
+        Ok(output) => Ok(output.rules().to_vec()),
+        Err(e) if e.code() == Some("NoSuchLifecycleConfiguration") => Ok(Vec::new()),
+        Err(e) => Err(TrainctlError::S3(format!(
+            "Failed to read lifecycle configuration of {}: {}",
+            bucket,
+            e.message()
+                .map(str::to_string)
+                .unwrap_or_else(|| e.to_string())
+        ))),
+    }
+}
+
+async fn save_rules(client: &S3Client, bucket: &str, rules: Vec<LifecycleRule>) -> Result<()> {
+    if rules.is_empty() {
+        client
+            .delete_bucket_lifecycle()
+            .bucket(bucket)
+            .send()
+            .await
+            .map_err(|e| {
+                TrainctlError::S3(format!("Failed to delete lifecycle configuration: {}", e))
+            })?;
+        return Ok(());
+    }
+    let configuration = BucketLifecycleConfiguration::builder()
+        .set_rules(Some(rules))
+        .build()
+        .map_err(|e| TrainctlError::S3(format!("Invalid lifecycle configuration: {}", e)))?;
+    client
+        .put_bucket_lifecycle_configuration()
+        .bucket(bucket)
+        .lifecycle_configuration(configuration)
+        .send()
+        .await
+        .map_err(|e| {
+            TrainctlError::S3(format!("Failed to update lifecycle configuration: {}", e))
+        })?;
+    Ok(())
+}
+
+fn confirm(question: &str, yes: bool) -> Result<bool> {
+    Ok(yes || crate::prompt::confirm(question)?)
+}
+
+#[derive(Serialize)]
+struct LifecycleResult<'a> {
+    bucket: &'a str,
+    #[serde(flatten)]
+    change: &'a RuleChange,
+    rule: &'a RuleSpec,
+    applied: bool,
+}
+
+pub(crate) async fn handle_command(
+    cmd: LifecycleCommands,
+    aws_config: &aws_config::SdkConfig,
+    output_format: &str,
+) -> Result<()> {
+    let client = S3Client::new(aws_config);
+    let text = output_format != "json";
+
+    match cmd {
+        LifecycleCommands::Show { bucket } => {
+            let bucket = bucket_name(&bucket)?;
+            let rules: Vec<RuleSpec> = load_rules(&client, &bucket)
+                .await?
+                .iter()
+                .map(from_sdk)
+                .collect();
+            if !text {
+                println!("{}", serde_json::to_string_pretty(&rules)?);
+            } else if rules.is_empty() {
+                println!("No lifecycle rules on {}", bucket);
+            } else {
+                println!("Lifecycle rules on {}:", bucket);
+                for rule in &rules {
+                    let owner = if rule.id.starts_with(RULE_ID_PREFIX) {
+                        ""
+                    } else {
+                        " [not managed by runctl]"
+                    };
+                    let prefix = if rule.prefix.is_empty() {
+                        "(whole bucket)"
+                    } else {
+                        rule.prefix.as_str()
+                    };
+                    println!("  {} {}: {}{}", rule.id, prefix, rule.describe(), owner);
+                }
+            }
+            Ok(())
+        }
+        LifecycleCommands::Apply {
+            bucket,
+            prefix,
+            transition_ia,
+            transition_glacier,
+            expire,
+            diff,
+            yes,
+        } => {
+            let bucket = bucket_name(&bucket)?;
+            let desired = desired_rule(&prefix, transition_ia, transition_glacier, expire)?;
+            let mut rules = load_rules(&client, &bucket).await?;
+            let current: Vec<RuleSpec> = rules.iter().map(from_sdk).collect();
+            let change = plan(&current, &desired);
+
+            if text {
+                match &change {
+                    RuleChange::Add => {
+                        println!("+ {} {}: {}", desired.id, prefix, desired.describe())
+                    }
+                    RuleChange::Update { before } => {
+                        println!("- {} {}: {}", before.id, before.prefix, before.describe());
+                        println!("+ {} {}: {}", desired.id, prefix, desired.describe());
+                    }
+                    RuleChange::Unchanged => {
+                        println!(
+                            "= {} {}: {} (unchanged)",
+                            desired.id,
+                            prefix,
+                            desired.describe()
+                        )
+                    }
+                }
+            }
+
+            let apply = change != RuleChange::Unchanged
+                && !diff
+                && confirm(&format!("Apply lifecycle rule to {}?", bucket), yes)?;
+            if apply {
+                rules.retain(|r| r.id() != Some(desired.id.as_str()));
+                rules.push(to_sdk(&desired)?);
+                save_rules(&client, &bucket, rules).await?;
+            }
+
+            if !text {
+                let result = LifecycleResult {
+                    bucket: &bucket,
+                    change: &change,
+                    rule: &desired,
+                    applied: apply,
+                };
+                println!("{}", serde_json::to_string_pretty(&result)?);
+            } else if apply {
+                println!("Lifecycle rule {} applied to {}", desired.id, bucket);
+            } else if diff {
+                println!("DIFF ONLY: nothing was changed");
+            } else if change != RuleChange::Unchanged {
+                println!("Cancelled");
+            }
+            Ok(())
+        }
+        LifecycleCommands::Remove {
+            bucket,
+            prefix,
+            yes,
+        } => {
+            let bucket = bucket_name(&bucket)?;
+            let id = rule_id(&prefix);
+            let mut rules = load_rules(&client, &bucket).await?;
+            let Some(existing) = rules.iter().find(|r| r.id() == Some(id.as_str())) else {
+                return Err(TrainctlError::ResourceNotFound {
+                    resource_type: "lifecycle rule".to_string(),
+                    resource_id: format!("{} on {}", id, bucket),
+                });
+            };
+            let existing = from_sdk(existing);
+            if text {
+                println!(
+                    "- {} {}: {}",
+                    existing.id,
+                    existing.prefix,
+                    existing.describe()
+                );
+            }
+            if !confirm(&format!("Remove lifecycle rule from {}?", bucket), yes)? {
+                println!("Cancelled");
+                return Ok(());
+            }
+            rules.retain(|r| r.id() != Some(id.as_str()));
+            save_rules(&client, &bucket, rules).await?;
+            if text {
+                println!("Lifecycle rule {} removed from {}", id, bucket);
+            } else {
+                println!(
+                    "{}",
+                    serde_json::json!({ "bucket": bucket, "removed": existing })
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_id() {
+        assert_eq!(rule_id("checkpoints/"), "runctl-checkpoints");
+        assert_eq!(rule_id("runs/exp1/"), "runctl-runs/exp1");
+        assert_eq!(rule_id(""), "runctl-bucket");
+    }
+
+    #[test]
+    fn test_desired_rule_validation() {
+        assert!(desired_rule("c/", None, None, None).is_err());
+        assert!(desired_rule("c/", Some(7), None, None).is_err());
+        assert!(desired_rule("c/", Some(30), Some(30), None).is_err());
+        assert!(desired_rule("c/", Some(30), None, Some(30)).is_err());
+        assert!(desired_rule("c/", None, Some(90), Some(60)).is_err());
+        let rule = desired_rule("c/", Some(30), Some(90), Some(180)).unwrap();
+        assert_eq!(rule.id, "runctl-c");
+        assert_eq!(
+            rule.describe(),
+            "STANDARD_IA after 30d, GLACIER after 90d, expire after 180d"
+        );
+    }
+
+    #[test]
+    fn test_plan() {
+        let desired = desired_rule("c/", Some(30), None, Some(180)).unwrap();
+        assert_eq!(plan(&[], &desired), RuleChange::Add);
+        assert_eq!(plan(&[desired.clone()], &desired), RuleChange::Unchanged);
+
+        let mut before = desired.clone();
+        before.expire_days = Some(90);
+        assert_eq!(
+            plan(&[before.clone()], &desired),
+            RuleChange::Update { before }
+        );
+    }
+
+    #[test]
+    fn test_sdk_round_trip() {
+        let spec = desired_rule("checkpoints/", Some(30), Some(90), Some(365)).unwrap();
+        assert_eq!(from_sdk(&to_sdk(&spec).unwrap()), spec);
+    }
+}
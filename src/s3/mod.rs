@@ -11,6 +11,10 @@
 //! - **Cleanup**: Remove old objects based on age or count
 //! - **Watch**: Monitor S3 paths for changes (like `tail -f` for S3)
 //! - **Review**: Analyze S3 usage and estimate costs
+//! - **Analyze**: Usage breakdown, stale data, and lifecycle recommendations
+//! - **Lifecycle**: Manage lifecycle rules for runctl prefixes
+//! - **Share**: Pre-signed URLs for collaborators without AWS access
+//! - **Verify**: Check a local copy of a dataset against its SHA-256 manifest
 //!
 //! ## Performance
 //!
@@ -33,6 +37,7 @@
 //!         destination: "s3://my-bucket/checkpoints/".to_string(),
 //!         use_s5cmd: false,
 //!         recursive: true,
+//!         encrypt: false,
 //!     },
 //!     &config,
 //!     "text"
@@ -41,14 +46,20 @@
 //! # }
 //! ```
 
+mod analyze;
+mod lifecycle;
+mod share;
+mod verify;
+
+pub use lifecycle::LifecycleCommands;
+
 use crate::config::Config;
 use crate::error::{Result, TrainctlError};
-use aws_config::BehaviorVersion;
 use aws_sdk_s3::Client as S3Client;
 use clap::Subcommand;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
-use tracing::info;
+use tracing::{info, warn};
 use which::which;
 
 #[derive(Subcommand, Clone)]
@@ -62,12 +73,13 @@ pub enum S3Commands {
     ///   runctl s3 upload checkpoints/ s3://bucket/checkpoints/
     ///   runctl s3 upload model.pt s3://bucket/models/model.pt --recursive
     ///   runctl s3 upload data/ s3://bucket/data/ --use-s5cmd
+    ///   runctl s3 upload checkpoints/ s3://bucket/checkpoints/ --encrypt
     Upload {
         /// Local path to upload (file or directory)
         #[arg(value_name = "SOURCE")]
         source: PathBuf,
         /// S3 destination path (s3://bucket/path)
-        #[arg(value_name = "DESTINATION")]
+        #[arg(value_name = "DESTINATION", value_parser = crate::validation::s3_uri_arg)]
         destination: String,
         /// Use s5cmd if available (optional, native Rust is default)
         #[arg(long, default_value_t = false)]
@@ -75,18 +87,22 @@ pub enum S3Commands {
         /// Recursive upload
         #[arg(short, long)]
         recursive: bool,
+        /// Encrypt files client-side before upload (adds a .enc suffix)
+        #[arg(long)]
+        encrypt: bool,
     },
     /// Download files or directories from S3
     ///
     /// Downloads files or directories from S3 to local storage. Uses native Rust
     /// parallel transfers by default. Use --use-s5cmd to use external s5cmd tool.
+    /// Files uploaded with --encrypt are decrypted automatically.
     ///
     /// Examples:
     ///   runctl s3 download s3://bucket/checkpoints/ ./checkpoints/
     ///   runctl s3 download s3://bucket/data/ ./data/ --recursive
     Download {
         /// S3 source path (s3://bucket/path)
-        #[arg(value_name = "SOURCE")]
+        #[arg(value_name = "SOURCE", value_parser = crate::validation::s3_uri_arg)]
         source: String,
         /// Local destination path (file or directory)
         #[arg(value_name = "DESTINATION")]
@@ -97,6 +113,9 @@ pub enum S3Commands {
         /// Recursive download
         #[arg(short, long)]
         recursive: bool,
+        /// Skip the egress cost confirmation
+        #[arg(short, long)]
+        yes: bool,
     },
     /// Sync local directory with S3
     ///
@@ -111,7 +130,7 @@ pub enum S3Commands {
         #[arg(value_name = "LOCAL_PATH")]
         local: PathBuf,
         /// S3 path (s3://bucket/path)
-        #[arg(value_name = "S3_PATH")]
+        #[arg(value_name = "S3_PATH", value_parser = crate::validation::s3_uri_arg)]
         s3_path: String,
         /// Direction: up (local->s3), down (s3->local), or both
         #[arg(long, default_value = "up")]
@@ -130,7 +149,7 @@ pub enum S3Commands {
     ///   runctl s3 list s3://bucket/data/ --recursive --human-readable
     List {
         /// S3 path to list (s3://bucket/path)
-        #[arg(value_name = "S3_PATH")]
+        #[arg(value_name = "S3_PATH", value_parser = crate::validation::s3_uri_arg)]
         path: String,
         /// Recursive listing
         #[arg(short, long)]
@@ -149,7 +168,7 @@ pub enum S3Commands {
     ///   runctl s3 cleanup s3://bucket/checkpoints/ --keep-last-n 5 --dry-run
     Cleanup {
         /// S3 path to checkpoints (s3://bucket/checkpoints/)
-        #[arg(value_name = "S3_PATH")]
+        #[arg(value_name = "S3_PATH", value_parser = crate::validation::s3_uri_arg)]
         path: String,
         /// Keep last N checkpoints
         #[arg(long, default_value = "10")]
@@ -168,10 +187,10 @@ pub enum S3Commands {
     ///   runctl s3 watch s3://bucket/outputs/ --interval 10
     Watch {
         /// S3 path to watch (s3://bucket/path)
-        #[arg(value_name = "S3_PATH")]
+        #[arg(value_name = "S3_PATH", value_parser = crate::validation::s3_uri_arg)]
         path: String,
-        /// Poll interval in seconds
-        #[arg(long, default_value = "30")]
+        /// Poll interval (e.g. 30, 90s, 5m; bare numbers are seconds)
+        #[arg(long, default_value = "30", value_parser = crate::units::parse_secs)]
         interval: u64,
     },
     /// Review/audit S3 training artifacts
@@ -184,16 +203,96 @@ pub enum S3Commands {
     ///   runctl s3 review s3://bucket/training/ --detailed
     Review {
         /// S3 path to review (s3://bucket/path)
-        #[arg(value_name = "S3_PATH")]
+        #[arg(value_name = "S3_PATH", value_parser = crate::validation::s3_uri_arg)]
         path: String,
         /// Show detailed info
         #[arg(short, long)]
         detailed: bool,
     },
+    /// Analyze bucket usage and recommend lifecycle rules
+    ///
+    /// Summarizes object count, total size, size by prefix, extension and
+    /// storage class, and data not modified in --stale-days days (S3 has no
+    /// access times without S3 Inventory, so last-modified is used). Suggests
+    /// lifecycle rules for stale prefixes with estimated monthly savings.
+    ///
+    /// Examples:
+    ///   runctl s3 analyze s3://bucket/
+    ///   runctl s3 analyze s3://bucket/runs/ --depth 2 --stale-days 60
+    Analyze {
+        /// S3 path to analyze (s3://bucket/prefix)
+        #[arg(value_name = "S3_PATH", value_parser = crate::validation::s3_uri_arg)]
+        path: String,
+        /// Objects not modified in this many days count as stale
+        #[arg(long, default_value = "30")]
+        stale_days: u64,
+        /// Directory levels below the path to group by
+        #[arg(long, default_value = "1")]
+        depth: usize,
+        /// Number of prefixes and extensions to show
+        #[arg(long, default_value = "10")]
+        top: usize,
+    },
+    /// Manage bucket lifecycle rules for runctl prefixes
+    ///
+    /// Rules are named runctl-<prefix>; rules created outside runctl are kept.
+    ///
+    /// Examples:
+    ///   runctl s3 lifecycle show s3://bucket
+    ///   runctl s3 lifecycle apply s3://bucket --prefix checkpoints/ --transition-ia 30d --expire 180d --diff
+    Lifecycle {
+        #[command(subcommand)]
+        subcommand: LifecycleCommands,
+    },
+    /// Generate pre-signed URLs for sharing artifacts
+    ///
+    /// Produces time-limited download links for an object, or for every object
+    /// under a prefix (path ending in /). With --put, produces an upload link
+    /// for a single key instead. Shares are recorded in .runctl/audit.jsonl.
+    ///
+    /// Examples:
+    ///   runctl s3 share s3://bucket/checkpoints/final.pt --expires 7d
+    ///   runctl s3 share s3://bucket/results/run-42/ --expires 12h
+    ///   runctl s3 share s3://bucket/incoming/data.tar --put --expires 1d
+    Share {
+        /// S3 object or prefix (s3://bucket/key)
+        #[arg(value_name = "S3_PATH", value_parser = crate::validation::s3_uri_arg)]
+        path: String,
+        /// How long the URLs stay valid (e.g. 30m, 12h, 7d; max 7d)
+        #[arg(
+            long,
+            value_name = "DURATION",
+            default_value = "24h",
+            value_parser = share::expiry_arg
+        )]
+        expires: std::time::Duration,
+        /// Generate an upload (PUT) URL instead of download URLs
+        #[arg(long)]
+        put: bool,
+    },
+    /// Check a local copy of a dataset against S3
+    ///
+    /// Hashes the local files and compares them with the SHA-256 manifest
+    /// stored by `runctl transfer --manifest` (SHA256SUMS under a prefix,
+    /// <key>.sha256 for a single object). Without a manifest, compares
+    /// file sizes with the object listing. Fails when any file is missing or
+    /// differs; local files not in S3 are listed but don't fail the check.
+    ///
+    /// Examples:
+    ///   runctl s3 verify s3://bucket/datasets/imagenet/ ./imagenet
+    ///   runctl s3 verify s3://bucket/models/final.pt ./final.pt
+    Verify {
+        /// S3 object or prefix the copy was made from (s3://bucket/path)
+        #[arg(value_name = "S3_PATH", value_parser = crate::validation::s3_uri_arg)]
+        path: String,
+        /// Local copy (file or directory)
+        #[arg(value_name = "LOCAL_PATH")]
+        local: PathBuf,
+    },
 }
 
-pub async fn handle_command(cmd: S3Commands, _config: &Config, output_format: &str) -> Result<()> {
-    let aws_config = aws_config::load_defaults(BehaviorVersion::latest()).await;
+pub async fn handle_command(cmd: S3Commands, config: &Config, output_format: &str) -> Result<()> {
+    let aws_config = crate::aws_utils::shared_sdk_config().await;
 
     match cmd {
         S3Commands::Upload {
@@ -201,9 +300,28 @@ pub async fn handle_command(cmd: S3Commands, _config: &Config, output_format: &s
             destination,
             use_s5cmd,
             recursive,
+            encrypt,
         } => {
             crate::validation::validate_path_path(&source)?;
             crate::validation::validate_s3_path(&destination)?;
+            if encrypt {
+                let key = crate::encryption::EncryptionKey::load(config)?;
+                let stage = crate::encryption::stage_for_upload(&key, &source)?;
+                let destination = if source.is_file() && !destination.ends_with('/') {
+                    crate::encryption::encrypted_name(&destination)
+                } else {
+                    destination
+                };
+                return upload_to_s3(
+                    stage.path().to_path_buf(),
+                    destination,
+                    use_s5cmd,
+                    recursive,
+                    &aws_config,
+                    output_format,
+                )
+                .await;
+            }
             upload_to_s3(
                 source,
                 destination,
@@ -219,18 +337,49 @@ pub async fn handle_command(cmd: S3Commands, _config: &Config, output_format: &s
             destination,
             use_s5cmd,
             recursive,
+            yes,
         } => {
             crate::validation::validate_s3_path(&source)?;
             crate::validation::validate_path_path(&destination)?;
+
+            let (bucket, key) = parse_s3_path(&source)?;
+            let description = format!("{} -> {}", source, destination.display());
+            let estimate = crate::egress::estimate_download(&aws_config, &bucket, &key, recursive)
+                .await
+                .unwrap_or_else(|e| {
+                    warn!("Could not estimate egress for {}: {}", description, e);
+                    None
+                });
+            if let Some(estimate) = &estimate {
+                if !crate::egress::confirm_egress(
+                    estimate,
+                    &description,
+                    config,
+                    yes,
+                    output_format,
+                )? {
+                    println!("Cancelled");
+                    return Ok(());
+                }
+            }
+
             download_from_s3(
                 source,
-                destination,
+                destination.clone(),
                 use_s5cmd,
                 recursive,
                 &aws_config,
                 output_format,
             )
-            .await
+            .await?;
+            if let Some(estimate) = &estimate {
+                crate::egress::record_egress(estimate, &description);
+            }
+            let decrypted = crate::encryption::decrypt_downloaded(config, &destination)?;
+            if decrypted > 0 && output_format == "text" {
+                println!("Decrypted {} file(s)", decrypted);
+            }
+            Ok(())
         }
         S3Commands::Sync {
             local,
@@ -274,6 +423,27 @@ pub async fn handle_command(cmd: S3Commands, _config: &Config, output_format: &s
             crate::validation::validate_s3_path(&path)?;
             review_s3(path, detailed, &aws_config, output_format).await
         }
+        S3Commands::Analyze {
+            path,
+            stale_days,
+            depth,
+            top,
+        } => {
+            crate::validation::validate_s3_path(&path)?;
+            analyze::analyze_s3(path, stale_days, depth, top, &aws_config, output_format).await
+        }
+        S3Commands::Lifecycle { subcommand } => {
+            lifecycle::handle_command(subcommand, &aws_config, output_format).await
+        }
+        S3Commands::Share { path, expires, put } => {
+            crate::validation::validate_s3_path(&path)?;
+            share::share_s3(path, expires, put, config, &aws_config, output_format).await
+        }
+        S3Commands::Verify { path, local } => {
+            crate::validation::validate_s3_path(&path)?;
+            crate::validation::validate_path_path(&local)?;
+            verify::verify_s3(path, &local, &aws_config, output_format).await
+        }
     }
 }
 
@@ -687,7 +857,10 @@ async fn list_s3(
             return Err(TrainctlError::S3(format!("s5cmd list failed: {}", stderr)));
         }
 
-        print!("{}", String::from_utf8_lossy(&output.stdout));
+        print!(
+            "{}",
+            crate::output_style::sanitize(&String::from_utf8_lossy(&output.stdout))
+        );
         return Ok(());
     }
 
@@ -787,7 +960,7 @@ async fn cleanup_s3(
         .collect();
 
     // Sort by modification time (newest first)
-    checkpoints.sort_by(|a, b| b.1.cmp(&a.1));
+    checkpoints.sort_by_key(|b| std::cmp::Reverse(b.1));
 
     let total = checkpoints.len();
     if total <= keep_last_n {
@@ -1016,7 +1189,7 @@ async fn upload_directory_recursive_parallel(
     prefix: &str,
     source_dir: &Path,
 ) -> Result<()> {
-    use indicatif::{ProgressBar, ProgressStyle};
+    use indicatif::ProgressBar;
     use walkdir::WalkDir;
 
     let source_path = source_dir
@@ -1044,16 +1217,19 @@ async fn upload_directory_recursive_parallel(
     }
 
     info!("Uploading {} files with parallel transfers...", total_files);
+    let total_bytes = files
+        .iter()
+        .filter_map(|e| e.metadata().ok())
+        .map(|m| m.len())
+        .sum();
+    let progress =
+        crate::progress::TransferProgress::new("transfer.upload", total_files as u64, total_bytes);
 
     // Create progress bar
     let pb = ProgressBar::new(total_files as u64);
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template(
-                "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})",
-            )
-            .expect("Progress bar template"),
-    );
+    pb.set_style(crate::output_style::bar(
+        "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})",
+    ));
 
     // Parallel upload with concurrency limit (similar to s5cmd's default)
     const PARALLEL_CONCURRENCY: usize = 10;
@@ -1066,7 +1242,9 @@ async fn upload_directory_recursive_parallel(
         let bucket = bucket.to_string();
         let source_path_clone = source_path.clone();
         let path = entry.path().to_path_buf();
+        let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
         let pb = pb.clone();
+        let progress = progress.clone();
 
         // Calculate relative path and S3 key
         let relative_path = path
@@ -1086,6 +1264,9 @@ async fn upload_directory_recursive_parallel(
         let handle = tokio::spawn(async move {
             let result = upload_file_to_s3(&client, &bucket, &key, &path).await;
             pb.inc(1);
+            if result.is_ok() {
+                progress.file_done(&key, size);
+            }
             result
         });
 
@@ -1146,7 +1327,7 @@ async fn download_directory_recursive_parallel(
     key_prefix: &str,
     destination: &Path,
 ) -> Result<()> {
-    use indicatif::{ProgressBar, ProgressStyle};
+    use indicatif::ProgressBar;
 
     // List all objects with the prefix
     let mut list_objects = client.list_objects_v2().bucket(bucket);
@@ -1175,12 +1356,18 @@ async fn download_directory_recursive_parallel(
 
     // Create progress bar
     let pb = ProgressBar::new(total_files as u64);
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template(
-                "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})",
-            )
-            .expect("Progress bar template"),
+    pb.set_style(crate::output_style::bar(
+        "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})",
+    ));
+
+    let total_bytes = contents
+        .iter()
+        .map(|obj| obj.size().unwrap_or(0).max(0) as u64)
+        .sum();
+    let progress = crate::progress::TransferProgress::new(
+        "transfer.download",
+        total_files as u64,
+        total_bytes,
     );
 
     // Ensure destination directory exists
@@ -1198,9 +1385,10 @@ async fn download_directory_recursive_parallel(
         let bucket = bucket.to_string();
         let destination = destination.to_path_buf();
         let pb = pb.clone();
+        let progress = progress.clone();
 
         let key = obj.key().unwrap_or("").to_string();
-        let _size = obj.size().unwrap_or(0);
+        let size = obj.size().unwrap_or(0).max(0) as u64;
 
         // Skip if key is empty or is a directory marker
         if key.is_empty() || key.ends_with('/') {
@@ -1247,6 +1435,7 @@ async fn download_directory_recursive_parallel(
                     .map_err(|e| TrainctlError::S3(format!("Failed to write file: {}", e)))?;
 
                 pb.inc(1);
+                progress.file_done(&key, size);
                 Ok(())
             });
 
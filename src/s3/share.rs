@@ -0,0 +1,229 @@
+//! Pre-signed URLs for sharing artifacts
+//!
+//! `runctl s3 share` hands collaborators without AWS credentials a time-limited
+//! link: GET to download a checkpoint or every object under a prefix, or PUT
+//! to let them upload to one key. URLs are signed locally with the caller's
+//! credentials (SigV4 caps them at 7 days, and they stop working earlier if
+//! those credentials are temporary). Every share is written to the audit log.
+
+use super::parse_s3_path;
+use crate::audit::{self, AuditEntry};
+use crate::config::Config;
+use crate::error::{Result, TrainctlError};
+use aws_sdk_s3::presigning::PresigningConfig;
+use aws_sdk_s3::Client as S3Client;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::time::Duration;
+use tracing::warn;
+
+/// SigV4 pre-signed URLs are valid for at most 7 days
+const MAX_EXPIRY_SECS: u64 = 7 * 24 * 3600;
+
+/// Upper bound on URLs generated for a prefix
+const MAX_SHARED_OBJECTS: usize = 1000;
+
+/// Parse an expiry like `7d`, `12h`, `30m` or `3600s` (plain numbers are seconds)
+pub fn parse_expiry(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    let expires = crate::units::parse_secs(value)
+        .map(Duration::from_secs)
+        .map_err(|reason| TrainctlError::Validation {
+            field: "expires".to_string(),
+            reason,
+        })?;
+    if expires.as_secs() > MAX_EXPIRY_SECS {
+        return Err(TrainctlError::Validation {
+            field: "expires".to_string(),
+            reason: format!("Pre-signed URLs can be valid for at most 7d: '{}'", value),
+        });
+    }
+    Ok(expires)
+}
+
+/// Clap value parser for `--expires`
+pub(crate) fn expiry_arg(value: &str) -> std::result::Result<Duration, String> {
+    parse_expiry(value).map_err(|e| match e {
+        TrainctlError::Validation { reason, .. } => reason,
+        other => other.to_string(),
+    })
+}
+
+#[derive(Debug, Serialize)]
+struct SharedUrl {
+    key: String,
+    method: &'static str,
+    url: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ShareResult {
+    path: String,
+    expires_at: DateTime<Utc>,
+    urls: Vec<SharedUrl>,
+}
+
+async fn list_keys(client: &S3Client, bucket: &str, prefix: &str) -> Result<Vec<String>> {
+    let mut keys = Vec::new();
+    let mut token: Option<String> = None;
+    loop {
+        let mut request = client.list_objects_v2().bucket(bucket).prefix(prefix);
+        if let Some(t) = &token {
+            request = request.continuation_token(t);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| TrainctlError::S3(format!("Failed to list objects: {}", e)))?;
+        keys.extend(
+            response
+                .contents()
+                .iter()
+                .filter_map(|o| o.key())
+                .filter(|k| !k.ends_with('/'))
+                .map(str::to_string),
+        );
+        if keys.len() > MAX_SHARED_OBJECTS {
+            return Err(TrainctlError::Validation {
+                field: "path".to_string(),
+                reason: format!(
+                    "More than {} objects under s3://{}/{}; share a narrower prefix",
+                    MAX_SHARED_OBJECTS, bucket, prefix
+                ),
+            });
+        }
+        match response.next_continuation_token() {
+            Some(next) => token = Some(next.to_string()),
+            None => break,
+        }
+    }
+    Ok(keys)
+}
+
+/// Generate pre-signed URLs for an object or prefix
+pub(crate) async fn share_s3(
+    path: String,
+    expires_in: Duration,
+    put: bool,
+    config: &Config,
+    aws_config: &aws_config::SdkConfig,
+    output_format: &str,
+) -> Result<()> {
+    let (bucket, key) = parse_s3_path(&path)?;
+    let is_prefix = key.is_empty() || key.ends_with('/');
+    if put && is_prefix {
+        return Err(TrainctlError::Validation {
+            field: "path".to_string(),
+            reason: "--put needs an object key, not a prefix".to_string(),
+        });
+    }
+
+    let client = S3Client::new(aws_config);
+    let presigning = PresigningConfig::expires_in(expires_in)
+        .map_err(|e| TrainctlError::S3(format!("Invalid expiry: {}", e)))?;
+    let expires_at = Utc::now()
+        + chrono::Duration::from_std(expires_in).unwrap_or_else(|_| chrono::Duration::zero());
+
+    let mut urls = Vec::new();
+    if put {
+        let request = client
+            .put_object()
+            .bucket(&bucket)
+            .key(&key)
+            .presigned(presigning)
+            .await
+            .map_err(|e| TrainctlError::S3(format!("Failed to presign upload: {}", e)))?;
+        urls.push(SharedUrl {
+            key: key.clone(),
+            method: "PUT",
+            url: request.uri().to_string(),
+        });
+    } else {
+        let keys = if is_prefix {
+            list_keys(&client, &bucket, &key).await?
+        } else {
+            // Fail now rather than hand out a link that 404s
+            client
+                .head_object()
+                .bucket(&bucket)
+                .key(&key)
+                .send()
+                .await
+                .map_err(|e| TrainctlError::S3(format!("Cannot read {}: {}", path, e)))?;
+            vec![key.clone()]
+        };
+        if keys.is_empty() {
+            return Err(TrainctlError::ResourceNotFound {
+                resource_type: "S3 objects".to_string(),
+                resource_id: path,
+            });
+        }
+        for object_key in keys {
+            let request = client
+                .get_object()
+                .bucket(&bucket)
+                .key(&object_key)
+                .presigned(presigning.clone())
+                .await
+                .map_err(|e| TrainctlError::S3(format!("Failed to presign download: {}", e)))?;
+            urls.push(SharedUrl {
+                key: object_key,
+                method: "GET",
+                url: request.uri().to_string(),
+            });
+        }
+    }
+
+    let entry = AuditEntry::new(config, "s3.share", &path)
+        .detail("method", if put { "PUT" } else { "GET" })
+        .detail("objects", urls.len())
+        .detail("expires_at", expires_at.to_rfc3339());
+    if let Err(e) = audit::record(&entry) {
+        warn!("Failed to write audit log: {}", e);
+    }
+
+    if output_format == "json" {
+        let result = ShareResult {
+            path,
+            expires_at,
+            urls,
+        };
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        for shared in &urls {
+            if urls.len() > 1 {
+                println!("{}:", shared.key);
+            }
+            println!("{}", shared.url);
+        }
+        println!();
+        println!(
+            "{} URL{} ({}) valid until {}",
+            urls.len(),
+            if urls.len() == 1 { "" } else { "s" },
+            if put { "upload" } else { "download" },
+            expires_at.format("%Y-%m-%d %H:%M UTC")
+        );
+        if put {
+            println!("Upload with: curl -X PUT --upload-file <file> '<url>'");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_expiry() {
+        assert_eq!(parse_expiry("7d").unwrap(), Duration::from_secs(604800));
+        assert_eq!(parse_expiry("12h").unwrap(), Duration::from_secs(43200));
+        assert_eq!(parse_expiry("30m").unwrap(), Duration::from_secs(1800));
+        assert_eq!(parse_expiry("3600").unwrap(), Duration::from_secs(3600));
+        assert!(parse_expiry("8d").is_err());
+        assert!(parse_expiry("0h").is_err());
+        assert!(parse_expiry("2w").is_err());
+        assert!(parse_expiry("").is_err());
+    }
+}
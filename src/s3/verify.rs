@@ -0,0 +1,186 @@
+//! Auditing a local copy of an S3 dataset
+//!
+//! `runctl s3 verify` hashes the local copy and compares it with the
+//! checksum manifest stored next to the data by `runctl transfer --manifest`
+//! (see `crate::checksums`). Data uploaded without one is compared with the
+//! object listing by size instead, which finds missing and truncated files
+//! but not corruption that keeps the size.
+
+use super::parse_s3_path;
+use crate::checksums::{self, Verification, MANIFEST_NAME};
+use crate::error::{Result, TrainctlError};
+use aws_sdk_s3::Client as S3Client;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Files listed as extra before the rest are summarized
+const MAX_EXTRA_SHOWN: usize = 20;
+
+#[derive(Debug, Serialize)]
+struct VerifyResult {
+    source: String,
+    local: String,
+    /// "sha256" with a manifest, "size" without one
+    method: &'static str,
+    ok: bool,
+    #[serde(flatten)]
+    verification: Verification,
+}
+
+/// Objects under `key` by path relative to it, with their sizes, and
+/// whether `key` names a single object (listed under its file name)
+async fn list_sizes(
+    client: &S3Client,
+    bucket: &str,
+    key: &str,
+) -> Result<(BTreeMap<String, u64>, bool)> {
+    let mut sizes = BTreeMap::new();
+    if !key.is_empty() && !key.ends_with('/') {
+        if let Ok(head) = client.head_object().bucket(bucket).key(key).send().await {
+            let name = key.rsplit('/').next().unwrap_or(key).to_string();
+            sizes.insert(name, head.content_length().unwrap_or(0).max(0) as u64);
+            return Ok((sizes, true));
+        }
+    }
+
+    let prefix = if key.is_empty() || key.ends_with('/') {
+        key.to_string()
+    } else {
+        format!("{}/", key)
+    };
+    let mut token: Option<String> = None;
+    loop {
+        let mut request = client.list_objects_v2().bucket(bucket).prefix(&prefix);
+        if let Some(t) = &token {
+            request = request.continuation_token(t);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| TrainctlError::S3(format!("Failed to list objects: {}", e)))?;
+        for object in response.contents() {
+            let Some(relative) = object.key().and_then(|k| k.strip_prefix(prefix.as_str())) else {
+                continue;
+            };
+            if relative.is_empty() || relative.ends_with('/') || relative == MANIFEST_NAME {
+                continue;
+            }
+            sizes.insert(
+                relative.to_string(),
+                object.size().unwrap_or(0).max(0) as u64,
+            );
+        }
+        token = response.next_continuation_token().map(String::from);
+        if token.is_none() {
+            break;
+        }
+    }
+    Ok((sizes, false))
+}
+
+/// Compare the local copy with the object listing by size
+fn verify_sizes(
+    sizes: &BTreeMap<String, u64>,
+    single: bool,
+    key: &str,
+    local: &Path,
+) -> Verification {
+    let mut verification = Verification::default();
+    for (name, size) in sizes {
+        let path = if single {
+            checksums::download_target(key, local)
+        } else {
+            local.join(name)
+        };
+        match std::fs::metadata(&path) {
+            Ok(meta) if meta.is_file() && meta.len() == *size => verification.verified += 1,
+            Ok(meta) if meta.is_file() => verification.mismatched.push(name.clone()),
+            _ => verification.missing.push(name.clone()),
+        }
+    }
+    if !single && local.is_dir() {
+        verification.extra = checksums::list_files(local)
+            .into_iter()
+            .map(|(name, _)| name)
+            .filter(|name| !sizes.contains_key(name))
+            .collect();
+    }
+    verification
+}
+
+/// Check a local copy of `path` against its manifest (or the listing)
+pub(crate) async fn verify_s3(
+    path: String,
+    local: &Path,
+    aws_config: &aws_config::SdkConfig,
+    output_format: &str,
+) -> Result<()> {
+    if !local.exists() {
+        return Err(TrainctlError::ResourceNotFound {
+            resource_type: "local copy".to_string(),
+            resource_id: local.display().to_string(),
+        });
+    }
+    let (bucket, key) = parse_s3_path(&path)?;
+    let client = S3Client::new(aws_config);
+
+    let (method, verification) = match checksums::fetch_manifest(&client, &bucket, &key).await? {
+        Some((manifest, single)) => {
+            if output_format == "text" {
+                println!("Hashing {} file(s) in {}", manifest.len(), local.display());
+            }
+            let verification = if single {
+                manifest.verify_file(&checksums::download_target(&key, local))?
+            } else {
+                manifest.verify(local)?
+            };
+            ("sha256", verification)
+        }
+        None => {
+            let (sizes, single) = list_sizes(&client, &bucket, &key).await?;
+            if sizes.is_empty() {
+                return Err(TrainctlError::ResourceNotFound {
+                    resource_type: "S3 object".to_string(),
+                    resource_id: path,
+                });
+            }
+            if output_format == "text" {
+                println!(
+                    "No checksum manifest for {}; comparing sizes only (upload with `runctl transfer --manifest` to store one)",
+                    path
+                );
+            }
+            ("size", verify_sizes(&sizes, single, &key, local))
+        }
+    };
+
+    if output_format == "json" {
+        let result = VerifyResult {
+            source: path.clone(),
+            local: local.display().to_string(),
+            method,
+            ok: verification.is_ok(),
+            verification: verification.clone(),
+        };
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        for name in verification.extra.iter().take(MAX_EXTRA_SHOWN) {
+            println!("   EXTRA    {}", name);
+        }
+        if verification.extra.len() > MAX_EXTRA_SHOWN {
+            println!(
+                "   ... and {} more not in S3",
+                verification.extra.len() - MAX_EXTRA_SHOWN
+            );
+        }
+    }
+    verification.check(&local.display().to_string())?;
+    if output_format == "text" {
+        println!(
+            "{} file(s) match {} ({})",
+            verification.verified, path, method
+        );
+    }
+    Ok(())
+}
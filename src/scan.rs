@@ -0,0 +1,576 @@
+//! Account guardrail scan (`runctl scan`)
+//!
+//! Checks the account for defaults that put training workloads at risk or
+//! quietly cost money:
+//!
+//! - checkpoint buckets (`[aws] s3_bucket`, `[batch] s3_bucket`,
+//!   `[checkpoint] sync_dest` and `--bucket`) that are public, or whose public
+//!   access block doesn't block everything
+//! - security groups that open SSH to 0.0.0.0/0 or ::/0
+//! - unencrypted EBS volumes
+//! - GPU instances without runctl tags, which `resources cleanup`, budgets
+//!   and cost reports never see
+//! - fleets holding idle workers above their minimum size
+//!
+//! A check that can't run (typically an IAM permission missing) is reported
+//! as skipped rather than failing the scan. `--fail-on <severity>` exits
+//! non-zero when a finding at or above that severity remains, and
+//! `--output json` prints the findings for CI policy checks.
+
+use crate::config::Config;
+use crate::error::{Result, TrainctlError};
+use aws_sdk_autoscaling::Client as AsgClient;
+use aws_sdk_ec2::types::{Instance as Ec2Instance, IpPermission};
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::types::PublicAccessBlockConfiguration;
+use aws_sdk_s3::Client as S3Client;
+use comfy_table::Table;
+use console::style;
+use serde::Serialize;
+use std::fmt;
+
+/// How bad a finding is; `--min-severity` and `--fail-on` compare against it
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, clap::ValueEnum, Default,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Low,
+    Medium,
+    High,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Low => write!(f, "low"),
+            Severity::Medium => write!(f, "medium"),
+            Severity::High => write!(f, "high"),
+        }
+    }
+}
+
+/// One risky setting found in the account
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    /// Check that produced it, e.g. `public-bucket`
+    pub check: &'static str,
+    pub severity: Severity,
+    /// Bucket, security group, volume, instance or fleet
+    pub resource: String,
+    pub message: String,
+}
+
+/// A check that couldn't run, and why
+#[derive(Debug, Clone, Serialize)]
+pub struct Skipped {
+    pub check: &'static str,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    /// Buckets to check besides the configured ones
+    pub buckets: Vec<String>,
+    /// Hide findings below this severity
+    pub min_severity: Severity,
+    /// Fail when a finding at or above this severity remains
+    pub fail_on: Option<Severity>,
+}
+
+/// Scan the account and print what was found
+pub async fn run(options: ScanOptions, config: &Config, output_format: &str) -> Result<()> {
+    let sdk_config = crate::aws_utils::shared_sdk_config().await;
+    let ec2 = Ec2Client::new(&sdk_config);
+    let s3 = S3Client::new(&sdk_config);
+    let asg = AsgClient::new(&sdk_config);
+
+    let buckets = scanned_buckets(config, &options.buckets);
+    let (bucket_findings, ssh_findings, volume_findings, tag_findings, fleet_findings) = tokio::join!(
+        check_buckets(&s3, &buckets),
+        check_security_groups(&ec2),
+        check_volumes(&ec2),
+        check_untagged_instances(&ec2),
+        check_fleets(&asg),
+    );
+
+    let mut findings = Vec::new();
+    let mut skipped = Vec::new();
+    for (check, result) in [
+        ("public-bucket", bucket_findings),
+        ("open-ssh", ssh_findings),
+        ("unencrypted-volume", volume_findings),
+        ("untagged-instance", tag_findings),
+        ("idle-fleet", fleet_findings),
+    ] {
+        match result {
+            Ok(found) => findings.extend(found),
+            Err(e) => skipped.push(Skipped {
+                check,
+                error: e.to_string(),
+            }),
+        }
+    }
+    findings.retain(|f| f.severity >= options.min_severity);
+    findings.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then_with(|| a.check.cmp(b.check))
+            .then_with(|| a.resource.cmp(&b.resource))
+    });
+
+    if output_format == "json" {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "findings": findings,
+                "skipped": skipped,
+            }))?
+        );
+    } else {
+        print_findings(&findings, &skipped, buckets.is_empty());
+    }
+
+    if let Some(threshold) = options.fail_on {
+        let failing = failing_count(&findings, threshold);
+        if failing > 0 {
+            return Err(TrainctlError::Validation {
+                field: "scan".to_string(),
+                reason: format!("{} finding(s) at or above {} severity", failing, threshold),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn print_findings(findings: &[Finding], skipped: &[Skipped], no_buckets: bool) {
+    if findings.is_empty() {
+        println!("No findings.");
+    } else {
+        let mut table = Table::new();
+        table.set_header(vec!["Severity", "Check", "Resource", "Finding"]);
+        for finding in findings {
+            let severity = match finding.severity {
+                Severity::High => style(finding.severity).red().bold().to_string(),
+                Severity::Medium => style(finding.severity).yellow().to_string(),
+                Severity::Low => finding.severity.to_string(),
+            };
+            table.add_row(vec![
+                severity,
+                finding.check.to_string(),
+                finding.resource.clone(),
+                finding.message.clone(),
+            ]);
+        }
+        println!("{}", table);
+    }
+    if no_buckets {
+        println!(
+            "Buckets not checked: set [aws] s3_bucket or pass --bucket to include checkpoint buckets."
+        );
+    }
+    for skip in skipped {
+        println!("Skipped {}: {}", skip.check, skip.error);
+    }
+}
+
+/// Findings at or above `threshold`
+fn failing_count(findings: &[Finding], threshold: Severity) -> usize {
+    findings.iter().filter(|f| f.severity >= threshold).count()
+}
+
+/// Configured checkpoint and code buckets plus `extra`, without duplicates
+fn scanned_buckets(config: &Config, extra: &[String]) -> Vec<String> {
+    let configured = [
+        config.aws.as_ref().and_then(|aws| aws.s3_bucket.clone()),
+        config
+            .batch
+            .as_ref()
+            .and_then(|batch| batch.s3_bucket.clone()),
+        config
+            .checkpoint
+            .sync_dest
+            .as_deref()
+            .and_then(|dest| crate::utils::parse_s3_path(dest).ok())
+            .map(|(bucket, _)| bucket),
+    ];
+    let mut buckets: Vec<String> = Vec::new();
+    for bucket in configured
+        .into_iter()
+        .flatten()
+        .chain(extra.iter().map(|b| {
+            b.trim_start_matches("s3://")
+                .split('/')
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        }))
+    {
+        if !bucket.is_empty() && !buckets.contains(&bucket) {
+            buckets.push(bucket);
+        }
+    }
+    buckets
+}
+
+/// Public access block settings that are off or missing
+fn public_access_gaps(block: Option<&PublicAccessBlockConfiguration>) -> Vec<&'static str> {
+    let Some(block) = block else {
+        return vec![
+            "BlockPublicAcls",
+            "IgnorePublicAcls",
+            "BlockPublicPolicy",
+            "RestrictPublicBuckets",
+        ];
+    };
+    [
+        ("BlockPublicAcls", block.block_public_acls()),
+        ("IgnorePublicAcls", block.ignore_public_acls()),
+        ("BlockPublicPolicy", block.block_public_policy()),
+        ("RestrictPublicBuckets", block.restrict_public_buckets()),
+    ]
+    .into_iter()
+    .filter(|(_, enabled)| *enabled != Some(true))
+    .map(|(name, _)| name)
+    .collect()
+}
+
+async fn check_buckets(client: &S3Client, buckets: &[String]) -> Result<Vec<Finding>> {
+    let mut findings = Vec::new();
+    for bucket in buckets {
+        let is_public = match client
+            .get_bucket_policy_status()
+            .bucket(bucket)
+            .send()
+            .await
+        {
+            Ok(output) => output
+                .policy_status()
+                .and_then(|status| status.is_public())
+                .unwrap_or(false),
+            Err(e) if e.code() == Some("NoSuchBucketPolicy") => false,
+            Err(e) => return Err(s3_error(bucket, "policy status", &e)),
+        };
+        if is_public {
+            findings.push(Finding {
+                check: "public-bucket",
+                severity: Severity::High,
+                resource: format!("s3://{}", bucket),
+                message:
+                    "bucket policy makes it public; checkpoints and code are readable by anyone"
+                        .to_string(),
+            });
+            continue;
+        }
+
+        let block = match client.get_public_access_block().bucket(bucket).send().await {
+            Ok(output) => output.public_access_block_configuration().cloned(),
+            Err(e) if e.code() == Some("NoSuchPublicAccessBlockConfiguration") => None,
+            Err(e) => return Err(s3_error(bucket, "public access block", &e)),
+        };
+        let gaps = public_access_gaps(block.as_ref());
+        if !gaps.is_empty() {
+            findings.push(Finding {
+                check: "public-bucket",
+                severity: Severity::Medium,
+                resource: format!("s3://{}", bucket),
+                message: format!(
+                    "public access block doesn't set {}; a policy or ACL change could expose it",
+                    gaps.join(", ")
+                ),
+            });
+        }
+    }
+    Ok(findings)
+}
+
+fn s3_error<E: ProvideErrorMetadata + fmt::Display>(
+    bucket: &str,
+    what: &str,
+    e: &E,
+) -> TrainctlError {
+    TrainctlError::S3(format!(
+        "Failed to read {} of {}: {}",
+        what,
+        bucket,
+        e.message()
+            .map(str::to_string)
+            .unwrap_or_else(|| e.to_string())
+    ))
+}
+
+/// Whether a rule lets SSH in from anywhere
+fn opens_ssh_to_world(permission: &IpPermission) -> bool {
+    let covers_ssh = match permission.ip_protocol() {
+        Some("-1") => true,
+        Some("tcp") | Some("6") => {
+            let from = permission.from_port().unwrap_or(0);
+            let to = permission.to_port().unwrap_or(65535);
+            from <= 22 && 22 <= to
+        }
+        _ => false,
+    };
+    covers_ssh
+        && (permission
+            .ip_ranges()
+            .iter()
+            .any(|r| r.cidr_ip() == Some("0.0.0.0/0"))
+            || permission
+                .ipv6_ranges()
+                .iter()
+                .any(|r| r.cidr_ipv6() == Some("::/0")))
+}
+
+async fn check_security_groups(client: &Ec2Client) -> Result<Vec<Finding>> {
+    let mut findings = Vec::new();
+    let mut next_token: Option<String> = None;
+    loop {
+        let response = client
+            .describe_security_groups()
+            .set_next_token(next_token)
+            .send()
+            .await
+            .map_err(|e| {
+                TrainctlError::Aws(format!("Failed to describe security groups: {}", e))
+            })?;
+        for group in response.security_groups() {
+            if group.ip_permissions().iter().any(opens_ssh_to_world) {
+                findings.push(Finding {
+                    check: "open-ssh",
+                    severity: Severity::High,
+                    resource: group.group_id().unwrap_or("unknown").to_string(),
+                    message: format!(
+                        "{} allows SSH from anywhere; restrict it to your IP or use SSM",
+                        group.group_name().unwrap_or("security group")
+                    ),
+                });
+            }
+        }
+        next_token = response.next_token().map(str::to_string);
+        if next_token.is_none() {
+            return Ok(findings);
+        }
+    }
+}
+
+async fn check_volumes(client: &Ec2Client) -> Result<Vec<Finding>> {
+    let mut findings = Vec::new();
+    let mut next_token: Option<String> = None;
+    loop {
+        let response = client
+            .describe_volumes()
+            .set_next_token(next_token)
+            .send()
+            .await
+            .map_err(|e| TrainctlError::Aws(format!("Failed to describe volumes: {}", e)))?;
+        for volume in response.volumes() {
+            if volume.encrypted() == Some(false) {
+                let attached = volume
+                    .attachments()
+                    .first()
+                    .and_then(|a| a.instance_id())
+                    .map(|id| format!(", attached to {}", id))
+                    .unwrap_or_default();
+                findings.push(Finding {
+                    check: "unencrypted-volume",
+                    severity: Severity::Medium,
+                    resource: volume.volume_id().unwrap_or("unknown").to_string(),
+                    message: format!(
+                        "{} GB volume is not encrypted{}",
+                        volume.size().unwrap_or(0),
+                        attached
+                    ),
+                });
+            }
+        }
+        next_token = response.next_token().map(str::to_string);
+        if next_token.is_none() {
+            return Ok(findings);
+        }
+    }
+}
+
+/// GPU and ML accelerator families (p5, g6e, trn1, inf2, dl1, ...)
+fn is_accelerated_instance_type(instance_type: &str) -> bool {
+    let family: String = instance_type
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect();
+    matches!(family.as_str(), "p" | "g" | "trn" | "inf" | "dl")
+}
+
+fn has_runctl_tag(instance: &Ec2Instance) -> bool {
+    instance
+        .tags()
+        .iter()
+        .any(|t| t.key().is_some_and(|k| k.starts_with("runctl:")))
+}
+
+async fn check_untagged_instances(client: &Ec2Client) -> Result<Vec<Finding>> {
+    let response = crate::resources::describe_instances(client).await?;
+    let mut findings = Vec::new();
+    for instance in response.reservations().iter().flat_map(|r| r.instances()) {
+        let state = instance
+            .state()
+            .and_then(|s| s.name())
+            .map(|s| s.as_str())
+            .unwrap_or("unknown");
+        let instance_type = instance
+            .instance_type()
+            .map(|t| t.as_str())
+            .unwrap_or("unknown");
+        if !matches!(state, "running" | "stopped")
+            || !is_accelerated_instance_type(instance_type)
+            || has_runctl_tag(instance)
+        {
+            continue;
+        }
+        findings.push(Finding {
+            check: "untagged-instance",
+            severity: Severity::Low,
+            resource: instance.instance_id().unwrap_or("unknown").to_string(),
+            message: format!(
+                "{} {} has no runctl tags; cleanup, budgets and cost reports don't see it",
+                state, instance_type
+            ),
+        });
+    }
+    Ok(findings)
+}
+
+async fn check_fleets(client: &AsgClient) -> Result<Vec<Finding>> {
+    let findings = crate::aws::idle_surplus(client)
+        .await?
+        .into_iter()
+        .map(|surplus| {
+            let hourly: f64 = surplus
+                .instance_types
+                .iter()
+                .map(|t| crate::aws_pricing::hourly_price(t, None, None))
+                .sum();
+            Finding {
+                check: "idle-fleet",
+                severity: Severity::Medium,
+                resource: format!("fleet {}", surplus.fleet),
+                message: format!(
+                    "{} idle worker(s) above min size {} (${:.2}/hr); scale down with `runctl aws fleet scale {} <size>`",
+                    surplus.instance_types.len(),
+                    surplus.min,
+                    hourly,
+                    surplus.fleet
+                ),
+            }
+        })
+        .collect();
+    Ok(findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_ec2::types::{IpRange, Ipv6Range};
+
+    fn rule(protocol: &str, from: i32, to: i32, cidr: &str) -> IpPermission {
+        let builder = IpPermission::builder()
+            .ip_protocol(protocol)
+            .from_port(from)
+            .to_port(to);
+        if cidr.contains(':') {
+            builder
+                .ipv6_ranges(Ipv6Range::builder().cidr_ipv6(cidr).build())
+                .build()
+        } else {
+            builder
+                .ip_ranges(IpRange::builder().cidr_ip(cidr).build())
+                .build()
+        }
+    }
+
+    #[test]
+    fn test_opens_ssh_to_world() {
+        assert!(opens_ssh_to_world(&rule("tcp", 22, 22, "0.0.0.0/0")));
+        assert!(opens_ssh_to_world(&rule("tcp", 0, 1024, "0.0.0.0/0")));
+        assert!(opens_ssh_to_world(&rule("tcp", 22, 22, "::/0")));
+        assert!(opens_ssh_to_world(&rule("-1", -1, -1, "0.0.0.0/0")));
+        assert!(!opens_ssh_to_world(&rule("tcp", 22, 22, "203.0.113.7/32")));
+        assert!(!opens_ssh_to_world(&rule("tcp", 443, 443, "0.0.0.0/0")));
+        assert!(!opens_ssh_to_world(&rule("udp", 22, 22, "0.0.0.0/0")));
+    }
+
+    #[test]
+    fn test_public_access_gaps() {
+        assert_eq!(public_access_gaps(None).len(), 4);
+        let full = PublicAccessBlockConfiguration::builder()
+            .block_public_acls(true)
+            .ignore_public_acls(true)
+            .block_public_policy(true)
+            .restrict_public_buckets(true)
+            .build();
+        assert!(public_access_gaps(Some(&full)).is_empty());
+        let partial = PublicAccessBlockConfiguration::builder()
+            .block_public_acls(true)
+            .ignore_public_acls(true)
+            .block_public_policy(false)
+            .build();
+        assert_eq!(
+            public_access_gaps(Some(&partial)),
+            vec!["BlockPublicPolicy", "RestrictPublicBuckets"]
+        );
+    }
+
+    #[test]
+    fn test_accelerated_instance_types() {
+        for t in [
+            "p5.48xlarge",
+            "g6e.xlarge",
+            "g4dn.xlarge",
+            "trn1.32xlarge",
+            "inf2.xlarge",
+            "dl1.24xlarge",
+        ] {
+            assert!(is_accelerated_instance_type(t), "{}", t);
+        }
+        for t in ["t3.micro", "c7gn.large", "m5.xlarge", "gr6.4xlarge"] {
+            assert!(!is_accelerated_instance_type(t), "{}", t);
+        }
+    }
+
+    #[test]
+    fn test_failing_count_and_severity_order() {
+        let finding = |severity| Finding {
+            check: "open-ssh",
+            severity,
+            resource: "sg-1".to_string(),
+            message: String::new(),
+        };
+        let findings = vec![
+            finding(Severity::Low),
+            finding(Severity::Medium),
+            finding(Severity::High),
+        ];
+        assert_eq!(failing_count(&findings, Severity::Low), 3);
+        assert_eq!(failing_count(&findings, Severity::Medium), 2);
+        assert_eq!(failing_count(&findings, Severity::High), 1);
+    }
+
+    #[test]
+    fn test_scanned_buckets() {
+        let mut config = Config::default();
+        config.checkpoint.sync_dest = Some("s3://ckpt-bucket/checkpoints/".to_string());
+        let buckets = scanned_buckets(
+            &config,
+            &["s3://extra/prefix".to_string(), "ckpt-bucket".to_string()],
+        );
+        assert!(buckets.contains(&"ckpt-bucket".to_string()));
+        assert!(buckets.contains(&"extra".to_string()));
+        assert_eq!(
+            buckets
+                .iter()
+                .filter(|b| b.as_str() == "ckpt-bucket")
+                .count(),
+            1
+        );
+    }
+}
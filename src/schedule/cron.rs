@@ -0,0 +1,401 @@
+//! Five-field cron expressions
+//!
+//! `minute hour day-of-month month day-of-week`, as in crontab(5): each field
+//! is `*` or a comma-separated list of values, `a-b` ranges and `/step`s.
+//! Months and weekdays also accept three-letter names (`JAN`, `MON`), and
+//! Sunday is 0 or 7. When both day fields are restricted, a day matches if
+//! either does. `@hourly`, `@daily` (`@midnight`), `@weekly`, `@monthly` and
+//! `@yearly` (`@annually`) stand for their usual expansions. Expressions are
+//! evaluated in UTC.
+
+use crate::error::{Result, TrainctlError};
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
+use std::fmt;
+
+const MONTH_NAMES: &[&str] = &[
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAY_NAMES: &[&str] = &["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// `@` shorthands and the five fields each stands for
+const MACROS: &[(&str, &str)] = &[
+    ("@hourly", "0 * * * *"),
+    ("@daily", "0 0 * * *"),
+    ("@midnight", "0 0 * * *"),
+    ("@weekly", "0 0 * * 0"),
+    ("@monthly", "0 0 1 * *"),
+    ("@yearly", "0 0 1 1 *"),
+    ("@annually", "0 0 1 1 *"),
+];
+
+/// How far `next_after` looks ahead before giving up (covers Feb 29)
+const MAX_LOOKAHEAD_DAYS: i64 = 366 * 5;
+
+/// A parsed cron expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpr {
+    source: String,
+    fields: [String; 5],
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+struct FieldSpec {
+    name: &'static str,
+    min: u32,
+    max: u32,
+    names: &'static [&'static str],
+    /// Value the first name stands for (1 for months, 0 for weekdays)
+    names_start: u32,
+}
+
+const FIELDS: [FieldSpec; 5] = [
+    FieldSpec {
+        name: "minute",
+        min: 0,
+        max: 59,
+        names: &[],
+        names_start: 0,
+    },
+    FieldSpec {
+        name: "hour",
+        min: 0,
+        max: 23,
+        names: &[],
+        names_start: 0,
+    },
+    FieldSpec {
+        name: "day of month",
+        min: 1,
+        max: 31,
+        names: &[],
+        names_start: 0,
+    },
+    FieldSpec {
+        name: "month",
+        min: 1,
+        max: 12,
+        names: MONTH_NAMES,
+        names_start: 1,
+    },
+    FieldSpec {
+        name: "day of week",
+        min: 0,
+        max: 7,
+        names: WEEKDAY_NAMES,
+        names_start: 0,
+    },
+];
+
+fn invalid(expr: &str, reason: impl Into<String>) -> TrainctlError {
+    TrainctlError::Validation {
+        field: "cron".to_string(),
+        reason: format!("'{}': {}", expr, reason.into()),
+    }
+}
+
+impl FieldSpec {
+    fn value(&self, expr: &str, text: &str) -> Result<u32> {
+        let upper = text.to_ascii_uppercase();
+        if let Some(i) = self.names.iter().position(|n| *n == upper) {
+            return Ok(self.names_start + i as u32);
+        }
+        let value: u32 = text
+            .parse()
+            .map_err(|_| invalid(expr, format!("bad {} value '{}'", self.name, text)))?;
+        if value < self.min || value > self.max {
+            return Err(invalid(
+                expr,
+                format!(
+                    "{} {} is outside {}-{}",
+                    self.name, value, self.min, self.max
+                ),
+            ));
+        }
+        Ok(value)
+    }
+
+    /// Bit set of the values a field matches
+    fn parse(&self, expr: &str, field: &str) -> Result<u64> {
+        let mut bits = 0u64;
+        for item in field.split(',') {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => {
+                    let step: u32 = step
+                        .parse()
+                        .ok()
+                        .filter(|s| *s > 0)
+                        .ok_or_else(|| invalid(expr, format!("bad step in '{}'", item)))?;
+                    (range, step)
+                }
+                None => (item, 1),
+            };
+            let (start, end) = if range == "*" {
+                (self.min, self.max)
+            } else if let Some((a, b)) = range.split_once('-') {
+                (self.value(expr, a)?, self.value(expr, b)?)
+            } else {
+                let start = self.value(expr, range)?;
+                // `5/15` runs from 5 to the end of the range
+                let end = if step > 1 { self.max } else { start };
+                (start, end)
+            };
+            if start > end {
+                return Err(invalid(expr, format!("range '{}' is backwards", range)));
+            }
+            for v in (start..=end).step_by(step as usize) {
+                bits |= 1 << v;
+            }
+        }
+        Ok(bits)
+    }
+}
+
+impl CronExpr {
+    /// Parse a five-field expression or an `@` shorthand
+    pub fn parse(expr: &str) -> Result<Self> {
+        let trimmed = expr.trim();
+        let expanded = MACROS
+            .iter()
+            .find(|(name, _)| trimmed.eq_ignore_ascii_case(name))
+            .map(|(_, fields)| *fields)
+            .unwrap_or(trimmed);
+        let parts: Vec<&str> = expanded.split_whitespace().collect();
+        if parts.len() != 5 {
+            return Err(invalid(
+                expr,
+                format!(
+                    "expected 5 fields (minute hour day month weekday), got {}",
+                    parts.len()
+                ),
+            ));
+        }
+        let mut sets = [0u64; 5];
+        for (i, (spec, field)) in FIELDS.iter().zip(&parts).enumerate() {
+            sets[i] = spec.parse(expr, field)?;
+        }
+        // Sunday may be written as 7
+        let weekdays = if sets[4] & (1 << 7) != 0 {
+            (sets[4] | 1) & !(1 << 7)
+        } else {
+            sets[4]
+        };
+        Ok(Self {
+            source: parts.join(" "),
+            fields: std::array::from_fn(|i| parts[i].to_string()),
+            minutes: sets[0],
+            hours: sets[1],
+            days: sets[2],
+            months: sets[3],
+            weekdays,
+            any_day: parts[2] == "*",
+            any_weekday: parts[4] == "*",
+        })
+    }
+
+    /// Whether the expression fires in the minute containing `t`
+    pub fn matches(&self, t: &DateTime<Utc>) -> bool {
+        let has = |set: u64, v: u32| set & (1 << v) != 0;
+        if !has(self.minutes, t.minute()) || !has(self.hours, t.hour()) {
+            return false;
+        }
+        if !has(self.months, t.month()) {
+            return false;
+        }
+        let day = has(self.days, t.day());
+        let weekday = has(self.weekdays, t.weekday().num_days_from_sunday());
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+
+    /// First minute strictly after `t` at which the expression fires
+    pub fn next_after(&self, t: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut candidate = t.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = *t + Duration::days(MAX_LOOKAHEAD_DAYS);
+        while candidate <= limit {
+            if self.hours & (1 << candidate.hour()) == 0 {
+                // Skip to the next hour
+                candidate = candidate.with_minute(0)? + Duration::hours(1);
+                continue;
+            }
+            if self.matches(&candidate) {
+                return Some(candidate);
+            }
+            candidate += Duration::minutes(1);
+        }
+        None
+    }
+
+    /// The same schedule as an EventBridge `cron(...)` expression
+    ///
+    /// EventBridge needs `?` in one of the two day fields and numbers
+    /// weekdays from 1, so weekdays are written out by name. It can't match
+    /// "this day of month or this weekday", so restricting both is an error.
+    pub fn to_eventbridge(&self) -> Result<String> {
+        let [minute, hour, day, month, _] = &self.fields;
+        let (day, weekday) = match (self.any_day, self.any_weekday) {
+            (_, true) => (day.clone(), "?".to_string()),
+            (true, false) => {
+                let names: Vec<&str> = (0..7)
+                    .filter(|d| self.weekdays & (1 << d) != 0)
+                    .map(|d| WEEKDAY_NAMES[d])
+                    .collect();
+                ("?".to_string(), names.join(","))
+            }
+            (false, false) => {
+                return Err(invalid(
+                    &self.source,
+                    "EventBridge can't restrict both day of month and day of week",
+                ))
+            }
+        };
+        Ok(format!(
+            "cron({} {} {} {} {} *)",
+            minute, hour, day, month, weekday
+        ))
+    }
+}
+
+impl fmt::Display for CronExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_match() {
+        let expr = CronExpr::parse("0 22 * * *").unwrap();
+        assert!(expr.matches(&at(2026, 3, 4, 22, 0)));
+        assert!(!expr.matches(&at(2026, 3, 4, 22, 1)));
+        assert!(!expr.matches(&at(2026, 3, 4, 21, 0)));
+    }
+
+    #[test]
+    fn test_weekday_ranges_and_names() {
+        // 2026-03-02 is a Monday
+        let numeric = CronExpr::parse("0 8 * * 1-5").unwrap();
+        let named = CronExpr::parse("0 8 * * mon-FRI").unwrap();
+        for day in 1..=8 {
+            let t = at(2026, 3, day, 8, 0);
+            assert_eq!(numeric.matches(&t), named.matches(&t));
+        }
+        assert!(numeric.matches(&at(2026, 3, 2, 8, 0)));
+        assert!(!numeric.matches(&at(2026, 3, 1, 8, 0)));
+        assert!(!numeric.matches(&at(2026, 3, 7, 8, 0)));
+    }
+
+    #[test]
+    fn test_sunday_as_seven() {
+        let expr = CronExpr::parse("0 0 * * 7").unwrap();
+        assert!(expr.matches(&at(2026, 3, 1, 0, 0)));
+        assert_eq!(expr.weekdays, 1);
+    }
+
+    #[test]
+    fn test_steps_and_lists() {
+        let expr = CronExpr::parse("*/15 9,17 * * *").unwrap();
+        assert!(expr.matches(&at(2026, 3, 4, 9, 45)));
+        assert!(expr.matches(&at(2026, 3, 4, 17, 0)));
+        assert!(!expr.matches(&at(2026, 3, 4, 9, 10)));
+        let offset = CronExpr::parse("5/20 * * * *").unwrap();
+        assert!(offset.matches(&at(2026, 3, 4, 1, 45)));
+        assert!(!offset.matches(&at(2026, 3, 4, 1, 40)));
+    }
+
+    #[test]
+    fn test_day_or_weekday() {
+        // The 1st of the month or any Friday
+        let expr = CronExpr::parse("0 0 1 * 5").unwrap();
+        assert!(expr.matches(&at(2026, 3, 1, 0, 0)));
+        assert!(expr.matches(&at(2026, 3, 6, 0, 0)));
+        assert!(!expr.matches(&at(2026, 3, 7, 0, 0)));
+    }
+
+    #[test]
+    fn test_macros() {
+        let daily = CronExpr::parse("@daily").unwrap();
+        assert_eq!(daily, CronExpr::parse("0 0 * * *").unwrap());
+        assert!(daily.matches(&at(2026, 3, 7, 0, 0)));
+        assert_eq!(CronExpr::parse("@WEEKLY").unwrap().to_string(), "0 0 * * 0");
+        assert!(CronExpr::parse("@sometimes").is_err());
+    }
+
+    #[test]
+    fn test_rejects_bad_expressions() {
+        for bad in [
+            "0 22 * *",
+            "60 * * * *",
+            "0 24 * * *",
+            "0 0 0 * *",
+            "0 0 * 13 *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "x * * * *",
+        ] {
+            assert!(CronExpr::parse(bad).is_err(), "{} should not parse", bad);
+        }
+    }
+
+    #[test]
+    fn test_next_after() {
+        let expr = CronExpr::parse("0 8 * * 1-5").unwrap();
+        // Friday evening -> Monday morning
+        let next = expr.next_after(&at(2026, 3, 6, 20, 30)).unwrap();
+        assert_eq!(next, at(2026, 3, 9, 8, 0));
+        // Strictly after
+        let next = expr.next_after(&at(2026, 3, 9, 8, 0)).unwrap();
+        assert_eq!(next, at(2026, 3, 10, 8, 0));
+        let leap = CronExpr::parse("0 0 29 2 *").unwrap();
+        assert_eq!(
+            leap.next_after(&at(2026, 3, 1, 0, 0)).unwrap(),
+            at(2028, 2, 29, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_to_eventbridge() {
+        assert_eq!(
+            CronExpr::parse("0 22 * * *")
+                .unwrap()
+                .to_eventbridge()
+                .unwrap(),
+            "cron(0 22 * * ? *)"
+        );
+        assert_eq!(
+            CronExpr::parse("0 8 * * 1-5")
+                .unwrap()
+                .to_eventbridge()
+                .unwrap(),
+            "cron(0 8 ? * MON,TUE,WED,THU,FRI *)"
+        );
+        assert_eq!(
+            CronExpr::parse("30 6 1 * *")
+                .unwrap()
+                .to_eventbridge()
+                .unwrap(),
+            "cron(30 6 1 * ? *)"
+        );
+        assert!(CronExpr::parse("0 0 1 * 5")
+            .unwrap()
+            .to_eventbridge()
+            .is_err());
+    }
+}
@@ -0,0 +1,260 @@
+//! `runctl scheduler daemon`: applies local schedules once a minute
+//!
+//! The schedule file is reread on every tick. Minutes missed while the
+//! process was suspended are caught up on, up to an hour back, so a laptop
+//! waking from sleep at 22:05 still runs a 22:00 stop.
+
+use super::{load_schedules, project_instances, Schedule, ScheduleAction, ScheduleBackend};
+use crate::config::Config;
+use crate::error::Result;
+use crate::provider::ResourceState;
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_ssm::Client as SsmClient;
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use std::path::Path;
+use tracing::warn;
+
+/// Furthest back a tick catches up on missed minutes
+const MAX_CATCH_UP_MINUTES: i64 = 60;
+
+fn minute_of(t: DateTime<Utc>) -> DateTime<Utc> {
+    t.duration_trunc(Duration::minutes(1)).unwrap_or(t)
+}
+
+/// Minutes to evaluate on this tick: those after `last` up to and including `now`
+pub(super) fn due_minutes(last: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+    let now = minute_of(now);
+    let first = match last {
+        Some(last) => (minute_of(last) + Duration::minutes(1))
+            .max(now - Duration::minutes(MAX_CATCH_UP_MINUTES - 1)),
+        None => now,
+    };
+    let mut minutes = Vec::new();
+    let mut minute = first;
+    while minute <= now {
+        minutes.push(minute);
+        minute += Duration::minutes(1);
+    }
+    minutes
+}
+
+/// Local schedule entries that fire in `minute`
+pub(super) fn due_actions<'a>(
+    schedules: &'a [Schedule],
+    minute: &DateTime<Utc>,
+) -> Vec<(&'a Schedule, ScheduleAction)> {
+    let mut due = Vec::new();
+    for schedule in schedules
+        .iter()
+        .filter(|s| s.backend == ScheduleBackend::Local)
+    {
+        match schedule.entries() {
+            Ok(entries) => due.extend(
+                entries
+                    .into_iter()
+                    .filter(|(_, expr)| expr.matches(minute))
+                    .map(|(action, _)| (schedule, action)),
+            ),
+            Err(e) => warn!("Skipping schedule for '{}': {}", schedule.project, e),
+        }
+    }
+    due
+}
+
+async fn apply(
+    client: &Ec2Client,
+    ssm_client: &SsmClient,
+    config: &Config,
+    project: &str,
+    action: ScheduleAction,
+    dry_run: bool,
+    output_format: &str,
+) {
+    let ids = match project_instances(client, project, action.source_states()).await {
+        Ok(ids) => ids,
+        Err(e) => {
+            eprintln!("ERROR: {} project '{}': {}", action, project, e);
+            return;
+        }
+    };
+
+    let result = if ids.is_empty() || dry_run {
+        Ok(())
+    } else {
+        match action {
+            ScheduleAction::Stop => {
+                // Give training a chance to checkpoint at a boundary first
+                futures::future::join_all(ids.iter().map(|id| {
+                    crate::aws_utils::stop_training_gracefully(ssm_client, id, config, "schedule")
+                }))
+                .await;
+                client
+                    .stop_instances()
+                    .set_instance_ids(Some(ids.clone()))
+                    .send()
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            }
+            ScheduleAction::Start => client
+                .start_instances()
+                .set_instance_ids(Some(ids.clone()))
+                .send()
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+        }
+    };
+
+    if output_format == "json" {
+        println!(
+            "{}",
+            serde_json::json!({
+                "time": Utc::now().to_rfc3339(),
+                "project": project,
+                "action": action,
+                "instances": ids,
+                "dry_run": dry_run,
+                "error": result.as_ref().err(),
+            })
+        );
+    } else {
+        let when = Utc::now().format("%Y-%m-%d %H:%M UTC");
+        match &result {
+            Ok(()) if ids.is_empty() => {
+                println!("[{}] {} '{}': nothing to {}", when, action, project, action)
+            }
+            Ok(()) => println!(
+                "[{}] {}{} '{}': {}",
+                when,
+                if dry_run { "would " } else { "" },
+                action,
+                project,
+                ids.join(", ")
+            ),
+            Err(e) => eprintln!("[{}] ERROR: {} '{}' failed: {}", when, action, project, e),
+        }
+    }
+
+    if result.is_ok() && !dry_run {
+        if let Some(tracker) = &config.resource_tracker {
+            let state = match action {
+                ScheduleAction::Stop => ResourceState::Stopped,
+                ScheduleAction::Start => ResourceState::Starting,
+            };
+            for id in &ids {
+                if let Err(e) = tracker.update_state(id, state.clone()).await {
+                    warn!("Failed to record {} of {}: {}", action, id, e);
+                }
+            }
+        }
+    }
+}
+
+/// Apply local schedules from `path` every minute (or once, with `once`)
+pub async fn run_daemon(
+    path: &Path,
+    once: bool,
+    dry_run: bool,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    let aws_config = crate::aws_utils::shared_sdk_config().await;
+    let client = Ec2Client::new(&aws_config);
+    let ssm_client = SsmClient::new(&aws_config);
+    if !once && output_format != "json" {
+        println!(
+            "Applying schedules from {} every minute (times in UTC). Ctrl-C to stop.",
+            path.display()
+        );
+    }
+
+    let mut last: Option<DateTime<Utc>> = None;
+    loop {
+        let now = Utc::now();
+        match load_schedules(path) {
+            Ok(schedules) => {
+                for minute in due_minutes(last, now) {
+                    for (schedule, action) in due_actions(&schedules, &minute) {
+                        apply(
+                            &client,
+                            &ssm_client,
+                            config,
+                            &schedule.project,
+                            action,
+                            dry_run,
+                            output_format,
+                        )
+                        .await;
+                    }
+                }
+            }
+            Err(e) => warn!("Failed to read schedules: {}", e),
+        }
+        last = Some(now);
+        if once {
+            return Ok(());
+        }
+
+        // Wake just after the next minute starts
+        let next = minute_of(Utc::now()) + Duration::minutes(1) + Duration::seconds(1);
+        let wait = (next - Utc::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(h: u32, m: u32, s: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, h, m, s).unwrap()
+    }
+
+    fn schedule(project: &str, stop: &str, backend: ScheduleBackend) -> Schedule {
+        Schedule {
+            project: project.to_string(),
+            stop: Some(stop.to_string()),
+            start: Some("0 8 * * 1-5".to_string()),
+            backend,
+            rules: Vec::new(),
+            created_at: at(0, 0, 0),
+        }
+    }
+
+    #[test]
+    fn test_due_minutes_first_tick_is_current_minute() {
+        assert_eq!(due_minutes(None, at(22, 0, 30)), vec![at(22, 0, 0)]);
+    }
+
+    #[test]
+    fn test_due_minutes_catches_up_without_repeats() {
+        let minutes = due_minutes(Some(at(21, 58, 1)), at(22, 1, 1));
+        assert_eq!(minutes, vec![at(21, 59, 0), at(22, 0, 0), at(22, 1, 0)]);
+        // Same minute again: nothing new
+        assert!(due_minutes(Some(at(22, 1, 1)), at(22, 1, 59)).is_empty());
+        // Long gaps are capped
+        let minutes = due_minutes(Some(at(10, 0, 0)), at(22, 0, 0));
+        assert_eq!(minutes.len() as i64, MAX_CATCH_UP_MINUTES);
+        assert_eq!(*minutes.last().unwrap(), at(22, 0, 0));
+    }
+
+    #[test]
+    fn test_due_actions_skips_eventbridge_schedules() {
+        let schedules = vec![
+            schedule("local", "0 22 * * *", ScheduleBackend::Local),
+            schedule("cloud", "0 22 * * *", ScheduleBackend::Eventbridge),
+        ];
+        let due = due_actions(&schedules, &at(22, 0, 0));
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].0.project, "local");
+        assert_eq!(due[0].1, ScheduleAction::Stop);
+
+        // 2026-03-02 is a Monday
+        let due = due_actions(&schedules, &at(8, 0, 0));
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].1, ScheduleAction::Start);
+        assert!(due_actions(&schedules, &at(12, 0, 0)).is_empty());
+    }
+}
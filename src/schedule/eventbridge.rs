@@ -0,0 +1,160 @@
+//! EventBridge rules for `resources schedule --eventbridge`
+//!
+//! Each stop or start expression becomes a scheduled rule named
+//! `runctl-<project>-<action>` whose target is the AWS-owned
+//! `AWS-StopEC2Instance` or `AWS-StartEC2Instance` automation document, run
+//! with the given role for the project's instances at the time the rule is
+//! written. Saving the schedule again refreshes that instance list.
+
+use super::{project_instances, ScheduleAction};
+use crate::error::{Result, TrainctlError};
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_eventbridge::types::{RuleState, Target};
+use aws_sdk_eventbridge::Client as EventBridgeClient;
+
+/// EventBridge rule names are limited to 64 characters
+const MAX_RULE_NAME_LEN: usize = 64;
+
+/// Target ID used for the automation target on every rule
+const TARGET_ID: &str = "runctl-automation";
+
+/// Instance states worth including in a rule's instance list
+const SCHEDULABLE_STATES: &[&str] = &["pending", "running", "stopping", "stopped"];
+
+pub(super) fn rule_name(project: &str, action: ScheduleAction) -> String {
+    let suffix = format!("-{}", action);
+    let budget = MAX_RULE_NAME_LEN - "runctl-".len() - suffix.len();
+    let project: String = project.chars().take(budget).collect();
+    format!("runctl-{}{}", project, suffix)
+}
+
+fn automation_document(action: ScheduleAction) -> &'static str {
+    match action {
+        ScheduleAction::Stop => "AWS-StopEC2Instance",
+        ScheduleAction::Start => "AWS-StartEC2Instance",
+    }
+}
+
+/// Create or update one rule per schedule entry; returns the rule names
+pub(super) async fn put_rules(
+    aws_config: &aws_config::SdkConfig,
+    project: &str,
+    entries: &[(ScheduleAction, super::CronExpr)],
+    role_arn: &str,
+    output_format: &str,
+) -> Result<Vec<String>> {
+    let region = aws_config
+        .region()
+        .map(|r| r.to_string())
+        .ok_or_else(|| TrainctlError::Aws("No AWS region configured".to_string()))?;
+    let account = crate::docker::get_account_id(aws_config).await?;
+
+    let ec2 = Ec2Client::new(aws_config);
+    let instances = project_instances(&ec2, project, SCHEDULABLE_STATES).await?;
+    if instances.is_empty() {
+        return Err(TrainctlError::ResourceNotFound {
+            resource_type: "instances for project".to_string(),
+            resource_id: project.to_string(),
+        });
+    }
+    let input = serde_json::json!({ "InstanceId": instances }).to_string();
+
+    let client = EventBridgeClient::new(aws_config);
+    let mut rules = Vec::new();
+    for (action, expr) in entries {
+        let name = rule_name(project, *action);
+        client
+            .put_rule()
+            .name(&name)
+            .schedule_expression(expr.to_eventbridge()?)
+            .state(RuleState::Enabled)
+            .description(format!(
+                "runctl: {} project '{}' at \"{}\" (UTC)",
+                action, project, expr
+            ))
+            .send()
+            .await
+            .map_err(|e| TrainctlError::Aws(format!("Failed to create rule {}: {}", name, e)))?;
+
+        let target = Target::builder()
+            .id(TARGET_ID)
+            .arn(format!(
+                "arn:aws:ssm:{}:{}:automation-definition/{}",
+                region,
+                account,
+                automation_document(*action)
+            ))
+            .role_arn(role_arn)
+            .input(&input)
+            .build()
+            .map_err(|e| TrainctlError::Aws(format!("Invalid EventBridge target: {}", e)))?;
+        client
+            .put_targets()
+            .rule(&name)
+            .targets(target)
+            .send()
+            .await
+            .map_err(|e| {
+                TrainctlError::Aws(format!("Failed to set target for rule {}: {}", name, e))
+            })?;
+
+        if output_format != "json" {
+            println!(
+                "  EventBridge rule {} covers {} instance(s)",
+                name,
+                instances.len()
+            );
+        }
+        rules.push(name);
+    }
+    Ok(rules)
+}
+
+/// Delete rules created by `put_rules`; rules that are already gone are skipped
+pub(super) async fn delete_rules(
+    aws_config: &aws_config::SdkConfig,
+    rules: &[String],
+) -> Result<()> {
+    let client = EventBridgeClient::new(aws_config);
+    for name in rules {
+        if let Err(e) = client
+            .remove_targets()
+            .rule(name)
+            .ids(TARGET_ID)
+            .send()
+            .await
+        {
+            if e.as_service_error()
+                .map(|se| se.is_resource_not_found_exception())
+                .unwrap_or(false)
+            {
+                continue;
+            }
+            return Err(TrainctlError::Aws(format!(
+                "Failed to remove targets from rule {}: {}",
+                name, e
+            )));
+        }
+        client
+            .delete_rule()
+            .name(name)
+            .send()
+            .await
+            .map_err(|e| TrainctlError::Aws(format!("Failed to delete rule {}: {}", name, e)))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_name_fits_limit() {
+        assert_eq!(rule_name("llm", ScheduleAction::Stop), "runctl-llm-stop");
+        let long = "p".repeat(64);
+        let name = rule_name(&long, ScheduleAction::Start);
+        assert_eq!(name.len(), MAX_RULE_NAME_LEN);
+        assert!(name.ends_with("-start"));
+    }
+}
@@ -0,0 +1,422 @@
+//! Scheduled stop/start of a project's instances
+//!
+//! `runctl resources schedule --stop "0 22 * * *" --start "0 8 * * 1-5"
+//! --project myproj` records a schedule in `.runctl/schedules.json`. At each
+//! time the stop expression fires, the project's running EC2 instances (by
+//! `runctl:project` tag) are stopped; at each start time its stopped
+//! instances are started. Expressions are five-field cron, evaluated in UTC
+//! (see `cron`).
+//!
+//! Schedules are applied one of two ways:
+//!
+//! - locally, by `runctl scheduler daemon`, which checks every minute and
+//!   rereads the schedule file, so changes apply without a restart
+//! - with `--eventbridge`, as EventBridge rules that run the
+//!   `AWS-StopEC2Instance` / `AWS-StartEC2Instance` automation documents
+//!   (see `eventbridge`); nothing has to keep running locally, but the rules
+//!   cover the instances the project had when the schedule was saved
+//!
+//! Only `cron` is compiled without the `aws` feature; both commands then
+//! fail with `FeatureNotCompiled`.
+
+// Without `aws` the schedule file types are only used by the tests
+#![cfg_attr(not(feature = "aws"), allow(dead_code))]
+
+pub mod cron;
+#[cfg(feature = "aws")]
+mod daemon;
+#[cfg(feature = "aws")]
+mod eventbridge;
+
+pub use cron::CronExpr;
+#[cfg(feature = "aws")]
+pub use daemon::run_daemon;
+
+use crate::config::Config;
+use crate::error::{Result, TrainctlError};
+use crate::utils::ensure_dir;
+#[cfg(feature = "aws")]
+use aws_sdk_ec2::types::Filter;
+#[cfg(feature = "aws")]
+use aws_sdk_ec2::Client as Ec2Client;
+use chrono::{DateTime, Utc};
+use clap::Subcommand;
+use comfy_table::{Cell, Table};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// Where schedules are stored, relative to the project root
+pub const SCHEDULES_PATH: &str = ".runctl/schedules.json";
+
+#[derive(Subcommand, Clone)]
+pub enum SchedulerCommands {
+    /// Apply local schedules from .runctl/schedules.json
+    ///
+    /// Checks once a minute and stops or starts instances whose schedule
+    /// fires. EventBridge schedules are skipped; AWS applies those.
+    ///
+    /// Examples:
+    ///   runctl scheduler daemon
+    ///   runctl scheduler daemon --once --dry-run
+    Daemon {
+        /// Check the current minute once and exit (e.g. from system cron)
+        #[arg(long)]
+        once: bool,
+
+        /// Show what would be stopped or started without doing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[cfg(feature = "aws")]
+pub async fn handle_command(
+    cmd: SchedulerCommands,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    match cmd {
+        SchedulerCommands::Daemon { once, dry_run } => {
+            run_daemon(
+                Path::new(SCHEDULES_PATH),
+                once,
+                dry_run,
+                config,
+                output_format,
+            )
+            .await
+        }
+    }
+}
+
+#[cfg(not(feature = "aws"))]
+pub async fn handle_command(
+    _cmd: SchedulerCommands,
+    _config: &Config,
+    _output_format: &str,
+) -> Result<()> {
+    Err(crate::error::ConfigError::FeatureNotCompiled("aws".to_string()).into())
+}
+
+/// What a schedule entry does when it fires
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleAction {
+    Stop,
+    Start,
+}
+
+impl ScheduleAction {
+    /// Instance states this action applies to
+    fn source_states(self) -> &'static [&'static str] {
+        match self {
+            ScheduleAction::Stop => &["running"],
+            ScheduleAction::Start => &["stopped"],
+        }
+    }
+}
+
+impl fmt::Display for ScheduleAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ScheduleAction::Stop => "stop",
+            ScheduleAction::Start => "start",
+        })
+    }
+}
+
+/// Who applies a schedule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleBackend {
+    /// `runctl scheduler daemon`
+    #[default]
+    Local,
+    /// EventBridge rules in the AWS account
+    Eventbridge,
+}
+
+impl fmt::Display for ScheduleBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ScheduleBackend::Local => "local",
+            ScheduleBackend::Eventbridge => "eventbridge",
+        })
+    }
+}
+
+/// A project's stop/start schedule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Schedule {
+    pub project: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+    #[serde(default)]
+    pub backend: ScheduleBackend,
+    /// EventBridge rule names created for this schedule
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Schedule {
+    /// The schedule's cron expressions with the action each triggers
+    pub fn entries(&self) -> Result<Vec<(ScheduleAction, CronExpr)>> {
+        let mut entries = Vec::new();
+        if let Some(expr) = &self.stop {
+            entries.push((ScheduleAction::Stop, CronExpr::parse(expr)?));
+        }
+        if let Some(expr) = &self.start {
+            entries.push((ScheduleAction::Start, CronExpr::parse(expr)?));
+        }
+        Ok(entries)
+    }
+}
+
+/// Read all schedules; a missing file means none
+pub fn load_schedules(path: &Path) -> Result<Vec<Schedule>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(path)?;
+    serde_json::from_str(&content).map_err(|e| {
+        TrainctlError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Failed to parse {}: {}", path.display(), e),
+        ))
+    })
+}
+
+/// Write all schedules, replacing the file
+pub fn save_schedules(path: &Path, schedules: &[Schedule]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        ensure_dir(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(schedules)?)?;
+    Ok(())
+}
+
+/// Options for `runctl resources schedule`
+#[derive(Debug, Clone)]
+pub struct ScheduleOptions {
+    pub project: Option<String>,
+    pub stop: Option<String>,
+    pub start: Option<String>,
+    pub eventbridge: bool,
+    pub role_arn: Option<String>,
+    pub remove: bool,
+}
+
+/// IDs of the project's EC2 instances in any of `states`
+#[cfg(feature = "aws")]
+pub(crate) async fn project_instances(
+    client: &Ec2Client,
+    project: &str,
+    states: &[&str],
+) -> Result<Vec<String>> {
+    let mut ids = Vec::new();
+    let mut next_token: Option<String> = None;
+    loop {
+        let response = client
+            .describe_instances()
+            .filters(
+                Filter::builder()
+                    .name("tag:runctl:project")
+                    .values(project)
+                    .build(),
+            )
+            .filters(
+                Filter::builder()
+                    .name("instance-state-name")
+                    .set_values(Some(states.iter().map(|s| s.to_string()).collect()))
+                    .build(),
+            )
+            .set_next_token(next_token)
+            .send()
+            .await
+            .map_err(|e| TrainctlError::Aws(format!("Failed to describe instances: {}", e)))?;
+        ids.extend(
+            response
+                .reservations()
+                .iter()
+                .flat_map(|r| r.instances())
+                .filter_map(|i| i.instance_id().map(str::to_string)),
+        );
+        next_token = response.next_token().map(str::to_string);
+        if next_token.is_none() {
+            return Ok(ids);
+        }
+    }
+}
+
+/// Add, replace, remove or list schedules (`runctl resources schedule`)
+#[cfg(feature = "aws")]
+pub async fn handle_schedule(
+    options: ScheduleOptions,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    let path = Path::new(SCHEDULES_PATH);
+    let mut schedules = load_schedules(path)?;
+
+    if options.stop.is_none() && options.start.is_none() && !options.remove {
+        return print_schedules(&schedules, output_format);
+    }
+
+    let project = crate::utils::get_project_name(options.project.clone(), config);
+    crate::validation::validate_project_name(&project)?;
+    let existing = schedules.iter().position(|s| s.project == project);
+
+    if options.remove {
+        let Some(index) = existing else {
+            return Err(TrainctlError::ResourceNotFound {
+                resource_type: "schedule".to_string(),
+                resource_id: project,
+            });
+        };
+        delete_old_rules(&schedules[index]).await?;
+        schedules.remove(index);
+        save_schedules(path, &schedules)?;
+        if output_format == "json" {
+            println!(
+                "{}",
+                serde_json::json!({"success": true, "project": project, "removed": true})
+            );
+        } else {
+            println!("Removed schedule for project '{}'", project);
+        }
+        return Ok(());
+    }
+
+    let mut schedule = Schedule {
+        project: project.clone(),
+        stop: options.stop.clone(),
+        start: options.start.clone(),
+        backend: if options.eventbridge {
+            ScheduleBackend::Eventbridge
+        } else {
+            ScheduleBackend::Local
+        },
+        rules: Vec::new(),
+        created_at: Utc::now(),
+    };
+    let entries = schedule.entries()?;
+    let role_arn = match (options.eventbridge, options.role_arn.as_deref()) {
+        (true, None) => {
+            return Err(TrainctlError::Validation {
+                field: "role_arn".to_string(),
+                reason: "--eventbridge needs --role-arn, a role EventBridge can assume to run SSM Automation (ec2:StopInstances, ec2:StartInstances)".to_string(),
+            })
+        }
+        (_, role_arn) => role_arn,
+    };
+
+    if options.eventbridge {
+        // Fail before touching existing rules if EventBridge can't express it
+        for (_, expr) in &entries {
+            expr.to_eventbridge()?;
+        }
+    }
+
+    // Rules from an earlier EventBridge schedule are replaced
+    if let Some(index) = existing {
+        delete_old_rules(&schedules[index]).await?;
+    }
+    if let Some(role_arn) = role_arn.filter(|_| options.eventbridge) {
+        let aws_config = crate::aws_utils::shared_sdk_config().await;
+        schedule.rules =
+            eventbridge::put_rules(&aws_config, &project, &entries, role_arn, output_format)
+                .await?;
+    }
+
+    match existing {
+        Some(index) => schedules[index] = schedule.clone(),
+        None => schedules.push(schedule.clone()),
+    }
+    save_schedules(path, &schedules)?;
+
+    if output_format == "json" {
+        println!("{}", serde_json::to_string_pretty(&schedule)?);
+    } else {
+        println!("Scheduled project '{}' ({}):", project, schedule.backend);
+        let now = Utc::now();
+        for (action, expr) in &entries {
+            match expr.next_after(&now) {
+                Some(next) => println!(
+                    "  {} at \"{}\" (next: {})",
+                    action,
+                    expr,
+                    next.format("%Y-%m-%d %H:%M UTC")
+                ),
+                None => println!("  {} at \"{}\" (never fires)", action, expr),
+            }
+        }
+        if schedule.backend == ScheduleBackend::Local {
+            println!("Run `runctl scheduler daemon` to apply it.");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "aws"))]
+pub async fn handle_schedule(
+    _options: ScheduleOptions,
+    _config: &Config,
+    _output_format: &str,
+) -> Result<()> {
+    Err(crate::error::ConfigError::FeatureNotCompiled("aws".to_string()).into())
+}
+
+#[cfg(feature = "aws")]
+async fn delete_old_rules(schedule: &Schedule) -> Result<()> {
+    if schedule.rules.is_empty() {
+        return Ok(());
+    }
+    let aws_config = crate::aws_utils::shared_sdk_config().await;
+    eventbridge::delete_rules(&aws_config, &schedule.rules).await
+}
+
+fn print_schedules(schedules: &[Schedule], output_format: &str) -> Result<()> {
+    if output_format == "json" {
+        println!("{}", serde_json::to_string_pretty(schedules)?);
+        return Ok(());
+    }
+    if schedules.is_empty() {
+        println!("No schedules. Add one with: runctl resources schedule --stop \"0 22 * * *\" --project <name>");
+        return Ok(());
+    }
+    let now = Utc::now();
+    let mut table = Table::new();
+    table.set_header(vec![
+        "Project",
+        "Stop (UTC)",
+        "Start (UTC)",
+        "Next",
+        "Backend",
+    ]);
+    for schedule in schedules {
+        let next = schedule
+            .entries()
+            .ok()
+            .and_then(|entries| {
+                entries
+                    .iter()
+                    .filter_map(|(action, expr)| expr.next_after(&now).map(|t| (t, *action)))
+                    .min_by_key(|(t, _)| *t)
+            })
+            .map(|(t, action)| format!("{} {}", action, t.format("%Y-%m-%d %H:%M")))
+            .unwrap_or_else(|| "-".to_string());
+        table.add_row(vec![
+            Cell::new(&schedule.project),
+            Cell::new(schedule.stop.as_deref().unwrap_or("-")),
+            Cell::new(schedule.start.as_deref().unwrap_or("-")),
+            Cell::new(next),
+            Cell::new(schedule.backend),
+        ]);
+    }
+    println!("{}", table);
+    Ok(())
+}
@@ -0,0 +1,670 @@
+//! Secrets for API keys and tokens
+//!
+//! `runctl secrets set/get/list/remove` keep RunPod keys, W&B and Hugging
+//! Face tokens and the like out of plaintext config. Values live in the OS
+//! keyring (the macOS Keychain through `security`, the Secret Service through
+//! `secret-tool` on Linux) or, with `[secrets] backend = "aws"` or
+//! `--backend aws`, in AWS Secrets Manager as `<aws_prefix><NAME>`.
+//!
+//! ## References
+//!
+//! Config values and training environment values can be `secret://NAME`
+//! instead of the secret itself. They are resolved when used:
+//!
+//! - `[runpod] api_key = "secret://runpod"`
+//! - environment passed to training (`runctl jobs`, manifests, `[env]`)
+//!
+//! `RUNCTL_SECRET_<NAME>` (upper case, `-` and `.` as `_`) overrides a
+//! secret, for CI runners that have no keyring.
+//!
+//! ## Index
+//!
+//! Which backend holds each name is recorded, without the values, in
+//! `~/.runctl/secrets.json`; that is what `secrets list` shows, along with
+//! any Secrets Manager secrets under the prefix created elsewhere.
+
+use crate::config::{Config, SecretBackend};
+use crate::error::{Result, TrainctlError};
+use chrono::{DateTime, Utc};
+use clap::Subcommand;
+use comfy_table::Table;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+use tracing::debug;
+
+/// Prefix marking a config value as a secret reference
+pub const SCHEME: &str = "secret://";
+
+/// Keyring service the secrets are stored under
+const KEYRING_SERVICE: &str = "runctl";
+
+#[derive(Subcommand, Clone)]
+pub enum SecretsCommands {
+    /// Store a secret
+    ///
+    /// The value is read from stdin, or typed without echo at a terminal,
+    /// never from the command line, so it stays out of shell history.
+    ///
+    /// Examples:
+    ///   runctl secrets set runpod
+    ///   echo "$WANDB_API_KEY" | runctl secrets set wandb --backend aws
+    Set {
+        /// Secret name (letters, digits, '-', '_', '.')
+        name: String,
+        /// Where to store it (default: [secrets] backend)
+        #[arg(long, value_enum)]
+        backend: Option<SecretBackend>,
+    },
+    /// Print a secret's value
+    ///
+    /// Examples:
+    ///   runctl secrets get hf
+    ///   export HF_TOKEN=$(runctl secrets get hf)
+    Get {
+        /// Secret name
+        name: String,
+    },
+    /// List stored secrets (names only)
+    List,
+    /// Delete a secret
+    Remove {
+        /// Secret name
+        name: String,
+    },
+}
+
+/// A stored secret, as recorded in the index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretEntry {
+    pub name: String,
+    pub backend: SecretBackend,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SecretIndex {
+    #[serde(default)]
+    secrets: BTreeMap<String, SecretEntry>,
+}
+
+fn index_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".runctl").join("secrets.json"))
+}
+
+impl SecretIndex {
+    fn load() -> Self {
+        index_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = index_path() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            crate::utils::ensure_dir(parent)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+}
+
+/// Check a secret name is usable as a keyring account and a Secrets Manager name
+pub fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 128
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(TrainctlError::Validation {
+            field: "name".to_string(),
+            reason: format!(
+                "'{}' is not a valid secret name (letters, digits, '-', '_', '.')",
+                name
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Environment variable that overrides secret `name`
+pub fn env_override(name: &str) -> String {
+    format!(
+        "RUNCTL_SECRET_{}",
+        name.to_ascii_uppercase().replace(['-', '.'], "_")
+    )
+}
+
+fn not_found(name: &str) -> TrainctlError {
+    TrainctlError::ResourceNotFound {
+        resource_type: "secret".to_string(),
+        resource_id: format!("{} (store it with `runctl secrets set {}`)", name, name),
+    }
+}
+
+fn tool_error(tool: &str, detail: impl std::fmt::Display) -> TrainctlError {
+    TrainctlError::Io(std::io::Error::other(format!(
+        "{} failed: {}",
+        tool, detail
+    )))
+}
+
+/// OS keyring access through the platform's command-line tool
+mod keyring {
+    use super::*;
+
+    /// Run `tool args`, feeding `input` on stdin; `None` if it exits non-zero
+    fn run(tool: &str, args: &[&str], input: Option<&str>) -> Result<Option<String>> {
+        let mut child = Command::new(tool)
+            .args(args)
+            .stdin(if input.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                tool_error(
+                    tool,
+                    format!(
+                        "{} (use `--backend aws` or {} instead)",
+                        e,
+                        env_override("NAME")
+                    ),
+                )
+            })?;
+        if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+            stdin.write_all(input.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            debug!(
+                "{} {} exited with {}: {}",
+                tool,
+                args.first().unwrap_or(&""),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            return Ok(None);
+        }
+        Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()))
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn set(name: &str, value: &str) -> Result<()> {
+        // -U updates an existing item instead of failing
+        let args = [
+            "add-generic-password",
+            "-U",
+            "-s",
+            KEYRING_SERVICE,
+            "-a",
+            name,
+            "-w",
+            value,
+        ];
+        run("security", &args, None)?
+            .map(|_| ())
+            .ok_or_else(|| tool_error("security", format!("could not store {}", name)))
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn get(name: &str) -> Result<Option<String>> {
+        let args = [
+            "find-generic-password",
+            "-s",
+            KEYRING_SERVICE,
+            "-a",
+            name,
+            "-w",
+        ];
+        Ok(run("security", &args, None)?.map(|v| v.trim_end_matches('\n').to_string()))
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn remove(name: &str) -> Result<bool> {
+        let args = ["delete-generic-password", "-s", KEYRING_SERVICE, "-a", name];
+        Ok(run("security", &args, None)?.is_some())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    pub fn set(name: &str, value: &str) -> Result<()> {
+        let label = format!("{} {}", KEYRING_SERVICE, name);
+        let args = [
+            "store",
+            "--label",
+            &label,
+            "service",
+            KEYRING_SERVICE,
+            "account",
+            name,
+        ];
+        run("secret-tool", &args, Some(value))?
+            .map(|_| ())
+            .ok_or_else(|| tool_error("secret-tool", format!("could not store {}", name)))
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    pub fn get(name: &str) -> Result<Option<String>> {
+        let args = ["lookup", "service", KEYRING_SERVICE, "account", name];
+        Ok(run("secret-tool", &args, None)?.filter(|v| !v.is_empty()))
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    pub fn remove(name: &str) -> Result<bool> {
+        let args = ["clear", "service", KEYRING_SERVICE, "account", name];
+        Ok(run("secret-tool", &args, None)?.is_some())
+    }
+}
+
+/// AWS Secrets Manager access
+#[cfg(feature = "aws")]
+mod secrets_manager {
+    use super::*;
+    use aws_sdk_secretsmanager::types::{Filter, FilterNameStringType};
+    use aws_sdk_secretsmanager::Client;
+
+    async fn client() -> Client {
+        Client::new(&crate::aws_utils::shared_sdk_config().await)
+    }
+
+    fn error(action: &str, id: &str, e: impl std::fmt::Display) -> TrainctlError {
+        TrainctlError::CloudProvider {
+            provider: "aws".to_string(),
+            message: format!("Failed to {} secret {}: {}", action, id, e),
+            source: None,
+        }
+    }
+
+    pub async fn set(id: &str, value: &str) -> Result<()> {
+        let client = client().await;
+        match client
+            .put_secret_value()
+            .secret_id(id)
+            .secret_string(value)
+            .send()
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e)
+                if e.as_service_error()
+                    .is_some_and(|e| e.is_resource_not_found_exception()) =>
+            {
+                client
+                    .create_secret()
+                    .name(id)
+                    .secret_string(value)
+                    .description("Stored by runctl secrets set")
+                    .send()
+                    .await
+                    .map_err(|e| error("create", id, e))?;
+                Ok(())
+            }
+            Err(e) => Err(error("update", id, e)),
+        }
+    }
+
+    pub async fn get(id: &str) -> Result<Option<String>> {
+        match client().await.get_secret_value().secret_id(id).send().await {
+            Ok(output) => Ok(output.secret_string().map(str::to_string)),
+            Err(e)
+                if e.as_service_error()
+                    .is_some_and(|e| e.is_resource_not_found_exception()) =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(error("read", id, e)),
+        }
+    }
+
+    pub async fn remove(id: &str) -> Result<bool> {
+        match client()
+            .await
+            .delete_secret()
+            .secret_id(id)
+            .recovery_window_in_days(7)
+            .send()
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(e)
+                if e.as_service_error()
+                    .is_some_and(|e| e.is_resource_not_found_exception()) =>
+            {
+                Ok(false)
+            }
+            Err(e) => Err(error("delete", id, e)),
+        }
+    }
+
+    /// Secret names under `prefix`, without it
+    pub async fn list(prefix: &str) -> Result<Vec<(String, Option<DateTime<Utc>>)>> {
+        let client = client().await;
+        let filter = Filter::builder()
+            .key(FilterNameStringType::Name)
+            .values(prefix)
+            .build();
+        let mut names = Vec::new();
+        let mut next_token: Option<String> = None;
+        loop {
+            let output = client
+                .list_secrets()
+                .filters(filter.clone())
+                .set_next_token(next_token.clone())
+                .send()
+                .await
+                .map_err(|e| error("list", prefix, e))?;
+            for secret in output.secret_list() {
+                let Some(name) = secret.name().and_then(|n| n.strip_prefix(prefix)) else {
+                    continue;
+                };
+                let updated = secret
+                    .last_changed_date()
+                    .and_then(|t| DateTime::from_timestamp(t.secs(), t.subsec_nanos()));
+                names.push((name.to_string(), updated));
+            }
+            next_token = output.next_token().map(str::to_string);
+            if next_token.is_none() {
+                break;
+            }
+        }
+        Ok(names)
+    }
+
+    /// Run a lookup from sync code, whatever runtime (if any) is current
+    pub fn get_blocking(id: &str) -> Result<Option<String>> {
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()?
+                        .block_on(get(id))
+                })
+                .join()
+                .unwrap_or_else(|_| Err(error("read", id, "lookup thread panicked")))
+        })
+    }
+}
+
+#[cfg(feature = "aws")]
+fn aws_id(name: &str, config: &Config) -> String {
+    format!("{}{}", config.secrets.aws_prefix, name)
+}
+
+#[cfg(not(feature = "aws"))]
+fn aws_unavailable() -> TrainctlError {
+    crate::error::ConfigError::FeatureNotCompiled("aws".to_string()).into()
+}
+
+/// Values resolved so far in this process
+fn cache() -> &'static Mutex<HashMap<String, String>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+#[cfg_attr(not(feature = "aws"), allow(unused_variables))]
+fn fetch_from(backend: SecretBackend, name: &str, config: &Config) -> Result<Option<String>> {
+    match backend {
+        SecretBackend::Keyring => keyring::get(name),
+        #[cfg(feature = "aws")]
+        SecretBackend::Aws => secrets_manager::get_blocking(&aws_id(name, config)),
+        #[cfg(not(feature = "aws"))]
+        SecretBackend::Aws => Err(aws_unavailable()),
+    }
+}
+
+/// Look up secret `name`
+///
+/// `RUNCTL_SECRET_<NAME>` wins; otherwise the backend recorded in the index
+/// is asked, or, for names stored elsewhere, the keyring and then (with
+/// `[secrets] backend = "aws"`) Secrets Manager.
+pub fn lookup(name: &str, config: &Config) -> Result<String> {
+    validate_name(name)?;
+    if let Ok(value) = std::env::var(env_override(name)) {
+        return Ok(value);
+    }
+    if let Some(value) = cache().lock().ok().and_then(|c| c.get(name).cloned()) {
+        return Ok(value);
+    }
+    let value = match SecretIndex::load().secrets.get(name) {
+        Some(entry) => fetch_from(entry.backend, name, config)?,
+        None => match keyring::get(name) {
+            Ok(Some(value)) => Some(value),
+            _ if config.secrets.backend == SecretBackend::Aws => {
+                fetch_from(SecretBackend::Aws, name, config)?
+            }
+            result => result?,
+        },
+    };
+    let value = value.ok_or_else(|| not_found(name))?;
+    if let Ok(mut cache) = cache().lock() {
+        cache.insert(name.to_string(), value.clone());
+    }
+    Ok(value)
+}
+
+/// `value` itself, or the secret it references (`secret://NAME`)
+pub fn resolve(value: &str, config: &Config) -> Result<String> {
+    match value.strip_prefix(SCHEME) {
+        Some(name) => lookup(name, config),
+        None => Ok(value.to_string()),
+    }
+}
+
+/// Resolve `secret://` references in training environment values
+pub fn resolve_env(env: &[(String, String)], config: &Config) -> Result<Vec<(String, String)>> {
+    env.iter()
+        .map(|(key, value)| Ok((key.clone(), resolve(value, config)?)))
+        .collect()
+}
+
+/// Store secret `name` in `backend` and record it in the index
+#[cfg_attr(not(feature = "aws"), allow(unused_variables))]
+pub async fn set(name: &str, value: &str, backend: SecretBackend, config: &Config) -> Result<()> {
+    validate_name(name)?;
+    if value.is_empty() {
+        return Err(TrainctlError::Validation {
+            field: "value".to_string(),
+            reason: "secret value is empty".to_string(),
+        });
+    }
+    match backend {
+        SecretBackend::Keyring => keyring::set(name, value)?,
+        #[cfg(feature = "aws")]
+        SecretBackend::Aws => secrets_manager::set(&aws_id(name, config), value).await?,
+        #[cfg(not(feature = "aws"))]
+        SecretBackend::Aws => return Err(aws_unavailable()),
+    }
+    let mut index = SecretIndex::load();
+    index.secrets.insert(
+        name.to_string(),
+        SecretEntry {
+            name: name.to_string(),
+            backend,
+            updated_at: Some(Utc::now()),
+        },
+    );
+    index.save()?;
+    if let Ok(mut cache) = cache().lock() {
+        cache.remove(name);
+    }
+    Ok(())
+}
+
+/// Delete secret `name` from its backend; false if it didn't exist
+pub async fn remove(name: &str, config: &Config) -> Result<bool> {
+    validate_name(name)?;
+    let mut index = SecretIndex::load();
+    let backend = index
+        .secrets
+        .get(name)
+        .map(|e| e.backend)
+        .unwrap_or(config.secrets.backend);
+    let removed = match backend {
+        SecretBackend::Keyring => keyring::remove(name)?,
+        #[cfg(feature = "aws")]
+        SecretBackend::Aws => secrets_manager::remove(&aws_id(name, config)).await?,
+        #[cfg(not(feature = "aws"))]
+        SecretBackend::Aws => return Err(aws_unavailable()),
+    };
+    let indexed = index.secrets.remove(name).is_some();
+    index.save()?;
+    Ok(removed || indexed)
+}
+
+/// Secrets in the index, plus Secrets Manager ones when that is the backend
+#[cfg_attr(not(feature = "aws"), allow(unused_variables))]
+pub async fn list(config: &Config) -> Result<Vec<SecretEntry>> {
+    #[cfg_attr(not(feature = "aws"), allow(unused_mut))]
+    let mut secrets = SecretIndex::load().secrets;
+    #[cfg(feature = "aws")]
+    if config.secrets.backend == SecretBackend::Aws {
+        for (name, updated_at) in secrets_manager::list(&config.secrets.aws_prefix).await? {
+            secrets.entry(name.clone()).or_insert(SecretEntry {
+                name,
+                backend: SecretBackend::Aws,
+                updated_at,
+            });
+        }
+    }
+    Ok(secrets.into_values().collect())
+}
+
+/// Read a secret value from stdin, without echo when it is a terminal
+fn read_value(name: &str) -> Result<String> {
+    let stdin = std::io::stdin();
+    let mut value = String::new();
+    if stdin.is_terminal() {
+        eprint!("Value for {}: ", name);
+        std::io::stderr().flush()?;
+        let echo_off = Command::new("stty")
+            .arg("-echo")
+            .stdin(Stdio::inherit())
+            .status()
+            .is_ok_and(|s| s.success());
+        let read = stdin.read_line(&mut value);
+        if echo_off {
+            let _ = Command::new("stty")
+                .arg("echo")
+                .stdin(Stdio::inherit())
+                .status();
+        }
+        eprintln!();
+        read?;
+    } else {
+        std::io::Read::read_to_string(&mut stdin.lock(), &mut value)?;
+    }
+    Ok(value.trim_end_matches(['\r', '\n']).to_string())
+}
+
+pub async fn handle_command(
+    cmd: SecretsCommands,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    match cmd {
+        SecretsCommands::Set { name, backend } => {
+            validate_name(&name)?;
+            let backend = backend.unwrap_or(config.secrets.backend);
+            let value = read_value(&name)?;
+            set(&name, &value, backend, config).await?;
+            if output_format == "json" {
+                println!(
+                    "{}",
+                    serde_json::json!({ "name": name, "backend": backend.to_string() })
+                );
+            } else {
+                println!(
+                    "Stored {} in {}; reference it as {}{}",
+                    name, backend, SCHEME, name
+                );
+            }
+            Ok(())
+        }
+        SecretsCommands::Get { name } => {
+            println!("{}", lookup(&name, config)?);
+            Ok(())
+        }
+        SecretsCommands::List => {
+            let secrets = list(config).await?;
+            if output_format == "json" {
+                println!("{}", serde_json::to_string_pretty(&secrets)?);
+                return Ok(());
+            }
+            if secrets.is_empty() {
+                println!("No secrets stored (add one with `runctl secrets set NAME`)");
+                return Ok(());
+            }
+            let mut table = Table::new();
+            table.set_header(vec!["Name", "Backend", "Updated", "Reference"]);
+            for secret in &secrets {
+                table.add_row(vec![
+                    secret.name.clone(),
+                    secret.backend.to_string(),
+                    secret
+                        .updated_at
+                        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_default(),
+                    format!("{}{}", SCHEME, secret.name),
+                ]);
+            }
+            println!("{}", table);
+            Ok(())
+        }
+        SecretsCommands::Remove { name } => {
+            if !remove(&name, config).await? {
+                return Err(not_found(&name));
+            }
+            if output_format != "json" {
+                println!("Removed {}", name);
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_and_overrides() {
+        assert!(validate_name("hf-token.v2").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("../key").is_err());
+        assert!(validate_name("a b").is_err());
+        assert_eq!(env_override("wandb-api.key"), "RUNCTL_SECRET_WANDB_API_KEY");
+    }
+
+    #[test]
+    fn test_resolve_references() {
+        let config = Config::default();
+        std::env::set_var("RUNCTL_SECRET_TEST_RESOLVE", "s3cr3t");
+        assert_eq!(resolve("secret://test-resolve", &config).unwrap(), "s3cr3t");
+        assert_eq!(resolve("plain", &config).unwrap(), "plain");
+        let env = resolve_env(
+            &[
+                ("HF_TOKEN".to_string(), "secret://test_resolve".to_string()),
+                ("WANDB_MODE".to_string(), "offline".to_string()),
+            ],
+            &config,
+        )
+        .unwrap();
+        assert_eq!(env[0].1, "s3cr3t");
+        assert_eq!(env[1].1, "offline");
+        assert!(resolve("secret://bad name", &config).is_err());
+        std::env::remove_var("RUNCTL_SECRET_TEST_RESOLVE");
+    }
+}
@@ -0,0 +1,357 @@
+//! Interactive `runctl shell`
+//!
+//! A readline loop that parses each line with the same clap definitions as the
+//! command line and runs it in this process, so loading the config, setting up
+//! logging and resolving AWS credentials (see
+//! `runctl::aws_utils::shared_sdk_config`) happen once per session instead of
+//! once per command.
+//!
+//! Context selected with `use` persists between lines:
+//!
+//! - `use instance <id>`: `$instance` expands to the ID
+//! - `use project <name>`: `$project` expands to the name, and it becomes the
+//!   default project (`[aws] default_project_name`) for later commands
+//! - `use profile <name>`: sets `AWS_PROFILE` for later AWS calls
+//!
+//! Tab completes command names, flags, and resource IDs from the resource
+//! tracker's cache. History is kept in `~/.runctl/shell_history`.
+
+use crate::{execute, report_error, Cli};
+use clap::{CommandFactory, Parser};
+use runctl::config::Config;
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::path::PathBuf;
+
+/// Words handled by the shell itself rather than passed to clap
+const SHELL_COMMANDS: &[&str] = &["use", "unset", "context", "help", "exit", "quit"];
+
+/// Kinds of context `use` and `unset` accept
+const CONTEXT_KINDS: &[&str] = &["instance", "project", "profile"];
+
+const SHELL_HELP: &str = "\
+Shell commands:
+  use instance <id>     Select an instance ($instance)
+  use project <name>    Select a project ($project, default project name)
+  use profile <name>    Select an AWS profile (AWS_PROFILE)
+  unset <kind>          Clear instance, project or profile
+  context               Show the current selection
+  help [command]        Show this help, or help for a runctl command
+  exit, quit            Leave the shell (or Ctrl-D)
+
+Any other line is a runctl command without the `runctl` prefix, e.g.
+  aws status $instance
+  resources list --platform aws";
+
+/// Selection carried from one line to the next
+#[derive(Debug, Default)]
+struct ShellContext {
+    instance: Option<String>,
+    project: Option<String>,
+    profile: Option<String>,
+}
+
+impl ShellContext {
+    fn prompt(&self) -> String {
+        let parts: Vec<&str> = [&self.profile, &self.project, &self.instance]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        if parts.is_empty() {
+            "runctl> ".to_string()
+        } else {
+            format!("runctl[{}]> ", parts.join("|"))
+        }
+    }
+
+    /// Replace `$instance` and `$project` with the selection
+    fn substitute(&self, words: Vec<String>) -> Result<Vec<String>, String> {
+        words
+            .into_iter()
+            .map(|word| {
+                let (value, kind) = match word.as_str() {
+                    "$instance" => (&self.instance, "instance"),
+                    "$project" => (&self.project, "project"),
+                    _ => return Ok(word),
+                };
+                value
+                    .clone()
+                    .ok_or_else(|| format!("no {} selected (use {} <...>)", kind, kind))
+            })
+            .collect()
+    }
+}
+
+/// Tab completion for command names, flags and cached resource IDs
+struct ShellHelper {
+    top_level: Vec<String>,
+    resource_ids: Vec<String>,
+}
+
+impl ShellHelper {
+    fn candidates(&self, previous: &[&str], word: &str) -> Vec<String> {
+        let mut out: Vec<String> = match previous {
+            [] => self.top_level.clone(),
+            ["use" | "unset"] => CONTEXT_KINDS.iter().map(|s| s.to_string()).collect(),
+            ["use", "instance"] => self.resource_ids.clone(),
+            ["use" | "unset", ..] => Vec::new(),
+            _ => {
+                // Walk the clap tree along the subcommands typed so far
+                let mut command = Cli::command();
+                for w in previous.iter().filter(|w| !w.starts_with('-')) {
+                    match command.find_subcommand(w) {
+                        Some(sub) => command = sub.clone(),
+                        None => break,
+                    }
+                }
+                if word.starts_with('-') {
+                    command
+                        .get_arguments()
+                        .filter_map(|arg| arg.get_long())
+                        .map(|long| format!("--{}", long))
+                        .collect()
+                } else {
+                    let mut names: Vec<String> = command
+                        .get_subcommands()
+                        .map(|sub| sub.get_name().to_string())
+                        .collect();
+                    names.extend(self.resource_ids.iter().cloned());
+                    names.push("$instance".to_string());
+                    names.push("$project".to_string());
+                    names
+                }
+            }
+        };
+        out.retain(|c| c.starts_with(word));
+        out.sort();
+        out.dedup();
+        out
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let before = &line[..pos];
+        let start = before
+            .rfind(char::is_whitespace)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let previous: Vec<&str> = before[..start].split_whitespace().collect();
+        let pairs = self
+            .candidates(&previous, &before[start..])
+            .into_iter()
+            .map(|c| Pair {
+                display: c.clone(),
+                replacement: c,
+            })
+            .collect();
+        Ok((start, pairs))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+fn history_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".runctl").join("shell_history"))
+}
+
+/// IDs of tracked resources, for completion
+async fn cached_resource_ids(config: &Config) -> Vec<String> {
+    match &config.resource_tracker {
+        Some(tracker) => tracker
+            .get_all()
+            .await
+            .into_iter()
+            .map(|r| r.status.id)
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Handle `use`, `unset` and `context`; returns a message to print
+fn handle_context_command(
+    ctx: &mut ShellContext,
+    config: &mut Config,
+    words: &[String],
+) -> Result<String, String> {
+    let word = |i: usize| words.get(i).map(String::as_str);
+    match (word(0), word(1), word(2)) {
+        (Some("context"), None, None) => Ok(format!(
+            "instance: {}\nproject:  {}\nprofile:  {}",
+            ctx.instance.as_deref().unwrap_or("-"),
+            ctx.project.as_deref().unwrap_or("-"),
+            ctx.profile.as_deref().unwrap_or("-"),
+        )),
+        (Some("use"), Some("instance"), Some(id)) => {
+            runctl::validation::validate_instance_id(id).map_err(|e| e.to_string())?;
+            ctx.instance = Some(id.to_string());
+            Ok(format!("Using instance {}", id))
+        }
+        (Some("use"), Some("project"), Some(name)) => {
+            runctl::validation::validate_project_name(name).map_err(|e| e.to_string())?;
+            ctx.project = Some(name.to_string());
+            if let Some(aws) = config.aws.as_mut() {
+                aws.default_project_name = Some(name.to_string());
+            }
+            Ok(format!("Using project {}", name))
+        }
+        (Some("use"), Some("profile"), Some(name)) => {
+            std::env::set_var("AWS_PROFILE", name);
+            ctx.profile = Some(name.to_string());
+            Ok(format!("Using AWS profile {}", name))
+        }
+        (Some("unset"), Some(kind), None) => {
+            match kind {
+                "instance" => ctx.instance = None,
+                "project" => ctx.project = None,
+                "profile" => {
+                    std::env::remove_var("AWS_PROFILE");
+                    ctx.profile = None;
+                }
+                _ => return Err(format!("unknown context '{}'", kind)),
+            }
+            Ok(format!("Cleared {}", kind))
+        }
+        _ => Err(format!(
+            "usage: use <{}> <value> | unset <kind> | context",
+            CONTEXT_KINDS.join("|")
+        )),
+    }
+}
+
+/// Run the interactive shell until `exit` or end of input
+pub async fn run(
+    mut config: Config,
+    config_path: Option<PathBuf>,
+    builtins: Vec<String>,
+) -> anyhow::Result<()> {
+    let mut top_level: Vec<String> = builtins.clone();
+    top_level.extend(SHELL_COMMANDS.iter().map(|s| s.to_string()));
+    top_level.extend(config.aliases.keys().cloned());
+
+    let mut editor: Editor<ShellHelper, DefaultHistory> = Editor::new()?;
+    editor.set_helper(Some(ShellHelper {
+        top_level,
+        resource_ids: cached_resource_ids(&config).await,
+    }));
+    let history = history_path();
+    if let Some(path) = &history {
+        // A missing history file just means a first session
+        let _ = editor.load_history(path);
+    }
+
+    let mut ctx = ShellContext::default();
+    println!("runctl shell. Type `help` for shell commands, `exit` to leave.");
+
+    loop {
+        let prompt = ctx.prompt();
+        let line = match tokio::task::block_in_place(|| editor.readline(&prompt)) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+
+        let words = match shell_words::split(line) {
+            Ok(words) => words,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                continue;
+            }
+        };
+        match words[0].as_str() {
+            "exit" | "quit" => break,
+            "help" if words.len() == 1 => {
+                println!("{}\n", SHELL_HELP);
+                let _ = Cli::command().print_help();
+                continue;
+            }
+            "use" | "unset" | "context" => {
+                match handle_context_command(&mut ctx, &mut config, &words) {
+                    Ok(message) => println!("{}", message),
+                    Err(message) => eprintln!("Error: {}", message),
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        let words = match ctx.substitute(words) {
+            Ok(words) => words,
+            Err(message) => {
+                eprintln!("Error: {}", message);
+                continue;
+            }
+        };
+        // Global flags go before the command word so they never land after `--`
+        let mut args = vec!["runctl".to_string()];
+        if let Some(path) = &config_path {
+            args.push("--config".to_string());
+            args.push(path.display().to_string());
+        }
+        args.extend(words);
+        let args = match runctl::aliases::expand(args, &config.aliases, &builtins) {
+            Ok(args) => args,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                continue;
+            }
+        };
+        let cli = match Cli::try_parse_from(args) {
+            Ok(cli) => cli,
+            Err(e) => {
+                // Also covers --help and --version output
+                let _ = e.print();
+                continue;
+            }
+        };
+        if matches!(cli.command, crate::Commands::Shell) {
+            eprintln!("Error: already in a runctl shell");
+            continue;
+        }
+
+        let output_format = cli.output.clone();
+        #[cfg(feature = "aws")]
+        runctl::resources::prefetch::invalidate().await;
+        if let Err(e) = execute(cli, &config).await {
+            report_error(&e, &output_format);
+        }
+        if let Some(helper) = editor.helper_mut() {
+            helper.resource_ids = cached_resource_ids(&config).await;
+        }
+    }
+
+    if let Some(path) = &history {
+        if let Some(dir) = path.parent() {
+            runctl::utils::ensure_dir(dir)?;
+        }
+        editor.save_history(path)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,159 @@
+//! Reaching instances without a public IP
+//!
+//! `[ssh] bastion` routes every SSH connection (sync, train, monitor,
+//! ssh-check) through a jump host, so instances in private subnets can be
+//! used:
+//!
+//! ```toml
+//! [ssh]
+//! bastion = "ec2-user@bastion.example.com"   # ProxyJump, to the private IP
+//! # or
+//! bastion = "ssm"                            # SSM Session Manager, by instance ID
+//! ```
+//!
+//! A jump host is reached with your own SSH setup (agent, `~/.ssh/config`).
+//! `ssm` needs the AWS CLI with the Session Manager plugin locally and an
+//! instance profile with SSM access on the instance. The native ssh2 sync
+//! can't use a proxy, so proxied syncs use the tar-over-ssh path.
+
+use crate::config::Config;
+use crate::error::{ConfigError, Result, TrainctlError};
+
+/// `[ssh] bastion` value that selects SSM Session Manager
+pub const SSM_BASTION: &str = "ssm";
+
+/// How SSH connections reach an instance
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SshProxy {
+    /// Straight to the public IP
+    Direct,
+    /// Through a jump host (`ProxyJump`), to the private IP
+    Jump(String),
+    /// Through an SSM session (`ProxyCommand`), addressed by instance ID
+    Ssm { region: Option<String> },
+}
+
+impl SshProxy {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let Some(bastion) = config.ssh.bastion.as_deref() else {
+            return Ok(Self::Direct);
+        };
+        if bastion == SSM_BASTION {
+            return Ok(Self::Ssm {
+                region: config.aws.as_ref().map(|aws| aws.region.clone()),
+            });
+        }
+        let valid = !bastion.is_empty()
+            && !bastion.starts_with('-')
+            && bastion
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "._-@:,[]".contains(c));
+        if !valid {
+            return Err(TrainctlError::Config(ConfigError::InvalidValue {
+                field: "ssh.bastion".to_string(),
+                reason: format!(
+                    "'{}' is not \"ssm\" or a [user@]host[:port] jump host",
+                    bastion
+                ),
+            }));
+        }
+        Ok(Self::Jump(bastion.to_string()))
+    }
+
+    pub fn is_direct(&self) -> bool {
+        *self == Self::Direct
+    }
+
+    /// `-o` options for the `ssh` and `scp` CLIs
+    pub fn ssh_args(&self) -> Vec<String> {
+        let option = match self {
+            Self::Direct => return Vec::new(),
+            Self::Jump(bastion) => format!("ProxyJump={}", bastion),
+            Self::Ssm { region } => {
+                let mut command = "ProxyCommand=aws ssm start-session --target %h \
+                     --document-name AWS-StartSSHSession --parameters portNumber=%p"
+                    .to_string();
+                if let Some(region) = region {
+                    command.push_str(&format!(" --region {}", region));
+                }
+                command
+            }
+        };
+        vec!["-o".to_string(), option]
+    }
+
+    /// Host to pass to ssh for an instance
+    pub fn target(
+        &self,
+        instance_id: &str,
+        public_ip: Option<&str>,
+        private_ip: Option<&str>,
+    ) -> Result<String> {
+        let address = match self {
+            Self::Direct => public_ip,
+            Self::Jump(_) => private_ip.or(public_ip),
+            Self::Ssm { .. } => Some(instance_id),
+        };
+        address.map(str::to_string).ok_or_else(|| {
+            TrainctlError::Aws(format!(
+                "Instance {} has no public IPv4 or IPv6 address.\n\n\
+                To resolve:\n\
+                  1. Reach it through a bastion: set [ssh] bastion = \"user@bastion-host\" in .runctl.toml\n\
+                  2. Or through SSM: set [ssh] bastion = \"ssm\" (needs an instance profile and the Session Manager plugin)\n\
+                  3. Or use SSM sync: create the instance with --iam-instance-profile and set [aws] s3_bucket\n\
+                  4. Or launch it in a public subnet with an internet gateway",
+                instance_id
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proxy(bastion: Option<&str>) -> Result<SshProxy> {
+        let mut config = Config::default();
+        config.ssh.bastion = bastion.map(str::to_string);
+        SshProxy::from_config(&config)
+    }
+
+    #[test]
+    fn test_from_config() {
+        assert_eq!(proxy(None).unwrap(), SshProxy::Direct);
+        assert_eq!(
+            proxy(Some("ec2-user@bastion.example.com:2222")).unwrap(),
+            SshProxy::Jump("ec2-user@bastion.example.com:2222".to_string())
+        );
+        assert!(matches!(proxy(Some("ssm")).unwrap(), SshProxy::Ssm { .. }));
+        assert!(proxy(Some("-oProxyCommand=evil")).is_err());
+        assert!(proxy(Some("host; rm -rf /")).is_err());
+    }
+
+    #[test]
+    fn test_target() {
+        let jump = SshProxy::Jump("bastion".to_string());
+        assert_eq!(
+            jump.target("i-0abc", Some("3.3.3.3"), Some("10.0.0.5"))
+                .unwrap(),
+            "10.0.0.5"
+        );
+        let ssm = SshProxy::Ssm { region: None };
+        assert_eq!(ssm.target("i-0abc", None, None).unwrap(), "i-0abc");
+        assert!(SshProxy::Direct
+            .target("i-0abc", None, Some("10.0.0.5"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_ssh_args() {
+        assert!(SshProxy::Direct.ssh_args().is_empty());
+        let ssm = SshProxy::Ssm {
+            region: Some("us-west-2".to_string()),
+        };
+        let args = ssm.ssh_args();
+        assert_eq!(args[0], "-o");
+        assert!(args[1].starts_with("ProxyCommand=aws ssm start-session --target %h"));
+        assert!(args[1].ends_with("--region us-west-2"));
+    }
+}
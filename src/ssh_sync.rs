@@ -2,73 +2,62 @@
 //!
 //! Replaces shell-based tar/rsync/ssh commands with native Rust implementations
 //! using ssh2-rs for SSH connections and tar crate for archive operations.
+//! Which files are shipped is decided by `sync_filter`.
 
 use crate::error::{Result, TrainctlError};
+use crate::host_keys::HostKeys;
+use crate::progress::ProgressEvent;
+use crate::ssh_proxy::SshProxy;
+use crate::sync_filter::SyncFilter;
 use flate2::write::GzEncoder;
 use flate2::Compression;
-use ignore::gitignore::{Gitignore, GitignoreBuilder};
-use ignore::WalkBuilder;
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::ProgressBar;
 use ssh2::Session;
 use std::fs::File;
 use std::io::{Read, Write};
 use std::net::TcpStream;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use tar::Builder;
 use tracing::info;
 
 /// Sync code to instance using shell-based tar+ssh (fallback method)
 ///
 /// This is more reliable for large codebases and when native sync hangs.
+#[allow(clippy::too_many_arguments)]
 pub async fn sync_code_shell(
     key_path: &str,
     ip: &str,
     user: &str,
+    host_keys: &HostKeys,
+    proxy: &SshProxy,
     project_dir: &str,
     project_root: &Path,
     output_format: &str,
-    include_patterns: &[String],
+    filter: &SyncFilter,
 ) -> Result<()> {
-    use std::process::Command;
     use std::time::Duration;
 
     if output_format != "json" {
         println!("   Using shell-based sync (tar+ssh)...");
     }
 
-    // Build exclude patterns for tar (aggressive exclusions for large repos)
-    let mut exclude_args = Vec::new();
-    exclude_args.push("--exclude=.git".to_string());
-    exclude_args.push("--exclude=__pycache__".to_string());
-    exclude_args.push("--exclude=*.pyc".to_string());
-    exclude_args.push("--exclude=.venv".to_string());
-    exclude_args.push("--exclude=node_modules".to_string());
-    exclude_args.push("--exclude=data/embeddings".to_string()); // Exclude entire embeddings dir
-    exclude_args.push("--exclude=data/embeddings/**".to_string());
-    exclude_args.push("--exclude=*.wv".to_string()); // All Word2Vec files
-    exclude_args.push("--exclude=*.json".to_string()); // Large JSON files (except small configs)
-    exclude_args.push("--exclude=*.log".to_string());
-    exclude_args.push("--exclude=target".to_string());
-    exclude_args.push("--exclude=.DS_Store".to_string());
-    exclude_args.push("--exclude=old-scraper-data".to_string());
-    exclude_args.push("--exclude=deploy".to_string());
-    exclude_args.push("--exclude=*.zst".to_string()); // Compressed data files
-    exclude_args.push("--exclude=*.parquet".to_string());
-    exclude_args.push("--exclude=*.csv".to_string()); // Large CSV files
-    // But include small config JSONs and essential files
-    exclude_args.push("--include=pyproject.toml".to_string());
-    exclude_args.push("--include=requirements.txt".to_string());
-    exclude_args.push("--include=justfile".to_string());
-    exclude_args.push("--include=.runctl.toml".to_string());
-
-    // Build tar command arguments
-    let mut tar_args = vec!["-czf".to_string(), "-".to_string()];
-    tar_args.extend(exclude_args.iter().cloned());
-    for pattern in include_patterns {
-        tar_args.push("--include".to_string());
-        tar_args.push(pattern.clone());
+    // Hand tar the same file list the native sync would ship
+    let selection = filter.select(project_root)?;
+    let list_path = std::env::temp_dir().join(format!("runctl-sync-{}.txt", uuid::Uuid::new_v4()));
+    let mut list = String::new();
+    for file in &selection.files {
+        if let Ok(relative) = file.strip_prefix(project_root) {
+            list.push_str(&relative.to_string_lossy());
+            list.push('\n');
+        }
     }
-    tar_args.push(".".to_string());
+    std::fs::write(&list_path, list)?;
+    let tar_args = [
+        "-czf".to_string(),
+        "-".to_string(),
+        "-T".to_string(),
+        list_path.to_string_lossy().to_string(),
+    ];
 
     // Create SSH command to extract on remote
     let ssh_cmd = format!(
@@ -81,18 +70,19 @@ pub async fn sync_code_shell(
     let key_path_clone = key_path.to_string();
     let ip_clone = ip.to_string();
     let user_clone = user.to_string();
-    let output_format_clone = output_format.to_string();
 
     // Build the full command: cd project_root && tar ... | ssh ...
-    let tar_cmd_str = format!(
-        "cd {} && tar {}",
-        project_root_str,
-        tar_args.join(" ")
-    );
+    let tar_cmd_str = format!("cd {} && tar {}", project_root_str, tar_args.join(" "));
 
+    let host_key_args: Vec<String> = host_keys
+        .ssh_args()
+        .iter()
+        .chain(proxy.ssh_args().iter())
+        .map(|arg| crate::utils::shell_quote(arg))
+        .collect();
     let full_cmd = format!(
-        "{} | ssh -o StrictHostKeyChecking=no -o ConnectTimeout=10 -o ServerAliveInterval=60 -o ServerAliveCountMax=3 -o TCPKeepAlive=yes -i {} {}@{} '{}'",
-        tar_cmd_str, key_path_clone, user_clone, ip_clone, ssh_cmd
+        "{} | ssh {} -o ConnectTimeout=10 -o ServerAliveInterval=60 -o ServerAliveCountMax=3 -o TCPKeepAlive=yes -i {} {}@{} '{}'",
+        tar_cmd_str, host_key_args.join(" "), key_path_clone, user_clone, ip_clone, ssh_cmd
     );
 
     if output_format != "json" {
@@ -125,12 +115,16 @@ pub async fn sync_code_shell(
         }),
     )
     .await;
+    let _ = std::fs::remove_file(&list_path);
 
     match sync_result {
         Ok(Ok(Ok(()))) => {
             if output_format != "json" {
                 println!("   Code synced successfully (shell-based)");
             }
+            ProgressEvent::new("sync.done", "Code synced (shell-based)")
+                .done()
+                .emit();
             Ok(())
         }
         Ok(Ok(Err(e))) => Err(e),
@@ -146,50 +140,54 @@ pub async fn sync_code_shell(
 /// Sync code to instance using native Rust SSH and tar
 ///
 /// # Arguments
-/// * `include_patterns` - Patterns to include even if gitignored (e.g., `data/`, `datasets/`)
-///   These are added as negations to override `.gitignore` rules
+/// * `filter` - Which files to ship (see `sync_filter`)
+#[allow(clippy::too_many_arguments)]
 pub async fn sync_code_native(
     key_path: &str,
     ip: &str,
     user: &str,
+    host_keys: &HostKeys,
+    proxy: &SshProxy,
     project_dir: &str,
     project_root: &Path,
     output_format: &str,
-    include_patterns: &[String],
+    filter: &SyncFilter,
 ) -> Result<()> {
-    // Check if shell-based sync is requested
-    if std::env::var("TRAINCTL_USE_SHELL_SYNC").is_ok() {
+    // Shell-based sync if requested, or needed to go through a proxy
+    if std::env::var("TRAINCTL_USE_SHELL_SYNC").is_ok() || !proxy.is_direct() {
         return sync_code_shell(
             key_path,
             ip,
             user,
+            host_keys,
+            proxy,
             project_dir,
             project_root,
             output_format,
-            include_patterns,
+            filter,
         )
         .await;
     }
     let pb = if output_format != "json" {
         let pb = ProgressBar::new_spinner();
-        pb.set_style(
-            ProgressStyle::default_spinner()
-                .template("{spinner:.green} [{elapsed_precise}] {msg}")
-                .expect("Progress bar template should be valid"),
-        );
+        pb.set_style(crate::output_style::spinner(
+            "{spinner:.green} [{elapsed_precise}] {msg}",
+        ));
         pb.set_message("Connecting to instance...");
         Some(pb)
     } else {
         None
     };
+    crate::progress::emit("sync.connect", format!("Connecting to {}", ip));
 
     // Run SSH operations in blocking task (ssh2 is synchronous)
     let key_path_clone = key_path.to_string();
     let ip_clone = ip.to_string();
     let user_clone = user.to_string();
+    let host_keys_clone = host_keys.clone();
     let project_dir_clone = project_dir.to_string();
     let project_root_clone = project_root.to_path_buf();
-    let include_patterns_clone = include_patterns.to_vec();
+    let filter_clone = filter.clone();
     let pb_clone = pb.clone();
 
     // Add timeout to prevent hanging
@@ -197,76 +195,89 @@ pub async fn sync_code_native(
         std::time::Duration::from_secs(300), // 5 minute timeout
         tokio::task::spawn_blocking(move || {
             // Connect via SSH
-            let tcp = TcpStream::connect(format!("{}:22", ip_clone))
-                .map_err(|e| TrainctlError::Ssm(format!("Failed to connect to {}:22: {}", ip_clone, e)))?;
-
-        let mut sess = Session::new()
-            .map_err(|e| TrainctlError::Ssm(format!("Failed to create SSH session: {}", e)))?;
-
-        sess.set_tcp_stream(tcp);
-        sess.handshake()
-            .map_err(|e| TrainctlError::Ssm(format!("SSH handshake failed: {}", e)))?;
-
-        // Authenticate with private key
-        sess.userauth_pubkey_file(&user_clone, None, Path::new(&key_path_clone), None)
-            .map_err(|e| {
+            let tcp = TcpStream::connect((ip_clone.as_str(), 22)).map_err(|e| {
                 TrainctlError::Ssm(format!(
-                    "SSH authentication failed: {}. Check key permissions (chmod 600 {})",
-                    e, key_path_clone
+                    "Failed to connect to {}:22: {}",
+                    crate::utils::bracket_ipv6(&ip_clone),
+                    e
                 ))
             })?;
 
-        if !sess.authenticated() {
-            return Err(TrainctlError::Ssm(format!(
-                "SSH authentication failed. Check key permissions: chmod 600 {}",
-                key_path_clone
-            )));
-        }
+            let mut sess = Session::new()
+                .map_err(|e| TrainctlError::Ssm(format!("Failed to create SSH session: {}", e)))?;
 
-        if let Some(ref p) = pb_clone {
-            p.set_message("Checking if code exists on instance...");
-        }
+            sess.set_tcp_stream(tcp);
+            sess.handshake()
+                .map_err(|e| TrainctlError::Ssm(format!("SSH handshake failed: {}", e)))?;
+            host_keys_clone.verify_session(&sess)?;
+
+            // Authenticate with private key
+            sess.userauth_pubkey_file(&user_clone, None, Path::new(&key_path_clone), None)
+                .map_err(|e| {
+                    TrainctlError::Ssm(format!(
+                        "SSH authentication failed: {}. Check key permissions (chmod 600 {})",
+                        e, key_path_clone
+                    ))
+                })?;
+
+            if !sess.authenticated() {
+                return Err(TrainctlError::Ssm(format!(
+                    "SSH authentication failed. Check key permissions: chmod 600 {}",
+                    key_path_clone
+                )));
+            }
+
+            if let Some(ref p) = pb_clone {
+                p.set_message("Checking if code exists on instance...");
+            }
 
-        // Check if code exists (for incremental sync)
-        let check_cmd = format!("test -d {} && echo EXISTS || echo NOT_FOUND", project_dir_clone);
-        let use_incremental = check_remote_directory(&sess, &check_cmd)?;
+            // Check if code exists (for incremental sync)
+            let check_cmd = format!(
+                "test -d {} && echo EXISTS || echo NOT_FOUND",
+                project_dir_clone
+            );
+            let use_incremental = check_remote_directory(&sess, &check_cmd)?;
+
+            if use_incremental {
+                if let Some(ref p) = pb_clone {
+                    p.set_message("Code exists, using incremental sync...");
+                }
+
+                // Incremental sync: compare files and sync only changes
+                sync_incremental_blocking(
+                    &sess,
+                    &project_root_clone,
+                    &project_dir_clone,
+                    &pb_clone,
+                    &filter_clone,
+                )?;
+
+                if let Some(ref p) = pb_clone {
+                    p.finish_with_message("Code synced (incremental)");
+                }
+                ProgressEvent::new("sync.done", "Code synced (incremental)")
+                    .done()
+                    .emit();
+                return Ok(());
+            }
 
-        if use_incremental {
+            // Full sync: create tar archive and transfer
             if let Some(ref p) = pb_clone {
-                p.set_message("Code exists, using incremental sync...");
+                p.set_message("Performing full sync (tar archive)...");
             }
 
-            // Incremental sync: compare files and sync only changes
-            sync_incremental_blocking(
+            sync_full_tar_blocking(
                 &sess,
                 &project_root_clone,
                 &project_dir_clone,
                 &pb_clone,
-                &include_patterns_clone,
+                &filter_clone,
             )?;
 
             if let Some(ref p) = pb_clone {
-                p.finish_with_message("Code synced (incremental)");
+                p.finish_with_message("Code synced successfully");
             }
-            return Ok(());
-        }
-
-        // Full sync: create tar archive and transfer
-        if let Some(ref p) = pb_clone {
-            p.set_message("Performing full sync (tar archive)...");
-        }
-
-        sync_full_tar_blocking(
-            &sess,
-            &project_root_clone,
-            &project_dir_clone,
-            &pb_clone,
-            &include_patterns_clone,
-        )?;
-
-        if let Some(ref p) = pb_clone {
-            p.finish_with_message("Code synced successfully");
-        }
+            ProgressEvent::new("sync.done", "Code synced").done().emit();
 
             Ok(())
         }),
@@ -284,10 +295,12 @@ pub async fn sync_code_native(
                 key_path,
                 ip,
                 user,
+                host_keys,
+                proxy,
                 project_dir,
                 project_root,
                 output_format,
-                include_patterns,
+                filter,
             )
             .await
         }
@@ -300,10 +313,12 @@ pub async fn sync_code_native(
                 key_path,
                 ip,
                 user,
+                host_keys,
+                proxy,
                 project_dir,
                 project_root,
                 output_format,
-                include_patterns,
+                filter,
             )
             .await
         }
@@ -332,120 +347,15 @@ fn check_remote_directory(sess: &Session, command: &str) -> Result<bool> {
     Ok(output.contains("EXISTS"))
 }
 
-/// Build a gitignore matcher with overrides for include_patterns
-fn build_gitignore_matcher(project_root: &Path, include_patterns: &[String]) -> Result<Gitignore> {
-    let mut builder = GitignoreBuilder::new(project_root);
-
-    // Add negations for patterns to include (even if gitignored)
-    // In gitignore, ! prefix negates a pattern
-    for pattern in include_patterns {
-        // Normalize pattern: ensure it ends with / if it's a directory pattern
-        let normalized_pattern = if pattern.ends_with('/') {
-            format!("!{}**", pattern)
-        } else {
-            format!("!{}", pattern)
-        };
-
-        builder.add_line(None, &normalized_pattern).map_err(|e| {
-            TrainctlError::Io(std::io::Error::other(format!(
-                "Failed to add include pattern '{}': {}",
-                pattern, e
-            )))
-        })?;
-    }
-
-    builder.build().map_err(|e| {
-        TrainctlError::Io(std::io::Error::other(format!(
-            "Failed to build gitignore matcher: {}",
-            e
-        )))
-    })
-}
-
-/// Check if a path matches any include pattern using proper path matching
-fn matches_include_pattern(path: &Path, pattern: &str, project_root: &Path) -> bool {
-    let rel_path = match path.strip_prefix(project_root) {
-        Ok(p) => p,
-        Err(_) => return false,
-    };
-
-    // Normalize pattern: remove leading/trailing slashes for comparison
-    let pattern = pattern.trim_matches('/');
-    if pattern.is_empty() {
-        return false;
-    }
-
-    // Check if pattern matches as a directory prefix
-    // "data/" should match "data/train.csv" but not "my_data_file.txt"
-    let pattern_path = Path::new(pattern);
-
-    // Match if:
-    // 1. Relative path starts with pattern (directory prefix match)
-    // 2. Pattern is a parent directory of the file
-    rel_path.starts_with(pattern_path)
-        || rel_path
-            .parent()
-            .map(|p| p == pattern_path || p.starts_with(pattern_path))
-            .unwrap_or(false)
-}
-
-/// Get list of files to sync (unified logic for both incremental and full sync)
-fn get_files_to_sync(project_root: &Path, include_patterns: &[String]) -> Result<Vec<PathBuf>> {
-    // Build gitignore matcher with overrides
-    let gitignore = build_gitignore_matcher(project_root, include_patterns)?;
-
-    // Walk all files (we'll filter manually using the matcher)
-    let files: Vec<PathBuf> = WalkBuilder::new(project_root)
-        .git_ignore(false) // Don't use WalkBuilder's gitignore - we'll check manually
-        .git_global(false)
-        .git_exclude(false)
-        .build()
-        .filter_map(|entry| {
-            let entry = entry.ok()?;
-            let path = entry.path();
-
-            // Skip directories
-            if !path.is_file() {
-                return None;
-            }
-
-            let rel_path = match path.strip_prefix(project_root) {
-                Ok(p) => p,
-                Err(_) => return None,
-            };
-
-            // Check if this file matches any include pattern
-            let matches_include = include_patterns
-                .iter()
-                .any(|pattern| matches_include_pattern(path, pattern, project_root));
-
-            // Use gitignore matcher to check if file should be ignored
-            let matched = gitignore.matched(rel_path, false);
-
-            // Include if:
-            // 1. Matches include pattern (even if gitignored), OR
-            // 2. Not gitignored (normal case)
-            if matches_include || !matched.is_ignore() {
-                Some(path.to_path_buf())
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    Ok(files)
-}
-
 /// Incremental sync: compare and sync only changed files (blocking)
 fn sync_incremental_blocking(
     sess: &Session,
     project_root: &Path,
     remote_dir: &str,
     pb: &Option<ProgressBar>,
-    include_patterns: &[String],
+    filter: &SyncFilter,
 ) -> Result<()> {
-    // Get list of files to sync using unified logic
-    let files_to_sync = get_files_to_sync(project_root, include_patterns)?;
+    let files_to_sync = filter.select(project_root)?.files;
 
     if let Some(ref p) = pb {
         p.set_message(format!("Syncing {} files...", files_to_sync.len()));
@@ -537,6 +447,9 @@ fn sync_incremental_blocking(
                 files_to_sync.len()
             ));
         }
+        ProgressEvent::new("sync.upload", relative_path.display().to_string())
+            .steps(synced as u64, files_to_sync.len() as u64)
+            .emit();
     }
 
     info!("Incremental sync completed: {} files", synced);
@@ -549,14 +462,13 @@ fn sync_full_tar_blocking(
     project_root: &Path,
     remote_dir: &str,
     pb: &Option<ProgressBar>,
-    include_patterns: &[String],
+    filter: &SyncFilter,
 ) -> Result<()> {
     if let Some(ref p) = pb {
         p.set_message("Creating tar archive...");
     }
 
-    // Get list of files to sync using unified logic
-    let files_to_sync = get_files_to_sync(project_root, include_patterns)?;
+    let files_to_sync = filter.select(project_root)?.files;
 
     if let Some(ref p) = pb {
         p.set_message(format!("Archiving {} files...", files_to_sync.len()));
@@ -597,6 +509,10 @@ fn sync_full_tar_blocking(
     if let Some(ref p) = pb {
         p.set_message(format!("Transferring {} bytes...", archive_data.len()));
     }
+    let archive_size = archive_data.len() as u64;
+    ProgressEvent::new("sync.upload", "Transferring code archive")
+        .bytes(0, Some(archive_size))
+        .emit();
 
     // Create remote directory
     let mkdir_cmd = format!("mkdir -p {}", remote_dir);
@@ -627,6 +543,10 @@ fn sync_full_tar_blocking(
         .send_eof()
         .map_err(|e| TrainctlError::Ssm(format!("Failed to send EOF: {}", e)))?;
 
+    ProgressEvent::new("sync.extract", "Extracting on instance")
+        .bytes(archive_size, Some(archive_size))
+        .emit();
+
     // Wait for completion
     channel
         .wait_close()
@@ -0,0 +1,389 @@
+//! File selection for code sync
+//!
+//! Both SSH sync (`ssh_sync`) and SSM sync (`aws::ssm_sync`) ship the same set
+//! of files, chosen here from the `[sync]` config plus per-command overrides:
+//!
+//! - Files ignored by the project's root `.gitignore` are skipped, unless they
+//!   match an `include` pattern (e.g. a gitignored `data/` directory).
+//! - Files matching an `exclude` pattern are always skipped, even when also
+//!   included. The defaults keep virtualenvs, caches and experiment-tracker
+//!   output (`wandb/`, `mlruns/`) off the instance.
+//! - Files larger than `max_file_size` are skipped with a warning.
+//!
+//! Before syncing, the selection's file count and size are shown; above
+//! `confirm_above` the sync has to be confirmed (or `--yes` passed), so an
+//! accidentally included dataset is caught before it is uploaded.
+//!
+//! Exclude patterns use gitignore syntax; a pattern matching a directory
+//! excludes everything under it.
+//!
+//! ```toml
+//! [sync]
+//! include = ["data/"]
+//! exclude = [".venv", "wandb/", "*.ckpt"]
+//! follow_symlinks = false
+//! max_file_size = "100MB"
+//! confirm_above = "1GB"
+//! ```
+
+use crate::config::SyncConfig;
+use crate::error::{ConfigError, Result, TrainctlError};
+use crate::units::parse_size;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::WalkBuilder;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Largest files listed in the sync preview
+const PREVIEW_LARGEST: usize = 5;
+
+/// Files selected for a sync
+#[derive(Debug, Default)]
+pub struct SyncSelection {
+    pub files: Vec<PathBuf>,
+    /// Total size of `files` in bytes
+    pub total_bytes: u64,
+    /// Files skipped for exceeding `max_file_size`, with their sizes
+    pub oversized: Vec<(PathBuf, u64)>,
+    /// Largest selected files, biggest first
+    pub largest: Vec<(PathBuf, u64)>,
+}
+
+/// Include/exclude rules for code sync
+#[derive(Debug, Clone, Default)]
+pub struct SyncFilter {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub follow_symlinks: bool,
+    pub max_file_size: Option<u64>,
+    /// Syncs larger than this (bytes) need confirmation
+    pub confirm_above: Option<u64>,
+}
+
+impl SyncFilter {
+    /// Rules from the `[sync]` config section
+    pub fn from_config(config: &SyncConfig) -> Result<Self> {
+        let size_setting = |field: &str, value: Option<&str>| {
+            value.map(parse_size).transpose().map_err(|reason| {
+                TrainctlError::Config(ConfigError::InvalidValue {
+                    field: field.to_string(),
+                    reason,
+                })
+            })
+        };
+        Ok(Self {
+            include: config.include.clone(),
+            exclude: config.exclude.clone(),
+            follow_symlinks: config.follow_symlinks,
+            max_file_size: size_setting("sync.max_file_size", config.max_file_size.as_deref())?,
+            confirm_above: size_setting("sync.confirm_above", config.confirm_above.as_deref())?,
+        })
+    }
+
+    /// Apply per-command overrides
+    ///
+    /// Patterns are added to the configured ones; a size limit replaces it.
+    pub fn with_overrides(
+        mut self,
+        include: &[String],
+        exclude: &[String],
+        max_file_size: Option<u64>,
+    ) -> Self {
+        self.include.extend(include.iter().cloned());
+        self.exclude.extend(exclude.iter().cloned());
+        if max_file_size.is_some() {
+            self.max_file_size = max_file_size;
+        }
+        self
+    }
+
+    /// Whether `rel_path` falls under an include pattern
+    ///
+    /// `data/` matches `data/train.csv` but not `my_data_file.txt`.
+    pub fn is_included(&self, rel_path: &Path) -> bool {
+        self.include.iter().any(|pattern| {
+            let pattern = pattern.trim_matches('/');
+            !pattern.is_empty() && rel_path.starts_with(Path::new(pattern))
+        })
+    }
+
+    fn build_matcher(root: &Path, lines: &[String], what: &str) -> Result<Gitignore> {
+        let mut builder = GitignoreBuilder::new(root);
+        for line in lines {
+            builder.add_line(None, line).map_err(|e| {
+                TrainctlError::Io(std::io::Error::other(format!(
+                    "Invalid {} pattern '{}': {}",
+                    what, line, e
+                )))
+            })?;
+        }
+        builder.build().map_err(|e| {
+            TrainctlError::Io(std::io::Error::other(format!(
+                "Failed to build {} matcher: {}",
+                what, e
+            )))
+        })
+    }
+
+    /// Select the files under `project_root` to sync
+    pub fn select(&self, project_root: &Path) -> Result<SyncSelection> {
+        let gitignore_lines: Vec<String> = std::fs::read_to_string(project_root.join(".gitignore"))
+            .map(|content| content.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+        let gitignore = Self::build_matcher(project_root, &gitignore_lines, "gitignore")?;
+        let excludes = Self::build_matcher(project_root, &self.exclude, "exclude")?;
+
+        let mut selection = SyncSelection::default();
+        let mut sized = Vec::new();
+        let walker = WalkBuilder::new(project_root)
+            .git_ignore(false)
+            .git_global(false)
+            .git_exclude(false)
+            .follow_links(self.follow_symlinks)
+            .build();
+        for entry in walker {
+            let Ok(entry) = entry else { continue };
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            let Ok(rel_path) = path.strip_prefix(project_root) else {
+                continue;
+            };
+            if excludes
+                .matched_path_or_any_parents(rel_path, false)
+                .is_ignore()
+            {
+                continue;
+            }
+            if !self.is_included(rel_path)
+                && gitignore
+                    .matched_path_or_any_parents(rel_path, false)
+                    .is_ignore()
+            {
+                continue;
+            }
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            if self.max_file_size.is_some_and(|max| size > max) {
+                selection.oversized.push((path.to_path_buf(), size));
+                continue;
+            }
+            selection.total_bytes += size;
+            sized.push((path.to_path_buf(), size));
+        }
+        selection.files = sized.iter().map(|(path, _)| path.clone()).collect();
+        sized.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+        sized.truncate(PREVIEW_LARGEST);
+        selection.largest = sized;
+
+        for (path, size) in &selection.oversized {
+            warn!(
+                "Skipping {} ({} bytes, over sync.max_file_size)",
+                path.display(),
+                size
+            );
+        }
+        Ok(selection)
+    }
+
+    /// Show what a sync would ship and confirm it if over `confirm_above`
+    ///
+    /// Returns `false` if the user declined. Without a terminal, a sync over
+    /// the threshold fails unless `yes` is set.
+    pub fn confirm(
+        &self,
+        selection: &SyncSelection,
+        project_root: &Path,
+        yes: bool,
+        output_format: &str,
+    ) -> Result<bool> {
+        let summary = format!(
+            "{} files, {}",
+            selection.files.len(),
+            format_size(selection.total_bytes)
+        );
+        let over = self
+            .confirm_above
+            .is_some_and(|limit| selection.total_bytes > limit);
+        if output_format == "text" {
+            println!("   Sync preview: {}", summary);
+            if over || !selection.oversized.is_empty() {
+                for (path, size) in &selection.largest {
+                    let shown = path.strip_prefix(project_root).unwrap_or(path);
+                    println!("     {:>10}  {}", format_size(*size), shown.display());
+                }
+            }
+            if !selection.oversized.is_empty() {
+                println!(
+                    "   Skipping {} file(s) over sync.max_file_size",
+                    selection.oversized.len()
+                );
+            }
+        }
+        if !over || yes || crate::prompt::is_non_interactive() {
+            return Ok(true);
+        }
+
+        let limit = format_size(self.confirm_above.unwrap_or_default());
+        if output_format != "text" || !crate::prompt::can_prompt() {
+            return Err(TrainctlError::Validation {
+                field: "yes".to_string(),
+                reason: format!(
+                    "Sync of {} exceeds sync.confirm_above ({}); pass --yes to proceed, \
+                     or add large paths to [sync] exclude",
+                    summary, limit
+                ),
+            });
+        }
+        crate::prompt::confirm(&format!(
+            "WARNING: about to sync {} (over {}). Continue?",
+            summary, limit
+        ))
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit_idx = 0;
+
+    while size >= 1024.0 && unit_idx < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit_idx += 1;
+    }
+
+    format!("{:.2} {}", size, UNITS[unit_idx])
+}
+
+/// Write `files` (under `project_root`) to a tar.gz at `archive`
+///
+/// Files deleted since selection are skipped. Returns how many were added.
+pub(crate) fn write_code_archive(
+    project_root: &Path,
+    files: &[PathBuf],
+    archive: &Path,
+) -> Result<usize> {
+    let file = std::fs::File::create(archive).map_err(|e| {
+        TrainctlError::Io(std::io::Error::other(format!(
+            "Failed to create archive: {}",
+            e
+        )))
+    })?;
+    let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+    let mut tar = tar::Builder::new(encoder);
+
+    let mut files_added = 0;
+    for file_path in files {
+        // Skip if file doesn't exist (might have been deleted)
+        if !file_path.exists() {
+            warn!("Skipping non-existent file: {}", file_path.display());
+            continue;
+        }
+
+        let relative_path = file_path.strip_prefix(project_root).map_err(|e| {
+            TrainctlError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Failed to get relative path: {}", e),
+            ))
+        })?;
+
+        tar.append_path_with_name(file_path, relative_path)
+            .map_err(|e| {
+                TrainctlError::Io(std::io::Error::other(format!(
+                    "Failed to add file to archive: {}",
+                    e
+                )))
+            })?;
+        files_added += 1;
+    }
+
+    tar.finish().map_err(|e| {
+        TrainctlError::Io(std::io::Error::other(format!(
+            "Failed to finalize archive: {}",
+            e
+        )))
+    })?;
+    Ok(files_added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write(root: &Path, rel: &str, bytes: usize) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![b'x'; bytes]).unwrap();
+    }
+
+    fn selected(filter: &SyncFilter, root: &Path) -> Vec<String> {
+        let mut files: Vec<String> = filter
+            .select(root)
+            .unwrap()
+            .files
+            .iter()
+            .map(|p| p.strip_prefix(root).unwrap().display().to_string())
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_select_applies_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join(".gitignore"), "data/\n*.tmp\n").unwrap();
+        write(root, "train.py", 10);
+        write(root, "data/train.csv", 10);
+        write(root, "data/cache.ckpt", 10);
+        write(root, "scratch.tmp", 10);
+        write(root, "wandb/run-1/files/output.log", 10);
+        write(root, "models/big.bin", 5000);
+
+        let filter = SyncFilter::from_config(&SyncConfig::default()).unwrap();
+        assert_eq!(selected(&filter, root), vec!["models/big.bin", "train.py"]);
+
+        let filter =
+            filter.with_overrides(&["data/".to_string()], &["*.ckpt".to_string()], Some(1000));
+        let selection = filter.select(root).unwrap();
+        assert_eq!(selection.oversized.len(), 1);
+        assert_eq!(selection.total_bytes, 20);
+        assert_eq!(selection.largest.len(), 2);
+        assert_eq!(selected(&filter, root), vec!["data/train.csv", "train.py"]);
+    }
+
+    #[test]
+    fn test_confirm_threshold() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "weights.bin", 2048);
+        let filter = SyncFilter {
+            confirm_above: Some(1024),
+            ..SyncFilter::default()
+        };
+        let selection = filter.select(dir.path()).unwrap();
+        assert!(filter
+            .confirm(&selection, dir.path(), true, "json")
+            .unwrap());
+        assert!(filter
+            .confirm(&selection, dir.path(), false, "json")
+            .is_err());
+
+        let small = SyncFilter {
+            confirm_above: Some(4096),
+            ..SyncFilter::default()
+        };
+        assert!(small
+            .confirm(&selection, dir.path(), false, "json")
+            .unwrap());
+    }
+
+    #[test]
+    fn test_invalid_max_file_size() {
+        let config = SyncConfig {
+            max_file_size: Some("huge".to_string()),
+            ..SyncConfig::default()
+        };
+        assert!(SyncFilter::from_config(&config).is_err());
+    }
+}
@@ -24,14 +24,84 @@ pub struct TrainingSession {
     pub checkpoint_dir: PathBuf,
     pub log_file: Option<PathBuf>,
     pub status: TrainingStatus,
+    /// PID of the training process (local sessions only)
+    #[serde(default)]
+    pub pid: Option<u32>,
+    /// PID of the runctl process that launched the training process
+    #[serde(default)]
+    pub runctl_pid: Option<u32>,
+    /// Launch progress of a remote session (`runctl run`)
+    #[serde(default)]
+    pub launch: Option<LaunchState>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TrainingStatus {
+    /// Provisioning a resource; training has not started yet
+    Launching,
     Running,
     Completed,
     Failed(String),
     Interrupted,
+    /// Launch abandoned and its resource terminated by `runctl jobs recover`
+    RolledBack,
+}
+
+/// How far a remote launch got
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LaunchStage {
+    /// Resource requested; its ID is not known yet
+    Provisioning,
+    /// Resource exists; training not started
+    ResourceCreated,
+    /// Training script started on the resource
+    TrainingStarted,
+}
+
+/// What a remote session needs to be continued or rolled back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchState {
+    pub stage: LaunchStage,
+    /// Resource created for the session, once known
+    #[serde(default)]
+    pub resource_id: Option<String>,
+    pub instance_type: String,
+    #[serde(default)]
+    pub spot: bool,
+    #[serde(default)]
+    pub script_args: Vec<String>,
+    /// What `runctl run` does with the resource when the run ends
+    #[serde(default)]
+    pub cleanup: Cleanup,
+    /// Started with `--detach`: nobody is waiting on it by design
+    #[serde(default)]
+    pub detached: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// What happens to a run's resource once the run ends
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cleanup {
+    /// Terminate it whatever the outcome
+    #[default]
+    Always,
+    /// Terminate it after training succeeds; keep it after a failure or
+    /// timeout so it can be inspected (`--terminate-on-complete`)
+    OnSuccess,
+    /// Leave it running (`--keep`)
+    Never,
+}
+
+impl Cleanup {
+    /// Whether to terminate given whether training succeeded
+    pub fn terminates(self, succeeded: bool) -> bool {
+        match self {
+            Cleanup::Always => true,
+            Cleanup::OnSuccess => succeeded,
+            Cleanup::Never => false,
+        }
+    }
 }
 
 impl TrainingSession {
@@ -44,14 +114,90 @@ impl TrainingSession {
             checkpoint_dir,
             log_file: None,
             status: TrainingStatus::Running,
+            pid: None,
+            runctl_pid: None,
+            launch: None,
         }
     }
 
+    /// A remote session about to provision its resource
+    ///
+    /// Owned by the current process until training starts, so a session left
+    /// in `Launching` by a process that is gone was interrupted mid-launch.
+    pub fn launching(
+        platform: String,
+        script: PathBuf,
+        checkpoint_dir: PathBuf,
+        instance_type: String,
+        spot: bool,
+        script_args: Vec<String>,
+    ) -> Self {
+        let mut session = Self::new(platform, script, checkpoint_dir);
+        session.status = TrainingStatus::Launching;
+        session.runctl_pid = Some(std::process::id());
+        session.launch = Some(LaunchState {
+            stage: LaunchStage::Provisioning,
+            resource_id: None,
+            instance_type,
+            spot,
+            script_args,
+            cleanup: Cleanup::default(),
+            detached: false,
+            updated_at: Utc::now(),
+        });
+        session
+    }
+
+    /// Record launch progress; reaching `TrainingStarted` marks the session running
+    pub fn advance(&mut self, stage: LaunchStage, resource_id: Option<String>) {
+        if let Some(launch) = &mut self.launch {
+            launch.stage = stage;
+            if resource_id.is_some() {
+                launch.resource_id = resource_id;
+            }
+            launch.updated_at = Utc::now();
+        }
+        if stage == LaunchStage::TrainingStarted {
+            self.status = TrainingStatus::Running;
+        }
+    }
+
+    /// Resource created for this session, if any
+    pub fn resource_id(&self) -> Option<&str> {
+        self.launch.as_ref()?.resource_id.as_deref()
+    }
+
+    /// Whether this session was left mid-launch by a runctl process that is gone
+    pub fn is_abandoned_launch(&self) -> bool {
+        matches!(self.status, TrainingStatus::Launching)
+            && !self.runctl_pid.is_some_and(process_alive)
+    }
+
+    /// Whether this is a `runctl run` whose waiting runctl process is gone
+    ///
+    /// Covers launches cut off mid-way and runs whose process died while
+    /// training was under way; `--detach` runs are left alone.
+    pub fn is_interrupted_run(&self) -> bool {
+        self.launch.as_ref().is_some_and(|launch| !launch.detached)
+            && matches!(
+                self.status,
+                TrainingStatus::Launching | TrainingStatus::Running
+            )
+            && !self.runctl_pid.is_some_and(process_alive)
+    }
+
+    /// Sessions left mid-launch by crashed or killed runctl processes
+    pub fn abandoned_launches(sessions_dir: &Path) -> Result<Vec<Self>> {
+        Ok(Self::list_sessions(sessions_dir)?
+            .into_iter()
+            .filter(Self::is_abandoned_launch)
+            .collect())
+    }
+
     pub fn save(&self, sessions_dir: &Path) -> Result<()> {
-        let sessions_dir = sessions_dir.join("sessions");
-        fs::create_dir_all(&sessions_dir)?;
+        fs::create_dir_all(sessions_dir.join("sessions"))?;
 
-        let session_file = sessions_dir.join(format!("{}.json", self.id));
+        let session_file = self.manifest_path(sessions_dir);
         let content = serde_json::to_string_pretty(self).map_err(|e| {
             TrainctlError::Io(std::io::Error::other(format!(
                 "Failed to serialize session: {}",
@@ -68,7 +214,6 @@ impl TrainingSession {
         Ok(())
     }
 
-    #[allow(dead_code)] // Reserved for future session loading
     pub fn load(sessions_dir: &Path, session_id: &str) -> Result<Self> {
         let session_file = sessions_dir
             .join("sessions")
@@ -90,7 +235,13 @@ impl TrainingSession {
         Ok(session)
     }
 
-    #[allow(dead_code)] // Reserved for future session listing
+    /// Path of this session's manifest file under `sessions_dir`
+    pub fn manifest_path(&self, sessions_dir: &Path) -> PathBuf {
+        sessions_dir
+            .join("sessions")
+            .join(format!("{}.json", self.id))
+    }
+
     pub fn list_sessions(sessions_dir: &Path) -> Result<Vec<Self>> {
         let sessions_path = sessions_dir.join("sessions");
         if !sessions_path.exists() {
@@ -110,11 +261,17 @@ impl TrainingSession {
             }
         }
 
-        sessions.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.started_at));
         Ok(sessions)
     }
 }
 
+fn process_alive(pid: u32) -> bool {
+    let mut system = sysinfo::System::new();
+    let pid = sysinfo::Pid::from_u32(pid);
+    system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[pid])) > 0
+}
+
 /// Checkpoint metadata helper
 pub fn extract_checkpoint_info(checkpoint_path: &Path) -> Result<CheckpointInfo> {
     // For PyTorch checkpoints, we'd need torch-sys or similar
@@ -0,0 +1,365 @@
+//! Structured training progress from log lines
+//!
+//! `monitor` and `aws monitor` feed every log line they show through a
+//! [`ProgressTracker`], which turns common log formats into one progress
+//! view: epoch and step, loss and its trend, throughput, percent done and an
+//! ETA. Understood formats:
+//!
+//! - plain `key=value` / `key: value` logs (`Epoch 3/10 step 120/500 loss=0.42`),
+//!   parsed by [`crate::watch::parse_metrics`]
+//! - tqdm bars (`45%|████▌     | 450/1000 [01:23<01:40,  5.40it/s]`)
+//! - PyTorch Lightning bars (`Epoch 3:  45%|...| 450/1000 [..., train_loss=0.420]`)
+//! - throughput such as `5.4 it/s`, `1.2 s/it` or `830 samples/s`
+//!
+//! Lines written with carriage returns (tqdm redrawing in place) count by
+//! their last segment.
+
+use crate::watch::parse_metrics;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+
+/// Losses kept for the trend
+const TREND_WINDOW: usize = 20;
+
+/// Relative change in mean loss below which the trend is flat
+const FLAT_THRESHOLD: f64 = 0.01;
+
+/// Width of the text progress bar, in characters
+const BAR_WIDTH: usize = 24;
+
+/// Direction the loss is moving in over the last readings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LossTrend {
+    Falling,
+    Flat,
+    Rising,
+}
+
+impl std::fmt::Display for LossTrend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Falling => "falling",
+            Self::Flat => "flat",
+            Self::Rising => "rising",
+        })
+    }
+}
+
+/// Latest progress parsed from a training log
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TrainingProgress {
+    pub epoch: Option<u64>,
+    pub total_epochs: Option<u64>,
+    pub step: Option<u64>,
+    pub total_steps: Option<u64>,
+    pub loss: Option<f64>,
+    pub loss_trend: Option<LossTrend>,
+    /// Iterations (or samples, tokens) per second
+    pub rate: Option<f64>,
+    /// What `rate` counts, such as `it/s` or `samples/s`
+    pub rate_unit: Option<String>,
+    /// Percent done, from the step counts or else the epoch counts
+    pub percent: Option<f64>,
+    /// Time left for the counted steps (the current epoch's, for per-epoch bars)
+    pub eta_secs: Option<u64>,
+}
+
+impl TrainingProgress {
+    /// Whether anything was parsed at all
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// One-line text view with a progress bar
+    pub fn render(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(percent) = self.percent {
+            let filled = ((percent / 100.0) * BAR_WIDTH as f64).round() as usize;
+            let filled = filled.min(BAR_WIDTH);
+            parts.push(format!(
+                "[{}{}] {:>3.0}%",
+                "#".repeat(filled),
+                "-".repeat(BAR_WIDTH - filled),
+                percent
+            ));
+        }
+        match (self.epoch, self.total_epochs) {
+            (Some(epoch), Some(total)) => parts.push(format!("epoch {}/{}", epoch, total)),
+            (Some(epoch), None) => parts.push(format!("epoch {}", epoch)),
+            _ => {}
+        }
+        match (self.step, self.total_steps) {
+            (Some(step), Some(total)) => parts.push(format!("step {}/{}", step, total)),
+            (Some(step), None) => parts.push(format!("step {}", step)),
+            _ => {}
+        }
+        if let Some(loss) = self.loss {
+            match self.loss_trend {
+                Some(trend) => parts.push(format!("loss {:.4} ({})", loss, trend)),
+                None => parts.push(format!("loss {:.4}", loss)),
+            }
+        }
+        if let (Some(rate), Some(unit)) = (self.rate, &self.rate_unit) {
+            parts.push(format!("{:.2} {}", rate, unit));
+        }
+        if let Some(eta) = self.eta_secs {
+            parts.push(format!("ETA {}", crate::utils::format_duration(eta)));
+        }
+        parts.join("  ")
+    }
+}
+
+fn tqdm_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(\d{1,3})%\|[^|]*\|\s*(\d+)/(\d+)\s*\[([\d:]+)<([\d:?]+)(?:,\s*([\d.]+|\?)\s*([A-Za-z/]+))?",
+        )
+        .expect("static tqdm regex")
+    })
+}
+
+fn rate_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(?i)(\d+(?:\.\d+)?)\s*(it/s|s/it|(?:samples|steps|tokens|examples|images)/s(?:ec)?)\b",
+        )
+        .expect("static rate regex")
+    })
+}
+
+fn epoch_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?i)\bepoch\s*[:=]?\s*(\d+)\b").expect("static epoch regex"))
+}
+
+/// `HH:MM:SS` or `MM:SS` as seconds
+fn parse_clock(clock: &str) -> Option<u64> {
+    clock.split(':').try_fold(0u64, |total, part| {
+        Some(total * 60 + part.parse::<u64>().ok()?)
+    })
+}
+
+/// Normalize a throughput reading to a per-second rate
+fn per_second(value: f64, unit: &str) -> Option<(f64, String)> {
+    let unit = unit.to_lowercase();
+    if unit == "s/it" {
+        return (value > 0.0).then(|| (1.0 / value, "it/s".to_string()));
+    }
+    Some((value, unit.trim_end_matches("ec").to_string()))
+}
+
+/// Direction of `losses`: the mean of the newer half against the older half
+pub fn loss_trend(losses: &[f64]) -> Option<LossTrend> {
+    if losses.len() < 4 {
+        return None;
+    }
+    let (older, newer) = losses.split_at(losses.len() / 2);
+    let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+    let (before, after) = (mean(older), mean(newer));
+    if before.abs() < f64::EPSILON {
+        return Some(LossTrend::Flat);
+    }
+    let change = (after - before) / before.abs();
+    Some(if change < -FLAT_THRESHOLD {
+        LossTrend::Falling
+    } else if change > FLAT_THRESHOLD {
+        LossTrend::Rising
+    } else {
+        LossTrend::Flat
+    })
+}
+
+/// Accumulates progress over a stream of log lines
+#[derive(Debug, Default)]
+pub struct ProgressTracker {
+    progress: TrainingProgress,
+    losses: VecDeque<f64>,
+    /// Seconds left as reported by a progress bar, for rates not in iterations
+    bar_remaining: Option<u64>,
+}
+
+impl ProgressTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update from new log lines; returns whether the progress changed
+    pub fn observe(&mut self, lines: &[String]) -> bool {
+        let before = self.progress.clone();
+        for line in lines {
+            let segment = line
+                .rsplit('\r')
+                .find(|s| !s.trim().is_empty())
+                .unwrap_or_default();
+            if !segment.is_empty() {
+                self.observe_line(segment);
+            }
+        }
+        self.update_derived();
+        self.progress != before
+    }
+
+    fn observe_line(&mut self, line: &str) {
+        let metrics = parse_metrics(&[line.to_string()]);
+        let progress = &mut self.progress;
+        if let Some((epoch, total)) = metrics.epoch {
+            progress.epoch = Some(epoch);
+            progress.total_epochs = Some(total);
+        } else if let Some(caps) = epoch_regex().captures_iter(line).last() {
+            progress.epoch = caps[1].parse().ok();
+        }
+        if let Some((step, total)) = metrics.step {
+            progress.step = Some(step);
+            progress.total_steps = Some(total);
+        }
+        if let Some(loss) = metrics.loss.filter(|l| l.is_finite()) {
+            progress.loss = Some(loss);
+            if self.losses.len() == TREND_WINDOW {
+                self.losses.pop_front();
+            }
+            self.losses.push_back(loss);
+        }
+
+        if let Some(caps) = tqdm_regex().captures_iter(line).last() {
+            progress.step = caps[2].parse().ok();
+            progress.total_steps = caps[3].parse().ok();
+            self.bar_remaining = parse_clock(&caps[5]);
+            if let (Some(value), Some(unit)) = (caps.get(6), caps.get(7)) {
+                if let Some((rate, unit)) = value
+                    .as_str()
+                    .parse()
+                    .ok()
+                    .and_then(|v| per_second(v, unit.as_str()))
+                {
+                    progress.rate = Some(rate);
+                    progress.rate_unit = Some(unit);
+                }
+            }
+        } else if let Some(caps) = rate_regex().captures_iter(line).last() {
+            if let Some((rate, unit)) = caps[1].parse().ok().and_then(|v| per_second(v, &caps[2])) {
+                progress.rate = Some(rate);
+                progress.rate_unit = Some(unit);
+            }
+        }
+    }
+
+    fn update_derived(&mut self) {
+        let losses: Vec<f64> = self.losses.iter().copied().collect();
+        let progress = &mut self.progress;
+        progress.loss_trend = loss_trend(&losses);
+
+        // Steps over epochs, as in `watch`
+        let fraction =
+            |current: u64, total: u64| (total > 0).then(|| current as f64 / total as f64);
+        progress.percent = progress
+            .step
+            .zip(progress.total_steps)
+            .and_then(|(s, t)| fraction(s, t))
+            .or_else(|| {
+                progress
+                    .epoch
+                    .zip(progress.total_epochs)
+                    .and_then(|(e, t)| fraction(e, t))
+            })
+            .map(|f| f.min(1.0) * 100.0);
+
+        let steps_left = progress
+            .step
+            .zip(progress.total_steps)
+            .map(|(step, total)| total.saturating_sub(step));
+        progress.eta_secs = match (steps_left, progress.rate, progress.rate_unit.as_deref()) {
+            (Some(left), Some(rate), Some("it/s")) if rate > 0.0 => {
+                Some((left as f64 / rate).round() as u64)
+            }
+            _ => self.bar_remaining,
+        };
+    }
+
+    pub fn progress(&self) -> &TrainingProgress {
+        &self.progress
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_plain_log_lines() {
+        let mut tracker = ProgressTracker::new();
+        assert!(tracker.observe(&lines(
+            "Epoch 1/1 step 100/400 loss=2.5 4.0 it/s\nEpoch 1/1 step 200/400 loss=2.0 4.0 it/s"
+        )));
+        let p = tracker.progress();
+        assert_eq!((p.step, p.total_steps), (Some(200), Some(400)));
+        assert_eq!(p.loss, Some(2.0));
+        assert_eq!(p.rate_unit.as_deref(), Some("it/s"));
+        assert_eq!(p.percent, Some(50.0));
+        assert_eq!(p.eta_secs, Some(50));
+        // Nothing new
+        assert!(!tracker.observe(&lines("loading data...")));
+    }
+
+    #[test]
+    fn test_tqdm_bar_with_carriage_returns() {
+        let mut tracker = ProgressTracker::new();
+        tracker.observe(&[
+            " 10%|█         | 100/1000 [00:20<03:00,  5.00it/s]\r 45%|████▌     | 450/1000 [01:23<01:40,  5.40it/s]"
+                .to_string(),
+        ]);
+        let p = tracker.progress();
+        assert_eq!((p.step, p.total_steps), (Some(450), Some(1000)));
+        assert_eq!(p.rate, Some(5.4));
+        assert_eq!(p.percent, Some(45.0));
+        assert_eq!(p.eta_secs, Some(102));
+    }
+
+    #[test]
+    fn test_lightning_bar_and_seconds_per_iteration() {
+        let mut tracker = ProgressTracker::new();
+        tracker.observe(&lines(
+            "Epoch 3:  50%|█████     | 10/20 [00:20<00:20,  2.00s/it, v_num=1, train_loss=0.420]",
+        ));
+        let p = tracker.progress();
+        assert_eq!(p.epoch, Some(3));
+        assert_eq!(p.loss, Some(0.42));
+        assert_eq!(p.rate, Some(0.5));
+        assert_eq!(p.rate_unit.as_deref(), Some("it/s"));
+        assert_eq!(p.eta_secs, Some(20));
+    }
+
+    #[test]
+    fn test_loss_trend() {
+        assert_eq!(loss_trend(&[1.0, 0.9]), None);
+        assert_eq!(loss_trend(&[1.0, 0.9, 0.8, 0.7]), Some(LossTrend::Falling));
+        assert_eq!(loss_trend(&[0.5, 0.5, 0.501, 0.5]), Some(LossTrend::Flat));
+        assert_eq!(loss_trend(&[0.5, 0.6, 0.7, 0.9]), Some(LossTrend::Rising));
+    }
+
+    #[test]
+    fn test_render() {
+        let progress = TrainingProgress {
+            epoch: Some(2),
+            total_epochs: Some(10),
+            loss: Some(0.5),
+            loss_trend: Some(LossTrend::Falling),
+            percent: Some(50.0),
+            eta_secs: Some(90),
+            ..Default::default()
+        };
+        assert_eq!(
+            progress.render(),
+            "[############------------]  50%  epoch 2/10  loss 0.5000 (falling)  ETA 1m 30s"
+        );
+        assert!(TrainingProgress::default().is_empty());
+    }
+}
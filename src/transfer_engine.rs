@@ -0,0 +1,779 @@
+//! Parallel multipart S3 transfers
+//!
+//! Files at least [`MULTIPART_THRESHOLD`] bytes are split into parts that
+//! move concurrently: uploads as an S3 multipart upload, downloads as ranged
+//! reads written at their offsets in a `.part` file that is renamed into
+//! place once complete. Smaller files are a single request.
+//!
+//! ## Resume
+//!
+//! Finished parts are recorded after each one, uploads in `.runctl/uploads/`
+//! and downloads in `.runctl/downloads/`. Transferring the same file again
+//! continues from the recorded parts, as long as the local file (uploads)
+//! or the object's ETag (downloads) hasn't changed.
+//!
+//! ## Tuning
+//!
+//! `[transfer] part_size`, `part_parallel` and `bandwidth_limit` (or
+//! `transfer --part-size/--part-parallel/--bandwidth-limit`) set the part
+//! size, parts in flight per file, and a cap on throughput shared by every
+//! file of a transfer. The cap is applied per part, so it holds on average
+//! rather than for every second. Failed requests are retried with backoff
+//! (see `crate::retry`).
+
+use crate::config::Config;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "aws")]
+use crate::error::{Result, TrainctlError};
+#[cfg(feature = "aws")]
+use crate::retry::{ExponentialBackoffPolicy, RetryPolicy};
+#[cfg(feature = "aws")]
+use aws_sdk_s3::Client as S3Client;
+#[cfg(feature = "aws")]
+use futures::StreamExt;
+#[cfg(feature = "aws")]
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget};
+#[cfg(feature = "aws")]
+use std::path::Path;
+#[cfg(feature = "aws")]
+use std::sync::Arc;
+#[cfg(feature = "aws")]
+use tracing::info;
+
+/// Files at least this large are transferred in parts, resumably
+pub const MULTIPART_THRESHOLD: u64 = 64 * 1024 * 1024;
+
+/// Default part size (S3's minimum is 5 MiB)
+const DEFAULT_PART_SIZE: u64 = 16 * 1024 * 1024;
+
+/// S3's limits on part size
+const S3_MIN_PART_SIZE: u64 = 5 * 1024 * 1024;
+const S3_MAX_PART_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+
+/// S3's limit on parts per upload
+const MAX_PARTS: u64 = 10_000;
+
+/// Default parts in flight per file
+pub const DEFAULT_PART_PARALLEL: usize = 4;
+
+/// Attempts per request before a part fails
+#[cfg(feature = "aws")]
+const PART_ATTEMPTS: u32 = 4;
+
+/// Where in-progress multipart uploads are recorded
+pub fn upload_state_dir() -> PathBuf {
+    PathBuf::from(".runctl").join("uploads")
+}
+
+/// Where in-progress ranged downloads are recorded
+pub fn download_state_dir() -> PathBuf {
+    PathBuf::from(".runctl").join("downloads")
+}
+
+/// Part size for a file of `size` bytes
+///
+/// `requested` (default 16 MiB) is kept within S3's part size limits and
+/// raised, in whole MiB, when the file would otherwise need more than
+/// 10,000 parts.
+pub fn part_size_for(size: u64, requested: Option<u64>) -> u64 {
+    const MIB: u64 = 1024 * 1024;
+    let needed = size.div_ceil(MAX_PARTS).div_ceil(MIB) * MIB;
+    requested
+        .unwrap_or(DEFAULT_PART_SIZE)
+        .clamp(S3_MIN_PART_SIZE, S3_MAX_PART_SIZE)
+        .max(needed)
+}
+
+/// Offset and length of part `index` (from 0) of a `size`-byte file
+///
+/// The last part is shorter unless `part_size` divides `size`.
+#[cfg(feature = "aws")]
+fn part_range(index: u64, part_size: u64, size: u64) -> (u64, u64) {
+    let offset = index * part_size;
+    (offset, part_size.min(size - offset))
+}
+
+/// State file for `bucket`/`key` in `state_dir`
+#[cfg(feature = "aws")]
+fn state_path(state_dir: &Path, bucket: &str, key: &str) -> PathBuf {
+    let name: String = format!("{}/{}", bucket, key)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    state_dir.join(format!("{}.json", name))
+}
+
+/// Throughput cap shared by the parts of a transfer
+///
+/// Each part reserves the time its bytes take at the capped rate and waits
+/// until its slot starts, so concurrent parts queue behind each other.
+#[derive(Debug)]
+pub struct BandwidthLimiter {
+    bytes_per_sec: u64,
+    next_slot: Mutex<Option<Instant>>,
+}
+
+impl BandwidthLimiter {
+    pub fn new(bytes_per_sec: u64) -> Self {
+        Self {
+            bytes_per_sec: bytes_per_sec.max(1),
+            next_slot: Mutex::new(None),
+        }
+    }
+
+    /// Reserve `bytes` at `now`; returns how long to wait before sending them
+    fn reserve(&self, bytes: u64, now: Instant) -> Duration {
+        let mut next_slot = self
+            .next_slot
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let start = next_slot.map_or(now, |slot| slot.max(now));
+        *next_slot =
+            Some(start + Duration::from_secs_f64(bytes as f64 / self.bytes_per_sec as f64));
+        start.saturating_duration_since(now)
+    }
+
+    /// Wait until `bytes` may be sent
+    pub async fn acquire(&self, bytes: u64) {
+        let wait = self.reserve(bytes, Instant::now());
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Part size, parallelism and bandwidth for a transfer
+#[derive(Debug, Clone, Default)]
+pub struct EngineSettings {
+    /// Part size; `None` picks one from the file size
+    pub part_size: Option<u64>,
+    /// Parts in flight per file (default 4)
+    pub part_parallel: Option<usize>,
+    /// Bytes per second across the whole transfer
+    pub bandwidth_limit: Option<u64>,
+}
+
+impl EngineSettings {
+    /// Settings from `[transfer]`
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            part_size: config.transfer.part_size,
+            part_parallel: config.transfer.part_parallel,
+            bandwidth_limit: config.transfer.bandwidth_limit,
+        }
+    }
+}
+
+/// Progress of a multipart upload, saved after every part
+#[cfg(feature = "aws")]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+struct UploadState {
+    bucket: String,
+    key: String,
+    /// Size and mtime of the file when the upload started; a changed file starts over
+    size: u64,
+    modified_secs: u64,
+    upload_id: String,
+    part_size: u64,
+    parts: Vec<UploadedPart>,
+}
+
+#[cfg(feature = "aws")]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+struct UploadedPart {
+    number: i32,
+    etag: String,
+}
+
+/// Progress of a ranged download, saved after every part
+#[cfg(feature = "aws")]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+struct DownloadState {
+    bucket: String,
+    key: String,
+    /// Object size and ETag when the download started; a changed object starts over
+    size: u64,
+    etag: String,
+    part_size: u64,
+    /// Numbers of the parts written to the `.part` file
+    parts: Vec<u64>,
+}
+
+#[cfg(feature = "aws")]
+fn load_state<T: serde::de::DeserializeOwned>(path: &Path) -> Option<T> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+}
+
+#[cfg(feature = "aws")]
+fn save_state<T: serde::Serialize>(path: &Path, state: &T) -> crate::error::Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(state)?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Where a download is written until it is complete
+#[cfg(feature = "aws")]
+fn partial_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    dest.with_file_name(name)
+}
+
+/// Uploads and downloads S3 objects in parallel parts
+///
+/// Clones share the bandwidth limit and progress display, so one engine
+/// serves every file of a transfer.
+#[cfg(feature = "aws")]
+#[derive(Clone)]
+pub struct TransferEngine {
+    client: S3Client,
+    settings: EngineSettings,
+    limiter: Option<Arc<BandwidthLimiter>>,
+    progress: MultiProgress,
+    upload_state_dir: PathBuf,
+    download_state_dir: PathBuf,
+}
+
+#[cfg(feature = "aws")]
+impl TransferEngine {
+    pub fn new(client: S3Client, settings: EngineSettings) -> Self {
+        let limiter = settings
+            .bandwidth_limit
+            .map(|limit| Arc::new(BandwidthLimiter::new(limit)));
+        Self {
+            client,
+            settings,
+            limiter,
+            progress: MultiProgress::new(),
+            upload_state_dir: upload_state_dir(),
+            download_state_dir: download_state_dir(),
+        }
+    }
+
+    /// Record in-progress uploads in `dir` instead of `.runctl/uploads`
+    pub fn with_upload_state_dir(mut self, dir: &Path) -> Self {
+        self.upload_state_dir = dir.to_path_buf();
+        self
+    }
+
+    /// Don't draw progress bars
+    pub fn quiet(self) -> Self {
+        self.progress.set_draw_target(ProgressDrawTarget::hidden());
+        self
+    }
+
+    /// Display the per-file bars are drawn in, for adding an overall bar
+    pub fn progress(&self) -> &MultiProgress {
+        &self.progress
+    }
+
+    fn part_parallel(&self) -> usize {
+        self.settings
+            .part_parallel
+            .unwrap_or(DEFAULT_PART_PARALLEL)
+            .max(1)
+    }
+
+    async fn throttle(&self, bytes: u64) {
+        if let Some(limiter) = &self.limiter {
+            limiter.acquire(bytes).await;
+        }
+    }
+
+    fn file_bar(&self, name: &str, size: u64) -> ProgressBar {
+        let pb = self.progress.add(ProgressBar::new(size));
+        pb.set_style(crate::output_style::bar(
+            "{msg:30!} [{bar:30.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})",
+        ));
+        pb.set_message(name.to_string());
+        pb
+    }
+
+    /// Upload `path` to `bucket`/`key`, in resumable parts when it is large
+    pub async fn upload_file(&self, path: &Path, bucket: &str, key: &str) -> Result<()> {
+        let metadata = std::fs::metadata(path)?;
+        let size = metadata.len();
+        let modified_secs = metadata
+            .modified()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let pb = self.file_bar(key, size);
+        let result = if size < MULTIPART_THRESHOLD {
+            self.put_object(path, bucket, key, size).await
+        } else {
+            self.upload_parts(path, bucket, key, size, modified_secs, &pb)
+                .await
+        };
+        match &result {
+            Ok(()) => {
+                pb.set_position(size);
+                pb.finish_and_clear();
+            }
+            Err(_) => pb.abandon(),
+        }
+        result
+    }
+
+    async fn put_object(&self, path: &Path, bucket: &str, key: &str, size: u64) -> Result<()> {
+        self.throttle(size).await;
+        ExponentialBackoffPolicy::new(PART_ATTEMPTS)
+            .execute_with_retry(|| async {
+                let body = aws_sdk_s3::primitives::ByteStream::from_path(path)
+                    .await
+                    .map_err(|e| {
+                        TrainctlError::Io(std::io::Error::other(format!(
+                            "Failed to read {}: {}",
+                            path.display(),
+                            e
+                        )))
+                    })?;
+                self.client
+                    .put_object()
+                    .bucket(bucket)
+                    .key(key)
+                    .body(body)
+                    .send()
+                    .await
+                    .map_err(|e| request_error(format!("Upload of {} failed", key), e))?;
+                Ok(())
+            })
+            .await
+    }
+
+    async fn upload_parts(
+        &self,
+        path: &Path,
+        bucket: &str,
+        key: &str,
+        size: u64,
+        modified_secs: u64,
+        pb: &ProgressBar,
+    ) -> Result<()> {
+        crate::utils::ensure_dir(&self.upload_state_dir)?;
+        let state_path = state_path(&self.upload_state_dir, bucket, key);
+        let saved = load_state::<UploadState>(&state_path).filter(|state| {
+            state.bucket == bucket
+                && state.key == key
+                && state.size == size
+                && state.modified_secs == modified_secs
+        });
+        let mut state = match saved {
+            Some(state) => {
+                info!(
+                    "Resuming upload of {} ({} parts done)",
+                    path.display(),
+                    state.parts.len()
+                );
+                state
+            }
+            None => {
+                let upload = ExponentialBackoffPolicy::new(PART_ATTEMPTS)
+                    .execute_with_retry(|| async {
+                        self.client
+                            .create_multipart_upload()
+                            .bucket(bucket)
+                            .key(key)
+                            .send()
+                            .await
+                            .map_err(|e| {
+                                request_error(format!("Failed to start upload of {}", key), e)
+                            })
+                    })
+                    .await?;
+                let upload_id = upload
+                    .upload_id()
+                    .ok_or_else(|| TrainctlError::S3("S3 returned no upload ID".to_string()))?
+                    .to_string();
+                let state = UploadState {
+                    bucket: bucket.to_string(),
+                    key: key.to_string(),
+                    size,
+                    modified_secs,
+                    upload_id,
+                    part_size: part_size_for(size, self.settings.part_size),
+                    parts: Vec::new(),
+                };
+                save_state(&state_path, &state)?;
+                state
+            }
+        };
+
+        let part_size = state.part_size;
+        let total_parts = size.div_ceil(part_size) as i32;
+        let pending: Vec<i32> = (1..=total_parts)
+            .filter(|number| !state.parts.iter().any(|p| p.number == *number))
+            .collect();
+        let pending_bytes: u64 = pending
+            .iter()
+            .map(|&number| part_range(number as u64 - 1, part_size, size).1)
+            .sum();
+        pb.set_position(size - pending_bytes);
+
+        let upload_id = state.upload_id.clone();
+        let upload_id = upload_id.as_str();
+        let mut parts = futures::stream::iter(pending)
+            .map(|number| async move {
+                let (offset, length) = part_range(number as u64 - 1, part_size, size);
+                self.throttle(length).await;
+                let etag = ExponentialBackoffPolicy::new(PART_ATTEMPTS)
+                    .execute_with_retry(|| async {
+                        let body = aws_sdk_s3::primitives::ByteStream::read_from()
+                            .path(path)
+                            .offset(offset)
+                            .length(aws_sdk_s3::primitives::Length::Exact(length))
+                            .build()
+                            .await
+                            .map_err(|e| {
+                                TrainctlError::Io(std::io::Error::other(format!(
+                                    "Failed to read {}: {}",
+                                    path.display(),
+                                    e
+                                )))
+                            })?;
+                        let part = self
+                            .client
+                            .upload_part()
+                            .bucket(bucket)
+                            .key(key)
+                            .upload_id(upload_id)
+                            .part_number(number)
+                            .body(body)
+                            .send()
+                            .await
+                            .map_err(|e| {
+                                let message = format!(
+                                    "Failed to upload part {}/{} of {}",
+                                    number,
+                                    total_parts,
+                                    path.display()
+                                );
+                                if format!("{:?}", e).contains("NoSuchUpload") {
+                                    // Aborted or expired upstream; retrying can't help
+                                    TrainctlError::S3(format!(
+                                        "{}: upload no longer exists",
+                                        message
+                                    ))
+                                } else {
+                                    request_error(message, e)
+                                }
+                            })?;
+                        Ok(part.e_tag().unwrap_or_default().to_string())
+                    })
+                    .await?;
+                Ok::<_, TrainctlError>((UploadedPart { number, etag }, length))
+            })
+            .buffer_unordered(self.part_parallel());
+        while let Some(result) = parts.next().await {
+            let (part, length) = match result {
+                Ok(done) => done,
+                Err(e) => {
+                    // Only an expired upload fails without retrying as an S3
+                    // error; the next attempt starts a new one
+                    if matches!(e, TrainctlError::S3(_)) {
+                        let _ = std::fs::remove_file(&state_path);
+                    }
+                    return Err(e);
+                }
+            };
+            pb.inc(length);
+            state.parts.push(part);
+            save_state(&state_path, &state)?;
+        }
+        drop(parts);
+
+        state.parts.sort_by_key(|p| p.number);
+        let completed = aws_sdk_s3::types::CompletedMultipartUpload::builder()
+            .set_parts(Some(
+                state
+                    .parts
+                    .iter()
+                    .map(|p| {
+                        aws_sdk_s3::types::CompletedPart::builder()
+                            .part_number(p.number)
+                            .e_tag(&p.etag)
+                            .build()
+                    })
+                    .collect(),
+            ))
+            .build();
+        ExponentialBackoffPolicy::new(PART_ATTEMPTS)
+            .execute_with_retry(|| async {
+                self.client
+                    .complete_multipart_upload()
+                    .bucket(bucket)
+                    .key(key)
+                    .upload_id(&state.upload_id)
+                    .multipart_upload(completed.clone())
+                    .send()
+                    .await
+                    .map_err(|e| request_error(format!("Failed to complete upload of {}", key), e))
+            })
+            .await?;
+        let _ = std::fs::remove_file(&state_path);
+        Ok(())
+    }
+
+    /// Download `bucket`/`key` to `dest`, in resumable parts when it is large
+    ///
+    /// The object is written to `<dest>.part` and renamed once complete.
+    pub async fn download_file(&self, bucket: &str, key: &str, dest: &Path) -> Result<()> {
+        let head = ExponentialBackoffPolicy::new(PART_ATTEMPTS)
+            .execute_with_retry(|| async {
+                self.client
+                    .head_object()
+                    .bucket(bucket)
+                    .key(key)
+                    .send()
+                    .await
+                    .map_err(|e| {
+                        request_error(format!("Failed to read s3://{}/{}", bucket, key), e)
+                    })
+            })
+            .await?;
+        let size = head.content_length().unwrap_or(0).max(0) as u64;
+        let etag = head.e_tag().unwrap_or_default().to_string();
+
+        if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+            crate::utils::ensure_dir(parent)?;
+        }
+        let partial = partial_path(dest);
+        let pb = self.file_bar(&dest.display().to_string(), size);
+        let result = if size < MULTIPART_THRESHOLD {
+            self.get_range(bucket, key, &etag, 0, size)
+                .await
+                .and_then(|data| std::fs::write(&partial, data).map_err(TrainctlError::from))
+        } else {
+            self.download_parts(bucket, key, size, &etag, &partial, &pb)
+                .await
+        };
+        let result = result.and_then(|()| std::fs::rename(&partial, dest).map_err(Into::into));
+        match &result {
+            Ok(()) => {
+                pb.set_position(size);
+                pb.finish_and_clear();
+            }
+            Err(_) => pb.abandon(),
+        }
+        result
+    }
+
+    /// Bytes `offset..offset + length` of the object, checked against `etag`
+    async fn get_range(
+        &self,
+        bucket: &str,
+        key: &str,
+        etag: &str,
+        offset: u64,
+        length: u64,
+    ) -> Result<Vec<u8>> {
+        self.throttle(length).await;
+        ExponentialBackoffPolicy::new(PART_ATTEMPTS)
+            .execute_with_retry(|| async {
+                let read_error = |e: String| TrainctlError::CloudProvider {
+                    provider: "aws".to_string(),
+                    message: format!("Failed to download s3://{}/{}: {}", bucket, key, e),
+                    source: None,
+                };
+                let mut request = self.client.get_object().bucket(bucket).key(key);
+                if length > 0 {
+                    request = request.range(format!("bytes={}-{}", offset, offset + length - 1));
+                }
+                if !etag.is_empty() {
+                    request = request.if_match(etag);
+                }
+                let response = request.send().await.map_err(|e| {
+                    if format!("{:?}", e).contains("PreconditionFailed") {
+                        // Changed since the download started; retrying can't help
+                        TrainctlError::S3(format!(
+                            "s3://{}/{} changed during the download",
+                            bucket, key
+                        ))
+                    } else {
+                        read_error(e.to_string())
+                    }
+                })?;
+                let data = response
+                    .body
+                    .collect()
+                    .await
+                    .map_err(|e| read_error(e.to_string()))?
+                    .into_bytes();
+                if data.len() as u64 != length {
+                    return Err(read_error(format!(
+                        "got {} bytes, expected {}",
+                        data.len(),
+                        length
+                    )));
+                }
+                Ok(data.to_vec())
+            })
+            .await
+    }
+
+    async fn download_parts(
+        &self,
+        bucket: &str,
+        key: &str,
+        size: u64,
+        etag: &str,
+        partial: &Path,
+        pb: &ProgressBar,
+    ) -> Result<()> {
+        use std::io::{Seek, SeekFrom, Write};
+
+        crate::utils::ensure_dir(&self.download_state_dir)?;
+        let state_path = state_path(&self.download_state_dir, bucket, key);
+        let saved = load_state::<DownloadState>(&state_path).filter(|state| {
+            state.bucket == bucket
+                && state.key == key
+                && state.size == size
+                && state.etag == etag
+                && partial.exists()
+        });
+        let mut state = match saved {
+            Some(state) => {
+                info!(
+                    "Resuming download of s3://{}/{} ({} parts done)",
+                    bucket,
+                    key,
+                    state.parts.len()
+                );
+                state
+            }
+            None => {
+                let file = std::fs::File::create(partial)?;
+                file.set_len(size)?;
+                let state = DownloadState {
+                    bucket: bucket.to_string(),
+                    key: key.to_string(),
+                    size,
+                    etag: etag.to_string(),
+                    part_size: part_size_for(size, self.settings.part_size),
+                    parts: Vec::new(),
+                };
+                save_state(&state_path, &state)?;
+                state
+            }
+        };
+
+        let part_size = state.part_size;
+        let total_parts = size.div_ceil(part_size);
+        let pending: Vec<u64> = (0..total_parts)
+            .filter(|number| !state.parts.contains(number))
+            .collect();
+        let pending_bytes: u64 = pending
+            .iter()
+            .map(|&number| part_range(number, part_size, size).1)
+            .sum();
+        pb.set_position(size - pending_bytes);
+
+        let mut parts = futures::stream::iter(pending)
+            .map(|number| async move {
+                let (offset, length) = part_range(number, part_size, size);
+                let data = self.get_range(bucket, key, etag, offset, length).await?;
+                let mut file = std::fs::OpenOptions::new().write(true).open(partial)?;
+                file.seek(SeekFrom::Start(offset))?;
+                file.write_all(&data)?;
+                // On disk before the state file says the part is done
+                file.sync_data()?;
+                Ok::<_, TrainctlError>((number, length))
+            })
+            .buffer_unordered(self.part_parallel());
+        while let Some(result) = parts.next().await {
+            let (number, length) = match result {
+                Ok(done) => done,
+                Err(e) => {
+                    // The object changed; the next attempt starts over
+                    if matches!(e, TrainctlError::S3(_)) {
+                        let _ = std::fs::remove_file(&state_path);
+                    }
+                    return Err(e);
+                }
+            };
+            pb.inc(length);
+            state.parts.push(number);
+            save_state(&state_path, &state)?;
+        }
+        drop(parts);
+        let _ = std::fs::remove_file(&state_path);
+        Ok(())
+    }
+}
+
+/// A failed S3 request as a retryable error
+#[cfg(feature = "aws")]
+fn request_error(message: String, error: impl std::fmt::Display) -> TrainctlError {
+    TrainctlError::CloudProvider {
+        provider: "aws".to_string(),
+        message: format!("{}: {}", message, error),
+        source: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_size_for() {
+        const MIB: u64 = 1024 * 1024;
+        assert_eq!(part_size_for(100 * MIB, None), DEFAULT_PART_SIZE);
+        assert_eq!(part_size_for(100 * MIB, Some(64 * MIB)), 64 * MIB);
+        // Below S3's minimum
+        assert_eq!(part_size_for(100 * MIB, Some(MIB)), S3_MIN_PART_SIZE);
+        // 1 TiB needs parts over 100 MiB to stay within 10,000 parts
+        let size = 1024 * 1024 * MIB;
+        let part = part_size_for(size, Some(8 * MIB));
+        assert_eq!(part % MIB, 0);
+        assert!(size.div_ceil(part) <= MAX_PARTS);
+    }
+
+    #[cfg(feature = "aws")]
+    #[test]
+    fn test_part_range() {
+        assert_eq!(part_range(0, 10, 25), (0, 10));
+        assert_eq!(part_range(2, 10, 25), (20, 5));
+        let pending_bytes: u64 = [1, 2].iter().map(|&i| part_range(i, 10, 25).1).sum();
+        assert_eq!(pending_bytes, 15);
+    }
+
+    #[cfg(feature = "aws")]
+    #[test]
+    fn test_state_path() {
+        let path = state_path(Path::new("/tmp/up"), "bucket", "ckpt/run 1/epoch_2.pt");
+        assert_eq!(
+            path,
+            PathBuf::from("/tmp/up/bucket_ckpt_run_1_epoch_2.pt.json")
+        );
+        assert_eq!(
+            partial_path(Path::new("data/train.bin")),
+            PathBuf::from("data/train.bin.part")
+        );
+    }
+
+    #[test]
+    fn test_bandwidth_limiter_queues_parts() {
+        // 1 MB/s: each 500 KB part waits for the ones before it
+        let limiter = BandwidthLimiter::new(1_000_000);
+        let now = Instant::now();
+        assert_eq!(limiter.reserve(500_000, now), Duration::ZERO);
+        assert_eq!(limiter.reserve(500_000, now), Duration::from_millis(500));
+        assert_eq!(limiter.reserve(500_000, now), Duration::from_secs(1));
+        // Once the earlier parts are sent, a new part goes straight away
+        let later = now + Duration::from_secs(5);
+        assert_eq!(limiter.reserve(1_000, later), Duration::ZERO);
+    }
+}
@@ -0,0 +1,836 @@
+//! Resumable `runctl transfer` sessions
+//!
+//! Every `runctl transfer` is recorded as a session in
+//! `.runctl/transfers/<id>.json`: where it copies from and to, the options it
+//! was started with, and progress per file (size, whether it finished, how
+//! many attempts it took, the last error). Directory uploads and S3 prefix
+//! downloads check files off as they finish, so `runctl transfer resume <id>`
+//! (or running the same transfer again) skips what already made it across.
+//! Transfers handed to s5cmd or run on an instance are tracked as one unit;
+//! a resumed s5cmd copy skips files whose size already matches.
+//!
+//! A session whose process died before recording an outcome is reported as
+//! interrupted. With `--detach` the transfer runs in a background `runctl`
+//! process, the same way `watch --detach` does.
+
+use crate::checksums::{self, ChecksumManifest};
+use crate::config::Config;
+use crate::data_locality::EgressEstimate;
+use crate::data_transfer::{
+    parse_location, parse_s3_path, DataLocation, DataTransfer, TransferOptions,
+};
+use crate::error::{Result, TrainctlError};
+use chrono::{DateTime, Utc};
+use clap::Subcommand;
+use comfy_table::Table;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessesToUpdate, System};
+use tracing::warn;
+
+/// Minimum time between progress writes while files finish
+const SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Subcommand, Clone)]
+pub enum TransferCommands {
+    /// Continue an interrupted or failed transfer
+    ///
+    /// Files that already finished are skipped. The session keeps the
+    /// options it was started with.
+    ///
+    /// Examples:
+    ///   runctl transfer resume t-1a2b3c4d
+    ///   runctl transfer resume t-1a2b --detach
+    Resume {
+        /// Session ID (or a unique prefix)
+        #[arg(value_name = "SESSION_ID")]
+        session_id: String,
+
+        /// Continue in the background; check on it with `transfer sessions show`
+        #[arg(long)]
+        detach: bool,
+
+        /// Run as the background process of a detached session (internal)
+        #[arg(long, hide = true)]
+        worker: bool,
+    },
+    /// List, inspect and remove transfer sessions
+    ///
+    /// Examples:
+    ///   runctl transfer sessions list
+    ///   runctl transfer sessions show t-1a2b
+    Sessions {
+        #[command(subcommand)]
+        command: SessionCommands,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+pub enum SessionCommands {
+    /// List transfer sessions, newest first
+    List,
+    /// Show a session and the files it has not finished
+    Show {
+        /// Session ID (or a unique prefix)
+        #[arg(value_name = "SESSION_ID")]
+        session_id: String,
+    },
+    /// Forget a session
+    Remove {
+        /// Session ID (or a unique prefix)
+        #[arg(value_name = "SESSION_ID")]
+        session_id: String,
+    },
+}
+
+/// Where a transfer session is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionStatus {
+    Running,
+    /// Recorded as running, but its process is gone
+    Interrupted,
+    Failed,
+    Completed,
+}
+
+impl fmt::Display for SessionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SessionStatus::Running => "running",
+            SessionStatus::Interrupted => "interrupted",
+            SessionStatus::Failed => "failed",
+            SessionStatus::Completed => "completed",
+        })
+    }
+}
+
+/// Progress of one file or object in a session
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileProgress {
+    pub size: u64,
+    pub done: bool,
+    /// Times the file has been started, across runs
+    pub attempts: u32,
+    /// Why the last attempt failed
+    pub error: Option<String>,
+}
+
+/// A transfer, persisted as it progresses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferSession {
+    pub id: String,
+    pub source: String,
+    pub destination: String,
+    pub parallel: Option<usize>,
+    #[serde(default)]
+    pub compress: bool,
+    pub verify: bool,
+    #[serde(default)]
+    pub encrypt: bool,
+    /// Part size, parts per file and bandwidth cap the transfer was started
+    /// with (`None`: `[transfer]` config)
+    #[serde(default)]
+    pub part_size: Option<u64>,
+    #[serde(default)]
+    pub part_parallel: Option<usize>,
+    #[serde(default)]
+    pub bandwidth_limit: Option<u64>,
+    /// Store or check a SHA-256 manifest (see `crate::checksums`)
+    #[serde(default)]
+    pub manifest: bool,
+    pub status: SessionStatus,
+    /// Process running the transfer while the session is running
+    pub pid: Option<u32>,
+    /// Times the transfer has been started
+    #[serde(default)]
+    pub runs: u32,
+    /// Egress confirmed when the session was created, recorded in the cost
+    /// history once it completes
+    #[serde(default)]
+    pub egress: Option<EgressEstimate>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Progress keyed by destination object key or local path
+    #[serde(default)]
+    pub files: BTreeMap<String, FileProgress>,
+}
+
+impl TransferSession {
+    pub fn new(source: String, destination: String) -> Self {
+        let now = Utc::now();
+        Self {
+            id: format!("t-{}", &uuid::Uuid::new_v4().simple().to_string()[..8]),
+            source,
+            destination,
+            parallel: None,
+            compress: false,
+            verify: true,
+            encrypt: false,
+            part_size: None,
+            part_parallel: None,
+            bandwidth_limit: None,
+            manifest: false,
+            status: SessionStatus::Failed,
+            pid: None,
+            runs: 0,
+            egress: None,
+            error: None,
+            created_at: now,
+            updated_at: now,
+            files: BTreeMap::new(),
+        }
+    }
+
+    fn path(dir: &Path, id: &str) -> PathBuf {
+        dir.join(format!("{}.json", id))
+    }
+
+    /// Write the session to `dir`, replacing the file atomically
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        crate::utils::ensure_dir(dir)?;
+        let path = Self::path(dir, &self.id);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// Status, reporting a running session whose process is gone as interrupted
+    pub fn current_status(&self) -> SessionStatus {
+        self.resolve_status(self.pid.is_some_and(process_alive))
+    }
+
+    fn resolve_status(&self, alive: bool) -> SessionStatus {
+        match self.status {
+            SessionStatus::Running if !alive => SessionStatus::Interrupted,
+            status => status,
+        }
+    }
+
+    pub fn files_done(&self) -> usize {
+        self.files.values().filter(|f| f.done).count()
+    }
+
+    pub fn bytes_done(&self) -> u64 {
+        self.files.values().filter(|f| f.done).map(|f| f.size).sum()
+    }
+
+    pub fn bytes_total(&self) -> u64 {
+        self.files.values().map(|f| f.size).sum()
+    }
+
+    /// Files whose last attempt failed
+    pub fn failed_files(&self) -> impl Iterator<Item = (&String, &FileProgress)> {
+        self.files
+            .iter()
+            .filter(|(_, f)| !f.done && f.error.is_some())
+    }
+
+    fn progress_summary(&self) -> String {
+        format!(
+            "{}/{} files, {} of {}",
+            self.files_done(),
+            self.files.len(),
+            format_size(self.bytes_done()),
+            format_size(self.bytes_total())
+        )
+    }
+}
+
+fn process_alive(pid: u32) -> bool {
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::Some(&[Pid::from_u32(pid)])) > 0
+}
+
+pub fn transfers_dir() -> PathBuf {
+    PathBuf::from(".runctl").join("transfers")
+}
+
+/// Load every session in `dir`, newest first
+///
+/// Files that don't parse are skipped.
+pub fn load_sessions(dir: &Path) -> Result<Vec<TransferSession>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut sessions: Vec<TransferSession> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+    Ok(sessions)
+}
+
+/// Find a session by ID or unique ID prefix
+pub fn find_session(dir: &Path, id: &str) -> Result<TransferSession> {
+    let mut matches: Vec<TransferSession> = load_sessions(dir)?
+        .into_iter()
+        .filter(|s| s.id.starts_with(id))
+        .collect();
+    match matches.len() {
+        0 => Err(TrainctlError::ResourceNotFound {
+            resource_type: "transfer session".to_string(),
+            resource_id: id.to_string(),
+        }),
+        1 => Ok(matches.remove(0)),
+        n => Err(TrainctlError::Validation {
+            field: "session_id".to_string(),
+            reason: format!("'{}' matches {} sessions; use more of the ID", id, n),
+        }),
+    }
+}
+
+/// Newest unfinished session copying `source` to `destination`, if any
+pub fn find_resumable(
+    dir: &Path,
+    source: &str,
+    destination: &str,
+) -> Result<Option<TransferSession>> {
+    Ok(load_sessions(dir)?.into_iter().find(|s| {
+        s.source == source
+            && s.destination == destination
+            && matches!(
+                s.current_status(),
+                SessionStatus::Interrupted | SessionStatus::Failed
+            )
+    }))
+}
+
+/// Handle the transfer engine records per-file progress through
+///
+/// Progress is written out at most once a second while files finish, and
+/// right away when one fails.
+#[derive(Clone)]
+pub(crate) struct SessionHandle {
+    inner: Arc<Mutex<Tracked>>,
+}
+
+struct Tracked {
+    session: TransferSession,
+    dir: PathBuf,
+    saved_at: Instant,
+}
+
+impl SessionHandle {
+    fn new(session: TransferSession, dir: PathBuf) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Tracked {
+                session,
+                dir,
+                saved_at: Instant::now(),
+            })),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Tracked> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether an earlier run already transferred `name` at this size
+    pub(crate) fn is_done(&self, name: &str, size: u64) -> bool {
+        self.lock()
+            .session
+            .files
+            .get(name)
+            .is_some_and(|f| f.done && f.size == size)
+    }
+
+    /// Whether the session ran before this run
+    pub(crate) fn is_resumed(&self) -> bool {
+        self.lock().session.runs > 1
+    }
+
+    pub(crate) fn start_file(&self, name: &str, size: u64) {
+        let mut tracked = self.lock();
+        let file = tracked.session.files.entry(name.to_string()).or_default();
+        file.size = size;
+        file.done = false;
+        file.attempts += 1;
+    }
+
+    /// Mark `name` as not transferred, so the next run copies it again
+    pub(crate) fn invalidate_file(&self, name: &str, reason: &str) {
+        let mut tracked = self.lock();
+        if let Some(file) = tracked.session.files.get_mut(name) {
+            file.done = false;
+            file.error = Some(reason.to_string());
+        }
+    }
+
+    pub(crate) fn finish_file(&self, name: &str, result: &Result<()>) {
+        let mut tracked = self.lock();
+        let file = tracked.session.files.entry(name.to_string()).or_default();
+        match result {
+            Ok(()) => {
+                file.done = true;
+                file.error = None;
+            }
+            Err(e) => file.error = Some(e.to_string()),
+        }
+        if result.is_err() || tracked.saved_at.elapsed() >= SAVE_INTERVAL {
+            tracked.session.updated_at = Utc::now();
+            if let Err(e) = tracked.session.save(&tracked.dir) {
+                warn!("Failed to save transfer session: {}", e);
+            }
+            tracked.saved_at = Instant::now();
+        }
+    }
+
+    fn session(&self) -> TransferSession {
+        self.lock().session.clone()
+    }
+}
+
+/// Run (or continue) a session in this process and record how it ended
+pub async fn run_session(mut session: TransferSession, config: &Config) -> Result<()> {
+    let dir = transfers_dir();
+    session.status = SessionStatus::Running;
+    session.pid = Some(std::process::id());
+    session.runs += 1;
+    session.error = None;
+    session.updated_at = Utc::now();
+    session.save(&dir)?;
+
+    let handle = SessionHandle::new(session.clone(), dir.clone());
+    let result = transfer_files(&session, &handle, config).await;
+
+    let mut session = handle.session();
+    match &result {
+        Ok(()) => {
+            session.status = SessionStatus::Completed;
+            if let Some(estimate) = &session.egress {
+                let description = format!("{} -> {}", session.source, session.destination);
+                crate::egress::record_egress(estimate, &description);
+            }
+        }
+        Err(e) => {
+            session.status = SessionStatus::Failed;
+            session.error = Some(e.to_string());
+        }
+    }
+    session.pid = None;
+    session.updated_at = Utc::now();
+    session.save(&dir)?;
+    result
+}
+
+async fn transfer_files(
+    session: &TransferSession,
+    handle: &SessionHandle,
+    config: &Config,
+) -> Result<()> {
+    let mut src = parse_location(&session.source)?;
+    let mut dst = parse_location(&session.destination)?;
+
+    // Hash what is uploaded before it goes anywhere
+    let upload_manifest = match (&src, &dst) {
+        (DataLocation::Local(path), DataLocation::S3(_)) if session.manifest => {
+            println!("Computing checksums for {}", path.display());
+            Some((ChecksumManifest::compute(path)?, path.is_dir()))
+        }
+        _ => None,
+    };
+
+    // Encrypt into a staging area and upload that instead of the source
+    let _stage = if session.encrypt {
+        let (DataLocation::Local(path), DataLocation::S3(uri)) = (&src, &dst) else {
+            return Err(TrainctlError::Validation {
+                field: "encrypt".to_string(),
+                reason: "--encrypt applies to transfers from a local path to S3".to_string(),
+            });
+        };
+        let key = crate::encryption::EncryptionKey::load(config)?;
+        let stage = crate::encryption::stage_for_upload(&key, path)?;
+        if path.is_file() && !uri.ends_with('/') {
+            dst = DataLocation::S3(crate::encryption::encrypted_name(uri));
+        }
+        src = DataLocation::Local(stage.path().to_path_buf());
+        Some(stage)
+    } else {
+        None
+    };
+
+    let aws_config = crate::aws_utils::shared_sdk_config().await;
+    let client = aws_sdk_s3::Client::new(&aws_config);
+
+    // Fail before downloading anything when there is nothing to check against
+    let expected = match (&src, &dst) {
+        (DataLocation::S3(uri), DataLocation::Local(_)) if session.manifest => {
+            let (bucket, key) = parse_s3_path(uri)?;
+            let manifest = checksums::fetch_manifest(&client, &bucket, &key)
+                .await?
+                .ok_or_else(|| TrainctlError::ResourceNotFound {
+                    resource_type: "checksum manifest".to_string(),
+                    resource_id: format!(
+                        "s3://{}/{}",
+                        bucket,
+                        checksums::manifest_key(&key, key.is_empty() || key.ends_with('/'))
+                    ),
+                })?;
+            Some((manifest, key))
+        }
+        _ => None,
+    };
+
+    let transfer =
+        DataTransfer::new(config.clone(), Some(&aws_config)).with_session(handle.clone());
+    let options = TransferOptions {
+        parallel: session.parallel,
+        compression: session.compress,
+        verify: session.verify,
+        resume: true,
+        part_size: session.part_size,
+        part_parallel: session.part_parallel,
+        bandwidth_limit: session.bandwidth_limit,
+        ..TransferOptions::default()
+    };
+    transfer.transfer(&src, &dst, options).await?;
+
+    if let DataLocation::Local(path) = &dst {
+        let decrypted = crate::encryption::decrypt_downloaded(config, path)?;
+        if decrypted > 0 {
+            println!("Decrypted {} file(s)", decrypted);
+        }
+    }
+
+    if let (Some((manifest, is_dir)), DataLocation::S3(uri)) =
+        (upload_manifest, parse_location(&session.destination)?)
+    {
+        let (bucket, key) = parse_s3_path(&uri)?;
+        let manifest_key =
+            checksums::upload_manifest(&client, &bucket, &key, is_dir, &manifest).await?;
+        println!(
+            "Stored checksums for {} file(s) in s3://{}/{}",
+            manifest.len(),
+            bucket,
+            manifest_key
+        );
+    }
+    if let (Some(((manifest, single), key)), DataLocation::Local(path)) = (expected, &dst) {
+        verify_download(&manifest, single, &key, path, handle)?;
+    }
+    Ok(())
+}
+
+const MISMATCH_REASON: &str = "checksum does not match the manifest";
+
+/// Check a finished download against its manifest
+///
+/// Files that don't match are marked unfinished in the session, so resuming
+/// it downloads them again.
+fn verify_download(
+    manifest: &ChecksumManifest,
+    single: bool,
+    key: &str,
+    destination: &Path,
+    handle: &SessionHandle,
+) -> Result<()> {
+    let verification = if single {
+        let target = checksums::download_target(key, destination);
+        let verification = manifest.verify_file(&target)?;
+        if !verification.is_ok() {
+            handle.invalidate_file(&target.display().to_string(), MISMATCH_REASON);
+        }
+        verification
+    } else {
+        let verification = manifest.verify(destination)?;
+        for name in verification.mismatched.iter().chain(&verification.missing) {
+            handle.invalidate_file(
+                &destination.join(name).display().to_string(),
+                MISMATCH_REASON,
+            );
+        }
+        verification
+    };
+    verification.check(&destination.display().to_string())?;
+    println!(
+        "Verified {} file(s) against the manifest",
+        verification.verified
+    );
+    Ok(())
+}
+
+/// Continue `session` in a background `runctl` process; returns its PID
+pub fn detach(mut session: TransferSession, config_path: Option<&Path>) -> Result<u32> {
+    let exe = std::env::current_exe()?;
+    let mut cmd = std::process::Command::new(exe);
+    if let Some(path) = config_path {
+        cmd.arg("--config").arg(path);
+    }
+    cmd.args(["transfer", "resume", &session.id, "--worker"])
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    let child = cmd.spawn().map_err(|e| {
+        TrainctlError::Io(std::io::Error::other(format!(
+            "Failed to start transfer process: {}",
+            e
+        )))
+    })?;
+
+    // Mark it running now so a second resume doesn't start another copy
+    session.status = SessionStatus::Running;
+    session.pid = Some(child.id());
+    session.updated_at = Utc::now();
+    session.save(&transfers_dir())?;
+    Ok(child.id())
+}
+
+/// Refuse to touch a session another process is running
+fn ensure_not_running(session: &TransferSession) -> Result<()> {
+    if session.current_status() == SessionStatus::Running {
+        return Err(TrainctlError::ResourceExists {
+            resource_type: "running transfer session".to_string(),
+            resource_id: format!("{} (PID {})", session.id, session.pid.unwrap_or_default()),
+        });
+    }
+    Ok(())
+}
+
+pub async fn handle_command(
+    cmd: TransferCommands,
+    config: &Config,
+    config_path: Option<&Path>,
+    output_format: &str,
+) -> Result<()> {
+    let dir = transfers_dir();
+    match cmd {
+        TransferCommands::Resume {
+            session_id,
+            detach: background,
+            worker,
+        } => {
+            let session = find_session(&dir, &session_id)?;
+            if worker {
+                return run_session(session, config).await;
+            }
+            ensure_not_running(&session)?;
+            if session.status == SessionStatus::Completed {
+                println!("{} already completed", session.id);
+                return Ok(());
+            }
+
+            let id = session.id.clone();
+            if background {
+                let pid = detach(session, config_path)?;
+                println!("Resuming {} in the background (PID {})", id, pid);
+                println!("   Check on it with: runctl transfer sessions show {}", id);
+                return Ok(());
+            }
+            println!("Resuming {} ({})", id, session.progress_summary());
+            let (source, destination) = (session.source.clone(), session.destination.clone());
+            match run_session(session, config).await {
+                Ok(()) => {
+                    println!("Transfer complete: {} -> {}", source, destination);
+                    Ok(())
+                }
+                Err(e) => {
+                    eprintln!("   Resume with: runctl transfer resume {}", id);
+                    Err(e)
+                }
+            }
+        }
+        TransferCommands::Sessions { command } => match command {
+            SessionCommands::List => {
+                let mut sessions = load_sessions(&dir)?;
+                for session in &mut sessions {
+                    session.status = session.current_status();
+                }
+                if output_format == "json" {
+                    println!("{}", serde_json::to_string_pretty(&sessions)?);
+                    return Ok(());
+                }
+                if sessions.is_empty() {
+                    println!("No transfer sessions");
+                    return Ok(());
+                }
+                let mut table = Table::new();
+                table.set_header(vec![
+                    "ID",
+                    "Status",
+                    "Source",
+                    "Destination",
+                    "Progress",
+                    "Runs",
+                    "Updated",
+                    "Error",
+                ]);
+                for session in &sessions {
+                    table.add_row(vec![
+                        session.id.clone(),
+                        session.status.to_string(),
+                        session.source.clone(),
+                        session.destination.clone(),
+                        session.progress_summary(),
+                        session.runs.to_string(),
+                        session.updated_at.format("%Y-%m-%d %H:%M").to_string(),
+                        session.error.clone().unwrap_or_default(),
+                    ]);
+                }
+                println!("{}", table);
+                Ok(())
+            }
+            SessionCommands::Show { session_id } => {
+                let mut session = find_session(&dir, &session_id)?;
+                session.status = session.current_status();
+                if output_format == "json" {
+                    println!("{}", serde_json::to_string_pretty(&session)?);
+                    return Ok(());
+                }
+                println!(
+                    "{}: {} -> {}",
+                    session.id, session.source, session.destination
+                );
+                println!("   Status:   {}", session.status);
+                println!("   Progress: {}", session.progress_summary());
+                println!("   Runs:     {}", session.runs);
+                if let Some(error) = &session.error {
+                    println!("   Error:    {}", error);
+                }
+                let unfinished: Vec<_> = session.files.iter().filter(|(_, f)| !f.done).collect();
+                if !unfinished.is_empty() {
+                    let mut table = Table::new();
+                    table.set_header(vec!["File", "Size", "Attempts", "Error"]);
+                    for (name, file) in unfinished {
+                        table.add_row(vec![
+                            name.clone(),
+                            format_size(file.size),
+                            file.attempts.to_string(),
+                            file.error.clone().unwrap_or_default(),
+                        ]);
+                    }
+                    println!("{}", table);
+                }
+                if matches!(
+                    session.status,
+                    SessionStatus::Interrupted | SessionStatus::Failed
+                ) {
+                    println!("   Resume with: runctl transfer resume {}", session.id);
+                }
+                Ok(())
+            }
+            SessionCommands::Remove { session_id } => {
+                let session = find_session(&dir, &session_id)?;
+                ensure_not_running(&session)?;
+                std::fs::remove_file(TransferSession::path(&dir, &session.id))?;
+                if output_format == "json" {
+                    println!("{}", serde_json::json!({ "removed": session.id }));
+                } else {
+                    println!("Removed {}", session.id);
+                }
+                Ok(())
+            }
+        },
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit_idx = 0;
+
+    while size >= 1024.0 && unit_idx < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit_idx += 1;
+    }
+
+    format!("{:.2} {}", size, UNITS[unit_idx])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(source: &str, status: SessionStatus) -> TransferSession {
+        let mut session = TransferSession::new(source.to_string(), "s3://bucket/data/".to_string());
+        session.status = status;
+        session
+    }
+
+    #[test]
+    fn test_running_session_without_process_is_interrupted() {
+        let running = session("./data", SessionStatus::Running);
+        assert_eq!(running.resolve_status(true), SessionStatus::Running);
+        assert_eq!(running.resolve_status(false), SessionStatus::Interrupted);
+        // No recorded process at all
+        assert_eq!(running.current_status(), SessionStatus::Interrupted);
+
+        let failed = session("./data", SessionStatus::Failed);
+        assert_eq!(failed.resolve_status(false), SessionStatus::Failed);
+    }
+
+    #[test]
+    fn test_find_resumable_skips_completed_and_other_transfers() {
+        let dir = tempfile::tempdir().unwrap();
+        session("./data", SessionStatus::Completed)
+            .save(dir.path())
+            .unwrap();
+        session("./other", SessionStatus::Failed)
+            .save(dir.path())
+            .unwrap();
+        assert!(find_resumable(dir.path(), "./data", "s3://bucket/data/")
+            .unwrap()
+            .is_none());
+
+        let failed = session("./data", SessionStatus::Failed);
+        failed.save(dir.path()).unwrap();
+        let found = find_resumable(dir.path(), "./data", "s3://bucket/data/")
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id, failed.id);
+        assert_eq!(
+            find_session(dir.path(), &failed.id[..8]).unwrap().id,
+            failed.id
+        );
+    }
+
+    #[test]
+    fn test_handle_tracks_files_across_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let handle = SessionHandle::new(
+            session("./data", SessionStatus::Running),
+            dir.path().to_path_buf(),
+        );
+        handle.start_file("data/a.bin", 10);
+        handle.finish_file("data/a.bin", &Ok(()));
+        handle.start_file("data/b.bin", 20);
+        handle.finish_file(
+            "data/b.bin",
+            &Err(TrainctlError::S3("connection reset".to_string())),
+        );
+        handle.start_file("data/b.bin", 20);
+
+        assert!(handle.is_done("data/a.bin", 10));
+        // A file that changed size is transferred again
+        assert!(!handle.is_done("data/a.bin", 11));
+        assert!(!handle.is_done("data/b.bin", 20));
+
+        let session = handle.session();
+        assert_eq!(session.files_done(), 1);
+        assert_eq!(session.bytes_total(), 30);
+        assert_eq!(session.files["data/b.bin"].attempts, 2);
+        let failed: Vec<_> = session
+            .failed_files()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(failed, vec!["data/b.bin"]);
+
+        // The failure was written out right away
+        let saved = find_session(dir.path(), &session.id).unwrap();
+        assert!(saved.files["data/b.bin"].error.is_some());
+    }
+}
@@ -0,0 +1,296 @@
+//! Human-friendly durations and sizes
+//!
+//! One parser each for durations ("90s", "2m", "8h") and sizes ("500GB",
+//! "1TiB"), shared by clap value parsers and config deserialization. Bare
+//! numbers keep their old meaning (seconds, bytes or GiB depending on the
+//! field) so existing scripts and `.runctl.toml` files keep working.
+
+use serde::{Deserialize, Deserializer};
+
+const GIB: u64 = 1 << 30;
+
+/// Parse a size like `100MB`, `1.5G`, `512k` or a plain byte count
+///
+/// Units are binary (`1MB` = 1024 * 1024 bytes); the trailing `B` and an
+/// `i` (`MiB`) are optional.
+pub fn parse_size(value: &str) -> std::result::Result<u64, String> {
+    let trimmed = value.trim();
+    let upper = trimmed.to_ascii_uppercase();
+    let without_b = upper.strip_suffix('B').unwrap_or(&upper);
+    let without_i = without_b.strip_suffix('I').unwrap_or(without_b);
+    let (number, multiplier) = match without_i.chars().last() {
+        Some('K') => (&without_i[..without_i.len() - 1], 1u64 << 10),
+        Some('M') => (&without_i[..without_i.len() - 1], 1 << 20),
+        Some('G') => (&without_i[..without_i.len() - 1], 1 << 30),
+        Some('T') => (&without_i[..without_i.len() - 1], 1 << 40),
+        _ => (without_i, 1),
+    };
+    let number: f64 = number
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid size '{}' (expected e.g. 100MB, 2G)", value))?;
+    if !number.is_finite() || number < 0.0 {
+        return Err(format!("invalid size '{}'", value));
+    }
+    Ok((number * multiplier as f64) as u64)
+}
+
+/// Parse a transfer rate like `50MB/s`, `1G` or a plain byte count per second
+pub fn parse_rate(value: &str) -> std::result::Result<u64, String> {
+    let trimmed = value.trim();
+    let size = trimmed
+        .strip_suffix("/s")
+        .or_else(|| trimmed.strip_suffix("/S"))
+        .unwrap_or(trimmed);
+    let rate = parse_size(size)
+        .map_err(|_| format!("invalid rate '{}' (expected e.g. 50MB/s, 1G/s)", value))?;
+    if rate == 0 {
+        return Err(format!("rate '{}' must be positive", value));
+    }
+    Ok(rate)
+}
+
+/// Parse a volume size in whole GiB: `100` (GiB), `500GB` or `1TiB`
+pub fn parse_gib(value: &str) -> std::result::Result<i32, String> {
+    let trimmed = value.trim();
+    let gib = if trimmed.chars().all(|c| c.is_ascii_digit()) && !trimmed.is_empty() {
+        trimmed
+            .parse::<u64>()
+            .map_err(|_| format!("invalid size '{}'", value))?
+    } else {
+        let bytes = parse_size(trimmed)?;
+        if bytes % GIB != 0 {
+            return Err(format!(
+                "size '{}' is not a whole number of GiB (volumes are sized in GiB)",
+                value
+            ));
+        }
+        bytes / GIB
+    };
+    if gib == 0 {
+        return Err(format!("size '{}' must be at least 1GiB", value));
+    }
+    i32::try_from(gib).map_err(|_| format!("size '{}' is too large", value))
+}
+
+fn invalid_duration(value: &str) -> String {
+    format!(
+        "invalid duration '{}' (expected e.g. 30m, 8h; units s, m, h, d, w)",
+        value
+    )
+}
+
+/// Parse a duration with a unit: `45s`, `30m`, `8h`, `2d` or `1w`
+///
+/// # Examples
+/// ```
+/// use runctl::units::parse_duration;
+/// use std::time::Duration;
+///
+/// assert_eq!(parse_duration("30m").unwrap(), Duration::from_secs(1800));
+/// assert!(parse_duration("30").is_err());
+/// ```
+pub fn parse_duration(value: &str) -> std::result::Result<std::time::Duration, String> {
+    let trimmed = value.trim();
+    let invalid = || invalid_duration(value);
+    let unit_idx = trimmed
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (amount, unit) = trimmed.split_at(unit_idx);
+    let amount: u64 = amount.parse().map_err(|_| invalid())?;
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        "w" => 7 * 86400,
+        _ => return Err(invalid()),
+    };
+    if amount == 0 {
+        return Err(format!("duration '{}' must be positive", value));
+    }
+    Ok(std::time::Duration::from_secs(
+        amount.saturating_mul(multiplier),
+    ))
+}
+
+/// Parse a duration in seconds: `30` (seconds), `90s`, `2m` or `1h`
+pub fn parse_secs(value: &str) -> std::result::Result<u64, String> {
+    let trimmed = value.trim();
+    if !trimmed.is_empty() && trimmed.chars().all(|c| c.is_ascii_digit()) {
+        let secs: u64 = trimmed.parse().map_err(|_| invalid_duration(value))?;
+        if secs == 0 {
+            return Err(format!("duration '{}' must be positive", value));
+        }
+        return Ok(secs);
+    }
+    parse_duration(value).map(|d| d.as_secs())
+}
+
+/// Parse a duration in whole days: `30d`, `8w` or `720h`
+///
+/// For settings S3 and others count in days; values that aren't a whole
+/// number of days are rejected rather than rounded.
+pub fn parse_days(value: &str) -> std::result::Result<i32, String> {
+    let secs = parse_duration(value)?.as_secs();
+    if secs % 86400 != 0 {
+        return Err(format!(
+            "duration '{}' is not a whole number of days (e.g. 30d, 8w)",
+            value
+        ));
+    }
+    i32::try_from(secs / 86400).map_err(|_| format!("duration '{}' is too long", value))
+}
+
+/// Config value written either as a number or a string with units
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumberOrString {
+    Number(u64),
+    String(String),
+}
+
+/// `deserialize_with` for second counts (`interval_secs = 300` or `"5m"`)
+pub fn deserialize_secs<'de, D>(deserializer: D) -> std::result::Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match NumberOrString::deserialize(deserializer)? {
+        NumberOrString::Number(n) => Ok(n),
+        NumberOrString::String(s) => parse_secs(&s).map_err(serde::de::Error::custom),
+    }
+}
+
+/// `deserialize_with` for optional byte sizes (`part_size = 16777216` or `"16MB"`)
+pub fn deserialize_size_opt<'de, D>(deserializer: D) -> std::result::Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<NumberOrString>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(NumberOrString::Number(n)) => Ok(Some(n)),
+        Some(NumberOrString::String(s)) => {
+            parse_size(&s).map(Some).map_err(serde::de::Error::custom)
+        }
+    }
+}
+
+/// `deserialize_with` for optional rates in bytes per second (`bandwidth_limit = "50MB/s"`)
+pub fn deserialize_rate_opt<'de, D>(deserializer: D) -> std::result::Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<NumberOrString>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(NumberOrString::Number(n)) => Ok(Some(n)),
+        Some(NumberOrString::String(s)) => {
+            parse_rate(&s).map(Some).map_err(serde::de::Error::custom)
+        }
+    }
+}
+
+/// `deserialize_with` for optional GiB sizes (`data_volume_size = 500` or `"1TiB"`)
+pub fn deserialize_gib_opt<'de, D>(deserializer: D) -> std::result::Result<Option<i32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<NumberOrString>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(NumberOrString::Number(n)) => i32::try_from(n)
+            .map(Some)
+            .map_err(|_| serde::de::Error::custom(format!("size {} is too large", n))),
+        Some(NumberOrString::String(s)) => {
+            parse_gib(&s).map(Some).map_err(serde::de::Error::custom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("1k").unwrap(), 1024);
+        assert_eq!(parse_size("100MB").unwrap(), 100 << 20);
+        assert_eq!(parse_size("2GiB").unwrap(), 2 << 30);
+        assert_eq!(parse_size("1.5G").unwrap(), 3 << 29);
+        assert!(parse_size("lots").is_err());
+        assert!(parse_size("-1M").is_err());
+    }
+
+    #[test]
+    fn test_parse_rate() {
+        assert_eq!(parse_rate("50MB/s").unwrap(), 50 << 20);
+        assert_eq!(parse_rate("1G").unwrap(), 1 << 30);
+        assert_eq!(parse_rate("1048576").unwrap(), 1 << 20);
+        assert!(parse_rate("0/s").is_err());
+        assert!(parse_rate("fast").is_err());
+    }
+
+    #[test]
+    fn test_parse_gib() {
+        assert_eq!(parse_gib("100").unwrap(), 100);
+        assert_eq!(parse_gib("500GB").unwrap(), 500);
+        assert_eq!(parse_gib("1TiB").unwrap(), 1024);
+        assert_eq!(parse_gib("1.5T").unwrap(), 1536);
+        assert!(parse_gib("0").is_err());
+        assert!(parse_gib("512MB").is_err());
+        assert!(parse_gib("huge").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        use std::time::Duration;
+        assert_eq!(parse_duration("45s").unwrap(), Duration::from_secs(45));
+        assert_eq!(parse_duration("8h").unwrap(), Duration::from_secs(28800));
+        assert_eq!(parse_duration("2d").unwrap(), Duration::from_secs(172800));
+        assert_eq!(parse_duration("1w").unwrap(), Duration::from_secs(604800));
+        assert!(parse_duration("0m").is_err());
+        assert!(parse_duration("10").is_err()); // No unit
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("3y").is_err());
+    }
+
+    #[test]
+    fn test_parse_days() {
+        assert_eq!(parse_days("30d").unwrap(), 30);
+        assert_eq!(parse_days("8w").unwrap(), 56);
+        assert_eq!(parse_days("48h").unwrap(), 2);
+        assert!(parse_days("30").is_err()); // No unit
+        assert!(parse_days("0d").is_err());
+        assert!(parse_days("36h").is_err());
+        assert!(parse_days("90m").is_err());
+    }
+
+    #[test]
+    fn test_parse_secs() {
+        assert_eq!(parse_secs("30").unwrap(), 30);
+        assert_eq!(parse_secs("90s").unwrap(), 90);
+        assert_eq!(parse_secs("2m").unwrap(), 120);
+        assert_eq!(parse_secs("1h").unwrap(), 3600);
+        assert!(parse_secs("0").is_err());
+        assert!(parse_secs("2x").is_err());
+    }
+
+    #[test]
+    fn test_deserialize_from_toml() {
+        #[derive(Deserialize)]
+        struct Sample {
+            #[serde(deserialize_with = "deserialize_secs")]
+            interval: u64,
+            #[serde(default, deserialize_with = "deserialize_gib_opt")]
+            size: Option<i32>,
+        }
+        let sample: Sample = toml::from_str("interval = \"5m\"\nsize = \"1TiB\"").unwrap();
+        assert_eq!(sample.interval, 300);
+        assert_eq!(sample.size, Some(1024));
+        let sample: Sample = toml::from_str("interval = 10\nsize = 200").unwrap();
+        assert_eq!(sample.interval, 10);
+        assert_eq!(sample.size, Some(200));
+        let sample: Sample = toml::from_str("interval = 10").unwrap();
+        assert_eq!(sample.size, None);
+        assert!(toml::from_str::<Sample>("interval = \"soon\"").is_err());
+    }
+}
@@ -12,12 +12,15 @@
 //! Cost calculations use approximate 2024-2025 AWS pricing. Actual costs may vary
 //! by region, spot pricing, and discounts. The `get_instance_cost()` function
 //! provides a fallback estimate for unknown instance types based on naming patterns.
+//! Live prices, when fetched, come from `crate::aws_pricing`, which falls back to
+//! this table.
 //!
 //! ## Time Formatting
 //!
 //! Duration and runtime formatting uses human-readable strings (e.g., "1h 23m 45s").
 //! This is optimized for CLI output readability, not precision.
 
+use crate::config::Config;
 use crate::error::{Result, TrainctlError};
 use chrono::{DateTime, Utc};
 use std::path::Path;
@@ -36,6 +39,54 @@ pub fn ensure_dir(path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Quotes a value for a POSIX shell command line.
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// `host` as written before `:port` or `:path` (scp, rsync)
+///
+/// IPv6 literals are bracketed (`[2600:1f18::1]`); other hosts are unchanged.
+pub fn bracket_ipv6(host: &str) -> String {
+    if host.parse::<std::net::Ipv6Addr>().is_ok() {
+        format!("[{}]", host)
+    } else {
+        host.to_string()
+    }
+}
+
+/// Whether `name` can be used as a shell environment variable name
+pub fn is_env_name(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// `export NAME='value' && ` for each variable, to prefix a remote command
+///
+/// Values are quoted; names must pass `is_env_name`.
+pub fn env_exports(env: &[(String, String)]) -> String {
+    env.iter()
+        .map(|(name, value)| format!("export {}={} && ", name, shell_quote(value)))
+        .collect()
+}
+
+/// Parses an exit code file written by a background job (empty while it runs).
+pub fn parse_exit_code(content: &str) -> Result<Option<i32>> {
+    let content = content.trim();
+    if content.is_empty() {
+        return Ok(None);
+    }
+    content
+        .parse()
+        .map(Some)
+        .map_err(|_| TrainctlError::Validation {
+            field: "exit_code".to_string(),
+            reason: format!("unexpected exit code file contents: '{}'", content),
+        })
+}
+
 /// Formats a duration in seconds as a human-readable string (e.g., "1h 23m 45s").
 pub fn format_duration(secs: u64) -> String {
     let hours = secs / 3600;
@@ -102,7 +153,10 @@ pub async fn get_instance_cost_with_tracker(
         }
     }
 
-    // Fallback to calculation
+    // Fallback to calculation, with the live price when one is cached
+    #[cfg(feature = "aws")]
+    let cost_per_hour = crate::aws_pricing::hourly_price(instance_type, None, None);
+    #[cfg(not(feature = "aws"))]
     let cost_per_hour = get_instance_cost(instance_type);
     let accumulated = if is_running {
         calculate_accumulated_cost(cost_per_hour, launch_time)
@@ -211,6 +265,87 @@ pub fn get_instance_cost(instance_type: &str) -> f64 {
     }
 }
 
+/// Split `s3://bucket/key` into bucket and key
+pub fn parse_s3_path(s3_path: &str) -> Result<(String, String)> {
+    if !s3_path.starts_with("s3://") {
+        return Err(TrainctlError::S3(
+            "S3 path must start with s3://".to_string(),
+        ));
+    }
+
+    let path = &s3_path[5..];
+    let parts: Vec<&str> = path.splitn(2, '/').collect();
+
+    if parts.len() != 2 {
+        return Err(TrainctlError::S3(
+            "Invalid S3 path format. Expected s3://bucket/key".to_string(),
+        ));
+    }
+
+    Ok((parts[0].to_string(), parts[1].to_string()))
+}
+
+/// Get user identifier for tagging
+pub(crate) fn get_user_id(config: &Config) -> String {
+    // Try config first
+    if let Some(aws_cfg) = &config.aws {
+        if let Some(user_id) = &aws_cfg.user_id {
+            return user_id.clone();
+        }
+    }
+
+    // Auto-detect from username
+    if let Ok(username) = std::env::var("USER") {
+        return username;
+    }
+    if let Ok(username) = std::env::var("USERNAME") {
+        return username;
+    }
+
+    // Fallback
+    "unknown".to_string()
+}
+
+/// Get project name, deriving from current directory if not provided
+pub fn get_project_name(provided: Option<String>, config: &Config) -> String {
+    // Use provided value if given
+    if let Some(name) = provided {
+        return name;
+    }
+
+    // Try config
+    if let Some(aws_cfg) = &config.aws {
+        if let Some(project) = &aws_cfg.default_project_name {
+            return project.clone();
+        }
+    }
+
+    // Derive from current directory
+    if let Ok(current_dir) = std::env::current_dir() {
+        if let Some(dir_name) = current_dir.file_name() {
+            if let Some(name_str) = dir_name.to_str() {
+                // Sanitize directory name for use as project name
+                let sanitized = name_str
+                    .chars()
+                    .map(|c| {
+                        if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                            c
+                        } else {
+                            '-'
+                        }
+                    })
+                    .collect::<String>();
+                if !sanitized.is_empty() {
+                    return sanitized;
+                }
+            }
+        }
+    }
+
+    // Final fallback
+    "runctl-project".to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -224,6 +359,36 @@ mod tests {
         assert_eq!(format_duration(7200), "2h 0m 0s");
     }
 
+    #[test]
+    fn test_bracket_ipv6() {
+        assert_eq!(bracket_ipv6("2600:1f18::1"), "[2600:1f18::1]");
+        assert_eq!(bracket_ipv6("3.3.3.3"), "3.3.3.3");
+        assert_eq!(bracket_ipv6("bastion.example.com"), "bastion.example.com");
+    }
+
+    #[test]
+    fn test_env_exports() {
+        assert!(is_env_name("WANDB_PROJECT"));
+        assert!(is_env_name("_x1"));
+        assert!(!is_env_name("1X"));
+        assert!(!is_env_name("A-B"));
+        assert!(!is_env_name(""));
+        let env = vec![
+            ("A".to_string(), "1".to_string()),
+            ("B".to_string(), "it's".to_string()),
+        ];
+        assert_eq!(env_exports(&env), "export A='1' && export B='it'\\''s' && ");
+        assert_eq!(env_exports(&[]), "");
+    }
+
+    #[test]
+    fn test_parse_exit_code() {
+        assert_eq!(parse_exit_code("").unwrap(), None);
+        assert_eq!(parse_exit_code("0\n").unwrap(), Some(0));
+        assert_eq!(parse_exit_code(" 137 ").unwrap(), Some(137));
+        assert!(parse_exit_code("running").is_err());
+    }
+
     #[test]
     fn test_format_runtime() {
         let now = Utc::now();
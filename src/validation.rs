@@ -15,6 +15,13 @@
 //! - `validate_s3_path()` ensures S3 paths are properly formatted
 //! - All validators return `TrainctlError::Validation` with clear field/reason
 //!
+//! ## Clap Integration
+//!
+//! The `*_arg` functions wrap validators as clap value parsers
+//! (`#[arg(value_parser = validation::instance_type_arg)]`), so bad values are
+//! rejected at parse time with the validator's reason instead of failing deep
+//! inside an AWS call.
+//!
 //! ## When to Validate
 //!
 //! Validate inputs:
@@ -234,7 +241,9 @@ pub fn validate_path_path(path: &std::path::Path) -> Result<()> {
 
 /// Validate S3 path format
 ///
-/// S3 paths must be in format s3://bucket/key
+/// S3 paths must be in format s3://bucket/key. Bucket names are 3-63
+/// lowercase letters, digits, dots and hyphens, starting and ending with a
+/// letter or digit.
 pub fn validate_s3_path(s3_path: &str) -> Result<()> {
     if !s3_path.starts_with("s3://") {
         return Err(TrainctlError::Validation {
@@ -271,9 +280,242 @@ pub fn validate_s3_path(s3_path: &str) -> Result<()> {
         });
     }
 
+    let bucket = parts[0];
+    if !bucket
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '-')
+    {
+        return Err(TrainctlError::Validation {
+            field: "s3_path".to_string(),
+            reason: format!(
+                "S3 bucket name can only contain lowercase letters, digits, dots, and hyphens, got: {}",
+                bucket
+            ),
+        });
+    }
+    let alnum_edge = |c: Option<char>| c.is_some_and(|c| c.is_ascii_alphanumeric());
+    if !alnum_edge(bucket.chars().next()) || !alnum_edge(bucket.chars().last()) {
+        return Err(TrainctlError::Validation {
+            field: "s3_path".to_string(),
+            reason: format!(
+                "S3 bucket name must start and end with a letter or digit, got: {}",
+                bucket
+            ),
+        });
+    }
+    if bucket.contains("..") {
+        return Err(TrainctlError::Validation {
+            field: "s3_path".to_string(),
+            reason: format!("S3 bucket name cannot contain '..', got: {}", bucket),
+        });
+    }
+
     Ok(())
 }
 
+/// EC2 instance families runctl recognises
+///
+/// Covers current general purpose, compute, memory, storage and accelerated
+/// families. Add new families here as AWS releases them.
+#[rustfmt::skip]
+pub const KNOWN_INSTANCE_FAMILIES: &[&str] = &[
+    // General purpose
+    "a1", "m4", "m5", "m5a", "m5ad", "m5d", "m5dn", "m5n", "m5zn", "m6a", "m6g", "m6gd", "m6i",
+    "m6id", "m6idn", "m6in", "m7a", "m7g", "m7gd", "m7i", "m7i-flex", "m8g", "mac1", "mac2", "t2",
+    "t3", "t3a", "t4g",
+    // Compute optimized
+    "c4", "c5", "c5a", "c5ad", "c5d", "c5n", "c6a", "c6g", "c6gd", "c6gn", "c6i", "c6id", "c6in",
+    "c7a", "c7g", "c7gd", "c7gn", "c7i", "c7i-flex", "c8g", "hpc6a", "hpc6id", "hpc7a", "hpc7g",
+    // Memory optimized
+    "r4", "r5", "r5a", "r5ad", "r5b", "r5d", "r5dn", "r5n", "r6a", "r6g", "r6gd", "r6i", "r6id",
+    "r6idn", "r6in", "r7a", "r7g", "r7gd", "r7i", "r7iz", "r8g", "u-3tb1", "u-6tb1", "u-12tb1",
+    "x1", "x1e", "x2gd", "x2idn", "x2iedn", "x2iezn", "x8g", "z1d",
+    // Storage optimized
+    "d2", "d3", "d3en", "h1", "i3", "i3en", "i4g", "i4i", "im4gn", "is4gen",
+    // Accelerated computing
+    "dl1", "dl2q", "f1", "f2", "g3", "g3s", "g4ad", "g4dn", "g5", "g5g", "g6", "g6e", "gr6",
+    "inf1", "inf2", "p2", "p3", "p3dn", "p4d", "p4de", "p5", "p5e", "p5en", "trn1", "trn1n",
+    "trn2", "vt1",
+];
+
+fn is_instance_size(size: &str) -> bool {
+    const NAMED: &[&str] = &[
+        "nano", "micro", "small", "medium", "large", "xlarge", "metal",
+    ];
+    if NAMED.contains(&size) {
+        return true;
+    }
+    // 2xlarge, 24xlarge, metal-24xl, metal-48xl
+    if let Some(n) = size.strip_suffix("xlarge") {
+        return !n.is_empty() && n.chars().all(|c| c.is_ascii_digit());
+    }
+    if let Some(n) = size
+        .strip_prefix("metal-")
+        .and_then(|rest| rest.strip_suffix("xl"))
+    {
+        return !n.is_empty() && n.chars().all(|c| c.is_ascii_digit());
+    }
+    false
+}
+
+/// Validate EC2 instance type format (`family.size`, e.g. `g5.xlarge`)
+///
+/// The family isn't checked against `KNOWN_INSTANCE_FAMILIES`, so types AWS
+/// releases after this build still work; see `unknown_family_warning`.
+///
+/// # Examples
+/// ```
+/// use runctl::validation::validate_instance_type;
+///
+/// assert!(validate_instance_type("g5.xlarge").is_ok());
+/// assert!(validate_instance_type("p4d.24xlarge").is_ok());
+/// assert!(validate_instance_type("g5xlarge").is_err());
+/// assert!(validate_instance_type("g5.huge").is_err());
+/// ```
+pub fn validate_instance_type(instance_type: &str) -> Result<()> {
+    let invalid = |reason: String| TrainctlError::Validation {
+        field: "instance_type".to_string(),
+        reason,
+    };
+    let Some((family, size)) = instance_type.split_once('.') else {
+        return Err(invalid(format!(
+            "Instance type must look like 'family.size' (e.g. g5.xlarge), got: {}",
+            instance_type
+        )));
+    };
+    if family.is_empty() {
+        return Err(invalid(format!(
+            "Instance type is missing its family, got: {}",
+            instance_type
+        )));
+    }
+    if !is_instance_size(size) {
+        return Err(invalid(format!(
+            "Unknown instance size '{}' in {} (expected e.g. large, xlarge, 2xlarge, metal)",
+            size, instance_type
+        )));
+    }
+    Ok(())
+}
+
+/// Warning for an instance family not in `KNOWN_INSTANCE_FAMILIES`
+///
+/// Suggests known families with the same leading letter, which catches most
+/// typos. `None` for known families and malformed types.
+pub fn unknown_family_warning(instance_type: &str) -> Option<String> {
+    let family = instance_type.split_once('.')?.0;
+    let first = family.chars().next()?;
+    if KNOWN_INSTANCE_FAMILIES.contains(&family) {
+        return None;
+    }
+    let similar: Vec<&str> = KNOWN_INSTANCE_FAMILIES
+        .iter()
+        .copied()
+        .filter(|known| known.starts_with(first))
+        .collect();
+    let hint = if similar.is_empty() {
+        String::new()
+    } else {
+        format!(
+            " (known families starting with '{}': {})",
+            first,
+            similar.join(", ")
+        )
+    };
+    Some(format!(
+        "Unknown instance family '{}' in {}{}",
+        family, instance_type, hint
+    ))
+}
+
+/// Highest spot max price accepted, in USD per hour
+///
+/// Well above the on-demand price of any instance; larger values are almost
+/// certainly typos.
+pub const MAX_SPOT_PRICE: f64 = 100.0;
+
+/// Validate a spot max price (USD per hour) and return it as a number
+pub fn validate_spot_price(price: &str) -> Result<f64> {
+    let invalid = |reason: String| TrainctlError::Validation {
+        field: "spot_max_price".to_string(),
+        reason,
+    };
+    let value: f64 = price.trim().parse().map_err(|_| {
+        invalid(format!(
+            "Spot price must be a number in USD per hour (e.g. 0.50), got: {}",
+            price
+        ))
+    })?;
+    if !value.is_finite() || value <= 0.0 {
+        return Err(invalid(format!(
+            "Spot price must be greater than 0, got: {}",
+            price
+        )));
+    }
+    if value > MAX_SPOT_PRICE {
+        return Err(invalid(format!(
+            "Spot price must be at most ${:.0}/hour, got: {}",
+            MAX_SPOT_PRICE, price
+        )));
+    }
+    Ok(value)
+}
+
+/// Validate a 5-field cron expression (`minute hour day-of-month month day-of-week`)
+///
+/// Also accepts `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly`.
+/// This is `schedule::CronExpr::parse`, so whatever validates here is what
+/// the scheduler runs.
+///
+/// # Examples
+/// ```
+/// use runctl::validation::validate_cron;
+///
+/// assert!(validate_cron("0 2 * * *").is_ok());
+/// assert!(validate_cron("*/15 9-17 * * MON-FRI").is_ok());
+/// assert!(validate_cron("0 25 * * *").is_err());
+/// ```
+pub fn validate_cron(expr: &str) -> Result<()> {
+    crate::schedule::CronExpr::parse(expr).map(|_| ())
+}
+
+/// Turn a validator error into a clap value-parser message
+fn arg_error(error: TrainctlError) -> String {
+    match error {
+        TrainctlError::Validation { reason, .. } => reason,
+        other => other.to_string(),
+    }
+}
+
+/// Clap value parser for EC2 instance types
+///
+/// An unknown family only warns; AWS rejects types that don't exist.
+pub fn instance_type_arg(value: &str) -> std::result::Result<String, String> {
+    validate_instance_type(value).map_err(arg_error)?;
+    if let Some(warning) = unknown_family_warning(value) {
+        eprintln!("WARNING: {}", warning);
+    }
+    Ok(value.to_string())
+}
+
+/// Clap value parser for `s3://bucket/key` URIs
+pub fn s3_uri_arg(value: &str) -> std::result::Result<String, String> {
+    validate_s3_path(value).map_err(arg_error)?;
+    Ok(value.to_string())
+}
+
+/// Clap value parser for spot max prices (kept as the string AWS expects)
+pub fn spot_price_arg(value: &str) -> std::result::Result<String, String> {
+    validate_spot_price(value).map_err(arg_error)?;
+    Ok(value.trim().to_string())
+}
+
+/// Clap value parser for cron expressions
+pub fn cron_arg(value: &str) -> std::result::Result<String, String> {
+    validate_cron(value).map_err(arg_error)?;
+    Ok(value.trim().to_string())
+}
+
 /// Validate volume size (in GB)
 ///
 /// Volume sizes must be between 1 GB and 16384 GB (16 TB).
@@ -346,6 +588,69 @@ mod tests {
         assert!(validate_s3_path("invalid").is_err()); // Wrong format
     }
 
+    #[test]
+    fn test_validate_s3_bucket_names() {
+        assert!(validate_s3_path("s3://my-bucket.logs/key").is_ok());
+        assert!(validate_s3_path("s3://My-Bucket/key").is_err()); // Uppercase
+        assert!(validate_s3_path("s3://bucket_name/key").is_err()); // Underscore
+        assert!(validate_s3_path("s3://-bucket/key").is_err()); // Leading hyphen
+        assert!(validate_s3_path("s3://bucket../key").is_err());
+    }
+
+    #[test]
+    fn test_validate_instance_type() {
+        assert!(validate_instance_type("t3.medium").is_ok());
+        assert!(validate_instance_type("g4dn.12xlarge").is_ok());
+        assert!(validate_instance_type("m7i-flex.large").is_ok());
+        assert!(validate_instance_type("c6i.metal").is_ok());
+        assert!(validate_instance_type("m7i.metal-24xl").is_ok());
+        assert!(validate_instance_type("g5").is_err()); // No size
+        assert!(validate_instance_type("g5.huge").is_err()); // Unknown size
+        assert!(validate_instance_type("g5.xxlarge").is_err());
+        assert!(validate_instance_type(".large").is_err());
+        // Unknown families are allowed, with a warning
+        assert!(validate_instance_type("gx9.xlarge").is_ok());
+        let warning = unknown_family_warning("gx9.xlarge").unwrap();
+        assert!(warning.contains("g4dn"), "{}", warning); // Suggests similar families
+        assert!(unknown_family_warning("g5.xlarge").is_none());
+    }
+
+    #[test]
+    fn test_validate_spot_price() {
+        assert_eq!(validate_spot_price("0.50").unwrap(), 0.5);
+        assert!(validate_spot_price("12").is_ok());
+        assert!(validate_spot_price("0").is_err());
+        assert!(validate_spot_price("-1").is_err());
+        assert!(validate_spot_price("500").is_err());
+        assert!(validate_spot_price("cheap").is_err());
+    }
+
+    #[test]
+    fn test_validate_cron() {
+        assert!(validate_cron("0 2 * * *").is_ok());
+        assert!(validate_cron("*/15 * * * *").is_ok());
+        assert!(validate_cron("0,30 9-17 1-15 JAN-JUN mon-fri").is_ok());
+        assert!(validate_cron("10-40/10 0 * * 7").is_ok());
+        assert!(validate_cron("@daily").is_ok());
+        assert!(validate_cron("0 2 * *").is_err()); // 4 fields
+        assert!(validate_cron("60 * * * *").is_err());
+        assert!(validate_cron("0 0 0 * *").is_err()); // Day 0
+        assert!(validate_cron("5/10 * * * *").is_ok()); // From 5 to the end of the range
+        assert!(validate_cron("*/0 * * * *").is_err());
+        assert!(validate_cron("0 5-2 * * *").is_err()); // Reversed range
+    }
+
+    #[test]
+    fn test_arg_parsers() {
+        assert!(instance_type_arg("g5.xlarge").is_ok());
+        assert_eq!(
+            s3_uri_arg("bucket/key").unwrap_err(),
+            "S3 path must start with 's3://', got: bucket/key"
+        );
+        assert_eq!(spot_price_arg(" 0.25 ").unwrap(), "0.25");
+        assert!(cron_arg("* * *").is_err());
+    }
+
     #[test]
     fn test_validate_volume_size() {
         assert!(validate_volume_size(1).is_ok());
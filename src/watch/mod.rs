@@ -0,0 +1,767 @@
+//! Live dashboard for a single training job
+//!
+//! `runctl top` gives a fleet-level view. `runctl watch <job-id>` focuses on
+//! one run and shows it on a single ratatui screen:
+//!
+//! - **Header**: platform, status, elapsed time, cost so far, and ETA
+//! - **Metrics**: the latest epoch/step/loss (and other `key=value` numbers)
+//!   parsed from the training log, plus a loss sparkline
+//! - **Utilization**: CPU, memory, and GPU gauges
+//! - **Log**: a tail of the training log
+//!
+//! A job ID is either an EC2 instance ID (`i-...`) running a `runctl aws
+//! train` job, or a local session ID from `.runctl/sessions` (a unique
+//! prefix is enough).
+//!
+//! ## Controls
+//!
+//! - `q` or `Esc`: Quit
+//! - `r`: Refresh now
+//!
+//! - `Up`/`Down`, `PgUp`/`PgDn`: Scroll the log; `End` to follow again
+//!
+//! With `--output json` a single snapshot is printed instead of the TUI.
+//! The TUI itself needs the `dashboard` cargo feature (on by default).
+//!
+//! Sessions can also run detached under a background daemon and be
+//! re-attached later; see [`session`].
+
+use crate::config::Config;
+use crate::error::{Result, TrainctlError};
+use crate::training::{TrainingSession, TrainingStatus};
+#[cfg(feature = "aws")]
+use aws_config::BehaviorVersion;
+#[cfg(feature = "aws")]
+use aws_sdk_ec2::Client as Ec2Client;
+#[cfg(feature = "aws")]
+use aws_sdk_s3::Client as S3Client;
+#[cfg(feature = "aws")]
+use aws_sdk_ssm::Client as SsmClient;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+use sysinfo::{Pid, System};
+
+pub mod session;
+#[cfg(feature = "dashboard")]
+mod tui;
+
+#[cfg(feature = "dashboard")]
+use tui::run_tui;
+
+pub use session::{Scrollback, WatchEvent, WatchEventKind};
+
+/// Number of log lines kept for the log pane and metric parsing
+const LOG_TAIL_LINES: usize = 200;
+
+/// Number of loss values kept for the sparkline
+const LOSS_HISTORY_LEN: usize = 120;
+
+/// Separator between the status block and the log tail in the SSM output
+#[cfg_attr(not(feature = "aws"), allow(dead_code))]
+const LOG_SEPARATOR: &str = "---RUNCTL-LOG---";
+
+/// Training metrics parsed from the log tail
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    /// Current and total epochs (`epoch 3/10`)
+    pub epoch: Option<(u64, u64)>,
+    /// Current and total steps (`step 1200/5000`)
+    pub step: Option<(u64, u64)>,
+    /// Latest `loss` value
+    pub loss: Option<f64>,
+    /// Latest value for every other `key=value` / `key: value` number
+    pub values: BTreeMap<String, f64>,
+}
+
+impl MetricsSnapshot {
+    /// Fraction of training completed, preferring steps over epochs
+    pub fn progress(&self) -> Option<f64> {
+        self.step
+            .or(self.epoch)
+            .filter(|(_, total)| *total > 0)
+            .map(|(current, total)| (current as f64 / total as f64).min(1.0))
+    }
+}
+
+fn progress_regex(name: &str) -> Regex {
+    Regex::new(&format!(
+        r"(?i)\b{}\s*[:=]?\s*\[?(\d+)\s*(?:/|of)\s*(\d+)",
+        name
+    ))
+    .expect("static progress regex")
+}
+
+fn epoch_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| progress_regex("epoch"))
+}
+
+fn step_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| progress_regex("(?:step|iter|iteration|batch)"))
+}
+
+fn value_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"\b([A-Za-z_][A-Za-z0-9_./]*)\s*[:=]\s*([-+]?(?:\d+\.?\d*|\.\d+)(?:[eE][-+]?\d+)?)",
+        )
+        .expect("static value regex")
+    })
+}
+
+fn parse_progress(re: &Regex, line: &str) -> Option<(u64, u64)> {
+    let caps = re.captures_iter(line).last()?;
+    Some((caps[1].parse().ok()?, caps[2].parse().ok()?))
+}
+
+/// Parse training metrics from log lines; later lines win
+pub fn parse_metrics(lines: &[String]) -> MetricsSnapshot {
+    let mut metrics = MetricsSnapshot::default();
+    for line in lines {
+        if let Some(epoch) = parse_progress(epoch_regex(), line) {
+            metrics.epoch = Some(epoch);
+        }
+        if let Some(step) = parse_progress(step_regex(), line) {
+            metrics.step = Some(step);
+        }
+        for caps in value_regex().captures_iter(line) {
+            let key = caps[1].to_lowercase();
+            let Ok(value) = caps[2].parse::<f64>() else {
+                continue;
+            };
+            if key == "loss" || key == "train_loss" {
+                metrics.loss = Some(value);
+            } else if key != "epoch" && key != "step" {
+                metrics.values.insert(key, value);
+            }
+        }
+    }
+    metrics
+}
+
+/// Loss values in log order, for the sparkline
+pub fn loss_history(lines: &[String]) -> Vec<f64> {
+    lines
+        .iter()
+        .filter_map(|line| parse_metrics(std::slice::from_ref(line)).loss)
+        .collect()
+}
+
+/// Estimate remaining time by extrapolating elapsed time over progress
+pub fn estimate_eta(elapsed: Duration, progress: f64) -> Option<Duration> {
+    if !(progress > 0.0 && progress < 1.0) {
+        return None;
+    }
+    Some(elapsed.mul_f64((1.0 - progress) / progress))
+}
+
+/// Split the SSM status/log output into job status and log lines
+#[cfg_attr(not(feature = "aws"), allow(dead_code))]
+fn parse_remote_tail(output: &str) -> (Option<String>, Option<i32>, Vec<String>) {
+    let (head, log) = output.split_once(LOG_SEPARATOR).unwrap_or((output, ""));
+    let mut status = None;
+    let mut exit_code = None;
+    for line in head.lines() {
+        if let Some(s) = line.trim().strip_prefix("STATUS:") {
+            status = Some(s.to_string());
+        } else if let Some(code) = line.trim().strip_prefix("EXIT:") {
+            exit_code = code.trim().parse().ok();
+        }
+    }
+    let lines = log
+        .trim_start_matches('\n')
+        .lines()
+        .map(str::to_string)
+        .collect();
+    (status, exit_code, lines)
+}
+
+/// Point-in-time view of a job (also the `--output json` shape)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JobSnapshot {
+    pub job_id: String,
+    pub platform: String,
+    pub status: String,
+    pub started_at: Option<DateTime<Utc>>,
+    pub elapsed_secs: Option<u64>,
+    pub cost_per_hour: f64,
+    pub cost_so_far: f64,
+    pub eta_secs: Option<u64>,
+    pub cpu_percent: Option<f64>,
+    pub memory_percent: Option<f64>,
+    pub gpu_utilization: Option<f64>,
+    pub gpu_memory_percent: Option<f64>,
+    pub metrics: MetricsSnapshot,
+    pub loss_history: Vec<f64>,
+    pub log_tail: Vec<String>,
+    /// Last refresh error, shown in the header instead of aborting the TUI
+    pub error: Option<String>,
+}
+
+impl JobSnapshot {
+    fn apply_log(&mut self, lines: Vec<String>) {
+        self.metrics = parse_metrics(&lines);
+        let mut history = loss_history(&lines);
+        if history.len() > LOSS_HISTORY_LEN {
+            history.drain(..history.len() - LOSS_HISTORY_LEN);
+        }
+        self.loss_history = history;
+        self.log_tail = lines;
+
+        self.elapsed_secs = self
+            .started_at
+            .map(|start| (Utc::now() - start).num_seconds().max(0) as u64);
+        self.eta_secs = match (self.elapsed_secs, self.metrics.progress()) {
+            (Some(elapsed), Some(progress)) if self.status == "running" => {
+                estimate_eta(Duration::from_secs(elapsed), progress).map(|d| d.as_secs())
+            }
+            _ => None,
+        };
+    }
+}
+
+/// Where a job runs and how to sample it
+pub(crate) enum JobSource {
+    #[cfg(feature = "aws")]
+    Aws {
+        instance_id: String,
+        ec2_client: Ec2Client,
+        ssm_client: SsmClient,
+        s3_client: S3Client,
+    },
+    Local {
+        sessions_dir: PathBuf,
+        session_id: String,
+        // Boxed: sysinfo's System is large and kept across refreshes
+        system: Box<System>,
+    },
+}
+
+/// Find the local session whose ID equals or uniquely starts with `job_id`
+pub(crate) fn resolve_local_session(sessions_dir: &Path, job_id: &str) -> Result<TrainingSession> {
+    let sessions = TrainingSession::list_sessions(sessions_dir)?;
+    if let Some(session) = sessions.iter().find(|s| s.id == job_id) {
+        return Ok(session.clone());
+    }
+    let mut matches: Vec<_> = sessions
+        .into_iter()
+        .filter(|s| s.id.starts_with(job_id))
+        .collect();
+    if matches.len() > 1 {
+        return Err(TrainctlError::Validation {
+            field: "job_id".to_string(),
+            reason: format!(
+                "'{}' matches {} local sessions; use a longer prefix",
+                job_id,
+                matches.len()
+            ),
+        });
+    }
+    matches
+        .pop()
+        .ok_or_else(|| TrainctlError::ResourceNotFound {
+            resource_type: "job".to_string(),
+            resource_id: job_id.to_string(),
+        })
+}
+
+fn read_log_tail(path: &Path, max_lines: usize) -> Vec<String> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines.len().saturating_sub(max_lines);
+    lines[start..].iter().map(|l| l.to_string()).collect()
+}
+
+#[cfg(feature = "aws")]
+fn instance_tag<'a>(instance: &'a aws_sdk_ec2::types::Instance, key: &str) -> Option<&'a str> {
+    instance
+        .tags()
+        .iter()
+        .find(|t| t.key() == Some(key))
+        .and_then(|t| t.value())
+}
+
+#[cfg(feature = "aws")]
+async fn refresh_aws(
+    snapshot: &mut JobSnapshot,
+    instance_id: &str,
+    ec2_client: &Ec2Client,
+    ssm_client: &SsmClient,
+    s3_client: &S3Client,
+    config: &Config,
+) -> Result<()> {
+    let response = ec2_client
+        .describe_instances()
+        .instance_ids(instance_id)
+        .send()
+        .await
+        .map_err(|e| TrainctlError::Aws(format!("Failed to describe instance: {}", e)))?;
+    let instance = response
+        .reservations()
+        .iter()
+        .flat_map(|r| r.instances())
+        .find(|i| i.instance_id() == Some(instance_id))
+        .ok_or_else(|| TrainctlError::ResourceNotFound {
+            resource_type: "instance".to_string(),
+            resource_id: instance_id.to_string(),
+        })?;
+
+    let state = instance
+        .state()
+        .and_then(|s| s.name())
+        .map(|n| n.as_str().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let instance_type = instance
+        .instance_type()
+        .map(|t| t.as_str().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let launch_time = instance
+        .launch_time()
+        .and_then(|t| DateTime::from_timestamp(t.secs(), 0));
+    snapshot.started_at = launch_time;
+
+    crate::aws_pricing::prefetch(ec2_client, std::iter::once(instance)).await;
+    // Keep the tracked cost right if the instance was stopped or restarted elsewhere
+    if let Some(tracker) = &config.resource_tracker {
+        crate::aws::reconcile_instance(tracker, instance).await;
+    }
+    let (cost_per_hour, cost_so_far) = crate::utils::get_instance_cost_with_tracker(
+        config.resource_tracker.as_deref(),
+        instance_id,
+        &instance_type,
+        launch_time,
+        state == "running",
+    )
+    .await;
+    snapshot.cost_per_hour = cost_per_hour;
+    snapshot.cost_so_far = cost_so_far;
+
+    if state != "running" {
+        snapshot.status = state;
+        snapshot.apply_log(Vec::new());
+        return Ok(());
+    }
+
+    let user = crate::aws::resolve_ssh_user(ec2_client, instance, None).await;
+    let project_name = instance_tag(instance, "Project")
+        .or_else(|| instance_tag(instance, "runctl:project"))
+        .unwrap_or("runctl");
+    let project_dir = format!("/home/{}/{}", user, project_name);
+
+    let cmd = format!(
+        "cd {dir} 2>/dev/null || exit 0; \
+         if [ -s training_complete.txt ]; then echo STATUS:completed; \
+         elif [ -f training.pid ] && ps -p $(cat training.pid) >/dev/null 2>&1; then echo STATUS:running; \
+         elif [ -f training.log ]; then echo STATUS:stopped; \
+         else echo STATUS:pending; fi; \
+         [ -f training_exit_code.txt ] && echo EXIT:$(cat training_exit_code.txt); \
+         echo {sep}; tail -n {n} training.log 2>/dev/null",
+        dir = project_dir,
+        sep = LOG_SEPARATOR,
+        n = LOG_TAIL_LINES
+    );
+    let output = crate::aws_utils::execute_ssm_command(ssm_client, instance_id, &cmd).await?;
+    let (status, exit_code, lines) = parse_remote_tail(&output);
+    snapshot.status = match (status.as_deref(), exit_code) {
+        (Some("completed" | "stopped"), Some(code)) if code != 0 => {
+            format!("failed (exit {})", code)
+        }
+        // The process exited cleanly without writing the completion marker
+        (Some("stopped"), Some(0)) => "completed".to_string(),
+        (Some(s), _) => s.to_string(),
+        (None, _) => "unknown".to_string(),
+    };
+
+    // A fresh `runctl agent` report saves the metrics SSM round-trip
+    if let Some(agent) = crate::agent::fetch_fresh_status(config, s3_client, instance_id).await {
+        snapshot.cpu_percent = agent.cpu_percent;
+        snapshot.memory_percent = agent.memory_percent;
+        snapshot.gpu_utilization = agent.gpu_utilization;
+        snapshot.gpu_memory_percent = agent.gpu_memory_percent;
+        snapshot.apply_log(lines);
+        return Ok(());
+    }
+
+    match crate::diagnostics::get_instance_resource_usage(ssm_client, instance_id).await {
+        Ok(usage) => {
+            snapshot.cpu_percent = Some(usage.cpu_percent);
+            snapshot.memory_percent = Some(usage.memory_percent);
+            let gpus = usage.gpu_info.map(|g| g.gpus).unwrap_or_default();
+            if !gpus.is_empty() {
+                let n = gpus.len() as f64;
+                snapshot.gpu_utilization =
+                    Some(gpus.iter().map(|g| g.utilization_percent).sum::<f64>() / n);
+                snapshot.gpu_memory_percent =
+                    Some(gpus.iter().map(|g| g.memory_percent).sum::<f64>() / n);
+            }
+        }
+        Err(e) => tracing::debug!("Failed to read resource usage: {}", e),
+    }
+
+    snapshot.apply_log(lines);
+    Ok(())
+}
+
+fn refresh_local(
+    snapshot: &mut JobSnapshot,
+    sessions_dir: &Path,
+    session_id: &str,
+    system: &mut System,
+    log_override: Option<&Path>,
+) -> Result<()> {
+    let session = TrainingSession::load(sessions_dir, session_id)?;
+    snapshot.started_at = Some(session.started_at);
+
+    system.refresh_all();
+    let process = session
+        .pid
+        .and_then(|pid| system.process(Pid::from_u32(pid)));
+    snapshot.status = match (&session.status, process) {
+        (TrainingStatus::Running, Some(_)) => "running".to_string(),
+        (TrainingStatus::Running, None) => "exited".to_string(),
+        (TrainingStatus::Completed, _) => "completed".to_string(),
+        (TrainingStatus::Failed(reason), _) => format!("failed ({})", reason),
+        (TrainingStatus::Interrupted, _) => "interrupted".to_string(),
+        (TrainingStatus::Launching, _) => "launching".to_string(),
+        (TrainingStatus::RolledBack, _) => "rolled back".to_string(),
+    };
+    match process {
+        Some(process) => {
+            let total = system.total_memory().max(1) as f64;
+            snapshot.cpu_percent = Some(process.cpu_usage() as f64);
+            snapshot.memory_percent = Some(process.memory() as f64 / total * 100.0);
+        }
+        None => {
+            snapshot.cpu_percent = None;
+            snapshot.memory_percent = None;
+        }
+    }
+
+    let lines = log_override
+        .or(session.log_file.as_deref())
+        .map(|path| read_log_tail(path, LOG_TAIL_LINES))
+        .unwrap_or_default();
+    snapshot.apply_log(lines);
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "aws"), allow(unused_variables))]
+pub(crate) async fn refresh(
+    snapshot: &mut JobSnapshot,
+    source: &mut JobSource,
+    config: &Config,
+    log_override: Option<&Path>,
+) {
+    let result = match source {
+        #[cfg(feature = "aws")]
+        JobSource::Aws {
+            instance_id,
+            ec2_client,
+            ssm_client,
+            s3_client,
+        } => {
+            refresh_aws(
+                snapshot,
+                instance_id,
+                ec2_client,
+                ssm_client,
+                s3_client,
+                config,
+            )
+            .await
+        }
+        JobSource::Local {
+            sessions_dir,
+            session_id,
+            system,
+        } => refresh_local(snapshot, sessions_dir, session_id, system, log_override),
+    };
+    snapshot.error = result.err().map(|e| e.to_string());
+}
+
+#[cfg_attr(not(feature = "aws"), allow(unused_variables))]
+pub(crate) async fn resolve_job(job_id: &str, config: &Config) -> Result<(JobSnapshot, JobSource)> {
+    #[cfg(not(feature = "aws"))]
+    if job_id.starts_with("i-") {
+        return Err(crate::error::ConfigError::FeatureNotCompiled("aws".to_string()).into());
+    }
+    #[cfg(feature = "aws")]
+    if job_id.starts_with("i-") {
+        crate::validation::validate_instance_id(job_id)?;
+        let region = config
+            .aws
+            .as_ref()
+            .map(|a| a.region.clone())
+            .unwrap_or_else(|| "us-east-1".to_string());
+        let sdk_config = aws_config::defaults(BehaviorVersion::latest())
+            .region(aws_sdk_ec2::config::Region::new(region))
+            .load()
+            .await;
+        let snapshot = JobSnapshot {
+            job_id: job_id.to_string(),
+            platform: "aws".to_string(),
+            ..Default::default()
+        };
+        return Ok((
+            snapshot,
+            JobSource::Aws {
+                instance_id: job_id.to_string(),
+                ec2_client: Ec2Client::new(&sdk_config),
+                ssm_client: SsmClient::new(&sdk_config),
+                s3_client: S3Client::new(&sdk_config),
+            },
+        ));
+    }
+
+    let sessions_dir = PathBuf::from(".runctl");
+    let session = resolve_local_session(&sessions_dir, job_id)?;
+    let snapshot = JobSnapshot {
+        job_id: session.id.clone(),
+        platform: session.platform.clone(),
+        ..Default::default()
+    };
+    Ok((
+        snapshot,
+        JobSource::Local {
+            sessions_dir,
+            session_id: session.id,
+            system: Box::new(System::new_all()),
+        },
+    ))
+}
+
+/// How `runctl watch` runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchMode {
+    /// Sample the job and show the TUI in the foreground
+    Interactive,
+    /// Start a background daemon for the job and return
+    Detach {
+        /// Config file the daemon should load (the CLI's `--config`)
+        config_path: Option<PathBuf>,
+    },
+    /// Show a detached session's scrollback and follow it
+    Attach,
+    /// Stop a detached session's daemon
+    Stop,
+    /// Run as the daemon itself (spawned by `Detach`)
+    DaemonWorker,
+}
+
+/// What the TUI reads each refresh
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+enum Feed<'a> {
+    /// Sample the job directly
+    Live {
+        source: JobSource,
+        log: Option<&'a Path>,
+    },
+    /// Re-read the scrollback a daemon is writing
+    Attached { job_id: String },
+}
+
+/// Builds without the `dashboard` feature have no TUI; `--output json`,
+/// `--detach` and `--stop` still work
+#[cfg(not(feature = "dashboard"))]
+async fn run_tui(
+    _feed: Feed<'_>,
+    _scrollback: Scrollback,
+    _update_interval_secs: u64,
+    _config: &Config,
+) -> Result<()> {
+    Err(crate::error::ConfigError::FeatureNotCompiled("dashboard".to_string()).into())
+}
+
+/// Run the single-job dashboard
+///
+/// # Arguments
+///
+/// * `job_id` - EC2 instance ID or local session ID (prefix)
+/// * `log` - Log file to tail for local jobs whose session has no log file
+/// * `update_interval_secs` - Refresh interval in seconds
+/// * `mode` - Foreground TUI, or detach/attach/stop a background session
+///
+/// # Errors
+///
+/// Returns `TrainctlError::ResourceNotFound` if the job (or, for attach, the
+/// detached session) can't be found, or an I/O error if the terminal can't be
+/// initialized. Refresh failures are shown in the header rather than ending
+/// the session.
+pub async fn run_job_watch(
+    job_id: &str,
+    log: Option<PathBuf>,
+    update_interval_secs: u64,
+    mode: WatchMode,
+    config: &Config,
+    output_format: &str,
+) -> Result<()> {
+    if let Some(path) = &log {
+        crate::validation::validate_path_path(path)?;
+    }
+    let log = log.as_deref();
+
+    match mode {
+        WatchMode::Interactive => {
+            let (snapshot, mut source) = resolve_job(job_id, config).await?;
+            if output_format == "json" {
+                let mut snapshot = snapshot;
+                refresh(&mut snapshot, &mut source, config, log).await;
+                println!("{}", serde_json::to_string_pretty(&snapshot)?);
+                return Ok(());
+            }
+            let scrollback = Scrollback::new(snapshot);
+            let feed = Feed::Live { source, log };
+            run_tui(feed, scrollback, update_interval_secs, config).await
+        }
+        WatchMode::Detach { config_path } => {
+            let (snapshot, _) = resolve_job(job_id, config).await?;
+            let pid = session::spawn_daemon(
+                &snapshot.job_id,
+                log,
+                update_interval_secs,
+                config_path.as_deref(),
+            )?;
+            if output_format == "json" {
+                let json = serde_json::json!({
+                    "job_id": snapshot.job_id,
+                    "daemon_pid": pid,
+                });
+                println!("{}", serde_json::to_string_pretty(&json)?);
+            } else {
+                println!(
+                    "Watching {} in the background (daemon PID {})",
+                    snapshot.job_id, pid
+                );
+                println!("  Attach: runctl watch --attach {}", snapshot.job_id);
+                println!("  Stop:   runctl watch --stop {}", snapshot.job_id);
+            }
+            Ok(())
+        }
+        WatchMode::Attach => {
+            let job_id = session::resolve_detached(job_id)?;
+            let scrollback = session::load_scrollback(&job_id)?;
+            if output_format == "json" {
+                println!("{}", serde_json::to_string_pretty(&scrollback)?);
+                return Ok(());
+            }
+            let feed = Feed::Attached { job_id };
+            run_tui(feed, scrollback, update_interval_secs, config).await
+        }
+        WatchMode::Stop => {
+            let job_id = session::resolve_detached(job_id)?;
+            let stopped = session::stop_daemon(&job_id)?;
+            if output_format == "json" {
+                let json = serde_json::json!({
+                    "job_id": job_id,
+                    "stopped_pid": stopped,
+                });
+                println!("{}", serde_json::to_string_pretty(&json)?);
+            } else {
+                match stopped {
+                    Some(pid) => println!("Stopped watch daemon for {} (PID {})", job_id, pid),
+                    None => println!("No watch daemon running for {}", job_id),
+                }
+            }
+            Ok(())
+        }
+        WatchMode::DaemonWorker => {
+            session::run_daemon(job_id, log, update_interval_secs, config).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_parse_metrics_latest_wins() {
+        let log = lines(
+            "Epoch 1/10 step 100/1000 loss=2.31 lr=0.001\n\
+             Epoch 2/10 step 200/1000 loss: 1.75 acc=0.61\n\
+             Saving checkpoint...",
+        );
+        let metrics = parse_metrics(&log);
+        assert_eq!(metrics.epoch, Some((2, 10)));
+        assert_eq!(metrics.step, Some((200, 1000)));
+        assert_eq!(metrics.loss, Some(1.75));
+        assert_eq!(metrics.values.get("lr"), Some(&0.001));
+        assert_eq!(metrics.values.get("acc"), Some(&0.61));
+    }
+
+    #[test]
+    fn test_parse_metrics_alternate_formats() {
+        let metrics = parse_metrics(&lines("[epoch: 3 of 5] train_loss=4e-2"));
+        assert_eq!(metrics.epoch, Some((3, 5)));
+        assert_eq!(metrics.loss, Some(0.04));
+        assert!(parse_metrics(&lines("no metrics here"))
+            .progress()
+            .is_none());
+    }
+
+    #[test]
+    fn test_progress_prefers_steps() {
+        let metrics = MetricsSnapshot {
+            epoch: Some((1, 4)),
+            step: Some((750, 1000)),
+            ..Default::default()
+        };
+        assert_eq!(metrics.progress(), Some(0.75));
+    }
+
+    #[test]
+    fn test_estimate_eta() {
+        assert_eq!(
+            estimate_eta(Duration::from_secs(600), 0.25),
+            Some(Duration::from_secs(1800))
+        );
+        assert_eq!(estimate_eta(Duration::from_secs(600), 0.0), None);
+        assert_eq!(estimate_eta(Duration::from_secs(600), 1.0), None);
+    }
+
+    #[test]
+    fn test_loss_history() {
+        let log = lines("loss=3.0\nvalidating\nloss=2.5\nloss=2.0");
+        assert_eq!(loss_history(&log), vec![3.0, 2.5, 2.0]);
+    }
+
+    #[test]
+    fn test_parse_remote_tail() {
+        let output = format!(
+            "STATUS:completed\nEXIT:1\n{}\nepoch 1/2\nTraceback...\n",
+            LOG_SEPARATOR
+        );
+        let (status, exit, log) = parse_remote_tail(&output);
+        assert_eq!(status.as_deref(), Some("completed"));
+        assert_eq!(exit, Some(1));
+        assert_eq!(log, vec!["epoch 1/2", "Traceback..."]);
+    }
+
+    #[test]
+    fn test_resolve_local_session_by_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        let session = TrainingSession::new(
+            "local".to_string(),
+            PathBuf::from("train.py"),
+            PathBuf::from("checkpoints"),
+        );
+        session.save(dir.path()).unwrap();
+
+        let found = resolve_local_session(dir.path(), &session.id[..8]).unwrap();
+        assert_eq!(found.id, session.id);
+        assert!(resolve_local_session(dir.path(), "nope").is_err());
+    }
+}
@@ -0,0 +1,530 @@
+//! Detachable watch sessions
+//!
+//! `runctl watch <job-id> --detach` starts a background runctl process (the
+//! watch daemon) that keeps sampling the job and writes a scrollback file to
+//! `.runctl/watch/<job-id>.scrollback.json`: the latest snapshot, recent log
+//! lines, and an event history (status changes, epoch boundaries, refresh
+//! failures). `runctl watch --attach <job-id>` renders that scrollback and
+//! follows it, so closing the terminal or laptop doesn't lose context.
+//!
+//! The daemon's PID lives in `<job-id>.pid` next to the scrollback and is
+//! removed when the job finishes or `runctl watch --stop <job-id>` is run.
+//! The scrollback is kept so a finished run can still be attached to.
+
+use super::{refresh, resolve_job, JobSnapshot};
+use crate::config::Config;
+use crate::error::{Result, TrainctlError};
+use crate::notifications::{Notifier, NotifyEvent};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+/// Log lines kept in the scrollback
+const SCROLLBACK_LINES: usize = 2000;
+
+/// Events kept in the scrollback
+const SCROLLBACK_EVENTS: usize = 200;
+
+/// Raise an alert when a running job's log is silent this long
+const STALL_ALERT_MINS: i64 = 15;
+
+/// Directory holding daemon PID files and scrollbacks
+pub(crate) fn watch_dir() -> PathBuf {
+    PathBuf::from(".runctl").join("watch")
+}
+
+fn scrollback_path(dir: &Path, job_id: &str) -> PathBuf {
+    dir.join(format!("{}.scrollback.json", job_id))
+}
+
+fn pid_path(dir: &Path, job_id: &str) -> PathBuf {
+    dir.join(format!("{}.pid", job_id))
+}
+
+/// What an event means, for highlighting and notifications
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchEventKind {
+    #[default]
+    Info,
+    Completed,
+    Failed,
+    Alert,
+}
+
+impl WatchEventKind {
+    /// Classify the status a job moved into
+    fn for_status(status: &str) -> Self {
+        match status {
+            "completed" => WatchEventKind::Completed,
+            "exited" | "interrupted" => WatchEventKind::Failed,
+            s if s.starts_with("failed") => WatchEventKind::Failed,
+            _ => WatchEventKind::Info,
+        }
+    }
+}
+
+/// Something worth remembering that happened while watching
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchEvent {
+    pub at: DateTime<Utc>,
+    #[serde(default)]
+    pub kind: WatchEventKind,
+    pub message: String,
+}
+
+/// Latest snapshot plus accumulated log lines and events
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Scrollback {
+    pub snapshot: JobSnapshot,
+    pub log_lines: VecDeque<String>,
+    pub events: VecDeque<WatchEvent>,
+    /// When new log output was last seen
+    #[serde(default)]
+    pub last_output_at: Option<DateTime<Utc>>,
+    /// Whether the current silent stretch already raised an alert
+    #[serde(default)]
+    pub stall_alerted: bool,
+}
+
+impl Scrollback {
+    pub fn new(snapshot: JobSnapshot) -> Self {
+        Self {
+            snapshot,
+            ..Default::default()
+        }
+    }
+
+    /// Fold a fresh snapshot in, recording new log lines and events
+    ///
+    /// Returns the events added by this update.
+    pub fn update(&mut self, snapshot: JobSnapshot) -> Vec<WatchEvent> {
+        let now = Utc::now();
+        let prev = &self.snapshot;
+        let mut new_events = Vec::new();
+        let mut push = |kind, message| {
+            new_events.push(WatchEvent {
+                at: now,
+                kind,
+                message,
+            })
+        };
+
+        if prev.status.is_empty() {
+            push(
+                WatchEventKind::Info,
+                format!("watching {} ({})", snapshot.job_id, snapshot.status),
+            );
+        } else if prev.status != snapshot.status {
+            push(
+                WatchEventKind::for_status(&snapshot.status),
+                format!("status {} -> {}", prev.status, snapshot.status),
+            );
+        }
+        if let (Some(old), Some(new)) = (prev.metrics.epoch, snapshot.metrics.epoch) {
+            if new.0 > old.0 {
+                let loss = snapshot
+                    .metrics
+                    .loss
+                    .map(|l| format!(" (loss {:.4})", l))
+                    .unwrap_or_default();
+                push(
+                    WatchEventKind::Info,
+                    format!("epoch {}/{}{}", new.0, new.1, loss),
+                );
+            }
+        }
+        match (&prev.error, &snapshot.error) {
+            (_, Some(err)) if prev.error.as_ref() != Some(err) => {
+                push(WatchEventKind::Alert, format!("refresh failed: {}", err));
+            }
+            (Some(_), None) => push(WatchEventKind::Info, "refresh recovered".to_string()),
+            _ => {}
+        }
+
+        let appended = merge_log_lines(&mut self.log_lines, &snapshot.log_tail, SCROLLBACK_LINES);
+        match self.last_output_at {
+            Some(last) if appended == 0 => {
+                let silent = now - last;
+                if snapshot.status == "running"
+                    && !self.stall_alerted
+                    && silent.num_minutes() >= STALL_ALERT_MINS
+                {
+                    push(
+                        WatchEventKind::Alert,
+                        format!("no log output for {} min", silent.num_minutes()),
+                    );
+                    self.stall_alerted = true;
+                }
+            }
+            _ => {
+                self.last_output_at = Some(now);
+                self.stall_alerted = false;
+            }
+        }
+
+        self.events.extend(new_events.iter().cloned());
+        while self.events.len() > SCROLLBACK_EVENTS {
+            self.events.pop_front();
+        }
+        self.snapshot = snapshot;
+        new_events
+    }
+}
+
+/// Append the part of `tail` not already at the end of `buffer`
+///
+/// Each refresh returns the last N log lines, which usually overlap with what
+/// was seen before. The longest suffix of `buffer` that is a prefix of `tail`
+/// is treated as already seen; if nothing overlaps (rotated or truncated
+/// log), the whole tail is appended. Returns the number of lines appended.
+pub fn merge_log_lines(buffer: &mut VecDeque<String>, tail: &[String], cap: usize) -> usize {
+    let max_overlap = tail.len().min(buffer.len());
+    let overlap = (1..=max_overlap)
+        .rev()
+        .find(|&k| buffer.iter().skip(buffer.len() - k).eq(tail[..k].iter()))
+        .unwrap_or(0);
+    buffer.extend(tail[overlap..].iter().cloned());
+    while buffer.len() > cap {
+        buffer.pop_front();
+    }
+    tail.len() - overlap
+}
+
+fn load_scrollback_from(dir: &Path, job_id: &str) -> Result<Scrollback> {
+    let path = scrollback_path(dir, job_id);
+    let content = fs::read_to_string(&path)?;
+    serde_json::from_str(&content).map_err(|e| {
+        TrainctlError::Io(std::io::Error::other(format!(
+            "Failed to parse watch scrollback {}: {}",
+            path.display(),
+            e
+        )))
+    })
+}
+
+/// Load the scrollback written by a detached session
+pub fn load_scrollback(job_id: &str) -> Result<Scrollback> {
+    load_scrollback_from(&watch_dir(), job_id)
+}
+
+fn save_scrollback(dir: &Path, scrollback: &Scrollback) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    let path = scrollback_path(dir, &scrollback.snapshot.job_id);
+    // Write then rename so an attached viewer never reads a partial file
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string(scrollback)?)?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+/// Resolve a job ID (or unique prefix) to a job with a scrollback on disk
+fn resolve_detached_in(dir: &Path, job_id: &str) -> Result<String> {
+    let mut matches = Vec::new();
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if let Some(id) = name.strip_suffix(".scrollback.json") {
+                if id == job_id {
+                    return Ok(id.to_string());
+                }
+                if id.starts_with(job_id) {
+                    matches.push(id.to_string());
+                }
+            }
+        }
+    }
+    if matches.len() > 1 {
+        return Err(TrainctlError::Validation {
+            field: "job_id".to_string(),
+            reason: format!(
+                "'{}' matches {} detached watch sessions; use a longer prefix",
+                job_id,
+                matches.len()
+            ),
+        });
+    }
+    matches
+        .pop()
+        .ok_or_else(|| TrainctlError::ResourceNotFound {
+            resource_type: "detached watch session".to_string(),
+            resource_id: format!(
+                "{} (start one with: runctl watch {} --detach)",
+                job_id, job_id
+            ),
+        })
+}
+
+pub(crate) fn resolve_detached(job_id: &str) -> Result<String> {
+    resolve_detached_in(&watch_dir(), job_id)
+}
+
+/// PID of the running daemon for `job_id`, if any
+pub fn daemon_pid(job_id: &str) -> Option<u32> {
+    let pid: u32 = fs::read_to_string(pid_path(&watch_dir(), job_id))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    let mut system = System::new();
+    let pid = Pid::from_u32(pid);
+    (system.refresh_processes(ProcessesToUpdate::Some(&[pid])) > 0).then_some(pid.as_u32())
+}
+
+/// Send desktop notifications for completion, failure, and alert events
+pub(crate) fn send_notifications(notifier: &Notifier, job_id: &str, events: &[WatchEvent]) {
+    for event in events {
+        let (kind, what) = match event.kind {
+            WatchEventKind::Completed => (NotifyEvent::Completed, "completed"),
+            WatchEventKind::Failed => (NotifyEvent::Failed, "failed"),
+            WatchEventKind::Alert => (NotifyEvent::Alert, "needs attention"),
+            WatchEventKind::Info => continue,
+        };
+        notifier.notify(
+            kind,
+            &format!("runctl: {} {}", job_id, what),
+            &event.message,
+        );
+    }
+}
+
+/// Whether a job status means there is nothing left to watch
+fn is_finished(status: &str) -> bool {
+    matches!(
+        status,
+        "completed" | "exited" | "interrupted" | "stopped" | "terminated"
+    ) || status.starts_with("failed")
+}
+
+/// Start a background watch daemon for `job_id`
+///
+/// Returns the daemon PID. The daemon re-executes the current binary with the
+/// hidden `--detached-worker` flag, in its own process group so terminal
+/// hangups and Ctrl+C don't reach it.
+pub(crate) fn spawn_daemon(
+    job_id: &str,
+    log: Option<&Path>,
+    interval_secs: u64,
+    config_path: Option<&Path>,
+) -> Result<u32> {
+    if let Some(pid) = daemon_pid(job_id) {
+        return Err(TrainctlError::ResourceExists {
+            resource_type: "detached watch session".to_string(),
+            resource_id: format!("{} (daemon PID {})", job_id, pid),
+        });
+    }
+
+    let exe = std::env::current_exe()?;
+    let mut cmd = std::process::Command::new(exe);
+    if let Some(path) = config_path {
+        cmd.arg("--config").arg(path);
+    }
+    cmd.arg("watch")
+        .arg(job_id)
+        .arg("--detached-worker")
+        .arg("--interval")
+        .arg(interval_secs.to_string());
+    if let Some(path) = log {
+        cmd.arg("--log").arg(path);
+    }
+    cmd.stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+
+    let child = cmd.spawn().map_err(|e| {
+        TrainctlError::Io(std::io::Error::other(format!(
+            "Failed to start watch daemon: {}",
+            e
+        )))
+    })?;
+    Ok(child.id())
+}
+
+/// Daemon loop: sample the job and persist the scrollback until it finishes
+pub(crate) async fn run_daemon(
+    job_id: &str,
+    log: Option<&Path>,
+    interval_secs: u64,
+    config: &Config,
+) -> Result<()> {
+    let (snapshot, mut source) = resolve_job(job_id, config).await?;
+    let dir = watch_dir();
+    fs::create_dir_all(&dir)?;
+    let pid_file = pid_path(&dir, &snapshot.job_id);
+    fs::write(&pid_file, std::process::id().to_string())?;
+
+    // Pick up where a previous daemon left off so history isn't lost
+    let mut scrollback =
+        load_scrollback_from(&dir, &snapshot.job_id).unwrap_or_else(|_| Scrollback::new(snapshot));
+    let interval = Duration::from_secs(interval_secs.max(1));
+    let notifier = Notifier::new(config.notifications.clone());
+
+    let result = loop {
+        let mut next = scrollback.snapshot.clone();
+        refresh(&mut next, &mut source, config, log).await;
+        let finished = is_finished(&next.status);
+        let job_id = next.job_id.clone();
+        let events = scrollback.update(next);
+        send_notifications(&notifier, &job_id, &events);
+        if let Err(e) = save_scrollback(&dir, &scrollback) {
+            break Err(e);
+        }
+        if finished {
+            break Ok(());
+        }
+        tokio::time::sleep(interval).await;
+    };
+
+    let _ = fs::remove_file(&pid_file);
+    result
+}
+
+/// Stop the daemon for `job_id`; returns its PID if one was running
+pub(crate) fn stop_daemon(job_id: &str) -> Result<Option<u32>> {
+    let pid = daemon_pid(job_id);
+    if let Some(pid) = pid {
+        let mut system = System::new();
+        let pid = Pid::from_u32(pid);
+        if system.refresh_processes(ProcessesToUpdate::Some(&[pid])) > 0 {
+            if let Some(process) = system.process(pid) {
+                process.kill();
+            }
+        }
+    }
+    let _ = fs::remove_file(pid_path(&watch_dir(), job_id));
+    Ok(pid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_merge_log_lines_overlap() {
+        let mut buffer: VecDeque<String> = lines(&["a", "b", "c"]).into();
+        let appended = merge_log_lines(&mut buffer, &lines(&["b", "c", "d", "e"]), 10);
+        assert_eq!(appended, 2);
+        assert_eq!(buffer, lines(&["a", "b", "c", "d", "e"]));
+
+        // Unchanged tail appends nothing
+        assert_eq!(merge_log_lines(&mut buffer, &lines(&["d", "e"]), 10), 0);
+        assert_eq!(buffer.len(), 5);
+    }
+
+    #[test]
+    fn test_merge_log_lines_no_overlap_and_cap() {
+        let mut buffer: VecDeque<String> = lines(&["a", "b"]).into();
+        merge_log_lines(&mut buffer, &lines(&["x", "y", "z"]), 4);
+        assert_eq!(buffer, lines(&["b", "x", "y", "z"]));
+    }
+
+    #[test]
+    fn test_scrollback_records_events() {
+        let mut scrollback = Scrollback::new(JobSnapshot {
+            job_id: "i-abc".to_string(),
+            ..Default::default()
+        });
+
+        let mut snap = scrollback.snapshot.clone();
+        snap.status = "running".to_string();
+        snap.metrics.epoch = Some((1, 3));
+        snap.log_tail = lines(&["epoch 1/3"]);
+        scrollback.update(snap.clone());
+
+        snap.metrics.epoch = Some((2, 3));
+        snap.metrics.loss = Some(0.5);
+        snap.log_tail = lines(&["epoch 1/3", "epoch 2/3 loss=0.5"]);
+        scrollback.update(snap.clone());
+
+        snap.status = "completed".to_string();
+        snap.error = Some("ssm timeout".to_string());
+        scrollback.update(snap);
+
+        let messages: Vec<_> = scrollback
+            .events
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect();
+        let kinds: Vec<_> = scrollback.events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                WatchEventKind::Info,
+                WatchEventKind::Info,
+                WatchEventKind::Completed,
+                WatchEventKind::Alert,
+            ]
+        );
+        assert_eq!(
+            messages,
+            vec![
+                "watching i-abc (running)",
+                "epoch 2/3 (loss 0.5000)",
+                "status running -> completed",
+                "refresh failed: ssm timeout",
+            ]
+        );
+        assert_eq!(scrollback.log_lines.len(), 2);
+    }
+
+    #[test]
+    fn test_stall_alert_fires_once() {
+        let mut scrollback = Scrollback::new(JobSnapshot::default());
+        let mut snap = JobSnapshot {
+            job_id: "i-abc".to_string(),
+            status: "running".to_string(),
+            log_tail: lines(&["step 1/10"]),
+            ..Default::default()
+        };
+        scrollback.update(snap.clone());
+        scrollback.last_output_at = Some(Utc::now() - chrono::Duration::minutes(20));
+
+        let events = scrollback.update(snap.clone());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, WatchEventKind::Alert);
+        assert!(scrollback.update(snap.clone()).is_empty());
+
+        // New output clears the stall
+        snap.log_tail = lines(&["step 1/10", "step 2/10"]);
+        scrollback.update(snap);
+        assert!(!scrollback.stall_alerted);
+    }
+
+    #[test]
+    fn test_scrollback_roundtrip_and_resolve() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut scrollback = Scrollback::new(JobSnapshot {
+            job_id: "3f2a9c1e-0000".to_string(),
+            ..Default::default()
+        });
+        scrollback.log_lines.push_back("hello".to_string());
+        save_scrollback(dir.path(), &scrollback).unwrap();
+
+        let id = resolve_detached_in(dir.path(), "3f2a").unwrap();
+        assert_eq!(id, "3f2a9c1e-0000");
+        let loaded = load_scrollback_from(dir.path(), &id).unwrap();
+        assert_eq!(loaded.log_lines, scrollback.log_lines);
+        assert!(resolve_detached_in(dir.path(), "ffff").is_err());
+    }
+
+    #[test]
+    fn test_is_finished() {
+        assert!(is_finished("completed"));
+        assert!(is_finished("failed (exit 1)"));
+        assert!(!is_finished("running"));
+        assert!(!is_finished("pending"));
+    }
+}
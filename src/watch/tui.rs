@@ -0,0 +1,333 @@
+//! Terminal UI for `runctl watch` (needs the `dashboard` feature)
+
+use super::{refresh, session, Feed, JobSnapshot, Scrollback, WatchEventKind};
+use crate::config::Config;
+use crate::error::Result;
+use crate::notifications::Notifier;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Gauge, Paragraph, Sparkline, Wrap},
+    Frame,
+};
+use std::time::{Duration, Instant};
+
+pub(super) async fn run_tui(
+    mut feed: Feed<'_>,
+    mut scrollback: Scrollback,
+    update_interval_secs: u64,
+    config: &Config,
+) -> Result<()> {
+    let update_interval = Duration::from_secs(update_interval_secs.max(1));
+    let mut terminal = crate::dashboard::init_terminal()?;
+    let mut last_update: Option<Instant> = None;
+    // Lines scrolled up from the bottom of the log; 0 follows new output
+    let mut scroll: usize = 0;
+    let mut daemon_label: Option<String> = None;
+    // Attached sessions leave notifications to the daemon
+    let notifier = Notifier::new(config.notifications.clone());
+
+    let result: Result<()> = async {
+        loop {
+            let due = match last_update {
+                Some(t) => t.elapsed() >= update_interval,
+                None => true,
+            };
+            if due {
+                match &mut feed {
+                    Feed::Live { source, log } => {
+                        let mut next = scrollback.snapshot.clone();
+                        refresh(&mut next, source, config, *log).await;
+                        let job_id = next.job_id.clone();
+                        let events = scrollback.update(next);
+                        session::send_notifications(&notifier, &job_id, &events);
+                    }
+                    Feed::Attached { job_id } => {
+                        match session::load_scrollback(job_id) {
+                            Ok(latest) => scrollback = latest,
+                            Err(e) => scrollback.snapshot.error = Some(e.to_string()),
+                        }
+                        daemon_label = Some(match session::daemon_pid(job_id) {
+                            Some(pid) => format!("attached, daemon PID {}", pid),
+                            None => "attached, daemon not running".to_string(),
+                        });
+                    }
+                }
+                last_update = Some(Instant::now());
+            }
+
+            terminal.draw(|f| render(f, &scrollback, scroll, daemon_label.as_deref()))?;
+
+            if event::poll(Duration::from_millis(250))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        let max_scroll = scrollback.log_lines.len();
+                        match key.code {
+                            KeyCode::Char('q') | KeyCode::Esc => break,
+                            KeyCode::Char('r') => last_update = None,
+                            KeyCode::Up => scroll = (scroll + 1).min(max_scroll),
+                            KeyCode::Down => scroll = scroll.saturating_sub(1),
+                            KeyCode::PageUp => scroll = (scroll + 20).min(max_scroll),
+                            KeyCode::PageDown => scroll = scroll.saturating_sub(20),
+                            KeyCode::End => scroll = 0,
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+    .await;
+
+    crate::dashboard::restore_terminal(&mut terminal)?;
+    result
+}
+
+fn format_secs(secs: u64) -> String {
+    let (h, m, s) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    if h > 0 {
+        format!("{}h {}m", h, m)
+    } else if m > 0 {
+        format!("{}m {}s", m, s)
+    } else {
+        format!("{}s", s)
+    }
+}
+
+fn render(f: &mut Frame, scrollback: &Scrollback, scroll: usize, mode_label: Option<&str>) {
+    let snapshot = &scrollback.snapshot;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(4),
+            Constraint::Length(10),
+            Constraint::Min(5),
+        ])
+        .split(f.size());
+
+    render_header(f, chunks[0], snapshot, mode_label);
+
+    let middle = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+        .split(chunks[1]);
+    render_metrics(f, middle[0], snapshot);
+    render_utilization(f, middle[1], snapshot);
+
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
+        .split(chunks[2]);
+    render_log(f, bottom[0], scrollback, scroll);
+    render_events(f, bottom[1], scrollback);
+}
+
+fn render_header(f: &mut Frame, area: Rect, snapshot: &JobSnapshot, mode_label: Option<&str>) {
+    let label = Style::default().fg(Color::Cyan);
+    let status_color = match snapshot.status.as_str() {
+        "running" => Color::Green,
+        "completed" => Color::Blue,
+        s if s.starts_with("failed") || s == "exited" => Color::Red,
+        _ => Color::Yellow,
+    };
+    let mut lines = vec![Line::from(vec![
+        Span::styled("Status: ", label),
+        Span::styled(
+            snapshot.status.clone(),
+            Style::default()
+                .fg(status_color)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(" | "),
+        Span::styled("Elapsed: ", label),
+        Span::raw(
+            snapshot
+                .elapsed_secs
+                .map(format_secs)
+                .unwrap_or_else(|| "-".to_string()),
+        ),
+        Span::raw(" | "),
+        Span::styled("Cost: ", label),
+        Span::styled(
+            format!(
+                "${:.2} (${:.2}/h)",
+                snapshot.cost_so_far, snapshot.cost_per_hour
+            ),
+            Style::default().fg(Color::Yellow),
+        ),
+        Span::raw(" | "),
+        Span::styled("ETA: ", label),
+        Span::raw(
+            snapshot
+                .eta_secs
+                .map(format_secs)
+                .unwrap_or_else(|| "-".to_string()),
+        ),
+    ])];
+    if let Some(error) = &snapshot.error {
+        lines.push(Line::from(Span::styled(
+            format!("Refresh failed: {}", error),
+            Style::default().fg(Color::Red),
+        )));
+    }
+
+    let mode = mode_label.map(|m| format!(" [{}]", m)).unwrap_or_default();
+    let title = format!(
+        "runctl watch {} ({}){} - q to quit, r to refresh",
+        snapshot.job_id, snapshot.platform, mode
+    );
+    let header = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .wrap(Wrap { trim: true });
+    f.render_widget(header, area);
+}
+
+fn render_metrics(f: &mut Frame, area: Rect, snapshot: &JobSnapshot) {
+    let block = Block::default().borders(Borders::ALL).title("Metrics");
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let parts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(3)])
+        .split(inner);
+
+    let metrics = &snapshot.metrics;
+    let label = Style::default().fg(Color::Cyan);
+    let progress = |p: Option<(u64, u64)>| {
+        p.map(|(c, t)| format!("{}/{}", c, t))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("Epoch: ", label),
+            Span::raw(progress(metrics.epoch)),
+            Span::raw("  "),
+            Span::styled("Step: ", label),
+            Span::raw(progress(metrics.step)),
+        ]),
+        Line::from(vec![
+            Span::styled("Loss: ", label),
+            Span::styled(
+                metrics
+                    .loss
+                    .map(|l| format!("{:.4}", l))
+                    .unwrap_or_else(|| "-".to_string()),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+    ];
+    for (key, value) in &metrics.values {
+        lines.push(Line::from(vec![
+            Span::styled(format!("{}: ", key), label),
+            Span::raw(format!("{}", value)),
+        ]));
+    }
+    f.render_widget(Paragraph::new(lines), parts[0]);
+
+    // Sparkline needs integers; scale relative to the window's max
+    let max = snapshot
+        .loss_history
+        .iter()
+        .copied()
+        .fold(0.0_f64, f64::max);
+    let data: Vec<u64> = if max > 0.0 {
+        snapshot
+            .loss_history
+            .iter()
+            .map(|l| ((l / max) * 100.0).max(0.0) as u64)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let sparkline = Sparkline::default()
+        .block(Block::default().title("loss"))
+        .data(&data)
+        .style(Style::default().fg(Color::Yellow));
+    f.render_widget(sparkline, parts[1]);
+}
+
+fn render_utilization(f: &mut Frame, area: Rect, snapshot: &JobSnapshot) {
+    let block = Block::default().borders(Borders::ALL).title("Utilization");
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2); 4])
+        .split(inner);
+
+    let gauges = [
+        ("CPU", snapshot.cpu_percent, Color::Green),
+        ("Memory", snapshot.memory_percent, Color::Magenta),
+        ("GPU", snapshot.gpu_utilization, Color::Yellow),
+        ("GPU Mem", snapshot.gpu_memory_percent, Color::Cyan),
+    ];
+    for ((name, value, color), row) in gauges.into_iter().zip(rows.iter()) {
+        let (percent, label) = match value {
+            Some(v) => (v.clamp(0.0, 100.0) as u16, format!("{}: {:.1}%", name, v)),
+            None => (0, format!("{}: N/A", name)),
+        };
+        let gauge = Gauge::default()
+            .gauge_style(Style::default().fg(color))
+            .percent(percent)
+            .label(label);
+        f.render_widget(gauge, *row);
+    }
+}
+
+fn render_log(f: &mut Frame, area: Rect, scrollback: &Scrollback, scroll: usize) {
+    let visible = area.height.saturating_sub(2) as usize;
+    let total = scrollback.log_lines.len();
+    let end = total.saturating_sub(scroll);
+    let start = end.saturating_sub(visible);
+    let lines: Vec<Line> = scrollback
+        .log_lines
+        .range(start..end)
+        .map(|l| Line::from(l.as_str()))
+        .collect();
+    let title = if total == 0 {
+        "Log (no output yet)".to_string()
+    } else if scroll > 0 {
+        format!("Log (scrolled {} lines up, End to follow)", total - end)
+    } else {
+        "Log".to_string()
+    };
+    let log = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(log, area);
+}
+
+fn render_events(f: &mut Frame, area: Rect, scrollback: &Scrollback) {
+    let visible = area.height.saturating_sub(2) as usize;
+    let start = scrollback.events.len().saturating_sub(visible);
+    let lines: Vec<Line> = scrollback
+        .events
+        .range(start..)
+        .map(|e| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{} ", e.at.format("%H:%M:%S")),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    e.message.as_str(),
+                    match e.kind {
+                        WatchEventKind::Completed => Style::default().fg(Color::Green),
+                        WatchEventKind::Failed => Style::default().fg(Color::Red),
+                        WatchEventKind::Alert => Style::default().fg(Color::Yellow),
+                        WatchEventKind::Info => Style::default(),
+                    },
+                ),
+            ])
+        })
+        .collect();
+    let events = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("Events"))
+        .wrap(Wrap { trim: true });
+    f.render_widget(events, area);
+}
@@ -14,6 +14,13 @@
 //!
 //! - **Train**: Complete training workflow (create instance → sync code → train → wait)
 //!
+//! ## Data Locality
+//!
+//! With `--data`, the workflow resolves where the dataset lives and launches
+//! in the same region (see `data_locality`). With `--region` the region is
+//! pinned instead, and a mismatch is reported with an egress estimate and
+//! needs confirmation.
+//!
 //! ## Future Workflows
 //!
 //! - **Resume**: Resume training from checkpoint on new instance
@@ -34,6 +41,9 @@
 //!         script: "train.py".into(),
 //!         instance_type: "g4dn.xlarge".to_string(),
 //!         spot: true,
+//!         data: Some("s3://my-bucket/datasets/imagenet/".to_string()),
+//!         data_size_gb: None,
+//!         region: None,
 //!         script_args: vec!["--epochs".to_string(), "50".to_string()],
 //!     },
 //!     &config,
@@ -48,6 +58,7 @@ use crate::aws::{
     TrainInstanceOptions,
 };
 use crate::config::Config;
+use crate::data_locality::{self, DatasetLocation, Placement, Site};
 use crate::error::Result;
 use aws_config::BehaviorVersion;
 use clap::Subcommand;
@@ -63,22 +74,39 @@ pub enum WorkflowCommands {
     /// 3. Waits for training to complete
     /// 4. Verifies training succeeded
     ///
+    /// With --data, the instance is launched in the dataset's region; with
+    /// --region as well, a mismatch is reported with an egress estimate.
+    ///
     /// Examples:
     ///   runctl workflow train training/train.py --instance-type g4dn.xlarge
     ///   runctl workflow train training/train.py --instance-type t3.micro --spot
+    ///   runctl workflow train train.py --instance-type g5.xlarge --data s3://bucket/datasets/
     Train {
         /// Training script path
         #[arg(value_name = "SCRIPT")]
         script: PathBuf,
 
         /// EC2 instance type (e.g., t3.medium, g4dn.xlarge)
-        #[arg(long, value_name = "INSTANCE_TYPE")]
+        #[arg(long, value_name = "INSTANCE_TYPE", value_parser = crate::validation::instance_type_arg)]
         instance_type: String,
 
         /// Use spot instance (cheaper, can be interrupted)
         #[arg(long)]
         spot: bool,
 
+        /// Dataset location (s3://bucket/prefix, efs://[region/]fs-ID,
+        /// runpod-volume://ID or a local path)
+        #[arg(long, value_name = "LOCATION")]
+        data: Option<String>,
+
+        /// Dataset size in GB, for estimates where it can't be listed (EFS, RunPod volumes)
+        #[arg(long, value_name = "GB")]
+        data_size_gb: Option<u64>,
+
+        /// Launch in this region even if the dataset lives elsewhere
+        #[arg(long, value_name = "REGION")]
+        region: Option<String>,
+
         /// Additional arguments to pass to training script
         #[arg(last = true, value_name = "ARGS")]
         script_args: Vec<String>,
@@ -115,6 +143,9 @@ pub enum WorkflowCommands {
 ///         script: "train.py".into(),
 ///         instance_type: "g4dn.xlarge".to_string(),
 ///         spot: true,
+///         data: None,
+///         data_size_gb: None,
+///         region: None,
 ///         script_args: vec![],
 ///     },
 ///     &config,
@@ -133,9 +164,38 @@ pub async fn handle_command(
             script,
             instance_type,
             spot,
+            data,
+            data_size_gb,
+            region,
             script_args,
         } => {
-            let aws_config = aws_config::load_defaults(BehaviorVersion::latest()).await;
+            let region = match data.as_deref() {
+                Some(data) => {
+                    match choose_region(data, data_size_gb, region, config, output_format).await? {
+                        Some(region) => Some(region),
+                        None => {
+                            println!("Cancelled");
+                            return Ok(());
+                        }
+                    }
+                }
+                None => region,
+            };
+            let aws_config = match &region {
+                Some(region) => {
+                    aws_config::defaults(BehaviorVersion::latest())
+                        .region(aws_sdk_ec2::config::Region::new(region.clone()))
+                        .load()
+                        .await
+                }
+                None => aws_config::load_defaults(BehaviorVersion::latest()).await,
+            };
+            // AMI lookup reads the region from config
+            let mut config = config.clone();
+            if let (Some(region), Some(aws)) = (&region, config.aws.as_mut()) {
+                aws.region = region.clone();
+            }
+            let config = &config;
 
             // Step 1: Create instance with --wait
             if output_format != "json" {
@@ -147,6 +207,7 @@ pub async fn handle_command(
                 use_spot: spot,
                 spot_max_price: None,
                 no_fallback: false,
+                availability_zone: None,
                 key_name: None,
                 security_group: None,
                 ami_id: None,
@@ -155,6 +216,12 @@ pub async fn handle_command(
                 project_name: get_project_name(None, config),
                 iam_instance_profile: None, // TODO: Get from config
                 wait: true,                 // Always wait for instance to be ready
+                preset: None,
+                client_token: None,
+                allow_duplicate: false,
+                refresh_ami: false,
+                ami_filter: None,
+                ssh_user: None,
             };
 
             // Create instance and get instance ID
@@ -173,16 +240,26 @@ pub async fn handle_command(
             let train_options = TrainInstanceOptions {
                 instance_id: instance_id.clone(),
                 script,
-                data_s3: None,
+                data_s3: data.filter(|d| d.starts_with("s3://")),
                 output_s3: None,
                 sync_code: true,
                 include_patterns: vec![],
+                exclude_patterns: vec![],
+                max_file_size: None,
+                yes: false,
                 project_name: get_project_name(None, config),
+                ssh_user: None,
                 script_args,
+                resume: None,
+                env: vec![],
                 wait: true,           // Always wait for training to complete
                 timeout_minutes: 120, // Default 2 hour timeout
                 docker: false,
                 docker_image: None,
+                job: config.job.clone(),
+                agent: false,
+                launcher: None,
+                on_complete: None,
             };
 
             train_on_instance(train_options, config, &aws_config, output_format).await?;
@@ -197,3 +274,78 @@ pub async fn handle_command(
         }
     }
 }
+
+/// Pick the launch region for a dataset
+///
+/// Returns `None` if the user declined a remote-data placement.
+async fn choose_region(
+    data: &str,
+    data_size_gb: Option<u64>,
+    region: Option<String>,
+    config: &Config,
+    output_format: &str,
+) -> Result<Option<String>> {
+    let dataset = DatasetLocation::parse(data)?;
+    let default_config = aws_config::load_defaults(BehaviorVersion::latest()).await;
+    let pinned = region.is_some();
+    let planned_region = region
+        .or_else(|| default_config.region().map(|r| r.to_string()))
+        .or_else(|| config.aws.as_ref().map(|aws| aws.region.clone()))
+        .unwrap_or_else(|| "us-east-1".to_string());
+
+    let (data_site, listed_bytes) =
+        data_locality::resolve(&dataset, &default_config, &planned_region).await?;
+    let size_bytes = listed_bytes.or(data_size_gb.map(|gb| gb * 1024 * 1024 * 1024));
+    let planned = Site::Aws {
+        region: planned_region.clone(),
+    };
+
+    match data_locality::assess(&dataset, &data_site, &planned, size_bytes, pinned) {
+        Placement::Local => Ok(Some(planned_region)),
+        Placement::Move {
+            to: Site::Aws { region },
+        } => {
+            if output_format != "json" {
+                println!(
+                    "Dataset {} is in {}; launching there instead of {}",
+                    dataset, region, planned_region
+                );
+            }
+            Ok(Some(region))
+        }
+        Placement::Move { .. } => Ok(Some(planned_region)),
+        Placement::Remote {
+            data_site,
+            estimate,
+            note,
+        } => {
+            let mut message = format!(
+                "Dataset {} is in {}, but the instance will run in {}",
+                dataset, data_site, planned
+            );
+            match &estimate {
+                Some(estimate) => {
+                    message.push_str(&format!(" ({})", data_locality::format_estimate(estimate)))
+                }
+                None => message.push_str(" (size unknown; pass --data-size-gb for an estimate)"),
+            }
+            if let Some(note) = note {
+                message.push_str(&format!(". {}", note));
+            }
+            if confirm_remote_data(&message, output_format)? {
+                Ok(Some(planned_region))
+            } else {
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Ask before training against remote data; non-interactive runs proceed with a warning
+fn confirm_remote_data(message: &str, output_format: &str) -> Result<bool> {
+    if output_format != "text" || !crate::prompt::can_prompt() {
+        tracing::warn!("{}", message);
+        return Ok(true);
+    }
+    crate::prompt::confirm(&format!("WARNING: {}. Continue?", message))
+}
@@ -0,0 +1,172 @@
+//! Tests for command alias expansion and external subcommand lookup
+
+use runctl::aliases::{command_index, config_flag, expand, find_external, shadowed, AliasValue};
+use runctl::config::Config;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(str::to_string).collect()
+}
+
+fn builtins() -> Vec<String> {
+    ["aws", "run", "local", "help"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn aliases(entries: &[(&str, AliasValue)]) -> BTreeMap<String, AliasValue> {
+    entries
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.clone()))
+        .collect()
+}
+
+#[test]
+fn test_expand_replaces_alias_and_keeps_trailing_args() {
+    let table = aliases(&[(
+        "gpu",
+        AliasValue::Line("aws create g5.xlarge --spot --preset gpu-dev".to_string()),
+    )]);
+    let expanded = expand(args("runctl --verbose gpu --wait"), &table, &builtins()).unwrap();
+    assert_eq!(
+        expanded,
+        args("runctl --verbose aws create g5.xlarge --spot --preset gpu-dev --wait")
+    );
+}
+
+#[test]
+fn test_expand_skips_global_flag_values() {
+    let table = aliases(&[("gpu", AliasValue::Line("aws create".to_string()))]);
+    let expanded = expand(
+        args("runctl --config gpu.toml --output json gpu"),
+        &table,
+        &builtins(),
+    )
+    .unwrap();
+    assert_eq!(
+        expanded,
+        args("runctl --config gpu.toml --output json aws create")
+    );
+}
+
+#[test]
+fn test_expand_list_form_and_quoting() {
+    let table = aliases(&[
+        (
+            "nightly",
+            AliasValue::Args(vec![
+                "run".to_string(),
+                "train.py".to_string(),
+                "--".to_string(),
+                "--note".to_string(),
+                "two words".to_string(),
+            ]),
+        ),
+        (
+            "quoted",
+            AliasValue::Line("run train.py -- --note 'two words'".to_string()),
+        ),
+    ]);
+    let from_list = expand(args("runctl nightly"), &table, &builtins()).unwrap();
+    let from_line = expand(args("runctl quoted"), &table, &builtins()).unwrap();
+    assert_eq!(from_list, from_line);
+    assert_eq!(from_list.last().unwrap(), "two words");
+}
+
+#[test]
+fn test_expand_nested_aliases() {
+    let table = aliases(&[
+        ("g", AliasValue::Line("gpu --wait".to_string())),
+        ("gpu", AliasValue::Line("aws create g5.xlarge".to_string())),
+    ]);
+    let expanded = expand(args("runctl g"), &table, &builtins()).unwrap();
+    assert_eq!(expanded, args("runctl aws create g5.xlarge --wait"));
+}
+
+#[test]
+fn test_expand_detects_cycles() {
+    let table = aliases(&[
+        ("a", AliasValue::Line("b".to_string())),
+        ("b", AliasValue::Line("a --x".to_string())),
+    ]);
+    let err = expand(args("runctl a"), &table, &builtins()).unwrap_err();
+    assert!(err.to_string().contains("a -> b -> a"), "{}", err);
+}
+
+#[test]
+fn test_builtins_are_not_shadowed() {
+    let table = aliases(&[("aws", AliasValue::Line("local train.py".to_string()))]);
+    let expanded = expand(args("runctl aws status"), &table, &builtins()).unwrap();
+    assert_eq!(expanded, args("runctl aws status"));
+    assert_eq!(shadowed(&table, &builtins()), vec!["aws"]);
+}
+
+#[test]
+fn test_empty_alias_is_an_error() {
+    let table = aliases(&[("nothing", AliasValue::Line("  ".to_string()))]);
+    assert!(expand(args("runctl nothing"), &table, &builtins()).is_err());
+}
+
+#[test]
+fn test_command_index_and_config_flag() {
+    assert_eq!(command_index(&args("runctl -v -c x.toml aws")), Some(4));
+    assert_eq!(command_index(&args("runctl --verbose")), None);
+    assert_eq!(
+        config_flag(&args("runctl -c x.toml aws")),
+        Some(PathBuf::from("x.toml"))
+    );
+    assert_eq!(
+        config_flag(&args("runctl --config=y.toml aws")),
+        Some(PathBuf::from("y.toml"))
+    );
+    // --config is global, but script arguments after `--` are not runctl's
+    assert_eq!(
+        config_flag(&args("runctl aws status --config z.toml")),
+        Some(PathBuf::from("z.toml"))
+    );
+    assert_eq!(config_flag(&args("runctl local train.py -- -c x")), None);
+}
+
+#[test]
+fn test_find_external_rejects_paths() {
+    assert_eq!(find_external("../evil"), None);
+    assert_eq!(find_external(""), None);
+}
+
+#[test]
+fn test_aliases_parse_from_config() {
+    let config: Config = toml::from_str(
+        r#"
+        [checkpoint]
+        dir = "checkpoints"
+        save_interval = 5
+        keep_last_n = 10
+
+        [monitoring]
+        log_dir = "logs"
+        update_interval_secs = 10
+        enable_warnings = true
+
+        [aliases]
+        gpu = "aws create g5.xlarge --spot"
+        nightly = ["run", "train.py"]
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.aliases.get("gpu"),
+        Some(&AliasValue::Line("aws create g5.xlarge --spot".to_string()))
+    );
+    assert_eq!(
+        config
+            .aliases
+            .get("nightly")
+            .unwrap()
+            .to_args("nightly")
+            .unwrap(),
+        args("run train.py")
+    );
+}
@@ -73,6 +73,7 @@ fn test_resource_status_serialization() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: Some("1.2.3.4".to_string()),
+        public_ipv6: None,
         tags: vec![
             ("Project".to_string(), "test".to_string()),
             ("Environment".to_string(), "dev".to_string()),
@@ -99,6 +100,7 @@ fn test_resource_status_with_minimal_fields() {
         launch_time: None,
         cost_per_hour: 0.0,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
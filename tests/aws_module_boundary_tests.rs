@@ -3,6 +3,8 @@
 //! Tests that verify the new modular structure works correctly,
 //! including module exports, type conversions, and error handling.
 
+#![cfg(feature = "aws")]
+
 use runctl::config::Config;
 use runctl::provider::{ResourceState, ResourceStatus};
 use std::path::PathBuf;
@@ -18,6 +20,7 @@ fn test_aws_module_exports() {
         use_spot: false,
         spot_max_price: None,
         no_fallback: false,
+        availability_zone: None,
         key_name: None,
         security_group: None,
         ami_id: None,
@@ -51,6 +54,7 @@ fn test_aws_module_command_enum() {
         spot: false,
         spot_max_price: None,
         no_fallback: false,
+        availability_zone: None,
         key_name: None,
         security_group: None,
         ami_id: None,
@@ -131,6 +135,7 @@ fn test_resource_status_equality() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: Some("1.2.3.4".to_string()),
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -142,6 +147,7 @@ fn test_resource_status_equality() {
         launch_time: status1.launch_time,
         cost_per_hour: 0.01,
         public_ip: Some("1.2.3.4".to_string()),
+        public_ipv6: None,
         tags: vec![],
     };
 
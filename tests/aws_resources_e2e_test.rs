@@ -5,6 +5,8 @@
 //!
 //! Safety: Tests use dry-run mode and cleanup after themselves.
 
+#![cfg(feature = "aws")]
+
 use aws_config::BehaviorVersion;
 use aws_sdk_ec2::Client as Ec2Client;
 use std::env;
@@ -0,0 +1,229 @@
+//! Tests for per-project budgets
+
+use chrono::{Duration, TimeZone, Utc};
+use runctl::config::{BudgetAction, BudgetConfig, BudgetLimits, Config};
+use runctl::provider::{ResourceState, ResourceStatus};
+use runctl::resource_tracking::budget::{
+    check_launch, evaluate, evaluate_accounts, spend_since, BudgetPeriod, ACCOUNT_TAG,
+};
+use runctl::resource_tracking::TrackedResource;
+
+fn resource(
+    id: &str,
+    project: &str,
+    state: ResourceState,
+    launch: chrono::DateTime<Utc>,
+) -> TrackedResource {
+    TrackedResource {
+        status: ResourceStatus {
+            id: id.to_string(),
+            name: None,
+            state,
+            instance_type: Some("g5.xlarge".to_string()),
+            launch_time: Some(launch),
+            cost_per_hour: 2.0,
+            public_ip: None,
+            public_ipv6: None,
+            tags: vec![],
+        },
+        created_at: launch,
+        usage_history: vec![],
+        accumulated_cost: 0.0,
+        tags: [("runctl:project".to_string(), project.to_string())]
+            .into_iter()
+            .collect(),
+        prior_cost: 0.0,
+    }
+}
+
+fn limits(hourly: Option<f64>, daily: Option<f64>, actions: Vec<BudgetAction>) -> BudgetLimits {
+    BudgetLimits {
+        hourly,
+        daily,
+        monthly: None,
+        actions,
+    }
+}
+
+#[test]
+fn test_spend_counts_only_inside_the_period() {
+    let now = Utc.with_ymd_and_hms(2026, 3, 10, 6, 0, 0).unwrap();
+    // Launched at 22:00 the day before: 8 hours this month, 6 of them today
+    let running = resource(
+        "i-1",
+        "demo",
+        ResourceState::Running,
+        now - Duration::hours(8),
+    );
+    let midnight = Utc.with_ymd_and_hms(2026, 3, 10, 0, 0, 0).unwrap();
+    assert!((spend_since(&running, midnight, now) - 12.0).abs() < 0.01);
+
+    let report = evaluate(
+        &BudgetConfig {
+            default: Some(limits(None, Some(10.0), vec![BudgetAction::Warn])),
+            ..Default::default()
+        },
+        &[running],
+        now,
+    );
+    assert_eq!(report.len(), 1);
+    assert!((report[0].spend.this_month - 16.0).abs() < 0.01);
+    assert_eq!(report[0].breaches.len(), 1);
+    assert_eq!(report[0].breaches[0].period, BudgetPeriod::Daily);
+    assert!(report[0].wants(BudgetAction::Warn));
+    assert!(!report[0].wants(BudgetAction::Stop));
+}
+
+#[test]
+fn test_projects_are_limited_separately() {
+    let now = Utc::now();
+    let resources = vec![
+        resource("i-a", "alpha", ResourceState::Running, now),
+        resource("i-b", "beta", ResourceState::Running, now),
+        resource("i-c", "beta", ResourceState::Running, now),
+    ];
+    let config = BudgetConfig {
+        projects: [
+            (
+                "beta".to_string(),
+                limits(Some(3.0), None, vec![BudgetAction::Stop]),
+            ),
+            (
+                "gamma".to_string(),
+                limits(Some(1.0), None, vec![BudgetAction::Warn]),
+            ),
+        ]
+        .into_iter()
+        .collect(),
+        ..Default::default()
+    };
+
+    let report = evaluate(&config, &resources, now);
+    // alpha has no limits; gamma is listed although it has nothing running
+    let names: Vec<&str> = report.iter().map(|b| b.project.as_str()).collect();
+    assert_eq!(names, vec!["beta", "gamma"]);
+    assert!(report[0].wants(BudgetAction::Stop));
+    assert_eq!(report[0].spend.running.len(), 2);
+    assert!(!report[1].is_exceeded());
+}
+
+#[test]
+fn test_accounts_are_limited_by_account_tag() {
+    let now = Utc::now();
+    let in_account = |id: &str, project: &str, account: &str| {
+        let mut r = resource(id, project, ResourceState::Running, now);
+        r.tags.insert(ACCOUNT_TAG.to_string(), account.to_string());
+        r
+    };
+    let resources = vec![
+        in_account("i-a", "alpha", "sandbox-a"),
+        in_account("i-b", "beta", "sandbox-a"),
+        in_account("i-c", "alpha", "sandbox-b"),
+        resource("i-d", "alpha", ResourceState::Running, now),
+    ];
+    let config = BudgetConfig {
+        accounts: [
+            (
+                "sandbox-a".to_string(),
+                limits(Some(3.0), None, vec![BudgetAction::Notify]),
+            ),
+            (
+                "sandbox-c".to_string(),
+                limits(Some(1.0), None, vec![BudgetAction::Warn]),
+            ),
+        ]
+        .into_iter()
+        .collect(),
+        ..Default::default()
+    };
+
+    // Only configured accounts, across projects; sandbox-c has no spend
+    let report = evaluate_accounts(&config, &resources, now);
+    let names: Vec<&str> = report.iter().map(|b| b.project.as_str()).collect();
+    assert_eq!(names, vec!["sandbox-a", "sandbox-c"]);
+    assert_eq!(report[0].spend.running.len(), 2);
+    assert!(report[0].wants(BudgetAction::Notify));
+    assert!(!report[1].is_exceeded());
+    // Account limits don't turn into project limits
+    assert!(evaluate(&config, &resources, now).is_empty());
+}
+
+#[test]
+fn test_refuse_blocks_launches_over_the_hourly_limit() {
+    let now = Utc::now();
+    let resources = vec![resource("i-a", "demo", ResourceState::Running, now)];
+    let refuse = BudgetConfig {
+        default: Some(limits(
+            Some(3.0),
+            None,
+            vec![BudgetAction::Warn, BudgetAction::Refuse],
+        )),
+        ..Default::default()
+    };
+
+    assert!(check_launch(&refuse, &resources, "demo", 0.5, now)
+        .unwrap()
+        .is_empty());
+    let err = check_launch(&refuse, &resources, "demo", 2.0, now).unwrap_err();
+    assert!(err.to_string().contains("over budget"));
+
+    let warn_only = BudgetConfig {
+        default: Some(limits(Some(3.0), None, vec![BudgetAction::Warn])),
+        ..Default::default()
+    };
+    let exceeded = check_launch(&warn_only, &resources, "demo", 2.0, now).unwrap();
+    assert_eq!(exceeded[0].period, BudgetPeriod::Hourly);
+}
+
+#[test]
+fn test_budget_config_parses() {
+    let config: Config = toml::from_str(
+        r#"
+        [checkpoint]
+        dir = "checkpoints"
+        save_interval = 5
+        keep_last_n = 10
+
+        [monitoring]
+        log_dir = "logs"
+        update_interval_secs = 10
+        enable_warnings = true
+
+        [budget.default]
+        daily = 50.0
+
+        [budget.projects.llm]
+        hourly = 20.0
+        monthly = 2000.0
+        actions = ["warn", "refuse", "stop", "notify"]
+
+        [budget.accounts.sandbox-a]
+        monthly = 500.0
+
+        [accounts.sandbox-a]
+        profile = "sandbox-a"
+
+        [accounts.shared]
+        role_arn = "arn:aws:iam::123456789012:role/runctl-readonly"
+        region = "us-west-2"
+        "#,
+    )
+    .unwrap();
+
+    let default = config.budget.limits_for("other").unwrap();
+    assert_eq!(default.daily, Some(50.0));
+    assert_eq!(default.actions, vec![BudgetAction::Warn]);
+    let llm = config.budget.limits_for("llm").unwrap();
+    assert_eq!(llm.monthly, Some(2000.0));
+    assert!(llm.actions.contains(&BudgetAction::Refuse));
+    assert_eq!(config.budget.accounts["sandbox-a"].monthly, Some(500.0));
+
+    assert_eq!(config.accounts.len(), 2);
+    assert_eq!(
+        config.accounts["sandbox-a"].profile.as_deref(),
+        Some("sandbox-a")
+    );
+    let shared = &config.accounts["shared"];
+    assert!(shared.profile.is_none());
+    assert_eq!(shared.region.as_deref(), Some("us-west-2"));
+}
@@ -0,0 +1,84 @@
+//! Tests for the local checkpoint store
+
+use runctl::checkpoint::{CheckpointStore, LocalCheckpointStore};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+/// Write a checkpoint modified `age_secs` ago
+fn write_checkpoint(dir: &Path, name: &str, age_secs: u64) {
+    let path = dir.join(name);
+    fs::write(&path, name.as_bytes()).unwrap();
+    let file = fs::File::options().write(true).open(&path).unwrap();
+    file.set_modified(SystemTime::now() - Duration::from_secs(age_secs))
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_list_latest_and_best() {
+    let temp = TempDir::new().unwrap();
+    write_checkpoint(temp.path(), "epoch_1_val_loss=0.50.pt", 300);
+    write_checkpoint(temp.path(), "epoch_2_val_loss=0.21.pt", 200);
+    write_checkpoint(temp.path(), "epoch_3_val_loss=0.34.pt", 100);
+    fs::write(temp.path().join("notes.txt"), b"not a checkpoint").unwrap();
+    let store = LocalCheckpointStore::new(temp.path());
+
+    let names: Vec<String> = store
+        .list()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|c| c.name)
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            "epoch_3_val_loss=0.34.pt",
+            "epoch_2_val_loss=0.21.pt",
+            "epoch_1_val_loss=0.50.pt"
+        ]
+    );
+
+    let latest = store.latest().await.unwrap().unwrap();
+    assert_eq!(latest.epoch, Some(3));
+    let best = store.best("val_loss", false).await.unwrap().unwrap();
+    assert_eq!(best.name, "epoch_2_val_loss=0.21.pt");
+    let worst = store.best("val_loss", true).await.unwrap().unwrap();
+    assert_eq!(worst.epoch, Some(1));
+    assert!(store.best("accuracy", true).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_put_get_delete() {
+    let temp = TempDir::new().unwrap();
+    let store = LocalCheckpointStore::new(temp.path().join("store"));
+    assert!(store.list().await.unwrap().is_empty());
+
+    let src = temp.path().join("model.pt");
+    fs::write(&src, b"weights").unwrap();
+    let meta = store.put(&src, Some("epoch_4.pt")).await.unwrap();
+    assert_eq!(meta.size, 7);
+    assert_eq!(meta.epoch, Some(4));
+    assert_eq!(store.stat("epoch_4.pt").await.unwrap().size, 7);
+
+    let dest = temp.path().join("restored/epoch_4.pt");
+    store.get("epoch_4.pt", &dest).await.unwrap();
+    assert_eq!(fs::read(&dest).unwrap(), b"weights");
+
+    store.delete("epoch_4.pt").await.unwrap();
+    assert!(store.list().await.unwrap().is_empty());
+    assert!(store.get("epoch_4.pt", &dest).await.is_err());
+}
+
+#[tokio::test]
+async fn test_rejects_names_outside_the_store() {
+    let temp = TempDir::new().unwrap();
+    let store = LocalCheckpointStore::new(temp.path());
+    let src = temp.path().join("model.pt");
+    fs::write(&src, b"weights").unwrap();
+
+    assert!(store.put(&src, Some("../escape.pt")).await.is_err());
+    assert!(store.put(&src, Some("model.txt")).await.is_err());
+    assert!(store.delete("../model.pt").await.is_err());
+}
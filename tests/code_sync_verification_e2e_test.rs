@@ -11,6 +11,8 @@
 //!
 //! Cost: ~$0.10-0.30 per run (uses t3.micro, minimal time)
 
+#![cfg(feature = "aws")]
+
 use aws_config::BehaviorVersion;
 use aws_sdk_ec2::Client as Ec2Client;
 use aws_sdk_ssm::Client as SsmClient;
@@ -12,6 +12,8 @@
 //!
 //! Cost: ~$0.10-0.50 per run (uses t3.micro, minimal training)
 
+#![cfg(feature = "aws")]
+
 use aws_config::BehaviorVersion;
 use aws_sdk_ec2::Client as Ec2Client;
 use aws_sdk_ssm::Client as SsmClient;
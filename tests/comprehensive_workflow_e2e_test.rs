@@ -7,6 +7,8 @@
 //!
 //! Cost: ~$1-3 per run (creates instance, runs full training workflow)
 
+#![cfg(feature = "aws")]
+
 use aws_config::BehaviorVersion;
 use aws_sdk_ec2::Client as Ec2Client;
 use aws_sdk_ssm::Client as SsmClient;
@@ -19,6 +19,7 @@ async fn test_get_instance_cost_with_tracker_uses_tracker() {
         launch_time: Some(Utc::now() - Duration::hours(2)),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -109,6 +110,7 @@ async fn test_get_instance_cost_with_tracker_not_running() {
         launch_time: Some(Utc::now() - Duration::hours(1)),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -159,6 +161,7 @@ async fn test_cost_accumulation_over_time() {
         launch_time: Some(now - Duration::minutes(30)),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -170,6 +173,7 @@ async fn test_cost_accumulation_over_time() {
         launch_time: Some(now - Duration::hours(10)),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
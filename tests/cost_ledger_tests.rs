@@ -0,0 +1,72 @@
+//! Tests for the resource tracker writing to the cost ledger
+
+use chrono::{Duration, Utc};
+use runctl::costs::{Ledger, LedgerEvent};
+use runctl::provider::{ResourceState, ResourceStatus};
+use runctl::resource_tracking::ResourceTracker;
+use tempfile::TempDir;
+
+fn status(id: &str) -> ResourceStatus {
+    ResourceStatus {
+        id: id.to_string(),
+        name: None,
+        state: ResourceState::Running,
+        instance_type: Some("g5.xlarge".to_string()),
+        launch_time: Some(Utc::now() - Duration::hours(2)),
+        cost_per_hour: 1.0,
+        public_ip: None,
+        public_ipv6: None,
+        tags: vec![("runctl:project".to_string(), "demo".to_string())],
+    }
+}
+
+#[tokio::test]
+async fn test_lifecycle_is_recorded() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("ledger.jsonl");
+    let tracker = ResourceTracker::new().with_ledger(Ledger::new(&path));
+
+    tracker.register(status("i-ledger")).await.unwrap();
+    tracker
+        .update_state(&"i-ledger".to_string(), ResourceState::Stopped)
+        .await
+        .unwrap();
+    tracker.remove(&"i-ledger".to_string()).await.unwrap();
+
+    let entries = Ledger::new(&path).load().unwrap();
+    let events: Vec<LedgerEvent> = entries.iter().map(|e| e.event).collect();
+    assert_eq!(
+        events,
+        vec![
+            LedgerEvent::Launched,
+            LedgerEvent::Stopped,
+            LedgerEvent::Terminated
+        ]
+    );
+    assert_eq!(entries[0].project.as_deref(), Some("demo"));
+    // Launched two hours ago, so the cost so far is already on the first entry
+    assert!((entries[0].accumulated_cost - 2.0).abs() < 0.01);
+    // The cost survives the resource leaving the tracker
+    assert!(entries[2].accumulated_cost >= entries[0].accumulated_cost);
+}
+
+#[tokio::test]
+async fn test_snapshots_are_hourly() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("ledger.jsonl");
+    let tracker = ResourceTracker::new().with_ledger(Ledger::new(&path));
+    tracker.register(status("i-snap")).await.unwrap();
+
+    // The launch entry already covers this hour
+    tracker.refresh_costs().await;
+    tracker.refresh_costs().await;
+    let entries = Ledger::new(&path).load().unwrap();
+    assert_eq!(entries.len(), 1);
+
+    // A fresh process starting in a later hour snapshots again
+    let later = Utc::now() + Duration::hours(1);
+    let written = Ledger::new(&path)
+        .record_snapshots(&tracker.get_running().await, later)
+        .unwrap();
+    assert_eq!(written, 1);
+}
@@ -6,6 +6,8 @@
 //!
 //! Cost: ~$0.00 (read-only operations)
 
+#![cfg(feature = "aws")]
+
 use aws_config::BehaviorVersion;
 use aws_sdk_ec2::Client as Ec2Client;
 use std::env;
@@ -2,6 +2,8 @@
 //!
 //! Tests verify path parsing, location detection, and transfer logic.
 
+#![cfg(feature = "aws")]
+
 use runctl::data_transfer::{DataLocation, TransferOptions};
 use std::path::PathBuf;
 
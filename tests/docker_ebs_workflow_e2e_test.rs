@@ -11,6 +11,8 @@
 //!
 //! Cost: ~$0.20-1.00 per run (uses t3.micro, creates EBS volume)
 
+#![cfg(feature = "aws")]
+
 use aws_config::BehaviorVersion;
 use aws_sdk_ec2::Client as Ec2Client;
 use aws_sdk_ssm::Client as SsmClient;
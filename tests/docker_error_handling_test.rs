@@ -6,6 +6,8 @@
 //! - Container execution failures
 //! - Missing Dockerfile handling
 
+#![cfg(feature = "aws")]
+
 use std::path::PathBuf;
 use tempfile::TempDir;
 
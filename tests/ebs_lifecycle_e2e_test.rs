@@ -9,6 +9,8 @@
 //!
 //! Cost: ~$0.10-0.30 per test run
 
+#![cfg(feature = "aws")]
+
 use aws_config::BehaviorVersion;
 use aws_sdk_ec2::Client as Ec2Client;
 use std::env;
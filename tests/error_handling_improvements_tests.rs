@@ -8,6 +8,8 @@
 //! Note: Some functions are tested indirectly through public APIs
 //! since the internal functions are private.
 
+#![cfg(feature = "aws")]
+
 #[test]
 fn test_error_handling_improvements_documented() {
     // This test documents the error handling improvements made:
@@ -106,3 +106,16 @@ fn test_error_chain_preservation() {
     // Source error should be preserved
     assert!(msg.contains("File not found") || msg.contains("not found"));
 }
+
+#[test]
+fn test_feature_not_compiled_names_the_feature() {
+    let err = TrainctlError::from(ConfigError::FeatureNotCompiled("dashboard".to_string()));
+
+    let msg = format!("{}", err);
+    assert!(msg.contains("`dashboard` feature"));
+    assert!(msg.contains("--features dashboard"));
+    assert_eq!(
+        runctl::exit_codes::exit_code_for_error(&err),
+        runctl::exit_codes::codes::CONFIG_ERROR
+    );
+}
@@ -9,6 +9,8 @@
 //!
 //! Run with: TRAINCTL_E2E=1 cargo test --test error_scenarios_e2e_test --features e2e -- --ignored
 
+#![cfg(feature = "aws")]
+
 use aws_config::BehaviorVersion;
 use aws_sdk_ec2::Client as Ec2Client;
 use std::env;
@@ -26,6 +26,7 @@ async fn test_concurrent_registration() {
                 launch_time: Some(Utc::now()),
                 cost_per_hour: 0.01,
                 public_ip: None,
+                public_ipv6: None,
                 tags: vec![],
             };
             tracker_clone.register(status).await
@@ -63,6 +64,7 @@ async fn test_concurrent_read_write() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.10,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
     tracker.register(status).await.expect("Should register");
@@ -130,6 +132,7 @@ async fn test_concurrent_cost_calculation() {
             launch_time: Some(Utc::now() - chrono::Duration::hours(i)),
             cost_per_hour: 0.10,
             public_ip: None,
+            public_ipv6: None,
             tags: vec![],
         };
         tracker.register(status).await.expect("Should register");
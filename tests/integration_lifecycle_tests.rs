@@ -26,6 +26,7 @@ async fn test_lifecycle_create_registers_resource() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: Some("1.2.3.4".to_string()),
+        public_ipv6: None,
         tags: vec![("Project".to_string(), "test".to_string())],
     };
 
@@ -61,6 +62,7 @@ async fn test_lifecycle_start_updates_state() {
         launch_time: Some(Utc::now() - Duration::hours(1)),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -99,6 +101,7 @@ async fn test_lifecycle_stop_updates_state() {
         launch_time: Some(Utc::now() - Duration::hours(1)),
         cost_per_hour: 0.01,
         public_ip: Some("1.2.3.4".to_string()),
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -157,6 +160,7 @@ async fn test_lifecycle_terminate_removes_resource() {
         launch_time: Some(Utc::now() - Duration::hours(1)),
         cost_per_hour: 0.01,
         public_ip: Some("1.2.3.4".to_string()),
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -193,6 +197,7 @@ async fn test_lifecycle_spot_instance_registration() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.50, // Spot instances can have different pricing
         public_ip: Some("5.6.7.8".to_string()),
+        public_ipv6: None,
         tags: vec![
             ("Spot".to_string(), "true".to_string()),
             ("Project".to_string(), "test".to_string()),
@@ -231,6 +236,7 @@ async fn test_lifecycle_full_workflow() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: Some("1.2.3.4".to_string()),
+        public_ipv6: None,
         tags: vec![("Project".to_string(), "test".to_string())],
     };
 
@@ -282,6 +288,7 @@ async fn test_lifecycle_cost_accumulation_through_states() {
         launch_time: Some(Utc::now() - Duration::hours(2)),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
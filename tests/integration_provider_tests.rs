@@ -3,6 +3,8 @@
 //! These tests verify that the provider trait system works correctly,
 //! even though it's not yet used by the CLI (see docs/PROVIDER_TRAIT_DECISION.md).
 
+#![cfg(feature = "aws")]
+
 use chrono::Utc;
 use runctl::provider::{ResourceState, ResourceStatus, TrainingProvider};
 use runctl::providers::AwsProvider;
@@ -73,6 +75,7 @@ async fn test_resource_status_conversion() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.10,
         public_ip: Some("1.2.3.4".to_string()),
+        public_ipv6: None,
         tags: vec![("runctl:project".to_string(), "test".to_string())],
     };
 
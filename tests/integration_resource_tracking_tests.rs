@@ -23,6 +23,7 @@ async fn test_integration_cost_tracking_workflow() {
             launch_time: Some(Utc::now() - Duration::hours(2)),
             cost_per_hour: 0.01,
             public_ip: Some("1.2.3.4".to_string()),
+            public_ipv6: None,
             tags: vec![("Project".to_string(), "test".to_string())],
         },
         ResourceStatus {
@@ -33,6 +34,7 @@ async fn test_integration_cost_tracking_workflow() {
             launch_time: Some(Utc::now() - Duration::hours(1)),
             cost_per_hour: 0.50,
             public_ip: Some("5.6.7.8".to_string()),
+            public_ipv6: None,
             tags: vec![("Project".to_string(), "test".to_string())],
         },
         ResourceStatus {
@@ -43,6 +45,7 @@ async fn test_integration_cost_tracking_workflow() {
             launch_time: Some(Utc::now() - Duration::hours(3)),
             cost_per_hour: 0.02,
             public_ip: None,
+            public_ipv6: None,
             tags: vec![],
         },
     ];
@@ -138,6 +141,7 @@ async fn test_integration_protected_resource_cleanup() {
         launch_time: Some(Utc::now() - Duration::hours(5)),
         cost_per_hour: 0.50,
         public_ip: Some("10.0.0.1".to_string()),
+        public_ipv6: None,
         tags: vec![
             ("runctl:protected".to_string(), "true".to_string()),
             ("Project".to_string(), "production".to_string()),
@@ -152,6 +156,7 @@ async fn test_integration_protected_resource_cleanup() {
         launch_time: Some(Utc::now() - Duration::hours(1)),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![("Project".to_string(), "test".to_string())],
     };
 
@@ -200,6 +205,7 @@ async fn test_integration_cost_accumulation_with_usage() {
         launch_time: Some(Utc::now() - Duration::hours(1)),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
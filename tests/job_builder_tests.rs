@@ -0,0 +1,250 @@
+//! Tests for submitting jobs through the library API
+
+use async_trait::async_trait;
+use runctl::error::{JobOutcome, Result, TrainctlError};
+use runctl::provider::{
+    CreateResourceOptions, ExecutionStatus, ResourceId, ResourceState, ResourceStatus, TrainingJob,
+    TrainingProvider, TrainingStatus as JobStatus,
+};
+use runctl::training::{LaunchStage, TrainingSession, TrainingStatus};
+use runctl::{CheckpointPolicy, Config, JobBuilder, JobHandle};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tempfile::TempDir;
+
+/// Records what it is asked to do; `train` fails when `fail_train` is set
+#[derive(Default)]
+struct FakeProvider {
+    fail_train: bool,
+    created: Mutex<Vec<(String, CreateResourceOptions)>>,
+    jobs: Mutex<Vec<TrainingJob>>,
+    terminated: Mutex<Vec<ResourceId>>,
+    statuses: Mutex<Vec<ExecutionStatus>>,
+}
+
+#[async_trait]
+impl TrainingProvider for FakeProvider {
+    fn name(&self) -> &'static str {
+        "fake"
+    }
+
+    async fn create_resource(
+        &self,
+        instance_type: &str,
+        options: CreateResourceOptions,
+    ) -> Result<ResourceId> {
+        self.created
+            .lock()
+            .unwrap()
+            .push((instance_type.to_string(), options));
+        Ok("res-1".to_string())
+    }
+
+    async fn get_resource_status(&self, resource_id: &ResourceId) -> Result<ResourceStatus> {
+        Ok(ResourceStatus {
+            id: resource_id.clone(),
+            name: None,
+            state: ResourceState::Running,
+            instance_type: None,
+            launch_time: None,
+            cost_per_hour: 1.0,
+            public_ip: None,
+            public_ipv6: None,
+            tags: vec![],
+        })
+    }
+
+    async fn list_resources(&self) -> Result<Vec<ResourceStatus>> {
+        Ok(vec![])
+    }
+
+    async fn train(&self, resource_id: &ResourceId, job: TrainingJob) -> Result<JobStatus> {
+        if self.fail_train {
+            return Err(TrainctlError::CloudProvider {
+                provider: "fake".to_string(),
+                message: "sync failed".to_string(),
+                source: None,
+            });
+        }
+        self.jobs.lock().unwrap().push(job);
+        Ok(JobStatus {
+            job_id: Some(resource_id.clone()),
+            status: ExecutionStatus::Running,
+            log_output: None,
+            checkpoint_path: None,
+        })
+    }
+
+    async fn monitor(&self, _resource_id: &ResourceId, _follow: bool) -> Result<()> {
+        Ok(())
+    }
+
+    async fn download(&self, _: &ResourceId, _: &Path, _: &Path) -> Result<()> {
+        Ok(())
+    }
+
+    async fn terminate(&self, resource_id: &ResourceId) -> Result<()> {
+        self.terminated.lock().unwrap().push(resource_id.clone());
+        Ok(())
+    }
+
+    fn estimate_cost(&self, _instance_type: &str, hours: f64) -> f64 {
+        hours
+    }
+
+    fn default_instance_type(&self) -> Option<String> {
+        Some("small".to_string())
+    }
+
+    async fn training_status(&self, _resource_id: &ResourceId) -> Result<ExecutionStatus> {
+        // Pops queued statuses, then reports completion
+        Ok(self
+            .statuses
+            .lock()
+            .unwrap()
+            .pop()
+            .unwrap_or(ExecutionStatus::Completed))
+    }
+}
+
+fn script(dir: &TempDir) -> std::path::PathBuf {
+    let path = dir.path().join("train.py");
+    std::fs::write(&path, "print('hi')").unwrap();
+    path
+}
+
+#[tokio::test]
+async fn test_submit_passes_the_job_spec_and_records_the_session() {
+    let dir = TempDir::new().unwrap();
+    let sessions = dir.path().join(".runctl");
+    let provider = Arc::new(FakeProvider::default());
+
+    let handle = JobBuilder::new("fake", script(&dir))
+        .spot(true)
+        .args(["--epochs", "3"])
+        .env("WANDB_MODE", "offline")
+        .data("s3://bucket/data/")
+        .checkpoints(CheckpointPolicy::new("ckpt").sync_to("s3://bucket/ckpt/"))
+        .project("demo")
+        .tag("team", "research")
+        .sessions_dir(&sessions)
+        .submit_to(provider.clone(), &Config::default())
+        .await
+        .unwrap();
+
+    assert_eq!(handle.resource_id(), "res-1");
+    assert_eq!(handle.provider(), "fake");
+    let created = provider.created.lock().unwrap();
+    let (instance_type, options) = &created[0];
+    assert_eq!(instance_type, "small");
+    assert!(options.use_spot);
+    assert!(options
+        .tags
+        .contains(&("runctl:project".to_string(), "demo".to_string())));
+    assert!(options
+        .tags
+        .contains(&("team".to_string(), "research".to_string())));
+
+    let jobs = provider.jobs.lock().unwrap();
+    assert_eq!(jobs[0].args, vec!["--epochs", "3"]);
+    assert_eq!(jobs[0].data_source.as_deref(), Some("s3://bucket/data/"));
+    assert_eq!(jobs[0].output_dest.as_deref(), Some("s3://bucket/ckpt/"));
+    assert_eq!(jobs[0].checkpoint_dir.as_deref(), Some(Path::new("ckpt")));
+    assert_eq!(
+        jobs[0].environment,
+        vec![("WANDB_MODE".to_string(), "offline".to_string())]
+    );
+
+    let saved = TrainingSession::load(&sessions, handle.session_id()).unwrap();
+    assert!(matches!(saved.status, TrainingStatus::Running));
+    assert_eq!(saved.resource_id(), Some("res-1"));
+    assert_eq!(
+        saved.launch.as_ref().map(|l| l.stage),
+        Some(LaunchStage::TrainingStarted)
+    );
+}
+
+#[tokio::test]
+async fn test_failed_start_terminates_the_resource() {
+    let dir = TempDir::new().unwrap();
+    let sessions = dir.path().join(".runctl");
+    let provider = Arc::new(FakeProvider {
+        fail_train: true,
+        ..Default::default()
+    });
+
+    let err = JobBuilder::new("fake", script(&dir))
+        .sessions_dir(&sessions)
+        .submit_to(provider.clone(), &Config::default())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("sync failed"));
+    assert_eq!(
+        *provider.terminated.lock().unwrap(),
+        vec!["res-1".to_string()]
+    );
+
+    let saved = TrainingSession::list_sessions(&sessions).unwrap();
+    assert!(matches!(saved[0].status, TrainingStatus::Failed(_)));
+}
+
+#[tokio::test]
+async fn test_invalid_spec_is_rejected_before_provisioning() {
+    let dir = TempDir::new().unwrap();
+    let provider = Arc::new(FakeProvider::default());
+
+    let missing = JobBuilder::new("fake", dir.path().join("missing.py"))
+        .submit_to(provider.clone(), &Config::default())
+        .await;
+    assert!(missing.is_err());
+    let bad_env = JobBuilder::new("fake", script(&dir))
+        .env("NOT-VALID", "1")
+        .submit_to(provider.clone(), &Config::default())
+        .await;
+    assert!(bad_env.is_err());
+    let bad_data = JobBuilder::new("fake", script(&dir))
+        .data("/local/path")
+        .submit_to(provider.clone(), &Config::default())
+        .await;
+    assert!(bad_data.is_err());
+    assert!(provider.created.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_wait_records_the_outcome_and_cancel_terminates() {
+    let dir = TempDir::new().unwrap();
+    let sessions = dir.path().join(".runctl");
+    let provider = Arc::new(FakeProvider::default());
+    provider.statuses.lock().unwrap().extend([
+        ExecutionStatus::Failed("exit code 1".to_string()),
+        ExecutionStatus::Running,
+    ]);
+
+    let mut handle = JobBuilder::new("fake", script(&dir))
+        .sessions_dir(&sessions)
+        .submit_to(provider.clone(), &Config::default())
+        .await
+        .unwrap();
+    let err = handle
+        .wait(Duration::from_millis(1), None)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        TrainctlError::Job {
+            outcome: JobOutcome::Failed,
+            ..
+        }
+    ));
+    let saved = TrainingSession::load(&sessions, handle.session_id()).unwrap();
+    assert!(matches!(saved.status, TrainingStatus::Failed(_)));
+
+    // Re-attached handles act on the same resource
+    let mut attached = JobHandle::from_session(provider.clone(), saved, &sessions).unwrap();
+    attached.cancel().await.unwrap();
+    assert_eq!(
+        *provider.terminated.lock().unwrap(),
+        vec!["res-1".to_string()]
+    );
+}
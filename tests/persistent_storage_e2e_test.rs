@@ -7,6 +7,8 @@
 //!
 //! Cost: ~$0.10-0.50 per test run (creates/deletes volumes and instances)
 
+#![cfg(feature = "aws")]
+
 use aws_config::BehaviorVersion;
 use aws_sdk_ec2::Client as Ec2Client;
 use std::env;
@@ -39,6 +39,7 @@ fn test_resource_status() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.0416,
         public_ip: Some("1.2.3.4".to_string()),
+        public_ipv6: None,
         tags: vec![("Name".to_string(), "test".to_string())],
     };
 
@@ -3,6 +3,8 @@
 //! Tests that resources are properly cleaned up after E2E tests.
 //! Run with: TRAINCTL_E2E=1 cargo test --test resource_cleanup_test --features e2e
 
+#![cfg(feature = "aws")]
+
 use aws_config::BehaviorVersion;
 use aws_sdk_ec2::Client as Ec2Client;
 use std::env;
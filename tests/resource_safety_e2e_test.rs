@@ -10,6 +10,8 @@
 //!
 //! Cost: ~$0.20-1.00 per test run (creates instances/volumes)
 
+#![cfg(feature = "aws")]
+
 use aws_config::BehaviorVersion;
 use aws_sdk_ec2::Client as Ec2Client;
 use std::env;
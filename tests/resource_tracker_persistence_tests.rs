@@ -0,0 +1,147 @@
+//! Tests for the file-backed resource tracker
+//!
+//! Separate `ResourceTracker::persistent` instances on one file stand in for
+//! concurrent runctl processes.
+
+use chrono::Utc;
+use fs2::FileExt;
+use runctl::provider::{ResourceState, ResourceStatus};
+use runctl::resource_tracking::{repair_store, ResourceTracker};
+use std::fs;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+fn status(id: &str) -> ResourceStatus {
+    ResourceStatus {
+        id: id.to_string(),
+        name: None,
+        state: ResourceState::Running,
+        instance_type: Some("t3.micro".to_string()),
+        launch_time: Some(Utc::now()),
+        cost_per_hour: 0.01,
+        public_ip: None,
+        public_ipv6: None,
+        tags: vec![],
+    }
+}
+
+#[tokio::test]
+async fn test_persistent_tracker_survives_reload() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("resources.json");
+
+    let tracker = ResourceTracker::persistent(&path);
+    tracker.register(status("i-persist")).await.unwrap();
+    tracker
+        .update_state(&"i-persist".to_string(), ResourceState::Stopped)
+        .await
+        .unwrap();
+
+    let reopened = ResourceTracker::persistent(&path);
+    let tracked = reopened.get_by_id(&"i-persist".to_string()).await.unwrap();
+    assert_eq!(tracked.status.state, ResourceState::Stopped);
+    // The lock file stays, but nothing holds it
+    let lock = fs::File::open(dir.path().join("resources.json.lock")).unwrap();
+    lock.try_lock_exclusive().unwrap();
+}
+
+#[tokio::test]
+async fn test_concurrent_trackers_keep_every_update() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("resources.json");
+
+    let mut handles = vec![];
+    for i in 0..10 {
+        // A separate tracker per task, like separate processes
+        let tracker = Arc::new(ResourceTracker::persistent(&path));
+        handles.push(tokio::spawn(async move {
+            tracker.register(status(&format!("i-{}", i))).await
+        }));
+    }
+    for handle in handles {
+        handle.await.unwrap().unwrap();
+    }
+
+    let tracker = ResourceTracker::persistent(&path);
+    assert_eq!(tracker.get_running().await.len(), 10);
+}
+
+#[tokio::test]
+async fn test_tracker_sees_other_process_changes() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("resources.json");
+
+    let watcher = ResourceTracker::persistent(&path);
+    assert!(watcher.get_running().await.is_empty());
+
+    let creator = ResourceTracker::persistent(&path);
+    creator.register(status("i-new")).await.unwrap();
+
+    assert!(watcher.exists(&"i-new".to_string()).await);
+}
+
+#[tokio::test]
+async fn test_damaged_file_recovers_from_backup() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("resources.json");
+
+    let tracker = ResourceTracker::persistent(&path);
+    tracker.register(status("i-a")).await.unwrap();
+    // The second write copies the first version to the backup
+    tracker.register(status("i-b")).await.unwrap();
+    fs::write(&path, "{ truncated").unwrap();
+
+    let reopened = ResourceTracker::persistent(&path);
+    assert!(reopened.exists(&"i-a".to_string()).await);
+
+    let report = repair_store(&path, false).await.unwrap();
+    assert_eq!(report.recovered_from.as_deref(), Some("backup"));
+    assert_eq!(report.resources, 1);
+    assert!(report.damaged_file.unwrap().exists());
+    assert!(repair_store(&path, false).await.unwrap().is_healthy());
+}
+
+#[tokio::test]
+async fn test_repair_drops_bad_entries_despite_leftover_lock_file() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("resources.json");
+
+    let tracker = ResourceTracker::persistent(&path);
+    tracker.register(status("i-good")).await.unwrap();
+    let mut content: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    content["resources"]
+        .as_array_mut()
+        .unwrap()
+        .push(serde_json::json!({ "status": "not a resource" }));
+    fs::write(&path, content.to_string()).unwrap();
+    // A lock file left by a crashed process isn't locked
+    fs::write(dir.path().join("resources.json.lock"), "4000000000").unwrap();
+
+    let dry_run = repair_store(&path, true).await.unwrap();
+    assert_eq!(dry_run.dropped_entries, 1);
+
+    let report = repair_store(&path, false).await.unwrap();
+    assert_eq!(report.dropped_entries, 1);
+    assert_eq!(report.resources, 1);
+
+    tracker.register(status("i-next")).await.unwrap();
+    assert_eq!(tracker.get_running().await.len(), 2);
+}
+
+#[tokio::test]
+async fn test_repair_refuses_a_held_lock() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("resources.json");
+    ResourceTracker::persistent(&path)
+        .register(status("i-held"))
+        .await
+        .unwrap();
+
+    let holder = fs::File::open(dir.path().join("resources.json.lock")).unwrap();
+    holder.lock_exclusive().unwrap();
+    assert!(repair_store(&path, false).await.is_err());
+
+    FileExt::unlock(&holder).unwrap();
+    assert!(repair_store(&path, false).await.unwrap().is_healthy());
+}
@@ -24,6 +24,7 @@ proptest! {
             launch_time: Some(Utc::now() - Duration::hours(hours_ago)),
             cost_per_hour,
             public_ip: None,
+            public_ipv6: None,
             tags: vec![],
         };
 
@@ -57,6 +58,7 @@ proptest! {
                 launch_time: Some(Utc::now() - Duration::hours(hours1)),
                 cost_per_hour,
                 public_ip: None,
+                public_ipv6: None,
                 tags: vec![],
             };
 
@@ -68,6 +70,7 @@ proptest! {
                 launch_time: Some(Utc::now() - Duration::hours(hours2)),
                 cost_per_hour,
                 public_ip: None,
+                public_ipv6: None,
                 tags: vec![],
             };
 
@@ -100,6 +103,7 @@ proptest! {
             launch_time: Some(Utc::now() - Duration::hours(hours_ago)),
             cost_per_hour,
             public_ip: None,
+            public_ipv6: None,
             tags: vec![],
         };
 
@@ -132,6 +136,7 @@ proptest! {
                     launch_time: Some(Utc::now()),
                     cost_per_hour: 0.01,
                     public_ip: None,
+                    public_ipv6: None,
                     tags: vec![],
                 };
 
@@ -167,6 +172,7 @@ proptest! {
             launch_time: Some(Utc::now()),
             cost_per_hour: 0.01,
             public_ip: None,
+            public_ipv6: None,
             tags: vec![],
         };
 
@@ -213,6 +219,7 @@ proptest! {
                     launch_time: Some(Utc::now() - Duration::hours(i as i64)),
                     cost_per_hour,
                     public_ip: None,
+                    public_ipv6: None,
                     tags: vec![],
                 };
 
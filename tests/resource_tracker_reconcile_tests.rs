@@ -0,0 +1,172 @@
+//! Tests for reconciling tracked resources with live provider state
+
+use chrono::{Duration, Utc};
+use runctl::provider::{ResourceState, ResourceStatus};
+use runctl::resource_tracking::budget::ACCOUNT_TAG;
+use runctl::resource_tracking::{
+    reconcile_resource, Reconciliation, ResourceTracker, TrackedResource,
+};
+
+fn status(id: &str, state: ResourceState, hours_ago: i64) -> ResourceStatus {
+    ResourceStatus {
+        id: id.to_string(),
+        name: None,
+        state,
+        instance_type: Some("t3.micro".to_string()),
+        launch_time: Some(Utc::now() - Duration::hours(hours_ago)),
+        cost_per_hour: 1.0,
+        public_ip: None,
+        public_ipv6: None,
+        tags: vec![],
+    }
+}
+
+async fn tracked(tracker: &ResourceTracker, id: &str) -> TrackedResource {
+    tracker.get_by_id(&id.to_string()).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_reconcile_registers_unknown_resource() {
+    let tracker = ResourceTracker::new();
+    let outcome = tracker
+        .reconcile(status("i-new", ResourceState::Running, 1), None)
+        .await
+        .unwrap();
+    assert_eq!(outcome, Reconciliation::Registered);
+    assert!(tracker.exists(&"i-new".to_string()).await);
+}
+
+#[tokio::test]
+async fn test_stop_outside_runctl_freezes_cost_at_transition() {
+    let tracker = ResourceTracker::new();
+    let running = status("i-stop", ResourceState::Running, 3);
+    let launch = running.launch_time;
+    tracker.register(running).await.unwrap();
+
+    let mut stopped = status("i-stop", ResourceState::Stopped, 3);
+    stopped.launch_time = launch;
+    let stopped_at = launch.unwrap() + Duration::hours(2);
+    let outcome = tracker.reconcile(stopped, Some(stopped_at)).await.unwrap();
+
+    assert_eq!(outcome, Reconciliation::Stopped);
+    let resource = tracked(&tracker, "i-stop").await;
+    assert_eq!(resource.status.state, ResourceState::Stopped);
+    assert!((resource.accumulated_cost - 2.0).abs() < 0.01);
+}
+
+#[tokio::test]
+async fn test_restart_carries_earlier_runtime() {
+    let tracker = ResourceTracker::new();
+    let running = status("i-restart", ResourceState::Running, 3);
+    let launch = running.launch_time;
+    tracker.register(running).await.unwrap();
+
+    let mut stopped = status("i-restart", ResourceState::Stopped, 3);
+    stopped.launch_time = launch;
+    tracker
+        .reconcile(stopped, Some(launch.unwrap() + Duration::hours(2)))
+        .await
+        .unwrap();
+
+    // Started again an hour ago: 2h before the stop plus 1h since
+    let outcome = tracker
+        .reconcile(status("i-restart", ResourceState::Running, 1), None)
+        .await
+        .unwrap();
+    assert_eq!(outcome, Reconciliation::Restarted);
+    let resource = tracked(&tracker, "i-restart").await;
+    assert!((resource.prior_cost - 2.0).abs() < 0.01);
+    assert!((resource.accumulated_cost - 3.0).abs() < 0.01);
+}
+
+#[test]
+fn test_new_launch_time_between_refreshes_counts_the_gap() {
+    let now = Utc::now();
+    let mut resource = TrackedResource {
+        status: status("i-gap", ResourceState::Running, 4),
+        created_at: now - Duration::hours(4),
+        usage_history: vec![],
+        accumulated_cost: 0.0,
+        tags: Default::default(),
+        prior_cost: 0.0,
+    };
+
+    // Stopped and started again without a refresh in between
+    let outcome = reconcile_resource(
+        &mut resource,
+        status("i-gap", ResourceState::Running, 1),
+        None,
+        now,
+    );
+    assert_eq!(outcome, Reconciliation::Restarted);
+    assert!((resource.prior_cost - 3.0).abs() < 0.01);
+    assert!((resource.accumulated_cost - 4.0).abs() < 0.01);
+}
+
+#[test]
+fn test_account_label_survives_reconcile() {
+    let now = Utc::now();
+    let live = status("i-acct", ResourceState::Running, 1);
+    let mut resource = TrackedResource {
+        status: live.clone(),
+        created_at: now,
+        usage_history: vec![],
+        accumulated_cost: 0.0,
+        tags: [(ACCOUNT_TAG.to_string(), "sandbox-a".to_string())]
+            .into_iter()
+            .collect(),
+        prior_cost: 0.0,
+    };
+
+    // A listing through the ambient credentials carries no account tag
+    reconcile_resource(&mut resource, live, None, now);
+    assert_eq!(
+        resource.tags.get(ACCOUNT_TAG).map(String::as_str),
+        Some("sandbox-a")
+    );
+}
+
+#[test]
+fn test_ip_change_is_an_update() {
+    let now = Utc::now();
+    let live = status("i-ip", ResourceState::Running, 1);
+    let mut resource = TrackedResource {
+        status: live.clone(),
+        created_at: now,
+        usage_history: vec![],
+        accumulated_cost: 0.0,
+        tags: Default::default(),
+        prior_cost: 0.0,
+    };
+
+    let unchanged = reconcile_resource(&mut resource, live.clone(), None, now);
+    assert_eq!(unchanged, Reconciliation::Unchanged);
+
+    let mut moved = live;
+    moved.public_ip = Some("203.0.113.7".to_string());
+    let outcome = reconcile_resource(&mut resource, moved, None, now);
+    assert_eq!(outcome, Reconciliation::Updated);
+    assert_eq!(resource.status.public_ip.as_deref(), Some("203.0.113.7"));
+}
+
+#[test]
+fn test_price_change_is_an_update() {
+    let now = Utc::now();
+    let live = status("i-price", ResourceState::Running, 2);
+    let mut resource = TrackedResource {
+        status: live.clone(),
+        created_at: now,
+        usage_history: vec![],
+        accumulated_cost: 2.0,
+        tags: Default::default(),
+        prior_cost: 0.0,
+    };
+
+    // A live price replaced the built-in estimate
+    let mut repriced = live;
+    repriced.cost_per_hour = 0.5;
+    let outcome = reconcile_resource(&mut resource, repriced, None, now);
+    assert_eq!(outcome, Reconciliation::Updated);
+    assert_eq!(resource.status.cost_per_hour, 0.5);
+    assert!((resource.accumulated_cost - 1.0).abs() < 0.01);
+}
@@ -21,6 +21,7 @@ async fn test_refresh_costs_updates_all() {
             launch_time: Some(now - Duration::hours(1)),
             cost_per_hour: 0.01,
             public_ip: None,
+            public_ipv6: None,
             tags: vec![],
         },
         ResourceStatus {
@@ -31,6 +32,7 @@ async fn test_refresh_costs_updates_all() {
             launch_time: Some(now - Duration::hours(2)),
             cost_per_hour: 0.50,
             public_ip: None,
+            public_ipv6: None,
             tags: vec![],
         },
         ResourceStatus {
@@ -41,6 +43,7 @@ async fn test_refresh_costs_updates_all() {
             launch_time: Some(now - Duration::hours(3)),
             cost_per_hour: 0.01,
             public_ip: None,
+            public_ipv6: None,
             tags: vec![],
         },
     ];
@@ -81,6 +84,7 @@ async fn test_automatic_cost_update_on_access() {
         launch_time: Some(Utc::now() - Duration::hours(1)),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -109,6 +113,7 @@ async fn test_cost_updates_over_time() {
         launch_time: Some(Utc::now() - Duration::minutes(30)),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -147,6 +152,7 @@ async fn test_get_running_updates_costs() {
             launch_time: Some(Utc::now() - Duration::hours(1)),
             cost_per_hour: 0.01,
             public_ip: None,
+            public_ipv6: None,
             tags: vec![],
         },
         ResourceStatus {
@@ -157,6 +163,7 @@ async fn test_get_running_updates_costs() {
             launch_time: Some(Utc::now() - Duration::hours(1)),
             cost_per_hour: 0.01,
             public_ip: None,
+            public_ipv6: None,
             tags: vec![],
         },
     ];
@@ -189,6 +196,7 @@ async fn test_get_total_cost_updates_all() {
             launch_time: Some(Utc::now() - Duration::hours(1)),
             cost_per_hour: 0.01,
             public_ip: None,
+            public_ipv6: None,
             tags: vec![],
         },
         ResourceStatus {
@@ -199,6 +207,7 @@ async fn test_get_total_cost_updates_all() {
             launch_time: Some(Utc::now() - Duration::hours(2)),
             cost_per_hour: 0.01,
             public_ip: None,
+            public_ipv6: None,
             tags: vec![],
         },
     ];
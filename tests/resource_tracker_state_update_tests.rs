@@ -18,6 +18,7 @@ async fn test_update_state_running_to_stopped() {
         launch_time: Some(Utc::now() - Duration::hours(1)),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -54,6 +55,7 @@ async fn test_update_state_stopped_to_running() {
         launch_time: Some(Utc::now() - Duration::hours(1)),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -106,6 +108,7 @@ async fn test_update_state_preserves_other_fields() {
         launch_time: Some(Utc::now() - Duration::hours(1)),
         cost_per_hour: 0.50,
         public_ip: Some("1.2.3.4".to_string()),
+        public_ipv6: None,
         tags: vec![("Project".to_string(), "test".to_string())],
     };
 
@@ -27,6 +27,7 @@ async fn test_register_resource() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: Some("1.2.3.4".to_string()),
+        public_ipv6: None,
         tags: vec![("Environment".to_string(), "test".to_string())],
     };
 
@@ -54,6 +55,7 @@ async fn test_register_duplicate_resource() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -83,6 +85,7 @@ async fn test_update_usage() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -146,6 +149,7 @@ async fn test_get_running_resources() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -157,6 +161,7 @@ async fn test_get_running_resources() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -168,6 +173,7 @@ async fn test_get_running_resources() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -196,6 +202,7 @@ async fn test_get_total_cost() {
             launch_time: Some(Utc::now() - Duration::hours(1)),
             cost_per_hour: 0.01,
             public_ip: None,
+            public_ipv6: None,
             tags: vec![],
         },
         ResourceStatus {
@@ -206,6 +213,7 @@ async fn test_get_total_cost() {
             launch_time: Some(Utc::now() - Duration::hours(2)),
             cost_per_hour: 0.50,
             public_ip: None,
+            public_ipv6: None,
             tags: vec![],
         },
     ];
@@ -247,6 +255,7 @@ async fn test_get_by_tag() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![
             ("Project".to_string(), "test".to_string()),
             ("Environment".to_string(), "dev".to_string()),
@@ -261,6 +270,7 @@ async fn test_get_by_tag() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![
             ("Project".to_string(), "production".to_string()),
             ("Environment".to_string(), "prod".to_string()),
@@ -275,6 +285,7 @@ async fn test_get_by_tag() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![
             ("Project".to_string(), "test".to_string()),
             ("Environment".to_string(), "staging".to_string()),
@@ -310,6 +321,7 @@ async fn test_get_by_id() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: Some("1.2.3.4".to_string()),
+        public_ipv6: None,
         tags: vec![("Tag".to_string(), "Value".to_string())],
     };
 
@@ -342,6 +354,7 @@ async fn test_remove_resource() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -375,6 +388,7 @@ async fn test_usage_history_limit() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -420,6 +434,7 @@ async fn test_concurrent_operations() {
                 launch_time: Some(Utc::now()),
                 cost_per_hour: 0.01,
                 public_ip: None,
+                public_ipv6: None,
                 tags: vec![],
             };
             tracker_clone.register(status).await
@@ -32,6 +32,7 @@ async fn test_resource_tracking() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: Some("1.2.3.4".to_string()),
+        public_ipv6: None,
         tags: vec![("Environment".to_string(), "test".to_string())],
     };
 
@@ -86,6 +87,7 @@ async fn test_cost_tracking() {
             launch_time: Some(Utc::now()),
             cost_per_hour: 0.01,
             public_ip: None,
+            public_ipv6: None,
             tags: vec![],
         },
         ResourceStatus {
@@ -96,6 +98,7 @@ async fn test_cost_tracking() {
             launch_time: Some(Utc::now()),
             cost_per_hour: 0.50,
             public_ip: None,
+            public_ipv6: None,
             tags: vec![],
         },
     ];
@@ -132,6 +135,7 @@ async fn test_resource_filtering_by_tag() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![("Project".to_string(), "test".to_string())],
     };
 
@@ -143,6 +147,7 @@ async fn test_resource_filtering_by_tag() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![("Project".to_string(), "production".to_string())],
     };
 
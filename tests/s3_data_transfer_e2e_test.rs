@@ -11,6 +11,8 @@
 //!
 //! Cost: ~$0.10-0.50 per run (uses t3.micro, minimal S3 storage)
 
+#![cfg(feature = "aws")]
+
 use aws_config::BehaviorVersion;
 use aws_sdk_ec2::Client as Ec2Client;
 use aws_sdk_s3::Client as S3Client;
@@ -34,6 +34,7 @@ async fn test_protected_resources() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![("runctl:protected".to_string(), "true".to_string())],
     };
 
@@ -77,6 +78,7 @@ async fn test_dry_run_cleanup() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -123,6 +125,7 @@ async fn test_cleanup_with_protection() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![("runctl:protected".to_string(), "true".to_string())],
     };
 
@@ -134,6 +137,7 @@ async fn test_cleanup_with_protection() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
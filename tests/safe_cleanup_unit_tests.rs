@@ -35,6 +35,7 @@ async fn test_can_delete_new_resource() {
         launch_time: Some(Utc::now()), // Just created
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -63,6 +64,7 @@ async fn test_can_delete_old_resource() {
         launch_time: Some(Utc::now() - Duration::hours(1)),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -91,6 +93,7 @@ async fn test_can_delete_protected_resource() {
         launch_time: Some(Utc::now() - Duration::hours(1)),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![("runctl:protected".to_string(), "true".to_string())],
     };
 
@@ -122,6 +125,7 @@ async fn test_can_delete_with_force() {
         launch_time: Some(Utc::now()), // Just created, too new
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -164,6 +168,7 @@ async fn test_safe_cleanup_dry_run() {
         launch_time: Some(Utc::now() - Duration::hours(1)),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -202,6 +207,7 @@ async fn test_safe_cleanup_actual_deletion() {
         launch_time: Some(Utc::now() - Duration::hours(1)),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -245,6 +251,7 @@ async fn test_safe_cleanup_mixed_resources() {
         launch_time: Some(Utc::now() - Duration::hours(1)),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![("runctl:protected".to_string(), "true".to_string())],
     };
 
@@ -256,6 +263,7 @@ async fn test_safe_cleanup_mixed_resources() {
         launch_time: Some(Utc::now() - Duration::hours(1)),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -267,6 +275,7 @@ async fn test_safe_cleanup_mixed_resources() {
         launch_time: Some(Utc::now()),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
@@ -372,6 +381,7 @@ async fn test_cleanup_result_structure() {
         launch_time: Some(Utc::now() - Duration::hours(1)),
         cost_per_hour: 0.01,
         public_ip: None,
+        public_ipv6: None,
         tags: vec![],
     };
 
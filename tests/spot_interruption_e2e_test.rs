@@ -6,6 +6,8 @@
 //!
 //! Cost: ~$0.50-2.00 per run (creates real spot instance, runs training)
 
+#![cfg(feature = "aws")]
+
 use aws_config::BehaviorVersion;
 use aws_sdk_ec2::Client as Ec2Client;
 use aws_sdk_ssm::Client as SsmClient;
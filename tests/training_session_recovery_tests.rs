@@ -0,0 +1,120 @@
+//! Tests for persisted launch progress and interrupted-launch detection
+
+use runctl::training::{Cleanup, LaunchStage, TrainingSession, TrainingStatus};
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+fn launching() -> TrainingSession {
+    TrainingSession::launching(
+        "aws".to_string(),
+        PathBuf::from("train.py"),
+        PathBuf::from("checkpoints"),
+        "g5.xlarge".to_string(),
+        true,
+        vec!["--epochs".to_string(), "10".to_string()],
+    )
+}
+
+#[test]
+fn test_launch_progress_round_trips() {
+    let dir = TempDir::new().unwrap();
+    let mut session = launching();
+    session.advance(LaunchStage::ResourceCreated, Some("i-0abc".to_string()));
+    session.save(dir.path()).unwrap();
+
+    let loaded = TrainingSession::load(dir.path(), &session.id).unwrap();
+    assert!(matches!(loaded.status, TrainingStatus::Launching));
+    assert_eq!(loaded.resource_id(), Some("i-0abc"));
+    let launch = loaded.launch.unwrap();
+    assert_eq!(launch.stage, LaunchStage::ResourceCreated);
+    assert_eq!(launch.script_args, vec!["--epochs", "10"]);
+    assert!(launch.spot);
+}
+
+#[test]
+fn test_training_started_marks_session_running() {
+    let mut session = launching();
+    session.advance(LaunchStage::ResourceCreated, Some("i-0abc".to_string()));
+    session.advance(LaunchStage::TrainingStarted, None);
+    assert!(matches!(session.status, TrainingStatus::Running));
+    // The resource ID is kept when later stages don't pass one
+    assert_eq!(session.resource_id(), Some("i-0abc"));
+}
+
+#[test]
+fn test_only_launches_of_dead_processes_are_abandoned() {
+    let dir = TempDir::new().unwrap();
+
+    // Still owned by this (live) process
+    let live = launching();
+    live.save(dir.path()).unwrap();
+
+    // PIDs this large are never in use
+    let mut crashed = launching();
+    crashed.runctl_pid = Some(4_000_000_000);
+    crashed.advance(LaunchStage::ResourceCreated, Some("i-0dead".to_string()));
+    crashed.save(dir.path()).unwrap();
+
+    let mut finished = launching();
+    finished.runctl_pid = Some(4_000_000_000);
+    finished.status = TrainingStatus::Completed;
+    finished.save(dir.path()).unwrap();
+
+    let abandoned = TrainingSession::abandoned_launches(dir.path()).unwrap();
+    assert_eq!(abandoned.len(), 1);
+    assert_eq!(abandoned[0].id, crashed.id);
+}
+
+#[test]
+fn test_runs_whose_process_died_while_training_are_interrupted() {
+    let mut training = launching();
+    training.runctl_pid = Some(4_000_000_000);
+    training.advance(LaunchStage::ResourceCreated, Some("i-0abc".to_string()));
+    training.advance(LaunchStage::TrainingStarted, None);
+    // Past the launch, so `jobs recover` leaves it alone, but `run --resume` picks it up
+    assert!(!training.is_abandoned_launch());
+    assert!(training.is_interrupted_run());
+
+    // --detach runs are meant to be left running
+    training.launch.as_mut().unwrap().detached = true;
+    assert!(!training.is_interrupted_run());
+
+    let mut done = launching();
+    done.runctl_pid = Some(4_000_000_000);
+    done.status = TrainingStatus::Completed;
+    assert!(!done.is_interrupted_run());
+}
+
+#[test]
+fn test_cleanup_policy() {
+    assert!(Cleanup::Always.terminates(false));
+    assert!(Cleanup::OnSuccess.terminates(true));
+    assert!(!Cleanup::OnSuccess.terminates(false));
+    assert!(!Cleanup::Never.terminates(true));
+    // Sessions saved before the policy existed terminated unconditionally
+    assert_eq!(launching().launch.unwrap().cleanup, Cleanup::Always);
+}
+
+#[test]
+fn test_sessions_without_launch_state_still_load() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("sessions")).unwrap();
+    fs::write(
+        dir.path().join("sessions").join("old.json"),
+        r#"{
+            "id": "old",
+            "started_at": "2026-01-01T00:00:00Z",
+            "platform": "local",
+            "script": "train.py",
+            "checkpoint_dir": "checkpoints",
+            "log_file": null,
+            "status": "Running"
+        }"#,
+    )
+    .unwrap();
+
+    let session = TrainingSession::load(dir.path(), "old").unwrap();
+    assert!(session.launch.is_none());
+    assert!(!session.is_abandoned_launch());
+}
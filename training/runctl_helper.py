@@ -0,0 +1,67 @@
+"""
+Script side of runctl's checkpoint-before-stop protocol.
+
+Before a planned stop (`runctl aws stop`, a scheduled stop, bulk, budget or
+cleanup actions) runctl asks the training script to checkpoint at its next
+epoch or step boundary instead of sending SIGTERM mid-step. Scripts opt in
+by calling `listen()` at startup; otherwise runctl goes straight to SIGTERM.
+
+Usage:
+    import runctl_helper
+
+    runctl_helper.listen()
+    for epoch in range(start_epoch, epochs):
+        train_epoch(...)
+        if runctl_helper.checkpoint_requested():
+            path = save_checkpoint(...)
+            runctl_helper.checkpoint_done(path)
+            sys.exit(0)
+
+Files live in the working directory, next to training.pid:
+    checkpoint_listening      created by listen()
+    checkpoint_request.json   written by runctl: {"id", "reason", "deadline"}
+    checkpoint_done.txt       request ID, then the checkpoint path
+"""
+
+import atexit
+import json
+import os
+from pathlib import Path
+
+LISTENING_FILE = Path("checkpoint_listening")
+REQUEST_FILE = Path("checkpoint_request.json")
+DONE_FILE = Path("checkpoint_done.txt")
+
+
+def listen():
+    """Tell runctl this script checkpoints on request."""
+    LISTENING_FILE.touch()
+    atexit.register(_stop_listening)
+
+
+def _stop_listening():
+    try:
+        LISTENING_FILE.unlink()
+    except FileNotFoundError:
+        pass
+
+
+def checkpoint_requested():
+    """The pending request (dict with id, reason, deadline), or None.
+
+    Cheap enough to call at every step boundary.
+    """
+    try:
+        return json.loads(REQUEST_FILE.read_text())
+    except (FileNotFoundError, ValueError):
+        return None
+
+
+def checkpoint_done(path=None):
+    """Report the requested checkpoint as saved; the script should then exit."""
+    request = checkpoint_requested()
+    if request is None:
+        return
+    tmp = DONE_FILE.with_suffix(".tmp")
+    tmp.write_text(f"{request['id']}\n{path or ''}\n")
+    os.replace(tmp, DONE_FILE)
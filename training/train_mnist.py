@@ -7,6 +7,7 @@ This script demonstrates a complete training workflow that works with runctl:
 - Trains a simple CNN
 - Saves checkpoints
 - Supports resuming from checkpoints
+- Checkpoints at the next epoch boundary when runctl stops it (runctl_helper)
 
 Usage:
     # Local training
@@ -20,6 +21,7 @@ Usage:
 """
 
 import argparse
+import sys
 import time
 from pathlib import Path
 
@@ -29,6 +31,8 @@ import torch.optim as optim
 from torch.utils.data import DataLoader
 from torchvision import datasets, transforms
 
+import runctl_helper
+
 
 class SimpleCNN(nn.Module):
     """Simple CNN for MNIST classification."""
@@ -226,6 +230,7 @@ def main():
     print()
 
     best_val_acc = 0.0
+    runctl_helper.listen()
 
     for epoch in range(start_epoch, args.epochs):
         print(f"Epoch {epoch+1}/{args.epochs}")
@@ -251,13 +256,19 @@ def main():
         if is_best:
             best_val_acc = val_acc
 
-        save_checkpoint(
+        path = save_checkpoint(
             model, optimizer, epoch + 1, val_loss, val_acc, args.checkpoint_dir
         )
 
         if is_best:
             print(f"  New best validation accuracy: {val_acc:.2f}%")
 
+        request = runctl_helper.checkpoint_requested()
+        if request is not None:
+            print(f"Stopping after epoch {epoch+1} ({request['reason']})")
+            runctl_helper.checkpoint_done(path)
+            sys.exit(0)
+
         print()
 
     # Save final checkpoint